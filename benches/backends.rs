@@ -0,0 +1,66 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+extern crate criterion;
+extern crate orion;
+
+use criterion::*;
+
+use orion::hazardous::{aead::chacha20poly1305, stream::chacha20};
+use orion::util;
+
+static INPUT_SIZES: [usize; 3] = [64 * 1024, 128 * 1024, 256 * 1024];
+
+/// Compare the portable ChaCha20 backend with the one selected by CPU feature
+/// detection. They only differ on CPUs with AVX2. Run with
+/// `cargo bench --features test_utils --bench backends`.
+pub fn bench_chacha20_backends(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ChaCha20 backends");
+    let key = chacha20poly1305::SecretKey::generate();
+    let nonce = chacha20poly1305::Nonce::from([0u8; 12]);
+
+    for size in INPUT_SIZES.iter() {
+        let input = vec![0u8; *size];
+        let mut out = vec![0u8; input.len()];
+
+        group.throughput(Throughput::Bytes(*size as u64));
+        for (name, force_portable) in [("portable", true), ("detected", false)].iter() {
+            util::force_portable_backends(*force_portable);
+            group.bench_with_input(
+                BenchmarkId::new(*name, *size),
+                &input,
+                |b, input_message| {
+                    b.iter(|| chacha20::encrypt(&key, &nonce, 0, input_message, &mut out).unwrap())
+                },
+            );
+        }
+    }
+    util::force_portable_backends(false);
+}
+
+criterion_group! {
+    name = backend_benches;
+    config = Criterion::default();
+    targets = bench_chacha20_backends,
+}
+
+criterion_main!(backend_benches);
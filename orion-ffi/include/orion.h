@@ -0,0 +1,75 @@
+/*
+ * C bindings for orion. See orion-ffi/src/lib.rs for the full documentation
+ * of each function.
+ *
+ * Every function returns ORION_OK on success and ORION_ERROR on any failure.
+ * Buffers are passed as a pointer and an explicit length, and a pointer may
+ * only be NULL if its length is 0. Output buffers must have exactly the
+ * documented length.
+ */
+
+#ifndef ORION_H
+#define ORION_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define ORION_OK 0
+#define ORION_ERROR -1
+
+/* A 24-byte nonce and a 16-byte tag. */
+#define ORION_AEAD_ABYTES 40
+#define ORION_HASH_DIGEST_SIZE 32
+#define ORION_AUTH_TAG_SIZE 32
+/* Excluding the NUL terminator. */
+#define ORION_PWHASH_ENCODED_MAX 110
+
+/* An opaque secret key. Zeroed when released with orion_secret_key_free(). */
+typedef struct OrionSecretKey OrionSecretKey;
+
+/* Returns NULL on failure. */
+OrionSecretKey *orion_secret_key_generate(size_t len);
+/* Returns NULL on failure. The caller remains responsible for zeroing bytes. */
+OrionSecretKey *orion_secret_key_from_bytes(const uint8_t *bytes, size_t len);
+void orion_secret_key_free(OrionSecretKey *key);
+
+/* XChaCha20-Poly1305 with a random nonce. out_len == plaintext_len + ORION_AEAD_ABYTES. */
+int orion_aead_seal(const OrionSecretKey *key,
+                    const uint8_t *plaintext, size_t plaintext_len,
+                    uint8_t *out, size_t out_len);
+/* out_len == ciphertext_len - ORION_AEAD_ABYTES. */
+int orion_aead_open(const OrionSecretKey *key,
+                    const uint8_t *ciphertext, size_t ciphertext_len,
+                    uint8_t *out, size_t out_len);
+
+/* BLAKE2b-256. out_len == ORION_HASH_DIGEST_SIZE. */
+int orion_hash_digest(const uint8_t *data, size_t data_len,
+                      uint8_t *out, size_t out_len);
+
+/* BLAKE2b-256 in keyed mode. out_len == ORION_AUTH_TAG_SIZE. */
+int orion_auth_authenticate(const OrionSecretKey *key,
+                            const uint8_t *data, size_t data_len,
+                            uint8_t *out, size_t out_len);
+int orion_auth_verify(const OrionSecretKey *key,
+                      const uint8_t *tag, size_t tag_len,
+                      const uint8_t *data, size_t data_len);
+
+/* Argon2i. out_len >= ORION_PWHASH_ENCODED_MAX + 1. Writes a NUL-terminated
+ * encoded password hash and stores its length, excluding the NUL, in written. */
+int orion_pwhash_hash_password(const uint8_t *password, size_t password_len,
+                               uint32_t iterations, uint32_t memory,
+                               uint8_t *out, size_t out_len, size_t *written);
+/* expected does not need to be NUL-terminated. */
+int orion_pwhash_verify(const uint8_t *expected, size_t expected_len,
+                        const uint8_t *password, size_t password_len,
+                        uint32_t iterations, uint32_t memory);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ORION_H */
@@ -0,0 +1,536 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! C bindings for orion's high-level API.
+//!
+//! # About:
+//! This crate builds a `cdylib` and `staticlib` exposing `extern "C"`
+//! functions for authenticated encryption (`orion::aead`), message
+//! authentication (`orion::auth`), hashing (`orion::hash`) and password
+//! hashing (`orion::pwhash`). The matching C header is `include/orion.h`.
+//!
+//! All `unsafe` code needed to work with raw pointers lives in this crate, so
+//! that orion itself can keep forbidding it.
+//!
+//! # Conventions:
+//! - Every function returns [`ORION_OK`] on success and [`ORION_ERROR`] on any
+//!   failure, mirroring `UnknownCryptoError`. Handle constructors return `NULL`
+//!   on failure instead.
+//! - Every buffer is passed as a pointer and an explicit length. A pointer may
+//!   only be `NULL` if its length is 0.
+//! - Output buffers must have exactly the documented length, so that callers
+//!   cannot silently truncate or under-allocate outputs.
+//! - Secret keys are opaque handles. [`orion_secret_key_free()`] zeroes the key
+//!   before releasing its memory.
+//! - Panics never unwind into the caller. They are reported as [`ORION_ERROR`].
+//!
+//! [`ORION_OK`]: constant.ORION_OK.html
+//! [`ORION_ERROR`]: constant.ORION_ERROR.html
+//! [`orion_secret_key_free()`]: fn.orion_secret_key_free.html
+
+#![warn(missing_docs, rust_2018_idioms)]
+#![allow(clippy::missing_safety_doc)]
+
+use orion::errors::UnknownCryptoError;
+use orion::{aead, auth, hash, pwhash};
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use zeroize::Zeroize;
+
+/// Returned on success.
+pub const ORION_OK: c_int = 0;
+/// Returned on any failure.
+pub const ORION_ERROR: c_int = -1;
+
+/// Bytes added to a plaintext by `orion_aead_seal()`: a 24-byte nonce and a
+/// 16-byte tag.
+pub const ORION_AEAD_ABYTES: usize = 40;
+/// Length of a digest output by `orion_hash_digest()`.
+pub const ORION_HASH_DIGEST_SIZE: usize = 32;
+/// Length of a tag output by `orion_auth_authenticate()`.
+pub const ORION_AUTH_TAG_SIZE: usize = 32;
+/// Maximum length of an encoded password hash, excluding the NUL terminator.
+pub const ORION_PWHASH_ENCODED_MAX: usize = pwhash::PasswordHash::MAX_ENCODED_LEN;
+
+/// An opaque secret key, used with `orion_aead_*` and `orion_auth_*`.
+pub struct OrionSecretKey(aead::SecretKey);
+
+/// Create a slice from a pointer and length. `ptr` may only be `NULL` if
+/// `len` is 0.
+unsafe fn slice_from_raw<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], UnknownCryptoError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if ptr.is_null() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(std::slice::from_raw_parts(ptr, len))
+}
+
+/// Create a mutable slice from a pointer and length. `ptr` may only be `NULL`
+/// if `len` is 0.
+unsafe fn slice_from_raw_mut<'a>(
+    ptr: *mut u8,
+    len: usize,
+) -> Result<&'a mut [u8], UnknownCryptoError> {
+    if len == 0 {
+        return Ok(&mut []);
+    }
+    if ptr.is_null() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(std::slice::from_raw_parts_mut(ptr, len))
+}
+
+/// Run `f`, mapping errors and panics to `ORION_ERROR`.
+fn ffi_result<F: FnOnce() -> Result<(), UnknownCryptoError>>(f: F) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => ORION_OK,
+        _ => ORION_ERROR,
+    }
+}
+
+/// Copy `src` into `dst`, which must be exactly as long as `src`.
+fn copy_exact(src: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if src.len() != dst.len() {
+        return Err(UnknownCryptoError);
+    }
+    dst.copy_from_slice(src);
+
+    Ok(())
+}
+
+#[no_mangle]
+/// Generate a random secret key of `len` bytes. Returns `NULL` if `len` is 0
+/// or generating the key failed. The key must be released with
+/// `orion_secret_key_free()`.
+pub extern "C" fn orion_secret_key_generate(len: usize) -> *mut OrionSecretKey {
+    match catch_unwind(|| aead::SecretKey::generate(len)) {
+        Ok(Ok(sk)) => Box::into_raw(Box::new(OrionSecretKey(sk))),
+        _ => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// Create a secret key from `len` bytes at `bytes`. Returns `NULL` if `len` is
+/// 0 or `bytes` is `NULL`. The key must be released with
+/// `orion_secret_key_free()`. The caller remains responsible for zeroing
+/// `bytes`.
+pub unsafe extern "C" fn orion_secret_key_from_bytes(
+    bytes: *const u8,
+    len: usize,
+) -> *mut OrionSecretKey {
+    let res = catch_unwind(|| aead::SecretKey::from_slice(slice_from_raw(bytes, len)?));
+    match res {
+        Ok(Ok(sk)) => Box::into_raw(Box::new(OrionSecretKey(sk))),
+        _ => ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+/// Zero and release a secret key. Passing `NULL` does nothing.
+pub unsafe extern "C" fn orion_secret_key_free(key: *mut OrionSecretKey) {
+    if !key.is_null() {
+        // The key is zeroed when dropped.
+        drop(Box::from_raw(key));
+    }
+}
+
+#[no_mangle]
+/// Encrypt and authenticate `plaintext` using XChaCha20-Poly1305, with a
+/// random nonce. The key must be 32 bytes. `out_len` must be
+/// `plaintext_len + ORION_AEAD_ABYTES`, and `plaintext_len` must not be 0.
+pub unsafe extern "C" fn orion_aead_seal(
+    key: *const OrionSecretKey,
+    plaintext: *const u8,
+    plaintext_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    ffi_result(|| {
+        let key = key.as_ref().ok_or(UnknownCryptoError)?;
+        let plaintext = slice_from_raw(plaintext, plaintext_len)?;
+        let out = slice_from_raw_mut(out, out_len)?;
+
+        copy_exact(&aead::seal(&key.0, plaintext)?, out)
+    })
+}
+
+#[no_mangle]
+/// Authenticate and decrypt `ciphertext` output by `orion_aead_seal()`.
+/// `out_len` must be `ciphertext_len - ORION_AEAD_ABYTES`. Nothing is
+/// written to `out` if authentication fails.
+pub unsafe extern "C" fn orion_aead_open(
+    key: *const OrionSecretKey,
+    ciphertext: *const u8,
+    ciphertext_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    ffi_result(|| {
+        let key = key.as_ref().ok_or(UnknownCryptoError)?;
+        let ciphertext = slice_from_raw(ciphertext, ciphertext_len)?;
+        let out = slice_from_raw_mut(out, out_len)?;
+
+        let mut plaintext = aead::open(&key.0, ciphertext)?;
+        let res = copy_exact(&plaintext, out);
+        plaintext.iter_mut().zeroize();
+
+        res
+    })
+}
+
+#[no_mangle]
+/// Hash `data` using BLAKE2b-256. `out_len` must be `ORION_HASH_DIGEST_SIZE`.
+pub unsafe extern "C" fn orion_hash_digest(
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    ffi_result(|| {
+        let data = slice_from_raw(data, data_len)?;
+        let out = slice_from_raw_mut(out, out_len)?;
+
+        copy_exact(hash::digest(data)?.as_ref(), out)
+    })
+}
+
+#[no_mangle]
+/// Authenticate `data` using BLAKE2b-256 in keyed mode. The key must be at
+/// least 32 bytes. `out_len` must be `ORION_AUTH_TAG_SIZE`.
+pub unsafe extern "C" fn orion_auth_authenticate(
+    key: *const OrionSecretKey,
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    ffi_result(|| {
+        let key = key.as_ref().ok_or(UnknownCryptoError)?;
+        let data = slice_from_raw(data, data_len)?;
+        let out = slice_from_raw_mut(out, out_len)?;
+
+        copy_exact(
+            auth::authenticate(&key.0, data)?.unprotected_as_bytes(),
+            out,
+        )
+    })
+}
+
+#[no_mangle]
+/// Verify, in constant time, that `tag` authenticates `data` under `key`.
+pub unsafe extern "C" fn orion_auth_verify(
+    key: *const OrionSecretKey,
+    tag: *const u8,
+    tag_len: usize,
+    data: *const u8,
+    data_len: usize,
+) -> c_int {
+    ffi_result(|| {
+        let key = key.as_ref().ok_or(UnknownCryptoError)?;
+        let tag = auth::Tag::from_slice(slice_from_raw(tag, tag_len)?)?;
+        let data = slice_from_raw(data, data_len)?;
+
+        auth::authenticate_verify(&tag, &key.0, data)
+    })
+}
+
+#[no_mangle]
+/// Hash `password` using Argon2i and write the encoded password hash, followed
+/// by a NUL terminator, to `out`. `out_len` must be at least
+/// `ORION_PWHASH_ENCODED_MAX + 1`. The length of the encoded password hash,
+/// excluding the NUL terminator, is written to `written`.
+pub unsafe extern "C" fn orion_pwhash_hash_password(
+    password: *const u8,
+    password_len: usize,
+    iterations: u32,
+    memory: u32,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> c_int {
+    ffi_result(|| {
+        let password = pwhash::Password::from_slice(slice_from_raw(password, password_len)?)?;
+        let out = slice_from_raw_mut(out, out_len)?;
+        let written = written.as_mut().ok_or(UnknownCryptoError)?;
+        if out.len() <= ORION_PWHASH_ENCODED_MAX {
+            return Err(UnknownCryptoError);
+        }
+
+        let password_hash = pwhash::hash_password(&password, iterations, memory)?;
+        let encoded = password_hash.unprotected_as_encoded().as_bytes();
+        out[..encoded.len()].copy_from_slice(encoded);
+        out[encoded.len()] = 0;
+        *written = encoded.len();
+
+        Ok(())
+    })
+}
+
+#[no_mangle]
+/// Verify `password` against the encoded password hash `expected`, which does
+/// not need to be NUL-terminated. `iterations` and `memory` must match the
+/// ones `expected` was created with.
+pub unsafe extern "C" fn orion_pwhash_verify(
+    expected: *const u8,
+    expected_len: usize,
+    password: *const u8,
+    password_len: usize,
+    iterations: u32,
+    memory: u32,
+) -> c_int {
+    ffi_result(|| {
+        let expected = std::str::from_utf8(slice_from_raw(expected, expected_len)?)
+            .map_err(|_| UnknownCryptoError)?;
+        let expected = pwhash::PasswordHash::from_encoded(expected)?;
+        let password = pwhash::Password::from_slice(slice_from_raw(password, password_len)?)?;
+
+        pwhash::hash_password_verify(&expected, &password, iterations, memory)
+    })
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn key(len: usize) -> *mut OrionSecretKey {
+        let key = orion_secret_key_generate(len);
+        assert!(!key.is_null());
+        key
+    }
+
+    #[test]
+    fn test_secret_key() {
+        assert!(orion_secret_key_generate(0).is_null());
+        unsafe {
+            assert!(orion_secret_key_from_bytes(ptr::null(), 32).is_null());
+            assert!(orion_secret_key_from_bytes([0u8; 1].as_ptr(), 0).is_null());
+
+            let from_bytes = orion_secret_key_from_bytes([1u8; 32].as_ptr(), 32);
+            assert!(!from_bytes.is_null());
+            assert!((*from_bytes).0 == &[1u8; 32][..]);
+            orion_secret_key_free(from_bytes);
+            orion_secret_key_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_aead_seal_open() {
+        let key = key(32);
+        let plaintext = b"Secret message";
+        let mut ciphertext = [0u8; 14 + ORION_AEAD_ABYTES];
+        let mut decrypted = [0u8; 14];
+
+        unsafe {
+            assert_eq!(
+                orion_aead_seal(key, plaintext.as_ptr(), 14, ciphertext.as_mut_ptr(), 54),
+                ORION_OK
+            );
+            assert_eq!(
+                orion_aead_open(key, ciphertext.as_ptr(), 54, decrypted.as_mut_ptr(), 14),
+                ORION_OK
+            );
+            assert_eq!(&decrypted, plaintext);
+
+            // Output buffers of the wrong length.
+            assert_eq!(
+                orion_aead_seal(key, plaintext.as_ptr(), 14, ciphertext.as_mut_ptr(), 53),
+                ORION_ERROR
+            );
+            assert_eq!(
+                orion_aead_open(key, ciphertext.as_ptr(), 54, decrypted.as_mut_ptr(), 13),
+                ORION_ERROR
+            );
+
+            // Modified ciphertext.
+            ciphertext[30] ^= 1;
+            decrypted = [0u8; 14];
+            assert_eq!(
+                orion_aead_open(key, ciphertext.as_ptr(), 54, decrypted.as_mut_ptr(), 14),
+                ORION_ERROR
+            );
+            assert_eq!(decrypted, [0u8; 14]);
+
+            // Missing key and buffers.
+            assert_eq!(
+                orion_aead_seal(
+                    ptr::null(),
+                    plaintext.as_ptr(),
+                    14,
+                    ciphertext.as_mut_ptr(),
+                    54
+                ),
+                ORION_ERROR
+            );
+            assert_eq!(
+                orion_aead_seal(key, ptr::null(), 14, ciphertext.as_mut_ptr(), 54),
+                ORION_ERROR
+            );
+            assert_eq!(
+                orion_aead_seal(key, plaintext.as_ptr(), 14, ptr::null_mut(), 54),
+                ORION_ERROR
+            );
+
+            orion_secret_key_free(key);
+        }
+
+        // XChaCha20-Poly1305 requires a 32-byte key.
+        let long_key = self::key(64);
+        unsafe {
+            assert_eq!(
+                orion_aead_seal(
+                    long_key,
+                    plaintext.as_ptr(),
+                    14,
+                    ciphertext.as_mut_ptr(),
+                    54
+                ),
+                ORION_ERROR
+            );
+            orion_secret_key_free(long_key);
+        }
+    }
+
+    #[test]
+    fn test_hash_digest() {
+        let mut digest = [0u8; ORION_HASH_DIGEST_SIZE];
+        unsafe {
+            assert_eq!(
+                orion_hash_digest(b"data".as_ptr(), 4, digest.as_mut_ptr(), 32),
+                ORION_OK
+            );
+            assert_eq!(&digest[..], hash::digest(b"data").unwrap().as_ref());
+
+            assert_eq!(
+                orion_hash_digest(ptr::null(), 0, digest.as_mut_ptr(), 32),
+                ORION_OK
+            );
+            assert_eq!(&digest[..], hash::digest(b"").unwrap().as_ref());
+
+            assert_eq!(
+                orion_hash_digest(b"data".as_ptr(), 4, digest.as_mut_ptr(), 31),
+                ORION_ERROR
+            );
+        }
+    }
+
+    #[test]
+    fn test_auth_authenticate_verify() {
+        let key = key(32);
+        let mut tag = [0u8; ORION_AUTH_TAG_SIZE];
+
+        unsafe {
+            assert_eq!(
+                orion_auth_authenticate(key, b"data".as_ptr(), 4, tag.as_mut_ptr(), 32),
+                ORION_OK
+            );
+            assert_eq!(
+                orion_auth_verify(key, tag.as_ptr(), 32, b"data".as_ptr(), 4),
+                ORION_OK
+            );
+            assert_eq!(
+                orion_auth_verify(key, tag.as_ptr(), 32, b"dat".as_ptr(), 3),
+                ORION_ERROR
+            );
+            assert_eq!(
+                orion_auth_verify(key, tag.as_ptr(), 31, b"data".as_ptr(), 4),
+                ORION_ERROR
+            );
+            tag[0] ^= 1;
+            assert_eq!(
+                orion_auth_verify(key, tag.as_ptr(), 32, b"data".as_ptr(), 4),
+                ORION_ERROR
+            );
+
+            orion_secret_key_free(key);
+        }
+    }
+
+    #[test]
+    fn test_pwhash() {
+        let password = b"Secret password";
+        let mut encoded = [0u8; ORION_PWHASH_ENCODED_MAX + 1];
+        let mut written = 0usize;
+
+        unsafe {
+            assert_eq!(
+                orion_pwhash_hash_password(
+                    password.as_ptr(),
+                    password.len(),
+                    3,
+                    64,
+                    encoded.as_mut_ptr(),
+                    encoded.len(),
+                    &mut written
+                ),
+                ORION_OK
+            );
+            assert!(written > 0 && written <= ORION_PWHASH_ENCODED_MAX);
+            assert_eq!(encoded[written], 0);
+
+            assert_eq!(
+                orion_pwhash_verify(
+                    encoded.as_ptr(),
+                    written,
+                    password.as_ptr(),
+                    password.len(),
+                    3,
+                    64
+                ),
+                ORION_OK
+            );
+            assert_eq!(
+                orion_pwhash_verify(encoded.as_ptr(), written, password.as_ptr(), 6, 3, 64),
+                ORION_ERROR
+            );
+            assert_eq!(
+                orion_pwhash_verify(
+                    encoded.as_ptr(),
+                    written,
+                    password.as_ptr(),
+                    password.len(),
+                    4,
+                    64
+                ),
+                ORION_ERROR
+            );
+
+            // The output buffer must fit the longest encoding.
+            assert_eq!(
+                orion_pwhash_hash_password(
+                    password.as_ptr(),
+                    password.len(),
+                    3,
+                    64,
+                    encoded.as_mut_ptr(),
+                    ORION_PWHASH_ENCODED_MAX,
+                    &mut written
+                ),
+                ORION_ERROR
+            );
+        }
+    }
+}
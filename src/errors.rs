@@ -59,6 +59,13 @@ impl From<base64::DecodeError> for UnknownCryptoError {
     }
 }
 
+#[cfg(feature = "safe_api")]
+impl From<std::io::Error> for UnknownCryptoError {
+    fn from(_: std::io::Error) -> Self {
+        UnknownCryptoError
+    }
+}
+
 impl From<core::num::ParseIntError> for UnknownCryptoError {
     fn from(_: core::num::ParseIntError) -> Self {
         UnknownCryptoError
@@ -131,6 +138,18 @@ fn test_unknown_crypto_from_decode_error() {
     assert_eq!(err, "UnknownCryptoErrorUnknownCryptoError");
 }
 
+#[test]
+#[cfg(feature = "safe_api")]
+fn test_unknown_crypto_from_io_error() {
+    use std::io::{Error, ErrorKind};
+
+    let err_foreign = Error::new(ErrorKind::UnexpectedEof, "eof");
+
+    // Tests Debug impl through "{:?}" and Display impl though "{}"
+    let err = format!("{:?}", UnknownCryptoError::from(err_foreign));
+    assert_eq!(err, "UnknownCryptoError");
+}
+
 #[test]
 #[cfg(feature = "safe_api")]
 fn test_unknown_crypto_from_parseint_error() {
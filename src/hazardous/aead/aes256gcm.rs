@@ -0,0 +1,544 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! AES-256-GCM as specified in [NIST SP 800-38D], with 96-bit nonces and
+//! 128-bit tags. It is compatible with the `aes-256-gcm` of OpenSSL and
+//! `crypto_aead_aes256gcm` of libsodium.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `nonce`: The nonce value.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be `None`).
+//! - `ciphertext_with_tag`: The encrypted data with the corresponding 16 byte
+//!   tag appended to it.
+//! - `plaintext`: The data to be encrypted.
+//! - `dst_out`: Destination array that will hold the
+//!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//! - `ciphertext`, `tag`: The encrypted data and its 16 byte tag, when passed
+//!   separately to [`open_detached()`].
+//! - `dst_ciphertext`, `dst_tag`: Destination arrays that will hold the
+//!   ciphertext and the tag after encryption with [`seal_detached()`].
+//!
+//! None of the functions in this module allocate.
+//!
+//! [`open_verify_only()`] authenticates `ciphertext_with_tag` and `ad`
+//! without running AES-CTR over the ciphertext, so no plaintext is produced.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than `plaintext` + [`AES256GCM_TAGSIZE`] when calling [`seal()`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - [`AES256GCM_TAGSIZE`] when
+//!   calling [`open()`].
+//! - The length of `ciphertext_with_tag` is not at least [`AES256GCM_TAGSIZE`].
+//! - The received tag does not match the calculated tag when calling [`open()`],
+//!   [`open_detached()`] or [`open_verify_only()`].
+//! - `plaintext.len()` + [`AES256GCM_TAGSIZE`] overflows when calling [`seal()`].
+//! - The length of `dst_ciphertext` is less than `plaintext` when calling
+//!   [`seal_detached()`].
+//! - The length of `dst_out` is less than `ciphertext` when calling
+//!   [`open_detached()`].
+//! - The length of `dst_tag` or `tag` is not [`AES256GCM_TAGSIZE`].
+//! - The `plaintext` or `ciphertext` is longer than [`AES256GCM_MAX_MSGSIZE`].
+//!
+//! # Security:
+//! - It is critical for security that a given nonce is not re-used with a given
+//!   key. With AES-GCM, a repeated nonce not only reveals the XOR of the
+//!   plaintexts, but also allows forging tags for any message under the key.
+//! - The nonce is too small to be randomly generated using a CSPRNG for more
+//!   than about 2^32 messages per key. Use a counter, or [`XChaCha20Poly1305`]
+//!   if nonces must be random.
+//! - To securely generate a strong key, use [`SecretKey::generate()`].
+//! - The length of the `plaintext` is not hidden, only its contents.
+//! - AES and GHASH are computed without lookup tables, so that the execution
+//!   time does not depend on the key or data. GHASH assumes that 64-bit
+//!   integer multiplication runs in constant time, which is not the case on
+//!   some older and embedded CPUs.
+//! - Hardware AES instructions (AES-NI, ARMv8 Cryptography Extensions) are not
+//!   used, since calling them requires `unsafe` code which orion forbids. This
+//!   makes AES-256-GCM several times slower than ChaCha20-Poly1305 in orion.
+//!
+//! # Recommendation:
+//! - It is recommended to use [`XChaCha20Poly1305`] when possible, and
+//!   AES-256-GCM where it is mandated.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::aes256gcm;
+//!
+//! let secret_key = aes256gcm::SecretKey::generate();
+//!
+//! // WARNING: This nonce is only meant for demonstration and should not
+//! // be repeated. Please read the security section.
+//! let nonce = aes256gcm::Nonce::from([0u8; 12]);
+//! let ad = "Additional data".as_bytes();
+//! let message = "Data to protect".as_bytes();
+//!
+//! // Length of the above message is 15 and then we accommodate 16 for the tag.
+//! let mut dst_out_ct = [0u8; 15 + aes256gcm::AES256GCM_TAGSIZE];
+//! let mut dst_out_pt = [0u8; 15];
+//! // Encrypt and place ciphertext + tag in dst_out_ct
+//! aes256gcm::seal(&secret_key, &nonce, message, Some(&ad), &mut dst_out_ct)?;
+//! // Verify tag, if correct then decrypt and place message in dst_out_pt
+//! aes256gcm::open(&secret_key, &nonce, &dst_out_ct, Some(&ad), &mut dst_out_pt)?;
+//!
+//! assert_eq!(dst_out_pt.as_ref(), message.as_ref());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [NIST SP 800-38D]: https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+//! [`XChaCha20Poly1305`]: ../xchacha20poly1305/index.html
+//! [`AES256GCM_TAGSIZE`]: constant.AES256GCM_TAGSIZE.html
+//! [`AES256GCM_MAX_MSGSIZE`]: constant.AES256GCM_MAX_MSGSIZE.html
+//! [`seal()`]: fn.seal.html
+//! [`open()`]: fn.open.html
+//! [`seal_detached()`]: fn.seal_detached.html
+//! [`open_detached()`]: fn.open_detached.html
+//! [`open_verify_only()`]: fn.open_verify_only.html
+
+use super::aes_gcm::{AesGcm, GCM_NONCESIZE, GCM_TAGSIZE};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::block::aes::{AES256_KEYSIZE, AES_BLOCKSIZE};
+
+/// The size of an AES-256-GCM secret key.
+pub const AES256GCM_KEYSIZE: usize = AES256_KEYSIZE;
+/// The size of an AES-256-GCM nonce.
+pub const AES256GCM_NONCESIZE: usize = GCM_NONCESIZE;
+/// The size of an AES-256-GCM tag.
+pub const AES256GCM_TAGSIZE: usize = GCM_TAGSIZE;
+/// The maximum size of a plaintext, which is 2^32 - 2 blocks.
+pub const AES256GCM_MAX_MSGSIZE: u64 = ((1u64 << 32) - 2) * (AES_BLOCKSIZE as u64);
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` that AES-256-GCM uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, AES256GCM_KEYSIZE, AES256GCM_KEYSIZE, AES256GCM_KEYSIZE)
+}
+
+impl_from_trait!(SecretKey, AES256GCM_KEYSIZE);
+
+construct_public! {
+    /// A type that represents a `Nonce` that AES-256-GCM uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 12 bytes.
+    (Nonce, test_nonce, AES256GCM_NONCESIZE, AES256GCM_NONCESIZE)
+}
+
+impl_from_trait!(Nonce, AES256GCM_NONCESIZE);
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD AES-256-GCM encryption and authentication as specified in the [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf).
+pub fn seal(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    match plaintext.len().checked_add(AES256GCM_TAGSIZE) {
+        Some(out_min_len) => {
+            if dst_out.len() < out_min_len {
+                return Err(UnknownCryptoError);
+            }
+        }
+        None => return Err(UnknownCryptoError),
+    };
+
+    let (dst_ciphertext, dst_tag) = dst_out.split_at_mut(plaintext.len());
+    seal_detached(
+        secret_key,
+        nonce,
+        plaintext,
+        ad,
+        dst_ciphertext,
+        &mut dst_tag[..AES256GCM_TAGSIZE],
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD AES-256-GCM decryption and authentication as specified in the [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf).
+pub fn open(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < AES256GCM_TAGSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - AES256GCM_TAGSIZE);
+    open_detached(secret_key, nonce, ciphertext, tag, ad, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD AES-256-GCM encryption and authentication as specified in the [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf),
+/// writing the ciphertext and tag to separate buffers.
+pub fn seal_detached(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: Option<&[u8]>,
+    dst_ciphertext: &mut [u8],
+    dst_tag: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_ciphertext.len() < plaintext.len() {
+        return Err(UnknownCryptoError);
+    }
+    if dst_tag.len() != AES256GCM_TAGSIZE {
+        return Err(UnknownCryptoError);
+    }
+    if (plaintext.len() as u64) > AES256GCM_MAX_MSGSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let ctx = AesGcm::new(secret_key.unprotected_as_bytes())?;
+    let dst_ciphertext = &mut dst_ciphertext[..plaintext.len()];
+    dst_ciphertext.copy_from_slice(plaintext);
+
+    ctx.seal_in_place(
+        nonce.as_ref(),
+        ad.unwrap_or(&[0u8; 0]),
+        dst_ciphertext,
+        dst_tag,
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD AES-256-GCM decryption and authentication as specified in the [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf),
+/// reading the ciphertext and tag from separate buffers.
+pub fn open_detached(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext: &[u8],
+    tag: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if tag.len() != AES256GCM_TAGSIZE {
+        return Err(UnknownCryptoError);
+    }
+    if dst_out.len() < ciphertext.len() {
+        return Err(UnknownCryptoError);
+    }
+
+    let ctx = AesGcm::new(secret_key.unprotected_as_bytes())?;
+    ctx.open_into(
+        nonce.as_ref(),
+        ad.unwrap_or(&[0u8; 0]),
+        ciphertext,
+        tag,
+        dst_out,
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the GHASH tag of `ciphertext_with_tag` over `ad` and the
+/// ciphertext, without decrypting it.
+pub fn open_verify_only(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < AES256GCM_TAGSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - AES256GCM_TAGSIZE);
+    let ctx = AesGcm::new(secret_key.unprotected_as_bytes())?;
+    ctx.verify_only(nonce.as_ref(), ad.unwrap_or(&[0u8; 0]), ciphertext, tag)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+
+    // Proptests. Only executed when NOT testing no_std.
+    #[cfg(feature = "safe_api")]
+    mod proptest {
+        use super::*;
+        use crate::test_framework::aead_interface::*;
+        use crate::test_framework::streamcipher_interface::TestingRandom;
+
+        impl TestingRandom for SecretKey {
+            fn gen() -> Self {
+                Self::generate()
+            }
+        }
+
+        impl TestingRandom for Nonce {
+            fn gen() -> Self {
+                let mut n = [0u8; AES256GCM_NONCESIZE];
+                crate::util::secure_rand_bytes(&mut n).unwrap();
+                Self::from_slice(&n).unwrap()
+            }
+        }
+
+        quickcheck! {
+            fn prop_aead_interface(input: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from_slice(&[0u8; AES256GCM_NONCESIZE]).unwrap();
+                AeadTestRunner(seal, open, secret_key, nonce, &input, None, AES256GCM_TAGSIZE, &ad);
+                test_diff_params_err(&seal, &open, &input, AES256GCM_TAGSIZE);
+                true
+            }
+        }
+    }
+
+    mod test_detached {
+        use super::*;
+
+        #[test]
+        fn detached_tag_length_err() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut dst_ct = [0u8; 4];
+            let mut dst_pt = [0u8; 4];
+
+            for tag_len in [0usize, AES256GCM_TAGSIZE - 1, AES256GCM_TAGSIZE + 1].iter() {
+                let mut dst_tag = [0u8; AES256GCM_TAGSIZE + 1];
+                assert!(seal_detached(
+                    &secret_key,
+                    &nonce,
+                    b"test",
+                    None,
+                    &mut dst_ct,
+                    &mut dst_tag[..*tag_len]
+                )
+                .is_err());
+                assert!(open_detached(
+                    &secret_key,
+                    &nonce,
+                    &dst_ct,
+                    &dst_tag[..*tag_len],
+                    None,
+                    &mut dst_pt
+                )
+                .is_err());
+            }
+        }
+
+        #[test]
+        fn detached_dst_length() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut dst_ct = [0u8; 5];
+            let mut dst_tag = [0u8; AES256GCM_TAGSIZE];
+            let mut dst_pt = [0u8; 5];
+
+            assert!(seal_detached(
+                &secret_key,
+                &nonce,
+                b"test",
+                None,
+                &mut dst_ct[..3],
+                &mut dst_tag
+            )
+            .is_err());
+            // A longer destination is allowed.
+            seal_detached(
+                &secret_key,
+                &nonce,
+                b"test",
+                None,
+                &mut dst_ct,
+                &mut dst_tag,
+            )
+            .unwrap();
+
+            assert!(open_detached(
+                &secret_key,
+                &nonce,
+                &dst_ct[..4],
+                &dst_tag,
+                None,
+                &mut dst_pt[..3]
+            )
+            .is_err());
+            open_detached(
+                &secret_key,
+                &nonce,
+                &dst_ct[..4],
+                &dst_tag,
+                None,
+                &mut dst_pt,
+            )
+            .unwrap();
+            assert_eq!(&dst_pt[..4], b"test");
+        }
+
+        #[test]
+        fn invalid_tag_leaves_dst_untouched() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut dst_ct = [0u8; 4];
+            let mut dst_tag = [0u8; AES256GCM_TAGSIZE];
+            seal_detached(
+                &secret_key,
+                &nonce,
+                b"test",
+                None,
+                &mut dst_ct,
+                &mut dst_tag,
+            )
+            .unwrap();
+
+            dst_tag[15] ^= 1;
+            let mut dst_pt = [0xAAu8; 4];
+            assert!(
+                open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, None, &mut dst_pt).is_err()
+            );
+            assert_eq!(dst_pt, [0xAAu8; 4]);
+        }
+
+        quickcheck! {
+            /// Sealing detached should produce the same ciphertext and tag as
+            /// seal(), and be opened by both open() and open_detached().
+            fn prop_detached_same_as_combined(input: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; 12]);
+
+                let mut combined = vec![0u8; input.len() + AES256GCM_TAGSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut combined).unwrap();
+
+                let mut dst_ct = vec![0u8; input.len()];
+                let mut dst_tag = [0u8; AES256GCM_TAGSIZE];
+                seal_detached(&secret_key, &nonce, &input, Some(&ad), &mut dst_ct, &mut dst_tag).unwrap();
+
+                let mut dst_pt = vec![0u8; input.len()];
+                open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_pt).unwrap();
+                let mut dst_pt_combined = vec![0u8; input.len()];
+                open(&secret_key, &nonce, &combined, Some(&ad), &mut dst_pt_combined).unwrap();
+
+                let same_output = combined[..input.len()] == dst_ct[..]
+                    && combined[input.len()..] == dst_tag[..]
+                    && dst_pt == input
+                    && dst_pt_combined == input;
+
+                // Modifying the tag should fail.
+                dst_tag[0] ^= 1;
+                let modified_err = open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_pt).is_err();
+
+                same_output && modified_err
+            }
+        }
+    }
+
+    mod test_verify_only {
+        use super::*;
+
+        #[test]
+        fn verify_only_err() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut sealed = [0u8; 4 + AES256GCM_TAGSIZE];
+            seal(&secret_key, &nonce, b"test", None, &mut sealed).unwrap();
+
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, None).is_ok());
+            // Absent and empty additional data are the same.
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(&[])).is_ok());
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(b"ad")).is_err());
+            assert!(
+                open_verify_only(&secret_key, &nonce, &sealed[..AES256GCM_TAGSIZE - 1], None)
+                    .is_err()
+            );
+            assert!(open_verify_only(&secret_key, &nonce, &sealed[1..], None).is_err());
+        }
+
+        quickcheck! {
+            /// open_verify_only() should accept exactly what open() accepts.
+            fn prop_verify_only_same_as_open(input: Vec<u8>, ad: Vec<u8>, flip: usize) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; 12]);
+                let mut sealed = vec![0u8; input.len() + AES256GCM_TAGSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut sealed).unwrap();
+                let valid = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_ok();
+
+                let idx = flip % sealed.len();
+                sealed[idx] ^= 1;
+                let mut dst_pt = vec![0u8; input.len()];
+                let modified_err = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_err()
+                    && open(&secret_key, &nonce, &sealed, Some(&ad), &mut dst_pt).is_err();
+
+                valid && modified_err
+            }
+        }
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    #[test]
+    fn gcm_spec_test_case_15() {
+        // Test Case 15 from "The Galois/Counter Mode of Operation (GCM)".
+        let secret_key = SecretKey::from_slice(&[
+            0xFE, 0xFF, 0xE9, 0x92, 0x86, 0x65, 0x73, 0x1C, 0x6D, 0x6A, 0x8F, 0x94, 0x67, 0x30,
+            0x83, 0x08, 0xFE, 0xFF, 0xE9, 0x92, 0x86, 0x65, 0x73, 0x1C, 0x6D, 0x6A, 0x8F, 0x94,
+            0x67, 0x30, 0x83, 0x08,
+        ])
+        .unwrap();
+        let nonce = Nonce::from([
+            0xCA, 0xFE, 0xBA, 0xBE, 0xFA, 0xCE, 0xDB, 0xAD, 0xDE, 0xCA, 0xF8, 0x88,
+        ]);
+        let plaintext = [
+            0xD9, 0x31, 0x32, 0x25, 0xF8, 0x84, 0x06, 0xE5, 0xA5, 0x59, 0x09, 0xC5, 0xAF, 0xF5,
+            0x26, 0x9A, 0x86, 0xA7, 0xA9, 0x53, 0x15, 0x34, 0xF7, 0xDA, 0x2E, 0x4C, 0x30, 0x3D,
+            0x8A, 0x31, 0x8A, 0x72, 0x1C, 0x3C, 0x0C, 0x95, 0x95, 0x68, 0x09, 0x53, 0x2F, 0xCF,
+            0x0E, 0x24, 0x49, 0xA6, 0xB5, 0x25, 0xB1, 0x6A, 0xED, 0xF5, 0xAA, 0x0D, 0xE6, 0x57,
+            0xBA, 0x63, 0x7B, 0x39, 0x1A, 0xAF, 0xD2, 0x55,
+        ];
+        let expected = [
+            0x52, 0x2D, 0xC1, 0xF0, 0x99, 0x56, 0x7D, 0x07, 0xF4, 0x7F, 0x37, 0xA3, 0x2A, 0x84,
+            0x42, 0x7D, 0x64, 0x3A, 0x8C, 0xDC, 0xBF, 0xE5, 0xC0, 0xC9, 0x75, 0x98, 0xA2, 0xBD,
+            0x25, 0x55, 0xD1, 0xAA, 0x8C, 0xB0, 0x8E, 0x48, 0x59, 0x0D, 0xBB, 0x3D, 0xA7, 0xB0,
+            0x8B, 0x10, 0x56, 0x82, 0x88, 0x38, 0xC5, 0xF6, 0x1E, 0x63, 0x93, 0xBA, 0x7A, 0x0A,
+            0xBC, 0xC9, 0xF6, 0x62, 0x89, 0x80, 0x15, 0xAD, 0xB0, 0x94, 0xDA, 0xC5, 0xD9, 0x34,
+            0x71, 0xBD, 0xEC, 0x1A, 0x50, 0x22, 0x70, 0xE3, 0xCC, 0x6C,
+        ];
+
+        let mut dst_out = [0u8; 64 + AES256GCM_TAGSIZE];
+        seal(&secret_key, &nonce, &plaintext, None, &mut dst_out).unwrap();
+        assert_eq!(dst_out[..], expected[..]);
+
+        let mut dst_pt = [0u8; 64];
+        open(&secret_key, &nonce, &dst_out, None, &mut dst_pt).unwrap();
+        assert_eq!(dst_pt[..], plaintext[..]);
+    }
+}
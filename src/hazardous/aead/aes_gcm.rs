@@ -0,0 +1,459 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! AES-GCM as specified in [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf).
+//!
+//! This is used by [`aes256gcm`] and internally, by protocols that require
+//! AES-GCM with other key sizes, such as SRTP. Only 96-bit nonces and full
+//! 128-bit tags are supported. Prefer [`xchacha20poly1305`] everywhere else.
+//!
+//! GHASH is computed without lookup tables, with the constant-time carryless
+//! multiplication from BearSSL's `ghash_ctmul64`. It assumes that 64-bit
+//! integer multiplication runs in constant time.
+//!
+//! [`aes256gcm`]: ../aes256gcm/index.html
+//! [`xchacha20poly1305`]: ../xchacha20poly1305/index.html
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::block::aes::{self, Aes, AES_BLOCKSIZE};
+use zeroize::Zeroize;
+
+/// The nonce size for AES-GCM.
+pub(crate) const GCM_NONCESIZE: usize = 12;
+/// The tag size for AES-GCM.
+pub(crate) const GCM_TAGSIZE: usize = 16;
+
+/// The number of counter blocks encrypted at once, which is the number of
+/// blocks that [`Aes`] processes in parallel.
+const CTR_PARALLEL_BLOCKS: usize = 4;
+
+/// XOR `data` with the AES keystream for `counter_block`, incrementing the
+/// last 32 bits of the counter block, as a big-endian integer, for each block.
+pub(crate) fn ctr32_xor(aes: &Aes, counter_block: &[u8; AES_BLOCKSIZE], data: &mut [u8]) {
+    let mut keystream = [0u8; AES_BLOCKSIZE * CTR_PARALLEL_BLOCKS];
+    let mut counter = u32::from_be_bytes([
+        counter_block[12],
+        counter_block[13],
+        counter_block[14],
+        counter_block[15],
+    ]);
+
+    for chunk in data.chunks_mut(keystream.len()) {
+        let blocks = chunk.chunks(AES_BLOCKSIZE).count();
+        for block in keystream.chunks_mut(AES_BLOCKSIZE).take(blocks) {
+            block[..12].copy_from_slice(&counter_block[..12]);
+            block[12..].copy_from_slice(&counter.to_be_bytes());
+            counter = counter.wrapping_add(1);
+        }
+        // Cannot fail, since the keystream is a whole number of blocks.
+        aes.encrypt_blocks(&mut keystream[..blocks * AES_BLOCKSIZE])
+            .unwrap();
+        for (byte, key) in chunk.iter_mut().zip(keystream.iter()) {
+            *byte ^= key;
+        }
+    }
+
+    keystream.zeroize();
+}
+
+/// Carryless multiplication of `x` and `y`, truncated to 64 bits, where the
+/// integer multiplications leave holes so that carries cannot propagate.
+const fn bmul64(x: u64, y: u64) -> u64 {
+    const M0: u64 = 0x1111_1111_1111_1111;
+    const M1: u64 = 0x2222_2222_2222_2222;
+    const M2: u64 = 0x4444_4444_4444_4444;
+    const M3: u64 = 0x8888_8888_8888_8888;
+
+    let (x0, x1, x2, x3) = (x & M0, x & M1, x & M2, x & M3);
+    let (y0, y1, y2, y3) = (y & M0, y & M1, y & M2, y & M3);
+    let z0 = x0.wrapping_mul(y0) ^ x1.wrapping_mul(y3) ^ x2.wrapping_mul(y2) ^ x3.wrapping_mul(y1);
+    let z1 = x0.wrapping_mul(y1) ^ x1.wrapping_mul(y0) ^ x2.wrapping_mul(y3) ^ x3.wrapping_mul(y2);
+    let z2 = x0.wrapping_mul(y2) ^ x1.wrapping_mul(y1) ^ x2.wrapping_mul(y0) ^ x3.wrapping_mul(y3);
+    let z3 = x0.wrapping_mul(y3) ^ x1.wrapping_mul(y2) ^ x2.wrapping_mul(y1) ^ x3.wrapping_mul(y0);
+
+    (z0 & M0) | (z1 & M1) | (z2 & M2) | (z3 & M3)
+}
+
+/// Reverse the bits of `x`.
+const fn rev64(mut x: u64) -> u64 {
+    x = ((x & 0x5555_5555_5555_5555) << 1) | ((x >> 1) & 0x5555_5555_5555_5555);
+    x = ((x & 0x3333_3333_3333_3333) << 2) | ((x >> 2) & 0x3333_3333_3333_3333);
+    x = ((x & 0x0F0F_0F0F_0F0F_0F0F) << 4) | ((x >> 4) & 0x0F0F_0F0F_0F0F_0F0F);
+    x = ((x & 0x00FF_00FF_00FF_00FF) << 8) | ((x >> 8) & 0x00FF_00FF_00FF_00FF);
+    x = ((x & 0x0000_FFFF_0000_FFFF) << 16) | ((x >> 16) & 0x0000_FFFF_0000_FFFF);
+    x.rotate_right(32)
+}
+
+/// The GHASH universal hash.
+struct Ghash {
+    /// The hash key H, as the high and low 64 bits.
+    h1: u64,
+    h0: u64,
+    /// The accumulator Y, as the high and low 64 bits.
+    y1: u64,
+    y0: u64,
+}
+
+impl Drop for Ghash {
+    fn drop(&mut self) {
+        self.h1.zeroize();
+        self.h0.zeroize();
+        self.y1.zeroize();
+        self.y0.zeroize();
+    }
+}
+
+impl Ghash {
+    fn new(h: &[u8; AES_BLOCKSIZE]) -> Self {
+        let mut h1 = [0u8; 8];
+        let mut h0 = [0u8; 8];
+        h1.copy_from_slice(&h[..8]);
+        h0.copy_from_slice(&h[8..]);
+
+        Self {
+            h1: u64::from_be_bytes(h1),
+            h0: u64::from_be_bytes(h0),
+            y1: 0,
+            y0: 0,
+        }
+    }
+
+    /// Multiply the accumulator, after adding `block` to it, by H.
+    fn process_block(&mut self, block: &[u8; AES_BLOCKSIZE]) {
+        let mut hi = [0u8; 8];
+        let mut lo = [0u8; 8];
+        hi.copy_from_slice(&block[..8]);
+        lo.copy_from_slice(&block[8..]);
+        let y1 = self.y1 ^ u64::from_be_bytes(hi);
+        let y0 = self.y0 ^ u64::from_be_bytes(lo);
+
+        let (h0, h1) = (self.h0, self.h1);
+        let (h0r, h1r) = (rev64(h0), rev64(h1));
+        let (h2, h2r) = (h0 ^ h1, h0r ^ h1r);
+        let (y0r, y1r) = (rev64(y0), rev64(y1));
+        let (y2, y2r) = (y0 ^ y1, y0r ^ y1r);
+
+        // Karatsuba on the bit-reversed and the plain operands, which give the
+        // high and low halves of each 128-bit product.
+        let z0 = bmul64(y0, h0);
+        let z1 = bmul64(y1, h1);
+        let mut z2 = bmul64(y2, h2);
+        let mut z0h = bmul64(y0r, h0r);
+        let mut z1h = bmul64(y1r, h1r);
+        let mut z2h = bmul64(y2r, h2r);
+        z2 ^= z0 ^ z1;
+        z2h ^= z0h ^ z1h;
+        z0h = rev64(z0h) >> 1;
+        z1h = rev64(z1h) >> 1;
+        z2h = rev64(z2h) >> 1;
+
+        let v0 = z0;
+        let mut v1 = z0h ^ z2;
+        let mut v2 = z1 ^ z2h;
+        let mut v3 = z1h;
+
+        // The field elements are bit-reflected, so the product is shifted by
+        // one bit before the reduction modulo x^128 + x^7 + x^2 + x + 1.
+        v3 = (v3 << 1) | (v2 >> 63);
+        v2 = (v2 << 1) | (v1 >> 63);
+        v1 = (v1 << 1) | (v0 >> 63);
+        let v0 = v0 << 1;
+
+        v2 ^= v0 ^ (v0 >> 1) ^ (v0 >> 2) ^ (v0 >> 7);
+        v1 ^= (v0 << 63) ^ (v0 << 62) ^ (v0 << 57);
+        v3 ^= v1 ^ (v1 >> 1) ^ (v1 >> 2) ^ (v1 >> 7);
+        v2 ^= (v1 << 63) ^ (v1 << 62) ^ (v1 << 57);
+
+        self.y0 = v2;
+        self.y1 = v3;
+    }
+
+    /// Process `data`, zero-padded to a whole number of blocks.
+    fn update_padded(&mut self, data: &[u8]) {
+        let mut block = [0u8; AES_BLOCKSIZE];
+        for chunk in data.chunks(AES_BLOCKSIZE) {
+            block[..chunk.len()].copy_from_slice(chunk);
+            for byte in block.iter_mut().skip(chunk.len()) {
+                *byte = 0;
+            }
+            self.process_block(&block);
+        }
+        block.zeroize();
+    }
+
+    /// Process the lengths of `ad` and the ciphertext, and write the hash into
+    /// `dest`.
+    fn finalize(&mut self, ad_len: usize, ct_len: usize, dest: &mut [u8; AES_BLOCKSIZE]) {
+        let mut block = [0u8; AES_BLOCKSIZE];
+        block[..8].copy_from_slice(&((ad_len as u64) * 8).to_be_bytes());
+        block[8..].copy_from_slice(&((ct_len as u64) * 8).to_be_bytes());
+        self.process_block(&block);
+        dest[..8].copy_from_slice(&self.y1.to_be_bytes());
+        dest[8..].copy_from_slice(&self.y0.to_be_bytes());
+    }
+}
+
+/// An AES-GCM context with an expanded key.
+pub(crate) struct AesGcm {
+    aes: Aes,
+    h: [u8; AES_BLOCKSIZE],
+}
+
+impl Drop for AesGcm {
+    fn drop(&mut self) {
+        self.h.zeroize();
+    }
+}
+
+impl AesGcm {
+    /// Initialize with a 16-, 24- or 32-byte `secret_key`.
+    pub(crate) fn new(secret_key: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let aes = Aes::new(&aes::SecretKey::from_slice(secret_key)?)?;
+        let mut h = [0u8; AES_BLOCKSIZE];
+        aes.encrypt_block(&mut h)?;
+
+        Ok(Self { aes, h })
+    }
+
+    /// Compute the tag over `ad` and `ciphertext` into `dest`.
+    fn tag(&self, j0: &[u8; AES_BLOCKSIZE], ad: &[u8], ciphertext: &[u8], dest: &mut [u8]) {
+        let mut ghash = Ghash::new(&self.h);
+        ghash.update_padded(ad);
+        ghash.update_padded(ciphertext);
+        let mut s = [0u8; AES_BLOCKSIZE];
+        ghash.finalize(ad.len(), ciphertext.len(), &mut s);
+        ctr32_xor(&self.aes, j0, &mut s);
+        dest.copy_from_slice(&s);
+        s.zeroize();
+    }
+
+    /// The pre-counter block J0 for `nonce`.
+    fn j0(nonce: &[u8]) -> [u8; AES_BLOCKSIZE] {
+        let mut j0 = [0u8; AES_BLOCKSIZE];
+        j0[..GCM_NONCESIZE].copy_from_slice(nonce);
+        j0[15] = 1;
+        j0
+    }
+
+    /// The first counter block for the message, which is J0 incremented.
+    fn first_counter(j0: &[u8; AES_BLOCKSIZE]) -> [u8; AES_BLOCKSIZE] {
+        let mut counter = *j0;
+        counter[15] = 2;
+        counter
+    }
+
+    /// Encrypt `data` in place and write the tag over `ad` and the ciphertext
+    /// into `tag_out`.
+    pub(crate) fn seal_in_place(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        data: &mut [u8],
+        tag_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        if nonce.len() != GCM_NONCESIZE || tag_out.len() != GCM_TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+        // Each message is limited to 2^32 - 2 blocks.
+        if (data.len() as u64) > ((1u64 << 32) - 2) * (AES_BLOCKSIZE as u64) {
+            return Err(UnknownCryptoError);
+        }
+
+        let j0 = Self::j0(nonce);
+        ctr32_xor(&self.aes, &Self::first_counter(&j0), data);
+        self.tag(&j0, ad, data, tag_out);
+
+        Ok(())
+    }
+
+    /// Verify `tag` over `ad` and `ciphertext`, returning the pre-counter
+    /// block for decryption if it is valid.
+    fn verify(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<[u8; AES_BLOCKSIZE], UnknownCryptoError> {
+        if nonce.len() != GCM_NONCESIZE || tag.len() != GCM_TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+        if (ciphertext.len() as u64) > ((1u64 << 32) - 2) * (AES_BLOCKSIZE as u64) {
+            return Err(UnknownCryptoError);
+        }
+
+        let j0 = Self::j0(nonce);
+        let mut expected = [0u8; GCM_TAGSIZE];
+        self.tag(&j0, ad, ciphertext, &mut expected);
+        let valid = crate::util::secure_cmp(&expected, tag);
+        expected.zeroize();
+        valid?;
+
+        Ok(j0)
+    }
+
+    /// Verify `tag` over `ad` and `ciphertext` without decrypting it.
+    pub(crate) fn verify_only(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        self.verify(nonce, ad, ciphertext, tag).map(|_| ())
+    }
+
+    /// Verify `tag` over `ad` and `data`, and decrypt `data` in place if it
+    /// is valid.
+    pub(crate) fn open_in_place(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        data: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let j0 = self.verify(nonce, ad, data, tag)?;
+        ctr32_xor(&self.aes, &Self::first_counter(&j0), data);
+
+        Ok(())
+    }
+
+    /// Verify `tag` over `ad` and `ciphertext`, and decrypt `ciphertext`
+    /// into `dst_out` if it is valid. `dst_out` is not modified otherwise.
+    pub(crate) fn open_into(
+        &self,
+        nonce: &[u8],
+        ad: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        if dst_out.len() < ciphertext.len() {
+            return Err(UnknownCryptoError);
+        }
+        let j0 = self.verify(nonce, ad, ciphertext, tag)?;
+        let dst_out = &mut dst_out[..ciphertext.len()];
+        dst_out.copy_from_slice(ciphertext);
+        ctr32_xor(&self.aes, &Self::first_counter(&j0), dst_out);
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn test_runner(key: &str, nonce: &str, ad: &str, plaintext: &str, ct: &str, tag: &str) {
+        let key = hex::decode(key).unwrap();
+        let nonce = hex::decode(nonce).unwrap();
+        let ad = hex::decode(ad).unwrap();
+        let plaintext = hex::decode(plaintext).unwrap();
+        let ct = hex::decode(ct).unwrap();
+        let tag = hex::decode(tag).unwrap();
+
+        let ctx = AesGcm::new(&key).unwrap();
+        let mut data = plaintext.clone();
+        let mut tag_out = [0u8; GCM_TAGSIZE];
+        ctx.seal_in_place(&nonce, &ad, &mut data, &mut tag_out)
+            .unwrap();
+        assert_eq!(data, ct);
+        assert_eq!(&tag_out[..], &tag[..]);
+
+        ctx.open_in_place(&nonce, &ad, &mut data, &tag).unwrap();
+        assert_eq!(data, plaintext);
+
+        let mut bad_tag = tag.clone();
+        bad_tag[0] ^= 1;
+        let mut data = ct.clone();
+        assert!(ctx.open_in_place(&nonce, &ad, &mut data, &bad_tag).is_err());
+        // The ciphertext is left as it was when the tag is invalid.
+        assert_eq!(data, ct);
+    }
+
+    #[test]
+    fn test_gcm_spec_vectors() {
+        // Test Cases 1-4 from "The Galois/Counter Mode of Operation (GCM)".
+        test_runner(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "",
+            "",
+            "58e2fccefa7e3061367f1d57a4e7455a",
+        );
+        test_runner(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "0388dace60b6a392f328c2b971b2fe78",
+            "ab6e47d42cec13bdf53a67b21257bddf",
+        );
+        test_runner(
+            "feffe9928665731c6d6a8f9467308308",
+            "cafebabefacedbaddecaf888",
+            "",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+            "4d5c2af327cd64a62cf35abd2ba6fab4",
+        );
+        test_runner(
+            "feffe9928665731c6d6a8f9467308308",
+            "cafebabefacedbaddecaf888",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        );
+        // Test Case 16, with AES-256.
+        test_runner(
+            "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308",
+            "cafebabefacedbaddecaf888",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+            "76fc6ece0f4e1768cddf8853bb2d551b",
+        );
+    }
+
+    #[test]
+    fn test_parameter_lengths() {
+        let ctx = AesGcm::new(&[0u8; 16]).unwrap();
+        let mut tag = [0u8; GCM_TAGSIZE];
+        let mut data = [0u8; 32];
+        assert!(ctx
+            .seal_in_place(&[0u8; GCM_NONCESIZE - 1], &[], &mut data, &mut tag)
+            .is_err());
+        assert!(ctx
+            .seal_in_place(&[0u8; GCM_NONCESIZE], &[], &mut data, &mut tag[..15])
+            .is_err());
+        assert!(ctx
+            .open_in_place(&[0u8; GCM_NONCESIZE + 1], &[], &mut data, &tag)
+            .is_err());
+        assert!(ctx
+            .open_in_place(&[0u8; GCM_NONCESIZE], &[], &mut data, &tag[..15])
+            .is_err());
+        assert!(AesGcm::new(&[0u8; 15]).is_err());
+    }
+}
@@ -20,6 +20,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+//! # About:
+//! This is the IETF construction of ChaCha20Poly1305, which uses ChaCha20 with a
+//! 96-bit nonce and a 32-bit block counter. It is compatible with
+//! `crypto_aead_chacha20poly1305_ietf` of libsodium. For the original
+//! construction with a 64-bit nonce, see [`chacha20poly1305_original`].
+//!
 //! # Parameters:
 //! - `secret_key`: The secret key.
 //! - `nonce`: The nonce value.
@@ -29,6 +35,19 @@
 //! - `plaintext`: The data to be encrypted.
 //! - `dst_out`: Destination array that will hold the
 //!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//! - `ciphertext`, `tag`: The encrypted data and its 16 byte Poly1305 tag,
+//!   when passed separately to [`open_detached()`].
+//! - `dst_ciphertext`, `dst_tag`: Destination arrays that will hold the
+//!   ciphertext and the tag after encryption with [`seal_detached()`].
+//!
+//! None of the functions in this module allocate. [`seal_detached()`] and
+//! [`open_detached()`] are useful when the ciphertext and tag are stored
+//! apart, such as in packet formats with a trailing or leading tag field.
+//!
+//! [`open_verify_only()`] checks the tag of `ciphertext_with_tag` over `ad`
+//! without decrypting it, for relays that must validate messages but not read
+//! them. It produces no plaintext, and only computes the ChaCha20 block that
+//! holds the Poly1305 key.
 //!
 //! `ad`: "A typical use for these data is to authenticate version numbers,
 //! timestamps or monotonically increasing counters in order to discard previous
@@ -47,8 +66,14 @@
 //! - The length of `dst_out` is less than `ciphertext_with_tag` - [`POLY1305_OUTSIZE`] when
 //!   calling [`open()`].
 //! - The length of `ciphertext_with_tag` is not at least [`POLY1305_OUTSIZE`].
-//! - The received tag does not match the calculated tag when  calling [`open()`].
+//! - The received tag does not match the calculated tag when calling [`open()`]
+//!   or [`open_verify_only()`].
 //! - `plaintext.len()` + [`POLY1305_OUTSIZE`] overflows when  calling [`seal()`].
+//! - The length of `dst_ciphertext` is less than `plaintext` when calling
+//!   [`seal_detached()`].
+//! - The length of `dst_out` is less than `ciphertext` when calling
+//!   [`open_detached()`].
+//! - The length of `dst_tag` or `tag` is not [`POLY1305_OUTSIZE`].
 //! - Converting `usize` to `u64` would be a lossy conversion.
 //!
 //! # Panics:
@@ -90,13 +115,26 @@
 //! aead::chacha20poly1305::open(&secret_key, &nonce, &dst_out_ct, Some(&ad), &mut dst_out_pt)?;
 //!
 //! assert_eq!(dst_out_pt.as_ref(), message.as_ref());
+//!
+//! // Encrypt with the ciphertext and tag in separate buffers
+//! let mut dst_ct = [0u8; 15];
+//! let mut dst_tag = [0u8; 16];
+//! aead::chacha20poly1305::seal_detached(&secret_key, &nonce, message, Some(&ad), &mut dst_ct, &mut dst_tag)?;
+//! aead::chacha20poly1305::open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_out_pt)?;
+//!
+//! assert_eq!(dst_ct.as_ref(), &dst_out_ct[..15]);
+//! assert_eq!(dst_out_pt.as_ref(), message.as_ref());
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
 //! [`SecretKey::generate()`]: ../../stream/chacha20/struct.SecretKey.html
 //! [`XChaCha20Poly1305`]: ../xchacha20poly1305/index.html
+//! [`chacha20poly1305_original`]: ../chacha20poly1305_original/index.html
 //! [`POLY1305_OUTSIZE`]: ../../mac/poly1305/constant.POLY1305_OUTSIZE.html
 //! [`seal()`]: fn.seal.html
 //! [`open()`]: fn.open.html
+//! [`seal_detached()`]: fn.seal_detached.html
+//! [`open_detached()`]: fn.open_detached.html
+//! [`open_verify_only()`]: fn.open_verify_only.html
 pub use crate::hazardous::stream::chacha20::{Nonce, SecretKey};
 use crate::{
     errors::UnknownCryptoError,
@@ -162,38 +200,90 @@ pub fn seal(
         None => return Err(UnknownCryptoError),
     };
 
+    let (dst_ciphertext, dst_tag) = dst_out.split_at_mut(plaintext.len());
+    seal_detached(
+        secret_key,
+        nonce,
+        plaintext,
+        ad,
+        dst_ciphertext,
+        &mut dst_tag[..POLY1305_OUTSIZE],
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 decryption and authentication as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
+pub fn open(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - POLY1305_OUTSIZE);
+    open_detached(secret_key, nonce, ciphertext, tag, ad, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 encryption and authentication as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439),
+/// writing the ciphertext and tag to separate buffers.
+pub fn seal_detached(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: Option<&[u8]>,
+    dst_ciphertext: &mut [u8],
+    dst_tag: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_ciphertext.len() < plaintext.len() {
+        return Err(UnknownCryptoError);
+    }
+    if dst_tag.len() != POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
     let mut enc_ctx =
         ChaCha20::new(secret_key.unprotected_as_bytes(), nonce.as_ref(), true).unwrap();
     let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
 
     let pt_len = plaintext.len();
     if pt_len != 0 {
-        dst_out[..pt_len].copy_from_slice(plaintext);
-        chacha20::xor_keystream(&mut enc_ctx, ENC_CTR, tmp.as_mut(), &mut dst_out[..pt_len])?;
+        dst_ciphertext[..pt_len].copy_from_slice(plaintext);
+        chacha20::xor_keystream(
+            &mut enc_ctx,
+            ENC_CTR,
+            tmp.as_mut(),
+            &mut dst_ciphertext[..pt_len],
+        )?;
     }
 
     let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut enc_ctx, &mut tmp));
     let ad = ad.unwrap_or(&[0u8; 0]);
-    process_authentication(&mut auth_ctx, ad, &dst_out[..pt_len])?;
-    dst_out[pt_len..(pt_len + POLY1305_OUTSIZE)]
-        .copy_from_slice(auth_ctx.finalize()?.unprotected_as_bytes());
+    process_authentication(&mut auth_ctx, ad, &dst_ciphertext[..pt_len])?;
 
-    Ok(())
+    auth_ctx.finalize_into(dst_tag)
 }
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
-/// AEAD ChaCha20Poly1305 decryption and authentication as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
-pub fn open(
+/// AEAD ChaCha20Poly1305 decryption and authentication as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439),
+/// reading the ciphertext and tag from separate buffers.
+pub fn open_detached(
     secret_key: &SecretKey,
     nonce: &Nonce,
-    ciphertext_with_tag: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
     ad: Option<&[u8]>,
     dst_out: &mut [u8],
 ) -> Result<(), UnknownCryptoError> {
-    if ciphertext_with_tag.len() < POLY1305_OUTSIZE {
+    if tag.len() != POLY1305_OUTSIZE {
         return Err(UnknownCryptoError);
     }
-    if dst_out.len() < ciphertext_with_tag.len() - POLY1305_OUTSIZE {
+    if dst_out.len() < ciphertext.len() {
         return Err(UnknownCryptoError);
     }
 
@@ -202,16 +292,13 @@ pub fn open(
     let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
     let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut dec_ctx, &mut tmp));
 
-    let ciphertext_len = ciphertext_with_tag.len() - POLY1305_OUTSIZE;
+    let ciphertext_len = ciphertext.len();
     let ad = ad.unwrap_or(&[0u8; 0]);
-    process_authentication(&mut auth_ctx, ad, &ciphertext_with_tag[..ciphertext_len])?;
-    util::secure_cmp(
-        auth_ctx.finalize()?.unprotected_as_bytes(),
-        &ciphertext_with_tag[ciphertext_len..],
-    )?;
+    process_authentication(&mut auth_ctx, ad, ciphertext)?;
+    util::secure_cmp(auth_ctx.finalize()?.unprotected_as_bytes(), tag)?;
 
     if ciphertext_len != 0 {
-        dst_out[..ciphertext_len].copy_from_slice(&ciphertext_with_tag[..ciphertext_len]);
+        dst_out[..ciphertext_len].copy_from_slice(ciphertext);
         chacha20::xor_keystream(
             &mut dec_ctx,
             ENC_CTR,
@@ -223,6 +310,30 @@ pub fn open(
     Ok(())
 }
 
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the Poly1305 tag of `ciphertext_with_tag` over `ad` and the
+/// ciphertext, without decrypting it.
+pub fn open_verify_only(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - POLY1305_OUTSIZE);
+    let mut dec_ctx =
+        ChaCha20::new(secret_key.unprotected_as_bytes(), nonce.as_ref(), true).unwrap();
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut dec_ctx, &mut tmp));
+
+    process_authentication(&mut auth_ctx, ad.unwrap_or(&[0u8; 0]), ciphertext)?;
+    util::secure_cmp(auth_ctx.finalize()?.unprotected_as_bytes(), tag)
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 #[cfg(feature = "safe_api")]
@@ -245,6 +356,162 @@ mod public {
             }
         }
     }
+
+    mod test_detached {
+        use super::*;
+
+        #[test]
+        fn detached_tag_length_err() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut dst_ct = [0u8; 4];
+            let mut dst_pt = [0u8; 4];
+
+            for tag_len in [0usize, POLY1305_OUTSIZE - 1, POLY1305_OUTSIZE + 1].iter() {
+                let mut dst_tag = [0u8; POLY1305_OUTSIZE + 1];
+                assert!(seal_detached(
+                    &secret_key,
+                    &nonce,
+                    b"test",
+                    None,
+                    &mut dst_ct,
+                    &mut dst_tag[..*tag_len]
+                )
+                .is_err());
+                assert!(open_detached(
+                    &secret_key,
+                    &nonce,
+                    &dst_ct,
+                    &dst_tag[..*tag_len],
+                    None,
+                    &mut dst_pt
+                )
+                .is_err());
+            }
+        }
+
+        #[test]
+        fn detached_dst_length() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut dst_ct = [0u8; 5];
+            let mut dst_tag = [0u8; POLY1305_OUTSIZE];
+            let mut dst_pt = [0u8; 5];
+
+            assert!(seal_detached(
+                &secret_key,
+                &nonce,
+                b"test",
+                None,
+                &mut dst_ct[..3],
+                &mut dst_tag
+            )
+            .is_err());
+            // A longer destination is allowed.
+            seal_detached(
+                &secret_key,
+                &nonce,
+                b"test",
+                None,
+                &mut dst_ct,
+                &mut dst_tag,
+            )
+            .unwrap();
+
+            assert!(open_detached(
+                &secret_key,
+                &nonce,
+                &dst_ct[..4],
+                &dst_tag,
+                None,
+                &mut dst_pt[..3]
+            )
+            .is_err());
+            open_detached(
+                &secret_key,
+                &nonce,
+                &dst_ct[..4],
+                &dst_tag,
+                None,
+                &mut dst_pt,
+            )
+            .unwrap();
+            assert_eq!(&dst_pt[..4], b"test");
+        }
+
+        quickcheck! {
+            /// Sealing detached should produce the same ciphertext and tag as
+            /// seal(), and be opened by both open() and open_detached().
+            fn prop_detached_same_as_combined(input: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; 12]);
+
+                let mut combined = vec![0u8; input.len() + POLY1305_OUTSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut combined).unwrap();
+
+                let mut dst_ct = vec![0u8; input.len()];
+                let mut dst_tag = [0u8; POLY1305_OUTSIZE];
+                seal_detached(&secret_key, &nonce, &input, Some(&ad), &mut dst_ct, &mut dst_tag).unwrap();
+
+                let mut dst_pt = vec![0u8; input.len()];
+                open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_pt).unwrap();
+                let mut dst_pt_combined = vec![0u8; input.len()];
+                open(&secret_key, &nonce, &combined, Some(&ad), &mut dst_pt_combined).unwrap();
+
+                let same_output = combined[..input.len()] == dst_ct[..]
+                    && combined[input.len()..] == dst_tag[..]
+                    && dst_pt == input
+                    && dst_pt_combined == input;
+
+                // Modifying the tag should fail.
+                dst_tag[0] ^= 1;
+                let modified_err = open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_pt).is_err();
+
+                same_output && modified_err
+            }
+        }
+    }
+
+    mod test_verify_only {
+        use super::*;
+
+        #[test]
+        fn verify_only_err() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; 12]);
+            let mut sealed = [0u8; 4 + POLY1305_OUTSIZE];
+            seal(&secret_key, &nonce, b"test", None, &mut sealed).unwrap();
+
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, None).is_ok());
+            // Absent and empty additional data are the same.
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(&[])).is_ok());
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(b"ad")).is_err());
+            assert!(
+                open_verify_only(&secret_key, &nonce, &sealed[..POLY1305_OUTSIZE - 1], None)
+                    .is_err()
+            );
+            assert!(open_verify_only(&secret_key, &nonce, &sealed[1..], None).is_err());
+        }
+
+        quickcheck! {
+            /// open_verify_only() should accept exactly what open() accepts.
+            fn prop_verify_only_same_as_open(input: Vec<u8>, ad: Vec<u8>, flip: usize) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; 12]);
+                let mut sealed = vec![0u8; input.len() + POLY1305_OUTSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut sealed).unwrap();
+                let valid = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_ok();
+
+                let idx = flip % sealed.len();
+                sealed[idx] ^= 1;
+                let mut dst_pt = vec![0u8; input.len()];
+                let modified_err = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_err()
+                    && open(&secret_key, &nonce, &sealed, Some(&ad), &mut dst_pt).is_err();
+
+                valid && modified_err
+            }
+        }
+    }
 }
 
 // Testing any test vectors that aren't put into library's /tests folder.
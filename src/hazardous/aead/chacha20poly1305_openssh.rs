@@ -0,0 +1,390 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! This is the `chacha20-poly1305@openssh.com` packet cipher of SSH, as specified
+//! in [PROTOCOL.chacha20poly1305](https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL.chacha20poly1305?rev=HEAD).
+//!
+//! The 64-byte [`SecretKey`] is split into two ChaCha20 keys. The first 32
+//! bytes are the main key and the last 32 bytes are the header key. The packet
+//! sequence number, encoded as a 64-bit big-endian integer, is used as the
+//! nonce of the original ChaCha20 with a 64-bit nonce. Then:
+//! - The 4-byte packet length is encrypted with the header key.
+//! - The rest of the packet is encrypted with the main key, starting at block
+//!   counter 1.
+//! - The encrypted packet length and the rest of the encrypted packet are
+//!   authenticated with Poly1305, using a one-time key from block 0 of the
+//!   main key.
+//!
+//! A receiver needs the packet length to know how much to read, before the
+//! packet can be authenticated. [`decrypt_length()`] decrypts it on its own.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key, as derived by the SSH key exchange.
+//! - `sequence_number`: The sequence number of the packet.
+//! - `packet`: The packet to be encrypted, starting with its 4-byte length.
+//! - `packet_with_tag`: The encrypted packet with the corresponding 16 byte
+//!   Poly1305 tag appended to it.
+//! - `encrypted_length`: The first 4 bytes of an encrypted packet.
+//! - `dst_out`: Destination array that will hold the
+//!   `packet_with_tag`/`packet` after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `packet` is less than 4.
+//! - The length of `packet_with_tag` is less than 4 + [`POLY1305_OUTSIZE`].
+//! - The length of `dst_out` is less than `packet` + [`POLY1305_OUTSIZE`] when calling [`seal()`].
+//! - The length of `dst_out` is less than `packet_with_tag` - [`POLY1305_OUTSIZE`] when
+//!   calling [`open()`].
+//! - `packet.len()` + [`POLY1305_OUTSIZE`] overflows when calling [`seal()`].
+//! - The length of `encrypted_length` is not 4.
+//! - The received tag does not match the calculated tag when calling [`open()`].
+//! - More than `2^32-1 * 64` bytes of data are processed.
+//!
+//! # Security:
+//! - A sequence number must never be re-used with a given key. SSH requires
+//!   rekeying before the sequence number wraps around.
+//! - The length returned by [`decrypt_length()`] is not authenticated. It must only
+//!   be used to determine how much to read, and must be checked against the
+//!   maximum packet length before doing so.
+//! - If [`open()`] returns an error, `dst_out` is not modified.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::chacha20poly1305_openssh;
+//!
+//! let secret_key = chacha20poly1305_openssh::SecretKey::generate();
+//!
+//! // A packet with a length of 12, a padding length of 4, a 7-byte payload
+//! // and 4 bytes of padding.
+//! let mut packet = [0u8; 16];
+//! packet[..4].copy_from_slice(&12u32.to_be_bytes());
+//! packet[4] = 4;
+//! packet[5..12].copy_from_slice(b"payload");
+//!
+//! let mut sealed = [0u8; 16 + 16];
+//! chacha20poly1305_openssh::seal(&secret_key, 3, &packet, &mut sealed)?;
+//!
+//! // The receiver decrypts the length first, to know how much to read.
+//! let length = chacha20poly1305_openssh::decrypt_length(&secret_key, 3, &sealed[..4])?;
+//! assert_eq!(length, 12);
+//!
+//! let mut opened = [0u8; 16];
+//! chacha20poly1305_openssh::open(&secret_key, 3, &sealed, &mut opened)?;
+//! assert_eq!(opened, packet);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`POLY1305_OUTSIZE`]: ../../mac/poly1305/constant.POLY1305_OUTSIZE.html
+//! [`seal()`]: fn.seal.html
+//! [`open()`]: fn.open.html
+//! [`decrypt_length()`]: fn.decrypt_length.html
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::{chacha20poly1305::poly1305_key_gen, chacha20poly1305_original},
+        mac::poly1305::{Poly1305, POLY1305_OUTSIZE},
+        stream::chacha20::{self, ChaCha20, CHACHA_BLOCKSIZE, CHACHA_KEYSIZE},
+    },
+    util,
+};
+use zeroize::Zeroizing;
+
+/// The key size for `chacha20-poly1305@openssh.com`.
+pub const OPENSSH_KEYSIZE: usize = 2 * CHACHA_KEYSIZE;
+/// The size of the packet length field.
+pub const LENGTH_SIZE: usize = 4;
+
+/// The initial counter used for encryption and decryption of the packet length.
+const LENGTH_CTR: u32 = 0;
+
+/// The initial counter used for encryption and decryption of the rest of the packet.
+const ENC_CTR: u32 = 1;
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` that `chacha20-poly1305@openssh.com` uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, OPENSSH_KEYSIZE, OPENSSH_KEYSIZE, OPENSSH_KEYSIZE)
+}
+
+/// Initialize the ChaCha20 instances for the main key and the header key, in that order.
+fn init(secret_key: &SecretKey, sequence_number: u32) -> (ChaCha20, ChaCha20) {
+    let nonce = u64::from(sequence_number).to_be_bytes();
+    let (main_key, header_key) = secret_key.unprotected_as_bytes().split_at(CHACHA_KEYSIZE);
+
+    (
+        chacha20poly1305_original::init(main_key, &nonce),
+        chacha20poly1305_original::init(header_key, &nonce),
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the length of a packet, without authenticating it.
+pub fn decrypt_length(
+    secret_key: &SecretKey,
+    sequence_number: u32,
+    encrypted_length: &[u8],
+) -> Result<u32, UnknownCryptoError> {
+    if encrypted_length.len() != LENGTH_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (_, mut header_ctx) = init(secret_key, sequence_number);
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+    let mut length = [0u8; LENGTH_SIZE];
+    length.copy_from_slice(encrypted_length);
+    chacha20::xor_keystream(&mut header_ctx, LENGTH_CTR, tmp.as_mut(), &mut length)?;
+
+    Ok(u32::from_be_bytes(length))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encrypt and authenticate a packet using `chacha20-poly1305@openssh.com`.
+pub fn seal(
+    secret_key: &SecretKey,
+    sequence_number: u32,
+    packet: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if packet.len() < LENGTH_SIZE {
+        return Err(UnknownCryptoError);
+    }
+    match packet.len().checked_add(POLY1305_OUTSIZE) {
+        Some(out_min_len) => {
+            if dst_out.len() < out_min_len {
+                return Err(UnknownCryptoError);
+            }
+        }
+        None => return Err(UnknownCryptoError),
+    };
+
+    let (mut main_ctx, mut header_ctx) = init(secret_key, sequence_number);
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+
+    let (dst_packet, dst_tag) = dst_out.split_at_mut(packet.len());
+    dst_packet.copy_from_slice(packet);
+    let (dst_length, dst_rest) = dst_packet.split_at_mut(LENGTH_SIZE);
+    chacha20::xor_keystream(&mut header_ctx, LENGTH_CTR, tmp.as_mut(), dst_length)?;
+    if !dst_rest.is_empty() {
+        chacha20::xor_keystream(&mut main_ctx, ENC_CTR, tmp.as_mut(), dst_rest)?;
+    }
+
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut main_ctx, &mut tmp));
+    auth_ctx.update(dst_packet)?;
+    auth_ctx.finalize_into(&mut dst_tag[..POLY1305_OUTSIZE])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticate and decrypt a packet using `chacha20-poly1305@openssh.com`.
+pub fn open(
+    secret_key: &SecretKey,
+    sequence_number: u32,
+    packet_with_tag: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if packet_with_tag.len() < LENGTH_SIZE + POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+    let (packet, tag) = packet_with_tag.split_at(packet_with_tag.len() - POLY1305_OUTSIZE);
+    if dst_out.len() < packet.len() {
+        return Err(UnknownCryptoError);
+    }
+
+    let (mut main_ctx, mut header_ctx) = init(secret_key, sequence_number);
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut main_ctx, &mut tmp));
+    auth_ctx.update(packet)?;
+    util::secure_cmp(auth_ctx.finalize()?.unprotected_as_bytes(), tag)?;
+
+    let dst_packet = &mut dst_out[..packet.len()];
+    dst_packet.copy_from_slice(packet);
+    let (dst_length, dst_rest) = dst_packet.split_at_mut(LENGTH_SIZE);
+    chacha20::xor_keystream(&mut header_ctx, LENGTH_CTR, tmp.as_mut(), dst_length)?;
+    if !dst_rest.is_empty() {
+        chacha20::xor_keystream(&mut main_ctx, ENC_CTR, tmp.as_mut(), dst_rest)?;
+    }
+
+    Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_length_err() {
+        let secret_key = SecretKey::generate();
+        let mut dst = [0u8; 64];
+
+        assert!(seal(&secret_key, 0, &[0u8; 3], &mut dst).is_err());
+        assert!(seal(&secret_key, 0, &[0u8; 4], &mut dst[..19]).is_err());
+        seal(&secret_key, 0, &[0u8; 4], &mut dst[..20]).unwrap();
+
+        let mut dst_pt = [0u8; 4];
+        assert!(open(&secret_key, 0, &dst[..19], &mut dst_pt).is_err());
+        assert!(open(&secret_key, 0, &dst[..20], &mut dst_pt[..3]).is_err());
+        open(&secret_key, 0, &dst[..20], &mut dst_pt).unwrap();
+
+        assert!(decrypt_length(&secret_key, 0, &dst[..3]).is_err());
+        assert!(decrypt_length(&secret_key, 0, &dst[..5]).is_err());
+        assert_eq!(decrypt_length(&secret_key, 0, &dst[..4]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_open_modified_err() {
+        let secret_key = SecretKey::generate();
+        let packet = [7u8; 36];
+        let mut sealed = [0u8; 36 + POLY1305_OUTSIZE];
+        seal(&secret_key, 5, &packet, &mut sealed).unwrap();
+
+        let mut dst_pt = [0u8; 36];
+        for idx in 0..sealed.len() {
+            let mut modified = sealed;
+            modified[idx] ^= 1;
+            assert!(open(&secret_key, 5, &modified, &mut dst_pt).is_err());
+            assert_eq!(dst_pt, [0u8; 36]);
+        }
+
+        // Different sequence number and key.
+        assert!(open(&secret_key, 6, &sealed, &mut dst_pt).is_err());
+        assert!(open(&SecretKey::generate(), 5, &sealed, &mut dst_pt).is_err());
+        open(&secret_key, 5, &sealed, &mut dst_pt).unwrap();
+        assert_eq!(dst_pt, packet);
+    }
+
+    quickcheck! {
+        fn prop_seal_open(input: Vec<u8>, sequence_number: u32) -> bool {
+            let secret_key = SecretKey::generate();
+            let mut packet = (input.len() as u32).to_be_bytes().to_vec();
+            packet.extend_from_slice(&input);
+
+            let mut sealed = vec![0u8; packet.len() + POLY1305_OUTSIZE];
+            seal(&secret_key, sequence_number, &packet, &mut sealed).unwrap();
+            let length = decrypt_length(&secret_key, sequence_number, &sealed[..LENGTH_SIZE]).unwrap();
+
+            let mut opened = vec![0u8; packet.len()];
+            open(&secret_key, sequence_number, &sealed, &mut opened).unwrap();
+
+            length == input.len() as u32 && opened == packet
+        }
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod test_vectors {
+    use super::*;
+
+    const KEY: &str = "7cb8f3047267e0a0a43819df9a17c73a7ad494ff981988228cae5c6e118c54f8\
+                       b19f279dfa16a920bbdc2f42930d9f8645de11631ce4a940aa9ce597e55d68f2";
+
+    fn seal_open_test_runner(sequence_number: u32, packet: &[u8], sealed: &str, tag: &str) {
+        let secret_key = SecretKey::from_slice(&hex::decode(KEY).unwrap()).unwrap();
+        let mut expected = hex::decode(sealed).unwrap();
+        expected.extend_from_slice(&hex::decode(tag).unwrap());
+
+        let mut dst_out = vec![0u8; packet.len() + POLY1305_OUTSIZE];
+        seal(&secret_key, sequence_number, packet, &mut dst_out).unwrap();
+        assert_eq!(dst_out, expected);
+
+        let length = decrypt_length(&secret_key, sequence_number, &expected[..LENGTH_SIZE]);
+        assert_eq!(
+            length.unwrap().to_be_bytes().as_ref(),
+            &packet[..LENGTH_SIZE]
+        );
+
+        let mut dst_pt = vec![0u8; packet.len()];
+        open(&secret_key, sequence_number, &expected, &mut dst_pt).unwrap();
+        assert_eq!(dst_pt, packet);
+    }
+
+    // Generated with an independent implementation of PROTOCOL.chacha20poly1305,
+    // using the ChaCha20 and Poly1305 of pyca/cryptography.
+    const SERVICE_REQUEST: &str =
+        "0000001c0a050000000c7373682d757365726175746800000000000000000000";
+
+    #[test]
+    fn test_service_request_sequence_number_0() {
+        seal_open_test_runner(
+            0,
+            &hex::decode(SERVICE_REQUEST).unwrap(),
+            "b6b908895264357fabcbe901a72397357279790e4c824f053b48fbfb7553fb0d",
+            "8c013c89a8681467b6e6f943b555ebbd",
+        );
+    }
+
+    #[test]
+    fn test_service_request_sequence_number_3() {
+        seal_open_test_runner(
+            3,
+            &hex::decode(SERVICE_REQUEST).unwrap(),
+            "077822ab1b972cf9a63769ba579cc1ee830c08263c154f2cfbb4b6a6b558ab42",
+            "ed69e3aff93494c7d1641922e6752ae6",
+        );
+    }
+
+    #[test]
+    fn test_service_request_sequence_number_max() {
+        seal_open_test_runner(
+            u32::MAX,
+            &hex::decode(SERVICE_REQUEST).unwrap(),
+            "e1dc5e15324081ca5094f4869c18a6db3a9e28ffd522fa88ff3ba7598dce9c77",
+            "f65db2b90e8260214b157b30359590fb",
+        );
+    }
+
+    #[test]
+    fn test_length_only() {
+        seal_open_test_runner(1, &[0u8; 4], "f49ca715", "044820382f27a875b474098c2b27fcc3");
+    }
+
+    #[test]
+    fn test_multiple_blocks() {
+        let packet: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        seal_open_test_runner(
+            7,
+            &packet,
+            "3fd457235f62de83b82655c01660910d4e5cd246610405d8cacf208f4f803eaa\
+             ea9c4e1a6eef35cb0eb2ccc1cfa896c3cff695e12a848e3882235c5e16289422\
+             132c9013269d4a3c0e724f4d019e3ed30d9ee98b291f6f52f9eb4a92b2dee651\
+             a9c54f49d7e8a9a34ca06835da633e04dac7339c970e147cacd4c18076cb7bb2\
+             6ff0ee298997e155a5e6b0066ec2d08b3bfe474c902631fa81577f786d1b08ea\
+             fc94817e5630995b291bbea1507e9622f217af9235cc0761ba127dffb3a93c52\
+             467c0835202327094fba08d96b8b574eb080c0bb7ca7d9b27ea1be92f4bf1d97\
+             011e939799139be5caedcaa826707e96d1f6e5f4ac754fc406e8533810a9cc34\
+             0c5e3a13b2536cbe9bd467edab5609c5384c50177bd76fe515dfaf9cdfd9c098\
+             990dfe04333cbb57edbb9ffb",
+            "42d572a55beea87b135905f9cc7c3065",
+        );
+    }
+}
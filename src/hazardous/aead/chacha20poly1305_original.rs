@@ -0,0 +1,503 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! This is the original ChaCha20Poly1305 construction, as specified in
+//! [draft-agl-tls-chacha20poly1305](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04).
+//! It is compatible with `crypto_aead_chacha20poly1305` of libsodium, and is
+//! used by protocols that predate [RFC 8439](https://tools.ietf.org/html/rfc8439).
+//!
+//! It differs from the IETF construction in [`chacha20poly1305`] by:
+//! - Using ChaCha20 with a 64-bit nonce and a 64-bit block counter, instead of
+//!   a 96-bit nonce and a 32-bit block counter.
+//! - Authenticating `ad`, its length, the ciphertext and its length, without
+//!   padding `ad` and the ciphertext to the Poly1305 block size.
+//!
+//! Only use this for interoperability with protocols that require it. Otherwise,
+//! use [`chacha20poly1305`] or [`XChaCha20Poly1305`].
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `nonce`: The nonce value.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be `None`).
+//! - `ciphertext_with_tag`: The encrypted data with the corresponding 16 byte
+//!   Poly1305 tag appended to it.
+//! - `plaintext`: The data to be encrypted.
+//! - `dst_out`: Destination array that will hold the
+//!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//! - `ciphertext`, `tag`: The encrypted data and its 16 byte Poly1305 tag,
+//!   when passed separately to [`open_detached()`].
+//! - `dst_ciphertext`, `dst_tag`: Destination arrays that will hold the
+//!   ciphertext and the tag after encryption with [`seal_detached()`].
+//!
+//! [`open_verify_only()`] authenticates `ciphertext_with_tag` and `ad` without
+//! producing any plaintext.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than `plaintext` + [`POLY1305_OUTSIZE`] when calling [`seal()`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - [`POLY1305_OUTSIZE`] when
+//!   calling [`open()`].
+//! - The length of `ciphertext_with_tag` is not at least [`POLY1305_OUTSIZE`].
+//! - The received tag does not match the calculated tag when calling [`open()`]
+//!   or [`open_verify_only()`].
+//! - `plaintext.len()` + [`POLY1305_OUTSIZE`] overflows when calling [`seal()`].
+//! - The length of `dst_ciphertext` is less than `plaintext` when calling
+//!   [`seal_detached()`].
+//! - The length of `dst_out` is less than `ciphertext` when calling
+//!   [`open_detached()`].
+//! - The length of `dst_tag` or `tag` is not [`POLY1305_OUTSIZE`].
+//! - Converting `usize` to `u64` would be a lossy conversion.
+//! - More than `2^32-1 * 64` bytes of data are processed. The 64-bit block
+//!   counter of the original construction would allow more, but this is not
+//!   supported.
+//!
+//! # Security:
+//! - It is critical for security that a given nonce is not re-used with a given
+//!   key. Should this happen, the security of all data that has been encrypted
+//!   with that given key is compromised.
+//! - The nonce is too small to be randomly generated using a CSPRNG. It should be
+//!   a counter, or otherwise be guaranteed to be unique for a given key.
+//! - To securely generate a strong key, use [`SecretKey::generate()`].
+//! - The length of the `plaintext` is not hidden, only its contents.
+//!
+//! # Recommendation:
+//! - It is recommended to use [`XChaCha20Poly1305`] when possible.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::chacha20poly1305_original;
+//!
+//! let secret_key = chacha20poly1305_original::SecretKey::generate();
+//!
+//! // WARNING: This nonce is only meant for demonstration and should not
+//! // be repeated. Please read the security section.
+//! let nonce = chacha20poly1305_original::Nonce::from([0u8; 8]);
+//! let ad = "Additional data".as_bytes();
+//! let message = "Data to protect".as_bytes();
+//!
+//! // Length of the above message is 15 and then we accommodate 16 for the Poly1305
+//! // tag.
+//!
+//! let mut dst_out_ct = [0u8; 15 + 16];
+//! let mut dst_out_pt = [0u8; 15];
+//! // Encrypt and place ciphertext + tag in dst_out_ct
+//! chacha20poly1305_original::seal(&secret_key, &nonce, message, Some(&ad), &mut dst_out_ct)?;
+//! // Verify tag, if correct then decrypt and place message in dst_out_pt
+//! chacha20poly1305_original::open(&secret_key, &nonce, &dst_out_ct, Some(&ad), &mut dst_out_pt)?;
+//!
+//! assert_eq!(dst_out_pt.as_ref(), message.as_ref());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`chacha20poly1305`]: ../chacha20poly1305/index.html
+//! [`XChaCha20Poly1305`]: ../xchacha20poly1305/index.html
+//! [`SecretKey::generate()`]: ../../stream/chacha20/struct.SecretKey.html
+//! [`POLY1305_OUTSIZE`]: ../../mac/poly1305/constant.POLY1305_OUTSIZE.html
+//! [`seal()`]: fn.seal.html
+//! [`open()`]: fn.open.html
+//! [`seal_detached()`]: fn.seal_detached.html
+//! [`open_detached()`]: fn.open_detached.html
+//! [`open_verify_only()`]: fn.open_verify_only.html
+pub use crate::hazardous::stream::chacha20::SecretKey;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::chacha20poly1305::poly1305_key_gen,
+        mac::poly1305::{Poly1305, POLY1305_OUTSIZE},
+        stream::chacha20::{self, ChaCha20, CHACHA_BLOCKSIZE, IETF_CHACHA_NONCESIZE},
+    },
+    util,
+};
+use core::convert::TryInto;
+use zeroize::Zeroizing;
+
+/// The nonce size for the original ChaCha20.
+pub const ORIGINAL_CHACHA_NONCESIZE: usize = 8;
+
+/// The initial counter used for encryption and decryption.
+const ENC_CTR: u32 = 1;
+
+construct_public! {
+    /// A type that represents a `Nonce` that the original ChaCha20Poly1305 uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 8 bytes.
+    (Nonce, test_nonce, ORIGINAL_CHACHA_NONCESIZE, ORIGINAL_CHACHA_NONCESIZE)
+}
+
+impl_from_trait!(Nonce, ORIGINAL_CHACHA_NONCESIZE);
+
+/// Initialize the original ChaCha20 with a 64-bit `nonce`.
+///
+/// The last row of the original ChaCha20 state is the 64-bit block counter
+/// followed by the 64-bit nonce. This is the IETF state with the upper half of
+/// the counter in place of the first word of the nonce. Since the lower half is
+/// limited to 32 bits, the upper half is always zero.
+pub(crate) fn init(secret_key: &[u8], nonce: &[u8]) -> ChaCha20 {
+    debug_assert_eq!(nonce.len(), ORIGINAL_CHACHA_NONCESIZE);
+    let mut ietf_nonce = [0u8; IETF_CHACHA_NONCESIZE];
+    ietf_nonce[4..].copy_from_slice(nonce);
+
+    ChaCha20::new(secret_key, &ietf_nonce, true).unwrap()
+}
+
+/// Authenticates the ad, the ciphertext and their lengths.
+fn process_authentication(
+    auth_ctx: &mut Poly1305,
+    ad: &[u8],
+    ciphertext: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let (ad_len, ct_len): (u64, u64) = match (ad.len().try_into(), ciphertext.len().try_into()) {
+        (Ok(alen), Ok(clen)) => (alen, clen),
+        _ => return Err(UnknownCryptoError),
+    };
+
+    auth_ctx.update(ad)?;
+    auth_ctx.update(&ad_len.to_le_bytes())?;
+    auth_ctx.update(ciphertext)?;
+    auth_ctx.update(&ct_len.to_le_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 encryption and authentication, using the original construction.
+pub fn seal(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    match plaintext.len().checked_add(POLY1305_OUTSIZE) {
+        Some(out_min_len) => {
+            if dst_out.len() < out_min_len {
+                return Err(UnknownCryptoError);
+            }
+        }
+        None => return Err(UnknownCryptoError),
+    };
+
+    let (dst_ciphertext, dst_tag) = dst_out.split_at_mut(plaintext.len());
+    seal_detached(
+        secret_key,
+        nonce,
+        plaintext,
+        ad,
+        dst_ciphertext,
+        &mut dst_tag[..POLY1305_OUTSIZE],
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 decryption and authentication, using the original construction.
+pub fn open(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - POLY1305_OUTSIZE);
+    open_detached(secret_key, nonce, ciphertext, tag, ad, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 encryption and authentication, using the original construction,
+/// writing the ciphertext and tag to separate buffers.
+pub fn seal_detached(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    ad: Option<&[u8]>,
+    dst_ciphertext: &mut [u8],
+    dst_tag: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_ciphertext.len() < plaintext.len() {
+        return Err(UnknownCryptoError);
+    }
+    if dst_tag.len() != POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut enc_ctx = init(secret_key.unprotected_as_bytes(), nonce.as_ref());
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+
+    let pt_len = plaintext.len();
+    if pt_len != 0 {
+        dst_ciphertext[..pt_len].copy_from_slice(plaintext);
+        chacha20::xor_keystream(
+            &mut enc_ctx,
+            ENC_CTR,
+            tmp.as_mut(),
+            &mut dst_ciphertext[..pt_len],
+        )?;
+    }
+
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut enc_ctx, &mut tmp));
+    let ad = ad.unwrap_or(&[0u8; 0]);
+    process_authentication(&mut auth_ctx, ad, &dst_ciphertext[..pt_len])?;
+
+    auth_ctx.finalize_into(dst_tag)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// AEAD ChaCha20Poly1305 decryption and authentication, using the original construction,
+/// reading the ciphertext and tag from separate buffers.
+pub fn open_detached(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext: &[u8],
+    tag: &[u8],
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if tag.len() != POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+    if dst_out.len() < ciphertext.len() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut dec_ctx = init(secret_key.unprotected_as_bytes(), nonce.as_ref());
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut dec_ctx, &mut tmp));
+
+    let ciphertext_len = ciphertext.len();
+    let ad = ad.unwrap_or(&[0u8; 0]);
+    process_authentication(&mut auth_ctx, ad, ciphertext)?;
+    util::secure_cmp(auth_ctx.finalize()?.unprotected_as_bytes(), tag)?;
+
+    if ciphertext_len != 0 {
+        dst_out[..ciphertext_len].copy_from_slice(ciphertext);
+        chacha20::xor_keystream(
+            &mut dec_ctx,
+            ENC_CTR,
+            tmp.as_mut(),
+            &mut dst_out[..ciphertext_len],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the Poly1305 tag of `ciphertext_with_tag` over `ad` and the
+/// ciphertext, without decrypting it.
+pub fn open_verify_only(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    ciphertext_with_tag: &[u8],
+    ad: Option<&[u8]>,
+) -> Result<(), UnknownCryptoError> {
+    if ciphertext_with_tag.len() < POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) =
+        ciphertext_with_tag.split_at(ciphertext_with_tag.len() - POLY1305_OUTSIZE);
+    let mut dec_ctx = init(secret_key.unprotected_as_bytes(), nonce.as_ref());
+    let mut tmp = Zeroizing::new([0u8; CHACHA_BLOCKSIZE]);
+    let mut auth_ctx = Poly1305::new(&poly1305_key_gen(&mut dec_ctx, &mut tmp));
+
+    process_authentication(&mut auth_ctx, ad.unwrap_or(&[0u8; 0]), ciphertext)?;
+    util::secure_cmp(auth_ctx.finalize()?.unprotected_as_bytes(), tag)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+
+    // Proptests. Only executed when NOT testing no_std.
+    #[cfg(feature = "safe_api")]
+    mod proptest {
+        use super::*;
+        use crate::test_framework::aead_interface::*;
+        use crate::test_framework::streamcipher_interface::TestingRandom;
+
+        impl TestingRandom for Nonce {
+            fn gen() -> Self {
+                let mut n = [0u8; ORIGINAL_CHACHA_NONCESIZE];
+                util::secure_rand_bytes(&mut n).unwrap();
+                Self::from_slice(&n).unwrap()
+            }
+        }
+
+        quickcheck! {
+            fn prop_aead_interface(input: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; ORIGINAL_CHACHA_NONCESIZE]);
+                AeadTestRunner(seal, open, secret_key, nonce, &input, None, POLY1305_OUTSIZE, &ad);
+                test_diff_params_err(&seal, &open, &input, POLY1305_OUTSIZE);
+                true
+            }
+        }
+
+        quickcheck! {
+            /// Sealing detached should produce the same ciphertext and tag as
+            /// seal(), and be opened by open_detached().
+            fn prop_detached_same_as_combined(input: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; ORIGINAL_CHACHA_NONCESIZE]);
+
+                let mut combined = vec![0u8; input.len() + POLY1305_OUTSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut combined).unwrap();
+
+                let mut dst_ct = vec![0u8; input.len()];
+                let mut dst_tag = [0u8; POLY1305_OUTSIZE];
+                seal_detached(&secret_key, &nonce, &input, Some(&ad), &mut dst_ct, &mut dst_tag).unwrap();
+
+                let mut dst_pt = vec![0u8; input.len()];
+                open_detached(&secret_key, &nonce, &dst_ct, &dst_tag, Some(&ad), &mut dst_pt).unwrap();
+
+                combined[..input.len()] == dst_ct[..]
+                    && combined[input.len()..] == dst_tag[..]
+                    && dst_pt == input
+            }
+        }
+    }
+
+    mod test_verify_only {
+        use super::*;
+
+        #[test]
+        fn verify_only_err() {
+            let secret_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+            let nonce = Nonce::from([0u8; ORIGINAL_CHACHA_NONCESIZE]);
+            let mut sealed = [0u8; 4 + POLY1305_OUTSIZE];
+            seal(&secret_key, &nonce, b"test", None, &mut sealed).unwrap();
+
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, None).is_ok());
+            // Absent and empty additional data are the same.
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(&[])).is_ok());
+            assert!(open_verify_only(&secret_key, &nonce, &sealed, Some(b"ad")).is_err());
+            assert!(
+                open_verify_only(&secret_key, &nonce, &sealed[..POLY1305_OUTSIZE - 1], None)
+                    .is_err()
+            );
+            assert!(open_verify_only(&secret_key, &nonce, &sealed[1..], None).is_err());
+        }
+
+        quickcheck! {
+            /// open_verify_only() should accept exactly what open() accepts.
+            fn prop_verify_only_same_as_open(input: Vec<u8>, ad: Vec<u8>, flip: usize) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::from([0u8; ORIGINAL_CHACHA_NONCESIZE]);
+                let mut sealed = vec![0u8; input.len() + POLY1305_OUTSIZE];
+                seal(&secret_key, &nonce, &input, Some(&ad), &mut sealed).unwrap();
+                let valid = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_ok();
+
+                let idx = flip % sealed.len();
+                sealed[idx] ^= 1;
+                let mut dst_pt = vec![0u8; input.len()];
+                let modified_err = open_verify_only(&secret_key, &nonce, &sealed, Some(&ad)).is_err()
+                    && open(&secret_key, &nonce, &sealed, Some(&ad), &mut dst_pt).is_err();
+
+                valid && modified_err
+            }
+        }
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod test_vectors {
+    use super::*;
+
+    fn seal_open_test_runner(
+        key: &str,
+        nonce: &str,
+        plaintext: &[u8],
+        ad: &[u8],
+        ciphertext: &str,
+        tag: &str,
+    ) {
+        let secret_key = SecretKey::from_slice(&hex::decode(key).unwrap()).unwrap();
+        let nonce = Nonce::from_slice(&hex::decode(nonce).unwrap()).unwrap();
+        let mut expected = hex::decode(ciphertext).unwrap();
+        expected.extend_from_slice(&hex::decode(tag).unwrap());
+
+        let mut dst_ct = vec![0u8; plaintext.len() + POLY1305_OUTSIZE];
+        seal(&secret_key, &nonce, plaintext, Some(ad), &mut dst_ct).unwrap();
+        assert_eq!(dst_ct, expected);
+
+        let mut dst_pt = vec![0u8; plaintext.len()];
+        open(&secret_key, &nonce, &dst_ct, Some(ad), &mut dst_pt).unwrap();
+        assert_eq!(dst_pt, plaintext);
+
+        let last = dst_ct.len() - 1;
+        dst_ct[last] ^= 1;
+        assert!(open(&secret_key, &nonce, &dst_ct, Some(ad), &mut dst_pt).is_err());
+    }
+
+    #[test]
+    fn draft_agl_tls_chacha20poly1305_04() {
+        // Section 7 of draft-agl-tls-chacha20poly1305-04. This is also the test
+        // vector used by libsodium.
+        seal_open_test_runner(
+            "4290bcb154173531f314af57f3be3b5006da371ece272afa1b5dbdd1100a1007",
+            "cd7cf67be39c794a",
+            &hex::decode("86d09974840bded2a5ca").unwrap(),
+            &hex::decode("87e229d4500845a079c0").unwrap(),
+            "e3e446f7ede9a19b62a4",
+            "677dabf4e3d24b876bb284753896e1d6",
+        );
+    }
+
+    #[test]
+    fn empty_plaintext() {
+        seal_open_test_runner(
+            "b6fea778a118e1a742cca8290a70df5c7fc16ed13198c151af6ad1fa30c60ffc",
+            "0001020304050607",
+            b"",
+            b"Additional data",
+            "",
+            "810ad4ad7c67fc40762efffe0aa65741",
+        );
+    }
+
+    #[test]
+    fn multiple_blocks_no_ad() {
+        let plaintext: Vec<u8> = (0..200).map(|i| (i % 251) as u8).collect();
+        seal_open_test_runner(
+            "b6fea778a118e1a742cca8290a70df5c7fc16ed13198c151af6ad1fa30c60ffc",
+            "0001020304050607",
+            &plaintext,
+            b"",
+            "c2ffd0529f4f581e01f286447320d48dec60bcb0067e3e3660575cd4c1a9664a\
+             3483e0594cf3ee50b4dc706f9e6b45bd19644c2350338a1f525dbf36e6e153f6\
+             11aa69cca3cc8108e3bd0901f0202f2ee10d0f94a40d85fb4eda19461869df87\
+             9a3d507e7ba48c7ef268264e3a394f337e313b9eb4b0b5545a7d5c00aeef6d88\
+             20e7f1330e6ae308090aeea58a70466cc8abd8861a8077a9ae5cf019d64f6a42\
+             02ed3c50188ca0c35d3330744f727c6624e35d8553339fef670f155b10790190\
+             af743c9a4d134f79",
+            "a129d4faf18f10e392f391254d27d11e",
+        );
+    }
+}
@@ -0,0 +1,146 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Building blocks for the generic AEAD compositions in orion, [`siv`] and
+//! [`etm`]. A composition is generic over a [`Mac`] and a [`StreamCipher`],
+//! both of which are sealed and cannot be implemented outside of orion.
+//!
+//! orion provides:
+//! - [`HmacSha512`]: HMAC-SHA512 truncated to 32 bytes, with a 32-byte key.
+//! - [`XChaCha20`]: XChaCha20 with a 32-byte key and a 24-byte IV.
+//!
+//! [`siv`]: ../siv/index.html
+//! [`etm`]: ../etm/index.html
+//! [`Mac`]: trait.Mac.html
+//! [`StreamCipher`]: trait.StreamCipher.html
+//! [`HmacSha512`]: struct.HmacSha512.html
+//! [`XChaCha20`]: struct.XChaCha20.html
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha512::{Sha512, SHA512_OUTSIZE};
+use crate::hazardous::mac::hmac::HmacGeneric;
+use crate::hazardous::stream::{chacha20, xchacha20};
+use zeroize::Zeroize;
+
+/// The largest tag size (bytes) of any [`Mac`].
+///
+/// [`Mac`]: trait.Mac.html
+pub(crate) const MAX_TAGSIZE: usize = 64;
+
+mod private {
+    /// Prevents implementations of [`Mac`](../trait.Mac.html) and
+    /// [`StreamCipher`](../trait.StreamCipher.html) outside of orion.
+    pub trait Sealed {}
+
+    impl Sealed for super::HmacSha512 {}
+    impl Sealed for super::XChaCha20 {}
+}
+
+/// A MAC that a composition computes the tag with.
+///
+/// This trait is sealed and cannot be implemented outside of orion.
+pub trait Mac: private::Sealed + Sized {
+    /// The key size (bytes) of the MAC.
+    const KEYSIZE: usize;
+    /// The tag size (bytes) of the MAC.
+    const TAGSIZE: usize;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize the MAC with `key`, which must be `KEYSIZE` bytes.
+    fn new(key: &[u8]) -> Result<Self, UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write the tag into `dest`, which must be `TAGSIZE` bytes.
+    fn finalize_into(self, dest: &mut [u8]) -> Result<(), UnknownCryptoError>;
+}
+
+/// A stream cipher that a composition encrypts with.
+///
+/// This trait is sealed and cannot be implemented outside of orion.
+pub trait StreamCipher: private::Sealed {
+    /// The key size (bytes) of the stream cipher.
+    const KEYSIZE: usize;
+    /// The IV size (bytes) of the stream cipher.
+    const IVSIZE: usize;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// XOR the keystream for `key` and `iv` into `data`.
+    fn xor_keystream(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), UnknownCryptoError>;
+}
+
+/// HMAC-SHA512 truncated to 32 bytes, with a 32-byte key.
+pub struct HmacSha512 {
+    inner: HmacGeneric<Sha512>,
+}
+
+impl Mac for HmacSha512 {
+    const KEYSIZE: usize = 32;
+    const TAGSIZE: usize = 32;
+
+    fn new(key: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if key.len() != Self::KEYSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            inner: HmacGeneric::new(key)?,
+        })
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    fn finalize_into(mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != Self::TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut tag = [0u8; SHA512_OUTSIZE];
+        self.inner.finalize_into(&mut tag)?;
+        dest.copy_from_slice(&tag[..Self::TAGSIZE]);
+        tag.zeroize();
+
+        Ok(())
+    }
+}
+
+/// XChaCha20 with a 32-byte key and a 24-byte IV, starting at block 0.
+pub struct XChaCha20;
+
+impl StreamCipher for XChaCha20 {
+    const KEYSIZE: usize = chacha20::CHACHA_KEYSIZE;
+    const IVSIZE: usize = xchacha20::XCHACHA_NONCESIZE;
+
+    fn xor_keystream(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let secret_key = chacha20::SecretKey::from_slice(key)?;
+        let nonce = xchacha20::Nonce::from_slice(iv)?;
+        let (subkey, ietf_nonce) = xchacha20::subkey_and_nonce(&secret_key, &nonce);
+
+        chacha20::encrypt_in_place(&subkey, &ietf_nonce, 0, data)
+    }
+}
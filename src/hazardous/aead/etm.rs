@@ -0,0 +1,412 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Encrypt-then-MAC composes a stream cipher and a MAC into an AEAD. The
+//! plaintext is encrypted with the stream cipher, and the tag is the MAC of the
+//! additional data, the nonce and the ciphertext.
+//!
+//! [`EncryptThenMac`] implements the composition generically over a [`Mac`]
+//! and a [`StreamCipher`]. The MAC is computed over:
+//!
+//! `LE64(ad.len()) || ad || nonce || ciphertext || LE64(ciphertext.len())`
+//!
+//! The secret key is split into a MAC key, followed by a cipher key, so the
+//! two are never used with both primitives. The tag is appended to the
+//! ciphertext.
+//!
+//! orion provides [`XChaCha20HmacSha512`], which uses XChaCha20 and
+//! HMAC-SHA512 truncated to 32 bytes, with a 64-byte secret key. Protocols that
+//! specify e.g. AES-CTR with HMAC-SHA256 are not supported, since orion
+//! implements neither.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `nonce`: The nonce value, of `StreamCipher::IVSIZE` bytes.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be `None`).
+//! - `ciphertext_with_tag`: The encrypted data with the tag appended to it.
+//! - `plaintext`: The data to be encrypted.
+//! - `dst_out`: Destination array that will hold the
+//!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `secret_key` is not `Mac::KEYSIZE` + `StreamCipher::KEYSIZE`.
+//! - The length of `nonce` is not `StreamCipher::IVSIZE`.
+//! - The length of `dst_out` is less than `plaintext` + `Mac::TAGSIZE` when calling [`EncryptThenMac::seal()`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - `Mac::TAGSIZE` when
+//!   calling [`EncryptThenMac::open()`].
+//! - The length of the `ciphertext_with_tag` is not at least `Mac::TAGSIZE`.
+//! - The received tag does not match the calculated tag when calling [`EncryptThenMac::open()`].
+//! - `plaintext.len()` + `Mac::TAGSIZE` overflows when calling [`EncryptThenMac::seal()`].
+//!
+//! # Security:
+//! - It is critical for security that a given nonce is not re-used with a given
+//!   key. Should this happen, the security of all data that has been encrypted
+//!   with that given key is compromised.
+//! - The nonce of [`XChaCha20HmacSha512`] is big enough to be randomly generated
+//!   using a CSPRNG.
+//! - To securely generate a strong key, use [`SecretKey::generate()`].
+//! - The length of the `plaintext` is not hidden, only its contents.
+//! - The tag is verified before anything is decrypted.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::etm::{SecretKey, XChaCha20HmacSha512};
+//! use orion::hazardous::stream::xchacha20::Nonce;
+//!
+//! let secret_key = SecretKey::generate();
+//! let nonce = Nonce::generate();
+//! let message = "Data to protect".as_bytes();
+//!
+//! // Length of the above message is 15 and then we accommodate 32 for the tag.
+//! let mut dst_out_ct = [0u8; 15 + 32];
+//! let mut dst_out_pt = [0u8; 15];
+//! XChaCha20HmacSha512::seal(&secret_key, nonce.as_ref(), message, None, &mut dst_out_ct)?;
+//! XChaCha20HmacSha512::open(&secret_key, nonce.as_ref(), &dst_out_ct, None, &mut dst_out_pt)?;
+//!
+//! assert_eq!(dst_out_pt.as_ref(), message);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`EncryptThenMac`]: struct.EncryptThenMac.html
+//! [`Mac`]: ../composition/trait.Mac.html
+//! [`StreamCipher`]: ../composition/trait.StreamCipher.html
+//! [`XChaCha20HmacSha512`]: type.XChaCha20HmacSha512.html
+//! [`EncryptThenMac::seal()`]: struct.EncryptThenMac.html#method.seal
+//! [`EncryptThenMac::open()`]: struct.EncryptThenMac.html#method.open
+//! [`SecretKey::generate()`]: struct.SecretKey.html#method.generate
+
+pub use super::composition::{HmacSha512, XChaCha20};
+use super::composition::{Mac, StreamCipher, MAX_TAGSIZE};
+use crate::errors::UnknownCryptoError;
+use crate::util;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use zeroize::Zeroize;
+
+construct_secret_key! {
+    /// A type to represent the secret key that Encrypt-then-MAC uses. It holds
+    /// the MAC key followed by the cipher key.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is less than 32 or greater than 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, 32, 64, 64)
+}
+
+/// Encrypt-then-MAC over the stream cipher `C` and the MAC `M`.
+pub struct EncryptThenMac<M: Mac, C: StreamCipher> {
+    _mac: PhantomData<M>,
+    _cipher: PhantomData<C>,
+}
+
+/// Encrypt-then-MAC with XChaCha20, and HMAC-SHA512 truncated to 32 bytes.
+pub type XChaCha20HmacSha512 = EncryptThenMac<HmacSha512, XChaCha20>;
+
+impl<M: Mac, C: StreamCipher> EncryptThenMac<M, C> {
+    /// Split `secret_key` into the MAC key and the cipher key, and check the
+    /// length of `nonce`.
+    fn split_key<'a>(
+        secret_key: &'a SecretKey,
+        nonce: &[u8],
+    ) -> Result<(&'a [u8], &'a [u8]), UnknownCryptoError> {
+        if M::TAGSIZE > MAX_TAGSIZE || nonce.len() != C::IVSIZE {
+            return Err(UnknownCryptoError);
+        }
+        if secret_key.len() != M::KEYSIZE + C::KEYSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(secret_key.unprotected_as_bytes().split_at(M::KEYSIZE))
+    }
+
+    /// Compute the tag of `ad`, `nonce` and `ciphertext` into `dest`.
+    fn tag(
+        mac_key: &[u8],
+        ad: &[u8],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        dest: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let ad_len = u64::try_from(ad.len()).map_err(|_| UnknownCryptoError)?;
+        let ct_len = u64::try_from(ciphertext.len()).map_err(|_| UnknownCryptoError)?;
+
+        let mut mac = M::new(mac_key)?;
+        mac.update(&ad_len.to_le_bytes())?;
+        mac.update(ad)?;
+        mac.update(nonce)?;
+        mac.update(ciphertext)?;
+        mac.update(&ct_len.to_le_bytes())?;
+        mac.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt-then-MAC encryption.
+    pub fn seal(
+        secret_key: &SecretKey,
+        nonce: &[u8],
+        plaintext: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (mac_key, cipher_key) = Self::split_key(secret_key, nonce)?;
+        let out_len = match plaintext.len().checked_add(M::TAGSIZE) {
+            Some(out_min_len) => {
+                if dst_out.len() < out_min_len {
+                    return Err(UnknownCryptoError);
+                }
+                out_min_len
+            }
+            None => return Err(UnknownCryptoError),
+        };
+
+        let (ciphertext, tag) = dst_out[..out_len].split_at_mut(plaintext.len());
+        if !plaintext.is_empty() {
+            ciphertext.copy_from_slice(plaintext);
+            C::xor_keystream(cipher_key, nonce, ciphertext)?;
+        }
+
+        Self::tag(mac_key, ad.unwrap_or(&[0u8; 0]), nonce, ciphertext, tag)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt-then-MAC decryption.
+    pub fn open(
+        secret_key: &SecretKey,
+        nonce: &[u8],
+        ciphertext_with_tag: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (mac_key, cipher_key) = Self::split_key(secret_key, nonce)?;
+        if ciphertext_with_tag.len() < M::TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let msglen = ciphertext_with_tag.len() - M::TAGSIZE;
+        if dst_out.len() < msglen {
+            return Err(UnknownCryptoError);
+        }
+
+        let (ciphertext, tag) = ciphertext_with_tag.split_at(msglen);
+        let mut expected_tag = [0u8; MAX_TAGSIZE];
+        let result = Self::tag(
+            mac_key,
+            ad.unwrap_or(&[0u8; 0]),
+            nonce,
+            ciphertext,
+            &mut expected_tag[..M::TAGSIZE],
+        )
+        .and_then(|()| util::secure_cmp(&expected_tag[..M::TAGSIZE], tag));
+        expected_tag.zeroize();
+        result?;
+
+        if msglen != 0 {
+            dst_out[..msglen].copy_from_slice(ciphertext);
+            C::xor_keystream(cipher_key, nonce, &mut dst_out[..msglen])?;
+        }
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::mac::hmac;
+    use crate::hazardous::stream::{chacha20, xchacha20};
+
+    const KEY: [u8; 64] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c,
+        0x2d, 0x2e, 0x2f, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b,
+        0x3c, 0x3d, 0x3e, 0x3f,
+    ];
+
+    const NONCE: [u8; 24] = [0x42; 24];
+
+    #[test]
+    fn test_composition() {
+        // Compute the composition independently, using the public HMAC and
+        // XChaCha20 APIs.
+        let ad = b"Additional data";
+        let plaintext = b"Data to protect, which is longer than a ChaCha20 block of 64 bytes.";
+
+        let mut expected = [0u8; 67 + 32];
+        xchacha20::encrypt(
+            &chacha20::SecretKey::from_slice(&KEY[32..]).unwrap(),
+            &xchacha20::Nonce::from(NONCE),
+            0,
+            plaintext,
+            &mut expected[..67],
+        )
+        .unwrap();
+
+        let mut mac = hmac::Hmac::new(&hmac::SecretKey::from_slice(&KEY[..32]).unwrap());
+        mac.update(&(ad.len() as u64).to_le_bytes()).unwrap();
+        mac.update(ad).unwrap();
+        mac.update(&NONCE).unwrap();
+        mac.update(&expected[..67]).unwrap();
+        mac.update(&67u64.to_le_bytes()).unwrap();
+        let full_tag = mac.finalize().unwrap();
+        expected[67..].copy_from_slice(&full_tag.unprotected_as_bytes()[..32]);
+
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut actual = [0u8; 67 + 32];
+        XChaCha20HmacSha512::seal(&secret_key, &NONCE, plaintext, Some(ad), &mut actual).unwrap();
+        assert_eq!(actual[..], expected[..]);
+
+        let mut opened = [0u8; 67];
+        XChaCha20HmacSha512::open(&secret_key, &NONCE, &actual, Some(ad), &mut opened).unwrap();
+        assert_eq!(opened[..], plaintext[..]);
+    }
+
+    #[test]
+    fn test_empty_plaintext() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 32];
+        XChaCha20HmacSha512::seal(&secret_key, &NONCE, &[], None, &mut ciphertext).unwrap();
+        XChaCha20HmacSha512::open(&secret_key, &NONCE, &ciphertext, None, &mut []).unwrap();
+        assert!(
+            XChaCha20HmacSha512::open(&secret_key, &NONCE, &ciphertext, Some(b"ad"), &mut [])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_modified_err() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 10 + 32];
+        XChaCha20HmacSha512::seal(
+            &secret_key,
+            &NONCE,
+            &[1u8; 10],
+            Some(b"ad"),
+            &mut ciphertext,
+        )
+        .unwrap();
+
+        for idx in [0, 9, 10, 41].iter() {
+            let mut modified = ciphertext;
+            modified[*idx] ^= 1;
+            let mut dst_out = [0u8; 10];
+            assert!(XChaCha20HmacSha512::open(
+                &secret_key,
+                &NONCE,
+                &modified,
+                Some(b"ad"),
+                &mut dst_out
+            )
+            .is_err());
+            // Nothing is decrypted before the tag has been verified.
+            assert_eq!(dst_out, [0u8; 10]);
+        }
+
+        let mut dst_out = [0u8; 10];
+        let mut other_nonce = NONCE;
+        other_nonce[0] ^= 1;
+        assert!(XChaCha20HmacSha512::open(
+            &secret_key,
+            &other_nonce,
+            &ciphertext,
+            Some(b"ad"),
+            &mut dst_out
+        )
+        .is_err());
+        assert!(
+            XChaCha20HmacSha512::open(&secret_key, &NONCE, &ciphertext, None, &mut dst_out)
+                .is_err()
+        );
+        assert!(XChaCha20HmacSha512::open(
+            &SecretKey::from_slice(&[0u8; 64]).unwrap(),
+            &NONCE,
+            &ciphertext,
+            Some(b"ad"),
+            &mut dst_out
+        )
+        .is_err());
+        assert!(XChaCha20HmacSha512::open(
+            &secret_key,
+            &NONCE,
+            &ciphertext[..31],
+            Some(b"ad"),
+            &mut dst_out
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_length_err() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 10 + 32];
+        assert!(XChaCha20HmacSha512::seal(
+            &secret_key,
+            &NONCE,
+            &[1u8; 10],
+            None,
+            &mut ciphertext[..41]
+        )
+        .is_err());
+        assert!(XChaCha20HmacSha512::seal(
+            &secret_key,
+            &NONCE[..23],
+            &[1u8; 10],
+            None,
+            &mut ciphertext
+        )
+        .is_err());
+        XChaCha20HmacSha512::seal(&secret_key, &NONCE, &[1u8; 10], None, &mut ciphertext).unwrap();
+
+        let mut dst_out = [0u8; 10];
+        assert!(XChaCha20HmacSha512::open(
+            &secret_key,
+            &NONCE,
+            &ciphertext,
+            None,
+            &mut dst_out[..9]
+        )
+        .is_err());
+        assert!(XChaCha20HmacSha512::open(
+            &secret_key,
+            &NONCE[..23],
+            &ciphertext,
+            None,
+            &mut dst_out
+        )
+        .is_err());
+
+        let short_key = SecretKey::from_slice(&KEY[..32]).unwrap();
+        assert!(
+            XChaCha20HmacSha512::seal(&short_key, &NONCE, &[1u8; 10], None, &mut ciphertext)
+                .is_err()
+        );
+        assert!(
+            XChaCha20HmacSha512::open(&short_key, &NONCE, &ciphertext, None, &mut dst_out).is_err()
+        );
+    }
+}
@@ -23,8 +23,33 @@
 /// AEAD ChaCha20Poly1305 as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
 pub mod chacha20poly1305;
 
+/// AEAD ChaCha20Poly1305 using the original construction with a 64-bit nonce, as specified in
+/// [draft-agl-tls-chacha20poly1305](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04).
+pub mod chacha20poly1305_original;
+
+/// The `chacha20-poly1305@openssh.com` packet cipher of SSH.
+pub mod chacha20poly1305_openssh;
+
 /// AEAD XChaCha20Poly1305 as specified in the [draft RFC](https://github.com/bikeshedders/xchacha-rfc).
 pub mod xchacha20poly1305;
 
+/// AEAD AES-256-GCM as specified in [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf).
+pub mod aes256gcm;
+
+/// AES-GCM as specified in [NIST SP 800-38D](https://nvlpubs.nist.gov/nistpubs/Legacy/SP/nistspecialpublication800-38d.pdf).
+pub(crate) mod aes_gcm;
+
 /// Streaming AEAD based on XChaCha20Poly1305.
 pub mod streaming;
+
+/// Building blocks for generic AEAD compositions.
+pub mod composition;
+
+/// Deterministic, nonce misuse-resistant AEAD using the SIV composition.
+pub mod siv;
+
+/// AEAD using the Encrypt-then-MAC composition.
+pub mod etm;
+
+/// Deterministic derivation of AEAD nonces.
+pub mod nonce;
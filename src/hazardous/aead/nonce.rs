@@ -0,0 +1,472 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Deterministic derivation of AEAD nonces, for systems that must not store
+//! nonces but can reconstruct a unique counter and context for every message,
+//! such as a block number of an encrypted disk or a chunk index of a file.
+//!
+//! A nonce is computed as `PRF(secret_key, LE64(counter) || context)`, where
+//! the PRF is keyed BLAKE2b with an output size equal to the nonce size.
+//! [`derive_nonce()`] is generic over [`AeadNonce`], which is implemented for
+//! the nonce types of the AEADs, so the output size always matches the nonce
+//! type at compile time:
+//! - [`chacha20::Nonce`] (96 bits) for ChaCha20Poly1305.
+//! - [`xchacha20::Nonce`] (192 bits) for XChaCha20Poly1305.
+//! - [`aes256gcm::Nonce`] (96 bits) for AES-256-GCM.
+//!
+//! Record layers such as TLS 1.3 ([RFC 8446, section 5.3]) and QUIC
+//! ([RFC 9001, section 5.3]) instead form the nonce of each record as
+//! `static_iv XOR sequence_number`, where the 64-bit sequence number is
+//! encoded big-endian and left-padded with zeros to 96 bits. [`xor_nonce()`]
+//! computes this from a [`StaticIv`] and a [`SequenceNumber`], which keeps
+//! the sequence number from being used as the nonce directly, or being
+//! concatenated with the IV instead of XORed into it. It is only implemented
+//! for the 96-bit nonces of [`IetfNonce`].
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key of the PRF.
+//! - `counter`: A counter that is unique per message under `context`.
+//! - `context`: Data identifying the stream of messages, such as a file ID. It
+//!   may be empty.
+//! - `static_iv`: The IV from the key schedule of the record layer, such as
+//!   `client_write_iv` in TLS 1.3.
+//! - `sequence_number`: The number of the record or packet.
+//!
+//! # Errors:
+//! An error will never be returned by [`derive_nonce()`] or [`xor_nonce()`]
+//! for the nonce types orion provides. An error will be returned if:
+//! - [`SequenceNumber::next()`] is called on the last sequence number.
+//!
+//! # Security:
+//! - `secret_key` must be independent of the AEAD key, and must be generated
+//!   using a CSPRNG. [`SecretKey::generate()`] can be used for this.
+//! - Each pair of `counter` and `context` must only be used for a single
+//!   message under the same AEAD key. Deriving a nonce twice from the same
+//!   inputs gives the same nonce, and encrypting two different messages under
+//!   it breaks the confidentiality and authenticity of both.
+//! - Distinct inputs give nonces that are indistinguishable from random ones,
+//!   so they can collide by chance. With 96-bit nonces, no more than 2^32
+//!   messages should be encrypted with the same AEAD key. The 192-bit nonces
+//!   of XChaCha20Poly1305 make collisions negligible, and should be preferred.
+//! - With [`xor_nonce()`], each sequence number must only be used for a single
+//!   record under the same AEAD key and static IV. Record layers must close
+//!   or rekey the connection instead of letting the sequence number wrap,
+//!   which is why [`SequenceNumber::next()`] returns an error on overflow.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::{nonce, xchacha20poly1305};
+//!
+//! let nonce_key = nonce::SecretKey::generate();
+//! let aead_key = xchacha20poly1305::SecretKey::generate();
+//!
+//! // The nonce for chunk 3 of a file does not need to be stored.
+//! let chunk_nonce: xchacha20poly1305::Nonce = nonce::derive_nonce(&nonce_key, 3, b"file 42")?;
+//! let mut sealed = [0u8; 5 + 16];
+//! xchacha20poly1305::seal(&aead_key, &chunk_nonce, b"chunk", None, &mut sealed)?;
+//!
+//! let chunk_nonce: xchacha20poly1305::Nonce = nonce::derive_nonce(&nonce_key, 3, b"file 42")?;
+//! let mut opened = [0u8; 5];
+//! xchacha20poly1305::open(&aead_key, &chunk_nonce, &sealed, None, &mut opened)?;
+//! assert_eq!(&opened, b"chunk");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//!
+//! Nonces of a TLS 1.3 style record layer:
+//! ```rust
+//! use orion::hazardous::aead::{chacha20poly1305, nonce};
+//!
+//! // Both are derived by the key schedule of the protocol.
+//! let aead_key = chacha20poly1305::SecretKey::generate();
+//! let static_iv = nonce::StaticIv::generate();
+//!
+//! let mut sequence_number = nonce::SequenceNumber::new(0);
+//! for record in [&b"first"[..], &b"second"[..]].iter() {
+//!     let record_nonce: chacha20poly1305::Nonce = nonce::xor_nonce(&static_iv, sequence_number);
+//!     let mut sealed = vec![0u8; record.len() + 16];
+//!     chacha20poly1305::seal(&aead_key, &record_nonce, record, None, &mut sealed)?;
+//!
+//!     sequence_number = sequence_number.next()?;
+//! }
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`derive_nonce()`]: fn.derive_nonce.html
+//! [`AeadNonce`]: trait.AeadNonce.html
+//! [`chacha20::Nonce`]: ../../stream/chacha20/struct.Nonce.html
+//! [`xchacha20::Nonce`]: ../../stream/xchacha20/struct.Nonce.html
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+//! [`aes256gcm::Nonce`]: ../aes256gcm/struct.Nonce.html
+//! [`xor_nonce()`]: fn.xor_nonce.html
+//! [`StaticIv`]: struct.StaticIv.html
+//! [`SequenceNumber`]: struct.SequenceNumber.html
+//! [`SequenceNumber::next()`]: struct.SequenceNumber.html#method.next
+//! [`IetfNonce`]: trait.IetfNonce.html
+//! [RFC 8446, section 5.3]: https://www.rfc-editor.org/rfc/rfc8446#section-5.3
+//! [RFC 9001, section 5.3]: https://www.rfc-editor.org/rfc/rfc9001#section-5.3
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::aead::aes256gcm;
+use crate::hazardous::hash::blake2b::{self, Blake2b};
+use crate::hazardous::stream::{chacha20, xchacha20};
+
+/// The size of the secret key of the PRF.
+pub const NONCE_KEYSIZE: usize = 32;
+
+/// The size of a [`StaticIv`], which is the size of an [`IetfNonce`].
+///
+/// [`StaticIv`]: struct.StaticIv.html
+/// [`IetfNonce`]: trait.IetfNonce.html
+pub const STATIC_IV_SIZE: usize = 12;
+
+/// The largest nonce size (bytes) of any [`AeadNonce`].
+///
+/// [`AeadNonce`]: trait.AeadNonce.html
+const MAX_NONCESIZE: usize = xchacha20::XCHACHA_NONCESIZE;
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` that nonces are derived with.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, NONCE_KEYSIZE, NONCE_KEYSIZE, NONCE_KEYSIZE)
+}
+
+impl_from_trait!(SecretKey, NONCE_KEYSIZE);
+
+construct_secret_key! {
+    /// A type to represent the static IV that record nonces are formed with
+    /// by [`xor_nonce()`].
+    ///
+    /// [`xor_nonce()`]: fn.xor_nonce.html
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 12 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (StaticIv, test_static_iv, STATIC_IV_SIZE, STATIC_IV_SIZE, STATIC_IV_SIZE)
+}
+
+impl_from_trait!(StaticIv, STATIC_IV_SIZE);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The sequence number of a record or packet, which [`xor_nonce()`] combines
+/// with a [`StaticIv`].
+///
+/// [`xor_nonce()`]: fn.xor_nonce.html
+/// [`StaticIv`]: struct.StaticIv.html
+pub struct SequenceNumber(u64);
+
+impl SequenceNumber {
+    /// Create the sequence number `value`.
+    pub fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Return the value of the sequence number.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return the following sequence number, or an error if this is the last one.
+    pub fn next(&self) -> Result<Self, UnknownCryptoError> {
+        match self.0.checked_add(1) {
+            Some(value) => Ok(Self(value)),
+            None => Err(UnknownCryptoError),
+        }
+    }
+}
+
+mod private {
+    /// Prevents implementations of [`AeadNonce`](../trait.AeadNonce.html)
+    /// outside of orion.
+    pub trait Sealed {}
+
+    impl Sealed for super::chacha20::Nonce {}
+    impl Sealed for super::xchacha20::Nonce {}
+    impl Sealed for super::aes256gcm::Nonce {}
+}
+
+/// A nonce type of an AEAD, that nonces can be derived for.
+///
+/// This trait is sealed and cannot be implemented outside of orion.
+pub trait AeadNonce: private::Sealed + Sized {
+    /// The nonce size (bytes).
+    const NONCESIZE: usize;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a nonce from `bytes`, which must be `NONCESIZE` bytes.
+    fn from_derived(bytes: &[u8]) -> Result<Self, UnknownCryptoError>;
+}
+
+impl AeadNonce for chacha20::Nonce {
+    const NONCESIZE: usize = chacha20::IETF_CHACHA_NONCESIZE;
+
+    fn from_derived(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        Self::from_slice(bytes)
+    }
+}
+
+impl AeadNonce for xchacha20::Nonce {
+    const NONCESIZE: usize = xchacha20::XCHACHA_NONCESIZE;
+
+    fn from_derived(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        Self::from_slice(bytes)
+    }
+}
+
+impl AeadNonce for aes256gcm::Nonce {
+    const NONCESIZE: usize = aes256gcm::AES256GCM_NONCESIZE;
+
+    fn from_derived(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        Self::from_slice(bytes)
+    }
+}
+
+/// A 96-bit nonce type of an AEAD, that [`xor_nonce()`] can form nonces for.
+///
+/// This trait is sealed and cannot be implemented outside of orion.
+///
+/// [`xor_nonce()`]: fn.xor_nonce.html
+pub trait IetfNonce: AeadNonce {}
+
+impl IetfNonce for chacha20::Nonce {}
+
+impl IetfNonce for aes256gcm::Nonce {}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the nonce for `counter` and `context` with `secret_key`.
+pub fn derive_nonce<N: AeadNonce>(
+    secret_key: &SecretKey,
+    counter: u64,
+    context: &[u8],
+) -> Result<N, UnknownCryptoError> {
+    debug_assert!(N::NONCESIZE <= MAX_NONCESIZE);
+
+    let prf_key = blake2b::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+    let mut prf = Blake2b::new(Some(&prf_key), N::NONCESIZE)?;
+    prf.update(&counter.to_le_bytes())?;
+    prf.update(context)?;
+
+    N::from_derived(prf.finalize()?.as_ref())
+}
+
+/// Form the nonce for `sequence_number` by XORing it with `static_iv`, as in
+/// TLS 1.3 and QUIC.
+pub fn xor_nonce<N: IetfNonce>(static_iv: &StaticIv, sequence_number: SequenceNumber) -> N {
+    debug_assert_eq!(N::NONCESIZE, STATIC_IV_SIZE);
+
+    let mut nonce = [0u8; STATIC_IV_SIZE];
+    nonce.copy_from_slice(static_iv.unprotected_as_bytes());
+    for (byte, counter) in nonce[STATIC_IV_SIZE - 8..]
+        .iter_mut()
+        .zip(sequence_number.0.to_be_bytes().iter())
+    {
+        *byte ^= counter;
+    }
+
+    // This cannot fail, as every IetfNonce is STATIC_IV_SIZE bytes.
+    N::from_derived(&nonce).unwrap()
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn secret_key() -> SecretKey {
+        let mut bytes = [0u8; NONCE_KEYSIZE];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        SecretKey::from(bytes)
+    }
+
+    #[test]
+    fn test_known_nonces() {
+        // Computed with keyed BLAKE2b from Python's hashlib.
+        let vectors: [(u64, &[u8], &str, &str); 4] = [
+            (
+                0,
+                b"",
+                "d26f9f263dd1e89331c2fdcc",
+                "9aecee772f6fb4709af5112d645b661513a849f8488bc01e",
+            ),
+            (
+                1,
+                b"",
+                "55620a2d1e0cbfb106e812f8",
+                "1f0ad938373050d998e5dffc8506b949e336ff4e32d70a90",
+            ),
+            (
+                0,
+                b"file 42",
+                "b2ae04140ddf059227fbd98f",
+                "c7f2623afa8bf86a82c2e5ef5c0861ba9df9acccd3a9d1eb",
+            ),
+            (
+                u64::MAX,
+                b"file 42",
+                "47a19a7a13ebc6bacaf867c8",
+                "33036a1dba5b68e46089de726d0a60f7fc7a128135c12ed1",
+            ),
+        ];
+
+        for (counter, context, ietf, extended) in vectors.iter() {
+            let nonce: chacha20::Nonce = derive_nonce(&secret_key(), *counter, context).unwrap();
+            assert_eq!(nonce.as_ref(), &hex::decode(ietf).unwrap()[..]);
+            let nonce: xchacha20::Nonce = derive_nonce(&secret_key(), *counter, context).unwrap();
+            assert_eq!(nonce.as_ref(), &hex::decode(extended).unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(<chacha20::Nonce as AeadNonce>::NONCESIZE, 12);
+        assert_eq!(<xchacha20::Nonce as AeadNonce>::NONCESIZE, 24);
+        assert!(chacha20::Nonce::from_derived(&[0u8; 24]).is_err());
+        assert!(xchacha20::Nonce::from_derived(&[0u8; 12]).is_err());
+        assert_eq!(<aes256gcm::Nonce as AeadNonce>::NONCESIZE, 12);
+        assert!(aes256gcm::Nonce::from_derived(&[0u8; 24]).is_err());
+    }
+
+    #[test]
+    fn test_xor_nonce_quic() {
+        // The client Initial and server Initial packets of RFC 9001, A.2 and A.3.
+        let static_iv =
+            StaticIv::from_slice(&hex::decode("fa044b2f42a3fd3b46fb255c").unwrap()).unwrap();
+        let nonce: chacha20::Nonce = xor_nonce(&static_iv, SequenceNumber::new(2));
+        assert_eq!(
+            nonce.as_ref(),
+            &hex::decode("fa044b2f42a3fd3b46fb255e").unwrap()[..]
+        );
+        let static_iv =
+            StaticIv::from_slice(&hex::decode("0ac1493ca1905853b0bba03e").unwrap()).unwrap();
+        let nonce: aes256gcm::Nonce = xor_nonce(&static_iv, SequenceNumber::new(1));
+        assert_eq!(
+            nonce.as_ref(),
+            &hex::decode("0ac1493ca1905853b0bba03f").unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_xor_nonce_quic_chacha20poly1305() {
+        use crate::hazardous::aead::chacha20poly1305;
+
+        // The ChaCha20-Poly1305 short header packet of RFC 9001, A.5, before
+        // header protection.
+        let aead_key = chacha20poly1305::SecretKey::from_slice(
+            &hex::decode("c6d98ff3441c3fe1b2182094f69caa2ed4b716b65488960a7a984979fb23e1c8")
+                .unwrap(),
+        )
+        .unwrap();
+        let static_iv =
+            StaticIv::from_slice(&hex::decode("e0459b3474bdd0e44a41c144").unwrap()).unwrap();
+        let nonce: chacha20poly1305::Nonce = xor_nonce(&static_iv, SequenceNumber::new(654360564));
+        assert_eq!(
+            nonce.as_ref(),
+            &hex::decode("e0459b3474bdd0e46d417eb0").unwrap()[..]
+        );
+
+        let header = hex::decode("4200bff4").unwrap();
+        let mut sealed = [0u8; 1 + 16];
+        chacha20poly1305::seal(&aead_key, &nonce, &[0x01], Some(&header), &mut sealed).unwrap();
+        assert_eq!(
+            sealed.as_ref(),
+            &hex::decode("655e5cd55c41f69080575d7999c25a5bfb").unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_xor_nonce_sequence_numbers() {
+        let static_iv = StaticIv::from([0xA5u8; STATIC_IV_SIZE]);
+        let first: chacha20::Nonce = xor_nonce(&static_iv, SequenceNumber::new(0));
+        assert_eq!(first.as_ref(), &[0xA5u8; STATIC_IV_SIZE]);
+
+        // Only the last 64 bits of the IV are changed, in big-endian order.
+        let last: chacha20::Nonce = xor_nonce(&static_iv, SequenceNumber::new(u64::MAX));
+        assert_eq!(&last.as_ref()[..4], &[0xA5u8; 4]);
+        assert_eq!(&last.as_ref()[4..], &[0x5Au8; 8]);
+        let one: chacha20::Nonce = xor_nonce(&static_iv, SequenceNumber::new(1));
+        assert_eq!(one.as_ref()[11], 0xA4);
+        assert_eq!(&one.as_ref()[..11], &[0xA5u8; 11]);
+    }
+
+    #[test]
+    fn test_sequence_number_next() {
+        let sequence_number = SequenceNumber::new(41);
+        assert_eq!(sequence_number.next().unwrap().value(), 42);
+        assert!(sequence_number < sequence_number.next().unwrap());
+        assert!(SequenceNumber::new(u64::MAX).next().is_err());
+        assert_eq!(
+            SequenceNumber::new(u64::MAX - 1).next().unwrap(),
+            SequenceNumber::new(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_deterministic_and_distinct() {
+        let key = secret_key();
+        let nonce: xchacha20::Nonce = derive_nonce(&key, 7, b"ctx").unwrap();
+        let again: xchacha20::Nonce = derive_nonce(&key, 7, b"ctx").unwrap();
+        assert_eq!(nonce, again);
+
+        let other_counter: xchacha20::Nonce = derive_nonce(&key, 8, b"ctx").unwrap();
+        let other_context: xchacha20::Nonce = derive_nonce(&key, 7, b"ctX").unwrap();
+        let other_key: xchacha20::Nonce =
+            derive_nonce(&SecretKey::from([0u8; NONCE_KEYSIZE]), 7, b"ctx").unwrap();
+        assert_ne!(nonce, other_counter);
+        assert_ne!(nonce, other_context);
+        assert_ne!(nonce, other_key);
+
+        // The 96-bit nonce is not a prefix of the 192-bit one.
+        let ietf: chacha20::Nonce = derive_nonce(&key, 7, b"ctx").unwrap();
+        assert_ne!(ietf.as_ref(), &nonce.as_ref()[..12]);
+    }
+
+    #[test]
+    fn test_chacha20poly1305_roundtrip() {
+        use crate::hazardous::aead::chacha20poly1305;
+
+        let aead_key = chacha20poly1305::SecretKey::from([1u8; 32]);
+        let nonce: chacha20poly1305::Nonce = derive_nonce(&secret_key(), 1, b"").unwrap();
+        let mut sealed = [0u8; 5 + 16];
+        chacha20poly1305::seal(&aead_key, &nonce, b"hello", None, &mut sealed).unwrap();
+
+        let nonce: chacha20poly1305::Nonce = derive_nonce(&secret_key(), 1, b"").unwrap();
+        let mut opened = [0u8; 5];
+        chacha20poly1305::open(&aead_key, &nonce, &sealed, None, &mut opened).unwrap();
+        assert_eq!(&opened, b"hello");
+
+        let nonce: chacha20poly1305::Nonce = derive_nonce(&secret_key(), 2, b"").unwrap();
+        assert!(chacha20poly1305::open(&aead_key, &nonce, &sealed, None, &mut opened).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! SIV (Synthetic Initialization Vector), as described in [RFC 5297], composes
+//! a MAC and a stream cipher into a deterministic, nonce misuse-resistant AEAD.
+//! The tag is the MAC of the additional data and the plaintext, and is used as
+//! the IV of the stream cipher. Encrypting the same plaintext and additional
+//! data twice gives the same ciphertext, so reusing a nonce placed in `ad` only
+//! reveals whether two messages are equal.
+//!
+//! [`Siv`] implements the composition generically over a [`Mac`] and a
+//! [`StreamCipher`]. The MAC is computed over:
+//!
+//! `LE64(ad.len()) || ad || plaintext || LE64(plaintext.len())`
+//!
+//! The secret key is split into a MAC key, followed by a cipher key. The
+//! first `StreamCipher::IVSIZE` bytes of the tag are used as the IV.
+//!
+//! orion provides [`ChaCha20Siv`], which uses HMAC-SHA512 truncated to 32 bytes
+//! and XChaCha20 with a 64-byte secret key. It is NOT compatible with AES-SIV,
+//! which orion does not implement.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be `None`).
+//! - `ciphertext_with_tag`: The encrypted data with the tag prepended to it.
+//! - `plaintext`: The data to be encrypted.
+//! - `dst_out`: Destination array that will hold the
+//!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `secret_key` is not `Mac::KEYSIZE` + `StreamCipher::KEYSIZE`.
+//! - The length of `dst_out` is less than `plaintext` + `Mac::TAGSIZE` when calling [`Siv::seal()`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - `Mac::TAGSIZE` when
+//!   calling [`Siv::open()`].
+//! - The length of the `ciphertext_with_tag` is not at least `Mac::TAGSIZE`.
+//! - The received tag does not match the calculated tag when calling [`Siv::open()`].
+//! - `plaintext.len()` + `Mac::TAGSIZE` overflows when calling [`Siv::seal()`].
+//!
+//! # Security:
+//! - Equal plaintexts encrypted with the same key and additional data give equal
+//!   ciphertexts. Include a nonce in `ad` if this must not be revealed.
+//! - To securely generate a strong key, use [`SecretKey::generate()`].
+//! - The length of the `plaintext` is not hidden, only its contents.
+//! - If [`Siv::open()`] fails, `dst_out` is zeroed, since the plaintext is
+//!   decrypted before it is authenticated.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::siv::{ChaCha20Siv, SecretKey};
+//!
+//! let secret_key = SecretKey::generate();
+//! let message = "Data to protect".as_bytes();
+//!
+//! // Length of the above message is 15 and then we accommodate 32 for the tag.
+//! let mut dst_out_ct = [0u8; 15 + 32];
+//! let mut dst_out_pt = [0u8; 15];
+//! ChaCha20Siv::seal(&secret_key, message, Some(b"Additional data"), &mut dst_out_ct)?;
+//! ChaCha20Siv::open(&secret_key, &dst_out_ct, Some(b"Additional data"), &mut dst_out_pt)?;
+//!
+//! assert_eq!(dst_out_pt.as_ref(), message);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 5297]: https://tools.ietf.org/html/rfc5297
+//! [`Siv`]: struct.Siv.html
+//! [`Mac`]: ../composition/trait.Mac.html
+//! [`StreamCipher`]: ../composition/trait.StreamCipher.html
+//! [`ChaCha20Siv`]: type.ChaCha20Siv.html
+//! [`Siv::seal()`]: struct.Siv.html#method.seal
+//! [`Siv::open()`]: struct.Siv.html#method.open
+//! [`SecretKey::generate()`]: struct.SecretKey.html#method.generate
+
+pub use super::composition::{HmacSha512, XChaCha20};
+use super::composition::{Mac, StreamCipher, MAX_TAGSIZE};
+use crate::errors::UnknownCryptoError;
+use crate::util;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use zeroize::Zeroize;
+
+construct_secret_key! {
+    /// A type to represent the secret key that SIV uses. It holds the MAC key
+    /// followed by the cipher key.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is less than 32 or greater than 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, 32, 64, 64)
+}
+
+/// SIV over the MAC `M` and the stream cipher `C`.
+pub struct Siv<M: Mac, C: StreamCipher> {
+    _mac: PhantomData<M>,
+    _cipher: PhantomData<C>,
+}
+
+/// SIV with HMAC-SHA512 truncated to 32 bytes, and XChaCha20.
+pub type ChaCha20Siv = Siv<HmacSha512, XChaCha20>;
+
+impl<M: Mac, C: StreamCipher> Siv<M, C> {
+    /// Split `secret_key` into the MAC key and the cipher key.
+    fn split_key(secret_key: &SecretKey) -> Result<(&[u8], &[u8]), UnknownCryptoError> {
+        if C::IVSIZE > M::TAGSIZE || M::TAGSIZE > MAX_TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+        if secret_key.len() != M::KEYSIZE + C::KEYSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(secret_key.unprotected_as_bytes().split_at(M::KEYSIZE))
+    }
+
+    /// Compute the tag of `ad` and `plaintext` into `dest`.
+    fn tag(
+        mac_key: &[u8],
+        ad: &[u8],
+        plaintext: &[u8],
+        dest: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let ad_len = u64::try_from(ad.len()).map_err(|_| UnknownCryptoError)?;
+        let pt_len = u64::try_from(plaintext.len()).map_err(|_| UnknownCryptoError)?;
+
+        let mut mac = M::new(mac_key)?;
+        mac.update(&ad_len.to_le_bytes())?;
+        mac.update(ad)?;
+        mac.update(plaintext)?;
+        mac.update(&pt_len.to_le_bytes())?;
+        mac.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// SIV encryption.
+    pub fn seal(
+        secret_key: &SecretKey,
+        plaintext: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (mac_key, cipher_key) = Self::split_key(secret_key)?;
+        let out_len = match plaintext.len().checked_add(M::TAGSIZE) {
+            Some(out_min_len) => {
+                if dst_out.len() < out_min_len {
+                    return Err(UnknownCryptoError);
+                }
+                out_min_len
+            }
+            None => return Err(UnknownCryptoError),
+        };
+
+        let (tag, ciphertext) = dst_out[..out_len].split_at_mut(M::TAGSIZE);
+        Self::tag(mac_key, ad.unwrap_or(&[0u8; 0]), plaintext, tag)?;
+
+        if !plaintext.is_empty() {
+            ciphertext.copy_from_slice(plaintext);
+            C::xor_keystream(cipher_key, &tag[..C::IVSIZE], ciphertext)?;
+        }
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// SIV decryption.
+    pub fn open(
+        secret_key: &SecretKey,
+        ciphertext_with_tag: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (mac_key, cipher_key) = Self::split_key(secret_key)?;
+        if ciphertext_with_tag.len() < M::TAGSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let msglen = ciphertext_with_tag.len() - M::TAGSIZE;
+        if dst_out.len() < msglen {
+            return Err(UnknownCryptoError);
+        }
+
+        let (tag, ciphertext) = ciphertext_with_tag.split_at(M::TAGSIZE);
+        let plaintext = &mut dst_out[..msglen];
+        if msglen != 0 {
+            plaintext.copy_from_slice(ciphertext);
+            C::xor_keystream(cipher_key, &tag[..C::IVSIZE], plaintext)?;
+        }
+
+        let mut expected_tag = [0u8; MAX_TAGSIZE];
+        let result = Self::tag(
+            mac_key,
+            ad.unwrap_or(&[0u8; 0]),
+            plaintext,
+            &mut expected_tag[..M::TAGSIZE],
+        )
+        .and_then(|()| util::secure_cmp(&expected_tag[..M::TAGSIZE], tag));
+        expected_tag.zeroize();
+
+        if result.is_err() {
+            plaintext.iter_mut().zeroize();
+        }
+
+        result
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::mac::hmac;
+    use crate::hazardous::stream::{chacha20, xchacha20};
+
+    const KEY: [u8; 64] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+        0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d,
+        0x1e, 0x1f, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x29, 0x2a, 0x2b, 0x2c,
+        0x2d, 0x2e, 0x2f, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b,
+        0x3c, 0x3d, 0x3e, 0x3f,
+    ];
+
+    #[test]
+    fn test_composition() {
+        // Compute the composition independently, using the public HMAC and
+        // XChaCha20 APIs.
+        let ad = b"Additional data";
+        let plaintext = b"Data to protect, which is longer than a ChaCha20 block of 64 bytes.";
+
+        let mut mac = hmac::Hmac::new(&hmac::SecretKey::from_slice(&KEY[..32]).unwrap());
+        mac.update(&(ad.len() as u64).to_le_bytes()).unwrap();
+        mac.update(ad).unwrap();
+        mac.update(plaintext).unwrap();
+        mac.update(&(plaintext.len() as u64).to_le_bytes()).unwrap();
+        let full_tag = mac.finalize().unwrap();
+
+        let mut expected = [0u8; 32 + 67];
+        expected[..32].copy_from_slice(&full_tag.unprotected_as_bytes()[..32]);
+        xchacha20::encrypt(
+            &chacha20::SecretKey::from_slice(&KEY[32..]).unwrap(),
+            &xchacha20::Nonce::from_slice(&expected[..24]).unwrap(),
+            0,
+            plaintext,
+            &mut expected[32..],
+        )
+        .unwrap();
+
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut actual = [0u8; 32 + 67];
+        ChaCha20Siv::seal(&secret_key, plaintext, Some(ad), &mut actual).unwrap();
+        assert_eq!(actual[..], expected[..]);
+
+        let mut opened = [0u8; 67];
+        ChaCha20Siv::open(&secret_key, &actual, Some(ad), &mut opened).unwrap();
+        assert_eq!(opened[..], plaintext[..]);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut first = [0u8; 10 + 32];
+        let mut second = [0u8; 10 + 32];
+
+        ChaCha20Siv::seal(&secret_key, &[1u8; 10], Some(b"ad"), &mut first).unwrap();
+        ChaCha20Siv::seal(&secret_key, &[1u8; 10], Some(b"ad"), &mut second).unwrap();
+        assert_eq!(first, second);
+
+        ChaCha20Siv::seal(&secret_key, &[1u8; 10], Some(b"da"), &mut second).unwrap();
+        assert_ne!(first, second);
+        ChaCha20Siv::seal(&secret_key, &[1u8; 10], None, &mut second).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_empty_plaintext() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 32];
+        ChaCha20Siv::seal(&secret_key, &[], None, &mut ciphertext).unwrap();
+        ChaCha20Siv::open(&secret_key, &ciphertext, None, &mut []).unwrap();
+        assert!(ChaCha20Siv::open(&secret_key, &ciphertext, Some(b"ad"), &mut []).is_err());
+    }
+
+    #[test]
+    fn test_modified_err() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 10 + 32];
+        ChaCha20Siv::seal(&secret_key, &[1u8; 10], Some(b"ad"), &mut ciphertext).unwrap();
+
+        for idx in [0, 31, 32, 41].iter() {
+            let mut modified = ciphertext;
+            modified[*idx] ^= 1;
+            let mut dst_out = [0u8; 10];
+            assert!(ChaCha20Siv::open(&secret_key, &modified, Some(b"ad"), &mut dst_out).is_err());
+            assert_eq!(dst_out, [0u8; 10]);
+        }
+
+        let mut dst_out = [0u8; 10];
+        assert!(ChaCha20Siv::open(&secret_key, &ciphertext, None, &mut dst_out).is_err());
+        assert!(ChaCha20Siv::open(
+            &SecretKey::from_slice(&[0u8; 64]).unwrap(),
+            &ciphertext,
+            Some(b"ad"),
+            &mut dst_out
+        )
+        .is_err());
+        assert!(ChaCha20Siv::open(&secret_key, &ciphertext[..31], None, &mut dst_out).is_err());
+    }
+
+    #[test]
+    fn test_length_err() {
+        let secret_key = SecretKey::from_slice(&KEY).unwrap();
+        let mut ciphertext = [0u8; 10 + 32];
+        assert!(ChaCha20Siv::seal(&secret_key, &[1u8; 10], None, &mut ciphertext[..41]).is_err());
+        // A longer `dst_out` is allowed.
+        ChaCha20Siv::seal(&secret_key, &[1u8; 9], None, &mut ciphertext).unwrap();
+
+        let mut dst_out = [0u8; 10];
+        assert!(ChaCha20Siv::open(&secret_key, &ciphertext, None, &mut dst_out[..9]).is_err());
+
+        let short_key = SecretKey::from_slice(&KEY[..32]).unwrap();
+        assert!(ChaCha20Siv::seal(&short_key, &[1u8; 9], None, &mut ciphertext).is_err());
+        assert!(ChaCha20Siv::open(&short_key, &ciphertext, None, &mut dst_out).is_err());
+    }
+}
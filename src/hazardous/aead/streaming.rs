@@ -32,6 +32,8 @@
 //! - `plaintext`: The data to be encrypted.
 //! - `ciphertext`: The encrypted data with, a Poly1305 tag and a [`StreamTag`] indicating its function.
 //! - `dst_out`: Destination array that will hold the `ciphertext`/`plaintext` after encryption/decryption.
+//! - `chunk`: The `ciphertext` that is encrypted or decrypted in place, by
+//!   [`seal_chunk_in_place()`] and [`open_chunk_in_place()`].
 //! - `tag`: Indicates the type of message. The `tag` is a part of the output when encrypting. It
 //!   is encrypted and authenticated.
 //!
@@ -39,7 +41,7 @@
 //! An error will be returned if:
 //! - The length of `dst_out` is less than `plaintext` + [`ABYTES`] when calling [`seal_chunk()`].
 //! - The length of `dst_out` is less than `ciphertext` - [`ABYTES`] when calling [`open_chunk()`].
-//! - The length of the `ciphertext` is less than [`ABYTES`].
+//! - The length of the `ciphertext` or `chunk` is less than [`ABYTES`].
 //! - The received mac does not match the calculated mac when calling [`open_chunk()`]. This can
 //!   indicate a dropped or reordered message within the stream.
 //! - More than `2^32-3 * 64` bytes of data are processed when sealing/opening a single chunk.
@@ -91,6 +93,8 @@
 //! [`ABYTES`]: constant.ABYTES.html
 //! [`seal_chunk()`]: struct.StreamXChaCha20Poly1305.html#method.seal_chunk
 //! [`open_chunk()`]: struct.StreamXChaCha20Poly1305.html#method.open_chunk
+//! [`seal_chunk_in_place()`]: struct.StreamXChaCha20Poly1305.html#method.seal_chunk_in_place
+//! [`open_chunk_in_place()`]: struct.StreamXChaCha20Poly1305.html#method.open_chunk_in_place
 use crate::errors::UnknownCryptoError;
 use crate::hazardous::aead::chacha20poly1305::poly1305_key_gen;
 use crate::hazardous::mac::poly1305::{Poly1305, Tag as Poly1305Tag, POLY1305_OUTSIZE};
@@ -153,6 +157,20 @@ impl PartialEq<StreamTag> for StreamTag {
     }
 }
 
+#[cfg(feature = "test_utils")]
+impl Clone for StreamTag {
+    fn clone(&self) -> Self {
+        Self::try_from(self.as_byte()).unwrap()
+    }
+}
+
+#[cfg(feature = "test_utils")]
+impl quickcheck::Arbitrary for StreamTag {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Self::try_from(<u8 as quickcheck::Arbitrary>::arbitrary(g) & 0b0000_0011).unwrap()
+    }
+}
+
 /// The size of the internal counter.
 const COUNTERBYTES: usize = 4;
 /// The size of the internal nonce.
@@ -301,15 +319,41 @@ impl StreamXChaCha20Poly1305 {
         tag: StreamTag,
     ) -> Result<(), UnknownCryptoError> {
         let msglen = plaintext.len();
-        match ABYTES.checked_add(msglen) {
+        let out_len = match ABYTES.checked_add(msglen) {
             Some(out_min_len) => {
                 if dst_out.len() < out_min_len {
                     return Err(UnknownCryptoError);
                 }
+                out_min_len
             }
             None => return Err(UnknownCryptoError),
         };
 
+        dst_out[TAG_SIZE..(TAG_SIZE + msglen)].copy_from_slice(plaintext);
+        self.seal_chunk_in_place(&mut dst_out[..out_len], ad, tag)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt and authenticate a single message and tag in place.
+    ///
+    /// `chunk` must have the same layout as the output of [`seal_chunk()`]: The
+    /// message is read from `chunk[TAG_SIZE..chunk.len() - POLY1305_OUTSIZE]`,
+    /// and the first [`TAG_SIZE`] and last `POLY1305_OUTSIZE` bytes are overwritten
+    /// with the encrypted tag and the Poly1305 tag.
+    ///
+    /// [`seal_chunk()`]: struct.StreamXChaCha20Poly1305.html#method.seal_chunk
+    /// [`TAG_SIZE`]: constant.TAG_SIZE.html
+    pub fn seal_chunk_in_place(
+        &mut self,
+        chunk: &mut [u8],
+        ad: Option<&[u8]>,
+        tag: StreamTag,
+    ) -> Result<(), UnknownCryptoError> {
+        if chunk.len() < ABYTES {
+            return Err(UnknownCryptoError);
+        }
+
+        let msglen = chunk.len() - ABYTES;
         let mut block = [0u8; CHACHA_BLOCKSIZE];
         let ad = ad.unwrap_or(&[0u8; 0]);
 
@@ -318,14 +362,14 @@ impl StreamXChaCha20Poly1305 {
 
         block[0] = tag.as_byte();
         chacha20_xor_stream(&self.key, &nonce, 1, &mut block)?;
-        dst_out[0] = block[0];
+        chunk[0] = block[0];
 
         if msglen != 0 {
-            chacha20_enc(&self.key, &nonce, 2, plaintext, &mut dst_out[TAG_SIZE..])?;
+            chacha20_xor_stream(&self.key, &nonce, 2, &mut chunk[TAG_SIZE..macpos])?;
         }
 
-        let mac = self.generate_auth_tag(dst_out, ad, msglen, &block, TAG_SIZE)?;
-        dst_out[macpos..(macpos + POLY1305_OUTSIZE)].copy_from_slice(mac.unprotected_as_bytes());
+        let mac = self.generate_auth_tag(chunk, ad, msglen, &block, TAG_SIZE)?;
+        chunk[macpos..(macpos + POLY1305_OUTSIZE)].copy_from_slice(mac.unprotected_as_bytes());
 
         self.advance_state(&mac, &tag)
     }
@@ -375,6 +419,49 @@ impl StreamXChaCha20Poly1305 {
 
         Ok(tag)
     }
+
+    #[allow(clippy::range_plus_one)]
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Authenticate and decrypt a single message and tag in place.
+    ///
+    /// `chunk` is the output of [`seal_chunk()`] or [`seal_chunk_in_place()`].
+    /// If authentication succeeds, the message is decrypted into
+    /// `chunk[TAG_SIZE..chunk.len() - POLY1305_OUTSIZE]`. Otherwise, `chunk` is
+    /// left unmodified.
+    ///
+    /// [`seal_chunk()`]: struct.StreamXChaCha20Poly1305.html#method.seal_chunk
+    /// [`seal_chunk_in_place()`]: struct.StreamXChaCha20Poly1305.html#method.seal_chunk_in_place
+    pub fn open_chunk_in_place(
+        &mut self,
+        chunk: &mut [u8],
+        ad: Option<&[u8]>,
+    ) -> Result<StreamTag, UnknownCryptoError> {
+        if chunk.len() < ABYTES {
+            return Err(UnknownCryptoError);
+        }
+
+        let msglen = chunk.len() - ABYTES;
+        let mut block = [0u8; CHACHA_BLOCKSIZE];
+        let ad = ad.unwrap_or(&[0u8; 0]);
+
+        let macpos = TAG_SIZE + msglen;
+        let nonce = self.get_nonce();
+
+        block[0] = chunk[0];
+        chacha20_xor_stream(&self.key, &nonce, 1, &mut block)?;
+        let tag = StreamTag::try_from(block[0])?;
+        block[0] = chunk[0];
+        let mac = self.generate_auth_tag(chunk, ad, msglen, &block, TAG_SIZE)?;
+        if !(mac == &chunk[macpos..macpos + mac.len()]) {
+            return Err(UnknownCryptoError);
+        }
+        if msglen != 0 {
+            chacha20_xor_stream(&self.key, &nonce, 2, &mut chunk[TAG_SIZE..macpos])?;
+        }
+        self.advance_state(&mac, &tag)?;
+
+        Ok(tag)
+    }
 }
 
 #[cfg(test)]
@@ -393,6 +480,21 @@ mod public {
         assert_eq!(debug, expected);
     }
 
+    #[test]
+    #[cfg(feature = "test_utils")]
+    fn test_arbitrary_stream_tag() {
+        use crate::hazardous::aead::streaming::StreamTag;
+        use core::convert::TryFrom;
+        use quickcheck::{Arbitrary, Gen};
+
+        let mut g = Gen::new(128);
+        for _ in 0..128 {
+            let tag = StreamTag::arbitrary(&mut g);
+            assert!(StreamTag::try_from(tag.as_byte()).unwrap() == tag);
+            assert!(tag.clone() == tag);
+        }
+    }
+
     #[cfg(feature = "safe_api")]
     mod proptest {
         use crate::errors::UnknownCryptoError;
@@ -400,8 +502,53 @@ mod public {
             Nonce, SecretKey, StreamTag, StreamXChaCha20Poly1305, ABYTES,
         };
         use crate::test_framework::aead_interface::*;
+        use crate::test_framework::streaming_aead_interface::*;
         use core::convert::TryFrom;
 
+        impl TestableStreamingAead for StreamXChaCha20Poly1305 {
+            type Tag = StreamTag;
+
+            const ABYTES: usize = ABYTES;
+
+            fn seal_chunk(
+                &mut self,
+                plaintext: &[u8],
+                ad: Option<&[u8]>,
+                dst_out: &mut [u8],
+                tag: StreamTag,
+            ) -> Result<(), UnknownCryptoError> {
+                self.seal_chunk(plaintext, ad, dst_out, tag)
+            }
+
+            fn open_chunk(
+                &mut self,
+                ciphertext: &[u8],
+                ad: Option<&[u8]>,
+                dst_out: &mut [u8],
+            ) -> Result<StreamTag, UnknownCryptoError> {
+                self.open_chunk(ciphertext, ad, dst_out)
+            }
+
+            fn message_tag() -> StreamTag {
+                StreamTag::MESSAGE
+            }
+
+            fn final_tag() -> StreamTag {
+                StreamTag::FINISH
+            }
+        }
+
+        #[test]
+        fn default_streaming_aead_consistency_tests() {
+            let secret_key = SecretKey::generate();
+            let nonce = Nonce::generate();
+
+            let test_runner = StreamingAeadConsistencyTester::new(|| {
+                StreamXChaCha20Poly1305::new(&secret_key, &nonce)
+            });
+            test_runner.run_all_tests();
+        }
+
         fn seal(
             sk: &SecretKey,
             nonce: &Nonce,
@@ -450,6 +597,20 @@ mod public {
             }
         }
 
+        quickcheck! {
+            fn prop_streaming_aead_consistency(data: Vec<u8>, chunking: Vec<u8>, ad: Vec<u8>) -> bool {
+                let secret_key = SecretKey::generate();
+                let nonce = Nonce::generate();
+
+                let test_runner = StreamingAeadConsistencyTester::new(|| {
+                    StreamXChaCha20Poly1305::new(&secret_key, &nonce)
+                });
+                test_runner.run_all_tests_property(&data, &chunking, &ad);
+
+                true
+            }
+        }
+
         quickcheck! {
             fn prop_tag(byte: u8) -> bool {
                 match byte {
@@ -609,6 +770,55 @@ mod private {
         assert!(StreamTag::try_from(4u8).is_err());
     }
 
+    #[test]
+    fn test_seal_open_chunk_in_place() {
+        let mut s_enc = StreamXChaCha20Poly1305::new(&SecretKey::from(KEY), &Nonce::from(NONCE));
+        let mut s_in_place =
+            StreamXChaCha20Poly1305::new(&SecretKey::from(KEY), &Nonce::from(NONCE));
+        let mut s_dec = StreamXChaCha20Poly1305::new(&SecretKey::from(KEY), &Nonce::from(NONCE));
+
+        for (len, tag) in [(DEFAULT_MSG.len(), 0u8), (0, 1), (17, 2), (1, 3)].iter() {
+            let tag = || StreamTag::try_from(*tag).unwrap();
+            let mut expected = [0u8; 51 + ABYTES];
+            s_enc
+                .seal_chunk(&DEFAULT_MSG[..*len], Some(b"ad"), &mut expected, tag())
+                .unwrap();
+
+            let mut chunk = [0u8; 51 + ABYTES];
+            chunk[TAG_SIZE..(TAG_SIZE + len)].copy_from_slice(&DEFAULT_MSG[..*len]);
+            s_in_place
+                .seal_chunk_in_place(&mut chunk[..(len + ABYTES)], Some(b"ad"), tag())
+                .unwrap();
+            assert_eq!(chunk[..], expected[..]);
+
+            // Modified chunks are rejected and left unmodified.
+            let mut modified = chunk;
+            modified[len + ABYTES - 1] ^= 1;
+            let mut s_fail = StreamXChaCha20Poly1305 {
+                key: SecretKey::from_slice(s_dec.key.unprotected_as_bytes()).unwrap(),
+                counter: s_dec.counter,
+                inonce: s_dec.inonce,
+            };
+            assert!(s_fail
+                .open_chunk_in_place(&mut modified[..(len + ABYTES)], Some(b"ad"))
+                .is_err());
+            assert_eq!(modified[..(len + ABYTES - 1)], chunk[..(len + ABYTES - 1)]);
+
+            let opened_tag = s_dec
+                .open_chunk_in_place(&mut chunk[..(len + ABYTES)], Some(b"ad"))
+                .unwrap();
+            assert_eq!(opened_tag, tag());
+            assert_eq!(chunk[TAG_SIZE..(TAG_SIZE + len)], DEFAULT_MSG[..*len]);
+        }
+
+        assert!(s_enc
+            .seal_chunk_in_place(&mut [0u8; ABYTES - 1], None, StreamTag::MESSAGE)
+            .is_err());
+        assert!(s_dec
+            .open_chunk_in_place(&mut [0u8; ABYTES - 1], None)
+            .is_err());
+    }
+
     #[test]
     fn test_seal_open_with_explicit_rekey() {
         // Encrypt stream
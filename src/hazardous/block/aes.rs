@@ -0,0 +1,737 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `secret_key`: The secret key. Its length selects AES-128 (16 bytes),
+//!   AES-192 (24 bytes) or AES-256 (32 bytes).
+//! - `block`: A single block to be encrypted/decrypted in-place.
+//! - `blocks`: One or more consecutive blocks to be encrypted/decrypted in-place.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of the `secret_key` is not 16, 24 or 32 bytes.
+//! - The length of `block` is not [`AES_BLOCKSIZE`].
+//! - `blocks` is empty or its length is not a multiple of [`AES_BLOCKSIZE`].
+//!
+//! # Security:
+//! - AES on its own only encrypts single blocks and provides no security for
+//!   messages longer than that. It is meant as a building block for modes
+//!   of operation. Prefer an AEAD, such as those in [`aead`], for encryption.
+//! - This implementation does not use lookup tables. The S-box is computed from
+//!   the inverse in GF(2^8) on bitsliced data, so that neither the execution
+//!   time nor the memory access pattern depends on the secret key or the data.
+//! - Hardware AES instructions (AES-NI, ARMv8 Cryptography Extensions) are not
+//!   used, since calling them requires `unsafe` code which orion forbids. The
+//!   portable implementation is considerably slower than such instructions.
+//! - To securely generate a strong key, use [`SecretKey::generate()`], which
+//!   generates a 32-byte key for AES-256.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::block::aes::{Aes, SecretKey, AES_BLOCKSIZE};
+//!
+//! let secret_key = SecretKey::generate();
+//! let aes = Aes::new(&secret_key)?;
+//!
+//! let mut block = [0u8; AES_BLOCKSIZE];
+//! aes.encrypt_block(&mut block)?;
+//! assert_ne!(block, [0u8; AES_BLOCKSIZE]);
+//! aes.decrypt_block(&mut block)?;
+//! assert_eq!(block, [0u8; AES_BLOCKSIZE]);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`AES_BLOCKSIZE`]: constant.AES_BLOCKSIZE.html
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+//! [`aead`]: ../../aead/index.html
+
+use crate::errors::UnknownCryptoError;
+use zeroize::Zeroize;
+
+/// The blocksize of AES.
+pub const AES_BLOCKSIZE: usize = 16;
+/// The key size for AES-128.
+pub const AES128_KEYSIZE: usize = 16;
+/// The key size for AES-192.
+pub const AES192_KEYSIZE: usize = 24;
+/// The key size for AES-256.
+pub const AES256_KEYSIZE: usize = 32;
+/// The amount of rounds for AES-256, which is the maximum of all key sizes.
+const MAX_ROUNDS: usize = 14;
+/// The amount of bytes the bitsliced S-box processes at once. Each bit-plane is
+/// a `u64`, holding a single bit from each of 64 bytes.
+const SBOX_PARALLEL_BYTES: usize = 64;
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` that AES uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is less than 16 bytes.
+    /// - `slice` is greater than 32 bytes.
+    ///
+    /// Note that [`Aes::new()`] only accepts keys of 16, 24 or 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    ///
+    /// [`Aes::new()`]: struct.Aes.html
+    (SecretKey, test_secret_key, AES128_KEYSIZE, AES256_KEYSIZE, AES256_KEYSIZE)
+}
+
+/// Bit-planes of up to 64 bytes. Bit `i` of plane `k` is bit `k` of byte `i`.
+type Planes = [u64; 8];
+
+/// Transpose up to 64 bytes into bit-planes.
+fn bitslice(bytes: &[u8]) -> Planes {
+    debug_assert!(bytes.len() <= SBOX_PARALLEL_BYTES);
+    let mut planes = [0u64; 8];
+    for (i, byte) in bytes.iter().enumerate() {
+        for (k, plane) in planes.iter_mut().enumerate() {
+            *plane |= u64::from((byte >> k) & 1) << i;
+        }
+    }
+
+    planes
+}
+
+/// Transpose bit-planes back into bytes.
+fn unbitslice(planes: &Planes, bytes: &mut [u8]) {
+    debug_assert!(bytes.len() <= SBOX_PARALLEL_BYTES);
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let mut value = 0u8;
+        for (k, plane) in planes.iter().enumerate() {
+            value |= (((plane >> i) & 1) as u8) << k;
+        }
+        *byte = value;
+    }
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1 on bit-planes.
+fn gf_mul(a: &Planes, b: &Planes) -> Planes {
+    let mut product = [0u64; 15];
+    for (i, a_plane) in a.iter().enumerate() {
+        for (j, b_plane) in b.iter().enumerate() {
+            product[i + j] ^= a_plane & b_plane;
+        }
+    }
+    // Reduce from the highest degree down, since x^8 = x^4 + x^3 + x + 1.
+    for k in (8..15).rev() {
+        let high = product[k];
+        product[k - 4] ^= high;
+        product[k - 5] ^= high;
+        product[k - 7] ^= high;
+        product[k - 8] ^= high;
+    }
+
+    let mut out = [0u64; 8];
+    out.copy_from_slice(&product[..8]);
+    product.zeroize();
+
+    out
+}
+
+/// Inversion in GF(2^8) on bit-planes, computed as a^254. Zero maps to zero.
+fn gf_inv(a: &Planes) -> Planes {
+    let a2 = gf_mul(a, a);
+    let a3 = gf_mul(&a2, a);
+    let a6 = gf_mul(&a3, &a3);
+    let a12 = gf_mul(&a6, &a6);
+    let a15 = gf_mul(&a12, &a3);
+    let a30 = gf_mul(&a15, &a15);
+    let a60 = gf_mul(&a30, &a30);
+    let a120 = gf_mul(&a60, &a60);
+    let a240 = gf_mul(&a120, &a120);
+    let a252 = gf_mul(&a240, &a12);
+
+    gf_mul(&a252, &a2)
+}
+
+/// SubBytes on up to 64 bytes.
+fn sub_bytes(bytes: &mut [u8]) {
+    let inv = gf_inv(&bitslice(bytes));
+    let mut out = [0u64; 8];
+    for (i, plane) in out.iter_mut().enumerate() {
+        *plane = inv[i] ^ inv[(i + 4) % 8] ^ inv[(i + 5) % 8] ^ inv[(i + 6) % 8] ^ inv[(i + 7) % 8];
+    }
+    // Add the affine constant 0x63.
+    out[0] = !out[0];
+    out[1] = !out[1];
+    out[5] = !out[5];
+    out[6] = !out[6];
+
+    unbitslice(&out, bytes);
+}
+
+/// InvSubBytes on up to 64 bytes.
+fn inv_sub_bytes(bytes: &mut [u8]) {
+    let planes = bitslice(bytes);
+    let mut pre = [0u64; 8];
+    for (i, plane) in pre.iter_mut().enumerate() {
+        *plane = planes[(i + 2) % 8] ^ planes[(i + 5) % 8] ^ planes[(i + 7) % 8];
+    }
+    // Add the inverse affine constant 0x05.
+    pre[0] = !pre[0];
+    pre[2] = !pre[2];
+
+    unbitslice(&gf_inv(&pre), bytes);
+}
+
+/// Multiplication by x in GF(2^8), without branching on the input.
+const fn xtime(x: u8) -> u8 {
+    (x << 1) ^ (0x1b & 0u8.wrapping_sub(x >> 7))
+}
+
+fn shift_rows(block: &mut [u8]) {
+    let mut tmp = [0u8; AES_BLOCKSIZE];
+    tmp.copy_from_slice(block);
+    for col in 0..4 {
+        for row in 0..4 {
+            block[row + 4 * col] = tmp[row + 4 * ((col + row) % 4)];
+        }
+    }
+}
+
+fn inv_shift_rows(block: &mut [u8]) {
+    let mut tmp = [0u8; AES_BLOCKSIZE];
+    tmp.copy_from_slice(block);
+    for col in 0..4 {
+        for row in 0..4 {
+            block[row + 4 * ((col + row) % 4)] = tmp[row + 4 * col];
+        }
+    }
+}
+
+fn mix_columns(block: &mut [u8]) {
+    for col in block.chunks_exact_mut(4) {
+        let (a0, a1, a2, a3) = (col[0], col[1], col[2], col[3]);
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        col[0] = a0 ^ all ^ xtime(a0 ^ a1);
+        col[1] = a1 ^ all ^ xtime(a1 ^ a2);
+        col[2] = a2 ^ all ^ xtime(a2 ^ a3);
+        col[3] = a3 ^ all ^ xtime(a3 ^ a0);
+    }
+}
+
+fn inv_mix_columns(block: &mut [u8]) {
+    // InvMixColumns is MixColumns preceded by a multiplication with 4x^2 + 5,
+    // see "The Design of Rijndael", section 4.1.3.
+    for col in block.chunks_exact_mut(4) {
+        let u = xtime(xtime(col[0] ^ col[2]));
+        let v = xtime(xtime(col[1] ^ col[3]));
+        col[0] ^= u;
+        col[1] ^= v;
+        col[2] ^= u;
+        col[3] ^= v;
+    }
+    mix_columns(block);
+}
+
+/// An AES context with an expanded key.
+pub struct Aes {
+    round_keys: [u8; AES_BLOCKSIZE * (MAX_ROUNDS + 1)],
+    rounds: usize,
+}
+
+impl Drop for Aes {
+    fn drop(&mut self) {
+        self.round_keys.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Aes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Aes {{ round_keys: [***OMITTED***], rounds: {:?} }}",
+            self.rounds
+        )
+    }
+}
+
+impl Clone for Aes {
+    fn clone(&self) -> Self {
+        Self {
+            round_keys: self.round_keys,
+            rounds: self.rounds,
+        }
+    }
+}
+
+impl Aes {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize an `Aes` context by expanding `secret_key`.
+    pub fn new(secret_key: &SecretKey) -> Result<Self, UnknownCryptoError> {
+        let key = secret_key.unprotected_as_bytes();
+        let rounds = match key.len() {
+            AES128_KEYSIZE => 10,
+            AES192_KEYSIZE => 12,
+            AES256_KEYSIZE => 14,
+            _ => return Err(UnknownCryptoError),
+        };
+
+        let mut ctx = Self {
+            round_keys: [0u8; AES_BLOCKSIZE * (MAX_ROUNDS + 1)],
+            rounds,
+        };
+
+        // Key expansion operates on 4-byte words.
+        let nk = key.len() / 4;
+        ctx.round_keys[..key.len()].copy_from_slice(key);
+        let mut rcon = 1u8;
+        for idx in nk..(4 * (rounds + 1)) {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&ctx.round_keys[(idx - 1) * 4..idx * 4]);
+            if idx % nk == 0 {
+                word.rotate_left(1);
+                sub_bytes(&mut word);
+                word[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && idx % nk == 4 {
+                sub_bytes(&mut word);
+            }
+
+            for (pos, w) in word.iter().enumerate() {
+                ctx.round_keys[idx * 4 + pos] = ctx.round_keys[(idx - nk) * 4 + pos] ^ w;
+            }
+            word.zeroize();
+        }
+
+        Ok(ctx)
+    }
+
+    /// XOR the round key for `round` into each block of `blocks`.
+    fn add_round_key(&self, round: usize, blocks: &mut [u8]) {
+        let round_key = &self.round_keys[round * AES_BLOCKSIZE..(round + 1) * AES_BLOCKSIZE];
+        for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+            xor_slices!(round_key, block);
+        }
+    }
+
+    /// Encrypt up to 64 bytes of blocks in-place, sharing the S-box evaluation between them.
+    fn encrypt_parallel(&self, blocks: &mut [u8]) {
+        debug_assert!(blocks.len() <= SBOX_PARALLEL_BYTES);
+        debug_assert!(blocks.chunks_exact(AES_BLOCKSIZE).remainder().is_empty());
+
+        self.add_round_key(0, blocks);
+        for round in 1..self.rounds {
+            sub_bytes(blocks);
+            for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+                shift_rows(block);
+                mix_columns(block);
+            }
+            self.add_round_key(round, blocks);
+        }
+        sub_bytes(blocks);
+        for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+            shift_rows(block);
+        }
+        self.add_round_key(self.rounds, blocks);
+    }
+
+    /// Decrypt up to 64 bytes of blocks in-place, sharing the S-box evaluation between them.
+    fn decrypt_parallel(&self, blocks: &mut [u8]) {
+        debug_assert!(blocks.len() <= SBOX_PARALLEL_BYTES);
+        debug_assert!(blocks.chunks_exact(AES_BLOCKSIZE).remainder().is_empty());
+
+        self.add_round_key(self.rounds, blocks);
+        for round in (1..self.rounds).rev() {
+            for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+                inv_shift_rows(block);
+            }
+            inv_sub_bytes(blocks);
+            self.add_round_key(round, blocks);
+            for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+                inv_mix_columns(block);
+            }
+        }
+        for block in blocks.chunks_exact_mut(AES_BLOCKSIZE) {
+            inv_shift_rows(block);
+        }
+        inv_sub_bytes(blocks);
+        self.add_round_key(0, blocks);
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt a single `block` in-place.
+    pub fn encrypt_block(&self, block: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if block.len() != AES_BLOCKSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.encrypt_parallel(block);
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decrypt a single `block` in-place.
+    pub fn decrypt_block(&self, block: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if block.len() != AES_BLOCKSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.decrypt_parallel(block);
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt each block of `blocks` independently and in-place. Up to four
+    /// blocks are processed at once, which is faster than calling
+    /// [`encrypt_block()`](#method.encrypt_block) for each block.
+    pub fn encrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if blocks.is_empty() || !blocks.chunks_exact(AES_BLOCKSIZE).remainder().is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        for chunk in blocks.chunks_mut(SBOX_PARALLEL_BYTES) {
+            self.encrypt_parallel(chunk);
+        }
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decrypt each block of `blocks` independently and in-place. Up to four
+    /// blocks are processed at once, which is faster than calling
+    /// [`decrypt_block()`](#method.decrypt_block) for each block.
+    pub fn decrypt_blocks(&self, blocks: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if blocks.is_empty() || !blocks.chunks_exact(AES_BLOCKSIZE).remainder().is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        for chunk in blocks.chunks_mut(SBOX_PARALLEL_BYTES) {
+            self.decrypt_parallel(chunk);
+        }
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_new_key_sizes() {
+        for len in AES128_KEYSIZE..=AES256_KEYSIZE {
+            let sk = SecretKey::from_slice(&[0u8; AES256_KEYSIZE][..len]).unwrap();
+            let res = Aes::new(&sk);
+            match len {
+                AES128_KEYSIZE | AES192_KEYSIZE | AES256_KEYSIZE => assert!(res.is_ok()),
+                _ => assert!(res.is_err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_lengths() {
+        let aes = Aes::new(&SecretKey::from_slice(&[0u8; AES256_KEYSIZE]).unwrap()).unwrap();
+
+        assert!(aes.encrypt_block(&mut [0u8; AES_BLOCKSIZE]).is_ok());
+        assert!(aes.encrypt_block(&mut [0u8; AES_BLOCKSIZE - 1]).is_err());
+        assert!(aes.encrypt_block(&mut [0u8; AES_BLOCKSIZE + 1]).is_err());
+        assert!(aes.encrypt_block(&mut [0u8; 0]).is_err());
+        assert!(aes.decrypt_block(&mut [0u8; AES_BLOCKSIZE]).is_ok());
+        assert!(aes.decrypt_block(&mut [0u8; AES_BLOCKSIZE - 1]).is_err());
+        assert!(aes.decrypt_block(&mut [0u8; AES_BLOCKSIZE + 1]).is_err());
+        assert!(aes.decrypt_block(&mut [0u8; 0]).is_err());
+
+        assert!(aes.encrypt_blocks(&mut [0u8; AES_BLOCKSIZE * 5]).is_ok());
+        assert!(aes
+            .encrypt_blocks(&mut [0u8; AES_BLOCKSIZE * 5 + 1])
+            .is_err());
+        assert!(aes.encrypt_blocks(&mut [0u8; 0]).is_err());
+        assert!(aes.decrypt_blocks(&mut [0u8; AES_BLOCKSIZE * 5]).is_ok());
+        assert!(aes
+            .decrypt_blocks(&mut [0u8; AES_BLOCKSIZE * 5 + 1])
+            .is_err());
+        assert!(aes.decrypt_blocks(&mut [0u8; 0]).is_err());
+    }
+
+    #[test]
+    fn test_blocks_same_as_block() {
+        let aes = Aes::new(&SecretKey::from_slice(&[7u8; AES128_KEYSIZE]).unwrap()).unwrap();
+        // Nine blocks: two full parallel chunks and a single block.
+        let mut blocks = [0u8; AES_BLOCKSIZE * 9];
+        for (idx, byte) in blocks.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        let mut expected = blocks;
+        for block in expected.chunks_exact_mut(AES_BLOCKSIZE) {
+            aes.encrypt_block(block).unwrap();
+        }
+
+        let mut actual = blocks;
+        aes.encrypt_blocks(&mut actual).unwrap();
+        assert_eq!(actual[..], expected[..]);
+
+        aes.decrypt_blocks(&mut actual).unwrap();
+        assert_eq!(actual[..], blocks[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let aes = Aes::new(&SecretKey::generate()).unwrap();
+        let debug = format!("{:?}", aes);
+        let expected = "Aes { round_keys: [***OMITTED***], rounds: 14 }";
+        assert_eq!(debug, expected);
+    }
+
+    // Proptests. Only executed when NOT testing no_std.
+    #[cfg(feature = "safe_api")]
+    mod proptest {
+        use super::*;
+
+        quickcheck! {
+            fn prop_encrypt_decrypt_same_input(input: Vec<u8>) -> bool {
+                let mut blocks = input;
+                blocks.truncate(blocks.len() - blocks.len() % AES_BLOCKSIZE);
+                if blocks.is_empty() {
+                    return true;
+                }
+
+                let aes = Aes::new(&SecretKey::generate()).unwrap();
+                let mut actual = blocks.clone();
+                aes.encrypt_blocks(&mut actual).unwrap();
+                aes.decrypt_blocks(&mut actual).unwrap();
+
+                actual == blocks
+            }
+        }
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    /// Compute the AES S-box using logarithm tables, independently of the
+    /// bitsliced implementation.
+    fn reference_sbox() -> [u8; 256] {
+        let mut exp = [0u8; 256];
+        let mut log = [0u8; 256];
+        let mut x = 1u8;
+        for (idx, exp_value) in exp.iter_mut().enumerate().take(255) {
+            *exp_value = x;
+            log[x as usize] = idx as u8;
+            // Multiply by the generator 3.
+            x ^= xtime(x);
+        }
+
+        let mut sbox = [0u8; 256];
+        for value in 0..256usize {
+            let inv = if value == 0 {
+                0
+            } else {
+                exp[(255 - log[value] as usize) % 255]
+            };
+            sbox[value] = inv
+                ^ inv.rotate_left(1)
+                ^ inv.rotate_left(2)
+                ^ inv.rotate_left(3)
+                ^ inv.rotate_left(4)
+                ^ 0x63;
+        }
+
+        sbox
+    }
+
+    #[test]
+    fn test_sub_bytes_all_values() {
+        let reference = reference_sbox();
+        let mut bytes = [0u8; 256];
+        for (idx, byte) in bytes.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+
+        for chunk in bytes.chunks_mut(SBOX_PARALLEL_BYTES) {
+            sub_bytes(chunk);
+        }
+        assert_eq!(bytes[..], reference[..]);
+        assert_eq!(bytes[0x00], 0x63);
+        assert_eq!(bytes[0x53], 0xed);
+
+        for chunk in bytes.chunks_mut(SBOX_PARALLEL_BYTES) {
+            inv_sub_bytes(chunk);
+        }
+        for (idx, byte) in bytes.iter().enumerate() {
+            assert_eq!(*byte, idx as u8);
+        }
+    }
+
+    #[test]
+    fn test_sub_bytes_partial_planes() {
+        let mut one = [0x53u8];
+        sub_bytes(&mut one);
+        assert_eq!(one, [0xed]);
+        inv_sub_bytes(&mut one);
+        assert_eq!(one, [0x53]);
+    }
+
+    #[test]
+    fn test_shift_rows_inverse() {
+        let mut block = [0u8; AES_BLOCKSIZE];
+        for (idx, byte) in block.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        shift_rows(&mut block);
+        assert_eq!(
+            block,
+            [0, 5, 10, 15, 4, 9, 14, 3, 8, 13, 2, 7, 12, 1, 6, 11]
+        );
+        inv_shift_rows(&mut block);
+        for (idx, byte) in block.iter().enumerate() {
+            assert_eq!(*byte, idx as u8);
+        }
+    }
+
+    #[test]
+    fn test_mix_columns() {
+        // Test vector from FIPS 197, Appendix B, round 1.
+        let mut block = [
+            0xd4, 0xbf, 0x5d, 0x30, 0xe0, 0xb4, 0x52, 0xae, 0xb8, 0x41, 0x11, 0xf1, 0x1e, 0x27,
+            0x98, 0xe5,
+        ];
+        let expected = [
+            0x04, 0x66, 0x81, 0xe5, 0xe0, 0xcb, 0x19, 0x9a, 0x48, 0xf8, 0xd3, 0x7a, 0x28, 0x06,
+            0x26, 0x4c,
+        ];
+        let original = block;
+        mix_columns(&mut block);
+        assert_eq!(block, expected);
+        inv_mix_columns(&mut block);
+        assert_eq!(block, original);
+    }
+
+    #[test]
+    fn test_key_expansion() {
+        // FIPS 197, Appendix A.1.
+        let sk = SecretKey::from_slice(&[
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ])
+        .unwrap();
+        let aes = Aes::new(&sk).unwrap();
+        assert_eq!(aes.rounds, 10);
+        assert_eq!(
+            aes.round_keys[16..32],
+            [
+                0xa0, 0xfa, 0xfe, 0x17, 0x88, 0x54, 0x2c, 0xb1, 0x23, 0xa3, 0x39, 0x39, 0x2a, 0x6c,
+                0x76, 0x05
+            ]
+        );
+        assert_eq!(
+            aes.round_keys[160..176],
+            [
+                0xd0, 0x14, 0xf9, 0xa8, 0xc9, 0xee, 0x25, 0x89, 0xe1, 0x3f, 0x0c, 0xc8, 0xb6, 0x63,
+                0x0c, 0xa6
+            ]
+        );
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    fn encrypt_decrypt_test(key: &[u8], plaintext: &[u8], expected: &[u8]) {
+        let aes = Aes::new(&SecretKey::from_slice(key).unwrap()).unwrap();
+        let mut block = [0u8; AES_BLOCKSIZE];
+        block.copy_from_slice(plaintext);
+
+        aes.encrypt_block(&mut block).unwrap();
+        assert_eq!(block[..], expected[..]);
+        aes.decrypt_block(&mut block).unwrap();
+        assert_eq!(block[..], plaintext[..]);
+    }
+
+    const FIPS197_PLAINTEXT: [u8; 16] = [
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee,
+        0xff,
+    ];
+
+    fn fips197_key(len: usize) -> [u8; AES256_KEYSIZE] {
+        let mut key = [0u8; AES256_KEYSIZE];
+        for (idx, byte) in key.iter_mut().enumerate().take(len) {
+            *byte = idx as u8;
+        }
+        key
+    }
+
+    #[test]
+    fn fips197_c1_aes128() {
+        encrypt_decrypt_test(
+            &fips197_key(16)[..16],
+            &FIPS197_PLAINTEXT,
+            &[
+                0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+                0xc5, 0x5a,
+            ],
+        );
+    }
+
+    #[test]
+    fn fips197_c2_aes192() {
+        encrypt_decrypt_test(
+            &fips197_key(24)[..24],
+            &FIPS197_PLAINTEXT,
+            &[
+                0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d,
+                0x71, 0x91,
+            ],
+        );
+    }
+
+    #[test]
+    fn fips197_c3_aes256() {
+        encrypt_decrypt_test(
+            &fips197_key(32),
+            &FIPS197_PLAINTEXT,
+            &[
+                0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+                0x60, 0x89,
+            ],
+        );
+    }
+
+    #[test]
+    fn sp800_38a_f1_1_ecb_aes128() {
+        encrypt_decrypt_test(
+            &[
+                0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+                0x4f, 0x3c,
+            ],
+            &[
+                0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+                0x17, 0x2a,
+            ],
+            &[
+                0x3a, 0xd7, 0x7b, 0xb4, 0x0d, 0x7a, 0x36, 0x60, 0xa8, 0x9e, 0xca, 0xf3, 0x24, 0x66,
+                0xef, 0x97,
+            ],
+        );
+    }
+}
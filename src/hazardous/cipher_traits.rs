@@ -0,0 +1,454 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! This module implements the traits of the RustCrypto [`cipher`] crate for
+//! orion's ChaCha20, XChaCha20 and AES implementations, so that they can be
+//! used in generic code written against those traits, such as modes of
+//! operation and MACs built on a block cipher.
+//!
+//! - [`ChaCha20`] and [`XChaCha20`] implement `KeySizeUser`, `IvSizeUser`,
+//!   `KeyIvInit`, `StreamCipher` and `StreamCipherSeek`.
+//! - [`Aes128`], [`Aes192`] and [`Aes256`] implement `KeySizeUser`, `KeyInit`,
+//!   `BlockSizeUser`, `BlockCipher`, `BlockEncrypt` and `BlockDecrypt`.
+//!
+//! This module is only available with the `cipher` feature, which requires
+//! Rust 1.56 or later.
+//!
+//! # Security:
+//! - The security considerations of [`chacha20`], [`xchacha20`] and [`aes`]
+//!   apply to these types as well.
+//! - `StreamCipher::apply_keystream()` panics and `try_apply_keystream()`
+//!   returns an error, if the keystream would exceed the 32-bit block counter.
+//!
+//! # Example:
+//! ```rust
+//! use cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+//! use orion::hazardous::cipher_traits::ChaCha20;
+//!
+//! let key = [0x42; 32];
+//! let nonce = [0x24; 12];
+//! let mut buffer = *b"Some data to encrypt";
+//!
+//! let mut cipher = ChaCha20::new(&key.into(), &nonce.into());
+//! cipher.apply_keystream(&mut buffer);
+//!
+//! cipher.seek(0u32);
+//! cipher.apply_keystream(&mut buffer);
+//! assert_eq!(&buffer, b"Some data to encrypt");
+//! ```
+//! [`cipher`]: https://docs.rs/cipher
+//! [`ChaCha20`]: type.ChaCha20.html
+//! [`XChaCha20`]: type.XChaCha20.html
+//! [`Aes128`]: struct.Aes128.html
+//! [`Aes192`]: struct.Aes192.html
+//! [`Aes256`]: struct.Aes256.html
+//! [`chacha20`]: ../stream/chacha20/index.html
+//! [`xchacha20`]: ../stream/xchacha20/index.html
+//! [`aes`]: ../block/aes/index.html
+
+use crate::hazardous::block::aes;
+use crate::hazardous::stream::{chacha20, xchacha20};
+use cipher::consts::{U1, U12, U16, U24, U32, U4, U64};
+use cipher::inout::InOut;
+use cipher::{
+    Block, BlockBackend, BlockCipher, BlockClosure, BlockDecrypt, BlockEncrypt, BlockSizeUser, Iv,
+    IvSizeUser, Key, KeyInit, KeyIvInit, KeySizeUser, ParBlocks, ParBlocksSizeUser, StreamBackend,
+    StreamCipherCore, StreamCipherCoreWrapper, StreamCipherSeekCore, StreamClosure,
+};
+
+/// The core of ChaCha20 and XChaCha20: an IETF ChaCha20 state and the block
+/// counter of the next keystream block.
+struct ChaChaCore {
+    ctx: chacha20::ChaCha20,
+    counter: u32,
+}
+
+impl ChaChaCore {
+    fn new(key: &[u8], nonce: &[u8]) -> Self {
+        Self {
+            // Cannot panic, since the key and nonce sizes are guaranteed by
+            // the trait implementations calling this.
+            ctx: chacha20::ChaCha20::new(key, nonce, true).unwrap(),
+            counter: 0,
+        }
+    }
+
+    fn remaining_blocks(&self) -> Option<usize> {
+        Some((u32::MAX - self.counter) as usize)
+    }
+}
+
+/// Generates keystream blocks for `cipher`'s `StreamCipherCoreWrapper`.
+struct ChaChaBackend<'a>(&'a mut ChaChaCore);
+
+impl BlockSizeUser for ChaChaBackend<'_> {
+    type BlockSize = U64;
+}
+
+impl ParBlocksSizeUser for ChaChaBackend<'_> {
+    type ParBlocksSize = U1;
+}
+
+impl StreamBackend for ChaChaBackend<'_> {
+    fn gen_ks_block(&mut self, block: &mut Block<Self>) {
+        self.0.ctx.keystream_block(self.0.counter, block);
+        self.0.counter = self.0.counter.wrapping_add(1);
+    }
+}
+
+macro_rules! impl_chacha_core {
+    ($(#[$meta:meta])* ($core:ident, $name:ident, $iv_size:ty, $new:expr)) => {
+        $(#[$meta])*
+        ///
+        /// This is the core used by the `cipher` crate's `StreamCipherCoreWrapper`.
+        pub struct $core(ChaChaCore);
+
+        impl core::fmt::Debug for $core {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{} {{ ctx: [***OMITTED***] }}", stringify!($core))
+            }
+        }
+
+        impl KeySizeUser for $core {
+            type KeySize = U32;
+        }
+
+        impl IvSizeUser for $core {
+            type IvSize = $iv_size;
+        }
+
+        impl KeyIvInit for $core {
+            fn new(key: &Key<Self>, iv: &Iv<Self>) -> Self {
+                let new: fn(&[u8], &[u8]) -> ChaChaCore = $new;
+                Self(new(key, iv))
+            }
+        }
+
+        impl BlockSizeUser for $core {
+            type BlockSize = U64;
+        }
+
+        impl StreamCipherCore for $core {
+            fn remaining_blocks(&self) -> Option<usize> {
+                self.0.remaining_blocks()
+            }
+
+            fn process_with_backend(&mut self, f: impl StreamClosure<BlockSize = Self::BlockSize>) {
+                f.call(&mut ChaChaBackend(&mut self.0));
+            }
+        }
+
+        impl StreamCipherSeekCore for $core {
+            type Counter = u32;
+
+            fn get_block_pos(&self) -> Self::Counter {
+                self.0.counter
+            }
+
+            fn set_block_pos(&mut self, pos: Self::Counter) {
+                self.0.counter = pos;
+            }
+        }
+
+        $(#[$meta])*
+        pub type $name = StreamCipherCoreWrapper<$core>;
+    };
+}
+
+impl_chacha_core!(
+    /// IETF ChaCha20 with a 96-bit nonce and a 32-bit block counter.
+    (ChaCha20Core, ChaCha20, U12, ChaChaCore::new)
+);
+
+impl_chacha_core!(
+    /// XChaCha20 with a 192-bit nonce and a 32-bit block counter.
+    (XChaCha20Core, XChaCha20, U24, |key, nonce| {
+        // Cannot panic, since the key and nonce sizes are guaranteed by the
+        // trait implementations.
+        let (subkey, ietf_nonce) = xchacha20::subkey_and_nonce(
+            &xchacha20::SecretKey::from_slice(key).unwrap(),
+            &xchacha20::Nonce::from_slice(nonce).unwrap(),
+        );
+        ChaChaCore::new(subkey.unprotected_as_bytes(), ietf_nonce.as_ref())
+    })
+);
+
+/// Encrypts or decrypts blocks for `cipher`'s block cipher traits.
+struct AesBackend<'a> {
+    ctx: &'a aes::Aes,
+    encrypt: bool,
+}
+
+impl BlockSizeUser for AesBackend<'_> {
+    type BlockSize = U16;
+}
+
+impl ParBlocksSizeUser for AesBackend<'_> {
+    // The bitsliced S-box processes four blocks at once.
+    type ParBlocksSize = U4;
+}
+
+impl AesBackend<'_> {
+    fn process(&self, blocks: &mut [u8]) {
+        // Cannot panic, since `blocks` is one or more whole blocks.
+        if self.encrypt {
+            self.ctx.encrypt_blocks(blocks).unwrap();
+        } else {
+            self.ctx.decrypt_blocks(blocks).unwrap();
+        }
+    }
+}
+
+impl BlockBackend for AesBackend<'_> {
+    fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+        let mut tmp = block.clone_in();
+        self.process(&mut tmp);
+        *block.get_out() = tmp;
+    }
+
+    fn proc_par_blocks(&mut self, mut blocks: InOut<'_, '_, ParBlocks<Self>>) {
+        let mut tmp = [0u8; aes::AES_BLOCKSIZE * 4];
+        for (dst, src) in tmp
+            .chunks_exact_mut(aes::AES_BLOCKSIZE)
+            .zip(blocks.get_in())
+        {
+            dst.copy_from_slice(src);
+        }
+        self.process(&mut tmp);
+        for (dst, src) in blocks
+            .get_out()
+            .iter_mut()
+            .zip(tmp.chunks_exact(aes::AES_BLOCKSIZE))
+        {
+            dst.copy_from_slice(src);
+        }
+        zeroize::Zeroize::zeroize(&mut tmp);
+    }
+}
+
+macro_rules! impl_aes {
+    ($(#[$meta:meta])* ($name:ident, $key_size:ty)) => {
+        #[derive(Clone, Debug)]
+        $(#[$meta])*
+        pub struct $name(aes::Aes);
+
+        impl KeySizeUser for $name {
+            type KeySize = $key_size;
+        }
+
+        impl KeyInit for $name {
+            fn new(key: &Key<Self>) -> Self {
+                // Cannot panic, since the key size is guaranteed by `KeySize`.
+                let secret_key = aes::SecretKey::from_slice(key).unwrap();
+                Self(aes::Aes::new(&secret_key).unwrap())
+            }
+        }
+
+        impl BlockSizeUser for $name {
+            type BlockSize = U16;
+        }
+
+        impl BlockCipher for $name {}
+
+        impl BlockEncrypt for $name {
+            fn encrypt_with_backend(&self, f: impl BlockClosure<BlockSize = Self::BlockSize>) {
+                f.call(&mut AesBackend {
+                    ctx: &self.0,
+                    encrypt: true,
+                });
+            }
+        }
+
+        impl BlockDecrypt for $name {
+            fn decrypt_with_backend(&self, f: impl BlockClosure<BlockSize = Self::BlockSize>) {
+                f.call(&mut AesBackend {
+                    ctx: &self.0,
+                    encrypt: false,
+                });
+            }
+        }
+    };
+}
+
+impl_aes!(
+    /// AES-128.
+    (Aes128, U16)
+);
+
+impl_aes!(
+    /// AES-192.
+    (Aes192, U24)
+);
+
+impl_aes!(
+    /// AES-256.
+    (Aes256, U32)
+);
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use cipher::generic_array::GenericArray;
+    use cipher::{StreamCipher, StreamCipherSeek};
+
+    #[test]
+    fn test_chacha20_same_as_orion() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 12];
+        let data = [0xAAu8; 1000];
+
+        let mut expected = [0u8; 1000];
+        chacha20::encrypt(
+            &chacha20::SecretKey::from_slice(&key).unwrap(),
+            &chacha20::Nonce::from_slice(&nonce).unwrap(),
+            0,
+            &data,
+            &mut expected,
+        )
+        .unwrap();
+
+        // All at once, and in uneven chunks.
+        let mut actual = data;
+        ChaCha20::new(&key.into(), &nonce.into()).apply_keystream(&mut actual);
+        assert_eq!(actual[..], expected[..]);
+
+        let mut actual = data;
+        let mut cipher = ChaCha20::new(&key.into(), &nonce.into());
+        for chunk in actual.chunks_mut(7) {
+            cipher.apply_keystream(chunk);
+        }
+        assert_eq!(actual[..], expected[..]);
+    }
+
+    #[test]
+    fn test_xchacha20_same_as_orion() {
+        let key = [0x42u8; 32];
+        let nonce = [0x24u8; 24];
+        let data = [0xAAu8; 300];
+
+        let mut expected = [0u8; 300];
+        xchacha20::encrypt(
+            &xchacha20::SecretKey::from_slice(&key).unwrap(),
+            &xchacha20::Nonce::from_slice(&nonce).unwrap(),
+            1,
+            &data,
+            &mut expected,
+        )
+        .unwrap();
+
+        let mut actual = data;
+        let mut cipher = XChaCha20::new(&key.into(), &nonce.into());
+        cipher.seek(64u32);
+        cipher.apply_keystream(&mut actual);
+        assert_eq!(actual[..], expected[..]);
+    }
+
+    #[test]
+    fn test_chacha20_seek() {
+        let key = [0x01u8; 32];
+        let nonce = [0x02u8; 12];
+        let mut cipher = ChaCha20::new(&key.into(), &nonce.into());
+
+        let mut full = [0u8; 200];
+        cipher.apply_keystream(&mut full);
+        assert_eq!(cipher.current_pos::<u64>(), 200);
+
+        // Seeking to the middle of a block.
+        let mut partial = [0u8; 100];
+        cipher.seek(100u64);
+        cipher.apply_keystream(&mut partial);
+        assert_eq!(partial[..], full[100..]);
+    }
+
+    #[test]
+    fn test_chacha20_counter_exhausted() {
+        let mut cipher = ChaCha20::new(&[0u8; 32].into(), &[0u8; 12].into());
+        cipher.seek(u64::from(u32::MAX - 1) * 64);
+        assert!(cipher.try_apply_keystream(&mut [0u8; 64]).is_ok());
+        assert!(cipher.try_apply_keystream(&mut [0u8; 1]).is_err());
+    }
+
+    #[test]
+    fn test_aes_same_as_orion() {
+        fn check<C: KeyInit + BlockEncrypt + BlockDecrypt + BlockSizeUser<BlockSize = U16>>(
+            key: &[u8],
+        ) {
+            let ctx = aes::Aes::new(&aes::SecretKey::from_slice(key).unwrap()).unwrap();
+            let cipher = C::new_from_slice(key).unwrap();
+
+            // Five blocks, to cover both parallel and single blocks.
+            let mut expected = [0x11u8; 80];
+            ctx.encrypt_blocks(&mut expected).unwrap();
+
+            let mut blocks = [GenericArray::from([0x11u8; 16]); 5];
+            cipher.encrypt_blocks(&mut blocks);
+            for (block, expected) in blocks.iter().zip(expected.chunks(16)) {
+                assert_eq!(block[..], expected[..]);
+            }
+
+            let mut block = GenericArray::from([0x11u8; 16]);
+            cipher.encrypt_block(&mut block);
+            assert_eq!(block[..], expected[..16]);
+
+            cipher.decrypt_blocks(&mut blocks);
+            cipher.decrypt_block(&mut block);
+            assert!(blocks.iter().all(|b| b[..] == [0x11u8; 16]));
+            assert_eq!(block[..], [0x11u8; 16]);
+        }
+
+        check::<Aes128>(&[0x0Fu8; 16]);
+        check::<Aes192>(&[0x0Fu8; 24]);
+        check::<Aes256>(&[0x0Fu8; 32]);
+        assert!(Aes128::new_from_slice(&[0u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_aes_fips_197() {
+        // FIPS 197, Appendix C.1.
+        let key: [u8; 16] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let mut block = GenericArray::from([
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ]);
+        let expected = [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a,
+        ];
+
+        Aes128::new(&key.into()).encrypt_block(&mut block);
+        assert_eq!(block[..], expected[..]);
+    }
+
+    #[test]
+    fn test_debug_impl() {
+        let cipher = ChaCha20Core::new(&[0u8; 32].into(), &[0u8; 12].into());
+        assert_eq!(
+            format!("{:?}", cipher),
+            "ChaCha20Core { ctx: [***OMITTED***] }"
+        );
+    }
+}
@@ -0,0 +1,417 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Schnorr signatures over secp256k1, as specified in [BIP 340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki).
+//!
+//! Public keys are the 32-byte x-coordinate of a point, which is implicitly
+//! the one with an even y-coordinate. Signatures are 64 bytes.
+//!
+//! # Parameters:
+//! - `secret_key`: The 32-byte secret key, a big-endian integer between 1 and
+//!   the group order minus one.
+//! - `public_key`: The 32-byte public key.
+//! - `message`: The message to be signed or verified.
+//! - `aux_rand`: 32 bytes of fresh randomness, mixed into the nonce.
+//! - `signature`: The signature to be verified.
+//! - `dst_out`: Destination buffer for the public key or the signature.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes, is zero or is not less than the group
+//!   order.
+//! - `public_key` is not 32 bytes or is not the x-coordinate of a point on
+//!   the curve.
+//! - `aux_rand` is not 32 bytes.
+//! - `signature` is not 64 bytes or is not a valid signature of `message`
+//!   under `public_key`.
+//! - `dst_out` is not 32 bytes for a public key, or 64 bytes for a signature.
+//!
+//! # Security:
+//! - Signing is deterministic in `secret_key`, `message` and `aux_rand`.
+//!   Fresh randomness for `aux_rand` protects against side-channel attacks
+//!   on the nonce derivation, but signing remains secure if it is not
+//!   random. It must never be derived from the secret key in another way.
+//! - The secret key is used in constant time. Verification only uses public
+//!   values and makes no such guarantee.
+//! - Signatures are verified after signing, to guard against faults.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::bip340;
+//!
+//! let secret_key = [0x42u8; bip340::SECRET_KEYSIZE];
+//! let mut public_key = [0u8; bip340::PUBLIC_KEYSIZE];
+//! bip340::public_key(&secret_key, &mut public_key)?;
+//!
+//! let mut aux_rand = [0u8; bip340::AUX_RANDSIZE];
+//! orion::util::secure_rand_bytes(&mut aux_rand)?;
+//!
+//! let mut signature = [0u8; bip340::SIGNATURESIZE];
+//! bip340::sign(&secret_key, b"Message", &aux_rand, &mut signature)?;
+//! assert!(bip340::verify(&public_key, b"Message", &signature).is_ok());
+//! assert!(bip340::verify(&public_key, b"Another message", &signature).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+
+use super::secp256k1::{Point, Scalar};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+use zeroize::Zeroize;
+
+/// The size of a secret key.
+pub const SECRET_KEYSIZE: usize = 32;
+/// The size of a public key.
+pub const PUBLIC_KEYSIZE: usize = 32;
+/// The size of a signature.
+pub const SIGNATURESIZE: usize = 64;
+/// The size of the auxiliary randomness used when signing.
+pub const AUX_RANDSIZE: usize = 32;
+
+/// The tagged hash of the concatenation of `parts`, which is
+/// SHA256(SHA256(tag) || SHA256(tag) || parts).
+pub(crate) fn tagged_hash(
+    tag: &[u8],
+    parts: &[&[u8]],
+) -> Result<[u8; SHA256_OUTSIZE], UnknownCryptoError> {
+    let mut tag_hash = [0u8; SHA256_OUTSIZE];
+    Sha256::digest_into(tag, &mut tag_hash)?;
+
+    let mut state = Sha256::new();
+    state.update(&tag_hash)?;
+    state.update(&tag_hash)?;
+    for part in parts.iter() {
+        state.update(part)?;
+    }
+
+    let mut digest = [0u8; SHA256_OUTSIZE];
+    state.finalize_into(&mut digest)?;
+
+    Ok(digest)
+}
+
+/// Parse a secret key into a non-zero scalar.
+pub(crate) fn parse_secret_key(secret_key: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+    if secret_key.len() != SECRET_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut bytes = [0u8; SECRET_KEYSIZE];
+    bytes.copy_from_slice(secret_key);
+    let (scalar, is_canonical) = Scalar::from_bytes(&bytes);
+    bytes.zeroize();
+
+    if bool::from(!is_canonical | scalar.is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(scalar)
+}
+
+/// The challenge e = int(hash(r || public_key || message)) mod n.
+pub(crate) fn challenge(
+    r: &[u8],
+    public_key: &[u8],
+    message: &[u8],
+) -> Result<Scalar, UnknownCryptoError> {
+    let hash = tagged_hash(b"BIP0340/challenge", &[r, public_key, message])?;
+    Ok(Scalar::from_bytes_reduced(&hash))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the public key of `secret_key`.
+pub fn public_key(secret_key: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != PUBLIC_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut d = parse_secret_key(secret_key)?;
+    let public_key = Point::mul_base(&d).x_bytes();
+    d.zeroize();
+    dst_out.copy_from_slice(&public_key?);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `message` with `secret_key`.
+pub fn sign(
+    secret_key: &[u8],
+    message: &[u8],
+    aux_rand: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if aux_rand.len() != AUX_RANDSIZE || dst_out.len() != SIGNATURESIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut d = parse_secret_key(secret_key)?;
+    let public_point = Point::mul_base(&d);
+    let public_key = public_point.x_bytes()?;
+    if !public_point.has_even_y()? {
+        d = d.neg();
+    }
+
+    let mut t = d.to_bytes();
+    let aux_hash = tagged_hash(b"BIP0340/aux", &[aux_rand])?;
+    xor_slices!(aux_hash, t);
+    let mut k =
+        Scalar::from_bytes_reduced(&tagged_hash(b"BIP0340/nonce", &[&t, &public_key, message])?);
+    t.zeroize();
+
+    if bool::from(k.is_zero()) {
+        d.zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    let r_point = Point::mul_base(&k);
+    if !r_point.has_even_y()? {
+        k = k.neg();
+    }
+    let r = r_point.x_bytes()?;
+    let e = challenge(&r, &public_key, message)?;
+    let s = k.add(&e.mul(&d));
+    k.zeroize();
+    d.zeroize();
+
+    dst_out[..32].copy_from_slice(&r);
+    dst_out[32..].copy_from_slice(&s.to_bytes());
+
+    if verify(&public_key, message, dst_out).is_err() {
+        dst_out.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `message` under `public_key`.
+pub fn verify(
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    if public_key.len() != PUBLIC_KEYSIZE || signature.len() != SIGNATURESIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut x = [0u8; 32];
+    x.copy_from_slice(public_key);
+    let public_point = Point::lift_x(&x)?;
+
+    // Checking that r is the x-coordinate of R below also rejects r >= p.
+    let mut s_bytes = [0u8; 32];
+    s_bytes.copy_from_slice(&signature[32..]);
+    let (s, is_canonical) = Scalar::from_bytes(&s_bytes);
+    if !bool::from(is_canonical) {
+        return Err(UnknownCryptoError);
+    }
+
+    let e = challenge(&signature[..32], public_key, message)?;
+    let r_point = Point::mul_base(&s).add(&public_point.mul(&e).neg());
+    if r_point.is_identity() || !r_point.has_even_y()? || r_point.x_bytes()? != signature[..32] {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_lengths() {
+        let sk = [0x42u8; SECRET_KEYSIZE];
+        let mut pk = [0u8; PUBLIC_KEYSIZE];
+        let mut sig = [0u8; SIGNATURESIZE];
+        assert!(public_key(&sk[..31], &mut pk).is_err());
+        assert!(public_key(&sk, &mut pk[..31]).is_err());
+        public_key(&sk, &mut pk).unwrap();
+
+        assert!(sign(&sk[..31], b"", &[0u8; 32], &mut sig).is_err());
+        assert!(sign(&sk, b"", &[0u8; 31], &mut sig).is_err());
+        assert!(sign(&sk, b"", &[0u8; 32], &mut sig[..63]).is_err());
+        sign(&sk, b"", &[0u8; 32], &mut sig).unwrap();
+
+        assert!(verify(&pk[..31], b"", &sig).is_err());
+        assert!(verify(&pk, b"", &sig[..63]).is_err());
+        assert!(verify(&pk, b"", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_secret_keys() {
+        let mut pk = [0u8; PUBLIC_KEYSIZE];
+        assert!(public_key(&[0u8; SECRET_KEYSIZE], &mut pk).is_err());
+        // The group order.
+        let order = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+            .unwrap();
+        assert!(public_key(&order, &mut pk).is_err());
+        assert!(public_key(&[0xffu8; SECRET_KEYSIZE], &mut pk).is_err());
+    }
+
+    #[test]
+    fn test_modified_signature() {
+        let sk = [0x42u8; SECRET_KEYSIZE];
+        let mut pk = [0u8; PUBLIC_KEYSIZE];
+        let mut sig = [0u8; SIGNATURESIZE];
+        public_key(&sk, &mut pk).unwrap();
+        sign(&sk, b"Message", &[0u8; 32], &mut sig).unwrap();
+
+        for i in 0..SIGNATURESIZE {
+            let mut modified = sig;
+            modified[i] ^= 1;
+            assert!(verify(&pk, b"Message", &modified).is_err());
+        }
+
+        let mut other_pk = [0u8; PUBLIC_KEYSIZE];
+        public_key(&[0x43u8; SECRET_KEYSIZE], &mut other_pk).unwrap();
+        assert!(verify(&other_pk, b"Message", &sig).is_err());
+    }
+
+    #[test]
+    fn test_aux_rand_changes_signature() {
+        let sk = [0x42u8; SECRET_KEYSIZE];
+        let mut sig_1 = [0u8; SIGNATURESIZE];
+        let mut sig_2 = [0u8; SIGNATURESIZE];
+        sign(&sk, b"Message", &[0u8; 32], &mut sig_1).unwrap();
+        sign(&sk, b"Message", &[1u8; 32], &mut sig_2).unwrap();
+        assert_ne!(sig_1[..], sig_2[..]);
+    }
+}
+
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    // Test vectors from BIP 340.
+
+    fn sign_test(sk: &str, pk: &str, aux_rand: &str, message: &str, expected: &str) {
+        let sk = hex::decode(sk).unwrap();
+        let message = hex::decode(message).unwrap();
+        let mut actual_pk = [0u8; PUBLIC_KEYSIZE];
+        public_key(&sk, &mut actual_pk).unwrap();
+        assert_eq!(actual_pk[..], hex::decode(pk).unwrap()[..]);
+
+        let mut sig = [0u8; SIGNATURESIZE];
+        sign(&sk, &message, &hex::decode(aux_rand).unwrap(), &mut sig).unwrap();
+        assert_eq!(sig[..], hex::decode(expected).unwrap()[..]);
+        assert!(verify(&actual_pk, &message, &sig).is_ok());
+    }
+
+    fn verify_test(pk: &str, message: &str, sig: &str, is_valid: bool) {
+        let result = verify(
+            &hex::decode(pk).unwrap(),
+            &hex::decode(message).unwrap(),
+            &hex::decode(sig).unwrap(),
+        );
+        assert_eq!(result.is_ok(), is_valid);
+    }
+
+    const PK_1: &str = "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659";
+    const MSG_1: &str = "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89";
+    const SIG_1: &str = "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE33418906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A";
+
+    #[test]
+    fn test_vector_0() {
+        sign_test(
+            "0000000000000000000000000000000000000000000000000000000000000003",
+            "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA821525F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+        );
+    }
+
+    #[test]
+    fn test_vector_1() {
+        sign_test(
+            "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+            PK_1,
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            MSG_1,
+            SIG_1,
+        );
+    }
+
+    #[test]
+    fn test_vector_2() {
+        sign_test(
+            "C90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B14E5C9",
+            "DD308AFEC5777E13121FA72B9CC1B7CC0139715309B086C960E18FD969774EB8",
+            "C87AA53824B4D7AE2EB035A2B5BBBCCC080E76CDC6D1692C4B0B62D798E6D906",
+            "7E2D58D8B3BCDF1ABADEC7829054F90DDA9805AAB56C77333024B9D0A508B75C",
+            "5831AAEED7B44BB74E5EAB94BA9D4294C49BCF2A60728D8B4C200F50DD313C1BAB745879A5AD954A72C45A91C3A51D3C7ADEA98D82F8481E0E1E03674A6F3FB7",
+        );
+    }
+
+    #[test]
+    fn test_verify_invalid() {
+        verify_test(PK_1, MSG_1, SIG_1, true);
+        // Negated message.
+        verify_test(
+            PK_1,
+            "DBC0E59577BDA8E8AE81EE9BDD00D4D8D5CB1C1B4E9FFCD6EE7B23C7DE6B3B75",
+            SIG_1,
+            false,
+        );
+        // s equal to the group order.
+        let mut sig = hex::decode(SIG_1).unwrap();
+        sig[32..].copy_from_slice(
+            &hex::decode("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141")
+                .unwrap(),
+        );
+        assert!(verify(
+            &hex::decode(PK_1).unwrap(),
+            &hex::decode(MSG_1).unwrap(),
+            &sig
+        )
+        .is_err());
+        // r equal to the field prime.
+        let mut sig = hex::decode(SIG_1).unwrap();
+        sig[..32].copy_from_slice(
+            &hex::decode("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F")
+                .unwrap(),
+        );
+        assert!(verify(
+            &hex::decode(PK_1).unwrap(),
+            &hex::decode(MSG_1).unwrap(),
+            &sig
+        )
+        .is_err());
+        // Public key not on the curve.
+        verify_test(
+            "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+            MSG_1,
+            SIG_1,
+            false,
+        );
+        // Public key equal to the field prime.
+        verify_test(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F",
+            MSG_1,
+            SIG_1,
+            false,
+        );
+    }
+}
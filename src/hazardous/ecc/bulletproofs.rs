@@ -0,0 +1,690 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Bulletproofs range proofs over ristretto255, as described in
+//! [Bulletproofs: Short Proofs for Confidential Transactions and More](https://eprint.iacr.org/2017/1066).
+//!
+//! A range proof shows that one or more [Pedersen commitments] commit to
+//! values in the range `[0, 2^bits)`, without revealing anything else about
+//! the values. Proving the range of several commitments at once is called
+//! aggregation, and yields a proof that is only slightly larger than a
+//! proof for a single commitment.
+//!
+//! The proof is made non-interactive with a transcript based on SHA512,
+//! which binds it to the number of bits, the commitments and `context`. The
+//! generators are derived with the element derivation function of
+//! ristretto255 from SHA512 hashes of their index. Proofs are therefore not
+//! compatible with other Bulletproofs implementations.
+//!
+//! A proof is `32 * (9 + 2 * log2(bits * parties))` bytes, which is 672 bytes
+//! for a single 64-bit range proof. Use [`proof_size()`] to compute it.
+//!
+//! # Parameters:
+//! - `bits`: The size of the range, which must be 8, 16, 32 or 64.
+//! - `values`: The values that are committed to.
+//! - `blindings`: The blinding factors of the commitments to `values`.
+//! - `commitments`: The commitments whose range is proven.
+//! - `context`: Data that the proof is bound to, such as a protocol name.
+//! - `proof`: The range proof to verify.
+//! - `dst_out`: Destination buffer for the range proof.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `bits` is not 8, 16, 32 or 64.
+//! - The number of values, or commitments, is not a power of two between 1
+//!   and [`MAX_PARTIES`].
+//! - `values` and `blindings` do not have the same length.
+//! - Any of `values` is not less than `2^bits`.
+//! - `dst_out` or `proof` is not [`proof_size()`] bytes.
+//! - Any of `commitments` is not a valid encoding.
+//! - `proof` is not a valid range proof for `commitments`, `bits` and
+//!   `context`.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - The proof reveals nothing about the values beyond their range, as long
+//!   as the blinding factors are secret and random.
+//! - The commitments are passed to the verifier separately from the proof.
+//!   Make sure they are the commitments that the range is meant to be proven
+//!   for.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::bulletproofs;
+//! use orion::hazardous::ecc::pedersen::{self, Blinding};
+//!
+//! let blinding = Blinding::generate();
+//! let commitment = pedersen::commit(1337, &blinding)?;
+//!
+//! let mut proof = vec![0u8; bulletproofs::proof_size(64, 1)?];
+//! bulletproofs::prove(64, &[1337], &[&blinding], b"Example", &mut proof)?;
+//! assert!(bulletproofs::verify(64, &[&commitment], b"Example", &proof).is_ok());
+//! assert!(bulletproofs::verify(64, &[&commitment], b"Other", &proof).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [Pedersen commitments]: ../pedersen/index.html
+//! [`proof_size()`]: fn.proof_size.html
+//! [`MAX_PARTIES`]: constant.MAX_PARTIES.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use super::pedersen::{self, Commitment};
+use super::ristretto255::{
+    scalar_from_bytes, scalar_from_bytes_wide, scalar_to_bytes, RistrettoPoint, Scalar, POINTSIZE,
+};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha512::Sha512;
+
+#[cfg(feature = "safe_api")]
+use super::pedersen::Blinding;
+#[cfg(feature = "safe_api")]
+use subtle::ConditionallySelectable;
+#[cfg(feature = "safe_api")]
+use zeroize::Zeroize;
+
+/// The maximum number of values in an aggregated range proof.
+pub const MAX_PARTIES: usize = 64;
+
+/// The size of an encoded scalar or point in a proof.
+const ELEMENTSIZE: usize = POINTSIZE;
+
+/// A Fiat-Shamir transcript, which derives challenges from the SHA512 hash of
+/// all the messages appended so far.
+struct Transcript(Sha512);
+
+impl Transcript {
+    fn new(bits: usize, parties: usize, context: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut transcript = Self(Sha512::new());
+        transcript.append(b"dom-sep", b"orion bulletproofs range proof v1")?;
+        transcript.append(b"context", context)?;
+        transcript.append(b"bits", &(bits as u64).to_le_bytes())?;
+        transcript.append(b"parties", &(parties as u64).to_le_bytes())?;
+
+        Ok(transcript)
+    }
+
+    /// Append `message`, which is framed with its length and `label`.
+    fn append(&mut self, label: &[u8], message: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.0.update(&(label.len() as u64).to_le_bytes())?;
+        self.0.update(label)?;
+        self.0.update(&(message.len() as u64).to_le_bytes())?;
+        self.0.update(message)
+    }
+
+    fn append_point(
+        &mut self,
+        label: &[u8],
+        point: &RistrettoPoint,
+    ) -> Result<(), UnknownCryptoError> {
+        self.append(label, &point.to_bytes())
+    }
+
+    fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) -> Result<(), UnknownCryptoError> {
+        self.append(label, &scalar_to_bytes(scalar))
+    }
+
+    /// Derive a non-zero challenge, which is then appended to the transcript.
+    fn challenge(&mut self, label: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+        self.append(label, &[])?;
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(self.0.clone().finalize()?.as_ref());
+        self.append(b"challenge", &wide)?;
+
+        let challenge = scalar_from_bytes_wide(&wide);
+        if bool::from(challenge.is_zero()) {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(challenge)
+    }
+}
+
+/// Check the parameters of a proof and return log2(bits * parties).
+fn rounds(bits: usize, parties: usize) -> Result<usize, UnknownCryptoError> {
+    if !(bits == 8 || bits == 16 || bits == 32 || bits == 64)
+        || parties == 0
+        || parties > MAX_PARTIES
+        || !parties.is_power_of_two()
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok((bits * parties).trailing_zeros() as usize)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The size of a range proof for `parties` values of `bits` bits.
+pub fn proof_size(bits: usize, parties: usize) -> Result<usize, UnknownCryptoError> {
+    Ok(ELEMENTSIZE * (9 + 2 * rounds(bits, parties)?))
+}
+
+/// The generators G_i and H_i, for i in 0..n.
+fn generators(n: usize) -> Result<(Vec<RistrettoPoint>, Vec<RistrettoPoint>), UnknownCryptoError> {
+    let derive = |label: &[u8], i: usize| -> Result<RistrettoPoint, UnknownCryptoError> {
+        let mut state = Sha512::new();
+        state.update(b"orion bulletproofs generator ")?;
+        state.update(label)?;
+        state.update(&(i as u64).to_le_bytes())?;
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(state.finalize()?.as_ref());
+
+        Ok(RistrettoPoint::from_uniform_bytes(&hash))
+    };
+
+    let mut g = Vec::with_capacity(n);
+    let mut h = Vec::with_capacity(n);
+    for i in 0..n {
+        g.push(derive(b"G", i)?);
+        h.push(derive(b"H", i)?);
+    }
+
+    Ok((g, h))
+}
+
+/// 1, x, x^2, ..., x^(n - 1).
+fn powers(x: &Scalar, n: usize) -> Vec<Scalar> {
+    let mut out = Vec::with_capacity(n);
+    let mut current = Scalar::one();
+    for _ in 0..n {
+        out.push(current);
+        current = current.mul(x);
+    }
+
+    out
+}
+
+#[cfg(feature = "safe_api")]
+/// The inner product of `a` and `b`.
+fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter()
+        .zip(b.iter())
+        .fold(Scalar::ZERO, |acc, (a_i, b_i)| acc.add(&a_i.mul(b_i)))
+}
+
+/// Fold the generators of an inner product argument in half, as
+/// lo * x_lo + hi * x_hi.
+fn fold_generators(points: &mut Vec<RistrettoPoint>, x_lo: &Scalar, x_hi: &Scalar) {
+    let half = points.len() / 2;
+    for i in 0..half {
+        points[i] = points[i]
+            .mul_vartime(x_lo)
+            .add(&points[half + i].mul_vartime(x_hi));
+    }
+    points.truncate(half);
+}
+
+#[cfg(feature = "safe_api")]
+/// A uniformly random scalar.
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    crate::util::secure_rand_bytes(&mut bytes).unwrap();
+    let scalar = scalar_from_bytes_wide(&bytes);
+    bytes.iter_mut().zeroize();
+
+    scalar
+}
+
+#[cfg(feature = "safe_api")]
+/// Σ scalars_i * points_i, in constant time.
+fn multiscalar_mul(scalars: &[Scalar], points: &[RistrettoPoint]) -> RistrettoPoint {
+    scalars
+        .iter()
+        .zip(points.iter())
+        .fold(RistrettoPoint::IDENTITY, |acc, (s, p)| acc.add(&p.mul(s)))
+}
+
+#[cfg(feature = "safe_api")]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Prove that `values`, committed to with `blindings`, are less than
+/// `2^bits`. Not available in `no_std` context.
+pub fn prove(
+    bits: usize,
+    values: &[u64],
+    blindings: &[&Blinding],
+    context: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let parties = values.len();
+    let rounds = rounds(bits, parties)?;
+    if blindings.len() != parties || dst_out.len() != proof_size(bits, parties)? {
+        return Err(UnknownCryptoError);
+    }
+    if bits < 64 && values.iter().any(|v| *v >> bits != 0) {
+        return Err(UnknownCryptoError);
+    }
+
+    let n = bits * parties;
+    let (g, h) = generators(n)?;
+    let b = RistrettoPoint::GENERATOR;
+    let b_blinding = pedersen::blinding_generator()?;
+
+    let mut transcript = Transcript::new(bits, parties, context)?;
+    for (value, blinding) in values.iter().zip(blindings.iter()) {
+        transcript.append(b"V", pedersen::commit(*value, blinding)?.as_ref())?;
+    }
+
+    // a_L holds the bits of all values, and a_R = a_L - 1.
+    let mut a_l: Vec<Scalar> = Vec::with_capacity(n);
+    for value in values.iter() {
+        for i in 0..bits {
+            a_l.push(Scalar::from_u64((value >> i) & 1));
+        }
+    }
+    let mut a_r: Vec<Scalar> = a_l.iter().map(|bit| bit.sub(&Scalar::one())).collect();
+
+    // A = alpha * B_blinding + <a_L, G> + <a_R, H>, where each term of the
+    // inner products is either G_i or -H_i.
+    let mut alpha = random_scalar();
+    let mut a_point = b_blinding.mul(&alpha);
+    for (i, bit) in a_l.iter().enumerate() {
+        a_point = a_point.add(&RistrettoPoint::conditional_select(
+            &h[i].neg(),
+            &g[i],
+            !bit.is_zero(),
+        ));
+    }
+
+    let mut s_l: Vec<Scalar> = (0..n).map(|_| random_scalar()).collect();
+    let mut s_r: Vec<Scalar> = (0..n).map(|_| random_scalar()).collect();
+    let mut rho = random_scalar();
+    let s_point = b_blinding
+        .mul(&rho)
+        .add(&multiscalar_mul(&s_l, &g))
+        .add(&multiscalar_mul(&s_r, &h));
+
+    transcript.append_point(b"A", &a_point)?;
+    transcript.append_point(b"S", &s_point)?;
+    let y = transcript.challenge(b"y")?;
+    let z = transcript.challenge(b"z")?;
+
+    // l(X) = (a_L - z) + s_L * X
+    // r(X) = y^i * (a_R + z + s_R * X) + z^(2 + j) * 2^k, for i = j * bits + k
+    let y_powers = powers(&y, n);
+    let z_powers = powers(&z, parties + 3);
+    let two_powers = powers(&Scalar::from_u64(2), bits);
+    let mut l0: Vec<Scalar> = a_l.iter().map(|a| a.sub(&z)).collect();
+    let mut r0: Vec<Scalar> = Vec::with_capacity(n);
+    let mut r1: Vec<Scalar> = Vec::with_capacity(n);
+    for i in 0..n {
+        let z_two = z_powers[2 + i / bits].mul(&two_powers[i % bits]);
+        r0.push(y_powers[i].mul(&a_r[i].add(&z)).add(&z_two));
+        r1.push(y_powers[i].mul(&s_r[i]));
+    }
+
+    // t(X) = <l(X), r(X)> = t0 + t1 * X + t2 * X^2
+    let mut t1 = inner_product(&l0, &r1).add(&inner_product(&s_l, &r0));
+    let mut t2 = inner_product(&s_l, &r1);
+    let mut tau1 = random_scalar();
+    let mut tau2 = random_scalar();
+    let t1_point = b.mul(&t1).add(&b_blinding.mul(&tau1));
+    let t2_point = b.mul(&t2).add(&b_blinding.mul(&tau2));
+
+    transcript.append_point(b"T1", &t1_point)?;
+    transcript.append_point(b"T2", &t2_point)?;
+    let x = transcript.challenge(b"x")?;
+
+    let mut tau_x = tau2.mul(&x.square()).add(&tau1.mul(&x));
+    for (j, blinding) in blindings.iter().enumerate() {
+        tau_x = tau_x.add(&z_powers[2 + j].mul(&blinding.to_scalar()));
+    }
+    let mu = alpha.add(&rho.mul(&x));
+
+    let mut l: Vec<Scalar> = l0
+        .iter()
+        .zip(s_l.iter())
+        .map(|(l0_i, s_i)| l0_i.add(&s_i.mul(&x)))
+        .collect();
+    let mut r: Vec<Scalar> = r0
+        .iter()
+        .zip(r1.iter())
+        .map(|(r0_i, r1_i)| r0_i.add(&r1_i.mul(&x)))
+        .collect();
+    let t_hat = inner_product(&l, &r);
+
+    transcript.append_scalar(b"taux", &tau_x)?;
+    transcript.append_scalar(b"mu", &mu)?;
+    transcript.append_scalar(b"t_hat", &t_hat)?;
+    let w = transcript.challenge(b"w")?;
+    let q = b.mul(&w);
+
+    dst_out[..32].copy_from_slice(&a_point.to_bytes());
+    dst_out[32..64].copy_from_slice(&s_point.to_bytes());
+    dst_out[64..96].copy_from_slice(&t1_point.to_bytes());
+    dst_out[96..128].copy_from_slice(&t2_point.to_bytes());
+    dst_out[128..160].copy_from_slice(&scalar_to_bytes(&tau_x));
+    dst_out[160..192].copy_from_slice(&scalar_to_bytes(&mu));
+    dst_out[192..224].copy_from_slice(&scalar_to_bytes(&t_hat));
+
+    // The inner product argument, for <l, G> + <r, H'> + <l, r> * Q, where
+    // H'_i = y^-i * H_i.
+    let y_inv_powers = powers(&y.invert(), n);
+    let mut g_prime = g;
+    let mut h_prime: Vec<RistrettoPoint> = h
+        .iter()
+        .zip(y_inv_powers.iter())
+        .map(|(h_i, y_inv)| h_i.mul_vartime(y_inv))
+        .collect();
+
+    for round in 0..rounds {
+        let half = l.len() / 2;
+        let (l_lo, l_hi) = l.split_at(half);
+        let (r_lo, r_hi) = r.split_at(half);
+        let c_l = inner_product(l_lo, r_hi);
+        let c_r = inner_product(l_hi, r_lo);
+
+        let l_point = multiscalar_mul(l_lo, &g_prime[half..])
+            .add(&multiscalar_mul(r_hi, &h_prime[..half]))
+            .add(&q.mul(&c_l));
+        let r_point = multiscalar_mul(l_hi, &g_prime[..half])
+            .add(&multiscalar_mul(r_lo, &h_prime[half..]))
+            .add(&q.mul(&c_r));
+
+        transcript.append_point(b"L", &l_point)?;
+        transcript.append_point(b"R", &r_point)?;
+        let u = transcript.challenge(b"u")?;
+        let u_inv = u.invert();
+
+        let offset = 224 + 64 * round;
+        dst_out[offset..offset + 32].copy_from_slice(&l_point.to_bytes());
+        dst_out[offset + 32..offset + 64].copy_from_slice(&r_point.to_bytes());
+
+        for i in 0..half {
+            l[i] = l[i].mul(&u).add(&l[half + i].mul(&u_inv));
+            r[i] = r[i].mul(&u_inv).add(&r[half + i].mul(&u));
+        }
+        l[half..].iter_mut().for_each(Zeroize::zeroize);
+        r[half..].iter_mut().for_each(Zeroize::zeroize);
+        l.truncate(half);
+        r.truncate(half);
+        fold_generators(&mut g_prime, &u_inv, &u);
+        fold_generators(&mut h_prime, &u, &u_inv);
+    }
+
+    let offset = 224 + 64 * rounds;
+    dst_out[offset..offset + 32].copy_from_slice(&scalar_to_bytes(&l[0]));
+    dst_out[offset + 32..].copy_from_slice(&scalar_to_bytes(&r[0]));
+
+    for secrets in [
+        &mut a_l, &mut a_r, &mut s_l, &mut s_r, &mut l0, &mut l, &mut r,
+    ]
+    .iter_mut()
+    {
+        secrets.iter_mut().for_each(Zeroize::zeroize);
+    }
+    for secret in [&mut alpha, &mut rho, &mut t1, &mut t2, &mut tau1, &mut tau2].iter_mut() {
+        secret.zeroize();
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `proof` shows that `commitments` commit to values less than
+/// `2^bits`.
+pub fn verify(
+    bits: usize,
+    commitments: &[&Commitment],
+    context: &[u8],
+    proof: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let parties = commitments.len();
+    let rounds = rounds(bits, parties)?;
+    if proof.len() != proof_size(bits, parties)? {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut chunks = proof.chunks_exact(ELEMENTSIZE).map(|chunk| {
+        let mut bytes = [0u8; ELEMENTSIZE];
+        bytes.copy_from_slice(chunk);
+        bytes
+    });
+    let mut next = || chunks.next().ok_or(UnknownCryptoError);
+
+    let mut transcript = Transcript::new(bits, parties, context)?;
+    let mut v = Vec::with_capacity(parties);
+    for commitment in commitments.iter() {
+        v.push(pedersen::decode(commitment)?);
+        transcript.append(b"V", commitment.as_ref())?;
+    }
+
+    let a_point = RistrettoPoint::from_bytes(&next()?)?;
+    let s_point = RistrettoPoint::from_bytes(&next()?)?;
+    transcript.append_point(b"A", &a_point)?;
+    transcript.append_point(b"S", &s_point)?;
+    let y = transcript.challenge(b"y")?;
+    let z = transcript.challenge(b"z")?;
+
+    let t1_point = RistrettoPoint::from_bytes(&next()?)?;
+    let t2_point = RistrettoPoint::from_bytes(&next()?)?;
+    transcript.append_point(b"T1", &t1_point)?;
+    transcript.append_point(b"T2", &t2_point)?;
+    let x = transcript.challenge(b"x")?;
+
+    let tau_x = scalar_from_bytes(&next()?)?;
+    let mu = scalar_from_bytes(&next()?)?;
+    let t_hat = scalar_from_bytes(&next()?)?;
+    transcript.append_scalar(b"taux", &tau_x)?;
+    transcript.append_scalar(b"mu", &mu)?;
+    transcript.append_scalar(b"t_hat", &t_hat)?;
+    let w = transcript.challenge(b"w")?;
+
+    let n = bits * parties;
+    let (g, h) = generators(n)?;
+    let b = RistrettoPoint::GENERATOR;
+    let b_blinding = pedersen::blinding_generator()?;
+    let q = b.mul_vartime(&w);
+
+    let y_powers = powers(&y, n);
+    let z_powers = powers(&z, parties + 3);
+    let two_powers = powers(&Scalar::from_u64(2), bits);
+
+    // t_hat * B + tau_x * B_blinding must equal
+    // Σ z^(2 + j) * V_j + delta(y, z) * B + x * T1 + x^2 * T2, where
+    // delta(y, z) = (z - z^2) * <1, y^n> - Σ z^(3 + j) * <1, 2^bits>.
+    let sum_y = y_powers.iter().fold(Scalar::ZERO, |acc, y_i| acc.add(y_i));
+    let sum_two = two_powers.iter().fold(Scalar::ZERO, |acc, t| acc.add(t));
+    let mut delta = z.sub(&z_powers[2]).mul(&sum_y);
+    let mut expected = t1_point
+        .mul_vartime(&x)
+        .add(&t2_point.mul_vartime(&x.square()));
+    for (j, v_j) in v.iter().enumerate() {
+        delta = delta.sub(&z_powers[3 + j].mul(&sum_two));
+        expected = expected.add(&v_j.mul_vartime(&z_powers[2 + j]));
+    }
+    expected = expected.add(&b.mul_vartime(&delta));
+    let actual = b.mul_vartime(&t_hat).add(&b_blinding.mul_vartime(&tau_x));
+    if !bool::from(actual.equals(&expected)) {
+        return Err(UnknownCryptoError);
+    }
+
+    // P = A + x * S - mu * B_blinding - z * <1, G> +
+    //     Σ (z + z^(2 + j) * 2^k * y^-i) * H_i, for i = j * bits + k,
+    // which must equal <l, G> + <r, H'> with H'_i = y^-i * H_i.
+    let y_inv_powers = powers(&y.invert(), n);
+    let mut p = a_point
+        .add(&s_point.mul_vartime(&x))
+        .sub(&b_blinding.mul_vartime(&mu))
+        .add(&q.mul_vartime(&t_hat));
+    let mut h_prime = Vec::with_capacity(n);
+    for i in 0..n {
+        let z_two = z_powers[2 + i / bits].mul(&two_powers[i % bits]);
+        let h_scalar = z.add(&z_two.mul(&y_inv_powers[i]));
+        p = p
+            .sub(&g[i].mul_vartime(&z))
+            .add(&h[i].mul_vartime(&h_scalar));
+        h_prime.push(h[i].mul_vartime(&y_inv_powers[i]));
+    }
+
+    let mut g_prime = g;
+    for _ in 0..rounds {
+        let l_point = RistrettoPoint::from_bytes(&next()?)?;
+        let r_point = RistrettoPoint::from_bytes(&next()?)?;
+        transcript.append_point(b"L", &l_point)?;
+        transcript.append_point(b"R", &r_point)?;
+        let u = transcript.challenge(b"u")?;
+        let u_inv = u.invert();
+
+        p = p
+            .add(&l_point.mul_vartime(&u.square()))
+            .add(&r_point.mul_vartime(&u_inv.square()));
+        fold_generators(&mut g_prime, &u_inv, &u);
+        fold_generators(&mut h_prime, &u, &u_inv);
+    }
+
+    let a = scalar_from_bytes(&next()?)?;
+    let b_final = scalar_from_bytes(&next()?)?;
+    let expected = g_prime[0]
+        .mul_vartime(&a)
+        .add(&h_prime[0].mul_vartime(&b_final))
+        .add(&q.mul_vartime(&a.mul(&b_final)));
+
+    if bool::from(p.equals(&expected)) {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+    use crate::hazardous::ecc::pedersen::{commit, Blinding};
+
+    fn prove_and_commit(bits: usize, values: &[u64], context: &[u8]) -> (Vec<Commitment>, Vec<u8>) {
+        let blindings: Vec<Blinding> = values.iter().map(|_| Blinding::generate()).collect();
+        let blinding_refs: Vec<&Blinding> = blindings.iter().collect();
+        let commitments: Vec<Commitment> = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(v, r)| commit(*v, r).unwrap())
+            .collect();
+
+        let mut proof = vec![0u8; proof_size(bits, values.len()).unwrap()];
+        prove(bits, values, &blinding_refs, context, &mut proof).unwrap();
+
+        (commitments, proof)
+    }
+
+    #[test]
+    fn test_proof_size() {
+        assert_eq!(proof_size(64, 1).unwrap(), 672);
+        assert_eq!(proof_size(64, 2).unwrap(), 736);
+        assert_eq!(proof_size(8, 1).unwrap(), 32 * 15);
+        assert_eq!(proof_size(32, 4).unwrap(), 32 * 23);
+        assert!(proof_size(0, 1).is_err());
+        assert!(proof_size(7, 1).is_err());
+        assert!(proof_size(128, 1).is_err());
+        assert!(proof_size(64, 0).is_err());
+        assert!(proof_size(64, 3).is_err());
+        assert!(proof_size(64, MAX_PARTIES).is_ok());
+        assert!(proof_size(64, MAX_PARTIES * 2).is_err());
+    }
+
+    #[test]
+    fn test_single_64_bit() {
+        for value in [0, 1, 1337, u64::MAX].iter() {
+            let (commitments, proof) = prove_and_commit(64, &[*value], b"test");
+            assert!(verify(64, &[&commitments[0]], b"test", &proof).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_aggregated() {
+        let (c, proof) = prove_and_commit(32, &[7, u64::from(u32::MAX)], b"test");
+        assert!(verify(32, &[&c[0], &c[1]], b"test", &proof).is_ok());
+        // The order of the commitments matters.
+        assert!(verify(32, &[&c[1], &c[0]], b"test", &proof).is_err());
+        assert!(verify(32, &[&c[0]], b"test", &proof).is_err());
+        assert!(verify(64, &[&c[0], &c[1]], b"test", &proof).is_err());
+
+        let (c, proof) = prove_and_commit(8, &[0, 255, 1, 128], b"");
+        assert!(verify(8, &[&c[0], &c[1], &c[2], &c[3]], b"", &proof).is_ok());
+    }
+
+    #[test]
+    fn test_value_out_of_range() {
+        let blinding = Blinding::generate();
+        let mut proof = vec![0u8; proof_size(8, 1).unwrap()];
+        assert!(prove(8, &[256], &[&blinding], b"", &mut proof).is_err());
+        assert!(prove(8, &[255], &[&blinding], b"", &mut proof).is_ok());
+
+        let mut proof = vec![0u8; proof_size(32, 1).unwrap()];
+        assert!(prove(32, &[1 << 32], &[&blinding], b"", &mut proof).is_err());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let blinding = Blinding::generate();
+        let size = proof_size(8, 1).unwrap();
+        let mut proof = vec![0u8; size];
+        assert!(prove(8, &[1], &[&blinding], b"", &mut proof[..size - 1]).is_err());
+        assert!(prove(8, &[1], &[], b"", &mut proof).is_err());
+        assert!(prove(8, &[1, 2], &[&blinding], b"", &mut proof).is_err());
+        assert!(prove(8, &[], &[], b"", &mut proof).is_err());
+        assert!(prove(12, &[1], &[&blinding], b"", &mut proof).is_err());
+
+        let commitment = commit(1, &blinding).unwrap();
+        prove(8, &[1], &[&blinding], b"", &mut proof).unwrap();
+        assert!(verify(8, &[&commitment], b"", &proof).is_ok());
+        assert!(verify(8, &[&commitment], b"", &proof[..proof.len() - 1]).is_err());
+        assert!(verify(8, &[], b"", &proof).is_err());
+        let invalid = Commitment::from_slice(&[0xffu8; 32]).unwrap();
+        assert!(verify(8, &[&invalid], b"", &proof).is_err());
+    }
+
+    #[test]
+    fn test_wrong_commitment_or_context() {
+        let (commitments, proof) = prove_and_commit(8, &[42], b"context");
+        assert!(verify(8, &[&commitments[0]], b"context", &proof).is_ok());
+        assert!(verify(8, &[&commitments[0]], b"other", &proof).is_err());
+
+        let other = commit(42, &Blinding::generate()).unwrap();
+        assert!(verify(8, &[&other], b"context", &proof).is_err());
+    }
+
+    #[test]
+    fn test_modified_proof() {
+        let (commitments, proof) = prove_and_commit(8, &[42], b"");
+        for i in (0..proof.len()).step_by(7) {
+            let mut modified = proof.clone();
+            modified[i] ^= 1;
+            assert!(verify(8, &[&commitments[0]], b"", &modified).is_err());
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_commitment_rejected() {
+        // A valid 8-bit proof for 200 does not verify against a commitment to
+        // 256 + 200 with the same blinding factor.
+        let blinding = Blinding::generate();
+        let mut proof = vec![0u8; proof_size(8, 1).unwrap()];
+        prove(8, &[200], &[&blinding], b"", &mut proof).unwrap();
+        assert!(verify(8, &[&commit(200, &blinding).unwrap()], b"", &proof).is_ok());
+        assert!(verify(8, &[&commit(456, &blinding).unwrap()], b"", &proof).is_err());
+    }
+}
@@ -0,0 +1,619 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Low-level operations on the Montgomery form of Curve25519, as used by
+//! [X25519](https://tools.ietf.org/html/rfc7748), and conversion of Ed25519
+//! keys to X25519 keys. These are building blocks for protocols that go beyond
+//! plain X25519 key agreement, such as VXEdDSA or key blinding. For plain
+//! X25519 key agreement, use [`x25519`](../x25519/index.html).
+//!
+//! Points are represented by their 32-byte encoded u-coordinate and scalars by
+//! 32-byte little-endian integers:
+//! - [`clamp_scalar()`] clamps a scalar as specified in RFC 7748.
+//! - [`scalarmult()`] multiplies a point by a scalar, and [`scalarmult_base()`]
+//!   multiplies the basepoint u = 9 by a scalar. Neither clamps the scalar, so
+//!   `scalarmult()` with a clamped scalar is the X25519 function.
+//! - [`validate_point()`] checks that a point is canonically encoded and does
+//!   not have small order.
+//!
+//! Ed25519 keys are converted using the birational map between the twisted
+//! Edwards form of Curve25519, which Ed25519 uses, and its Montgomery form.
+//! This lets a single identity key be used both for signing and for key
+//! agreement, as some protocols require. A public key is converted by mapping
+//! the Edwards y-coordinate to the Montgomery u-coordinate,
+//! u = (1 + y) / (1 - y). A secret key is converted by hashing the 32-byte
+//! Ed25519 seed with SHA-512 and clamping the first 32 bytes of the digest,
+//! which is the scalar Ed25519 derives from the seed. The converted keys
+//! therefore form a matching X25519 key pair.
+//!
+//! # Parameters:
+//! - `scalar`: A scalar.
+//! - `point`: The encoded u-coordinate of a point.
+//! - `ed25519_public_key`: An encoded Ed25519 public key.
+//! - `ed25519_seed`: The 32-byte seed of an Ed25519 secret key.
+//! - `dst_out`: Destination buffer for the resulting point or key.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `scalar`, `point`, `ed25519_public_key`, `ed25519_seed` or
+//!   `dst_out` is not 32 bytes.
+//! - The result of [`scalarmult()`] or [`scalarmult_base()`] is the all-zero
+//!   point, which happens when `point` has small order or `scalar` is a
+//!   multiple of the group order.
+//! - [`validate_point()`] is given a point whose encoding is not canonical or
+//!   which has small order.
+//! - `ed25519_public_key` is not a canonical encoding of a point on the curve.
+//! - `ed25519_public_key` is a point of small order, or it does not lie in the
+//!   prime-order subgroup.
+//!
+//! # Security:
+//! - These functions are a lot easier to misuse than X25519 key agreement.
+//!   Unclamped scalars, in particular, do not clear the cofactor and can leak
+//!   information about the scalar when multiplied with attacker-chosen points.
+//! - [`scalarmult()`] accepts u-coordinates of points on the twist of
+//!   Curve25519, and non-canonical encodings, as RFC 7748 requires. Use
+//!   [`validate_point()`] where the protocol requires stricter checks.
+//! - Scalar multiplication runs in constant time with respect to the scalar.
+//! - Using the same key pair for two different primitives is only safe if the
+//!   protocol has been analysed for it. Prefer separate keys where possible.
+//! - The conversion is one-way. The Montgomery u-coordinate does not carry the
+//!   sign of the Edwards x-coordinate, so an X25519 public key cannot be
+//!   converted back into a unique Ed25519 public key.
+//! - `ed25519_seed` is the 32-byte seed, not the 64-byte expanded secret key
+//!   that some libraries store, which is the seed followed by the public key.
+//! - Public keys are validated, including a check that they lie in the
+//!   prime-order subgroup. This check is not constant time, which is fine for
+//!   public keys, but does not make it suitable for secret points.
+//! - Secret scalars and converted secret keys should be zeroized when no
+//!   longer needed.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::curve25519;
+//!
+//! let mut alice_secret = [0x4a; curve25519::SCALARSIZE];
+//! let mut bob_secret = [0x3f; curve25519::SCALARSIZE];
+//! curve25519::clamp_scalar(&mut alice_secret)?;
+//! curve25519::clamp_scalar(&mut bob_secret)?;
+//!
+//! let mut alice_public = [0u8; curve25519::POINTSIZE];
+//! let mut bob_public = [0u8; curve25519::POINTSIZE];
+//! curve25519::scalarmult_base(&alice_secret, &mut alice_public)?;
+//! curve25519::scalarmult_base(&bob_secret, &mut bob_public)?;
+//! curve25519::validate_point(&bob_public)?;
+//!
+//! let mut alice_shared = [0u8; curve25519::POINTSIZE];
+//! let mut bob_shared = [0u8; curve25519::POINTSIZE];
+//! curve25519::scalarmult(&alice_secret, &bob_public, &mut alice_shared)?;
+//! curve25519::scalarmult(&bob_secret, &alice_public, &mut bob_shared)?;
+//! assert_eq!(alice_shared, bob_shared);
+//!
+//! // The Ed25519 key pair from RFC 8032, section 7.1, test 1.
+//! let ed25519_seed = [
+//!     0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c,
+//!     0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae,
+//!     0x7f, 0x60,
+//! ];
+//! let ed25519_public_key = [
+//!     0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+//!     0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+//!     0x51, 0x1a,
+//! ];
+//!
+//! let mut x25519_secret_key = [0u8; curve25519::X25519_KEYSIZE];
+//! let mut x25519_public_key = [0u8; curve25519::X25519_KEYSIZE];
+//! curve25519::ed25519_seed_to_x25519(&ed25519_seed, &mut x25519_secret_key)?;
+//! curve25519::ed25519_public_key_to_x25519(&ed25519_public_key, &mut x25519_public_key)?;
+//!
+//! let mut derived_public_key = [0u8; curve25519::POINTSIZE];
+//! curve25519::scalarmult_base(&x25519_secret_key, &mut derived_public_key)?;
+//! assert_eq!(derived_public_key, x25519_public_key);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`clamp_scalar()`]: fn.clamp_scalar.html
+//! [`scalarmult()`]: fn.scalarmult.html
+//! [`scalarmult_base()`]: fn.scalarmult_base.html
+//! [`validate_point()`]: fn.validate_point.html
+
+use super::edwards25519::EdwardsPoint;
+use super::field25519::FieldElement;
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha512::{Sha512, SHA512_OUTSIZE};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+/// The size of a scalar.
+pub const SCALARSIZE: usize = 32;
+/// The size of an encoded point.
+pub const POINTSIZE: usize = 32;
+/// The size of an Ed25519 public key.
+pub const ED25519_PUBLIC_KEYSIZE: usize = 32;
+/// The size of an Ed25519 seed.
+pub const ED25519_SEEDSIZE: usize = 32;
+/// The size of an X25519 public or secret key.
+pub const X25519_KEYSIZE: usize = 32;
+
+/// The encoded basepoint, u = 9.
+const BASEPOINT: [u8; POINTSIZE] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Multiply the point with u-coordinate `u` by `scalar`, using the Montgomery
+/// ladder from [RFC 7748](https://tools.ietf.org/html/rfc7748#section-5). All
+/// bits of `scalar` are used.
+fn ladder(scalar: &[u8], u: &FieldElement) -> FieldElement {
+    let mut x2 = FieldElement::ONE;
+    let mut z2 = FieldElement::ZERO;
+    let mut x3 = *u;
+    let mut z3 = FieldElement::ONE;
+    let mut swap = Choice::from(0);
+
+    for t in (0..SCALARSIZE * 8).rev() {
+        let bit = Choice::from((scalar[t / 8] >> (t % 8)) & 1);
+        swap ^= bit;
+        FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+        FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+        swap = bit;
+
+        let a = x2.add(&z2);
+        let aa = a.square();
+        let b = x2.sub(&z2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x3.add(&z3);
+        let d = x3.sub(&z3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+
+        x3 = da.add(&cb).square();
+        z3 = u.mul(&da.sub(&cb).square());
+        x2 = aa.mul(&bb);
+        z2 = e.mul(&aa.add(&FieldElement::A24.mul(&e)));
+    }
+
+    FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+    FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+
+    x2.mul(&z2.invert())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Clamp `scalar` in-place, as specified in [RFC 7748](https://tools.ietf.org/html/rfc7748#section-5).
+pub fn clamp_scalar(scalar: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if scalar.len() != SCALARSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Multiply `point` by `scalar`, writing the resulting point to `dst_out`.
+pub fn scalarmult(
+    scalar: &[u8],
+    point: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if scalar.len() != SCALARSIZE || point.len() != POINTSIZE || dst_out.len() != POINTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut encoding = [0u8; POINTSIZE];
+    encoding.copy_from_slice(point);
+    let result = ladder(scalar, &FieldElement::from_bytes(&encoding));
+    if bool::from(result.is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    dst_out.copy_from_slice(&result.to_bytes());
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Multiply the basepoint by `scalar`, writing the resulting point to `dst_out`.
+pub fn scalarmult_base(scalar: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    scalarmult(scalar, &BASEPOINT, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Check that `point` is canonically encoded and does not have small order on
+/// Curve25519 or its twist.
+pub fn validate_point(point: &[u8]) -> Result<(), UnknownCryptoError> {
+    if point.len() != POINTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut encoding = [0u8; POINTSIZE];
+    encoding.copy_from_slice(point);
+    let u = FieldElement::from_bytes(&encoding);
+    if !bool::from(u.to_bytes().ct_eq(&encoding)) {
+        return Err(UnknownCryptoError);
+    }
+
+    // Multiplying by the cofactor of the curve, which is also a multiple of
+    // the cofactor of the twist, maps exactly the small-order points to zero.
+    let mut cofactor = [0u8; SCALARSIZE];
+    cofactor[0] = 8;
+    if bool::from(ladder(&cofactor, &u).is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Convert the Ed25519 public key `ed25519_public_key` to an X25519 public key.
+pub fn ed25519_public_key_to_x25519(
+    ed25519_public_key: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if ed25519_public_key.len() != ED25519_PUBLIC_KEYSIZE || dst_out.len() != X25519_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut encoding = [0u8; ED25519_PUBLIC_KEYSIZE];
+    encoding.copy_from_slice(ed25519_public_key);
+    let point = EdwardsPoint::from_bytes(&encoding)?;
+    if point.is_small_order() || !point.is_torsion_free() {
+        return Err(UnknownCryptoError);
+    }
+
+    dst_out.copy_from_slice(&point.to_montgomery());
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Convert the Ed25519 seed `ed25519_seed` to an X25519 secret key.
+pub fn ed25519_seed_to_x25519(
+    ed25519_seed: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if ed25519_seed.len() != ED25519_SEEDSIZE || dst_out.len() != X25519_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut hash = [0u8; SHA512_OUTSIZE];
+    Sha512::digest_into(ed25519_seed, &mut hash)?;
+    clamp_scalar(&mut hash[..X25519_KEYSIZE])?;
+    dst_out.copy_from_slice(&hash[..X25519_KEYSIZE]);
+    hash.zeroize();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_lengths() {
+        let mut dst = [0u8; X25519_KEYSIZE];
+        assert!(ed25519_seed_to_x25519(&[0u8; 31], &mut dst).is_err());
+        assert!(ed25519_seed_to_x25519(&[0u8; 33], &mut dst).is_err());
+        assert!(ed25519_seed_to_x25519(&[0u8; 32], &mut dst[..31]).is_err());
+        assert!(ed25519_seed_to_x25519(&[0u8; 32], &mut dst).is_ok());
+
+        assert!(ed25519_public_key_to_x25519(&test_vectors::RFC8032_1_PK[..31], &mut dst).is_err());
+        assert!(ed25519_public_key_to_x25519(&test_vectors::RFC8032_1_PK, &mut dst[..31]).is_err());
+        assert!(ed25519_public_key_to_x25519(&test_vectors::RFC8032_1_PK, &mut dst).is_ok());
+    }
+
+    #[test]
+    fn test_scalarmult_lengths() {
+        let mut dst = [0u8; POINTSIZE];
+        let scalar = [1u8; SCALARSIZE];
+        assert!(scalarmult(&scalar[..31], &BASEPOINT, &mut dst).is_err());
+        assert!(scalarmult(&scalar, &BASEPOINT[..31], &mut dst).is_err());
+        assert!(scalarmult(&scalar, &BASEPOINT, &mut dst[..31]).is_err());
+        assert!(scalarmult(&scalar, &BASEPOINT, &mut dst).is_ok());
+        assert!(scalarmult_base(&scalar[..31], &mut dst).is_err());
+        assert!(scalarmult_base(&scalar, &mut [0u8; 33]).is_err());
+        assert!(scalarmult_base(&scalar, &mut dst).is_ok());
+        assert!(clamp_scalar(&mut [0u8; 31]).is_err());
+        assert!(clamp_scalar(&mut [0u8; 33]).is_err());
+        assert!(validate_point(&BASEPOINT[..31]).is_err());
+        assert!(validate_point(&BASEPOINT).is_ok());
+    }
+
+    #[test]
+    fn test_clamp_scalar() {
+        let mut scalar = [0xffu8; SCALARSIZE];
+        clamp_scalar(&mut scalar).unwrap();
+        assert_eq!(scalar[0], 0xf8);
+        assert_eq!(scalar[1..31], [0xffu8; 30][..]);
+        assert_eq!(scalar[31], 0x7f);
+
+        let mut scalar = [0u8; SCALARSIZE];
+        clamp_scalar(&mut scalar).unwrap();
+        assert_eq!(scalar[31], 0x40);
+    }
+
+    #[test]
+    fn test_scalarmult_zero_scalar() {
+        let mut dst = [0u8; POINTSIZE];
+        assert!(scalarmult_base(&[0u8; SCALARSIZE], &mut dst).is_err());
+        assert!(scalarmult(&[0u8; SCALARSIZE], &BASEPOINT, &mut dst).is_err());
+    }
+
+    #[test]
+    fn test_small_order_points() {
+        let mut minus_one = [0xffu8; POINTSIZE];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        // A point of order 8, mapped from the Edwards form.
+        let order_eight = [
+            0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f,
+            0xc4, 0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16,
+            0x5f, 0x49, 0xb8, 0x00,
+        ];
+        let mut one = [0u8; POINTSIZE];
+        one[0] = 1;
+
+        let mut scalar = [0x42u8; SCALARSIZE];
+        clamp_scalar(&mut scalar).unwrap();
+        let mut dst = [0u8; POINTSIZE];
+        for point in [[0u8; POINTSIZE], one, minus_one, order_eight].iter() {
+            assert!(validate_point(point).is_err());
+            assert!(scalarmult(&scalar, point, &mut dst).is_err());
+        }
+    }
+
+    #[test]
+    fn test_non_canonical_points() {
+        // u = p + 9 is a non-canonical encoding of the basepoint.
+        let mut non_canonical = [0xffu8; POINTSIZE];
+        non_canonical[0] = 0xf6;
+        non_canonical[31] = 0x7f;
+        assert!(validate_point(&non_canonical).is_err());
+
+        // The most significant bit must be clear.
+        let mut high_bit = BASEPOINT;
+        high_bit[31] |= 0x80;
+        assert!(validate_point(&high_bit).is_err());
+
+        // scalarmult() accepts both, as RFC 7748 requires.
+        let scalar = [0x42u8; SCALARSIZE];
+        let mut expected = [0u8; POINTSIZE];
+        let mut dst = [0u8; POINTSIZE];
+        scalarmult_base(&scalar, &mut expected).unwrap();
+        scalarmult(&scalar, &non_canonical, &mut dst).unwrap();
+        assert_eq!(dst, expected);
+        scalarmult(&scalar, &high_bit, &mut dst).unwrap();
+        assert_eq!(dst, expected);
+    }
+
+    #[test]
+    fn test_converted_keys_match() {
+        let mut secret_key = [0u8; X25519_KEYSIZE];
+        let mut public_key = [0u8; X25519_KEYSIZE];
+        let mut derived = [0u8; POINTSIZE];
+        ed25519_seed_to_x25519(&[0u8; ED25519_SEEDSIZE], &mut secret_key).unwrap();
+        scalarmult_base(&secret_key, &mut derived).unwrap();
+        ed25519_public_key_to_x25519(&test_vectors::RFC8032_1_PK, &mut public_key).unwrap();
+        assert_ne!(derived, public_key);
+
+        ed25519_seed_to_x25519(&test_vectors::RFC8032_1_SEED, &mut secret_key).unwrap();
+        scalarmult_base(&secret_key, &mut derived).unwrap();
+        assert_eq!(derived, public_key);
+    }
+
+    #[test]
+    fn test_secret_key_clamped() {
+        let mut dst = [0u8; X25519_KEYSIZE];
+        for seed in 0..=255u8 {
+            ed25519_seed_to_x25519(&[seed; ED25519_SEEDSIZE], &mut dst).unwrap();
+            assert_eq!(dst[0] & 7, 0);
+            assert_eq!(dst[31] & 0xc0, 0x40);
+        }
+    }
+
+    #[test]
+    fn test_reject_invalid_public_keys() {
+        let mut dst = [0u8; X25519_KEYSIZE];
+
+        // The identity.
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(ed25519_public_key_to_x25519(&identity, &mut dst).is_err());
+
+        // y = 0, which is a point of order 4.
+        assert!(ed25519_public_key_to_x25519(&[0u8; 32], &mut dst).is_err());
+
+        // y = -1, which is a point of order 2.
+        let mut minus_one = [0xffu8; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        assert!(ed25519_public_key_to_x25519(&minus_one, &mut dst).is_err());
+
+        // y = 2, which is not on the curve.
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[0] = 2;
+        assert!(ed25519_public_key_to_x25519(&not_on_curve, &mut dst).is_err());
+
+        // The public key of RFC 8032 test 1 plus a point of order 8.
+        let mixed_order = [
+            0x91, 0x58, 0x31, 0x2a, 0x9a, 0x8d, 0x6e, 0x3b, 0x34, 0xc8, 0x91, 0xd6, 0xd6, 0x14,
+            0x44, 0xf8, 0xb8, 0x21, 0x1c, 0x51, 0x17, 0xeb, 0xad, 0x15, 0xbd, 0xb0, 0xbd, 0x68,
+            0xb0, 0x7e, 0x02, 0x45,
+        ];
+        assert!(ed25519_public_key_to_x25519(&mixed_order, &mut dst).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    // Ed25519 key pairs from RFC 8032, section 7.1, with the X25519 keys
+    // computed independently from them.
+    pub(super) const RFC8032_1_SEED: [u8; 32] = [
+        0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c,
+        0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae,
+        0x7f, 0x60,
+    ];
+    pub(super) const RFC8032_1_PK: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+        0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+        0x51, 0x1a,
+    ];
+
+    fn conversion_test(seed: &str, public_key: &str, x25519_sk: &str, x25519_pk: &str) {
+        let mut dst = [0u8; X25519_KEYSIZE];
+        ed25519_seed_to_x25519(&hex::decode(seed).unwrap(), &mut dst).unwrap();
+        assert_eq!(dst[..], hex::decode(x25519_sk).unwrap()[..]);
+        ed25519_public_key_to_x25519(&hex::decode(public_key).unwrap(), &mut dst).unwrap();
+        assert_eq!(dst[..], hex::decode(x25519_pk).unwrap()[..]);
+    }
+
+    #[test]
+    fn rfc8032_test_1() {
+        conversion_test(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "307c83864f2833cb427a2ef1c00a013cfdff2768d980c0a3a520f006904de94f",
+            "d85e07ec22b0ad881537c2f44d662d1a143cf830c57aca4305d85c7a90f6b62e",
+        );
+    }
+
+    #[test]
+    fn rfc8032_test_2() {
+        conversion_test(
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "68bd9ed75882d52815a97585caf4790a7f6c6b3b7f821c5e259a24b02e502e51",
+            "25c704c594b88afc00a76b69d1ed2b984d7e22550f3ed0802d04fbcd07d38d47",
+        );
+    }
+
+    fn scalarmult_test(scalar: &str, point: &str, expected: &str) {
+        let mut dst = [0u8; POINTSIZE];
+        scalarmult(
+            &hex::decode(scalar).unwrap(),
+            &hex::decode(point).unwrap(),
+            &mut dst,
+        )
+        .unwrap();
+        assert_eq!(dst[..], hex::decode(expected).unwrap()[..]);
+    }
+
+    fn clamped(scalar: &str) -> [u8; SCALARSIZE] {
+        let mut clamped = [0u8; SCALARSIZE];
+        clamped.copy_from_slice(&hex::decode(scalar).unwrap());
+        clamp_scalar(&mut clamped).unwrap();
+        clamped
+    }
+
+    #[test]
+    fn rfc7748_section_5_2_test_1() {
+        scalarmult_test(
+            &hex::encode(clamped(
+                "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+            )),
+            "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+        );
+    }
+
+    #[test]
+    fn rfc7748_section_5_2_test_2() {
+        scalarmult_test(
+            &hex::encode(clamped(
+                "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+            )),
+            "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+            "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+        );
+    }
+
+    #[test]
+    fn rfc7748_section_5_2_iterated() {
+        let mut scalar = BASEPOINT;
+        let mut point = BASEPOINT;
+        let mut clamped = [0u8; SCALARSIZE];
+        let mut result = [0u8; POINTSIZE];
+        for iteration in 1..=1000 {
+            clamped.copy_from_slice(&scalar);
+            clamp_scalar(&mut clamped).unwrap();
+            scalarmult(&clamped, &point, &mut result).unwrap();
+            // The next point is the current, unclamped, scalar and the next
+            // scalar is the result.
+            point.copy_from_slice(&scalar);
+            scalar.copy_from_slice(&result);
+            if iteration == 1 {
+                assert_eq!(
+                    result[..],
+                    hex::decode("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+                        .unwrap()[..]
+                );
+            }
+        }
+        assert_eq!(
+            result[..],
+            hex::decode("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn rfc7748_section_6_1() {
+        let alice_secret =
+            clamped("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob_secret =
+            clamped("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_public = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+        let bob_public = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+        let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+
+        let mut dst = [0u8; POINTSIZE];
+        scalarmult_base(&alice_secret, &mut dst).unwrap();
+        assert_eq!(dst[..], hex::decode(alice_public).unwrap()[..]);
+        scalarmult_base(&bob_secret, &mut dst).unwrap();
+        assert_eq!(dst[..], hex::decode(bob_public).unwrap()[..]);
+
+        scalarmult_test(&hex::encode(alice_secret), bob_public, shared);
+        scalarmult_test(&hex::encode(bob_secret), alice_public, shared);
+    }
+
+    #[test]
+    fn unclamped_scalars() {
+        // Computed independently via the Edwards form.
+        let mut three = [0u8; SCALARSIZE];
+        three[0] = 3;
+        scalarmult_test(
+            &hex::encode(three),
+            &hex::encode(BASEPOINT),
+            "123c71fbaf030ac059081c62674e82f864ba1bc2914d5345e6ab576d1abc121c",
+        );
+        scalarmult_test(
+            &hex::encode([0xffu8; SCALARSIZE]),
+            &hex::encode(BASEPOINT),
+            "f1e195e1b66cf4a4083ab52098f438be90783e6cd510982f72a2889f3a2d7a13",
+        );
+    }
+}
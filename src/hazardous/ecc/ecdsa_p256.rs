@@ -0,0 +1,489 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! ECDSA over P-256 with SHA-256, as specified in
+//! [FIPS 186-5](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-5.pdf),
+//! with deterministic nonces as specified in [RFC 6979](https://www.rfc-editor.org/rfc/rfc6979.html).
+//!
+//! A [`SecretKey`] is the 32-byte big-endian scalar, and a [`PublicKey`] is
+//! the 65-byte uncompressed SEC 1 encoding of a point. A [`Signature`] is
+//! `r || s`, each 32 bytes in big-endian. [`der::ecdsa_signature_to_der()`]
+//! and [`der::ecdsa_signature_from_der()`] convert it to and from the DER
+//! encoding used in X.509 and TLS.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key used to sign `message`.
+//! - `public_key`: The public key used to verify `signature`.
+//! - `message`: The message to be signed or verified. It is hashed with
+//!   SHA-256 first.
+//! - `signature`: The signature to be verified.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A [`SecretKey`] is created from a slice that is not 32 bytes, is zero,
+//!   or is not less than the group order.
+//! - `public_key` is not the uncompressed encoding of a point on the curve.
+//! - `r` or `s` of `signature` is zero or not less than the group order.
+//! - `signature` is not a valid signature of `message` under `public_key`.
+//!
+//! # Security:
+//! - Signing is deterministic, so the same `message` always results in the
+//!   same signature. No randomness is needed when signing.
+//! - The secret key is used in constant time. Verification only uses public
+//!   values and makes no such guarantee.
+//! - Both `s` and `n - s` are accepted when verifying, so signatures are
+//!   malleable. Do not use a signature as an identifier of a message.
+//! - Signatures are verified after signing, to guard against faults.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::ecdsa_p256::{self, PublicKey, SecretKey};
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = SecretKey::generate();
+//! let public_key = PublicKey::try_from(&secret_key)?;
+//!
+//! let signature = ecdsa_p256::sign(&secret_key, b"Message")?;
+//! assert!(ecdsa_p256::verify(&signature, &public_key, b"Message").is_ok());
+//! assert!(ecdsa_p256::verify(&signature, &public_key, b"Another message").is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`PublicKey`]: struct.PublicKey.html
+//! [`Signature`]: struct.Signature.html
+//! [`der::ecdsa_signature_to_der()`]: ../../../util/der/fn.ecdsa_signature_to_der.html
+//! [`der::ecdsa_signature_from_der()`]: ../../../util/der/fn.ecdsa_signature_from_der.html
+
+use super::p256::{Point, Scalar, SCALARSIZE, UNCOMPRESSED_POINTSIZE};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+use crate::hazardous::mac::hmac::HmacGeneric;
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of a secret key.
+pub const SECRET_KEYSIZE: usize = SCALARSIZE;
+/// The size of a public key.
+pub const PUBLIC_KEYSIZE: usize = UNCOMPRESSED_POINTSIZE;
+/// The size of a signature.
+pub const SIGNATURE_SIZE: usize = 2 * SCALARSIZE;
+
+/// Decode a canonical, non-zero scalar.
+fn decode_scalar(bytes: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+    if bytes.len() != SCALARSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut value = [0u8; SCALARSIZE];
+    value.copy_from_slice(bytes);
+    let (scalar, is_canonical) = Scalar::from_bytes(&value);
+    value.zeroize();
+    if !bool::from(is_canonical) || bool::from(scalar.is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(scalar)
+}
+
+/// A type to represent the `SecretKey` of an ECDSA P-256 key pair.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - `slice` is not the 32-byte big-endian encoding of a non-zero integer
+///   less than the group order.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - Failure to generate random bytes securely.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as
+///   it breaks all protections that the type implements.
+/// - The trait `PartialEq<&'_ [u8]>` is implemented for this type, and
+///   the comparison happens in constant time.
+pub struct SecretKey {
+    value: [u8; SECRET_KEYSIZE],
+    original_length: usize,
+}
+
+impl_omitted_debug_trait!(SecretKey);
+impl_drop_trait!(SecretKey);
+impl_ct_partialeq_trait!(SecretKey, unprotected_as_bytes);
+
+impl SecretKey {
+    fn from_scalar(scalar: &Scalar) -> Self {
+        Self {
+            value: scalar.to_bytes(),
+            original_length: SECRET_KEYSIZE,
+        }
+    }
+
+    fn to_scalar(&self) -> Scalar {
+        // The value is always canonical, so this cannot fail.
+        Scalar::from_bytes(&self.value).0
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct from a given byte slice, which must be the big-endian
+    /// encoding of a non-zero integer less than the group order.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut scalar = decode_scalar(slice)?;
+        let value = Self::from_scalar(&scalar);
+        scalar.zeroize();
+
+        Ok(value)
+    }
+
+    #[cfg(feature = "safe_api")]
+    /// Randomly generate using a CSPRNG. Not available in `no_std` context.
+    pub fn generate() -> Self {
+        // Reducing 64 bytes makes the bias towards small values negligible.
+        let mut bytes = [0u8; 64];
+        loop {
+            crate::util::secure_rand_bytes(&mut bytes).unwrap();
+            let mut scalar = Scalar::from_bytes_wide(&bytes);
+            if !bool::from(scalar.is_zero()) {
+                bytes.zeroize();
+                let value = Self::from_scalar(&scalar);
+                scalar.zeroize();
+                return value;
+            }
+        }
+    }
+
+    func_unprotected_as_bytes!();
+    func_len!();
+}
+
+construct_public! {
+    /// A type to represent the `PublicKey` of an ECDSA P-256 key pair, as an
+    /// uncompressed SEC 1 encoding.
+    ///
+    /// The encoding is only checked when the public key is used.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 65 bytes.
+    (PublicKey, test_public_key, PUBLIC_KEYSIZE, PUBLIC_KEYSIZE)
+}
+
+impl TryFrom<&SecretKey> for PublicKey {
+    type Error = UnknownCryptoError;
+
+    fn try_from(secret_key: &SecretKey) -> Result<Self, Self::Error> {
+        let mut d = secret_key.to_scalar();
+        let public_key = Point::mul_base(&d).to_uncompressed();
+        d.zeroize();
+
+        PublicKey::from_slice(&public_key?)
+    }
+}
+
+construct_public! {
+    /// A type to represent an ECDSA P-256 `Signature`, as `r || s`.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 64 bytes.
+    (Signature, test_signature, SIGNATURE_SIZE, SIGNATURE_SIZE)
+}
+
+/// HMAC-SHA256 of the concatenation of `parts`, with `key`.
+fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> Result<[u8; SHA256_OUTSIZE], UnknownCryptoError> {
+    let mut state = HmacGeneric::<Sha256>::new(key)?;
+    for part in parts.iter() {
+        state.update(part)?;
+    }
+    let mut tag = [0u8; SHA256_OUTSIZE];
+    state.finalize_into(&mut tag)?;
+
+    Ok(tag)
+}
+
+/// The nonce generator of RFC 6979 section 3.2, for SHA-256 and a group
+/// order of 256 bits, where bits2int is the identity.
+struct NonceGenerator {
+    k: [u8; SHA256_OUTSIZE],
+    v: [u8; SHA256_OUTSIZE],
+}
+
+impl Drop for NonceGenerator {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
+
+impl NonceGenerator {
+    fn new(d: &[u8; SCALARSIZE], h1: &[u8; SCALARSIZE]) -> Result<Self, UnknownCryptoError> {
+        let mut generator = Self {
+            k: [0x00; SHA256_OUTSIZE],
+            v: [0x01; SHA256_OUTSIZE],
+        };
+        generator.k = hmac_sha256(&generator.k, &[&generator.v, &[0x00], d, h1])?;
+        generator.v = hmac_sha256(&generator.k, &[&generator.v])?;
+        generator.k = hmac_sha256(&generator.k, &[&generator.v, &[0x01], d, h1])?;
+        generator.v = hmac_sha256(&generator.k, &[&generator.v])?;
+
+        Ok(generator)
+    }
+
+    /// The next candidate nonce in [1, n - 1].
+    fn next_nonce(&mut self) -> Result<Scalar, UnknownCryptoError> {
+        loop {
+            self.v = hmac_sha256(&self.k, &[&self.v])?;
+            let (k, is_canonical) = Scalar::from_bytes(&self.v);
+            // Prepare the state for a candidate that is rejected here, or
+            // one that results in r = 0 or s = 0.
+            self.k = hmac_sha256(&self.k, &[&self.v, &[0x00]])?;
+            self.v = hmac_sha256(&self.k, &[&self.v])?;
+            if bool::from(is_canonical) && !bool::from(k.is_zero()) {
+                return Ok(k);
+            }
+        }
+    }
+}
+
+/// SHA-256 of `message`, reduced modulo the group order.
+fn hash_message(message: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+    let mut hash = [0u8; SHA256_OUTSIZE];
+    Sha256::digest_into(message, &mut hash)?;
+
+    Ok(Scalar::from_bytes_reduced(&hash))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `message` with `secret_key`.
+pub fn sign(secret_key: &SecretKey, message: &[u8]) -> Result<Signature, UnknownCryptoError> {
+    let e = hash_message(message)?;
+    let mut d = secret_key.to_scalar();
+    let mut nonces = NonceGenerator::new(&secret_key.value, &e.to_bytes())?;
+
+    let mut signature = [0u8; SIGNATURE_SIZE];
+    loop {
+        let mut k = nonces.next_nonce()?;
+        // k is non-zero, so kG is not the point at infinity.
+        let r = Scalar::from_bytes_reduced(&Point::mul_base(&k).x_bytes()?);
+        let s = k.invert().mul(&e.add(&r.mul(&d)));
+        k.zeroize();
+
+        if !bool::from(r.is_zero() | s.is_zero()) {
+            signature[..SCALARSIZE].copy_from_slice(&r.to_bytes());
+            signature[SCALARSIZE..].copy_from_slice(&s.to_bytes());
+            break;
+        }
+    }
+    d.zeroize();
+
+    let signature = Signature::from_slice(&signature)?;
+    if verify(&signature, &PublicKey::try_from(secret_key)?, message).is_err() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(signature)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `message` under `public_key`.
+pub fn verify(
+    signature: &Signature,
+    public_key: &PublicKey,
+    message: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let q = Point::from_uncompressed(public_key.as_ref())?;
+    let r = decode_scalar(&signature.as_ref()[..SCALARSIZE])?;
+    let s = decode_scalar(&signature.as_ref()[SCALARSIZE..])?;
+
+    let e = hash_message(message)?;
+    let w = s.invert();
+    let point = Point::mul_base(&e.mul(&w)).add(&q.mul(&r.mul(&w)));
+    if point.is_identity() {
+        return Err(UnknownCryptoError);
+    }
+
+    let x = Scalar::from_bytes_reduced(&point.x_bytes()?);
+    if x.to_bytes() == r.to_bytes() {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    /// The key of RFC 6979 appendix A.2.5.
+    fn rfc6979_secret_key() -> SecretKey {
+        SecretKey::from_slice(
+            &hex::decode("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rfc6979() {
+        let secret_key = rfc6979_secret_key();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        assert_eq!(
+            public_key.as_ref(),
+            &hex::decode(
+                "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb67903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+            )
+            .unwrap()[..]
+        );
+
+        let vectors: [(&[u8], &str); 2] = [
+            (
+                b"sample",
+                "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8",
+            ),
+            (
+                b"test",
+                "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083",
+            ),
+        ];
+        for (message, expected) in vectors.iter() {
+            let signature = sign(&secret_key, message).unwrap();
+            assert_eq!(signature.as_ref(), &hex::decode(expected).unwrap()[..]);
+            assert!(verify(&signature, &public_key, message).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_secret_key() {
+        let order = hex::decode("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551")
+            .unwrap();
+        let mut order_minus_one = order.clone();
+        order_minus_one[31] -= 1;
+
+        assert!(SecretKey::from_slice(&[0u8; SECRET_KEYSIZE]).is_err());
+        assert!(SecretKey::from_slice(&[1u8; SECRET_KEYSIZE - 1]).is_err());
+        assert!(SecretKey::from_slice(&[1u8; SECRET_KEYSIZE + 1]).is_err());
+        assert!(SecretKey::from_slice(&order).is_err());
+        assert!(SecretKey::from_slice(&[0xffu8; SECRET_KEYSIZE]).is_err());
+        assert!(SecretKey::from_slice(&order_minus_one).is_ok());
+
+        assert_eq!(rfc6979_secret_key().len(), SECRET_KEYSIZE);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_secret_key_debug() {
+        let secret_key = rfc6979_secret_key();
+        assert_eq!(format!("{:?}", secret_key), "SecretKey {***OMITTED***}");
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_sign_verify() {
+        let secret_key = SecretKey::generate();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"Message").unwrap();
+
+        assert!(verify(&signature, &public_key, b"Message").is_ok());
+        assert!(verify(&signature, &public_key, b"Messagf").is_err());
+
+        let other_public_key = PublicKey::try_from(&SecretKey::generate()).unwrap();
+        assert!(verify(&signature, &other_public_key, b"Message").is_err());
+
+        let mut modified = [0u8; SIGNATURE_SIZE];
+        for idx in [0, 31, 32, 63].iter() {
+            modified.copy_from_slice(signature.as_ref());
+            modified[*idx] ^= 1;
+            let modified = Signature::from_slice(&modified).unwrap();
+            assert!(verify(&modified, &public_key, b"Message").is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_malleable_s() {
+        // n - s is also a valid signature.
+        let secret_key = rfc6979_secret_key();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"sample").unwrap();
+
+        let mut s = [0u8; SCALARSIZE];
+        s.copy_from_slice(&signature.as_ref()[SCALARSIZE..]);
+        let mut malleated = [0u8; SIGNATURE_SIZE];
+        malleated[..SCALARSIZE].copy_from_slice(&signature.as_ref()[..SCALARSIZE]);
+        malleated[SCALARSIZE..].copy_from_slice(&Scalar::from_bytes(&s).0.neg().to_bytes());
+        let malleated = Signature::from_slice(&malleated).unwrap();
+
+        assert!(verify(&malleated, &public_key, b"sample").is_ok());
+    }
+
+    #[test]
+    fn test_verify_rejects_out_of_range() {
+        let secret_key = rfc6979_secret_key();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"sample").unwrap();
+
+        let mut bad = [0u8; SIGNATURE_SIZE];
+        bad.copy_from_slice(signature.as_ref());
+        bad[..SCALARSIZE].copy_from_slice(&[0u8; SCALARSIZE]);
+        assert!(verify(
+            &Signature::from_slice(&bad).unwrap(),
+            &public_key,
+            b"sample"
+        )
+        .is_err());
+
+        bad.copy_from_slice(signature.as_ref());
+        bad[SCALARSIZE..].copy_from_slice(&[0u8; SCALARSIZE]);
+        assert!(verify(
+            &Signature::from_slice(&bad).unwrap(),
+            &public_key,
+            b"sample"
+        )
+        .is_err());
+
+        bad.copy_from_slice(signature.as_ref());
+        bad[..SCALARSIZE].copy_from_slice(&[0xffu8; SCALARSIZE]);
+        assert!(verify(
+            &Signature::from_slice(&bad).unwrap(),
+            &public_key,
+            b"sample"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_public_key() {
+        let secret_key = rfc6979_secret_key();
+        let signature = sign(&secret_key, b"sample").unwrap();
+        let mut public_key = [0u8; PUBLIC_KEYSIZE];
+        public_key.copy_from_slice(PublicKey::try_from(&secret_key).unwrap().as_ref());
+
+        public_key[64] ^= 1;
+        let not_on_curve = PublicKey::from_slice(&public_key).unwrap();
+        assert!(verify(&signature, &not_on_curve, b"sample").is_err());
+
+        public_key[64] ^= 1;
+        public_key[0] = 0x02;
+        let compressed_tag = PublicKey::from_slice(&public_key).unwrap();
+        assert!(verify(&signature, &compressed_tag, b"sample").is_err());
+    }
+}
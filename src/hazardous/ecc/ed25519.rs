@@ -0,0 +1,405 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Ed25519 signatures, as specified in the [RFC 8032](https://tools.ietf.org/html/rfc8032).
+//!
+//! A [`SecretKey`] is the 32-byte seed from which the signing scalar and the
+//! nonce prefix are derived, not the 64-byte expanded form that some other
+//! libraries store.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key used to sign `message`.
+//! - `public_key`: The public key used to verify `signature`.
+//! - `message`: The message to be signed or verified.
+//! - `signature`: The signature to be verified.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `signature` is not a valid signature of `message` under `public_key`.
+//! - The `S` half of `signature` is not less than the group order.
+//! - `public_key` is not a canonical encoding of a point on the curve, or it
+//!   is a point of small order.
+//!
+//! # Security:
+//! - Signing is deterministic, so the same `message` always results in the
+//!   same signature. No randomness is needed when signing.
+//! - The secret key is used in constant time. Verification only uses public
+//!   values and makes no such guarantee.
+//! - Verification uses the cofactorless equation `[S]B = R + [k]A`, and
+//!   rejects public keys of small order. Other implementations may accept a
+//!   few signatures that this one rejects, or the other way around, so
+//!   signatures should not be used where all verifiers must agree on every
+//!   edge case, such as in consensus protocols.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::ed25519::{self, PublicKey, SecretKey};
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = SecretKey::generate();
+//! let public_key = PublicKey::try_from(&secret_key)?;
+//!
+//! let signature = ed25519::sign(&secret_key, b"Message")?;
+//! assert!(ed25519::verify(&signature, &public_key, b"Message").is_ok());
+//! assert!(ed25519::verify(&signature, &public_key, b"Another message").is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`SecretKey`]: struct.SecretKey.html
+
+use super::{
+    edwards25519::EdwardsPoint,
+    ristretto255::{scalar_from_bytes, scalar_from_bytes_wide, scalar_to_bytes, Scalar},
+};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::hash::sha512::{Sha512, SHA512_OUTSIZE},
+};
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of a secret key.
+pub const SECRET_KEYSIZE: usize = 32;
+/// The size of a public key.
+pub const PUBLIC_KEYSIZE: usize = 32;
+/// The size of a signature.
+pub const SIGNATURE_SIZE: usize = 64;
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` of an Ed25519 key pair, which is
+    /// the 32-byte seed.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, SECRET_KEYSIZE, SECRET_KEYSIZE, SECRET_KEYSIZE)
+}
+
+impl_from_trait!(SecretKey, SECRET_KEYSIZE);
+
+construct_public! {
+    /// A type to represent the `PublicKey` of an Ed25519 key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (PublicKey, test_public_key, PUBLIC_KEYSIZE, PUBLIC_KEYSIZE)
+}
+
+impl_from_trait!(PublicKey, PUBLIC_KEYSIZE);
+
+impl TryFrom<&SecretKey> for PublicKey {
+    type Error = UnknownCryptoError;
+
+    fn try_from(secret_key: &SecretKey) -> Result<Self, Self::Error> {
+        let expanded = ExpandedSecretKey::new(secret_key)?;
+
+        Ok(PublicKey::from(expanded.public_key))
+    }
+}
+
+construct_public! {
+    /// A type to represent an Ed25519 `Signature`.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 64 bytes.
+    (Signature, test_signature, SIGNATURE_SIZE, SIGNATURE_SIZE)
+}
+
+/// The signing scalar, nonce prefix and public key derived from a seed.
+struct ExpandedSecretKey {
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+    public_key: [u8; PUBLIC_KEYSIZE],
+}
+
+impl Drop for ExpandedSecretKey {
+    fn drop(&mut self) {
+        self.scalar.zeroize();
+        self.prefix.zeroize();
+    }
+}
+
+impl ExpandedSecretKey {
+    /// Hash the seed and clamp the lower half, as specified in
+    /// [RFC 8032](https://tools.ietf.org/html/rfc8032#section-5.1.5).
+    fn new(secret_key: &SecretKey) -> Result<Self, UnknownCryptoError> {
+        let mut hash = [0u8; SHA512_OUTSIZE];
+        Sha512::digest_into(secret_key.unprotected_as_bytes(), &mut hash)?;
+
+        let mut scalar = [0u8; 32];
+        let mut prefix = [0u8; 32];
+        scalar.copy_from_slice(&hash[..32]);
+        prefix.copy_from_slice(&hash[32..]);
+        hash.zeroize();
+
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+
+        Ok(Self {
+            scalar,
+            prefix,
+            public_key: EdwardsPoint::BASEPOINT.mul(&scalar).to_bytes(),
+        })
+    }
+}
+
+/// SHA-512 of the concatenation of `parts`, reduced modulo the group order.
+fn hash_to_scalar(parts: &[&[u8]]) -> Result<Scalar, UnknownCryptoError> {
+    let mut state = Sha512::new();
+    for part in parts.iter() {
+        state.update(part)?;
+    }
+
+    let mut hash = [0u8; SHA512_OUTSIZE];
+    state.finalize_into(&mut hash)?;
+    let scalar = scalar_from_bytes_wide(&hash);
+    hash.zeroize();
+
+    Ok(scalar)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `message` with `secret_key`.
+pub fn sign(secret_key: &SecretKey, message: &[u8]) -> Result<Signature, UnknownCryptoError> {
+    let expanded = ExpandedSecretKey::new(secret_key)?;
+
+    let mut r = hash_to_scalar(&[&expanded.prefix, message])?;
+    let mut r_bytes = scalar_to_bytes(&r);
+    let big_r = EdwardsPoint::BASEPOINT.mul(&r_bytes).to_bytes();
+    r_bytes.zeroize();
+
+    let k = hash_to_scalar(&[&big_r, &expanded.public_key, message])?;
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&expanded.scalar);
+    let mut a = scalar_from_bytes_wide(&wide);
+    wide.zeroize();
+
+    let s = r.add(&k.mul(&a));
+    r.zeroize();
+    a.zeroize();
+
+    let mut signature = [0u8; SIGNATURE_SIZE];
+    signature[..32].copy_from_slice(&big_r);
+    signature[32..].copy_from_slice(&scalar_to_bytes(&s));
+
+    Signature::from_slice(&signature)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `message` under `public_key`.
+pub fn verify(
+    signature: &Signature,
+    public_key: &PublicKey,
+    message: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut big_r = [0u8; 32];
+    let mut s_bytes = [0u8; 32];
+    big_r.copy_from_slice(&signature.as_ref()[..32]);
+    s_bytes.copy_from_slice(&signature.as_ref()[32..]);
+    let s = scalar_from_bytes(&s_bytes)?;
+
+    let mut a_bytes = [0u8; PUBLIC_KEYSIZE];
+    a_bytes.copy_from_slice(public_key.as_ref());
+    let a = EdwardsPoint::from_bytes(&a_bytes)?;
+    if a.is_small_order() {
+        return Err(UnknownCryptoError);
+    }
+
+    let k = hash_to_scalar(&[&big_r, &a_bytes, message])?;
+    let expected_r = EdwardsPoint::BASEPOINT
+        .mul_vartime(&scalar_to_bytes(&s))
+        .add(&a.neg().mul_vartime(&scalar_to_bytes(&k)));
+
+    if expected_r.to_bytes() == big_r {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::super::edwards25519::GROUP_ORDER;
+    use super::*;
+
+    #[test]
+    fn test_sign_verify() {
+        let secret_key = SecretKey::generate();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"Message").unwrap();
+
+        verify(&signature, &public_key, b"Message").unwrap();
+        assert!(verify(&signature, &public_key, b"Messagf").is_err());
+        assert!(verify(&signature, &public_key, b"").is_err());
+
+        let other_key = PublicKey::try_from(&SecretKey::generate()).unwrap();
+        assert!(verify(&signature, &other_key, b"Message").is_err());
+    }
+
+    #[test]
+    fn test_sign_is_deterministic() {
+        let secret_key = SecretKey::generate();
+        assert_eq!(
+            sign(&secret_key, b"Message").unwrap(),
+            sign(&secret_key, b"Message").unwrap()
+        );
+        assert_ne!(
+            sign(&secret_key, b"Message").unwrap(),
+            sign(&secret_key, b"Another message").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_modified_signature_err() {
+        let secret_key = SecretKey::generate();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"Message").unwrap();
+
+        for idx in 0..SIGNATURE_SIZE {
+            let mut modified = [0u8; SIGNATURE_SIZE];
+            modified.copy_from_slice(signature.as_ref());
+            modified[idx] ^= 1;
+            let modified = Signature::from_slice(&modified).unwrap();
+            assert!(verify(&modified, &public_key, b"Message").is_err());
+        }
+    }
+
+    #[test]
+    fn test_non_canonical_s_err() {
+        let secret_key = SecretKey::generate();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        let signature = sign(&secret_key, b"Message").unwrap();
+
+        // S + L is an equivalent, but non-canonical, encoding of S.
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&signature.as_ref()[32..]);
+        let mut carry = 0u16;
+        for (byte, order_byte) in s.iter_mut().zip(GROUP_ORDER.iter()) {
+            let sum = u16::from(*byte) + u16::from(*order_byte) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        // S < L < 2^253, so S + L can't overflow 32 bytes.
+        assert_eq!(carry, 0);
+
+        let mut modified = [0u8; SIGNATURE_SIZE];
+        modified[..32].copy_from_slice(&signature.as_ref()[..32]);
+        modified[32..].copy_from_slice(&s);
+        let modified = Signature::from_slice(&modified).unwrap();
+        assert!(verify(&modified, &public_key, b"Message").is_err());
+    }
+
+    #[test]
+    fn test_small_order_public_key_err() {
+        // The identity, which any signature with R = identity and S = 0
+        // would verify under, for every message.
+        let mut identity = [0u8; PUBLIC_KEYSIZE];
+        identity[0] = 1;
+        let public_key = PublicKey::from(identity);
+
+        let mut signature = [0u8; SIGNATURE_SIZE];
+        signature[0] = 1;
+        let signature = Signature::from_slice(&signature).unwrap();
+        assert!(verify(&signature, &public_key, b"Message").is_err());
+        assert!(verify(&signature, &public_key, b"Another message").is_err());
+    }
+
+    #[test]
+    fn test_invalid_public_key_err() {
+        let secret_key = SecretKey::generate();
+        let signature = sign(&secret_key, b"Message").unwrap();
+
+        // y = p, which is a non-canonical encoding of y = 0.
+        let mut non_canonical = [0xffu8; PUBLIC_KEYSIZE];
+        non_canonical[0] = 0xed;
+        non_canonical[31] = 0x7f;
+        assert!(verify(&signature, &PublicKey::from(non_canonical), b"Message").is_err());
+    }
+
+    quickcheck! {
+        fn prop_sign_verify(message: Vec<u8>) -> bool {
+            let secret_key = SecretKey::generate();
+            let public_key = PublicKey::try_from(&secret_key).unwrap();
+            let signature = sign(&secret_key, &message).unwrap();
+
+            verify(&signature, &public_key, &message).is_ok()
+        }
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod test_vectors {
+    use super::*;
+
+    fn test_runner(secret_key: &str, public_key: &str, message: &str, signature: &str) {
+        let secret_key = SecretKey::from_slice(&hex::decode(secret_key).unwrap()).unwrap();
+        let public_key = PublicKey::from_slice(&hex::decode(public_key).unwrap()).unwrap();
+        let message = hex::decode(message).unwrap();
+        let signature = Signature::from_slice(&hex::decode(signature).unwrap()).unwrap();
+
+        assert_eq!(PublicKey::try_from(&secret_key).unwrap(), public_key);
+        assert_eq!(sign(&secret_key, &message).unwrap(), signature);
+        verify(&signature, &public_key, &message).unwrap();
+    }
+
+    // https://tools.ietf.org/html/rfc8032#section-7.1
+    #[test]
+    fn test_rfc8032_test_1() {
+        test_runner(
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        );
+    }
+
+    #[test]
+    fn test_rfc8032_test_2() {
+        test_runner(
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        );
+    }
+
+    #[test]
+    fn test_rfc8032_test_3() {
+        test_runner(
+            "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        );
+    }
+}
@@ -0,0 +1,338 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Points on the twisted Edwards curve -x^2 + y^2 = 1 + d*x^2*y^2, which is
+//! birationally equivalent to Curve25519.
+//!
+//! Points are represented in extended coordinates (X : Y : Z : T), where
+//! x = X/Z, y = Y/Z and x*y = T/Z.
+
+use super::field25519::FieldElement;
+use crate::errors::UnknownCryptoError;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// The order of the prime-order subgroup,
+/// 2^252 + 27742317777372353535851937790883648493, in little-endian.
+pub(crate) const GROUP_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+#[derive(Clone, Copy)]
+/// A point on the Edwards form of Curve25519.
+pub(crate) struct EdwardsPoint {
+    pub(super) x: FieldElement,
+    pub(super) y: FieldElement,
+    pub(super) z: FieldElement,
+    pub(super) t: FieldElement,
+}
+
+impl ConditionallySelectable for EdwardsPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+            t: FieldElement::conditional_select(&a.t, &b.t, choice),
+        }
+    }
+}
+
+impl EdwardsPoint {
+    /// The neutral element.
+    pub(crate) const IDENTITY: Self = Self {
+        x: FieldElement::ZERO,
+        y: FieldElement::ONE,
+        z: FieldElement::ONE,
+        t: FieldElement::ZERO,
+    };
+
+    /// The Ed25519 basepoint, which generates the prime-order subgroup.
+    pub(crate) const BASEPOINT: Self = Self {
+        x: FieldElement::BASEPOINT_X,
+        y: FieldElement::BASEPOINT_Y,
+        z: FieldElement::ONE,
+        t: FieldElement::BASEPOINT_XY,
+    };
+
+    /// Decode a point from its 32-byte encoding, as specified in
+    /// [RFC 8032](https://tools.ietf.org/html/rfc8032#section-5.1.3).
+    ///
+    /// Non-canonical encodings of the y-coordinate are rejected, as are
+    /// encodings of x = 0 with the sign bit set.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Result<Self, UnknownCryptoError> {
+        let y = FieldElement::from_bytes(bytes);
+        let sign = bytes[31] >> 7;
+
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        if y.to_bytes() != y_bytes {
+            return Err(UnknownCryptoError);
+        }
+
+        // x^2 = (y^2 - 1) / (d*y^2 + 1)
+        let y2 = y.square();
+        let u = y2.sub(&FieldElement::ONE);
+        let v = FieldElement::EDWARDS_D.mul(&y2).add(&FieldElement::ONE);
+
+        // x = u*v^3 * (u*v^7)^((p - 5) / 8)
+        let v3 = v.square().mul(&v);
+        let v7 = v3.square().mul(&v);
+        let mut x = u.mul(&v3).mul(&u.mul(&v7).pow_p58());
+
+        let vx2 = v.mul(&x.square());
+        if bool::from(vx2.ct_eq(&u.neg())) {
+            x = x.mul(&FieldElement::SQRT_M1);
+        } else if !bool::from(vx2.ct_eq(&u)) {
+            return Err(UnknownCryptoError);
+        }
+
+        if bool::from(x.is_zero()) && sign == 1 {
+            return Err(UnknownCryptoError);
+        }
+        if x.is_negative().unwrap_u8() != sign {
+            x = x.neg();
+        }
+
+        Ok(Self {
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: x.mul(&y),
+        })
+    }
+
+    /// Encode this point into 32 bytes.
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let z_inv = self.z.invert();
+        let x = self.x.mul(&z_inv);
+        let y = self.y.mul(&z_inv);
+
+        let mut bytes = y.to_bytes();
+        bytes[31] |= x.is_negative().unwrap_u8() << 7;
+
+        bytes
+    }
+
+    /// self + rhs, using the unified addition formula, which also holds
+    /// when self = rhs.
+    pub(crate) fn add(&self, rhs: &Self) -> Self {
+        let a = self.y.sub(&self.x).mul(&rhs.y.sub(&rhs.x));
+        let b = self.y.add(&self.x).mul(&rhs.y.add(&rhs.x));
+        let c = self.t.mul(&FieldElement::EDWARDS_D2).mul(&rhs.t);
+        let d = self.z.add(&self.z).mul(&rhs.z);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+
+        Self {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    /// -self.
+    pub(crate) fn neg(&self) -> Self {
+        Self {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    /// [scalar]self, where `scalar` is a little-endian integer, in constant
+    /// time.
+    pub(crate) fn mul(&self, scalar: &[u8; 32]) -> Self {
+        let mut result = Self::IDENTITY;
+        for byte in scalar.iter().rev() {
+            for bit in (0..8).rev() {
+                result = result.add(&result);
+                let sum = result.add(self);
+                result = Self::conditional_select(&result, &sum, Choice::from((byte >> bit) & 1));
+            }
+        }
+
+        result
+    }
+
+    /// [scalar]self, where `scalar` is a little-endian integer. The execution
+    /// time depends on `scalar`, which must therefore be public.
+    pub(crate) fn mul_vartime(&self, scalar: &[u8; 32]) -> Self {
+        let mut result = Self::IDENTITY;
+        for byte in scalar.iter().rev() {
+            for bit in (0..8).rev() {
+                result = result.add(&result);
+                if (byte >> bit) & 1 == 1 {
+                    result = result.add(self);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Whether this is the neutral element.
+    pub(crate) fn is_identity(&self) -> bool {
+        bool::from(self.x.is_zero() & self.y.ct_eq(&self.z))
+    }
+
+    /// Whether this point has an order dividing the cofactor 8.
+    pub(crate) fn is_small_order(&self) -> bool {
+        let p2 = self.add(self);
+        let p4 = p2.add(&p2);
+        p4.add(&p4).is_identity()
+    }
+
+    /// Whether this point lies in the prime-order subgroup.
+    pub(crate) fn is_torsion_free(&self) -> bool {
+        self.mul_vartime(&GROUP_ORDER).is_identity()
+    }
+
+    /// The u-coordinate of the corresponding point on the Montgomery form of
+    /// Curve25519, u = (1 + y) / (1 - y), encoded into 32 bytes. The identity
+    /// maps to u = 0.
+    pub(crate) fn to_montgomery(self) -> [u8; 32] {
+        // With y = Y/Z, u = (Z + Y) / (Z - Y).
+        let numerator = self.z.add(&self.y);
+        let denominator = self.z.sub(&self.y);
+
+        numerator.mul(&denominator.invert()).to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    /// The encoding of the Ed25519 basepoint.
+    const BASEPOINT: [u8; 32] = [
+        0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66,
+    ];
+
+    /// A point of order 8.
+    const ORDER_EIGHT: [u8; 32] = [
+        0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10, 0x67,
+        0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77, 0x92, 0xac,
+        0x03, 0x7a,
+    ];
+
+    #[test]
+    fn test_basepoint_roundtrip() {
+        let point = EdwardsPoint::from_bytes(&BASEPOINT).unwrap();
+        assert_eq!(point.to_bytes(), BASEPOINT);
+        assert!(!point.is_small_order());
+        assert!(point.is_torsion_free());
+        // The Montgomery form of the basepoint is u = 9.
+        let mut nine = [0u8; 32];
+        nine[0] = 9;
+        assert_eq!(point.to_montgomery(), nine);
+    }
+
+    #[test]
+    fn test_identity() {
+        let mut encoding = [0u8; 32];
+        encoding[0] = 1;
+        let identity = EdwardsPoint::from_bytes(&encoding).unwrap();
+        assert!(identity.is_identity());
+        assert_eq!(EdwardsPoint::IDENTITY.to_bytes(), encoding);
+
+        let point = EdwardsPoint::from_bytes(&BASEPOINT).unwrap();
+        assert_eq!(point.add(&identity).to_bytes(), BASEPOINT);
+        assert!(point.mul_vartime(&[0u8; 32]).is_identity());
+    }
+
+    #[test]
+    fn test_small_order() {
+        let point = EdwardsPoint::from_bytes(&ORDER_EIGHT).unwrap();
+        assert!(point.is_small_order());
+        assert!(!point.is_torsion_free());
+
+        // Adding a small-order point to the basepoint leaves the subgroup.
+        let basepoint = EdwardsPoint::from_bytes(&BASEPOINT).unwrap();
+        let mixed = basepoint.add(&point);
+        assert!(!mixed.is_small_order());
+        assert!(!mixed.is_torsion_free());
+    }
+
+    #[test]
+    fn test_reject_invalid() {
+        // y = p is non-canonical.
+        let mut y_is_p = [0xffu8; 32];
+        y_is_p[0] = 0xed;
+        y_is_p[31] = 0x7f;
+        assert!(EdwardsPoint::from_bytes(&y_is_p).is_err());
+
+        // y = 2 is not on the curve.
+        let mut y_is_two = [0u8; 32];
+        y_is_two[0] = 2;
+        assert!(EdwardsPoint::from_bytes(&y_is_two).is_err());
+
+        // y = 1 gives x = 0, which cannot have its sign bit set.
+        let mut negative_zero = [0u8; 32];
+        negative_zero[0] = 1;
+        negative_zero[31] = 0x80;
+        assert!(EdwardsPoint::from_bytes(&negative_zero).is_err());
+    }
+
+    #[test]
+    fn test_double_and_add() {
+        let point = EdwardsPoint::from_bytes(&BASEPOINT).unwrap();
+        let mut three = [0u8; 32];
+        three[0] = 3;
+        let tripled = point.add(&point).add(&point);
+        assert_eq!(point.mul_vartime(&three).to_bytes(), tripled.to_bytes());
+        assert_eq!(point.mul(&three).to_bytes(), tripled.to_bytes());
+    }
+
+    #[test]
+    fn test_basepoint_constant() {
+        assert_eq!(EdwardsPoint::BASEPOINT.to_bytes(), BASEPOINT);
+        assert!(EdwardsPoint::BASEPOINT.mul(&GROUP_ORDER).is_identity());
+    }
+
+    #[test]
+    fn test_mul_matches_mul_vartime() {
+        let scalar = [
+            0x3b, 0x91, 0x0e, 0xc4, 0x7f, 0x28, 0xd5, 0x61, 0x9a, 0x02, 0xef, 0x46, 0xb3, 0x7c,
+            0x18, 0xa9, 0x55, 0xe0, 0x2d, 0x83, 0x6f, 0x14, 0xca, 0x97, 0x40, 0xbb, 0x0d, 0x72,
+            0xf6, 0x39, 0x84, 0x0c,
+        ];
+        let point = EdwardsPoint::BASEPOINT;
+        assert_eq!(
+            point.mul(&scalar).to_bytes(),
+            point.mul_vartime(&scalar).to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_neg() {
+        let point = EdwardsPoint::BASEPOINT;
+        assert!(point.add(&point.neg()).is_identity());
+        assert!(EdwardsPoint::IDENTITY.neg().is_identity());
+    }
+}
@@ -0,0 +1,515 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic in the field of integers modulo 2^255 - 19.
+//!
+//! Elements are represented by five 51-bit limbs. Apart from
+//! [`FieldElement::pow_vartime()`], which only takes public exponents, all
+//! operations run in constant time.
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// The mask of the lower 51 bits of a limb.
+const LOW_51_BITS: u64 = (1u64 << 51) - 1;
+
+#[derive(Clone, Copy)]
+/// An element of the field of integers modulo 2^255 - 19.
+pub(crate) struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    /// The additive identity.
+    pub(crate) const ZERO: Self = Self([0, 0, 0, 0, 0]);
+    /// The multiplicative identity.
+    pub(crate) const ONE: Self = Self([1, 0, 0, 0, 0]);
+    /// The Edwards curve constant d = -121665/121666.
+    pub(crate) const EDWARDS_D: Self = Self([
+        929955233495203,
+        466365720129213,
+        1662059464998953,
+        2033849074728123,
+        1442794654840575,
+    ]);
+    /// 2 * d.
+    pub(crate) const EDWARDS_D2: Self = Self([
+        1859910466990425,
+        932731440258426,
+        1072319116312658,
+        1815898335770999,
+        633789495995903,
+    ]);
+    /// The Montgomery curve constant (A - 2) / 4 = 121665.
+    pub(crate) const A24: Self = Self([121665, 0, 0, 0, 0]);
+    /// A square root of -1.
+    pub(crate) const SQRT_M1: Self = Self([
+        1718705420411056,
+        234908883556509,
+        2233514472574048,
+        2117202627021982,
+        765476049583133,
+    ]);
+
+    /// 1 / sqrt(a - d), where a = -1, as used by ristretto255.
+    pub(crate) const INVSQRT_A_MINUS_D: Self = Self([
+        278908739862762,
+        821645201101625,
+        8113234426968,
+        1777959178193151,
+        2118520810568447,
+    ]);
+    /// sqrt(a * d - 1), where a = -1, as used by ristretto255.
+    pub(crate) const SQRT_AD_MINUS_ONE: Self = Self([
+        2241493124984347,
+        425987919032274,
+        2207028919301688,
+        1220490630685848,
+        974799131293748,
+    ]);
+    /// 1 - d^2.
+    pub(crate) const ONE_MINUS_D_SQ: Self = Self([
+        1136626929484150,
+        1998550399581263,
+        496427632559748,
+        118527312129759,
+        45110755273534,
+    ]);
+    /// (d - 1)^2.
+    pub(crate) const D_MINUS_ONE_SQ: Self = Self([
+        1507062230895904,
+        1572317787530805,
+        683053064812840,
+        317374165784489,
+        1572899562415810,
+    ]);
+    /// The x-coordinate of the Ed25519 basepoint.
+    pub(crate) const BASEPOINT_X: Self = Self([
+        1738742601995546,
+        1146398526822698,
+        2070867633025821,
+        562264141797630,
+        587772402128613,
+    ]);
+    /// The y-coordinate of the Ed25519 basepoint, 4/5.
+    pub(crate) const BASEPOINT_Y: Self = Self([
+        1801439850948184,
+        1351079888211148,
+        450359962737049,
+        900719925474099,
+        1801439850948198,
+    ]);
+    /// The product of the coordinates of the Ed25519 basepoint.
+    pub(crate) const BASEPOINT_XY: Self = Self([
+        1841354044333475,
+        16398895984059,
+        755974180946558,
+        900171276175154,
+        1821297809914039,
+    ]);
+
+    /// Load an element from its 32-byte little-endian encoding, ignoring the
+    /// most significant bit. Non-canonical encodings are accepted and reduced.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let load = |offset: usize| -> u64 {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_le_bytes(word)
+        };
+
+        Self([
+            load(0) & LOW_51_BITS,
+            (load(6) >> 3) & LOW_51_BITS,
+            (load(12) >> 6) & LOW_51_BITS,
+            (load(19) >> 1) & LOW_51_BITS,
+            (load(24) >> 12) & LOW_51_BITS,
+        ])
+    }
+
+    /// The canonical 32-byte little-endian encoding of this element.
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut limbs = Self::weak_reduce(self.0).0;
+
+        // Determine whether the value is at least p, by checking whether
+        // adding 19 carries out of 255 bits, and if so subtract p.
+        let mut q = (limbs[0] + 19) >> 51;
+        q = (limbs[1] + q) >> 51;
+        q = (limbs[2] + q) >> 51;
+        q = (limbs[3] + q) >> 51;
+        q = (limbs[4] + q) >> 51;
+
+        limbs[0] += 19 * q;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= LOW_51_BITS;
+        limbs[2] += limbs[1] >> 51;
+        limbs[1] &= LOW_51_BITS;
+        limbs[3] += limbs[2] >> 51;
+        limbs[2] &= LOW_51_BITS;
+        limbs[4] += limbs[3] >> 51;
+        limbs[3] &= LOW_51_BITS;
+        limbs[4] &= LOW_51_BITS;
+
+        let mut out = [0u8; 32];
+        let mut acc = 0u128;
+        let mut acc_bits = 0;
+        let mut idx = 0;
+        for limb in limbs.iter() {
+            acc |= u128::from(*limb) << acc_bits;
+            acc_bits += 51;
+            while acc_bits >= 8 {
+                out[idx] = acc as u8;
+                acc >>= 8;
+                acc_bits -= 8;
+                idx += 1;
+            }
+        }
+        out[idx] = acc as u8;
+
+        out
+    }
+
+    /// Carry the limbs so that each is below 2^52.
+    fn weak_reduce(mut limbs: [u64; 5]) -> Self {
+        let c0 = limbs[0] >> 51;
+        let c1 = limbs[1] >> 51;
+        let c2 = limbs[2] >> 51;
+        let c3 = limbs[3] >> 51;
+        let c4 = limbs[4] >> 51;
+
+        limbs[0] &= LOW_51_BITS;
+        limbs[1] &= LOW_51_BITS;
+        limbs[2] &= LOW_51_BITS;
+        limbs[3] &= LOW_51_BITS;
+        limbs[4] &= LOW_51_BITS;
+
+        limbs[0] += c4 * 19;
+        limbs[1] += c0;
+        limbs[2] += c1;
+        limbs[3] += c2;
+        limbs[4] += c3;
+
+        Self(limbs)
+    }
+
+    /// self + rhs.
+    pub(crate) fn add(&self, rhs: &Self) -> Self {
+        let mut limbs = self.0;
+        for (limb, r) in limbs.iter_mut().zip(rhs.0.iter()) {
+            *limb += r;
+        }
+
+        Self::weak_reduce(limbs)
+    }
+
+    /// self - rhs.
+    pub(crate) fn sub(&self, rhs: &Self) -> Self {
+        // Add 16 * p first, so that no limb underflows.
+        Self::weak_reduce([
+            (self.0[0] + 36028797018963664) - rhs.0[0],
+            (self.0[1] + 36028797018963952) - rhs.0[1],
+            (self.0[2] + 36028797018963952) - rhs.0[2],
+            (self.0[3] + 36028797018963952) - rhs.0[3],
+            (self.0[4] + 36028797018963952) - rhs.0[4],
+        ])
+    }
+
+    /// -self.
+    pub(crate) fn neg(&self) -> Self {
+        Self::ZERO.sub(self)
+    }
+
+    /// self * rhs.
+    pub(crate) fn mul(&self, rhs: &Self) -> Self {
+        let m = |x: u64, y: u64| -> u128 { u128::from(x) * u128::from(y) };
+        let a = &self.0;
+        let b = &rhs.0;
+
+        // Limbs above 2^255 wrap around multiplied by 19.
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 =
+            m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 =
+            m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        let mask = u128::from(LOW_51_BITS);
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+        let carry = (c4 >> 51) as u64;
+
+        let mut limbs = [
+            (c0 & mask) as u64,
+            (c1 & mask) as u64,
+            (c2 & mask) as u64,
+            (c3 & mask) as u64,
+            (c4 & mask) as u64,
+        ];
+        limbs[0] += carry * 19;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= LOW_51_BITS;
+
+        Self(limbs)
+    }
+
+    /// self^2.
+    pub(crate) fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    /// self^exponent, where `exponent` is a little-endian integer. The
+    /// execution time depends on `exponent`, which must therefore be public.
+    pub(crate) fn pow_vartime(&self, exponent: &[u8; 32]) -> Self {
+        let mut result = Self::ONE;
+        for byte in exponent.iter().rev() {
+            for bit in (0..8).rev() {
+                result = result.square();
+                if (byte >> bit) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// self^-1, or zero if self is zero.
+    pub(crate) fn invert(&self) -> Self {
+        // p - 2 = 2^255 - 21.
+        let mut exponent = [0xffu8; 32];
+        exponent[0] = 0xeb;
+        exponent[31] = 0x7f;
+
+        self.pow_vartime(&exponent)
+    }
+
+    /// self^((p - 5) / 8), used for computing square roots.
+    pub(crate) fn pow_p58(&self) -> Self {
+        // (p - 5) / 8 = 2^252 - 3.
+        let mut exponent = [0xffu8; 32];
+        exponent[0] = 0xfd;
+        exponent[31] = 0x0f;
+
+        self.pow_vartime(&exponent)
+    }
+
+    /// |self|, the one of self and -self whose encoding is even.
+    pub(crate) fn abs(&self) -> Self {
+        Self::conditional_select(self, &self.neg(), self.is_negative())
+    }
+
+    /// The non-negative square root of u/v, or of SQRT_M1 * u/v if u/v is not
+    /// a square, along with whether u/v was a square. If u is zero, the root
+    /// is zero and u/v is considered a square. If v is zero and u is not, u/v
+    /// is not a square. This is SQRT_RATIO_M1 from
+    /// [RFC 9496](https://www.rfc-editor.org/rfc/rfc9496#section-4.2).
+    pub(crate) fn sqrt_ratio_m1(u: &Self, v: &Self) -> (Choice, Self) {
+        let v3 = v.square().mul(v);
+        let v7 = v3.square().mul(v);
+        let mut r = u.mul(&v3).mul(&u.mul(&v7).pow_p58());
+        let check = v.mul(&r.square());
+
+        let u_neg = u.neg();
+        let correct_sign = check.ct_eq(u);
+        let flipped_sign = check.ct_eq(&u_neg);
+        let flipped_sign_i = check.ct_eq(&u_neg.mul(&Self::SQRT_M1));
+
+        r = Self::conditional_select(&r, &r.mul(&Self::SQRT_M1), flipped_sign | flipped_sign_i);
+
+        (correct_sign | flipped_sign, r.abs())
+    }
+
+    /// Whether this element is zero.
+    pub(crate) fn is_zero(&self) -> Choice {
+        self.to_bytes().ct_eq(&[0u8; 32])
+    }
+
+    /// Whether the canonical encoding of this element is odd, which is used as
+    /// its sign.
+    pub(crate) fn is_negative(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+}
+
+impl ConstantTimeEq for FieldElement {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
+}
+
+impl ConditionallySelectable for FieldElement {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut limbs = [0u64; 5];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = u64::conditional_select(&a.0[i], &b.0[i], choice);
+        }
+
+        Self(limbs)
+    }
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn from_u64(value: u64) -> FieldElement {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&value.to_le_bytes());
+        FieldElement::from_bytes(&bytes)
+    }
+
+    fn assert_eq_fe(a: &FieldElement, b: &FieldElement) {
+        assert_eq!(a.to_bytes(), b.to_bytes());
+    }
+
+    #[test]
+    fn test_constants() {
+        // d * 121666 = -121665
+        assert_eq_fe(
+            &FieldElement::EDWARDS_D.mul(&from_u64(121666)),
+            &from_u64(121665).neg(),
+        );
+        assert_eq_fe(
+            &FieldElement::EDWARDS_D.add(&FieldElement::EDWARDS_D),
+            &FieldElement::EDWARDS_D2,
+        );
+        assert_eq_fe(&FieldElement::SQRT_M1.square(), &FieldElement::ONE.neg());
+
+        let one = FieldElement::ONE;
+        let d = FieldElement::EDWARDS_D;
+        // (a - d) * INVSQRT_A_MINUS_D^2 = 1
+        assert_eq_fe(
+            &one.neg()
+                .sub(&d)
+                .mul(&FieldElement::INVSQRT_A_MINUS_D.square()),
+            &one,
+        );
+        assert_eq_fe(
+            &FieldElement::SQRT_AD_MINUS_ONE.square(),
+            &d.neg().sub(&one),
+        );
+        assert_eq_fe(&FieldElement::ONE_MINUS_D_SQ, &one.sub(&d.square()));
+        assert_eq_fe(&FieldElement::D_MINUS_ONE_SQ, &d.sub(&one).square());
+
+        // 5 * y = 4, and the basepoint is on the curve.
+        let x = FieldElement::BASEPOINT_X;
+        let y = FieldElement::BASEPOINT_Y;
+        assert_eq_fe(&y.mul(&from_u64(5)), &from_u64(4));
+        assert_eq_fe(&x.mul(&y), &FieldElement::BASEPOINT_XY);
+        let (x2, y2) = (x.square(), y.square());
+        assert_eq_fe(&y2.sub(&x2), &one.add(&d.mul(&x2).mul(&y2)));
+    }
+
+    #[test]
+    fn test_sqrt_ratio_m1() {
+        // 4/1 is a square, with the even root 2.
+        let (was_square, r) = FieldElement::sqrt_ratio_m1(&from_u64(4), &FieldElement::ONE);
+        assert!(bool::from(was_square));
+        assert_eq_fe(&r, &from_u64(2));
+
+        // 2 is not a square, so the root of 2 * SQRT_M1 is returned.
+        let (was_square, r) = FieldElement::sqrt_ratio_m1(&from_u64(2), &FieldElement::ONE);
+        assert!(!bool::from(was_square));
+        assert_eq_fe(&r.square(), &from_u64(2).mul(&FieldElement::SQRT_M1));
+        assert!(!bool::from(r.is_negative()));
+
+        // u = 0 gives zero, v = 0 with u != 0 is not a square.
+        let (was_square, r) = FieldElement::sqrt_ratio_m1(&FieldElement::ZERO, &from_u64(3));
+        assert!(bool::from(was_square));
+        assert!(bool::from(r.is_zero()));
+        let (was_square, r) = FieldElement::sqrt_ratio_m1(&from_u64(3), &FieldElement::ZERO);
+        assert!(!bool::from(was_square));
+        assert!(bool::from(r.is_zero()));
+
+        // 9/4 has the root 3/2.
+        let (was_square, r) = FieldElement::sqrt_ratio_m1(&from_u64(9), &from_u64(4));
+        assert!(bool::from(was_square));
+        assert_eq_fe(&r.square().mul(&from_u64(4)), &from_u64(9));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let bytes = [
+            0x1f, 0x9a, 0x03, 0x44, 0xde, 0x71, 0xb0, 0x2c, 0x58, 0x6e, 0x93, 0x0d, 0xa4, 0xf2,
+            0x37, 0x8b, 0x61, 0xc5, 0x0e, 0x7a, 0x29, 0xe3, 0x94, 0x4f, 0xb6, 0x10, 0x85, 0xcc,
+            0x3d, 0x7e, 0xa1, 0x52,
+        ];
+        assert_eq!(FieldElement::from_bytes(&bytes).to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_non_canonical_reduced() {
+        // p + 1 is reduced to 1.
+        let mut p_plus_one = [0xffu8; 32];
+        p_plus_one[0] = 0xee;
+        p_plus_one[31] = 0x7f;
+        assert_eq_fe(&FieldElement::from_bytes(&p_plus_one), &FieldElement::ONE);
+
+        // The most significant bit is ignored.
+        let mut one_high_bit = [0u8; 32];
+        one_high_bit[0] = 1;
+        one_high_bit[31] = 0x80;
+        assert_eq_fe(&FieldElement::from_bytes(&one_high_bit), &FieldElement::ONE);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = from_u64(1234567);
+        let b = from_u64(7654321);
+
+        assert_eq_fe(&a.add(&b), &from_u64(8888888));
+        assert_eq_fe(&b.sub(&a), &from_u64(6419754));
+        assert_eq_fe(&a.sub(&b).add(&b), &a);
+        assert_eq_fe(&a.mul(&b), &from_u64(1234567 * 7654321));
+        assert_eq_fe(&a.neg().add(&a), &FieldElement::ZERO);
+        assert_eq_fe(&a.mul(&a.invert()), &FieldElement::ONE);
+        assert_eq_fe(&FieldElement::ZERO.invert(), &FieldElement::ZERO);
+
+        // -1 is p - 1 and so even, while 1 is odd.
+        assert!(bool::from(FieldElement::ONE.is_negative()));
+        assert!(!bool::from(FieldElement::ONE.neg().is_negative()));
+        assert!(bool::from(FieldElement::ZERO.is_zero()));
+        assert!(!bool::from(FieldElement::ONE.is_zero()));
+    }
+
+    #[test]
+    fn test_conditional_swap() {
+        let mut a = FieldElement::ONE;
+        let mut b = FieldElement::SQRT_M1;
+        FieldElement::conditional_swap(&mut a, &mut b, Choice::from(0));
+        assert_eq_fe(&a, &FieldElement::ONE);
+        FieldElement::conditional_swap(&mut a, &mut b, Choice::from(1));
+        assert_eq_fe(&a, &FieldElement::SQRT_M1);
+        assert_eq_fe(&b, &FieldElement::ONE);
+    }
+
+    #[test]
+    fn test_invert_large() {
+        let a = FieldElement::SQRT_M1.mul(&FieldElement::EDWARDS_D);
+        assert_eq_fe(&a.invert().mul(&a), &FieldElement::ONE);
+        assert!(bool::from(a.invert().invert().ct_eq(&a)));
+    }
+}
@@ -0,0 +1,86 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// The macro modules come first, so that their macros are in scope for the
+// modules that use them.
+#[macro_use]
+/// Montgomery arithmetic modulo odd integers of any number of limbs.
+mod modular;
+
+#[macro_use]
+/// Complete point arithmetic on short Weierstrass curves.
+mod weierstrass;
+
+/// Schnorr signatures over secp256k1 (BIP 340).
+pub mod bip340;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Bulletproofs range proofs over ristretto255.
+pub mod bulletproofs;
+
+/// Operations on Curve25519.
+pub mod curve25519;
+
+/// ECDSA signatures over P-256 with SHA-256.
+pub mod ecdsa_p256;
+
+/// Ed25519 signatures.
+pub mod ed25519;
+
+/// Points on the twisted Edwards form of Curve25519.
+pub(crate) mod edwards25519;
+
+/// Arithmetic modulo 2^255 - 19.
+pub(crate) mod field25519;
+
+/// Montgomery arithmetic modulo 256-bit odd integers.
+pub(crate) mod modular256;
+
+/// Montgomery arithmetic modulo 384-bit odd integers.
+pub(crate) mod modular384;
+
+/// MuSig2 multi-signatures over secp256k1 (BIP 327).
+pub mod musig2;
+
+/// Points on the P-256 curve.
+pub(crate) mod p256;
+
+/// Points on the P-384 curve.
+pub(crate) mod p384;
+
+/// Pedersen commitments over ristretto255.
+pub mod pedersen;
+
+/// The ristretto255 prime-order group.
+pub(crate) mod ristretto255;
+
+/// Points on the secp256k1 curve.
+pub(crate) mod secp256k1;
+
+/// Ed25519 and X25519 key pairs derived from a master seed.
+pub mod seed;
+
+/// The P-384 verifiable oblivious pseudorandom function of RFC 9497.
+pub mod voprf;
+
+/// Diffie-Hellman key agreement using X25519.
+pub mod x25519;
@@ -0,0 +1,331 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic modulo odd moduli, in Montgomery form, for any number of 64-bit
+//! limbs.
+//!
+//! `impl_residue!` defines a `Modulus` trait and a `Residue<M>` type in the
+//! module that invokes it, so that every limb count has its own types but
+//! shares the same code. It is meant to be the only item of that module, apart
+//! from its tests.
+
+/// a + b * c + carry, returning the low and high words.
+pub(crate) fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let wide = u128::from(a) + u128::from(b) * u128::from(c) + u128::from(carry);
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// a + b + carry, returning the sum and the carry.
+pub(crate) fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let wide = u128::from(a) + u128::from(b) + u128::from(carry);
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// a - b - borrow, returning the difference and the borrow.
+pub(crate) fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let wide = u128::from(a).wrapping_sub(u128::from(b) + u128::from(borrow));
+    (wide as u64, (wide >> 127) as u64)
+}
+
+/// Macro that defines a `Modulus` trait for odd moduli of `$limbs` 64-bit
+/// limbs, and a `Residue<M>` type for integers modulo such a modulus. Residues
+/// are encoded as `$bytes` big-endian bytes, and `from_bytes_wide()` takes
+/// `$wide_bytes` bytes, which must be more than `$bytes` and at most twice as
+/// many.
+///
+/// Residues are kept fully reduced, as little-endian limbs. Apart from
+/// `Residue::pow_vartime()`, which only takes public exponents, all
+/// operations run in constant time.
+macro_rules! impl_residue (($limbs:expr, $bytes:expr, $wide_bytes:expr) => (
+    use crate::hazardous::ecc::modular::{adc, mac, sbb};
+    use core::marker::PhantomData;
+    use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+    use zeroize::Zeroize;
+
+    /// An odd modulus less than R.
+    pub(crate) trait Modulus: Copy {
+        /// The modulus, as little-endian 64-bit limbs.
+        const MODULUS: [u64; $limbs];
+        /// R^2 mod m, where R = 2^(64 * limbs).
+        const R2: [u64; $limbs];
+        /// -m^-1 mod 2^64.
+        const M0_INV: u64;
+    }
+
+    /// a - b, returning the difference and the borrow.
+    fn sub_limbs(a: &[u64; $limbs], b: &[u64; $limbs]) -> ([u64; $limbs], u64) {
+        let mut out = [0u64; $limbs];
+        let mut borrow = 0;
+        for i in 0..$limbs {
+            let (diff, next) = sbb(a[i], b[i], borrow);
+            out[i] = diff;
+            borrow = next;
+        }
+
+        (out, borrow)
+    }
+
+    /// Select `a` if `choice` is unset, or `b` if it is set.
+    fn select_limbs(a: &[u64; $limbs], b: &[u64; $limbs], choice: Choice) -> [u64; $limbs] {
+        let mut out = [0u64; $limbs];
+        for i in 0..$limbs {
+            out[i] = u64::conditional_select(&a[i], &b[i], choice);
+        }
+
+        out
+    }
+
+    /// The limbs of the small integer `value`.
+    fn small_limbs(value: u64) -> [u64; $limbs] {
+        let mut limbs = [0u64; $limbs];
+        limbs[0] = value;
+
+        limbs
+    }
+
+    /// The big-endian encoding of `limbs`.
+    fn limbs_to_bytes(limbs: &[u64; $limbs]) -> [u8; $bytes] {
+        let mut bytes = [0u8; $bytes];
+        for (i, limb) in limbs.iter().enumerate() {
+            bytes[$bytes - 8 - 8 * i..$bytes - 8 * i].copy_from_slice(&limb.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    #[derive(Clone, Copy)]
+    /// A residue modulo `M`.
+    pub(crate) struct Residue<M: Modulus> {
+        /// The value times R, modulo `M`.
+        limbs: [u64; $limbs],
+        modulus: PhantomData<M>,
+    }
+
+    impl<M: Modulus> Residue<M> {
+        /// Zero.
+        pub(crate) const ZERO: Self = Self {
+            limbs: [0u64; $limbs],
+            modulus: PhantomData,
+        };
+
+        fn from_limbs(limbs: [u64; $limbs]) -> Self {
+            Self {
+                limbs,
+                modulus: PhantomData,
+            }
+        }
+
+        /// Subtract the modulus from the value `high || limbs`, if it is at
+        /// least the modulus. The value must be less than twice the modulus.
+        fn reduce_once(limbs: &[u64; $limbs], high: u64) -> [u64; $limbs] {
+            let (reduced, borrow) = sub_limbs(limbs, &M::MODULUS);
+            let use_reduced = Choice::from((high | (borrow ^ 1)) as u8 & 1);
+
+            select_limbs(limbs, &reduced, use_reduced)
+        }
+
+        /// Montgomery multiplication, a * b / R mod m.
+        fn montgomery_mul(a: &[u64; $limbs], b: &[u64; $limbs]) -> [u64; $limbs] {
+            let mut t = [0u64; $limbs + 2];
+            for b_i in b.iter() {
+                let mut carry = 0;
+                for j in 0..$limbs {
+                    let (lo, hi) = mac(t[j], a[j], *b_i, carry);
+                    t[j] = lo;
+                    carry = hi;
+                }
+                let (sum, c) = adc(t[$limbs], carry, 0);
+                t[$limbs] = sum;
+                t[$limbs + 1] = c;
+
+                let m = t[0].wrapping_mul(M::M0_INV);
+                let (_, mut carry) = mac(t[0], m, M::MODULUS[0], 0);
+                for j in 1..$limbs {
+                    let (lo, hi) = mac(t[j], m, M::MODULUS[j], carry);
+                    t[j - 1] = lo;
+                    carry = hi;
+                }
+                let (sum, c) = adc(t[$limbs], carry, 0);
+                t[$limbs - 1] = sum;
+                t[$limbs] = t[$limbs + 1] + c;
+            }
+
+            let mut low = [0u64; $limbs];
+            low.copy_from_slice(&t[..$limbs]);
+
+            Self::reduce_once(&low, t[$limbs])
+        }
+
+        /// Parse a big-endian integer into limbs.
+        fn limbs_from_bytes(bytes: &[u8; $bytes]) -> [u64; $limbs] {
+            let mut limbs = [0u64; $limbs];
+            for (i, limb) in limbs.iter_mut().enumerate() {
+                let mut word = [0u8; 8];
+                word.copy_from_slice(&bytes[$bytes - 8 - 8 * i..$bytes - 8 * i]);
+                *limb = u64::from_be_bytes(word);
+            }
+
+            limbs
+        }
+
+        /// `value` mod m.
+        pub(crate) fn from_u64(value: u64) -> Self {
+            Self::from_limbs(Self::montgomery_mul(&small_limbs(value), &M::R2))
+        }
+
+        /// One.
+        pub(crate) fn one() -> Self {
+            Self::from_u64(1)
+        }
+
+        /// Load a big-endian integer, reducing it modulo m.
+        pub(crate) fn from_bytes_reduced(bytes: &[u8; $bytes]) -> Self {
+            // Montgomery multiplication reduces any left operand below R.
+            Self::from_limbs(Self::montgomery_mul(&Self::limbs_from_bytes(bytes), &M::R2))
+        }
+
+        /// Load a wide big-endian integer, reducing it modulo m.
+        pub(crate) fn from_bytes_wide(bytes: &[u8; $wide_bytes]) -> Self {
+            let mut high = [0u8; $bytes];
+            let mut low = [0u8; $bytes];
+            high[2 * $bytes - $wide_bytes..].copy_from_slice(&bytes[..$wide_bytes - $bytes]);
+            low.copy_from_slice(&bytes[$wide_bytes - $bytes..]);
+
+            // high * R^2 / R = high * R, the Montgomery form of high * R.
+            let high = Self::from_bytes_reduced(&high).mul(&Self::from_limbs(M::R2));
+            let low = Self::from_bytes_reduced(&low);
+
+            high.add(&low)
+        }
+
+        /// Load a big-endian integer, returning whether it was less than m. If
+        /// it was not, the returned residue is not meaningful.
+        pub(crate) fn from_bytes(bytes: &[u8; $bytes]) -> (Self, Choice) {
+            let limbs = Self::limbs_from_bytes(bytes);
+            let (_, borrow) = sub_limbs(&limbs, &M::MODULUS);
+
+            (
+                Self::from_limbs(Self::montgomery_mul(&limbs, &M::R2)),
+                Choice::from(borrow as u8),
+            )
+        }
+
+        /// The big-endian encoding of this residue.
+        pub(crate) fn to_bytes(self) -> [u8; $bytes] {
+            limbs_to_bytes(&Self::montgomery_mul(&self.limbs, &small_limbs(1)))
+        }
+
+        /// self + rhs.
+        pub(crate) fn add(&self, rhs: &Self) -> Self {
+            let mut sum = [0u64; $limbs];
+            let mut carry = 0;
+            for (i, limb) in sum.iter_mut().enumerate() {
+                let (s, c) = adc(self.limbs[i], rhs.limbs[i], carry);
+                *limb = s;
+                carry = c;
+            }
+
+            Self::from_limbs(Self::reduce_once(&sum, carry))
+        }
+
+        /// self - rhs.
+        pub(crate) fn sub(&self, rhs: &Self) -> Self {
+            let (diff, borrow) = sub_limbs(&self.limbs, &rhs.limbs);
+            let mut wrapped = [0u64; $limbs];
+            let mut carry = 0;
+            for (i, limb) in wrapped.iter_mut().enumerate() {
+                let (s, c) = adc(diff[i], M::MODULUS[i], carry);
+                *limb = s;
+                carry = c;
+            }
+
+            Self::from_limbs(select_limbs(&diff, &wrapped, Choice::from(borrow as u8)))
+        }
+
+        /// -self.
+        pub(crate) fn neg(&self) -> Self {
+            Self::ZERO.sub(self)
+        }
+
+        /// self * rhs.
+        pub(crate) fn mul(&self, rhs: &Self) -> Self {
+            Self::from_limbs(Self::montgomery_mul(&self.limbs, &rhs.limbs))
+        }
+
+        /// self^2.
+        pub(crate) fn square(&self) -> Self {
+            self.mul(self)
+        }
+
+        /// self^exponent, where `exponent` is a big-endian integer. The
+        /// execution time depends on `exponent`, which must therefore be
+        /// public.
+        pub(crate) fn pow_vartime(&self, exponent: &[u8; $bytes]) -> Self {
+            let mut result = Self::one();
+            for byte in exponent.iter() {
+                for bit in (0..8).rev() {
+                    result = result.square();
+                    if (byte >> bit) & 1 == 1 {
+                        result = result.mul(self);
+                    }
+                }
+            }
+
+            result
+        }
+
+        /// self^-1, or zero if self is zero. The modulus must be prime.
+        pub(crate) fn invert(&self) -> Self {
+            let (exponent, _) = sub_limbs(&M::MODULUS, &small_limbs(2));
+
+            self.pow_vartime(&limbs_to_bytes(&exponent))
+        }
+
+        /// Whether this residue is zero.
+        pub(crate) fn is_zero(&self) -> Choice {
+            self.limbs.ct_eq(&[0u64; $limbs])
+        }
+
+        /// Whether the canonical value of this residue is odd.
+        pub(crate) fn is_odd(&self) -> Choice {
+            Choice::from(self.to_bytes()[$bytes - 1] & 1)
+        }
+    }
+
+    impl<M: Modulus> ConstantTimeEq for Residue<M> {
+        fn ct_eq(&self, other: &Self) -> Choice {
+            self.limbs.ct_eq(&other.limbs)
+        }
+    }
+
+    impl<M: Modulus> ConditionallySelectable for Residue<M> {
+        fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+            Self::from_limbs(select_limbs(&a.limbs, &b.limbs, choice))
+        }
+    }
+
+    impl<M: Modulus> Zeroize for Residue<M> {
+        fn zeroize(&mut self) {
+            self.limbs.iter_mut().zeroize();
+        }
+    }
+));
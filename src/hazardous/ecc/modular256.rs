@@ -0,0 +1,140 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic modulo 256-bit odd moduli, in Montgomery form.
+//!
+//! Residues are kept fully reduced, as four little-endian 64-bit limbs. Apart
+//! from [`Residue::pow_vartime()`], which only takes public exponents, all
+//! operations run in constant time.
+
+impl_residue!(4, 32, 64);
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    /// The prime 2^255 - 19.
+    struct P25519;
+
+    impl Modulus for P25519 {
+        const MODULUS: [u64; 4] = [
+            0xffff_ffff_ffff_ffed,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0x7fff_ffff_ffff_ffff,
+        ];
+        const R2: [u64; 4] = [0x5a4, 0, 0, 0];
+        const M0_INV: u64 = 0x86bc_a1af_286b_ca1b;
+    }
+
+    type Fe = Residue<P25519>;
+
+    fn assert_eq_residue(a: &Fe, b: &Fe) {
+        assert_eq!(a.to_bytes(), b.to_bytes());
+    }
+
+    fn be_bytes(value: u64) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(37) ^ 0x5c;
+        }
+        bytes[0] &= 0x7f;
+        let (value, is_canonical) = Fe::from_bytes(&bytes);
+        assert!(bool::from(is_canonical));
+        assert_eq!(value.to_bytes(), bytes);
+        assert_eq!(Fe::from_u64(1234).to_bytes(), be_bytes(1234));
+    }
+
+    #[test]
+    fn test_non_canonical() {
+        // m + 5
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = 0x7f;
+        bytes[31] = 0xf2;
+        let (_, is_canonical) = Fe::from_bytes(&bytes);
+        assert!(!bool::from(is_canonical));
+        assert_eq_residue(&Fe::from_bytes_reduced(&bytes), &Fe::from_u64(5));
+
+        let (_, is_canonical) = Fe::from_bytes(&[0xffu8; 32]);
+        assert!(!bool::from(is_canonical));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Fe::from_u64(1234567);
+        let b = Fe::from_u64(7654321);
+
+        assert_eq_residue(&a.add(&b), &Fe::from_u64(8888888));
+        assert_eq_residue(&b.sub(&a), &Fe::from_u64(6419754));
+        assert_eq_residue(&a.sub(&b).add(&b), &a);
+        assert_eq_residue(&a.mul(&b), &Fe::from_u64(1234567 * 7654321));
+        assert_eq_residue(&a.neg().add(&a), &Fe::ZERO);
+        assert_eq_residue(&a.mul(&a.invert()), &Fe::one());
+        assert_eq_residue(&Fe::ZERO.invert(), &Fe::ZERO);
+        // -1 = m - 1 is even.
+        assert!(!bool::from(Fe::one().neg().is_odd()));
+        assert!(bool::from(Fe::one().is_odd()));
+        assert!(bool::from(Fe::ZERO.is_zero()));
+        assert!(bool::from(a.ct_eq(&Fe::from_u64(1234567))));
+    }
+
+    #[test]
+    fn test_large_values() {
+        // (m - 1)^2 = 1.
+        let minus_one = Fe::one().neg();
+        assert_eq_residue(&minus_one.square(), &Fe::one());
+        // 2^255 = 19.
+        let mut two_255 = [0u8; 32];
+        two_255[0] = 0x80;
+        assert_eq_residue(&Fe::from_bytes_reduced(&two_255), &Fe::from_u64(19));
+        assert_eq_residue(&minus_one.add(&minus_one), &Fe::from_u64(2).neg());
+        // 2^256 - 1 = 37.
+        assert_eq_residue(&Fe::from_bytes_reduced(&[0xffu8; 32]), &Fe::from_u64(37));
+    }
+
+    #[test]
+    fn test_from_bytes_wide() {
+        // 2^512 - 1 = 38^2 - 1 = 1443.
+        assert_eq_residue(&Fe::from_bytes_wide(&[0xffu8; 64]), &Fe::from_u64(1443));
+        // 2^256 + 5 = 43.
+        let mut bytes = [0u8; 64];
+        bytes[31] = 1;
+        bytes[63] = 5;
+        assert_eq_residue(&Fe::from_bytes_wide(&bytes), &Fe::from_u64(43));
+    }
+
+    #[test]
+    fn test_conditional_select() {
+        let a = Fe::from_u64(1);
+        let b = Fe::from_u64(2);
+        assert_eq_residue(&Fe::conditional_select(&a, &b, Choice::from(0)), &a);
+        assert_eq_residue(&Fe::conditional_select(&a, &b, Choice::from(1)), &b);
+    }
+}
@@ -0,0 +1,173 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic modulo 384-bit odd moduli, in Montgomery form.
+//!
+//! Residues are kept fully reduced, as six little-endian 64-bit limbs. Apart
+//! from [`Residue::pow_vartime()`], which only takes public exponents, all
+//! operations run in constant time. [`Residue::from_bytes_wide()`] takes 72
+//! bytes, the length that hash-to-field uses for 384-bit moduli.
+
+impl_residue!(6, 48, 72);
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    /// The prime 2^384 - 2^128 - 2^96 + 2^32 - 1.
+    struct P384;
+
+    impl Modulus for P384 {
+        const MODULUS: [u64; 6] = [
+            0x0000_0000_ffff_ffff,
+            0xffff_ffff_0000_0000,
+            0xffff_ffff_ffff_fffe,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+        ];
+        const R2: [u64; 6] = [
+            0xffff_fffe_0000_0001,
+            0x0000_0002_0000_0000,
+            0xffff_fffe_0000_0000,
+            0x0000_0002_0000_0000,
+            0x0000_0000_0000_0001,
+            0x0000_0000_0000_0000,
+        ];
+        const M0_INV: u64 = 0x0000_0001_0000_0001;
+    }
+
+    type Fe = Residue<P384>;
+
+    fn assert_eq_residue(a: &Fe, b: &Fe) {
+        assert_eq!(a.to_bytes()[..], b.to_bytes()[..]);
+    }
+
+    fn from_hex(hex_str: &str) -> [u8; 48] {
+        let mut bytes = [0u8; 48];
+        bytes.copy_from_slice(&hex::decode(hex_str).unwrap());
+        bytes
+    }
+
+    fn be_bytes(value: u64) -> [u8; 48] {
+        let mut bytes = [0u8; 48];
+        bytes[40..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut bytes = [0u8; 48];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(37) ^ 0x5c;
+        }
+        bytes[0] &= 0x7f;
+        let (value, is_canonical) = Fe::from_bytes(&bytes);
+        assert!(bool::from(is_canonical));
+        assert_eq!(value.to_bytes()[..], bytes[..]);
+        assert_eq!(Fe::from_u64(1234).to_bytes()[..], be_bytes(1234)[..]);
+    }
+
+    #[test]
+    fn test_non_canonical() {
+        let mut modulus = [0xffu8; 48];
+        modulus[31] = 0xfe;
+        modulus[36..44].copy_from_slice(&[0u8; 8]);
+        let (_, is_canonical) = Fe::from_bytes(&modulus);
+        assert!(!bool::from(is_canonical));
+        assert_eq_residue(&Fe::from_bytes_reduced(&modulus), &Fe::ZERO);
+
+        // m + 5
+        let mut m_plus_five = modulus;
+        m_plus_five[44..].copy_from_slice(&[0, 0, 0, 4]);
+        m_plus_five[43] = 0x01;
+        let (_, is_canonical) = Fe::from_bytes(&m_plus_five);
+        assert!(!bool::from(is_canonical));
+        assert_eq_residue(&Fe::from_bytes_reduced(&m_plus_five), &Fe::from_u64(5));
+
+        let (_, is_canonical) = Fe::from_bytes(&[0xffu8; 48]);
+        assert!(!bool::from(is_canonical));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Fe::from_u64(1234567);
+        let b = Fe::from_u64(7654321);
+
+        assert_eq_residue(&a.add(&b), &Fe::from_u64(8888888));
+        assert_eq_residue(&b.sub(&a), &Fe::from_u64(6419754));
+        assert_eq_residue(&a.sub(&b).add(&b), &a);
+        assert_eq_residue(&a.mul(&b), &Fe::from_u64(1234567 * 7654321));
+        assert_eq_residue(&a.neg().add(&a), &Fe::ZERO);
+        assert_eq_residue(&a.mul(&a.invert()), &Fe::one());
+        assert_eq_residue(&Fe::ZERO.invert(), &Fe::ZERO);
+        // -1 = m - 1 is even.
+        assert!(!bool::from(Fe::one().neg().is_odd()));
+        assert!(bool::from(Fe::one().is_odd()));
+        assert!(bool::from(Fe::ZERO.is_zero()));
+        assert!(bool::from(a.ct_eq(&Fe::from_u64(1234567))));
+    }
+
+    #[test]
+    fn test_large_values() {
+        // (m - 1)^2 = 1.
+        let minus_one = Fe::one().neg();
+        assert_eq_residue(&minus_one.square(), &Fe::one());
+        assert_eq_residue(&minus_one.add(&minus_one), &Fe::from_u64(2).neg());
+        // 2^384 - 1 = 2^128 + 2^96 - 2^32.
+        assert_eq!(
+            Fe::from_bytes_reduced(&[0xffu8; 48]).to_bytes()[..],
+            from_hex(
+                "000000000000000000000000000000000000000000000000000000000000000100000000ffffffffffffffff00000000"
+            )[..]
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_wide() {
+        assert_eq!(
+            Fe::from_bytes_wide(&[0xffu8; 72]).to_bytes()[..],
+            from_hex(
+                "000000000000000100000000ffffffffffffffff00000000ffffffffffffffffffffffffffffffffffffffffffffffff"
+            )[..]
+        );
+        // 2^384 + 5.
+        let mut bytes = [0u8; 72];
+        bytes[23] = 1;
+        bytes[71] = 5;
+        assert_eq!(
+            Fe::from_bytes_wide(&bytes).to_bytes()[..],
+            from_hex(
+                "000000000000000000000000000000000000000000000000000000000000000100000000ffffffffffffffff00000006"
+            )[..]
+        );
+    }
+
+    #[test]
+    fn test_conditional_select() {
+        let a = Fe::from_u64(1);
+        let b = Fe::from_u64(2);
+        assert_eq_residue(&Fe::conditional_select(&a, &b, Choice::from(0)), &a);
+        assert_eq_residue(&Fe::conditional_select(&a, &b, Choice::from(1)), &b);
+    }
+}
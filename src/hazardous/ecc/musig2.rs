@@ -0,0 +1,822 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! MuSig2 multi-signatures over secp256k1, as specified in [BIP 327](https://github.com/bitcoin/bips/blob/master/bip-0327.mediawiki).
+//!
+//! A group of signers aggregate their public keys into a single aggregate
+//! public key. In two rounds, they then produce a single [BIP 340] signature,
+//! which is verified against the aggregate public key with [`bip340::verify()`].
+//! All signers must take part in signing.
+//!
+//! Public keys of the individual signers are 33-byte compressed points,
+//! while the aggregate public key is a 32-byte BIP 340 public key.
+//!
+//! The signing session goes as follows:
+//! 1. Each signer calls [`nonce_gen()`] and sends the [`PublicNonce`] to the
+//!    others, keeping the [`SecretNonce`].
+//! 2. The public nonces are combined with [`aggregate_nonces()`].
+//! 3. Each signer calls [`sign()`] with their [`SecretNonce`] and sends the
+//!    [`PartialSignature`] to the others.
+//! 4. The partial signatures are combined with
+//!    [`aggregate_partial_signatures()`], optionally after checking each of
+//!    them with [`verify_partial()`].
+//!
+//! Tweaking of the aggregate public key is not supported.
+//!
+//! # Parameters:
+//! - `public_keys`: The compressed public keys of all signers.
+//! - `rand`: 32 bytes of fresh randomness for the nonce.
+//! - `secret_key`: The 32-byte secret key of the signer.
+//! - `public_key`: The compressed public key of the signer.
+//! - `agg_public_key`: The aggregate public key, if already known.
+//! - `message`: The message to be signed.
+//! - `dst_out`: Destination buffer for the aggregate signature.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `public_keys` is empty, or any of them is not a valid compressed point.
+//! - The public keys aggregate to the point at infinity.
+//! - `rand` is not 32 bytes.
+//! - `secret_key` is not a valid BIP 340 secret key, or does not belong to
+//!   `public_key`, or to the [`SecretNonce`] used for signing.
+//! - `agg_public_key` is not 32 bytes.
+//! - Any of the nonces is not made of valid compressed points.
+//! - A partial signature is not valid.
+//! - `dst_out` is not 64 bytes.
+//!
+//! # Security:
+//! - `rand` __must__ be fresh randomness for every call to [`nonce_gen()`].
+//!   Reusing a nonce for two signatures reveals the secret key. Using
+//!   [`secure_rand_bytes()`] is recommended. This is also why
+//!   [`SecretNonce`] cannot be copied or serialized, and is consumed by
+//!   [`sign()`].
+//! - The aggregate signature is only valid if all partial signatures are.
+//!   Use [`verify_partial()`] to find out which signer misbehaved if it is not.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::{bip340, musig2};
+//!
+//! let secret_keys = [[0x01u8; 32], [0x02u8; 32]];
+//! let mut public_keys = [[0u8; musig2::PUBLIC_KEYSIZE]; 2];
+//! for (sk, pk) in secret_keys.iter().zip(public_keys.iter_mut()) {
+//!     musig2::public_key(sk, pk)?;
+//! }
+//!
+//! let ctx = musig2::KeyAggContext::new(&[&public_keys[0], &public_keys[1]])?;
+//! let message = b"Message";
+//!
+//! // First round.
+//! let mut rand = [0u8; 32];
+//! orion::util::secure_rand_bytes(&mut rand)?;
+//! let (secnonce_1, pubnonce_1) =
+//!     musig2::nonce_gen(&rand, Some(&secret_keys[0]), &public_keys[0], None, Some(message))?;
+//! orion::util::secure_rand_bytes(&mut rand)?;
+//! let (secnonce_2, pubnonce_2) =
+//!     musig2::nonce_gen(&rand, Some(&secret_keys[1]), &public_keys[1], None, Some(message))?;
+//! let aggnonce = musig2::aggregate_nonces(&[&pubnonce_1, &pubnonce_2])?;
+//!
+//! // Second round.
+//! let psig_1 = musig2::sign(secnonce_1, &secret_keys[0], &ctx, &aggnonce, message)?;
+//! let psig_2 = musig2::sign(secnonce_2, &secret_keys[1], &ctx, &aggnonce, message)?;
+//! musig2::verify_partial(&psig_1, &pubnonce_1, &public_keys[0], &ctx, &aggnonce, message)?;
+//!
+//! let mut signature = [0u8; bip340::SIGNATURESIZE];
+//! musig2::aggregate_partial_signatures(&[&psig_1, &psig_2], &ctx, &aggnonce, message, &mut signature)?;
+//! assert!(bip340::verify(&ctx.agg_public_key(), message, &signature).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [BIP 340]: ../bip340/index.html
+//! [`bip340::verify()`]: ../bip340/fn.verify.html
+//! [`nonce_gen()`]: fn.nonce_gen.html
+//! [`aggregate_nonces()`]: fn.aggregate_nonces.html
+//! [`sign()`]: fn.sign.html
+//! [`verify_partial()`]: fn.verify_partial.html
+//! [`aggregate_partial_signatures()`]: fn.aggregate_partial_signatures.html
+//! [`PublicNonce`]: struct.PublicNonce.html
+//! [`SecretNonce`]: struct.SecretNonce.html
+//! [`PartialSignature`]: struct.PartialSignature.html
+//! [`secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+
+use super::bip340::{challenge, parse_secret_key, tagged_hash, PUBLIC_KEYSIZE as XONLY_KEYSIZE};
+use super::secp256k1::{Point, Scalar, COMPRESSED_POINTSIZE};
+use crate::errors::UnknownCryptoError;
+use zeroize::Zeroize;
+
+/// The size of the public key of a signer.
+pub const PUBLIC_KEYSIZE: usize = COMPRESSED_POINTSIZE;
+/// The size of a public nonce, and of an aggregate nonce.
+pub const NONCESIZE: usize = 2 * COMPRESSED_POINTSIZE;
+/// The size of a partial signature.
+pub const PARTIAL_SIGNATURESIZE: usize = 32;
+/// The size of the randomness used to generate a nonce.
+pub const RANDSIZE: usize = 32;
+
+construct_public! {
+    /// A type to represent the `PublicNonce` that a signer sends to the
+    /// others in the first round.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 66 bytes.
+    (PublicNonce, test_public_nonce, NONCESIZE, NONCESIZE)
+}
+
+construct_public! {
+    /// A type to represent the `AggregateNonce` of all public nonces.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 66 bytes.
+    (AggregateNonce, test_aggregate_nonce, NONCESIZE, NONCESIZE)
+}
+
+construct_public! {
+    /// A type to represent the `PartialSignature` that a signer sends to the
+    /// others in the second round.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (PartialSignature, test_partial_signature, PARTIAL_SIGNATURESIZE, PARTIAL_SIGNATURESIZE)
+}
+
+/// The secret part of a nonce, which must only be used once.
+///
+/// This is zeroized on drop.
+pub struct SecretNonce {
+    k1: Scalar,
+    k2: Scalar,
+    public_key: [u8; PUBLIC_KEYSIZE],
+}
+
+impl Drop for SecretNonce {
+    fn drop(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+    }
+}
+
+impl core::fmt::Debug for SecretNonce {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SecretNonce {{***OMITTED***}}")
+    }
+}
+
+/// Decode a compressed point, where 33 zero bytes encode the point at infinity.
+fn point_from_bytes_ext(bytes: &[u8]) -> Result<Point, UnknownCryptoError> {
+    if bytes.iter().all(|b| *b == 0) {
+        Ok(Point::identity())
+    } else {
+        Point::from_compressed(bytes)
+    }
+}
+
+/// Encode a compressed point, where the point at infinity is 33 zero bytes.
+fn point_to_bytes_ext(point: Point) -> Result<[u8; PUBLIC_KEYSIZE], UnknownCryptoError> {
+    if point.is_identity() {
+        Ok([0u8; PUBLIC_KEYSIZE])
+    } else {
+        point.to_compressed()
+    }
+}
+
+#[derive(Clone, Copy)]
+/// The aggregate public key of a group of signers, and the values needed to
+/// compute the key aggregation coefficient of each of them.
+pub struct KeyAggContext {
+    agg_point: Point,
+    agg_public_key: [u8; XONLY_KEYSIZE],
+    list_hash: [u8; 32],
+    second_key: [u8; PUBLIC_KEYSIZE],
+}
+
+impl core::fmt::Debug for KeyAggContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "KeyAggContext {{ agg_public_key: {:?} }}",
+            &self.agg_public_key[..]
+        )
+    }
+}
+
+impl KeyAggContext {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Aggregate `public_keys`. The order of the keys matters.
+    pub fn new(public_keys: &[&[u8]]) -> Result<Self, UnknownCryptoError> {
+        if public_keys.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        for public_key in public_keys.iter() {
+            if public_key.len() != PUBLIC_KEYSIZE {
+                return Err(UnknownCryptoError);
+            }
+        }
+
+        let list_hash = tagged_hash(b"KeyAgg list", public_keys)?;
+        let mut second_key = [0u8; PUBLIC_KEYSIZE];
+        if let Some(key) = public_keys[1..].iter().find(|pk| **pk != public_keys[0]) {
+            second_key.copy_from_slice(key);
+        }
+
+        let mut ctx = Self {
+            agg_point: Point::identity(),
+            agg_public_key: [0u8; XONLY_KEYSIZE],
+            list_hash,
+            second_key,
+        };
+
+        let mut agg_point = Point::identity();
+        for public_key in public_keys.iter() {
+            let point = Point::from_compressed(public_key)?;
+            agg_point = agg_point.add(&point.mul(&ctx.coefficient(public_key)?));
+        }
+        if agg_point.is_identity() {
+            return Err(UnknownCryptoError);
+        }
+
+        ctx.agg_point = agg_point;
+        ctx.agg_public_key = agg_point.x_bytes()?;
+
+        Ok(ctx)
+    }
+
+    /// The aggregate public key, which verifies the aggregate signature.
+    pub fn agg_public_key(&self) -> [u8; XONLY_KEYSIZE] {
+        self.agg_public_key
+    }
+
+    /// The key aggregation coefficient of `public_key`.
+    fn coefficient(&self, public_key: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+        if public_key == &self.second_key[..] {
+            return Ok(Scalar::one());
+        }
+
+        let hash = tagged_hash(b"KeyAgg coefficient", &[&self.list_hash, public_key])?;
+        Ok(Scalar::from_bytes_reduced(&hash))
+    }
+
+    /// Whether the secret keys of the signers must be negated, because the
+    /// aggregate public key has an odd y-coordinate.
+    fn is_negated(&self) -> Result<bool, UnknownCryptoError> {
+        Ok(!self.agg_point.has_even_y()?)
+    }
+}
+
+/// The values shared by all signers in a signing session: the nonce
+/// coefficient `b`, the final nonce `R` and the challenge `e`.
+fn session_values(
+    ctx: &KeyAggContext,
+    aggnonce: &AggregateNonce,
+    message: &[u8],
+) -> Result<(Scalar, Point, Scalar), UnknownCryptoError> {
+    let aggnonce = aggnonce.as_ref();
+    let r1 = point_from_bytes_ext(&aggnonce[..PUBLIC_KEYSIZE])?;
+    let r2 = point_from_bytes_ext(&aggnonce[PUBLIC_KEYSIZE..])?;
+
+    let b = Scalar::from_bytes_reduced(&tagged_hash(
+        b"MuSig/noncecoef",
+        &[aggnonce, &ctx.agg_public_key, message],
+    )?);
+
+    let mut r = r1.add(&r2.mul(&b));
+    if r.is_identity() {
+        r = Point::generator();
+    }
+    let e = challenge(&r.x_bytes()?, &ctx.agg_public_key, message)?;
+
+    Ok((b, r, e))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the compressed public key of `secret_key`.
+pub fn public_key(secret_key: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != PUBLIC_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut d = parse_secret_key(secret_key)?;
+    let public_key = Point::mul_base(&d).to_compressed();
+    d.zeroize();
+    dst_out.copy_from_slice(&public_key?);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Generate a nonce for signing. `secret_key`, `agg_public_key` and `message`
+/// are optional, but including them protects against bad randomness.
+pub fn nonce_gen(
+    rand: &[u8],
+    secret_key: Option<&[u8]>,
+    public_key: &[u8],
+    agg_public_key: Option<&[u8]>,
+    message: Option<&[u8]>,
+) -> Result<(SecretNonce, PublicNonce), UnknownCryptoError> {
+    if rand.len() != RANDSIZE || public_key.len() != PUBLIC_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+    let agg_public_key = agg_public_key.unwrap_or(&[]);
+    if !agg_public_key.is_empty() && agg_public_key.len() != XONLY_KEYSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut seed = [0u8; RANDSIZE];
+    match secret_key {
+        Some(secret_key) => {
+            if secret_key.len() != RANDSIZE {
+                return Err(UnknownCryptoError);
+            }
+            seed.copy_from_slice(secret_key);
+            xor_slices!(tagged_hash(b"MuSig/aux", &[rand])?, seed);
+        }
+        None => seed.copy_from_slice(rand),
+    }
+
+    // The message is prefixed with 0x01 and its length, or replaced by
+    // 0x00 if absent.
+    let mut message_prefix = [0u8; 9];
+    let (message_prefix, message): (&[u8], &[u8]) = match message {
+        Some(message) => {
+            message_prefix[0] = 1;
+            message_prefix[1..].copy_from_slice(&(message.len() as u64).to_be_bytes());
+            (&message_prefix, message)
+        }
+        None => (&message_prefix[..1], &[]),
+    };
+
+    let nonce_hash = |i: u8| -> Result<Scalar, UnknownCryptoError> {
+        let mut hash = tagged_hash(
+            b"MuSig/nonce",
+            &[
+                &seed,
+                &[PUBLIC_KEYSIZE as u8],
+                public_key,
+                &[agg_public_key.len() as u8],
+                agg_public_key,
+                message_prefix,
+                message,
+                // No extra input.
+                &0u32.to_be_bytes(),
+                &[i],
+            ],
+        )?;
+        let k = Scalar::from_bytes_reduced(&hash);
+        hash.zeroize();
+
+        Ok(k)
+    };
+    let k1 = nonce_hash(0);
+    let k2 = nonce_hash(1);
+    seed.zeroize();
+
+    let secnonce = SecretNonce {
+        k1: k1?,
+        k2: k2?,
+        public_key: [0u8; PUBLIC_KEYSIZE],
+    };
+    if bool::from(secnonce.k1.is_zero() | secnonce.k2.is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut pubnonce = [0u8; NONCESIZE];
+    pubnonce[..PUBLIC_KEYSIZE].copy_from_slice(&Point::mul_base(&secnonce.k1).to_compressed()?);
+    pubnonce[PUBLIC_KEYSIZE..].copy_from_slice(&Point::mul_base(&secnonce.k2).to_compressed()?);
+
+    let mut secnonce = secnonce;
+    secnonce.public_key.copy_from_slice(public_key);
+
+    Ok((secnonce, PublicNonce::from_slice(&pubnonce)?))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Aggregate the public nonces of all signers.
+pub fn aggregate_nonces(pubnonces: &[&PublicNonce]) -> Result<AggregateNonce, UnknownCryptoError> {
+    if pubnonces.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut aggnonce = [0u8; NONCESIZE];
+    for (j, dst) in aggnonce.chunks_exact_mut(PUBLIC_KEYSIZE).enumerate() {
+        let mut r = Point::identity();
+        for pubnonce in pubnonces.iter() {
+            let bytes = &pubnonce.as_ref()[j * PUBLIC_KEYSIZE..(j + 1) * PUBLIC_KEYSIZE];
+            r = r.add(&Point::from_compressed(bytes)?);
+        }
+        dst.copy_from_slice(&point_to_bytes_ext(r)?);
+    }
+
+    AggregateNonce::from_slice(&aggnonce)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Produce the partial signature of `message` with `secret_key`, consuming
+/// `secnonce`.
+pub fn sign(
+    secnonce: SecretNonce,
+    secret_key: &[u8],
+    ctx: &KeyAggContext,
+    aggnonce: &AggregateNonce,
+    message: &[u8],
+) -> Result<PartialSignature, UnknownCryptoError> {
+    let (b, r, e) = session_values(ctx, aggnonce, message)?;
+
+    let mut d = parse_secret_key(secret_key)?;
+    let public_key = Point::mul_base(&d).to_compressed()?;
+    if public_key != secnonce.public_key {
+        d.zeroize();
+        return Err(UnknownCryptoError);
+    }
+    if ctx.is_negated()? {
+        d = d.neg();
+    }
+
+    let (mut k1, mut k2) = (secnonce.k1, secnonce.k2);
+    drop(secnonce);
+    if !r.has_even_y()? {
+        k1 = k1.neg();
+        k2 = k2.neg();
+    }
+
+    let a = ctx.coefficient(&public_key)?;
+    let s = k1.add(&b.mul(&k2)).add(&e.mul(&a).mul(&d));
+    k1.zeroize();
+    k2.zeroize();
+    d.zeroize();
+
+    PartialSignature::from_slice(&s.to_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the partial signature of the signer with `public_key`.
+pub fn verify_partial(
+    psig: &PartialSignature,
+    pubnonce: &PublicNonce,
+    public_key: &[u8],
+    ctx: &KeyAggContext,
+    aggnonce: &AggregateNonce,
+    message: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let (b, r, e) = session_values(ctx, aggnonce, message)?;
+
+    let mut s_bytes = [0u8; PARTIAL_SIGNATURESIZE];
+    s_bytes.copy_from_slice(psig.as_ref());
+    let (s, is_canonical) = Scalar::from_bytes(&s_bytes);
+    if !bool::from(is_canonical) {
+        return Err(UnknownCryptoError);
+    }
+
+    let pubnonce = pubnonce.as_ref();
+    let r1 = Point::from_compressed(&pubnonce[..PUBLIC_KEYSIZE])?;
+    let r2 = Point::from_compressed(&pubnonce[PUBLIC_KEYSIZE..])?;
+    let mut expected_r = r1.add(&r2.mul(&b));
+    if !r.has_even_y()? {
+        expected_r = expected_r.neg();
+    }
+
+    let mut ea = e.mul(&ctx.coefficient(public_key)?);
+    if ctx.is_negated()? {
+        ea = ea.neg();
+    }
+    let point = Point::from_compressed(public_key)?;
+
+    if Point::mul_base(&s).equals(&expected_r.add(&point.mul(&ea))) {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Aggregate the partial signatures of all signers into a BIP 340 signature.
+pub fn aggregate_partial_signatures(
+    psigs: &[&PartialSignature],
+    ctx: &KeyAggContext,
+    aggnonce: &AggregateNonce,
+    message: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if psigs.is_empty() || dst_out.len() != 2 * PARTIAL_SIGNATURESIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (_, r, _) = session_values(ctx, aggnonce, message)?;
+
+    let mut s = Scalar::ZERO;
+    for psig in psigs.iter() {
+        let mut s_bytes = [0u8; PARTIAL_SIGNATURESIZE];
+        s_bytes.copy_from_slice(psig.as_ref());
+        let (s_i, is_canonical) = Scalar::from_bytes(&s_bytes);
+        if !bool::from(is_canonical) {
+            return Err(UnknownCryptoError);
+        }
+        s = s.add(&s_i);
+    }
+
+    dst_out[..32].copy_from_slice(&r.x_bytes()?);
+    dst_out[32..].copy_from_slice(&s.to_bytes());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::ecc::bip340;
+
+    const MESSAGE: [u8; 32] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31,
+    ];
+
+    fn keys(i: u8) -> ([u8; 32], [u8; PUBLIC_KEYSIZE]) {
+        let sk = [i + 1; 32];
+        let mut pk = [0u8; PUBLIC_KEYSIZE];
+        public_key(&sk, &mut pk).unwrap();
+        (sk, pk)
+    }
+
+    #[test]
+    fn test_key_agg_bip327_vectors() {
+        let x1 = hex::decode("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9")
+            .unwrap();
+        let x2 = hex::decode("03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659")
+            .unwrap();
+        let x3 = hex::decode("023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66")
+            .unwrap();
+
+        let cases: [(&[&[u8]], &str); 4] = [
+            (
+                &[&x1, &x2, &x3],
+                "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C",
+            ),
+            (
+                &[&x3, &x2, &x1],
+                "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B",
+            ),
+            (
+                &[&x1, &x1, &x1],
+                "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935",
+            ),
+            (
+                &[&x1, &x1, &x2, &x2],
+                "69BC22BFA5D106306E48A20679DE1D7389386124D07571D0D872686028C26A3E",
+            ),
+        ];
+
+        for (public_keys, expected) in cases.iter() {
+            let ctx = KeyAggContext::new(public_keys).unwrap();
+            assert_eq!(ctx.agg_public_key()[..], hex::decode(expected).unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn test_key_agg_invalid() {
+        let (_, pk) = keys(0);
+        assert!(KeyAggContext::new(&[]).is_err());
+        assert!(KeyAggContext::new(&[&pk[..32]]).is_err());
+        // Not a point on the curve.
+        let invalid =
+            hex::decode("020000000000000000000000000000000000000000000000000000000000000005")
+                .unwrap();
+        assert!(KeyAggContext::new(&[&pk, &invalid]).is_err());
+        // Not a valid prefix.
+        let mut invalid = pk;
+        invalid[0] = 0x04;
+        assert!(KeyAggContext::new(&[&pk, &invalid]).is_err());
+    }
+
+    #[test]
+    fn test_nonce_gen_without_optional_inputs() {
+        let (_, pk) = keys(0);
+        let (_, pubnonce) = nonce_gen(&[0u8; 32], None, &pk, None, None).unwrap();
+        assert_eq!(
+            pubnonce.as_ref(),
+            &hex::decode(
+                "02753040d6654f6a44c41bbf6facdc4b3cebdabfe34cccf2150efc7e314582d4da\
+                 036bd3ae0bb530d327a812fff591f2c8d20793590197de000eef1fb04a1f98d919"
+            )
+            .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_nonce_gen_lengths() {
+        let (sk, pk) = keys(0);
+        assert!(nonce_gen(&[0u8; 31], None, &pk, None, None).is_err());
+        assert!(nonce_gen(&[0u8; 32], Some(&sk[..31]), &pk, None, None).is_err());
+        assert!(nonce_gen(&[0u8; 32], None, &pk[..32], None, None).is_err());
+        assert!(nonce_gen(&[0u8; 32], None, &pk, Some(&[0u8; 31]), None).is_err());
+        assert!(nonce_gen(&[0u8; 32], Some(&sk), &pk, Some(&[0u8; 32]), Some(b"")).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    // format! is only available with std
+    fn test_secret_nonce_omitted_debug() {
+        let (_, pk) = keys(0);
+        let (secnonce, _) = nonce_gen(&[0u8; 32], None, &pk, None, None).unwrap();
+        assert_eq!(format!("{:?}", secnonce), "SecretNonce {***OMITTED***}");
+    }
+
+    /// Run a full signing session between three signers, checking all
+    /// intermediate values against a reference implementation of BIP 327.
+    #[test]
+    fn test_signing_session() {
+        let signers = [keys(0), keys(1), keys(2)];
+        let ctx = KeyAggContext::new(&[&signers[0].1, &signers[1].1, &signers[2].1]).unwrap();
+        assert_eq!(
+            ctx.agg_public_key()[..],
+            hex::decode("b6d830642403fc82511aca5ff98a5e76fcef0f89bffc1aadbe78ee74cd5a5716")
+                .unwrap()[..]
+        );
+
+        let (secnonce_0, pubnonce_0) = nonce_gen(
+            &[0x10; 32],
+            Some(&signers[0].0),
+            &signers[0].1,
+            Some(&ctx.agg_public_key()),
+            Some(&MESSAGE),
+        )
+        .unwrap();
+        let (secnonce_1, pubnonce_1) = nonce_gen(
+            &[0x11; 32],
+            Some(&signers[1].0),
+            &signers[1].1,
+            Some(&ctx.agg_public_key()),
+            Some(&MESSAGE),
+        )
+        .unwrap();
+        let (secnonce_2, pubnonce_2) = nonce_gen(
+            &[0x12; 32],
+            Some(&signers[2].0),
+            &signers[2].1,
+            Some(&ctx.agg_public_key()),
+            Some(&MESSAGE),
+        )
+        .unwrap();
+        let pubnonces = [pubnonce_0, pubnonce_1, pubnonce_2];
+
+        let aggnonce = aggregate_nonces(&[&pubnonces[0], &pubnonces[1], &pubnonces[2]]).unwrap();
+        assert_eq!(
+            aggnonce.as_ref(),
+            &hex::decode(
+                "03dcb6ca1a1ed7829e931a8cce41caeaf02c29a6e5b03c71a8639dc86d54704ad7\
+                 02424ede78375f1b20ff67fe691f1f2af2a6869d099b6838025d3cc277f06353d9"
+            )
+            .unwrap()[..]
+        );
+
+        let psigs = [
+            sign(secnonce_0, &signers[0].0, &ctx, &aggnonce, &MESSAGE).unwrap(),
+            sign(secnonce_1, &signers[1].0, &ctx, &aggnonce, &MESSAGE).unwrap(),
+            sign(secnonce_2, &signers[2].0, &ctx, &aggnonce, &MESSAGE).unwrap(),
+        ];
+        let expected_psigs = [
+            "634944bc48da4d4b1b8034bf72f7bbe941125db258f17d611751b2cd3c0ae5f2",
+            "3b86ecbf2ca308016c56dddd850135114460f8eeb1993de47ade2a78574c27fb",
+            "e43ccdde430dbc951554a5cfcde2351238a3459940c4a0f522aaadb42c3e35ff",
+        ];
+        for i in 0..3 {
+            assert_eq!(
+                psigs[i].as_ref(),
+                &hex::decode(expected_psigs[i]).unwrap()[..]
+            );
+            assert!(verify_partial(
+                &psigs[i],
+                &pubnonces[i],
+                &signers[i].1,
+                &ctx,
+                &aggnonce,
+                &MESSAGE
+            )
+            .is_ok());
+            // A partial signature is not valid for another signer.
+            assert!(verify_partial(
+                &psigs[i],
+                &pubnonces[(i + 1) % 3],
+                &signers[(i + 1) % 3].1,
+                &ctx,
+                &aggnonce,
+                &MESSAGE
+            )
+            .is_err());
+        }
+
+        let mut signature = [0u8; 64];
+        aggregate_partial_signatures(
+            &[&psigs[0], &psigs[1], &psigs[2]],
+            &ctx,
+            &aggnonce,
+            &MESSAGE,
+            &mut signature,
+        )
+        .unwrap();
+        assert_eq!(
+            signature[..],
+            hex::decode(
+                "123652f77c8df309f8acd901009ba2fa473b4f142d623bb278dfc08934b72b5b\
+                 830cff59b88b11e19d2bb86cc5db260e0367bf539c06bbfef5082c6cef5f02ab"
+            )
+            .unwrap()[..]
+        );
+        assert!(bip340::verify(&ctx.agg_public_key(), &MESSAGE, &signature).is_ok());
+
+        // Missing a partial signature.
+        aggregate_partial_signatures(
+            &[&psigs[0], &psigs[1]],
+            &ctx,
+            &aggnonce,
+            &MESSAGE,
+            &mut signature,
+        )
+        .unwrap();
+        assert!(bip340::verify(&ctx.agg_public_key(), &MESSAGE, &signature).is_err());
+        assert!(
+            aggregate_partial_signatures(&[], &ctx, &aggnonce, &MESSAGE, &mut signature).is_err()
+        );
+        assert!(aggregate_partial_signatures(
+            &[&psigs[0]],
+            &ctx,
+            &aggnonce,
+            &MESSAGE,
+            &mut signature[..63]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_sign_wrong_secret_key() {
+        let (sk_0, pk_0) = keys(0);
+        let (sk_1, pk_1) = keys(1);
+        let ctx = KeyAggContext::new(&[&pk_0, &pk_1]).unwrap();
+        let (secnonce, pubnonce) = nonce_gen(&[0u8; 32], None, &pk_0, None, None).unwrap();
+        let aggnonce = aggregate_nonces(&[&pubnonce]).unwrap();
+        assert!(sign(secnonce, &sk_1, &ctx, &aggnonce, b"").is_err());
+
+        let (secnonce, _) = nonce_gen(&[0u8; 32], None, &pk_0, None, None).unwrap();
+        assert!(sign(secnonce, &sk_0, &ctx, &aggnonce, b"").is_ok());
+    }
+
+    #[test]
+    fn test_infinite_aggregate_nonce() {
+        let (sk_0, pk_0) = keys(0);
+        let (sk_1, pk_1) = keys(1);
+        let ctx = KeyAggContext::new(&[&pk_0, &pk_1]).unwrap();
+        let (secnonce_0, pubnonce_0) = nonce_gen(&[0u8; 32], None, &pk_0, None, None).unwrap();
+        let (secnonce_1, _) = nonce_gen(&[1u8; 32], None, &pk_1, None, None).unwrap();
+
+        // A malicious second signer cancels out the nonce of the first.
+        let mut cancelling = [0u8; NONCESIZE];
+        cancelling.copy_from_slice(pubnonce_0.as_ref());
+        cancelling[0] ^= 1;
+        cancelling[PUBLIC_KEYSIZE] ^= 1;
+        let pubnonce_1 = PublicNonce::from_slice(&cancelling).unwrap();
+
+        let aggnonce = aggregate_nonces(&[&pubnonce_0, &pubnonce_1]).unwrap();
+        assert_eq!(aggnonce.as_ref(), &[0u8; NONCESIZE][..]);
+
+        let psig_0 = sign(secnonce_0, &sk_0, &ctx, &aggnonce, b"").unwrap();
+        let psig_1 = sign(secnonce_1, &sk_1, &ctx, &aggnonce, b"").unwrap();
+        assert!(verify_partial(&psig_0, &pubnonce_0, &pk_0, &ctx, &aggnonce, b"").is_ok());
+        assert!(verify_partial(&psig_1, &pubnonce_1, &pk_1, &ctx, &aggnonce, b"").is_err());
+    }
+
+    #[test]
+    fn test_invalid_nonces() {
+        let (_, pk) = keys(0);
+        assert!(aggregate_nonces(&[]).is_err());
+        let pubnonce = PublicNonce::from_slice(&[0u8; NONCESIZE]).unwrap();
+        assert!(aggregate_nonces(&[&pubnonce]).is_err());
+
+        let ctx = KeyAggContext::new(&[&pk]).unwrap();
+        let mut invalid = [0u8; NONCESIZE];
+        invalid[0] = 0x02;
+        invalid[32] = 0x05;
+        let aggnonce = AggregateNonce::from_slice(&invalid).unwrap();
+        let (secnonce, _) = nonce_gen(&[0u8; 32], None, &pk, None, None).unwrap();
+        assert!(sign(secnonce, &[1u8; 32], &ctx, &aggnonce, b"").is_err());
+    }
+}
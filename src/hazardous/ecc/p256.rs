@@ -0,0 +1,277 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The elliptic curve P-256, y^2 = x^3 - 3x + b, as specified in
+//! [SP 800-186](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-186.pdf).
+//!
+//! Points are represented in homogeneous projective coordinates (X : Y : Z),
+//! and added with the complete formulas of [Renes, Costello and Batina](https://eprint.iacr.org/2015/1060),
+//! which have no exceptional cases and run in constant time.
+
+use super::modular256::{Modulus, Residue};
+use crate::errors::UnknownCryptoError;
+use subtle::ConstantTimeEq;
+
+#[derive(Clone, Copy)]
+/// The field prime p = 2^256 - 2^224 + 2^192 + 2^96 - 1.
+pub(crate) struct FieldModulus;
+
+impl Modulus for FieldModulus {
+    const MODULUS: [u64; 4] = [
+        0xffff_ffff_ffff_ffff,
+        0x0000_0000_ffff_ffff,
+        0x0000_0000_0000_0000,
+        0xffff_ffff_0000_0001,
+    ];
+    const R2: [u64; 4] = [
+        0x0000_0000_0000_0003,
+        0xffff_fffb_ffff_ffff,
+        0xffff_ffff_ffff_fffe,
+        0x0000_0004_ffff_fffd,
+    ];
+    const M0_INV: u64 = 0x0000_0000_0000_0001;
+}
+
+#[derive(Clone, Copy)]
+/// The order n of the group generated by the basepoint.
+pub(crate) struct ScalarModulus;
+
+impl Modulus for ScalarModulus {
+    const MODULUS: [u64; 4] = [
+        0xf3b9_cac2_fc63_2551,
+        0xbce6_faad_a717_9e84,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_0000_0000,
+    ];
+    const R2: [u64; 4] = [
+        0x8324_4c95_be79_eea2,
+        0x4699_799c_49bd_6fa6,
+        0x2845_b239_2b6b_ec59,
+        0x66e1_2d94_f3d9_5620,
+    ];
+    const M0_INV: u64 = 0xccd1_c8aa_ee00_bc4f;
+}
+
+/// An element of the field of integers modulo p.
+pub(crate) type FieldElement = Residue<FieldModulus>;
+/// An integer modulo the group order n.
+pub(crate) type Scalar = Residue<ScalarModulus>;
+
+/// The x-coordinate of the basepoint.
+const GENERATOR_X: [u8; 32] = [
+    0x6b, 0x17, 0xd1, 0xf2, 0xe1, 0x2c, 0x42, 0x47, 0xf8, 0xbc, 0xe6, 0xe5, 0x63, 0xa4, 0x40, 0xf2,
+    0x77, 0x03, 0x7d, 0x81, 0x2d, 0xeb, 0x33, 0xa0, 0xf4, 0xa1, 0x39, 0x45, 0xd8, 0x98, 0xc2, 0x96,
+];
+/// The y-coordinate of the basepoint.
+const GENERATOR_Y: [u8; 32] = [
+    0x4f, 0xe3, 0x42, 0xe2, 0xfe, 0x1a, 0x7f, 0x9b, 0x8e, 0xe7, 0xeb, 0x4a, 0x7c, 0x0f, 0x9e, 0x16,
+    0x2b, 0xce, 0x33, 0x57, 0x6b, 0x31, 0x5e, 0xce, 0xcb, 0xb6, 0x40, 0x68, 0x37, 0xbf, 0x51, 0xf5,
+];
+/// The curve coefficient b.
+const CURVE_B: [u8; 32] = [
+    0x5a, 0xc6, 0x35, 0xd8, 0xaa, 0x3a, 0x93, 0xe7, 0xb3, 0xeb, 0xbd, 0x55, 0x76, 0x98, 0x86, 0xbc,
+    0x65, 0x1d, 0x06, 0xb0, 0xcc, 0x53, 0xb0, 0xf6, 0x3b, 0xce, 0x3c, 0x3e, 0x27, 0xd2, 0x60, 0x4b,
+];
+/// The size of an uncompressed point encoding.
+pub(crate) const UNCOMPRESSED_POINTSIZE: usize = 65;
+/// The size of a scalar encoding.
+pub(crate) const SCALARSIZE: usize = 32;
+
+/// The curve coefficient b.
+fn curve_b() -> FieldElement {
+    FieldElement::from_bytes(&CURVE_B).0
+}
+
+/// x^3 - 3x + b.
+fn curve_rhs(x: &FieldElement) -> FieldElement {
+    let three_x = x.add(x).add(x);
+    x.square().mul(x).sub(&three_x).add(&curve_b())
+}
+
+#[derive(Clone, Copy)]
+/// A point on P-256, or the point at infinity.
+pub(crate) struct Point {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl_point_arithmetic!(a = -3);
+
+impl Point {
+    /// Decode a point from its 65-byte uncompressed SEC 1 encoding. The point
+    /// at infinity has no such encoding.
+    pub(crate) fn from_uncompressed(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if bytes.len() != UNCOMPRESSED_POINTSIZE || bytes[0] != 0x04 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut coordinate = [0u8; 32];
+        coordinate.copy_from_slice(&bytes[1..33]);
+        let (x, x_is_canonical) = FieldElement::from_bytes(&coordinate);
+        coordinate.copy_from_slice(&bytes[33..]);
+        let (y, y_is_canonical) = FieldElement::from_bytes(&coordinate);
+        if !bool::from(x_is_canonical & y_is_canonical) {
+            return Err(UnknownCryptoError);
+        }
+        if !bool::from(y.square().ct_eq(&curve_rhs(&x))) {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            x,
+            y,
+            z: FieldElement::one(),
+        })
+    }
+
+    /// The 65-byte uncompressed SEC 1 encoding of this point, or an error if
+    /// it is the point at infinity.
+    pub(crate) fn to_uncompressed(
+        self,
+    ) -> Result<[u8; UNCOMPRESSED_POINTSIZE], UnknownCryptoError> {
+        if self.is_identity() {
+            return Err(UnknownCryptoError);
+        }
+
+        let z_inv = self.z.invert();
+        let mut bytes = [0u8; UNCOMPRESSED_POINTSIZE];
+        bytes[0] = 0x04;
+        bytes[1..33].copy_from_slice(&self.x.mul(&z_inv).to_bytes());
+        bytes[33..].copy_from_slice(&self.y.mul(&z_inv).to_bytes());
+
+        Ok(bytes)
+    }
+
+    /// The affine x-coordinate of this point, or an error if it is the point
+    /// at infinity.
+    pub(crate) fn x_bytes(&self) -> Result<[u8; 32], UnknownCryptoError> {
+        if self.is_identity() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(self.x.mul(&self.z.invert()).to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn scalar(value: u64) -> Scalar {
+        Scalar::from_u64(value)
+    }
+
+    #[test]
+    fn test_generator_on_curve() {
+        let g = Point::generator();
+        assert!(bool::from(g.y.square().ct_eq(&curve_rhs(&g.x))));
+    }
+
+    #[test]
+    fn test_group_order() {
+        // n - 1 times G is -G, so n times G is the point at infinity.
+        let minus_one = scalar(1).neg();
+        let point = Point::mul_base(&minus_one);
+        assert!(point.equals(&Point::generator().neg()));
+        assert!(point.add(&Point::generator()).is_identity());
+        assert!(Point::mul_base(&Scalar::ZERO).is_identity());
+    }
+
+    #[test]
+    fn test_small_multiples() {
+        let g = Point::generator();
+        let mut sum = Point::identity();
+        for i in 1..=16u64 {
+            sum = sum.add(&g);
+            assert!(sum.equals(&Point::mul_base(&scalar(i))));
+        }
+        // Doubling through add().
+        assert!(g.add(&g).equals(&Point::mul_base(&scalar(2))));
+    }
+
+    #[test]
+    fn test_identity() {
+        let g = Point::generator();
+        let identity = Point::identity();
+        assert!(identity.add(&identity).is_identity());
+        assert!(g.add(&identity).equals(&g));
+        assert!(identity.add(&g).equals(&g));
+        assert!(g.add(&g.neg()).is_identity());
+        assert!(identity.to_uncompressed().is_err());
+        assert!(identity.x_bytes().is_err());
+        assert!(!identity.equals(&g));
+    }
+
+    #[test]
+    fn test_uncompressed_roundtrip() {
+        for i in 1..=8u64 {
+            let point = Point::mul_base(&scalar(i));
+            let bytes = point.to_uncompressed().unwrap();
+            assert!(Point::from_uncompressed(&bytes).unwrap().equals(&point));
+            assert_eq!(bytes[1..33], point.x_bytes().unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn test_reject_invalid_encodings() {
+        let mut bytes = Point::generator().to_uncompressed().unwrap();
+        assert!(Point::from_uncompressed(&bytes[..64]).is_err());
+        bytes[0] = 0x02;
+        assert!(Point::from_uncompressed(&bytes).is_err());
+        bytes[0] = 0x04;
+
+        // Not on the curve.
+        bytes[64] ^= 1;
+        assert!(Point::from_uncompressed(&bytes).is_err());
+        bytes[64] ^= 1;
+        assert!(Point::from_uncompressed(&bytes).is_ok());
+
+        // y = p is not canonical.
+        let mut y_is_p = bytes;
+        y_is_p[33..].copy_from_slice(&[
+            0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff,
+        ]);
+        assert!(Point::from_uncompressed(&y_is_p).is_err());
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        // Checked against the P-256 implementation of OpenSSL.
+        let mut k = [0u8; SCALARSIZE];
+        k.copy_from_slice(
+            &hex::decode("2e5e5c4a1f0f7d8b6c3a29180746b5a4f3e2d1c0b9a8978675645342312e1f0d")
+                .unwrap(),
+        );
+        let (k, is_canonical) = Scalar::from_bytes(&k);
+        assert!(bool::from(is_canonical));
+        assert_eq!(
+            Point::mul_base(&k).to_uncompressed().unwrap()[..],
+            hex::decode(
+                "043a9477f49ba33a7d533bb5a814f377eacfd4cf2c67791e068c349870e54a7d46ea7c927c0921589ada34649789d594592901af00a9afd36a24aa958cf256ed61"
+            )
+            .unwrap()[..]
+        );
+    }
+}
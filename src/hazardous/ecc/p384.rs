@@ -0,0 +1,449 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The elliptic curve P-384, y^2 = x^3 - 3x + b, as specified in
+//! [SP 800-186](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-186.pdf),
+//! with hashing to the curve as specified in [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380).
+//!
+//! Points are represented in homogeneous projective coordinates (X : Y : Z),
+//! and added with the complete formulas of [Renes, Costello and Batina](https://eprint.iacr.org/2015/1060),
+//! which have no exceptional cases and run in constant time.
+
+use super::modular384::{Modulus, Residue};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha384::{Sha384, SHA384_BLOCKSIZE, SHA384_OUTSIZE};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+#[derive(Clone, Copy)]
+/// The field prime p = 2^384 - 2^128 - 2^96 + 2^32 - 1.
+pub(crate) struct FieldModulus;
+
+impl Modulus for FieldModulus {
+    const MODULUS: [u64; 6] = [
+        0x0000_0000_ffff_ffff,
+        0xffff_ffff_0000_0000,
+        0xffff_ffff_ffff_fffe,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+    ];
+    const R2: [u64; 6] = [
+        0xffff_fffe_0000_0001,
+        0x0000_0002_0000_0000,
+        0xffff_fffe_0000_0000,
+        0x0000_0002_0000_0000,
+        0x0000_0000_0000_0001,
+        0x0000_0000_0000_0000,
+    ];
+    const M0_INV: u64 = 0x0000_0001_0000_0001;
+}
+
+#[derive(Clone, Copy)]
+/// The order n of the group generated by the basepoint.
+pub(crate) struct ScalarModulus;
+
+impl Modulus for ScalarModulus {
+    const MODULUS: [u64; 6] = [
+        0xecec_196a_ccc5_2973,
+        0x581a_0db2_48b0_a77a,
+        0xc763_4d81_f437_2ddf,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+    ];
+    const R2: [u64; 6] = [
+        0x2d31_9b24_19b4_09a9,
+        0xff3d_81e5_df1a_a419,
+        0xbc3e_483a_fcb8_2947,
+        0xd40d_4917_4aab_1cc5,
+        0x3fb0_5b7a_2826_6895,
+        0x0c84_ee01_2b39_bf21,
+    ];
+    const M0_INV: u64 = 0x6ed4_6089_e88f_dc45;
+}
+
+/// An element of the field of integers modulo p.
+pub(crate) type FieldElement = Residue<FieldModulus>;
+/// An integer modulo the group order n.
+pub(crate) type Scalar = Residue<ScalarModulus>;
+
+/// The x-coordinate of the basepoint.
+const GENERATOR_X: [u8; 48] = [
+    0xaa, 0x87, 0xca, 0x22, 0xbe, 0x8b, 0x05, 0x37, 0x8e, 0xb1, 0xc7, 0x1e, 0xf3, 0x20, 0xad, 0x74,
+    0x6e, 0x1d, 0x3b, 0x62, 0x8b, 0xa7, 0x9b, 0x98, 0x59, 0xf7, 0x41, 0xe0, 0x82, 0x54, 0x2a, 0x38,
+    0x55, 0x02, 0xf2, 0x5d, 0xbf, 0x55, 0x29, 0x6c, 0x3a, 0x54, 0x5e, 0x38, 0x72, 0x76, 0x0a, 0xb7,
+];
+/// The y-coordinate of the basepoint.
+const GENERATOR_Y: [u8; 48] = [
+    0x36, 0x17, 0xde, 0x4a, 0x96, 0x26, 0x2c, 0x6f, 0x5d, 0x9e, 0x98, 0xbf, 0x92, 0x92, 0xdc, 0x29,
+    0xf8, 0xf4, 0x1d, 0xbd, 0x28, 0x9a, 0x14, 0x7c, 0xe9, 0xda, 0x31, 0x13, 0xb5, 0xf0, 0xb8, 0xc0,
+    0x0a, 0x60, 0xb1, 0xce, 0x1d, 0x7e, 0x81, 0x9d, 0x7a, 0x43, 0x1d, 0x7c, 0x90, 0xea, 0x0e, 0x5f,
+];
+/// The curve coefficient b.
+const CURVE_B: [u8; 48] = [
+    0xb3, 0x31, 0x2f, 0xa7, 0xe2, 0x3e, 0xe7, 0xe4, 0x98, 0x8e, 0x05, 0x6b, 0xe3, 0xf8, 0x2d, 0x19,
+    0x18, 0x1d, 0x9c, 0x6e, 0xfe, 0x81, 0x41, 0x12, 0x03, 0x14, 0x08, 0x8f, 0x50, 0x13, 0x87, 0x5a,
+    0xc6, 0x56, 0x39, 0x8d, 0x8a, 0x2e, 0xd1, 0x9d, 0x2a, 0x85, 0xc8, 0xed, 0xd3, 0xec, 0x2a, 0xef,
+];
+/// (p + 1) / 4, used for computing square roots, since p = 3 mod 4.
+const SQRT_EXPONENT: [u8; 48] = [
+    0x3f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbf, 0xff, 0xff, 0xff, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00,
+];
+/// The size of a compressed point encoding.
+pub(crate) const COMPRESSED_POINTSIZE: usize = 49;
+/// The size of a scalar encoding.
+pub(crate) const SCALARSIZE: usize = 48;
+/// The number of bytes hashed to each field element or scalar, L in RFC 9380.
+const HASH_TO_FIELD_LEN: usize = 72;
+
+/// The curve coefficient b.
+fn curve_b() -> FieldElement {
+    FieldElement::from_bytes(&CURVE_B).0
+}
+
+/// x^3 - 3x + b.
+fn curve_rhs(x: &FieldElement) -> FieldElement {
+    let three_x = x.add(x).add(x);
+    x.square().mul(x).sub(&three_x).add(&curve_b())
+}
+
+/// A square root of `value`, and whether `value` is a square. If it is not,
+/// the returned root is not meaningful.
+fn sqrt(value: &FieldElement) -> (FieldElement, Choice) {
+    let root = value.pow_vartime(&SQRT_EXPONENT);
+    (root, root.square().ct_eq(value))
+}
+
+#[derive(Clone, Copy)]
+/// A point on P-384, or the point at infinity.
+pub(crate) struct Point {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl_point_arithmetic!(a = -3);
+
+impl Point {
+    /// Decode a point from its 49-byte compressed SEC 1 encoding. The point
+    /// at infinity has no such encoding.
+    pub(crate) fn from_compressed(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if bytes.len() != COMPRESSED_POINTSIZE || (bytes[0] != 0x02 && bytes[0] != 0x03) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut x = [0u8; 48];
+        x.copy_from_slice(&bytes[1..]);
+        let (x, is_canonical) = FieldElement::from_bytes(&x);
+        if !bool::from(is_canonical) {
+            return Err(UnknownCryptoError);
+        }
+
+        let (y, is_square) = sqrt(&curve_rhs(&x));
+        if !bool::from(is_square) {
+            return Err(UnknownCryptoError);
+        }
+        let is_odd = Choice::from(bytes[0] & 1);
+        let y = FieldElement::conditional_select(&y, &y.neg(), y.is_odd() ^ is_odd);
+
+        Ok(Self {
+            x,
+            y,
+            z: FieldElement::one(),
+        })
+    }
+
+    /// The 49-byte compressed SEC 1 encoding of this point, or an error if it
+    /// is the point at infinity.
+    pub(crate) fn to_compressed(self) -> Result<[u8; COMPRESSED_POINTSIZE], UnknownCryptoError> {
+        if self.is_identity() {
+            return Err(UnknownCryptoError);
+        }
+
+        let z_inv = self.z.invert();
+        let x = self.x.mul(&z_inv);
+        let y = self.y.mul(&z_inv);
+        let mut bytes = [0u8; COMPRESSED_POINTSIZE];
+        bytes[0] = 0x02 | y.is_odd().unwrap_u8();
+        bytes[1..].copy_from_slice(&x.to_bytes());
+
+        Ok(bytes)
+    }
+
+    /// The simplified SWU map of `u` to the curve, as specified in RFC 9380
+    /// section 6.6.2, with Z = -12.
+    fn map_to_curve(u: &FieldElement) -> Self {
+        let a = FieldElement::from_u64(3).neg();
+        let b = curve_b();
+        let z = FieldElement::from_u64(12).neg();
+
+        let z_u2 = z.mul(&u.square());
+        let denominator = z_u2.square().add(&z_u2);
+        let tv1 = denominator.invert();
+
+        // x1 = (-b / a) * (1 + tv1), or b / (z * a) if tv1 = 0.
+        let minus_b_over_a = b.mul(&a.invert()).neg();
+        let x1 = minus_b_over_a.mul(&FieldElement::one().add(&tv1));
+        let exceptional = b.mul(&z.mul(&a).invert());
+        let x1 = FieldElement::conditional_select(&x1, &exceptional, tv1.is_zero());
+        let x2 = z_u2.mul(&x1);
+
+        let (y1, gx1_is_square) = sqrt(&curve_rhs(&x1));
+        let (y2, _) = sqrt(&curve_rhs(&x2));
+        let x = FieldElement::conditional_select(&x2, &x1, gx1_is_square);
+        let y = FieldElement::conditional_select(&y2, &y1, gx1_is_square);
+        let y = FieldElement::conditional_select(&y, &y.neg(), u.is_odd() ^ y.is_odd());
+
+        Self {
+            x,
+            y,
+            z: FieldElement::one(),
+        }
+    }
+}
+
+/// expand_message_xmd with SHA-384, as specified in RFC 9380 section 5.3.1,
+/// of the concatenation of `message` into all of `dst_out`.
+fn expand_message_xmd(
+    message: &[&[u8]],
+    dst: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let ell = dst_out.chunks(SHA384_OUTSIZE).len();
+    if dst_out.is_empty() || ell > 255 || dst.len() > 255 {
+        return Err(UnknownCryptoError);
+    }
+    let dst_len = [dst.len() as u8];
+
+    let mut state = Sha384::new();
+    state.update(&[0u8; SHA384_BLOCKSIZE])?;
+    for part in message.iter() {
+        state.update(part)?;
+    }
+    state.update(&(dst_out.len() as u16).to_be_bytes())?;
+    state.update(&[0u8])?;
+    state.update(dst)?;
+    state.update(&dst_len)?;
+    let mut b_0 = [0u8; SHA384_OUTSIZE];
+    state.finalize_into(&mut b_0)?;
+
+    let mut b_i = [0u8; SHA384_OUTSIZE];
+    for (i, chunk) in dst_out.chunks_mut(SHA384_OUTSIZE).enumerate() {
+        // b_i = H(strxor(b_0, b_(i - 1)) || i || DST_prime), where the first
+        // block has no previous block to XOR with.
+        if i > 0 {
+            xor_slices!(b_0, &mut b_i);
+        } else {
+            b_i.copy_from_slice(&b_0);
+        }
+        state.reset();
+        state.update(&b_i)?;
+        state.update(&[(i + 1) as u8])?;
+        state.update(dst)?;
+        state.update(&dst_len)?;
+        state.finalize_into(&mut b_i)?;
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+
+    b_0.zeroize();
+    b_i.zeroize();
+
+    Ok(())
+}
+
+/// Hash the concatenation of `message` to a point on the curve, with the
+/// `P384_XMD:SHA-384_SSWU_RO_` suite of RFC 9380 and the domain separation
+/// tag `dst`.
+pub(crate) fn hash_to_curve(message: &[&[u8]], dst: &[u8]) -> Result<Point, UnknownCryptoError> {
+    let mut uniform_bytes = [0u8; 2 * HASH_TO_FIELD_LEN];
+    expand_message_xmd(message, dst, &mut uniform_bytes)?;
+
+    let mut wide = [0u8; HASH_TO_FIELD_LEN];
+    wide.copy_from_slice(&uniform_bytes[..HASH_TO_FIELD_LEN]);
+    let q0 = Point::map_to_curve(&FieldElement::from_bytes_wide(&wide));
+    wide.copy_from_slice(&uniform_bytes[HASH_TO_FIELD_LEN..]);
+    let q1 = Point::map_to_curve(&FieldElement::from_bytes_wide(&wide));
+    uniform_bytes.zeroize();
+    wide.zeroize();
+
+    // P-384 has cofactor 1, so there is nothing to clear.
+    Ok(q0.add(&q1))
+}
+
+/// Hash the concatenation of `message` to a scalar, with hash_to_field of
+/// RFC 9380 using expand_message_xmd with SHA-384, and the domain
+/// separation tag `dst`.
+pub(crate) fn hash_to_scalar(message: &[&[u8]], dst: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+    let mut uniform_bytes = [0u8; HASH_TO_FIELD_LEN];
+    expand_message_xmd(message, dst, &mut uniform_bytes)?;
+    let scalar = Scalar::from_bytes_wide(&uniform_bytes);
+    uniform_bytes.zeroize();
+
+    Ok(scalar)
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn scalar(value: u64) -> Scalar {
+        Scalar::from_u64(value)
+    }
+
+    #[test]
+    fn test_generator_on_curve() {
+        let g = Point::generator();
+        assert!(bool::from(g.y.square().ct_eq(&curve_rhs(&g.x))));
+    }
+
+    #[test]
+    fn test_group_order() {
+        // n - 1 times G is -G, so n times G is the point at infinity.
+        let minus_one = scalar(1).neg();
+        let point = Point::mul_base(&minus_one);
+        assert!(point.equals(&Point::generator().neg()));
+        assert!(point.add(&Point::generator()).is_identity());
+        assert!(Point::mul_base(&Scalar::ZERO).is_identity());
+    }
+
+    #[test]
+    fn test_small_multiples() {
+        let g = Point::generator();
+        let mut sum = Point::identity();
+        for i in 1..=16u64 {
+            sum = sum.add(&g);
+            assert!(sum.equals(&Point::mul_base(&scalar(i))));
+        }
+        // Doubling through add().
+        assert!(g.add(&g).equals(&Point::mul_base(&scalar(2))));
+    }
+
+    #[test]
+    fn test_identity() {
+        let g = Point::generator();
+        let identity = Point::identity();
+        assert!(identity.add(&identity).is_identity());
+        assert!(g.add(&identity).equals(&g));
+        assert!(identity.add(&g).equals(&g));
+        assert!(g.add(&g.neg()).is_identity());
+        assert!(identity.to_compressed().is_err());
+        assert!(!identity.equals(&g));
+        assert!(identity.equals(&Point::identity()));
+    }
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        for i in 1..=8u64 {
+            let point = Point::mul_base(&scalar(i));
+            let bytes = point.to_compressed().unwrap();
+            assert!(Point::from_compressed(&bytes).unwrap().equals(&point));
+            let negated = point.neg().to_compressed().unwrap();
+            assert_eq!(bytes[1..], negated[1..]);
+            assert_ne!(bytes[0], negated[0]);
+        }
+    }
+
+    #[test]
+    fn test_reject_invalid_encodings() {
+        let mut bytes = Point::generator().to_compressed().unwrap();
+        assert!(Point::from_compressed(&bytes[..48]).is_err());
+        bytes[0] = 0x04;
+        assert!(Point::from_compressed(&bytes).is_err());
+
+        // x = p is not canonical.
+        let mut x_is_p = [0xffu8; COMPRESSED_POINTSIZE];
+        x_is_p[0] = 0x02;
+        x_is_p[32] = 0xfe;
+        x_is_p[37..45].copy_from_slice(&[0u8; 8]);
+        assert!(Point::from_compressed(&x_is_p).is_err());
+
+        // x = 4 is not on the curve.
+        let mut four = [0u8; COMPRESSED_POINTSIZE];
+        four[0] = 0x02;
+        four[48] = 4;
+        assert!(Point::from_compressed(&four).is_err());
+    }
+
+    #[test]
+    fn test_expand_message_xmd_lengths() {
+        let mut out = [0u8; 255 * SHA384_OUTSIZE + 1];
+        assert!(expand_message_xmd(&[b"msg"], b"dst", &mut out).is_err());
+        assert!(expand_message_xmd(&[b"msg"], b"dst", &mut out[..0]).is_err());
+        assert!(expand_message_xmd(&[b"msg"], &[0u8; 256], &mut out[..32]).is_err());
+        assert!(expand_message_xmd(&[b"msg"], b"dst", &mut out[..255 * SHA384_OUTSIZE]).is_ok());
+
+        // The message parts are concatenated.
+        let mut joined = [0u8; 100];
+        let mut split = [0u8; 100];
+        expand_message_xmd(&[b"message"], b"dst", &mut joined).unwrap();
+        expand_message_xmd(&[b"mes", b"", b"sage"], b"dst", &mut split).unwrap();
+        assert_eq!(joined[..], split[..]);
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        // Checked against the P-384 implementation of OpenSSL.
+        let mut k = [0u8; SCALARSIZE];
+        k.copy_from_slice(
+            &hex::decode(
+                "2e5e5c4a1f0f7d8b6c3a29180746b5a4f3e2d1c0b9a8978675645342312e1f0d0c1b2a39485766758493a2b1c0d9e8f7",
+            )
+            .unwrap(),
+        );
+        let (k, is_canonical) = Scalar::from_bytes(&k);
+        assert!(bool::from(is_canonical));
+        assert_eq!(
+            Point::mul_base(&k).to_compressed().unwrap()[..],
+            hex::decode(
+                "02295aff44401cd8b7854ed5113d101ecb98f07240c39d4a1ad3acfaf69e3204d567a662ee7e7696081b6985843509428c"
+            )
+            .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve_rfc_9380() {
+        // RFC 9380 appendix J.3.1, P384_XMD:SHA-384_SSWU_RO_.
+        let dst = b"QUUX-V01-CS02-with-P384_XMD:SHA-384_SSWU_RO_";
+        let vectors: [(&[u8], &str); 2] = [
+            (
+                b"",
+                "02eb9fe1b4f4e14e7140803c1d99d0a93cd823d2b024040f9c067a8eca1f5a2eeac9ad604973527a356f3fa3aeff0e4d83",
+            ),
+            (
+                b"abc",
+                "02e02fc1a5f44a7519419dd314e29863f30df55a514da2d655775a81d413003c4d4e7fd59af0826dfaad4200ac6f60abe1",
+            ),
+        ];
+
+        for (message, expected) in vectors.iter() {
+            let point = hash_to_curve(&[message], dst).unwrap();
+            assert_eq!(
+                point.to_compressed().unwrap()[..],
+                hex::decode(expected).unwrap()[..]
+            );
+        }
+    }
+}
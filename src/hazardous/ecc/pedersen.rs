@@ -0,0 +1,347 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Pedersen commitments over the [ristretto255](https://www.rfc-editor.org/rfc/rfc9496)
+//! prime-order group.
+//!
+//! A commitment to `value` with the blinding factor `r` is
+//! `C = value * G + r * H`, where `G` is the ristretto255 generator and `H`
+//! is a second generator whose discrete logarithm with respect to `G` is
+//! unknown. Commitments are additively homomorphic: the sum of two
+//! commitments is a commitment to the sum of their values, with the sum of
+//! their blinding factors.
+//!
+//! `H` is derived as `from_uniform_bytes(SHA512(G))`, where `G` is the
+//! 32-byte encoding of the generator and `from_uniform_bytes` is the
+//! element derivation function of RFC 9496. Its encoding is
+//! [`BLINDING_GENERATOR`].
+//!
+//! # Parameters:
+//! - `value`: The value to commit to.
+//! - `blinding`: The blinding factor, which hides `value`.
+//! - `commitment`: The commitment to verify or combine.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A `Blinding` is created from a slice that is not 32 bytes, or not the
+//!   little-endian encoding of an integer less than the group order.
+//! - A `Commitment` is not a valid encoding of a ristretto255 element.
+//! - `commitment` is not a commitment to `value` with `blinding`, when
+//!   verifying.
+//!
+//! # Security:
+//! - The commitment hides `value` only if `blinding` is secret and chosen
+//!   uniformly at random. Use [`Blinding::generate()`] for this, and never use
+//!   the same `Blinding` for two commitments.
+//! - The commitment binds to `value` under the discrete logarithm assumption.
+//!   This does not prove anything about `value`, such as it being in a range.
+//! - `value` is committed to as an element of the scalar field. Sums of many
+//!   values wrap around modulo the group order, which is much larger than
+//!   any sum of `u64` values in practice.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::pedersen::{self, Blinding};
+//!
+//! let blinding_1 = Blinding::generate();
+//! let blinding_2 = Blinding::generate();
+//! let commitment_1 = pedersen::commit(40, &blinding_1)?;
+//! let commitment_2 = pedersen::commit(2, &blinding_2)?;
+//! assert!(pedersen::verify(&commitment_1, 40, &blinding_1).is_ok());
+//! assert!(pedersen::verify(&commitment_1, 41, &blinding_1).is_err());
+//!
+//! // The sum of the commitments opens to the sum of the values.
+//! let sum = pedersen::add(&commitment_1, &commitment_2)?;
+//! assert!(pedersen::verify(&sum, 42, &blinding_1.add(&blinding_2)).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`BLINDING_GENERATOR`]: constant.BLINDING_GENERATOR.html
+//! [`Blinding::generate()`]: struct.Blinding.html#method.generate
+
+use super::ristretto255::{
+    scalar_from_bytes, scalar_to_bytes, RistrettoPoint, Scalar, POINTSIZE, SCALARSIZE,
+};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha512::Sha512;
+use subtle::ConstantTimeEq;
+
+/// The size of a commitment.
+pub const COMMITMENTSIZE: usize = POINTSIZE;
+/// The size of a blinding factor.
+pub const BLINDINGSIZE: usize = SCALARSIZE;
+
+/// The encoding of the generator `H`, which is multiplied with the blinding
+/// factor.
+pub const BLINDING_GENERATOR: [u8; COMMITMENTSIZE] = [
+    0x90, 0xca, 0x11, 0xcd, 0x6c, 0x62, 0x27, 0xcb, 0x0a, 0xbc, 0x39, 0xe2, 0x71, 0x0c, 0x44, 0x4a,
+    0xe6, 0x61, 0x7e, 0xa8, 0x18, 0x98, 0xe7, 0x16, 0x35, 0x3f, 0x34, 0x10, 0xd9, 0x65, 0x66, 0x05,
+];
+
+/// A blinding factor for a commitment, which is a scalar modulo the order of
+/// ristretto255.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as it
+///   breaks all protections that the type implements.
+/// - The trait `PartialEq<&'_ [u8]>` is implemented for this type, and the
+///   comparison happens in constant time.
+pub struct Blinding {
+    value: [u8; BLINDINGSIZE],
+    original_length: usize,
+}
+
+impl_omitted_debug_trait!(Blinding);
+impl_drop_trait!(Blinding);
+impl_ct_partialeq_trait!(Blinding, unprotected_as_bytes);
+
+impl Blinding {
+    fn from_scalar(scalar: &Scalar) -> Self {
+        Self {
+            value: scalar_to_bytes(scalar),
+            original_length: BLINDINGSIZE,
+        }
+    }
+
+    pub(super) fn to_scalar(&self) -> Scalar {
+        // The value is always canonical, so this never falls back to zero.
+        scalar_from_bytes(&self.value).unwrap_or(Scalar::ZERO)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct from a given byte slice, which must be the little-endian
+    /// encoding of an integer less than the group order.
+    pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if slice.len() != BLINDINGSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = [0u8; BLINDINGSIZE];
+        value.copy_from_slice(slice);
+        let blinding = Self {
+            value,
+            original_length: BLINDINGSIZE,
+        };
+        scalar_from_bytes(&blinding.value)?;
+
+        Ok(blinding)
+    }
+
+    #[cfg(feature = "safe_api")]
+    /// Randomly generate using a CSPRNG. Not available in `no_std` context.
+    pub fn generate() -> Self {
+        use zeroize::Zeroize;
+
+        let mut bytes = [0u8; 64];
+        crate::util::secure_rand_bytes(&mut bytes).unwrap();
+        let mut scalar = super::ristretto255::scalar_from_bytes_wide(&bytes);
+        bytes.iter_mut().zeroize();
+        let blinding = Self::from_scalar(&scalar);
+        scalar.zeroize();
+
+        blinding
+    }
+
+    /// The blinding factor of the sum of two commitments.
+    pub fn add(&self, other: &Self) -> Self {
+        Self::from_scalar(&self.to_scalar().add(&other.to_scalar()))
+    }
+
+    /// The blinding factor of the difference of two commitments.
+    pub fn sub(&self, other: &Self) -> Self {
+        Self::from_scalar(&self.to_scalar().sub(&other.to_scalar()))
+    }
+
+    func_unprotected_as_bytes!();
+    func_len!();
+}
+
+construct_public! {
+    /// A type to represent a `Commitment` to a value.
+    ///
+    /// The encoding is only checked when the commitment is used.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (Commitment, test_commitment, COMMITMENTSIZE, COMMITMENTSIZE)
+}
+
+/// The generator `H`, derived from the generator `G`.
+pub(super) fn blinding_generator() -> Result<RistrettoPoint, UnknownCryptoError> {
+    let mut hash = [0u8; 64];
+    hash.copy_from_slice(Sha512::digest(&RistrettoPoint::GENERATOR.to_bytes())?.as_ref());
+
+    Ok(RistrettoPoint::from_uniform_bytes(&hash))
+}
+
+pub(super) fn decode(commitment: &Commitment) -> Result<RistrettoPoint, UnknownCryptoError> {
+    let mut bytes = [0u8; COMMITMENTSIZE];
+    bytes.copy_from_slice(commitment.as_ref());
+
+    RistrettoPoint::from_bytes(&bytes)
+}
+
+fn encode(point: RistrettoPoint) -> Result<Commitment, UnknownCryptoError> {
+    Commitment::from_slice(&point.to_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Commit to `value` with `blinding`.
+pub fn commit(value: u64, blinding: &Blinding) -> Result<Commitment, UnknownCryptoError> {
+    use zeroize::Zeroize;
+
+    let mut r = blinding.to_scalar();
+    let point = RistrettoPoint::GENERATOR
+        .mul(&Scalar::from_u64(value))
+        .add(&blinding_generator()?.mul(&r));
+    r.zeroize();
+
+    encode(point)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `commitment` is a commitment to `value` with `blinding`.
+pub fn verify(
+    commitment: &Commitment,
+    value: u64,
+    blinding: &Blinding,
+) -> Result<(), UnknownCryptoError> {
+    let expected = commit(value, blinding)?;
+    if bool::from(expected.as_ref().ct_eq(commitment.as_ref())) {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The commitment to the sum of the values of `a` and `b`.
+pub fn add(a: &Commitment, b: &Commitment) -> Result<Commitment, UnknownCryptoError> {
+    encode(decode(a)?.add(&decode(b)?))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The commitment to the difference of the values of `a` and `b`.
+pub fn sub(a: &Commitment, b: &Commitment) -> Result<Commitment, UnknownCryptoError> {
+    encode(decode(a)?.sub(&decode(b)?))
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn blinding(byte: u8) -> Blinding {
+        let mut bytes = [byte; BLINDINGSIZE];
+        bytes[31] = 0;
+        Blinding::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_blinding_generator() {
+        assert_eq!(blinding_generator().unwrap().to_bytes(), BLINDING_GENERATOR);
+    }
+
+    #[test]
+    fn test_commit_vectors() {
+        let cases = [
+            (
+                5,
+                0x42,
+                "40582f5cf9500be40924b2e292bac790ad659896c4aaa6b6b1959e07b79ac07d",
+            ),
+            (
+                1000,
+                0x17,
+                "b0bde050032ec872a737ddcdc4e89c8f4f5a8a161d969ff6a9d86fe3c0df8516",
+            ),
+            (
+                u64::MAX,
+                0x42,
+                "06206d6eb512787283c1b5a870a43e7348f7627611c61478338143bd36473310",
+            ),
+        ];
+        for (value, byte, expected) in cases.iter() {
+            let commitment = commit(*value, &blinding(*byte)).unwrap();
+            assert_eq!(commitment.as_ref(), &hex::decode(expected).unwrap()[..]);
+            assert!(verify(&commitment, *value, &blinding(*byte)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_verify_wrong_opening() {
+        let commitment = commit(5, &blinding(0x42)).unwrap();
+        assert!(verify(&commitment, 6, &blinding(0x42)).is_err());
+        assert!(verify(&commitment, 5, &blinding(0x43)).is_err());
+    }
+
+    #[test]
+    fn test_homomorphic() {
+        let (r1, r2) = (blinding(0x42), blinding(0x17));
+        let c1 = commit(5, &r1).unwrap();
+        let c2 = commit(1000, &r2).unwrap();
+
+        let sum = add(&c1, &c2).unwrap();
+        assert_eq!(
+            sum.as_ref(),
+            &hex::decode("a8e15f97139c98e69a54846d07941c7c9b81abcb86841d7473f859f5ea304f15")
+                .unwrap()[..]
+        );
+        assert!(verify(&sum, 1005, &r1.add(&r2)).is_ok());
+
+        let difference = sub(&c2, &c1).unwrap();
+        assert!(verify(&difference, 995, &r2.sub(&r1)).is_ok());
+        assert!(verify(&sub(&c1, &c1).unwrap(), 0, &r1.sub(&r1)).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_commitment() {
+        let invalid = Commitment::from_slice(&[0xffu8; COMMITMENTSIZE]).unwrap();
+        let valid = commit(1, &blinding(1)).unwrap();
+        assert!(add(&invalid, &valid).is_err());
+        assert!(add(&valid, &invalid).is_err());
+        assert!(sub(&invalid, &valid).is_err());
+        assert!(verify(&invalid, 1, &blinding(1)).is_err());
+    }
+
+    #[test]
+    fn test_blinding_from_slice() {
+        assert!(Blinding::from_slice(&[0u8; 31]).is_err());
+        assert!(Blinding::from_slice(&[0u8; 33]).is_err());
+        assert!(Blinding::from_slice(&[0xffu8; 32]).is_err());
+        // The group order.
+        assert!(Blinding::from_slice(&super::super::edwards25519::GROUP_ORDER).is_err());
+        let b = Blinding::from_slice(&[0u8; 32]).unwrap();
+        assert_eq!(b.len(), BLINDINGSIZE);
+        assert!(b == &[0u8; 32][..]);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_blinding_generate() {
+        let b1 = Blinding::generate();
+        let b2 = Blinding::generate();
+        assert!(b1 != b2);
+        assert!(Blinding::from_slice(b1.unprotected_as_bytes()).is_ok());
+        assert_eq!(format!("{:?}", b1), "Blinding {***OMITTED***}");
+    }
+}
@@ -0,0 +1,394 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The ristretto255 prime-order group, as specified in
+//! [RFC 9496](https://www.rfc-editor.org/rfc/rfc9496), and its scalars.
+//!
+//! Elements are represented by points on the Edwards form of Curve25519, with
+//! several points representing the same element. All operations run in
+//! constant time, except for [`RistrettoPoint::mul_vartime()`].
+
+use super::edwards25519::EdwardsPoint;
+use super::field25519::FieldElement;
+use super::modular256::{Modulus, Residue};
+use crate::errors::UnknownCryptoError;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// The size of an encoded element.
+pub(crate) const POINTSIZE: usize = 32;
+/// The size of an encoded scalar.
+pub(crate) const SCALARSIZE: usize = 32;
+
+#[derive(Clone, Copy)]
+/// The order of the group, 2^252 + 27742317777372353535851937790883648493.
+pub(crate) struct GroupOrder;
+
+impl Modulus for GroupOrder {
+    const MODULUS: [u64; 4] = [
+        0x5812_631a_5cf5_d3ed,
+        0x14de_f9de_a2f7_9cd6,
+        0x0000_0000_0000_0000,
+        0x1000_0000_0000_0000,
+    ];
+    const R2: [u64; 4] = [
+        0xa406_11e3_449c_0f01,
+        0xd00e_1ba7_6885_9347,
+        0xceec_73d2_17f5_be65,
+        0x0399_411b_7c30_9a3d,
+    ];
+    const M0_INV: u64 = 0xd2b5_1da3_1254_7e1b;
+}
+
+/// An integer modulo the group order.
+pub(crate) type Scalar = Residue<GroupOrder>;
+
+/// Reverse the byte order of a 32-byte integer.
+fn reversed(bytes: &[u8; 32]) -> [u8; 32] {
+    let mut out = *bytes;
+    out.reverse();
+    out
+}
+
+/// Load a canonical scalar from its 32-byte little-endian encoding.
+pub(crate) fn scalar_from_bytes(bytes: &[u8; SCALARSIZE]) -> Result<Scalar, UnknownCryptoError> {
+    let (scalar, is_canonical) = Scalar::from_bytes(&reversed(bytes));
+    if bool::from(is_canonical) {
+        Ok(scalar)
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+/// Load a 64-byte little-endian integer, reducing it modulo the group order.
+pub(crate) fn scalar_from_bytes_wide(bytes: &[u8; 64]) -> Scalar {
+    use zeroize::Zeroize;
+
+    let mut be = *bytes;
+    be.reverse();
+    let scalar = Scalar::from_bytes_wide(&be);
+    be.iter_mut().zeroize();
+
+    scalar
+}
+
+/// The canonical 32-byte little-endian encoding of `scalar`.
+pub(crate) fn scalar_to_bytes(scalar: &Scalar) -> [u8; SCALARSIZE] {
+    reversed(&scalar.to_bytes())
+}
+
+#[derive(Clone, Copy)]
+/// An element of ristretto255.
+pub(crate) struct RistrettoPoint(EdwardsPoint);
+
+impl ConditionallySelectable for RistrettoPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(EdwardsPoint::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl RistrettoPoint {
+    #[cfg(any(test, feature = "safe_api"))]
+    /// The neutral element.
+    pub(crate) const IDENTITY: Self = Self(EdwardsPoint::IDENTITY);
+
+    /// The generator of the group, which is the Ed25519 basepoint.
+    pub(crate) const GENERATOR: Self = Self(EdwardsPoint::BASEPOINT);
+
+    /// Decode an element, rejecting all non-canonical encodings.
+    pub(crate) fn from_bytes(bytes: &[u8; POINTSIZE]) -> Result<Self, UnknownCryptoError> {
+        let s = FieldElement::from_bytes(bytes);
+        if !bool::from(s.to_bytes().ct_eq(bytes)) || bool::from(s.is_negative()) {
+            return Err(UnknownCryptoError);
+        }
+
+        let one = FieldElement::ONE;
+        let ss = s.square();
+        let u1 = one.sub(&ss);
+        let u2 = one.add(&ss);
+        let u2_sqr = u2.square();
+        let v = FieldElement::EDWARDS_D.mul(&u1.square()).neg().sub(&u2_sqr);
+
+        let (was_square, invsqrt) = FieldElement::sqrt_ratio_m1(&one, &v.mul(&u2_sqr));
+        let den_x = invsqrt.mul(&u2);
+        let den_y = invsqrt.mul(&den_x).mul(&v);
+
+        let x = s.add(&s).mul(&den_x).abs();
+        let y = u1.mul(&den_y);
+        let t = x.mul(&y);
+
+        if !bool::from(was_square) || bool::from(t.is_negative() | y.is_zero()) {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self(EdwardsPoint { x, y, z: one, t }))
+    }
+
+    /// The canonical encoding of this element.
+    pub(crate) fn to_bytes(self) -> [u8; POINTSIZE] {
+        let EdwardsPoint { x, y, z, t } = self.0;
+
+        let u1 = z.add(&y).mul(&z.sub(&y));
+        let u2 = x.mul(&y);
+        let (_, invsqrt) = FieldElement::sqrt_ratio_m1(&FieldElement::ONE, &u1.mul(&u2.square()));
+        let den1 = invsqrt.mul(&u1);
+        let den2 = invsqrt.mul(&u2);
+        let z_inv = den1.mul(&den2).mul(&t);
+
+        let ix = x.mul(&FieldElement::SQRT_M1);
+        let iy = y.mul(&FieldElement::SQRT_M1);
+        let enchanted_denominator = den1.mul(&FieldElement::INVSQRT_A_MINUS_D);
+
+        let rotate = t.mul(&z_inv).is_negative();
+        let x = FieldElement::conditional_select(&x, &iy, rotate);
+        let mut y = FieldElement::conditional_select(&y, &ix, rotate);
+        let den_inv = FieldElement::conditional_select(&den2, &enchanted_denominator, rotate);
+
+        y = FieldElement::conditional_select(&y, &y.neg(), x.mul(&z_inv).is_negative());
+
+        den_inv.mul(&z.sub(&y)).abs().to_bytes()
+    }
+
+    /// Map a field element to the group, as MAP in RFC 9496.
+    fn elligator(t: &FieldElement) -> Self {
+        let one = FieldElement::ONE;
+        let d = FieldElement::EDWARDS_D;
+
+        let r = FieldElement::SQRT_M1.mul(&t.square());
+        let u = r.add(&one).mul(&FieldElement::ONE_MINUS_D_SQ);
+        let v = one.neg().sub(&r.mul(&d)).mul(&r.add(&d));
+
+        let (was_square, mut s) = FieldElement::sqrt_ratio_m1(&u, &v);
+        let s_prime = s.mul(t).abs().neg();
+        s = FieldElement::conditional_select(&s_prime, &s, was_square);
+        let c = FieldElement::conditional_select(&r, &one.neg(), was_square);
+
+        let n = c
+            .mul(&r.sub(&one))
+            .mul(&FieldElement::D_MINUS_ONE_SQ)
+            .sub(&v);
+
+        let s_sqr = s.square();
+        let w0 = s.add(&s).mul(&v);
+        let w1 = n.mul(&FieldElement::SQRT_AD_MINUS_ONE);
+        let w2 = one.sub(&s_sqr);
+        let w3 = one.add(&s_sqr);
+
+        Self(EdwardsPoint {
+            x: w0.mul(&w3),
+            y: w2.mul(&w1),
+            z: w1.mul(&w3),
+            t: w0.mul(&w2),
+        })
+    }
+
+    /// Derive an element from 64 uniformly random bytes, such as the output
+    /// of a hash function. Nobody knows the discrete logarithm of the result.
+    pub(crate) fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        let mut half = [0u8; 32];
+        half.copy_from_slice(&bytes[..32]);
+        let p1 = Self::elligator(&FieldElement::from_bytes(&half));
+        half.copy_from_slice(&bytes[32..]);
+        let p2 = Self::elligator(&FieldElement::from_bytes(&half));
+
+        p1.add(&p2)
+    }
+
+    /// self + rhs.
+    pub(crate) fn add(&self, rhs: &Self) -> Self {
+        Self(self.0.add(&rhs.0))
+    }
+
+    /// self - rhs.
+    pub(crate) fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.neg())
+    }
+
+    /// -self.
+    pub(crate) fn neg(&self) -> Self {
+        Self(self.0.neg())
+    }
+
+    /// [scalar]self.
+    pub(crate) fn mul(&self, scalar: &Scalar) -> Self {
+        Self(self.0.mul(&scalar_to_bytes(scalar)))
+    }
+
+    #[cfg(any(test, feature = "safe_api", feature = "alloc"))]
+    /// [scalar]self. The execution time depends on `scalar`, which must
+    /// therefore be public.
+    pub(crate) fn mul_vartime(&self, scalar: &Scalar) -> Self {
+        Self(self.0.mul_vartime(&scalar_to_bytes(scalar)))
+    }
+
+    #[cfg(any(test, feature = "safe_api", feature = "alloc"))]
+    /// Whether self and rhs are the same element.
+    pub(crate) fn equals(&self, rhs: &Self) -> Choice {
+        let (a, b) = (&self.0, &rhs.0);
+        a.x.mul(&b.y).ct_eq(&a.y.mul(&b.x)) | a.y.mul(&b.y).ct_eq(&a.x.mul(&b.x))
+    }
+
+    #[cfg(test)]
+    /// Whether this is the neutral element.
+    pub(crate) fn is_identity(&self) -> Choice {
+        self.equals(&Self::IDENTITY)
+    }
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+    use crate::hazardous::ecc::edwards25519::GROUP_ORDER;
+    use crate::hazardous::hash::sha512::Sha512;
+
+    fn decode(hex_str: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&hex::decode(hex_str).unwrap());
+        bytes
+    }
+
+    // Encodings of multiples of the generator, from RFC 9496, Appendix A.1.
+    const MULTIPLES: [&str; 16] = [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76",
+        "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+        "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259",
+        "da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57",
+        "e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e",
+        "f64746d3c92b13050ed8d80236a7f0007c3b3f962f5ba793d19a601ebb1df403",
+        "44f53520926ec81fbd5a387845beb7df85a96a24ece18738bdcfa6a7822a176d",
+        "903293d8f2287ebe10e2374dc1a53e0bc887e592699f02d077d5263cdd55601c",
+        "02622ace8f7303a31cafc63f8fc48fdc16e1c8c8d234b2f0d6685282a9076031",
+        "20706fd788b2720a1ed2a5dad4952b01f413bcf0e7564de8cdc816689e2db95f",
+        "bce83f8ba5dd2fa572864c24ba1810f9522bc6004afe95877ac73241cafdab42",
+        "e4549ee16b9aa03099ca208c67adafcafa4c3f3e4e5303de6026e3ca8ff84460",
+        "aa52e000df2e16f55fb1032fc33bc42742dad6bd5a8fc0be0167436c5948501f",
+        "46376b80f409b29dc2b5f6f0c52591990896e5716f41477cd30085ab7f10301e",
+        "e0c418f7c8d9c4cdd7395b93ea124f3ad99021bb681dfc3302a9d99a2e53e64e",
+    ];
+
+    #[test]
+    fn test_multiples_of_generator() {
+        let mut point = RistrettoPoint::IDENTITY;
+        for (i, expected) in MULTIPLES.iter().enumerate() {
+            let expected = decode(expected);
+            assert_eq!(point.to_bytes(), expected);
+
+            let decoded = RistrettoPoint::from_bytes(&expected).unwrap();
+            assert!(bool::from(decoded.equals(&point)));
+            assert_eq!(decoded.to_bytes(), expected);
+
+            let scalar = Scalar::from_u64(i as u64);
+            assert_eq!(RistrettoPoint::GENERATOR.mul(&scalar).to_bytes(), expected);
+            assert_eq!(
+                RistrettoPoint::GENERATOR.mul_vartime(&scalar).to_bytes(),
+                expected
+            );
+
+            point = point.add(&RistrettoPoint::GENERATOR);
+        }
+    }
+
+    #[test]
+    fn test_bad_encodings() {
+        // From RFC 9496, Appendix A.2, and otherwise invalid encodings.
+        let bad = [
+            // Non-canonical field encodings.
+            "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // Negative field elements.
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // Non-square x^2.
+            "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+            "0200000000000000000000000000000000000000000000000000000000000000",
+        ];
+        for encoding in bad.iter() {
+            assert!(RistrettoPoint::from_bytes(&decode(encoding)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_from_uniform_bytes() {
+        // From RFC 9496, Appendix A.3.
+        let cases = [
+            (
+                "Ristretto is traditionally a short shot of espresso coffee",
+                "3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46",
+            ),
+            (
+                "made with the normal amount of ground coffee but extracted with",
+                "f26e5b6f7d362d2d2a94c5d0e7602cb4773c95a2e5c31a64f133189fa76ed61b",
+            ),
+            (
+                "about half the amount of water in the same amount of time",
+                "006ccd2a9e6867e6a2c5cea83d3302cc9de128dd2a9a57dd8ee7b9d7ffe02826",
+            ),
+            (
+                "by using a finer grind.",
+                "f8f0c87cf237953c5890aec3998169005dae3eca1fbb04548c635953c817f92a",
+            ),
+        ];
+        for (label, expected) in cases.iter() {
+            let mut hash = [0u8; 64];
+            hash.copy_from_slice(Sha512::digest(label.as_bytes()).unwrap().as_ref());
+            assert_eq!(
+                RistrettoPoint::from_uniform_bytes(&hash).to_bytes(),
+                decode(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_operations() {
+        let g = RistrettoPoint::GENERATOR;
+        let two = Scalar::from_u64(2);
+        let three = Scalar::from_u64(3);
+        assert!(bool::from(g.mul(&two).add(&g).equals(&g.mul(&three))));
+        assert!(bool::from(g.mul(&three).sub(&g).equals(&g.mul(&two))));
+        assert!(bool::from(g.add(&g.neg()).is_identity()));
+        assert!(!bool::from(g.is_identity()));
+        assert!(bool::from(g.mul(&Scalar::ZERO).is_identity()));
+        assert!(bool::from(g.mul(&Scalar::one().neg()).equals(&g.neg())));
+    }
+
+    #[test]
+    fn test_scalar_encoding() {
+        assert!(scalar_from_bytes(&GROUP_ORDER).is_err());
+        assert!(scalar_from_bytes(&[0xffu8; 32]).is_err());
+
+        let mut order_minus_one = GROUP_ORDER;
+        order_minus_one[0] -= 1;
+        let scalar = scalar_from_bytes(&order_minus_one).unwrap();
+        assert!(bool::from(scalar.ct_eq(&Scalar::one().neg())));
+        assert_eq!(scalar_to_bytes(&scalar), order_minus_one);
+
+        // 0, 1, ..., 63 as a little-endian integer, reduced.
+        let mut wide = [0u8; 64];
+        for (i, byte) in wide.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        assert_eq!(
+            scalar_to_bytes(&scalar_from_bytes_wide(&wide)),
+            decode("7a3c6282f02d37a05023b60d5428e6cc5961d4c31221937adae0b574e4d07205")
+        );
+    }
+}
@@ -0,0 +1,270 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The elliptic curve secp256k1, y^2 = x^3 + 7, as specified in
+//! [SEC 2](https://www.secg.org/sec2-v2.pdf).
+//!
+//! Points are represented in homogeneous projective coordinates (X : Y : Z),
+//! and added with the complete formulas of [Renes, Costello and Batina](https://eprint.iacr.org/2015/1060),
+//! which have no exceptional cases and run in constant time.
+
+use super::modular256::{Modulus, Residue};
+use crate::errors::UnknownCryptoError;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+#[derive(Clone, Copy)]
+/// The field prime p = 2^256 - 2^32 - 977.
+pub(crate) struct FieldModulus;
+
+impl Modulus for FieldModulus {
+    const MODULUS: [u64; 4] = [
+        0xffff_fffe_ffff_fc2f,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+    ];
+    const R2: [u64; 4] = [0x0000_07a2_000e_90a1, 0x1, 0x0, 0x0];
+    const M0_INV: u64 = 0xd838_091d_d225_3531;
+}
+
+#[derive(Clone, Copy)]
+/// The order n of the group generated by the basepoint.
+pub(crate) struct ScalarModulus;
+
+impl Modulus for ScalarModulus {
+    const MODULUS: [u64; 4] = [
+        0xbfd2_5e8c_d036_4141,
+        0xbaae_dce6_af48_a03b,
+        0xffff_ffff_ffff_fffe,
+        0xffff_ffff_ffff_ffff,
+    ];
+    const R2: [u64; 4] = [
+        0x896c_f214_67d7_d140,
+        0x7414_96c2_0e7c_f878,
+        0xe697_f5e4_5bcd_07c6,
+        0x9d67_1cd5_81c6_9bc5,
+    ];
+    const M0_INV: u64 = 0x4b0d_ff66_5588_b13f;
+}
+
+/// An element of the field of integers modulo p.
+pub(crate) type FieldElement = Residue<FieldModulus>;
+/// An integer modulo the group order n.
+pub(crate) type Scalar = Residue<ScalarModulus>;
+
+/// The x-coordinate of the basepoint.
+const GENERATOR_X: [u8; 32] = [
+    0x79, 0xbe, 0x66, 0x7e, 0xf9, 0xdc, 0xbb, 0xac, 0x55, 0xa0, 0x62, 0x95, 0xce, 0x87, 0x0b, 0x07,
+    0x02, 0x9b, 0xfc, 0xdb, 0x2d, 0xce, 0x28, 0xd9, 0x59, 0xf2, 0x81, 0x5b, 0x16, 0xf8, 0x17, 0x98,
+];
+/// The y-coordinate of the basepoint.
+const GENERATOR_Y: [u8; 32] = [
+    0x48, 0x3a, 0xda, 0x77, 0x26, 0xa3, 0xc4, 0x65, 0x5d, 0xa4, 0xfb, 0xfc, 0x0e, 0x11, 0x08, 0xa8,
+    0xfd, 0x17, 0xb4, 0x48, 0xa6, 0x85, 0x54, 0x19, 0x9c, 0x47, 0xd0, 0x8f, 0xfb, 0x10, 0xd4, 0xb8,
+];
+/// (p + 1) / 4, used for computing square roots, since p = 3 mod 4.
+const SQRT_EXPONENT: [u8; 32] = [
+    0x3f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xbf, 0xff, 0xff, 0x0c,
+];
+/// The size of a compressed point encoding.
+pub(crate) const COMPRESSED_POINTSIZE: usize = 33;
+
+/// The curve coefficient b.
+fn curve_b() -> FieldElement {
+    FieldElement::from_u64(7)
+}
+
+#[derive(Clone, Copy)]
+/// A point on secp256k1, or the point at infinity.
+pub(crate) struct Point {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl_point_arithmetic!(a = 0);
+
+impl Point {
+    /// The point with x-coordinate `x` and an even y-coordinate, as
+    /// specified in [BIP 340](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki).
+    pub(crate) fn lift_x(x: &[u8; 32]) -> Result<Self, UnknownCryptoError> {
+        let (x, is_canonical) = FieldElement::from_bytes(x);
+        if !bool::from(is_canonical) {
+            return Err(UnknownCryptoError);
+        }
+
+        let c = x.square().mul(&x).add(&curve_b());
+        let y = c.pow_vartime(&SQRT_EXPONENT);
+        if !bool::from(y.square().ct_eq(&c)) {
+            return Err(UnknownCryptoError);
+        }
+
+        let y = FieldElement::conditional_select(&y, &y.neg(), y.is_odd());
+
+        Ok(Self {
+            x,
+            y,
+            z: FieldElement::one(),
+        })
+    }
+
+    /// Decode a point from its 33-byte compressed encoding. The point at
+    /// infinity has no such encoding.
+    pub(crate) fn from_compressed(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if bytes.len() != COMPRESSED_POINTSIZE || (bytes[0] != 0x02 && bytes[0] != 0x03) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&bytes[1..]);
+        let point = Self::lift_x(&x)?;
+
+        Ok(if bytes[0] == 0x03 { point.neg() } else { point })
+    }
+
+    /// The affine coordinates of this point, or an error if it is the point
+    /// at infinity.
+    fn to_affine(self) -> Result<(FieldElement, FieldElement), UnknownCryptoError> {
+        if self.is_identity() {
+            return Err(UnknownCryptoError);
+        }
+
+        let z_inv = self.z.invert();
+        Ok((self.x.mul(&z_inv), self.y.mul(&z_inv)))
+    }
+
+    /// The 33-byte compressed encoding of this point.
+    pub(crate) fn to_compressed(self) -> Result<[u8; COMPRESSED_POINTSIZE], UnknownCryptoError> {
+        let (x, y) = self.to_affine()?;
+        let mut bytes = [0u8; COMPRESSED_POINTSIZE];
+        bytes[0] = 0x02 | y.is_odd().unwrap_u8();
+        bytes[1..].copy_from_slice(&x.to_bytes());
+
+        Ok(bytes)
+    }
+
+    /// The 32-byte encoding of the x-coordinate of this point.
+    pub(crate) fn x_bytes(self) -> Result<[u8; 32], UnknownCryptoError> {
+        Ok(self.to_affine()?.0.to_bytes())
+    }
+
+    /// Whether the y-coordinate of this point is even. The point at infinity
+    /// has no y-coordinate and returns an error.
+    pub(crate) fn has_even_y(self) -> Result<bool, UnknownCryptoError> {
+        Ok(!bool::from(self.to_affine()?.1.is_odd()))
+    }
+}
+
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn scalar(value: u64) -> Scalar {
+        Scalar::from_u64(value)
+    }
+
+    #[test]
+    fn test_generator_on_curve() {
+        let (x, y) = Point::generator().to_affine().unwrap();
+        let rhs = x.square().mul(&x).add(&curve_b());
+        assert!(bool::from(y.square().ct_eq(&rhs)));
+    }
+
+    #[test]
+    fn test_group_order() {
+        // n - 1 times G is -G, so n times G is the point at infinity.
+        let minus_one = scalar(1).neg();
+        let point = Point::mul_base(&minus_one);
+        assert!(point.equals(&Point::generator().neg()));
+        assert!(point.add(&Point::generator()).is_identity());
+        assert!(Point::mul_base(&Scalar::ZERO).is_identity());
+    }
+
+    #[test]
+    fn test_small_multiples() {
+        let g = Point::generator();
+        let mut sum = Point::identity();
+        for i in 1..=16u64 {
+            sum = sum.add(&g);
+            assert!(sum.equals(&Point::mul_base(&scalar(i))));
+        }
+        // Doubling through add().
+        assert!(g.add(&g).equals(&Point::mul_base(&scalar(2))));
+        // x(3G) is the BIP 340 public key of the secret key 3.
+        assert_eq!(
+            Point::mul_base(&scalar(3)).x_bytes().unwrap()[..],
+            hex::decode("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_identity() {
+        let g = Point::generator();
+        let identity = Point::identity();
+        assert!(identity.add(&identity).is_identity());
+        assert!(g.add(&identity).equals(&g));
+        assert!(identity.add(&g).equals(&g));
+        assert!(g.add(&g.neg()).is_identity());
+        assert!(identity.to_compressed().is_err());
+        assert!(identity.x_bytes().is_err());
+        assert!(identity.has_even_y().is_err());
+        assert!(!identity.equals(&g));
+        assert!(identity.equals(&Point::identity()));
+    }
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        for i in 1..=8u64 {
+            let point = Point::mul_base(&scalar(i));
+            let bytes = point.to_compressed().unwrap();
+            assert!(Point::from_compressed(&bytes).unwrap().equals(&point));
+            assert_eq!(bytes[0] == 0x02, point.has_even_y().unwrap());
+            assert_eq!(bytes[1..], point.x_bytes().unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn test_reject_invalid_encodings() {
+        let mut bytes = Point::generator().to_compressed().unwrap();
+        assert!(Point::from_compressed(&bytes[..32]).is_err());
+        bytes[0] = 0x04;
+        assert!(Point::from_compressed(&bytes).is_err());
+
+        // x = p is not canonical.
+        let mut x_is_p = [0xffu8; 32];
+        x_is_p[27] = 0xfe;
+        x_is_p[30] = 0xfc;
+        x_is_p[31] = 0x2f;
+        assert!(Point::lift_x(&x_is_p).is_err());
+
+        // x = 5 is not on the curve, since 5^3 + 7 = 132 is not a square.
+        let mut five = [0u8; 32];
+        five[31] = 5;
+        assert!(Point::lift_x(&five).is_err());
+
+        // lift_x() returns the point with an even y-coordinate.
+        let lifted = Point::lift_x(&Point::generator().x_bytes().unwrap()).unwrap();
+        assert!(lifted.has_even_y().unwrap());
+    }
+}
@@ -0,0 +1,330 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Deterministic derivation of Ed25519 and X25519 key pairs from a single
+//! master [`Seed`], so that an identity can be backed up and restored by
+//! storing only the seed.
+//!
+//! Each key pair is derived from its own 32-byte secret, which is
+//! HKDF-HMAC-SHA512 of the seed, with an empty salt and one of the following
+//! `info` strings:
+//! - `orion ecc seed ed25519` for the Ed25519 seed of [`Ed25519KeyPair`].
+//! - `orion ecc seed x25519` for the X25519 secret key of [`X25519KeyPair`],
+//!   which is then clamped as specified in the
+//!   [RFC 7748](https://tools.ietf.org/html/rfc7748).
+//!
+//! # Parameters:
+//! - `seed`: The master seed the key pair is derived from.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The slice given to [`Seed::from_slice()`] is not 32 bytes.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely in [`Seed::generate()`].
+//!
+//! # Security:
+//! - The seed must be generated using a CSPRNG, such as
+//!   [`Seed::generate()`], and must be kept as secret as the keys derived
+//!   from it. Anyone holding the seed can derive every key pair of the
+//!   identity.
+//! - The Ed25519 and X25519 key pairs of a seed are independent of each
+//!   other, unlike those converted with
+//!   [`curve25519::ed25519_seed_to_x25519()`](../curve25519/fn.ed25519_seed_to_x25519.html).
+//!   Knowing one key pair reveals nothing about the other.
+//! - The derivation is fixed. Changing it would change every key derived from
+//!   existing seeds, so it will not change in future versions.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::{ed25519, seed::{Ed25519KeyPair, Seed, X25519KeyPair}};
+//!
+//! let seed = Seed::generate();
+//! let signing = Ed25519KeyPair::from_seed(&seed)?;
+//! let agreement = X25519KeyPair::from_seed(&seed)?;
+//!
+//! // Restoring the identity from the stored seed gives the same key pairs.
+//! let restored = Seed::from_slice(seed.unprotected_as_bytes())?;
+//! assert_eq!(Ed25519KeyPair::from_seed(&restored)?.public_key(), signing.public_key());
+//! assert_eq!(X25519KeyPair::from_seed(&restored)?.public_key(), agreement.public_key());
+//!
+//! let signature = ed25519::sign(signing.secret_key(), b"Message")?;
+//! assert!(ed25519::verify(&signature, signing.public_key(), b"Message").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`Seed`]: struct.Seed.html
+//! [`Seed::from_slice()`]: struct.Seed.html#method.from_slice
+//! [`Seed::generate()`]: struct.Seed.html#method.generate
+//! [`Ed25519KeyPair`]: struct.Ed25519KeyPair.html
+//! [`X25519KeyPair`]: struct.X25519KeyPair.html
+
+use super::{curve25519, ed25519};
+use crate::{errors::UnknownCryptoError, hazardous::kdf::hkdf};
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of a master seed.
+pub const SEEDSIZE: usize = 32;
+
+/// The `info` of the HKDF step deriving the Ed25519 seed.
+const ED25519_INFO: &[u8] = b"orion ecc seed ed25519";
+
+/// The `info` of the HKDF step deriving the X25519 secret key.
+const X25519_INFO: &[u8] = b"orion ecc seed x25519";
+
+construct_secret_key! {
+    /// A type to represent the master `Seed` that key pairs are derived from.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (Seed, test_seed, SEEDSIZE, SEEDSIZE, SEEDSIZE)
+}
+
+impl_from_trait!(Seed, SEEDSIZE);
+
+construct_secret_key! {
+    /// A type to represent the clamped secret key of an X25519 key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (X25519SecretKey, test_x25519_secret_key, curve25519::X25519_KEYSIZE, curve25519::X25519_KEYSIZE, curve25519::X25519_KEYSIZE)
+}
+
+construct_public! {
+    /// A type to represent the public key of an X25519 key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (X25519PublicKey, test_x25519_public_key, curve25519::X25519_KEYSIZE, curve25519::X25519_KEYSIZE)
+}
+
+/// Derive the 32-byte secret for `info` from `seed`.
+fn derive_secret(seed: &Seed, info: &[u8]) -> Result<[u8; SEEDSIZE], UnknownCryptoError> {
+    let mut secret = [0u8; SEEDSIZE];
+    hkdf::derive_key(&[], seed.unprotected_as_bytes(), Some(info), &mut secret)?;
+
+    Ok(secret)
+}
+
+#[derive(Debug)]
+/// An Ed25519 key pair derived from a [`Seed`](struct.Seed.html).
+pub struct Ed25519KeyPair {
+    secret_key: ed25519::SecretKey,
+    public_key: ed25519::PublicKey,
+}
+
+impl Ed25519KeyPair {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive the Ed25519 key pair of `seed`.
+    pub fn from_seed(seed: &Seed) -> Result<Self, UnknownCryptoError> {
+        let mut secret = derive_secret(seed, ED25519_INFO)?;
+        let secret_key = ed25519::SecretKey::from_slice(&secret);
+        secret.zeroize();
+        let secret_key = secret_key?;
+        let public_key = ed25519::PublicKey::try_from(&secret_key)?;
+
+        Ok(Self {
+            secret_key,
+            public_key,
+        })
+    }
+
+    /// Return the secret key.
+    pub fn secret_key(&self) -> &ed25519::SecretKey {
+        &self.secret_key
+    }
+
+    /// Return the public key.
+    pub fn public_key(&self) -> &ed25519::PublicKey {
+        &self.public_key
+    }
+}
+
+#[derive(Debug)]
+/// An X25519 key pair derived from a [`Seed`](struct.Seed.html).
+pub struct X25519KeyPair {
+    secret_key: X25519SecretKey,
+    public_key: X25519PublicKey,
+}
+
+impl X25519KeyPair {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive the X25519 key pair of `seed`.
+    pub fn from_seed(seed: &Seed) -> Result<Self, UnknownCryptoError> {
+        let mut secret = derive_secret(seed, X25519_INFO)?;
+        let mut public = [0u8; curve25519::X25519_KEYSIZE];
+        let result = curve25519::clamp_scalar(&mut secret)
+            .and_then(|_| curve25519::scalarmult_base(&secret, &mut public))
+            .and_then(|_| X25519SecretKey::from_slice(&secret));
+        secret.zeroize();
+
+        Ok(Self {
+            secret_key: result?,
+            public_key: X25519PublicKey::from_slice(&public)?,
+        })
+    }
+
+    /// Return the secret key.
+    pub fn secret_key(&self) -> &X25519SecretKey {
+        &self.secret_key
+    }
+
+    /// Return the public key.
+    pub fn public_key(&self) -> &X25519PublicKey {
+        &self.public_key
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn seed() -> Seed {
+        let mut bytes = [0u8; SEEDSIZE];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        Seed::from(bytes)
+    }
+
+    #[test]
+    fn test_ed25519_known() {
+        // Computed with HKDF and Ed25519 from the `cryptography` package.
+        let key_pair = Ed25519KeyPair::from_seed(&seed()).unwrap();
+        assert_eq!(
+            key_pair.secret_key().unprotected_as_bytes(),
+            &hex::decode("18ef94b178f0e86ae38ad6860ec0a8fe65b15fab23d63d7c903014115d803ea3")
+                .unwrap()[..]
+        );
+        assert_eq!(
+            key_pair.public_key().as_ref(),
+            &hex::decode("ba77b45475355e557cba6d237e222e3cae281ed785378aa97f89e35dd68faebc")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_x25519_known() {
+        // Computed with HKDF and X25519 from the `cryptography` package.
+        let key_pair = X25519KeyPair::from_seed(&seed()).unwrap();
+        assert_eq!(
+            key_pair.secret_key().unprotected_as_bytes(),
+            &hex::decode("28f878131c22510f8da66c8f81ec9c1a3b4c8c3be5e066385d4719928632a071")
+                .unwrap()[..]
+        );
+        assert_eq!(
+            key_pair.public_key().as_ref(),
+            &hex::decode("4405897c1ab4beb553268da59c8bf2e8868e4f79f4a2a3109e7443234bb1ae4e")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_deterministic_and_independent() {
+        let seed = Seed::generate();
+        let other = Seed::generate();
+
+        let ed = Ed25519KeyPair::from_seed(&seed).unwrap();
+        let x = X25519KeyPair::from_seed(&seed).unwrap();
+        assert_eq!(
+            Ed25519KeyPair::from_seed(&seed).unwrap().public_key(),
+            ed.public_key()
+        );
+        assert_eq!(
+            X25519KeyPair::from_seed(&seed).unwrap().public_key(),
+            x.public_key()
+        );
+        assert_ne!(
+            Ed25519KeyPair::from_seed(&other).unwrap().public_key(),
+            ed.public_key()
+        );
+        assert_ne!(
+            X25519KeyPair::from_seed(&other).unwrap().public_key(),
+            x.public_key()
+        );
+
+        // The key pairs do not reuse the seed, or each other's secrets.
+        assert_ne!(
+            ed.secret_key().unprotected_as_bytes(),
+            seed.unprotected_as_bytes()
+        );
+        assert_ne!(
+            x.secret_key().unprotected_as_bytes(),
+            seed.unprotected_as_bytes()
+        );
+        assert_ne!(
+            ed.secret_key().unprotected_as_bytes(),
+            x.secret_key().unprotected_as_bytes()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_x25519_agreement() {
+        let alice = X25519KeyPair::from_seed(&Seed::generate()).unwrap();
+        let bob = X25519KeyPair::from_seed(&Seed::generate()).unwrap();
+
+        let mut alice_shared = [0u8; curve25519::POINTSIZE];
+        let mut bob_shared = [0u8; curve25519::POINTSIZE];
+        curve25519::scalarmult(
+            alice.secret_key().unprotected_as_bytes(),
+            bob.public_key().as_ref(),
+            &mut alice_shared,
+        )
+        .unwrap();
+        curve25519::scalarmult(
+            bob.secret_key().unprotected_as_bytes(),
+            alice.public_key().as_ref(),
+            &mut bob_shared,
+        )
+        .unwrap();
+        assert_eq!(alice_shared, bob_shared);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let key_pair = Ed25519KeyPair::from_seed(&seed()).unwrap();
+        let debug = format!("{:?}", key_pair);
+        assert!(debug.contains("***OMITTED***"));
+        assert!(!debug.contains("18ef94b1"));
+
+        let key_pair = X25519KeyPair::from_seed(&seed()).unwrap();
+        let debug = format!("{:?}", key_pair);
+        assert!(debug.contains("***OMITTED***"));
+    }
+}
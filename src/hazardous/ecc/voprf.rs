@@ -0,0 +1,828 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The verifiable oblivious pseudorandom function (VOPRF) of
+//! [RFC 9497](https://www.rfc-editor.org/rfc/rfc9497), with the
+//! `P384-SHA384` ciphersuite.
+//!
+//! A client learns the output of a keyed function on an input of its
+//! choice, while the server that holds the key learns neither the input nor
+//! the output. The server proves, in zero knowledge, that it used the secret
+//! key that belongs to its public key, so that it cannot tag clients by
+//! using different keys for them.
+//!
+//! The protocol runs as follows:
+//! 1. The client calls [`blind()`] on its input with a fresh [`Blind`], and
+//!    sends the [`BlindedElement`] to the server.
+//! 2. The server calls [`blind_evaluate()`] with a fresh [`ProofNonce`], and
+//!    sends the [`EvaluatedElement`] and [`Proof`] back.
+//! 3. The client calls [`finalize()`], which verifies the proof and returns
+//!    the output.
+//!
+//! The server can compute the same output directly with [`evaluate()`],
+//! which is how it checks outputs that clients present to it later.
+//!
+//! # Parameters:
+//! - `input`: The private input of the client.
+//! - `blind`: The secret blinding factor of the client.
+//! - `secret_key`: The secret key of the server.
+//! - `public_key`: The public key of the server.
+//! - `blinded_element`: The blinded input, sent from the client to the server.
+//! - `evaluated_element`: The evaluated blinded input, sent from the server to
+//!   the client.
+//! - `proof`: The proof that `evaluated_element` was computed with the secret
+//!   key of `public_key`.
+//! - `proof_nonce`: The secret randomness of the proof.
+//! - `dst_out`: Destination buffer for the output of the function.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A [`SecretKey`], [`Blind`] or [`ProofNonce`] is created from a slice that
+//!   is not 48 bytes, or is not the big-endian encoding of a non-zero integer
+//!   less than the group order.
+//! - The seed given to [`SecretKey::derive()`] is not [`SEEDSIZE`] bytes, or
+//!   `info` is longer than 65535 bytes.
+//! - `input` is longer than 65535 bytes.
+//! - `public_key`, `blinded_element` or `evaluated_element` is not a valid
+//!   encoding of a point on P-384.
+//! - `proof` does not verify.
+//! - `dst_out` is not [`OUTPUTSIZE`] bytes.
+//!
+//! # Security:
+//! - [`Blind`] and [`ProofNonce`] must be secret and chosen uniformly at
+//!   random for every evaluation, with [`Blind::generate()`] and
+//!   [`ProofNonce::generate()`]. Reusing a `ProofNonce` reveals the secret
+//!   key.
+//! - Batched evaluation of several elements with a single proof is not
+//!   supported.
+//! - The output reveals nothing about the secret key, but anyone who learns it
+//!   and the input can recognize it. It should be handled as a secret.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::voprf::{self, Blind, ProofNonce, SecretKey};
+//!
+//! // Server
+//! let secret_key = SecretKey::generate();
+//! let public_key = secret_key.public_key()?;
+//!
+//! // Client
+//! let blind = Blind::generate();
+//! let blinded_element = voprf::blind(b"Client input", &blind)?;
+//!
+//! // Server
+//! let (evaluated_element, proof) =
+//!     voprf::blind_evaluate(&secret_key, &blinded_element, &ProofNonce::generate())?;
+//!
+//! // Client
+//! let mut output = [0u8; voprf::OUTPUTSIZE];
+//! voprf::finalize(
+//!     b"Client input",
+//!     &blind,
+//!     &evaluated_element,
+//!     &blinded_element,
+//!     &public_key,
+//!     &proof,
+//!     &mut output,
+//! )?;
+//!
+//! // Server, when the client presents the output together with its input
+//! let mut expected = [0u8; voprf::OUTPUTSIZE];
+//! voprf::evaluate(&secret_key, b"Client input", &mut expected)?;
+//! assert_eq!(output, expected);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`blind()`]: fn.blind.html
+//! [`blind_evaluate()`]: fn.blind_evaluate.html
+//! [`finalize()`]: fn.finalize.html
+//! [`evaluate()`]: fn.evaluate.html
+//! [`Blind`]: struct.Blind.html
+//! [`Blind::generate()`]: struct.Blind.html#method.generate
+//! [`ProofNonce`]: struct.ProofNonce.html
+//! [`ProofNonce::generate()`]: struct.ProofNonce.html#method.generate
+//! [`BlindedElement`]: struct.BlindedElement.html
+//! [`EvaluatedElement`]: struct.EvaluatedElement.html
+//! [`Proof`]: struct.Proof.html
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`SecretKey::derive()`]: struct.SecretKey.html#method.derive
+//! [`SEEDSIZE`]: constant.SEEDSIZE.html
+//! [`OUTPUTSIZE`]: constant.OUTPUTSIZE.html
+
+use super::p384::{hash_to_curve, hash_to_scalar, Point, Scalar, COMPRESSED_POINTSIZE, SCALARSIZE};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha384::{Sha384, SHA384_OUTSIZE};
+use zeroize::Zeroize;
+
+/// The size of an encoded point, such as a public key.
+pub const ELEMENTSIZE: usize = COMPRESSED_POINTSIZE;
+/// The size of an encoded scalar, such as a secret key.
+pub const SECRETSIZE: usize = SCALARSIZE;
+/// The size of a proof.
+pub const PROOFSIZE: usize = 2 * SCALARSIZE;
+/// The size of the output of the function.
+pub const OUTPUTSIZE: usize = SHA384_OUTSIZE;
+/// The size of the seed that a secret key is derived from.
+pub const SEEDSIZE: usize = 32;
+
+/// The domain separation tag of HashToGroup, with the context string
+/// `"OPRFV1-" || 0x01 || "-P384-SHA384"` of the VOPRF mode.
+const HASH_TO_GROUP_DST: &[u8] = b"HashToGroup-OPRFV1-\x01-P384-SHA384";
+/// The domain separation tag of HashToScalar.
+const HASH_TO_SCALAR_DST: &[u8] = b"HashToScalar-OPRFV1-\x01-P384-SHA384";
+/// The domain separation tag of DeriveKeyPair.
+const DERIVE_KEY_PAIR_DST: &[u8] = b"DeriveKeyPair-OPRFV1-\x01-P384-SHA384";
+/// The domain separation tag of the seed of the composite elements.
+const SEED_DST: &[u8] = b"Seed-OPRFV1-\x01-P384-SHA384";
+
+/// I2OSP(`len`, 2), for lengths that have already been checked.
+fn length_prefix(len: usize) -> [u8; 2] {
+    debug_assert!(len <= u16::MAX as usize);
+    (len as u16).to_be_bytes()
+}
+
+/// Decode a canonical, non-zero scalar.
+fn decode_scalar(bytes: &[u8]) -> Result<Scalar, UnknownCryptoError> {
+    if bytes.len() != SCALARSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut value = [0u8; SCALARSIZE];
+    value.copy_from_slice(bytes);
+    let (scalar, is_canonical) = Scalar::from_bytes(&value);
+    value.zeroize();
+    if !bool::from(is_canonical) || bool::from(scalar.is_zero()) {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(scalar)
+}
+
+/// Define a secret, non-zero scalar type.
+macro_rules! construct_scalar {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        ///
+        /// # Security:
+        /// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as
+        ///   it breaks all protections that the type implements.
+        /// - The trait `PartialEq<&'_ [u8]>` is implemented for this type, and
+        ///   the comparison happens in constant time.
+        pub struct $name {
+            value: [u8; SCALARSIZE],
+            original_length: usize,
+        }
+
+        impl_omitted_debug_trait!($name);
+        impl_drop_trait!($name);
+        impl_ct_partialeq_trait!($name, unprotected_as_bytes);
+
+        impl $name {
+            fn from_scalar(scalar: &Scalar) -> Self {
+                Self {
+                    value: scalar.to_bytes(),
+                    original_length: SCALARSIZE,
+                }
+            }
+
+            fn to_scalar(&self) -> Scalar {
+                // The value is always canonical, so this cannot fail.
+                Scalar::from_bytes(&self.value).0
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Construct from a given byte slice, which must be the big-endian
+            /// encoding of a non-zero integer less than the group order.
+            pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+                let mut scalar = decode_scalar(slice)?;
+                let value = Self::from_scalar(&scalar);
+                scalar.zeroize();
+
+                Ok(value)
+            }
+
+            #[cfg(feature = "safe_api")]
+            /// Randomly generate using a CSPRNG. Not available in `no_std` context.
+            pub fn generate() -> Self {
+                let mut bytes = [0u8; 72];
+                loop {
+                    crate::util::secure_rand_bytes(&mut bytes).unwrap();
+                    let mut scalar = Scalar::from_bytes_wide(&bytes);
+                    if !bool::from(scalar.is_zero()) {
+                        bytes.zeroize();
+                        let value = Self::from_scalar(&scalar);
+                        scalar.zeroize();
+                        return value;
+                    }
+                }
+            }
+
+            func_unprotected_as_bytes!();
+            func_len!();
+        }
+    };
+}
+
+construct_scalar! {
+    /// The secret key of the server.
+    SecretKey
+}
+
+construct_scalar! {
+    /// A secret blinding factor, which hides the input of the client from the
+    /// server.
+    Blind
+}
+
+construct_scalar! {
+    /// The secret randomness of a proof.
+    ProofNonce
+}
+
+impl SecretKey {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive a secret key from a secret `seed` of [`SEEDSIZE`] bytes and
+    /// public `info`, with DeriveKeyPair of RFC 9497.
+    ///
+    /// [`SEEDSIZE`]: constant.SEEDSIZE.html
+    pub fn derive(seed: &[u8], info: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if seed.len() != SEEDSIZE || info.len() > u16::MAX as usize {
+            return Err(UnknownCryptoError);
+        }
+
+        let info_len = length_prefix(info.len());
+        for counter in 0..=255u8 {
+            let mut scalar =
+                hash_to_scalar(&[seed, &info_len, info, &[counter]], DERIVE_KEY_PAIR_DST)?;
+            if !bool::from(scalar.is_zero()) {
+                let secret_key = Self::from_scalar(&scalar);
+                scalar.zeroize();
+                return Ok(secret_key);
+            }
+        }
+
+        Err(UnknownCryptoError)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return the public key of this secret key.
+    pub fn public_key(&self) -> Result<PublicKey, UnknownCryptoError> {
+        let mut scalar = self.to_scalar();
+        let public_key = Point::mul_base(&scalar).to_compressed()?;
+        scalar.zeroize();
+
+        PublicKey::from_slice(&public_key)
+    }
+}
+
+construct_public! {
+    /// A type to represent the `PublicKey` of the server.
+    ///
+    /// The encoding is only checked when the public key is used.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 49 bytes.
+    (PublicKey, test_public_key, ELEMENTSIZE, ELEMENTSIZE)
+}
+
+construct_public! {
+    /// A type to represent a `BlindedElement`, which the client sends to the
+    /// server.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 49 bytes.
+    (BlindedElement, test_blinded_element, ELEMENTSIZE, ELEMENTSIZE)
+}
+
+construct_public! {
+    /// A type to represent an `EvaluatedElement`, which the server returns to
+    /// the client.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 49 bytes.
+    (EvaluatedElement, test_evaluated_element, ELEMENTSIZE, ELEMENTSIZE)
+}
+
+construct_public! {
+    /// A type to represent a `Proof` that an `EvaluatedElement` was computed
+    /// with the secret key of the server.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 96 bytes.
+    (Proof, test_proof, PROOFSIZE, PROOFSIZE)
+}
+
+/// The composite elements M and Z of RFC 9497, for a single pair of a
+/// blinded element `c` and an evaluated element `d`. With `secret_key`, Z is
+/// computed from M instead of from `d`, as the prover does.
+fn compute_composites(
+    secret_key: Option<&Scalar>,
+    public_key: &[u8],
+    c: &Point,
+    d: &Point,
+) -> Result<(Point, Point), UnknownCryptoError> {
+    let seed_dst_len = length_prefix(SEED_DST.len());
+    let element_len = length_prefix(ELEMENTSIZE);
+    let mut seed = [0u8; SHA384_OUTSIZE];
+    let mut state = Sha384::new();
+    state.update(&element_len)?;
+    state.update(public_key)?;
+    state.update(&seed_dst_len)?;
+    state.update(SEED_DST)?;
+    state.finalize_into(&mut seed)?;
+
+    let di = hash_to_scalar(
+        &[
+            &length_prefix(seed.len()),
+            &seed,
+            &length_prefix(0),
+            &element_len,
+            &c.to_compressed()?,
+            &element_len,
+            &d.to_compressed()?,
+            b"Composite",
+        ],
+        HASH_TO_SCALAR_DST,
+    )?;
+
+    let m = c.mul(&di);
+    let z = match secret_key {
+        Some(k) => m.mul(k),
+        None => d.mul(&di),
+    };
+
+    Ok((m, z))
+}
+
+/// The challenge of a proof, from the public key and the points M, Z, t2
+/// and t3.
+fn challenge(public_key: &[u8], points: &[&Point; 4]) -> Result<Scalar, UnknownCryptoError> {
+    let element_len = length_prefix(ELEMENTSIZE);
+    let mut encoded = [[0u8; ELEMENTSIZE]; 4];
+    for (dst, point) in encoded.iter_mut().zip(points.iter()) {
+        *dst = point.to_compressed()?;
+    }
+
+    hash_to_scalar(
+        &[
+            &element_len,
+            public_key,
+            &element_len,
+            &encoded[0],
+            &element_len,
+            &encoded[1],
+            &element_len,
+            &encoded[2],
+            &element_len,
+            &encoded[3],
+            b"Challenge",
+        ],
+        HASH_TO_SCALAR_DST,
+    )
+}
+
+/// The output of the function, from the input and the unblinded element.
+fn finalize_hash(
+    input: &[u8],
+    unblinded: &Point,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut state = Sha384::new();
+    state.update(&length_prefix(input.len()))?;
+    state.update(input)?;
+    state.update(&length_prefix(ELEMENTSIZE))?;
+    state.update(&unblinded.to_compressed()?)?;
+    state.update(b"Finalize")?;
+    state.finalize_into(dst_out)
+}
+
+/// HashToGroup of `input`, which must not be the point at infinity.
+fn hash_to_group(input: &[u8]) -> Result<Point, UnknownCryptoError> {
+    if input.len() > u16::MAX as usize {
+        return Err(UnknownCryptoError);
+    }
+
+    let point = hash_to_curve(&[input], HASH_TO_GROUP_DST)?;
+    if point.is_identity() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(point)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Blind `input` with `blind`, as the client.
+pub fn blind(input: &[u8], blind: &Blind) -> Result<BlindedElement, UnknownCryptoError> {
+    let mut scalar = blind.to_scalar();
+    let blinded = hash_to_group(input)?.mul(&scalar).to_compressed();
+    scalar.zeroize();
+
+    BlindedElement::from_slice(&blinded?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Evaluate `blinded_element` with `secret_key`, and prove that it was done
+/// with the secret key of the public key, as the server.
+pub fn blind_evaluate(
+    secret_key: &SecretKey,
+    blinded_element: &BlindedElement,
+    proof_nonce: &ProofNonce,
+) -> Result<(EvaluatedElement, Proof), UnknownCryptoError> {
+    let blinded = Point::from_compressed(blinded_element.as_ref())?;
+    let public_key = secret_key.public_key()?;
+    let mut k = secret_key.to_scalar();
+    let mut r = proof_nonce.to_scalar();
+
+    let evaluated = blinded.mul(&k);
+    let (m, z) = compute_composites(Some(&k), public_key.as_ref(), &blinded, &evaluated)?;
+    let t2 = Point::mul_base(&r);
+    let t3 = m.mul(&r);
+    let c = challenge(public_key.as_ref(), &[&m, &z, &t2, &t3])?;
+    let s = r.sub(&c.mul(&k));
+    k.zeroize();
+    r.zeroize();
+
+    let mut proof = [0u8; PROOFSIZE];
+    proof[..SCALARSIZE].copy_from_slice(&c.to_bytes());
+    proof[SCALARSIZE..].copy_from_slice(&s.to_bytes());
+
+    Ok((
+        EvaluatedElement::from_slice(&evaluated.to_compressed()?)?,
+        Proof::from_slice(&proof)?,
+    ))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify `proof` and unblind `evaluated_element`, writing the output of the
+/// function on `input` to `dst_out`, as the client.
+pub fn finalize(
+    input: &[u8],
+    blind: &Blind,
+    evaluated_element: &EvaluatedElement,
+    blinded_element: &BlindedElement,
+    public_key: &PublicKey,
+    proof: &Proof,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != OUTPUTSIZE || input.len() > u16::MAX as usize {
+        return Err(UnknownCryptoError);
+    }
+
+    let pk = Point::from_compressed(public_key.as_ref())?;
+    let blinded = Point::from_compressed(blinded_element.as_ref())?;
+    let evaluated = Point::from_compressed(evaluated_element.as_ref())?;
+    let (c, s) = proof.as_ref().split_at(SCALARSIZE);
+    let (c, c_is_canonical) = Scalar::from_bytes(&to_array(c));
+    let (s, s_is_canonical) = Scalar::from_bytes(&to_array(s));
+    if !bool::from(c_is_canonical & s_is_canonical) {
+        return Err(UnknownCryptoError);
+    }
+
+    let (m, z) = compute_composites(None, public_key.as_ref(), &blinded, &evaluated)?;
+    let t2 = Point::mul_base(&s).add(&pk.mul(&c));
+    let t3 = m.mul(&s).add(&z.mul(&c));
+    let expected = challenge(public_key.as_ref(), &[&m, &z, &t2, &t3])?;
+    if !bool::from(subtle::ConstantTimeEq::ct_eq(&expected, &c)) {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut blind_inv = blind.to_scalar().invert();
+    let unblinded = evaluated.mul(&blind_inv);
+    blind_inv.zeroize();
+
+    finalize_hash(input, &unblinded, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the output of the function on `input` directly with
+/// `secret_key`, writing it to `dst_out`, as the server.
+pub fn evaluate(
+    secret_key: &SecretKey,
+    input: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != OUTPUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut k = secret_key.to_scalar();
+    let issued = hash_to_group(input)?.mul(&k);
+    k.zeroize();
+
+    finalize_hash(input, &issued, dst_out)
+}
+
+/// Copy a scalar encoding of `SCALARSIZE` bytes into an array.
+fn to_array(bytes: &[u8]) -> [u8; SCALARSIZE] {
+    let mut array = [0u8; SCALARSIZE];
+    array.copy_from_slice(bytes);
+    array
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+
+    struct Exchange {
+        secret_key: SecretKey,
+        public_key: PublicKey,
+        blind: Blind,
+        blinded_element: BlindedElement,
+        evaluated_element: EvaluatedElement,
+        proof: Proof,
+    }
+
+    fn exchange(input: &[u8]) -> Exchange {
+        let secret_key = SecretKey::generate();
+        let public_key = secret_key.public_key().unwrap();
+        let blind = Blind::generate();
+        let blinded_element = super::blind(input, &blind).unwrap();
+        let (evaluated_element, proof) =
+            blind_evaluate(&secret_key, &blinded_element, &ProofNonce::generate()).unwrap();
+
+        Exchange {
+            secret_key,
+            public_key,
+            blind,
+            blinded_element,
+            evaluated_element,
+            proof,
+        }
+    }
+
+    fn finalize_exchange(
+        input: &[u8],
+        ex: &Exchange,
+    ) -> Result<[u8; OUTPUTSIZE], UnknownCryptoError> {
+        let mut output = [0u8; OUTPUTSIZE];
+        finalize(
+            input,
+            &ex.blind,
+            &ex.evaluated_element,
+            &ex.blinded_element,
+            &ex.public_key,
+            &ex.proof,
+            &mut output,
+        )?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_finalize_matches_evaluate() {
+        for input in [&b""[..], b"input", &[0xabu8; 300]].iter() {
+            let ex = exchange(input);
+            let output = finalize_exchange(input, &ex).unwrap();
+            let mut expected = [0u8; OUTPUTSIZE];
+            evaluate(&ex.secret_key, input, &mut expected).unwrap();
+            assert_eq!(output, expected);
+
+            // The output does not depend on the blind.
+            let again = exchange(input);
+            let mut other_key = [0u8; OUTPUTSIZE];
+            evaluate(&again.secret_key, input, &mut other_key).unwrap();
+            assert_ne!(output, other_key);
+        }
+    }
+
+    #[test]
+    fn test_reject_invalid_proof() {
+        let ex = exchange(b"input");
+
+        // A proof for a different public key.
+        let other = exchange(b"input");
+        let mut output = [0u8; OUTPUTSIZE];
+        assert!(finalize(
+            b"input",
+            &ex.blind,
+            &ex.evaluated_element,
+            &ex.blinded_element,
+            &other.public_key,
+            &ex.proof,
+            &mut output,
+        )
+        .is_err());
+
+        // An evaluation of a different blinded element.
+        assert!(finalize(
+            b"input",
+            &ex.blind,
+            &other.evaluated_element,
+            &ex.blinded_element,
+            &ex.public_key,
+            &other.proof,
+            &mut output,
+        )
+        .is_err());
+
+        // Modified proofs.
+        for i in [0, SCALARSIZE - 1, SCALARSIZE, PROOFSIZE - 1].iter() {
+            let mut proof = [0u8; PROOFSIZE];
+            proof.copy_from_slice(ex.proof.as_ref());
+            proof[*i] ^= 1;
+            assert!(finalize(
+                b"input",
+                &ex.blind,
+                &ex.evaluated_element,
+                &ex.blinded_element,
+                &ex.public_key,
+                &Proof::from_slice(&proof).unwrap(),
+                &mut output,
+            )
+            .is_err());
+        }
+        assert!(finalize_exchange(b"input", &ex).is_ok());
+    }
+
+    #[test]
+    fn test_reject_invalid_encodings() {
+        let ex = exchange(b"input");
+        let mut output = [0u8; OUTPUTSIZE];
+        let invalid = BlindedElement::from_slice(&[0u8; ELEMENTSIZE]).unwrap();
+        assert!(blind_evaluate(&ex.secret_key, &invalid, &ProofNonce::generate()).is_err());
+        assert!(finalize(
+            b"input",
+            &ex.blind,
+            &EvaluatedElement::from_slice(&[0u8; ELEMENTSIZE]).unwrap(),
+            &ex.blinded_element,
+            &ex.public_key,
+            &ex.proof,
+            &mut output,
+        )
+        .is_err());
+        assert!(finalize(
+            b"input",
+            &ex.blind,
+            &ex.evaluated_element,
+            &ex.blinded_element,
+            &PublicKey::from_slice(&[0u8; ELEMENTSIZE]).unwrap(),
+            &ex.proof,
+            &mut output,
+        )
+        .is_err());
+
+        // Scalars in the proof that are not less than the group order.
+        let mut proof = [0u8; PROOFSIZE];
+        proof.copy_from_slice(ex.proof.as_ref());
+        for byte in proof[..SCALARSIZE].iter_mut() {
+            *byte = 0xff;
+        }
+        let modified = Exchange {
+            proof: Proof::from_slice(&proof).unwrap(),
+            ..ex
+        };
+        assert!(finalize_exchange(b"input", &modified).is_err());
+    }
+
+    #[test]
+    fn test_lengths() {
+        let ex = exchange(b"input");
+        let mut output = [0u8; OUTPUTSIZE + 1];
+        assert!(finalize(
+            b"input",
+            &ex.blind,
+            &ex.evaluated_element,
+            &ex.blinded_element,
+            &ex.public_key,
+            &ex.proof,
+            &mut output,
+        )
+        .is_err());
+        assert!(evaluate(&ex.secret_key, b"input", &mut output).is_err());
+        assert!(evaluate(&ex.secret_key, b"input", &mut output[..OUTPUTSIZE]).is_ok());
+
+        let long_input = vec![0u8; u16::MAX as usize + 1];
+        assert!(blind(&long_input, &ex.blind).is_err());
+        assert!(evaluate(&ex.secret_key, &long_input, &mut output[..OUTPUTSIZE]).is_err());
+        assert!(blind(&long_input[1..], &ex.blind).is_ok());
+
+        assert!(SecretKey::derive(&[0u8; SEEDSIZE - 1], b"").is_err());
+        assert!(SecretKey::derive(&[0u8; SEEDSIZE], &long_input).is_err());
+        assert!(SecretKey::derive(&[0u8; SEEDSIZE], &long_input[1..]).is_ok());
+    }
+
+    #[test]
+    fn test_scalar_encodings() {
+        assert!(SecretKey::from_slice(&[0u8; SECRETSIZE]).is_err());
+        assert!(Blind::from_slice(&[0xffu8; SECRETSIZE]).is_err());
+        assert!(ProofNonce::from_slice(&[1u8; SECRETSIZE - 1]).is_err());
+
+        let secret_key = SecretKey::generate();
+        let copy = SecretKey::from_slice(secret_key.unprotected_as_bytes()).unwrap();
+        assert_eq!(secret_key, copy);
+        assert_eq!(copy.len(), SECRETSIZE);
+        assert_eq!(format!("{:?}", copy), "SecretKey {***OMITTED***}");
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    #[test]
+    fn test_generated_vector() {
+        // Generated with an independent implementation of RFC 9497, see
+        // tests/test_generation/generate_privacy_pass_test_vectors.py.
+        let secret_key = SecretKey::derive(&[0xa3u8; SEEDSIZE], b"test key").unwrap();
+        assert_eq!(
+            secret_key.unprotected_as_bytes(),
+            &hex::decode(
+                "4dfbea60da93d268ccc5d3a982afd2143ff2312ec1f104dac6553848d22d6e205bb40e90201b4aa9f4a009e2e4f29b43"
+            )
+            .unwrap()[..]
+        );
+        let public_key = secret_key.public_key().unwrap();
+        assert_eq!(
+            public_key.as_ref(),
+            &hex::decode(
+                "03e9554ee20eefd1de55650e6c6553b78d5a11c87013520bc6693d969fc91857040c093c977a5ac1211bf1e1d748ed3c95"
+            )
+            .unwrap()[..]
+        );
+
+        let blind = Blind::from_slice(
+            &hex::decode(
+                "504650f53df8f16f6861633388936ea23338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let blinded_element = super::blind(&[0x00], &blind).unwrap();
+        assert_eq!(
+            blinded_element.as_ref(),
+            &hex::decode(
+                "02d338c05cbecb82de13d6700f09cb61190543a7b7e2c6cd4fca56887e564ea82653b27fdad383995ea6d02cf26d0e24d9"
+            )
+            .unwrap()[..]
+        );
+
+        let proof_nonce = ProofNonce::from_slice(
+            &hex::decode(
+                "803d955f0e073a04aa5d92b3fb739f56f9db001266677f62c095021db018cd8cbb55941d4073698ce45c405d1348b7b1",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let (evaluated_element, proof) =
+            blind_evaluate(&secret_key, &blinded_element, &proof_nonce).unwrap();
+        assert_eq!(
+            evaluated_element.as_ref(),
+            &hex::decode(
+                "03f830b06f5b19411c64a9643e1cd6df56539c01717d17c2c7033f23b4e4322b9c42bef9689e27fc6224718311b51c62f5"
+            )
+            .unwrap()[..]
+        );
+        assert_eq!(
+            proof.as_ref(),
+            &hex::decode(
+                "9251482316ae7e79e2f7e206bae63cd6ab3baa1b5aed68aeacc972df18b51858587d24f5b4f77780cdeb5daa6390a37d\
+                 df34eb431004e98a945b77b91fa1abadac1c7690422985ee4f8524f6d2808a12856b51136918c9af6bdea24edfc6946e"
+            )
+            .unwrap()[..]
+        );
+
+        let expected =
+            hex::decode("59cf6bdab1e634f88657f6e01af705c33275d31a5c00d0046ce6c3bdd46bf101996680b6c3ab2e00c9e693c36719b5ed")
+                .unwrap();
+        let mut output = [0u8; OUTPUTSIZE];
+        finalize(
+            &[0x00],
+            &blind,
+            &evaluated_element,
+            &blinded_element,
+            &public_key,
+            &proof,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(&output[..], &expected[..]);
+        evaluate(&secret_key, &[0x00], &mut output).unwrap();
+        assert_eq!(&output[..], &expected[..]);
+    }
+}
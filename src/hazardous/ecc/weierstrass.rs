@@ -0,0 +1,231 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic on short Weierstrass curves, y^2 = x^3 + ax + b, in homogeneous
+//! projective coordinates (X : Y : Z).
+//!
+//! Points are added with the complete formulas of [Renes, Costello and Batina](https://eprint.iacr.org/2015/1060),
+//! which have no exceptional cases and run in constant time.
+
+/// Macro that implements the group operations on a `Point` struct with
+/// projective `x`, `y` and `z` coordinates of type `FieldElement`, for a curve
+/// with a = -3 or a = 0. The invoking module must also define `Scalar`, the
+/// basepoint coordinates `GENERATOR_X` and `GENERATOR_Y`, and `curve_b()`.
+macro_rules! impl_point_arithmetic {
+    (a = -3) => {
+        impl_point_arithmetic!(@impl
+            /// self + rhs, using algorithm 4 of Renes, Costello and Batina for
+            /// curves with a = -3. This also holds when self = rhs, and when
+            /// either is the point at infinity.
+            fn add(&self, rhs: &Self) -> Self {
+                let b = curve_b();
+
+                let mut t0 = self.x.mul(&rhs.x);
+                let mut t1 = self.y.mul(&rhs.y);
+                let mut t2 = self.z.mul(&rhs.z);
+                let mut t3 = self.x.add(&self.y);
+                let mut t4 = rhs.x.add(&rhs.y);
+                t3 = t3.mul(&t4);
+                t4 = t0.add(&t1);
+                t3 = t3.sub(&t4);
+                t4 = self.y.add(&self.z);
+                let mut x3 = rhs.y.add(&rhs.z);
+                t4 = t4.mul(&x3);
+                x3 = t1.add(&t2);
+                t4 = t4.sub(&x3);
+                x3 = self.x.add(&self.z);
+                let mut y3 = rhs.x.add(&rhs.z);
+                x3 = x3.mul(&y3);
+                y3 = t0.add(&t2);
+                y3 = x3.sub(&y3);
+                let mut z3 = b.mul(&t2);
+                x3 = y3.sub(&z3);
+                z3 = x3.add(&x3);
+                x3 = x3.add(&z3);
+                z3 = t1.sub(&x3);
+                x3 = t1.add(&x3);
+                y3 = b.mul(&y3);
+                t1 = t2.add(&t2);
+                t2 = t1.add(&t2);
+                y3 = y3.sub(&t2);
+                y3 = y3.sub(&t0);
+                t1 = y3.add(&y3);
+                y3 = t1.add(&y3);
+                t1 = t0.add(&t0);
+                t0 = t1.add(&t0);
+                t0 = t0.sub(&t2);
+                t1 = t4.mul(&y3);
+                t2 = t0.mul(&y3);
+                y3 = x3.mul(&z3);
+                y3 = y3.add(&t2);
+                x3 = x3.mul(&t3);
+                x3 = x3.sub(&t1);
+                z3 = z3.mul(&t4);
+                t1 = t3.mul(&t0);
+                z3 = z3.add(&t1);
+
+                Self {
+                    x: x3,
+                    y: y3,
+                    z: z3,
+                }
+            }
+        );
+    };
+    (a = 0) => {
+        impl_point_arithmetic!(@impl
+            /// self + rhs, using algorithm 7 of Renes, Costello and Batina for
+            /// curves with a = 0. This also holds when self = rhs, and when
+            /// either is the point at infinity.
+            fn add(&self, rhs: &Self) -> Self {
+                let b = curve_b();
+                let b3 = b.add(&b).add(&b);
+
+                let mut t0 = self.x.mul(&rhs.x);
+                let mut t1 = self.y.mul(&rhs.y);
+                let mut t2 = self.z.mul(&rhs.z);
+                let mut t3 = self.x.add(&self.y);
+                let mut t4 = rhs.x.add(&rhs.y);
+                t3 = t3.mul(&t4);
+                t4 = t0.add(&t1);
+                t3 = t3.sub(&t4);
+                t4 = self.y.add(&self.z);
+                let mut x3 = rhs.y.add(&rhs.z);
+                t4 = t4.mul(&x3);
+                x3 = t1.add(&t2);
+                t4 = t4.sub(&x3);
+                x3 = self.x.add(&self.z);
+                let mut y3 = rhs.x.add(&rhs.z);
+                x3 = x3.mul(&y3);
+                y3 = t0.add(&t2);
+                y3 = x3.sub(&y3);
+                x3 = t0.add(&t0);
+                t0 = x3.add(&t0);
+                t2 = b3.mul(&t2);
+                let mut z3 = t1.add(&t2);
+                t1 = t1.sub(&t2);
+                y3 = b3.mul(&y3);
+                x3 = t4.mul(&y3);
+                t2 = t3.mul(&t1);
+                x3 = t2.sub(&x3);
+                y3 = y3.mul(&t0);
+                t1 = t1.mul(&z3);
+                y3 = t1.add(&y3);
+                t0 = t0.mul(&t3);
+                z3 = z3.mul(&t4);
+                z3 = z3.add(&t0);
+
+                Self {
+                    x: x3,
+                    y: y3,
+                    z: z3,
+                }
+            }
+        );
+    };
+    (@impl $(#[$add_doc:meta])* fn add $($add:tt)*) => {
+        impl subtle::ConditionallySelectable for Point {
+            fn conditional_select(a: &Self, b: &Self, choice: subtle::Choice) -> Self {
+                Self {
+                    x: FieldElement::conditional_select(&a.x, &b.x, choice),
+                    y: FieldElement::conditional_select(&a.y, &b.y, choice),
+                    z: FieldElement::conditional_select(&a.z, &b.z, choice),
+                }
+            }
+        }
+
+        impl Point {
+            /// The point at infinity.
+            pub(crate) fn identity() -> Self {
+                Self {
+                    x: FieldElement::ZERO,
+                    y: FieldElement::one(),
+                    z: FieldElement::ZERO,
+                }
+            }
+
+            /// The basepoint G.
+            pub(crate) fn generator() -> Self {
+                Self {
+                    x: FieldElement::from_bytes(&GENERATOR_X).0,
+                    y: FieldElement::from_bytes(&GENERATOR_Y).0,
+                    z: FieldElement::one(),
+                }
+            }
+
+            /// Whether this is the point at infinity.
+            pub(crate) fn is_identity(&self) -> bool {
+                bool::from(self.z.is_zero())
+            }
+
+            #[allow(dead_code)] // Not every curve needs this outside of tests.
+            /// -self.
+            pub(crate) fn neg(&self) -> Self {
+                Self {
+                    x: self.x,
+                    y: self.y.neg(),
+                    z: self.z,
+                }
+            }
+
+            $(#[$add_doc])*
+            pub(crate) fn add $($add)*
+
+            /// [scalar]self, in constant time.
+            pub(crate) fn mul(&self, scalar: &Scalar) -> Self {
+                use subtle::{Choice, ConditionallySelectable};
+                use zeroize::Zeroize;
+
+                let mut bytes = scalar.to_bytes();
+                let mut result = Self::identity();
+                for byte in bytes.iter() {
+                    for bit in (0..8).rev() {
+                        result = result.add(&result);
+                        let sum = result.add(self);
+                        result =
+                            Self::conditional_select(&result, &sum, Choice::from((byte >> bit) & 1));
+                    }
+                }
+                bytes.zeroize();
+
+                result
+            }
+
+            /// [scalar]G, in constant time.
+            pub(crate) fn mul_base(scalar: &Scalar) -> Self {
+                Self::generator().mul(scalar)
+            }
+
+            #[allow(dead_code)] // Not every curve needs this outside of tests.
+            /// Whether self and rhs are the same point.
+            pub(crate) fn equals(&self, rhs: &Self) -> bool {
+                use subtle::ConstantTimeEq;
+
+                // X1/Z1 = X2/Z2 and Y1/Z1 = Y2/Z2, which also holds for two
+                // points at infinity, whose x-coordinates are both zero.
+                let x_eq = self.x.mul(&rhs.z).ct_eq(&rhs.x.mul(&self.z));
+                let y_eq = self.y.mul(&rhs.z).ct_eq(&rhs.y.mul(&self.z));
+                bool::from(x_eq & y_eq & (self.z.is_zero().ct_eq(&rhs.z.is_zero())))
+            }
+        }
+    };
+}
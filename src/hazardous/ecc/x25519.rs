@@ -0,0 +1,359 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Diffie-Hellman key agreement using X25519.
+//!
+//! # About:
+//! This implements X25519 key agreement as specified in
+//! [RFC 7748](https://tools.ietf.org/html/rfc7748). Two parties each generate
+//! a [`PrivateKey`], exchange the matching [`PublicKey`]s and call
+//! [`key_agreement()`] with their own private key and the public key of the
+//! other party, which gives both the same [`SharedSecret`].
+//!
+//! The private key is clamped as specified in RFC 7748 when it is used, so any
+//! 32 bytes are a valid [`PrivateKey`].
+//!
+//! # Parameters:
+//! - `private_key`: The private key of this party.
+//! - `public_key`: The public key of the other party.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The shared secret is the all-zero value, which happens when `public_key`
+//!   is a point of small order.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - The [`SharedSecret`] is not uniformly random and must not be used as a key
+//!   directly. Feed it through a KDF, such as [`hkdf`], together with both
+//!   public keys, to derive the keys that are actually used.
+//! - A [`PrivateKey`] that is used for a single key agreement, an ephemeral
+//!   key, must be generated anew each time and never be re-used.
+//! - [`key_agreement()`] accepts any 32 bytes as a public key, including
+//!   non-canonical encodings and points on the twist of Curve25519, as RFC 7748
+//!   requires. Use [`curve25519::validate_point()`] where the protocol requires
+//!   stricter checks.
+//! - The key agreement runs in constant time with respect to the private key.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::x25519::{self, PrivateKey, PublicKey};
+//! use orion::hazardous::kdf::hkdf;
+//! use core::convert::TryFrom;
+//!
+//! let alice_private = PrivateKey::generate();
+//! let alice_public = PublicKey::try_from(&alice_private)?;
+//! let bob_private = PrivateKey::generate();
+//! let bob_public = PublicKey::try_from(&bob_private)?;
+//!
+//! let alice_shared = x25519::key_agreement(&alice_private, &bob_public)?;
+//! let bob_shared = x25519::key_agreement(&bob_private, &alice_public)?;
+//! assert_eq!(alice_shared, bob_shared);
+//!
+//! // Derive the key to use from the shared secret and both public keys.
+//! let mut info = alice_public.to_bytes().to_vec();
+//! info.extend_from_slice(&bob_public.to_bytes());
+//! let mut key = [0u8; 32];
+//! hkdf::derive_key(&[], alice_shared.unprotected_as_bytes(), Some(&info), &mut key)?;
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`PrivateKey`]: struct.PrivateKey.html
+//! [`PublicKey`]: struct.PublicKey.html
+//! [`SharedSecret`]: struct.SharedSecret.html
+//! [`key_agreement()`]: fn.key_agreement.html
+//! [`hkdf`]: ../../kdf/hkdf/index.html
+//! [`curve25519::validate_point()`]: ../curve25519/fn.validate_point.html
+
+use super::curve25519;
+use crate::errors::UnknownCryptoError;
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of a private key.
+pub const PRIVATE_KEY_SIZE: usize = curve25519::X25519_KEYSIZE;
+
+/// The size of a public key.
+pub const PUBLIC_KEY_SIZE: usize = curve25519::X25519_KEYSIZE;
+
+/// The size of a shared secret.
+pub const SHARED_SECRET_SIZE: usize = curve25519::X25519_KEYSIZE;
+
+construct_secret_key! {
+    /// A type to represent the `PrivateKey` of an X25519 key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (PrivateKey, test_private_key, PRIVATE_KEY_SIZE, PRIVATE_KEY_SIZE, PRIVATE_KEY_SIZE)
+}
+
+impl_from_trait!(PrivateKey, PRIVATE_KEY_SIZE);
+
+construct_public! {
+    /// A type to represent the `PublicKey` of an X25519 key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (PublicKey, test_public_key, PUBLIC_KEY_SIZE, PUBLIC_KEY_SIZE)
+}
+
+impl_from_trait!(PublicKey, PUBLIC_KEY_SIZE);
+
+impl PublicKey {
+    /// Return the public key as a byte array.
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        let mut bytes = [0u8; PUBLIC_KEY_SIZE];
+        bytes.copy_from_slice(self.as_ref());
+        bytes
+    }
+}
+
+impl TryFrom<&PrivateKey> for PublicKey {
+    type Error = UnknownCryptoError;
+
+    fn try_from(private_key: &PrivateKey) -> Result<Self, Self::Error> {
+        let mut scalar = clamped(private_key);
+        let mut public = [0u8; PUBLIC_KEY_SIZE];
+        let result = curve25519::scalarmult_base(&scalar, &mut public);
+        scalar.zeroize();
+        result?;
+
+        Self::from_slice(&public)
+    }
+}
+
+construct_secret_key! {
+    /// A type to represent the `SharedSecret` that [`key_agreement()`] returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    ///
+    /// [`key_agreement()`]: fn.key_agreement.html
+    (SharedSecret, test_shared_secret, SHARED_SECRET_SIZE, SHARED_SECRET_SIZE, SHARED_SECRET_SIZE)
+}
+
+/// The clamped scalar of `private_key`.
+fn clamped(private_key: &PrivateKey) -> [u8; PRIVATE_KEY_SIZE] {
+    let mut scalar = [0u8; PRIVATE_KEY_SIZE];
+    scalar.copy_from_slice(private_key.unprotected_as_bytes());
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+
+    scalar
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the shared secret of `private_key` and `public_key`.
+pub fn key_agreement(
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+) -> Result<SharedSecret, UnknownCryptoError> {
+    let mut scalar = clamped(private_key);
+    let mut shared = [0u8; SHARED_SECRET_SIZE];
+    let result = curve25519::scalarmult(&scalar, public_key.as_ref(), &mut shared)
+        .and_then(|_| SharedSecret::from_slice(&shared));
+    scalar.zeroize();
+    shared.zeroize();
+
+    result
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn decode(value: &str) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (idx, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * idx..2 * idx + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_rfc7748_scalarmult() {
+        // RFC 7748, section 5.2.
+        let vectors = [
+            (
+                "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+                "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+                "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+            ),
+            (
+                "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+                "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+                "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+            ),
+        ];
+
+        for (scalar, u, expected) in vectors.iter() {
+            let private_key = PrivateKey::from_slice(&decode(scalar)).unwrap();
+            let public_key = PublicKey::from_slice(&decode(u)).unwrap();
+            let shared = key_agreement(&private_key, &public_key).unwrap();
+            assert_eq!(shared, &decode(expected)[..]);
+        }
+    }
+
+    #[test]
+    fn test_rfc7748_iterated() {
+        // RFC 7748, section 5.2, after 1 and 1,000 iterations.
+        let mut k = decode("0900000000000000000000000000000000000000000000000000000000000000");
+        let mut u = k;
+
+        for iteration in 1..=1000 {
+            let private_key = PrivateKey::from_slice(&k).unwrap();
+            let public_key = PublicKey::from_slice(&u).unwrap();
+            let result = key_agreement(&private_key, &public_key).unwrap();
+            u = k;
+            k.copy_from_slice(result.unprotected_as_bytes());
+
+            if iteration == 1 {
+                assert_eq!(
+                    k,
+                    decode("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+                );
+            }
+        }
+        assert_eq!(
+            k,
+            decode("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+        );
+    }
+
+    #[test]
+    fn test_rfc7748_key_agreement() {
+        // RFC 7748, section 6.1.
+        let alice_private = PrivateKey::from_slice(&decode(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ))
+        .unwrap();
+        let alice_public =
+            decode("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+        let bob_private = PrivateKey::from_slice(&decode(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ))
+        .unwrap();
+        let bob_public = decode("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+        let shared = decode("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+
+        assert_eq!(
+            PublicKey::try_from(&alice_private).unwrap(),
+            PublicKey::from(alice_public)
+        );
+        assert_eq!(
+            PublicKey::try_from(&bob_private).unwrap(),
+            PublicKey::from(bob_public)
+        );
+        assert_eq!(
+            key_agreement(&alice_private, &PublicKey::from(bob_public)).unwrap(),
+            &shared[..]
+        );
+        assert_eq!(
+            key_agreement(&bob_private, &PublicKey::from(alice_public)).unwrap(),
+            &shared[..]
+        );
+    }
+
+    #[test]
+    fn test_small_order_err() {
+        let private_key = PrivateKey::from([0x42; PRIVATE_KEY_SIZE]);
+        let small_order = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+            "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ];
+
+        for point in small_order.iter() {
+            let public_key = PublicKey::from(decode(point));
+            assert!(key_agreement(&private_key, &public_key).is_err());
+        }
+    }
+
+    #[test]
+    fn test_private_key_is_clamped() {
+        let mut unclamped = [0x42; PRIVATE_KEY_SIZE];
+        unclamped[0] |= 7;
+        unclamped[31] |= 128;
+        let mut clamped_bytes = unclamped;
+        curve25519::clamp_scalar(&mut clamped_bytes).unwrap();
+
+        let public_key = PublicKey::from([9u8; PUBLIC_KEY_SIZE]);
+        assert_eq!(
+            PublicKey::try_from(&PrivateKey::from(unclamped)).unwrap(),
+            PublicKey::try_from(&PrivateKey::from(clamped_bytes)).unwrap()
+        );
+        assert_eq!(
+            key_agreement(&PrivateKey::from(unclamped), &public_key).unwrap(),
+            key_agreement(&PrivateKey::from(clamped_bytes), &public_key).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_high_bit_of_public_key_ignored() {
+        let private_key = PrivateKey::from([0x42; PRIVATE_KEY_SIZE]);
+        let public_key = PublicKey::try_from(&PrivateKey::from([0x24; PRIVATE_KEY_SIZE])).unwrap();
+        let mut high_bit = public_key.to_bytes();
+        high_bit[31] |= 128;
+
+        assert_eq!(
+            key_agreement(&private_key, &public_key).unwrap(),
+            key_agreement(&private_key, &PublicKey::from(high_bit)).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_key_agreement_generated() {
+        let alice_private = PrivateKey::generate();
+        let bob_private = PrivateKey::generate();
+        let alice_public = PublicKey::try_from(&alice_private).unwrap();
+        let bob_public = PublicKey::try_from(&bob_private).unwrap();
+
+        assert_eq!(
+            key_agreement(&alice_private, &bob_public).unwrap(),
+            key_agreement(&bob_private, &alice_public).unwrap()
+        );
+        assert_ne!(
+            key_agreement(&alice_private, &bob_public).unwrap(),
+            key_agreement(&alice_private, &alice_public).unwrap()
+        );
+    }
+}
@@ -0,0 +1,438 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Finite-field Diffie-Hellman key agreement, with the RFC 7919 groups.
+//!
+//! # About:
+//! This implements Diffie-Hellman over the named safe-prime groups
+//! ffdhe2048, ffdhe3072 and ffdhe4096 from [RFC 7919], with the generator 2.
+//!
+//! The secret key is a 64-byte big-endian exponent, which exceeds the
+//! exponent sizes recommended by RFC 7919 for all three groups. Public keys
+//! and shared secrets are big-endian integers, left-padded with zeros to the
+//! size of the prime, as in TLS 1.3.
+//!
+//! # Parameters:
+//! - `group`: The group to use.
+//! - `secret_key`: The secret exponent.
+//! - `peer_public_key`: The public key of the other party.
+//! - `dst_out`: Destination buffer for the public key or the shared secret.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is less than 2.
+//! - `peer_public_key` is not [`Group::size()`] bytes, is not between 2 and
+//!   p - 2, or is not in the subgroup of order (p - 1) / 2.
+//! - `dst_out` is not [`Group::size()`] bytes.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely when calling [`SecretKey::generate()`].
+//!
+//! # Security:
+//! - The secret key must be generated with [`SecretKey::generate()`], or
+//!   otherwise with a CSPRNG, and should only be used for a single key
+//!   agreement.
+//! - The exponentiation with the secret key runs in constant time. The
+//!   validation of the peer public key only uses public values.
+//! - The shared secret is not uniformly random and should be passed to a KDF,
+//!   such as [`hkdf`], before it is used as a key.
+//! - TLS 1.2 removes leading zero bytes from the shared secret, which must be
+//!   done by the caller where needed.
+//! - Finite-field Diffie-Hellman is much slower than, and offers no security
+//!   benefit over, [`curve25519`]. It should only be used where a protocol
+//!   requires it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ffdhe::{self, Group, SecretKey};
+//!
+//! let group = Group::Ffdhe2048;
+//! let alice_sk = SecretKey::generate();
+//! let bob_sk = SecretKey::generate();
+//!
+//! let mut alice_pk = vec![0u8; group.size()];
+//! let mut bob_pk = vec![0u8; group.size()];
+//! ffdhe::public_key(group, &alice_sk, &mut alice_pk)?;
+//! ffdhe::public_key(group, &bob_sk, &mut bob_pk)?;
+//!
+//! let mut alice_shared = vec![0u8; group.size()];
+//! let mut bob_shared = vec![0u8; group.size()];
+//! ffdhe::shared_secret(group, &alice_sk, &bob_pk, &mut alice_shared)?;
+//! ffdhe::shared_secret(group, &bob_sk, &alice_pk, &mut bob_shared)?;
+//! assert_eq!(alice_shared, bob_shared);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 7919]: https://www.rfc-editor.org/rfc/rfc7919
+//! [`Group::size()`]: enum.Group.html#method.size
+//! [`SecretKey::generate()`]: struct.SecretKey.html#method.generate
+//! [`hkdf`]: ../kdf/hkdf/index.html
+//! [`curve25519`]: ../ecc/curve25519/index.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::rsa::bigint::{self, Modulus};
+use zeroize::Zeroize;
+
+/// The size of a secret key.
+pub const SECRET_KEYSIZE: usize = 64;
+
+/// The ffdhe2048 prime, from RFC 7919, Appendix A.
+const FFDHE2048_P: [u8; 256] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xad, 0xf8, 0x54, 0x58, 0xa2, 0xbb, 0x4a, 0x9a,
+    0xaf, 0xdc, 0x56, 0x20, 0x27, 0x3d, 0x3c, 0xf1, 0xd8, 0xb9, 0xc5, 0x83, 0xce, 0x2d, 0x36, 0x95,
+    0xa9, 0xe1, 0x36, 0x41, 0x14, 0x64, 0x33, 0xfb, 0xcc, 0x93, 0x9d, 0xce, 0x24, 0x9b, 0x3e, 0xf9,
+    0x7d, 0x2f, 0xe3, 0x63, 0x63, 0x0c, 0x75, 0xd8, 0xf6, 0x81, 0xb2, 0x02, 0xae, 0xc4, 0x61, 0x7a,
+    0xd3, 0xdf, 0x1e, 0xd5, 0xd5, 0xfd, 0x65, 0x61, 0x24, 0x33, 0xf5, 0x1f, 0x5f, 0x06, 0x6e, 0xd0,
+    0x85, 0x63, 0x65, 0x55, 0x3d, 0xed, 0x1a, 0xf3, 0xb5, 0x57, 0x13, 0x5e, 0x7f, 0x57, 0xc9, 0x35,
+    0x98, 0x4f, 0x0c, 0x70, 0xe0, 0xe6, 0x8b, 0x77, 0xe2, 0xa6, 0x89, 0xda, 0xf3, 0xef, 0xe8, 0x72,
+    0x1d, 0xf1, 0x58, 0xa1, 0x36, 0xad, 0xe7, 0x35, 0x30, 0xac, 0xca, 0x4f, 0x48, 0x3a, 0x79, 0x7a,
+    0xbc, 0x0a, 0xb1, 0x82, 0xb3, 0x24, 0xfb, 0x61, 0xd1, 0x08, 0xa9, 0x4b, 0xb2, 0xc8, 0xe3, 0xfb,
+    0xb9, 0x6a, 0xda, 0xb7, 0x60, 0xd7, 0xf4, 0x68, 0x1d, 0x4f, 0x42, 0xa3, 0xde, 0x39, 0x4d, 0xf4,
+    0xae, 0x56, 0xed, 0xe7, 0x63, 0x72, 0xbb, 0x19, 0x0b, 0x07, 0xa7, 0xc8, 0xee, 0x0a, 0x6d, 0x70,
+    0x9e, 0x02, 0xfc, 0xe1, 0xcd, 0xf7, 0xe2, 0xec, 0xc0, 0x34, 0x04, 0xcd, 0x28, 0x34, 0x2f, 0x61,
+    0x91, 0x72, 0xfe, 0x9c, 0xe9, 0x85, 0x83, 0xff, 0x8e, 0x4f, 0x12, 0x32, 0xee, 0xf2, 0x81, 0x83,
+    0xc3, 0xfe, 0x3b, 0x1b, 0x4c, 0x6f, 0xad, 0x73, 0x3b, 0xb5, 0xfc, 0xbc, 0x2e, 0xc2, 0x20, 0x05,
+    0xc5, 0x8e, 0xf1, 0x83, 0x7d, 0x16, 0x83, 0xb2, 0xc6, 0xf3, 0x4a, 0x26, 0xc1, 0xb2, 0xef, 0xfa,
+    0x88, 0x6b, 0x42, 0x38, 0x61, 0x28, 0x5c, 0x97, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// The ffdhe3072 prime, from RFC 7919, Appendix A.
+const FFDHE3072_P: [u8; 384] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xad, 0xf8, 0x54, 0x58, 0xa2, 0xbb, 0x4a, 0x9a,
+    0xaf, 0xdc, 0x56, 0x20, 0x27, 0x3d, 0x3c, 0xf1, 0xd8, 0xb9, 0xc5, 0x83, 0xce, 0x2d, 0x36, 0x95,
+    0xa9, 0xe1, 0x36, 0x41, 0x14, 0x64, 0x33, 0xfb, 0xcc, 0x93, 0x9d, 0xce, 0x24, 0x9b, 0x3e, 0xf9,
+    0x7d, 0x2f, 0xe3, 0x63, 0x63, 0x0c, 0x75, 0xd8, 0xf6, 0x81, 0xb2, 0x02, 0xae, 0xc4, 0x61, 0x7a,
+    0xd3, 0xdf, 0x1e, 0xd5, 0xd5, 0xfd, 0x65, 0x61, 0x24, 0x33, 0xf5, 0x1f, 0x5f, 0x06, 0x6e, 0xd0,
+    0x85, 0x63, 0x65, 0x55, 0x3d, 0xed, 0x1a, 0xf3, 0xb5, 0x57, 0x13, 0x5e, 0x7f, 0x57, 0xc9, 0x35,
+    0x98, 0x4f, 0x0c, 0x70, 0xe0, 0xe6, 0x8b, 0x77, 0xe2, 0xa6, 0x89, 0xda, 0xf3, 0xef, 0xe8, 0x72,
+    0x1d, 0xf1, 0x58, 0xa1, 0x36, 0xad, 0xe7, 0x35, 0x30, 0xac, 0xca, 0x4f, 0x48, 0x3a, 0x79, 0x7a,
+    0xbc, 0x0a, 0xb1, 0x82, 0xb3, 0x24, 0xfb, 0x61, 0xd1, 0x08, 0xa9, 0x4b, 0xb2, 0xc8, 0xe3, 0xfb,
+    0xb9, 0x6a, 0xda, 0xb7, 0x60, 0xd7, 0xf4, 0x68, 0x1d, 0x4f, 0x42, 0xa3, 0xde, 0x39, 0x4d, 0xf4,
+    0xae, 0x56, 0xed, 0xe7, 0x63, 0x72, 0xbb, 0x19, 0x0b, 0x07, 0xa7, 0xc8, 0xee, 0x0a, 0x6d, 0x70,
+    0x9e, 0x02, 0xfc, 0xe1, 0xcd, 0xf7, 0xe2, 0xec, 0xc0, 0x34, 0x04, 0xcd, 0x28, 0x34, 0x2f, 0x61,
+    0x91, 0x72, 0xfe, 0x9c, 0xe9, 0x85, 0x83, 0xff, 0x8e, 0x4f, 0x12, 0x32, 0xee, 0xf2, 0x81, 0x83,
+    0xc3, 0xfe, 0x3b, 0x1b, 0x4c, 0x6f, 0xad, 0x73, 0x3b, 0xb5, 0xfc, 0xbc, 0x2e, 0xc2, 0x20, 0x05,
+    0xc5, 0x8e, 0xf1, 0x83, 0x7d, 0x16, 0x83, 0xb2, 0xc6, 0xf3, 0x4a, 0x26, 0xc1, 0xb2, 0xef, 0xfa,
+    0x88, 0x6b, 0x42, 0x38, 0x61, 0x1f, 0xcf, 0xdc, 0xde, 0x35, 0x5b, 0x3b, 0x65, 0x19, 0x03, 0x5b,
+    0xbc, 0x34, 0xf4, 0xde, 0xf9, 0x9c, 0x02, 0x38, 0x61, 0xb4, 0x6f, 0xc9, 0xd6, 0xe6, 0xc9, 0x07,
+    0x7a, 0xd9, 0x1d, 0x26, 0x91, 0xf7, 0xf7, 0xee, 0x59, 0x8c, 0xb0, 0xfa, 0xc1, 0x86, 0xd9, 0x1c,
+    0xae, 0xfe, 0x13, 0x09, 0x85, 0x13, 0x92, 0x70, 0xb4, 0x13, 0x0c, 0x93, 0xbc, 0x43, 0x79, 0x44,
+    0xf4, 0xfd, 0x44, 0x52, 0xe2, 0xd7, 0x4d, 0xd3, 0x64, 0xf2, 0xe2, 0x1e, 0x71, 0xf5, 0x4b, 0xff,
+    0x5c, 0xae, 0x82, 0xab, 0x9c, 0x9d, 0xf6, 0x9e, 0xe8, 0x6d, 0x2b, 0xc5, 0x22, 0x36, 0x3a, 0x0d,
+    0xab, 0xc5, 0x21, 0x97, 0x9b, 0x0d, 0xea, 0xda, 0x1d, 0xbf, 0x9a, 0x42, 0xd5, 0xc4, 0x48, 0x4e,
+    0x0a, 0xbc, 0xd0, 0x6b, 0xfa, 0x53, 0xdd, 0xef, 0x3c, 0x1b, 0x20, 0xee, 0x3f, 0xd5, 0x9d, 0x7c,
+    0x25, 0xe4, 0x1d, 0x2b, 0x66, 0xc6, 0x2e, 0x37, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+/// The ffdhe4096 prime, from RFC 7919, Appendix A.
+const FFDHE4096_P: [u8; 512] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xad, 0xf8, 0x54, 0x58, 0xa2, 0xbb, 0x4a, 0x9a,
+    0xaf, 0xdc, 0x56, 0x20, 0x27, 0x3d, 0x3c, 0xf1, 0xd8, 0xb9, 0xc5, 0x83, 0xce, 0x2d, 0x36, 0x95,
+    0xa9, 0xe1, 0x36, 0x41, 0x14, 0x64, 0x33, 0xfb, 0xcc, 0x93, 0x9d, 0xce, 0x24, 0x9b, 0x3e, 0xf9,
+    0x7d, 0x2f, 0xe3, 0x63, 0x63, 0x0c, 0x75, 0xd8, 0xf6, 0x81, 0xb2, 0x02, 0xae, 0xc4, 0x61, 0x7a,
+    0xd3, 0xdf, 0x1e, 0xd5, 0xd5, 0xfd, 0x65, 0x61, 0x24, 0x33, 0xf5, 0x1f, 0x5f, 0x06, 0x6e, 0xd0,
+    0x85, 0x63, 0x65, 0x55, 0x3d, 0xed, 0x1a, 0xf3, 0xb5, 0x57, 0x13, 0x5e, 0x7f, 0x57, 0xc9, 0x35,
+    0x98, 0x4f, 0x0c, 0x70, 0xe0, 0xe6, 0x8b, 0x77, 0xe2, 0xa6, 0x89, 0xda, 0xf3, 0xef, 0xe8, 0x72,
+    0x1d, 0xf1, 0x58, 0xa1, 0x36, 0xad, 0xe7, 0x35, 0x30, 0xac, 0xca, 0x4f, 0x48, 0x3a, 0x79, 0x7a,
+    0xbc, 0x0a, 0xb1, 0x82, 0xb3, 0x24, 0xfb, 0x61, 0xd1, 0x08, 0xa9, 0x4b, 0xb2, 0xc8, 0xe3, 0xfb,
+    0xb9, 0x6a, 0xda, 0xb7, 0x60, 0xd7, 0xf4, 0x68, 0x1d, 0x4f, 0x42, 0xa3, 0xde, 0x39, 0x4d, 0xf4,
+    0xae, 0x56, 0xed, 0xe7, 0x63, 0x72, 0xbb, 0x19, 0x0b, 0x07, 0xa7, 0xc8, 0xee, 0x0a, 0x6d, 0x70,
+    0x9e, 0x02, 0xfc, 0xe1, 0xcd, 0xf7, 0xe2, 0xec, 0xc0, 0x34, 0x04, 0xcd, 0x28, 0x34, 0x2f, 0x61,
+    0x91, 0x72, 0xfe, 0x9c, 0xe9, 0x85, 0x83, 0xff, 0x8e, 0x4f, 0x12, 0x32, 0xee, 0xf2, 0x81, 0x83,
+    0xc3, 0xfe, 0x3b, 0x1b, 0x4c, 0x6f, 0xad, 0x73, 0x3b, 0xb5, 0xfc, 0xbc, 0x2e, 0xc2, 0x20, 0x05,
+    0xc5, 0x8e, 0xf1, 0x83, 0x7d, 0x16, 0x83, 0xb2, 0xc6, 0xf3, 0x4a, 0x26, 0xc1, 0xb2, 0xef, 0xfa,
+    0x88, 0x6b, 0x42, 0x38, 0x61, 0x1f, 0xcf, 0xdc, 0xde, 0x35, 0x5b, 0x3b, 0x65, 0x19, 0x03, 0x5b,
+    0xbc, 0x34, 0xf4, 0xde, 0xf9, 0x9c, 0x02, 0x38, 0x61, 0xb4, 0x6f, 0xc9, 0xd6, 0xe6, 0xc9, 0x07,
+    0x7a, 0xd9, 0x1d, 0x26, 0x91, 0xf7, 0xf7, 0xee, 0x59, 0x8c, 0xb0, 0xfa, 0xc1, 0x86, 0xd9, 0x1c,
+    0xae, 0xfe, 0x13, 0x09, 0x85, 0x13, 0x92, 0x70, 0xb4, 0x13, 0x0c, 0x93, 0xbc, 0x43, 0x79, 0x44,
+    0xf4, 0xfd, 0x44, 0x52, 0xe2, 0xd7, 0x4d, 0xd3, 0x64, 0xf2, 0xe2, 0x1e, 0x71, 0xf5, 0x4b, 0xff,
+    0x5c, 0xae, 0x82, 0xab, 0x9c, 0x9d, 0xf6, 0x9e, 0xe8, 0x6d, 0x2b, 0xc5, 0x22, 0x36, 0x3a, 0x0d,
+    0xab, 0xc5, 0x21, 0x97, 0x9b, 0x0d, 0xea, 0xda, 0x1d, 0xbf, 0x9a, 0x42, 0xd5, 0xc4, 0x48, 0x4e,
+    0x0a, 0xbc, 0xd0, 0x6b, 0xfa, 0x53, 0xdd, 0xef, 0x3c, 0x1b, 0x20, 0xee, 0x3f, 0xd5, 0x9d, 0x7c,
+    0x25, 0xe4, 0x1d, 0x2b, 0x66, 0x9e, 0x1e, 0xf1, 0x6e, 0x6f, 0x52, 0xc3, 0x16, 0x4d, 0xf4, 0xfb,
+    0x79, 0x30, 0xe9, 0xe4, 0xe5, 0x88, 0x57, 0xb6, 0xac, 0x7d, 0x5f, 0x42, 0xd6, 0x9f, 0x6d, 0x18,
+    0x77, 0x63, 0xcf, 0x1d, 0x55, 0x03, 0x40, 0x04, 0x87, 0xf5, 0x5b, 0xa5, 0x7e, 0x31, 0xcc, 0x7a,
+    0x71, 0x35, 0xc8, 0x86, 0xef, 0xb4, 0x31, 0x8a, 0xed, 0x6a, 0x1e, 0x01, 0x2d, 0x9e, 0x68, 0x32,
+    0xa9, 0x07, 0x60, 0x0a, 0x91, 0x81, 0x30, 0xc4, 0x6d, 0xc7, 0x78, 0xf9, 0x71, 0xad, 0x00, 0x38,
+    0x09, 0x29, 0x99, 0xa3, 0x33, 0xcb, 0x8b, 0x7a, 0x1a, 0x1d, 0xb9, 0x3d, 0x71, 0x40, 0x00, 0x3c,
+    0x2a, 0x4e, 0xce, 0xa9, 0xf9, 0x8d, 0x0a, 0xcc, 0x0a, 0x82, 0x91, 0xcd, 0xce, 0xc9, 0x7d, 0xcf,
+    0x8e, 0xc9, 0xb5, 0x5a, 0x7f, 0x88, 0xa4, 0x6b, 0x4d, 0xb5, 0xa8, 0x51, 0xf4, 0x41, 0x82, 0xe1,
+    0xc6, 0x8a, 0x00, 0x7e, 0x5e, 0x65, 0x5f, 0x6a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A named group from RFC 7919.
+pub enum Group {
+    /// ffdhe2048.
+    Ffdhe2048,
+    /// ffdhe3072.
+    Ffdhe3072,
+    /// ffdhe4096.
+    Ffdhe4096,
+}
+
+impl Group {
+    /// Return the size of the prime, and of public keys and shared secrets,
+    /// in bytes.
+    pub fn size(&self) -> usize {
+        self.prime().len()
+    }
+
+    /// The prime, in big-endian.
+    fn prime(&self) -> &'static [u8] {
+        match self {
+            Group::Ffdhe2048 => &FFDHE2048_P,
+            Group::Ffdhe3072 => &FFDHE3072_P,
+            Group::Ffdhe4096 => &FFDHE4096_P,
+        }
+    }
+
+    /// The prime, as a modulus.
+    fn modulus(&self) -> Modulus {
+        // This cannot fail, as all primes are odd and exactly fill their limbs.
+        let limbs = bigint::from_be_bytes(self.prime(), self.size() / 8).unwrap();
+        Modulus::new(&limbs).unwrap()
+    }
+}
+
+construct_secret_key! {
+    /// A type to represent the secret exponent that `ffdhe` uses.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, SECRET_KEYSIZE, SECRET_KEYSIZE, SECRET_KEYSIZE)
+}
+
+/// Parse `secret_key` into an exponent, which must be at least 2.
+fn secret_exponent(secret_key: &SecretKey) -> Result<Vec<u64>, UnknownCryptoError> {
+    // This cannot fail, as the secret key exactly fills its limbs.
+    let mut exponent =
+        bigint::from_be_bytes(secret_key.unprotected_as_bytes(), SECRET_KEYSIZE / 8).unwrap();
+    if bigint::bit_len_vartime(&exponent) < 2 {
+        exponent.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(exponent)
+}
+
+/// Compute base^secret_key mod p, and write it to `dst_out`.
+fn exponentiate(
+    modulus: &Modulus,
+    base: &[u64],
+    secret_key: &SecretKey,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut exponent = secret_exponent(secret_key)?;
+    let mut result = modulus.pow(base, &exponent);
+    bigint::to_be_bytes(&result, dst_out);
+    exponent.iter_mut().zeroize();
+    result.iter_mut().zeroize();
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the public key of `secret_key` in `group`.
+pub fn public_key(
+    group: Group,
+    secret_key: &SecretKey,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != group.size() {
+        return Err(UnknownCryptoError);
+    }
+
+    let modulus = group.modulus();
+    let mut generator = vec![0u64; modulus.limbs().len()];
+    generator[0] = 2;
+
+    exponentiate(&modulus, &generator, secret_key, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the shared secret of `secret_key` and `peer_public_key` in `group`.
+pub fn shared_secret(
+    group: Group,
+    secret_key: &SecretKey,
+    peer_public_key: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if peer_public_key.len() != group.size() || dst_out.len() != group.size() {
+        return Err(UnknownCryptoError);
+    }
+
+    let modulus = group.modulus();
+    let limbs = modulus.limbs().len();
+    // This cannot fail, as the public key has the same length as the prime.
+    let y = bigint::from_be_bytes(peer_public_key, limbs).unwrap();
+
+    // 2 <= y <= p - 2
+    let mut p_minus_one = modulus.limbs().to_vec();
+    p_minus_one[0] -= 1;
+    if bigint::bit_len_vartime(&y) < 2 || !bool::from(bigint::ct_lt(&y, &p_minus_one)) {
+        return Err(UnknownCryptoError);
+    }
+
+    // y^q = 1 mod p, where q = (p - 1) / 2 is prime.
+    let mut q = vec![0u64; limbs];
+    for i in 0..limbs {
+        let high = p_minus_one.get(i + 1).copied().unwrap_or(0);
+        q[i] = (p_minus_one[i] >> 1) | (high << 63);
+    }
+    let y_q = modulus.pow(&y, &q);
+    if bigint::bit_len_vartime(&y_q) != 1 {
+        return Err(UnknownCryptoError);
+    }
+
+    exponentiate(&modulus, &y, secret_key, dst_out)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const GROUPS: [Group; 3] = [Group::Ffdhe2048, Group::Ffdhe3072, Group::Ffdhe4096];
+
+    #[test]
+    fn test_group_sizes() {
+        assert_eq!(Group::Ffdhe2048.size(), 256);
+        assert_eq!(Group::Ffdhe3072.size(), 384);
+        assert_eq!(Group::Ffdhe4096.size(), 512);
+    }
+
+    #[test]
+    fn test_public_key_small_exponents() {
+        for group in GROUPS.iter() {
+            let mut secret = [0u8; SECRET_KEYSIZE];
+            let mut public = vec![0u8; group.size()];
+
+            // 2^10 = 1024
+            secret[SECRET_KEYSIZE - 1] = 10;
+            public_key(
+                *group,
+                &SecretKey::from_slice(&secret).unwrap(),
+                &mut public,
+            )
+            .unwrap();
+            let mut expected = vec![0u8; group.size()];
+            expected[group.size() - 2] = 0x04;
+            assert_eq!(public, expected);
+
+            // 0 and 1 are rejected.
+            secret[SECRET_KEYSIZE - 1] = 1;
+            let sk = SecretKey::from_slice(&secret).unwrap();
+            assert!(public_key(*group, &sk, &mut public).is_err());
+            secret[SECRET_KEYSIZE - 1] = 0;
+            let sk = SecretKey::from_slice(&secret).unwrap();
+            assert!(public_key(*group, &sk, &mut public).is_err());
+        }
+    }
+
+    #[test]
+    fn test_peer_public_key_validation() {
+        let sk = SecretKey::from_slice(&[0x42u8; SECRET_KEYSIZE]).unwrap();
+        for group in GROUPS.iter() {
+            let size = group.size();
+            let mut shared = vec![0u8; size];
+            let prime = group.prime();
+            let mut peer = vec![0u8; size];
+
+            // 0, 1, p - 1, p and 0xff..ff are rejected.
+            assert!(shared_secret(*group, &sk, &peer, &mut shared).is_err());
+            peer[size - 1] = 1;
+            assert!(shared_secret(*group, &sk, &peer, &mut shared).is_err());
+            peer.copy_from_slice(prime);
+            assert!(shared_secret(*group, &sk, &peer, &mut shared).is_err());
+            peer[size - 1] -= 1;
+            assert!(shared_secret(*group, &sk, &peer, &mut shared).is_err());
+            assert!(shared_secret(*group, &sk, &vec![0xffu8; size], &mut shared).is_err());
+
+            // 2 generates the subgroup of order q, so 2 is accepted. Since
+            // p = 7 mod 8, -1 is not a square and -2 is not in the subgroup.
+            let mut two = vec![0u8; size];
+            two[size - 1] = 2;
+            assert!(shared_secret(*group, &sk, &two, &mut shared).is_ok());
+            let mut minus_two = prime.to_vec();
+            minus_two[size - 1] -= 2;
+            assert!(shared_secret(*group, &sk, &minus_two, &mut shared).is_err());
+
+            // A small quadratic non-residue is not in the subgroup.
+            let non_residue = match group {
+                Group::Ffdhe3072 => 5,
+                _ => 7,
+            };
+            let mut small = vec![0u8; size];
+            small[size - 1] = non_residue;
+            assert!(shared_secret(*group, &sk, &small, &mut shared).is_err());
+
+            // 4 is a square, and so in the subgroup.
+            let mut four = vec![0u8; size];
+            four[size - 1] = 4;
+            assert!(shared_secret(*group, &sk, &four, &mut shared).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_lengths() {
+        let sk = SecretKey::from_slice(&[0x42u8; SECRET_KEYSIZE]).unwrap();
+        let group = Group::Ffdhe2048;
+        let mut public = vec![0u8; 256];
+        public_key(group, &sk, &mut public).unwrap();
+
+        assert!(public_key(group, &sk, &mut public[..255]).is_err());
+        assert!(public_key(Group::Ffdhe3072, &sk, &mut public).is_err());
+
+        let mut shared = vec![0u8; 256];
+        assert!(shared_secret(group, &sk, &public[1..], &mut shared).is_err());
+        assert!(shared_secret(group, &sk, &public, &mut shared[1..]).is_err());
+        assert!(shared_secret(group, &sk, &public, &mut shared).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_agreement() {
+        for group in GROUPS.iter() {
+            let alice = SecretKey::generate();
+            let bob = SecretKey::generate();
+            let mut alice_pk = vec![0u8; group.size()];
+            let mut bob_pk = vec![0u8; group.size()];
+            public_key(*group, &alice, &mut alice_pk).unwrap();
+            public_key(*group, &bob, &mut bob_pk).unwrap();
+
+            let mut alice_shared = vec![0u8; group.size()];
+            let mut bob_shared = vec![0u8; group.size()];
+            shared_secret(*group, &alice, &bob_pk, &mut alice_shared).unwrap();
+            shared_secret(*group, &bob, &alice_pk, &mut bob_shared).unwrap();
+            assert_eq!(alice_shared, bob_shared);
+        }
+    }
+}
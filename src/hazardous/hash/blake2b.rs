@@ -25,6 +25,9 @@
 //! - `size`: The desired output length for the digest.
 //! - `data`: The data to be hashed.
 //! - `expected`: The expected digest when verifying.
+//! - `state`: The chaining value that [`compress()`] updates.
+//! - `block`: A single block to be processed by [`compress()`].
+//! - `offset`: The number of bytes processed, including `block`.
 //!
 //! # Errors:
 //! An error will be returned if:
@@ -36,6 +39,8 @@
 //!   initialized with `None`.
 //! - [`reset()`] is called with `None` as `secret_key` but the struct was
 //!   initialized with `Some(secret_key)`.
+//! - The length of `dest` passed to [`finalize_into()`] is not `size`.
+//! - The length of `block` passed to [`compress()`] is not 128 bytes.
 //!
 //! # Panics:
 //! A panic will occur if:
@@ -51,6 +56,10 @@
 //!   to compare such MACs and use instead [`verify()`], which will compare
 //!   the MAC in constant time.
 //! - The recommended minimum output size is 32.
+//! - [`compress()`] is the raw compression function F. Hashing data with it
+//!   directly is only secure as part of a construction that is designed for
+//!   it, and that sets the parameter block, offset and flags as BLAKE2b
+//!   specifies. Use [`Blake2b`] to hash data.
 //!
 //! # Example:
 //! ```rust
@@ -75,13 +84,17 @@
 //! [`update()`]: struct.Blake2b.html
 //! [`reset()`]: struct.Blake2b.html
 //! [`finalize()`]: struct.Blake2b.html
+//! [`finalize_into()`]: struct.Blake2b.html
 //! [`SecretKey::generate()`]: struct.SecretKey.html
 //! [`verify()`]: struct.Blake2b.html
 //! [`as_ref()`]: struct.Digest.html
+//! [`compress()`]: fn.compress.html
+//! [`Blake2b`]: struct.Blake2b.html
 use crate::{errors::UnknownCryptoError, util::endianness::load_u64_into_le, util::u64x4::U64x4};
+use zeroize::Zeroize;
 
 /// The blocksize for the hash function BLAKE2b.
-const BLAKE2B_BLOCKSIZE: usize = 128;
+pub const BLAKE2B_BLOCKSIZE: usize = 128;
 /// The maximum key size for the hash function BLAKE2b when used in keyed mode.
 pub(crate) const BLAKE2B_KEYSIZE: usize = 64;
 /// The maximum output size for the hash function BLAKE2b.
@@ -215,6 +228,16 @@ impl Hasher {
         state.finalize()
     }
 
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a digest selected by the given Blake2b variant into `dest`, which
+    /// must be of the variant's size.
+    pub fn digest_into(&self, data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = self.init()?;
+        state.update(data)?;
+
+        state.finalize_into(dest)
+    }
+
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
     /// Return a `Blake2b` state selected by the given Blake2b variant.
     pub fn init(&self) -> Result<Blake2b, UnknownCryptoError> {
@@ -226,6 +249,85 @@ impl Hasher {
     }
 }
 
+/// The compression function F, processing `block` into the state `h` with
+/// the offset counter `t` and the finalization flags `f`.
+fn compress_block(h: &mut [U64x4; 2], block: &[u8], t: [u64; 2], f: [u64; 2]) {
+    debug_assert!(block.len() == BLAKE2B_BLOCKSIZE);
+    let mut m_vec = [0u64; 16];
+    load_u64_into_le(block, &mut m_vec);
+
+    let mut v0 = h[0];
+    let mut v1 = h[1];
+    let mut v2 = IV[0];
+    let mut v3 = U64x4(
+        t[0] ^ IV[1].0,
+        t[1] ^ IV[1].1,
+        f[0] ^ IV[1].2,
+        f[1] ^ IV[1].3,
+    );
+
+    ROUND!(v0, v1, v2, v3, SIGMA[0], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[1], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[2], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[3], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[4], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[5], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[6], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[7], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[8], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[9], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[10], m_vec);
+    ROUND!(v0, v1, v2, v3, SIGMA[11], m_vec);
+
+    h[0] ^= v0 ^ v2;
+    h[1] ^= v1 ^ v3;
+}
+
+/// The BLAKE2b initialization vector. The initial state for [`compress()`] is
+/// this, with the parameter block XORed into it.
+///
+/// [`compress()`]: fn.compress.html
+pub const BLAKE2B_IV: [u64; 8] = [
+    IV[0].0, IV[0].1, IV[0].2, IV[0].3, IV[1].0, IV[1].1, IV[1].2, IV[1].3,
+];
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Process a single `block` of [`BLAKE2B_BLOCKSIZE`] bytes into `state`, with
+/// the BLAKE2b compression function F. `offset` is the number of bytes
+/// processed so far, including those of `block`. `last_block` sets the
+/// finalization flag for the last block, and `last_node` the flag for the
+/// last node in tree hashing.
+///
+/// [`BLAKE2B_BLOCKSIZE`]: constant.BLAKE2B_BLOCKSIZE.html
+pub fn compress(
+    state: &mut [u64; 8],
+    block: &[u8],
+    offset: u128,
+    last_block: bool,
+    last_node: bool,
+) -> Result<(), UnknownCryptoError> {
+    if block.len() != BLAKE2B_BLOCKSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut h = [
+        U64x4(state[0], state[1], state[2], state[3]),
+        U64x4(state[4], state[5], state[6], state[7]),
+    ];
+    let t = [offset as u64, (offset >> 64) as u64];
+    let f = [
+        if last_block { u64::MAX } else { 0 },
+        if last_node { u64::MAX } else { 0 },
+    ];
+    compress_block(&mut h, block, t, f);
+    state.copy_from_slice(&[
+        h[0].0, h[0].1, h[0].2, h[0].3, h[1].0, h[1].1, h[1].2, h[1].3,
+    ]);
+    h.iter_mut().zeroize();
+
+    Ok(())
+}
+
 #[derive(Clone)]
 /// BLAKE2b streaming state.
 pub struct Blake2b {
@@ -274,40 +376,10 @@ impl Blake2b {
 
     /// The compression function f.
     fn compress_f(&mut self, data: Option<&[u8]>) {
-        let mut m_vec = [0u64; 16];
         match data {
-            Some(bytes) => {
-                debug_assert!(bytes.len() == BLAKE2B_BLOCKSIZE);
-                load_u64_into_le(bytes, &mut m_vec);
-            }
-            None => load_u64_into_le(&self.buffer, &mut m_vec),
+            Some(bytes) => compress_block(&mut self.internal_state, bytes, self.t, self.f),
+            None => compress_block(&mut self.internal_state, &self.buffer, self.t, self.f),
         }
-
-        let mut v0 = self.internal_state[0];
-        let mut v1 = self.internal_state[1];
-        let mut v2 = IV[0];
-        let mut v3 = U64x4(
-            self.t[0] ^ IV[1].0,
-            self.t[1] ^ IV[1].1,
-            self.f[0] ^ IV[1].2,
-            self.f[1] ^ IV[1].3,
-        );
-
-        ROUND!(v0, v1, v2, v3, SIGMA[0], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[1], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[2], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[3], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[4], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[5], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[6], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[7], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[8], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[9], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[10], m_vec);
-        ROUND!(v0, v1, v2, v3, SIGMA[11], m_vec);
-
-        self.internal_state[0] ^= v0 ^ v2;
-        self.internal_state[1] ^= v1 ^ v3;
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -429,9 +501,22 @@ impl Blake2b {
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
     /// Return a BLAKE2b digest.
     pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; BLAKE2B_OUTSIZE];
+        self.finalize_into(&mut digest[..self.size])?;
+
+        Digest::from_slice(&digest[..self.size])
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a BLAKE2b digest into `dest`, which must be of the `size` the
+    /// state was initialized with.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
         if self.is_finalized {
             return Err(UnknownCryptoError);
         }
+        if dest.len() != self.size {
+            return Err(UnknownCryptoError);
+        }
 
         self.is_finalized = true;
 
@@ -445,11 +530,12 @@ impl Blake2b {
         }
         self.compress_f(None);
 
-        let mut digest = [0u8; 64];
+        let mut digest = [0u8; BLAKE2B_OUTSIZE];
         self.internal_state[0].store_into_le(&mut digest[..32]);
         self.internal_state[1].store_into_le(&mut digest[32..]);
+        dest.copy_from_slice(&digest[..self.size]);
 
-        Digest::from_slice(&digest[..self.size])
+        Ok(())
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -476,6 +562,35 @@ impl Blake2b {
 mod public {
     use super::*;
 
+    #[test]
+    fn test_compress() {
+        // BLAKE2b-512 of "abc", without a key.
+        let mut state = BLAKE2B_IV;
+        state[0] ^= 0x0101_0040;
+        let mut block = [0u8; BLAKE2B_BLOCKSIZE];
+        block[..3].copy_from_slice(b"abc");
+        compress(&mut state, &block, 3, true, false).unwrap();
+
+        let mut digest = [0u8; BLAKE2B_OUTSIZE];
+        for (word, bytes) in state.iter().zip(digest.chunks_exact_mut(8)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        let expected = Blake2b::new(None, BLAKE2B_OUTSIZE)
+            .and_then(|mut ctx| ctx.update(b"abc").and(ctx.finalize()))
+            .unwrap();
+        assert_eq!(digest[..], expected.as_ref()[..]);
+
+        // The last node flag changes the output.
+        let mut last_node = BLAKE2B_IV;
+        last_node[0] ^= 0x0101_0040;
+        compress(&mut last_node, &block, 3, true, true).unwrap();
+        assert_ne!(last_node, state);
+
+        assert!(compress(&mut state, &block[..BLAKE2B_BLOCKSIZE - 1], 3, true, false).is_err());
+        assert!(compress(&mut state, &[0u8; BLAKE2B_BLOCKSIZE + 1], 3, true, false).is_err());
+        assert!(compress(&mut state, &[], 0, true, false).is_err());
+    }
+
     #[test]
     #[cfg(feature = "safe_api")]
     fn test_debug_impl() {
@@ -937,6 +1052,45 @@ mod public {
             }
         }
     }
+
+    mod test_finalize_into {
+        use super::*;
+
+        #[test]
+        fn finalize_into_same_as_finalize() {
+            for size in [1usize, 32, 48, 64].iter() {
+                let mut state = Blake2b::new(None, *size).unwrap();
+                state.update(b"Some data").unwrap();
+                let mut other = state.clone();
+
+                let mut dest = [0u8; BLAKE2B_OUTSIZE];
+                state.finalize_into(&mut dest[..*size]).unwrap();
+                assert_eq!(other.finalize().unwrap().as_ref(), &dest[..*size]);
+            }
+        }
+
+        #[test]
+        fn finalize_into_wrong_length_err() {
+            let mut state = Blake2b::new(None, 32).unwrap();
+            assert!(state.finalize_into(&mut [0u8; 31]).is_err());
+            assert!(state.finalize_into(&mut [0u8; 64]).is_err());
+            // The state has not been finalized by the failed calls.
+            assert!(state.finalize_into(&mut [0u8; 32]).is_ok());
+            assert!(state.finalize_into(&mut [0u8; 32]).is_err());
+        }
+
+        #[test]
+        fn hasher_digest_into_same_as_digest() {
+            let mut dest = [0u8; 48];
+            Hasher::Blake2b384.digest_into(b"Test", &mut dest).unwrap();
+            assert_eq!(
+                Hasher::Blake2b384.digest(b"Test").unwrap().as_ref(),
+                &dest[..]
+            );
+
+            assert!(Hasher::Blake2b256.digest_into(b"Test", &mut dest).is_err());
+        }
+    }
 }
 
 // Testing private functions in the module.
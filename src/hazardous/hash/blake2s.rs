@@ -0,0 +1,367 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! BLAKE2s as specified in the [RFC 7693](https://tools.ietf.org/html/rfc7693).
+//!
+//! This is only used internally, by protocols that require BLAKE2s, such as
+//! WireGuard. Prefer [`blake2b`] everywhere else.
+//!
+//! [`blake2b`]: ../blake2b/index.html
+
+use crate::errors::UnknownCryptoError;
+use crate::util::endianness::load_u32_le;
+use zeroize::Zeroize;
+
+/// The blocksize for the hash function BLAKE2s.
+pub(crate) const BLAKE2S_BLOCKSIZE: usize = 64;
+/// The maximum key size for the hash function BLAKE2s when used in keyed mode.
+pub(crate) const BLAKE2S_KEYSIZE: usize = 32;
+/// The maximum output size for the hash function BLAKE2s.
+pub(crate) const BLAKE2S_OUTSIZE: usize = 32;
+
+#[allow(clippy::unreadable_literal)]
+/// The BLAKE2s initialization vector as defined in the RFC 7693.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// BLAKE2s SIGMA as defined in the RFC 7693.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The BLAKE2s mixing function G.
+fn mix(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+/// BLAKE2s streaming state.
+pub(crate) struct Blake2s {
+    internal_state: [u32; 8],
+    buffer: [u8; BLAKE2S_BLOCKSIZE],
+    leftover: usize,
+    t: u64,
+    size: usize,
+}
+
+impl Drop for Blake2s {
+    fn drop(&mut self) {
+        self.internal_state.iter_mut().zeroize();
+        self.buffer.iter_mut().zeroize();
+    }
+}
+
+impl Blake2s {
+    /// Initialize a BLAKE2s state with an optional `secret_key` of 1..=32
+    /// bytes and an output size of 1..=32 bytes.
+    pub(crate) fn new(secret_key: Option<&[u8]>, size: usize) -> Result<Self, UnknownCryptoError> {
+        if !(1..=BLAKE2S_OUTSIZE).contains(&size) {
+            return Err(UnknownCryptoError);
+        }
+        let key = secret_key.unwrap_or(&[0u8; 0]);
+        if secret_key.is_some() && (key.is_empty() || key.len() > BLAKE2S_KEYSIZE) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut state = Self {
+            internal_state: IV,
+            buffer: [0u8; BLAKE2S_BLOCKSIZE],
+            leftover: 0,
+            t: 0,
+            size,
+        };
+        // Parameter block with a fanout and depth of 1.
+        state.internal_state[0] ^= 0x0101_0000 ^ ((key.len() as u32) << 8) ^ (size as u32);
+
+        if !key.is_empty() {
+            state.buffer[..key.len()].copy_from_slice(key);
+            state.leftover = BLAKE2S_BLOCKSIZE;
+        }
+
+        Ok(state)
+    }
+
+    /// Compress the buffered block.
+    fn compress(&mut self, last_block: bool) {
+        let mut m = [0u32; 16];
+        for (word, chunk) in m.iter_mut().zip(self.buffer.chunks_exact(4)) {
+            *word = load_u32_le(chunk);
+        }
+
+        let mut v = [0u32; 16];
+        v[..8].copy_from_slice(&self.internal_state);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.t as u32;
+        v[13] ^= (self.t >> 32) as u32;
+        if last_block {
+            v[14] = !v[14];
+        }
+
+        for s in SIGMA.iter() {
+            mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for (idx, h) in self.internal_state.iter_mut().enumerate() {
+            *h ^= v[idx] ^ v[idx + 8];
+        }
+
+        m.iter_mut().zeroize();
+        v.iter_mut().zeroize();
+    }
+
+    /// Update state with `data`. This can be called multiple times.
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        while !data.is_empty() {
+            // The last block is only compressed in finalize(), so a full
+            // buffer is only compressed once more data is available.
+            if self.leftover == BLAKE2S_BLOCKSIZE {
+                self.t = self.t.wrapping_add(BLAKE2S_BLOCKSIZE as u64);
+                self.compress(false);
+                self.leftover = 0;
+            }
+
+            let fill = core::cmp::min(BLAKE2S_BLOCKSIZE - self.leftover, data.len());
+            self.buffer[self.leftover..self.leftover + fill].copy_from_slice(&data[..fill]);
+            self.leftover += fill;
+            data = &data[fill..];
+        }
+    }
+
+    /// Finalize the hash and put the digest into `dst`, which must be of the
+    /// output size given at initialization.
+    pub(crate) fn finalize(mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst.len() != self.size {
+            return Err(UnknownCryptoError);
+        }
+
+        self.t = self.t.wrapping_add(self.leftover as u64);
+        for byte in self.buffer[self.leftover..].iter_mut() {
+            *byte = 0;
+        }
+        self.compress(true);
+
+        let mut digest = [0u8; BLAKE2S_OUTSIZE];
+        for (chunk, h) in digest.chunks_exact_mut(4).zip(self.internal_state.iter()) {
+            chunk.copy_from_slice(&h.to_le_bytes());
+        }
+        dst.copy_from_slice(&digest[..self.size]);
+        digest.iter_mut().zeroize();
+
+        Ok(())
+    }
+}
+
+/// Compute BLAKE2s of the concatenation of `data`, with an optional
+/// `secret_key`, into `dst`.
+pub(crate) fn digest_into(
+    secret_key: Option<&[u8]>,
+    data: &[&[u8]],
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut state = Blake2s::new(secret_key, dst.len())?;
+    for part in data.iter() {
+        state.update(part);
+    }
+    state.finalize(dst)
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    fn test_runner(key: Option<&[u8]>, len: usize, expected: &str) {
+        let mut message = [0u8; 255];
+        for (idx, byte) in message.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        let expected = hex::decode(expected).unwrap();
+
+        let mut one_shot = [0u8; BLAKE2S_OUTSIZE];
+        digest_into(key, &[&message[..len]], &mut one_shot[..expected.len()]).unwrap();
+        assert_eq!(&one_shot[..expected.len()], &expected[..]);
+
+        // Byte-by-byte updates.
+        let mut state = Blake2s::new(key, expected.len()).unwrap();
+        for byte in message[..len].iter() {
+            state.update(&[*byte]);
+        }
+        let mut streamed = [0u8; BLAKE2S_OUTSIZE];
+        state.finalize(&mut streamed[..expected.len()]).unwrap();
+        assert_eq!(&streamed[..expected.len()], &expected[..]);
+    }
+
+    #[test]
+    fn test_rfc7693_appendix_b() {
+        let mut digest = [0u8; BLAKE2S_OUTSIZE];
+        digest_into(None, &[b"abc"], &mut digest).unwrap();
+        assert_eq!(
+            digest.as_ref(),
+            &hex::decode("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_unkeyed() {
+        // Generated with Python's hashlib.
+        test_runner(
+            None,
+            0,
+            "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+        );
+        test_runner(
+            None,
+            1,
+            "e34d74dbaf4ff4c6abd871cc220451d2ea2648846c7757fbaac82fe51ad64bea",
+        );
+        test_runner(
+            None,
+            63,
+            "e57cb79487dd57902432b250733813bd96a84efce59f650fac26e6696aefafc3",
+        );
+        test_runner(
+            None,
+            64,
+            "56f34e8b96557e90c1f24b52d0c89d51086acf1b00f634cf1dde9233b8eaaa3e",
+        );
+        test_runner(
+            None,
+            65,
+            "1b53ee94aaf34e4b159d48de352c7f0661d0a40edff95a0b1639b4090e974472",
+        );
+        test_runner(
+            None,
+            128,
+            "1fa877de67259d19863a2a34bcc6962a2b25fcbf5cbecd7ede8f1fa36688a796",
+        );
+        test_runner(
+            None,
+            129,
+            "5bd169e67c82c2c2e98ef7008bdf261f2ddf30b1c00f9e7f275bb3e8a28dc9a2",
+        );
+        test_runner(
+            None,
+            255,
+            "f03f5789d3336b80d002d59fdf918bdb775b00956ed5528e86aa994acb38fe2d",
+        );
+    }
+
+    #[test]
+    fn test_keyed() {
+        // From blake2s-kat.txt of the BLAKE2 reference implementation.
+        let key: [u8; 32] = [
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
+            24, 25, 26, 27, 28, 29, 30, 31,
+        ];
+        let key = Some(&key[..]);
+        test_runner(
+            key,
+            0,
+            "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+        );
+        test_runner(
+            key,
+            1,
+            "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1",
+        );
+        test_runner(
+            key,
+            63,
+            "c65382513f07460da39833cb666c5ed82e61b9e998f4b0c4287cee56c3cc9bcd",
+        );
+        test_runner(
+            key,
+            64,
+            "8975b0577fd35566d750b362b0897a26c399136df07bababbde6203ff2954ed4",
+        );
+        test_runner(
+            key,
+            65,
+            "21fe0ceb0052be7fb0f004187cacd7de67fa6eb0938d927677f2398c132317a8",
+        );
+        test_runner(
+            key,
+            128,
+            "0c311f38c35a4fb90d651c289d486856cd1413df9b0677f53ece2cd9e477c60a",
+        );
+        test_runner(
+            key,
+            129,
+            "46a73a8dd3e70f59d3942c01df599def783c9da82fd83222cd662b53dce7dbdf",
+        );
+        test_runner(
+            key,
+            255,
+            "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+        );
+    }
+
+    #[test]
+    fn test_keyed_truncated() {
+        // Generated with Python's hashlib, with a 16-byte key and a 16-byte digest.
+        let key: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+        let key = Some(&key[..]);
+        test_runner(key, 0, "af4e5d3bb230e9ff9096ca6f7c501f76");
+        test_runner(key, 64, "dd55c51c98f8ab96ed272882941f0b6a");
+        test_runner(key, 65, "9018c671bb7e94a6d540a91765bbecad");
+        test_runner(key, 255, "b2953795123bd6b890ef3b6fa8b9bae2");
+    }
+
+    #[test]
+    fn test_bad_parameters() {
+        assert!(Blake2s::new(None, 0).is_err());
+        assert!(Blake2s::new(None, 33).is_err());
+        assert!(Blake2s::new(Some(&[]), 32).is_err());
+        assert!(Blake2s::new(Some(&[0u8; 33]), 32).is_err());
+        assert!(Blake2s::new(Some(&[0u8; 32]), 32).is_ok());
+
+        let mut dst = [0u8; 32];
+        assert!(digest_into(None, &[b"abc"], &mut dst[..0]).is_err());
+        let state = Blake2s::new(None, 16).unwrap();
+        assert!(state.finalize(&mut dst).is_err());
+    }
+}
@@ -0,0 +1,837 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `secret_key`: The secret key for keyed mode.
+//! - `context`: The context string for the key derivation mode.
+//! - `key_material`: The input key material to derive a key from.
+//! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//! - `dest`: Destination buffer for the output, which can be of any length.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] or [`finalize_into()`] is called twice, or after
+//!   squeezing, without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`], [`finalize_into()`] or
+//!   squeezing without a [`reset()`] in between.
+//! - Squeezing output after [`finalize()`] or [`finalize_into()`], without a
+//!   [`reset()`] in between.
+//! - `dest` is empty.
+//! - The digest does not match `expected` when verifying.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   [`SecretKey::generate()`] can be used for this. It generates a secret key
+//!   of 32 bytes.
+//! - When using BLAKE3 in keyed mode, the output can be used as a MAC. If this
+//!   is the intention, __**avoid using**__ [`as_ref()`] to compare such MACs and
+//!   use instead [`verify()`], which will compare the MAC in constant time.
+//! - The context string of the key derivation mode should be hardcoded,
+//!   globally unique and application-specific, such as
+//!   `"example.com 2024-01-01 session tokens v1"`. It must not be derived from
+//!   secret or variable data. Put such data in `key_material` instead.
+//! - Output of any length can be read with [`finalize_into()`] or, through
+//!   [`Xof`], incrementally. Shorter outputs are prefixes of longer ones, so the
+//!   length of the output does not separate domains. The security level is 128
+//!   bits regardless of the output length.
+//!
+//! # Recommendation:
+//! - This implementation is portable and does not use SIMD instructions or
+//!   multiple threads, which is where most of the speed of BLAKE3 comes from.
+//!   It is therefore not considerably faster than [BLAKE2b], which remains the
+//!   recommended hash function when a protocol does not require BLAKE3.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::blake3::{Blake3, SecretKey};
+//! use orion::hazardous::hash::Xof;
+//!
+//! // Using the streaming interface.
+//! let mut state = Blake3::new();
+//! state.update(b"Some data")?;
+//! let digest = state.finalize()?;
+//! assert_eq!(digest, Blake3::digest(b"Some data")?);
+//!
+//! // Using keyed mode as a MAC.
+//! let secret_key = SecretKey::generate();
+//! let mac = Blake3::keyed_hash(&secret_key, b"Some data")?;
+//! assert!(Blake3::verify(&mac, &secret_key, b"Some data").is_ok());
+//!
+//! // Deriving a key of any length.
+//! let mut derived = [0u8; 64];
+//! Blake3::derive_key("example.com 2024-01-01 session tokens v1", b"input key material", &mut derived)?;
+//!
+//! // Reading output incrementally.
+//! let mut xof = Blake3::new();
+//! xof.absorb(b"Some data")?;
+//! let mut first = [0u8; 32];
+//! let mut second = [0u8; 100];
+//! xof.squeeze(&mut first)?;
+//! xof.squeeze(&mut second)?;
+//! assert_eq!(&first[..], digest.as_ref());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`update()`]: struct.Blake3.html
+//! [`reset()`]: struct.Blake3.html
+//! [`finalize()`]: struct.Blake3.html
+//! [`finalize_into()`]: struct.Blake3.html
+//! [`verify()`]: struct.Blake3.html#method.verify
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+//! [`as_ref()`]: struct.Digest.html
+//! [`Xof`]: ../trait.Xof.html
+//! [BLAKE2b]: ../blake2b/index.html
+
+use crate::errors::UnknownCryptoError;
+use crate::util::endianness::{load_u32_into_le, store_u32_into_le};
+use zeroize::Zeroize;
+
+/// The blocksize for the hash function BLAKE3.
+pub const BLAKE3_BLOCKSIZE: usize = 64;
+/// The size of a chunk, which is the leaf of the BLAKE3 tree.
+pub const BLAKE3_CHUNKSIZE: usize = 1024;
+/// The key size for BLAKE3 when used in keyed mode.
+pub const BLAKE3_KEYSIZE: usize = 32;
+/// The default output size for the hash function BLAKE3.
+pub const BLAKE3_OUTSIZE: usize = 32;
+
+construct_secret_key! {
+    /// A type to represent the secret key that BLAKE3 uses for keyed mode.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, BLAKE3_KEYSIZE, BLAKE3_KEYSIZE, BLAKE3_KEYSIZE)
+}
+
+construct_public! {
+    /// A type to represent the `Digest` that BLAKE3 returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (Digest, test_digest, BLAKE3_OUTSIZE, BLAKE3_OUTSIZE)
+}
+
+impl_from_trait!(Digest, BLAKE3_OUTSIZE);
+
+#[allow(clippy::unreadable_literal)]
+/// The BLAKE3 IV, which is the same as that of SHA256.
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The message word permutation applied after each round.
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+// The domain separation flags.
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+/// The maximum depth of the tree, which allows hashing 2^64 bytes.
+const MAX_DEPTH: usize = 54;
+
+/// The quarter-round G function.
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// The BLAKE3 compression function, which returns the full 16-word state.
+fn compress(
+    chaining_value: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [
+        chaining_value[0],
+        chaining_value[1],
+        chaining_value[2],
+        chaining_value[3],
+        chaining_value[4],
+        chaining_value[5],
+        chaining_value[6],
+        chaining_value[7],
+        IV[0],
+        IV[1],
+        IV[2],
+        IV[3],
+        counter as u32,
+        (counter >> 32) as u32,
+        block_len,
+        flags,
+    ];
+    let mut m = *block_words;
+
+    for round in 0..7 {
+        g(&mut state, 0, 4, 8, 12, m[0], m[1]);
+        g(&mut state, 1, 5, 9, 13, m[2], m[3]);
+        g(&mut state, 2, 6, 10, 14, m[4], m[5]);
+        g(&mut state, 3, 7, 11, 15, m[6], m[7]);
+        g(&mut state, 0, 5, 10, 15, m[8], m[9]);
+        g(&mut state, 1, 6, 11, 12, m[10], m[11]);
+        g(&mut state, 2, 7, 8, 13, m[12], m[13]);
+        g(&mut state, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            let mut permuted = [0u32; 16];
+            for (dst, src) in permuted.iter_mut().zip(MSG_PERMUTATION.iter()) {
+                *dst = m[*src];
+            }
+            m = permuted;
+        }
+    }
+    m.zeroize();
+
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+
+    state
+}
+
+/// Return the first 8 words of a compression output, as a chaining value.
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    let mut cv = [0u32; 8];
+    cv.copy_from_slice(&words[..8]);
+    cv
+}
+
+#[derive(Clone)]
+/// The input to the compression of a node, which is either used as a
+/// chaining value or, for the root node, to produce output of any length.
+struct Output {
+    input_chaining_value: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        self.input_chaining_value.zeroize();
+        self.block_words.zeroize();
+    }
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        ))
+    }
+
+    /// Write the root output block with index `output_block_counter` into
+    /// `dest`.
+    fn root_output_block(&self, output_block_counter: u64, dest: &mut [u8; BLAKE3_BLOCKSIZE]) {
+        let mut words = compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            output_block_counter,
+            self.block_len,
+            self.flags | ROOT,
+        );
+        store_u32_into_le(&words, dest);
+        words.zeroize();
+    }
+}
+
+/// The output of a parent node with the chaining values of its children.
+fn parent_output(left: &[u32; 8], right: &[u32; 8], key_words: &[u32; 8], flags: u32) -> Output {
+    let mut block_words = [0u32; 16];
+    block_words[..8].copy_from_slice(left);
+    block_words[8..].copy_from_slice(right);
+
+    Output {
+        input_chaining_value: *key_words,
+        block_words,
+        counter: 0,
+        block_len: BLAKE3_BLOCKSIZE as u32,
+        flags: PARENT | flags,
+    }
+}
+
+#[derive(Clone)]
+/// The state of the chunk that is currently being hashed.
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLAKE3_BLOCKSIZE],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl Drop for ChunkState {
+    fn drop(&mut self) {
+        self.chaining_value.zeroize();
+        self.block.zeroize();
+    }
+}
+
+impl ChunkState {
+    fn new(key_words: &[u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        Self {
+            chaining_value: *key_words,
+            chunk_counter,
+            block: [0u8; BLAKE3_BLOCKSIZE],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLAKE3_BLOCKSIZE * self.blocks_compressed + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    fn block_words(&self) -> [u32; 16] {
+        let mut words = [0u32; 16];
+        load_u32_into_le(&self.block, &mut words);
+        words
+    }
+
+    /// Absorb `data`, which must fit in the chunk.
+    fn update(&mut self, mut data: &[u8]) {
+        debug_assert!(self.len() + data.len() <= BLAKE3_CHUNKSIZE);
+
+        while !data.is_empty() {
+            // The last block of a chunk is only compressed once it is known
+            // whether more data follows, since it needs the CHUNK_END flag.
+            if self.block_len == BLAKE3_BLOCKSIZE {
+                let mut block_words = self.block_words();
+                self.chaining_value = first_8_words(compress(
+                    &self.chaining_value,
+                    &block_words,
+                    self.chunk_counter,
+                    BLAKE3_BLOCKSIZE as u32,
+                    self.flags | self.start_flag(),
+                ));
+                block_words.zeroize();
+                self.blocks_compressed += 1;
+                self.block = [0u8; BLAKE3_BLOCKSIZE];
+                self.block_len = 0;
+            }
+
+            let take = core::cmp::min(BLAKE3_BLOCKSIZE - self.block_len, data.len());
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            input_chaining_value: self.chaining_value,
+            block_words: self.block_words(),
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+#[derive(Clone)]
+/// BLAKE3 streaming state.
+pub struct Blake3 {
+    key_words: [u32; 8],
+    flags: u32,
+    chunk_state: ChunkState,
+    cv_stack: [[u32; 8]; MAX_DEPTH],
+    cv_stack_len: usize,
+    is_finalized: bool,
+    // The root node and position of the output, once output is squeezed.
+    root: Option<Output>,
+    output_block: [u8; BLAKE3_BLOCKSIZE],
+    output_block_counter: u64,
+    output_position: usize,
+}
+
+impl Drop for Blake3 {
+    fn drop(&mut self) {
+        self.key_words.zeroize();
+        self.cv_stack.zeroize();
+        self.output_block.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Blake3 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Blake3 {{ key_words: [***OMITTED***], flags: {:?}, chunk_state: [***OMITTED***], \
+             cv_stack: [***OMITTED***], cv_stack_len: {:?}, is_finalized: {:?} }}",
+            self.flags, self.cv_stack_len, self.is_finalized
+        )
+    }
+}
+
+impl Default for Blake3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Blake3 {
+    fn new_internal(key_words: [u32; 8], flags: u32) -> Self {
+        Self {
+            key_words,
+            flags,
+            chunk_state: ChunkState::new(&key_words, 0, flags),
+            cv_stack: [[0u32; 8]; MAX_DEPTH],
+            cv_stack_len: 0,
+            is_finalized: false,
+            root: None,
+            output_block: [0u8; BLAKE3_BLOCKSIZE],
+            output_block_counter: 0,
+            output_position: 0,
+        }
+    }
+
+    /// Initialize a `Blake3` struct for the hash mode.
+    pub fn new() -> Self {
+        Self::new_internal(IV, 0)
+    }
+
+    /// Initialize a `Blake3` struct for the keyed hash mode.
+    pub fn new_keyed(secret_key: &SecretKey) -> Self {
+        let mut key_words = [0u32; 8];
+        load_u32_into_le(secret_key.unprotected_as_bytes(), &mut key_words);
+        let state = Self::new_internal(key_words, KEYED_HASH);
+        key_words.zeroize();
+
+        state
+    }
+
+    /// Initialize a `Blake3` struct for the key derivation mode with
+    /// `context`. The derived key is the output after updating the state with
+    /// the key material.
+    pub fn new_derive_key(context: &str) -> Self {
+        let mut context_state = Self::new_internal(IV, DERIVE_KEY_CONTEXT);
+        // Cannot fail, since the state is new.
+        context_state.update(context.as_bytes()).unwrap();
+        let mut context_key = [0u8; BLAKE3_KEYSIZE];
+        context_state.finalize_into(&mut context_key).unwrap();
+
+        let mut key_words = [0u32; 8];
+        load_u32_into_le(&context_key, &mut key_words);
+        context_key.zeroize();
+        let state = Self::new_internal(key_words, DERIVE_KEY_MATERIAL);
+        key_words.zeroize();
+
+        state
+    }
+
+    /// Reset to the state it was initialized to, in the same mode and with
+    /// the same key or context.
+    pub fn reset(&mut self) {
+        self.chunk_state = ChunkState::new(&self.key_words, 0, self.flags);
+        self.cv_stack = [[0u32; 8]; MAX_DEPTH];
+        self.cv_stack_len = 0;
+        self.is_finalized = false;
+        self.root = None;
+        self.output_block = [0u8; BLAKE3_BLOCKSIZE];
+        self.output_block_counter = 0;
+        self.output_position = 0;
+    }
+
+    /// Push the chaining value of a completed chunk, and merge it with the
+    /// subtrees that it completes. `total_chunks` is the number of chunks
+    /// hashed so far, including this one.
+    fn add_chunk_chaining_value(&mut self, mut new_cv: [u32; 8], mut total_chunks: u64) {
+        while total_chunks & 1 == 0 {
+            self.cv_stack_len -= 1;
+            new_cv = parent_output(
+                &self.cv_stack[self.cv_stack_len],
+                &new_cv,
+                &self.key_words,
+                self.flags,
+            )
+            .chaining_value();
+            total_chunks >>= 1;
+        }
+        self.cv_stack[self.cv_stack_len] = new_cv;
+        self.cv_stack_len += 1;
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, mut data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+
+        while !data.is_empty() {
+            // A full chunk is only added to the tree once more data follows,
+            // since the last chunk may be the root.
+            if self.chunk_state.len() == BLAKE3_CHUNKSIZE {
+                let chunk_cv = self.chunk_state.output().chaining_value();
+                let total_chunks = self.chunk_state.chunk_counter + 1;
+                self.add_chunk_chaining_value(chunk_cv, total_chunks);
+                self.chunk_state = ChunkState::new(&self.key_words, total_chunks, self.flags);
+            }
+
+            let take = core::cmp::min(BLAKE3_CHUNKSIZE - self.chunk_state.len(), data.len());
+            self.chunk_state.update(&data[..take]);
+            data = &data[take..];
+        }
+
+        Ok(())
+    }
+
+    /// The output of the root node, from merging the remaining subtrees.
+    fn root_output(&self) -> Output {
+        let mut output = self.chunk_state.output();
+        for cv in self.cv_stack[..self.cv_stack_len].iter().rev() {
+            output = parent_output(cv, &output.chaining_value(), &self.key_words, self.flags);
+        }
+
+        output
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a BLAKE3 digest of `BLAKE3_OUTSIZE` bytes.
+    pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; BLAKE3_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a BLAKE3 output of `dest.len()` bytes into `dest`, which must not
+    /// be empty.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized || dest.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        self.is_finalized = true;
+        let root = self.root_output();
+        let mut block = [0u8; BLAKE3_BLOCKSIZE];
+        for (counter, chunk) in dest.chunks_mut(BLAKE3_BLOCKSIZE).enumerate() {
+            root.root_output_block(counter as u64, &mut block);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        block.zeroize();
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a BLAKE3 digest of some `data`.
+    pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a BLAKE3 keyed hash of some `data`, which can be used as a
+    /// MAC.
+    pub fn keyed_hash(secret_key: &SecretKey, data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new_keyed(secret_key);
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify a BLAKE3 keyed hash of `data` in constant time.
+    pub fn verify(
+        expected: &Digest,
+        secret_key: &SecretKey,
+        data: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::keyed_hash(secret_key, data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive a key of `dest.len()` bytes from `key_material` with the
+    /// key derivation mode, using `context`.
+    pub fn derive_key(
+        context: &str,
+        key_material: &[u8],
+        dest: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new_derive_key(context);
+        state.update(key_material)?;
+        state.finalize_into(dest)
+    }
+}
+
+impl super::Xof for Blake3 {
+    fn reset(&mut self) {
+        Blake3::reset(self)
+    }
+
+    fn absorb(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.update(data)
+    }
+
+    fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if self.root.is_none() {
+            if self.is_finalized {
+                return Err(UnknownCryptoError);
+            }
+            self.is_finalized = true;
+            self.root = Some(self.root_output());
+            self.output_position = BLAKE3_BLOCKSIZE;
+        }
+
+        if let Some(root) = &self.root {
+            let mut dest = dest;
+            while !dest.is_empty() {
+                if self.output_position == BLAKE3_BLOCKSIZE {
+                    root.root_output_block(self.output_block_counter, &mut self.output_block);
+                    self.output_block_counter += 1;
+                    self.output_position = 0;
+                }
+                let take = core::cmp::min(BLAKE3_BLOCKSIZE - self.output_position, dest.len());
+                dest[..take].copy_from_slice(
+                    &self.output_block[self.output_position..self.output_position + take],
+                );
+                self.output_position += take;
+                dest = &mut dest[take..];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::hash::Xof;
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let initial_state = Blake3::new();
+        let debug = format!("{:?}", initial_state);
+        let expected = "Blake3 { key_words: [***OMITTED***], flags: 0, chunk_state: [***OMITTED***], cv_stack: [***OMITTED***], cv_stack_len: 0, is_finalized: false }";
+        assert_eq!(debug, expected);
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            Blake3::digest(b"").unwrap().as_ref(),
+            &hex::decode("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262")
+                .unwrap()[..]
+        );
+        assert_eq!(
+            Blake3::digest(b"abc").unwrap().as_ref(),
+            &hex::decode("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85")
+                .unwrap()[..]
+        );
+    }
+
+    mod test_streaming_interface {
+        use super::*;
+        use crate::test_framework::incremental_interface::*;
+
+        impl TestableStreamingContext<Digest> for Blake3 {
+            fn reset(&mut self) -> Result<(), UnknownCryptoError> {
+                Blake3::reset(self);
+                Ok(())
+            }
+
+            fn update(&mut self, input: &[u8]) -> Result<(), UnknownCryptoError> {
+                self.update(input)
+            }
+
+            fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+                self.finalize()
+            }
+
+            fn one_shot(input: &[u8]) -> Result<Digest, UnknownCryptoError> {
+                Blake3::digest(input)
+            }
+
+            fn verify_result(expected: &Digest, input: &[u8]) -> Result<(), UnknownCryptoError> {
+                if expected == &Blake3::digest(input)? {
+                    Ok(())
+                } else {
+                    Err(UnknownCryptoError)
+                }
+            }
+
+            fn compare_states(state_1: &Blake3, state_2: &Blake3) {
+                assert_eq!(state_1.key_words, state_2.key_words);
+                assert_eq!(state_1.flags, state_2.flags);
+                assert_eq!(
+                    state_1.chunk_state.chaining_value,
+                    state_2.chunk_state.chaining_value
+                );
+                assert_eq!(
+                    state_1.chunk_state.chunk_counter,
+                    state_2.chunk_state.chunk_counter
+                );
+                assert_eq!(state_1.chunk_state.block, state_2.chunk_state.block);
+                assert_eq!(state_1.chunk_state.len(), state_2.chunk_state.len());
+                assert_eq!(state_1.cv_stack, state_2.cv_stack);
+                assert_eq!(state_1.cv_stack_len, state_2.cv_stack_len);
+                assert_eq!(state_1.is_finalized, state_2.is_finalized);
+            }
+        }
+
+        #[test]
+        fn default_consistency_tests() {
+            let test_runner = StreamingContextConsistencyTester::<Digest, Blake3>::new(
+                Blake3::new(),
+                BLAKE3_BLOCKSIZE,
+            );
+            test_runner.run_all_tests();
+        }
+
+        // Proptests. Only executed when NOT testing no_std.
+        #[cfg(feature = "safe_api")]
+        mod proptest {
+            use super::*;
+
+            quickcheck! {
+                /// Test different streaming state usage patterns.
+                fn prop_input_to_consistency(data: Vec<u8>) -> bool {
+                    let test_runner = StreamingContextConsistencyTester::<Digest, Blake3>::new(
+                        Blake3::new(),
+                        BLAKE3_BLOCKSIZE,
+                    );
+                    test_runner.run_all_tests_property(&data);
+                    true
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_finalize_and_squeeze_state() {
+        let mut dest = [0u8; 64];
+
+        let mut state = Blake3::new();
+        assert!(state.finalize_into(&mut dest[..0]).is_err());
+        state.finalize_into(&mut dest).unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"data").is_err());
+        assert!(state.squeeze(&mut dest).is_err());
+
+        let mut state = Blake3::new();
+        state.squeeze(&mut dest[..10]).unwrap();
+        assert!(state.absorb(b"data").is_err());
+        assert!(state.finalize().is_err());
+        // Squeezing an empty buffer does nothing.
+        state.squeeze(&mut []).unwrap();
+        state.squeeze(&mut dest[10..]).unwrap();
+        assert_eq!(
+            &dest[..BLAKE3_OUTSIZE],
+            Blake3::digest(b"").unwrap().as_ref()
+        );
+
+        Xof::reset(&mut state);
+        state.update(b"data").unwrap();
+        assert_eq!(state.finalize().unwrap(), Blake3::digest(b"data").unwrap());
+    }
+
+    #[test]
+    fn test_output_is_prefix() {
+        let mut long = [0u8; 200];
+        let mut short = [0u8; 65];
+        let mut state = Blake3::new();
+        state.update(b"data").unwrap();
+        state.finalize_into(&mut long).unwrap();
+        state.reset();
+        state.update(b"data").unwrap();
+        state.finalize_into(&mut short).unwrap();
+        assert_eq!(&long[..65], &short[..]);
+    }
+
+    #[test]
+    fn test_keyed_mode() {
+        let secret_key = SecretKey::from_slice(&[7u8; BLAKE3_KEYSIZE]).unwrap();
+        let other_key = SecretKey::from_slice(&[8u8; BLAKE3_KEYSIZE]).unwrap();
+        let mac = Blake3::keyed_hash(&secret_key, b"data").unwrap();
+        assert_ne!(mac, Blake3::digest(b"data").unwrap());
+        assert!(Blake3::verify(&mac, &secret_key, b"data").is_ok());
+        assert!(Blake3::verify(&mac, &secret_key, b"datb").is_err());
+        assert!(Blake3::verify(&mac, &other_key, b"data").is_err());
+
+        // Resetting keeps the key.
+        let mut state = Blake3::new_keyed(&secret_key);
+        state.update(b"other").unwrap();
+        state.reset();
+        state.update(b"data").unwrap();
+        assert_eq!(state.finalize().unwrap(), mac);
+
+        assert!(SecretKey::from_slice(&[0u8; BLAKE3_KEYSIZE - 1]).is_err());
+        assert!(SecretKey::from_slice(&[0u8; BLAKE3_KEYSIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_derive_key() {
+        let mut key_a = [0u8; 32];
+        let mut key_b = [0u8; 32];
+        Blake3::derive_key("orion test context a", b"material", &mut key_a).unwrap();
+        Blake3::derive_key("orion test context b", b"material", &mut key_b).unwrap();
+        assert_ne!(key_a, key_b);
+        assert!(Blake3::derive_key("orion test context a", b"material", &mut []).is_err());
+
+        let mut state = Blake3::new_derive_key("orion test context a");
+        state.update(b"material").unwrap();
+        assert_eq!(state.finalize().unwrap().as_ref(), &key_a[..]);
+    }
+}
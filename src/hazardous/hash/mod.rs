@@ -23,5 +23,360 @@
 /// BLAKE2b as specified in the [RFC 7693](https://tools.ietf.org/html/rfc7693).
 pub mod blake2b;
 
+/// BLAKE3 as specified in the [BLAKE3 paper](https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf).
+pub mod blake3;
+
+/// BLAKE2s as specified in the [RFC 7693](https://tools.ietf.org/html/rfc7693).
+pub(crate) mod blake2s;
+
+/// SHA-1 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+pub(crate) mod sha1;
+
+/// SHA256 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+pub mod sha256;
+
+/// SHA384 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+pub mod sha384;
+
 /// SHA512 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
 pub mod sha512;
+
+/// SHA3-256, SHA3-384, SHA3-512, SHAKE128 and SHAKE256 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod sha3;
+
+/// A running hash over handshake messages, as used in TLS 1.3.
+pub mod transcript;
+
+use crate::errors::UnknownCryptoError;
+
+/// The largest block size (bytes) of any [`ShaHash`].
+///
+/// [`ShaHash`]: trait.ShaHash.html
+pub(crate) const MAX_SHA_BLOCKSIZE: usize = sha3::sha3_256::SHA3_256_RATE;
+/// The largest output size (bytes) of any [`ShaHash`].
+///
+/// [`ShaHash`]: trait.ShaHash.html
+pub(crate) const MAX_SHA_OUTSIZE: usize = 64;
+
+mod private {
+    /// Prevents implementations of [`ShaHash`](../trait.ShaHash.html) and
+    /// [`Xof`](../trait.Xof.html) outside of orion.
+    pub trait Sealed {}
+
+    impl Sealed for super::blake3::Blake3 {}
+    impl Sealed for super::sha1::Sha1 {}
+    impl Sealed for super::sha256::Sha256 {}
+    impl Sealed for super::sha384::Sha384 {}
+    impl Sealed for super::sha512::Sha512 {}
+    impl Sealed for super::sha3::sha3_256::Sha3_256 {}
+    impl Sealed for super::sha3::sha3_384::Sha3_384 {}
+    impl Sealed for super::sha3::sha3_512::Sha3_512 {}
+    impl Sealed for super::sha3::shake128::Shake128 {}
+    impl Sealed for super::sha3::shake256::Shake256 {}
+}
+
+/// A SHA-2 or SHA-3 hash function that HMAC, HKDF and PBKDF2 are built on.
+///
+/// This trait is sealed and cannot be implemented outside of orion. It allows
+/// writing code that is generic over the hash functions orion provides, and
+/// every hash function implementing it can be used with [`HmacGeneric`],
+/// [`Hkdf`] and, through [`HmacGeneric`], with [`derive_key_with_prf()`].
+///
+/// # Example:
+/// ```rust
+/// use orion::hazardous::hash::{sha512::Sha512, ShaHash};
+///
+/// fn hash_parts<H: ShaHash>(parts: &[&[u8]], dest: &mut [u8]) -> Result<(), orion::errors::UnknownCryptoError> {
+///     let mut state = H::default();
+///     for part in parts {
+///         ShaHash::update(&mut state, part)?;
+///     }
+///     ShaHash::finalize_into(&mut state, dest)
+/// }
+///
+/// let mut digest = [0u8; Sha512::OUTSIZE];
+/// hash_parts::<Sha512>(&[b"Hello ", b"world"], &mut digest)?;
+/// assert_eq!(Sha512::digest(b"Hello world")?.as_ref(), &digest[..]);
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`HmacGeneric`]: ../mac/hmac/struct.HmacGeneric.html
+/// [`Hkdf`]: ../kdf/hkdf/struct.Hkdf.html
+/// [`derive_key_with_prf()`]: ../kdf/pbkdf2/fn.derive_key_with_prf.html
+pub trait ShaHash: Clone + Default + Send + Sync + private::Sealed {
+    /// The block size (bytes) of the hash function.
+    const BLOCKSIZE: usize;
+    /// The output size (bytes) of the hash function.
+    const OUTSIZE: usize;
+
+    /// Reset to the initial state.
+    fn reset(&mut self);
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write the digest into `dest`, which must be `OUTSIZE` bytes.
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write the digest of `data` into `dest`, which must be `OUTSIZE` bytes.
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::default();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+}
+
+/// An extendable-output function (XOF), which absorbs data and can then be
+/// squeezed for output of any length.
+///
+/// This trait is sealed and cannot be implemented outside of orion. It allows
+/// writing code that is generic over the XOFs orion provides, such as
+/// deriving keys of arbitrary length. Under the `safe_api` feature, an
+/// [`XofReader`] reads the output through `std::io::Read`.
+///
+/// # Example:
+/// ```rust
+/// use orion::hazardous::hash::Xof;
+///
+/// /// Derive two keys of different lengths from `seed`.
+/// fn derive_keys<X: Xof>(
+///     mut xof: X,
+///     seed: &[u8],
+///     key_a: &mut [u8],
+///     key_b: &mut [u8],
+/// ) -> Result<(), orion::errors::UnknownCryptoError> {
+///     xof.absorb(seed)?;
+///     xof.squeeze(key_a)?;
+///     xof.squeeze(key_b)
+/// }
+/// ```
+/// [`XofReader`]: struct.XofReader.html
+pub trait Xof: Clone + Send + Sync + private::Sealed {
+    /// Reset to the initial state.
+    fn reset(&mut self);
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Absorb `data`. This can be called multiple times, but an error is
+    /// returned once output has been squeezed.
+    fn absorb(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Squeeze output into `dest`. This can be called multiple times, and each
+    /// call continues the output where the previous one ended.
+    fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError>;
+}
+
+#[cfg(feature = "safe_api")]
+#[derive(Clone)]
+/// Reads the output of an [`Xof`] through `std::io::Read`. Reading never
+/// reaches the end of the output.
+///
+/// [`Xof`]: trait.Xof.html
+pub struct XofReader<X: Xof> {
+    xof: X,
+}
+
+#[cfg(feature = "safe_api")]
+impl<X: Xof> core::fmt::Debug for XofReader<X> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "XofReader {{ xof: [***OMITTED***] }}")
+    }
+}
+
+#[cfg(feature = "safe_api")]
+impl<X: Xof> XofReader<X> {
+    /// Read the output of `xof`, after all data has been absorbed into it.
+    pub fn new(xof: X) -> Self {
+        Self { xof }
+    }
+
+    /// Return the `Xof`, to continue squeezing from it directly.
+    pub fn into_inner(self) -> X {
+        self.xof
+    }
+}
+
+#[cfg(feature = "safe_api")]
+impl<X: Xof> std::io::Read for XofReader<X> {
+    // `io::Error::other()` requires a newer MSRV.
+    #[allow(clippy::io_other_error)]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.xof
+            .squeeze(buf)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+
+        Ok(buf.len())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::hash::sha256::Sha256;
+    use crate::hazardous::hash::sha384::Sha384;
+    use crate::hazardous::hash::sha512::Sha512;
+
+    #[derive(Clone, Default)]
+    /// A toy XOF over SHA256 that outputs SHA256(data || counter) for each
+    /// block, used to test code that is generic over `Xof`.
+    struct TestXof {
+        data: [u8; 32],
+        len: usize,
+        buffer: [u8; 32],
+        position: usize,
+        counter: u8,
+        squeezing: bool,
+    }
+
+    impl private::Sealed for TestXof {}
+
+    impl Xof for TestXof {
+        fn reset(&mut self) {
+            *self = Self::default();
+        }
+
+        fn absorb(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+            if self.squeezing || self.len + data.len() > self.data.len() {
+                return Err(UnknownCryptoError);
+            }
+            self.data[self.len..self.len + data.len()].copy_from_slice(data);
+            self.len += data.len();
+            Ok(())
+        }
+
+        fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+            if !self.squeezing {
+                self.squeezing = true;
+                self.position = self.buffer.len();
+            }
+            for byte in dest.iter_mut() {
+                if self.position == self.buffer.len() {
+                    let mut state = Sha256::new();
+                    state.update(&self.data[..self.len])?;
+                    state.update(&[self.counter])?;
+                    state.finalize_into(&mut self.buffer)?;
+                    self.counter = self.counter.checked_add(1).ok_or(UnknownCryptoError)?;
+                    self.position = 0;
+                }
+                *byte = self.buffer[self.position];
+                self.position += 1;
+            }
+            Ok(())
+        }
+    }
+
+    fn squeeze_in_parts<X: Xof>(mut xof: X, parts: &[usize], dest: &mut [u8]) {
+        xof.absorb(b"Hello ").unwrap();
+        xof.absorb(b"world").unwrap();
+        let mut offset = 0;
+        for part in parts {
+            xof.squeeze(&mut dest[offset..offset + part]).unwrap();
+            offset += part;
+        }
+        assert!(xof.absorb(b"more").is_err());
+        xof.reset();
+        assert!(xof.absorb(b"more").is_ok());
+    }
+
+    #[test]
+    fn test_xof_squeeze_continues() {
+        let mut one_shot = [0u8; 100];
+        let mut parts = [0u8; 100];
+        squeeze_in_parts(TestXof::default(), &[100], &mut one_shot);
+        squeeze_in_parts(TestXof::default(), &[1, 31, 0, 33, 35], &mut parts);
+        assert_eq!(one_shot[..], parts[..]);
+        assert_ne!(one_shot[..32], one_shot[32..64]);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_xof_reader() {
+        use std::io::Read;
+
+        let mut xof = TestXof::default();
+        xof.absorb(b"Hello world").unwrap();
+        let mut expected = [0u8; 100];
+        xof.clone().squeeze(&mut expected).unwrap();
+
+        let mut reader = XofReader::new(xof);
+        assert_eq!(
+            format!("{:?}", reader),
+            "XofReader { xof: [***OMITTED***] }"
+        );
+        let mut actual = [0u8; 100];
+        assert_eq!(reader.read(&mut actual[..0]).unwrap(), 0);
+        reader.read_exact(&mut actual[..40]).unwrap();
+        assert_eq!(reader.read(&mut actual[40..]).unwrap(), 60);
+        assert_eq!(actual[..], expected[..]);
+
+        // Squeezing continues after the reader.
+        let mut xof = reader.into_inner();
+        let mut next = [0u8; 10];
+        xof.squeeze(&mut next).unwrap();
+        let mut longer = TestXof::default();
+        longer.absorb(b"Hello world").unwrap();
+        let mut expected = [0u8; 110];
+        longer.squeeze(&mut expected).unwrap();
+        assert_eq!(next[..], expected[100..]);
+
+        // Errors from the XOF are returned as `io::Error`. `TestXof` can
+        // only output 255 blocks.
+        let mut reader = XofReader::new(TestXof::default());
+        let mut buf = vec![0u8; 32 * 255];
+        assert!(reader.read(&mut buf).is_ok());
+        assert!(reader.read(&mut buf[..1]).is_err());
+    }
+
+    fn test_sha_hash<H: ShaHash>(data: &[u8], expected: &[u8]) {
+        assert!(H::BLOCKSIZE <= MAX_SHA_BLOCKSIZE);
+        assert!(H::OUTSIZE <= MAX_SHA_OUTSIZE);
+
+        let mut one_shot = [0u8; MAX_SHA_OUTSIZE];
+        H::digest_into(data, &mut one_shot[..H::OUTSIZE]).unwrap();
+        assert_eq!(&one_shot[..H::OUTSIZE], expected);
+
+        let mut state = H::default();
+        let mut streaming = [0u8; MAX_SHA_OUTSIZE];
+        state.update(&data[..1]).unwrap();
+        state.update(&data[1..]).unwrap();
+        state.finalize_into(&mut streaming[..H::OUTSIZE]).unwrap();
+        assert_eq!(&streaming[..H::OUTSIZE], expected);
+        assert!(state.finalize_into(&mut streaming[..H::OUTSIZE]).is_err());
+
+        state.reset();
+        state.update(data).unwrap();
+        assert!(state.finalize_into(&mut streaming[..H::OUTSIZE]).is_ok());
+        assert!(H::digest_into(data, &mut streaming[..H::OUTSIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_sha512() {
+        test_sha_hash::<Sha512>(
+            b"Hello world",
+            Sha512::digest(b"Hello world").unwrap().as_ref(),
+        );
+    }
+
+    #[test]
+    fn test_sha384() {
+        test_sha_hash::<Sha384>(
+            b"Hello world",
+            Sha384::digest(b"Hello world").unwrap().as_ref(),
+        );
+    }
+
+    #[test]
+    fn test_sha256() {
+        test_sha_hash::<Sha256>(
+            b"Hello world",
+            Sha256::digest(b"Hello world").unwrap().as_ref(),
+        );
+    }
+}
@@ -0,0 +1,311 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! SHA-1 as specified in [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+//!
+//! SHA-1 is not collision resistant and is only used internally, as
+//! HMAC-SHA1 by protocols that require it, such as SRTP. HMAC-SHA1 does not
+//! rely on collision resistance. Prefer [`sha256`] everywhere else.
+//!
+//! [`sha256`]: ../sha256/index.html
+
+use crate::{
+    errors::UnknownCryptoError,
+    util::endianness::{load_u32_into_be, store_u32_into_be},
+};
+use zeroize::Zeroize;
+
+/// The blocksize for the hash function SHA-1.
+pub(crate) const SHA1_BLOCKSIZE: usize = 64;
+/// The output size for the hash function SHA-1.
+pub(crate) const SHA1_OUTSIZE: usize = 20;
+
+#[allow(clippy::unreadable_literal)]
+/// The SHA-1 initial hash value H(0) as defined in FIPS 180-4.
+const H0: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+#[derive(Clone)]
+/// SHA-1 streaming state.
+pub(crate) struct Sha1 {
+    working_state: [u32; 5],
+    buffer: [u8; SHA1_BLOCKSIZE],
+    leftover: usize,
+    message_len: u64,
+    is_finalized: bool,
+}
+
+impl Drop for Sha1 {
+    fn drop(&mut self) {
+        self.working_state.zeroize();
+        self.buffer.zeroize();
+        self.message_len.zeroize();
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha1 {
+    #[allow(clippy::many_single_char_names)]
+    #[allow(clippy::unreadable_literal)]
+    /// Process a single `block` into `state`.
+    fn process_block(state: &mut [u32; 5], block: &[u8]) {
+        debug_assert!(block.len() == SHA1_BLOCKSIZE);
+        let mut w = [0u32; 80];
+        load_u32_into_be(block, &mut w[..16]);
+        for t in 16..80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let mut a = state[0];
+        let mut b = state[1];
+        let mut c = state[2];
+        let mut d = state[3];
+        let mut e = state[4];
+
+        for (t, wt) in w.iter().enumerate() {
+            let (f, k) = match t / 20 {
+                0 => (d ^ (b & (c ^ d)), 0x5a827999),
+                1 => (b ^ c ^ d, 0x6ed9eba1),
+                2 => ((b & c) | (d & (b | c)), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wt);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+        state[4] = state[4].wrapping_add(e);
+    }
+
+    /// Increment the message length, in bits, by `length` bytes.
+    fn increment_mlen(&mut self, length: u64) {
+        // If this panics size limit is reached.
+        self.message_len = self.message_len.checked_add(length * 8).unwrap();
+    }
+
+    /// Initialize a `Sha1` struct.
+    pub(crate) fn new() -> Self {
+        Self {
+            working_state: H0,
+            buffer: [0u8; SHA1_BLOCKSIZE],
+            leftover: 0,
+            message_len: 0,
+            is_finalized: false,
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub(crate) fn reset(&mut self) {
+        self.working_state = H0;
+        self.buffer = [0u8; SHA1_BLOCKSIZE];
+        self.leftover = 0;
+        self.message_len = 0;
+        self.is_finalized = false;
+    }
+
+    /// Update state with `data`. This can be called multiple times.
+    pub(crate) fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut bytes = data;
+        if self.leftover != 0 {
+            let want = core::cmp::min(SHA1_BLOCKSIZE - self.leftover, bytes.len());
+            self.buffer[self.leftover..self.leftover + want].copy_from_slice(&bytes[..want]);
+            bytes = &bytes[want..];
+            self.leftover += want;
+            self.increment_mlen(want as u64);
+
+            if self.leftover < SHA1_BLOCKSIZE {
+                return Ok(());
+            }
+
+            Self::process_block(&mut self.working_state, &self.buffer);
+            self.leftover = 0;
+        }
+
+        while bytes.len() >= SHA1_BLOCKSIZE {
+            Self::process_block(&mut self.working_state, &bytes[..SHA1_BLOCKSIZE]);
+            self.increment_mlen(SHA1_BLOCKSIZE as u64);
+            bytes = &bytes[SHA1_BLOCKSIZE..];
+        }
+
+        if !bytes.is_empty() {
+            self.buffer[..bytes.len()].copy_from_slice(bytes);
+            self.leftover = bytes.len();
+            self.increment_mlen(bytes.len() as u64);
+        }
+
+        Ok(())
+    }
+
+    /// Write a SHA-1 digest into `dest`, which must be `SHA1_OUTSIZE` bytes.
+    pub(crate) fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+        if dest.len() != SHA1_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.is_finalized = true;
+        debug_assert!(self.leftover < SHA1_BLOCKSIZE);
+        self.buffer[self.leftover] = 0x80;
+        for itm in self.buffer.iter_mut().skip(self.leftover + 1) {
+            *itm = 0;
+        }
+
+        // Check for available space for length padding.
+        if (SHA1_BLOCKSIZE - self.leftover - 1) < 8 {
+            Self::process_block(&mut self.working_state, &self.buffer);
+            self.buffer = [0u8; SHA1_BLOCKSIZE];
+        }
+
+        self.buffer[SHA1_BLOCKSIZE - 8..].copy_from_slice(&self.message_len.to_be_bytes());
+        Self::process_block(&mut self.working_state, &self.buffer);
+        store_u32_into_be(&self.working_state, dest);
+
+        Ok(())
+    }
+}
+
+impl super::ShaHash for Sha1 {
+    const BLOCKSIZE: usize = SHA1_BLOCKSIZE;
+    const OUTSIZE: usize = SHA1_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha1::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha1::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha1::finalize_into(self, dest)
+    }
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::mac::hmac::HmacGeneric;
+
+    fn test_runner(len: usize, expected: &str) {
+        let mut message = [0u8; 255];
+        for (i, byte) in message.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let message = &message[..len];
+        let expected = hex::decode(expected).unwrap();
+
+        let mut digest = [0u8; SHA1_OUTSIZE];
+        let mut state = Sha1::new();
+        state.update(message).unwrap();
+        state.finalize_into(&mut digest).unwrap();
+        assert_eq!(&digest[..], &expected[..]);
+
+        // Byte-by-byte updates.
+        state.reset();
+        for byte in message.iter() {
+            state.update(&[*byte]).unwrap();
+        }
+        state.finalize_into(&mut digest).unwrap();
+        assert_eq!(&digest[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_fips_abc() {
+        let mut digest = [0u8; SHA1_OUTSIZE];
+        let mut state = Sha1::new();
+        state.update(b"abc").unwrap();
+        state.finalize_into(&mut digest).unwrap();
+        assert_eq!(
+            digest.as_ref(),
+            &hex::decode("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_lengths() {
+        // Generated with Python's hashlib.
+        test_runner(0, "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        test_runner(1, "5ba93c9db0cff93f52b521d7420e43f6eda2784f");
+        test_runner(55, "8ae2d46729cfe68ff927af5eec9c7d1b66d65ac2");
+        test_runner(56, "636e2ec698dac903498e648bd2f3af641d3c88cb");
+        test_runner(63, "6d942da0c4392b123528f2905c713a3ce28364bd");
+        test_runner(64, "c6138d514ffa2135bfce0ed0b8fac65669917ec7");
+        test_runner(65, "69bd728ad6e13cd76ff19751fde427b00e395746");
+        test_runner(128, "e6434bc401f98603d7eda504790c98c67385d535");
+        test_runner(255, "fa2c27c443e60a0bcd8a1eb82d20fec20759c03e");
+    }
+
+    #[test]
+    fn test_finalize_state() {
+        let mut state = Sha1::new();
+        let mut digest = [0u8; SHA1_OUTSIZE];
+        assert!(state
+            .finalize_into(&mut digest[..SHA1_OUTSIZE - 1])
+            .is_err());
+        state.finalize_into(&mut digest).unwrap();
+        assert!(state.finalize_into(&mut digest).is_err());
+        assert!(state.update(b"abc").is_err());
+    }
+
+    #[test]
+    fn test_rfc2202_hmac_sha1() {
+        let mut tag = [0u8; SHA1_OUTSIZE];
+        let mut hmac = HmacGeneric::<Sha1>::new(&[0x0b; 20]).unwrap();
+        hmac.update(b"Hi There").unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("b617318655057264e28bc0b6fb378c8ef146be00").unwrap()[..]
+        );
+
+        let mut hmac = HmacGeneric::<Sha1>::new(&[0xaa; 80]).unwrap();
+        hmac.update(b"Test Using Larger Than Block-Size Key - Hash Key First")
+            .unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("aa4ae5e15272d00e95705637ce8a3b55ed402112").unwrap()[..]
+        );
+    }
+}
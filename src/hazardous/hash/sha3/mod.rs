@@ -0,0 +1,427 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The SHA3 hash functions and the SHAKE extendable-output functions, built on
+//! the Keccak-f\[1600\] permutation in the sponge construction. Each variant
+//! is in its own module.
+//!
+//! Unlike the SHA2 functions, the SHA3 functions are not vulnerable to length
+//! extension attacks, and their block size is the rate of the sponge.
+
+/// SHA3-256 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod sha3_256;
+
+/// SHA3-384 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod sha3_384;
+
+/// SHA3-512 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod sha3_512;
+
+/// SHAKE128 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod shake128;
+
+/// SHAKE256 as specified in the [FIPS PUB 202](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf).
+pub mod shake256;
+
+use crate::errors::UnknownCryptoError;
+
+/// The largest rate (bytes) of the variants, which is that of SHAKE128.
+const MAX_RATE: usize = 168;
+
+/// The domain separation bits of SHA3, followed by the first bit of the
+/// padding.
+const SHA3_DOMAIN: u8 = 0x06;
+
+/// The domain separation bits of SHAKE, followed by the first bit of the
+/// padding.
+const SHAKE_DOMAIN: u8 = 0x1f;
+
+/// The domain separation bits of cSHAKE, followed by the first bit of the
+/// padding.
+const CSHAKE_DOMAIN: u8 = 0x04;
+
+/// The number of 64-bit lanes in the Keccak state.
+const LANES: usize = 25;
+
+#[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
+/// The round constants of the iota step.
+const RC: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// The rotation offsets of the rho step, in the order lanes are visited by
+/// the pi step.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// The lanes visited by the pi step, starting from lane 1.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The Keccak-f\[1600\] permutation.
+fn keccak_f(a: &mut [u64; LANES]) {
+    for rc in RC.iter() {
+        // Theta
+        let mut c = [0u64; 5];
+        for (x, column) in c.iter_mut().enumerate() {
+            *column = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for lane in a.iter_mut().skip(x).step_by(5) {
+                *lane ^= d;
+            }
+        }
+
+        // Rho and pi
+        let mut last = a[1];
+        for (&lane, &rotation) in PI.iter().zip(RHO.iter()) {
+            let next = a[lane];
+            a[lane] = last.rotate_left(rotation);
+            last = next;
+        }
+
+        // Chi
+        for row in a.chunks_exact_mut(5) {
+            let mut copy = [0u64; 5];
+            copy.copy_from_slice(row);
+            for (x, lane) in row.iter_mut().enumerate() {
+                *lane = copy[x] ^ (!copy[(x + 1) % 5] & copy[(x + 2) % 5]);
+            }
+        }
+
+        // Iota
+        a[0] ^= rc;
+    }
+}
+
+/// XOR `block` into the first lanes of `state` and permute it.
+fn absorb_block(state: &mut [u64; LANES], block: &[u8]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(bytes);
+        *lane ^= u64::from_le_bytes(word);
+    }
+    keccak_f(state);
+}
+
+#[derive(Clone)]
+/// The Keccak sponge, shared by all variants.
+pub(crate) struct Sha3 {
+    state: [u64; LANES],
+    buffer: [u8; MAX_RATE],
+    // While absorbing, the number of bytes in `buffer`. Once finalized, the
+    // number of bytes of the current output block that have been squeezed.
+    leftover: usize,
+    rate: usize,
+    domain: u8,
+    is_finalized: bool,
+}
+
+impl Drop for Sha3 {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.state.zeroize();
+        self.buffer.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Sha3 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Sha3 {{ state: [***OMITTED***], buffer: [***OMITTED***], leftover: {:?}, rate: {:?}, \
+             is_finalized: {:?} }}",
+            self.leftover, self.rate, self.is_finalized
+        )
+    }
+}
+
+impl Sha3 {
+    /// Initialize a SHA3 sponge with a `rate` in bytes, which must be a
+    /// multiple of 8 and at most `MAX_RATE`.
+    pub(crate) fn new(rate: usize) -> Self {
+        Self::with_domain(rate, SHA3_DOMAIN)
+    }
+
+    /// Initialize a SHAKE sponge with a `rate` in bytes, which must be a
+    /// multiple of 8 and at most `MAX_RATE`.
+    pub(crate) fn new_shake(rate: usize) -> Self {
+        Self::with_domain(rate, SHAKE_DOMAIN)
+    }
+
+    /// Initialize a cSHAKE sponge with a `rate` in bytes, which must be a
+    /// multiple of 8 and at most `MAX_RATE`. The caller absorbs the encoded
+    /// function name and customization string, which must not both be empty.
+    pub(crate) fn new_cshake(rate: usize) -> Self {
+        Self::with_domain(rate, CSHAKE_DOMAIN)
+    }
+
+    fn with_domain(rate: usize, domain: u8) -> Self {
+        debug_assert!(rate & 7 == 0 && rate <= MAX_RATE);
+
+        Self {
+            state: [0u64; LANES],
+            buffer: [0u8; MAX_RATE],
+            leftover: 0,
+            rate,
+            domain,
+            is_finalized: false,
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub(crate) fn reset(&mut self) {
+        self.state = [0u64; LANES];
+        self.buffer = [0u8; MAX_RATE];
+        self.leftover = 0;
+        self.is_finalized = false;
+    }
+
+    /// Update state with `data`. This can be called multiple times.
+    pub(crate) fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut bytes = data;
+
+        if self.leftover != 0 {
+            let want = core::cmp::min(self.rate - self.leftover, bytes.len());
+            self.buffer[self.leftover..self.leftover + want].copy_from_slice(&bytes[..want]);
+            bytes = &bytes[want..];
+            self.leftover += want;
+
+            if self.leftover < self.rate {
+                return Ok(());
+            }
+
+            absorb_block(&mut self.state, &self.buffer[..self.rate]);
+            self.leftover = 0;
+        }
+
+        while bytes.len() >= self.rate {
+            absorb_block(&mut self.state, &bytes[..self.rate]);
+            bytes = &bytes[self.rate..];
+        }
+
+        if !bytes.is_empty() {
+            self.buffer[..bytes.len()].copy_from_slice(bytes);
+            self.leftover = bytes.len();
+        }
+
+        Ok(())
+    }
+
+    /// Pad the remaining data and absorb it, after which output can be
+    /// squeezed.
+    pub(crate) fn pad(&mut self) -> Result<(), UnknownCryptoError> {
+        if self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+
+        self.is_finalized = true;
+
+        // The domain separation bits, followed by pad10*1.
+        for byte in self.buffer[self.leftover..self.rate].iter_mut() {
+            *byte = 0;
+        }
+        self.buffer[self.leftover] ^= self.domain;
+        self.buffer[self.rate - 1] ^= 0x80;
+        absorb_block(&mut self.state, &self.buffer[..self.rate]);
+        self.leftover = 0;
+
+        Ok(())
+    }
+
+    /// Squeeze output into `dest`, continuing where the previous call ended.
+    /// This must be called after `pad()`.
+    pub(crate) fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if !self.is_finalized {
+            return Err(UnknownCryptoError);
+        }
+
+        for byte in dest.iter_mut() {
+            if self.leftover == self.rate {
+                keccak_f(&mut self.state);
+                self.leftover = 0;
+            }
+            *byte = (self.state[self.leftover / 8] >> (8 * (self.leftover % 8))) as u8;
+            self.leftover += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Pad the remaining data and write the first `dest.len()` bytes of the
+    /// output into `dest`.
+    pub(crate) fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.pad()?;
+        self.squeeze(dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::sha3_256::{Sha3_256, SHA3_256_OUTSIZE};
+    use super::sha3_512::{Sha3_512, SHA3_512_OUTSIZE};
+    use crate::hazardous::mac::hmac::HmacGeneric;
+
+    #[test]
+    fn test_hmac_sha3() {
+        let mut long_key = [0u8; 200];
+        for (i, byte) in long_key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        let mut tag = [0u8; SHA3_256_OUTSIZE];
+        let mut hmac = HmacGeneric::<Sha3_256>::new(&[0x0b; 20]).unwrap();
+        hmac.update(b"Hi There").unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("ba85192310dffa96e2a3a40e69774351140bb7185e1202cdcc917589f95e16bb")
+                .unwrap()[..]
+        );
+
+        // A key longer than the rate is hashed first.
+        let mut hmac = HmacGeneric::<Sha3_256>::new(&long_key).unwrap();
+        hmac.update(b"Test Using Larger Than Block-Size Key")
+            .unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("d8837e2ce3638ad4577441dfb453ad6fae5fb68ceb5ac0c55a5dc7968ba4ad6a")
+                .unwrap()[..]
+        );
+
+        let mut tag = [0u8; SHA3_512_OUTSIZE];
+        let mut hmac = HmacGeneric::<Sha3_512>::new(&[0x0b; 20]).unwrap();
+        hmac.update(b"Hi There").unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("eb3fbd4b2eaab8f5c504bd3a41465aacec15770a7cabac531e482f860b5ec7ba47ccb2c6f2afce8f88d22b6dc61380f23a668fd3888bb80537c0a0b86407689e")
+                .unwrap()[..]
+        );
+
+        let mut hmac = HmacGeneric::<Sha3_512>::new(&long_key).unwrap();
+        hmac.update(b"Test Using Larger Than Block-Size Key")
+            .unwrap();
+        hmac.finalize_into(&mut tag).unwrap();
+        assert_eq!(
+            tag.as_ref(),
+            &hex::decode("e374d6b7e5a69007c54b507ecd1096208fc2ee89e26f2ea6264580dec3d4b0fde322cb2b864202502608229d8bb79a6b7d1601aba70c7153009b7d5c6fa1c648")
+                .unwrap()[..]
+        );
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_keccak_f_zero_state() {
+        // The first lanes of Keccak-f[1600] applied to the all-zero state,
+        // from the Keccak team's KeccakF-1600-IntermediateValues.txt.
+        let mut state = [0u64; LANES];
+        keccak_f(&mut state);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+        assert_eq!(state[1], 0x84d5ccf933c0478a);
+        assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let state = Sha3::new(136);
+        let debug = format!("{:?}", state);
+        let expected = "Sha3 { state: [***OMITTED***], buffer: [***OMITTED***], leftover: 0, rate: 136, is_finalized: false }";
+        assert_eq!(debug, expected);
+    }
+
+    #[test]
+    fn test_finalize_twice_and_update_after_finalize() {
+        let mut state = Sha3::new(MAX_RATE);
+        state.update(b"data").unwrap();
+        state.finalize_into(&mut [0u8; 32]).unwrap();
+        assert!(state.finalize_into(&mut [0u8; 32]).is_err());
+        assert!(state.update(b"more").is_err());
+        state.reset();
+        assert!(state.update(b"more").is_ok());
+        assert!(state.squeeze(&mut [0u8; 32]).is_err());
+        assert!(state.pad().is_ok());
+        assert!(state.pad().is_err());
+        assert!(state.squeeze(&mut [0u8; 32]).is_ok());
+    }
+
+    #[test]
+    fn test_squeeze_across_blocks() {
+        let mut one_shot = Sha3::new_shake(MAX_RATE);
+        one_shot.update(b"data").unwrap();
+        let mut expected = [0u8; 3 * MAX_RATE + 1];
+        one_shot.finalize_into(&mut expected).unwrap();
+
+        let mut state = Sha3::new_shake(MAX_RATE);
+        state.update(b"data").unwrap();
+        state.pad().unwrap();
+        let mut actual = [0u8; 3 * MAX_RATE + 1];
+        let mut offset = 0;
+        for size in [1, MAX_RATE - 1, 0, MAX_RATE, MAX_RATE + 1].iter() {
+            state.squeeze(&mut actual[offset..offset + size]).unwrap();
+            offset += size;
+        }
+        assert_eq!(offset, actual.len());
+        assert_eq!(actual[..], expected[..]);
+    }
+
+    #[test]
+    fn test_leftover_at_block_boundaries() {
+        let data = [0xa3u8; 3 * MAX_RATE + 1];
+        let mut one_shot = Sha3::new(MAX_RATE);
+        one_shot.update(&data).unwrap();
+        let mut expected = [0u8; 32];
+        one_shot.finalize_into(&mut expected).unwrap();
+
+        for split in [1, MAX_RATE - 1, MAX_RATE, MAX_RATE + 1, 2 * MAX_RATE].iter() {
+            let mut state = Sha3::new(MAX_RATE);
+            state.update(&data[..*split]).unwrap();
+            state.update(&[]).unwrap();
+            state.update(&data[*split..]).unwrap();
+            let mut actual = [0u8; 32];
+            state.finalize_into(&mut actual).unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+}
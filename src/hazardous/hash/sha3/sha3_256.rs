@@ -0,0 +1,267 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] or [`digest_into()`]
+//!   is not 32 bytes.
+//! - The digest does not match `expected` when verifying.
+//!
+//! # Security:
+//! - SHA3-256 is not vulnerable to length extension attacks.
+//!
+//! # Recommendation:
+//! - It is recommended to use [BLAKE2b] when possible. SHA3-256 is provided
+//!   for protocols that require it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::sha3::sha3_256::Sha3_256;
+//!
+//! // Using the streaming interface
+//! let mut state = Sha3_256::new();
+//! state.update(b"Hello world")?;
+//! let hash = state.finalize()?;
+//!
+//! // Using the one-shot function
+//! let hash_one_shot = Sha3_256::digest(b"Hello world")?;
+//!
+//! assert_eq!(hash, hash_one_shot);
+//! assert!(Sha3_256::verify(&hash, b"Hello world").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`update()`]: struct.Sha3_256.html
+//! [`reset()`]: struct.Sha3_256.html
+//! [`finalize()`]: struct.Sha3_256.html
+//! [`finalize_into()`]: struct.Sha3_256.html
+//! [`digest_into()`]: struct.Sha3_256.html
+//! [BLAKE2b]: ../../blake2b/index.html
+
+use super::Sha3;
+use crate::errors::UnknownCryptoError;
+
+/// The rate of SHA3-256, which is its block size.
+pub const SHA3_256_RATE: usize = 136;
+/// The output size for the hash function SHA3-256.
+pub const SHA3_256_OUTSIZE: usize = 32;
+
+construct_public! {
+    /// A type to represent the `Digest` that SHA3-256 returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (Digest, test_digest, SHA3_256_OUTSIZE, SHA3_256_OUTSIZE)
+}
+
+impl_from_trait!(Digest, SHA3_256_OUTSIZE);
+
+#[derive(Clone)]
+/// SHA3-256 streaming state.
+pub struct Sha3_256 {
+    inner: Sha3,
+}
+
+impl core::fmt::Debug for Sha3_256 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sha3_256 {{ inner: {:?} }}", self.inner)
+    }
+}
+
+impl Default for Sha3_256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha3_256 {
+    /// Initialize a `Sha3_256` struct.
+    pub fn new() -> Self {
+        Self {
+            inner: Sha3::new(SHA3_256_RATE),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a SHA3-256 digest.
+    pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; SHA3_256_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-256 digest into `dest`, which must be `SHA3_256_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != SHA3_256_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.inner.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a SHA3-256 digest of some `data`.
+    pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-256 digest of some `data` into `dest`, which must be
+    /// `SHA3_256_OUTSIZE` bytes.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA3-256 digest of `data`.
+    pub fn verify(expected: &Digest, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::digest(data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl crate::hazardous::hash::ShaHash for Sha3_256 {
+    const BLOCKSIZE: usize = SHA3_256_RATE;
+    const OUTSIZE: usize = SHA3_256_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha3_256::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_256::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_256::finalize_into(self, dest)
+    }
+
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_256::digest_into(data, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_fips_202_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (
+                b"",
+                "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            ),
+            (
+                b"abc",
+                "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            ),
+            (
+                &[0xa3; 200],
+                "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+            ),
+        ];
+
+        for (data, expected) in vectors.iter() {
+            let expected = hex::decode(expected).unwrap();
+            assert_eq!(Sha3_256::digest(data).unwrap().as_ref(), &expected[..]);
+
+            let mut state = Sha3_256::new();
+            for byte in data.iter() {
+                state.update(&[*byte]).unwrap();
+            }
+            assert_eq!(state.finalize().unwrap().as_ref(), &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_reset_and_finalize_twice() {
+        let mut state = Sha3_256::new();
+        state.update(b"Hello world").unwrap();
+        let first = state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"more").is_err());
+
+        state.reset();
+        state.update(b"Hello world").unwrap();
+        assert_eq!(state.finalize().unwrap(), first);
+        assert_eq!(
+            Sha3_256::default().finalize().unwrap(),
+            Sha3_256::digest(b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let expected = Sha3_256::digest(b"Hello world").unwrap();
+        assert!(Sha3_256::verify(&expected, b"Hello world").is_ok());
+        assert!(Sha3_256::verify(&expected, b"Hello world!").is_err());
+        assert!(Sha3_256::verify(&expected, b"").is_err());
+    }
+
+    #[test]
+    fn test_finalize_into_wrong_length_err() {
+        let mut state = Sha3_256::new();
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_256_OUTSIZE - 1])
+            .is_err());
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_256_OUTSIZE + 1])
+            .is_err());
+        assert!(Sha3_256::digest_into(b"", &mut [0u8; 0]).is_err());
+        // The state has not been finalized by the failed calls.
+        assert!(state.finalize_into(&mut [0u8; SHA3_256_OUTSIZE]).is_ok());
+        assert!(state.finalize_into(&mut [0u8; SHA3_256_OUTSIZE]).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] or [`digest_into()`]
+//!   is not 48 bytes.
+//! - The digest does not match `expected` when verifying.
+//!
+//! # Security:
+//! - SHA3-384 is not vulnerable to length extension attacks.
+//!
+//! # Recommendation:
+//! - It is recommended to use [BLAKE2b] when possible. SHA3-384 is provided
+//!   for protocols that require it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::sha3::sha3_384::Sha3_384;
+//!
+//! // Using the streaming interface
+//! let mut state = Sha3_384::new();
+//! state.update(b"Hello world")?;
+//! let hash = state.finalize()?;
+//!
+//! // Using the one-shot function
+//! let hash_one_shot = Sha3_384::digest(b"Hello world")?;
+//!
+//! assert_eq!(hash, hash_one_shot);
+//! assert!(Sha3_384::verify(&hash, b"Hello world").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`update()`]: struct.Sha3_384.html
+//! [`reset()`]: struct.Sha3_384.html
+//! [`finalize()`]: struct.Sha3_384.html
+//! [`finalize_into()`]: struct.Sha3_384.html
+//! [`digest_into()`]: struct.Sha3_384.html
+//! [BLAKE2b]: ../../blake2b/index.html
+
+use super::Sha3;
+use crate::errors::UnknownCryptoError;
+
+/// The rate of SHA3-384, which is its block size.
+pub const SHA3_384_RATE: usize = 104;
+/// The output size for the hash function SHA3-384.
+pub const SHA3_384_OUTSIZE: usize = 48;
+
+construct_public! {
+    /// A type to represent the `Digest` that SHA3-384 returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 48 bytes.
+    (Digest, test_digest, SHA3_384_OUTSIZE, SHA3_384_OUTSIZE)
+}
+
+impl_from_trait!(Digest, SHA3_384_OUTSIZE);
+
+#[derive(Clone)]
+/// SHA3-384 streaming state.
+pub struct Sha3_384 {
+    inner: Sha3,
+}
+
+impl core::fmt::Debug for Sha3_384 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sha3_384 {{ inner: {:?} }}", self.inner)
+    }
+}
+
+impl Default for Sha3_384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha3_384 {
+    /// Initialize a `Sha3_384` struct.
+    pub fn new() -> Self {
+        Self {
+            inner: Sha3::new(SHA3_384_RATE),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a SHA3-384 digest.
+    pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; SHA3_384_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-384 digest into `dest`, which must be `SHA3_384_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != SHA3_384_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.inner.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a SHA3-384 digest of some `data`.
+    pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-384 digest of some `data` into `dest`, which must be
+    /// `SHA3_384_OUTSIZE` bytes.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA3-384 digest of `data`.
+    pub fn verify(expected: &Digest, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::digest(data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl crate::hazardous::hash::ShaHash for Sha3_384 {
+    const BLOCKSIZE: usize = SHA3_384_RATE;
+    const OUTSIZE: usize = SHA3_384_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha3_384::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_384::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_384::finalize_into(self, dest)
+    }
+
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_384::digest_into(data, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_fips_202_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (
+                b"",
+                "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004",
+            ),
+            (
+                b"abc",
+                "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+            ),
+            (
+                &[0xa3; 200],
+                "1881de2ca7e41ef95dc4732b8f5f002b189cc1e42b74168ed1732649ce1dbcdd76197a31fd55ee989f2d7050dd473e8f",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "991c665755eb3a4b6bbdfb75c78a492e8c56a22c5c4d7e429bfdbc32b9d4ad5aa04a1f076e62fea19eef51acd0657c22",
+            ),
+        ];
+
+        for (data, expected) in vectors.iter() {
+            let expected = hex::decode(expected).unwrap();
+            assert_eq!(Sha3_384::digest(data).unwrap().as_ref(), &expected[..]);
+
+            let mut state = Sha3_384::new();
+            for byte in data.iter() {
+                state.update(&[*byte]).unwrap();
+            }
+            assert_eq!(state.finalize().unwrap().as_ref(), &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_reset_and_finalize_twice() {
+        let mut state = Sha3_384::new();
+        state.update(b"Hello world").unwrap();
+        let first = state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"more").is_err());
+
+        state.reset();
+        state.update(b"Hello world").unwrap();
+        assert_eq!(state.finalize().unwrap(), first);
+        assert_eq!(
+            Sha3_384::default().finalize().unwrap(),
+            Sha3_384::digest(b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let expected = Sha3_384::digest(b"Hello world").unwrap();
+        assert!(Sha3_384::verify(&expected, b"Hello world").is_ok());
+        assert!(Sha3_384::verify(&expected, b"Hello world!").is_err());
+        assert!(Sha3_384::verify(&expected, b"").is_err());
+    }
+
+    #[test]
+    fn test_finalize_into_wrong_length_err() {
+        let mut state = Sha3_384::new();
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_384_OUTSIZE - 1])
+            .is_err());
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_384_OUTSIZE + 1])
+            .is_err());
+        assert!(Sha3_384::digest_into(b"", &mut [0u8; 0]).is_err());
+        // The state has not been finalized by the failed calls.
+        assert!(state.finalize_into(&mut [0u8; SHA3_384_OUTSIZE]).is_ok());
+        assert!(state.finalize_into(&mut [0u8; SHA3_384_OUTSIZE]).is_err());
+    }
+}
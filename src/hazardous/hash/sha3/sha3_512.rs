@@ -0,0 +1,267 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] or [`digest_into()`]
+//!   is not 64 bytes.
+//! - The digest does not match `expected` when verifying.
+//!
+//! # Security:
+//! - SHA3-512 is not vulnerable to length extension attacks.
+//!
+//! # Recommendation:
+//! - It is recommended to use [BLAKE2b] when possible. SHA3-512 is provided
+//!   for protocols that require it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::sha3::sha3_512::Sha3_512;
+//!
+//! // Using the streaming interface
+//! let mut state = Sha3_512::new();
+//! state.update(b"Hello world")?;
+//! let hash = state.finalize()?;
+//!
+//! // Using the one-shot function
+//! let hash_one_shot = Sha3_512::digest(b"Hello world")?;
+//!
+//! assert_eq!(hash, hash_one_shot);
+//! assert!(Sha3_512::verify(&hash, b"Hello world").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`update()`]: struct.Sha3_512.html
+//! [`reset()`]: struct.Sha3_512.html
+//! [`finalize()`]: struct.Sha3_512.html
+//! [`finalize_into()`]: struct.Sha3_512.html
+//! [`digest_into()`]: struct.Sha3_512.html
+//! [BLAKE2b]: ../../blake2b/index.html
+
+use super::Sha3;
+use crate::errors::UnknownCryptoError;
+
+/// The rate of SHA3-512, which is its block size.
+pub const SHA3_512_RATE: usize = 72;
+/// The output size for the hash function SHA3-512.
+pub const SHA3_512_OUTSIZE: usize = 64;
+
+construct_public! {
+    /// A type to represent the `Digest` that SHA3-512 returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 64 bytes.
+    (Digest, test_digest, SHA3_512_OUTSIZE, SHA3_512_OUTSIZE)
+}
+
+impl_from_trait!(Digest, SHA3_512_OUTSIZE);
+
+#[derive(Clone)]
+/// SHA3-512 streaming state.
+pub struct Sha3_512 {
+    inner: Sha3,
+}
+
+impl core::fmt::Debug for Sha3_512 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sha3_512 {{ inner: {:?} }}", self.inner)
+    }
+}
+
+impl Default for Sha3_512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha3_512 {
+    /// Initialize a `Sha3_512` struct.
+    pub fn new() -> Self {
+        Self {
+            inner: Sha3::new(SHA3_512_RATE),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a SHA3-512 digest.
+    pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; SHA3_512_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-512 digest into `dest`, which must be `SHA3_512_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != SHA3_512_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.inner.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a SHA3-512 digest of some `data`.
+    pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA3-512 digest of some `data` into `dest`, which must be
+    /// `SHA3_512_OUTSIZE` bytes.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA3-512 digest of `data`.
+    pub fn verify(expected: &Digest, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::digest(data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl crate::hazardous::hash::ShaHash for Sha3_512 {
+    const BLOCKSIZE: usize = SHA3_512_RATE;
+    const OUTSIZE: usize = SHA3_512_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha3_512::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_512::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_512::finalize_into(self, dest)
+    }
+
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha3_512::digest_into(data, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_fips_202_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (
+                b"",
+                "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+            ),
+            (
+                b"abc",
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+            ),
+            (
+                &[0xa3; 200],
+                "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca81b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00",
+            ),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636dee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e",
+            ),
+        ];
+
+        for (data, expected) in vectors.iter() {
+            let expected = hex::decode(expected).unwrap();
+            assert_eq!(Sha3_512::digest(data).unwrap().as_ref(), &expected[..]);
+
+            let mut state = Sha3_512::new();
+            for byte in data.iter() {
+                state.update(&[*byte]).unwrap();
+            }
+            assert_eq!(state.finalize().unwrap().as_ref(), &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_reset_and_finalize_twice() {
+        let mut state = Sha3_512::new();
+        state.update(b"Hello world").unwrap();
+        let first = state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"more").is_err());
+
+        state.reset();
+        state.update(b"Hello world").unwrap();
+        assert_eq!(state.finalize().unwrap(), first);
+        assert_eq!(
+            Sha3_512::default().finalize().unwrap(),
+            Sha3_512::digest(b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let expected = Sha3_512::digest(b"Hello world").unwrap();
+        assert!(Sha3_512::verify(&expected, b"Hello world").is_ok());
+        assert!(Sha3_512::verify(&expected, b"Hello world!").is_err());
+        assert!(Sha3_512::verify(&expected, b"").is_err());
+    }
+
+    #[test]
+    fn test_finalize_into_wrong_length_err() {
+        let mut state = Sha3_512::new();
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_512_OUTSIZE - 1])
+            .is_err());
+        assert!(state
+            .finalize_into(&mut [0u8; SHA3_512_OUTSIZE + 1])
+            .is_err());
+        assert!(Sha3_512::digest_into(b"", &mut [0u8; 0]).is_err());
+        // The state has not been finalized by the failed calls.
+        assert!(state.finalize_into(&mut [0u8; SHA3_512_OUTSIZE]).is_ok());
+        assert!(state.finalize_into(&mut [0u8; SHA3_512_OUTSIZE]).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be absorbed.
+//! - `dest`: Destination buffer for the output, which can be of any length.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`absorb()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - [`squeeze()`] is called before [`finalize()`].
+//! - `dest` passed to [`digest_into()`] is empty.
+//!
+//! # Security:
+//! - SHAKE128 provides 128-bit security against collision and preimage
+//!   attacks, if the output is at least 32 bytes long. Shorter output gives
+//!   correspondingly less security.
+//! - Output squeezed in several calls is the same as output squeezed in a
+//!   single call of the same total length. A shorter output is a prefix of a
+//!   longer output of the same input, so output lengths must not be chosen
+//!   by an attacker where this matters.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::sha3::shake128::Shake128;
+//!
+//! let mut state = Shake128::new();
+//! state.absorb(b"Hello world")?;
+//! state.finalize()?;
+//!
+//! let mut first = [0u8; 32];
+//! let mut second = [0u8; 100];
+//! state.squeeze(&mut first)?;
+//! state.squeeze(&mut second)?;
+//!
+//! let mut one_shot = [0u8; 132];
+//! Shake128::digest_into(b"Hello world", &mut one_shot)?;
+//! assert_eq!(&one_shot[..32], &first[..]);
+//! assert_eq!(&one_shot[32..], &second[..]);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`absorb()`]: struct.Shake128.html
+//! [`reset()`]: struct.Shake128.html
+//! [`finalize()`]: struct.Shake128.html
+//! [`squeeze()`]: struct.Shake128.html
+//! [`digest_into()`]: struct.Shake128.html
+
+use super::Sha3;
+use crate::errors::UnknownCryptoError;
+
+/// The rate of SHAKE128.
+pub const SHAKE128_RATE: usize = 168;
+
+#[derive(Clone)]
+/// SHAKE128 streaming state.
+pub struct Shake128 {
+    inner: Sha3,
+}
+
+impl core::fmt::Debug for Shake128 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Shake128 {{ inner: {:?} }}", self.inner)
+    }
+}
+
+impl Default for Shake128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shake128 {
+    /// Initialize a `Shake128` struct.
+    pub fn new() -> Self {
+        Self {
+            inner: Sha3::new_shake(SHAKE128_RATE),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Absorb `data`. This can be called multiple times, until `finalize()`.
+    pub fn absorb(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// End absorbing, after which output can be squeezed.
+    pub fn finalize(&mut self) -> Result<(), UnknownCryptoError> {
+        self.inner.pad()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Squeeze output into `dest`. This can be called multiple times, and each
+    /// call continues the output where the previous one ended.
+    pub fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.squeeze(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write `dest.len()` bytes of SHAKE128 output of some `data` into `dest`.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut state = Self::new();
+        state.absorb(data)?;
+        state.finalize()?;
+        state.squeeze(dest)
+    }
+}
+
+impl crate::hazardous::hash::Xof for Shake128 {
+    fn reset(&mut self) {
+        Shake128::reset(self)
+    }
+
+    fn absorb(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Shake128::absorb(self, data)
+    }
+
+    fn squeeze(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if !self.inner.is_finalized {
+            self.inner.pad()?;
+        }
+
+        Shake128::squeeze(self, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::hash::Xof;
+
+    #[test]
+    fn test_fips_202_vectors() {
+        let vectors: [(&[u8], &str); 3] = [
+            (
+                b"",
+                "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef263cb1eea988004b93103cfb0aeefd2a686e01fa4a58e8a3639ca8a1e3f9ae57e2",
+            ),
+            (
+                b"abc",
+                "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc844c50af32acd3f2cdd066568706f509bc1bdde58295dae3f891a9a0fca578378",
+            ),
+            (
+                &[0xa3; 200],
+                "131ab8d2b594946b9c81333f9bb6e0ce75c3b93104fa3469d3917457385da037cf232ef7164a6d1eb448c8908186ad852d3f85a5cf28da1ab6fe343817197846",
+            ),
+        ];
+
+        for (data, expected) in vectors.iter() {
+            let expected = hex::decode(expected).unwrap();
+            let mut actual = [0u8; 64];
+            Shake128::digest_into(data, &mut actual).unwrap();
+            assert_eq!(&actual[..], &expected[..]);
+
+            let mut state = Shake128::new();
+            for byte in data.iter() {
+                state.absorb(&[*byte]).unwrap();
+            }
+            state.finalize().unwrap();
+            for chunk in actual.chunks_mut(7) {
+                state.squeeze(chunk).unwrap();
+            }
+            assert_eq!(&actual[..], &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_state_transitions() {
+        let mut state = Shake128::new();
+        state.absorb(b"Hello world").unwrap();
+        assert!(state.squeeze(&mut [0u8; 32]).is_err());
+        state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.absorb(b"more").is_err());
+        let mut first = [0u8; 32];
+        state.squeeze(&mut first).unwrap();
+        state.squeeze(&mut []).unwrap();
+
+        state.reset();
+        state.absorb(b"Hello world").unwrap();
+        state.finalize().unwrap();
+        let mut second = [0u8; 32];
+        state.squeeze(&mut second).unwrap();
+        assert_eq!(first, second);
+
+        assert!(Shake128::digest_into(b"", &mut []).is_err());
+    }
+
+    #[test]
+    fn test_xof_squeeze_finalizes() {
+        let mut expected = [0u8; 2 * SHAKE128_RATE];
+        Shake128::digest_into(b"Hello world", &mut expected).unwrap();
+
+        let mut state = Shake128::default();
+        Xof::absorb(&mut state, b"Hello world").unwrap();
+        let mut actual = [0u8; 2 * SHAKE128_RATE];
+        Xof::squeeze(&mut state, &mut actual[..1]).unwrap();
+        Xof::squeeze(&mut state, &mut actual[1..]).unwrap();
+        assert_eq!(actual[..], expected[..]);
+        assert!(Xof::absorb(&mut state, b"more").is_err());
+        Xof::reset(&mut state);
+        assert!(Xof::absorb(&mut state, b"more").is_ok());
+    }
+}
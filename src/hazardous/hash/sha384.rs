@@ -0,0 +1,279 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] or [`digest_into()`]
+//!   is not 48 bytes.
+//! - The digest does not match `expected` when verifying.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2*(2^64-1) __bits__ of data are hashed.
+//!
+//! # Security:
+//! - SHA384 is SHA512 with a different initial hash value, truncated to 48
+//!   bytes. The truncation means it is not vulnerable to length extension
+//!   attacks.
+//!
+//! # Recommendation:
+//! - It is recommended to use [BLAKE2b] when possible. SHA384 is provided
+//!   for protocols that require it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::sha384::Sha384;
+//!
+//! // Using the streaming interface
+//! let mut state = Sha384::new();
+//! state.update(b"Hello world")?;
+//! let hash = state.finalize()?;
+//!
+//! // Using the one-shot function
+//! let hash_one_shot = Sha384::digest(b"Hello world")?;
+//!
+//! assert_eq!(hash, hash_one_shot);
+//! assert!(Sha384::verify(&hash, b"Hello world").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`update()`]: struct.Sha384.html
+//! [`reset()`]: struct.Sha384.html
+//! [`finalize()`]: struct.Sha384.html
+//! [`finalize_into()`]: struct.Sha384.html
+//! [`digest_into()`]: struct.Sha384.html
+//! [BLAKE2b]: ../blake2b/index.html
+
+use super::sha512::{Sha512, SHA512_BLOCKSIZE, SHA512_OUTSIZE};
+use crate::errors::UnknownCryptoError;
+use zeroize::Zeroize;
+
+/// The blocksize for the hash function SHA384.
+pub const SHA384_BLOCKSIZE: usize = SHA512_BLOCKSIZE;
+/// The output size for the hash function SHA384.
+pub const SHA384_OUTSIZE: usize = 48;
+
+construct_public! {
+    /// A type to represent the `Digest` that SHA384 returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 48 bytes.
+    (Digest, test_digest, SHA384_OUTSIZE, SHA384_OUTSIZE)
+}
+
+impl_from_trait!(Digest, SHA384_OUTSIZE);
+
+#[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
+/// The SHA384 initial hash value H(0) as defined in FIPS 180-4.
+const H0: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+#[derive(Clone)]
+/// SHA384 streaming state.
+pub struct Sha384 {
+    inner: Sha512,
+}
+
+impl core::fmt::Debug for Sha384 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sha384 {{ inner: {:?} }}", self.inner)
+    }
+}
+
+impl Default for Sha384 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha384 {
+    /// Initialize a `Sha384` struct.
+    pub fn new() -> Self {
+        Self {
+            inner: Sha512::with_initial_state(H0),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner = Sha512::with_initial_state(H0);
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a SHA384 digest.
+    pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; SHA384_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA384 digest into `dest`, which must be `SHA384_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != SHA384_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut full = [0u8; SHA512_OUTSIZE];
+        self.inner.finalize_into(&mut full)?;
+        dest.copy_from_slice(&full[..SHA384_OUTSIZE]);
+        full.zeroize();
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Calculate a SHA384 digest of some `data`.
+    pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA384 digest of some `data` into `dest`, which must be
+    /// `SHA384_OUTSIZE` bytes.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA384 digest of `data`.
+    pub fn verify(expected: &Digest, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::digest(data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl super::ShaHash for Sha384 {
+    const BLOCKSIZE: usize = SHA384_BLOCKSIZE;
+    const OUTSIZE: usize = SHA384_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha384::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha384::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha384::finalize_into(self, dest)
+    }
+
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha384::digest_into(data, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_fips_180_4_vectors() {
+        let vectors: [(&[u8], &str); 3] = [
+            (
+                b"abc",
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            ),
+            (
+                b"",
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+            ),
+            (
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+            ),
+        ];
+
+        for (data, expected) in vectors.iter() {
+            let expected = hex::decode(expected).unwrap();
+            assert_eq!(Sha384::digest(data).unwrap().as_ref(), &expected[..]);
+
+            let mut state = Sha384::new();
+            for byte in data.iter() {
+                state.update(&[*byte]).unwrap();
+            }
+            assert_eq!(state.finalize().unwrap().as_ref(), &expected[..]);
+        }
+    }
+
+    #[test]
+    fn test_reset_and_finalize_twice() {
+        let mut state = Sha384::new();
+        state.update(b"Hello world").unwrap();
+        let first = state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"more").is_err());
+
+        state.reset();
+        state.update(b"Hello world").unwrap();
+        assert_eq!(state.finalize().unwrap(), first);
+        assert_eq!(
+            Sha384::default().finalize().unwrap(),
+            Sha384::digest(b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let expected = Sha384::digest(b"Hello world").unwrap();
+        assert!(Sha384::verify(&expected, b"Hello world").is_ok());
+        assert!(Sha384::verify(&expected, b"Hello world!").is_err());
+        assert!(Sha384::verify(&expected, b"").is_err());
+    }
+
+    #[test]
+    fn test_finalize_into_wrong_length_err() {
+        let mut state = Sha384::new();
+        assert!(state.finalize_into(&mut [0u8; SHA384_OUTSIZE - 1]).is_err());
+        assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_err());
+        assert!(Sha384::digest_into(b"", &mut [0u8; 0]).is_err());
+        // The state has not been finalized by the failed calls.
+        assert!(state.finalize_into(&mut [0u8; SHA384_OUTSIZE]).is_ok());
+        assert!(state.finalize_into(&mut [0u8; SHA384_OUTSIZE]).is_err());
+    }
+}
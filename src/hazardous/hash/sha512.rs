@@ -22,12 +22,21 @@
 
 //! # Parameters:
 //! - `data`: The data to be hashed.
+//! - `expected`: The expected digest when verifying.
+//! - `reader`: The source of the data to be hashed when verifying.
+//! - `state`: The chaining value that [`compress()`] updates.
+//! - `block`: A single block to be processed by [`compress()`].
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - [`finalize()`] is called twice without a [`reset()`] in between.
 //! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
 //!   between.
+//! - The length of `dest` passed to [`finalize_into()`] or [`digest_into()`]
+//!   is not 64 bytes.
+//! - The digest does not match `expected` when verifying.
+//! - Reading from `reader` fails when verifying.
+//! - The length of `block` passed to [`compress()`] is not 128 bytes.
 //!
 //! # Panics:
 //! A panic will occur if:
@@ -35,6 +44,13 @@
 //!
 //! # Security:
 //! - SHA512 is vulnerable to length extension attacks.
+//! - [`verify_stream()`] only returns once all data has been read. Data read
+//!   from `reader` must not be used until it has returned `Ok`, such as when
+//!   verifying a download against a published checksum.
+//! - [`compress()`] is the raw compression function, without padding or
+//!   length encoding. Hashing data with it directly is not SHA512, and is
+//!   only secure as part of a construction that is designed for it. Use
+//!   [`Sha512`] to hash data.
 //!
 //! # Recommendation:
 //! - It is recommended to use [BLAKE2b] when possible.
@@ -52,11 +68,26 @@
 //! let hash_one_shot = Sha512::digest(b"Hello world")?;
 //!
 //! assert_eq!(hash, hash_one_shot);
+//!
+//! // Writing the digest directly into a buffer
+//! let mut dest = [0u8; 64];
+//! Sha512::digest_into(b"Hello world", &mut dest)?;
+//!
+//! assert_eq!(hash.as_ref(), &dest[..]);
+//!
+//! // Verifying data, or all data read from a stream, against a digest
+//! assert!(Sha512::verify(&hash, b"Hello world").is_ok());
+//! assert!(Sha512::verify_stream(&hash, &b"Hello world"[..]).is_ok());
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
 //! [`update()`]: struct.Sha512.html
 //! [`reset()`]: struct.Sha512.html
 //! [`finalize()`]: struct.Sha512.html
+//! [`finalize_into()`]: struct.Sha512.html
+//! [`digest_into()`]: struct.Sha512.html
+//! [`compress()`]: fn.compress.html
+//! [`verify_stream()`]: struct.Sha512.html#method.verify_stream
+//! [`Sha512`]: struct.Sha512.html
 //! [BLAKE2b]: ../blake2b/index.html
 
 use crate::{
@@ -211,16 +242,11 @@ impl Sha512 {
 
     #[rustfmt::skip]
 	#[allow(clippy::many_single_char_names)]
-    /// Process data in `self.buffer` or optionally `data`.
-    fn process(&mut self, data: Option<&[u8]>) {
+    /// Process a single `block` into `state`.
+    fn process_block(state: &mut [u64; 8], block: &[u8]) {
 		let mut w = [0u64; 80];
-		match data {
-			Some(bytes) => {
-				debug_assert!(bytes.len() == SHA512_BLOCKSIZE);
-				load_u64_into_be(bytes, &mut w[..16]);
-			}
-			None => load_u64_into_be(&self.buffer, &mut w[..16]),
-		}
+		debug_assert!(block.len() == SHA512_BLOCKSIZE);
+		load_u64_into_be(block, &mut w[..16]);
 
 		for t in 16..80 {
 			w[t] = Self::small_sigma_1(w[t - 2])
@@ -229,14 +255,14 @@ impl Sha512 {
 				.wrapping_add(w[t - 16]);
 		}
 
-		let mut a = self.working_state[0];
-		let mut b = self.working_state[1];
-		let mut c = self.working_state[2];
-		let mut d = self.working_state[3];
-		let mut e = self.working_state[4];
-		let mut f = self.working_state[5];
-		let mut g = self.working_state[6];
-		let mut h = self.working_state[7];
+		let mut a = state[0];
+		let mut b = state[1];
+		let mut c = state[2];
+		let mut d = state[3];
+		let mut e = state[4];
+		let mut f = state[5];
+		let mut g = state[6];
+		let mut h = state[7];
 
 		let mut t = 0;
 		while t < 80 {
@@ -250,16 +276,24 @@ impl Sha512 {
 			Self::compress(b, c, d, &mut e, f, g, h, &mut a, w[t], K[t]); t += 1;
 		}
 
-		self.working_state[0] = self.working_state[0].wrapping_add(a);
-		self.working_state[1] = self.working_state[1].wrapping_add(b);
-		self.working_state[2] = self.working_state[2].wrapping_add(c);
-		self.working_state[3] = self.working_state[3].wrapping_add(d);
-		self.working_state[4] = self.working_state[4].wrapping_add(e);
-		self.working_state[5] = self.working_state[5].wrapping_add(f);
-		self.working_state[6] = self.working_state[6].wrapping_add(g);
-		self.working_state[7] = self.working_state[7].wrapping_add(h);
+		state[0] = state[0].wrapping_add(a);
+		state[1] = state[1].wrapping_add(b);
+		state[2] = state[2].wrapping_add(c);
+		state[3] = state[3].wrapping_add(d);
+		state[4] = state[4].wrapping_add(e);
+		state[5] = state[5].wrapping_add(f);
+		state[6] = state[6].wrapping_add(g);
+		state[7] = state[7].wrapping_add(h);
 	}
 
+    /// Process data in `self.buffer` or optionally `data`.
+    fn process(&mut self, data: Option<&[u8]>) {
+        match data {
+            Some(bytes) => Self::process_block(&mut self.working_state, bytes),
+            None => Self::process_block(&mut self.working_state, &self.buffer),
+        }
+    }
+
     /// Increment the message length during processing of data.
     fn increment_mlen(&mut self, length: u64) {
         // The checked shift checks that the right-hand side is a legal shift.
@@ -291,6 +325,15 @@ impl Sha512 {
         }
     }
 
+    /// Initialize a `Sha512` struct that starts from `initial_state` instead
+    /// of the SHA512 initial hash value, as the truncated variants do.
+    pub(crate) fn with_initial_state(initial_state: [u64; 8]) -> Self {
+        Self {
+            working_state: initial_state,
+            ..Self::new()
+        }
+    }
+
     /// Reset to `new()` state.
     pub fn reset(&mut self) {
         self.working_state = H0;
@@ -355,9 +398,21 @@ impl Sha512 {
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
     /// Return a SHA512 digest.
     pub fn finalize(&mut self) -> Result<Digest, UnknownCryptoError> {
+        let mut digest = [0u8; SHA512_OUTSIZE];
+        self.finalize_into(&mut digest)?;
+
+        Ok(Digest::from(digest))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA512 digest into `dest`, which must be `SHA512_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
         if self.is_finalized {
             return Err(UnknownCryptoError);
         }
+        if dest.len() != SHA512_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
 
         self.is_finalized = true;
 
@@ -385,11 +440,9 @@ impl Sha512 {
             .copy_from_slice(&self.message_len[1].to_be_bytes());
 
         self.process(None);
+        store_u64_into_be(&self.working_state, dest);
 
-        let mut digest = [0u8; SHA512_OUTSIZE];
-        store_u64_into_be(&self.working_state, &mut digest);
-
-        Ok(Digest::from(digest))
+        Ok(())
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -399,6 +452,94 @@ impl Sha512 {
         state.update(data)?;
         state.finalize()
     }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a SHA512 digest of some `data` into `dest`, which must be
+    /// `SHA512_OUTSIZE` bytes.
+    pub fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        state.update(data)?;
+        state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA512 digest of `data`.
+    pub fn verify(expected: &Digest, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        if expected == &Self::digest(data)? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify in constant time that `expected` is the SHA512 digest of all data
+    /// read from `reader`, until it reaches the end.
+    pub fn verify_stream<R: std::io::Read>(
+        expected: &Digest,
+        mut reader: R,
+    ) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new();
+        let mut buf = [0u8; SHA512_BLOCKSIZE * 128];
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => return Err(UnknownCryptoError),
+            };
+            state.update(&buf[..n])?;
+        }
+
+        if expected == &state.finalize()? {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl super::ShaHash for Sha512 {
+    const BLOCKSIZE: usize = SHA512_BLOCKSIZE;
+    const OUTSIZE: usize = SHA512_OUTSIZE;
+
+    fn reset(&mut self) {
+        Sha512::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Sha512::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha512::finalize_into(self, dest)
+    }
+
+    fn digest_into(data: &[u8], dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Sha512::digest_into(data, dest)
+    }
+}
+
+/// The SHA512 initial hash value, to start [`compress()`] from.
+///
+/// [`compress()`]: fn.compress.html
+pub const SHA512_INITIAL_STATE: [u64; 8] = H0;
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Process a single `block` of [`SHA512_BLOCKSIZE`] bytes into `state`, with the
+/// SHA512 compression function. No padding or length encoding is applied.
+///
+/// [`SHA512_BLOCKSIZE`]: constant.SHA512_BLOCKSIZE.html
+pub fn compress(state: &mut [u64; 8], block: &[u8]) -> Result<(), UnknownCryptoError> {
+    if block.len() != SHA512_BLOCKSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    Sha512::process_block(state, block);
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -417,6 +558,100 @@ pub fn compare_sha512_states(state_1: &Sha512, state_2: &Sha512) {
 mod public {
     use super::*;
 
+    #[test]
+    fn test_verify() {
+        let expected = Sha512::digest(b"Hello world").unwrap();
+        assert!(Sha512::verify(&expected, b"Hello world").is_ok());
+        assert!(Sha512::verify(&expected, b"Hello world!").is_err());
+        assert!(Sha512::verify(&expected, b"").is_err());
+    }
+
+    #[cfg(feature = "safe_api")]
+    mod test_verify_stream {
+        use super::*;
+        use std::io;
+
+        /// Reads `data` in chunks of `chunk` bytes, interrupting every other
+        /// read, and fails at the end if `fail` is set.
+        struct ChunkedReader<'a> {
+            data: &'a [u8],
+            chunk: usize,
+            interrupt: bool,
+            fail: bool,
+        }
+
+        impl io::Read for ChunkedReader<'_> {
+            #[allow(clippy::io_other_error)]
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.interrupt = !self.interrupt;
+                if self.interrupt {
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+                }
+                if self.data.is_empty() && self.fail {
+                    return Err(io::Error::new(io::ErrorKind::Other, "failed"));
+                }
+                let n = self.chunk.min(buf.len()).min(self.data.len());
+                buf[..n].copy_from_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                Ok(n)
+            }
+        }
+
+        #[test]
+        fn test_verify_stream() {
+            let data = vec![0x61u8; SHA512_BLOCKSIZE * 300 + 1];
+            let expected = Sha512::digest(&data).unwrap();
+            assert!(Sha512::verify_stream(&expected, &data[..]).is_ok());
+            assert!(Sha512::verify_stream(&expected, &data[1..]).is_err());
+            assert!(Sha512::verify_stream(&expected, io::empty()).is_err());
+
+            let reader = ChunkedReader {
+                data: &data,
+                chunk: 7,
+                interrupt: false,
+                fail: false,
+            };
+            assert!(Sha512::verify_stream(&expected, reader).is_ok());
+
+            // A read error after all data is an error, even though the data
+            // matches.
+            let reader = ChunkedReader {
+                data: &data,
+                chunk: 7,
+                interrupt: false,
+                fail: true,
+            };
+            assert!(Sha512::verify_stream(&expected, reader).is_err());
+        }
+    }
+
+    #[test]
+    fn test_compress() {
+        // "abc", padded to a single block.
+        let mut block = [0u8; SHA512_BLOCKSIZE];
+        block[..4].copy_from_slice(b"abc\x80");
+        block[SHA512_BLOCKSIZE - 1] = 24;
+
+        let mut state = SHA512_INITIAL_STATE;
+        compress(&mut state, &block).unwrap();
+        let mut digest = [0u8; SHA512_OUTSIZE];
+        store_u64_into_be(&state, &mut digest);
+        assert_eq!(digest[..], Sha512::digest(b"abc").unwrap().as_ref()[..]);
+
+        // Chaining blocks, as the streaming state does.
+        let data = [0x61u8; SHA512_BLOCKSIZE * 2];
+        let mut streaming = Sha512::new();
+        streaming.update(&data).unwrap();
+        let mut state = SHA512_INITIAL_STATE;
+        compress(&mut state, &data[..SHA512_BLOCKSIZE]).unwrap();
+        compress(&mut state, &data[SHA512_BLOCKSIZE..]).unwrap();
+        assert_eq!(state, streaming.working_state);
+
+        assert!(compress(&mut state, &block[..SHA512_BLOCKSIZE - 1]).is_err());
+        assert!(compress(&mut state, &data).is_err());
+        assert!(compress(&mut state, &[]).is_err());
+    }
+
     #[test]
     fn test_default_equals_new() {
         let new = Sha512::new();
@@ -501,6 +736,36 @@ mod public {
             }
         }
     }
+
+    mod test_finalize_into {
+        use super::*;
+
+        #[test]
+        fn finalize_into_same_as_finalize() {
+            let mut state = Sha512::new();
+            state.update(b"Some data").unwrap();
+            let mut other = state.clone();
+
+            let mut dest = [0u8; SHA512_OUTSIZE];
+            state.finalize_into(&mut dest).unwrap();
+            assert_eq!(other.finalize().unwrap().as_ref(), &dest[..]);
+
+            let mut dest_one_shot = [0u8; SHA512_OUTSIZE];
+            Sha512::digest_into(b"Some data", &mut dest_one_shot).unwrap();
+            assert_eq!(dest, dest_one_shot);
+        }
+
+        #[test]
+        fn finalize_into_wrong_length_err() {
+            let mut state = Sha512::new();
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE - 1]).is_err());
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE + 1]).is_err());
+            assert!(Sha512::digest_into(b"", &mut [0u8; 0]).is_err());
+            // The state has not been finalized by the failed calls.
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_ok());
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_err());
+        }
+    }
 }
 
 // Testing private functions in the module.
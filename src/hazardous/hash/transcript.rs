@@ -0,0 +1,241 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! A running hash over the messages of a handshake, as used by the TLS 1.3 key
+//! schedule and similar protocols. It is generic over any [`ShaHash`].
+//!
+//! The transcript can be hashed at any point with [`current_hash()`], without
+//! ending it. Where a protocol needs the hash of the transcript at a specific
+//! message later on, clone it at that point and keep the clone as a
+//! checkpoint.
+//!
+//! # Parameters:
+//! - `data`: Raw data to add to the transcript.
+//! - `msg_type`: The handshake message type.
+//! - `body`: The body of a handshake message, without its header.
+//! - `dst_out`: Destination buffer for the hash of the transcript.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `body` is 2^24 bytes or longer.
+//! - The length of `dst_out` is not `H::OUTSIZE`.
+//!
+//! # Security:
+//! - The transcript must include every message exactly as it was sent or
+//!   received. Re-encoding a parsed message can change the hash.
+//! - [`replace_with_message_hash()`] must only be called when the protocol
+//!   requires it, such as after a TLS 1.3 HelloRetryRequest.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::hash::{sha256::Sha256, transcript::TranscriptHash};
+//!
+//! let mut transcript = TranscriptHash::<Sha256>::new();
+//! // ClientHello and ServerHello, with their 4-byte handshake headers.
+//! transcript.update_message(1, b"client hello")?;
+//! transcript.update_message(2, b"server hello")?;
+//!
+//! // The handshake secrets are derived from the hash up to the ServerHello.
+//! let mut hello_hash = [0u8; 32];
+//! transcript.current_hash(&mut hello_hash)?;
+//!
+//! // Keep the transcript up to the server Finished for the application secrets.
+//! transcript.update_message(20, b"server finished")?;
+//! let checkpoint = transcript.clone();
+//! transcript.update_message(20, b"client finished")?;
+//!
+//! let mut server_finished_hash = [0u8; 32];
+//! checkpoint.current_hash(&mut server_finished_hash)?;
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`ShaHash`]: ../trait.ShaHash.html
+//! [`current_hash()`]: struct.TranscriptHash.html#method.current_hash
+//! [`replace_with_message_hash()`]: struct.TranscriptHash.html#method.replace_with_message_hash
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::{ShaHash, MAX_SHA_OUTSIZE};
+
+/// The handshake message type of the synthetic `message_hash` message in
+/// TLS 1.3.
+pub const MESSAGE_HASH: u8 = 254;
+/// The largest handshake message body whose length fits in the 3-byte header.
+const MAX_BODY_LEN: usize = (1 << 24) - 1;
+
+#[derive(Clone)]
+/// A running hash over the messages of a handshake.
+pub struct TranscriptHash<H: ShaHash> {
+    state: H,
+}
+
+impl<H: ShaHash> core::fmt::Debug for TranscriptHash<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "TranscriptHash {{ state: [***OMITTED***] }}")
+    }
+}
+
+impl<H: ShaHash> Default for TranscriptHash<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: ShaHash> TranscriptHash<H> {
+    /// Initialize an empty transcript.
+    pub fn new() -> Self {
+        Self {
+            state: H::default(),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Add raw `data` to the transcript.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.state.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Add a handshake message to the transcript, preceded by its header of
+    /// `msg_type` and the 3-byte big-endian length of `body`.
+    pub fn update_message(&mut self, msg_type: u8, body: &[u8]) -> Result<(), UnknownCryptoError> {
+        if body.len() > MAX_BODY_LEN {
+            return Err(UnknownCryptoError);
+        }
+
+        let len = (body.len() as u32).to_be_bytes();
+        self.state.update(&[msg_type, len[1], len[2], len[3]])?;
+        self.state.update(body)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write the hash of the transcript so far to `dst_out`. The transcript
+    /// can still be updated afterwards.
+    pub fn current_hash(&self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dst_out.len() != H::OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        self.state.clone().finalize_into(dst_out)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Replace the transcript with a synthetic `message_hash` message that
+    /// contains its hash, as TLS 1.3 does for the first ClientHello after a
+    /// HelloRetryRequest.
+    pub fn replace_with_message_hash(&mut self) -> Result<(), UnknownCryptoError> {
+        let mut hash = [0u8; MAX_SHA_OUTSIZE];
+        self.current_hash(&mut hash[..H::OUTSIZE])?;
+        self.state.reset();
+
+        self.update_message(MESSAGE_HASH, &hash[..H::OUTSIZE])
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::hash::sha256::Sha256;
+    use crate::hazardous::hash::sha512::Sha512;
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let transcript = TranscriptHash::<Sha256>::new();
+        let debug = format!("{:?}", transcript);
+        assert_eq!(debug, "TranscriptHash { state: [***OMITTED***] }");
+    }
+
+    fn test_messages<H: ShaHash>() {
+        let mut transcript = TranscriptHash::<H>::default();
+        transcript.update_message(1, b"client hello").unwrap();
+        let checkpoint = transcript.clone();
+        transcript.update(&[2, 0, 0, 0]).unwrap();
+
+        let mut actual = [0u8; MAX_SHA_OUTSIZE + 1];
+        let mut expected = [0u8; MAX_SHA_OUTSIZE + 1];
+        transcript.current_hash(&mut actual[..H::OUTSIZE]).unwrap();
+        H::digest_into(
+            b"\x01\x00\x00\x0cclient hello\x02\x00\x00\x00",
+            &mut expected[..H::OUTSIZE],
+        )
+        .unwrap();
+        assert_eq!(actual[..], expected[..]);
+
+        // Hashing the transcript does not end it, and does not affect clones.
+        transcript.current_hash(&mut actual[..H::OUTSIZE]).unwrap();
+        assert_eq!(actual[..], expected[..]);
+        checkpoint.current_hash(&mut actual[..H::OUTSIZE]).unwrap();
+        H::digest_into(b"\x01\x00\x00\x0cclient hello", &mut expected[..H::OUTSIZE]).unwrap();
+        assert_eq!(actual[..], expected[..]);
+
+        assert!(transcript
+            .current_hash(&mut actual[..H::OUTSIZE - 1])
+            .is_err());
+        assert!(transcript
+            .current_hash(&mut actual[..H::OUTSIZE + 1])
+            .is_err());
+    }
+
+    #[test]
+    fn test_messages_sha256() {
+        test_messages::<Sha256>();
+    }
+
+    #[test]
+    fn test_messages_sha512() {
+        test_messages::<Sha512>();
+    }
+
+    #[test]
+    fn test_replace_with_message_hash() {
+        let mut transcript = TranscriptHash::<Sha256>::new();
+        transcript.update_message(1, b"client hello").unwrap();
+        transcript.replace_with_message_hash().unwrap();
+        transcript
+            .update_message(2, b"hello retry request")
+            .unwrap();
+
+        let mut client_hello_hash = [0u8; 32];
+        Sha256::digest_into(b"\x01\x00\x00\x0cclient hello", &mut client_hello_hash).unwrap();
+        let mut expected = TranscriptHash::<Sha256>::new();
+        expected.update(&[254, 0, 0, 32]).unwrap();
+        expected.update(&client_hello_hash).unwrap();
+        expected.update_message(2, b"hello retry request").unwrap();
+
+        let mut actual_hash = [0u8; 32];
+        let mut expected_hash = [0u8; 32];
+        transcript.current_hash(&mut actual_hash).unwrap();
+        expected.current_hash(&mut expected_hash).unwrap();
+        assert_eq!(actual_hash, expected_hash);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_message_too_long() {
+        let mut transcript = TranscriptHash::<Sha256>::new();
+        assert!(transcript.update_message(1, &vec![0u8; 1 << 24]).is_err());
+        assert!(transcript
+            .update_message(1, &vec![0u8; (1 << 24) - 1])
+            .is_ok());
+    }
+}
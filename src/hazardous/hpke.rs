@@ -0,0 +1,778 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Hybrid Public Key Encryption (HPKE) in base mode.
+//!
+//! # About:
+//! This implements the base mode of [RFC 9180] with the cipher suite
+//! DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and ChaCha20Poly1305:
+//! - [`setup_base_sender()`] encapsulates a shared secret to the recipient's
+//!   [`PublicKey`] and returns the encapsulated key `enc`, which is sent to the
+//!   recipient, and a [`SenderContext`].
+//! - [`setup_base_receiver()`] decapsulates the shared secret from `enc` with
+//!   the recipient's [`PrivateKey`] and returns a [`ReceiverContext`].
+//! - [`SenderContext::seal()`] and [`ReceiverContext::open()`] encrypt and
+//!   decrypt a sequence of messages. The nonce of each message is derived from
+//!   its position in the sequence, so messages must be opened in the order
+//!   they were sealed.
+//! - [`SenderContext::export()`] and [`ReceiverContext::export()`] derive
+//!   secrets that both parties share from an exporter context.
+//! - [`derive_key_pair()`] deterministically derives a key pair from input
+//!   keying material, as specified in RFC 9180.
+//!
+//! # Parameters:
+//! - `public_key_r`: The public key of the recipient.
+//! - `private_key_r`: The private key of the recipient.
+//! - `ephemeral_key`: The ephemeral private key of the sender.
+//! - `enc`: The encapsulated key, which is the public key of `ephemeral_key`.
+//! - `info`: Application-specific information, which binds the context to it.
+//! - `aad`: Additional data to authenticate with a message.
+//! - `plaintext`: The message to seal.
+//! - `ciphertext_with_tag`: The sealed message to open.
+//! - `exporter_context`: The context of a secret to export.
+//! - `ikm`: Input keying material to derive a key pair from.
+//! - `dst_out`: Destination buffer for the sealed or opened message, or the
+//!   exported secret.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `public_key_r` or `enc` is a point of small order.
+//! - The length of `dst_out` is less than `plaintext` + [`TAG_SIZE`] when
+//!   sealing.
+//! - The length of `ciphertext_with_tag` is less than [`TAG_SIZE`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - [`TAG_SIZE`]
+//!   when opening.
+//! - The received tag does not match the calculated tag when opening.
+//! - `2^64 - 1` messages have been sealed or opened with a context.
+//! - `dst_out` is empty or greater than [`MAX_EXPORT_SIZE`] when exporting.
+//! - `ikm` is less than 32 bytes.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2^32-1 * 64 bytes of data are processed.
+//!
+//! # Security:
+//! - `ephemeral_key` must be generated with [`PrivateKey::generate()`] for
+//!   every call to [`setup_base_sender()`], and never be re-used. Re-using it
+//!   with the same recipient and `info` re-uses the key and nonces of the
+//!   AEAD.
+//! - Base mode does not authenticate the sender. Anyone who knows the
+//!   recipient's public key can set up a sender context to it.
+//! - A failed [`ReceiverContext::open()`] does not advance the sequence of the
+//!   context, so the message can be retried. A message that was lost must be
+//!   skipped with a new context, since later messages depend on its position.
+//! - The input keying material to [`derive_key_pair()`] must be uniformly
+//!   random, and at least 32 bytes.
+//!
+//! # Example:
+//! ```rust
+//! # #[cfg(feature = "safe_api")] {
+//! use orion::hazardous::hpke::{self, PrivateKey, PublicKey, TAG_SIZE};
+//! use core::convert::TryFrom;
+//!
+//! let private_key_r = PrivateKey::generate();
+//! let public_key_r = PublicKey::try_from(&private_key_r)?;
+//!
+//! let info = b"Example application";
+//! let (enc, mut sender) = hpke::setup_base_sender(&public_key_r, &PrivateKey::generate(), info)?;
+//!
+//! let mut ciphertext = [0u8; 13 + TAG_SIZE];
+//! sender.seal(b"aad", b"Hello, world!", &mut ciphertext)?;
+//!
+//! let mut receiver = hpke::setup_base_receiver(&enc, &private_key_r, info)?;
+//! let mut plaintext = [0u8; 13];
+//! receiver.open(b"aad", &ciphertext, &mut plaintext)?;
+//! assert_eq!(&plaintext, b"Hello, world!");
+//!
+//! // Both parties can export the same secrets.
+//! let mut sender_secret = [0u8; 32];
+//! let mut receiver_secret = [0u8; 32];
+//! sender.export(b"Some exporter context", &mut sender_secret)?;
+//! receiver.export(b"Some exporter context", &mut receiver_secret)?;
+//! assert_eq!(sender_secret, receiver_secret);
+//! # }
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180
+//! [`setup_base_sender()`]: fn.setup_base_sender.html
+//! [`setup_base_receiver()`]: fn.setup_base_receiver.html
+//! [`derive_key_pair()`]: fn.derive_key_pair.html
+//! [`PublicKey`]: ../ecc/x25519/struct.PublicKey.html
+//! [`PrivateKey`]: ../ecc/x25519/struct.PrivateKey.html
+//! [`PrivateKey::generate()`]: ../ecc/x25519/struct.PrivateKey.html#method.generate
+//! [`SenderContext`]: struct.SenderContext.html
+//! [`ReceiverContext`]: struct.ReceiverContext.html
+//! [`SenderContext::seal()`]: struct.SenderContext.html#method.seal
+//! [`ReceiverContext::open()`]: struct.ReceiverContext.html#method.open
+//! [`SenderContext::export()`]: struct.SenderContext.html#method.export
+//! [`ReceiverContext::export()`]: struct.ReceiverContext.html#method.export
+//! [`TAG_SIZE`]: constant.TAG_SIZE.html
+//! [`MAX_EXPORT_SIZE`]: constant.MAX_EXPORT_SIZE.html
+
+pub use crate::hazardous::ecc::x25519::{PrivateKey, PublicKey};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::chacha20poly1305,
+        ecc::x25519::{self, PRIVATE_KEY_SIZE, PUBLIC_KEY_SIZE},
+        hash::sha256::{Sha256, SHA256_OUTSIZE},
+        mac::{hmac::HmacGeneric, poly1305::POLY1305_OUTSIZE},
+        stream::chacha20::{self, IETF_CHACHA_NONCESIZE},
+    },
+};
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of the tag that [`SenderContext::seal()`] appends to a message.
+///
+/// [`SenderContext::seal()`]: struct.SenderContext.html#method.seal
+pub const TAG_SIZE: usize = POLY1305_OUTSIZE;
+
+/// The maximum size of a secret exported from a context.
+pub const MAX_EXPORT_SIZE: usize = 255 * SHA256_OUTSIZE;
+
+/// The version label that all labeled KDF inputs start with.
+const HPKE_VERSION: &[u8] = b"HPKE-v1";
+
+/// The suite ID of DHKEM(X25519, HKDF-SHA256), used within the KEM.
+const KEM_SUITE_ID: &[u8] = b"KEM\x00\x20";
+
+/// The suite ID of DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and
+/// ChaCha20Poly1305, used in the key schedule.
+const HPKE_SUITE_ID: &[u8] = b"HPKE\x00\x20\x00\x01\x00\x03";
+
+/// The identifier of the base mode.
+const MODE_BASE: u8 = 0x00;
+
+/// `LabeledExtract(salt, label, ikm)` of RFC 9180, with HKDF-SHA256.
+fn labeled_extract(
+    salt: &[u8],
+    suite_id: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+    prk: &mut [u8; SHA256_OUTSIZE],
+) -> Result<(), UnknownCryptoError> {
+    let mut hmac = HmacGeneric::<Sha256>::new(salt)?;
+    hmac.update(HPKE_VERSION)?;
+    hmac.update(suite_id)?;
+    hmac.update(label)?;
+    hmac.update(ikm)?;
+    hmac.finalize_into(prk)
+}
+
+/// `LabeledExpand(prk, label, info, L)` of RFC 9180, with HKDF-SHA256 and
+/// `L` the length of `dst_out`. The labeled info is passed to HMAC in parts,
+/// so it is never copied into a buffer.
+fn labeled_expand(
+    prk: &[u8; SHA256_OUTSIZE],
+    suite_id: &[u8],
+    label: &[u8],
+    info: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_out.is_empty() || dst_out.len() > MAX_EXPORT_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let length = (dst_out.len() as u16).to_be_bytes();
+    let mut hmac = HmacGeneric::<Sha256>::new(prk)?;
+    let mut block = [0u8; SHA256_OUTSIZE];
+    for (idx, okm_block) in dst_out.chunks_mut(SHA256_OUTSIZE).enumerate() {
+        if idx > 0 {
+            hmac.reset();
+            hmac.update(&block)?;
+        }
+        hmac.update(&length)?;
+        hmac.update(HPKE_VERSION)?;
+        hmac.update(suite_id)?;
+        hmac.update(label)?;
+        hmac.update(info)?;
+        hmac.update(&[idx as u8 + 1])?;
+        hmac.finalize_into(&mut block)?;
+        okm_block.copy_from_slice(&block[..okm_block.len()]);
+    }
+    block.zeroize();
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive a key pair from `ikm`, as `DeriveKeyPair()` of DHKEM(X25519,
+/// HKDF-SHA256).
+pub fn derive_key_pair(ikm: &[u8]) -> Result<(PrivateKey, PublicKey), UnknownCryptoError> {
+    if ikm.len() < PRIVATE_KEY_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut dkp_prk = [0u8; SHA256_OUTSIZE];
+    let mut sk = [0u8; PRIVATE_KEY_SIZE];
+    let private_key = labeled_extract(b"", KEM_SUITE_ID, b"dkp_prk", ikm, &mut dkp_prk)
+        .and_then(|_| labeled_expand(&dkp_prk, KEM_SUITE_ID, b"sk", b"", &mut sk))
+        .and_then(|_| PrivateKey::from_slice(&sk));
+    dkp_prk.zeroize();
+    sk.zeroize();
+
+    let private_key = private_key?;
+    let public_key = PublicKey::try_from(&private_key)?;
+
+    Ok((private_key, public_key))
+}
+
+/// `ExtractAndExpand()` of DHKEM(X25519, HKDF-SHA256), computing the KEM
+/// shared secret from `private_key` and `public_key`, with `enc` and
+/// `public_key_r` as the KEM context.
+fn kem_shared_secret(
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+    enc: &PublicKey,
+    public_key_r: &PublicKey,
+    shared_secret: &mut [u8; SHA256_OUTSIZE],
+) -> Result<(), UnknownCryptoError> {
+    let dh = x25519::key_agreement(private_key, public_key)?;
+    let mut kem_context = [0u8; 2 * PUBLIC_KEY_SIZE];
+    kem_context[..PUBLIC_KEY_SIZE].copy_from_slice(enc.as_ref());
+    kem_context[PUBLIC_KEY_SIZE..].copy_from_slice(public_key_r.as_ref());
+
+    let mut eae_prk = [0u8; SHA256_OUTSIZE];
+    let result = labeled_extract(
+        b"",
+        KEM_SUITE_ID,
+        b"eae_prk",
+        dh.unprotected_as_bytes(),
+        &mut eae_prk,
+    )
+    .and_then(|_| {
+        labeled_expand(
+            &eae_prk,
+            KEM_SUITE_ID,
+            b"shared_secret",
+            &kem_context,
+            shared_secret,
+        )
+    });
+    eae_prk.zeroize();
+
+    result
+}
+
+/// The state shared by [`SenderContext`] and [`ReceiverContext`].
+///
+/// [`SenderContext`]: struct.SenderContext.html
+/// [`ReceiverContext`]: struct.ReceiverContext.html
+struct Context {
+    key: chacha20poly1305::SecretKey,
+    base_nonce: [u8; IETF_CHACHA_NONCESIZE],
+    seq: u64,
+    exporter_secret: [u8; SHA256_OUTSIZE],
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        self.base_nonce.zeroize();
+        self.exporter_secret.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "key: [***OMITTED***], base_nonce: [***OMITTED***], seq: {:?}, exporter_secret: \
+             [***OMITTED***]",
+            self.seq
+        )
+    }
+}
+
+impl Context {
+    /// `KeySchedule()` of RFC 9180 in base mode.
+    fn new(shared_secret: &[u8; SHA256_OUTSIZE], info: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut key_schedule_context = [0u8; 1 + 2 * SHA256_OUTSIZE];
+        key_schedule_context[0] = MODE_BASE;
+        let (psk_id_hash, info_hash) = key_schedule_context[1..].split_at_mut(SHA256_OUTSIZE);
+        let mut psk_id_hash_buf = [0u8; SHA256_OUTSIZE];
+        let mut info_hash_buf = [0u8; SHA256_OUTSIZE];
+        labeled_extract(
+            b"",
+            HPKE_SUITE_ID,
+            b"psk_id_hash",
+            b"",
+            &mut psk_id_hash_buf,
+        )?;
+        labeled_extract(b"", HPKE_SUITE_ID, b"info_hash", info, &mut info_hash_buf)?;
+        psk_id_hash.copy_from_slice(&psk_id_hash_buf);
+        info_hash.copy_from_slice(&info_hash_buf);
+
+        let mut secret = [0u8; SHA256_OUTSIZE];
+        let mut key = [0u8; chacha20::CHACHA_KEYSIZE];
+        let mut context = Self {
+            key: chacha20poly1305::SecretKey::from([0u8; chacha20::CHACHA_KEYSIZE]),
+            base_nonce: [0u8; IETF_CHACHA_NONCESIZE],
+            seq: 0,
+            exporter_secret: [0u8; SHA256_OUTSIZE],
+        };
+        let result = labeled_extract(shared_secret, HPKE_SUITE_ID, b"secret", b"", &mut secret)
+            .and_then(|_| {
+                labeled_expand(
+                    &secret,
+                    HPKE_SUITE_ID,
+                    b"key",
+                    &key_schedule_context,
+                    &mut key,
+                )
+            })
+            .and_then(|_| {
+                labeled_expand(
+                    &secret,
+                    HPKE_SUITE_ID,
+                    b"base_nonce",
+                    &key_schedule_context,
+                    &mut context.base_nonce,
+                )
+            })
+            .and_then(|_| {
+                labeled_expand(
+                    &secret,
+                    HPKE_SUITE_ID,
+                    b"exp",
+                    &key_schedule_context,
+                    &mut context.exporter_secret,
+                )
+            });
+        context.key = chacha20poly1305::SecretKey::from(key);
+        secret.zeroize();
+        key.zeroize();
+
+        result.map(|_| context)
+    }
+
+    /// The nonce of the current message, `base_nonce` XOR `seq`.
+    fn nonce(&self) -> Result<chacha20poly1305::Nonce, UnknownCryptoError> {
+        // The sequence number is never incremented past u64::MAX - 1, so
+        // incrementing after a successful seal() or open() cannot overflow.
+        if self.seq == u64::MAX {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut nonce = self.base_nonce;
+        for (n, s) in nonce[IETF_CHACHA_NONCESIZE - 8..]
+            .iter_mut()
+            .zip(self.seq.to_be_bytes().iter())
+        {
+            *n ^= s;
+        }
+
+        Ok(chacha20poly1305::Nonce::from(nonce))
+    }
+
+    fn export(
+        &self,
+        exporter_context: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        labeled_expand(
+            &self.exporter_secret,
+            HPKE_SUITE_ID,
+            b"sec",
+            exporter_context,
+            dst_out,
+        )
+    }
+}
+
+/// The context of the sender, that seals messages to the recipient.
+pub struct SenderContext {
+    inner: Context,
+}
+
+impl core::fmt::Debug for SenderContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SenderContext {{ {:?} }}", self.inner)
+    }
+}
+
+impl SenderContext {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Seal the next message, writing the ciphertext and tag to `dst_out`.
+    pub fn seal(
+        &mut self,
+        aad: &[u8],
+        plaintext: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let nonce = self.inner.nonce()?;
+        chacha20poly1305::seal(&self.inner.key, &nonce, plaintext, Some(aad), dst_out)?;
+        self.inner.seq += 1;
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Export a secret of the length of `dst_out` for `exporter_context`.
+    pub fn export(
+        &self,
+        exporter_context: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        self.inner.export(exporter_context, dst_out)
+    }
+}
+
+/// The context of the recipient, that opens messages from the sender.
+pub struct ReceiverContext {
+    inner: Context,
+}
+
+impl core::fmt::Debug for ReceiverContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReceiverContext {{ {:?} }}", self.inner)
+    }
+}
+
+impl ReceiverContext {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Open the next message, writing the plaintext to `dst_out`.
+    pub fn open(
+        &mut self,
+        aad: &[u8],
+        ciphertext_with_tag: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let nonce = self.inner.nonce()?;
+        chacha20poly1305::open(
+            &self.inner.key,
+            &nonce,
+            ciphertext_with_tag,
+            Some(aad),
+            dst_out,
+        )?;
+        self.inner.seq += 1;
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Export a secret of the length of `dst_out` for `exporter_context`.
+    pub fn export(
+        &self,
+        exporter_context: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        self.inner.export(exporter_context, dst_out)
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Set up a [`SenderContext`] to `public_key_r` in base mode, returning the
+/// encapsulated key and the context.
+///
+/// [`SenderContext`]: struct.SenderContext.html
+pub fn setup_base_sender(
+    public_key_r: &PublicKey,
+    ephemeral_key: &PrivateKey,
+    info: &[u8],
+) -> Result<(PublicKey, SenderContext), UnknownCryptoError> {
+    let enc = PublicKey::try_from(ephemeral_key)?;
+    let mut shared_secret = [0u8; SHA256_OUTSIZE];
+    let context = kem_shared_secret(
+        ephemeral_key,
+        public_key_r,
+        &enc,
+        public_key_r,
+        &mut shared_secret,
+    )
+    .and_then(|_| Context::new(&shared_secret, info));
+    shared_secret.zeroize();
+
+    Ok((enc, SenderContext { inner: context? }))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Set up a [`ReceiverContext`] from the encapsulated key `enc` with
+/// `private_key_r` in base mode.
+///
+/// [`ReceiverContext`]: struct.ReceiverContext.html
+pub fn setup_base_receiver(
+    enc: &PublicKey,
+    private_key_r: &PrivateKey,
+    info: &[u8],
+) -> Result<ReceiverContext, UnknownCryptoError> {
+    let public_key_r = PublicKey::try_from(private_key_r)?;
+    let mut shared_secret = [0u8; SHA256_OUTSIZE];
+    let context = kem_shared_secret(private_key_r, enc, enc, &public_key_r, &mut shared_secret)
+        .and_then(|_| Context::new(&shared_secret, info));
+    shared_secret.zeroize();
+
+    Ok(ReceiverContext { inner: context? })
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn decode<'a>(value: &str, dst: &'a mut [u8]) -> &'a [u8] {
+        let len = value.len() / 2;
+        for (idx, byte) in dst[..len].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * idx..2 * idx + 2], 16).unwrap();
+        }
+        &dst[..len]
+    }
+
+    const PLAINTEXT: &[u8] = b"Beauty is truth, truth beauty";
+
+    /// The key pairs of RFC 9180, appendix A.2.1, as (skEm, pkEm, skRm, pkRm).
+    fn rfc_key_pairs() -> (PrivateKey, PublicKey, PrivateKey, PublicKey) {
+        let mut ikm = [0u8; 32];
+        let (sk_e, pk_e) = derive_key_pair(decode(
+            "909a9b35d3dc4713a5e72a4da274b55d3d3821a37e5d099e74a647db583a904b",
+            &mut ikm,
+        ))
+        .unwrap();
+        let (sk_r, pk_r) = derive_key_pair(decode(
+            "1ac01f181fdf9f352797655161c58b75c656a6cc2716dcb66372da835542e1df",
+            &mut ikm,
+        ))
+        .unwrap();
+
+        (sk_e, pk_e, sk_r, pk_r)
+    }
+
+    fn rfc_info() -> &'static [u8] {
+        b"Ode on a Grecian Urn"
+    }
+
+    #[test]
+    fn test_rfc9180_derive_key_pair() {
+        let mut expected = [0u8; 32];
+        let (sk_e, pk_e, sk_r, pk_r) = rfc_key_pairs();
+
+        assert_eq!(
+            sk_e,
+            decode(
+                "f4ec9b33b792c372c1d2c2063507b684ef925b8c75a42dbcbf57d63ccd381600",
+                &mut expected
+            )
+        );
+        assert_eq!(
+            pk_e,
+            decode(
+                "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a",
+                &mut expected
+            )
+        );
+        assert_eq!(
+            sk_r,
+            decode(
+                "8057991eef8f1f1af18f4a9491d16a1ce333f695d4db8e38da75975c4478e0fb",
+                &mut expected
+            )
+        );
+        assert_eq!(
+            pk_r,
+            decode(
+                "4310ee97d88cc1f088a5576c77ab0cf5c3ac797f3d95139c6c84b5429c59662a",
+                &mut expected
+            )
+        );
+    }
+
+    #[test]
+    fn test_rfc9180_base_mode() {
+        // RFC 9180, appendix A.2.1.
+        let (sk_e, pk_e, sk_r, pk_r) = rfc_key_pairs();
+        let (enc, mut sender) = setup_base_sender(&pk_r, &sk_e, rfc_info()).unwrap();
+        assert_eq!(enc, pk_e);
+        let mut receiver = setup_base_receiver(&enc, &sk_r, rfc_info()).unwrap();
+
+        let encryptions = [
+            (
+                &b"Count-0"[..],
+                "1c5250d8034ec2b784ba2cfd69dbdb8af406cfe3ff938e131f0def8c8b60b4db\
+                 21993c62ce81883d2dd1b51a28",
+            ),
+            (
+                &b"Count-1"[..],
+                "6b53c051e4199c518de79594e1c4ab18b96f081549d45ce015be002090bb119e\
+                 85285337cc95ba5f59992dc98c",
+            ),
+        ];
+        let mut expected = [0u8; 64];
+        let mut ciphertext = [0u8; 29 + TAG_SIZE];
+        let mut plaintext = [0u8; 29];
+        for (aad, expected_ct) in encryptions.iter() {
+            sender.seal(aad, PLAINTEXT, &mut ciphertext).unwrap();
+            assert_eq!(&ciphertext[..], decode(expected_ct, &mut expected));
+            receiver.open(aad, &ciphertext, &mut plaintext).unwrap();
+            assert_eq!(&plaintext[..], PLAINTEXT);
+        }
+
+        let exports = [
+            (
+                &b""[..],
+                "4bbd6243b8bb54cec311fac9df81841b6fd61f56538a775e7c80a9f40160606e",
+            ),
+            (
+                &[0u8][..],
+                "8c1df14732580e5501b00f82b10a1647b40713191b7c1240ac80e2b68808ba69",
+            ),
+            (
+                &b"TestContext"[..],
+                "5acb09211139c43b3090489a9da433e8a30ee7188ba8b0a9a1ccf0c229283e53",
+            ),
+        ];
+        let mut exported = [0u8; 32];
+        for (exporter_context, expected_secret) in exports.iter() {
+            let expected_secret = decode(expected_secret, &mut expected);
+            sender.export(exporter_context, &mut exported).unwrap();
+            assert_eq!(&exported[..], expected_secret);
+            receiver.export(exporter_context, &mut exported).unwrap();
+            assert_eq!(&exported[..], expected_secret);
+        }
+    }
+
+    #[test]
+    fn test_open_err_does_not_advance() {
+        let (sk_e, _, sk_r, pk_r) = rfc_key_pairs();
+        let (enc, mut sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let mut receiver = setup_base_receiver(&enc, &sk_r, b"").unwrap();
+
+        let mut first = [0u8; 29 + TAG_SIZE];
+        let mut second = [0u8; 29 + TAG_SIZE];
+        let mut plaintext = [0u8; 29];
+        sender.seal(b"", PLAINTEXT, &mut first).unwrap();
+        sender.seal(b"", PLAINTEXT, &mut second).unwrap();
+        assert_ne!(first, second);
+
+        // Out of order, with the wrong aad and modified.
+        assert!(receiver.open(b"", &second, &mut plaintext).is_err());
+        assert!(receiver.open(b"aad", &first, &mut plaintext).is_err());
+        first[0] ^= 1;
+        assert!(receiver.open(b"", &first, &mut plaintext).is_err());
+        first[0] ^= 1;
+
+        receiver.open(b"", &first, &mut plaintext).unwrap();
+        receiver.open(b"", &second, &mut plaintext).unwrap();
+        assert_eq!(&plaintext[..], PLAINTEXT);
+    }
+
+    #[test]
+    fn test_info_and_key_mismatch_err() {
+        let (sk_e, _, sk_r, pk_r) = rfc_key_pairs();
+        let (enc, mut sender) = setup_base_sender(&pk_r, &sk_e, b"info").unwrap();
+        let mut ciphertext = [0u8; 29 + TAG_SIZE];
+        let mut plaintext = [0u8; 29];
+        sender.seal(b"", PLAINTEXT, &mut ciphertext).unwrap();
+
+        let mut receiver = setup_base_receiver(&enc, &sk_r, b"other info").unwrap();
+        assert!(receiver.open(b"", &ciphertext, &mut plaintext).is_err());
+        let mut receiver = setup_base_receiver(&enc, &sk_e, b"info").unwrap();
+        assert!(receiver.open(b"", &ciphertext, &mut plaintext).is_err());
+        let mut receiver = setup_base_receiver(&enc, &sk_r, b"info").unwrap();
+        assert!(receiver.open(b"", &ciphertext, &mut plaintext).is_ok());
+    }
+
+    #[test]
+    fn test_empty_plaintext() {
+        let (sk_e, _, sk_r, pk_r) = rfc_key_pairs();
+        let (enc, mut sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let mut receiver = setup_base_receiver(&enc, &sk_r, b"").unwrap();
+
+        let mut ciphertext = [0u8; TAG_SIZE];
+        sender.seal(b"aad", b"", &mut ciphertext).unwrap();
+        assert!(receiver.open(b"aad", &ciphertext, &mut []).is_ok());
+        assert!(receiver
+            .open(b"aad", &ciphertext[..TAG_SIZE - 1], &mut [])
+            .is_err());
+    }
+
+    #[test]
+    fn test_dst_out_too_small_err() {
+        let (sk_e, _, sk_r, pk_r) = rfc_key_pairs();
+        let (enc, mut sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let mut receiver = setup_base_receiver(&enc, &sk_r, b"").unwrap();
+
+        let mut ciphertext = [0u8; 29 + TAG_SIZE];
+        assert!(sender
+            .seal(b"", PLAINTEXT, &mut ciphertext[..29 + TAG_SIZE - 1])
+            .is_err());
+        sender.seal(b"", PLAINTEXT, &mut ciphertext).unwrap();
+        assert!(receiver.open(b"", &ciphertext, &mut [0u8; 28]).is_err());
+        assert!(receiver.open(b"", &ciphertext, &mut [0u8; 29]).is_ok());
+    }
+
+    #[test]
+    fn test_sequence_exhausted_err() {
+        let (sk_e, _, _, pk_r) = rfc_key_pairs();
+        let (_, mut sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let mut ciphertext = [0u8; TAG_SIZE];
+
+        sender.inner.seq = u64::MAX - 1;
+        assert!(sender.seal(b"", b"", &mut ciphertext).is_ok());
+        assert!(sender.seal(b"", b"", &mut ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_export_size() {
+        let (sk_e, _, _, pk_r) = rfc_key_pairs();
+        let (_, sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let mut exported = [0u8; MAX_EXPORT_SIZE + 1];
+
+        assert!(sender.export(b"", &mut exported[..0]).is_err());
+        assert!(sender.export(b"", &mut exported[..1]).is_ok());
+        assert!(sender.export(b"", &mut exported[..MAX_EXPORT_SIZE]).is_ok());
+        assert!(sender.export(b"", &mut exported).is_err());
+
+        // The length is part of the derivation.
+        let mut short = [0u8; 16];
+        let mut long = [0u8; 32];
+        sender.export(b"", &mut short).unwrap();
+        sender.export(b"", &mut long).unwrap();
+        assert_ne!(short, long[..16]);
+    }
+
+    #[test]
+    fn test_small_order_err() {
+        let (sk_e, _, sk_r, _) = rfc_key_pairs();
+        let small_order = PublicKey::from_slice(&[0u8; PUBLIC_KEY_SIZE]).unwrap();
+        assert!(setup_base_sender(&small_order, &sk_e, b"").is_err());
+        assert!(setup_base_receiver(&small_order, &sk_r, b"").is_err());
+    }
+
+    #[test]
+    fn test_derive_key_pair_ikm_too_short_err() {
+        assert!(derive_key_pair(&[0u8; 31]).is_err());
+        assert!(derive_key_pair(&[0u8; 32]).is_ok());
+        assert!(derive_key_pair(&[0u8; 100]).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let (sk_e, _, _, pk_r) = rfc_key_pairs();
+        let (_, sender) = setup_base_sender(&pk_r, &sk_e, b"").unwrap();
+        let debug = format!("{:?}", sender);
+        let expected = "SenderContext { key: [***OMITTED***], base_nonce: [***OMITTED***], \
+                        seq: 0, exporter_secret: [***OMITTED***] }";
+        assert_eq!(debug, expected);
+    }
+}
@@ -24,7 +24,8 @@
 //! Argon2i version 1.3. This implementation is available with features `safe_api` and `alloc`.
 //!
 //! # Note:
-//! This implementation only supports a single thread/lane.
+//! This implementation only supports a single thread/lane. See [`argon2id`]
+//! for multiple lanes.
 //!
 //! # Parameters:
 //! - `expected`: The expected derived key.
@@ -86,11 +87,14 @@
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
 //! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+//! [`argon2id`]: ../argon2id/index.html
 
 use crate::errors::UnknownCryptoError;
 use crate::hazardous::hash::blake2b::{Blake2b, BLAKE2B_OUTSIZE};
 use crate::util;
+use crate::util::cpu::{self, Backend};
 use crate::util::endianness::{load_u64_into_le, store_u64_into_le};
+use crate::util::{NoProgress, Progress};
 use zeroize::Zeroize;
 
 /// The Argon2 version (0x13).
@@ -100,7 +104,7 @@ pub const ARGON2_VERSION: u32 = 0x13;
 pub const ARGON2_VARIANT: u32 = 1;
 
 /// The amount of segments per lane, as defined in the spec.
-const SEGMENTS_PER_LANE: usize = 4;
+pub(crate) const SEGMENTS_PER_LANE: usize = 4;
 
 /// The amount of lanes supported.
 pub(crate) const LANES: u32 = 1;
@@ -169,6 +173,32 @@ fn initial_hash(
     s: &[u8],
     k: &[u8],
     x: &[u8],
+) -> Result<[u8; 72], UnknownCryptoError> {
+    initial_hash_with(
+        LANES,
+        ARGON2_VARIANT,
+        hash_length,
+        memory_kib,
+        passes,
+        p,
+        s,
+        k,
+        x,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+/// H0 as defined in the specification, for any amount of lanes and variant.
+pub(crate) fn initial_hash_with(
+    lanes: u32,
+    variant: u32,
+    hash_length: u32,
+    memory_kib: u32,
+    passes: u32,
+    p: &[u8],
+    s: &[u8],
+    k: &[u8],
+    x: &[u8],
 ) -> Result<[u8; 72], UnknownCryptoError> {
     // We save additional 8 bytes in H0 for when the first two blocks are processed,
     // so that this may contain two little-endian integers.
@@ -176,12 +206,12 @@ fn initial_hash(
     let mut hasher = Blake2b::new(None, BLAKE2B_OUTSIZE)?;
 
     // Collect the first part to reduce times we update the hasher state.
-    h0[0..4].copy_from_slice(&LANES.to_le_bytes());
+    h0[0..4].copy_from_slice(&lanes.to_le_bytes());
     h0[4..8].copy_from_slice(&hash_length.to_le_bytes());
     h0[8..12].copy_from_slice(&memory_kib.to_le_bytes());
     h0[12..16].copy_from_slice(&passes.to_le_bytes());
     h0[16..20].copy_from_slice(&ARGON2_VERSION.to_le_bytes());
-    h0[20..24].copy_from_slice(&ARGON2_VARIANT.to_le_bytes());
+    h0[20..24].copy_from_slice(&variant.to_le_bytes());
     h0[24..28].copy_from_slice(&(p.len() as u32).to_le_bytes());
 
     hasher.update(&h0[..28])?;
@@ -198,7 +228,7 @@ fn initial_hash(
 }
 
 /// H' as defined in the specification.
-fn extended_hash(input: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+pub(crate) fn extended_hash(input: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
     if dst.is_empty() {
         return Err(UnknownCryptoError);
     }
@@ -240,8 +270,99 @@ fn extended_hash(input: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError>
     Ok(())
 }
 
+/// The amount of independent applications of P in each pass of the
+/// compression function, which the vector backend computes at once.
+const P_LANES: usize = 8;
+
+/// Offsets of the 16 words that each application of P uses in the row pass,
+/// relative to the first word.
+const ROW_OFFSETS: [usize; 16] = [
+    0, 1, 16, 17, 32, 33, 48, 49, 64, 65, 80, 81, 96, 97, 112, 113,
+];
+
+/// BLAKE2 G with 64-bit multiplications, applied to the same state words of all
+/// [`P_LANES`] instances of P. Each state word holds one lane per instance, so
+/// every step is a lane-wise loop of fixed length, which the compiler lowers to
+/// vector instructions where available.
+macro_rules! G_WIDE {
+    ($v:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+        for lane in 0..P_LANES {
+            $v[$a][lane] = lower_mult_add($v[$a][lane], $v[$b][lane]);
+            $v[$d][lane] = ($v[$d][lane] ^ $v[$a][lane]).rotate_right(32);
+        }
+        for lane in 0..P_LANES {
+            $v[$c][lane] = lower_mult_add($v[$c][lane], $v[$d][lane]);
+            $v[$b][lane] = ($v[$b][lane] ^ $v[$c][lane]).rotate_right(24);
+        }
+        for lane in 0..P_LANES {
+            $v[$a][lane] = lower_mult_add($v[$a][lane], $v[$b][lane]);
+            $v[$d][lane] = ($v[$d][lane] ^ $v[$a][lane]).rotate_right(16);
+        }
+        for lane in 0..P_LANES {
+            $v[$c][lane] = lower_mult_add($v[$c][lane], $v[$d][lane]);
+            $v[$b][lane] = ($v[$b][lane] ^ $v[$c][lane]).rotate_right(63);
+        }
+    };
+}
+
+/// The permutation P on [`P_LANES`] word-sliced instances at once.
+fn permutation_p_wide(v: &mut [[u64; P_LANES]; 16]) {
+    G_WIDE!(v, 0, 4, 8, 12);
+    G_WIDE!(v, 1, 5, 9, 13);
+    G_WIDE!(v, 2, 6, 10, 14);
+    G_WIDE!(v, 3, 7, 11, 15);
+    G_WIDE!(v, 0, 5, 10, 15);
+    G_WIDE!(v, 1, 6, 11, 12);
+    G_WIDE!(v, 2, 7, 8, 13);
+    G_WIDE!(v, 3, 4, 9, 14);
+}
+
+/// The compression function's permutation of a block, using the vector backend.
+fn fill_block_vector(w: &mut [u64; 128]) {
+    let mut v = [[0u64; P_LANES]; 16];
+
+    // Operate on columns. Instance `lane` uses the 16 consecutive words
+    // starting at `16 * lane`.
+    for (word_idx, word) in v.iter_mut().enumerate() {
+        for (lane, value) in word.iter_mut().enumerate() {
+            *value = w[16 * lane + word_idx];
+        }
+    }
+    permutation_p_wide(&mut v);
+    for (word_idx, word) in v.iter().enumerate() {
+        for (lane, value) in word.iter().enumerate() {
+            w[16 * lane + word_idx] = *value;
+        }
+    }
+
+    // Operate on rows. Instance `lane` uses the words at `ROW_OFFSETS`,
+    // relative to `2 * lane`.
+    for (word, offset) in v.iter_mut().zip(ROW_OFFSETS.iter()) {
+        for (lane, value) in word.iter_mut().enumerate() {
+            *value = w[2 * lane + offset];
+        }
+    }
+    permutation_p_wide(&mut v);
+    for (word, offset) in v.iter().zip(ROW_OFFSETS.iter()) {
+        for (lane, value) in word.iter().enumerate() {
+            w[2 * lane + offset] = *value;
+        }
+    }
+
+    v.iter_mut().zeroize();
+}
+
+/// The compression function's permutation of a block.
+pub(crate) fn fill_block(w: &mut [u64; 128]) {
+    match cpu::backend() {
+        Backend::Vector => fill_block_vector(w),
+        Backend::Portable => fill_block_portable(w),
+    }
+}
+
 #[rustfmt::skip]
-fn fill_block(w: &mut [u64; 128]) {
+/// The compression function's permutation of a block, using the portable backend.
+fn fill_block_portable(w: &mut [u64; 128]) {
 	
 	let mut v0:  u64; let mut v1:  u64; let mut v2:  u64; let mut v3:  u64;
 	let mut v4:  u64; let mut v5:  u64; let mut v6:  u64; let mut v7:  u64; 
@@ -394,6 +515,31 @@ pub fn derive_key(
     secret: Option<&[u8]>,
     ad: Option<&[u8]>,
     dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    derive_key_with_progress(
+        password,
+        salt,
+        iterations,
+        memory,
+        secret,
+        ad,
+        dst_out,
+        &mut NoProgress,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+/// Argon2i, reporting each completed segment to `progress`. An error is
+/// returned if `progress` cancels the computation.
+pub(crate) fn derive_key_with_progress(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    secret: Option<&[u8]>,
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+    progress: &mut dyn Progress,
 ) -> Result<(), UnknownCryptoError> {
     if password.len() > 0xFFFF_FFFF {
         return Err(UnknownCryptoError);
@@ -471,8 +617,10 @@ pub fn derive_key(
 
     let mut gidx = Gidx::new(n_blocks, iterations, segment_length);
     let mut working_block = [0u64; 128];
+    let total_segments = u64::from(iterations) * SEGMENTS_PER_LANE as u64;
+    let mut cancelled = false;
 
-    for pass_n in 0..iterations as usize {
+    'passes: for pass_n in 0..iterations as usize {
         for segment_n in 0..SEGMENTS_PER_LANE {
             let offset = match (pass_n, segment_n) {
                 (0, 0) => 2, // The first two blocks have already been processed
@@ -505,11 +653,19 @@ pub fn derive_key(
                 fill_block(&mut working_block);
                 xor_slices!(working_block, cur_b);
             }
+
+            let completed = (pass_n * SEGMENTS_PER_LANE + segment_n + 1) as u64;
+            if !progress.report(completed, Some(total_segments)) {
+                cancelled = true;
+                break 'passes;
+            }
         }
     }
 
-    store_u64_into_le(blocks.get(n_blocks as usize - 1).unwrap(), &mut tmp);
-    extended_hash(&tmp, dst_out)?;
+    if !cancelled {
+        store_u64_into_le(blocks.get(n_blocks as usize - 1).unwrap(), &mut tmp);
+        extended_hash(&tmp, dst_out)?;
+    }
 
     working_block.zeroize();
     tmp.zeroize();
@@ -518,7 +674,11 @@ pub fn derive_key(
         block.zeroize();
     }
 
-    Ok(())
+    if cancelled {
+        Err(UnknownCryptoError)
+    } else {
+        Ok(())
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1000,6 +1160,63 @@ mod public {
 mod private {
     use super::*;
 
+    mod test_derive_key_with_progress {
+        use super::*;
+
+        #[test]
+        fn test_reports_segments() {
+            let mut expected = [0u8; 32];
+            let mut actual = [0u8; 32];
+            derive_key(b"password", b"saltsalt", 3, 32, None, None, &mut expected).unwrap();
+
+            let mut reports = 0;
+            let mut progress = |completed: u64, total: Option<u64>| {
+                reports += 1;
+                assert_eq!(completed, reports);
+                assert_eq!(total, Some(12));
+                true
+            };
+            derive_key_with_progress(
+                b"password",
+                b"saltsalt",
+                3,
+                32,
+                None,
+                None,
+                &mut actual,
+                &mut progress,
+            )
+            .unwrap();
+
+            assert_eq!(actual, expected);
+            assert_eq!(reports, 12);
+        }
+
+        #[test]
+        fn test_cancel() {
+            let mut dst_out = [0u8; 32];
+            let mut last = 0;
+            let mut progress = |completed: u64, _: Option<u64>| {
+                last = completed;
+                completed < 5
+            };
+
+            assert!(derive_key_with_progress(
+                b"password",
+                b"saltsalt",
+                3,
+                32,
+                None,
+                None,
+                &mut dst_out,
+                &mut progress,
+            )
+            .is_err());
+            assert_eq!(last, 5);
+            assert_eq!(dst_out, [0u8; 32]);
+        }
+    }
+
     mod test_initial_hash {
         use super::*;
 
@@ -2587,5 +2804,55 @@ mod private {
             assert_eq!(v14, r14);
             assert_eq!(v15, r15);
         }
+
+        #[test]
+        fn p_wide_same_as_p() {
+            let mut v = [[0u64; P_LANES]; 16];
+            for (word_idx, word) in v.iter_mut().enumerate() {
+                for (lane, value) in word.iter_mut().enumerate() {
+                    *value = ((word_idx * P_LANES + lane) as u64 + 1)
+                        .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+                }
+            }
+            let original = v;
+            permutation_p_wide(&mut v);
+
+            for lane in 0..P_LANES {
+                let mut words = [0u64; 16];
+                for (word_idx, word) in words.iter_mut().enumerate() {
+                    *word = original[word_idx][lane];
+                }
+
+                {
+                    let [v0, v1, v2, v3, v4, v5, v6, v7, v8, v9, v10, v11, v12, v13, v14, v15] =
+                        &mut words;
+                    permutation_p(
+                        v0, v1, v2, v3, v4, v5, v6, v7, v8, v9, v10, v11, v12, v13, v14, v15,
+                    );
+                }
+
+                for (word_idx, word) in words.iter().enumerate() {
+                    assert_eq!(*word, v[word_idx][lane]);
+                }
+            }
+        }
+    }
+
+    mod test_fill_block {
+        use super::*;
+
+        #[test]
+        fn vector_same_as_portable() {
+            let mut portable = [0u64; 128];
+            for (idx, word) in portable.iter_mut().enumerate() {
+                *word = (idx as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            }
+            let mut vector = portable;
+
+            fill_block_portable(&mut portable);
+            fill_block_vector(&mut vector);
+
+            assert_eq!(portable[..], vector[..]);
+        }
     }
 }
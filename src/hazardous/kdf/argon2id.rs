@@ -0,0 +1,939 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Argon2id version 1.3, as specified in [RFC 9106](https://www.rfc-editor.org/rfc/rfc9106.html).
+//! This implementation is available with features `safe_api` and `alloc`.
+//!
+//! # Parallelism:
+//! With the `rayon` feature, the lanes of each segment are computed in
+//! parallel on rayon's global thread pool, if there is more than one lane and
+//! each segment is at least 16 blocks long. Otherwise, lanes are computed one
+//! after another on the calling thread. The derived key is the same either way.
+//! The amount of lanes changes the derived key, so it must match the one used by
+//! other implementations.
+//!
+//! # Parameters:
+//! - `expected`: The expected derived key.
+//! - `password`: Password.
+//! - `salt`: Salt value.
+//! - `iterations`: Iteration count.
+//! - `memory`: Memory size in kibibytes (KiB).
+//! - `lanes`: Degree of parallelism.
+//! - `secret`: Optional secret value used for hashing.
+//! - `ad`: Optional associated data used for hashing.
+//! - `dst_out`: Destination buffer for the derived key. The length of the
+//!   derived key is implied by the length of `dst_out`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of the `password` is greater than `u32::MAX`.
+//! - The length of the `salt` is greater than `u32::MAX` or less than `8`.
+//! - The length of the `secret` is greater than `u32::MAX`.
+//! - The length of the `ad` is greater than `u32::MAX`.
+//! - The length of `dst_out` is greater than `u32::MAX` or less than `4`.
+//! - `iterations` is less than `1`.
+//! - `lanes` is less than `1` or greater than `0xFF_FFFF`.
+//! - `memory` is less than `8 * lanes`.
+//! - The hashed password does not match the expected when verifying.
+//!
+//! # Panics:
+//! A panic will occur if:
+//!
+//! # Security:
+//! - Salts should always be generated using a CSPRNG.
+//!   [`util::secure_rand_bytes()`] can be used for this.
+//! - The minimum recommended length for a salt is `16` bytes.
+//! - The minimum recommended length for a hashed password is `16` bytes.
+//! - The minimum recommended iteration count is `3`.
+//! - Password hashes should always be compared in constant-time.
+//!
+//! # Example:
+//! ```rust
+//! use orion::{hazardous::kdf::argon2id, util};
+//!
+//! let mut salt = [0u8; 16];
+//! util::secure_rand_bytes(&mut salt)?;
+//! let password = b"Secret password";
+//! let mut dst_out = [0u8; 64];
+//!
+//! argon2id::derive_key(password, &salt, 3, 1<<16, 4, None, None, &mut dst_out)?;
+//!
+//! let expected_dk = dst_out;
+//!
+//! assert!(argon2id::verify(
+//!     &expected_dk,
+//!     password,
+//!     &salt,
+//!     3,
+//!     1<<16,
+//!     4,
+//!     None,
+//!     None,
+//!     &mut dst_out
+//! )
+//! .is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+
+use super::argon2i::{
+    extended_hash, fill_block, initial_hash_with, MIN_ITERATIONS, SEGMENTS_PER_LANE,
+};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::blake2b::BLAKE2B_OUTSIZE;
+use crate::util;
+use crate::util::endianness::{load_u64_into_le, store_u64_into_le};
+use crate::util::{NoProgress, Progress};
+use zeroize::Zeroize;
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+pub use super::argon2i::ARGON2_VERSION;
+
+/// The Argon2 variant (id).
+pub const ARGON2_VARIANT: u32 = 2;
+
+/// The minimum amount of lanes.
+pub const MIN_LANES: u32 = 1;
+
+/// The maximum amount of lanes.
+pub const MAX_LANES: u32 = 0x00FF_FFFF;
+
+/// The amount of addresses in each address block.
+const ADDRESSES_IN_BLOCK: u32 = 128;
+
+#[cfg(feature = "rayon")]
+/// The minimum segment length, in blocks, for which lanes are computed in
+/// parallel. Below this, scheduling the lanes costs more than it saves.
+const PARALLEL_MIN_SEGMENT_LENGTH: u32 = 16;
+
+/// The blocks before and after the segment being computed, for each lane.
+type SegmentView<'a> = Vec<(&'a [[u64; 128]], &'a [[u64; 128]])>;
+
+/// Address generation for the data-independent segments.
+struct AddressBlock {
+    input: [u64; 128],
+    addresses: [u64; 128],
+}
+
+impl AddressBlock {
+    fn new(pass_n: u32, lane: u32, segment_n: u32, blocks: u32, passes: u32) -> Self {
+        let mut input = [0u64; 128];
+        input[0] = u64::from(pass_n);
+        input[1] = u64::from(lane);
+        input[2] = u64::from(segment_n);
+        input[3] = u64::from(blocks);
+        input[4] = u64::from(passes);
+        input[5] = u64::from(ARGON2_VARIANT);
+
+        Self {
+            input,
+            addresses: [0u64; 128],
+        }
+    }
+
+    fn next_addresses(&mut self, tmp_block: &mut [u64; 128]) {
+        self.input[6] += 1; // Counter
+                            // G-two operation
+        tmp_block.copy_from_slice(&self.input);
+        fill_block(tmp_block);
+        xor_slices!(self.input, tmp_block);
+
+        self.addresses.copy_from_slice(tmp_block);
+        fill_block(&mut self.addresses);
+        xor_slices!(tmp_block, self.addresses);
+    }
+}
+
+/// The memory layout shared by all segments.
+struct Instance {
+    passes: u32,
+    lanes: u32,
+    lane_length: u32,
+    segment_length: u32,
+}
+
+impl Instance {
+    /// The position of the reference block within its lane. This follows
+    /// `index_alpha()` of the reference implementation, see the note in
+    /// the `argon2i` module on where it differs from the specification.
+    fn reference_index(
+        &self,
+        pass_n: u32,
+        segment_n: u32,
+        segment_idx: u32,
+        j1: u64,
+        same_lane: bool,
+    ) -> u32 {
+        // Blocks of the current segment in other lanes may not have been
+        // computed yet, so only the last one computed in the current lane
+        // is excluded when referencing the same lane.
+        let finished = if pass_n == 0 {
+            segment_n * self.segment_length
+        } else {
+            self.lane_length - self.segment_length
+        };
+        let ref_area_size: u32 = if same_lane {
+            finished + segment_idx - 1
+        } else if segment_idx == 0 {
+            finished - 1
+        } else {
+            finished
+        };
+
+        let mut ref_pos: u64 = (j1 * j1) >> 32;
+        ref_pos = (u64::from(ref_area_size) * ref_pos) >> 32;
+        ref_pos = (u64::from(ref_area_size) - 1) - ref_pos;
+
+        let start_pos = if pass_n == 0 || segment_n as usize == SEGMENTS_PER_LANE - 1 {
+            0
+        } else {
+            (segment_n + 1) * self.segment_length
+        };
+
+        ((u64::from(start_pos) + ref_pos) % u64::from(self.lane_length)) as u32
+    }
+
+    /// Split `blocks` into the segment `segment_n` of each lane, and a view of
+    /// the blocks before and after it. Computing a segment only writes to the
+    /// segment itself, and only reads the same segment of its own lane.
+    fn split_segments<'a>(
+        &self,
+        blocks: &'a mut [[u64; 128]],
+        segment_n: u32,
+    ) -> (SegmentView<'a>, Vec<&'a mut [[u64; 128]]>) {
+        let start = (segment_n * self.segment_length) as usize;
+        let mut view = Vec::with_capacity(self.lanes as usize);
+        let mut segments = Vec::with_capacity(self.lanes as usize);
+
+        for lane in blocks.chunks_mut(self.lane_length as usize) {
+            let (before, rest) = lane.split_at_mut(start);
+            let (segment, after) = rest.split_at_mut(self.segment_length as usize);
+            view.push((&*before, &*after));
+            segments.push(segment);
+        }
+
+        (view, segments)
+    }
+
+    /// The block at `idx`, which is either in `view` or in `segment`, the
+    /// segment `segment_n` of the lane being computed.
+    fn block<'a>(
+        &self,
+        view: &SegmentView<'a>,
+        segment: &'a [[u64; 128]],
+        segment_n: u32,
+        idx: u32,
+    ) -> &'a [u64; 128] {
+        let (lane, position) = (idx / self.lane_length, idx % self.lane_length);
+        let start = segment_n * self.segment_length;
+
+        if position < start {
+            &view[lane as usize].0[position as usize]
+        } else if position >= start + self.segment_length {
+            &view[lane as usize].1[(position - start - self.segment_length) as usize]
+        } else {
+            &segment[(position - start) as usize]
+        }
+    }
+
+    /// Compute the segment `segment_n` of every lane. All lanes must finish a
+    /// segment before any lane starts the next, as later segments may reference
+    /// blocks in every lane.
+    fn fill_segments(
+        &self,
+        blocks: &mut [[u64; 128]],
+        pass_n: u32,
+        segment_n: u32,
+        working_block: &mut [u64; 128],
+    ) {
+        let (view, segments) = self.split_segments(blocks, segment_n);
+
+        #[cfg(feature = "rayon")]
+        {
+            if self.lanes > 1 && self.segment_length >= PARALLEL_MIN_SEGMENT_LENGTH {
+                use rayon::prelude::*;
+
+                segments
+                    .into_par_iter()
+                    .enumerate()
+                    .for_each(|(lane, segment)| {
+                        let mut working_block = [0u64; 128];
+                        self.fill_segment(
+                            &view,
+                            segment,
+                            pass_n,
+                            segment_n,
+                            lane as u32,
+                            &mut working_block,
+                        );
+                        working_block.zeroize();
+                    });
+
+                return;
+            }
+        }
+
+        for (lane, segment) in segments.into_iter().enumerate() {
+            self.fill_segment(
+                &view,
+                segment,
+                pass_n,
+                segment_n,
+                lane as u32,
+                working_block,
+            );
+        }
+    }
+
+    /// Compute `segment`, the segment `segment_n` of `lane`, reading all other
+    /// blocks from `view`.
+    fn fill_segment(
+        &self,
+        view: &SegmentView<'_>,
+        segment: &mut [[u64; 128]],
+        pass_n: u32,
+        segment_n: u32,
+        lane: u32,
+        working_block: &mut [u64; 128],
+    ) {
+        // Argon2id uses data-independent addressing for the first half of the
+        // first pass and data-dependent addressing for the rest.
+        let mut address_block = if pass_n == 0 && (segment_n as usize) < SEGMENTS_PER_LANE / 2 {
+            Some(AddressBlock::new(
+                pass_n,
+                lane,
+                segment_n,
+                self.lanes * self.lane_length,
+                self.passes,
+            ))
+        } else {
+            None
+        };
+
+        let offset = match (pass_n, segment_n) {
+            (0, 0) => 2, // The first two blocks have already been processed
+            _ => 0,
+        };
+        if offset != 0 {
+            if let Some(address_block) = address_block.as_mut() {
+                address_block.next_addresses(working_block);
+            }
+        }
+
+        let lane_start = lane * self.lane_length;
+
+        for segment_idx in offset..self.segment_length {
+            let current_idx = lane_start + segment_n * self.segment_length + segment_idx;
+            let previous_idx = if segment_n == 0 && segment_idx == 0 {
+                lane_start + self.lane_length - 1
+            } else {
+                current_idx - 1
+            };
+
+            let pseudo_rand: u64 = match address_block.as_mut() {
+                Some(address_block) => {
+                    if segment_idx % ADDRESSES_IN_BLOCK == 0 {
+                        address_block.next_addresses(working_block);
+                    }
+                    address_block.addresses[(segment_idx % ADDRESSES_IN_BLOCK) as usize]
+                }
+                None => self.block(view, segment, segment_n, previous_idx)[0],
+            };
+
+            // The first segment of the first pass may only reference its own lane.
+            let ref_lane = if pass_n == 0 && segment_n == 0 {
+                lane
+            } else {
+                ((pseudo_rand >> 32) % u64::from(self.lanes)) as u32
+            };
+            let reference_idx = ref_lane * self.lane_length
+                + self.reference_index(
+                    pass_n,
+                    segment_n,
+                    segment_idx,
+                    pseudo_rand & 0xFFFF_FFFFu64,
+                    ref_lane == lane,
+                );
+
+            let prev_b = self.block(view, segment, segment_n, previous_idx);
+            let ref_b = self.block(view, segment, segment_n, reference_idx);
+
+            // G-xor operation
+            for (el_tmp, (el_prev, el_ref)) in working_block
+                .iter_mut()
+                .zip(prev_b.iter().zip(ref_b.iter()))
+            {
+                *el_tmp = el_prev ^ el_ref;
+            }
+            let cur_b = &mut segment[segment_idx as usize];
+            xor_slices!(working_block, cur_b);
+            fill_block(working_block);
+            xor_slices!(working_block, cur_b);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Argon2id password hashing function as specified in the [RFC 9106](https://www.rfc-editor.org/rfc/rfc9106.html).
+pub fn derive_key(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    secret: Option<&[u8]>,
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    derive_key_with_progress(
+        password,
+        salt,
+        iterations,
+        memory,
+        lanes,
+        secret,
+        ad,
+        dst_out,
+        &mut NoProgress,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+/// Argon2id, reporting each completed segment of all lanes to `progress`. An
+/// error is returned if `progress` cancels the computation.
+pub(crate) fn derive_key_with_progress(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    secret: Option<&[u8]>,
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+    progress: &mut dyn Progress,
+) -> Result<(), UnknownCryptoError> {
+    if password.len() > 0xFFFF_FFFF {
+        return Err(UnknownCryptoError);
+    }
+    if salt.len() > 0xFFFF_FFFF || salt.len() < 8 {
+        return Err(UnknownCryptoError);
+    }
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+    if !(MIN_LANES..=MAX_LANES).contains(&lanes) {
+        return Err(UnknownCryptoError);
+    }
+    if memory < 8 * lanes {
+        return Err(UnknownCryptoError);
+    }
+
+    let k = match secret {
+        Some(n_val) => {
+            if n_val.len() > 0xFFFF_FFFF {
+                return Err(UnknownCryptoError);
+            }
+
+            n_val
+        }
+        None => &[0u8; 0],
+    };
+
+    let x = match ad {
+        Some(n_val) => {
+            if n_val.len() > 0xFFFF_FFFF {
+                return Err(UnknownCryptoError);
+            }
+
+            n_val
+        }
+        None => &[0u8; 0],
+    };
+
+    if dst_out.len() > 0xFFFF_FFFF || dst_out.len() < 4 {
+        return Err(UnknownCryptoError);
+    }
+
+    // Round down to 4 * p blocks
+    let segment_length = memory / (SEGMENTS_PER_LANE as u32 * lanes);
+    let lane_length = segment_length * SEGMENTS_PER_LANE as u32;
+    let instance = Instance {
+        passes: iterations,
+        lanes,
+        lane_length,
+        segment_length,
+    };
+
+    let mut blocks: Vec<[u64; 128]> = vec![[0u64; 128]; (lanes * lane_length) as usize];
+
+    let mut h0 = initial_hash_with(
+        lanes,
+        ARGON2_VARIANT,
+        dst_out.len() as u32,
+        memory,
+        iterations,
+        password,
+        salt,
+        k,
+        x,
+    )?;
+    let mut tmp = [0u8; 1024];
+
+    // H' into the first two blocks of each lane
+    for lane in 0..lanes {
+        let lane_start = (lane * lane_length) as usize;
+        h0[(BLAKE2B_OUTSIZE + 4)..].copy_from_slice(&lane.to_le_bytes());
+
+        h0[BLAKE2B_OUTSIZE..(BLAKE2B_OUTSIZE + 4)].copy_from_slice(&0u32.to_le_bytes());
+        extended_hash(&h0, &mut tmp)?;
+        load_u64_into_le(&tmp, &mut blocks[lane_start]);
+
+        h0[BLAKE2B_OUTSIZE..(BLAKE2B_OUTSIZE + 4)].copy_from_slice(&1u32.to_le_bytes());
+        extended_hash(&h0, &mut tmp)?;
+        load_u64_into_le(&tmp, &mut blocks[lane_start + 1]);
+    }
+
+    let mut working_block = [0u64; 128];
+    let total_segments = u64::from(iterations) * SEGMENTS_PER_LANE as u64;
+    let mut cancelled = false;
+
+    'passes: for pass_n in 0..iterations {
+        for segment_n in 0..SEGMENTS_PER_LANE as u32 {
+            instance.fill_segments(&mut blocks, pass_n, segment_n, &mut working_block);
+
+            let completed = u64::from(pass_n) * SEGMENTS_PER_LANE as u64 + u64::from(segment_n) + 1;
+            if !progress.report(completed, Some(total_segments)) {
+                cancelled = true;
+                break 'passes;
+            }
+        }
+    }
+
+    if !cancelled {
+        // The final block is the XOR of the last block in each lane.
+        working_block.copy_from_slice(&blocks[lane_length as usize - 1]);
+        for lane in 1..lanes {
+            let last_b = &blocks[((lane + 1) * lane_length) as usize - 1];
+            xor_slices!(last_b, working_block);
+        }
+        store_u64_into_le(&working_block, &mut tmp);
+        extended_hash(&tmp, dst_out)?;
+    }
+
+    working_block.zeroize();
+    tmp.zeroize();
+    h0.zeroize();
+    for block in blocks.iter_mut() {
+        block.zeroize();
+    }
+
+    if cancelled {
+        Err(UnknownCryptoError)
+    } else {
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify Argon2id derived key in constant time.
+pub fn verify(
+    expected: &[u8],
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    secret: Option<&[u8]>,
+    ad: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    derive_key(
+        password, salt, iterations, memory, lanes, secret, ad, dst_out,
+    )?;
+    util::secure_cmp(dst_out, expected)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[cfg(feature = "safe_api")]
+    mod test_verify {
+        use super::*;
+
+        // Proptests. Only executed when NOT testing no_std.
+        mod proptest {
+            use super::*;
+
+            quickcheck! {
+                fn prop_test_same_input_verify_true(kib: u32, lanes: u8, p: Vec<u8>, s: Vec<u8>) -> bool {
+                    let lanes = u32::from(lanes % 4) + 1;
+                    let mem = if kib < 8 * lanes || kib > 256 {
+                        64
+                    } else {
+                        kib
+                    };
+                    let salt = if s.len() < 8 {
+                        vec![37u8; 8]
+                    } else {
+                        s
+                    };
+
+                    let mut dst_out = [0u8; 32];
+                    derive_key(&p, &salt, 1, mem, lanes, None, None, &mut dst_out).unwrap();
+                    let expected = dst_out;
+
+                    verify(&expected, &p, &salt, 1, mem, lanes, None, None, &mut dst_out).is_ok()
+                }
+            }
+        }
+
+        #[test]
+        fn test_verify_wrong_lanes() {
+            let mut expected = [0u8; 32];
+            derive_key(
+                b"password",
+                b"saltsalt",
+                1,
+                32,
+                2,
+                None,
+                None,
+                &mut expected,
+            )
+            .unwrap();
+
+            let mut dst_out = [0u8; 32];
+            assert!(verify(
+                &expected,
+                b"password",
+                b"saltsalt",
+                1,
+                32,
+                2,
+                None,
+                None,
+                &mut dst_out
+            )
+            .is_ok());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"saltsalt",
+                1,
+                32,
+                1,
+                None,
+                None,
+                &mut dst_out
+            )
+            .is_err());
+        }
+    }
+
+    mod test_derive_key {
+        use super::*;
+
+        #[test]
+        fn test_invalid_mem() {
+            let mut dst_out = [0u8; 32];
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 7, 1, None, None, &mut dst_out).is_err()
+            );
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 8, 1, None, None, &mut dst_out).is_ok()
+            );
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 31, 4, None, None, &mut dst_out).is_err()
+            );
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 32, 4, None, None, &mut dst_out).is_ok()
+            );
+        }
+
+        #[test]
+        fn test_invalid_lanes() {
+            let mut dst_out = [0u8; 32];
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 8, 0, None, None, &mut dst_out).is_err()
+            );
+            assert!(derive_key(
+                b"password",
+                b"saltsalt",
+                1,
+                u32::MAX,
+                MAX_LANES + 1,
+                None,
+                None,
+                &mut dst_out
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn test_invalid_passes() {
+            let mut dst_out = [0u8; 32];
+            assert!(
+                derive_key(b"password", b"saltsalt", 0, 8, 1, None, None, &mut dst_out).is_err()
+            );
+        }
+
+        #[test]
+        fn test_dst_out() {
+            let mut dst_small = [0u8; 3];
+            let mut dst_min = [0u8; 4];
+            assert!(derive_key(
+                b"password",
+                b"saltsalt",
+                1,
+                8,
+                1,
+                None,
+                None,
+                &mut dst_small
+            )
+            .is_err());
+            assert!(
+                derive_key(b"password", b"saltsalt", 1, 8, 1, None, None, &mut dst_min).is_ok()
+            );
+        }
+
+        #[test]
+        fn test_invalid_salt() {
+            let mut dst_out = [0u8; 32];
+            assert!(
+                derive_key(b"password", b"saltsal", 1, 8, 1, None, None, &mut dst_out).is_err()
+            );
+        }
+
+        #[test]
+        fn test_some_or_none_same_result() {
+            let mut dst_one = [0u8; 32];
+            let mut dst_two = [0u8; 32];
+            derive_key(b"password", b"saltsalt", 1, 16, 2, None, None, &mut dst_one).unwrap();
+            derive_key(
+                b"password",
+                b"saltsalt",
+                1,
+                16,
+                2,
+                Some(&[]),
+                Some(&[]),
+                &mut dst_two,
+            )
+            .unwrap();
+
+            assert_eq!(dst_one, dst_two);
+        }
+
+        #[test]
+        fn test_rfc9106() {
+            // https://www.rfc-editor.org/rfc/rfc9106.html#section-5.3
+            let expected = [
+                0x0d, 0x64, 0x0d, 0xf5, 0x8d, 0x78, 0x76, 0x6c, 0x08, 0xc0, 0x37, 0xa3, 0x4a, 0x8b,
+                0x53, 0xc9, 0xd0, 0x1e, 0xf0, 0x45, 0x2d, 0x75, 0xb6, 0x5e, 0xb5, 0x25, 0x20, 0xe9,
+                0x6b, 0x01, 0xe6, 0x59,
+            ];
+
+            let mut actual = [0u8; 32];
+            derive_key(
+                &[1u8; 32],
+                &[2u8; 16],
+                3,
+                32,
+                4,
+                Some(&[3u8; 8]),
+                Some(&[4u8; 12]),
+                &mut actual,
+            )
+            .unwrap();
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn test_hash_lanes() {
+            // Memory that isn't a multiple of 4 * lanes, more lanes than the
+            // amount of segments referenced in the first pass and the
+            // minimum output length.
+            let mut actual = [0u8; 32];
+            derive_key(b"password", b"saltsalt", 2, 64, 3, None, None, &mut actual).unwrap();
+            assert_eq!(
+                actual.as_ref(),
+                &[
+                    0x77, 0xf2, 0x65, 0xf4, 0x41, 0x9c, 0xeb, 0xeb, 0xd4, 0xc7, 0xb4, 0x5a, 0x2d,
+                    0xb8, 0x2f, 0x2a, 0xc3, 0x65, 0x18, 0xd8, 0x21, 0xff, 0x9d, 0xfe, 0x8f, 0x36,
+                    0xb3, 0x13, 0xdf, 0x00, 0x5c, 0x7a
+                ][..]
+            );
+
+            let mut actual = [0u8; 40];
+            derive_key(b"password", b"somesalt", 2, 67, 2, None, None, &mut actual).unwrap();
+            assert_eq!(
+                actual.as_ref(),
+                &[
+                    0xef, 0x5f, 0xa2, 0xbd, 0x33, 0x06, 0x92, 0x71, 0x13, 0x41, 0x60, 0xca, 0xbb,
+                    0x41, 0xf8, 0x36, 0xbd, 0x83, 0x6d, 0xe2, 0x9d, 0x32, 0xa7, 0x76, 0x5f, 0x65,
+                    0x19, 0xc8, 0x06, 0x4f, 0x1a, 0xe5, 0xdc, 0x99, 0xfc, 0x9e, 0xd8, 0x06, 0x1d,
+                    0x54
+                ][..]
+            );
+
+            let mut actual = [0u8; 4];
+            derive_key(b"", b"saltsalt", 1, 16, 2, None, None, &mut actual).unwrap();
+            assert_eq!(actual, [0xfd, 0x2e, 0xe7, 0xb3]);
+
+            // Segments long enough for the lanes to be computed in parallel.
+            let mut actual = [0u8; 32];
+            derive_key(b"password", b"saltsalt", 3, 256, 4, None, None, &mut actual).unwrap();
+            assert_eq!(
+                actual.as_ref(),
+                &[
+                    0xda, 0x84, 0x1b, 0xc8, 0x35, 0x55, 0x07, 0x4b, 0xcd, 0x7c, 0xfd, 0xfe, 0x53,
+                    0x49, 0x7f, 0xad, 0xdb, 0x9c, 0x2f, 0x0d, 0x40, 0x66, 0x23, 0x95, 0xc8, 0x8f,
+                    0xfa, 0x50, 0x66, 0x53, 0x01, 0x59
+                ][..]
+            );
+        }
+
+        #[test]
+        fn test_hash_single_lane() {
+            let mut actual = [0u8; 32];
+            derive_key(b"password", b"saltsalt", 1, 8, 1, None, None, &mut actual).unwrap();
+            assert_eq!(
+                actual,
+                [
+                    0x78, 0xf1, 0xd7, 0xea, 0xd7, 0xa9, 0x5d, 0x6b, 0xac, 0x7f, 0xa6, 0xfa, 0x30,
+                    0x7f, 0x53, 0x27, 0xe4, 0x95, 0x1a, 0xd7, 0x9d, 0xdd, 0x1f, 0xc5, 0xb4, 0x29,
+                    0x31, 0xab, 0xf5, 0xf9, 0x51, 0x5a
+                ]
+            );
+        }
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    mod test_derive_key_with_progress {
+        use super::*;
+
+        #[test]
+        fn test_reports_segments() {
+            let mut expected = [0u8; 32];
+            let mut actual = [0u8; 32];
+            derive_key(
+                b"password",
+                b"saltsalt",
+                3,
+                32,
+                2,
+                None,
+                None,
+                &mut expected,
+            )
+            .unwrap();
+
+            let mut reports = 0;
+            let mut progress = |completed: u64, total: Option<u64>| {
+                reports += 1;
+                assert_eq!(completed, reports);
+                assert_eq!(total, Some(12));
+                true
+            };
+            derive_key_with_progress(
+                b"password",
+                b"saltsalt",
+                3,
+                32,
+                2,
+                None,
+                None,
+                &mut actual,
+                &mut progress,
+            )
+            .unwrap();
+
+            assert_eq!(actual, expected);
+            assert_eq!(reports, 12);
+        }
+
+        #[test]
+        fn test_cancel() {
+            let mut dst_out = [0u8; 32];
+            let mut last = 0;
+            let mut progress = |completed: u64, _: Option<u64>| {
+                last = completed;
+                completed < 5
+            };
+
+            assert!(derive_key_with_progress(
+                b"password",
+                b"saltsalt",
+                3,
+                32,
+                2,
+                None,
+                None,
+                &mut dst_out,
+                &mut progress,
+            )
+            .is_err());
+            assert_eq!(last, 5);
+            assert_eq!(dst_out, [0u8; 32]);
+        }
+    }
+
+    mod test_reference_index {
+        use super::*;
+
+        #[test]
+        fn test_reference_area() {
+            let instance = Instance {
+                passes: 2,
+                lanes: 2,
+                lane_length: 16,
+                segment_length: 4,
+            };
+
+            // The largest pseudo-random value references the start of the area,
+            // the smallest references the block right before the current one.
+            assert_eq!(instance.reference_index(0, 0, 2, 0, true), 0);
+            assert_eq!(instance.reference_index(0, 0, 2, 0xFFFF_FFFF, true), 0);
+            assert_eq!(instance.reference_index(0, 1, 1, 0, true), 3);
+            assert_eq!(instance.reference_index(0, 1, 1, 0, false), 3);
+            assert_eq!(instance.reference_index(0, 1, 0, 0, false), 2);
+            // Later passes start after the current segment and wrap around.
+            assert_eq!(instance.reference_index(1, 0, 0, 0, true), 14);
+            assert_eq!(instance.reference_index(1, 0, 0, 0xFFFF_FFFF, true), 4);
+            assert_eq!(instance.reference_index(1, 3, 0, 0xFFFF_FFFF, true), 0);
+        }
+    }
+}
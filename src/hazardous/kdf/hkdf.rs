@@ -32,8 +32,22 @@
 //! # Errors:
 //! An error will be returned if:
 //! - The length of `dst_out` is less than 1.
-//! - The length of `dst_out` is greater than 255 * [`SHA512_OUTSIZE`].
+//! - The length of `dst_out` is greater than 255 * `OUTSIZE` of the hash
+//!   function, which is [`SHA512_OUTSIZE`] for the SHA-512 based functions.
 //! - The derived key does not match the expected when verifying.
+//! - The pseudorandom key passed to [`Hkdf::from_prk()`] is shorter than the
+//!   output size of the hash function.
+//!
+//! # Performance:
+//! [`expand()`] keys HMAC with the pseudorandom key on every call. When
+//! deriving several keys from the same pseudorandom key, such as in a key
+//! schedule, create an [`Expander`] once and call [`Expander::expand()`] for
+//! each `info` instead.
+//!
+//! # Generic usage:
+//! [`Hkdf`] is HKDF over any hash function implementing [`ShaHash`]. It holds
+//! HMAC keyed with the pseudorandom key, so that it can be passed around and
+//! expanded any number of times, like [`Expander`].
 //!
 //! # Security:
 //! - Salts should always be generated using a CSPRNG.
@@ -56,64 +70,217 @@
 //! let exp_okm = okm_out;
 //!
 //! assert!(hkdf::verify(&exp_okm, &salt, "IKM".as_bytes(), None, &mut okm_out).is_ok());
+//!
+//! // Deriving several keys from the same pseudorandom key
+//! let prk = hkdf::extract(&salt, "IKM".as_bytes())?;
+//! let mut expander = hkdf::Expander::new(&prk)?;
+//! let mut client_key = [0u8; 32];
+//! let mut server_key = [0u8; 32];
+//! expander.expand(Some(b"client"), &mut client_key)?;
+//! expander.expand(Some(b"server"), &mut server_key)?;
+//!
+//! // The same, generic over the hash function
+//! use orion::hazardous::{hash::sha512::Sha512, kdf::hkdf::Hkdf};
+//!
+//! let hkdf = Hkdf::<Sha512>::extract(&salt, "IKM".as_bytes())?;
+//! hkdf.expand(Some(b"client"), &mut okm_out)?;
+//! assert_eq!(okm_out, client_key);
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
 //! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
 //! [`SHA512_OUTSIZE`]: ../../hash/sha512/constant.SHA512_OUTSIZE.html
+//! [`expand()`]: fn.expand.html
+//! [`Expander`]: struct.Expander.html
+//! [`Expander::expand()`]: struct.Expander.html#method.expand
+//! [`Hkdf`]: struct.Hkdf.html
+//! [`Hkdf::from_prk()`]: struct.Hkdf.html#method.from_prk
+//! [`ShaHash`]: ../../hash/trait.ShaHash.html
 
 use crate::{
     errors::UnknownCryptoError,
     hazardous::{
-        hash::sha512::SHA512_OUTSIZE,
-        mac::hmac::{self, SecretKey},
+        hash::{
+            sha512::{Sha512, SHA512_OUTSIZE},
+            ShaHash, MAX_SHA_OUTSIZE,
+        },
+        mac::hmac::{self, HmacGeneric},
     },
     util,
 };
+use zeroize::Zeroize;
+
+/// The HKDF extract step, generic over the hash function. The pseudorandom
+/// key is written to `prk_out`, which must be `H::OUTSIZE` bytes.
+pub(crate) fn extract_into<H: ShaHash>(
+    salt: &[u8],
+    ikm: &[u8],
+    prk_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut prk = HmacGeneric::<H>::new(salt)?;
+    prk.update(ikm)?;
+    prk.finalize_into(prk_out)
+}
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
 /// The HKDF extract step.
 pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<hmac::Tag, UnknownCryptoError> {
-    let mut prk = hmac::Hmac::new(&SecretKey::from_slice(salt)?);
-    prk.update(ikm)?;
-    prk.finalize()
+    let mut prk = [0u8; SHA512_OUTSIZE];
+    extract_into::<Sha512>(salt, ikm, &mut prk)?;
+    let tag = hmac::Tag::from(prk);
+    prk.zeroize();
+
+    Ok(tag)
 }
 
-#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
-/// The HKDF expand step.
-pub fn expand(
-    prk: &hmac::Tag,
-    info: Option<&[u8]>,
-    dst_out: &mut [u8],
-) -> Result<(), UnknownCryptoError> {
-    if dst_out.len() > 255 * SHA512_OUTSIZE {
-        return Err(UnknownCryptoError);
+#[derive(Clone)]
+/// The HKDF expand step, generic over the hash function.
+pub(crate) struct ExpanderGeneric<H: ShaHash> {
+    hmac: HmacGeneric<H>,
+}
+
+impl<H: ShaHash> ExpanderGeneric<H> {
+    /// Initialize with the pseudorandom key `prk`.
+    pub(crate) fn new(prk: &[u8]) -> Result<Self, UnknownCryptoError> {
+        Ok(Self {
+            hmac: HmacGeneric::new(prk)?,
+        })
     }
-    if dst_out.is_empty() {
-        return Err(UnknownCryptoError);
+
+    /// The HKDF expand step, reusing the HMAC state keyed with the pseudorandom key.
+    pub(crate) fn expand(
+        &mut self,
+        info: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        if dst_out.len() > 255 * H::OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+        if dst_out.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        let optional_info = info.unwrap_or(&[0u8; 0]);
+        let hmac = &mut self.hmac;
+        hmac.reset();
+        let okm_len = dst_out.len();
+
+        for (idx, hlen_block) in dst_out.chunks_mut(H::OUTSIZE).enumerate() {
+            let block_len = hlen_block.len();
+
+            hmac.update(optional_info)?;
+            hmac.update(&[idx as u8 + 1_u8])?;
+            if block_len == H::OUTSIZE {
+                hmac.finalize_into(hlen_block)?;
+            } else {
+                let mut last_block = [0u8; MAX_SHA_OUTSIZE];
+                hmac.finalize_into(&mut last_block[..H::OUTSIZE])?;
+                hlen_block.copy_from_slice(&last_block[..block_len]);
+                last_block.zeroize();
+            }
+
+            // Check if it's the last iteration, if yes don't process anything
+            if block_len < H::OUTSIZE || (block_len * (idx + 1) == okm_len) {
+                break;
+            } else {
+                hmac.reset();
+                hmac.update(hlen_block)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+/// The HKDF expand step, keyed with a pseudorandom key once and reusable for
+/// any number of `expand()` calls.
+pub struct Expander {
+    inner: ExpanderGeneric<Sha512>,
+}
+
+impl core::fmt::Debug for Expander {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Expander {{ hmac: [***OMITTED***] }}")
     }
+}
 
-    let optional_info = info.unwrap_or(&[0u8; 0]);
+impl Expander {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize an `Expander` with the pseudorandom key `prk` from `extract()`.
+    pub fn new(prk: &hmac::Tag) -> Result<Self, UnknownCryptoError> {
+        Ok(Self {
+            inner: ExpanderGeneric::new(prk.unprotected_as_bytes())?,
+        })
+    }
 
-    let mut hmac = hmac::Hmac::new(&hmac::SecretKey::from_slice(&prk.unprotected_as_bytes())?);
-    let okm_len = dst_out.len();
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The HKDF expand step, reusing the HMAC state keyed with the pseudorandom key.
+    pub fn expand(
+        &mut self,
+        info: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        self.inner.expand(info, dst_out)
+    }
+}
 
-    for (idx, hlen_block) in dst_out.chunks_mut(SHA512_OUTSIZE).enumerate() {
-        let block_len = hlen_block.len();
+#[derive(Clone)]
+/// HKDF over the hash function `H`, holding HMAC keyed with the pseudorandom
+/// key.
+pub struct Hkdf<H: ShaHash> {
+    expander: ExpanderGeneric<H>,
+}
 
-        hmac.update(optional_info)?;
-        hmac.update(&[idx as u8 + 1_u8])?;
-        hlen_block.copy_from_slice(&hmac.finalize()?.unprotected_as_bytes()[..block_len]);
+impl<H: ShaHash> core::fmt::Debug for Hkdf<H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Hkdf {{ expander: [***OMITTED***] }}")
+    }
+}
 
-        // Check if it's the last iteration, if yes don't process anything
-        if block_len < SHA512_OUTSIZE || (block_len * (idx + 1) == okm_len) {
-            break;
-        } else {
-            hmac.reset();
-            hmac.update(&hlen_block)?;
+impl<H: ShaHash> Hkdf<H> {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The HKDF extract step, computing the pseudorandom key from `salt` and `ikm`.
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut prk = [0u8; MAX_SHA_OUTSIZE];
+        let hkdf = extract_into::<H>(salt, ikm, &mut prk[..H::OUTSIZE])
+            .and_then(|_| Self::from_prk(&prk[..H::OUTSIZE]));
+        prk.zeroize();
+
+        hkdf
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Use the existing pseudorandom key `prk`, skipping the extract step.
+    pub fn from_prk(prk: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if prk.len() < H::OUTSIZE {
+            return Err(UnknownCryptoError);
         }
+
+        Ok(Self {
+            expander: ExpanderGeneric::new(prk)?,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The HKDF expand step, writing output keying material for `info` to
+    /// `dst_out`.
+    pub fn expand(
+        &self,
+        info: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        self.expander.clone().expand(info, dst_out)
     }
+}
 
-    Ok(())
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The HKDF expand step.
+pub fn expand(
+    prk: &hmac::Tag,
+    info: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    Expander::new(prk)?.expand(info, dst_out)
 }
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -184,6 +351,107 @@ mod public {
         }
     }
 
+    mod test_expander {
+        use super::*;
+
+        #[test]
+        fn expander_same_as_expand() {
+            let prk = extract(b"salt", b"IKM").unwrap();
+            let mut expander = Expander::new(&prk).unwrap();
+
+            // Covers: partial block, exactly one block and several blocks,
+            // with the same `Expander` reused for all.
+            for (info, len) in [(&b"a"[..], 32usize), (b"", 64), (b"bb", 130)].iter() {
+                let mut expected = [0u8; 130];
+                let mut actual = [0u8; 130];
+                expand(&prk, Some(info), &mut expected[..*len]).unwrap();
+                expander.expand(Some(info), &mut actual[..*len]).unwrap();
+                assert_eq!(expected[..], actual[..]);
+            }
+        }
+
+        #[test]
+        fn expander_reusable_after_err() {
+            let prk = extract(b"salt", b"IKM").unwrap();
+            let mut expander = Expander::new(&prk).unwrap();
+
+            let mut expected = [0u8; 32];
+            expander.expand(None, &mut expected).unwrap();
+            assert!(expander.expand(None, &mut [0u8; 0]).is_err());
+            assert!(expander.expand(None, &mut [0u8; 16321]).is_err());
+
+            let mut actual = [0u8; 32];
+            expander.expand(None, &mut actual).unwrap();
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_debug_impl() {
+            let prk = extract(b"salt", b"IKM").unwrap();
+            let debug = format!("{:?}", Expander::new(&prk).unwrap());
+            assert_eq!(debug, "Expander { hmac: [***OMITTED***] }");
+        }
+    }
+
+    mod test_hkdf_generic {
+        use super::*;
+
+        #[test]
+        fn same_as_functions() {
+            let hkdf = Hkdf::<Sha512>::extract(b"salt", b"ikm").unwrap();
+            let prk = extract(b"salt", b"ikm").unwrap();
+
+            // Also covers a partial last block.
+            for len in [1usize, 64, 100, 255 * SHA512_OUTSIZE].iter() {
+                let mut expected = [0u8; 255 * SHA512_OUTSIZE];
+                let mut actual = [0u8; 255 * SHA512_OUTSIZE];
+                expand(&prk, Some(b"info"), &mut expected[..*len]).unwrap();
+                hkdf.expand(Some(b"info"), &mut actual[..*len]).unwrap();
+                assert_eq!(expected[..], actual[..]);
+            }
+
+            let from_prk = Hkdf::<Sha512>::from_prk(prk.unprotected_as_bytes()).unwrap();
+            let mut expected = [0u8; 32];
+            let mut actual = [0u8; 32];
+            hkdf.expand(None, &mut expected).unwrap();
+            from_prk.expand(None, &mut actual).unwrap();
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        fn expand_repeatable() {
+            let hkdf = Hkdf::<Sha512>::extract(b"salt", b"ikm").unwrap();
+            let mut first = [0u8; 100];
+            let mut second = [0u8; 100];
+            hkdf.expand(Some(b"info"), &mut first).unwrap();
+            hkdf.expand(Some(b"info"), &mut second).unwrap();
+            assert_eq!(first[..], second[..]);
+
+            hkdf.expand(Some(b"other"), &mut second).unwrap();
+            assert_ne!(first[..], second[..]);
+        }
+
+        #[test]
+        fn err_on_bad_lengths() {
+            let hkdf = Hkdf::<Sha512>::extract(b"", b"").unwrap();
+            assert!(hkdf.expand(None, &mut []).is_err());
+            assert!(hkdf
+                .expand(None, &mut [0u8; 255 * SHA512_OUTSIZE + 1])
+                .is_err());
+
+            assert!(Hkdf::<Sha512>::from_prk(&[0u8; SHA512_OUTSIZE - 1]).is_err());
+            assert!(Hkdf::<Sha512>::from_prk(&[0u8; SHA512_OUTSIZE + 1]).is_ok());
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_debug_impl() {
+            let hkdf = Hkdf::<Sha512>::extract(b"", b"").unwrap();
+            assert_eq!(format!("{:?}", hkdf), "Hkdf { expander: [***OMITTED***] }");
+        }
+    }
+
     #[cfg(feature = "safe_api")]
     // Mark safe_api because currently it only contains proptests.
     mod test_derive_key {
@@ -29,3 +29,11 @@ pub mod pbkdf2;
 #[cfg(any(feature = "safe_api", feature = "alloc"))]
 /// Argon2i password hashing function as described in the [P-H-C specification](https://github.com/P-H-C/phc-winner-argon2/blob/master/argon2-specs.pdf).
 pub mod argon2i;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Argon2id password hashing function as specified in the [RFC 9106](https://www.rfc-editor.org/rfc/rfc9106.html).
+pub mod argon2id;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// scrypt password-based key derivation function as specified in the [RFC 7914](https://www.rfc-editor.org/rfc/rfc7914).
+pub mod scrypt;
@@ -22,21 +22,34 @@
 
 //! # Parameters:
 //! - `password`: Password.
+//! - `prf`: The pseudorandom function used by [`derive_key_with_prf()`], which
+//!   must already be keyed with the password.
 //! - `salt`: Salt value.
 //! - `iterations`: Iteration count.
 //! - `dst_out`: Destination buffer for the derived key. The length of the
 //!   derived key is implied by the length of `dst_out`.
 //! - `expected`: The expected derived key.
 //!
+//! # Parallelism:
+//! If `dst_out` spans multiple HMAC-SHA512 outputs and `iterations` is at least
+//! 1000, the independent output blocks are computed in parallel. With `safe_api`
+//! this spawns up to 8 threads per call, and the blocks of any thread that
+//! cannot be spawned are computed on the calling thread. With the `rayon`
+//! feature, rayon's global thread pool is used instead. On `wasm32`, where
+//! threads cannot be spawned, the blocks are computed sequentially unless
+//! `rayon` is enabled. The derived key is the same either way.
+//!
 //! # Errors:
 //! An error will be returned if:
 //! - The length of `dst_out` is less than 1.
 //! - The specified iteration count is less than 1.
+//! - The output size of `prf` is greater than 64.
 //! - The hashed password does not match the expected when verifying.
 //!
 //! # Panics:
 //! A panic will occur if:
-//! - The length of `dst_out` is greater than (2^32 - 1) * 64.
+//! - The length of `dst_out` is greater than (2^32 - 1) * 64, or (2^32 - 1)
+//!   times the output size of `prf` for [`derive_key_with_prf()`].
 //!
 //! # Security:
 //! - Use [`Password::generate()`] to randomly generate a password of 128 bytes.
@@ -62,17 +75,34 @@
 //! assert!(pbkdf2::verify(&expected_dk, &password, &salt, 10000, &mut dst_out).is_ok());
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
+//!
+//! # Generic usage:
+//! [`derive_key_with_prf()`] computes PBKDF2 over any [`Prf`], such as HMAC
+//! with another hash function, keyed BLAKE2b or KMAC. This is needed for
+//! formats that specify e.g. PBKDF2-HMAC-SHA256 or PBKDF2-HMAC-SHA1.
+//!
+//! ```rust
+//! use orion::hazardous::{hash::sha256::Sha256, kdf::pbkdf2, mac::hmac::HmacGeneric};
+//!
+//! let prf = HmacGeneric::<Sha256>::new(b"Secret password")?;
+//! let mut dst_out = [0u8; 32];
+//! pbkdf2::derive_key_with_prf(prf, b"Some salt", 10000, &mut dst_out)?;
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
 //! [`Password::generate()`]: struct.Password.html#method.generate
+//! [`derive_key_with_prf()`]: fn.derive_key_with_prf.html
+//! [`Prf`]: ../../mac/trait.Prf.html
 //! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
 
 use crate::{
     errors::UnknownCryptoError,
     hazardous::{
-        hash::sha512::{SHA512_BLOCKSIZE, SHA512_OUTSIZE},
-        mac::hmac,
+        hash::sha512::{Sha512, SHA512_BLOCKSIZE},
+        mac::{hmac::HmacGeneric, Prf, MAX_PRF_OUTSIZE},
     },
     util,
 };
+use zeroize::Zeroize;
 
 construct_hmac_key! {
     /// A type to represent the `Password` that PBKDF2 hashes.
@@ -88,41 +118,186 @@ construct_hmac_key! {
     /// # Panics:
     /// A panic will occur if:
     /// - Failure to generate random bytes securely.
-    (Password, test_pbkdf2_password, SHA512_BLOCKSIZE)
+    (Password, test_pbkdf2_password, Sha512, SHA512_BLOCKSIZE)
 }
 
 /// The F function as described in the RFC.
-fn function_f(
+fn function_f<P: Prf>(
     salt: &[u8],
     iterations: usize,
     index: u32,
     dk_block: &mut [u8],
     block_len: usize,
-    hmac: &mut hmac::Hmac,
+    prf: &mut P,
 ) -> Result<(), UnknownCryptoError> {
-    hmac.update(salt)?;
-    hmac.update(&index.to_be_bytes())?;
+    prf.update(salt)?;
+    prf.update(&index.to_be_bytes())?;
 
-    let mut u_step = hmac.finalize()?;
-    dk_block.copy_from_slice(&u_step.unprotected_as_bytes()[..block_len]);
+    let mut u_step_buf = [0u8; MAX_PRF_OUTSIZE];
+    let u_step = &mut u_step_buf[..prf.output_size()];
+    prf.finalize_into(u_step)?;
+    dk_block.copy_from_slice(&u_step[..block_len]);
 
     if iterations > 1 {
         for _ in 1..iterations {
-            hmac.reset();
-            hmac.update(u_step.unprotected_as_bytes())?;
-            u_step = hmac.finalize()?;
-            xor_slices!(u_step.unprotected_as_bytes(), dk_block);
+            prf.reset();
+            prf.update(u_step)?;
+            prf.finalize_into(u_step)?;
+            xor_slices!(u_step, dk_block);
         }
     }
 
+    u_step_buf.zeroize();
+
     Ok(())
 }
 
+/// The minimum iteration count for which output blocks are computed in
+/// parallel. Below this, spawning threads costs more than it saves.
+#[cfg(any(
+    all(feature = "safe_api", not(target_arch = "wasm32")),
+    feature = "rayon"
+))]
+const PARALLEL_MIN_ITERATIONS: usize = 1000;
+
+#[cfg(all(
+    feature = "safe_api",
+    not(feature = "rayon"),
+    not(target_arch = "wasm32")
+))]
+/// The maximum amount of threads used to compute output blocks.
+const PARALLEL_MAX_THREADS: usize = 8;
+
+/// Compute consecutive output blocks of `dk`, the first of which has the
+/// one-based index `first_block_idx`.
+fn derive_blocks<P: Prf>(
+    prf: &mut P,
+    salt: &[u8],
+    iterations: usize,
+    first_block_idx: u32,
+    dk: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    for (idx, dk_block) in dk.chunks_mut(prf.output_size()).enumerate() {
+        // If this panics, then the size limit for PBKDF2 is reached.
+        let block_idx = first_block_idx.checked_add(idx as u32).unwrap();
+
+        function_f(salt, iterations, block_idx, dk_block, dk_block.len(), prf)?;
+        prf.reset();
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rayon")]
+/// Compute all output blocks of `dst_out` in parallel, using rayon's thread pool.
+fn derive_blocks_parallel<P: Prf + 'static>(
+    prf: &P,
+    salt: &[u8],
+    iterations: usize,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    use rayon::prelude::*;
+
+    dst_out
+        .par_chunks_mut(prf.output_size())
+        .enumerate()
+        .try_for_each_init(
+            || prf.clone(),
+            |prf, (idx, dk_block)| {
+                // If this panics, then the size limit for PBKDF2 is reached.
+                let block_idx = (1u32).checked_add(idx as u32).unwrap();
+                derive_blocks(prf, salt, iterations, block_idx, dk_block)
+            },
+        )
+}
+
+#[cfg(all(
+    feature = "safe_api",
+    not(feature = "rayon"),
+    not(target_arch = "wasm32")
+))]
+/// Compute all output blocks of `dst_out` in parallel. The output is split into
+/// segments of whole blocks, one per thread, the first of which is computed on
+/// the calling thread. Segments for which a thread cannot be spawned are also
+/// computed on the calling thread.
+fn derive_blocks_parallel<P: Prf + 'static>(
+    prf: &P,
+    salt: &[u8],
+    iterations: usize,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let blocks = dst_out.chunks(prf.output_size()).len();
+    let threads = core::cmp::min(blocks, PARALLEL_MAX_THREADS);
+    let blocks_per_segment = (blocks - 1) / threads + 1;
+    let segment_len = blocks_per_segment * prf.output_size();
+
+    let mut handles = Vec::with_capacity(threads - 1);
+    for (segment_idx, segment) in dst_out.chunks(segment_len).enumerate().skip(1) {
+        let mut prf = prf.clone();
+        let salt = salt.to_vec();
+        let dk_len = segment.len();
+        // If this panics, then the size limit for PBKDF2 is reached.
+        let first_block_idx = (1u32)
+            .checked_add((segment_idx * blocks_per_segment) as u32)
+            .unwrap();
+
+        let handle =
+            std::thread::Builder::new().spawn(move || -> Result<Vec<u8>, UnknownCryptoError> {
+                let mut dk = vec![0u8; dk_len];
+                match derive_blocks(&mut prf, &salt, iterations, first_block_idx, &mut dk) {
+                    Ok(()) => Ok(dk),
+                    Err(err) => {
+                        dk.iter_mut().zeroize();
+                        Err(err)
+                    }
+                }
+            });
+        // If the thread cannot be spawned, the segment is computed on the
+        // calling thread instead.
+        handles.push((first_block_idx, handle.ok()));
+    }
+
+    let mut segments = dst_out.chunks_mut(segment_len);
+    let mut prf = prf.clone();
+    derive_blocks(&mut prf, salt, iterations, 1, segments.next().unwrap())?;
+
+    let mut result = Ok(());
+    for ((first_block_idx, handle), segment) in handles.into_iter().zip(segments) {
+        // Join all threads, even if one has failed.
+        let segment_result = match handle {
+            Some(handle) => handle
+                .join()
+                .expect("PBKDF2 worker thread panicked")
+                .map(|mut dk| {
+                    segment.copy_from_slice(&dk);
+                    dk.iter_mut().zeroize();
+                }),
+            None => derive_blocks(&mut prf, salt, iterations, first_block_idx, segment),
+        };
+        if segment_result.is_err() {
+            result = segment_result;
+        }
+    }
+
+    result
+}
+
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
-/// PBKDF2-SHA512 (Password-Based Key Derivation Function 2) as specified in the
-/// [RFC 8018](https://tools.ietf.org/html/rfc8018).
-pub fn derive_key(
-    password: &Password,
+/// PBKDF2 with the pseudorandom function `prf`, which must already be keyed
+/// with the password.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The length of `dst_out` is less than 1.
+/// - The specified iteration count is less than 1.
+/// - The output size of `prf` is greater than 64.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - The length of `dst_out` is greater than (2^32 - 1) times the output size
+///   of `prf`.
+pub fn derive_key_with_prf<P: Prf + 'static>(
+    mut prf: P,
     salt: &[u8],
     iterations: usize,
     dst_out: &mut [u8],
@@ -133,27 +308,34 @@ pub fn derive_key(
     if dst_out.is_empty() {
         return Err(UnknownCryptoError);
     }
+    if prf.output_size() == 0 || prf.output_size() > MAX_PRF_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
 
-    let mut hmac = hmac::Hmac::new(&hmac::SecretKey::from_slice(
-        &password.unprotected_as_bytes(),
-    )?);
-
-    for (idx, dk_block) in dst_out.chunks_mut(SHA512_OUTSIZE).enumerate() {
-        // If this panics, then the size limit for PBKDF2 is reached.
-        let block_idx = (1u32).checked_add(idx as u32).unwrap();
-
-        function_f(
-            salt,
-            iterations,
-            block_idx,
-            dk_block,
-            dk_block.len(),
-            &mut hmac,
-        )?;
-        hmac.reset();
+    #[cfg(any(
+        all(feature = "safe_api", not(target_arch = "wasm32")),
+        feature = "rayon"
+    ))]
+    {
+        if dst_out.len() > prf.output_size() && iterations >= PARALLEL_MIN_ITERATIONS {
+            return derive_blocks_parallel(&prf, salt, iterations, dst_out);
+        }
     }
 
-    Ok(())
+    derive_blocks(&mut prf, salt, iterations, 1, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// PBKDF2-SHA512 (Password-Based Key Derivation Function 2) as specified in the
+/// [RFC 8018](https://tools.ietf.org/html/rfc8018).
+pub fn derive_key(
+    password: &Password,
+    salt: &[u8],
+    iterations: usize,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let prf = HmacGeneric::<Sha512>::new(password.unprotected_as_bytes())?;
+    derive_key_with_prf(prf, salt, iterations, dst_out)
 }
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -272,4 +454,142 @@ mod public {
             assert!(derive_key(&password, salt, iterations, &mut okm_out).is_err());
         }
     }
+
+    mod test_derive_key_with_prf {
+        use super::*;
+        use crate::hazardous::hash::sha256::Sha256;
+        use crate::hazardous::mac::{
+            blake2b::{self, Blake2bMac},
+            kmac,
+        };
+
+        #[test]
+        // RFC 7914, Section 11.
+        fn test_rfc7914_hmac_sha256() {
+            let prf = HmacGeneric::<Sha256>::new(b"passwd").unwrap();
+            let mut dk = [0u8; 64];
+            derive_key_with_prf(prf, b"salt", 1, &mut dk).unwrap();
+            assert_eq!(
+                dk[..],
+                [
+                    0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25,
+                    0x44, 0xb6, 0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b,
+                    0x9d, 0x57, 0xc2, 0x0d, 0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6,
+                    0x45, 0x99, 0x16, 0x64, 0xb3, 0x9d, 0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45,
+                    0xb1, 0xe3, 0x0b, 0xd5, 0x09, 0x11, 0x20, 0x41, 0xd3, 0xa1, 0x97, 0x83,
+                ][..]
+            );
+
+            let prf = HmacGeneric::<Sha256>::new(b"Password").unwrap();
+            derive_key_with_prf(prf, b"NaCl", 80000, &mut dk).unwrap();
+            assert_eq!(
+                dk[..],
+                [
+                    0x4d, 0xdc, 0xd8, 0xf6, 0x0b, 0x98, 0xbe, 0x21, 0x83, 0x0c, 0xee, 0x5e, 0xf2,
+                    0x27, 0x01, 0xf9, 0x64, 0x1a, 0x44, 0x18, 0xd0, 0x4c, 0x04, 0x14, 0xae, 0xff,
+                    0x08, 0x87, 0x6b, 0x34, 0xab, 0x56, 0xa1, 0xd4, 0x25, 0xa1, 0x22, 0x58, 0x33,
+                    0x54, 0x9a, 0xdb, 0x84, 0x1b, 0x51, 0xc9, 0xb3, 0x17, 0x6a, 0x27, 0x2b, 0xde,
+                    0xbb, 0xa1, 0xd0, 0x78, 0x47, 0x8f, 0x62, 0xb3, 0x97, 0xf3, 0x3c, 0x8d,
+                ][..]
+            );
+        }
+
+        #[test]
+        fn hmac_sha512_same_as_derive_key() {
+            let password = Password::from_slice(b"password").unwrap();
+            let mut expected = [0u8; 100];
+            let mut actual = [0u8; 100];
+
+            derive_key(&password, b"salt", 2, &mut expected).unwrap();
+            let prf = HmacGeneric::<Sha512>::new(b"password").unwrap();
+            derive_key_with_prf(prf, b"salt", 2, &mut actual).unwrap();
+
+            assert_eq!(expected[..], actual[..]);
+        }
+
+        #[test]
+        fn smaller_outsize() {
+            // Keyed BLAKE2b with a 16-byte output, to test a PRF with a
+            // smaller output size.
+            let key = blake2b::SecretKey::from_slice(b"password").unwrap();
+            let mut prf = Blake2bMac::new(&key, 16).unwrap();
+            let mut dk = [0u8; 40];
+            derive_key_with_prf(prf.clone(), b"salt", 1, &mut dk).unwrap();
+
+            // With one iteration, each block is the PRF of salt || INT(i).
+            for (idx, block) in dk.chunks(16).enumerate() {
+                prf.reset();
+                prf.update(b"salt").unwrap();
+                prf.update(&(idx as u32 + 1).to_be_bytes()).unwrap();
+                let tag = prf.finalize().unwrap();
+                assert_eq!(block, &tag.unprotected_as_bytes()[..block.len()]);
+            }
+
+            assert!(derive_key_with_prf(prf.clone(), b"salt", 0, &mut dk).is_err());
+            assert!(derive_key_with_prf(prf, b"salt", 1, &mut []).is_err());
+        }
+
+        #[test]
+        fn outsize_too_large_err() {
+            let key = kmac::SecretKey::from_slice(b"password password").unwrap();
+            let mut dk = [0u8; 40];
+
+            let prf = kmac::Kmac256::new(&key, b"", MAX_PRF_OUTSIZE + 1).unwrap();
+            assert!(derive_key_with_prf(prf, b"salt", 1, &mut dk).is_err());
+            let prf = kmac::Kmac256::new(&key, b"", MAX_PRF_OUTSIZE).unwrap();
+            assert!(derive_key_with_prf(prf, b"salt", 1, &mut dk).is_ok());
+        }
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+#[cfg(any(
+    all(feature = "safe_api", not(target_arch = "wasm32")),
+    feature = "rayon"
+))]
+mod private {
+    use super::*;
+
+    mod test_derive_blocks_parallel {
+        use super::*;
+
+        #[test]
+        fn parallel_same_as_sequential() {
+            let hmac = HmacGeneric::<Sha512>::new(b"password").unwrap();
+            let salt = b"salt";
+
+            // Covers: partial last block, more blocks than threads and segments
+            // of uneven length.
+            for len in [65usize, 128, 129, 8 * 64, 9 * 64 + 1, 17 * 64 - 3].iter() {
+                let mut parallel = [0u8; 17 * 64];
+                let mut sequential = [0u8; 17 * 64];
+
+                derive_blocks_parallel(&hmac, salt, 3, &mut parallel[..*len]).unwrap();
+                derive_blocks(&mut hmac.clone(), salt, 3, 1, &mut sequential[..*len]).unwrap();
+
+                assert_eq!(parallel[..], sequential[..]);
+            }
+        }
+
+        #[test]
+        fn derive_key_parallel_same_as_sequential() {
+            let password = Password::from_slice(b"password").unwrap();
+            let mut parallel = [0u8; 3 * 64 + 7];
+            let mut sequential = [0u8; 3 * 64 + 7];
+
+            derive_key(&password, b"salt", PARALLEL_MIN_ITERATIONS, &mut parallel).unwrap();
+            let mut hmac = HmacGeneric::<Sha512>::new(b"password").unwrap();
+            derive_blocks(
+                &mut hmac,
+                b"salt",
+                PARALLEL_MIN_ITERATIONS,
+                1,
+                &mut sequential,
+            )
+            .unwrap();
+
+            assert_eq!(parallel[..], sequential[..]);
+        }
+    }
 }
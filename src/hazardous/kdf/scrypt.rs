@@ -0,0 +1,654 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! scrypt version as specified in [RFC 7914](https://www.rfc-editor.org/rfc/rfc7914),
+//! with PBKDF2-HMAC-SHA256 and Salsa20/8. This implementation is available
+//! with features `safe_api` and `alloc`.
+//!
+//! scrypt is supported for compatibility with existing password hashes and
+//! encrypted keys, such as those created by other languages' standard
+//! libraries. New applications should use [`argon2id`] instead.
+//!
+//! The `p` independent mixing functions are computed one after another on the
+//! calling thread, so the memory use is that of a single one.
+//!
+//! # Parameters:
+//! - `expected`: The expected derived key.
+//! - `password`: Password.
+//! - `salt`: Salt value.
+//! - `n`: CPU/memory cost parameter.
+//! - `r`: Block size parameter.
+//! - `p`: Parallelization parameter.
+//! - `max_memory`: The maximum amount of memory, in bytes, that the
+//!   parameters are allowed to use.
+//! - `dst_out`: Destination buffer for the derived key. The length of the
+//!   derived key is implied by the length of `dst_out`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `n` is not a power of two greater than `1`.
+//! - `n` is `2^(16 * r)` or greater.
+//! - `r` or `p` is less than `1`.
+//! - `r * p` is `2^30` or greater.
+//! - The memory needed, which is [`required_memory()`] bytes, is greater than
+//!   `max_memory` or cannot be addressed.
+//! - The length of `dst_out` is less than `1`.
+//! - The hashed password does not match the expected when verifying.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - The length of `dst_out` is greater than (2^32 - 1) * 32.
+//!
+//! # Security:
+//! - Salts should always be generated using a CSPRNG.
+//!   [`util::secure_rand_bytes()`] can be used for this.
+//! - The minimum recommended length for a salt is `16` bytes.
+//! - [RFC 7914] suggests `n = 2^20`, `r = 8` and `p = 1` for interactive
+//!   logins, which uses 1 GiB of memory. OWASP recommends at least
+//!   `n = 2^17`, `r = 8` and `p = 1`.
+//! - `max_memory` should be low enough that the system can serve the
+//!   expected number of concurrent hashing operations, since a stored hash
+//!   with large parameters makes every verification allocate and use that
+//!   much memory. [`validate_params()`] can be used to check parameters
+//!   before storing them.
+//! - Password hashes should always be compared in constant-time.
+//!
+//! # Example:
+//! ```rust
+//! use orion::{hazardous::kdf::scrypt, util};
+//!
+//! let mut salt = [0u8; 16];
+//! util::secure_rand_bytes(&mut salt)?;
+//! let password = b"Secret password";
+//! let mut dst_out = [0u8; 64];
+//!
+//! // Parameters that are read from a stored hash, limited to 64 MiB.
+//! let (n, r, p) = (1 << 14, 8, 1);
+//! let max_memory = 64 * 1024 * 1024;
+//!
+//! scrypt::derive_key(password, &salt, n, r, p, max_memory, &mut dst_out)?;
+//!
+//! let expected_dk = dst_out;
+//!
+//! assert!(scrypt::verify(&expected_dk, password, &salt, n, r, p, max_memory, &mut dst_out).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`argon2id`]: ../argon2id/index.html
+//! [`required_memory()`]: fn.required_memory.html
+//! [`validate_params()`]: fn.validate_params.html
+//! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+//! [RFC 7914]: https://www.rfc-editor.org/rfc/rfc7914
+
+use super::pbkdf2::derive_key_with_prf;
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::Sha256;
+use crate::hazardous::mac::hmac::HmacGeneric;
+use crate::util;
+use crate::util::endianness::{load_u32_into_le, store_u32_into_le};
+use zeroize::Zeroize;
+
+/// The amount of 32-bit words in a Salsa20 block.
+const SALSA_WORDS: usize = 16;
+
+/// The size of a block of `BlockMix` for `r = 1`, in bytes.
+const BLOCK_SIZE: usize = 128;
+
+/// The Salsa20 quarter round on the words `a`, `b`, `c` and `d` of `x`.
+macro_rules! QUARTER_ROUND {
+    ($x:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+        $x[$b] ^= $x[$a].wrapping_add($x[$d]).rotate_left(7);
+        $x[$c] ^= $x[$b].wrapping_add($x[$a]).rotate_left(9);
+        $x[$d] ^= $x[$c].wrapping_add($x[$b]).rotate_left(13);
+        $x[$a] ^= $x[$d].wrapping_add($x[$c]).rotate_left(18);
+    };
+}
+
+/// The Salsa20/8 core, applied to `block` in place.
+fn salsa20_8(block: &mut [u32]) {
+    debug_assert_eq!(block.len(), SALSA_WORDS);
+    let mut x = [0u32; SALSA_WORDS];
+    x.copy_from_slice(block);
+
+    for _ in 0..4 {
+        // Columns.
+        QUARTER_ROUND!(x, 0, 4, 8, 12);
+        QUARTER_ROUND!(x, 5, 9, 13, 1);
+        QUARTER_ROUND!(x, 10, 14, 2, 6);
+        QUARTER_ROUND!(x, 15, 3, 7, 11);
+        // Rows.
+        QUARTER_ROUND!(x, 0, 1, 2, 3);
+        QUARTER_ROUND!(x, 5, 6, 7, 4);
+        QUARTER_ROUND!(x, 10, 11, 8, 9);
+        QUARTER_ROUND!(x, 15, 12, 13, 14);
+    }
+
+    for (word, mixed) in block.iter_mut().zip(x.iter()) {
+        *word = word.wrapping_add(*mixed);
+    }
+    x.zeroize();
+}
+
+/// `scryptBlockMix` of the `2 * r` Salsa20 blocks of `input`, writing the
+/// result to `output`.
+fn block_mix(input: &[u32], output: &mut [u32], r: usize) {
+    debug_assert_eq!(input.len(), 2 * r * SALSA_WORDS);
+    debug_assert_eq!(input.len(), output.len());
+
+    let mut x = [0u32; SALSA_WORDS];
+    x.copy_from_slice(&input[(2 * r - 1) * SALSA_WORDS..]);
+
+    for (idx, block) in input.chunks_exact(SALSA_WORDS).enumerate() {
+        xor_slices!(block, x);
+        salsa20_8(&mut x);
+
+        // Even blocks go to the first half of the output, odd to the second.
+        let position = (idx / 2 + (idx % 2) * r) * SALSA_WORDS;
+        output[position..position + SALSA_WORDS].copy_from_slice(&x);
+    }
+    x.zeroize();
+}
+
+/// `Integerify()` of `block`, reduced modulo `n`, which is a power of two.
+fn integerify(block: &[u32], r: usize, n: u64) -> usize {
+    let last = (2 * r - 1) * SALSA_WORDS;
+    let value = u64::from(block[last]) | (u64::from(block[last + 1]) << 32);
+
+    (value & (n - 1)) as usize
+}
+
+/// `scryptROMix` of `b`, with `v` as the memory of `n` blocks and `x` and `y`
+/// as the working blocks.
+fn ro_mix(b: &mut [u8], n: u64, r: usize, v: &mut [u32], x: &mut [u32], y: &mut [u32]) {
+    let block_words = 2 * r * SALSA_WORDS;
+    load_u32_into_le(b, x);
+
+    for v_block in v.chunks_exact_mut(block_words) {
+        v_block.copy_from_slice(x);
+        block_mix(x, y, r);
+        x.copy_from_slice(y);
+    }
+
+    for _ in 0..n {
+        let j = integerify(x, r, n);
+        xor_slices!(&v[j * block_words..(j + 1) * block_words], x);
+        block_mix(x, y, r);
+        x.copy_from_slice(y);
+    }
+
+    store_u32_into_le(x, b);
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The amount of memory, in bytes, that [`derive_key()`] needs for the
+/// parameters `n`, `r` and `p`. This is `128 * r * (n + p)` bytes, for the
+/// `n` blocks of the mixing function and the `p` blocks it mixes.
+///
+/// [`derive_key()`]: fn.derive_key.html
+pub fn required_memory(n: u64, r: u32, p: u32) -> Result<usize, UnknownCryptoError> {
+    if n < 2 || !n.is_power_of_two() {
+        return Err(UnknownCryptoError);
+    }
+    if r < 1 || p < 1 || u64::from(r) * u64::from(p) >= 1 << 30 {
+        return Err(UnknownCryptoError);
+    }
+    if r < 4 && n >= 1 << (16 * r) {
+        return Err(UnknownCryptoError);
+    }
+
+    (BLOCK_SIZE as u64)
+        .checked_mul(u64::from(r))
+        .and_then(|block| block.checked_mul(n.checked_add(u64::from(p))?))
+        .filter(|memory| *memory <= usize::MAX as u64)
+        .map(|memory| memory as usize)
+        .ok_or(UnknownCryptoError)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Check that `n`, `r` and `p` are valid parameters, that need no more than
+/// `max_memory` bytes of memory.
+pub fn validate_params(
+    n: u64,
+    r: u32,
+    p: u32,
+    max_memory: usize,
+) -> Result<(), UnknownCryptoError> {
+    if required_memory(n, r, p)? > max_memory {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// scrypt password-based key derivation function as specified in the [RFC 7914](https://www.rfc-editor.org/rfc/rfc7914).
+pub fn derive_key(
+    password: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: u32,
+    p: u32,
+    max_memory: usize,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    validate_params(n, r, p, max_memory)?;
+    if dst_out.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let r = r as usize;
+    let block_words = 2 * r * SALSA_WORDS;
+    let prf = HmacGeneric::<Sha256>::new(password)?;
+
+    let mut b = vec![0u8; BLOCK_SIZE * r * p as usize];
+    let mut v = vec![0u32; block_words * n as usize];
+    let mut x = vec![0u32; block_words];
+    let mut y = vec![0u32; block_words];
+
+    let result = derive_key_with_prf(prf.clone(), salt, 1, &mut b).and_then(|_| {
+        for b_block in b.chunks_exact_mut(BLOCK_SIZE * r) {
+            ro_mix(b_block, n, r, &mut v, &mut x, &mut y);
+        }
+        derive_key_with_prf(prf, &b, 1, dst_out)
+    });
+
+    b.iter_mut().zeroize();
+    v.iter_mut().zeroize();
+    x.iter_mut().zeroize();
+    y.iter_mut().zeroize();
+
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify scrypt derived key in constant time.
+pub fn verify(
+    expected: &[u8],
+    password: &[u8],
+    salt: &[u8],
+    n: u64,
+    r: u32,
+    p: u32,
+    max_memory: usize,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    derive_key(password, salt, n, r, p, max_memory, dst_out)?;
+    util::secure_cmp(dst_out, expected)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    /// A memory limit that is higher than any of the small tests need.
+    const MAX_MEMORY: usize = 1 << 20;
+
+    fn decode(hex: &str, buf: &mut [u8]) {
+        assert_eq!(hex.len(), buf.len() * 2);
+        for (byte, pair) in buf.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(core::str::from_utf8(pair).unwrap(), 16).unwrap();
+        }
+    }
+
+    fn derive_hex(password: &[u8], salt: &[u8], n: u64, r: u32, p: u32, expected: &str) {
+        let mut expected_dk = vec![0u8; expected.len() / 2];
+        decode(expected, &mut expected_dk);
+        let mut dst_out = vec![0u8; expected_dk.len()];
+
+        let max_memory = required_memory(n, r, p).unwrap();
+        derive_key(password, salt, n, r, p, max_memory, &mut dst_out).unwrap();
+        assert_eq!(dst_out, expected_dk);
+        assert!(verify(
+            &expected_dk,
+            password,
+            salt,
+            n,
+            r,
+            p,
+            max_memory,
+            &mut dst_out
+        )
+        .is_ok());
+    }
+
+    mod test_derive_key {
+        use super::*;
+
+        #[test]
+        fn test_rfc7914_vector_1() {
+            derive_hex(
+                b"",
+                b"",
+                16,
+                1,
+                1,
+                "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+                 fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+            );
+        }
+
+        #[test]
+        fn test_rfc7914_vector_2() {
+            derive_hex(
+                b"password",
+                b"NaCl",
+                1024,
+                8,
+                16,
+                "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                 2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+            );
+        }
+
+        #[test]
+        fn test_rfc7914_vector_3() {
+            derive_hex(
+                b"pleaseletmein",
+                b"SodiumChloride",
+                16384,
+                8,
+                1,
+                "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2\
+                 d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+            );
+        }
+
+        #[test]
+        fn test_output_not_multiple_of_hash_size() {
+            derive_hex(
+                b"pw",
+                b"salt",
+                4,
+                2,
+                3,
+                "0ef4708ae1491078c20a9956aa57556b9f0f3b86c910326649b0289406fa6d29cc",
+            );
+        }
+
+        #[test]
+        fn test_empty_dst_out() {
+            assert!(derive_key(b"password", b"salt", 16, 1, 1, MAX_MEMORY, &mut [0u8; 0]).is_err());
+        }
+
+        #[test]
+        fn test_invalid_n() {
+            let mut dst_out = [0u8; 32];
+            assert!(derive_key(b"password", b"salt", 0, 1, 1, MAX_MEMORY, &mut dst_out).is_err());
+            assert!(derive_key(b"password", b"salt", 1, 1, 1, MAX_MEMORY, &mut dst_out).is_err());
+            assert!(derive_key(b"password", b"salt", 24, 1, 1, MAX_MEMORY, &mut dst_out).is_err());
+            assert!(derive_key(b"password", b"salt", 2, 1, 1, MAX_MEMORY, &mut dst_out).is_ok());
+        }
+
+        #[test]
+        fn test_invalid_r_and_p() {
+            let mut dst_out = [0u8; 32];
+            assert!(derive_key(b"password", b"salt", 16, 0, 1, MAX_MEMORY, &mut dst_out).is_err());
+            assert!(derive_key(b"password", b"salt", 16, 1, 0, MAX_MEMORY, &mut dst_out).is_err());
+        }
+
+        #[test]
+        fn test_max_memory() {
+            let mut dst_out = [0u8; 32];
+            let memory = required_memory(16, 1, 1).unwrap();
+            assert!(derive_key(b"password", b"salt", 16, 1, 1, memory, &mut dst_out).is_ok());
+            assert!(derive_key(b"password", b"salt", 16, 1, 1, memory - 1, &mut dst_out).is_err());
+
+            let expected = dst_out;
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                1,
+                1,
+                memory,
+                &mut dst_out
+            )
+            .is_ok());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                1,
+                1,
+                memory - 1,
+                &mut dst_out
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn test_max_memory_before_allocation() {
+            // Needs 2^62 bytes, which must be rejected before allocating.
+            let mut dst_out = [0u8; 32];
+            assert!(derive_key(
+                b"password",
+                b"salt",
+                1 << 52,
+                8,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+        }
+    }
+
+    mod test_verify {
+        use super::*;
+
+        #[test]
+        fn test_verify_wrong_params() {
+            let mut expected = [0u8; 32];
+            derive_key(b"password", b"salt", 16, 1, 1, MAX_MEMORY, &mut expected).unwrap();
+
+            let mut dst_out = [0u8; 32];
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                1,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_ok());
+            assert!(verify(
+                &expected,
+                b"Password",
+                b"salt",
+                16,
+                1,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"Salt",
+                16,
+                1,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                32,
+                1,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                2,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                1,
+                2,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn test_verify_wrong_length() {
+            let mut expected = [0u8; 32];
+            derive_key(b"password", b"salt", 16, 1, 1, MAX_MEMORY, &mut expected).unwrap();
+
+            let mut dst_out = [0u8; 31];
+            assert!(verify(
+                &expected,
+                b"password",
+                b"salt",
+                16,
+                1,
+                1,
+                MAX_MEMORY,
+                &mut dst_out
+            )
+            .is_err());
+        }
+    }
+
+    mod test_params {
+        use super::*;
+
+        #[test]
+        fn test_required_memory() {
+            assert_eq!(required_memory(16, 1, 1).unwrap(), 128 * 17);
+            assert_eq!(required_memory(1024, 8, 16).unwrap(), 128 * 8 * (1024 + 16));
+            assert_eq!(
+                required_memory(1 << 20, 8, 1).unwrap(),
+                128 * 8 * ((1 << 20) + 1)
+            );
+        }
+
+        #[test]
+        fn test_n_limited_by_r() {
+            assert!(required_memory(1 << 15, 1, 1).is_ok());
+            assert!(required_memory(1 << 16, 1, 1).is_err());
+            assert!(required_memory(1 << 47, 3, 1).is_ok());
+            assert!(required_memory(1 << 48, 3, 1).is_err());
+        }
+
+        #[test]
+        fn test_r_times_p_limit() {
+            assert!(required_memory(16, 1, (1 << 30) - 1).is_ok());
+            assert!(required_memory(16, 1, 1 << 30).is_err());
+            assert!(required_memory(16, 1 << 15, 1 << 15).is_err());
+        }
+
+        #[test]
+        fn test_memory_overflow() {
+            assert!(required_memory(1 << 63, 8, 1).is_err());
+        }
+
+        #[test]
+        fn test_validate_params_memory_limit() {
+            let memory = required_memory(1 << 14, 8, 1).unwrap();
+            assert!(validate_params(1 << 14, 8, 1, memory).is_ok());
+            assert!(validate_params(1 << 14, 8, 1, memory - 1).is_err());
+            assert!(validate_params(1 << 14, 8, 2, memory).is_err());
+            assert!(validate_params(1 << 15, 8, 1, memory).is_err());
+            assert!(validate_params(3, 8, 1, usize::MAX).is_err());
+        }
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    // From section 8 of the RFC 7914.
+    fn test_salsa20_8_core() {
+        let input: [u8; 64] = [
+            0x7e, 0x87, 0x9a, 0x21, 0x4f, 0x3e, 0xc9, 0x86, 0x7c, 0xa9, 0x40, 0xe6, 0x41, 0x71,
+            0x8f, 0x26, 0xba, 0xee, 0x55, 0x5b, 0x8c, 0x61, 0xc1, 0xb5, 0x0d, 0xf8, 0x46, 0x11,
+            0x6d, 0xcd, 0x3b, 0x1d, 0xee, 0x24, 0xf3, 0x19, 0xdf, 0x9b, 0x3d, 0x85, 0x14, 0x12,
+            0x1e, 0x4b, 0x5a, 0xc5, 0xaa, 0x32, 0x76, 0x02, 0x1d, 0x29, 0x09, 0xc7, 0x48, 0x29,
+            0xed, 0xeb, 0xc6, 0x8d, 0xb8, 0xb8, 0xc2, 0x5e,
+        ];
+        let expected: [u8; 64] = [
+            0xa4, 0x1f, 0x85, 0x9c, 0x66, 0x08, 0xcc, 0x99, 0x3b, 0x81, 0xca, 0xcb, 0x02, 0x0c,
+            0xef, 0x05, 0x04, 0x4b, 0x21, 0x81, 0xa2, 0xfd, 0x33, 0x7d, 0xfd, 0x7b, 0x1c, 0x63,
+            0x96, 0x68, 0x2f, 0x29, 0xb4, 0x39, 0x31, 0x68, 0xe3, 0xc9, 0xe6, 0xbc, 0xfe, 0x6b,
+            0xc5, 0xb7, 0xa0, 0x6d, 0x96, 0xba, 0xe4, 0x24, 0xcc, 0x10, 0x2c, 0x91, 0x74, 0x5c,
+            0x24, 0xad, 0x67, 0x3d, 0xc7, 0x61, 0x8f, 0x81,
+        ];
+
+        let mut block = [0u32; SALSA_WORDS];
+        load_u32_into_le(&input, &mut block);
+        salsa20_8(&mut block);
+        let mut actual = [0u8; 64];
+        store_u32_into_le(&block, &mut actual);
+
+        assert_eq!(actual[..], expected[..]);
+    }
+
+    #[test]
+    fn test_block_mix_ordering() {
+        // With r = 2, the output blocks are Y_0, Y_2, Y_1, Y_3.
+        let input = [1u32; 4 * SALSA_WORDS];
+        let mut output = [0u32; 4 * SALSA_WORDS];
+        block_mix(&input, &mut output, 2);
+
+        let mut y = [0u32; SALSA_WORDS];
+        y.copy_from_slice(&input[3 * SALSA_WORDS..]);
+        let mut expected = [[0u32; SALSA_WORDS]; 4];
+        for (idx, block) in input.chunks_exact(SALSA_WORDS).enumerate() {
+            xor_slices!(block, y);
+            salsa20_8(&mut y);
+            expected[idx] = y;
+        }
+
+        assert_eq!(output[..SALSA_WORDS], expected[0]);
+        assert_eq!(output[SALSA_WORDS..2 * SALSA_WORDS], expected[2]);
+        assert_eq!(output[2 * SALSA_WORDS..3 * SALSA_WORDS], expected[1]);
+        assert_eq!(output[3 * SALSA_WORDS..], expected[3]);
+    }
+}
@@ -0,0 +1,258 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! [`Blake2bMac`] is BLAKE2b in keyed mode, which can be used as a MAC or
+//! [`Prf`]. It computes the same tags as a [`Blake2b`] state initialized with
+//! a secret key, but keeps its keyed initial state, so [`reset()`] does not
+//! need the secret key again.
+//!
+//! # Parameters:
+//! - `secret_key`: The authentication key.
+//! - `size`: The desired output length for the tag.
+//! - `data`: Data to be authenticated.
+//! - `expected`: The expected authentication tag.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `size` is 0 or greater than 64.
+//! - [`finalize()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] is not `size`.
+//! - The tag does not match the expected when verifying.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2*(2^64-1) bytes of data are authenticated.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   [`SecretKey::generate()`] can be used for this. It generates
+//!   a secret key of 32 bytes.
+//! - The minimum recommended size for a secret key is 32 bytes.
+//! - The recommended minimum output size is 32.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::mac::blake2b::{Blake2bMac, SecretKey};
+//!
+//! let key = SecretKey::generate();
+//!
+//! let mut state = Blake2bMac::new(&key, 32)?;
+//! state.update(b"Some message.")?;
+//! let tag = state.finalize()?;
+//!
+//! assert!(Blake2bMac::verify(&tag, &key, b"Some message.").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`Blake2bMac`]: struct.Blake2bMac.html
+//! [`Prf`]: ../trait.Prf.html
+//! [`Blake2b`]: ../../hash/blake2b/struct.Blake2b.html
+//! [`update()`]: struct.Blake2bMac.html#method.update
+//! [`reset()`]: struct.Blake2bMac.html#method.reset
+//! [`finalize()`]: struct.Blake2bMac.html#method.finalize
+//! [`finalize_into()`]: struct.Blake2bMac.html#method.finalize_into
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+
+pub use crate::hazardous::hash::blake2b::SecretKey;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::hash::blake2b::{Blake2b, BLAKE2B_OUTSIZE},
+};
+
+construct_tag! {
+    /// A type to represent the `Tag` that keyed BLAKE2b returns.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is empty.
+    /// - `slice` is greater than 64 bytes.
+    (Tag, test_tag, 1, BLAKE2B_OUTSIZE)
+}
+
+#[derive(Clone, Debug)]
+/// Keyed BLAKE2b streaming state.
+pub struct Blake2bMac {
+    initial: Blake2b,
+    state: Blake2b,
+    size: usize,
+}
+
+impl Blake2bMac {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize a `Blake2bMac` struct with a given key and output size.
+    pub fn new(secret_key: &SecretKey, size: usize) -> Result<Self, UnknownCryptoError> {
+        let initial = Blake2b::new(Some(secret_key), size)?;
+
+        Ok(Self {
+            state: initial.clone(),
+            initial,
+            size,
+        })
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.state = self.initial.clone();
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.state.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a keyed BLAKE2b tag.
+    pub fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+        let mut tag = [0u8; BLAKE2B_OUTSIZE];
+        self.finalize_into(&mut tag[..self.size])?;
+
+        Tag::from_slice(&tag[..self.size])
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a keyed BLAKE2b tag into `dest`, which must be of the `size` the
+    /// state was initialized with.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.state.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify a keyed BLAKE2b tag in constant time. The output size is that of
+    /// `expected`.
+    pub fn verify(
+        expected: &Tag,
+        secret_key: &SecretKey,
+        data: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let mut state = Self::new(secret_key, expected.len())?;
+        state.update(data)?;
+        if &state.finalize()? == expected {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+impl super::Prf for Blake2bMac {
+    fn output_size(&self) -> usize {
+        self.size
+    }
+
+    fn reset(&mut self) {
+        Blake2bMac::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Blake2bMac::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Blake2bMac::finalize_into(self, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::hash::blake2b::Digest;
+
+    fn keyed_blake2b(secret_key: &SecretKey, size: usize, data: &[u8]) -> Digest {
+        let mut state = Blake2b::new(Some(secret_key), size).unwrap();
+        state.update(data).unwrap();
+        state.finalize().unwrap()
+    }
+
+    #[test]
+    fn test_matches_keyed_blake2b() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        for size in [1, 16, 32, 64].iter() {
+            for data_len in [0, 1, 127, 128, 129, 300].iter() {
+                let data = &[0xab; 300][..*data_len];
+                let mut state = Blake2bMac::new(&secret_key, *size).unwrap();
+                state.update(data).unwrap();
+                let tag = state.finalize().unwrap();
+
+                assert_eq!(tag.len(), *size);
+                assert_eq!(
+                    tag.unprotected_as_bytes(),
+                    keyed_blake2b(&secret_key, *size, data).as_ref()
+                );
+                assert!(Blake2bMac::verify(&tag, &secret_key, data).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let secret_key = SecretKey::from_slice(&[0x42; 64]).unwrap();
+        let mut state = Blake2bMac::new(&secret_key, 32).unwrap();
+        state.update(b"first").unwrap();
+        let first = state.finalize().unwrap();
+        assert!(state.finalize().is_err());
+        assert!(state.update(b"more").is_err());
+
+        state.reset();
+        state.update(b"second").unwrap();
+        let second = state.finalize().unwrap();
+        state.reset();
+        state.update(b"first").unwrap();
+
+        assert_eq!(state.finalize().unwrap(), first);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_invalid_size_err() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        assert!(Blake2bMac::new(&secret_key, 0).is_err());
+        assert!(Blake2bMac::new(&secret_key, BLAKE2B_OUTSIZE + 1).is_err());
+        assert!(Blake2bMac::new(&secret_key, BLAKE2B_OUTSIZE).is_ok());
+    }
+
+    #[test]
+    fn test_finalize_into_wrong_size_err() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let mut state = Blake2bMac::new(&secret_key, 32).unwrap();
+        assert!(state.finalize_into(&mut [0u8; 31]).is_err());
+        assert!(state.finalize_into(&mut [0u8; 33]).is_err());
+        assert!(state.finalize_into(&mut [0u8; 32]).is_ok());
+    }
+
+    #[test]
+    fn test_verify_err() {
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let other_key = SecretKey::from_slice(&[0x24; 32]).unwrap();
+        let mut state = Blake2bMac::new(&secret_key, 32).unwrap();
+        state.update(b"data").unwrap();
+        let tag = state.finalize().unwrap();
+
+        assert!(Blake2bMac::verify(&tag, &other_key, b"data").is_err());
+        assert!(Blake2bMac::verify(&tag, &secret_key, b"Data").is_err());
+        let truncated = Tag::from_slice(&tag.unprotected_as_bytes()[..16]).unwrap();
+        assert!(Blake2bMac::verify(&truncated, &secret_key, b"data").is_err());
+    }
+}
@@ -30,6 +30,8 @@
 //! - [`finalize()`] is called twice without a [`reset()`] in between.
 //! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
 //!   between.
+//! - The length of `dest` passed to [`finalize_into()`] is not 64 bytes, or
+//!   not the output size of the hash function for [`HmacGeneric`].
 //! - The HMAC does not match the expected when verifying.
 //!
 //! # Security:
@@ -55,15 +57,46 @@
 //! assert!(Hmac::verify(&tag, &key, b"Some message.").is_ok());
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
+//!
+//! # Generic usage:
+//! [`HmacGeneric`] computes HMAC over any [`ShaHash`], such as SHA-256 or
+//! SHA3-256. It takes the key as a slice, which is hashed if it is longer
+//! than the block size of the hash function, and writes the tag to a
+//! caller-provided buffer of the hash function's `OUTSIZE`. Tags must be
+//! compared with [`secure_cmp()`].
+//!
+//! ```rust
+//! use orion::hazardous::{hash::sha256::Sha256, mac::hmac::HmacGeneric};
+//! use orion::util::secure_cmp;
+//!
+//! let mut state = HmacGeneric::<Sha256>::new(b"Some key.")?;
+//! state.update(b"Some message.")?;
+//! let mut tag = [0u8; 32];
+//! state.finalize_into(&mut tag)?;
+//!
+//! let mut expected = [0u8; 32];
+//! let mut state = HmacGeneric::<Sha256>::new(b"Some key.")?;
+//! state.update(b"Some message.")?;
+//! state.finalize_into(&mut expected)?;
+//! assert!(secure_cmp(&tag, &expected).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
 //! [`update()`]: struct.Hmac.html
 //! [`reset()`]: struct.Hmac.html
 //! [`finalize()`]: struct.Hmac.html
+//! [`finalize_into()`]: struct.Hmac.html
 //! [`SecretKey::generate()`]: struct.SecretKey.html
+//! [`HmacGeneric`]: struct.HmacGeneric.html
+//! [`ShaHash`]: ../../hash/trait.ShaHash.html
+//! [`secure_cmp()`]: ../../../util/fn.secure_cmp.html
 //! [Cryptographic Right Answers]: https://latacora.micro.blog/2018/04/03/cryptographic-right-answers.html
 
 use crate::{
     errors::UnknownCryptoError,
-    hazardous::hash::sha512::{self, SHA512_BLOCKSIZE, SHA512_OUTSIZE},
+    hazardous::hash::{
+        sha512::{Sha512, SHA512_BLOCKSIZE, SHA512_OUTSIZE},
+        ShaHash, MAX_SHA_BLOCKSIZE,
+    },
 };
 use zeroize::Zeroize;
 
@@ -80,7 +113,7 @@ construct_hmac_key! {
     /// # Panics:
     /// A panic will occur if:
     /// - Failure to generate random bytes securely.
-    (SecretKey, test_hmac_key, SHA512_BLOCKSIZE)
+    (SecretKey, test_hmac_key, Sha512, SHA512_BLOCKSIZE)
 }
 
 construct_tag! {
@@ -95,53 +128,59 @@ construct_tag! {
 impl_from_trait!(Tag, SHA512_OUTSIZE);
 
 #[derive(Clone)]
-/// HMAC-SHA512 streaming state.
-pub struct Hmac {
-    working_hasher: sha512::Sha512,
-    opad_hasher: sha512::Sha512,
-    ipad_hasher: sha512::Sha512,
+/// HMAC streaming state, generic over the hash function.
+pub struct HmacGeneric<H: ShaHash> {
+    working_hasher: H,
+    opad_hasher: H,
+    ipad_hasher: H,
     is_finalized: bool,
 }
 
-impl core::fmt::Debug for Hmac {
+impl<H: ShaHash> core::fmt::Debug for HmacGeneric<H> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "Hmac {{ working_hasher: [***OMITTED***], opad_hasher: [***OMITTED***], ipad_hasher: [***OMITTED***], is_finalized: {:?} }}",
+            "HmacGeneric {{ working_hasher: [***OMITTED***], opad_hasher: [***OMITTED***], ipad_hasher: [***OMITTED***], is_finalized: {:?} }}",
             self.is_finalized
         )
     }
 }
 
-impl Hmac {
-    /// Pad `key` with `ipad` and `opad`.
-    fn pad_key_io(&mut self, key: &SecretKey) {
-        let mut ipad = [0x36; SHA512_BLOCKSIZE];
-        let mut opad = [0x5C; SHA512_BLOCKSIZE];
-        // The key is padded in SecretKey::from_slice
-        for (idx, itm) in key.unprotected_as_bytes().iter().enumerate() {
-            opad[idx] ^= itm;
-            ipad[idx] ^= itm;
+impl<H: ShaHash> HmacGeneric<H> {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize with `key`, which is hashed if it is longer than the block
+    /// size of `H`.
+    pub fn new(key: &[u8]) -> Result<Self, UnknownCryptoError> {
+        debug_assert!(H::BLOCKSIZE <= MAX_SHA_BLOCKSIZE);
+        debug_assert!(H::OUTSIZE <= H::BLOCKSIZE);
+
+        let mut ipad = [0x36; MAX_SHA_BLOCKSIZE];
+        let mut opad = [0x5C; MAX_SHA_BLOCKSIZE];
+        let mut padded_key = [0u8; MAX_SHA_BLOCKSIZE];
+        if key.len() > H::BLOCKSIZE {
+            H::digest_into(key, &mut padded_key[..H::OUTSIZE])?;
+        } else {
+            padded_key[..key.len()].copy_from_slice(key);
+        }
+        for ((i, o), k) in ipad.iter_mut().zip(opad.iter_mut()).zip(padded_key.iter()) {
+            *i ^= k;
+            *o ^= k;
         }
 
-        self.ipad_hasher.update(ipad.as_ref()).unwrap();
-        self.opad_hasher.update(opad.as_ref()).unwrap();
-        self.working_hasher = self.ipad_hasher.clone();
+        let mut ipad_hasher = H::default();
+        let mut opad_hasher = H::default();
+        ipad_hasher.update(&ipad[..H::BLOCKSIZE])?;
+        opad_hasher.update(&opad[..H::BLOCKSIZE])?;
         ipad.zeroize();
         opad.zeroize();
-    }
+        padded_key.zeroize();
 
-    /// Initialize `Hmac` struct with a given key.
-    pub fn new(secret_key: &SecretKey) -> Self {
-        let mut state = Self {
-            working_hasher: sha512::Sha512::new(),
-            opad_hasher: sha512::Sha512::new(),
-            ipad_hasher: sha512::Sha512::new(),
+        Ok(Self {
+            working_hasher: ipad_hasher.clone(),
+            opad_hasher,
+            ipad_hasher,
             is_finalized: false,
-        };
-
-        state.pad_key_io(secret_key);
-        state
+        })
     }
 
     /// Reset to `new()` state.
@@ -161,16 +200,91 @@ impl Hmac {
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
-    /// Return a HMAC-SHA512 tag.
-    pub fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+    /// Write the tag into `dest`, which must be `H::OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
         if self.is_finalized {
             return Err(UnknownCryptoError);
         }
+        if dest.len() != H::OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
 
         self.is_finalized = true;
         let mut outer_hasher = self.opad_hasher.clone();
-        outer_hasher.update(self.working_hasher.finalize()?.as_ref())?;
-        Tag::from_slice(outer_hasher.finalize()?.as_ref())
+        // The inner digest is written to `dest`, before being overwritten by the tag.
+        self.working_hasher.finalize_into(dest)?;
+        outer_hasher.update(dest)?;
+        outer_hasher.finalize_into(dest)
+    }
+}
+
+impl<H: ShaHash> super::Prf for HmacGeneric<H> {
+    fn output_size(&self) -> usize {
+        H::OUTSIZE
+    }
+
+    fn reset(&mut self) {
+        HmacGeneric::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        HmacGeneric::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        HmacGeneric::finalize_into(self, dest)
+    }
+}
+
+#[derive(Clone)]
+/// HMAC-SHA512 streaming state.
+pub struct Hmac {
+    inner: HmacGeneric<Sha512>,
+}
+
+impl core::fmt::Debug for Hmac {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Hmac {{ working_hasher: [***OMITTED***], opad_hasher: [***OMITTED***], ipad_hasher: [***OMITTED***], is_finalized: {:?} }}",
+            self.inner.is_finalized
+        )
+    }
+}
+
+impl Hmac {
+    /// Initialize `Hmac` struct with a given key.
+    pub fn new(secret_key: &SecretKey) -> Self {
+        // Cannot panic, since the key is already padded to the block size.
+        Self {
+            inner: HmacGeneric::new(secret_key.unprotected_as_bytes()).unwrap(),
+        }
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return a HMAC-SHA512 tag.
+    pub fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+        let mut tag = [0u8; SHA512_OUTSIZE];
+        self.finalize_into(&mut tag)?;
+
+        Ok(Tag::from(tag))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a HMAC-SHA512 tag into `dest`, which must be `SHA512_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.finalize_into(dest)
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -196,6 +310,24 @@ impl Hmac {
     }
 }
 
+impl super::Prf for Hmac {
+    fn output_size(&self) -> usize {
+        SHA512_OUTSIZE
+    }
+
+    fn reset(&mut self) {
+        Hmac::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Hmac::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Hmac::finalize_into(self, dest)
+    }
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -211,9 +343,59 @@ mod public {
         assert_eq!(debug, expected);
     }
 
-    #[cfg(feature = "safe_api")]
-    mod test_verify {
+    mod test_mac_interface {
         use super::*;
+        use crate::test_framework::mac_interface::*;
+
+        impl TestableMac for Hmac {
+            type Key = SecretKey;
+            type Tag = Tag;
+
+            fn new(key: &SecretKey) -> Self {
+                Hmac::new(key)
+            }
+
+            fn reset(&mut self) {
+                self.reset()
+            }
+
+            fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+                self.update(data)
+            }
+
+            fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+                self.finalize()
+            }
+
+            fn one_shot(key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+                Hmac::hmac(key, data)
+            }
+
+            fn verify(
+                expected: &Tag,
+                key: &SecretKey,
+                data: &[u8],
+            ) -> Result<(), UnknownCryptoError> {
+                Hmac::verify(expected, key, data)
+            }
+
+            fn tag_as_bytes(tag: &Tag) -> &[u8] {
+                tag.unprotected_as_bytes()
+            }
+
+            fn tag_from_slice(bytes: &[u8]) -> Result<Tag, UnknownCryptoError> {
+                Tag::from_slice(bytes)
+            }
+        }
+
+        #[test]
+        fn default_mac_tests() {
+            let test_runner = MacTester::<Hmac>::new(
+                SecretKey::from_slice(&[0u8; 32]).unwrap(),
+                SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            );
+            test_runner.run_all_tests();
+        }
 
         // Proptests. Only executed when NOT testing no_std.
         #[cfg(feature = "safe_api")]
@@ -221,16 +403,10 @@ mod public {
             use super::*;
 
             quickcheck! {
-                /// When using a different key, verify() should always yield an error.
-                /// NOTE: Using different and same input data is tested with TestableStreamingContext.
-                fn prop_verify_diff_key_false(data: Vec<u8>) -> bool {
-                    let sk = SecretKey::generate();
-                    let mut state = Hmac::new(&sk);
-                    state.update(&data[..]).unwrap();
-                    let tag = state.finalize().unwrap();
-                    let bad_sk = SecretKey::generate();
-
-                    Hmac::verify(&tag, &bad_sk, &data[..]).is_err()
+                fn prop_mac_interface(data: Vec<u8>) -> bool {
+                    let test_runner = MacTester::<Hmac>::new(SecretKey::generate(), SecretKey::generate());
+                    test_runner.run_all_tests_property(&data);
+                    true
                 }
             }
         }
@@ -267,6 +443,7 @@ mod public {
             }
 
             fn compare_states(state_1: &Hmac, state_2: &Hmac) {
+                let (state_1, state_2) = (&state_1.inner, &state_2.inner);
                 compare_sha512_states(&state_1.opad_hasher, &state_2.opad_hasher);
                 compare_sha512_states(&state_1.ipad_hasher, &state_2.ipad_hasher);
                 compare_sha512_states(&state_1.working_hasher, &state_2.working_hasher);
@@ -306,4 +483,101 @@ mod public {
             }
         }
     }
+
+    mod test_finalize_into {
+        use super::*;
+
+        #[test]
+        fn finalize_into_same_as_finalize() {
+            let mut state = Hmac::new(&SecretKey::from_slice(&[0u8; 64]).unwrap());
+            state.update(b"Some message.").unwrap();
+            let mut other = state.clone();
+
+            let mut dest = [0u8; SHA512_OUTSIZE];
+            state.finalize_into(&mut dest).unwrap();
+            assert_eq!(other.finalize().unwrap(), &dest[..]);
+        }
+
+        #[test]
+        fn finalize_into_wrong_length_err() {
+            let mut state = Hmac::new(&SecretKey::from_slice(&[0u8; 64]).unwrap());
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE - 1]).is_err());
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE + 1]).is_err());
+            // The state has not been finalized by the failed calls.
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_ok());
+            assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_err());
+        }
+    }
+
+    mod test_generic {
+        use super::*;
+        use crate::hazardous::hash::{sha256::Sha256, sha384::Sha384};
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_debug_impl() {
+            let state = HmacGeneric::<Sha256>::new(b"Jefe").unwrap();
+            let debug = format!("{:?}", state);
+            let expected = "HmacGeneric { working_hasher: [***OMITTED***], opad_hasher: [***OMITTED***], ipad_hasher: [***OMITTED***], is_finalized: false }";
+            assert_eq!(debug, expected);
+        }
+
+        #[test]
+        // RFC 4231, Test Case 2.
+        fn test_rfc4231_case_2() {
+            let mut tag = [0u8; 32];
+            let mut state = HmacGeneric::<Sha256>::new(b"Jefe").unwrap();
+            state.update(b"what do ya want ").unwrap();
+            state.update(b"for nothing?").unwrap();
+            state.finalize_into(&mut tag).unwrap();
+            assert_eq!(
+                tag,
+                [
+                    0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08,
+                    0x95, 0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec,
+                    0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+                ]
+            );
+
+            let mut tag = [0u8; 48];
+            let mut state = HmacGeneric::<Sha384>::new(b"Jefe").unwrap();
+            state.update(b"what do ya want for nothing?").unwrap();
+            state.finalize_into(&mut tag).unwrap();
+            assert_eq!(
+                tag[..],
+                [
+                    0xaf, 0x45, 0xd2, 0xe3, 0x76, 0x48, 0x40, 0x31, 0x61, 0x7f, 0x78, 0xd2, 0xb5,
+                    0x8a, 0x6b, 0x1b, 0x9c, 0x7e, 0xf4, 0x64, 0xf5, 0xa0, 0x1b, 0x47, 0xe4, 0x2e,
+                    0xc3, 0x73, 0x63, 0x22, 0x44, 0x5e, 0x8e, 0x22, 0x40, 0xca, 0x5e, 0x69, 0xe2,
+                    0xc7, 0x8b, 0x32, 0x39, 0xec, 0xfa, 0xb2, 0x16, 0x49,
+                ][..]
+            );
+        }
+
+        #[test]
+        fn test_same_as_hmac_sha512() {
+            let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+            let mut tag = [0u8; SHA512_OUTSIZE];
+            let mut state = HmacGeneric::<Sha512>::new(&[1u8; 32]).unwrap();
+            state.update(b"Some message.").unwrap();
+            state.finalize_into(&mut tag).unwrap();
+            assert_eq!(Hmac::hmac(&key, b"Some message.").unwrap(), &tag[..]);
+        }
+
+        #[test]
+        fn test_finalize_and_reset() {
+            let mut state = HmacGeneric::<Sha256>::new(&[0u8; 100]).unwrap();
+            let mut tag = [0u8; 32];
+            assert!(state.finalize_into(&mut [0u8; 31]).is_err());
+            assert!(state.finalize_into(&mut [0u8; 33]).is_err());
+            assert!(state.finalize_into(&mut tag).is_ok());
+            assert!(state.finalize_into(&mut tag).is_err());
+            assert!(state.update(b"").is_err());
+
+            let mut other = [0u8; 32];
+            state.reset();
+            assert!(state.finalize_into(&mut other).is_ok());
+            assert_eq!(tag, other);
+        }
+    }
 }
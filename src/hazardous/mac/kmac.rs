@@ -0,0 +1,590 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! KMAC is a MAC and PRF built on cSHAKE, as specified in
+//! [NIST SP 800-185](https://doi.org/10.6028/NIST.SP.800-185). [`Kmac128`] is
+//! built on cSHAKE128 and [`Kmac256`] on cSHAKE256.
+//!
+//! The output size is chosen when a state is initialized. It is part of the
+//! computation, so outputs of different sizes are unrelated, instead of one
+//! being a prefix of the other. An optional customization string separates
+//! different uses of the same key.
+//!
+//! # Parameters:
+//! - `secret_key`: The authentication key.
+//! - `customization`: The customization string, which may be empty.
+//! - `size`: The desired output length for the tag.
+//! - `data`: Data to be authenticated.
+//! - `expected`: The expected authentication tag.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `size` is 0, or its length in bits does not fit in a `u64`.
+//! - The length in bits of `customization` does not fit in a `u64`.
+//! - [`finalize_into()`] is called twice without a [`reset()`] in between.
+//! - [`update()`] is called after [`finalize_into()`] without a [`reset()`] in
+//!   between.
+//! - The length of `dest` passed to [`finalize_into()`] is not `size`.
+//! - `expected` is empty, or the tag does not match it when verifying.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   [`SecretKey::generate()`] can be used for this. It generates
+//!   a secret key of 32 bytes.
+//! - The secret key must be at least 16 bytes. For [`Kmac256`], it should be
+//!   at least 32 bytes.
+//! - The recommended minimum output size is 32.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::mac::kmac::{Kmac256, SecretKey};
+//!
+//! let key = SecretKey::generate();
+//!
+//! let mut state = Kmac256::new(&key, b"My Tagged Application", 32)?;
+//! state.update(b"Some message.")?;
+//! let mut tag = [0u8; 32];
+//! state.finalize_into(&mut tag)?;
+//!
+//! assert!(Kmac256::verify(&tag, &key, b"My Tagged Application", b"Some message.").is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`Kmac128`]: struct.Kmac128.html
+//! [`Kmac256`]: struct.Kmac256.html
+//! [`update()`]: struct.Kmac256.html#method.update
+//! [`reset()`]: struct.Kmac256.html#method.reset
+//! [`finalize_into()`]: struct.Kmac256.html#method.finalize_into
+//! [`SecretKey::generate()`]: struct.SecretKey.html
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::hash::sha3::{shake128::SHAKE128_RATE, shake256::SHAKE256_RATE, Sha3},
+};
+use subtle::{Choice, ConstantTimeEq};
+
+/// The function name of KMAC, that cSHAKE is initialized with.
+const FUNCTION_NAME: &[u8] = b"KMAC";
+
+/// The minimum size of a secret key.
+const MIN_KEYSIZE: usize = 16;
+
+/// The maximum size of a secret key.
+const MAX_KEYSIZE: usize = 64;
+
+construct_secret_key! {
+    /// A type to represent the `SecretKey` that KMAC uses for authentication.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is less than 16 bytes.
+    /// - `slice` is greater than 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, MIN_KEYSIZE, MAX_KEYSIZE, 32)
+}
+
+/// Absorb `left_encode(value)` and return the amount of bytes absorbed.
+fn left_encode(sponge: &mut Sha3, value: u64) -> Result<usize, UnknownCryptoError> {
+    let bytes = value.to_be_bytes();
+    let len = core::cmp::max(8 - (value.leading_zeros() / 8) as usize, 1);
+    sponge.update(&[len as u8])?;
+    sponge.update(&bytes[8 - len..])?;
+
+    Ok(1 + len)
+}
+
+/// Absorb `right_encode(value)`.
+fn right_encode(sponge: &mut Sha3, value: u64) -> Result<(), UnknownCryptoError> {
+    let bytes = value.to_be_bytes();
+    let len = core::cmp::max(8 - (value.leading_zeros() / 8) as usize, 1);
+    sponge.update(&bytes[8 - len..])?;
+    sponge.update(&[len as u8])
+}
+
+/// Absorb `encode_string(string)` and return the amount of bytes absorbed.
+fn encode_string(sponge: &mut Sha3, string: &[u8]) -> Result<usize, UnknownCryptoError> {
+    let bits = (string.len() as u64)
+        .checked_mul(8)
+        .ok_or(UnknownCryptoError)?;
+    let len = left_encode(sponge, bits)?;
+    sponge.update(string)?;
+
+    Ok(len + string.len())
+}
+
+/// Absorb `bytepad(encode_string(string_a) || encode_string(string_b), rate)`,
+/// leaving out `string_b` if it is `None`.
+fn bytepad(
+    sponge: &mut Sha3,
+    rate: usize,
+    string_a: &[u8],
+    string_b: Option<&[u8]>,
+) -> Result<(), UnknownCryptoError> {
+    let mut absorbed = left_encode(sponge, rate as u64)?;
+    absorbed += encode_string(sponge, string_a)?;
+    if let Some(string_b) = string_b {
+        absorbed += encode_string(sponge, string_b)?;
+    }
+
+    let padding = (rate - absorbed % rate) % rate;
+    sponge.update(&[0u8; SHAKE128_RATE][..padding])
+}
+
+#[derive(Clone, Debug)]
+/// KMAC streaming state for a given rate.
+struct Kmac {
+    initial: Sha3,
+    state: Sha3,
+    size: usize,
+}
+
+impl Kmac {
+    fn new(
+        rate: usize,
+        secret_key: &SecretKey,
+        customization: &[u8],
+        size: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        if size == 0 || (size as u64).checked_mul(8).is_none() {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut initial = Sha3::new_cshake(rate);
+        bytepad(&mut initial, rate, FUNCTION_NAME, Some(customization))?;
+        bytepad(&mut initial, rate, secret_key.unprotected_as_bytes(), None)?;
+
+        Ok(Self {
+            state: initial.clone(),
+            initial,
+            size,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.state = self.initial.clone();
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.state.update(data)
+    }
+
+    /// Absorb the output size and pad, so the output can be squeezed.
+    fn pad(&mut self) -> Result<(), UnknownCryptoError> {
+        // The size was checked not to overflow in new().
+        right_encode(&mut self.state, self.size as u64 * 8)?;
+        self.state.pad()
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        if dest.len() != self.size {
+            return Err(UnknownCryptoError);
+        }
+
+        self.pad()?;
+        self.state.squeeze(dest)
+    }
+
+    fn verify(&mut self, expected: &[u8]) -> Result<(), UnknownCryptoError> {
+        debug_assert_eq!(expected.len(), self.size);
+        self.pad()?;
+
+        // The output is compared in pieces, so that no buffer of the size of
+        // `expected` is needed.
+        let mut is_equal = Choice::from(1);
+        let mut piece = [0u8; 64];
+        for expected_piece in expected.chunks(piece.len()) {
+            let piece = &mut piece[..expected_piece.len()];
+            self.state.squeeze(piece)?;
+            is_equal &= piece.ct_eq(expected_piece);
+        }
+
+        if bool::from(is_equal) {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// KMAC128 streaming state.
+pub struct Kmac128 {
+    inner: Kmac,
+}
+
+impl Kmac128 {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize a `Kmac128` struct with a given key, customization string
+    /// and output size.
+    pub fn new(
+        secret_key: &SecretKey,
+        customization: &[u8],
+        size: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        Ok(Self {
+            inner: Kmac::new(SHAKE128_RATE, secret_key, customization, size)?,
+        })
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a KMAC128 tag into `dest`, which must be of the `size` the state
+    /// was initialized with.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify a KMAC128 tag in constant time. The output size is that of
+    /// `expected`.
+    pub fn verify(
+        expected: &[u8],
+        secret_key: &SecretKey,
+        customization: &[u8],
+        data: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let mut state = Kmac::new(SHAKE128_RATE, secret_key, customization, expected.len())?;
+        state.update(data)?;
+        state.verify(expected)
+    }
+}
+
+#[derive(Clone, Debug)]
+/// KMAC256 streaming state.
+pub struct Kmac256 {
+    inner: Kmac,
+}
+
+impl Kmac256 {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize a `Kmac256` struct with a given key, customization string
+    /// and output size.
+    pub fn new(
+        secret_key: &SecretKey,
+        customization: &[u8],
+        size: usize,
+    ) -> Result<Self, UnknownCryptoError> {
+        Ok(Self {
+            inner: Kmac::new(SHAKE256_RATE, secret_key, customization, size)?,
+        })
+    }
+
+    /// Reset to `new()` state.
+    pub fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.update(data)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a KMAC256 tag into `dest`, which must be of the `size` the state
+    /// was initialized with.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.finalize_into(dest)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify a KMAC256 tag in constant time. The output size is that of
+    /// `expected`.
+    pub fn verify(
+        expected: &[u8],
+        secret_key: &SecretKey,
+        customization: &[u8],
+        data: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let mut state = Kmac::new(SHAKE256_RATE, secret_key, customization, expected.len())?;
+        state.update(data)?;
+        state.verify(expected)
+    }
+}
+
+impl super::Prf for Kmac128 {
+    fn output_size(&self) -> usize {
+        self.inner.size
+    }
+
+    fn reset(&mut self) {
+        Kmac128::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Kmac128::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Kmac128::finalize_into(self, dest)
+    }
+}
+
+impl super::Prf for Kmac256 {
+    fn output_size(&self) -> usize {
+        self.inner.size
+    }
+
+    fn reset(&mut self) {
+        Kmac256::reset(self)
+    }
+
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        Kmac256::update(self, data)
+    }
+
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        Kmac256::finalize_into(self, dest)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const CUSTOMIZATION: &[u8] = b"My Tagged Application";
+
+    /// Decode the hexadecimal `value` into the start of `dst`, returning the
+    /// decoded part.
+    fn decode<'a>(value: &str, dst: &'a mut [u8]) -> &'a [u8] {
+        let len = value.len() / 2;
+        for (idx, byte) in dst[..len].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * idx..2 * idx + 2], 16).unwrap();
+        }
+        &dst[..len]
+    }
+
+    fn sample_key() -> SecretKey {
+        let mut key = [0u8; 32];
+        for (idx, byte) in key.iter_mut().enumerate() {
+            *byte = 0x40 + idx as u8;
+        }
+        SecretKey::from_slice(&key).unwrap()
+    }
+
+    fn sample_data(len: usize) -> [u8; 200] {
+        let mut data = [0u8; 200];
+        for (idx, byte) in data[..len].iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        data
+    }
+
+    #[test]
+    fn test_nist_samples() {
+        // The KMAC samples of NIST SP 800-185.
+        let kmac128 = [
+            (
+                4,
+                &b""[..],
+                "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e",
+            ),
+            (
+                4,
+                CUSTOMIZATION,
+                "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5",
+            ),
+            (
+                200,
+                CUSTOMIZATION,
+                "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230",
+            ),
+        ];
+        let kmac256 = [
+            (
+                4,
+                CUSTOMIZATION,
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+                 f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+            ),
+            (
+                200,
+                &b""[..],
+                "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691\
+                 589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69",
+            ),
+            (
+                200,
+                CUSTOMIZATION,
+                "b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d9\
+                 70fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965",
+            ),
+        ];
+        let key = sample_key();
+        let mut expected_buf = [0u8; 64];
+        let mut tag = [0u8; 64];
+
+        for (data_len, customization, expected) in kmac128.iter() {
+            let data = &sample_data(*data_len)[..*data_len];
+            let expected = decode(expected, &mut expected_buf);
+            let tag = &mut tag[..expected.len()];
+
+            let mut state = Kmac128::new(&key, customization, expected.len()).unwrap();
+            state.update(data).unwrap();
+            state.finalize_into(tag).unwrap();
+            assert_eq!(tag, expected);
+            assert!(Kmac128::verify(expected, &key, customization, data).is_ok());
+        }
+
+        for (data_len, customization, expected) in kmac256.iter() {
+            let data = &sample_data(*data_len)[..*data_len];
+            let expected = decode(expected, &mut expected_buf);
+            let tag = &mut tag[..expected.len()];
+
+            let mut state = Kmac256::new(&key, customization, expected.len()).unwrap();
+            state.update(data).unwrap();
+            state.finalize_into(tag).unwrap();
+            assert_eq!(tag, expected);
+            assert!(Kmac256::verify(expected, &key, customization, data).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_streaming_and_reset() {
+        let key = sample_key();
+        let data = sample_data(200);
+        let mut one_shot = [0u8; 48];
+        let mut streaming = [0u8; 48];
+
+        let mut state = Kmac256::new(&key, b"", 48).unwrap();
+        state.update(&data).unwrap();
+        state.finalize_into(&mut one_shot).unwrap();
+        assert!(state.finalize_into(&mut streaming).is_err());
+        assert!(state.update(b"").is_err());
+
+        state.reset();
+        for chunk in data.chunks(7) {
+            state.update(chunk).unwrap();
+        }
+        state.finalize_into(&mut streaming).unwrap();
+        assert_eq!(one_shot, streaming);
+    }
+
+    #[test]
+    fn test_size_is_part_of_output() {
+        let key = sample_key();
+        let mut short = [0u8; 32];
+        let mut long = [0u8; 64];
+
+        let mut state = Kmac128::new(&key, b"", 32).unwrap();
+        state.finalize_into(&mut short).unwrap();
+        let mut state = Kmac128::new(&key, b"", 64).unwrap();
+        state.finalize_into(&mut long).unwrap();
+        assert_ne!(short, long[..32]);
+    }
+
+    #[test]
+    fn test_long_output() {
+        // An output longer than the rate and the comparison pieces of verify().
+        let key = sample_key();
+        let mut tag = [0u8; 500];
+
+        let mut state = Kmac128::new(&key, CUSTOMIZATION, tag.len()).unwrap();
+        state.update(b"data").unwrap();
+        state.finalize_into(&mut tag).unwrap();
+        assert!(Kmac128::verify(&tag, &key, CUSTOMIZATION, b"data").is_ok());
+
+        tag[499] ^= 1;
+        assert!(Kmac128::verify(&tag, &key, CUSTOMIZATION, b"data").is_err());
+        tag[499] ^= 1;
+        tag[0] ^= 1;
+        assert!(Kmac128::verify(&tag, &key, CUSTOMIZATION, b"data").is_err());
+    }
+
+    #[test]
+    fn test_invalid_size_err() {
+        let key = sample_key();
+        assert!(Kmac128::new(&key, b"", 0).is_err());
+        assert!(Kmac256::new(&key, b"", 0).is_err());
+        assert!(Kmac128::verify(&[], &key, b"", b"").is_err());
+        assert!(Kmac256::verify(&[], &key, b"", b"").is_err());
+
+        let mut state = Kmac256::new(&key, b"", 32).unwrap();
+        assert!(state.finalize_into(&mut [0u8; 31]).is_err());
+        assert!(state.finalize_into(&mut [0u8; 33]).is_err());
+        assert!(state.finalize_into(&mut [0u8; 32]).is_ok());
+    }
+
+    #[test]
+    fn test_verify_err() {
+        let key = sample_key();
+        let other_key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+        let mut tag = [0u8; 32];
+        let mut state = Kmac256::new(&key, CUSTOMIZATION, 32).unwrap();
+        state.update(b"data").unwrap();
+        state.finalize_into(&mut tag).unwrap();
+
+        assert!(Kmac256::verify(&tag, &key, CUSTOMIZATION, b"data").is_ok());
+        assert!(Kmac256::verify(&tag, &other_key, CUSTOMIZATION, b"data").is_err());
+        assert!(Kmac256::verify(&tag, &key, b"", b"data").is_err());
+        assert!(Kmac256::verify(&tag, &key, CUSTOMIZATION, b"Data").is_err());
+        assert!(Kmac256::verify(&tag[..16], &key, CUSTOMIZATION, b"data").is_err());
+        assert!(Kmac128::verify(&tag, &key, CUSTOMIZATION, b"data").is_err());
+    }
+
+    #[test]
+    fn test_encodings() {
+        // The examples of left_encode() and right_encode() values in
+        // NIST SP 800-185, checked through the bytes a sponge absorbs.
+        let cases: [(u64, &[u8]); 4] = [
+            (0, &[0x00]),
+            (8, &[0x08]),
+            (256, &[0x01, 0x00]),
+            (u64::MAX, &[0xff; 8]),
+        ];
+        for (value, be) in cases.iter() {
+            let mut sponge = Sha3::new_cshake(SHAKE128_RATE);
+            let mut expected = Sha3::new_cshake(SHAKE128_RATE);
+            assert_eq!(left_encode(&mut sponge, *value).unwrap(), be.len() + 1);
+            expected.update(&[be.len() as u8]).unwrap();
+            expected.update(be).unwrap();
+            let mut out = [0u8; 32];
+            let mut expected_out = [0u8; 32];
+            sponge.finalize_into(&mut out).unwrap();
+            expected.finalize_into(&mut expected_out).unwrap();
+            assert_eq!(out, expected_out);
+
+            let mut sponge = Sha3::new_cshake(SHAKE128_RATE);
+            let mut expected = Sha3::new_cshake(SHAKE128_RATE);
+            right_encode(&mut sponge, *value).unwrap();
+            expected.update(be).unwrap();
+            expected.update(&[be.len() as u8]).unwrap();
+            sponge.finalize_into(&mut out).unwrap();
+            expected.finalize_into(&mut expected_out).unwrap();
+            assert_eq!(out, expected_out);
+        }
+    }
+}
@@ -20,8 +20,309 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-/// HMAC-SHA512 (Hash-based Message Authentication Code) as specified in the [RFC 2104](https://tools.ietf.org/html/rfc2104).
+/// HMAC (Hash-based Message Authentication Code) as specified in the [RFC 2104](https://tools.ietf.org/html/rfc2104).
 pub mod hmac;
 
 /// Poly1305 as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
 pub mod poly1305;
+
+/// Keyed BLAKE2b as specified in the [RFC 7693](https://tools.ietf.org/html/rfc7693).
+pub mod blake2b;
+
+/// KMAC128 and KMAC256 as specified in the [NIST SP 800-185](https://doi.org/10.6028/NIST.SP.800-185).
+pub mod kmac;
+
+use crate::errors::UnknownCryptoError;
+use zeroize::Zeroize;
+
+/// The largest output size (bytes) of any [`Prf`] used by PBKDF2 and
+/// [`PrfStream`].
+///
+/// [`Prf`]: trait.Prf.html
+/// [`PrfStream`]: struct.PrfStream.html
+pub(crate) const MAX_PRF_OUTSIZE: usize = crate::hazardous::hash::MAX_SHA_OUTSIZE;
+
+mod private {
+    /// Prevents implementations of [`Prf`](../trait.Prf.html) outside of orion.
+    pub trait Sealed {}
+
+    impl Sealed for super::hmac::Hmac {}
+    impl<H: crate::hazardous::hash::ShaHash> Sealed for super::hmac::HmacGeneric<H> {}
+    impl Sealed for super::blake2b::Blake2bMac {}
+    impl Sealed for super::kmac::Kmac128 {}
+    impl Sealed for super::kmac::Kmac256 {}
+}
+
+/// A keyed pseudorandom function: [`Hmac`], [`HmacGeneric`], [`Blake2bMac`],
+/// [`Kmac128`] or [`Kmac256`].
+///
+/// Code that is generic over `Prf` takes an already keyed state, so a
+/// specification that allows "any approved PRF" can be instantiated with
+/// any of them. The output size is that of the state, which is fixed for
+/// [`Hmac`] and [`HmacGeneric`], and chosen at initialization for the others.
+///
+/// This trait is sealed and cannot be implemented outside of orion.
+///
+/// [`Hmac`]: hmac/struct.Hmac.html
+/// [`HmacGeneric`]: hmac/struct.HmacGeneric.html
+/// [`Blake2bMac`]: blake2b/struct.Blake2bMac.html
+/// [`Kmac128`]: kmac/struct.Kmac128.html
+/// [`Kmac256`]: kmac/struct.Kmac256.html
+pub trait Prf: Clone + Send + Sync + private::Sealed {
+    /// The output size (bytes) of the PRF.
+    fn output_size(&self) -> usize;
+
+    /// Reset to the keyed initial state.
+    fn reset(&mut self);
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Update state with `data`. This can be called multiple times.
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write the output into `dest`, which must be `output_size()` bytes.
+    fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError>;
+}
+
+/// A deterministic byte stream, expanded from a [`Prf`] and a context
+/// string `info`.
+///
+/// Block `i` of the stream is `PRF(LE64(len(info)) || info || BE64(i))`,
+/// starting at `i = 0`. The same keyed PRF and `info` always produce the same
+/// stream, which makes it suitable for test vectors and protocol-specified
+/// expansions. It is not a replacement for the system CSPRNG.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The output size of the PRF is greater than 64.
+/// - The stream has produced `2^64 - 1` blocks.
+///
+/// # Example:
+/// ```rust
+/// use orion::hazardous::mac::{hmac, PrfStream};
+///
+/// let key = hmac::SecretKey::generate();
+/// let mut stream = PrfStream::new(hmac::Hmac::new(&key), b"Example context")?;
+///
+/// let mut first = [0u8; 100];
+/// let mut second = [0u8; 100];
+/// stream.fill(&mut first)?;
+/// stream.fill(&mut second)?;
+/// assert_ne!(first, second);
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`Prf`]: trait.Prf.html
+pub struct PrfStream<P: Prf> {
+    base: P,
+    block: [u8; MAX_PRF_OUTSIZE],
+    // The number of bytes of `block` that have been used.
+    position: usize,
+    counter: u64,
+}
+
+impl<P: Prf> Drop for PrfStream<P> {
+    fn drop(&mut self) {
+        self.block.zeroize();
+    }
+}
+
+impl<P: Prf> core::fmt::Debug for PrfStream<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PrfStream {{ base: [***OMITTED***], block: [***OMITTED***], position: {:?}, counter: {:?} }}",
+            self.position, self.counter
+        )
+    }
+}
+
+impl<P: Prf> PrfStream<P> {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Initialize a `PrfStream` from a keyed `prf` and a context string `info`.
+    pub fn new(mut prf: P, info: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let size = prf.output_size();
+        if size == 0 || size > MAX_PRF_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        prf.reset();
+        prf.update(&(info.len() as u64).to_le_bytes())?;
+        prf.update(info)?;
+
+        Ok(Self {
+            base: prf,
+            block: [0u8; MAX_PRF_OUTSIZE],
+            position: size,
+            counter: 0,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Fill `dest` with the next `dest.len()` bytes of the stream.
+    pub fn fill(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        let size = self.base.output_size();
+
+        for byte in dest.iter_mut() {
+            if self.position == size {
+                if self.counter == u64::MAX {
+                    return Err(UnknownCryptoError);
+                }
+
+                let mut prf = self.base.clone();
+                prf.update(&self.counter.to_be_bytes())?;
+                prf.finalize_into(&mut self.block[..size])?;
+                self.counter += 1;
+                self.position = 0;
+            }
+
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn hmac() -> hmac::Hmac {
+        hmac::Hmac::new(&hmac::SecretKey::from_slice(&[0x0b; 32]).unwrap())
+    }
+
+    fn kmac() -> kmac::Kmac128 {
+        let key = kmac::SecretKey::from_slice(&[0x0b; 32]).unwrap();
+        kmac::Kmac128::new(&key, b"", 20).unwrap()
+    }
+
+    /// Block `counter` of a stream, computed directly from the PRF.
+    fn expected_block<P: Prf>(prf: &P, info: &[u8], counter: u64, dest: &mut [u8]) {
+        let mut prf = prf.clone();
+        prf.reset();
+        prf.update(&(info.len() as u64).to_le_bytes()).unwrap();
+        prf.update(info).unwrap();
+        prf.update(&counter.to_be_bytes()).unwrap();
+        prf.finalize_into(dest).unwrap();
+    }
+
+    #[test]
+    fn test_stream_blocks() {
+        let mut stream = PrfStream::new(hmac(), b"info").unwrap();
+        let mut actual = [0u8; 3 * 64];
+        stream.fill(&mut actual).unwrap();
+
+        let mut expected = [0u8; 3 * 64];
+        for (counter, block) in expected.chunks_mut(64).enumerate() {
+            expected_block(&hmac(), b"info", counter as u64, block);
+        }
+        assert_eq!(actual[..], expected[..]);
+    }
+
+    #[test]
+    fn test_stream_blocks_variable_output() {
+        let mut stream = PrfStream::new(kmac(), b"").unwrap();
+        let mut actual = [0u8; 50];
+        stream.fill(&mut actual).unwrap();
+
+        let mut expected = [0u8; 60];
+        for (counter, block) in expected.chunks_mut(20).enumerate() {
+            expected_block(&kmac(), b"", counter as u64, block);
+        }
+        assert_eq!(actual[..], expected[..50]);
+    }
+
+    #[test]
+    fn test_fill_in_parts_same_as_once() {
+        let mut once = [0u8; 200];
+        PrfStream::new(kmac(), b"info")
+            .unwrap()
+            .fill(&mut once)
+            .unwrap();
+
+        for split in [0usize, 1, 19, 20, 21, 199, 200].iter() {
+            let mut parts = [0u8; 200];
+            let mut stream = PrfStream::new(kmac(), b"info").unwrap();
+            stream.fill(&mut parts[..*split]).unwrap();
+            stream.fill(&mut parts[*split..]).unwrap();
+            assert_eq!(once[..], parts[..]);
+        }
+    }
+
+    #[test]
+    fn test_info_and_prf_separate_streams() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+
+        PrfStream::new(hmac(), b"a")
+            .unwrap()
+            .fill(&mut first)
+            .unwrap();
+        PrfStream::new(hmac(), b"b")
+            .unwrap()
+            .fill(&mut second)
+            .unwrap();
+        assert_ne!(first, second);
+
+        // The length prefix separates `info` from the counter.
+        PrfStream::new(hmac(), b"")
+            .unwrap()
+            .fill(&mut first)
+            .unwrap();
+        PrfStream::new(hmac(), &[0u8; 8])
+            .unwrap()
+            .fill(&mut second)
+            .unwrap();
+        assert_ne!(first, second);
+
+        let key = hmac::SecretKey::from_slice(&[0x0c; 32]).unwrap();
+        PrfStream::new(hmac::Hmac::new(&key), b"")
+            .unwrap()
+            .fill(&mut second)
+            .unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_used_prf_is_reset() {
+        let mut used = hmac();
+        used.update(b"data").unwrap();
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+
+        PrfStream::new(used, b"").unwrap().fill(&mut first).unwrap();
+        PrfStream::new(hmac(), b"")
+            .unwrap()
+            .fill(&mut second)
+            .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_output_size_too_large_err() {
+        let key = kmac::SecretKey::from_slice(&[0x0b; 32]).unwrap();
+        let prf = kmac::Kmac256::new(&key, b"", MAX_PRF_OUTSIZE + 1).unwrap();
+        assert!(PrfStream::new(prf, b"").is_err());
+        let prf = kmac::Kmac256::new(&key, b"", MAX_PRF_OUTSIZE).unwrap();
+        assert!(PrfStream::new(prf, b"").is_ok());
+    }
+
+    #[test]
+    fn test_counter_exhausted_err() {
+        let mut stream = PrfStream::new(hmac(), b"").unwrap();
+        stream.counter = u64::MAX - 1;
+        assert!(stream.fill(&mut [0u8; 64]).is_ok());
+        assert!(stream.fill(&mut [0u8; 1]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let stream = PrfStream::new(hmac(), b"").unwrap();
+        let debug = format!("{:?}", stream);
+        let expected =
+            "PrfStream { base: [***OMITTED***], block: [***OMITTED***], position: 64, counter: 0 }";
+        assert_eq!(debug, expected);
+    }
+}
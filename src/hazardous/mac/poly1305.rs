@@ -35,6 +35,7 @@
 //! - [`finalize()`] is called twice without a [`reset()`] in between.
 //! - [`update()`] is called after [`finalize()`] without a [`reset()`] in
 //!   between.
+//! - The length of `dest` passed to [`finalize_into()`] is not 16 bytes.
 //! - The calculated tag does not match the expected when verifying.
 //!
 //! # Security:
@@ -65,6 +66,7 @@
 //! [`update()`]: struct.Poly1305.html
 //! [`reset()`]: struct.Poly1305.html
 //! [`finalize()`]: struct.Poly1305.html
+//! [`finalize_into()`]: struct.Poly1305.html
 //! [`OneTimeKey::generate()`]: struct.OneTimeKey.html
 //! [`OneTimeKey`]: struct.OneTimeKey.html
 
@@ -81,6 +83,11 @@ pub const POLY1305_OUTSIZE: usize = 16;
 pub const POLY1305_KEYSIZE: usize = 32;
 /// Type for a Poly1305 tag.
 type Poly1305Tag = [u8; POLY1305_OUTSIZE];
+/// The amount of blocks processed with a single reduction, using precomputed
+/// powers of `r`.
+const POLY1305_WIDE_BLOCKS: usize = 4;
+/// The amount of bytes processed with a single reduction.
+const POLY1305_WIDE_BLOCKSIZE: usize = POLY1305_BLOCKSIZE * POLY1305_WIDE_BLOCKS;
 
 construct_secret_key! {
     /// A type to represent the `OneTimeKey` that Poly1305 uses for authentication.
@@ -113,6 +120,10 @@ impl_from_trait!(Tag, POLY1305_OUTSIZE);
 pub struct Poly1305 {
     a: [u32; 5],
     r: [u32; 5],
+    // Limb k of r^4, r^3, r^2 and r, in that order, at `r_wide[k]`.
+    r_wide: [[u64; POLY1305_WIDE_BLOCKS]; 5],
+    // 5 times limb k + 1 of r^4, r^3, r^2 and r, at `s_wide[k]`.
+    s_wide: [[u64; POLY1305_WIDE_BLOCKS]; 4],
     s: [u32; 4],
     leftover: usize,
     buffer: [u8; POLY1305_BLOCKSIZE],
@@ -124,6 +135,8 @@ impl Drop for Poly1305 {
         use zeroize::Zeroize;
         self.a.zeroize();
         self.r.zeroize();
+        self.r_wide.iter_mut().for_each(|r| r.zeroize());
+        self.s_wide.iter_mut().for_each(|s| s.zeroize());
         self.s.zeroize();
         self.buffer.zeroize();
     }
@@ -139,7 +152,115 @@ impl core::fmt::Debug for Poly1305 {
     }
 }
 
+#[allow(clippy::unreadable_literal)]
+/// Load a block of `POLY1305_BLOCKSIZE` length into radix 2^26 limbs, with
+/// the 2^128 bit set.
+fn load_block_limbs(data: &[u8]) -> [u32; 5] {
+    debug_assert!(data.len() == POLY1305_BLOCKSIZE);
+    [
+        (load_u32_le(&data[0..4])) & 0x3ffffff,
+        (load_u32_le(&data[3..7]) >> 2) & 0x3ffffff,
+        (load_u32_le(&data[6..10]) >> 4) & 0x3ffffff,
+        (load_u32_le(&data[9..13]) >> 6) & 0x3ffffff,
+        (load_u32_le(&data[12..16]) >> 8) | (1 << 24),
+    ]
+}
+
+#[rustfmt::skip]
+#[allow(clippy::cast_lossless)]
+/// Multiply `h` by `r` modulo 2^130 - 5, without carrying. Up to four such
+/// products can be summed before [`carry_reduce`] without overflowing, provided
+/// all limbs of `h` are below 2^27 and all limbs of `r` below 2^26 + 2^12.
+fn mul_unreduced(h: &[u32; 5], r: &[u32; 5]) -> [u64; 5] {
+    let (h0, h1, h2, h3, h4) = (h[0] as u64, h[1] as u64, h[2] as u64, h[3] as u64, h[4] as u64);
+    let (r0, r1, r2, r3, r4) = (r[0] as u64, r[1] as u64, r[2] as u64, r[3] as u64, r[4] as u64);
+    let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+    [
+        (h0 * r0) + (h1 * s4) + (h2 * s3) + (h3 * s2) + (h4 * s1),
+        (h0 * r1) + (h1 * r0) + (h2 * s4) + (h3 * s3) + (h4 * s2),
+        (h0 * r2) + (h1 * r1) + (h2 * r0) + (h3 * s4) + (h4 * s3),
+        (h0 * r3) + (h1 * r2) + (h2 * r1) + (h3 * r0) + (h4 * s4),
+        (h0 * r4) + (h1 * r3) + (h2 * r2) + (h3 * r1) + (h4 * r0),
+    ]
+}
+
+#[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
+/// Carry a (sum of) product(s) from [`mul_unreduced`] back into radix 2^26
+/// limbs, partially reducing modulo 2^130 - 5.
+fn carry_reduce(d: [u64; 5]) -> [u32; 5] {
+    let [mut d0, mut d1, mut d2, mut d3, mut d4] = d;
+
+    let mut c: u64 = d0 >> 26; d0 &= 0x3ffffff;
+    d1 += c; c = d1 >> 26; d1 &= 0x3ffffff;
+    d2 += c; c = d2 >> 26; d2 &= 0x3ffffff;
+    d3 += c; c = d3 >> 26; d3 &= 0x3ffffff;
+    d4 += c; c = d4 >> 26; d4 &= 0x3ffffff;
+    d0 += c * 5; c = d0 >> 26; d0 &= 0x3ffffff;
+    d1 += c;
+
+    [d0 as u32, d1 as u32, d2 as u32, d3 as u32, d4 as u32]
+}
+
 impl Poly1305 {
+    /// Process `POLY1305_WIDE_BLOCKS` full blocks with a single reduction:
+    /// h = (h + m1)r^4 + m2r^3 + m3r^2 + m4r.
+    fn process_blocks_wide(&mut self, data: &[u8]) {
+        debug_assert!(data.len() == POLY1305_WIDE_BLOCKSIZE);
+        debug_assert!(!self.is_finalized);
+
+        // Word-sliced: limb k of every block/power is kept in `x[k][lane]`.
+        let mut m = [[0u64; POLY1305_WIDE_BLOCKS]; 5];
+        for (lane, block) in data.chunks_exact(POLY1305_BLOCKSIZE).enumerate() {
+            for (limb, value) in load_block_limbs(block).iter().enumerate() {
+                m[limb][lane] = *value as u64;
+            }
+        }
+        for (limb, a) in self.a.iter().enumerate() {
+            m[limb][0] += *a as u64;
+        }
+
+        let r = &self.r_wide;
+        let s = &self.s_wide;
+        let mut d = [[0u64; POLY1305_WIDE_BLOCKS]; 5];
+        for lane in 0..POLY1305_WIDE_BLOCKS {
+            let (m0, m1, m2, m3, m4) = (m[0][lane], m[1][lane], m[2][lane], m[3][lane], m[4][lane]);
+            d[0][lane] = m0 * r[0][lane]
+                + m1 * s[3][lane]
+                + m2 * s[2][lane]
+                + m3 * s[1][lane]
+                + m4 * s[0][lane];
+            d[1][lane] = m0 * r[1][lane]
+                + m1 * r[0][lane]
+                + m2 * s[3][lane]
+                + m3 * s[2][lane]
+                + m4 * s[1][lane];
+            d[2][lane] = m0 * r[2][lane]
+                + m1 * r[1][lane]
+                + m2 * r[0][lane]
+                + m3 * s[3][lane]
+                + m4 * s[2][lane];
+            d[3][lane] = m0 * r[3][lane]
+                + m1 * r[2][lane]
+                + m2 * r[1][lane]
+                + m3 * r[0][lane]
+                + m4 * s[3][lane];
+            d[4][lane] = m0 * r[4][lane]
+                + m1 * r[3][lane]
+                + m2 * r[2][lane]
+                + m3 * r[1][lane]
+                + m4 * r[0][lane];
+        }
+
+        let mut acc = [0u64; 5];
+        for (acc_limb, d_limb) in acc.iter_mut().zip(d.iter()) {
+            *acc_limb = d_limb.iter().sum();
+        }
+
+        self.a = carry_reduce(acc);
+    }
+
     #[rustfmt::skip]
     #[allow(clippy::cast_lossless)]
     #[allow(clippy::identity_op)]
@@ -298,6 +419,8 @@ impl Poly1305 {
         let mut state = Self {
             a: [0u32; 5],
             r: [0u32; 5],
+            r_wide: [[0u64; POLY1305_WIDE_BLOCKS]; 5],
+            s_wide: [[0u64; POLY1305_WIDE_BLOCKS]; 4],
             s: [0u32; 4],
             leftover: 0,
             buffer: [0u8; POLY1305_BLOCKSIZE],
@@ -315,6 +438,21 @@ impl Poly1305 {
         state.s[2] = load_u32_le(&one_time_key.unprotected_as_bytes()[24..28]);
         state.s[3] = load_u32_le(&one_time_key.unprotected_as_bytes()[28..32]);
 
+        // Lane `POLY1305_WIDE_BLOCKS - 1` holds r, and every lane before it the
+        // next power of r.
+        let mut r_pow = state.r;
+        for lane in (0..POLY1305_WIDE_BLOCKS).rev() {
+            if lane != POLY1305_WIDE_BLOCKS - 1 {
+                r_pow = carry_reduce(mul_unreduced(&r_pow, &state.r));
+            }
+            for (limb, value) in r_pow.iter().enumerate() {
+                state.r_wide[limb][lane] = *value as u64;
+                if limb > 0 {
+                    state.s_wide[limb - 1][lane] = *value as u64 * 5;
+                }
+            }
+        }
+
         state
     }
 
@@ -331,7 +469,12 @@ impl Poly1305 {
             return Ok(());
         }
 
-        let mut blocksize_iter = data.chunks_exact(POLY1305_BLOCKSIZE);
+        let mut wide_iter = data.chunks_exact(POLY1305_WIDE_BLOCKSIZE);
+        for blocks in &mut wide_iter {
+            self.process_blocks_wide(blocks);
+        }
+
+        let mut blocksize_iter = wide_iter.remainder().chunks_exact(POLY1305_BLOCKSIZE);
         for block in &mut blocksize_iter {
             self.process_block(block).unwrap();
         }
@@ -389,6 +532,11 @@ impl Poly1305 {
             self.leftover = 0;
         }
 
+        while bytes.len() >= POLY1305_WIDE_BLOCKSIZE {
+            self.process_blocks_wide(&bytes[..POLY1305_WIDE_BLOCKSIZE]);
+            bytes = &bytes[POLY1305_WIDE_BLOCKSIZE..];
+        }
+
         while bytes.len() >= POLY1305_BLOCKSIZE {
             self.process_block(&bytes[0..POLY1305_BLOCKSIZE])?;
             bytes = &bytes[POLY1305_BLOCKSIZE..];
@@ -403,9 +551,21 @@ impl Poly1305 {
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
     /// Return a Poly1305 tag.
     pub fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+        let mut tag: Poly1305Tag = [0u8; POLY1305_OUTSIZE];
+        self.finalize_into(&mut tag)?;
+
+        Ok(Tag::from(tag))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Write a Poly1305 tag into `dest`, which must be `POLY1305_OUTSIZE` bytes.
+    pub fn finalize_into(&mut self, dest: &mut [u8]) -> Result<(), UnknownCryptoError> {
         if self.is_finalized {
             return Err(UnknownCryptoError);
         }
+        if dest.len() != POLY1305_OUTSIZE {
+            return Err(UnknownCryptoError);
+        }
 
         self.is_finalized = true;
 
@@ -426,9 +586,9 @@ impl Poly1305 {
         }
 
         self.process_end_of_stream();
-        store_u32_into_le(&self.a[0..4], &mut local_buffer);
+        store_u32_into_le(&self.a[0..4], dest);
 
-        Ok(Tag::from(local_buffer))
+        Ok(())
     }
 
     #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
@@ -469,9 +629,61 @@ mod public {
         assert_eq!(debug, expected);
     }
 
-    #[cfg(feature = "safe_api")]
-    mod test_verify {
+    mod test_mac_interface {
         use super::*;
+        use crate::test_framework::mac_interface::*;
+
+        impl TestableMac for Poly1305 {
+            type Key = OneTimeKey;
+            type Tag = Tag;
+
+            fn new(key: &OneTimeKey) -> Self {
+                Poly1305::new(key)
+            }
+
+            fn reset(&mut self) {
+                self.reset()
+            }
+
+            fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+                self.update(data)
+            }
+
+            fn finalize(&mut self) -> Result<Tag, UnknownCryptoError> {
+                self.finalize()
+            }
+
+            fn one_shot(key: &OneTimeKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+                Poly1305::poly1305(key, data)
+            }
+
+            fn verify(
+                expected: &Tag,
+                key: &OneTimeKey,
+                data: &[u8],
+            ) -> Result<(), UnknownCryptoError> {
+                Poly1305::verify(expected, key, data)
+            }
+
+            fn tag_as_bytes(tag: &Tag) -> &[u8] {
+                tag.unprotected_as_bytes()
+            }
+
+            fn tag_from_slice(bytes: &[u8]) -> Result<Tag, UnknownCryptoError> {
+                Tag::from_slice(bytes)
+            }
+        }
+
+        #[test]
+        fn default_mac_tests() {
+            // If a Poly1305 one-time key is all 0's then the tag will also be, regardless
+            // of which message data has been processed.
+            let test_runner = MacTester::<Poly1305>::new(
+                OneTimeKey::from_slice(&[24u8; 32]).unwrap(),
+                OneTimeKey::from_slice(&[25u8; 32]).unwrap(),
+            );
+            test_runner.run_all_tests();
+        }
 
         // Proptests. Only executed when NOT testing no_std.
         #[cfg(feature = "safe_api")]
@@ -479,16 +691,10 @@ mod public {
             use super::*;
 
             quickcheck! {
-                /// When using a different key, verify() should always yield an error.
-                /// NOTE: Using different and same input data is tested with TestableStreamingContext.
-                fn prop_verify_diff_key_false(data: Vec<u8>) -> bool {
-                    let sk = OneTimeKey::generate();
-                    let mut state = Poly1305::new(&sk);
-                    state.update(&data[..]).unwrap();
-                    let tag = state.finalize().unwrap();
-                    let bad_sk = OneTimeKey::generate();
-
-                    Poly1305::verify(&tag, &bad_sk, &data[..]).is_err()
+                fn prop_mac_interface(data: Vec<u8>) -> bool {
+                    let test_runner = MacTester::<Poly1305>::new(OneTimeKey::generate(), OneTimeKey::generate());
+                    test_runner.run_all_tests_property(&data);
+                    true
                 }
             }
         }
@@ -566,6 +772,35 @@ mod public {
             }
         }
     }
+
+    mod test_finalize_into {
+        use super::*;
+
+        #[test]
+        fn finalize_into_same_as_finalize() {
+            let mut state = Poly1305::new(&OneTimeKey::from_slice(&[1u8; 32]).unwrap());
+            state.update(b"Some message.").unwrap();
+            let mut other = state.clone();
+
+            let mut dest = [0u8; POLY1305_OUTSIZE];
+            state.finalize_into(&mut dest).unwrap();
+            assert_eq!(other.finalize().unwrap(), &dest[..]);
+        }
+
+        #[test]
+        fn finalize_into_wrong_length_err() {
+            let mut state = Poly1305::new(&OneTimeKey::from_slice(&[1u8; 32]).unwrap());
+            assert!(state
+                .finalize_into(&mut [0u8; POLY1305_OUTSIZE - 1])
+                .is_err());
+            assert!(state
+                .finalize_into(&mut [0u8; POLY1305_OUTSIZE + 1])
+                .is_err());
+            // The state has not been finalized by the failed calls.
+            assert!(state.finalize_into(&mut [0u8; POLY1305_OUTSIZE]).is_ok());
+            assert!(state.finalize_into(&mut [0u8; POLY1305_OUTSIZE]).is_err());
+        }
+    }
 }
 
 // Testing private functions in the module.
@@ -644,4 +879,62 @@ mod private {
             state.process_end_of_stream();
         }
     }
+
+    mod test_process_blocks_wide {
+        use super::*;
+
+        fn assert_wide_same_as_single(key: &[u8], data: &[u8]) {
+            let sk = OneTimeKey::from_slice(key).unwrap();
+            let mut wide = Poly1305::new(&sk);
+            let mut single = Poly1305::new(&sk);
+
+            for blocks in data.chunks_exact(POLY1305_WIDE_BLOCKSIZE) {
+                wide.process_blocks_wide(blocks);
+                for block in blocks.chunks_exact(POLY1305_BLOCKSIZE) {
+                    single.process_block(block).unwrap();
+                }
+                // Limbs are only partially reduced, so compare the final tags.
+                let mut wide_tag = wide.clone();
+                let mut single_tag = single.clone();
+                wide_tag.process_end_of_stream();
+                single_tag.process_end_of_stream();
+                assert_eq!(wide_tag.a[..4], single_tag.a[..4]);
+            }
+        }
+
+        #[test]
+        fn test_wide_same_as_single() {
+            let mut data = [0u8; POLY1305_WIDE_BLOCKSIZE * 4];
+            for (idx, byte) in data.iter_mut().enumerate() {
+                *byte = (idx as u8).wrapping_mul(31).wrapping_add(7);
+            }
+            let mut key = [0u8; POLY1305_KEYSIZE];
+            key.copy_from_slice(&data[3..35]);
+
+            assert_wide_same_as_single(&key, &data);
+        }
+
+        #[test]
+        fn test_wide_same_as_single_max_limbs() {
+            // All message limbs and (clamped) key limbs at their maximum.
+            assert_wide_same_as_single(&[0xffu8; 32], &[0xffu8; POLY1305_WIDE_BLOCKSIZE * 8]);
+        }
+
+        #[test]
+        fn test_update_unaligned_same_as_single_blocks() {
+            let sk = OneTimeKey::from_slice(&[0x5au8; 32]).unwrap();
+            let data = [0xa5u8; POLY1305_WIDE_BLOCKSIZE * 3 + 5];
+
+            let mut expected = Poly1305::new(&sk);
+            for chunk in data.chunks(POLY1305_BLOCKSIZE - 1) {
+                expected.update(chunk).unwrap();
+            }
+
+            let mut actual = Poly1305::new(&sk);
+            actual.update(&data[..3]).unwrap();
+            actual.update(&data[3..]).unwrap();
+
+            assert_eq!(actual.finalize().unwrap(), expected.finalize().unwrap());
+        }
+    }
 }
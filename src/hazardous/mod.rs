@@ -29,9 +29,26 @@
 /// AEADs (Authenticated Encryption with Associated Data).
 pub mod aead;
 
+/// Block ciphers.
+pub mod block;
+
+#[cfg(feature = "cipher")]
+/// Implementations of the RustCrypto `cipher` traits.
+pub mod cipher_traits;
+
+/// Elliptic curve cryptography.
+pub mod ecc;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Finite-field Diffie-Hellman key agreement.
+pub mod ffdhe;
+
 /// Cryptographic hash functions.
 pub mod hash;
 
+/// HPKE (Hybrid Public Key Encryption) as specified in the [RFC 9180](https://www.rfc-editor.org/rfc/rfc9180).
+pub mod hpke;
+
 /// MACs (Message Authentication Code).
 pub mod mac;
 
@@ -39,5 +56,15 @@ pub mod mac;
 /// Function).
 pub mod kdf;
 
+/// Cryptographic protocols built from the primitives in `hazardous`.
+pub mod protocols;
+
+/// QUIC version 1 key derivation and header protection.
+pub mod quic;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// RSA signatures and encryption.
+pub mod rsa;
+
 /// Stream ciphers.
 pub mod stream;
@@ -0,0 +1,515 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Forward-secure, hash-chained audit logs.
+//!
+//! # About:
+//! A [`LogSealer`] computes a tag for each entry of an append-only log, such
+//! that the tags of past entries cannot be forged by an attacker who
+//! compromises the system that writes the log. A [`LogVerifier`], that holds
+//! the initial [`SecretKey`], checks the entries and their tags in order.
+//!
+//! This is the construction of Bellare and Yee, with the tags chained:
+//! - Entry `i` is authenticated with the key `K_i`, starting from `K_0`, the
+//!   initial [`SecretKey`]. Its tag is keyed BLAKE2b-256 of
+//!   `"orion audit log entry" || LE64(i) || previous_tag || entry`, where
+//!   `previous_tag` is the tag of entry `i - 1`, or 32 zero bytes for the
+//!   first entry.
+//! - After each entry, the key is ratcheted to
+//!   `K_{i+1} = BLAKE2b-256(key = K_i, "orion audit log key")`, and `K_i` is
+//!   overwritten. Knowing `K_{i+1}` does not reveal `K_i`, so an attacker can
+//!   only forge entries from the point of compromise onward.
+//! - Each tag covers the previous one, so entries cannot be removed,
+//!   reordered or moved between logs without the verification failing.
+//!
+//! A [`LogSealer`] that has to be stopped, for example when the writer
+//! restarts, can be turned into its current key, index and last tag with
+//! [`LogSealer::into_state()`], and continued later with
+//! [`LogSealer::resume()`].
+//!
+//! # Parameters:
+//! - `secret_key`: The initial key of the log, or the current key of the
+//!   [`LogSealer`] being resumed.
+//! - `index`: The amount of entries sealed before the [`LogSealer`] was
+//!   stopped.
+//! - `previous_tag`: The tag of the last entry sealed before the
+//!   [`LogSealer`] was stopped.
+//! - `entry`: An entry of the log.
+//! - `tag`: The tag of `entry`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `tag` does not match the tag of `entry` at the current position in the
+//!   log.
+//! - `2^64 - 1` entries have been sealed or verified.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - The initial [`SecretKey`] must be generated using a CSPRNG, with
+//!   [`SecretKey::generate()`], and must only be kept by the verifier.
+//!   [`LogSealer::new()`] takes ownership of it and overwrites it, so the
+//!   verifier must be given a copy before the [`LogSealer`] is created.
+//! - The key returned by [`LogSealer::into_state()`] can forge all entries
+//!   from that point onward. If it is stored to resume the log later, it must
+//!   be protected like the initial key, and deleted once the log has been
+//!   resumed. Resuming the same state twice lets both sealers produce tags
+//!   for the same positions.
+//! - Anyone who knows the initial key can verify and forge the whole log. It
+//!   must be stored offline or by a trusted party.
+//! - An attacker who compromises the writer can still delete the most recent
+//!   entries and stop the log. To detect this, the number of entries and the
+//!   last tag should be sent to the verifier, or another system, regularly.
+//! - A failed [`LogVerifier::verify()`] does not advance the verifier, so a
+//!   log that fails to verify must be treated as tampered with from that entry
+//!   onward.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::audit_log::{LogSealer, LogVerifier, SecretKey};
+//!
+//! let secret_key = SecretKey::generate();
+//! // The verifier keeps a copy of the initial key, for example offline.
+//! let verifier_key = SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+//! let mut sealer = LogSealer::new(secret_key);
+//!
+//! let first = sealer.seal(b"user alice logged in")?;
+//!
+//! // The writer restarts, and continues the log where it stopped.
+//! let (key, index, previous_tag) = sealer.into_state();
+//! let mut sealer = LogSealer::resume(key, index, &previous_tag);
+//! let second = sealer.seal(b"user alice deleted a record")?;
+//!
+//! let mut verifier = LogVerifier::new(&verifier_key);
+//! verifier.verify(b"user alice logged in", &first)?;
+//! assert!(verifier.verify(b"user bob deleted a record", &second).is_err());
+//! verifier.verify(b"user alice deleted a record", &second)?;
+//! assert_eq!(verifier.index(), 2);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`LogSealer`]: struct.LogSealer.html
+//! [`LogSealer::new()`]: struct.LogSealer.html#method.new
+//! [`LogSealer::into_state()`]: struct.LogSealer.html#method.into_state
+//! [`LogSealer::resume()`]: struct.LogSealer.html#method.resume
+//! [`LogVerifier`]: struct.LogVerifier.html
+//! [`LogVerifier::verify()`]: struct.LogVerifier.html#method.verify
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`SecretKey::generate()`]: struct.SecretKey.html#method.generate
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::blake2b::{self, Blake2b};
+use zeroize::Zeroize;
+
+/// The size of the keys of the log.
+pub const AUDIT_LOG_KEYSIZE: usize = 32;
+
+/// The size of the tag of an entry.
+pub const AUDIT_LOG_TAGSIZE: usize = 32;
+
+/// The label that the tag of every entry is computed over first.
+const ENTRY_LABEL: &[u8] = b"orion audit log entry";
+
+/// The label that the next key is computed over.
+const RATCHET_LABEL: &[u8] = b"orion audit log key";
+
+construct_secret_key! {
+    /// A type to represent the initial key of an audit log.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (SecretKey, test_secret_key, AUDIT_LOG_KEYSIZE, AUDIT_LOG_KEYSIZE, AUDIT_LOG_KEYSIZE)
+}
+
+impl_from_trait!(SecretKey, AUDIT_LOG_KEYSIZE);
+
+construct_tag! {
+    /// A type to represent the tag of an audit log entry.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (Tag, test_tag, AUDIT_LOG_TAGSIZE, AUDIT_LOG_TAGSIZE)
+}
+
+impl_from_trait!(Tag, AUDIT_LOG_TAGSIZE);
+
+/// The state shared by [`LogSealer`] and [`LogVerifier`].
+///
+/// [`LogSealer`]: struct.LogSealer.html
+/// [`LogVerifier`]: struct.LogVerifier.html
+struct Chain {
+    key: [u8; AUDIT_LOG_KEYSIZE],
+    index: u64,
+    previous_tag: [u8; AUDIT_LOG_TAGSIZE],
+}
+
+impl Drop for Chain {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "key: [***OMITTED***], index: {:?}, previous_tag: [***OMITTED***]",
+            self.index
+        )
+    }
+}
+
+impl Chain {
+    fn new(secret_key: &SecretKey, index: u64, previous_tag: &[u8]) -> Self {
+        let mut chain = Self {
+            key: [0u8; AUDIT_LOG_KEYSIZE],
+            index,
+            previous_tag: [0u8; AUDIT_LOG_TAGSIZE],
+        };
+        chain.key.copy_from_slice(secret_key.unprotected_as_bytes());
+        chain.previous_tag.copy_from_slice(previous_tag);
+
+        chain
+    }
+
+    /// Keyed BLAKE2b-256 with the current key over `parts`.
+    fn mac(&self, parts: &[&[u8]]) -> Result<[u8; AUDIT_LOG_TAGSIZE], UnknownCryptoError> {
+        let key = blake2b::SecretKey::from_slice(&self.key)?;
+        let mut ctx = Blake2b::new(Some(&key), AUDIT_LOG_TAGSIZE)?;
+        for part in parts.iter() {
+            ctx.update(part)?;
+        }
+        let mut out = [0u8; AUDIT_LOG_TAGSIZE];
+        ctx.finalize_into(&mut out)?;
+
+        Ok(out)
+    }
+
+    /// The tag of `entry` at the current position. The last index is never
+    /// used, so that there is always a next key to ratchet to.
+    fn tag(&self, entry: &[u8]) -> Result<Tag, UnknownCryptoError> {
+        if self.index == u64::MAX {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut tag = self.mac(&[
+            ENTRY_LABEL,
+            &self.index.to_le_bytes(),
+            &self.previous_tag,
+            entry,
+        ])?;
+        let result = Tag::from(tag);
+        tag.zeroize();
+
+        Ok(result)
+    }
+
+    /// Move past the entry with `tag`, ratcheting the key and overwriting the
+    /// current one.
+    fn advance(&mut self, tag: &Tag) -> Result<(), UnknownCryptoError> {
+        let mut next_key = self.mac(&[RATCHET_LABEL])?;
+        self.key.copy_from_slice(&next_key);
+        next_key.zeroize();
+        self.previous_tag
+            .copy_from_slice(tag.unprotected_as_bytes());
+        self.index += 1;
+
+        Ok(())
+    }
+}
+
+/// Computes the tags of the entries of an audit log.
+pub struct LogSealer {
+    inner: Chain,
+}
+
+impl core::fmt::Debug for LogSealer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "LogSealer {{ {:?} }}", self.inner)
+    }
+}
+
+impl LogSealer {
+    /// Start a new log with the initial `secret_key`, which is overwritten.
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            inner: Chain::new(&secret_key, 0, &[0u8; AUDIT_LOG_TAGSIZE]),
+        }
+    }
+
+    /// Continue a log from the state returned by [`into_state()`]. The
+    /// `secret_key` is overwritten.
+    ///
+    /// [`into_state()`]: struct.LogSealer.html#method.into_state
+    pub fn resume(secret_key: SecretKey, index: u64, previous_tag: &Tag) -> Self {
+        Self {
+            inner: Chain::new(&secret_key, index, previous_tag.unprotected_as_bytes()),
+        }
+    }
+
+    /// Stop sealing, and return the current key, the index of the next entry
+    /// and the tag of the last entry, which [`resume()`] continues from.
+    ///
+    /// [`resume()`]: struct.LogSealer.html#method.resume
+    pub fn into_state(self) -> (SecretKey, u64, Tag) {
+        (
+            SecretKey::from(self.inner.key),
+            self.inner.index,
+            Tag::from(self.inner.previous_tag),
+        )
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return the tag of the next `entry`, and ratchet the key.
+    pub fn seal(&mut self, entry: &[u8]) -> Result<Tag, UnknownCryptoError> {
+        let tag = self.inner.tag(entry)?;
+        self.inner.advance(&tag)?;
+
+        Ok(tag)
+    }
+
+    /// The amount of entries sealed so far, which is the index of the next.
+    pub fn index(&self) -> u64 {
+        self.inner.index
+    }
+}
+
+/// Verifies the entries of an audit log, in order.
+pub struct LogVerifier {
+    inner: Chain,
+}
+
+impl core::fmt::Debug for LogVerifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "LogVerifier {{ {:?} }}", self.inner)
+    }
+}
+
+impl LogVerifier {
+    /// Start verifying a log with the initial `secret_key`.
+    pub fn new(secret_key: &SecretKey) -> Self {
+        Self {
+            inner: Chain::new(secret_key, 0, &[0u8; AUDIT_LOG_TAGSIZE]),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify the `tag` of the next `entry` in constant time, and move past it
+    /// if it matches.
+    pub fn verify(&mut self, entry: &[u8], tag: &Tag) -> Result<(), UnknownCryptoError> {
+        let expected = self.inner.tag(entry)?;
+        if &expected != tag {
+            return Err(UnknownCryptoError);
+        }
+
+        self.inner.advance(&expected)
+    }
+
+    /// The amount of entries verified so far, which is the index of the next.
+    pub fn index(&self) -> u64 {
+        self.inner.index
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn decode<'a>(value: &str, dst: &'a mut [u8]) -> &'a [u8] {
+        let len = value.len() / 2;
+        for (idx, byte) in dst[..len].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * idx..2 * idx + 2], 16).unwrap();
+        }
+        &dst[..len]
+    }
+
+    const ENTRIES: [&[u8]; 3] = [b"first entry", b"", b"third entry"];
+
+    fn secret_key() -> SecretKey {
+        let mut key = [0u8; AUDIT_LOG_KEYSIZE];
+        for (idx, byte) in key.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+
+        SecretKey::from(key)
+    }
+
+    fn sealed_log() -> [Tag; 3] {
+        let mut sealer = LogSealer::new(secret_key());
+
+        [
+            sealer.seal(ENTRIES[0]).unwrap(),
+            sealer.seal(ENTRIES[1]).unwrap(),
+            sealer.seal(ENTRIES[2]).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_known_answer() {
+        // Computed with keyed BLAKE2b of Python's hashlib, following the
+        // construction in the module documentation.
+        let mut buf = [0u8; AUDIT_LOG_TAGSIZE];
+        let expected = [
+            "d1a7690256b1b725a236161c5cde0ff0fb85b862ebf7bf7c06acba61cfc1a4b0",
+            "d52f3ea88dd48c2dbf9241d5ee8e7feae70457936f93c159e1405e40c255c3de",
+            "718fb897083015bcae6d6c769f82074ecdb7b3f39b597f87b484a6945a25eec9",
+        ];
+
+        for (tag, expected) in sealed_log().iter().zip(expected.iter()) {
+            assert_eq!(*tag, decode(expected, &mut buf));
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let tags = sealed_log();
+        let mut verifier = LogVerifier::new(&secret_key());
+
+        for (entry, tag) in ENTRIES.iter().zip(tags.iter()) {
+            verifier.verify(entry, tag).unwrap();
+        }
+        assert_eq!(verifier.index(), 3);
+    }
+
+    #[test]
+    fn test_verify_tampered_err() {
+        let tags = sealed_log();
+
+        // A modified entry.
+        let mut verifier = LogVerifier::new(&secret_key());
+        assert!(verifier.verify(b"First entry", &tags[0]).is_err());
+        // A removed entry.
+        let mut verifier = LogVerifier::new(&secret_key());
+        verifier.verify(ENTRIES[0], &tags[0]).unwrap();
+        assert!(verifier.verify(ENTRIES[2], &tags[2]).is_err());
+        // Reordered entries.
+        let mut verifier = LogVerifier::new(&secret_key());
+        assert!(verifier.verify(ENTRIES[1], &tags[1]).is_err());
+        // A different key.
+        let mut verifier = LogVerifier::new(&SecretKey::from([1u8; AUDIT_LOG_KEYSIZE]));
+        assert!(verifier.verify(ENTRIES[0], &tags[0]).is_err());
+    }
+
+    #[test]
+    fn test_verify_err_does_not_advance() {
+        let tags = sealed_log();
+        let mut verifier = LogVerifier::new(&secret_key());
+
+        assert!(verifier.verify(ENTRIES[1], &tags[0]).is_err());
+        assert_eq!(verifier.index(), 0);
+        verifier.verify(ENTRIES[0], &tags[0]).unwrap();
+        assert_eq!(verifier.index(), 1);
+    }
+
+    #[test]
+    fn test_key_is_ratcheted() {
+        let mut sealer = LogSealer::new(secret_key());
+        assert_eq!(sealer.inner.key, secret_key().unprotected_as_bytes());
+
+        // The same entry gets a different tag at every position.
+        let first = sealer.seal(b"entry").unwrap();
+        assert_ne!(sealer.inner.key, secret_key().unprotected_as_bytes());
+        let second = sealer.seal(b"entry").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(sealer.index(), 2);
+    }
+
+    #[test]
+    fn test_resume() {
+        let tags = sealed_log();
+
+        for stop_at in 0..=ENTRIES.len() {
+            let mut sealer = LogSealer::new(secret_key());
+            for (entry, tag) in ENTRIES[..stop_at].iter().zip(tags.iter()) {
+                assert_eq!(sealer.seal(entry).unwrap(), *tag);
+            }
+
+            let (key, index, previous_tag) = sealer.into_state();
+            assert_eq!(index, stop_at as u64);
+            let mut sealer = LogSealer::resume(key, index, &previous_tag);
+            for (entry, tag) in ENTRIES[stop_at..].iter().zip(tags[stop_at..].iter()) {
+                assert_eq!(sealer.seal(entry).unwrap(), *tag);
+            }
+        }
+    }
+
+    #[test]
+    fn test_resume_new_state() {
+        let (key, index, previous_tag) = LogSealer::new(secret_key()).into_state();
+        assert_eq!(key, secret_key().unprotected_as_bytes());
+        assert_eq!(index, 0);
+        assert_eq!(previous_tag, [0u8; AUDIT_LOG_TAGSIZE].as_ref());
+    }
+
+    #[test]
+    fn test_resume_wrong_state_err() {
+        let tags = sealed_log();
+        let mut sealer = LogSealer::new(secret_key());
+        sealer.seal(ENTRIES[0]).unwrap();
+        let (key, index, previous_tag) = sealer.into_state();
+        let mut key_bytes = [0u8; AUDIT_LOG_KEYSIZE];
+        key_bytes.copy_from_slice(key.unprotected_as_bytes());
+
+        // A different index or last tag gives different tags.
+        let mut sealer = LogSealer::resume(key, index + 1, &previous_tag);
+        assert_ne!(sealer.seal(ENTRIES[1]).unwrap(), tags[1]);
+        let mut sealer = LogSealer::resume(SecretKey::from(key_bytes), index, &tags[1]);
+        assert_ne!(sealer.seal(ENTRIES[1]).unwrap(), tags[1]);
+    }
+
+    #[test]
+    fn test_index_exhausted_err() {
+        let mut sealer = LogSealer::new(secret_key());
+        let mut verifier = LogVerifier::new(&secret_key());
+        sealer.inner.index = u64::MAX - 1;
+        verifier.inner.index = u64::MAX - 1;
+
+        let tag = sealer.seal(b"entry").unwrap();
+        verifier.verify(b"entry", &tag).unwrap();
+        assert!(sealer.seal(b"entry").is_err());
+        assert!(verifier.verify(b"entry", &tag).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let sealer = LogSealer::new(secret_key());
+        let debug = format!("{:?}", sealer);
+        let expected =
+            "LogSealer { key: [***OMITTED***], index: 0, previous_tag: [***OMITTED***] }";
+        assert_eq!(debug, expected);
+
+        let verifier = LogVerifier::new(&secret_key());
+        let debug = format!("{:?}", verifier);
+        let expected =
+            "LogVerifier { key: [***OMITTED***], index: 0, previous_tag: [***OMITTED***] }";
+        assert_eq!(debug, expected);
+    }
+}
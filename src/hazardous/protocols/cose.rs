@@ -0,0 +1,554 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! COSE single-signer signatures and single-recipient encryption, as
+//! specified in [RFC 9052].
+//!
+//! # About:
+//! - [`sign1()`] and [`verify1()`] create and verify `COSE_Sign1` messages,
+//!   with EdDSA (Ed25519) or ES256 (ECDSA P-256 with SHA-256) as specified
+//!   in [RFC 9053].
+//! - [`encrypt0()`] and [`decrypt0()`] create and open `COSE_Encrypt0`
+//!   messages, with ChaCha20/Poly1305 as specified in [RFC 9053].
+//!
+//! The algorithm is put in the protected header, and the IV of
+//! `COSE_Encrypt0` in the unprotected header. Messages are created with
+//! their CBOR tag, and verified and opened with or without it. Other header
+//! parameters, such as a key ID, are ignored when verifying and opening.
+//!
+//! Only the preferred serialization of CBOR is accepted, and detached
+//! payloads are not supported.
+//!
+//! # Parameters:
+//! - `key`: The key to sign, verify, encrypt or decrypt with, which
+//!   determines the algorithm.
+//! - `nonce`: The IV of the message.
+//! - `payload`: The payload to sign.
+//! - `plaintext`: The plaintext to encrypt.
+//! - `external_aad`: Data that is authenticated, but not included in the
+//!   message. It must be the same when verifying or decrypting.
+//! - `message`: A `COSE_Sign1` or `COSE_Encrypt0` message.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `message` is not valid CBOR, or is not a `COSE_Sign1` or
+//!   `COSE_Encrypt0` message respectively.
+//! - The protected header does not have the algorithm of `key`, or has
+//!   critical header parameters.
+//! - The IV is missing or is not 12 bytes.
+//! - The signature or authentication tag does not match.
+//!
+//! # Security:
+//! - The nonce MUST NEVER be reused with the same key. Random nonces from
+//!   [`secure_rand_bytes()`] are fine as long as far fewer than 2^32
+//!   messages are encrypted with one key.
+//! - The payload of a `COSE_Sign1` message is only returned after its
+//!   signature has been verified, but it is not encrypted.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::aead::chacha20poly1305::{Nonce, SecretKey};
+//! use orion::hazardous::ecc::ed25519;
+//! use orion::hazardous::protocols::cose::{self, SigningKey, VerificationKey};
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = ed25519::SecretKey::generate();
+//! let public_key = ed25519::PublicKey::try_from(&secret_key)?;
+//!
+//! let message = cose::sign1(&SigningKey::Ed25519(&secret_key), b"payload", b"")?;
+//! let payload = cose::verify1(&VerificationKey::Ed25519(&public_key), &message, b"")?;
+//! assert_eq!(payload, b"payload");
+//!
+//! let key = SecretKey::generate();
+//! let mut nonce = [0u8; 12];
+//! orion::util::secure_rand_bytes(&mut nonce)?;
+//! let message = cose::encrypt0(&key, &Nonce::from(nonce), b"plaintext", b"")?;
+//! assert_eq!(cose::decrypt0(&key, &message, b"")?, b"plaintext");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9052]: https://www.rfc-editor.org/rfc/rfc9052
+//! [RFC 9053]: https://www.rfc-editor.org/rfc/rfc9053
+//! [`sign1()`]: fn.sign1.html
+//! [`verify1()`]: fn.verify1.html
+//! [`encrypt0()`]: fn.encrypt0.html
+//! [`decrypt0()`]: fn.decrypt0.html
+//! [`secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::chacha20poly1305::{self, Nonce, SecretKey},
+        ecc::{ecdsa_p256, ed25519},
+        mac::poly1305::POLY1305_OUTSIZE,
+    },
+    util::cbor::Value,
+};
+
+/// The algorithm identifier of EdDSA.
+pub const ALG_EDDSA: i64 = -8;
+/// The algorithm identifier of ES256.
+pub const ALG_ES256: i64 = -7;
+/// The algorithm identifier of ChaCha20/Poly1305.
+pub const ALG_CHACHA20_POLY1305: i64 = 24;
+
+/// The CBOR tag of `COSE_Sign1`.
+pub const TAG_SIGN1: u64 = 18;
+/// The CBOR tag of `COSE_Encrypt0`.
+pub const TAG_ENCRYPT0: u64 = 16;
+
+const HEADER_ALG: i64 = 1;
+const HEADER_CRIT: i64 = 2;
+const HEADER_IV: i64 = 5;
+
+/// The size of the IV of ChaCha20/Poly1305.
+const IV_SIZE: usize = 12;
+
+#[derive(Debug)]
+/// A key to sign a `COSE_Sign1` message with.
+pub enum SigningKey<'a> {
+    /// An Ed25519 secret key, used with EdDSA.
+    Ed25519(&'a ed25519::SecretKey),
+    /// An ECDSA P-256 secret key, used with ES256.
+    EcdsaP256(&'a ecdsa_p256::SecretKey),
+}
+
+impl SigningKey<'_> {
+    fn algorithm(&self) -> i64 {
+        match self {
+            SigningKey::Ed25519(_) => ALG_EDDSA,
+            SigningKey::EcdsaP256(_) => ALG_ES256,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A key to verify a `COSE_Sign1` message with.
+pub enum VerificationKey<'a> {
+    /// An Ed25519 public key, used with EdDSA.
+    Ed25519(&'a ed25519::PublicKey),
+    /// An ECDSA P-256 public key, used with ES256.
+    EcdsaP256(&'a ecdsa_p256::PublicKey),
+}
+
+impl VerificationKey<'_> {
+    fn algorithm(&self) -> i64 {
+        match self {
+            VerificationKey::Ed25519(_) => ALG_EDDSA,
+            VerificationKey::EcdsaP256(_) => ALG_ES256,
+        }
+    }
+}
+
+/// The encoded protected header with the algorithm `algorithm`.
+fn protected_header(algorithm: i64) -> Vec<u8> {
+    Value::Map(vec![(Value::int(HEADER_ALG), Value::int(algorithm))]).to_vec()
+}
+
+/// Check that the encoded protected header `protected` has the algorithm
+/// `algorithm` and no critical header parameters, and return it decoded.
+fn check_protected_header(protected: &[u8], algorithm: i64) -> Result<Value, UnknownCryptoError> {
+    let header = Value::decode(protected)?;
+    if header.as_map().is_none()
+        || header.get(HEADER_CRIT).is_some()
+        || header.get(HEADER_ALG).and_then(Value::as_int) != Some(algorithm)
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(header)
+}
+
+/// Decode `message` as an array of `len` items, with or without the tag
+/// `tag`.
+fn decode_message(message: &[u8], tag: u64, len: usize) -> Result<Vec<Value>, UnknownCryptoError> {
+    let value = match Value::decode(message)? {
+        Value::Tag(t, value) if t == tag => *value,
+        Value::Tag(_, _) => return Err(UnknownCryptoError),
+        value => value,
+    };
+    match value {
+        Value::Array(items) if items.len() == len => {
+            // The unprotected header is a map.
+            if items[1].as_map().is_none() {
+                return Err(UnknownCryptoError);
+            }
+            Ok(items)
+        }
+        _ => Err(UnknownCryptoError),
+    }
+}
+
+/// The `Sig_structure` of a `COSE_Sign1` message.
+fn sig_structure(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    Value::Array(vec![
+        Value::Text("Signature1".into()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(external_aad.to_vec()),
+        Value::Bytes(payload.to_vec()),
+    ])
+    .to_vec()
+}
+
+/// The `Enc_structure` of a `COSE_Encrypt0` message.
+fn enc_structure(protected: &[u8], external_aad: &[u8]) -> Vec<u8> {
+    Value::Array(vec![
+        Value::Text("Encrypt0".into()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(external_aad.to_vec()),
+    ])
+    .to_vec()
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `payload` with `key`, and return the tagged `COSE_Sign1` message.
+pub fn sign1(
+    key: &SigningKey<'_>,
+    payload: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let protected = protected_header(key.algorithm());
+    let to_be_signed = sig_structure(&protected, external_aad, payload);
+    let signature = match key {
+        SigningKey::Ed25519(secret_key) => {
+            ed25519::sign(secret_key, &to_be_signed)?.as_ref().to_vec()
+        }
+        SigningKey::EcdsaP256(secret_key) => ecdsa_p256::sign(secret_key, &to_be_signed)?
+            .as_ref()
+            .to_vec(),
+    };
+
+    Ok(Value::Tag(
+        TAG_SIGN1,
+        Box::new(Value::Array(vec![
+            Value::Bytes(protected),
+            Value::Map(vec![]),
+            Value::Bytes(payload.to_vec()),
+            Value::Bytes(signature),
+        ])),
+    )
+    .to_vec())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the `COSE_Sign1` `message` with `key`, and return its payload.
+pub fn verify1(
+    key: &VerificationKey<'_>,
+    message: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let items = decode_message(message, TAG_SIGN1, 4)?;
+    let (protected, payload, signature) = match (
+        items[0].as_bytes(),
+        items[2].as_bytes(),
+        items[3].as_bytes(),
+    ) {
+        (Some(protected), Some(payload), Some(signature)) => (protected, payload, signature),
+        _ => return Err(UnknownCryptoError),
+    };
+    check_protected_header(protected, key.algorithm())?;
+
+    let to_be_signed = sig_structure(protected, external_aad, payload);
+    match key {
+        VerificationKey::Ed25519(public_key) => ed25519::verify(
+            &ed25519::Signature::from_slice(signature)?,
+            public_key,
+            &to_be_signed,
+        )?,
+        VerificationKey::EcdsaP256(public_key) => ecdsa_p256::verify(
+            &ecdsa_p256::Signature::from_slice(signature)?,
+            public_key,
+            &to_be_signed,
+        )?,
+    }
+
+    Ok(payload.to_vec())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encrypt `plaintext` with `key` and `nonce`, and return the tagged
+/// `COSE_Encrypt0` message.
+pub fn encrypt0(
+    key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let protected = protected_header(ALG_CHACHA20_POLY1305);
+    let aad = enc_structure(&protected, external_aad);
+    let mut ciphertext = vec![0u8; plaintext.len() + POLY1305_OUTSIZE];
+    chacha20poly1305::seal(key, nonce, plaintext, Some(&aad), &mut ciphertext)?;
+
+    Ok(Value::Tag(
+        TAG_ENCRYPT0,
+        Box::new(Value::Array(vec![
+            Value::Bytes(protected),
+            Value::Map(vec![(
+                Value::int(HEADER_IV),
+                Value::Bytes(nonce.as_ref().to_vec()),
+            )]),
+            Value::Bytes(ciphertext),
+        ])),
+    )
+    .to_vec())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the `COSE_Encrypt0` `message` with `key`, and return its plaintext.
+pub fn decrypt0(
+    key: &SecretKey,
+    message: &[u8],
+    external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let items = decode_message(message, TAG_ENCRYPT0, 3)?;
+    let (protected, ciphertext) = match (items[0].as_bytes(), items[2].as_bytes()) {
+        (Some(protected), Some(ciphertext)) => (protected, ciphertext),
+        _ => return Err(UnknownCryptoError),
+    };
+    let protected_header = check_protected_header(protected, ALG_CHACHA20_POLY1305)?;
+
+    // The IV may be in either header, but not both.
+    let iv = match (protected_header.get(HEADER_IV), items[1].get(HEADER_IV)) {
+        (Some(iv), None) | (None, Some(iv)) => iv.as_bytes().ok_or(UnknownCryptoError)?,
+        _ => return Err(UnknownCryptoError),
+    };
+    if iv.len() != IV_SIZE || ciphertext.len() < POLY1305_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let aad = enc_structure(protected, external_aad);
+    let mut plaintext = vec![0u8; ciphertext.len() - POLY1305_OUTSIZE];
+    chacha20poly1305::open(
+        key,
+        &Nonce::from_slice(iv)?,
+        ciphertext,
+        Some(&aad),
+        &mut plaintext,
+    )?;
+
+    Ok(plaintext)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn message(items: Vec<Value>, tag: u64) -> Vec<u8> {
+        Value::Tag(tag, Box::new(Value::Array(items))).to_vec()
+    }
+
+    fn chacha_key() -> SecretKey {
+        SecretKey::from_slice(&[0x42; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_sign1_invalid_err() {
+        let secret_key = ed25519::SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let key = VerificationKey::Ed25519(&public_key);
+        let signed = sign1(&SigningKey::Ed25519(&secret_key), b"payload", b"").unwrap();
+        assert_eq!(verify1(&key, &signed, b"").unwrap(), b"payload");
+
+        let items = match Value::decode(&signed).unwrap() {
+            Value::Tag(_, items) => match *items {
+                Value::Array(items) => items,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        // The encoding is correct.
+        assert_eq!(message(items.clone(), TAG_SIGN1), signed);
+
+        // Wrong tag.
+        assert!(verify1(&key, &message(items.clone(), TAG_ENCRYPT0), b"").is_err());
+        // Wrong number of items.
+        assert!(verify1(&key, &message(items[..3].to_vec(), TAG_SIGN1), b"").is_err());
+        // Detached payload.
+        let mut modified = items.clone();
+        modified[2] = Value::Null;
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_err());
+        // Unprotected header is not a map.
+        let mut modified = items.clone();
+        modified[1] = Value::Array(vec![]);
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_err());
+        // Other header parameters are ignored.
+        let mut modified = items.clone();
+        modified[1] = Value::Map(vec![(Value::int(4), Value::Bytes(b"kid".to_vec()))]);
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_ok());
+        // Modified payload and signature.
+        let mut modified = items.clone();
+        modified[2] = Value::Bytes(b"payloae".to_vec());
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_err());
+        let mut modified = items.clone();
+        modified[3] = Value::Bytes(vec![0u8; 64]);
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_err());
+        let mut modified = items.clone();
+        modified[3] = Value::Bytes(vec![0u8; 63]);
+        assert!(verify1(&key, &message(modified, TAG_SIGN1), b"").is_err());
+        // Trailing data.
+        let mut modified = signed.clone();
+        modified.push(0);
+        assert!(verify1(&key, &modified, b"").is_err());
+    }
+
+    #[test]
+    fn test_protected_header_err() {
+        let secret_key = ed25519::SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let key = VerificationKey::Ed25519(&public_key);
+
+        let bad_headers = [
+            // Empty protected header.
+            vec![],
+            Value::Map(vec![]).to_vec(),
+            // Wrong algorithm.
+            protected_header(ALG_ES256),
+            Value::Map(vec![(Value::int(HEADER_ALG), Value::Text("EdDSA".into()))]).to_vec(),
+            // Critical header parameters.
+            Value::Map(vec![
+                (Value::int(HEADER_ALG), Value::int(ALG_EDDSA)),
+                (Value::int(HEADER_CRIT), Value::Array(vec![Value::int(42)])),
+            ])
+            .to_vec(),
+            // Not a map.
+            Value::Array(vec![Value::int(HEADER_ALG), Value::int(ALG_EDDSA)]).to_vec(),
+        ];
+        for protected in bad_headers.iter() {
+            // Correctly signed, so only the header is rejected.
+            let to_be_signed = sig_structure(protected, b"", b"payload");
+            let signature = ed25519::sign(&secret_key, &to_be_signed).unwrap();
+            let signed = message(
+                vec![
+                    Value::Bytes(protected.clone()),
+                    Value::Map(vec![]),
+                    Value::Bytes(b"payload".to_vec()),
+                    Value::Bytes(signature.as_ref().to_vec()),
+                ],
+                TAG_SIGN1,
+            );
+            assert!(verify1(&key, &signed, b"").is_err());
+        }
+    }
+
+    #[test]
+    fn test_encrypt0_invalid_err() {
+        let key = chacha_key();
+        let nonce = Nonce::from([0x01; IV_SIZE]);
+        let encrypted = encrypt0(&key, &nonce, b"plaintext", b"").unwrap();
+        assert_eq!(decrypt0(&key, &encrypted, b"").unwrap(), b"plaintext");
+        assert!(decrypt0(
+            &SecretKey::from_slice(&[0x43; 32]).unwrap(),
+            &encrypted,
+            b""
+        )
+        .is_err());
+
+        let protected = protected_header(ALG_CHACHA20_POLY1305);
+        let aad = enc_structure(&protected, b"");
+        let mut ciphertext = vec![0u8; 9 + POLY1305_OUTSIZE];
+        chacha20poly1305::seal(&key, &nonce, b"plaintext", Some(&aad), &mut ciphertext).unwrap();
+        let iv = |iv: &[u8]| Value::Map(vec![(Value::int(HEADER_IV), Value::Bytes(iv.to_vec()))]);
+
+        // The IV in the protected header.
+        let protected_iv = Value::Map(vec![
+            (Value::int(HEADER_ALG), Value::int(ALG_CHACHA20_POLY1305)),
+            (Value::int(HEADER_IV), Value::Bytes(nonce.as_ref().to_vec())),
+        ])
+        .to_vec();
+        let mut protected_iv_ciphertext = vec![0u8; 9 + POLY1305_OUTSIZE];
+        chacha20poly1305::seal(
+            &key,
+            &nonce,
+            b"plaintext",
+            Some(&enc_structure(&protected_iv, b"")),
+            &mut protected_iv_ciphertext,
+        )
+        .unwrap();
+        let good = message(
+            vec![
+                Value::Bytes(protected_iv.clone()),
+                Value::Map(vec![]),
+                Value::Bytes(protected_iv_ciphertext.clone()),
+            ],
+            TAG_ENCRYPT0,
+        );
+        assert_eq!(decrypt0(&key, &good, b"").unwrap(), b"plaintext");
+        // In both headers.
+        let both = message(
+            vec![
+                Value::Bytes(protected_iv),
+                iv(nonce.as_ref()),
+                Value::Bytes(protected_iv_ciphertext),
+            ],
+            TAG_ENCRYPT0,
+        );
+        assert!(decrypt0(&key, &both, b"").is_err());
+
+        let bad_messages = [
+            // Missing IV, or of the wrong length.
+            vec![
+                Value::Bytes(protected.clone()),
+                Value::Map(vec![]),
+                Value::Bytes(ciphertext.clone()),
+            ],
+            vec![
+                Value::Bytes(protected.clone()),
+                iv(&[0x01; IV_SIZE - 1]),
+                Value::Bytes(ciphertext.clone()),
+            ],
+            vec![
+                Value::Bytes(protected.clone()),
+                Value::Map(vec![(Value::int(HEADER_IV), Value::Text("iv".into()))]),
+                Value::Bytes(ciphertext.clone()),
+            ],
+            // Too short for the tag.
+            vec![
+                Value::Bytes(protected.clone()),
+                iv(nonce.as_ref()),
+                Value::Bytes(ciphertext[..POLY1305_OUTSIZE - 1].to_vec()),
+            ],
+            // Wrong algorithm.
+            vec![
+                Value::Bytes(protected_header(ALG_EDDSA)),
+                iv(nonce.as_ref()),
+                Value::Bytes(ciphertext.clone()),
+            ],
+            // Wrong number of items.
+            vec![Value::Bytes(protected.clone()), iv(nonce.as_ref())],
+        ];
+        for items in bad_messages.iter() {
+            assert!(decrypt0(&key, &message(items.clone(), TAG_ENCRYPT0), b"").is_err());
+        }
+        // Wrong tag.
+        let items = vec![
+            Value::Bytes(protected),
+            iv(nonce.as_ref()),
+            Value::Bytes(ciphertext),
+        ];
+        assert!(decrypt0(&key, &message(items.clone(), TAG_ENCRYPT0), b"").is_ok());
+        assert!(decrypt0(&key, &message(items, TAG_SIGN1), b"").is_err());
+    }
+}
@@ -0,0 +1,479 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! CBOR Web Tokens (CWT), as specified in [RFC 8392].
+//!
+//! # About:
+//! A CWT is a set of [`Claims`], encoded as a CBOR map, which is signed as a
+//! `COSE_Sign1` message or encrypted as a `COSE_Encrypt0` message. See
+//! [`cose`] for the supported algorithms.
+//!
+//! The registered claims `iss`, `sub`, `aud`, `exp`, `nbf`, `iat` and `cti`
+//! are supported. `aud` must be a single string, and times must be
+//! non-negative integers. Other claims are ignored.
+//!
+//! Tokens are created without the CWT tag, and verified and decrypted with or
+//! without it.
+//!
+//! When verifying or decrypting a token, its claims are checked with a
+//! [`Validation`] at the time `now`, in seconds since the Unix epoch:
+//! - The token is rejected at or after its `exp`, and before its `nbf`.
+//!   `leeway` allows for clock differences between the issuer and `now`.
+//! - If `issuer` is set, `iss` must be present and equal to it.
+//! - If `aud` is present, `audience` must be set and equal to it. If
+//!   `audience` is set, `aud` must be present.
+//!
+//! # Parameters:
+//! - `key`: The key to sign, verify, encrypt or decrypt with.
+//! - `nonce`: The IV of the `COSE_Encrypt0` message.
+//! - `claims`: The claims of the token.
+//! - `token`: A CWT.
+//! - `validation`: The expected claims.
+//! - `now`: The current time, in seconds since the Unix epoch.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`cose`] returns an error for the token.
+//! - The payload is not a CBOR map, or a registered claim has the wrong type.
+//! - The claims do not pass `validation` at `now`.
+//!
+//! # Security:
+//! - Tokens without `exp` never expire. Issue tokens with an `exp` claim.
+//! - `iat` is not checked. `cti` is returned, but checking that a token is
+//!   not replayed is left to the caller.
+//! - The nonce MUST NEVER be reused with the same key.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::ecdsa_p256;
+//! use orion::hazardous::protocols::cose::{SigningKey, VerificationKey};
+//! use orion::hazardous::protocols::cwt::{self, Claims, Validation};
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = ecdsa_p256::SecretKey::generate();
+//! let public_key = ecdsa_p256::PublicKey::try_from(&secret_key)?;
+//!
+//! let claims = Claims {
+//!     issuer: Some("coap://as.example.com".into()),
+//!     subject: Some("device-0042".into()),
+//!     audience: Some("coap://light.example.com".into()),
+//!     expiration: Some(1_700_003_600),
+//!     issued_at: Some(1_700_000_000),
+//!     ..Default::default()
+//! };
+//! let token = cwt::sign(&SigningKey::EcdsaP256(&secret_key), &claims)?;
+//!
+//! let validation = Validation {
+//!     issuer: Some("coap://as.example.com".into()),
+//!     audience: Some("coap://light.example.com".into()),
+//!     leeway: 60,
+//! };
+//! let key = VerificationKey::EcdsaP256(&public_key);
+//! assert_eq!(cwt::verify(&key, &token, &validation, 1_700_000_100)?, claims);
+//! // Expired.
+//! assert!(cwt::verify(&key, &token, &validation, 1_700_003_660).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 8392]: https://www.rfc-editor.org/rfc/rfc8392
+//! [`Claims`]: struct.Claims.html
+//! [`Validation`]: struct.Validation.html
+//! [`cose`]: ../cose/index.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::{string::String, vec::Vec};
+
+use super::cose::{self, SigningKey, VerificationKey};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::aead::chacha20poly1305::{Nonce, SecretKey},
+    util::cbor::Value,
+};
+
+/// The CBOR tag of a CWT.
+pub const TAG_CWT: u64 = 61;
+
+const CLAIM_ISS: i64 = 1;
+const CLAIM_SUB: i64 = 2;
+const CLAIM_AUD: i64 = 3;
+const CLAIM_EXP: i64 = 4;
+const CLAIM_NBF: i64 = 5;
+const CLAIM_IAT: i64 = 6;
+const CLAIM_CTI: i64 = 7;
+
+/// The encoding of the head of [`TAG_CWT`](constant.TAG_CWT.html).
+const TAG_CWT_HEAD: [u8; 2] = [0xD8, 0x3D];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The registered claims of a CWT.
+pub struct Claims {
+    /// The issuer (`iss`).
+    pub issuer: Option<String>,
+    /// The subject (`sub`).
+    pub subject: Option<String>,
+    /// The audience (`aud`).
+    pub audience: Option<String>,
+    /// The expiration time (`exp`).
+    pub expiration: Option<u64>,
+    /// The time before which the token must not be accepted (`nbf`).
+    pub not_before: Option<u64>,
+    /// The time at which the token was issued (`iat`).
+    pub issued_at: Option<u64>,
+    /// The CWT ID (`cti`).
+    pub cwt_id: Option<Vec<u8>>,
+}
+
+impl Claims {
+    fn to_cbor(&self) -> Vec<u8> {
+        let mut entries = Vec::new();
+        let texts = [
+            (CLAIM_ISS, &self.issuer),
+            (CLAIM_SUB, &self.subject),
+            (CLAIM_AUD, &self.audience),
+        ];
+        for (claim, value) in texts.iter() {
+            if let Some(value) = value {
+                entries.push((Value::int(*claim), Value::Text(value.clone())));
+            }
+        }
+        let times = [
+            (CLAIM_EXP, self.expiration),
+            (CLAIM_NBF, self.not_before),
+            (CLAIM_IAT, self.issued_at),
+        ];
+        for (claim, value) in times.iter() {
+            if let Some(value) = value {
+                entries.push((Value::int(*claim), Value::Unsigned(*value)));
+            }
+        }
+        if let Some(cwt_id) = &self.cwt_id {
+            entries.push((Value::int(CLAIM_CTI), Value::Bytes(cwt_id.clone())));
+        }
+
+        Value::Map(entries).to_vec()
+    }
+
+    fn from_cbor(payload: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let claims = Value::decode(payload)?;
+        if claims.as_map().is_none() {
+            return Err(UnknownCryptoError);
+        }
+
+        let text = |claim| -> Result<Option<String>, UnknownCryptoError> {
+            match claims.get(claim) {
+                None => Ok(None),
+                Some(value) => Ok(Some(value.as_text().ok_or(UnknownCryptoError)?.into())),
+            }
+        };
+        let time = |claim| -> Result<Option<u64>, UnknownCryptoError> {
+            match claims.get(claim) {
+                None => Ok(None),
+                Some(Value::Unsigned(value)) => Ok(Some(*value)),
+                Some(_) => Err(UnknownCryptoError),
+            }
+        };
+
+        Ok(Self {
+            issuer: text(CLAIM_ISS)?,
+            subject: text(CLAIM_SUB)?,
+            audience: text(CLAIM_AUD)?,
+            expiration: time(CLAIM_EXP)?,
+            not_before: time(CLAIM_NBF)?,
+            issued_at: time(CLAIM_IAT)?,
+            cwt_id: match claims.get(CLAIM_CTI) {
+                None => None,
+                Some(Value::Bytes(value)) => Some(value.clone()),
+                Some(_) => return Err(UnknownCryptoError),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The expected claims of a CWT.
+pub struct Validation {
+    /// The expected issuer.
+    pub issuer: Option<String>,
+    /// The audience of the party verifying or decrypting the token.
+    pub audience: Option<String>,
+    /// The number of seconds that `exp` and `nbf` may be exceeded by.
+    pub leeway: u64,
+}
+
+impl Validation {
+    fn check(&self, claims: &Claims, now: u64) -> Result<(), UnknownCryptoError> {
+        if let Some(expiration) = claims.expiration {
+            if now >= expiration.saturating_add(self.leeway) {
+                return Err(UnknownCryptoError);
+            }
+        }
+        if let Some(not_before) = claims.not_before {
+            if now.saturating_add(self.leeway) < not_before {
+                return Err(UnknownCryptoError);
+            }
+        }
+        if self.issuer.is_some() && claims.issuer != self.issuer {
+            return Err(UnknownCryptoError);
+        }
+        if claims.audience != self.audience {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(())
+    }
+}
+
+/// Strip the CWT tag from `token`, if it has one.
+fn untag(token: &[u8]) -> &[u8] {
+    if token.starts_with(&TAG_CWT_HEAD) {
+        &token[TAG_CWT_HEAD.len()..]
+    } else {
+        token
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `claims` with `key`, and return the CWT.
+pub fn sign(key: &SigningKey<'_>, claims: &Claims) -> Result<Vec<u8>, UnknownCryptoError> {
+    cose::sign1(key, &claims.to_cbor(), &[])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the signed `token` with `key`, and return its claims if they pass
+/// `validation` at `now`.
+pub fn verify(
+    key: &VerificationKey<'_>,
+    token: &[u8],
+    validation: &Validation,
+    now: u64,
+) -> Result<Claims, UnknownCryptoError> {
+    let claims = Claims::from_cbor(&cose::verify1(key, untag(token), &[])?)?;
+    validation.check(&claims, now)?;
+
+    Ok(claims)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encrypt `claims` with `key` and `nonce`, and return the CWT.
+pub fn encrypt(
+    key: &SecretKey,
+    nonce: &Nonce,
+    claims: &Claims,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    cose::encrypt0(key, nonce, &claims.to_cbor(), &[])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the encrypted `token` with `key`, and return its claims if they
+/// pass `validation` at `now`.
+pub fn decrypt(
+    key: &SecretKey,
+    token: &[u8],
+    validation: &Validation,
+    now: u64,
+) -> Result<Claims, UnknownCryptoError> {
+    let claims = Claims::from_cbor(&cose::decrypt0(key, untag(token), &[])?)?;
+    validation.check(&claims, now)?;
+
+    Ok(claims)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::ecc::{ecdsa_p256, ed25519};
+    use core::convert::TryFrom;
+
+    /// The signed CWT of RFC 8392 appendix A.3, with its key.
+    const RFC_8392_SIGNED: &str = "d28443a10126a104524173796d6d657472696345434453413235365850a70175636f61703a2f2f61732e6578616d706c652e636f6d02656572696b77037818636f61703a2f2f6c696768742e6578616d706c652e636f6d041a5612aeb0051a5610d9f0061a5610d9f007420b7158405427c1ff28d23fbad1f29c4c7c6a555e601d6fa29f9179bc3d7438bacaca5acd08c8d4d4f96131680c429a01f85951ecee743a52b9b63632c57209120e1c9e30";
+    const RFC_8392_KEY: &str = "6c1382765aec5358f117733d281c1c7bdc39884d04a45a1e6c67c858bc206c19";
+
+    fn rfc_8392_claims() -> Claims {
+        Claims {
+            issuer: Some("coap://as.example.com".into()),
+            subject: Some("erikw".into()),
+            audience: Some("coap://light.example.com".into()),
+            expiration: Some(1_444_064_944),
+            not_before: Some(1_443_944_944),
+            issued_at: Some(1_443_944_944),
+            cwt_id: Some(vec![0x0b, 0x71]),
+        }
+    }
+
+    fn rfc_8392_validation() -> Validation {
+        Validation {
+            issuer: Some("coap://as.example.com".into()),
+            audience: Some("coap://light.example.com".into()),
+            leeway: 0,
+        }
+    }
+
+    #[test]
+    fn test_rfc_8392_signed() {
+        let secret_key =
+            ecdsa_p256::SecretKey::from_slice(&hex::decode(RFC_8392_KEY).unwrap()).unwrap();
+        let public_key = ecdsa_p256::PublicKey::try_from(&secret_key).unwrap();
+        let key = VerificationKey::EcdsaP256(&public_key);
+        let token = hex::decode(RFC_8392_SIGNED).unwrap();
+        let validation = rfc_8392_validation();
+
+        assert_eq!(
+            verify(&key, &token, &validation, 1_444_000_000).unwrap(),
+            rfc_8392_claims()
+        );
+        // With the CWT tag.
+        let mut tagged = TAG_CWT_HEAD.to_vec();
+        tagged.extend_from_slice(&token);
+        assert!(verify(&key, &tagged, &validation, 1_444_000_000).is_ok());
+        // Another tag.
+        tagged[1] = 0x3E;
+        assert!(verify(&key, &tagged, &validation, 1_444_000_000).is_err());
+
+        // The payload of the RFC is encoded the same way.
+        let signed = sign(&SigningKey::EcdsaP256(&secret_key), &rfc_8392_claims()).unwrap();
+        assert_eq!(
+            signed[7..7 + 2 + 80],
+            token[token.len() - 66 - 82..token.len() - 66]
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let claims = rfc_8392_claims();
+        let validation = rfc_8392_validation();
+        let exp = claims.expiration.unwrap();
+        let nbf = claims.not_before.unwrap();
+
+        assert!(validation.check(&claims, nbf).is_ok());
+        assert!(validation.check(&claims, exp - 1).is_ok());
+        assert!(validation.check(&claims, exp).is_err());
+        assert!(validation.check(&claims, nbf - 1).is_err());
+
+        let with_leeway = Validation {
+            leeway: 10,
+            ..rfc_8392_validation()
+        };
+        assert!(with_leeway.check(&claims, exp + 9).is_ok());
+        assert!(with_leeway.check(&claims, exp + 10).is_err());
+        assert!(with_leeway.check(&claims, nbf - 10).is_ok());
+        assert!(with_leeway.check(&claims, nbf - 11).is_err());
+
+        let no_times = Claims {
+            expiration: None,
+            not_before: None,
+            ..rfc_8392_claims()
+        };
+        assert!(validation.check(&no_times, 0).is_ok());
+        assert!(validation.check(&no_times, u64::MAX).is_ok());
+        let max_exp = Claims {
+            expiration: Some(u64::MAX),
+            ..no_times.clone()
+        };
+        assert!(with_leeway.check(&max_exp, u64::MAX - 1).is_ok());
+
+        let wrong_issuer = Validation {
+            issuer: Some("coap://other.example.com".into()),
+            ..rfc_8392_validation()
+        };
+        assert!(wrong_issuer.check(&claims, nbf).is_err());
+        let any_issuer = Validation {
+            issuer: None,
+            ..rfc_8392_validation()
+        };
+        assert!(any_issuer.check(&claims, nbf).is_ok());
+        let no_issuer = Claims {
+            issuer: None,
+            ..rfc_8392_claims()
+        };
+        assert!(validation.check(&no_issuer, nbf).is_err());
+
+        let wrong_audience = Validation {
+            audience: Some("coap://other.example.com".into()),
+            ..rfc_8392_validation()
+        };
+        assert!(wrong_audience.check(&claims, nbf).is_err());
+        let no_audience = Validation {
+            audience: None,
+            ..rfc_8392_validation()
+        };
+        assert!(no_audience.check(&claims, nbf).is_err());
+        let for_anyone = Claims {
+            audience: None,
+            ..rfc_8392_claims()
+        };
+        assert!(no_audience.check(&for_anyone, nbf).is_ok());
+        assert!(validation.check(&for_anyone, nbf).is_err());
+    }
+
+    #[test]
+    fn test_claims_encoding() {
+        let claims = rfc_8392_claims();
+        assert_eq!(Claims::from_cbor(&claims.to_cbor()).unwrap(), claims);
+        assert_eq!(Claims::default().to_cbor(), [0xA0]);
+        assert_eq!(Claims::from_cbor(&[0xA0]).unwrap(), Claims::default());
+
+        // Other claims are ignored.
+        let other = Value::Map(vec![
+            (Value::int(CLAIM_SUB), Value::Text("erikw".into())),
+            (Value::int(-65537), Value::Bytes(vec![1, 2, 3])),
+            (Value::Text("scope".into()), Value::Text("read".into())),
+        ]);
+        let decoded = Claims::from_cbor(&other.to_vec()).unwrap();
+        assert_eq!(decoded.subject.as_deref(), Some("erikw"));
+
+        let bad = [
+            Value::Array(vec![]),
+            Value::Map(vec![(Value::int(CLAIM_ISS), Value::Bytes(vec![]))]),
+            Value::Map(vec![(Value::int(CLAIM_AUD), Value::Array(vec![]))]),
+            Value::Map(vec![(Value::int(CLAIM_EXP), Value::int(-1))]),
+            Value::Map(vec![(Value::int(CLAIM_NBF), Value::Text("0".into()))]),
+            Value::Map(vec![(Value::int(CLAIM_IAT), Value::Null)]),
+            Value::Map(vec![(Value::int(CLAIM_CTI), Value::Text("id".into()))]),
+        ];
+        for value in bad.iter() {
+            assert!(Claims::from_cbor(&value.to_vec()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sign_encrypt_roundtrip() {
+        let secret_key = ed25519::SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let key = VerificationKey::Ed25519(&public_key);
+        let claims = rfc_8392_claims();
+        let validation = rfc_8392_validation();
+        let now = claims.not_before.unwrap();
+
+        let token = sign(&SigningKey::Ed25519(&secret_key), &claims).unwrap();
+        assert_eq!(verify(&key, &token, &validation, now).unwrap(), claims);
+        assert!(verify(&key, &token, &validation, claims.expiration.unwrap()).is_err());
+
+        let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let nonce = Nonce::from([0x24; 12]);
+        let token = encrypt(&secret_key, &nonce, &claims).unwrap();
+        assert_eq!(
+            decrypt(&secret_key, &token, &validation, now).unwrap(),
+            claims
+        );
+        assert!(decrypt(&secret_key, &token, &Validation::default(), now).is_err());
+    }
+}
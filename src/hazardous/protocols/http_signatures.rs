@@ -0,0 +1,1451 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! HTTP message signatures, as specified in the [RFC 9421].
+//!
+//! # About:
+//! A signature covers an ordered list of components of an HTTP message,
+//! such as header fields and the request method. The covered components and
+//! the signature parameters are serialized into the signature base, which is
+//! what is signed. The `Signature-Input` and `Signature` header fields carry
+//! the covered components and parameters, and the signature itself.
+//!
+//! Resolving the values of components from a message is left to the caller,
+//! which passes them as [`Component`]s:
+//! - [`Component::field()`] is an HTTP field. All of its values, in the order
+//!   they appear in the message, are trimmed and combined.
+//! - [`Component::derived()`] is a derived component, such as `@method`,
+//!   `@authority` or `@path`.
+//! - [`Component::query_param()`] is a single query parameter, using its
+//!   percent-encoded name and value.
+//! - [`Component::dictionary_member()`] is a member of a Dictionary
+//!   Structured Field, using the caller's serialization of its value.
+//!
+//! The `sf`, `bs`, `req` and `tr` component parameters are not supported.
+//!
+//! [`sign()`] produces both header field values for a signature with the
+//! given label, and [`verify()`] checks the signature with a given label
+//! against the components that its `Signature-Input` says are covered.
+//!
+//! The supported algorithms are `ed25519` and `hmac-sha256`.
+//!
+//! # Parameters:
+//! - `key`: The key to sign or verify with, which determines the algorithm.
+//! - `label`: The label of the signature in the header fields.
+//! - `components`: The components of the message. When signing, these are
+//!   the covered components, in order. When verifying, these must include
+//!   all covered components, in any order.
+//! - `params`: The signature parameters.
+//! - `signature_input`: The value of the `Signature-Input` header field.
+//! - `signature`: The value of the `Signature` header field.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A field name is not lowercase or contains characters not allowed in a
+//!   field name, or no field values are given.
+//! - A derived component name is not one that is defined in RFC 9421, or it
+//!   is `@query-param` or `@signature-params`.
+//! - A component value contains control characters.
+//! - A query parameter name, dictionary key, `label` or string parameter
+//!   contains characters that can't be serialized.
+//! - The same component is covered twice.
+//! - The `alg` parameter does not match the algorithm of `key`.
+//! - `signature_input` or `signature` is not a valid Dictionary, or has no
+//!   member named `label` of the expected type.
+//! - A covered component is not in `components`.
+//! - The signature does not match.
+//!
+//! # Security:
+//! - [`verify()`] does not check the `created`, `expires`, `nonce` or `keyid`
+//!   parameters. Use the [`SignatureParams`] it returns to check that the
+//!   signature is fresh, not replayed and made with the expected key.
+//! - [`verify()`] checks that the covered components are present in the
+//!   message, but not that all the components the application requires are
+//!   covered. The caller must check the covered components of a verified
+//!   signature, using [`covered_components()`], against its own policy.
+//! - An HMAC key should be at least 32 bytes of random data, such as from
+//!   [`HmacKey::generate()`].
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::ed25519;
+//! use orion::hazardous::protocols::http_signatures::{
+//!     self, Component, SignatureParams, SigningKey, VerificationKey,
+//! };
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = ed25519::SecretKey::generate();
+//! let public_key = ed25519::PublicKey::try_from(&secret_key)?;
+//!
+//! let components = [
+//!     Component::derived("@method", "POST")?,
+//!     Component::derived("@authority", "example.com")?,
+//!     Component::field("content-type", &["application/json"])?,
+//! ];
+//! let params = SignatureParams {
+//!     created: Some(1618884473),
+//!     keyid: Some("test-key-ed25519".into()),
+//!     ..Default::default()
+//! };
+//!
+//! let headers = http_signatures::sign(&SigningKey::Ed25519(&secret_key), "sig1", &components, &params)?;
+//! assert_eq!(
+//!     headers.signature_input(),
+//!     r#"sig1=("@method" "@authority" "content-type");created=1618884473;keyid="test-key-ed25519""#
+//! );
+//!
+//! let verified = http_signatures::verify(
+//!     &VerificationKey::Ed25519(&public_key),
+//!     "sig1",
+//!     headers.signature_input(),
+//!     headers.signature(),
+//!     &components,
+//! )?;
+//! assert_eq!(verified, params);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9421]: https://www.rfc-editor.org/rfc/rfc9421
+//! [`Component`]: struct.Component.html
+//! [`Component::field()`]: struct.Component.html#method.field
+//! [`Component::derived()`]: struct.Component.html#method.derived
+//! [`Component::query_param()`]: struct.Component.html#method.query_param
+//! [`Component::dictionary_member()`]: struct.Component.html#method.dictionary_member
+//! [`SignatureParams`]: struct.SignatureParams.html
+//! [`HmacKey::generate()`]: struct.HmacKey.html
+//! [`sign()`]: fn.sign.html
+//! [`verify()`]: fn.verify.html
+//! [`covered_components()`]: fn.covered_components.html
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        ecc::ed25519,
+        hash::sha256::{Sha256, SHA256_BLOCKSIZE, SHA256_OUTSIZE},
+        mac::hmac::HmacGeneric,
+    },
+    util,
+};
+use base64::{decode_config, encode_config, STANDARD};
+
+/// The minimum size of an HMAC key.
+pub const MIN_HMAC_KEYSIZE: usize = 32;
+
+/// The derived component names that can be passed to [`Component::derived()`].
+///
+/// [`Component::derived()`]: struct.Component.html#method.derived
+pub const DERIVED_COMPONENTS: [&str; 8] = [
+    "@method",
+    "@target-uri",
+    "@authority",
+    "@scheme",
+    "@request-target",
+    "@path",
+    "@query",
+    "@status",
+];
+
+construct_secret_key! {
+    /// A type to represent the shared `HmacKey` used with `hmac-sha256`.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is less than 32 bytes or more than 64 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (HmacKey, test_hmac_key, MIN_HMAC_KEYSIZE, SHA256_BLOCKSIZE, MIN_HMAC_KEYSIZE)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A signature algorithm.
+pub enum Algorithm {
+    /// EdDSA using curve edwards25519.
+    Ed25519,
+    /// HMAC using SHA-256.
+    HmacSha256,
+}
+
+impl Algorithm {
+    /// The name of the algorithm, as used in the `alg` parameter.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Ed25519 => "ed25519",
+            Algorithm::HmacSha256 => "hmac-sha256",
+        }
+    }
+
+    fn from_name(name: &str) -> Result<Self, UnknownCryptoError> {
+        match name {
+            "ed25519" => Ok(Algorithm::Ed25519),
+            "hmac-sha256" => Ok(Algorithm::HmacSha256),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A key to sign a message with.
+pub enum SigningKey<'a> {
+    /// An Ed25519 secret key.
+    Ed25519(&'a ed25519::SecretKey),
+    /// An HMAC-SHA256 key.
+    HmacSha256(&'a HmacKey),
+}
+
+impl SigningKey<'_> {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            SigningKey::Ed25519(_) => Algorithm::Ed25519,
+            SigningKey::HmacSha256(_) => Algorithm::HmacSha256,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A key to verify a message with.
+pub enum VerificationKey<'a> {
+    /// An Ed25519 public key.
+    Ed25519(&'a ed25519::PublicKey),
+    /// An HMAC-SHA256 key.
+    HmacSha256(&'a HmacKey),
+}
+
+impl VerificationKey<'_> {
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            VerificationKey::Ed25519(_) => Algorithm::Ed25519,
+            VerificationKey::HmacSha256(_) => Algorithm::HmacSha256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A component of an HTTP message and its value.
+pub struct Component {
+    identifier: String,
+    value: String,
+}
+
+impl Component {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// An HTTP field with the lowercase `name`, and `values` in the order
+    /// they appear in the message.
+    pub fn field(name: &str, values: &[&str]) -> Result<Self, UnknownCryptoError> {
+        if !is_field_name(name) || values.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = String::new();
+        for (idx, field_value) in values.iter().enumerate() {
+            check_value(field_value)?;
+            if idx > 0 {
+                value.push_str(", ");
+            }
+            value.push_str(field_value.trim_matches(|c| c == ' ' || c == '\t'));
+        }
+
+        Ok(Self {
+            identifier: serialize_string(name)?,
+            value,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// A derived component, which is one of [`DERIVED_COMPONENTS`]. The value
+    /// of `@authority` and `@scheme` is lowercased.
+    ///
+    /// [`DERIVED_COMPONENTS`]: constant.DERIVED_COMPONENTS.html
+    pub fn derived(name: &str, value: &str) -> Result<Self, UnknownCryptoError> {
+        if !DERIVED_COMPONENTS.contains(&name) {
+            return Err(UnknownCryptoError);
+        }
+        check_value(value)?;
+        if name == "@status" && (value.len() != 3 || !value.bytes().all(|b| b.is_ascii_digit())) {
+            return Err(UnknownCryptoError);
+        }
+
+        let value = match name {
+            "@authority" | "@scheme" => value.to_ascii_lowercase(),
+            _ => value.to_string(),
+        };
+
+        Ok(Self {
+            identifier: serialize_string(name)?,
+            value,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The query parameter with the percent-encoded `name` and `value`.
+    pub fn query_param(name: &str, value: &str) -> Result<Self, UnknownCryptoError> {
+        check_value(value)?;
+
+        let mut identifier = serialize_string("@query-param")?;
+        identifier.push_str(";name=");
+        identifier.push_str(&serialize_string(name)?);
+
+        Ok(Self {
+            identifier,
+            value: value.to_string(),
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The member `key` of the Dictionary Structured Field with the lowercase
+    /// `name`, where `value` is the serialized value of that member.
+    pub fn dictionary_member(
+        name: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<Self, UnknownCryptoError> {
+        if !is_field_name(name) || !is_key(key) {
+            return Err(UnknownCryptoError);
+        }
+        check_value(value)?;
+
+        let mut identifier = serialize_string(name)?;
+        identifier.push_str(";key=");
+        identifier.push_str(&serialize_string(key)?);
+
+        Ok(Self {
+            identifier,
+            value: value.to_string(),
+        })
+    }
+
+    /// The serialized component identifier, such as `"content-type"`.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The value of the component, as it appears in the signature base.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The signature parameters defined in RFC 9421.
+pub struct SignatureParams {
+    /// The creation time, as a Unix timestamp.
+    pub created: Option<u64>,
+    /// The expiration time, as a Unix timestamp.
+    pub expires: Option<u64>,
+    /// A nonce, to detect replays.
+    pub nonce: Option<String>,
+    /// The algorithm, which must match the key if present.
+    pub alg: Option<Algorithm>,
+    /// The identifier of the key.
+    pub keyid: Option<String>,
+    /// An application-specific tag.
+    pub tag: Option<String>,
+}
+
+impl SignatureParams {
+    /// The parameters that are set, in the order they are defined in RFC 9421.
+    fn to_parameters(&self) -> Result<Parameters, UnknownCryptoError> {
+        let mut parameters = Parameters::new();
+        let mut push_integer = |key: &str, value: Option<u64>| -> Result<(), UnknownCryptoError> {
+            if let Some(value) = value {
+                if value > MAX_INTEGER as u64 {
+                    return Err(UnknownCryptoError);
+                }
+                parameters.push((key.to_string(), BareItem::Integer(value as i64)));
+            }
+            Ok(())
+        };
+        push_integer("created", self.created)?;
+        push_integer("expires", self.expires)?;
+
+        let strings = [
+            ("nonce", self.nonce.as_deref()),
+            ("alg", self.alg.map(|alg| alg.name())),
+            ("keyid", self.keyid.as_deref()),
+            ("tag", self.tag.as_deref()),
+        ];
+        for (key, value) in strings.iter() {
+            if let Some(value) = value {
+                parameters.push((key.to_string(), BareItem::String(value.to_string())));
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    /// The known parameters of `parameters`. Unknown parameters are ignored.
+    fn from_parameters(parameters: &[(String, BareItem)]) -> Result<Self, UnknownCryptoError> {
+        let mut params = Self::default();
+        for (key, value) in parameters.iter() {
+            match (key.as_str(), value) {
+                ("created", BareItem::Integer(n)) if *n >= 0 => params.created = Some(*n as u64),
+                ("expires", BareItem::Integer(n)) if *n >= 0 => params.expires = Some(*n as u64),
+                ("nonce", BareItem::String(s)) => params.nonce = Some(s.clone()),
+                ("alg", BareItem::String(s)) => params.alg = Some(Algorithm::from_name(s)?),
+                ("keyid", BareItem::String(s)) => params.keyid = Some(s.clone()),
+                ("tag", BareItem::String(s)) => params.tag = Some(s.clone()),
+                ("created", _)
+                | ("expires", _)
+                | ("nonce", _)
+                | ("alg", _)
+                | ("keyid", _)
+                | ("tag", _) => return Err(UnknownCryptoError),
+                _ => (),
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The values of the header fields for a signature.
+pub struct SignatureHeaders {
+    signature_input: String,
+    signature: String,
+}
+
+impl SignatureHeaders {
+    /// The value of the `Signature-Input` header field.
+    pub fn signature_input(&self) -> &str {
+        &self.signature_input
+    }
+
+    /// The value of the `Signature` header field.
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+}
+
+/// Check that `name` is a lowercase field name.
+fn is_field_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| {
+            b.is_ascii_lowercase() || b.is_ascii_digit() || b"!#$%&'*+-.^_`|~".contains(&b)
+        })
+}
+
+/// Check that `key` is a Structured Field key.
+fn is_key(key: &str) -> bool {
+    match key.as_bytes().first() {
+        Some(b) if b.is_ascii_lowercase() || *b == b'*' => key
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-.*".contains(&b)),
+        _ => false,
+    }
+}
+
+/// Check that `value` has no control characters, other than horizontal tabs.
+fn check_value(value: &str) -> Result<(), UnknownCryptoError> {
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        Err(UnknownCryptoError)
+    } else {
+        Ok(())
+    }
+}
+
+/// Serialize `value` as a Structured Field string.
+fn serialize_string(value: &str) -> Result<String, UnknownCryptoError> {
+    let mut serialized = String::with_capacity(value.len() + 2);
+    serialized.push('"');
+    for b in value.bytes() {
+        if !(0x20..=0x7e).contains(&b) {
+            return Err(UnknownCryptoError);
+        }
+        if b == b'"' || b == b'\\' {
+            serialized.push('\\');
+        }
+        serialized.push(b as char);
+    }
+    serialized.push('"');
+
+    Ok(serialized)
+}
+
+/// The largest integer allowed in a Structured Field.
+const MAX_INTEGER: i64 = 999_999_999_999_999;
+
+#[derive(Debug, Clone, PartialEq)]
+/// The subset of Structured Field bare items used by RFC 9421. Decimals are
+/// not supported.
+enum BareItem {
+    Integer(i64),
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+/// The parameters of an item or inner list, in order.
+type Parameters = Vec<(String, BareItem)>;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A member of a Dictionary Structured Field.
+enum Member {
+    Item(BareItem, Parameters),
+    InnerList(Vec<(BareItem, Parameters)>, Parameters),
+}
+
+impl BareItem {
+    fn serialize(&self, dst: &mut String) -> Result<(), UnknownCryptoError> {
+        match self {
+            BareItem::Integer(n) => dst.push_str(&n.to_string()),
+            BareItem::String(s) => dst.push_str(&serialize_string(s)?),
+            BareItem::Token(t) => dst.push_str(t),
+            BareItem::ByteSequence(bytes) => {
+                dst.push(':');
+                dst.push_str(&encode_config(bytes, STANDARD));
+                dst.push(':');
+            }
+            BareItem::Boolean(b) => dst.push_str(if *b { "?1" } else { "?0" }),
+        }
+
+        Ok(())
+    }
+}
+
+/// Serialize `parameters`, where a `true` value is left out.
+fn serialize_parameters(
+    parameters: &[(String, BareItem)],
+    dst: &mut String,
+) -> Result<(), UnknownCryptoError> {
+    for (key, value) in parameters.iter() {
+        dst.push(';');
+        dst.push_str(key);
+        if value != &BareItem::Boolean(true) {
+            dst.push('=');
+            value.serialize(dst)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A parser for the parts of RFC 8941 that RFC 9421 uses.
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn next_byte(&mut self) -> Result<u8, UnknownCryptoError> {
+        let b = self.peek().ok_or(UnknownCryptoError)?;
+        self.position += 1;
+        Ok(b)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), UnknownCryptoError> {
+        if self.next_byte()? == expected {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    fn skip(&mut self, whitespace: &[u8]) {
+        while matches!(self.peek(), Some(b) if whitespace.contains(&b)) {
+            self.position += 1;
+        }
+    }
+
+    /// Take the bytes from the current position while `predicate` holds.
+    fn take_while<F: Fn(u8) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.position;
+        while matches!(self.peek(), Some(b) if predicate(b)) {
+            self.position += 1;
+        }
+        // Only ASCII bytes are ever accepted by the predicates.
+        core::str::from_utf8(&self.input[start..self.position]).unwrap_or_default()
+    }
+
+    /// Parse a Dictionary. Later members replace earlier ones with the same key.
+    fn parse_dictionary(input: &'a str) -> Result<Vec<(String, Member)>, UnknownCryptoError> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        let mut members: Vec<(String, Member)> = Vec::new();
+
+        parser.skip(b" ");
+        while parser.peek().is_some() {
+            let key = parser.parse_key()?;
+            let member = if parser.peek() == Some(b'=') {
+                parser.position += 1;
+                parser.parse_item_or_inner_list()?
+            } else {
+                Member::Item(BareItem::Boolean(true), parser.parse_parameters()?)
+            };
+            match members.iter_mut().find(|(existing, _)| existing == &key) {
+                Some(existing) => existing.1 = member,
+                None => members.push((key, member)),
+            }
+
+            parser.skip(b" \t");
+            if parser.peek().is_none() {
+                break;
+            }
+            parser.expect(b',')?;
+            parser.skip(b" \t");
+            if parser.peek().is_none() {
+                return Err(UnknownCryptoError);
+            }
+        }
+
+        Ok(members)
+    }
+
+    fn parse_item_or_inner_list(&mut self) -> Result<Member, UnknownCryptoError> {
+        if self.peek() != Some(b'(') {
+            let item = self.parse_bare_item()?;
+            return Ok(Member::Item(item, self.parse_parameters()?));
+        }
+
+        self.position += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip(b" ");
+            if self.peek() == Some(b')') {
+                self.position += 1;
+                return Ok(Member::InnerList(items, self.parse_parameters()?));
+            }
+            let item = self.parse_bare_item()?;
+            items.push((item, self.parse_parameters()?));
+            match self.peek() {
+                Some(b' ') | Some(b')') => (),
+                _ => return Err(UnknownCryptoError),
+            }
+        }
+    }
+
+    fn parse_parameters(&mut self) -> Result<Parameters, UnknownCryptoError> {
+        let mut parameters: Parameters = Vec::new();
+        while self.peek() == Some(b';') {
+            self.position += 1;
+            self.skip(b" ");
+            let key = self.parse_key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.position += 1;
+                self.parse_bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+            match parameters.iter_mut().find(|(existing, _)| existing == &key) {
+                Some(existing) => existing.1 = value,
+                None => parameters.push((key, value)),
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    fn parse_key(&mut self) -> Result<String, UnknownCryptoError> {
+        match self.peek() {
+            Some(b) if b.is_ascii_lowercase() || b == b'*' => Ok(self
+                .take_while(|b| {
+                    b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-.*".contains(&b)
+                })
+                .to_string()),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    fn parse_bare_item(&mut self) -> Result<BareItem, UnknownCryptoError> {
+        match self.peek().ok_or(UnknownCryptoError)? {
+            b'-' | b'0'..=b'9' => self.parse_integer(),
+            b'"' => self.parse_string(),
+            b':' => self.parse_byte_sequence(),
+            b'?' => {
+                self.position += 1;
+                match self.next_byte()? {
+                    b'0' => Ok(BareItem::Boolean(false)),
+                    b'1' => Ok(BareItem::Boolean(true)),
+                    _ => Err(UnknownCryptoError),
+                }
+            }
+            b if b.is_ascii_alphabetic() || b == b'*' => Ok(BareItem::Token(
+                self.take_while(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&b))
+                    .to_string(),
+            )),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<BareItem, UnknownCryptoError> {
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.position += 1;
+        }
+        let digits = self.take_while(|b| b.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 15 || self.peek() == Some(b'.') {
+            return Err(UnknownCryptoError);
+        }
+        let value: i64 = digits.parse().map_err(|_| UnknownCryptoError)?;
+
+        Ok(BareItem::Integer(if negative { -value } else { value }))
+    }
+
+    fn parse_string(&mut self) -> Result<BareItem, UnknownCryptoError> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            match self.next_byte()? {
+                b'"' => return Ok(BareItem::String(value)),
+                b'\\' => match self.next_byte()? {
+                    b @ b'"' | b @ b'\\' => value.push(b as char),
+                    _ => return Err(UnknownCryptoError),
+                },
+                b @ 0x20..=0x7e => value.push(b as char),
+                _ => return Err(UnknownCryptoError),
+            }
+        }
+    }
+
+    fn parse_byte_sequence(&mut self) -> Result<BareItem, UnknownCryptoError> {
+        self.expect(b':')?;
+        let encoded = self.take_while(|b| b.is_ascii_alphanumeric() || b"+/=".contains(&b));
+        self.expect(b':')?;
+
+        Ok(BareItem::ByteSequence(decode_config(encoded, STANDARD)?))
+    }
+}
+
+/// Find the member named `label` in the Dictionary Structured Field `input`.
+fn find_member(input: &str, label: &str) -> Result<Member, UnknownCryptoError> {
+    Parser::parse_dictionary(input)?
+        .into_iter()
+        .find(|(key, _)| key == label)
+        .map(|(_, member)| member)
+        .ok_or(UnknownCryptoError)
+}
+
+/// Serialize the inner list of `identifiers` with `parameters`, which is the
+/// value of the `@signature-params` component.
+fn signature_params<'a, I: Iterator<Item = &'a str>>(
+    identifiers: I,
+    parameters: &[(String, BareItem)],
+) -> Result<String, UnknownCryptoError> {
+    let mut covered: Vec<&str> = Vec::new();
+    for identifier in identifiers {
+        if covered.contains(&identifier) {
+            return Err(UnknownCryptoError);
+        }
+        covered.push(identifier);
+    }
+
+    let mut serialized = String::from("(");
+    serialized.push_str(&covered.join(" "));
+    serialized.push(')');
+    serialize_parameters(parameters, &mut serialized)?;
+
+    Ok(serialized)
+}
+
+/// Build the signature base from the covered `components` and the value of
+/// `@signature-params`.
+fn build_base(components: &[&Component], signature_params: &str) -> String {
+    let mut base = String::new();
+    for component in components.iter() {
+        base.push_str(&component.identifier);
+        base.push_str(": ");
+        base.push_str(&component.value);
+        base.push('\n');
+    }
+    base.push_str("\"@signature-params\": ");
+    base.push_str(signature_params);
+
+    base
+}
+
+/// HMAC-SHA256 of `data`.
+fn hmac_sha256(key: &HmacKey, data: &[u8]) -> Result<[u8; SHA256_OUTSIZE], UnknownCryptoError> {
+    let mut state = HmacGeneric::<Sha256>::new(key.unprotected_as_bytes())?;
+    state.update(data)?;
+    let mut tag = [0u8; SHA256_OUTSIZE];
+    state.finalize_into(&mut tag)?;
+
+    Ok(tag)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Build the signature base for the covered `components` and `params`.
+pub fn signature_base(
+    components: &[Component],
+    params: &SignatureParams,
+) -> Result<String, UnknownCryptoError> {
+    let covered: Vec<&Component> = components.iter().collect();
+    let signature_params = signature_params(
+        components.iter().map(|c| c.identifier.as_str()),
+        &params.to_parameters()?,
+    )?;
+
+    Ok(build_base(&covered, &signature_params))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign the covered `components` and `params` with `key`, returning the
+/// values of the `Signature-Input` and `Signature` header fields.
+pub fn sign(
+    key: &SigningKey<'_>,
+    label: &str,
+    components: &[Component],
+    params: &SignatureParams,
+) -> Result<SignatureHeaders, UnknownCryptoError> {
+    if !is_key(label) || matches!(params.alg, Some(alg) if alg != key.algorithm()) {
+        return Err(UnknownCryptoError);
+    }
+
+    let covered: Vec<&Component> = components.iter().collect();
+    let signature_params = signature_params(
+        components.iter().map(|c| c.identifier.as_str()),
+        &params.to_parameters()?,
+    )?;
+    let base = build_base(&covered, &signature_params);
+
+    let signature = match key {
+        SigningKey::Ed25519(secret_key) => {
+            encode_config(ed25519::sign(secret_key, base.as_bytes())?, STANDARD)
+        }
+        SigningKey::HmacSha256(hmac_key) => {
+            encode_config(hmac_sha256(hmac_key, base.as_bytes())?, STANDARD)
+        }
+    };
+
+    Ok(SignatureHeaders {
+        signature_input: format!("{}={}", label, signature_params),
+        signature: format!("{}=:{}:", label, signature),
+    })
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// The identifiers of the components covered by the signature with `label`
+/// in `signature_input`, in order.
+pub fn covered_components(
+    signature_input: &str,
+    label: &str,
+) -> Result<Vec<String>, UnknownCryptoError> {
+    let items = match find_member(signature_input, label)? {
+        Member::InnerList(items, _) => items,
+        _ => return Err(UnknownCryptoError),
+    };
+
+    let mut identifiers = Vec::with_capacity(items.len());
+    for (item, parameters) in items.iter() {
+        let mut identifier = match item {
+            BareItem::String(name) => serialize_string(name)?,
+            _ => return Err(UnknownCryptoError),
+        };
+        serialize_parameters(parameters, &mut identifier)?;
+        identifiers.push(identifier);
+    }
+
+    Ok(identifiers)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the signature with `label`, using the covered components from
+/// `components`, and return its parameters.
+pub fn verify(
+    key: &VerificationKey<'_>,
+    label: &str,
+    signature_input: &str,
+    signature: &str,
+    components: &[Component],
+) -> Result<SignatureParams, UnknownCryptoError> {
+    let parameters = match find_member(signature_input, label)? {
+        Member::InnerList(_, parameters) => parameters,
+        _ => return Err(UnknownCryptoError),
+    };
+    let params = SignatureParams::from_parameters(&parameters)?;
+    if matches!(params.alg, Some(alg) if alg != key.algorithm()) {
+        return Err(UnknownCryptoError);
+    }
+
+    let identifiers = covered_components(signature_input, label)?;
+    let mut covered = Vec::with_capacity(identifiers.len());
+    for identifier in identifiers.iter() {
+        covered.push(
+            components
+                .iter()
+                .find(|c| &c.identifier == identifier)
+                .ok_or(UnknownCryptoError)?,
+        );
+    }
+    let signature_params = signature_params(identifiers.iter().map(|i| i.as_str()), &parameters)?;
+    let base = build_base(&covered, &signature_params);
+
+    let signature = match find_member(signature, label)? {
+        Member::Item(BareItem::ByteSequence(bytes), _) => bytes,
+        _ => return Err(UnknownCryptoError),
+    };
+    match key {
+        VerificationKey::Ed25519(public_key) => ed25519::verify(
+            &ed25519::Signature::from_slice(&signature)?,
+            public_key,
+            base.as_bytes(),
+        )?,
+        VerificationKey::HmacSha256(hmac_key) => {
+            util::secure_cmp(&hmac_sha256(hmac_key, base.as_bytes())?, &signature)?
+        }
+    }
+
+    Ok(params)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn components() -> Vec<Component> {
+        vec![
+            Component::derived("@method", "POST").unwrap(),
+            Component::derived("@authority", "Example.COM").unwrap(),
+            Component::field("content-type", &["application/json"]).unwrap(),
+            Component::query_param("pet", "dog").unwrap(),
+            Component::dictionary_member("example-dict", "a", "1").unwrap(),
+        ]
+    }
+
+    fn params() -> SignatureParams {
+        SignatureParams {
+            created: Some(1618884473),
+            expires: Some(1618884773),
+            nonce: Some("nonce".into()),
+            alg: None,
+            keyid: Some("key".into()),
+            tag: Some("app".into()),
+        }
+    }
+
+    #[test]
+    fn test_component_identifiers_and_values() {
+        let components = components();
+        assert_eq!(components[0].identifier(), "\"@method\"");
+        assert_eq!(components[1].value(), "example.com");
+        assert_eq!(components[3].identifier(), "\"@query-param\";name=\"pet\"");
+        assert_eq!(components[4].identifier(), "\"example-dict\";key=\"a\"");
+
+        let field = Component::field("x-list", &[" a ", "\tb, c", ""]).unwrap();
+        assert_eq!(field.value(), "a, b, c, ");
+    }
+
+    #[test]
+    fn test_component_err() {
+        assert!(Component::field("Content-Type", &["a"]).is_err());
+        assert!(Component::field("", &["a"]).is_err());
+        assert!(Component::field("content type", &["a"]).is_err());
+        assert!(Component::field("content-type", &[]).is_err());
+        assert!(Component::field("content-type", &["a\r\nb: c"]).is_err());
+        assert!(Component::derived("@signature-params", "()").is_err());
+        assert!(Component::derived("@query-param", "a").is_err());
+        assert!(Component::derived("@unknown", "a").is_err());
+        assert!(Component::derived("method", "POST").is_err());
+        assert!(Component::derived("@method", "PO\nST").is_err());
+        assert!(Component::derived("@status", "2000").is_err());
+        assert!(Component::derived("@status", "2x0").is_err());
+        Component::derived("@status", "200").unwrap();
+        assert!(Component::query_param("p\u{e9}t", "dog").is_err());
+        assert!(Component::dictionary_member("example-dict", "A", "1").is_err());
+        assert!(Component::dictionary_member("example-dict", "1a", "1").is_err());
+    }
+
+    #[test]
+    fn test_signature_base() {
+        let base = signature_base(&components()[..3], &params()).unwrap();
+        assert_eq!(
+            base,
+            "\"@method\": POST\n\
+             \"@authority\": example.com\n\
+             \"content-type\": application/json\n\
+             \"@signature-params\": (\"@method\" \"@authority\" \"content-type\")\
+             ;created=1618884473;expires=1618884773;nonce=\"nonce\";keyid=\"key\";tag=\"app\""
+        );
+
+        let base = signature_base(&[], &SignatureParams::default()).unwrap();
+        assert_eq!(base, "\"@signature-params\": ()");
+    }
+
+    #[test]
+    fn test_duplicate_component_err() {
+        let mut components = components();
+        components.push(Component::derived("@method", "GET").unwrap());
+        assert!(signature_base(&components, &params()).is_err());
+
+        let secret_key = ed25519::SecretKey::generate();
+        let key = SigningKey::Ed25519(&secret_key);
+        assert!(sign(&key, "sig1", &components, &params()).is_err());
+    }
+
+    #[test]
+    fn test_string_params_err() {
+        let mut params = params();
+        params.keyid = Some("k\u{e9}y".into());
+        assert!(signature_base(&components(), &params).is_err());
+
+        let mut params = self::params();
+        params.created = Some(MAX_INTEGER as u64 + 1);
+        assert!(signature_base(&components(), &params).is_err());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let secret_key = ed25519::SecretKey::generate();
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let hmac_key = HmacKey::generate();
+        let components = components();
+        let mut params = params();
+
+        for (signing_key, verification_key, alg) in [
+            (
+                SigningKey::Ed25519(&secret_key),
+                VerificationKey::Ed25519(&public_key),
+                Algorithm::Ed25519,
+            ),
+            (
+                SigningKey::HmacSha256(&hmac_key),
+                VerificationKey::HmacSha256(&hmac_key),
+                Algorithm::HmacSha256,
+            ),
+        ]
+        .iter()
+        {
+            params.alg = Some(*alg);
+            let headers = sign(signing_key, "sig1", &components, &params).unwrap();
+            let verified = verify(
+                verification_key,
+                "sig1",
+                headers.signature_input(),
+                headers.signature(),
+                &components,
+            )
+            .unwrap();
+            assert_eq!(verified, params);
+
+            // The covered components can be passed in any order, along with others.
+            let mut reordered = components.clone();
+            reordered.reverse();
+            reordered.push(Component::derived("@path", "/foo").unwrap());
+            verify(
+                verification_key,
+                "sig1",
+                headers.signature_input(),
+                headers.signature(),
+                &reordered,
+            )
+            .unwrap();
+
+            // A missing covered component.
+            assert!(verify(
+                verification_key,
+                "sig1",
+                headers.signature_input(),
+                headers.signature(),
+                &components[1..],
+            )
+            .is_err());
+
+            // A modified component value.
+            let mut modified = components.clone();
+            modified[2] = Component::field("content-type", &["text/plain"]).unwrap();
+            assert!(verify(
+                verification_key,
+                "sig1",
+                headers.signature_input(),
+                headers.signature(),
+                &modified,
+            )
+            .is_err());
+
+            // A modified parameter.
+            let modified_input = headers
+                .signature_input()
+                .replace("1618884773", "1618884774");
+            assert!(verify(
+                verification_key,
+                "sig1",
+                &modified_input,
+                headers.signature(),
+                &components,
+            )
+            .is_err());
+
+            // Another label.
+            assert!(verify(
+                verification_key,
+                "sig2",
+                headers.signature_input(),
+                headers.signature(),
+                &components,
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_alg_mismatch_err() {
+        let secret_key = ed25519::SecretKey::generate();
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let hmac_key = HmacKey::generate();
+        let mut params = params();
+        params.alg = Some(Algorithm::HmacSha256);
+
+        assert!(sign(
+            &SigningKey::Ed25519(&secret_key),
+            "sig1",
+            &components(),
+            &params
+        )
+        .is_err());
+
+        let headers = sign(
+            &SigningKey::HmacSha256(&hmac_key),
+            "sig1",
+            &components(),
+            &params,
+        )
+        .unwrap();
+        assert!(verify(
+            &VerificationKey::Ed25519(&public_key),
+            "sig1",
+            headers.signature_input(),
+            headers.signature(),
+            &components(),
+        )
+        .is_err());
+
+        // An unknown algorithm.
+        let unknown = headers
+            .signature_input()
+            .replace("hmac-sha256", "hmac-sha512");
+        assert!(verify(
+            &VerificationKey::HmacSha256(&hmac_key),
+            "sig1",
+            &unknown,
+            headers.signature(),
+            &components(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_invalid_label_err() {
+        let hmac_key = HmacKey::generate();
+        let key = SigningKey::HmacSha256(&hmac_key);
+        assert!(sign(&key, "Sig1", &components(), &params()).is_err());
+        assert!(sign(&key, "", &components(), &params()).is_err());
+        assert!(sign(&key, "sig 1", &components(), &params()).is_err());
+    }
+
+    #[test]
+    fn test_verify_multiple_signatures_and_parameter_order() {
+        let hmac_key = HmacKey::generate();
+        let components = components();
+        let base = "\"@method\": POST\n\
+                    \"@signature-params\": (\"@method\");keyid=\"key\";created=1;unknown=?0";
+        let tag = encode_config(hmac_sha256(&hmac_key, base.as_bytes()).unwrap(), STANDARD);
+
+        let signature_input = "other=(\"@path\");created=2, \
+                               sig1=( \"@method\" );keyid=\"key\";created=1;unknown=?0";
+        let signature = format!("other=:AAAA:,sig1=:{}:", tag);
+        let verified = verify(
+            &VerificationKey::HmacSha256(&hmac_key),
+            "sig1",
+            signature_input,
+            &signature,
+            &components,
+        )
+        .unwrap();
+        assert_eq!(verified.created, Some(1));
+        assert_eq!(verified.keyid.as_deref(), Some("key"));
+        assert_eq!(
+            covered_components(signature_input, "sig1").unwrap(),
+            vec!["\"@method\"".to_string()]
+        );
+        assert_eq!(
+            covered_components(signature_input, "other").unwrap(),
+            vec!["\"@path\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_dictionary() {
+        let members = Parser::parse_dictionary(
+            "a=(\"x\";key=\"y\" \"z\");n=-5, b=:AQI=:, c, d=?0;p, e=tok/en:1",
+        )
+        .unwrap();
+        assert_eq!(members.len(), 5);
+        assert_eq!(
+            members[0].1,
+            Member::InnerList(
+                vec![
+                    (
+                        BareItem::String("x".into()),
+                        vec![("key".into(), BareItem::String("y".into()))]
+                    ),
+                    (BareItem::String("z".into()), vec![]),
+                ],
+                vec![("n".into(), BareItem::Integer(-5))]
+            )
+        );
+        assert_eq!(
+            members[1].1,
+            Member::Item(BareItem::ByteSequence(vec![1, 2]), vec![])
+        );
+        assert_eq!(members[2].1, Member::Item(BareItem::Boolean(true), vec![]));
+        assert_eq!(
+            members[3].1,
+            Member::Item(
+                BareItem::Boolean(false),
+                vec![("p".into(), BareItem::Boolean(true))]
+            )
+        );
+        assert_eq!(
+            members[4].1,
+            Member::Item(BareItem::Token("tok/en:1".into()), vec![])
+        );
+
+        // Later members replace earlier ones.
+        let members = Parser::parse_dictionary("a=1, a=2").unwrap();
+        assert_eq!(
+            members,
+            vec![("a".into(), Member::Item(BareItem::Integer(2), vec![]))]
+        );
+        assert!(Parser::parse_dictionary("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_dictionary_err() {
+        for input in [
+            "a=1,",
+            "A=1",
+            "a=1 b=2",
+            "a=(\"x\"\"y\")",
+            "a=(\"x\"",
+            "a=\"unterminated",
+            "a=\"bad\\escape\"",
+            "a=1.5",
+            "a=1234567890123456",
+            "a=:not base64!:",
+            "a=?2",
+            "a=@",
+        ]
+        .iter()
+        {
+            assert!(Parser::parse_dictionary(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_malformed_headers_err() {
+        let hmac_key = HmacKey::generate();
+        let key = VerificationKey::HmacSha256(&hmac_key);
+        let headers = sign(
+            &SigningKey::HmacSha256(&hmac_key),
+            "sig1",
+            &components(),
+            &params(),
+        )
+        .unwrap();
+
+        // The signature input is not an inner list.
+        assert!(verify(
+            &key,
+            "sig1",
+            "sig1=\"x\"",
+            headers.signature(),
+            &components()
+        )
+        .is_err());
+        // A covered component is not a string.
+        assert!(verify(
+            &key,
+            "sig1",
+            "sig1=(tok)",
+            headers.signature(),
+            &components()
+        )
+        .is_err());
+        // The signature is not a byte sequence.
+        assert!(verify(
+            &key,
+            "sig1",
+            headers.signature_input(),
+            "sig1=\"x\"",
+            &components()
+        )
+        .is_err());
+        // A known parameter has the wrong type.
+        assert!(verify(
+            &key,
+            "sig1",
+            "sig1=();created=\"1\"",
+            headers.signature(),
+            &[]
+        )
+        .is_err());
+        assert!(verify(&key, "sig1", "sig1=();created=-1", headers.signature(), &[]).is_err());
+        // Duplicate covered components.
+        assert!(verify(
+            &key,
+            "sig1",
+            "sig1=(\"@method\" \"@method\")",
+            headers.signature(),
+            &components()
+        )
+        .is_err());
+        // A truncated signature.
+        assert!(verify(
+            &key,
+            "sig1",
+            headers.signature_input(),
+            "sig1=:AAAA:",
+            &components()
+        )
+        .is_err());
+    }
+}
+
+// Testing any test vectors that aren't put into library's /tests folder.
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    fn rfc_components() -> Vec<Component> {
+        vec![
+            Component::field("date", &["Tue, 20 Apr 2021 02:07:55 GMT"]).unwrap(),
+            Component::derived("@method", "POST").unwrap(),
+            Component::derived("@path", "/foo").unwrap(),
+            Component::derived("@authority", "example.com").unwrap(),
+            Component::field("content-type", &["application/json"]).unwrap(),
+            Component::field("content-length", &["18"]).unwrap(),
+        ]
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc9421#appendix-B.2.5
+    #[test]
+    fn test_rfc9421_b_2_5_hmac_sha256() {
+        let hmac_key = HmacKey::from_slice(
+            &decode_config(
+                "uzvJfB4u3N0Jy4T7NZ75MDVcr8zSTInedJtkgcu46YW4XByzNJjxBdtjUkdJPBtbmHhIDi6pcl8jsasjlTMtDQ==",
+                STANDARD,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let components = rfc_components();
+        let covered = [
+            components[0].clone(),
+            components[3].clone(),
+            components[4].clone(),
+        ];
+        let params = SignatureParams {
+            created: Some(1618884473),
+            keyid: Some("test-shared-secret".into()),
+            ..Default::default()
+        };
+
+        let headers = sign(
+            &SigningKey::HmacSha256(&hmac_key),
+            "sig-b25",
+            &covered,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(
+            headers.signature_input(),
+            "sig-b25=(\"date\" \"@authority\" \"content-type\");created=1618884473;keyid=\"test-shared-secret\""
+        );
+        assert_eq!(
+            headers.signature(),
+            "sig-b25=:pxcQw6G3AjtMBQjwo8XzkZf/bws5LelbaMk5rGIGtE8=:"
+        );
+
+        let verified = verify(
+            &VerificationKey::HmacSha256(&hmac_key),
+            "sig-b25",
+            headers.signature_input(),
+            headers.signature(),
+            &components,
+        )
+        .unwrap();
+        assert_eq!(verified, params);
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc9421#appendix-B.2.6
+    #[test]
+    fn test_rfc9421_b_2_6_ed25519() {
+        let secret_key = ed25519::SecretKey::from_slice(
+            &hex::decode("9f8362f87a484a954e6e740c5b4c0e84229139a20aa8ab56ff66586f6a7d29c5")
+                .unwrap(),
+        )
+        .unwrap();
+        let public_key = ed25519::PublicKey::from_slice(
+            &hex::decode("26b40b8f93fff3d897112f7ebc582b232dbd72517d082fe83cfb30ddce43d1bb")
+                .unwrap(),
+        )
+        .unwrap();
+        let components = rfc_components();
+        let params = SignatureParams {
+            created: Some(1618884473),
+            keyid: Some("test-key-ed25519".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            signature_base(&components, &params).unwrap(),
+            "\"date\": Tue, 20 Apr 2021 02:07:55 GMT\n\
+             \"@method\": POST\n\
+             \"@path\": /foo\n\
+             \"@authority\": example.com\n\
+             \"content-type\": application/json\n\
+             \"content-length\": 18\n\
+             \"@signature-params\": (\"date\" \"@method\" \"@path\" \"@authority\" \
+             \"content-type\" \"content-length\");created=1618884473;keyid=\"test-key-ed25519\""
+        );
+
+        let headers = sign(
+            &SigningKey::Ed25519(&secret_key),
+            "sig-b26",
+            &components,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(
+            headers.signature(),
+            "sig-b26=:wqcAqbmYJ2ji2glfAMaRy4gruYYnx2nEFN2HN6jrnDnQCK1u02Gb04v9EDgwUPiu4A0w6vuQv5lIp5WPpBKRCw==:"
+        );
+
+        verify(
+            &VerificationKey::Ed25519(&public_key),
+            "sig-b26",
+            headers.signature_input(),
+            headers.signature(),
+            &components,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Forward-secure, hash-chained audit logs.
+pub mod audit_log;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// COSE single-signer signatures and single-recipient encryption.
+pub mod cose;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// CBOR Web Tokens.
+pub mod cwt;
+
+#[cfg(feature = "safe_api")]
+/// HTTP message signatures.
+pub mod http_signatures;
+
+/// Key confirmation and AEAD sessions after a password-authenticated key
+/// exchange.
+pub mod pake_session;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Importing private keys and certificates from PKCS #12 archives.
+pub mod pkcs12;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Password-protected PKCS #8 private keys.
+pub mod pkcs8;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Privacy Pass token issuance and redemption.
+pub mod privacy_pass;
+
+/// Sliding window replay protection for message counters.
+pub mod replay;
+
+/// SRTP and SRTCP key derivation and packet protection.
+pub mod srtp;
+
+/// WireGuard handshake, cookie and transport data primitives.
+pub mod wireguard;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// X.509 certificates and certificate signing requests.
+pub mod x509;
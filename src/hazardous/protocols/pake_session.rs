@@ -0,0 +1,660 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! Key confirmation and directional AEAD sessions for the end of a
+//! password-authenticated key exchange (PAKE), such as OPAQUE, CPace or
+//! SPAKE2. These leave both parties with a shared secret, and require that
+//! each party confirms to the other that it holds the same secret and has
+//! seen the same protocol transcript, before any keys are used.
+//!
+//! [`Handshake::new()`] derives all keys of a session from the shared secret
+//! and the transcript with HKDF-SHA256:
+//! - The transcript hash `th` is the SHA-256 digest of `transcript`, and the
+//!   pseudorandom key is `HKDF-Extract(th, shared_secret)`.
+//! - Each party has a confirmation key, an AEAD key and a static IV, expanded
+//!   from the pseudorandom key with the info
+//!   `"orion pake session initiator confirmation"`, `"... initiator key"` and
+//!   `"... initiator iv"`, or the same with `responder` for the responder.
+//! - The confirmation message of a party is `HMAC-SHA256(confirmation_key, th)`.
+//!
+//! Each party sends the message of [`Handshake::confirmation()`] to the other.
+//! [`Handshake::finish()`] verifies the confirmation message of the peer, and
+//! only then returns a [`SealingSession`] and an [`OpeningSession`]. These
+//! encrypt the messages of each direction with ChaCha20Poly1305 under their own
+//! key, and the nonce of each message is the static IV XOR its sequence number,
+//! as computed by [`nonce::xor_nonce()`]. Messages must therefore be opened in
+//! the order they were sealed.
+//!
+//! # Parameters:
+//! - `shared_secret`: The shared secret output by the PAKE.
+//! - `transcript`: The transcript of the PAKE.
+//! - `role`: Whether this party initiated the PAKE or responded to it.
+//! - `peer_confirmation`: The confirmation message received from the peer.
+//! - `aad`: Additional data to authenticate with a message.
+//! - `plaintext`: The message to seal.
+//! - `ciphertext_with_tag`: The sealed message to open.
+//! - `dst_out`: Destination buffer for the sealed or opened message.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `shared_secret` is less than [`MIN_SHARED_SECRET_SIZE`] bytes.
+//! - `transcript` is empty.
+//! - `peer_confirmation` does not match the expected confirmation message of
+//!   the peer.
+//! - The length of `dst_out` is less than `plaintext` + [`TAG_SIZE`] when
+//!   sealing.
+//! - The length of `ciphertext_with_tag` is less than [`TAG_SIZE`].
+//! - The length of `dst_out` is less than `ciphertext_with_tag` - [`TAG_SIZE`]
+//!   when opening.
+//! - The received tag does not match the calculated tag when opening.
+//! - `2^64 - 1` messages have been sealed or opened with a session.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2^32-1 * 64 bytes of data are processed.
+//!
+//! # Security:
+//! - `transcript` must unambiguously encode every message of the PAKE as it
+//!   was sent or received, along with the identities of both parties and any
+//!   context the PAKE binds to. The sessions are only bound to what the
+//!   transcript contains.
+//! - The peer must not be treated as authenticated, and no application data
+//!   must be sent, before [`Handshake::finish()`] has succeeded. If it fails,
+//!   the PAKE must be aborted and not retried with the same shared secret.
+//! - The confirmation messages of the initiator and the responder use separate
+//!   keys, so a confirmation message reflected back to its sender is rejected.
+//!   Both parties must agree on their roles.
+//! - A failed [`OpeningSession::open()`] does not advance the sequence of the
+//!   session, so the message can be retried. A message that was lost cannot be
+//!   skipped, since later messages depend on its position.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::pake_session::{Handshake, Role, TAG_SIZE};
+//!
+//! // The shared secret and transcript of a completed PAKE.
+//! let shared_secret = [7u8; 32];
+//! let transcript = b"client id, server id and all PAKE messages";
+//!
+//! let client = Handshake::new(&shared_secret, transcript, Role::Initiator)?;
+//! let server = Handshake::new(&shared_secret, transcript, Role::Responder)?;
+//!
+//! // Each party sends its confirmation message to the other.
+//! let client_confirmation = client.confirmation()?;
+//! let server_confirmation = server.confirmation()?;
+//!
+//! let (mut client_sealer, mut client_opener) = client.finish(&server_confirmation)?;
+//! let (mut server_sealer, mut server_opener) = server.finish(&client_confirmation)?;
+//!
+//! let mut ciphertext = [0u8; 5 + TAG_SIZE];
+//! client_sealer.seal(b"", b"hello", &mut ciphertext)?;
+//! let mut plaintext = [0u8; 5];
+//! server_opener.open(b"", &ciphertext, &mut plaintext)?;
+//! assert_eq!(&plaintext, b"hello");
+//!
+//! server_sealer.seal(b"", b"world", &mut ciphertext)?;
+//! client_opener.open(b"", &ciphertext, &mut plaintext)?;
+//! assert_eq!(&plaintext, b"world");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`Handshake::new()`]: struct.Handshake.html#method.new
+//! [`Handshake::confirmation()`]: struct.Handshake.html#method.confirmation
+//! [`Handshake::finish()`]: struct.Handshake.html#method.finish
+//! [`SealingSession`]: struct.SealingSession.html
+//! [`OpeningSession`]: struct.OpeningSession.html
+//! [`OpeningSession::open()`]: struct.OpeningSession.html#method.open
+//! [`nonce::xor_nonce()`]: ../../aead/nonce/fn.xor_nonce.html
+//! [`MIN_SHARED_SECRET_SIZE`]: constant.MIN_SHARED_SECRET_SIZE.html
+//! [`TAG_SIZE`]: constant.TAG_SIZE.html
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::{
+            chacha20poly1305,
+            nonce::{self, SequenceNumber, StaticIv, STATIC_IV_SIZE},
+        },
+        hash::sha256::{Sha256, SHA256_OUTSIZE},
+        kdf::hkdf::Hkdf,
+        mac::{hmac::HmacGeneric, poly1305::POLY1305_OUTSIZE},
+        stream::chacha20::CHACHA_KEYSIZE,
+    },
+};
+use zeroize::Zeroize;
+
+/// The size of a [`Confirmation`] message.
+///
+/// [`Confirmation`]: struct.Confirmation.html
+pub const CONFIRMATION_SIZE: usize = SHA256_OUTSIZE;
+
+/// The minimum size of the shared secret of the PAKE.
+pub const MIN_SHARED_SECRET_SIZE: usize = 16;
+
+/// The size of the tag that [`SealingSession::seal()`] appends to a message.
+///
+/// [`SealingSession::seal()`]: struct.SealingSession.html#method.seal
+pub const TAG_SIZE: usize = POLY1305_OUTSIZE;
+
+/// The HKDF info of the confirmation key of each role.
+const CONFIRMATION_INFO: [&[u8]; 2] = [
+    b"orion pake session initiator confirmation",
+    b"orion pake session responder confirmation",
+];
+
+/// The HKDF info of the AEAD key of each role.
+const KEY_INFO: [&[u8]; 2] = [
+    b"orion pake session initiator key",
+    b"orion pake session responder key",
+];
+
+/// The HKDF info of the static IV of each role.
+const IV_INFO: [&[u8]; 2] = [
+    b"orion pake session initiator iv",
+    b"orion pake session responder iv",
+];
+
+construct_tag! {
+    /// A type to represent the key confirmation message of a party.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (Confirmation, test_confirmation, CONFIRMATION_SIZE, CONFIRMATION_SIZE)
+}
+
+impl_from_trait!(Confirmation, CONFIRMATION_SIZE);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The role of a party in the PAKE.
+pub enum Role {
+    /// The party that sent the first message of the PAKE.
+    Initiator,
+    /// The party that responded to the first message of the PAKE.
+    Responder,
+}
+
+impl Role {
+    /// The index of this role in the HKDF info tables.
+    fn index(self) -> usize {
+        match self {
+            Role::Initiator => 0,
+            Role::Responder => 1,
+        }
+    }
+
+    /// The role of the peer.
+    fn peer(self) -> Self {
+        match self {
+            Role::Initiator => Role::Responder,
+            Role::Responder => Role::Initiator,
+        }
+    }
+}
+
+/// The state of a party after the PAKE has completed, before the confirmation
+/// message of the peer has been verified.
+pub struct Handshake {
+    role: Role,
+    transcript_hash: [u8; SHA256_OUTSIZE],
+    hkdf: Hkdf<Sha256>,
+}
+
+impl Drop for Handshake {
+    fn drop(&mut self) {
+        self.transcript_hash.zeroize();
+    }
+}
+
+impl core::fmt::Debug for Handshake {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Handshake {{ role: {:?}, transcript_hash: [***OMITTED***], hkdf: [***OMITTED***] }}",
+            self.role
+        )
+    }
+}
+
+impl Handshake {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive the key schedule of `role` from the `shared_secret` and
+    /// `transcript` of a PAKE.
+    pub fn new(
+        shared_secret: &[u8],
+        transcript: &[u8],
+        role: Role,
+    ) -> Result<Self, UnknownCryptoError> {
+        if shared_secret.len() < MIN_SHARED_SECRET_SIZE || transcript.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut transcript_hash = [0u8; SHA256_OUTSIZE];
+        Sha256::digest_into(transcript, &mut transcript_hash)?;
+        let hkdf = Hkdf::<Sha256>::extract(&transcript_hash, shared_secret);
+
+        match hkdf {
+            Ok(hkdf) => Ok(Self {
+                role,
+                transcript_hash,
+                hkdf,
+            }),
+            Err(err) => {
+                transcript_hash.zeroize();
+                Err(err)
+            }
+        }
+    }
+
+    /// The confirmation message of `role`.
+    fn confirmation_of(&self, role: Role) -> Result<Confirmation, UnknownCryptoError> {
+        let mut confirmation_key = [0u8; SHA256_OUTSIZE];
+        let mut confirmation = [0u8; CONFIRMATION_SIZE];
+        let result = self
+            .hkdf
+            .expand(Some(CONFIRMATION_INFO[role.index()]), &mut confirmation_key)
+            .and_then(|_| HmacGeneric::<Sha256>::new(&confirmation_key))
+            .and_then(|mut hmac| {
+                hmac.update(&self.transcript_hash)?;
+                hmac.finalize_into(&mut confirmation)
+            })
+            .map(|_| Confirmation::from(confirmation));
+        confirmation_key.zeroize();
+        confirmation.zeroize();
+
+        result
+    }
+
+    /// The AEAD key and static IV of `role`.
+    fn session_keys(
+        &self,
+        role: Role,
+    ) -> Result<(chacha20poly1305::SecretKey, StaticIv), UnknownCryptoError> {
+        let mut key = [0u8; CHACHA_KEYSIZE];
+        let mut iv = [0u8; STATIC_IV_SIZE];
+        let result = self
+            .hkdf
+            .expand(Some(KEY_INFO[role.index()]), &mut key)
+            .and_then(|_| self.hkdf.expand(Some(IV_INFO[role.index()]), &mut iv))
+            .map(|_| (chacha20poly1305::SecretKey::from(key), StaticIv::from(iv)));
+        key.zeroize();
+        iv.zeroize();
+
+        result
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The confirmation message of this party, to send to the peer.
+    pub fn confirmation(&self) -> Result<Confirmation, UnknownCryptoError> {
+        self.confirmation_of(self.role)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify the confirmation message of the peer in constant time, and return
+    /// the sessions that seal messages to the peer and open messages from it.
+    pub fn finish(
+        self,
+        peer_confirmation: &Confirmation,
+    ) -> Result<(SealingSession, OpeningSession), UnknownCryptoError> {
+        if &self.confirmation_of(self.role.peer())? != peer_confirmation {
+            return Err(UnknownCryptoError);
+        }
+
+        let (sealing_key, sealing_iv) = self.session_keys(self.role)?;
+        let (opening_key, opening_iv) = self.session_keys(self.role.peer())?;
+
+        Ok((
+            SealingSession {
+                inner: Session::new(sealing_key, sealing_iv),
+            },
+            OpeningSession {
+                inner: Session::new(opening_key, opening_iv),
+            },
+        ))
+    }
+}
+
+/// The state shared by [`SealingSession`] and [`OpeningSession`].
+///
+/// [`SealingSession`]: struct.SealingSession.html
+/// [`OpeningSession`]: struct.OpeningSession.html
+struct Session {
+    key: chacha20poly1305::SecretKey,
+    static_iv: StaticIv,
+    sequence_number: SequenceNumber,
+}
+
+impl core::fmt::Debug for Session {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "key: [***OMITTED***], static_iv: [***OMITTED***], sequence_number: {:?}",
+            self.sequence_number.value()
+        )
+    }
+}
+
+impl Session {
+    fn new(key: chacha20poly1305::SecretKey, static_iv: StaticIv) -> Self {
+        Self {
+            key,
+            static_iv,
+            sequence_number: SequenceNumber::new(0),
+        }
+    }
+
+    /// The nonce of the current message, and the sequence number of the next.
+    /// The last sequence number is never used, so that there is always a next
+    /// one to move to after a message.
+    fn nonce(&self) -> Result<(chacha20poly1305::Nonce, SequenceNumber), UnknownCryptoError> {
+        let next = self.sequence_number.next()?;
+
+        Ok((
+            nonce::xor_nonce(&self.static_iv, self.sequence_number),
+            next,
+        ))
+    }
+}
+
+/// The session of a party that seals messages to the peer.
+pub struct SealingSession {
+    inner: Session,
+}
+
+impl core::fmt::Debug for SealingSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SealingSession {{ {:?} }}", self.inner)
+    }
+}
+
+impl SealingSession {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Seal the next message, writing the ciphertext and tag to `dst_out`.
+    pub fn seal(
+        &mut self,
+        aad: &[u8],
+        plaintext: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (nonce, next) = self.inner.nonce()?;
+        chacha20poly1305::seal(&self.inner.key, &nonce, plaintext, Some(aad), dst_out)?;
+        self.inner.sequence_number = next;
+
+        Ok(())
+    }
+}
+
+/// The session of a party that opens messages from the peer.
+pub struct OpeningSession {
+    inner: Session,
+}
+
+impl core::fmt::Debug for OpeningSession {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "OpeningSession {{ {:?} }}", self.inner)
+    }
+}
+
+impl OpeningSession {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Open the next message, writing the plaintext to `dst_out`.
+    pub fn open(
+        &mut self,
+        aad: &[u8],
+        ciphertext_with_tag: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let (nonce, next) = self.inner.nonce()?;
+        chacha20poly1305::open(
+            &self.inner.key,
+            &nonce,
+            ciphertext_with_tag,
+            Some(aad),
+            dst_out,
+        )?;
+        self.inner.sequence_number = next;
+
+        Ok(())
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn decode<'a>(value: &str, dst: &'a mut [u8]) -> &'a [u8] {
+        let len = value.len() / 2;
+        for (idx, byte) in dst[..len].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * idx..2 * idx + 2], 16).unwrap();
+        }
+        &dst[..len]
+    }
+
+    const SHARED_SECRET: [u8; 32] = [
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29, 30, 31,
+    ];
+
+    const TRANSCRIPT: &[u8] = b"orion pake session test transcript";
+
+    fn sessions(role: Role) -> (SealingSession, OpeningSession) {
+        let own = Handshake::new(&SHARED_SECRET, TRANSCRIPT, role).unwrap();
+        let peer = Handshake::new(&SHARED_SECRET, TRANSCRIPT, role.peer()).unwrap();
+
+        own.finish(&peer.confirmation().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_known_answer() {
+        // Computed with HKDF-SHA256, HMAC-SHA256 and ChaCha20Poly1305 of the
+        // Python cryptography package, following the key schedule in the
+        // module documentation.
+        let mut buf = [0u8; 64];
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        let responder = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Responder).unwrap();
+        assert_eq!(
+            initiator.confirmation().unwrap(),
+            decode(
+                "0cb1cc4db069965c82abafdb8f436a2ded81b80fa0f5e97e3848cc87da72fc2b",
+                &mut buf
+            )
+        );
+        assert_eq!(
+            responder.confirmation().unwrap(),
+            decode(
+                "125d1070ef26e6bc44e0ba177a8842bc76b5299ee49ffb693da1db91f22f6646",
+                &mut buf
+            )
+        );
+
+        let (mut sealer, _) = initiator
+            .finish(&responder.confirmation().unwrap())
+            .unwrap();
+        let mut ciphertext = [0u8; 14 + TAG_SIZE];
+        sealer
+            .seal(b"aad", b"first message", &mut ciphertext[..13 + TAG_SIZE])
+            .unwrap();
+        assert_eq!(
+            &ciphertext[..13 + TAG_SIZE],
+            decode(
+                "b443a1055ba9908a208046fddb214f0f90768330428a753a22b501c7b2",
+                &mut buf
+            )
+        );
+        sealer
+            .seal(b"aad", b"second message", &mut ciphertext)
+            .unwrap();
+        assert_eq!(
+            &ciphertext[..],
+            decode(
+                "047c5c26473dd0f4f97069546a5b2e93bc671a26ef466d045fac4596cea7",
+                &mut buf
+            )
+        );
+    }
+
+    #[test]
+    fn test_both_directions() {
+        let (mut initiator_sealer, mut initiator_opener) = sessions(Role::Initiator);
+        let (mut responder_sealer, mut responder_opener) = sessions(Role::Responder);
+
+        let mut ciphertext = [0u8; 5 + TAG_SIZE];
+        let mut plaintext = [0u8; 5];
+        initiator_sealer
+            .seal(b"", b"hello", &mut ciphertext)
+            .unwrap();
+        // Messages of one direction cannot be opened in the other.
+        assert!(initiator_opener
+            .open(b"", &ciphertext, &mut plaintext)
+            .is_err());
+        responder_opener
+            .open(b"", &ciphertext, &mut plaintext)
+            .unwrap();
+        assert_eq!(&plaintext, b"hello");
+
+        responder_sealer
+            .seal(b"", b"world", &mut ciphertext)
+            .unwrap();
+        assert!(responder_opener
+            .open(b"", &ciphertext, &mut plaintext)
+            .is_err());
+        initiator_opener
+            .open(b"", &ciphertext, &mut plaintext)
+            .unwrap();
+        assert_eq!(&plaintext, b"world");
+    }
+
+    #[test]
+    fn test_confirmation_mismatch_err() {
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        let responder = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Responder).unwrap();
+        let expected = responder.confirmation().unwrap();
+
+        // A reflected confirmation message.
+        let reflected = initiator.confirmation().unwrap();
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        assert!(initiator.finish(&reflected).is_err());
+
+        // A different transcript or shared secret.
+        let other_transcript =
+            Handshake::new(&SHARED_SECRET, b"other transcript", Role::Responder).unwrap();
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        assert!(initiator
+            .finish(&other_transcript.confirmation().unwrap())
+            .is_err());
+        let other_secret = Handshake::new(&[1u8; 32], TRANSCRIPT, Role::Responder).unwrap();
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        assert!(initiator
+            .finish(&other_secret.confirmation().unwrap())
+            .is_err());
+
+        let initiator = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        assert!(initiator.finish(&expected).is_ok());
+    }
+
+    #[test]
+    fn test_shared_secret_and_transcript_length() {
+        assert!(Handshake::new(
+            &[0u8; MIN_SHARED_SECRET_SIZE - 1],
+            TRANSCRIPT,
+            Role::Initiator
+        )
+        .is_err());
+        assert!(
+            Handshake::new(&[0u8; MIN_SHARED_SECRET_SIZE], TRANSCRIPT, Role::Initiator).is_ok()
+        );
+        assert!(Handshake::new(&[0u8; 64], TRANSCRIPT, Role::Initiator).is_ok());
+        assert!(Handshake::new(&SHARED_SECRET, b"", Role::Initiator).is_err());
+    }
+
+    #[test]
+    fn test_open_err_does_not_advance() {
+        let (mut sealer, _) = sessions(Role::Initiator);
+        let (_, mut opener) = sessions(Role::Responder);
+
+        let mut first = [0u8; 5 + TAG_SIZE];
+        let mut second = [0u8; 5 + TAG_SIZE];
+        let mut plaintext = [0u8; 5];
+        sealer.seal(b"", b"hello", &mut first).unwrap();
+        sealer.seal(b"", b"hello", &mut second).unwrap();
+        assert_ne!(first, second);
+
+        // Out of order, with the wrong aad and modified.
+        assert!(opener.open(b"", &second, &mut plaintext).is_err());
+        assert!(opener.open(b"aad", &first, &mut plaintext).is_err());
+        first[0] ^= 1;
+        assert!(opener.open(b"", &first, &mut plaintext).is_err());
+        first[0] ^= 1;
+
+        opener.open(b"", &first, &mut plaintext).unwrap();
+        opener.open(b"", &second, &mut plaintext).unwrap();
+        assert_eq!(&plaintext, b"hello");
+    }
+
+    #[test]
+    fn test_dst_out_too_small_err() {
+        let (mut sealer, _) = sessions(Role::Initiator);
+        let (_, mut opener) = sessions(Role::Responder);
+
+        let mut ciphertext = [0u8; 5 + TAG_SIZE];
+        assert!(sealer
+            .seal(b"", b"hello", &mut ciphertext[..5 + TAG_SIZE - 1])
+            .is_err());
+        sealer.seal(b"", b"hello", &mut ciphertext).unwrap();
+        assert!(opener.open(b"", &ciphertext, &mut [0u8; 4]).is_err());
+        assert!(opener
+            .open(b"", &ciphertext[..TAG_SIZE - 1], &mut [])
+            .is_err());
+        assert!(opener.open(b"", &ciphertext, &mut [0u8; 5]).is_ok());
+    }
+
+    #[test]
+    fn test_sequence_exhausted_err() {
+        let (mut sealer, _) = sessions(Role::Initiator);
+        let mut ciphertext = [0u8; TAG_SIZE];
+
+        sealer.inner.sequence_number = SequenceNumber::new(u64::MAX - 1);
+        assert!(sealer.seal(b"", b"", &mut ciphertext).is_ok());
+        assert!(sealer.seal(b"", b"", &mut ciphertext).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let handshake = Handshake::new(&SHARED_SECRET, TRANSCRIPT, Role::Initiator).unwrap();
+        let debug = format!("{:?}", handshake);
+        let expected = "Handshake { role: Initiator, transcript_hash: [***OMITTED***], \
+                        hkdf: [***OMITTED***] }";
+        assert_eq!(debug, expected);
+
+        let (sealer, opener) = sessions(Role::Initiator);
+        let debug = format!("{:?}", sealer);
+        let expected = "SealingSession { key: [***OMITTED***], static_iv: [***OMITTED***], \
+                        sequence_number: 0 }";
+        assert_eq!(debug, expected);
+        let debug = format!("{:?}", opener);
+        let expected = "OpeningSession { key: [***OMITTED***], static_iv: [***OMITTED***], \
+                        sequence_number: 0 }";
+        assert_eq!(debug, expected);
+    }
+}
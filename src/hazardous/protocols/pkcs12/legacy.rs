@@ -0,0 +1,525 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The legacy password-based encryption schemes of PKCS #12, as specified in
+//! RFC 7292, appendix C, with Triple DES and RC2 in CBC mode. These are only
+//! used for decryption.
+
+use super::{derive_key, read_iterations, Plaintext};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{hash::sha1::Sha1, protocols::pkcs8::unpad},
+    util::der::{DerReader, TAG_OCTET_STRING},
+};
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+const OID_PBE_SHA1_3DES: [u8; 10] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x01, 0x03];
+const OID_PBE_SHA1_2DES: [u8; 10] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x01, 0x04];
+const OID_PBE_SHA1_RC2_128: [u8; 10] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x01, 0x05];
+const OID_PBE_SHA1_RC2_40: [u8; 10] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x01, 0x06];
+
+/// The ID of the key derived for encryption, as specified in RFC 7292, B.3.
+const ID_KEY: u8 = 1;
+/// The ID of the IV derived for encryption, as specified in RFC 7292, B.3.
+const ID_IV: u8 = 2;
+
+/// The block size of DES and RC2.
+const BLOCKSIZE: usize = 8;
+
+/// Look up `table[index]` without an index-dependent memory access.
+fn ct_lookup(table: &[u8], index: u8) -> u8 {
+    let mut value = 0u8;
+    for (i, entry) in table.iter().enumerate() {
+        value.conditional_assign(entry, (i as u8).ct_eq(&index));
+    }
+
+    value
+}
+
+/// Apply the bit permutation `table`, whose entries are the 1-based
+/// positions of bits counted from the most significant bit of the `bits`
+/// low bits of `input`.
+fn permute(input: u64, bits: u32, table: &[u8]) -> u64 {
+    table.iter().fold(0u64, |output, position| {
+        (output << 1) | ((input >> (bits - u32::from(*position))) & 1)
+    })
+}
+
+const DES_IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, 62, 54, 46, 38, 30, 22, 14, 6,
+    64, 56, 48, 40, 32, 24, 16, 8, 57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, 61,
+    53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const DES_FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, 38, 6, 46, 14, 54, 22, 62, 30,
+    37, 5, 45, 13, 53, 21, 61, 29, 36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27,
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const DES_E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, 8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, 16, 17, 18,
+    19, 20, 21, 20, 21, 22, 23, 24, 25, 24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const DES_P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, 2, 8, 24, 14, 32, 27, 3, 9, 19,
+    13, 30, 6, 22, 11, 4, 25,
+];
+
+const DES_PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, 10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60,
+    52, 44, 36, 63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, 14, 6, 61, 53, 45, 37, 29,
+    21, 13, 5, 28, 20, 12, 4,
+];
+
+const DES_PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, 23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, 41, 52,
+    31, 37, 47, 55, 30, 40, 51, 45, 33, 48, 44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const DES_SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+/// The S-boxes of DES, each as four rows of 16 entries.
+const DES_SBOXES: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, 0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12,
+        11, 9, 5, 3, 8, 4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, 15, 12, 8, 2, 4, 9,
+        1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, 3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1,
+        10, 6, 9, 11, 5, 0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, 13, 8, 10, 1, 3, 15,
+        4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, 13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5,
+        14, 12, 11, 15, 1, 13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, 1, 10, 13, 0, 6,
+        9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, 13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2,
+        12, 1, 10, 14, 9, 10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, 3, 15, 0, 6, 10, 1,
+        13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, 14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15,
+        10, 3, 9, 8, 6, 4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, 11, 8, 12, 7, 1, 14,
+        2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, 10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13,
+        14, 0, 11, 3, 8, 9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, 4, 3, 2, 12, 9, 5,
+        15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, 13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5,
+        12, 2, 15, 8, 6, 1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, 6, 11, 13, 8, 1, 4,
+        10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, 1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6,
+        11, 0, 14, 9, 2, 7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, 2, 1, 14, 7, 4, 10,
+        8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+/// A DES key schedule.
+struct Des {
+    subkeys: [u64; 16],
+}
+
+impl Drop for Des {
+    fn drop(&mut self) {
+        self.subkeys.zeroize();
+    }
+}
+
+impl Des {
+    /// Expand the 8-byte `key`. The parity bits are ignored.
+    fn new(key: &[u8]) -> Self {
+        let mut key_bytes = [0u8; 8];
+        key_bytes.copy_from_slice(key);
+        let mut state = permute(u64::from_be_bytes(key_bytes), 64, &DES_PC1);
+        key_bytes.zeroize();
+
+        let mut subkeys = [0u64; 16];
+        let (mut c, mut d) = (state >> 28, state & 0x0FFF_FFFF);
+        for (subkey, shift) in subkeys.iter_mut().zip(DES_SHIFTS.iter()) {
+            c = ((c << shift) | (c >> (28 - shift))) & 0x0FFF_FFFF;
+            d = ((d << shift) | (d >> (28 - shift))) & 0x0FFF_FFFF;
+            *subkey = permute((c << 28) | d, 56, &DES_PC2);
+        }
+        state.zeroize();
+        c.zeroize();
+        d.zeroize();
+
+        Self { subkeys }
+    }
+
+    /// The round function.
+    fn feistel(half: u64, subkey: u64) -> u64 {
+        let expanded = permute(half, 32, &DES_E) ^ subkey;
+        let mut output = 0u64;
+        for (i, sbox) in DES_SBOXES.iter().enumerate() {
+            let six = ((expanded >> (42 - 6 * i)) & 0x3F) as u8;
+            let row = ((six & 0x20) >> 4) | (six & 0x01);
+            let column = (six >> 1) & 0x0F;
+            output = (output << 4) | u64::from(ct_lookup(sbox, row * 16 + column));
+        }
+
+        permute(output, 32, &DES_P)
+    }
+
+    /// Encrypt or decrypt `block`, depending on the order of the subkeys.
+    fn process(&self, block: u64, decrypt: bool) -> u64 {
+        let state = permute(block, 64, &DES_IP);
+        let (mut left, mut right) = (state >> 32, state & 0xFFFF_FFFF);
+        for round in 0..16 {
+            let subkey = if decrypt {
+                self.subkeys[15 - round]
+            } else {
+                self.subkeys[round]
+            };
+            let next = left ^ Self::feistel(right, subkey);
+            left = right;
+            right = next;
+        }
+
+        permute((right << 32) | left, 64, &DES_FP)
+    }
+}
+
+/// Triple DES in the encrypt-decrypt-encrypt construction.
+struct TripleDes {
+    keys: [Des; 3],
+}
+
+impl TripleDes {
+    /// Expand the 24-byte `key`.
+    fn new(key: &[u8]) -> Self {
+        Self {
+            keys: [
+                Des::new(&key[..8]),
+                Des::new(&key[8..16]),
+                Des::new(&key[16..24]),
+            ],
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut value = [0u8; BLOCKSIZE];
+        value.copy_from_slice(block);
+        let mut state = u64::from_be_bytes(value);
+        state = self.keys[2].process(state, true);
+        state = self.keys[1].process(state, false);
+        state = self.keys[0].process(state, true);
+        block.copy_from_slice(&state.to_be_bytes());
+    }
+}
+
+/// The permutation of the digits of pi used by the key expansion of RC2.
+const RC2_PITABLE: [u8; 256] = [
+    0xD9, 0x78, 0xF9, 0xC4, 0x19, 0xDD, 0xB5, 0xED, 0x28, 0xE9, 0xFD, 0x79, 0x4A, 0xA0, 0xD8, 0x9D,
+    0xC6, 0x7E, 0x37, 0x83, 0x2B, 0x76, 0x53, 0x8E, 0x62, 0x4C, 0x64, 0x88, 0x44, 0x8B, 0xFB, 0xA2,
+    0x17, 0x9A, 0x59, 0xF5, 0x87, 0xB3, 0x4F, 0x13, 0x61, 0x45, 0x6D, 0x8D, 0x09, 0x81, 0x7D, 0x32,
+    0xBD, 0x8F, 0x40, 0xEB, 0x86, 0xB7, 0x7B, 0x0B, 0xF0, 0x95, 0x21, 0x22, 0x5C, 0x6B, 0x4E, 0x82,
+    0x54, 0xD6, 0x65, 0x93, 0xCE, 0x60, 0xB2, 0x1C, 0x73, 0x56, 0xC0, 0x14, 0xA7, 0x8C, 0xF1, 0xDC,
+    0x12, 0x75, 0xCA, 0x1F, 0x3B, 0xBE, 0xE4, 0xD1, 0x42, 0x3D, 0xD4, 0x30, 0xA3, 0x3C, 0xB6, 0x26,
+    0x6F, 0xBF, 0x0E, 0xDA, 0x46, 0x69, 0x07, 0x57, 0x27, 0xF2, 0x1D, 0x9B, 0xBC, 0x94, 0x43, 0x03,
+    0xF8, 0x11, 0xC7, 0xF6, 0x90, 0xEF, 0x3E, 0xE7, 0x06, 0xC3, 0xD5, 0x2F, 0xC8, 0x66, 0x1E, 0xD7,
+    0x08, 0xE8, 0xEA, 0xDE, 0x80, 0x52, 0xEE, 0xF7, 0x84, 0xAA, 0x72, 0xAC, 0x35, 0x4D, 0x6A, 0x2A,
+    0x96, 0x1A, 0xD2, 0x71, 0x5A, 0x15, 0x49, 0x74, 0x4B, 0x9F, 0xD0, 0x5E, 0x04, 0x18, 0xA4, 0xEC,
+    0xC2, 0xE0, 0x41, 0x6E, 0x0F, 0x51, 0xCB, 0xCC, 0x24, 0x91, 0xAF, 0x50, 0xA1, 0xF4, 0x70, 0x39,
+    0x99, 0x7C, 0x3A, 0x85, 0x23, 0xB8, 0xB4, 0x7A, 0xFC, 0x02, 0x36, 0x5B, 0x25, 0x55, 0x97, 0x31,
+    0x2D, 0x5D, 0xFA, 0x98, 0xE3, 0x8A, 0x92, 0xAE, 0x05, 0xDF, 0x29, 0x10, 0x67, 0x6C, 0xBA, 0xC9,
+    0xD3, 0x00, 0xE6, 0xCF, 0xE1, 0x9E, 0xA8, 0x2C, 0x63, 0x16, 0x01, 0x3F, 0x58, 0xE2, 0x89, 0xA9,
+    0x0D, 0x38, 0x34, 0x1B, 0xAB, 0x33, 0xFF, 0xB0, 0xBB, 0x48, 0x0C, 0x5F, 0xB9, 0xB1, 0xCD, 0x2E,
+    0xC5, 0xF3, 0xDB, 0x47, 0xE5, 0xA5, 0x9C, 0x77, 0x0A, 0xA6, 0x20, 0x68, 0xFE, 0x7F, 0xC1, 0xAD,
+];
+
+/// An RC2 key schedule, as specified in RFC 2268.
+struct Rc2 {
+    subkeys: [u16; 64],
+}
+
+impl Drop for Rc2 {
+    fn drop(&mut self) {
+        self.subkeys.zeroize();
+    }
+}
+
+impl Rc2 {
+    /// Expand `key` with an effective key size of `effective_bits` bits.
+    fn new(key: &[u8], effective_bits: usize) -> Self {
+        debug_assert!(!key.is_empty() && key.len() <= 128);
+        debug_assert!(effective_bits > 0 && effective_bits <= 1024);
+
+        let mut expanded = [0u8; 128];
+        expanded[..key.len()].copy_from_slice(key);
+        for i in key.len()..128 {
+            let index = expanded[i - 1].wrapping_add(expanded[i - key.len()]);
+            expanded[i] = ct_lookup(&RC2_PITABLE, index);
+        }
+        let effective_bytes = (effective_bits + 7) >> 3;
+        let mask = (0xFFu16 >> (8 * effective_bytes - effective_bits)) as u8;
+        let last = 128 - effective_bytes;
+        expanded[last] = ct_lookup(&RC2_PITABLE, expanded[last] & mask);
+        for i in (0..last).rev() {
+            expanded[i] = ct_lookup(
+                &RC2_PITABLE,
+                expanded[i + 1] ^ expanded[i + effective_bytes],
+            );
+        }
+
+        let mut subkeys = [0u16; 64];
+        for (subkey, bytes) in subkeys.iter_mut().zip(expanded.chunks_exact(2)) {
+            *subkey = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+        expanded.zeroize();
+
+        Self { subkeys }
+    }
+
+    /// Look up the subkey at the low six bits of `word`, without an
+    /// index-dependent memory access.
+    fn subkey_at(&self, word: u16) -> u16 {
+        let index = (word & 0x3F) as u8;
+        let mut subkey = 0u16;
+        for (i, entry) in self.subkeys.iter().enumerate() {
+            subkey.conditional_assign(entry, (i as u8).ct_eq(&index));
+        }
+
+        subkey
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut r = [0u16; 4];
+        for (word, bytes) in r.iter_mut().zip(block.chunks_exact(2)) {
+            *word = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        let mut j = 64;
+        for round in (0..16).rev() {
+            // The inverse of a mixing round.
+            for i in (0..4).rev() {
+                j -= 1;
+                let rotation = [1, 2, 3, 5][i];
+                r[i] = r[i].rotate_right(rotation);
+                r[i] = r[i]
+                    .wrapping_sub(self.subkeys[j])
+                    .wrapping_sub(r[(i + 3) % 4] & r[(i + 2) % 4])
+                    .wrapping_sub(!r[(i + 3) % 4] & r[(i + 1) % 4]);
+            }
+            // The inverse of the mashing rounds, after the fifth and
+            // eleventh mixing rounds.
+            if round == 5 || round == 11 {
+                for i in (0..4).rev() {
+                    r[i] = r[i].wrapping_sub(self.subkey_at(r[(i + 3) % 4]));
+                }
+            }
+        }
+
+        for (word, bytes) in r.iter().zip(block.chunks_exact_mut(2)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        r.zeroize();
+    }
+}
+
+/// Decrypt `ciphertext` in CBC mode with `decrypt_block` and `iv`, and
+/// remove the padding.
+fn cbc_decrypt<F: Fn(&mut [u8])>(
+    decrypt_block: F,
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    if ciphertext.is_empty() || !ciphertext.chunks_exact(BLOCKSIZE).remainder().is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut plaintext = Plaintext(ciphertext.to_vec());
+    let previous = core::iter::once(iv).chain(ciphertext.chunks(BLOCKSIZE));
+    for (block, previous) in plaintext.0.chunks_mut(BLOCKSIZE).zip(previous) {
+        decrypt_block(block);
+        for (byte, mask) in block.iter_mut().zip(previous.iter()) {
+            *byte ^= mask;
+        }
+    }
+
+    unpad(plaintext, BLOCKSIZE)
+}
+
+/// Decrypt `ciphertext` with the legacy scheme `oid` and its parameters
+/// `params`.
+pub(super) fn decrypt(
+    oid: &[u8],
+    params: &[u8],
+    password: &str,
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    let key_size = if oid == OID_PBE_SHA1_3DES {
+        24
+    } else if oid == OID_PBE_SHA1_2DES || oid == OID_PBE_SHA1_RC2_128 {
+        16
+    } else if oid == OID_PBE_SHA1_RC2_40 {
+        5
+    } else {
+        return Err(UnknownCryptoError);
+    };
+
+    let mut params = DerReader::new(params);
+    let salt = params.read(TAG_OCTET_STRING)?;
+    let iterations = read_iterations(&mut params)?;
+    params.finish()?;
+
+    let password = super::bmp_password(password);
+    let mut key = [0u8; 24];
+    let mut iv = [0u8; BLOCKSIZE];
+    derive_key::<Sha1>(ID_KEY, &password.0, salt, iterations, &mut key[..key_size])?;
+    derive_key::<Sha1>(ID_IV, &password.0, salt, iterations, &mut iv)?;
+
+    let plaintext = if oid == OID_PBE_SHA1_3DES || oid == OID_PBE_SHA1_2DES {
+        // Two-key Triple DES uses the first key as the third.
+        if key_size == 16 {
+            let (first, rest) = key.split_at_mut(16);
+            rest[..8].copy_from_slice(&first[..8]);
+        }
+        let cipher = TripleDes::new(&key);
+        cbc_decrypt(|block| cipher.decrypt_block(block), &iv, ciphertext)
+    } else {
+        let cipher = Rc2::new(&key[..key_size], 8 * key_size);
+        cbc_decrypt(|block| cipher.decrypt_block(block), &iv, ciphertext)
+    };
+    key.zeroize();
+    iv.zeroize();
+
+    plaintext
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_des_known_answers() {
+        // Key, plaintext and ciphertext.
+        let vectors: [(u64, u64, u64); 3] = [
+            (
+                0x1334_5779_9BBC_DFF1,
+                0x0123_4567_89AB_CDEF,
+                0x85E8_1354_0F0A_B405,
+            ),
+            (
+                0x0123_4567_89AB_CDEF,
+                0x4E6F_7720_6973_2074,
+                0x3FA4_0E8A_984D_4815,
+            ),
+            (
+                0x0E32_9232_EA6D_0D73,
+                0x8787_8787_8787_8787,
+                0x0000_0000_0000_0000,
+            ),
+        ];
+        for (key, plaintext, ciphertext) in vectors.iter() {
+            let des = Des::new(&key.to_be_bytes());
+            assert_eq!(des.process(*plaintext, false), *ciphertext);
+            assert_eq!(des.process(*ciphertext, true), *plaintext);
+        }
+    }
+
+    #[test]
+    fn test_triple_des_with_equal_keys_is_des() {
+        let key = 0x0123_4567_89AB_CDEFu64.to_be_bytes();
+        let mut triple_key = [0u8; 24];
+        for chunk in triple_key.chunks_mut(8) {
+            chunk.copy_from_slice(&key);
+        }
+        let mut block = 0x3FA4_0E8A_984D_4815u64.to_be_bytes();
+        TripleDes::new(&triple_key).decrypt_block(&mut block);
+        assert_eq!(u64::from_be_bytes(block), 0x4E6F_7720_6973_2074);
+    }
+
+    /// A key, effective key bits, plaintext and ciphertext.
+    type Rc2Vector = (&'static [u8], usize, [u8; 8], [u8; 8]);
+
+    #[test]
+    fn test_rc2_rfc2268() {
+        // From RFC 2268, section 5.
+        let vectors: [Rc2Vector; 5] = [
+            (
+                &[0x00; 8],
+                63,
+                [0x00; 8],
+                [0xEB, 0xB7, 0x73, 0xF9, 0x93, 0x27, 0x8E, 0xFF],
+            ),
+            (
+                &[0xFF; 8],
+                64,
+                [0xFF; 8],
+                [0x27, 0x8B, 0x27, 0xE4, 0x2E, 0x2F, 0x0D, 0x49],
+            ),
+            (
+                &[0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+                64,
+                [0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01],
+                [0x30, 0x64, 0x9E, 0xDF, 0x9B, 0xE7, 0xD2, 0xC2],
+            ),
+            (
+                &[0x88],
+                64,
+                [0x00; 8],
+                [0x61, 0xA8, 0xA2, 0x44, 0xAD, 0xAC, 0xCC, 0xF0],
+            ),
+            (
+                &[
+                    0x88, 0xBC, 0xA9, 0x0E, 0x90, 0x87, 0x5A, 0x7F, 0x0F, 0x79, 0xC3, 0x84, 0x62,
+                    0x7B, 0xAF, 0xB2,
+                ],
+                128,
+                [0x00; 8],
+                [0x22, 0x69, 0x55, 0x2A, 0xB0, 0xF8, 0x5C, 0xA6],
+            ),
+        ];
+        for (key, effective_bits, plaintext, ciphertext) in vectors.iter() {
+            let mut block = *ciphertext;
+            Rc2::new(key, *effective_bits).decrypt_block(&mut block);
+            assert_eq!(block, *plaintext);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_err() {
+        let params = [0x04, 0x01, 0x00, 0x02, 0x01, 0x01];
+        let ciphertext = [0u8; 16];
+        // Unknown scheme.
+        assert!(decrypt(&[0x2A], &params, "", &ciphertext).is_err());
+        // Ciphertext that is not a multiple of the block size, or empty.
+        assert!(decrypt(&OID_PBE_SHA1_3DES, &params, "", &ciphertext[..15]).is_err());
+        assert!(decrypt(&OID_PBE_SHA1_3DES, &params, "", &[]).is_err());
+        // Zero iterations.
+        let params = [0x04, 0x01, 0x00, 0x02, 0x01, 0x00];
+        assert!(decrypt(&OID_PBE_SHA1_3DES, &params, "", &ciphertext).is_err());
+    }
+}
@@ -0,0 +1,498 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Importing private keys and certificates from PKCS #12 archives, as
+//! specified in [RFC 7292].
+//!
+//! # About:
+//! PKCS #12 archives, also known as PFX or `.p12` files, are commonly used
+//! to distribute a client identity, which is a private key with its
+//! certificate chain. [`parse()`] verifies the integrity of an archive with
+//! its password and returns the private keys and certificates it contains.
+//!
+//! Archives must be protected in password integrity mode, with HMAC using
+//! SHA-1, SHA-256, SHA-384 or SHA-512 as the MAC. Encrypted contents and
+//! private keys are decrypted with the same password, and must use PBES2
+//! ([RFC 8018]) with PBKDF2 and AES-CBC, which is the default of OpenSSL 3.
+//!
+//! Archives made by older software typically use the legacy password-based
+//! encryption schemes of PKCS #12, with RC2 or Triple DES. These are only
+//! supported with the `legacy` feature of orion, and only for decryption.
+//!
+//! Private keys are PKCS #8 PrivateKeyInfo structures, of which Ed25519,
+//! ECDSA P-256 and RSA keys are supported, as with [`pkcs8`]. Certificates are returned in
+//! their DER encoding, in the order they appear in the archive. Other bags,
+//! such as CRLs and secrets, and bag attributes, such as friendly names,
+//! are ignored.
+//!
+//! # Parameters:
+//! - `pfx`: The DER-encoded PKCS #12 archive.
+//! - `password`: The password of the archive.
+//! - `der`: A DER-encoded PKCS #8 PrivateKeyInfo.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `pfx` is not a supported DER encoding of a PKCS #12 archive.
+//! - `pfx` has no MAC, or the MAC does not match `password`.
+//! - The contents of `pfx` are encrypted with an unsupported scheme, or
+//!   fail to decrypt.
+//! - A private key in `pfx`, or `der`, is not a supported PKCS #8 encoding
+//!   of an Ed25519, P-256 or RSA private key.
+//! - `pfx` is protected with public keys instead of a password.
+//!
+//! # Security:
+//! - The iteration counts of the MAC and the encryption are chosen by whoever
+//!   made the archive. Archives from untrusted sources can make [`parse()`]
+//!   arbitrarily slow.
+//! - The returned certificates are not verified in any way. Use
+//!   [`x509::verify_certificate()`] or other means to decide whether to
+//!   trust them.
+//! - A password does not protect the contents of an archive that uses the
+//!   legacy schemes, as RC2 with 40-bit keys can be broken by brute force.
+//!   The legacy ciphers are also not hardened against side channels. Only
+//!   enable the `legacy` feature to import archives that cannot be
+//!   re-exported with PBES2.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::pkcs12::{self, PrivateKey};
+//!
+//! fn import_identity(pfx: &[u8], password: &str) -> Result<(), orion::errors::UnknownCryptoError> {
+//!     let contents = pkcs12::parse(pfx, password)?;
+//!
+//!     for private_key in contents.private_keys.iter() {
+//!         match private_key {
+//!             PrivateKey::Ed25519(_) => println!("Ed25519 key"),
+//!             PrivateKey::EcdsaP256(_) => println!("P-256 key"),
+//!             PrivateKey::Rsa(secret_key) => println!("RSA-{} key", secret_key.size() * 8),
+//!         }
+//!     }
+//!     println!("{} certificates", contents.certificates.len());
+//!
+//!     Ok(())
+//! }
+//!
+//! // A wrong password, or an archive that is not valid, is an error.
+//! assert!(import_identity(&[0x30, 0x00], "password").is_err());
+//! ```
+//! [RFC 7292]: https://www.rfc-editor.org/rfc/rfc7292
+//! [RFC 8018]: https://www.rfc-editor.org/rfc/rfc8018
+//! [`parse()`]: fn.parse.html
+//! [`pkcs8`]: ../pkcs8/index.html
+//! [`x509::verify_certificate()`]: ../x509/fn.verify_certificate.html
+
+#[cfg(feature = "legacy")]
+mod legacy;
+#[cfg(feature = "legacy")]
+use legacy::decrypt as decrypt_legacy;
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+pub use super::pkcs8::PrivateKey;
+
+use super::pkcs8::{pbes2_decrypt, read_hash_algorithm, read_iterations, Plaintext};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        hash::{
+            sha1::Sha1, sha256::Sha256, sha384::Sha384, sha512::Sha512, ShaHash, MAX_SHA_BLOCKSIZE,
+        },
+        mac::hmac::HmacGeneric,
+    },
+    util::{
+        self,
+        der::{DerReader, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE},
+    },
+};
+use zeroize::Zeroize;
+
+const TAG_SET: u8 = 0x31;
+/// `[0]`, constructed.
+const TAG_CONTEXT_0: u8 = 0xA0;
+/// `[0] IMPLICIT`, primitive, the encryptedContent of an EncryptedData.
+const TAG_ENCRYPTED_CONTENT: u8 = 0x80;
+
+/// The size of the largest supported hash function output.
+const MAX_DIGEST_SIZE: usize = 64;
+
+const OID_DATA: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x01];
+const OID_ENCRYPTED_DATA: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x06];
+const OID_KEY_BAG: [u8; 11] = [
+    0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x0A, 0x01, 0x01,
+];
+const OID_SHROUDED_KEY_BAG: [u8; 11] = [
+    0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x0A, 0x01, 0x02,
+];
+const OID_CERT_BAG: [u8; 11] = [
+    0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x0C, 0x0A, 0x01, 0x03,
+];
+const OID_X509_CERTIFICATE: [u8; 10] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x16, 0x01];
+
+const OID_SHA1: [u8; 5] = [0x2B, 0x0E, 0x03, 0x02, 0x1A];
+const OID_SHA256: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const OID_SHA384: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+const OID_SHA512: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+
+const OID_PBES2: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x05, 0x0D];
+
+/// The ID of the key derived for a MAC, as specified in RFC 7292, B.3.
+const ID_MAC: u8 = 3;
+
+#[derive(Debug)]
+/// The private keys and certificates of a PKCS #12 archive.
+pub struct Contents {
+    /// The private keys, in the order they appear in the archive.
+    pub private_keys: Vec<PrivateKey>,
+    /// The DER-encoded X.509 certificates, in the order they appear in the
+    /// archive.
+    pub certificates: Vec<Vec<u8>>,
+}
+
+/// Encode `password` as a BMPString with a terminating NULL character, as
+/// used by the MAC and the legacy encryption schemes.
+fn bmp_password(password: &str) -> Plaintext {
+    let mut encoded = Vec::with_capacity(2 * password.len() + 2);
+    for unit in password.encode_utf16() {
+        encoded.extend_from_slice(&unit.to_be_bytes());
+    }
+    encoded.extend_from_slice(&[0, 0]);
+
+    Plaintext(encoded)
+}
+
+/// The key derivation function of RFC 7292, B.2, with the hash function `H`,
+/// `id` and the BMPString `password`, writing `dst.len()` bytes to `dst`.
+fn derive_key<H: ShaHash>(
+    id: u8,
+    password: &[u8],
+    salt: &[u8],
+    iterations: usize,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    debug_assert!(H::OUTSIZE <= MAX_DIGEST_SIZE);
+    debug_assert!(H::BLOCKSIZE <= MAX_SHA_BLOCKSIZE);
+    if iterations == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let v = H::BLOCKSIZE;
+    // The salt and password, each repeated to a multiple of the block size.
+    let mut input = Vec::new();
+    for value in [salt, password].iter() {
+        let len = v * value.chunks(v).count();
+        input.extend(value.iter().cycle().take(len));
+    }
+    let mut input = Plaintext(input);
+    let diversifier = [id; MAX_SHA_BLOCKSIZE];
+
+    let mut digest = [0u8; MAX_DIGEST_SIZE];
+    let digest = &mut digest[..H::OUTSIZE];
+    for chunk in dst.chunks_mut(H::OUTSIZE) {
+        let mut hasher = H::default();
+        hasher.update(&diversifier[..v])?;
+        hasher.update(&input.0)?;
+        hasher.finalize_into(digest)?;
+        for _ in 1..iterations {
+            hasher.reset();
+            hasher.update(digest)?;
+            hasher.finalize_into(digest)?;
+        }
+        chunk.copy_from_slice(&digest[..chunk.len()]);
+
+        // Add the digest, repeated to the block size, plus one to each
+        // block of the input.
+        for block in input.0.chunks_mut(v) {
+            let mut carry = 1u16;
+            for (i, byte) in block.iter_mut().enumerate().rev() {
+                let sum = u16::from(*byte) + u16::from(digest[i % H::OUTSIZE]) + carry;
+                *byte = sum as u8;
+                carry = sum >> 8;
+            }
+        }
+    }
+    digest.zeroize();
+
+    Ok(())
+}
+
+/// Verify the HMAC `expected` of `data`, with the hash function `H`.
+fn verify_mac<H: ShaHash>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: usize,
+    data: &[u8],
+    expected: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut key = [0u8; MAX_DIGEST_SIZE];
+    derive_key::<H>(ID_MAC, password, salt, iterations, &mut key[..H::OUTSIZE])?;
+    let mut mac = HmacGeneric::<H>::new(&key[..H::OUTSIZE])?;
+    key.zeroize();
+    mac.update(data)?;
+    let mut tag = [0u8; MAX_DIGEST_SIZE];
+    mac.finalize_into(&mut tag[..H::OUTSIZE])?;
+
+    util::secure_cmp(&tag[..H::OUTSIZE], expected)
+}
+
+/// Verify the MacData `mac_data` of the authenticated safe `data`.
+fn verify_mac_data(mac_data: &[u8], data: &[u8], password: &str) -> Result<(), UnknownCryptoError> {
+    let mut mac_data = DerReader::new(mac_data);
+    let mut digest_info = DerReader::new(mac_data.read(TAG_SEQUENCE)?);
+    let algorithm = read_hash_algorithm(&mut digest_info)?;
+    let expected = digest_info.read(TAG_OCTET_STRING)?;
+    digest_info.finish()?;
+    let salt = mac_data.read(TAG_OCTET_STRING)?;
+    let iterations = if mac_data.is_empty() {
+        1
+    } else {
+        read_iterations(&mut mac_data)?
+    };
+    mac_data.finish()?;
+
+    let password = bmp_password(password);
+    let password = &password.0;
+    if algorithm == OID_SHA1 {
+        verify_mac::<Sha1>(password, salt, iterations, data, expected)
+    } else if algorithm == OID_SHA256 {
+        verify_mac::<Sha256>(password, salt, iterations, data, expected)
+    } else if algorithm == OID_SHA384 {
+        verify_mac::<Sha384>(password, salt, iterations, data, expected)
+    } else if algorithm == OID_SHA512 {
+        verify_mac::<Sha512>(password, salt, iterations, data, expected)
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+/// Decrypt `ciphertext` with the password-based encryption scheme whose
+/// complete AlgorithmIdentifier is `algorithm`.
+fn decrypt(
+    algorithm: &[u8],
+    password: &str,
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    let mut outer = DerReader::new(algorithm);
+    let mut algorithm = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    let oid = algorithm.read(TAG_OID)?;
+    let params = algorithm.read(TAG_SEQUENCE)?;
+    algorithm.finish()?;
+
+    if oid == OID_PBES2 {
+        pbes2_decrypt(params, password, ciphertext)
+    } else {
+        decrypt_legacy(oid, params, password, ciphertext)
+    }
+}
+
+#[cfg(not(feature = "legacy"))]
+/// The legacy encryption schemes are only supported with the `legacy` feature.
+fn decrypt_legacy(
+    _oid: &[u8],
+    _params: &[u8],
+    _password: &str,
+    _ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    Err(UnknownCryptoError)
+}
+
+/// Parse the SafeContents `safe_contents`, adding its keys and certificates
+/// to `contents`.
+fn parse_safe_contents(
+    safe_contents: &[u8],
+    password: &str,
+    contents: &mut Contents,
+) -> Result<(), UnknownCryptoError> {
+    let mut outer = DerReader::new(safe_contents);
+    let mut bags = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+
+    while !bags.is_empty() {
+        let mut bag = DerReader::new(bags.read(TAG_SEQUENCE)?);
+        let bag_id = bag.read(TAG_OID)?;
+        let mut value = DerReader::new(bag.read(TAG_CONTEXT_0)?);
+        bag.read_optional(TAG_SET)?;
+        bag.finish()?;
+
+        if bag_id == OID_KEY_BAG {
+            let private_key = value.read_tlv(TAG_SEQUENCE)?;
+            value.finish()?;
+            contents
+                .private_keys
+                .push(PrivateKey::from_pkcs8_der(private_key)?);
+        } else if bag_id == OID_SHROUDED_KEY_BAG {
+            let mut encrypted = DerReader::new(value.read(TAG_SEQUENCE)?);
+            value.finish()?;
+            let algorithm = encrypted.read_tlv(TAG_SEQUENCE)?;
+            let ciphertext = encrypted.read(TAG_OCTET_STRING)?;
+            encrypted.finish()?;
+            let private_key = decrypt(algorithm, password, ciphertext)?;
+            contents
+                .private_keys
+                .push(PrivateKey::from_pkcs8_der(&private_key.0)?);
+        } else if bag_id == OID_CERT_BAG {
+            let mut cert_bag = DerReader::new(value.read(TAG_SEQUENCE)?);
+            value.finish()?;
+            let cert_id = cert_bag.read(TAG_OID)?;
+            let mut cert_value = DerReader::new(cert_bag.read(TAG_CONTEXT_0)?);
+            cert_bag.finish()?;
+            if cert_id == OID_X509_CERTIFICATE {
+                let certificate = cert_value.read(TAG_OCTET_STRING)?;
+                cert_value.finish()?;
+                contents.certificates.push(certificate.to_vec());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the content of a ContentInfo of the data content type.
+fn read_data<'a>(content_info: &mut DerReader<'a>) -> Result<&'a [u8], UnknownCryptoError> {
+    if content_info.read(TAG_OID)? != OID_DATA {
+        return Err(UnknownCryptoError);
+    }
+    let mut content = DerReader::new(content_info.read(TAG_CONTEXT_0)?);
+    let data = content.read(TAG_OCTET_STRING)?;
+    content.finish()?;
+
+    Ok(data)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify the integrity of the PKCS #12 archive `pfx` with `password`, and
+/// return the private keys and certificates it contains.
+pub fn parse(pfx: &[u8], password: &str) -> Result<Contents, UnknownCryptoError> {
+    let mut outer = DerReader::new(pfx);
+    let mut pfx = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    if pfx.read(TAG_INTEGER)? != [0x03] {
+        return Err(UnknownCryptoError);
+    }
+    let auth_safe = read_data(&mut DerReader::new(pfx.read(TAG_SEQUENCE)?))?;
+    let mac_data = pfx.read(TAG_SEQUENCE)?;
+    pfx.finish()?;
+
+    verify_mac_data(mac_data, auth_safe, password)?;
+
+    let mut contents = Contents {
+        private_keys: Vec::new(),
+        certificates: Vec::new(),
+    };
+    let mut outer = DerReader::new(auth_safe);
+    let mut content_infos = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    while !content_infos.is_empty() {
+        let content_info = content_infos.read(TAG_SEQUENCE)?;
+        let mut reader = DerReader::new(content_info);
+        if reader.read(TAG_OID)? != OID_ENCRYPTED_DATA {
+            let mut reader = DerReader::new(content_info);
+            parse_safe_contents(read_data(&mut reader)?, password, &mut contents)?;
+            reader.finish()?;
+            continue;
+        }
+
+        let mut content = DerReader::new(reader.read(TAG_CONTEXT_0)?);
+        reader.finish()?;
+        let mut encrypted_data = DerReader::new(content.read(TAG_SEQUENCE)?);
+        content.finish()?;
+        if encrypted_data.read(TAG_INTEGER)? != [0x00] {
+            return Err(UnknownCryptoError);
+        }
+        let mut encrypted_content_info = DerReader::new(encrypted_data.read(TAG_SEQUENCE)?);
+        encrypted_data.finish()?;
+        if encrypted_content_info.read(TAG_OID)? != OID_DATA {
+            return Err(UnknownCryptoError);
+        }
+        let algorithm = encrypted_content_info.read_tlv(TAG_SEQUENCE)?;
+        let ciphertext = encrypted_content_info.read(TAG_ENCRYPTED_CONTENT)?;
+        encrypted_content_info.finish()?;
+
+        let safe_contents = decrypt(algorithm, password, ciphertext)?;
+        parse_safe_contents(&safe_contents.0, password, &mut contents)?;
+    }
+
+    Ok(contents)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_parse_err() {
+        assert!(parse(&[], "").is_err());
+        assert!(parse(&[0x30, 0x00], "").is_err());
+        // Version 3 without any content.
+        assert!(parse(&[0x30, 0x03, 0x02, 0x01, 0x03], "").is_err());
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_bmp_password() {
+        assert_eq!(bmp_password("").0, [0x00, 0x00]);
+        assert_eq!(bmp_password("ab").0, [0x00, 0x61, 0x00, 0x62, 0x00, 0x00]);
+        assert_eq!(bmp_password("ä").0, [0x00, 0xE4, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_derive_key() {
+        // The key and IV of pbeWithSHAAnd3-KeyTripleDES-CBC with the password
+        // "smeg", also used in the tests of OpenSSL and Bouncy Castle.
+        let password = bmp_password("smeg");
+        let salt = [0x0A, 0x58, 0xCF, 0x64, 0x53, 0x0D, 0x82, 0x3F];
+        let mut key = [0u8; 24];
+        derive_key::<Sha1>(1, &password.0, &salt, 1, &mut key).unwrap();
+        assert_eq!(
+            key,
+            [
+                0x8A, 0xAA, 0xE6, 0x29, 0x7B, 0x6C, 0xB0, 0x46, 0x42, 0xAB, 0x5B, 0x07, 0x78, 0x51,
+                0x28, 0x4E, 0xB7, 0x12, 0x8F, 0x1A, 0x2A, 0x7F, 0xBC, 0xA3
+            ]
+        );
+        let mut iv = [0u8; 8];
+        derive_key::<Sha1>(2, &password.0, &salt, 1, &mut iv).unwrap();
+        assert_eq!(iv, [0x79, 0x99, 0x3D, 0xFE, 0x04, 0x8D, 0x3B, 0x76]);
+
+        // Output longer than the digest, with more iterations and a hash
+        // function with a 128-byte block size.
+        let mut output = [0u8; 100];
+        derive_key::<Sha512>(ID_MAC, &password.0, &salt, 3, &mut output).unwrap();
+        assert_eq!(
+            output[..8],
+            [0xF1, 0x20, 0x8C, 0x3D, 0x89, 0xEA, 0x38, 0x1D]
+        );
+        assert_eq!(
+            output[92..],
+            [0x29, 0x3D, 0xC4, 0xB2, 0x3F, 0xA9, 0xCE, 0x39]
+        );
+
+        assert!(derive_key::<Sha1>(1, &password.0, &salt, 0, &mut key).is_err());
+    }
+}
@@ -0,0 +1,838 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Password-protected PKCS #8 private keys, as specified in [RFC 5958] and
+//! [RFC 8018].
+//!
+//! # About:
+//! An EncryptedPrivateKeyInfo is the standard format for storing a private
+//! key encrypted with a passphrase, such as the `-----BEGIN ENCRYPTED PRIVATE
+//! KEY-----` PEM files written by `openssl pkcs8 -topk8`. This module reads
+//! and writes the DER encoding of these with the PBES2 scheme.
+//!
+//! [`decrypt()`] supports PBES2 with PBKDF2, using HMAC with SHA-1, SHA-256,
+//! SHA-384 or SHA-512, and AES-128, AES-192 or AES-256 in either CBC mode
+//! or GCM ([RFC 5084]). Keys encrypted with scrypt, or with the older PBES1
+//! schemes, are not supported.
+//!
+//! [`encrypt()`] always uses PBKDF2 with HMAC-SHA256, a random 16-byte salt
+//! and AES-256, in the mode given by [`Cipher`]. It is only available with
+//! the `safe_api` feature.
+//!
+//! The plaintext of both is a PKCS #8 PrivateKeyInfo of any algorithm.
+//! [`PrivateKeyInfo::to_private_key()`] and [`PrivateKey::from_pkcs8_der()`]
+//! import those of Ed25519, ECDSA P-256 and RSA keys.
+//!
+//! # Parameters:
+//! - `encrypted_private_key_info`: A DER-encoded EncryptedPrivateKeyInfo.
+//! - `private_key_info`: A DER-encoded PKCS #8 PrivateKeyInfo.
+//! - `password`: The passphrase that protects the private key.
+//! - `iterations`: The number of PBKDF2 iterations.
+//! - `cipher`: The cipher that encrypts the private key.
+//! - `der`: A DER-encoded PKCS #8 PrivateKeyInfo.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `encrypted_private_key_info` is not a supported DER encoding of an
+//!   EncryptedPrivateKeyInfo.
+//! - `encrypted_private_key_info` fails to decrypt with `password`.
+//! - `private_key_info` is not a DER-encoded SEQUENCE, or is longer than
+//!   16384 bytes.
+//! - `iterations` is 0 or greater than `u32::MAX`.
+//! - `der` is not a supported PKCS #8 encoding of an Ed25519, P-256 or RSA
+//!   private key.
+//! - Failure to generate random bytes securely when calling [`encrypt()`].
+//!
+//! # Security:
+//! - The passphrase is the only protection of the private key. Use a strong
+//!   passphrase, and as many PBKDF2 iterations as can be tolerated. OpenSSL
+//!   uses 2048 iterations by default, which is too few to protect a weak
+//!   passphrase.
+//! - The iteration count of an EncryptedPrivateKeyInfo is chosen by whoever
+//!   made it. Decrypting one from an untrusted source can be arbitrarily slow.
+//! - With AES-CBC, a wrong passphrase is only detected by the padding and the
+//!   structure of the decrypted PrivateKeyInfo. With AES-GCM, the ciphertext is
+//!   authenticated. However, OpenSSL cannot read private keys encrypted with
+//!   AES-GCM, so prefer [`Cipher::Aes256Cbc`] for compatibility with it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::pkcs8::{self, Cipher, PrivateKey};
+//!
+//! // A PKCS #8 PrivateKeyInfo of an Ed25519 key, from RFC 8410.
+//! let private_key_info = [
+//!     0x30, 0x2E, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70, 0x04, 0x22, 0x04,
+//!     0x20, 0xD4, 0xEE, 0x72, 0xDB, 0xF9, 0x13, 0x58, 0x4A, 0xD5, 0xB6, 0xD8, 0xF1, 0xF7, 0x69,
+//!     0xF8, 0xAD, 0x3A, 0xFE, 0x7C, 0x28, 0xCB, 0xF1, 0xD4, 0xFB, 0xE0, 0x97, 0xA8, 0x8F, 0x44,
+//!     0x75, 0x58, 0x42,
+//! ];
+//!
+//! let encrypted = pkcs8::encrypt(&private_key_info, "passphrase", 100_000, Cipher::Aes256Cbc)?;
+//!
+//! let decrypted = pkcs8::decrypt(&encrypted, "passphrase")?;
+//! assert_eq!(decrypted.unprotected_as_bytes(), &private_key_info[..]);
+//! match decrypted.to_private_key()? {
+//!     PrivateKey::Ed25519(_) => (),
+//!     _ => panic!("not an Ed25519 key"),
+//! }
+//!
+//! assert!(pkcs8::decrypt(&encrypted, "wrong passphrase").is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 5958]: https://www.rfc-editor.org/rfc/rfc5958
+//! [RFC 8018]: https://www.rfc-editor.org/rfc/rfc8018
+//! [RFC 5084]: https://www.rfc-editor.org/rfc/rfc5084
+//! [`decrypt()`]: fn.decrypt.html
+//! [`encrypt()`]: fn.encrypt.html
+//! [`Cipher`]: enum.Cipher.html
+//! [`Cipher::Aes256Cbc`]: enum.Cipher.html#variant.Aes256Cbc
+//! [`PrivateKeyInfo::to_private_key()`]: struct.PrivateKeyInfo.html#method.to_private_key
+//! [`PrivateKey::from_pkcs8_der()`]: enum.PrivateKey.html#method.from_pkcs8_der
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "safe_api")]
+use super::x509::write_tlv;
+#[cfg(feature = "safe_api")]
+use crate::hazardous::aead::aes_gcm::GCM_NONCESIZE;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::aes_gcm::{AesGcm, GCM_TAGSIZE},
+        block::aes::{self, Aes, AES_BLOCKSIZE},
+        ecc::{ecdsa_p256, ed25519},
+        hash::{sha1::Sha1, sha256::Sha256, sha384::Sha384, sha512::Sha512},
+        kdf::pbkdf2,
+        mac::hmac::HmacGeneric,
+        rsa,
+    },
+    util::der::{DerReader, TAG_INTEGER, TAG_NULL, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE},
+};
+use zeroize::Zeroize;
+
+/// `[0]`, constructed.
+const TAG_CONTEXT_0: u8 = 0xA0;
+/// `[1]`, constructed.
+const TAG_CONTEXT_1: u8 = 0xA1;
+/// `[1] IMPLICIT`, primitive, the publicKey of a OneAsymmetricKey.
+const TAG_PUBLIC_KEY: u8 = 0x81;
+
+#[cfg(feature = "safe_api")]
+/// The size of the salt used by [`encrypt()`].
+const SALT_SIZE: usize = 16;
+#[cfg(feature = "safe_api")]
+/// The maximum size of a PrivateKeyInfo to encrypt, which is well above the
+/// size of one with an RSA-16384 key.
+const MAX_PRIVATE_KEY_INFO_SIZE: usize = 16384;
+
+const OID_PBES2: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x05, 0x0D];
+const OID_PBKDF2: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x05, 0x0C];
+const OID_HMAC_SHA1: [u8; 8] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x02, 0x07];
+const OID_HMAC_SHA256: [u8; 8] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x02, 0x09];
+const OID_HMAC_SHA384: [u8; 8] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x02, 0x0A];
+const OID_HMAC_SHA512: [u8; 8] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x02, 0x0B];
+const OID_AES128_CBC: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x02];
+const OID_AES192_CBC: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x16];
+const OID_AES256_CBC: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2A];
+const OID_AES128_GCM: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x06];
+const OID_AES192_GCM: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x1A];
+const OID_AES256_GCM: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x01, 0x2E];
+
+/// The AlgorithmIdentifier of Ed25519.
+const ED25519_ALGORITHM: [u8; 7] = [0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70];
+/// The AlgorithmIdentifier of an elliptic curve key on P-256.
+const P256_ALGORITHM: [u8; 21] = [
+    0x30, 0x13, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x08, 0x2A, 0x86, 0x48,
+    0xCE, 0x3D, 0x03, 0x01, 0x07,
+];
+/// The AlgorithmIdentifier of an RSA key.
+const RSA_ALGORITHM: [u8; 15] = [
+    0x30, 0x0D, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01, 0x05, 0x00,
+];
+
+// A single key is typically imported at a time, so the size difference is
+// not boxed.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+/// A private key from a PKCS #8 PrivateKeyInfo.
+pub enum PrivateKey {
+    /// An Ed25519 secret key.
+    Ed25519(ed25519::SecretKey),
+    /// An ECDSA P-256 secret key.
+    EcdsaP256(ecdsa_p256::SecretKey),
+    /// An RSA secret key.
+    Rsa(rsa::keys::SecretKey),
+}
+
+impl PrivateKey {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Import a DER-encoded PKCS #8 PrivateKeyInfo of an Ed25519, P-256 or
+    /// RSA private key.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut outer = DerReader::new(der);
+        let mut pkcs8 = DerReader::new(outer.read(TAG_SEQUENCE)?);
+        outer.finish()?;
+        // Version 1 is a OneAsymmetricKey (RFC 5958), which can include the
+        // public key.
+        let version = pkcs8.read(TAG_INTEGER)?;
+        if version != [0x00] && version != [0x01] {
+            return Err(UnknownCryptoError);
+        }
+        let algorithm = pkcs8.read_tlv(TAG_SEQUENCE)?;
+        let private_key = pkcs8.read(TAG_OCTET_STRING)?;
+        // Attributes.
+        pkcs8.read_optional(TAG_CONTEXT_0)?;
+        if version == [0x01] {
+            pkcs8.read_optional(TAG_PUBLIC_KEY)?;
+        }
+        pkcs8.finish()?;
+
+        if algorithm == ED25519_ALGORITHM {
+            let mut curve_private_key = DerReader::new(private_key);
+            let seed = curve_private_key.read(TAG_OCTET_STRING)?;
+            curve_private_key.finish()?;
+
+            Ok(PrivateKey::Ed25519(ed25519::SecretKey::from_slice(seed)?))
+        } else if algorithm == P256_ALGORITHM {
+            let mut outer = DerReader::new(private_key);
+            let mut ec_private_key = DerReader::new(outer.read(TAG_SEQUENCE)?);
+            outer.finish()?;
+            if ec_private_key.read(TAG_INTEGER)? != [0x01] {
+                return Err(UnknownCryptoError);
+            }
+            let scalar = ec_private_key.read(TAG_OCTET_STRING)?;
+            // The curve and public key are optional, and the curve is already
+            // given by the algorithm.
+            ec_private_key.read_optional(TAG_CONTEXT_0)?;
+            ec_private_key.read_optional(TAG_CONTEXT_1)?;
+            ec_private_key.finish()?;
+
+            Ok(PrivateKey::EcdsaP256(ecdsa_p256::SecretKey::from_slice(
+                scalar,
+            )?))
+        } else if algorithm == RSA_ALGORITHM && version == [0x00] {
+            Ok(PrivateKey::Rsa(rsa::keys::SecretKey::from_pkcs8_der(der)?))
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+#[cfg(feature = "safe_api")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The cipher that [`encrypt()`] uses, with a key derived by PBKDF2-HMAC-SHA256.
+///
+/// [`encrypt()`]: fn.encrypt.html
+pub enum Cipher {
+    /// AES-256 in CBC mode with PKCS #7 padding, which is what OpenSSL 3 uses
+    /// by default.
+    Aes256Cbc,
+    /// AES-256-GCM with a 16-byte tag, as specified in RFC 5084. This is not
+    /// supported by OpenSSL.
+    Aes256Gcm,
+}
+
+/// Decrypted data, which is zeroized on drop.
+pub(super) struct Plaintext(pub(super) Vec<u8>);
+
+impl Drop for Plaintext {
+    fn drop(&mut self) {
+        self.0.iter_mut().zeroize();
+    }
+}
+
+/// A DER-encoded PKCS #8 PrivateKeyInfo decrypted by [`decrypt()`], which is
+/// zeroized on drop.
+///
+/// [`decrypt()`]: fn.decrypt.html
+pub struct PrivateKeyInfo(Plaintext);
+
+impl core::fmt::Debug for PrivateKeyInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PrivateKeyInfo {{***OMITTED***}}")
+    }
+}
+
+impl PrivateKeyInfo {
+    /// Return the DER encoding of the PrivateKeyInfo. __**Warning**__: Should
+    /// not be used unless strictly needed.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        &(self.0).0
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Import the PrivateKeyInfo as an Ed25519, P-256 or RSA private key.
+    pub fn to_private_key(&self) -> Result<PrivateKey, UnknownCryptoError> {
+        PrivateKey::from_pkcs8_der(self.unprotected_as_bytes())
+    }
+}
+
+/// Read a positive iteration count.
+pub(super) fn read_iterations(reader: &mut DerReader<'_>) -> Result<usize, UnknownCryptoError> {
+    let value = reader.read_unsigned_integer()?;
+    if value.len() > 4 {
+        return Err(UnknownCryptoError);
+    }
+    let iterations = value
+        .iter()
+        .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte));
+    if iterations == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(iterations)
+}
+
+/// Read the contents of an AlgorithmIdentifier with absent or NULL
+/// parameters, and return its OID.
+pub(super) fn read_hash_algorithm<'a>(
+    reader: &mut DerReader<'a>,
+) -> Result<&'a [u8], UnknownCryptoError> {
+    let mut algorithm = DerReader::new(reader.read(TAG_SEQUENCE)?);
+    let oid = algorithm.read(TAG_OID)?;
+    if !algorithm.is_empty() && !algorithm.read(TAG_NULL)?.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+    algorithm.finish()?;
+
+    Ok(oid)
+}
+
+/// Remove the PKCS #7 padding for the block size `blocksize` from `plaintext`.
+pub(super) fn unpad(
+    mut plaintext: Plaintext,
+    blocksize: usize,
+) -> Result<Plaintext, UnknownCryptoError> {
+    let padding = match plaintext.0.last() {
+        Some(last) => usize::from(*last),
+        None => return Err(UnknownCryptoError),
+    };
+    if padding == 0 || padding > blocksize || padding > plaintext.0.len() {
+        return Err(UnknownCryptoError);
+    }
+    let len = plaintext.0.len() - padding;
+    if plaintext.0[len..]
+        .iter()
+        .any(|byte| usize::from(*byte) != padding)
+    {
+        return Err(UnknownCryptoError);
+    }
+    plaintext.0.truncate(len);
+
+    Ok(plaintext)
+}
+
+/// Decrypt `ciphertext` with AES-CBC and remove the PKCS #7 padding.
+fn aes_cbc_decrypt(
+    key: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    if iv.len() != AES_BLOCKSIZE {
+        return Err(UnknownCryptoError);
+    }
+    let aes = Aes::new(&aes::SecretKey::from_slice(key)?)?;
+    let mut plaintext = Plaintext(ciphertext.to_vec());
+    aes.decrypt_blocks(&mut plaintext.0)?;
+
+    let previous = core::iter::once(iv).chain(ciphertext.chunks(AES_BLOCKSIZE));
+    for (block, previous) in plaintext.0.chunks_mut(AES_BLOCKSIZE).zip(previous) {
+        for (byte, mask) in block.iter_mut().zip(previous.iter()) {
+            *byte ^= mask;
+        }
+    }
+
+    unpad(plaintext, AES_BLOCKSIZE)
+}
+
+#[cfg(feature = "safe_api")]
+/// Pad `plaintext` with PKCS #7 and encrypt it with AES-CBC.
+fn aes_cbc_encrypt(
+    key: &[u8],
+    iv: &[u8; AES_BLOCKSIZE],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let aes = Aes::new(&aes::SecretKey::from_slice(key)?)?;
+    let padding = AES_BLOCKSIZE - plaintext.len() % AES_BLOCKSIZE;
+    let mut ciphertext = Vec::with_capacity(plaintext.len() + padding);
+    ciphertext.extend_from_slice(plaintext);
+    ciphertext.resize(plaintext.len() + padding, padding as u8);
+
+    let mut previous = *iv;
+    for block in ciphertext.chunks_mut(AES_BLOCKSIZE) {
+        for (byte, mask) in block.iter_mut().zip(previous.iter()) {
+            *byte ^= mask;
+        }
+        aes.encrypt_block(block)?;
+        previous.copy_from_slice(block);
+    }
+
+    Ok(ciphertext)
+}
+
+/// Decrypt `ciphertext`, which has the tag appended, with AES-GCM and the
+/// GCMParameters `params`.
+fn aes_gcm_decrypt(
+    key: &[u8],
+    params: &[u8],
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    let mut params = DerReader::new(params);
+    let nonce = params.read(TAG_OCTET_STRING)?;
+    // The tag length defaults to 12, but only 16-byte tags are supported.
+    if params.read_optional(TAG_INTEGER)? != Some(&[GCM_TAGSIZE as u8]) {
+        return Err(UnknownCryptoError);
+    }
+    params.finish()?;
+    if ciphertext.len() < GCM_TAGSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - GCM_TAGSIZE);
+    let mut plaintext = Plaintext(ciphertext.to_vec());
+    AesGcm::new(key)?.open_in_place(nonce, &[], &mut plaintext.0, tag)?;
+
+    Ok(plaintext)
+}
+
+/// Derive `dst` from `password` with PBKDF2, using the HMAC with the OID `prf`.
+fn derive_key(
+    prf: &[u8],
+    password: &str,
+    salt: &[u8],
+    iterations: usize,
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let password = password.as_bytes();
+    if prf == OID_HMAC_SHA1 {
+        let prf = HmacGeneric::<Sha1>::new(password)?;
+        pbkdf2::derive_key_with_prf(prf, salt, iterations, dst)
+    } else if prf == OID_HMAC_SHA256 {
+        let prf = HmacGeneric::<Sha256>::new(password)?;
+        pbkdf2::derive_key_with_prf(prf, salt, iterations, dst)
+    } else if prf == OID_HMAC_SHA384 {
+        let prf = HmacGeneric::<Sha384>::new(password)?;
+        pbkdf2::derive_key_with_prf(prf, salt, iterations, dst)
+    } else if prf == OID_HMAC_SHA512 {
+        let prf = HmacGeneric::<Sha512>::new(password)?;
+        pbkdf2::derive_key_with_prf(prf, salt, iterations, dst)
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+/// Decrypt `ciphertext` with PBES2, with the parameters `params`.
+pub(super) fn pbes2_decrypt(
+    params: &[u8],
+    password: &str,
+    ciphertext: &[u8],
+) -> Result<Plaintext, UnknownCryptoError> {
+    let mut params = DerReader::new(params);
+    let mut kdf = DerReader::new(params.read(TAG_SEQUENCE)?);
+    let mut encryption = DerReader::new(params.read(TAG_SEQUENCE)?);
+    params.finish()?;
+
+    if kdf.read(TAG_OID)? != OID_PBKDF2 {
+        return Err(UnknownCryptoError);
+    }
+    let mut kdf_params = DerReader::new(kdf.read(TAG_SEQUENCE)?);
+    kdf.finish()?;
+    let salt = kdf_params.read(TAG_OCTET_STRING)?;
+    let iterations = read_iterations(&mut kdf_params)?;
+    let key_len = kdf_params.read_optional(TAG_INTEGER)?;
+    // The PRF defaults to HMAC-SHA1.
+    let prf = if kdf_params.is_empty() {
+        &OID_HMAC_SHA1[..]
+    } else {
+        read_hash_algorithm(&mut kdf_params)?
+    };
+    kdf_params.finish()?;
+
+    let cipher = encryption.read(TAG_OID)?;
+    let (key_size, is_gcm) = if cipher == OID_AES128_CBC {
+        (aes::AES128_KEYSIZE, false)
+    } else if cipher == OID_AES192_CBC {
+        (aes::AES192_KEYSIZE, false)
+    } else if cipher == OID_AES256_CBC {
+        (aes::AES256_KEYSIZE, false)
+    } else if cipher == OID_AES128_GCM {
+        (aes::AES128_KEYSIZE, true)
+    } else if cipher == OID_AES192_GCM {
+        (aes::AES192_KEYSIZE, true)
+    } else if cipher == OID_AES256_GCM {
+        (aes::AES256_KEYSIZE, true)
+    } else {
+        return Err(UnknownCryptoError);
+    };
+    let cipher_params = if is_gcm {
+        encryption.read(TAG_SEQUENCE)?
+    } else {
+        encryption.read(TAG_OCTET_STRING)?
+    };
+    encryption.finish()?;
+    if let Some(key_len) = key_len {
+        if key_len != [key_size as u8] {
+            return Err(UnknownCryptoError);
+        }
+    }
+
+    let mut key = [0u8; aes::AES256_KEYSIZE];
+    let key = &mut key[..key_size];
+    derive_key(prf, password, salt, iterations, key)?;
+    let plaintext = if is_gcm {
+        aes_gcm_decrypt(key, cipher_params, ciphertext)
+    } else {
+        aes_cbc_decrypt(key, cipher_params, ciphertext)
+    };
+    key.zeroize();
+
+    plaintext
+}
+
+#[cfg(feature = "safe_api")]
+/// Write a non-negative INTEGER with the value `value`.
+fn write_integer(value: u32, dst: &mut Vec<u8>) -> Result<(), UnknownCryptoError> {
+    let bytes = value.to_be_bytes();
+    let zeroes = bytes.iter().take_while(|b| **b == 0).count();
+    let bytes = &bytes[core::cmp::min(zeroes, bytes.len() - 1)..];
+    let mut encoded = Vec::with_capacity(bytes.len() + 1);
+    if bytes[0] & 0x80 != 0 {
+        encoded.push(0);
+    }
+    encoded.extend_from_slice(bytes);
+
+    write_tlv(TAG_INTEGER, &encoded, dst)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the DER-encoded EncryptedPrivateKeyInfo
+/// `encrypted_private_key_info` with `password`.
+pub fn decrypt(
+    encrypted_private_key_info: &[u8],
+    password: &str,
+) -> Result<PrivateKeyInfo, UnknownCryptoError> {
+    let mut outer = DerReader::new(encrypted_private_key_info);
+    let mut encrypted = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    let mut algorithm = DerReader::new(encrypted.read(TAG_SEQUENCE)?);
+    let ciphertext = encrypted.read(TAG_OCTET_STRING)?;
+    encrypted.finish()?;
+    if algorithm.read(TAG_OID)? != OID_PBES2 {
+        return Err(UnknownCryptoError);
+    }
+    let params = algorithm.read(TAG_SEQUENCE)?;
+    algorithm.finish()?;
+
+    let plaintext = pbes2_decrypt(params, password, ciphertext)?;
+    // Reject anything that is not a single SEQUENCE, which with AES-CBC is
+    // the main indication of a wrong password.
+    let mut reader = DerReader::new(&plaintext.0);
+    reader.read(TAG_SEQUENCE)?;
+    reader.finish()?;
+
+    Ok(PrivateKeyInfo(plaintext))
+}
+
+#[cfg(feature = "safe_api")]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encrypt the DER-encoded PKCS #8 PrivateKeyInfo `private_key_info` with
+/// `password`, returning the DER encoding of an EncryptedPrivateKeyInfo.
+pub fn encrypt(
+    private_key_info: &[u8],
+    password: &str,
+    iterations: usize,
+    cipher: Cipher,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if private_key_info.len() > MAX_PRIVATE_KEY_INFO_SIZE {
+        return Err(UnknownCryptoError);
+    }
+    let mut reader = DerReader::new(private_key_info);
+    reader.read(TAG_SEQUENCE)?;
+    reader.finish()?;
+    if iterations == 0 || iterations > u32::MAX as usize {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut salt = [0u8; SALT_SIZE];
+    crate::util::secure_rand_bytes(&mut salt)?;
+    // The IV for AES-CBC, or the nonce for AES-GCM.
+    let mut iv = [0u8; AES_BLOCKSIZE];
+    crate::util::secure_rand_bytes(&mut iv)?;
+    let nonce = &iv[..GCM_NONCESIZE];
+
+    let mut key = [0u8; aes::AES256_KEYSIZE];
+    let ciphertext =
+        derive_key(&OID_HMAC_SHA256, password, &salt, iterations, &mut key).and_then(|_| {
+            match cipher {
+                Cipher::Aes256Cbc => aes_cbc_encrypt(&key, &iv, private_key_info),
+                Cipher::Aes256Gcm => {
+                    let mut ciphertext = private_key_info.to_vec();
+                    let mut tag = [0u8; GCM_TAGSIZE];
+                    AesGcm::new(&key)?.seal_in_place(nonce, &[], &mut ciphertext, &mut tag)?;
+                    ciphertext.extend_from_slice(&tag);
+                    Ok(ciphertext)
+                }
+            }
+        });
+    key.zeroize();
+    let ciphertext = ciphertext?;
+
+    let mut encryption = Vec::new();
+    match cipher {
+        Cipher::Aes256Cbc => {
+            write_tlv(TAG_OID, &OID_AES256_CBC, &mut encryption)?;
+            write_tlv(TAG_OCTET_STRING, &iv, &mut encryption)?;
+        }
+        Cipher::Aes256Gcm => {
+            let mut gcm_params = Vec::new();
+            write_tlv(TAG_OCTET_STRING, nonce, &mut gcm_params)?;
+            write_integer(GCM_TAGSIZE as u32, &mut gcm_params)?;
+            write_tlv(TAG_OID, &OID_AES256_GCM, &mut encryption)?;
+            write_tlv(TAG_SEQUENCE, &gcm_params, &mut encryption)?;
+        }
+    }
+
+    let mut prf = Vec::new();
+    write_tlv(TAG_OID, &OID_HMAC_SHA256, &mut prf)?;
+    write_tlv(TAG_NULL, &[], &mut prf)?;
+    let mut pbkdf2_params = Vec::new();
+    write_tlv(TAG_OCTET_STRING, &salt, &mut pbkdf2_params)?;
+    write_integer(iterations as u32, &mut pbkdf2_params)?;
+    write_tlv(TAG_SEQUENCE, &prf, &mut pbkdf2_params)?;
+    let mut kdf = Vec::new();
+    write_tlv(TAG_OID, &OID_PBKDF2, &mut kdf)?;
+    write_tlv(TAG_SEQUENCE, &pbkdf2_params, &mut kdf)?;
+
+    let mut pbes2_params = Vec::new();
+    write_tlv(TAG_SEQUENCE, &kdf, &mut pbes2_params)?;
+    write_tlv(TAG_SEQUENCE, &encryption, &mut pbes2_params)?;
+    let mut algorithm = Vec::new();
+    write_tlv(TAG_OID, &OID_PBES2, &mut algorithm)?;
+    write_tlv(TAG_SEQUENCE, &pbes2_params, &mut algorithm)?;
+
+    let mut encrypted = Vec::new();
+    write_tlv(TAG_SEQUENCE, &algorithm, &mut encrypted)?;
+    write_tlv(TAG_OCTET_STRING, &ciphertext, &mut encrypted)?;
+    let mut encrypted_private_key_info = Vec::new();
+    write_tlv(TAG_SEQUENCE, &encrypted, &mut encrypted_private_key_info)?;
+
+    Ok(encrypted_private_key_info)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    /// A PKCS #8 PrivateKeyInfo of an Ed25519 key, from RFC 8410, section 10.3.
+    const ED25519_PKCS8: [u8; 48] = [
+        0x30, 0x2E, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70, 0x04, 0x22, 0x04,
+        0x20, 0xD4, 0xEE, 0x72, 0xDB, 0xF9, 0x13, 0x58, 0x4A, 0xD5, 0xB6, 0xD8, 0xF1, 0xF7, 0x69,
+        0xF8, 0xAD, 0x3A, 0xFE, 0x7C, 0x28, 0xCB, 0xF1, 0xD4, 0xFB, 0xE0, 0x97, 0xA8, 0x8F, 0x44,
+        0x75, 0x58, 0x42,
+    ];
+
+    #[test]
+    fn test_ed25519_pkcs8() {
+        match PrivateKey::from_pkcs8_der(&ED25519_PKCS8).unwrap() {
+            PrivateKey::Ed25519(secret_key) => {
+                assert_eq!(secret_key.unprotected_as_bytes(), &ED25519_PKCS8[16..]);
+            }
+            _ => panic!(),
+        }
+
+        // Trailing data, another version and another algorithm.
+        let mut bad = [0u8; 49];
+        bad[..48].copy_from_slice(&ED25519_PKCS8);
+        assert!(PrivateKey::from_pkcs8_der(&bad).is_err());
+        let mut bad = ED25519_PKCS8;
+        bad[4] = 0x02;
+        assert!(PrivateKey::from_pkcs8_der(&bad).is_err());
+        let mut bad = ED25519_PKCS8;
+        bad[11] = 0x6E;
+        assert!(PrivateKey::from_pkcs8_der(&bad).is_err());
+        // A seed of another length.
+        let mut bad = ED25519_PKCS8;
+        bad[15] = 0x1F;
+        assert!(PrivateKey::from_pkcs8_der(&bad).is_err());
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[test]
+    fn test_encrypt_decrypt() {
+        for cipher in [Cipher::Aes256Cbc, Cipher::Aes256Gcm].iter() {
+            let encrypted = encrypt(&ED25519_PKCS8, "password", 10, *cipher).unwrap();
+            let decrypted = decrypt(&encrypted, "password").unwrap();
+            assert_eq!(decrypted.unprotected_as_bytes(), &ED25519_PKCS8[..]);
+            assert!(matches!(
+                decrypted.to_private_key().unwrap(),
+                PrivateKey::Ed25519(_)
+            ));
+            // A random salt and IV.
+            assert_ne!(
+                encrypted,
+                encrypt(&ED25519_PKCS8, "password", 10, *cipher).unwrap()
+            );
+
+            assert!(decrypt(&encrypted, "Password").is_err());
+            assert!(decrypt(&encrypted, "").is_err());
+            let mut modified = encrypted.clone();
+            *modified.last_mut().unwrap() ^= 1;
+            assert!(decrypt(&modified, "password").is_err());
+            assert!(decrypt(&encrypted[..encrypted.len() - 1], "password").is_err());
+        }
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[test]
+    fn test_debug_impl() {
+        let encrypted = encrypt(&ED25519_PKCS8, "password", 1, Cipher::Aes256Gcm).unwrap();
+        let decrypted = decrypt(&encrypted, "password").unwrap();
+        assert_eq!(format!("{:?}", decrypted), "PrivateKeyInfo {***OMITTED***}");
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[test]
+    fn test_encrypt_err() {
+        let cipher = Cipher::Aes256Cbc;
+        assert!(encrypt(&ED25519_PKCS8, "password", 0, cipher).is_err());
+        assert!(encrypt(&[], "password", 1, cipher).is_err());
+        assert!(encrypt(&ED25519_PKCS8[..47], "password", 1, cipher).is_err());
+        let mut trailing = ED25519_PKCS8.to_vec();
+        trailing.push(0);
+        assert!(encrypt(&trailing, "password", 1, cipher).is_err());
+
+        let mut long = vec![0x30, 0x82, 0x40, 0x01];
+        long.resize(MAX_PRIVATE_KEY_INFO_SIZE + 5, 0);
+        assert!(encrypt(&long, "password", 1, cipher).is_err());
+        long.truncate(MAX_PRIVATE_KEY_INFO_SIZE);
+        long[1..4].copy_from_slice(&[0x82, 0x3F, 0xFC]);
+        let encrypted = encrypt(&long, "password", 1, Cipher::Aes256Gcm).unwrap();
+        assert_eq!(
+            decrypt(&encrypted, "password")
+                .unwrap()
+                .unprotected_as_bytes(),
+            &long[..]
+        );
+    }
+
+    #[test]
+    fn test_decrypt_err() {
+        assert!(decrypt(&[], "").is_err());
+        assert!(decrypt(&[0x30, 0x00], "").is_err());
+        // A PrivateKeyInfo that is not encrypted.
+        assert!(decrypt(&ED25519_PKCS8, "").is_err());
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_unpad() {
+        let unpadded = |input: &[u8]| unpad(Plaintext(input.to_vec()), 8).map(|p| p.0.clone());
+
+        assert_eq!(
+            unpadded(&[1, 2, 3, 4, 5, 6, 7, 1]).unwrap(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+        assert_eq!(unpadded(&[8; 8]).unwrap(), Vec::<u8>::new());
+        assert!(unpadded(&[]).is_err());
+        assert!(unpadded(&[1, 2, 3, 4, 5, 6, 7, 0]).is_err());
+        assert!(unpadded(&[9; 9]).is_err());
+        assert!(unpadded(&[1, 2, 3, 4, 5, 6, 2, 3]).is_err());
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[test]
+    fn test_aes_cbc() {
+        // CBC-AES256.Encrypt from NIST SP 800-38A, F.2.5, with the first two
+        // blocks and PKCS #7 padding.
+        let key = [
+            0x60, 0x3D, 0xEB, 0x10, 0x15, 0xCA, 0x71, 0xBE, 0x2B, 0x73, 0xAE, 0xF0, 0x85, 0x7D,
+            0x77, 0x81, 0x1F, 0x35, 0x2C, 0x07, 0x3B, 0x61, 0x08, 0xD7, 0x2D, 0x98, 0x10, 0xA3,
+            0x09, 0x14, 0xDF, 0xF4,
+        ];
+        let iv = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+            0x0E, 0x0F,
+        ];
+        let plaintext = [
+            0x6B, 0xC1, 0xBE, 0xE2, 0x2E, 0x40, 0x9F, 0x96, 0xE9, 0x3D, 0x7E, 0x11, 0x73, 0x93,
+            0x17, 0x2A, 0xAE, 0x2D, 0x8A, 0x57, 0x1E, 0x03, 0xAC, 0x9C, 0x9E, 0xB7, 0x6F, 0xAC,
+            0x45, 0xAF, 0x8E, 0x51,
+        ];
+        let expected = [
+            0xF5, 0x8C, 0x4C, 0x04, 0xD6, 0xE5, 0xF1, 0xBA, 0x77, 0x9E, 0xAB, 0xFB, 0x5F, 0x7B,
+            0xFB, 0xD6, 0x9C, 0xFC, 0x4E, 0x96, 0x7E, 0xDB, 0x80, 0x8D, 0x67, 0x9F, 0x77, 0x7B,
+            0xC6, 0x70, 0x2C, 0x7D,
+        ];
+
+        let ciphertext = aes_cbc_encrypt(&key, &iv, &plaintext).unwrap();
+        assert_eq!(ciphertext.len(), 48);
+        assert_eq!(ciphertext[..32], expected[..]);
+        assert_eq!(
+            aes_cbc_decrypt(&key, &iv, &ciphertext).unwrap().0,
+            &plaintext[..]
+        );
+
+        assert!(aes_cbc_decrypt(&key, &iv[..15], &ciphertext).is_err());
+        assert!(aes_cbc_decrypt(&key, &iv, &ciphertext[..32]).is_err());
+        assert!(aes_cbc_decrypt(&key, &iv, &ciphertext[..47]).is_err());
+    }
+
+    #[cfg(feature = "safe_api")]
+    #[test]
+    fn test_write_integer() {
+        let written = |value: u32| {
+            let mut dst = Vec::new();
+            write_integer(value, &mut dst).unwrap();
+            dst
+        };
+
+        assert_eq!(written(0), [0x02, 0x01, 0x00]);
+        assert_eq!(written(16), [0x02, 0x01, 0x10]);
+        assert_eq!(written(128), [0x02, 0x02, 0x00, 0x80]);
+        assert_eq!(written(2048), [0x02, 0x02, 0x08, 0x00]);
+        assert_eq!(
+            written(u32::MAX),
+            [0x02, 0x05, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+
+        for value in [1u32, 255, 256, 600_000, u32::MAX].iter() {
+            let encoded = written(*value);
+            let mut reader = DerReader::new(&encoded);
+            assert_eq!(read_iterations(&mut reader).unwrap(), *value as usize);
+        }
+    }
+
+    #[test]
+    fn test_derive_key_prf() {
+        let mut key = [0u8; 32];
+        derive_key(&OID_HMAC_SHA256, "password", b"salt", 1, &mut key).unwrap();
+        assert!(derive_key(&OID_PBKDF2, "password", b"salt", 1, &mut key).is_err());
+        assert!(derive_key(&OID_HMAC_SHA256, "password", b"salt", 0, &mut key).is_err());
+    }
+}
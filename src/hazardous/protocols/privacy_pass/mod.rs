@@ -0,0 +1,579 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The basic issuance protocols of Privacy Pass, as specified in
+//! [RFC 9576], [RFC 9577] and [RFC 9578].
+//!
+//! An origin sends a [`TokenChallenge`] to a client. The client runs an
+//! issuance protocol with an issuer to obtain a [`Token`] for the challenge,
+//! without the issuer learning which challenge or token it issued, and
+//! redeems the token with the origin. Two token types are supported:
+//! - [`private_token`]: Privately verifiable tokens, with the VOPRF of
+//!   [`hazardous::ecc::voprf`] (token type `0x0001`). Only the issuer, or an
+//!   origin that shares its secret key, can verify these tokens.
+//! - [`public_token`]: Publicly verifiable tokens, with RSA blind signatures
+//!   with SHA-384 and a 2048-bit modulus (token type `0x0002`). Anyone with
+//!   the public key of the issuer can verify these tokens.
+//!
+//! Both modules follow the same flow:
+//! 1. The client calls `create_token_request()` with the challenge and a
+//!    fresh nonce, sends the [`TokenRequest`] to the issuer and keeps the
+//!    `ClientState`.
+//! 2. The issuer calls `issue_token_response()`, and sends the
+//!    [`TokenResponse`] back.
+//! 3. The client calls `finalize_token()` to obtain the [`Token`].
+//! 4. The origin calls `verify_token()` with the challenge it sent.
+//!
+//! All messages are encoded and decoded in the wire format of the RFCs.
+//! Transporting them, selecting issuers and keys, and checking the issuer
+//! name and origin info of a challenge are left to the caller.
+//!
+//! # Parameters:
+//! - `token_type`: One of [`TOKEN_TYPE_VOPRF`] or [`TOKEN_TYPE_BLIND_RSA`].
+//! - `issuer_name`: The name of the issuer, which must not be empty.
+//! - `redemption_context`: Either empty, or [`REDEMPTION_CONTEXTSIZE`] bytes
+//!   that bind the token to a context chosen by the origin.
+//! - `origin_info`: The names of the origins that accept the token, separated
+//!   by commas, or empty if any origin does.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `token_type` is not a supported token type.
+//! - `issuer_name` is empty or longer than 65535 bytes.
+//! - `redemption_context` is neither empty nor [`REDEMPTION_CONTEXTSIZE`]
+//!   bytes.
+//! - `origin_info` is longer than 65535 bytes.
+//! - A message is truncated, has trailing bytes or has the wrong size for its
+//!   token type.
+//!
+//! # Security:
+//! - The nonce of a token request must be chosen uniformly at random for
+//!   every token, so that tokens cannot be linked to each other.
+//! - A token proves that the issuer issued it, not that it has not been
+//!   redeemed before. Origins must keep track of redeemed tokens, for example
+//!   by their nonce, if a token may only be redeemed once.
+//! - Issuers must use a key for many clients, and clients must check that the
+//!   key they are given is the one that everyone else is given. A key that is
+//!   unique to a client lets the issuer link its tokens to it.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::privacy_pass::{
+//!     private_token, TokenChallenge, TOKEN_TYPE_VOPRF, NONCESIZE,
+//! };
+//! use orion::hazardous::ecc::voprf::{Blind, ProofNonce, SecretKey};
+//!
+//! // Issuer
+//! let secret_key = SecretKey::generate();
+//! let public_key = secret_key.public_key()?;
+//!
+//! // Origin
+//! let challenge = TokenChallenge::new(TOKEN_TYPE_VOPRF, b"issuer.example", &[], b"origin.example")?;
+//!
+//! // Client
+//! let mut nonce = [0u8; NONCESIZE];
+//! orion::util::secure_rand_bytes(&mut nonce)?;
+//! let (request, state) =
+//!     private_token::create_token_request(&public_key, &challenge, &nonce, Blind::generate())?;
+//!
+//! // Issuer
+//! let response = private_token::issue_token_response(&secret_key, &request, &ProofNonce::generate())?;
+//!
+//! // Client
+//! let token = private_token::finalize_token(&state, &response)?;
+//!
+//! // Origin, which shares the secret key with the issuer
+//! assert!(private_token::verify_token(&secret_key, &challenge, &token).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9576]: https://www.rfc-editor.org/rfc/rfc9576
+//! [RFC 9577]: https://www.rfc-editor.org/rfc/rfc9577
+//! [RFC 9578]: https://www.rfc-editor.org/rfc/rfc9578
+//! [`private_token`]: private_token/index.html
+//! [`public_token`]: public_token/index.html
+//! [`hazardous::ecc::voprf`]: ../../ecc/voprf/index.html
+//! [`TokenChallenge`]: struct.TokenChallenge.html
+//! [`TokenRequest`]: struct.TokenRequest.html
+//! [`TokenResponse`]: struct.TokenResponse.html
+//! [`Token`]: struct.Token.html
+//! [`TOKEN_TYPE_VOPRF`]: constant.TOKEN_TYPE_VOPRF.html
+//! [`TOKEN_TYPE_BLIND_RSA`]: constant.TOKEN_TYPE_BLIND_RSA.html
+//! [`REDEMPTION_CONTEXTSIZE`]: constant.REDEMPTION_CONTEXTSIZE.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::ecc::voprf::{ELEMENTSIZE, OUTPUTSIZE};
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+
+/// Privately verifiable tokens, issued with a VOPRF.
+pub mod private_token;
+
+/// Publicly verifiable tokens, issued with RSA blind signatures.
+pub mod public_token;
+
+/// The token type of privately verifiable tokens, with the VOPRF over P-384
+/// and SHA-384.
+pub const TOKEN_TYPE_VOPRF: u16 = 0x0001;
+/// The token type of publicly verifiable tokens, with RSA blind signatures
+/// with SHA-384 and a 2048-bit modulus.
+pub const TOKEN_TYPE_BLIND_RSA: u16 = 0x0002;
+/// The size of the nonce of a token.
+pub const NONCESIZE: usize = 32;
+/// The size of a non-empty redemption context.
+pub const REDEMPTION_CONTEXTSIZE: usize = 32;
+/// The size of the digest of a token challenge.
+pub const CHALLENGE_DIGESTSIZE: usize = SHA256_OUTSIZE;
+/// The size of the identifier of an issuer key.
+pub const TOKEN_KEY_IDSIZE: usize = SHA256_OUTSIZE;
+/// The size of the modulus of the issuer keys of [`TOKEN_TYPE_BLIND_RSA`].
+///
+/// [`TOKEN_TYPE_BLIND_RSA`]: constant.TOKEN_TYPE_BLIND_RSA.html
+pub const BLIND_RSA_MODULUSSIZE: usize = 256;
+
+/// The size of the input that the authenticator of a token is computed
+/// over: the token type, nonce, challenge digest and token key ID.
+const TOKEN_INPUTSIZE: usize = 2 + NONCESIZE + CHALLENGE_DIGESTSIZE + TOKEN_KEY_IDSIZE;
+
+/// The size of the blinded message in a token request of `token_type`.
+fn blinded_message_size(token_type: u16) -> Result<usize, UnknownCryptoError> {
+    match token_type {
+        TOKEN_TYPE_VOPRF => Ok(ELEMENTSIZE),
+        TOKEN_TYPE_BLIND_RSA => Ok(BLIND_RSA_MODULUSSIZE),
+        _ => Err(UnknownCryptoError),
+    }
+}
+
+/// The size of the authenticator of a token of `token_type`.
+fn authenticator_size(token_type: u16) -> Result<usize, UnknownCryptoError> {
+    match token_type {
+        TOKEN_TYPE_VOPRF => Ok(OUTPUTSIZE),
+        TOKEN_TYPE_BLIND_RSA => Ok(BLIND_RSA_MODULUSSIZE),
+        _ => Err(UnknownCryptoError),
+    }
+}
+
+/// A reader of the length-prefixed encodings of the RFCs.
+struct Reader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], UnknownCryptoError> {
+        if len > self.input.len() {
+            return Err(UnknownCryptoError);
+        }
+        let (taken, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, UnknownCryptoError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, UnknownCryptoError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn finish(self) -> Result<(), UnknownCryptoError> {
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A challenge from an origin, which a token is issued for.
+pub struct TokenChallenge {
+    token_type: u16,
+    issuer_name: Vec<u8>,
+    redemption_context: Vec<u8>,
+    origin_info: Vec<u8>,
+}
+
+impl TokenChallenge {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct a challenge for a token of `token_type` from `issuer_name`.
+    pub fn new(
+        token_type: u16,
+        issuer_name: &[u8],
+        redemption_context: &[u8],
+        origin_info: &[u8],
+    ) -> Result<Self, UnknownCryptoError> {
+        blinded_message_size(token_type)?;
+        if issuer_name.is_empty()
+            || issuer_name.len() > u16::MAX as usize
+            || !(redemption_context.is_empty()
+                || redemption_context.len() == REDEMPTION_CONTEXTSIZE)
+            || origin_info.len() > u16::MAX as usize
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            token_type,
+            issuer_name: issuer_name.to_vec(),
+            redemption_context: redemption_context.to_vec(),
+            origin_info: origin_info.to_vec(),
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decode a challenge from its wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut reader = Reader { input: bytes };
+        let token_type = reader.read_u16()?;
+        let len = reader.read_u16()?;
+        let issuer_name = reader.take(usize::from(len))?;
+        let len = reader.read_u8()?;
+        let redemption_context = reader.take(usize::from(len))?;
+        let len = reader.read_u16()?;
+        let origin_info = reader.take(usize::from(len))?;
+        reader.finish()?;
+
+        Self::new(token_type, issuer_name, redemption_context, origin_info)
+    }
+
+    /// Encode the challenge in its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            7 + self.issuer_name.len() + self.redemption_context.len() + self.origin_info.len(),
+        );
+        bytes.extend_from_slice(&self.token_type.to_be_bytes());
+        bytes.extend_from_slice(&(self.issuer_name.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.issuer_name);
+        bytes.push(self.redemption_context.len() as u8);
+        bytes.extend_from_slice(&self.redemption_context);
+        bytes.extend_from_slice(&(self.origin_info.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.origin_info);
+
+        bytes
+    }
+
+    /// Return the SHA-256 digest of the wire format of the challenge.
+    pub fn digest(&self) -> [u8; CHALLENGE_DIGESTSIZE] {
+        let mut digest = [0u8; CHALLENGE_DIGESTSIZE];
+        // This cannot fail, as the output has the size of a digest.
+        Sha256::digest_into(&self.to_bytes(), &mut digest).unwrap();
+
+        digest
+    }
+
+    /// Return the token type.
+    pub fn token_type(&self) -> u16 {
+        self.token_type
+    }
+
+    /// Return the name of the issuer.
+    pub fn issuer_name(&self) -> &[u8] {
+        &self.issuer_name
+    }
+
+    /// Return the redemption context, which may be empty.
+    pub fn redemption_context(&self) -> &[u8] {
+        &self.redemption_context
+    }
+
+    /// Return the origin info, which may be empty.
+    pub fn origin_info(&self) -> &[u8] {
+        &self.origin_info
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A token request, sent from the client to the issuer.
+pub struct TokenRequest {
+    token_type: u16,
+    truncated_token_key_id: u8,
+    blinded_message: Vec<u8>,
+}
+
+impl TokenRequest {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decode a token request from its wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut reader = Reader { input: bytes };
+        let token_type = reader.read_u16()?;
+        let truncated_token_key_id = reader.read_u8()?;
+        let blinded_message = reader.take(blinded_message_size(token_type)?)?;
+        reader.finish()?;
+
+        Ok(Self {
+            token_type,
+            truncated_token_key_id,
+            blinded_message: blinded_message.to_vec(),
+        })
+    }
+
+    /// Encode the token request in its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(3 + self.blinded_message.len());
+        bytes.extend_from_slice(&self.token_type.to_be_bytes());
+        bytes.push(self.truncated_token_key_id);
+        bytes.extend_from_slice(&self.blinded_message);
+
+        bytes
+    }
+
+    /// Return the token type.
+    pub fn token_type(&self) -> u16 {
+        self.token_type
+    }
+
+    /// Return the last byte of the ID of the issuer key that the request is
+    /// for.
+    pub fn truncated_token_key_id(&self) -> u8 {
+        self.truncated_token_key_id
+    }
+
+    /// Check that the request is of `token_type`, and for the key with
+    /// `token_key_id`, returning the blinded message.
+    fn blinded_message_for(
+        &self,
+        token_type: u16,
+        token_key_id: &[u8; TOKEN_KEY_IDSIZE],
+    ) -> Result<&[u8], UnknownCryptoError> {
+        if self.token_type != token_type
+            || self.truncated_token_key_id != token_key_id[TOKEN_KEY_IDSIZE - 1]
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(&self.blinded_message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A token response, sent from the issuer to the client. Its size depends on
+/// the token type, which is checked when the token is finalized.
+pub struct TokenResponse {
+    value: Vec<u8>,
+}
+
+impl TokenResponse {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decode a token response from its wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if bytes.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            value: bytes.to_vec(),
+        })
+    }
+
+    /// Encode the token response in its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.value.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A token, which the client redeems with the origin.
+pub struct Token {
+    token_input: Vec<u8>,
+    authenticator: Vec<u8>,
+}
+
+impl Token {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decode a token from its wire format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut reader = Reader { input: bytes };
+        let token_input = reader.take(TOKEN_INPUTSIZE)?;
+        let token_type = u16::from_be_bytes([token_input[0], token_input[1]]);
+        let authenticator = reader.take(authenticator_size(token_type)?)?;
+        reader.finish()?;
+
+        Ok(Self {
+            token_input: token_input.to_vec(),
+            authenticator: authenticator.to_vec(),
+        })
+    }
+
+    /// Encode the token in its wire format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(TOKEN_INPUTSIZE + self.authenticator.len());
+        bytes.extend_from_slice(&self.token_input);
+        bytes.extend_from_slice(&self.authenticator);
+
+        bytes
+    }
+
+    /// Return the token type.
+    pub fn token_type(&self) -> u16 {
+        u16::from_be_bytes([self.token_input[0], self.token_input[1]])
+    }
+
+    /// Return the nonce, which origins may use to detect tokens that are
+    /// redeemed more than once.
+    pub fn nonce(&self) -> &[u8] {
+        &self.token_input[2..2 + NONCESIZE]
+    }
+
+    /// Return the digest of the challenge that the token was issued for.
+    pub fn challenge_digest(&self) -> &[u8] {
+        &self.token_input[2 + NONCESIZE..2 + NONCESIZE + CHALLENGE_DIGESTSIZE]
+    }
+
+    /// Return the ID of the issuer key that the token was issued with.
+    pub fn token_key_id(&self) -> &[u8] {
+        &self.token_input[TOKEN_INPUTSIZE - TOKEN_KEY_IDSIZE..]
+    }
+
+    /// Return the authenticator.
+    pub fn authenticator(&self) -> &[u8] {
+        &self.authenticator
+    }
+
+    /// Check that the token is of `token_type`, for `challenge` and issued
+    /// with the key with `token_key_id`, returning the input that the
+    /// authenticator is computed over.
+    fn token_input_for(
+        &self,
+        token_type: u16,
+        challenge: &TokenChallenge,
+        token_key_id: &[u8; TOKEN_KEY_IDSIZE],
+    ) -> Result<&[u8], UnknownCryptoError> {
+        if self.token_type() != token_type
+            || challenge.token_type() != token_type
+            || self.challenge_digest() != challenge.digest()
+            || self.token_key_id() != token_key_id
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(&self.token_input)
+    }
+}
+
+/// Compute the input that the authenticator of a token is computed over.
+fn token_input(
+    token_type: u16,
+    nonce: &[u8],
+    challenge: &TokenChallenge,
+    token_key_id: &[u8; TOKEN_KEY_IDSIZE],
+) -> Result<[u8; TOKEN_INPUTSIZE], UnknownCryptoError> {
+    if nonce.len() != NONCESIZE || challenge.token_type() != token_type {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut input = [0u8; TOKEN_INPUTSIZE];
+    input[..2].copy_from_slice(&token_type.to_be_bytes());
+    input[2..2 + NONCESIZE].copy_from_slice(nonce);
+    input[2 + NONCESIZE..2 + NONCESIZE + CHALLENGE_DIGESTSIZE].copy_from_slice(&challenge.digest());
+    input[TOKEN_INPUTSIZE - TOKEN_KEY_IDSIZE..].copy_from_slice(token_key_id);
+
+    Ok(input)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_challenge_roundtrip() {
+        let challenge = TokenChallenge::new(
+            TOKEN_TYPE_BLIND_RSA,
+            b"issuer",
+            &[7u8; 32],
+            b"a.example,b.example",
+        )
+        .unwrap();
+        let bytes = challenge.to_bytes();
+        assert_eq!(&bytes[..2], &[0x00, 0x02]);
+        assert_eq!(&bytes[2..4], &[0x00, 0x06]);
+        assert_eq!(bytes[10], 32);
+        assert_eq!(TokenChallenge::from_bytes(&bytes).unwrap(), challenge);
+        assert_eq!(challenge.issuer_name(), b"issuer");
+        assert_eq!(challenge.redemption_context(), &[7u8; 32]);
+        assert_eq!(challenge.origin_info(), b"a.example,b.example");
+
+        let mut digest = [0u8; CHALLENGE_DIGESTSIZE];
+        Sha256::digest_into(&bytes, &mut digest).unwrap();
+        assert_eq!(challenge.digest(), digest);
+    }
+
+    #[test]
+    fn test_challenge_err() {
+        assert!(TokenChallenge::new(0x0003, b"issuer", &[], &[]).is_err());
+        assert!(TokenChallenge::new(TOKEN_TYPE_VOPRF, b"", &[], &[]).is_err());
+        assert!(TokenChallenge::new(TOKEN_TYPE_VOPRF, b"issuer", &[0u8; 31], &[]).is_err());
+        assert!(TokenChallenge::new(TOKEN_TYPE_VOPRF, b"issuer", &[0u8; 33], &[]).is_err());
+        assert!(TokenChallenge::new(TOKEN_TYPE_VOPRF, b"issuer", &[], &vec![0u8; 65536]).is_err());
+
+        let bytes = TokenChallenge::new(TOKEN_TYPE_VOPRF, b"issuer", &[], b"origin")
+            .unwrap()
+            .to_bytes();
+        assert!(TokenChallenge::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(TokenChallenge::from_bytes(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_token_request_err() {
+        let mut bytes = vec![0x00, 0x01, 0xAA];
+        bytes.extend_from_slice(&[0x02; ELEMENTSIZE]);
+        let request = TokenRequest::from_bytes(&bytes).unwrap();
+        assert_eq!(request.token_type(), TOKEN_TYPE_VOPRF);
+        assert_eq!(request.truncated_token_key_id(), 0xAA);
+        assert_eq!(request.to_bytes(), bytes);
+
+        assert!(TokenRequest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        bytes[1] = 0x02;
+        assert!(TokenRequest::from_bytes(&bytes).is_err());
+        bytes[1] = 0x03;
+        assert!(TokenRequest::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_token_accessors_and_err() {
+        let mut bytes = vec![0x00, 0x01];
+        bytes.extend_from_slice(&[1u8; NONCESIZE]);
+        bytes.extend_from_slice(&[2u8; CHALLENGE_DIGESTSIZE]);
+        bytes.extend_from_slice(&[3u8; TOKEN_KEY_IDSIZE]);
+        bytes.extend_from_slice(&[4u8; OUTPUTSIZE]);
+        let token = Token::from_bytes(&bytes).unwrap();
+        assert_eq!(token.token_type(), TOKEN_TYPE_VOPRF);
+        assert_eq!(token.nonce(), &[1u8; NONCESIZE]);
+        assert_eq!(token.challenge_digest(), &[2u8; CHALLENGE_DIGESTSIZE]);
+        assert_eq!(token.token_key_id(), &[3u8; TOKEN_KEY_IDSIZE]);
+        assert_eq!(token.authenticator(), &[4u8; OUTPUTSIZE][..]);
+        assert_eq!(token.to_bytes(), bytes);
+
+        assert!(Token::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        // The authenticator of a blind RSA token is larger.
+        bytes[1] = 0x02;
+        assert!(Token::from_bytes(&bytes).is_err());
+        assert!(TokenResponse::from_bytes(&[]).is_err());
+    }
+}
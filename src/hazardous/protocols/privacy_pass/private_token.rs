@@ -0,0 +1,194 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The issuance protocol for privately verifiable tokens of [RFC 9578],
+//! with the VOPRF of [`hazardous::ecc::voprf`] over P-384 and SHA-384.
+//!
+//! The token key ID is the SHA-256 digest of the compressed public key of
+//! the issuer, and the authenticator is the output of the VOPRF on the token
+//! input. Verifying a token requires the secret key of the issuer.
+//!
+//! # Parameters:
+//! - `public_key`: The public key of the issuer.
+//! - `secret_key`: The secret key of the issuer.
+//! - `challenge`: The challenge of the origin.
+//! - `nonce`: A [`NONCESIZE`]-byte nonce, chosen uniformly at random by the
+//!   client for every token.
+//! - `blind`: The secret blinding factor of the client, see
+//!   [`Blind::generate()`].
+//! - `proof_nonce`: The secret randomness of the proof of the issuer, see
+//!   [`ProofNonce::generate()`].
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `challenge` is not for [`TOKEN_TYPE_VOPRF`].
+//! - `nonce` is not [`NONCESIZE`] bytes.
+//! - The token request or token is of another token type, or for another
+//!   key.
+//! - The token response does not consist of a valid evaluated element and
+//!   proof, or the proof does not verify.
+//! - The token is not for `challenge`, or its authenticator does not match.
+//!
+//! # Security:
+//! - `blind` and `proof_nonce` must never be reused.
+//!
+//! # Example:
+//! See the [module documentation] of Privacy Pass.
+//!
+//! [RFC 9578]: https://www.rfc-editor.org/rfc/rfc9578#section-5
+//! [`hazardous::ecc::voprf`]: ../../../ecc/voprf/index.html
+//! [`NONCESIZE`]: ../constant.NONCESIZE.html
+//! [`TOKEN_TYPE_VOPRF`]: ../constant.TOKEN_TYPE_VOPRF.html
+//! [`Blind::generate()`]: ../../../ecc/voprf/struct.Blind.html#method.generate
+//! [`ProofNonce::generate()`]: ../../../ecc/voprf/struct.ProofNonce.html#method.generate
+//! [module documentation]: ../index.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use super::{
+    token_input, Token, TokenChallenge, TokenRequest, TokenResponse, TOKEN_INPUTSIZE,
+    TOKEN_KEY_IDSIZE, TOKEN_TYPE_VOPRF,
+};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::ecc::voprf::{
+    self, Blind, BlindedElement, EvaluatedElement, Proof, ProofNonce, PublicKey, SecretKey,
+    ELEMENTSIZE, OUTPUTSIZE,
+};
+use crate::hazardous::hash::sha256::Sha256;
+use zeroize::Zeroize;
+
+/// Return the ID of `public_key`, the SHA-256 digest of its encoding.
+pub fn token_key_id(public_key: &PublicKey) -> [u8; TOKEN_KEY_IDSIZE] {
+    let mut id = [0u8; TOKEN_KEY_IDSIZE];
+    // This cannot fail, as the output has the size of a digest.
+    Sha256::digest_into(public_key.as_ref(), &mut id).unwrap();
+
+    id
+}
+
+/// The state that the client keeps between creating a token request and
+/// finalizing the token.
+pub struct ClientState {
+    token_input: [u8; TOKEN_INPUTSIZE],
+    blind: Blind,
+    blinded_element: BlindedElement,
+    public_key: PublicKey,
+}
+
+impl_omitted_debug_trait!(ClientState);
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Create a token request for `challenge` to the issuer with `public_key`,
+/// as the client.
+pub fn create_token_request(
+    public_key: &PublicKey,
+    challenge: &TokenChallenge,
+    nonce: &[u8],
+    blind: Blind,
+) -> Result<(TokenRequest, ClientState), UnknownCryptoError> {
+    let token_key_id = token_key_id(public_key);
+    let token_input = token_input(TOKEN_TYPE_VOPRF, nonce, challenge, &token_key_id)?;
+    let blinded_element = voprf::blind(&token_input, &blind)?;
+
+    let request = TokenRequest {
+        token_type: TOKEN_TYPE_VOPRF,
+        truncated_token_key_id: token_key_id[TOKEN_KEY_IDSIZE - 1],
+        blinded_message: blinded_element.as_ref().to_vec(),
+    };
+    let state = ClientState {
+        token_input,
+        blind,
+        blinded_element,
+        public_key: *public_key,
+    };
+
+    Ok((request, state))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Evaluate `request` with `secret_key`, as the issuer.
+pub fn issue_token_response(
+    secret_key: &SecretKey,
+    request: &TokenRequest,
+    proof_nonce: &ProofNonce,
+) -> Result<TokenResponse, UnknownCryptoError> {
+    let token_key_id = token_key_id(&secret_key.public_key()?);
+    let blinded_message = request.blinded_message_for(TOKEN_TYPE_VOPRF, &token_key_id)?;
+    let blinded_element = BlindedElement::from_slice(blinded_message)?;
+    let (evaluated_element, proof) =
+        voprf::blind_evaluate(secret_key, &blinded_element, proof_nonce)?;
+
+    let mut value = Vec::with_capacity(ELEMENTSIZE + voprf::PROOFSIZE);
+    value.extend_from_slice(evaluated_element.as_ref());
+    value.extend_from_slice(proof.as_ref());
+
+    Ok(TokenResponse { value })
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify `response` and compute the token from it, as the client.
+pub fn finalize_token(
+    state: &ClientState,
+    response: &TokenResponse,
+) -> Result<Token, UnknownCryptoError> {
+    if response.value.len() != ELEMENTSIZE + voprf::PROOFSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (evaluated_element, proof) = response.value.split_at(ELEMENTSIZE);
+    let mut authenticator = vec![0u8; OUTPUTSIZE];
+    voprf::finalize(
+        &state.token_input,
+        &state.blind,
+        &EvaluatedElement::from_slice(evaluated_element)?,
+        &state.blinded_element,
+        &state.public_key,
+        &Proof::from_slice(proof)?,
+        &mut authenticator,
+    )?;
+
+    Ok(Token {
+        token_input: state.token_input.to_vec(),
+        authenticator,
+    })
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `token` was issued for `challenge` with `secret_key`, as the
+/// origin.
+pub fn verify_token(
+    secret_key: &SecretKey,
+    challenge: &TokenChallenge,
+    token: &Token,
+) -> Result<(), UnknownCryptoError> {
+    let token_key_id = token_key_id(&secret_key.public_key()?);
+    let token_input = token.token_input_for(TOKEN_TYPE_VOPRF, challenge, &token_key_id)?;
+
+    let mut expected = [0u8; OUTPUTSIZE];
+    voprf::evaluate(secret_key, token_input, &mut expected)?;
+    let result = crate::util::secure_cmp(&expected, &token.authenticator);
+    expected.zeroize();
+
+    result
+}
@@ -0,0 +1,348 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The issuance protocol for publicly verifiable tokens of [RFC 9578], with
+//! the `RSABSSA-SHA384-PSS-Deterministic` RSA blind signatures of
+//! [RFC 9474] and a 2048-bit modulus.
+//!
+//! The public key of the issuer is encoded as a SubjectPublicKeyInfo with
+//! the RSASSA-PSS algorithm, SHA-384 and a 48-byte salt, and the token key ID
+//! is the SHA-256 digest of that encoding. The authenticator is an
+//! RSASSA-PSS signature of the token input, which anyone can verify with the
+//! public key.
+//!
+//! # Parameters:
+//! - `public_key`: The public key of the issuer, with a
+//!   [`BLIND_RSA_MODULUSSIZE`]-byte modulus.
+//! - `secret_key`: The secret key of the issuer.
+//! - `challenge`: The challenge of the origin.
+//! - `nonce`: A [`NONCESIZE`]-byte nonce, chosen uniformly at random by the
+//!   client for every token.
+//! - `salt`: A [`SALTSIZE`]-byte salt, chosen uniformly at random by the
+//!   client for every token.
+//! - `blinding_seed`: A secret [`BLINDING_SEEDSIZE`]-byte seed that the
+//!   blinding factor is derived from, chosen uniformly at random by the
+//!   client for every token.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The modulus of `public_key` or `secret_key` is not
+//!   [`BLIND_RSA_MODULUSSIZE`] bytes.
+//! - `challenge` is not for [`TOKEN_TYPE_BLIND_RSA`].
+//! - `nonce`, `salt` or `blinding_seed` is not of the sizes above.
+//! - The token request or token is of another token type, or for another
+//!   key.
+//! - The token response does not unblind to a valid signature.
+//! - The token is not for `challenge`, or its authenticator is not a valid
+//!   signature.
+//! - The encoded public key is not the exact encoding described above.
+//!
+//! # Security:
+//! - `salt` and `blinding_seed` must never be reused. A reused blinding
+//!   seed lets the issuer link the token to its token request.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::privacy_pass::{
+//!     public_token, TokenChallenge, NONCESIZE, TOKEN_TYPE_BLIND_RSA,
+//! };
+//! use orion::hazardous::rsa::keys::SecretKey;
+//! # let der = std::fs::read("./tests/test_data/rsa/rsa2048_e65537.pk8.der")?;
+//!
+//! // Issuer, which publishes the encoded public key
+//! let secret_key = SecretKey::from_pkcs8_der(&der)?;
+//! let encoded_public_key = public_token::encode_public_key(&secret_key.public_key())?;
+//!
+//! // Origin
+//! let challenge = TokenChallenge::new(TOKEN_TYPE_BLIND_RSA, b"issuer.example", &[], &[])?;
+//!
+//! // Client
+//! let public_key = public_token::decode_public_key(&encoded_public_key)?;
+//! let mut nonce = [0u8; NONCESIZE];
+//! let mut salt = [0u8; public_token::SALTSIZE];
+//! let mut blinding_seed = [0u8; public_token::BLINDING_SEEDSIZE];
+//! orion::util::secure_rand_bytes(&mut nonce)?;
+//! orion::util::secure_rand_bytes(&mut salt)?;
+//! orion::util::secure_rand_bytes(&mut blinding_seed)?;
+//! let (request, state) = public_token::create_token_request(
+//!     &public_key,
+//!     &challenge,
+//!     &nonce,
+//!     &salt,
+//!     &blinding_seed,
+//! )?;
+//!
+//! // Issuer
+//! let response = public_token::issue_token_response(&secret_key, &request)?;
+//!
+//! // Client
+//! let token = public_token::finalize_token(&state, &response)?;
+//!
+//! // Origin
+//! assert!(public_token::verify_token(&public_key, &challenge, &token).is_ok());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//! [RFC 9578]: https://www.rfc-editor.org/rfc/rfc9578#section-6
+//! [RFC 9474]: https://www.rfc-editor.org/rfc/rfc9474
+//! [`BLIND_RSA_MODULUSSIZE`]: ../constant.BLIND_RSA_MODULUSSIZE.html
+//! [`NONCESIZE`]: ../constant.NONCESIZE.html
+//! [`TOKEN_TYPE_BLIND_RSA`]: ../constant.TOKEN_TYPE_BLIND_RSA.html
+//! [`SALTSIZE`]: constant.SALTSIZE.html
+//! [`BLINDING_SEEDSIZE`]: constant.BLINDING_SEEDSIZE.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use super::{
+    token_input, Token, TokenChallenge, TokenRequest, TokenResponse, BLIND_RSA_MODULUSSIZE,
+    TOKEN_INPUTSIZE, TOKEN_KEY_IDSIZE, TOKEN_TYPE_BLIND_RSA,
+};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::Sha256;
+use crate::hazardous::rsa::bigint;
+use crate::hazardous::rsa::blind::{self, Variant};
+use crate::hazardous::rsa::keys::{PublicKey, SecretKey};
+use crate::util::der::{DerReader, TAG_BIT_STRING, TAG_INTEGER, TAG_SEQUENCE};
+use zeroize::Zeroize;
+
+/// The size of the salt of a signature.
+pub const SALTSIZE: usize = blind::SALTSIZE;
+/// The variant of RSA blind signatures of this token type.
+const VARIANT: Variant = Variant::Sha384PssDeterministic;
+/// The size of the seed that the blinding factor is derived from.
+pub const BLINDING_SEEDSIZE: usize = blind::BLINDING_SEEDSIZE;
+
+/// The contents of the AlgorithmIdentifier of RSASSA-PSS, OID
+/// 1.2.840.113549.1.1.10, with SHA-384 as the hash function, MGF1 with
+/// SHA-384 as the mask generation function and a 48-byte salt.
+const RSASSA_PSS_SHA384_ALGORITHM: [u8; 61] = [
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a, 0x30, 0x30, 0xa0, 0x0d, 0x30,
+    0x0b, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0xa1, 0x1a, 0x30, 0x18,
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x08, 0x30, 0x0b, 0x06, 0x09, 0x60,
+    0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0xa2, 0x03, 0x02, 0x01, 0x30,
+];
+
+/// Append the DER encoding of a TLV with `tag` and `value` to `out`.
+fn push_tlv(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    match value.len() {
+        len @ 0x00..=0x7F => out.push(len as u8),
+        len @ 0x80..=0xFF => out.extend_from_slice(&[0x81, len as u8]),
+        len => out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+    }
+    out.extend_from_slice(value);
+}
+
+/// Append the DER encoding of the non-negative big-endian `value` to `out`.
+fn push_unsigned_integer(out: &mut Vec<u8>, value: &[u8]) {
+    let zeroes = value.iter().take_while(|b| **b == 0).count();
+    let value = &value[core::cmp::min(zeroes, value.len() - 1)..];
+    let mut integer = Vec::with_capacity(value.len() + 1);
+    if value[0] & 0x80 != 0 {
+        integer.push(0);
+    }
+    integer.extend_from_slice(value);
+
+    push_tlv(out, TAG_INTEGER, &integer);
+}
+
+/// Return an error if the modulus of `public_key` is not
+/// `BLIND_RSA_MODULUSSIZE` bytes.
+fn check_size(public_key: &PublicKey) -> Result<(), UnknownCryptoError> {
+    if public_key.size() != BLIND_RSA_MODULUSSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encode `public_key` as a DER-encoded SubjectPublicKeyInfo with the
+/// RSASSA-PSS algorithm and its parameters for this token type.
+pub fn encode_public_key(public_key: &PublicKey) -> Result<Vec<u8>, UnknownCryptoError> {
+    check_size(public_key)?;
+
+    let mut modulus = [0u8; BLIND_RSA_MODULUSSIZE];
+    bigint::to_be_bytes(public_key.modulus().limbs(), &mut modulus);
+    let mut integers = Vec::new();
+    push_unsigned_integer(&mut integers, &modulus);
+    push_unsigned_integer(&mut integers, &public_key.exponent().to_be_bytes());
+    // There are no unused bits in the BIT STRING.
+    let mut rsa_public_key = vec![0u8];
+    push_tlv(&mut rsa_public_key, TAG_SEQUENCE, &integers);
+
+    let mut contents = Vec::new();
+    push_tlv(&mut contents, TAG_SEQUENCE, &RSASSA_PSS_SHA384_ALGORITHM);
+    push_tlv(&mut contents, TAG_BIT_STRING, &rsa_public_key);
+    let mut spki = Vec::new();
+    push_tlv(&mut spki, TAG_SEQUENCE, &contents);
+
+    Ok(spki)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decode a public key from the encoding of [`encode_public_key()`].
+///
+/// [`encode_public_key()`]: fn.encode_public_key.html
+pub fn decode_public_key(der: &[u8]) -> Result<PublicKey, UnknownCryptoError> {
+    let mut outer = DerReader::new(der);
+    let mut spki = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    if spki.read(TAG_SEQUENCE)? != RSASSA_PSS_SHA384_ALGORITHM {
+        return Err(UnknownCryptoError);
+    }
+    let bit_string = spki.read(TAG_BIT_STRING)?;
+    spki.finish()?;
+
+    // There must be no unused bits.
+    let rsa_public_key = match bit_string {
+        [0, rest @ ..] => rest,
+        _ => return Err(UnknownCryptoError),
+    };
+    let mut outer = DerReader::new(rsa_public_key);
+    let mut integers = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    let n = integers.read_unsigned_integer()?;
+    let e = integers.read_unsigned_integer()?;
+    integers.finish()?;
+
+    let public_key = PublicKey::from_components(n, e)?;
+    check_size(&public_key)?;
+
+    Ok(public_key)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Return the ID of `public_key`, the SHA-256 digest of its encoding.
+pub fn token_key_id(public_key: &PublicKey) -> Result<[u8; TOKEN_KEY_IDSIZE], UnknownCryptoError> {
+    let mut id = [0u8; TOKEN_KEY_IDSIZE];
+    Sha256::digest_into(&encode_public_key(public_key)?, &mut id)?;
+
+    Ok(id)
+}
+
+/// The state that the client keeps between creating a token request and
+/// finalizing the token.
+pub struct ClientState {
+    token_input: [u8; TOKEN_INPUTSIZE],
+    public_key: PublicKey,
+    inverse: Vec<u8>,
+}
+
+impl_omitted_debug_trait!(ClientState);
+
+impl Drop for ClientState {
+    fn drop(&mut self) {
+        self.inverse.iter_mut().zeroize();
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Create a token request for `challenge` to the issuer with `public_key`,
+/// as the client.
+pub fn create_token_request(
+    public_key: &PublicKey,
+    challenge: &TokenChallenge,
+    nonce: &[u8],
+    salt: &[u8],
+    blinding_seed: &[u8],
+) -> Result<(TokenRequest, ClientState), UnknownCryptoError> {
+    let token_key_id = token_key_id(public_key)?;
+    let token_input = token_input(TOKEN_TYPE_BLIND_RSA, nonce, challenge, &token_key_id)?;
+
+    let mut blinded_message = vec![0u8; BLIND_RSA_MODULUSSIZE];
+    let mut inverse = vec![0u8; BLIND_RSA_MODULUSSIZE];
+    blind::blind(
+        public_key,
+        VARIANT,
+        &token_input,
+        salt,
+        blinding_seed,
+        &mut blinded_message,
+        &mut inverse,
+    )?;
+
+    let request = TokenRequest {
+        token_type: TOKEN_TYPE_BLIND_RSA,
+        truncated_token_key_id: token_key_id[TOKEN_KEY_IDSIZE - 1],
+        blinded_message,
+    };
+    let state = ClientState {
+        token_input,
+        public_key: public_key.clone(),
+        inverse,
+    };
+
+    Ok((request, state))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign the blinded message of `request` with `secret_key`, as the issuer.
+pub fn issue_token_response(
+    secret_key: &SecretKey,
+    request: &TokenRequest,
+) -> Result<TokenResponse, UnknownCryptoError> {
+    let token_key_id = token_key_id(&secret_key.public_key())?;
+    let blinded_message = request.blinded_message_for(TOKEN_TYPE_BLIND_RSA, &token_key_id)?;
+
+    let mut value = vec![0u8; BLIND_RSA_MODULUSSIZE];
+    blind::blind_sign(secret_key, blinded_message, &mut value)?;
+
+    Ok(TokenResponse { value })
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Unblind the signature in `response` and verify it, as the client.
+pub fn finalize_token(
+    state: &ClientState,
+    response: &TokenResponse,
+) -> Result<Token, UnknownCryptoError> {
+    let mut authenticator = vec![0u8; BLIND_RSA_MODULUSSIZE];
+    blind::finalize(
+        &state.public_key,
+        VARIANT,
+        &state.token_input,
+        &response.value,
+        &state.inverse,
+        &mut authenticator,
+    )?;
+
+    Ok(Token {
+        token_input: state.token_input.to_vec(),
+        authenticator,
+    })
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `token` was issued for `challenge` with the secret key of
+/// `public_key`, as the origin.
+pub fn verify_token(
+    public_key: &PublicKey,
+    challenge: &TokenChallenge,
+    token: &Token,
+) -> Result<(), UnknownCryptoError> {
+    let token_key_id = token_key_id(public_key)?;
+    let token_input = token.token_input_for(TOKEN_TYPE_BLIND_RSA, challenge, &token_key_id)?;
+
+    blind::verify(public_key, VARIANT, token_input, &token.authenticator)
+}
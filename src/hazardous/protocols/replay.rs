@@ -0,0 +1,326 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sliding window replay protection for message counters.
+//!
+//! # About:
+//! A [`ReplayWindow`] keeps track of which message counters of a session have
+//! already been received, so that a datagram protocol can reject messages
+//! that an attacker has recorded and sends again. Messages may arrive out of
+//! order, as long as their counter is within [`WINDOW_SIZE`] of the greatest
+//! counter received so far.
+//!
+//! The window is a ring of bits as described in [RFC 6479], the same approach
+//! as used by WireGuard and IPsec. It needs no allocation and is usable in a
+//! `no_std` context.
+//!
+//! [`ReplayWindow::check()`] tells whether a counter would be accepted,
+//! without changing the window. [`ReplayWindow::update()`] checks a counter
+//! again and marks it as received.
+//!
+//! # Parameters:
+//! - `counter`: The counter, nonce or packet index of a received message.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `counter` has already been marked as received.
+//! - `counter` is [`WINDOW_SIZE`] or more below the greatest counter marked as
+//!   received, so it can no longer be told whether it was received.
+//!
+//! # Security:
+//! - A counter must only be passed to [`ReplayWindow::update()`] after the
+//!   message it belongs to has been authenticated. Otherwise, an attacker can
+//!   forge messages with large counters to move the window forward and make
+//!   the session reject all genuine messages.
+//! - Use [`ReplayWindow::check()`] before authenticating, to avoid the work of
+//!   decrypting messages that would be rejected anyway.
+//! - A [`ReplayWindow`] belongs to a single key and sender. Use a new one,
+//!   or [`ReplayWindow::reset()`], whenever the keys of a session change.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::replay::ReplayWindow;
+//!
+//! let mut window = ReplayWindow::new();
+//!
+//! // Messages arriving out of order are accepted.
+//! window.update(2)?;
+//! window.update(0)?;
+//! window.update(1)?;
+//!
+//! // Messages received before are not.
+//! assert!(window.check(1).is_err());
+//! assert!(window.update(2).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 6479]: https://www.rfc-editor.org/rfc/rfc6479
+//! [`ReplayWindow`]: struct.ReplayWindow.html
+//! [`ReplayWindow::check()`]: struct.ReplayWindow.html#method.check
+//! [`ReplayWindow::update()`]: struct.ReplayWindow.html#method.update
+//! [`ReplayWindow::reset()`]: struct.ReplayWindow.html#method.reset
+//! [`WINDOW_SIZE`]: constant.WINDOW_SIZE.html
+
+use crate::errors::UnknownCryptoError;
+
+/// The amount of bits in each word of the ring.
+const WORD_BITS: u64 = 64;
+
+/// The amount of words in the ring.
+const WORDS: u64 = 32;
+
+/// The amount of counters below the greatest counter received, including it,
+/// that are kept track of. One word of the ring is always being cleared for
+/// reuse, so it is not part of the window.
+pub const WINDOW_SIZE: u64 = (WORDS - 1) * WORD_BITS;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A sliding window of received message counters.
+pub struct ReplayWindow {
+    ring: [u64; WORDS as usize],
+    greatest: Option<u64>,
+}
+
+impl ReplayWindow {
+    /// Create a window in which no counter has been received.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Check whether `counter` would be accepted, without marking it as received.
+    pub fn check(&self, counter: u64) -> Result<(), UnknownCryptoError> {
+        let greatest = match self.greatest {
+            Some(greatest) if counter <= greatest => greatest,
+            _ => return Ok(()),
+        };
+        if greatest - counter >= WINDOW_SIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let (word, bit) = Self::position(counter);
+        if self.ring[word] & bit != 0 {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Check whether `counter` is accepted, and if so, mark it as received.
+    pub fn update(&mut self, counter: u64) -> Result<(), UnknownCryptoError> {
+        self.check(counter)?;
+
+        match self.greatest {
+            Some(greatest) if counter <= greatest => (),
+            Some(greatest) => {
+                // Clear the words that the window slides onto, at most the
+                // whole ring, since they hold counters from a previous lap.
+                let current = greatest / WORD_BITS;
+                let steps = core::cmp::min(counter / WORD_BITS - current, WORDS);
+                for step in 1..=steps {
+                    self.ring[((current + step) % WORDS) as usize] = 0;
+                }
+                self.greatest = Some(counter);
+            }
+            None => self.greatest = Some(counter),
+        }
+
+        let (word, bit) = Self::position(counter);
+        self.ring[word] |= bit;
+
+        Ok(())
+    }
+
+    /// The greatest counter marked as received, if any.
+    pub fn greatest(&self) -> Option<u64> {
+        self.greatest
+    }
+
+    /// Forget all received counters.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The index of the word in the ring and the bit within it for `counter`.
+    fn position(counter: u64) -> (usize, u64) {
+        (
+            ((counter / WORD_BITS) % WORDS) as usize,
+            1u64 << (counter % WORD_BITS),
+        )
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_new_is_empty() {
+        let window = ReplayWindow::new();
+        assert_eq!(window.greatest(), None);
+        assert!(window.check(0).is_ok());
+        assert!(window.check(u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_in_order() {
+        let mut window = ReplayWindow::new();
+        for counter in 0..10_000 {
+            assert!(window.check(counter).is_ok());
+            assert!(window.update(counter).is_ok());
+            assert!(window.check(counter).is_err());
+            assert!(window.update(counter).is_err());
+            assert_eq!(window.greatest(), Some(counter));
+        }
+    }
+
+    #[test]
+    fn test_out_of_order_within_window() {
+        let mut window = ReplayWindow::new();
+        assert!(window.update(WINDOW_SIZE + 100).is_ok());
+
+        for counter in (101..WINDOW_SIZE + 100).rev() {
+            assert!(window.update(counter).is_ok());
+        }
+        for counter in 101..=WINDOW_SIZE + 100 {
+            assert!(window.update(counter).is_err());
+        }
+        assert_eq!(window.greatest(), Some(WINDOW_SIZE + 100));
+    }
+
+    #[test]
+    fn test_window_boundary() {
+        let mut window = ReplayWindow::new();
+        let greatest = 5 * WINDOW_SIZE;
+        assert!(window.update(greatest).is_ok());
+
+        // The oldest counter still in the window.
+        assert!(window.check(greatest - (WINDOW_SIZE - 1)).is_ok());
+        // The first one that is too old.
+        assert!(window.check(greatest - WINDOW_SIZE).is_err());
+        assert!(window.update(greatest - WINDOW_SIZE).is_err());
+        assert!(window.check(0).is_err());
+    }
+
+    #[test]
+    fn test_check_does_not_update() {
+        let mut window = ReplayWindow::new();
+        assert!(window.update(10).is_ok());
+        let before = window.clone();
+
+        assert!(window.check(5).is_ok());
+        assert!(window.check(5000).is_ok());
+        assert_eq!(window, before);
+        assert!(window.update(5).is_ok());
+    }
+
+    #[test]
+    fn test_rejected_update_does_not_update() {
+        let mut window = ReplayWindow::new();
+        assert!(window.update(5000).is_ok());
+        let before = window.clone();
+
+        assert!(window.update(5000).is_err());
+        assert!(window.update(0).is_err());
+        assert_eq!(window, before);
+    }
+
+    #[test]
+    fn test_jump_clears_previous_lap() {
+        let mut window = ReplayWindow::new();
+        for counter in 0..64 {
+            assert!(window.update(counter).is_ok());
+        }
+
+        // Slides exactly one lap of the ring forward, so the first word is
+        // reused for the counters it now holds.
+        let lap = WORDS * WORD_BITS;
+        assert!(window.update(lap).is_ok());
+        for counter in lap + 1..lap + 64 {
+            assert!(window.check(counter).is_ok());
+        }
+        // Counters in between were never received.
+        for counter in lap - (WINDOW_SIZE - 1)..lap {
+            assert!(window.check(counter).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_large_jump() {
+        let mut window = ReplayWindow::new();
+        for counter in 0..WINDOW_SIZE {
+            assert!(window.update(counter).is_ok());
+        }
+
+        assert!(window.update(u64::MAX - 1).is_ok());
+        assert!(window.check(u64::MAX - 2).is_ok());
+        assert!(window.update(u64::MAX).is_ok());
+        assert!(window.update(u64::MAX).is_err());
+        assert!(window.update(u64::MAX - 1).is_err());
+        for counter in u64::MAX - (WINDOW_SIZE - 1)..u64::MAX - 1 {
+            assert!(window.check(counter).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut window = ReplayWindow::new();
+        assert!(window.update(100).is_ok());
+        window.reset();
+
+        assert_eq!(window, ReplayWindow::new());
+        assert!(window.update(100).is_ok());
+        assert!(window.update(0).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_against_naive_window() {
+        use std::collections::HashSet;
+
+        let mut window = ReplayWindow::new();
+        let mut received = HashSet::new();
+        let mut greatest = 0u64;
+        // A simple LCG gives a reproducible mix of replays, reordering and jumps.
+        let mut state = 0x853c_49e6_748f_ea9bu64;
+
+        for _ in 0..200_000 {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let offset = (state >> 33) % (2 * WINDOW_SIZE + 200);
+            let counter = (greatest + 150).saturating_sub(offset);
+
+            let expected = if received.contains(&counter)
+                || (!received.is_empty() && counter + WINDOW_SIZE <= greatest)
+            {
+                false
+            } else {
+                received.insert(counter);
+                greatest = core::cmp::max(greatest, counter);
+                true
+            };
+            assert_eq!(window.update(counter).is_ok(), expected);
+        }
+    }
+}
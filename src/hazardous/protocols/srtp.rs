@@ -0,0 +1,900 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! SRTP and SRTCP key derivation and packet protection.
+//!
+//! # About:
+//! This implements the SRTP key derivation function and packet protection of
+//! [RFC 3711], with the protection profiles that WebRTC uses:
+//! - `AES_CM_128_HMAC_SHA1_80` and `AES_CM_128_HMAC_SHA1_32`, which encrypt
+//!   with AES-128 in counter mode and authenticate with HMAC-SHA1 truncated
+//!   to 80 or 32 bits, as registered for DTLS-SRTP in [RFC 5764].
+//! - `AEAD_AES_128_GCM` and `AEAD_AES_256_GCM`, which encrypt and
+//!   authenticate with AES-GCM, as specified in [RFC 7714].
+//!
+//! A [`Context`] derives the session keys for both SRTP and SRTCP from the
+//! master key and master salt once, with a key derivation rate of zero, so
+//! that protecting a packet does not derive or expand any keys.
+//!
+//! Packets are protected and unprotected in place. A buffer passed to
+//! [`Context::protect_rtp()`] holds the RTP packet followed by
+//! [`Profile::rtp_tag_size()`] bytes for the authentication tag, and a buffer
+//! passed to [`Context::protect_rtcp()`] holds the RTCP packet followed by
+//! [`Profile::rtcp_trailer_size()`] bytes for the SRTCP index and the
+//! authentication tag. Unprotecting returns the length of the packet without
+//! these.
+//!
+//! # Parameters:
+//! - `profile`: The SRTP protection profile.
+//! - `master_key`: The master key, which is [`Profile::master_key_size()`] bytes.
+//! - `master_salt`: The master salt, which is [`Profile::master_salt_size()`]
+//!   bytes.
+//! - `packet`: The packet, followed by space for, or the contents of, the
+//!   authentication tag and, for SRTCP, the SRTCP index.
+//! - `roc`: The rollover counter of the SRTP stream, which together with the
+//!   sequence number forms the packet index.
+//! - `index`: The SRTCP index, which must be below 2^31.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `master_key` or `master_salt` is not the size of the `profile`.
+//! - `packet` is too short to hold the tag, or the SRTCP index and tag.
+//! - The RTP or RTCP packet is not version 2, or its header, CSRC list or
+//!   header extension is longer than the packet.
+//! - `index` is 2^31 or above.
+//! - The E flag of an SRTCP packet is unset, since unencrypted SRTCP packets
+//!   are not supported.
+//! - The authentication tag is invalid when unprotecting.
+//!
+//! # Security:
+//! - A master key and master salt must only be used with one [`Context`], and
+//!   must be replaced before 2^48 SRTP or 2^31 SRTCP packets are protected.
+//!   Each packet index or SRTCP index must only be used once per SSRC, as the
+//!   keystream is derived from it.
+//! - Replay protection is not part of a [`Context`]. After a packet has been
+//!   unprotected, its index must be checked against a replay list, such as a
+//!   [`ReplayWindow`] for each SSRC, before it is used.
+//! - The rollover counter of a received stream is not authenticated on its
+//!   own. Use [`estimate_roc()`] to determine it from the highest received
+//!   sequence number, and only update the highest received sequence number
+//!   and rollover counter once the packet has been unprotected.
+//! - RTP header extensions are authenticated but not encrypted, see RFC 6904.
+//!   Master Key Identifiers (MKI) are not supported.
+//! - For the AEAD profiles, the 12-byte master salt is padded with two zero
+//!   bytes for key derivation, as done by libsrtp.
+//! - AES is a constant-time implementation without hardware acceleration, see
+//!   [`aes`].
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::srtp::{Context, Profile};
+//!
+//! let profile = Profile::AeadAes128Gcm;
+//! let master_key = [0x11u8; 16];
+//! let master_salt = [0x22u8; 12];
+//! let sender = Context::new(profile, &master_key, &master_salt)?;
+//! let receiver = Context::new(profile, &master_key, &master_salt)?;
+//!
+//! // A 12-byte RTP header and a 4-byte payload, followed by space for the tag.
+//! let mut packet = [0u8; 16 + 16];
+//! packet[..12].copy_from_slice(&[
+//!     0x80, 0x60, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0xca, 0xfe, 0xba, 0xbe,
+//! ]);
+//! packet[12..16].copy_from_slice(b"data");
+//! assert_eq!(packet.len(), 16 + profile.rtp_tag_size());
+//!
+//! sender.protect_rtp(&mut packet, 0)?;
+//! let len = receiver.unprotect_rtp(&mut packet, 0)?;
+//! assert_eq!(&packet[12..len], b"data");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 3711]: https://www.rfc-editor.org/rfc/rfc3711
+//! [RFC 5764]: https://www.rfc-editor.org/rfc/rfc5764
+//! [RFC 7714]: https://www.rfc-editor.org/rfc/rfc7714
+//! [`Context`]: struct.Context.html
+//! [`ReplayWindow`]: ../replay/struct.ReplayWindow.html
+//! [`Context::protect_rtp()`]: struct.Context.html#method.protect_rtp
+//! [`Context::protect_rtcp()`]: struct.Context.html#method.protect_rtcp
+//! [`Profile::rtp_tag_size()`]: enum.Profile.html#method.rtp_tag_size
+//! [`Profile::rtcp_trailer_size()`]: enum.Profile.html#method.rtcp_trailer_size
+//! [`Profile::master_key_size()`]: enum.Profile.html#method.master_key_size
+//! [`Profile::master_salt_size()`]: enum.Profile.html#method.master_salt_size
+//! [`estimate_roc()`]: fn.estimate_roc.html
+//! [`aes`]: ../../block/aes/index.html
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::aead::aes_gcm::{ctr32_xor, AesGcm, GCM_NONCESIZE, GCM_TAGSIZE};
+use crate::hazardous::block::aes::{self, Aes, AES_BLOCKSIZE};
+use crate::hazardous::hash::sha1::{Sha1, SHA1_OUTSIZE};
+use crate::hazardous::mac::hmac::HmacGeneric;
+use zeroize::Zeroize;
+
+/// The size of the RTP header without CSRCs or a header extension.
+pub const RTP_HEADER_SIZE: usize = 12;
+/// The size of the RTCP header that is not encrypted.
+pub const RTCP_HEADER_SIZE: usize = 8;
+/// The size of the E flag and SRTCP index in an SRTCP packet.
+pub const SRTCP_INDEX_SIZE: usize = 4;
+
+/// The size of the master salt and session salt for AES counter mode.
+const CM_SALT_SIZE: usize = 14;
+/// The size of the HMAC-SHA1 session authentication key.
+const AUTH_KEY_SIZE: usize = 20;
+/// The size of the SRTCP authentication tag for both HMAC-SHA1 profiles.
+const CM_RTCP_TAG_SIZE: usize = 10;
+/// The E flag of the SRTCP index, which is set when the packet is encrypted.
+const SRTCP_E_FLAG: u32 = 1 << 31;
+
+/// The labels of the SRTP key derivation function, from RFC 3711 section 4.3.
+const LABEL_RTP_ENCRYPTION: u8 = 0x00;
+const LABEL_RTP_AUTH: u8 = 0x01;
+const LABEL_RTP_SALT: u8 = 0x02;
+const LABEL_RTCP_ENCRYPTION: u8 = 0x03;
+const LABEL_RTCP_AUTH: u8 = 0x04;
+const LABEL_RTCP_SALT: u8 = 0x05;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An SRTP protection profile.
+pub enum Profile {
+    /// `SRTP_AES128_CM_HMAC_SHA1_80` from RFC 5764.
+    Aes128CmHmacSha1_80,
+    /// `SRTP_AES128_CM_HMAC_SHA1_32` from RFC 5764. The SRTCP tag is still 80
+    /// bits.
+    Aes128CmHmacSha1_32,
+    /// `SRTP_AEAD_AES_128_GCM` from RFC 7714.
+    AeadAes128Gcm,
+    /// `SRTP_AEAD_AES_256_GCM` from RFC 7714.
+    AeadAes256Gcm,
+}
+
+impl Profile {
+    /// Return the profile for a DTLS-SRTP protection profile identifier, as
+    /// negotiated with the `use_srtp` extension.
+    pub fn from_id(id: u16) -> Result<Self, UnknownCryptoError> {
+        match id {
+            0x0001 => Ok(Profile::Aes128CmHmacSha1_80),
+            0x0002 => Ok(Profile::Aes128CmHmacSha1_32),
+            0x0007 => Ok(Profile::AeadAes128Gcm),
+            0x0008 => Ok(Profile::AeadAes256Gcm),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    /// Return the DTLS-SRTP protection profile identifier.
+    pub fn id(&self) -> u16 {
+        match self {
+            Profile::Aes128CmHmacSha1_80 => 0x0001,
+            Profile::Aes128CmHmacSha1_32 => 0x0002,
+            Profile::AeadAes128Gcm => 0x0007,
+            Profile::AeadAes256Gcm => 0x0008,
+        }
+    }
+
+    /// Return the size of the master key, which is also the size of the
+    /// session encryption keys.
+    pub fn master_key_size(&self) -> usize {
+        match self {
+            Profile::AeadAes256Gcm => 32,
+            _ => 16,
+        }
+    }
+
+    /// Return the size of the master salt, which is also the size of the
+    /// session salts.
+    pub fn master_salt_size(&self) -> usize {
+        if self.is_aead() {
+            GCM_NONCESIZE
+        } else {
+            CM_SALT_SIZE
+        }
+    }
+
+    /// Return the size of the authentication tag that is appended to an SRTP
+    /// packet.
+    pub fn rtp_tag_size(&self) -> usize {
+        match self {
+            Profile::Aes128CmHmacSha1_80 => 10,
+            Profile::Aes128CmHmacSha1_32 => 4,
+            Profile::AeadAes128Gcm | Profile::AeadAes256Gcm => GCM_TAGSIZE,
+        }
+    }
+
+    /// Return the size of the SRTCP index and authentication tag that are
+    /// appended to an SRTCP packet.
+    pub fn rtcp_trailer_size(&self) -> usize {
+        SRTCP_INDEX_SIZE + self.rtcp_tag_size()
+    }
+
+    fn rtcp_tag_size(&self) -> usize {
+        if self.is_aead() {
+            GCM_TAGSIZE
+        } else {
+            CM_RTCP_TAG_SIZE
+        }
+    }
+
+    fn is_aead(&self) -> bool {
+        matches!(self, Profile::AeadAes128Gcm | Profile::AeadAes256Gcm)
+    }
+}
+
+/// The SRTP key derivation function with a key derivation rate of zero,
+/// which writes the keystream of AES in counter mode, keyed with the master
+/// key, for `label` into `dst_out`.
+fn derive_session_key(master: &Aes, master_salt: &[u8], label: u8, dst_out: &mut [u8]) {
+    debug_assert!(master_salt.len() <= CM_SALT_SIZE);
+    // The key_id, which is the label followed by the zero index, is XORed
+    // with the master salt, which is zero-padded to 14 bytes.
+    let mut iv = [0u8; AES_BLOCKSIZE];
+    iv[..master_salt.len()].copy_from_slice(master_salt);
+    iv[7] ^= label;
+
+    for byte in dst_out.iter_mut() {
+        *byte = 0;
+    }
+    ctr32_xor(master, &iv, dst_out);
+    iv.zeroize();
+}
+
+// A `Context` only holds two of these, so the size difference is not boxed.
+#[allow(clippy::large_enum_variant)]
+/// The session cipher and authentication of either SRTP or SRTCP.
+enum SessionCipher {
+    Cm { aes: Aes, hmac: HmacGeneric<Sha1> },
+    Gcm(AesGcm),
+}
+
+/// The session keys of either SRTP or SRTCP.
+struct SessionKeys {
+    cipher: SessionCipher,
+    salt: [u8; CM_SALT_SIZE],
+}
+
+impl Drop for SessionKeys {
+    fn drop(&mut self) {
+        self.salt.zeroize();
+    }
+}
+
+impl SessionKeys {
+    fn new(
+        profile: Profile,
+        master: &Aes,
+        master_salt: &[u8],
+        labels: [u8; 3],
+    ) -> Result<Self, UnknownCryptoError> {
+        let mut key = [0u8; 32];
+        let key = &mut key[..profile.master_key_size()];
+        derive_session_key(master, master_salt, labels[0], key);
+
+        let mut salt = [0u8; CM_SALT_SIZE];
+        derive_session_key(
+            master,
+            master_salt,
+            labels[2],
+            &mut salt[..profile.master_salt_size()],
+        );
+
+        let cipher = if profile.is_aead() {
+            AesGcm::new(key).map(SessionCipher::Gcm)
+        } else {
+            let mut auth_key = [0u8; AUTH_KEY_SIZE];
+            derive_session_key(master, master_salt, labels[1], &mut auth_key);
+            let hmac = HmacGeneric::<Sha1>::new(&auth_key);
+            auth_key.zeroize();
+
+            Ok(SessionCipher::Cm {
+                aes: Aes::new(&aes::SecretKey::from_slice(key)?)?,
+                hmac: hmac?,
+            })
+        };
+        key.zeroize();
+
+        Ok(Self {
+            cipher: cipher?,
+            salt,
+        })
+    }
+
+    /// The AES counter mode IV for `ssrc` and the 48-bit `index`.
+    fn cm_iv(&self, ssrc: &[u8], index: u64) -> [u8; AES_BLOCKSIZE] {
+        let mut iv = [0u8; AES_BLOCKSIZE];
+        iv[..CM_SALT_SIZE].copy_from_slice(&self.salt);
+        for (iv, byte) in iv[4..8].iter_mut().zip(ssrc.iter()) {
+            *iv ^= byte;
+        }
+        for (iv, byte) in iv[8..14].iter_mut().zip(index.to_be_bytes()[2..].iter()) {
+            *iv ^= byte;
+        }
+        iv
+    }
+
+    /// The AES-GCM IV for `ssrc` and the 6 bytes following it, which are the
+    /// ROC and sequence number for SRTP, or the SRTCP index for SRTCP.
+    fn gcm_iv(&self, ssrc: &[u8], index: &[u8; 6]) -> [u8; GCM_NONCESIZE] {
+        let mut iv = [0u8; GCM_NONCESIZE];
+        iv[2..6].copy_from_slice(ssrc);
+        iv[6..].copy_from_slice(index);
+        for (iv, byte) in iv.iter_mut().zip(self.salt.iter()) {
+            *iv ^= byte;
+        }
+        iv
+    }
+
+    /// Write the HMAC-SHA1 tag of `parts`, truncated to the length of
+    /// `dst_out`, into `dst_out`.
+    fn auth_tag(hmac: &HmacGeneric<Sha1>, parts: &[&[u8]], dst_out: &mut [u8]) {
+        let mut state = hmac.clone();
+        let mut tag = [0u8; SHA1_OUTSIZE];
+        // Cannot fail, since the state is new and the tag is SHA1_OUTSIZE bytes.
+        for part in parts {
+            state.update(part).unwrap();
+        }
+        state.finalize_into(&mut tag).unwrap();
+        dst_out.copy_from_slice(&tag[..dst_out.len()]);
+    }
+
+    /// Verify the HMAC-SHA1 tag of `parts` in constant time.
+    fn verify_auth_tag(
+        hmac: &HmacGeneric<Sha1>,
+        parts: &[&[u8]],
+        expected: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        let mut tag = [0u8; SHA1_OUTSIZE];
+        Self::auth_tag(hmac, parts, &mut tag[..expected.len()]);
+        crate::util::secure_cmp(&tag[..expected.len()], expected)
+    }
+}
+
+/// Return the length of the RTP header of `packet`, including the CSRC list
+/// and header extension.
+fn rtp_header_len(packet: &[u8]) -> Result<usize, UnknownCryptoError> {
+    if packet.len() < RTP_HEADER_SIZE || packet[0] >> 6 != 2 {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut len = RTP_HEADER_SIZE + 4 * usize::from(packet[0] & 0x0f);
+    if packet[0] & 0x10 != 0 {
+        if packet.len() < len + 4 {
+            return Err(UnknownCryptoError);
+        }
+        len += 4 + 4 * usize::from(u16::from_be_bytes([packet[len + 2], packet[len + 3]]));
+    }
+    if packet.len() < len {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(len)
+}
+
+/// Check that `packet` starts with an RTCP header of version 2.
+fn check_rtcp_header(packet: &[u8]) -> Result<(), UnknownCryptoError> {
+    if packet.len() < RTCP_HEADER_SIZE || packet[0] >> 6 != 2 {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+/// Return the RTP packet index for the rollover counter `roc` and the
+/// sequence number of `packet`, as a 48-bit integer and as the 6 bytes used
+/// in the AES-GCM IV.
+fn rtp_index(packet: &[u8], roc: u32) -> (u64, [u8; 6]) {
+    let mut bytes = [0u8; 6];
+    bytes[..4].copy_from_slice(&roc.to_be_bytes());
+    bytes[4..].copy_from_slice(&packet[2..4]);
+    let index = (u64::from(roc) << 16) | u64::from(u16::from_be_bytes([packet[2], packet[3]]));
+
+    (index, bytes)
+}
+
+/// Return the SRTCP index from the E flag and SRTCP index of a packet, which
+/// must have the E flag set.
+fn srtcp_index(e_index: &[u8]) -> Result<u32, UnknownCryptoError> {
+    let e_index = u32::from_be_bytes([e_index[0], e_index[1], e_index[2], e_index[3]]);
+    if e_index & SRTCP_E_FLAG == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(e_index & !SRTCP_E_FLAG)
+}
+
+/// Return the SRTCP index as the 6 bytes used in the AES-GCM IV.
+fn rtcp_index_bytes(index: u32) -> [u8; 6] {
+    let mut bytes = [0u8; 6];
+    bytes[2..].copy_from_slice(&index.to_be_bytes());
+    bytes
+}
+
+/// The SRTP and SRTCP session keys derived from a master key and master salt.
+pub struct Context {
+    profile: Profile,
+    rtp: SessionKeys,
+    rtcp: SessionKeys,
+}
+
+impl core::fmt::Debug for Context {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Context {{ profile: {:?}, rtp: [***OMITTED***], rtcp: [***OMITTED***] }}",
+            self.profile
+        )
+    }
+}
+
+impl Context {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Derive the SRTP and SRTCP session keys for `profile` from `master_key`
+    /// and `master_salt`.
+    pub fn new(
+        profile: Profile,
+        master_key: &[u8],
+        master_salt: &[u8],
+    ) -> Result<Self, UnknownCryptoError> {
+        if master_key.len() != profile.master_key_size()
+            || master_salt.len() != profile.master_salt_size()
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        let master = Aes::new(&aes::SecretKey::from_slice(master_key)?)?;
+        let rtp = SessionKeys::new(
+            profile,
+            &master,
+            master_salt,
+            [LABEL_RTP_ENCRYPTION, LABEL_RTP_AUTH, LABEL_RTP_SALT],
+        )?;
+        let rtcp = SessionKeys::new(
+            profile,
+            &master,
+            master_salt,
+            [LABEL_RTCP_ENCRYPTION, LABEL_RTCP_AUTH, LABEL_RTCP_SALT],
+        )?;
+
+        Ok(Self { profile, rtp, rtcp })
+    }
+
+    /// Return the protection profile.
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt and authenticate an RTP packet in place.
+    ///
+    /// `packet` holds the RTP packet followed by [`Profile::rtp_tag_size()`]
+    /// bytes, which are overwritten with the authentication tag.
+    ///
+    /// [`Profile::rtp_tag_size()`]: enum.Profile.html#method.rtp_tag_size
+    pub fn protect_rtp(&self, packet: &mut [u8], roc: u32) -> Result<(), UnknownCryptoError> {
+        let tag_size = self.profile.rtp_tag_size();
+        if packet.len() < tag_size {
+            return Err(UnknownCryptoError);
+        }
+        let (body, tag) = packet.split_at_mut(packet.len() - tag_size);
+        let header_len = rtp_header_len(body)?;
+        let (index, index_bytes) = rtp_index(body, roc);
+        let (header, payload) = body.split_at_mut(header_len);
+        let ssrc = &header[8..12];
+
+        match &self.rtp.cipher {
+            SessionCipher::Cm { aes, hmac } => {
+                ctr32_xor(aes, &self.rtp.cm_iv(ssrc, index), payload);
+                SessionKeys::auth_tag(hmac, &[header, payload, &roc.to_be_bytes()], tag);
+                Ok(())
+            }
+            SessionCipher::Gcm(gcm) => {
+                gcm.seal_in_place(&self.rtp.gcm_iv(ssrc, &index_bytes), header, payload, tag)
+            }
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify and decrypt an SRTP packet in place, returning the length of
+    /// the RTP packet, which is `packet` without the authentication tag.
+    ///
+    /// `packet` is left unmodified if the authentication tag is invalid.
+    pub fn unprotect_rtp(&self, packet: &mut [u8], roc: u32) -> Result<usize, UnknownCryptoError> {
+        let tag_size = self.profile.rtp_tag_size();
+        if packet.len() < tag_size {
+            return Err(UnknownCryptoError);
+        }
+        let body_len = packet.len() - tag_size;
+        let (body, tag) = packet.split_at_mut(body_len);
+        let header_len = rtp_header_len(body)?;
+        let (index, index_bytes) = rtp_index(body, roc);
+        let (header, payload) = body.split_at_mut(header_len);
+        let ssrc = &header[8..12];
+
+        match &self.rtp.cipher {
+            SessionCipher::Cm { aes, hmac } => {
+                SessionKeys::verify_auth_tag(hmac, &[header, payload, &roc.to_be_bytes()], tag)?;
+                ctr32_xor(aes, &self.rtp.cm_iv(ssrc, index), payload);
+            }
+            SessionCipher::Gcm(gcm) => {
+                gcm.open_in_place(&self.rtp.gcm_iv(ssrc, &index_bytes), header, payload, tag)?
+            }
+        }
+
+        Ok(body_len)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Encrypt and authenticate an RTCP packet in place, with the SRTCP
+    /// `index`.
+    ///
+    /// `packet` holds the RTCP packet, which may be a compound packet,
+    /// followed by [`Profile::rtcp_trailer_size()`] bytes, which are
+    /// overwritten with the SRTCP index and authentication tag.
+    ///
+    /// [`Profile::rtcp_trailer_size()`]: enum.Profile.html#method.rtcp_trailer_size
+    pub fn protect_rtcp(&self, packet: &mut [u8], index: u32) -> Result<(), UnknownCryptoError> {
+        let trailer_size = self.profile.rtcp_trailer_size();
+        if packet.len() < trailer_size || index & SRTCP_E_FLAG != 0 {
+            return Err(UnknownCryptoError);
+        }
+        let body_len = packet.len() - trailer_size;
+        check_rtcp_header(&packet[..body_len])?;
+        let e_index = (SRTCP_E_FLAG | index).to_be_bytes();
+
+        match &self.rtcp.cipher {
+            SessionCipher::Cm { aes, hmac } => {
+                // Encrypted portion, then the E flag and index, then the tag.
+                let (body, trailer) = packet.split_at_mut(body_len);
+                let (e_index_out, tag) = trailer.split_at_mut(SRTCP_INDEX_SIZE);
+                let (header, payload) = body.split_at_mut(RTCP_HEADER_SIZE);
+                ctr32_xor(
+                    aes,
+                    &self.rtcp.cm_iv(&header[4..8], u64::from(index)),
+                    payload,
+                );
+                e_index_out.copy_from_slice(&e_index);
+                SessionKeys::auth_tag(hmac, &[header, payload, &e_index], tag);
+                Ok(())
+            }
+            SessionCipher::Gcm(gcm) => {
+                // Encrypted portion and tag, then the E flag and index.
+                let (body, trailer) = packet.split_at_mut(body_len);
+                let (tag, e_index_out) = trailer.split_at_mut(GCM_TAGSIZE);
+                let (header, payload) = body.split_at_mut(RTCP_HEADER_SIZE);
+                e_index_out.copy_from_slice(&e_index);
+
+                let mut aad = [0u8; RTCP_HEADER_SIZE + SRTCP_INDEX_SIZE];
+                aad[..RTCP_HEADER_SIZE].copy_from_slice(header);
+                aad[RTCP_HEADER_SIZE..].copy_from_slice(&e_index);
+                let iv = self.rtcp.gcm_iv(&header[4..8], &rtcp_index_bytes(index));
+                gcm.seal_in_place(&iv, &aad, payload, tag)
+            }
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify and decrypt an SRTCP packet in place, returning the length of
+    /// the RTCP packet, which is `packet` without the SRTCP index and
+    /// authentication tag, and the SRTCP index.
+    ///
+    /// `packet` is left unmodified if the authentication tag is invalid.
+    pub fn unprotect_rtcp(&self, packet: &mut [u8]) -> Result<(usize, u32), UnknownCryptoError> {
+        let trailer_size = self.profile.rtcp_trailer_size();
+        if packet.len() < trailer_size {
+            return Err(UnknownCryptoError);
+        }
+        let body_len = packet.len() - trailer_size;
+        check_rtcp_header(&packet[..body_len])?;
+        let (body, trailer) = packet.split_at_mut(body_len);
+        let (header, payload) = body.split_at_mut(RTCP_HEADER_SIZE);
+
+        match &self.rtcp.cipher {
+            SessionCipher::Cm { aes, hmac } => {
+                let (e_index, tag) = trailer.split_at(SRTCP_INDEX_SIZE);
+                let index = srtcp_index(e_index)?;
+                SessionKeys::verify_auth_tag(hmac, &[header, payload, e_index], tag)?;
+                ctr32_xor(
+                    aes,
+                    &self.rtcp.cm_iv(&header[4..8], u64::from(index)),
+                    payload,
+                );
+
+                Ok((body_len, index))
+            }
+            SessionCipher::Gcm(gcm) => {
+                let (tag, e_index) = trailer.split_at(GCM_TAGSIZE);
+                let index = srtcp_index(e_index)?;
+                let mut aad = [0u8; RTCP_HEADER_SIZE + SRTCP_INDEX_SIZE];
+                aad[..RTCP_HEADER_SIZE].copy_from_slice(header);
+                aad[RTCP_HEADER_SIZE..].copy_from_slice(e_index);
+                let iv = self.rtcp.gcm_iv(&header[4..8], &rtcp_index_bytes(index));
+                gcm.open_in_place(&iv, &aad, payload, tag)?;
+
+                Ok((body_len, index))
+            }
+        }
+    }
+}
+
+/// Estimate the rollover counter of a received SRTP packet with sequence
+/// number `seq`, from the rollover counter `roc` and the highest sequence
+/// number `s_l` that have been received so far, as in RFC 3711 appendix A.
+pub fn estimate_roc(roc: u32, s_l: u16, seq: u16) -> u32 {
+    if s_l < 0x8000 {
+        if seq > s_l && seq - s_l > 0x8000 {
+            return roc.wrapping_sub(1);
+        }
+    } else if s_l - 0x8000 > seq {
+        return roc.wrapping_add(1);
+    }
+
+    roc
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const PROFILES: [Profile; 4] = [
+        Profile::Aes128CmHmacSha1_80,
+        Profile::Aes128CmHmacSha1_32,
+        Profile::AeadAes128Gcm,
+        Profile::AeadAes256Gcm,
+    ];
+
+    fn context(profile: Profile) -> Context {
+        let key = [0x42u8; 32];
+        let salt = [0x24u8; CM_SALT_SIZE];
+        Context::new(
+            profile,
+            &key[..profile.master_key_size()],
+            &salt[..profile.master_salt_size()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let debug = format!("{:?}", context(Profile::AeadAes128Gcm));
+        assert_eq!(
+            debug,
+            "Context { profile: AeadAes128Gcm, rtp: [***OMITTED***], rtcp: [***OMITTED***] }"
+        );
+    }
+
+    #[test]
+    fn test_profile_ids() {
+        for profile in PROFILES.iter() {
+            assert_eq!(Profile::from_id(profile.id()).unwrap(), *profile);
+        }
+        assert!(Profile::from_id(0x0000).is_err());
+        assert!(Profile::from_id(0x0005).is_err());
+    }
+
+    #[test]
+    fn test_master_key_and_salt_lengths() {
+        for profile in PROFILES.iter() {
+            let key = [0u8; 33];
+            let salt = [0u8; 15];
+            let key_size = profile.master_key_size();
+            let salt_size = profile.master_salt_size();
+            assert!(Context::new(*profile, &key[..key_size], &salt[..salt_size]).is_ok());
+            assert!(Context::new(*profile, &key[..key_size - 1], &salt[..salt_size]).is_err());
+            assert!(Context::new(*profile, &key[..key_size + 1], &salt[..salt_size]).is_err());
+            assert!(Context::new(*profile, &key[..key_size], &salt[..salt_size - 1]).is_err());
+            assert!(Context::new(*profile, &key[..key_size], &salt[..salt_size + 1]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_rtp_header_errors() {
+        for profile in PROFILES.iter() {
+            let ctx = context(*profile);
+            let tag_size = profile.rtp_tag_size();
+            let mut packet = [0u8; 64];
+            packet[0] = 0x80;
+
+            // Too short for the tag, and for the fixed header.
+            assert!(ctx.protect_rtp(&mut packet[..tag_size - 1], 0).is_err());
+            assert!(ctx.protect_rtp(&mut packet[..11 + tag_size], 0).is_err());
+            assert!(ctx.unprotect_rtp(&mut packet[..11 + tag_size], 0).is_err());
+            assert!(ctx.protect_rtp(&mut packet[..12 + tag_size], 0).is_ok());
+
+            // Version 1.
+            packet[0] = 0x40;
+            assert!(ctx.protect_rtp(&mut packet[..12 + tag_size], 0).is_err());
+
+            // Two CSRCs.
+            packet[0] = 0x82;
+            assert!(ctx.protect_rtp(&mut packet[..19 + tag_size], 0).is_err());
+            assert!(ctx.protect_rtp(&mut packet[..20 + tag_size], 0).is_ok());
+
+            // A header extension of one word, after the tags written above.
+            packet[0] = 0x90;
+            packet[14] = 0;
+            packet[15] = 1;
+            assert!(ctx.protect_rtp(&mut packet[..15 + tag_size], 0).is_err());
+            assert!(ctx.protect_rtp(&mut packet[..19 + tag_size], 0).is_err());
+            assert!(ctx.protect_rtp(&mut packet[..20 + tag_size], 0).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_rtp_roundtrip() {
+        for profile in PROFILES.iter() {
+            let ctx = context(*profile);
+            let tag_size = profile.rtp_tag_size();
+            let mut packet = [0u8; 100 + GCM_TAGSIZE];
+            packet[0] = 0x80;
+            packet[12..100].copy_from_slice(&[0xab; 88]);
+            let packet = &mut packet[..100 + tag_size];
+
+            ctx.protect_rtp(packet, 3).unwrap();
+            assert_ne!(&packet[12..100], &[0xab; 88][..]);
+            let other = Context::new(
+                *profile,
+                &[0u8; 32][..profile.master_key_size()],
+                &[0x24u8; CM_SALT_SIZE][..profile.master_salt_size()],
+            )
+            .unwrap();
+            assert!(other.unprotect_rtp(packet, 3).is_err());
+            assert_eq!(ctx.unprotect_rtp(packet, 3).unwrap(), 100);
+            assert_eq!(&packet[12..100], &[0xab; 88][..]);
+        }
+    }
+
+    #[test]
+    fn test_rtcp_errors() {
+        for profile in PROFILES.iter() {
+            let ctx = context(*profile);
+            let trailer_size = profile.rtcp_trailer_size();
+            let mut packet = [0u8; 64];
+            packet[0] = 0x80;
+
+            assert!(ctx
+                .protect_rtcp(&mut packet[..trailer_size - 1], 0)
+                .is_err());
+            assert!(ctx
+                .protect_rtcp(&mut packet[..7 + trailer_size], 0)
+                .is_err());
+            assert!(ctx.unprotect_rtcp(&mut packet[..7 + trailer_size]).is_err());
+            assert!(ctx
+                .protect_rtcp(&mut packet[..8 + trailer_size], 1 << 31)
+                .is_err());
+            packet[0] = 0x40;
+            assert!(ctx
+                .protect_rtcp(&mut packet[..8 + trailer_size], 0)
+                .is_err());
+            packet[0] = 0x80;
+
+            let packet = &mut packet[..16 + trailer_size];
+            ctx.protect_rtcp(packet, (1 << 31) - 1).unwrap();
+            assert_eq!(ctx.unprotect_rtcp(packet).unwrap(), (16, (1 << 31) - 1));
+
+            // Unencrypted SRTCP packets are not supported.
+            let e_flag = if profile.is_aead() {
+                16 + GCM_TAGSIZE
+            } else {
+                16
+            };
+            packet[e_flag] &= 0x7f;
+            assert!(ctx.unprotect_rtcp(packet).is_err());
+        }
+    }
+
+    #[test]
+    fn test_estimate_roc() {
+        // No wrap-around.
+        assert_eq!(estimate_roc(5, 1000, 1001), 5);
+        assert_eq!(estimate_roc(5, 1000, 999), 5);
+        assert_eq!(estimate_roc(5, 40000, 40001), 5);
+        // A late packet from before s_l wrapped around.
+        assert_eq!(estimate_roc(5, 10, 65530), 4);
+        // A packet after the sequence number wrapped around.
+        assert_eq!(estimate_roc(5, 65530, 10), 6);
+        assert_eq!(estimate_roc(u32::MAX, 65530, 10), 0);
+        assert_eq!(estimate_roc(0, 10, 65530), u32::MAX);
+        // Exactly half the sequence number space away.
+        assert_eq!(estimate_roc(5, 0, 0x8000), 5);
+        assert_eq!(estimate_roc(5, 0x8000, 0), 5);
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_rfc3711_key_derivation() {
+        // RFC 3711 appendix B.3.
+        let master_key = hex::decode("e1f97a0d3e018be0d64fa32c06de4139").unwrap();
+        let master_salt = hex::decode("0ec675ad498afeebb6960b3aabe6").unwrap();
+        let master = Aes::new(&aes::SecretKey::from_slice(&master_key).unwrap()).unwrap();
+
+        let mut key = [0u8; 16];
+        derive_session_key(&master, &master_salt, LABEL_RTP_ENCRYPTION, &mut key);
+        assert_eq!(
+            key.as_ref(),
+            &hex::decode("c61e7a93744f39ee10734afe3ff7a087").unwrap()[..]
+        );
+        let mut salt = [0u8; CM_SALT_SIZE];
+        derive_session_key(&master, &master_salt, LABEL_RTP_SALT, &mut salt);
+        assert_eq!(
+            salt.as_ref(),
+            &hex::decode("30cbbc08863d8c85d49db34a9ae1").unwrap()[..]
+        );
+        let mut auth_key = [0u8; AUTH_KEY_SIZE];
+        derive_session_key(&master, &master_salt, LABEL_RTP_AUTH, &mut auth_key);
+        assert_eq!(
+            auth_key.as_ref(),
+            &hex::decode("cebe321f6ff7716b6fd4ab49af256a156d38baa4").unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_rfc3711_aes_cm_keystream() {
+        // RFC 3711 appendix B.2, with SSRC and index zero.
+        let session_key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let mut salt = [0u8; CM_SALT_SIZE];
+        salt.copy_from_slice(&hex::decode("f0f1f2f3f4f5f6f7f8f9fafbfcfd").unwrap());
+        let keys = SessionKeys {
+            cipher: SessionCipher::Cm {
+                aes: Aes::new(&aes::SecretKey::from_slice(&session_key).unwrap()).unwrap(),
+                hmac: HmacGeneric::<Sha1>::new(&[0u8; AUTH_KEY_SIZE]).unwrap(),
+            },
+            salt,
+        };
+
+        let mut keystream = [0u8; 48];
+        match &keys.cipher {
+            SessionCipher::Cm { aes, .. } => {
+                ctr32_xor(aes, &keys.cm_iv(&[0u8; 4], 0), &mut keystream)
+            }
+            SessionCipher::Gcm(_) => unreachable!(),
+        }
+        assert_eq!(
+            keystream.as_ref(),
+            &hex::decode(
+                "e03ead0935c95e80e166b16dd92b4eb4d23513162b02d0f72a43a2fe4a5f97ab41e95b3bb0a2e8dd477901e4fca894c0"
+            )
+            .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_ivs() {
+        // The AES-GCM IV from RFC 7714 section 16.1.1.
+        let mut salt = [0u8; CM_SALT_SIZE];
+        salt[..GCM_NONCESIZE].copy_from_slice(b"Quid pro quo");
+        let keys = SessionKeys {
+            cipher: SessionCipher::Gcm(AesGcm::new(&[0u8; 16]).unwrap()),
+            salt,
+        };
+        let packet = hex::decode("8040f17b8041f8d35501a0b2").unwrap();
+        let (index, index_bytes) = rtp_index(&packet, 0);
+        assert_eq!(index, 0xf17b);
+        assert_eq!(
+            keys.gcm_iv(&packet[8..12], &index_bytes).as_ref(),
+            &hex::decode("51753c6580c2726f20718414").unwrap()[..]
+        );
+    }
+}
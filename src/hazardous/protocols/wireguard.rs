@@ -0,0 +1,1379 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! WireGuard handshake, cookie and transport data primitives.
+//!
+//! # About:
+//! This implements the cryptographic parts of the [WireGuard] protocol, which
+//! uses the `Noise_IKpsk2_25519_ChaChaPoly_BLAKE2s` handshake:
+//! - [`Initiator`] writes a handshake initiation message and derives the
+//!   [`TransportKeys`] from the handshake response.
+//! - [`Responder`] reads a handshake initiation message, and writes the
+//!   handshake response that also derives the [`TransportKeys`].
+//! - [`verify_mac1()`], [`verify_mac2()`] and [`write_mac2()`] handle the two
+//!   MACs at the end of each handshake message, [`make_cookie()`] computes the
+//!   cookie for a source address and [`seal_cookie_reply()`] and
+//!   [`open_cookie_reply()`] handle the cookie reply message.
+//! - [`tai64n()`] encodes the timestamp that is sent in the initiation.
+//! - [`seal_transport()`] and [`open_transport()`] handle transport data
+//!   messages.
+//!
+//! Everything about the wire format is produced and checked here, including
+//! the message types and indices. Sending messages, timers, rekeying and the
+//! decision of when to require a cookie are left to the caller.
+//!
+//! # Parameters:
+//! - `local_static`: The static private key of this peer.
+//! - `remote_static`: The static public key of the other peer.
+//! - `preshared_key`: The optional preshared key for this pair of peers.
+//! - `ephemeral`: A newly generated private key, used for a single handshake.
+//! - `local_index`: The index this peer chose to identify the session.
+//! - `timestamp`: A [`TIMESTAMP_SIZE`]-byte TAI64N timestamp, see [`tai64n()`].
+//! - `message`: A handshake initiation or handshake response message.
+//! - `source`: The source IP address followed by the source port, of the
+//!   peer that sent a handshake message.
+//! - `counter`: The counter of a transport data message.
+//! - `dst_out`: Destination buffer for a message or plaintext.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A message or `dst_out` is not the exact size of the message it is
+//!   expected to hold, or `dst_out` is too small to hold a transport data
+//!   message or its plaintext.
+//! - A message has the wrong message type or receiver index.
+//! - `timestamp` is not [`TIMESTAMP_SIZE`] bytes.
+//! - A MAC or an AEAD tag does not match.
+//! - A Diffie-Hellman computation results in the all-zero value.
+//! - `counter` is [`REJECT_AFTER_MESSAGES`] or greater.
+//! - `nanoseconds` is `1_000_000_000` or greater when calling [`tai64n()`].
+//!
+//! # Security:
+//! - A private key passed as `ephemeral` must never be re-used.
+//! - [`Responder::new()`] authenticates the initiator, but not the freshness of
+//!   the initiation. The responder must check that [`Responder::timestamp()`]
+//!   is greater than the timestamp of the last initiation it accepted from
+//!   [`Responder::remote_static()`], by comparing the bytes in order, before
+//!   responding.
+//! - [`open_transport()`] does not protect against replay. The counter it
+//!   returns must be checked against a sliding window, such as a
+//!   [`ReplayWindow`] for each [`TransportKeys`], and only be added to it
+//!   after [`open_transport()`] has succeeded.
+//! - A counter must never be re-used with the same [`TransportKeys`]. The
+//!   WireGuard protocol also requires a new handshake well before
+//!   [`REJECT_AFTER_MESSAGES`] is reached, and after a fixed amount of time.
+//! - Transport data plaintexts should be padded to a multiple of 16 bytes,
+//!   as WireGuard does, to hide their exact length.
+//! - A [`CookieSecret`] should be replaced with a new random one every two
+//!   minutes.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::protocols::replay::ReplayWindow;
+//! use orion::hazardous::protocols::wireguard::{self, Initiator, PrivateKey, Responder};
+//!
+//! let initiator_static = PrivateKey::generate();
+//! let responder_static = PrivateKey::generate();
+//! let responder_public = responder_static.public_key()?;
+//!
+//! let mut initiation = [0u8; wireguard::INITIATION_SIZE];
+//! let initiator = Initiator::new(
+//!     &initiator_static,
+//!     &responder_public,
+//!     None,
+//!     &PrivateKey::generate(),
+//!     1,
+//!     &wireguard::tai64n(1700000000, 0)?,
+//!     &mut initiation,
+//! )?;
+//!
+//! let responder = Responder::new(&responder_static, &initiation)?;
+//! assert_eq!(responder.remote_static(), &initiator_static.public_key()?);
+//!
+//! let mut response = [0u8; wireguard::RESPONSE_SIZE];
+//! let responder_keys = responder.respond(None, &PrivateKey::generate(), 2, &mut response)?;
+//! let initiator_keys = initiator.process_response(&response)?;
+//!
+//! let mut message = [0u8; wireguard::TRANSPORT_HEADER_SIZE + 16 + 16];
+//! wireguard::seal_transport(&initiator_keys, 0, &[0u8; 16], &mut message)?;
+//!
+//! let mut replay_window = ReplayWindow::new();
+//! let mut plaintext = [1u8; 16];
+//! let counter = wireguard::open_transport(&responder_keys, &message, &mut plaintext)?;
+//! replay_window.update(counter)?;
+//! assert_eq!(counter, 0);
+//! assert_eq!(plaintext, [0u8; 16]);
+//!
+//! // The same message is rejected when it is received again.
+//! let counter = wireguard::open_transport(&responder_keys, &message, &mut plaintext)?;
+//! assert!(replay_window.update(counter).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [WireGuard]: https://www.wireguard.com/papers/wireguard.pdf
+//! [`Initiator`]: struct.Initiator.html
+//! [`Responder`]: struct.Responder.html
+//! [`TransportKeys`]: struct.TransportKeys.html
+//! [`CookieSecret`]: struct.CookieSecret.html
+//! [`ReplayWindow`]: ../replay/struct.ReplayWindow.html
+//! [`Responder::new()`]: struct.Responder.html#method.new
+//! [`Responder::timestamp()`]: struct.Responder.html#method.timestamp
+//! [`Responder::remote_static()`]: struct.Responder.html#method.remote_static
+//! [`verify_mac1()`]: fn.verify_mac1.html
+//! [`verify_mac2()`]: fn.verify_mac2.html
+//! [`write_mac2()`]: fn.write_mac2.html
+//! [`make_cookie()`]: fn.make_cookie.html
+//! [`seal_cookie_reply()`]: fn.seal_cookie_reply.html
+//! [`open_cookie_reply()`]: fn.open_cookie_reply.html
+//! [`tai64n()`]: fn.tai64n.html
+//! [`seal_transport()`]: fn.seal_transport.html
+//! [`open_transport()`]: fn.open_transport.html
+//! [`TIMESTAMP_SIZE`]: constant.TIMESTAMP_SIZE.html
+//! [`REJECT_AFTER_MESSAGES`]: constant.REJECT_AFTER_MESSAGES.html
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::{chacha20poly1305, xchacha20poly1305},
+        ecc::curve25519::{self, X25519_KEYSIZE},
+        hash::blake2s::{self, Blake2s, BLAKE2S_BLOCKSIZE, BLAKE2S_OUTSIZE},
+        mac::poly1305::POLY1305_OUTSIZE,
+        stream::{
+            chacha20::{self, IETF_CHACHA_NONCESIZE},
+            xchacha20,
+        },
+    },
+    util,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+/// The size of a WireGuard private key, public key or preshared key.
+pub const WG_KEYSIZE: usize = X25519_KEYSIZE;
+/// The size of a cookie.
+pub const COOKIE_SIZE: usize = 16;
+/// The size of a TAI64N timestamp.
+pub const TIMESTAMP_SIZE: usize = 12;
+/// The size of a handshake initiation message.
+pub const INITIATION_SIZE: usize = 148;
+/// The size of a handshake response message.
+pub const RESPONSE_SIZE: usize = 92;
+/// The size of a cookie reply message.
+pub const COOKIE_REPLY_SIZE: usize = 64;
+/// The size of the header of a transport data message.
+pub const TRANSPORT_HEADER_SIZE: usize = 16;
+/// The first counter that may not be used for a transport data message.
+pub const REJECT_AFTER_MESSAGES: u64 = u64::MAX - (1 << 13);
+
+/// The Noise protocol name.
+const CONSTRUCTION: &[u8] = b"Noise_IKpsk2_25519_ChaChaPoly_BLAKE2s";
+/// The WireGuard prologue.
+const IDENTIFIER: &[u8] = b"WireGuard v1 zx2c4 Jason@zx2c4.com";
+/// The label used to derive the key for mac1.
+const LABEL_MAC1: &[u8] = b"mac1----";
+/// The label used to derive the key for the cookie reply.
+const LABEL_COOKIE: &[u8] = b"cookie--";
+
+/// The size of mac1 and mac2.
+const MAC_SIZE: usize = 16;
+/// The size of the encrypted static public key in an initiation.
+const ENCRYPTED_STATIC_SIZE: usize = WG_KEYSIZE + POLY1305_OUTSIZE;
+/// The size of the encrypted timestamp in an initiation.
+const ENCRYPTED_TIMESTAMP_SIZE: usize = TIMESTAMP_SIZE + POLY1305_OUTSIZE;
+
+/// The message type of a handshake initiation.
+const TYPE_INITIATION: u32 = 1;
+/// The message type of a handshake response.
+const TYPE_RESPONSE: u32 = 2;
+/// The message type of a cookie reply.
+const TYPE_COOKIE_REPLY: u32 = 3;
+/// The message type of transport data.
+const TYPE_TRANSPORT: u32 = 4;
+
+/// The offset of mac1 in a handshake initiation.
+const INITIATION_MAC1: usize = INITIATION_SIZE - 2 * MAC_SIZE;
+/// The offset of mac1 in a handshake response.
+const RESPONSE_MAC1: usize = RESPONSE_SIZE - 2 * MAC_SIZE;
+
+construct_secret_key! {
+    /// A type to represent the `PrivateKey` of a static or ephemeral
+    /// WireGuard key pair.
+    ///
+    /// The key is clamped when it is used, so any 32 bytes are accepted.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (PrivateKey, test_private_key, WG_KEYSIZE, WG_KEYSIZE, WG_KEYSIZE)
+}
+
+impl_from_trait!(PrivateKey, WG_KEYSIZE);
+
+impl PrivateKey {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Compute the public key of this private key.
+    pub fn public_key(&self) -> Result<PublicKey, UnknownCryptoError> {
+        let mut scalar = Zeroizing::new([0u8; WG_KEYSIZE]);
+        scalar.copy_from_slice(self.unprotected_as_bytes());
+        curve25519::clamp_scalar(scalar.as_mut())?;
+
+        let mut public = [0u8; WG_KEYSIZE];
+        curve25519::scalarmult_base(scalar.as_ref(), &mut public)?;
+
+        Ok(PublicKey::from(public))
+    }
+}
+
+construct_public! {
+    /// A type to represent the `PublicKey` of a WireGuard key pair.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    (PublicKey, test_public_key, WG_KEYSIZE, WG_KEYSIZE)
+}
+
+impl_from_trait!(PublicKey, WG_KEYSIZE);
+
+construct_secret_key! {
+    /// A type to represent the optional `PresharedKey` of a pair of peers.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (PresharedKey, test_preshared_key, WG_KEYSIZE, WG_KEYSIZE, WG_KEYSIZE)
+}
+
+impl_from_trait!(PresharedKey, WG_KEYSIZE);
+
+construct_secret_key! {
+    /// A type to represent a `Cookie`, which proves that a peer can receive
+    /// messages at its source address.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 16 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (Cookie, test_cookie, COOKIE_SIZE, COOKIE_SIZE, COOKIE_SIZE)
+}
+
+construct_secret_key! {
+    /// A type to represent the `CookieSecret` that a peer uses to compute
+    /// cookies.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (CookieSecret, test_cookie_secret, WG_KEYSIZE, WG_KEYSIZE, WG_KEYSIZE)
+}
+
+/// The keys and indices of a session, derived by a completed handshake.
+pub struct TransportKeys {
+    sending_key: chacha20::SecretKey,
+    receiving_key: chacha20::SecretKey,
+    local_index: u32,
+    remote_index: u32,
+}
+
+impl core::fmt::Debug for TransportKeys {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "TransportKeys {{ sending_key: ***OMITTED***, receiving_key: ***OMITTED***, \
+             local_index: {:?}, remote_index: {:?} }}",
+            self.local_index, self.remote_index
+        )
+    }
+}
+
+impl TransportKeys {
+    /// The key used to encrypt transport data sent by this peer.
+    pub fn sending_key(&self) -> &chacha20::SecretKey {
+        &self.sending_key
+    }
+
+    /// The key used to decrypt transport data received by this peer.
+    pub fn receiving_key(&self) -> &chacha20::SecretKey {
+        &self.receiving_key
+    }
+
+    /// The index this peer chose, which the other peer sends as the receiver.
+    pub fn local_index(&self) -> u32 {
+        self.local_index
+    }
+
+    /// The index the other peer chose, which this peer sends as the receiver.
+    pub fn remote_index(&self) -> u32 {
+        self.remote_index
+    }
+}
+
+/// HASH(data) from the WireGuard paper, which is BLAKE2s-256.
+fn hash(data: &[&[u8]]) -> Result<[u8; BLAKE2S_OUTSIZE], UnknownCryptoError> {
+    let mut digest = [0u8; BLAKE2S_OUTSIZE];
+    blake2s::digest_into(None, data, &mut digest)?;
+
+    Ok(digest)
+}
+
+/// MAC(key, data) from the WireGuard paper, which is keyed BLAKE2s-128.
+fn mac(key: &[u8], data: &[u8]) -> Result<[u8; MAC_SIZE], UnknownCryptoError> {
+    let mut tag = [0u8; MAC_SIZE];
+    blake2s::digest_into(Some(key), &[data], &mut tag)?;
+
+    Ok(tag)
+}
+
+/// HMAC(key, data) from the WireGuard paper, which is HMAC-BLAKE2s. `key` is
+/// never longer than the blocksize here.
+fn hmac(
+    key: &[u8],
+    data: &[&[u8]],
+) -> Result<Zeroizing<[u8; BLAKE2S_OUTSIZE]>, UnknownCryptoError> {
+    debug_assert!(key.len() <= BLAKE2S_BLOCKSIZE);
+    let mut pad = Zeroizing::new([0x36u8; BLAKE2S_BLOCKSIZE]);
+    for (pad_byte, key_byte) in pad.iter_mut().zip(key.iter()) {
+        *pad_byte ^= key_byte;
+    }
+
+    let mut inner = Zeroizing::new([0u8; BLAKE2S_OUTSIZE]);
+    let mut state = Blake2s::new(None, BLAKE2S_OUTSIZE)?;
+    state.update(pad.as_ref());
+    for part in data.iter() {
+        state.update(part);
+    }
+    state.finalize(inner.as_mut())?;
+
+    for pad_byte in pad.iter_mut() {
+        *pad_byte ^= 0x36 ^ 0x5c;
+    }
+    let mut outer = Zeroizing::new([0u8; BLAKE2S_OUTSIZE]);
+    blake2s::digest_into(None, &[pad.as_ref(), inner.as_ref()], outer.as_mut())?;
+
+    Ok(outer)
+}
+
+/// KDF_n(key, input) from the WireGuard paper, where n is the number of `outputs`.
+fn kdf(
+    key: &[u8],
+    input: &[u8],
+    outputs: &mut [[u8; BLAKE2S_OUTSIZE]],
+) -> Result<(), UnknownCryptoError> {
+    let secret = hmac(key, &[input])?;
+    for idx in 0..outputs.len() {
+        let (done, rest) = outputs.split_at_mut(idx);
+        let previous: &[u8] = match done.last() {
+            Some(output) => output,
+            None => &[],
+        };
+        rest[0] = *hmac(secret.as_ref(), &[previous, &[idx as u8 + 1]])?;
+    }
+
+    Ok(())
+}
+
+/// DH(private, public) from the WireGuard paper, which is X25519.
+fn dh(
+    private_key: &PrivateKey,
+    public_key: &[u8],
+) -> Result<Zeroizing<[u8; WG_KEYSIZE]>, UnknownCryptoError> {
+    let mut scalar = Zeroizing::new([0u8; WG_KEYSIZE]);
+    scalar.copy_from_slice(private_key.unprotected_as_bytes());
+    curve25519::clamp_scalar(scalar.as_mut())?;
+
+    let mut shared = Zeroizing::new([0u8; WG_KEYSIZE]);
+    curve25519::scalarmult(scalar.as_ref(), public_key, shared.as_mut())?;
+
+    Ok(shared)
+}
+
+/// The AEAD nonce for `counter`, which is 32 zero bits followed by the
+/// little-endian counter.
+fn counter_nonce(counter: u64) -> chacha20::Nonce {
+    let mut nonce = [0u8; IETF_CHACHA_NONCESIZE];
+    nonce[4..].copy_from_slice(&counter.to_le_bytes());
+
+    chacha20::Nonce::from(nonce)
+}
+
+/// Read the little-endian `u32` at `offset` of `message`.
+fn read_u32(message: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&message[offset..offset + 4]);
+
+    u32::from_le_bytes(bytes)
+}
+
+/// The offset of mac1 in `message`, which must be a handshake initiation or
+/// handshake response.
+fn mac1_offset(message: &[u8]) -> Result<usize, UnknownCryptoError> {
+    match message.len() {
+        INITIATION_SIZE if read_u32(message, 0) == TYPE_INITIATION => Ok(INITIATION_MAC1),
+        RESPONSE_SIZE if read_u32(message, 0) == TYPE_RESPONSE => Ok(RESPONSE_MAC1),
+        _ => Err(UnknownCryptoError),
+    }
+}
+
+/// Compute mac1 of `message`, for the peer with the static public key `receiver`.
+fn compute_mac1(
+    receiver: &PublicKey,
+    message: &[u8],
+) -> Result<[u8; MAC_SIZE], UnknownCryptoError> {
+    let mac1_offset = mac1_offset(message)?;
+    let key = hash(&[LABEL_MAC1, receiver.as_ref()])?;
+
+    mac(&key, &message[..mac1_offset])
+}
+
+/// The chaining key and hash of a handshake in progress.
+#[derive(Clone)]
+struct HandshakeState {
+    chaining_key: [u8; BLAKE2S_OUTSIZE],
+    hash: [u8; BLAKE2S_OUTSIZE],
+}
+
+impl Drop for HandshakeState {
+    fn drop(&mut self) {
+        self.chaining_key.zeroize();
+        self.hash.zeroize();
+    }
+}
+
+impl HandshakeState {
+    /// Initialize the state for a handshake with the responder `responder_static`.
+    fn new(responder_static: &PublicKey) -> Result<Self, UnknownCryptoError> {
+        let chaining_key = hash(&[CONSTRUCTION])?;
+        let hash_ = hash(&[&chaining_key, IDENTIFIER])?;
+
+        Ok(Self {
+            chaining_key,
+            hash: hash(&[&hash_, responder_static.as_ref()])?,
+        })
+    }
+
+    /// H := HASH(H || data).
+    fn mix_hash(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.hash = hash(&[&self.hash, data])?;
+
+        Ok(())
+    }
+
+    /// C := KDF_1(C, input).
+    fn mix_key(&mut self, input: &[u8]) -> Result<(), UnknownCryptoError> {
+        let mut outputs = Zeroizing::new([[0u8; BLAKE2S_OUTSIZE]; 1]);
+        kdf(&self.chaining_key, input, outputs.as_mut())?;
+        self.chaining_key = outputs[0];
+
+        Ok(())
+    }
+
+    /// (C, k) := KDF_2(C, input).
+    fn mix_key_with_output(
+        &mut self,
+        input: &[u8],
+    ) -> Result<chacha20::SecretKey, UnknownCryptoError> {
+        let mut outputs = Zeroizing::new([[0u8; BLAKE2S_OUTSIZE]; 2]);
+        kdf(&self.chaining_key, input, outputs.as_mut())?;
+        self.chaining_key = outputs[0];
+
+        chacha20::SecretKey::from_slice(&outputs[1])
+    }
+
+    /// (C, t, k) := KDF_3(C, psk) followed by H := HASH(H || t).
+    fn mix_preshared_key(
+        &mut self,
+        preshared_key: Option<&PresharedKey>,
+    ) -> Result<chacha20::SecretKey, UnknownCryptoError> {
+        let zero_key = [0u8; WG_KEYSIZE];
+        let input = match preshared_key {
+            Some(psk) => psk.unprotected_as_bytes(),
+            None => &zero_key,
+        };
+
+        let mut outputs = Zeroizing::new([[0u8; BLAKE2S_OUTSIZE]; 3]);
+        kdf(&self.chaining_key, input, outputs.as_mut())?;
+        self.chaining_key = outputs[0];
+        self.mix_hash(&outputs[1])?;
+
+        chacha20::SecretKey::from_slice(&outputs[2])
+    }
+
+    /// Encrypt `plaintext` with `H` as the associated data, then mix the
+    /// ciphertext into `H`.
+    fn encrypt_and_hash(
+        &mut self,
+        secret_key: &chacha20::SecretKey,
+        plaintext: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        chacha20poly1305::seal(
+            secret_key,
+            &counter_nonce(0),
+            plaintext,
+            Some(&self.hash),
+            dst_out,
+        )?;
+        self.mix_hash(dst_out)
+    }
+
+    /// Decrypt `ciphertext` with `H` as the associated data, then mix the
+    /// ciphertext into `H`.
+    fn decrypt_and_hash(
+        &mut self,
+        secret_key: &chacha20::SecretKey,
+        ciphertext: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        chacha20poly1305::open(
+            secret_key,
+            &counter_nonce(0),
+            ciphertext,
+            Some(&self.hash),
+            dst_out,
+        )?;
+        self.mix_hash(ciphertext)
+    }
+
+    /// Derive the initiator's sending key and the responder's sending key.
+    fn split(&self) -> Result<(chacha20::SecretKey, chacha20::SecretKey), UnknownCryptoError> {
+        let mut outputs = Zeroizing::new([[0u8; BLAKE2S_OUTSIZE]; 2]);
+        kdf(&self.chaining_key, &[], outputs.as_mut())?;
+
+        Ok((
+            chacha20::SecretKey::from_slice(&outputs[0])?,
+            chacha20::SecretKey::from_slice(&outputs[1])?,
+        ))
+    }
+}
+
+/// The initiator of a handshake, waiting for the handshake response.
+pub struct Initiator {
+    state: HandshakeState,
+    local_static: PrivateKey,
+    ephemeral: PrivateKey,
+    preshared_key: Option<PresharedKey>,
+    local_index: u32,
+}
+
+impl core::fmt::Debug for Initiator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Initiator {{ state: ***OMITTED***, local_index: {:?} }}",
+            self.local_index
+        )
+    }
+}
+
+impl Initiator {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Start a handshake with `remote_static`, writing the handshake
+    /// initiation message to `dst_initiation`.
+    ///
+    /// mac2 is left as zeroes. Use [`write_mac2()`] if the responder has sent
+    /// a cookie.
+    ///
+    /// [`write_mac2()`]: fn.write_mac2.html
+    pub fn new(
+        local_static: &PrivateKey,
+        remote_static: &PublicKey,
+        preshared_key: Option<&PresharedKey>,
+        ephemeral: &PrivateKey,
+        local_index: u32,
+        timestamp: &[u8],
+        dst_initiation: &mut [u8],
+    ) -> Result<Self, UnknownCryptoError> {
+        if dst_initiation.len() != INITIATION_SIZE || timestamp.len() != TIMESTAMP_SIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut state = HandshakeState::new(remote_static)?;
+        let mut message = [0u8; INITIATION_SIZE];
+        message[..4].copy_from_slice(&TYPE_INITIATION.to_le_bytes());
+        message[4..8].copy_from_slice(&local_index.to_le_bytes());
+
+        let ephemeral_public = ephemeral.public_key()?;
+        message[8..40].copy_from_slice(ephemeral_public.as_ref());
+        state.mix_hash(ephemeral_public.as_ref())?;
+        state.mix_key(ephemeral_public.as_ref())?;
+
+        let secret_key =
+            state.mix_key_with_output(dh(ephemeral, remote_static.as_ref())?.as_ref())?;
+        let (encrypted_static, rest) = message[40..].split_at_mut(ENCRYPTED_STATIC_SIZE);
+        state.encrypt_and_hash(
+            &secret_key,
+            local_static.public_key()?.as_ref(),
+            encrypted_static,
+        )?;
+
+        let secret_key =
+            state.mix_key_with_output(dh(local_static, remote_static.as_ref())?.as_ref())?;
+        state.encrypt_and_hash(
+            &secret_key,
+            timestamp,
+            &mut rest[..ENCRYPTED_TIMESTAMP_SIZE],
+        )?;
+
+        let mac1 = compute_mac1(remote_static, &message)?;
+        message[INITIATION_MAC1..INITIATION_MAC1 + MAC_SIZE].copy_from_slice(&mac1);
+        dst_initiation.copy_from_slice(&message);
+
+        Ok(Self {
+            state,
+            local_static: PrivateKey::from_slice(local_static.unprotected_as_bytes())?,
+            ephemeral: PrivateKey::from_slice(ephemeral.unprotected_as_bytes())?,
+            preshared_key: match preshared_key {
+                Some(psk) => Some(PresharedKey::from_slice(psk.unprotected_as_bytes())?),
+                None => None,
+            },
+            local_index,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Process the handshake `response`, and derive the [`TransportKeys`].
+    ///
+    /// The `Initiator` is left unchanged, so that a forged response does not
+    /// prevent processing the real one. It should be dropped once this has
+    /// succeeded.
+    ///
+    /// [`TransportKeys`]: struct.TransportKeys.html
+    pub fn process_response(&self, response: &[u8]) -> Result<TransportKeys, UnknownCryptoError> {
+        if response.len() != RESPONSE_SIZE
+            || read_u32(response, 0) != TYPE_RESPONSE
+            || read_u32(response, 8) != self.local_index
+        {
+            return Err(UnknownCryptoError);
+        }
+        verify_mac1(&self.local_static.public_key()?, response)?;
+
+        let mut state = self.state.clone();
+        let remote_ephemeral = &response[12..44];
+        state.mix_hash(remote_ephemeral)?;
+        state.mix_key(remote_ephemeral)?;
+        state.mix_key(dh(&self.ephemeral, remote_ephemeral)?.as_ref())?;
+        state.mix_key(dh(&self.local_static, remote_ephemeral)?.as_ref())?;
+        let secret_key = state.mix_preshared_key(self.preshared_key.as_ref())?;
+        state.decrypt_and_hash(&secret_key, &response[44..RESPONSE_MAC1], &mut [])?;
+
+        let (sending_key, receiving_key) = state.split()?;
+
+        Ok(TransportKeys {
+            sending_key,
+            receiving_key,
+            local_index: self.local_index,
+            remote_index: read_u32(response, 4),
+        })
+    }
+}
+
+/// The responder of a handshake, that has accepted a handshake initiation.
+pub struct Responder {
+    state: HandshakeState,
+    remote_static: PublicKey,
+    remote_ephemeral: PublicKey,
+    remote_index: u32,
+    timestamp: [u8; TIMESTAMP_SIZE],
+}
+
+impl core::fmt::Debug for Responder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Responder {{ state: ***OMITTED***, remote_static: {:?}, remote_index: {:?} }}",
+            self.remote_static, self.remote_index
+        )
+    }
+}
+
+impl Responder {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Verify mac1 of the handshake `initiation` and decrypt the static public
+    /// key and timestamp of the initiator.
+    pub fn new(local_static: &PrivateKey, initiation: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if initiation.len() != INITIATION_SIZE || read_u32(initiation, 0) != TYPE_INITIATION {
+            return Err(UnknownCryptoError);
+        }
+        let local_public = local_static.public_key()?;
+        verify_mac1(&local_public, initiation)?;
+
+        let mut state = HandshakeState::new(&local_public)?;
+        let remote_ephemeral = &initiation[8..40];
+        state.mix_hash(remote_ephemeral)?;
+        state.mix_key(remote_ephemeral)?;
+
+        let secret_key = state.mix_key_with_output(dh(local_static, remote_ephemeral)?.as_ref())?;
+        let mut remote_static = [0u8; WG_KEYSIZE];
+        state.decrypt_and_hash(
+            &secret_key,
+            &initiation[40..40 + ENCRYPTED_STATIC_SIZE],
+            &mut remote_static,
+        )?;
+
+        let secret_key = state.mix_key_with_output(dh(local_static, &remote_static)?.as_ref())?;
+        let mut timestamp = [0u8; TIMESTAMP_SIZE];
+        state.decrypt_and_hash(
+            &secret_key,
+            &initiation[40 + ENCRYPTED_STATIC_SIZE..INITIATION_MAC1],
+            &mut timestamp,
+        )?;
+
+        Ok(Self {
+            state,
+            remote_static: PublicKey::from(remote_static),
+            remote_ephemeral: PublicKey::from_slice(remote_ephemeral)?,
+            remote_index: read_u32(initiation, 4),
+            timestamp,
+        })
+    }
+
+    /// The static public key of the initiator.
+    pub fn remote_static(&self) -> &PublicKey {
+        &self.remote_static
+    }
+
+    /// The TAI64N timestamp sent by the initiator.
+    pub fn timestamp(&self) -> &[u8] {
+        &self.timestamp
+    }
+
+    /// The index the initiator chose.
+    pub fn remote_index(&self) -> u32 {
+        self.remote_index
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Complete the handshake, writing the handshake response message to
+    /// `dst_response` and deriving the [`TransportKeys`].
+    ///
+    /// mac2 is left as zeroes. Use [`write_mac2()`] if the initiator has sent
+    /// a cookie.
+    ///
+    /// [`TransportKeys`]: struct.TransportKeys.html
+    /// [`write_mac2()`]: fn.write_mac2.html
+    pub fn respond(
+        mut self,
+        preshared_key: Option<&PresharedKey>,
+        ephemeral: &PrivateKey,
+        local_index: u32,
+        dst_response: &mut [u8],
+    ) -> Result<TransportKeys, UnknownCryptoError> {
+        if dst_response.len() != RESPONSE_SIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut message = [0u8; RESPONSE_SIZE];
+        message[..4].copy_from_slice(&TYPE_RESPONSE.to_le_bytes());
+        message[4..8].copy_from_slice(&local_index.to_le_bytes());
+        message[8..12].copy_from_slice(&self.remote_index.to_le_bytes());
+
+        let ephemeral_public = ephemeral.public_key()?;
+        message[12..44].copy_from_slice(ephemeral_public.as_ref());
+        self.state.mix_hash(ephemeral_public.as_ref())?;
+        self.state.mix_key(ephemeral_public.as_ref())?;
+        self.state
+            .mix_key(dh(ephemeral, self.remote_ephemeral.as_ref())?.as_ref())?;
+        self.state
+            .mix_key(dh(ephemeral, self.remote_static.as_ref())?.as_ref())?;
+        let secret_key = self.state.mix_preshared_key(preshared_key)?;
+        self.state
+            .encrypt_and_hash(&secret_key, &[], &mut message[44..RESPONSE_MAC1])?;
+
+        let mac1 = compute_mac1(&self.remote_static, &message)?;
+        message[RESPONSE_MAC1..RESPONSE_MAC1 + MAC_SIZE].copy_from_slice(&mac1);
+
+        let (receiving_key, sending_key) = self.state.split()?;
+        dst_response.copy_from_slice(&message);
+
+        Ok(TransportKeys {
+            sending_key,
+            receiving_key,
+            local_index,
+            remote_index: self.remote_index,
+        })
+    }
+}
+
+impl Drop for Responder {
+    fn drop(&mut self) {
+        self.timestamp.zeroize();
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify mac1 of the handshake `message`, sent to the peer with the static
+/// public key `local_static`.
+pub fn verify_mac1(local_static: &PublicKey, message: &[u8]) -> Result<(), UnknownCryptoError> {
+    let mac1_offset = mac1_offset(message)?;
+    let expected = compute_mac1(local_static, message)?;
+
+    util::secure_cmp(&expected, &message[mac1_offset..mac1_offset + MAC_SIZE])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify mac2 of the handshake `message`, using the `cookie` of its sender.
+pub fn verify_mac2(cookie: &Cookie, message: &[u8]) -> Result<(), UnknownCryptoError> {
+    let mac2_offset = mac1_offset(message)? + MAC_SIZE;
+    let expected = mac(cookie.unprotected_as_bytes(), &message[..mac2_offset])?;
+
+    util::secure_cmp(&expected, &message[mac2_offset..])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Write mac2 of the handshake `message`, using the `cookie` received from
+/// the other peer. This must be done after mac1 has been written.
+pub fn write_mac2(cookie: &Cookie, message: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    let mac2_offset = mac1_offset(message)? + MAC_SIZE;
+    let mac2 = mac(cookie.unprotected_as_bytes(), &message[..mac2_offset])?;
+    message[mac2_offset..].copy_from_slice(&mac2);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the cookie of the peer at `source`.
+pub fn make_cookie(
+    cookie_secret: &CookieSecret,
+    source: &[u8],
+) -> Result<Cookie, UnknownCryptoError> {
+    Cookie::from_slice(&mac(cookie_secret.unprotected_as_bytes(), source)?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Write a cookie reply to the handshake `message`, which was sent to the
+/// peer with the static public key `local_static`, to `dst_out`.
+pub fn seal_cookie_reply(
+    local_static: &PublicKey,
+    message: &[u8],
+    cookie: &Cookie,
+    nonce: &xchacha20::Nonce,
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != COOKIE_REPLY_SIZE {
+        return Err(UnknownCryptoError);
+    }
+    let mac1_offset = mac1_offset(message)?;
+    let secret_key = chacha20::SecretKey::from(hash(&[LABEL_COOKIE, local_static.as_ref()])?);
+
+    let (header, encrypted_cookie) = dst_out.split_at_mut(32);
+    xchacha20poly1305::seal(
+        &secret_key,
+        nonce,
+        cookie.unprotected_as_bytes(),
+        Some(&message[mac1_offset..mac1_offset + MAC_SIZE]),
+        encrypted_cookie,
+    )?;
+    header[..4].copy_from_slice(&TYPE_COOKIE_REPLY.to_le_bytes());
+    header[4..8].copy_from_slice(&message[4..8]);
+    header[8..].copy_from_slice(nonce.as_ref());
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the cookie from a cookie `reply` to the handshake `sent_message`,
+/// which was sent to the peer with the static public key `remote_static`.
+pub fn open_cookie_reply(
+    remote_static: &PublicKey,
+    sent_message: &[u8],
+    reply: &[u8],
+) -> Result<Cookie, UnknownCryptoError> {
+    let mac1_offset = mac1_offset(sent_message)?;
+    if reply.len() != COOKIE_REPLY_SIZE
+        || read_u32(reply, 0) != TYPE_COOKIE_REPLY
+        || reply[4..8] != sent_message[4..8]
+    {
+        return Err(UnknownCryptoError);
+    }
+    let secret_key = chacha20::SecretKey::from(hash(&[LABEL_COOKIE, remote_static.as_ref()])?);
+
+    let mut cookie = Zeroizing::new([0u8; COOKIE_SIZE]);
+    xchacha20poly1305::open(
+        &secret_key,
+        &xchacha20::Nonce::from_slice(&reply[8..32])?,
+        &reply[32..],
+        Some(&sent_message[mac1_offset..mac1_offset + MAC_SIZE]),
+        cookie.as_mut(),
+    )?;
+
+    Cookie::from_slice(cookie.as_ref())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encode a TAI64N timestamp from the seconds and nanoseconds since the Unix
+/// epoch.
+pub fn tai64n(
+    unix_seconds: u64,
+    nanoseconds: u32,
+) -> Result<[u8; TIMESTAMP_SIZE], UnknownCryptoError> {
+    if nanoseconds >= 1_000_000_000 {
+        return Err(UnknownCryptoError);
+    }
+    let label = match unix_seconds.checked_add(0x400000000000000a) {
+        Some(label) => label,
+        None => return Err(UnknownCryptoError),
+    };
+
+    let mut timestamp = [0u8; TIMESTAMP_SIZE];
+    timestamp[..8].copy_from_slice(&label.to_be_bytes());
+    timestamp[8..].copy_from_slice(&nanoseconds.to_be_bytes());
+
+    Ok(timestamp)
+}
+
+#[cfg(all(
+    feature = "safe_api",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encode the current system time as a TAI64N timestamp. Not available on
+/// `wasm32-unknown-unknown`, which has no system time. Use [`tai64n()`] with a
+/// time from elsewhere there.
+///
+/// [`tai64n()`]: fn.tai64n.html
+pub fn tai64n_now() -> Result<[u8; TIMESTAMP_SIZE], UnknownCryptoError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|_| UnknownCryptoError)?;
+
+    tai64n(now.as_secs(), now.subsec_nanos())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Write a transport data message containing `plaintext` to `dst_out`.
+pub fn seal_transport(
+    keys: &TransportKeys,
+    counter: u64,
+    plaintext: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if counter >= REJECT_AFTER_MESSAGES || dst_out.len() < TRANSPORT_HEADER_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (header, packet) = dst_out.split_at_mut(TRANSPORT_HEADER_SIZE);
+    chacha20poly1305::seal(
+        &keys.sending_key,
+        &counter_nonce(counter),
+        plaintext,
+        None,
+        packet,
+    )?;
+    header[..4].copy_from_slice(&TYPE_TRANSPORT.to_le_bytes());
+    header[4..8].copy_from_slice(&keys.remote_index.to_le_bytes());
+    header[8..].copy_from_slice(&counter.to_le_bytes());
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt the transport data `message` to `dst_out`, returning its counter.
+pub fn open_transport(
+    keys: &TransportKeys,
+    message: &[u8],
+    dst_out: &mut [u8],
+) -> Result<u64, UnknownCryptoError> {
+    if message.len() < TRANSPORT_HEADER_SIZE + POLY1305_OUTSIZE
+        || read_u32(message, 0) != TYPE_TRANSPORT
+        || read_u32(message, 4) != keys.local_index
+    {
+        return Err(UnknownCryptoError);
+    }
+    let mut counter = [0u8; 8];
+    counter.copy_from_slice(&message[8..TRANSPORT_HEADER_SIZE]);
+    let counter = u64::from_le_bytes(counter);
+    if counter >= REJECT_AFTER_MESSAGES {
+        return Err(UnknownCryptoError);
+    }
+
+    chacha20poly1305::open(
+        &keys.receiving_key,
+        &counter_nonce(counter),
+        &message[TRANSPORT_HEADER_SIZE..],
+        None,
+        dst_out,
+    )?;
+
+    Ok(counter)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(feature = "safe_api")]
+mod public {
+    use super::*;
+
+    struct Peers {
+        initiator_static: PrivateKey,
+        responder_static: PrivateKey,
+    }
+
+    impl Peers {
+        fn generate() -> Self {
+            Self {
+                initiator_static: PrivateKey::generate(),
+                responder_static: PrivateKey::generate(),
+            }
+        }
+
+        fn initiate(&self, psk: Option<&PresharedKey>, initiation: &mut [u8]) -> Initiator {
+            Initiator::new(
+                &self.initiator_static,
+                &self.responder_static.public_key().unwrap(),
+                psk,
+                &PrivateKey::generate(),
+                7,
+                &tai64n(1700000000, 0).unwrap(),
+                initiation,
+            )
+            .unwrap()
+        }
+    }
+
+    fn handshake(psk: Option<&PresharedKey>) -> (TransportKeys, TransportKeys) {
+        let peers = Peers::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let initiator = peers.initiate(psk, &mut initiation);
+
+        let responder = Responder::new(&peers.responder_static, &initiation).unwrap();
+        let mut response = [0u8; RESPONSE_SIZE];
+        let responder_keys = responder
+            .respond(psk, &PrivateKey::generate(), 9, &mut response)
+            .unwrap();
+
+        (
+            initiator.process_response(&response).unwrap(),
+            responder_keys,
+        )
+    }
+
+    #[test]
+    fn test_handshake_keys_match() {
+        let psk = PresharedKey::generate();
+        for psk in [None, Some(&psk)].iter() {
+            let (initiator_keys, responder_keys) = handshake(*psk);
+            assert_eq!(initiator_keys.sending_key(), responder_keys.receiving_key());
+            assert_eq!(initiator_keys.receiving_key(), responder_keys.sending_key());
+            assert_ne!(initiator_keys.sending_key(), initiator_keys.receiving_key());
+            assert_eq!(initiator_keys.local_index(), 7);
+            assert_eq!(initiator_keys.remote_index(), 9);
+            assert_eq!(responder_keys.local_index(), 9);
+            assert_eq!(responder_keys.remote_index(), 7);
+        }
+    }
+
+    #[test]
+    fn test_responder_reads_initiation() {
+        let peers = Peers::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let _initiator = peers.initiate(None, &mut initiation);
+
+        let responder = Responder::new(&peers.responder_static, &initiation).unwrap();
+        assert_eq!(
+            responder.remote_static(),
+            &peers.initiator_static.public_key().unwrap()
+        );
+        assert_eq!(responder.timestamp(), &tai64n(1700000000, 0).unwrap()[..]);
+        assert_eq!(responder.remote_index(), 7);
+        assert_eq!(&initiation[INITIATION_MAC1 + MAC_SIZE..], &[0u8; MAC_SIZE]);
+
+        // Another responder.
+        assert!(Responder::new(&PrivateKey::generate(), &initiation).is_err());
+    }
+
+    #[test]
+    fn test_preshared_key_mismatch_err() {
+        let peers = Peers::generate();
+        let psk = PresharedKey::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let initiator = peers.initiate(Some(&psk), &mut initiation);
+
+        let responder = Responder::new(&peers.responder_static, &initiation).unwrap();
+        let mut response = [0u8; RESPONSE_SIZE];
+        let _ = responder
+            .respond(None, &PrivateKey::generate(), 9, &mut response)
+            .unwrap();
+        assert!(initiator.process_response(&response).is_err());
+    }
+
+    #[test]
+    fn test_modified_messages_err() {
+        let peers = Peers::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let initiator = peers.initiate(None, &mut initiation);
+
+        // mac2 is not covered by mac1, so it is not checked here.
+        for idx in 0..INITIATION_MAC1 + MAC_SIZE {
+            let mut modified = initiation;
+            modified[idx] ^= 1;
+            assert!(Responder::new(&peers.responder_static, &modified).is_err());
+        }
+        assert!(Responder::new(&peers.responder_static, &initiation[..147]).is_err());
+
+        let responder = Responder::new(&peers.responder_static, &initiation).unwrap();
+        let mut response = [0u8; RESPONSE_SIZE];
+        let _ = responder
+            .respond(None, &PrivateKey::generate(), 9, &mut response)
+            .unwrap();
+
+        for idx in 0..RESPONSE_MAC1 + MAC_SIZE {
+            let mut modified = response;
+            modified[idx] ^= 1;
+            assert!(initiator.process_response(&modified).is_err());
+        }
+        assert!(initiator.process_response(&response[..91]).is_err());
+        // A forged response does not affect the real one.
+        initiator.process_response(&response).unwrap();
+    }
+
+    #[test]
+    fn test_length_err() {
+        let peers = Peers::generate();
+        let remote_static = peers.responder_static.public_key().unwrap();
+        let timestamp = tai64n(0, 0).unwrap();
+        let ephemeral = PrivateKey::generate();
+        let mut initiation = [0u8; INITIATION_SIZE + 1];
+
+        assert!(Initiator::new(
+            &peers.initiator_static,
+            &remote_static,
+            None,
+            &ephemeral,
+            0,
+            &timestamp,
+            &mut initiation,
+        )
+        .is_err());
+        assert!(Initiator::new(
+            &peers.initiator_static,
+            &remote_static,
+            None,
+            &ephemeral,
+            0,
+            &timestamp[..11],
+            &mut initiation[..INITIATION_SIZE],
+        )
+        .is_err());
+
+        let _ = peers.initiate(None, &mut initiation[..INITIATION_SIZE]);
+        let responder =
+            Responder::new(&peers.responder_static, &initiation[..INITIATION_SIZE]).unwrap();
+        let mut response = [0u8; RESPONSE_SIZE - 1];
+        assert!(responder
+            .respond(None, &ephemeral, 0, &mut response)
+            .is_err());
+    }
+
+    #[test]
+    fn test_low_order_ephemeral_err() {
+        let peers = Peers::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let _ = peers.initiate(None, &mut initiation);
+
+        // An ephemeral key of zero gives an all-zero shared secret. mac1 is
+        // recomputed so that only the Diffie-Hellman computation can fail.
+        initiation[8..40].copy_from_slice(&[0u8; 32]);
+        let mac1 = compute_mac1(&peers.responder_static.public_key().unwrap(), &initiation);
+        initiation[INITIATION_MAC1..INITIATION_MAC1 + MAC_SIZE].copy_from_slice(&mac1.unwrap());
+        assert!(Responder::new(&peers.responder_static, &initiation).is_err());
+    }
+
+    #[test]
+    fn test_mac2() {
+        let peers = Peers::generate();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let _ = peers.initiate(None, &mut initiation);
+
+        let cookie = make_cookie(&CookieSecret::generate(), &[192, 0, 2, 1, 0xca, 0x6c]).unwrap();
+        assert!(verify_mac2(&cookie, &initiation).is_err());
+        write_mac2(&cookie, &mut initiation).unwrap();
+        verify_mac2(&cookie, &initiation).unwrap();
+        assert!(verify_mac2(&Cookie::generate(), &initiation).is_err());
+
+        // mac1 is still valid, since mac2 is not covered by it.
+        verify_mac1(&peers.responder_static.public_key().unwrap(), &initiation).unwrap();
+        Responder::new(&peers.responder_static, &initiation).unwrap();
+
+        assert!(write_mac2(&cookie, &mut initiation[..147]).is_err());
+        assert!(verify_mac2(&cookie, &[0u8; RESPONSE_SIZE]).is_err());
+    }
+
+    #[test]
+    fn test_make_cookie() {
+        let cookie_secret = CookieSecret::generate();
+        let source = [192, 0, 2, 1, 0xca, 0x6c];
+        assert_eq!(
+            make_cookie(&cookie_secret, &source).unwrap(),
+            make_cookie(&cookie_secret, &source).unwrap()
+        );
+        assert_ne!(
+            make_cookie(&cookie_secret, &source).unwrap(),
+            make_cookie(&cookie_secret, &source[..5]).unwrap()
+        );
+        assert_ne!(
+            make_cookie(&cookie_secret, &source).unwrap(),
+            make_cookie(&CookieSecret::generate(), &source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cookie_reply() {
+        let peers = Peers::generate();
+        let responder_public = peers.responder_static.public_key().unwrap();
+        let mut initiation = [0u8; INITIATION_SIZE];
+        let _ = peers.initiate(None, &mut initiation);
+
+        let cookie = Cookie::generate();
+        let nonce = xchacha20::Nonce::generate();
+        let mut reply = [0u8; COOKIE_REPLY_SIZE];
+        seal_cookie_reply(&responder_public, &initiation, &cookie, &nonce, &mut reply).unwrap();
+        assert_eq!(&reply[4..8], &7u32.to_le_bytes());
+        assert_eq!(
+            open_cookie_reply(&responder_public, &initiation, &reply).unwrap(),
+            cookie
+        );
+
+        for idx in 0..COOKIE_REPLY_SIZE {
+            let mut modified = reply;
+            modified[idx] ^= 1;
+            assert!(open_cookie_reply(&responder_public, &initiation, &modified).is_err());
+        }
+        // The reply is bound to mac1 of the sent message.
+        let mut other_initiation = [0u8; INITIATION_SIZE];
+        let _ = peers.initiate(None, &mut other_initiation);
+        assert!(open_cookie_reply(&responder_public, &other_initiation, &reply).is_err());
+        assert!(open_cookie_reply(
+            &peers.initiator_static.public_key().unwrap(),
+            &initiation,
+            &reply
+        )
+        .is_err());
+
+        assert!(seal_cookie_reply(
+            &responder_public,
+            &initiation,
+            &cookie,
+            &nonce,
+            &mut reply[..63]
+        )
+        .is_err());
+        assert!(seal_cookie_reply(
+            &responder_public,
+            &initiation[..147],
+            &cookie,
+            &nonce,
+            &mut reply
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_tai64n() {
+        assert_eq!(
+            tai64n(0, 0).unwrap(),
+            [0x40, 0, 0, 0, 0, 0, 0, 0x0a, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            tai64n(1, 999_999_999).unwrap(),
+            [0x40, 0, 0, 0, 0, 0, 0, 0x0b, 0x3b, 0x9a, 0xc9, 0xff]
+        );
+        assert!(tai64n(0, 1_000_000_000).is_err());
+        assert!(tai64n(u64::MAX, 0).is_err());
+
+        // Later timestamps compare greater, byte by byte.
+        assert!(tai64n(1, 0).unwrap() > tai64n(0, 999_999_999).unwrap());
+        assert!(tai64n_now().unwrap() > tai64n(1700000000, 0).unwrap());
+    }
+
+    #[test]
+    fn test_transport() {
+        let (initiator_keys, responder_keys) = handshake(None);
+        let plaintext = [1u8; 32];
+        let mut message = [0u8; TRANSPORT_HEADER_SIZE + 32 + POLY1305_OUTSIZE];
+        seal_transport(&initiator_keys, 5, &plaintext, &mut message).unwrap();
+
+        let mut dst_pt = [0u8; 32];
+        assert_eq!(
+            open_transport(&responder_keys, &message, &mut dst_pt).unwrap(),
+            5
+        );
+        assert_eq!(dst_pt, plaintext);
+
+        // The initiator can't open its own message.
+        assert!(open_transport(&initiator_keys, &message, &mut dst_pt).is_err());
+
+        for idx in 0..message.len() {
+            let mut modified = message;
+            modified[idx] ^= 1;
+            assert!(open_transport(&responder_keys, &modified, &mut dst_pt).is_err());
+        }
+
+        seal_transport(&responder_keys, 0, &[], &mut message[..32]).unwrap();
+        assert_eq!(
+            open_transport(&initiator_keys, &message[..32], &mut []).unwrap(),
+            0
+        );
+        assert!(open_transport(&initiator_keys, &message[..31], &mut []).is_err());
+        assert!(seal_transport(&responder_keys, 0, &[], &mut message[..31]).is_err());
+    }
+
+    #[test]
+    fn test_transport_counter_limit() {
+        let (initiator_keys, responder_keys) = handshake(None);
+        let mut message = [0u8; TRANSPORT_HEADER_SIZE + POLY1305_OUTSIZE];
+        assert!(seal_transport(&initiator_keys, REJECT_AFTER_MESSAGES, &[], &mut message).is_err());
+        seal_transport(
+            &initiator_keys,
+            REJECT_AFTER_MESSAGES - 1,
+            &[],
+            &mut message,
+        )
+        .unwrap();
+        assert_eq!(
+            open_transport(&responder_keys, &message, &mut []).unwrap(),
+            REJECT_AFTER_MESSAGES - 1
+        );
+    }
+
+    #[test]
+    fn test_debug_impls_omit_secrets() {
+        let (initiator_keys, _) = handshake(None);
+        let debug = format!("{:?}", initiator_keys);
+        assert!(debug.contains("***OMITTED***"));
+        assert!(!debug.contains(&format!(
+            "{:?}",
+            initiator_keys.sending_key().unprotected_as_bytes()
+        )));
+    }
+}
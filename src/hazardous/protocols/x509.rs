@@ -0,0 +1,978 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Minimal X.509 certificates and certificate signing requests, as specified
+//! in [RFC 5280] and [RFC 2986].
+//!
+//! # About:
+//! This covers provisioning identities with Ed25519 ([RFC 8410]) and ECDSA
+//! P-256 with SHA-256 ([RFC 5758]) keys:
+//! - [`certificate_signing_request()`] builds and signs a PKCS #10 request
+//!   for a subject.
+//! - [`self_signed_certificate()`] builds and signs a certificate for the
+//!   key that signs it, such as a root CA.
+//! - [`issue_certificate()`] builds a certificate for a subject public key,
+//!   such as one from a verified signing request, signed by an issuer.
+//! - [`verify_certificate()`] and [`verify_certificate_signing_request()`]
+//!   verify the signatures of DER-encoded certificates and requests.
+//!
+//! Certificates are X.509 v3. Names consist of a common name and an optional
+//! organization, which are encoded as UTF8String. Certificates have the
+//! basic constraints and key usage extensions, the subject alternative name
+//! extension when DNS names are given, and subject and authority key
+//! identifiers. Key identifiers are the leftmost 160 bits of the SHA-256
+//! digest of the public key ([RFC 7093]).
+//!
+//! Times are seconds since the Unix epoch. They are encoded as UTCTime up
+//! to 2049, and as GeneralizedTime from 2050.
+//!
+//! # Parameters:
+//! - `key`: The key that signs the certificate or request.
+//! - `issuer`: The name of the issuer of the certificate.
+//! - `subject`: The name of the subject of the certificate or request.
+//! - `subject_public_key`: The public key the certificate is issued for.
+//! - `params`: The contents of the certificate.
+//! - `dns_names`: The DNS names requested for the subject.
+//! - `certificate`: A DER-encoded certificate.
+//! - `issuer_public_key`: The public key of the issuer of `certificate`.
+//! - `csr`: A DER-encoded certificate signing request.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - A name is empty, longer than 64 characters or contains control
+//!   characters.
+//! - A DNS name is empty, longer than 253 bytes or contains other characters
+//!   than ASCII letters, digits, `-`, `.` and `*`.
+//! - The serial number is zero, or longer than 20 bytes once encoded.
+//! - `not_before` is after `not_after`, or either is after the year 9999.
+//! - `certificate` or `csr` is not a supported DER encoding, or its public
+//!   key is not an Ed25519 or P-256 key.
+//! - The signature algorithm does not match the key.
+//! - The signature does not match.
+//!
+//! # Security:
+//! - [`verify_certificate()`] only verifies the signature of the certificate.
+//!   It does not check the validity period, the names, the extensions or
+//!   whether `issuer_public_key` belongs to a trusted issuer. It is not a
+//!   replacement for certificate path validation.
+//! - [`verify_certificate_signing_request()`] only proves possession of the
+//!   secret key of the returned public key. The subject and DNS names in the
+//!   request are not returned, and the issuer must decide what to certify
+//!   for the key by other means.
+//! - Serial numbers should be at least 64 bits of random data, such as 16
+//!   bytes from [`secure_rand_bytes()`].
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::ecc::{ecdsa_p256, ed25519};
+//! use orion::hazardous::protocols::x509::{self, CertificateParams, Name, SigningKey};
+//!
+//! // A root CA.
+//! let ca_secret_key = ed25519::SecretKey::generate();
+//! let ca_key = SigningKey::Ed25519(&ca_secret_key);
+//! let ca_name = Name {
+//!     common_name: "Device Root CA".into(),
+//!     organization: Some("Example".into()),
+//! };
+//! let mut serial_number = vec![0u8; 16];
+//! orion::util::secure_rand_bytes(&mut serial_number)?;
+//! let ca_certificate = x509::self_signed_certificate(
+//!     &ca_key,
+//!     &CertificateParams {
+//!         serial_number,
+//!         subject: ca_name.clone(),
+//!         not_before: 1_700_000_000,
+//!         not_after: 2_000_000_000,
+//!         dns_names: vec![],
+//!         is_ca: true,
+//!     },
+//! )?;
+//!
+//! // A device requests a certificate for its key.
+//! let device_secret_key = ecdsa_p256::SecretKey::generate();
+//! let device_name = Name {
+//!     common_name: "device-0042".into(),
+//!     organization: None,
+//! };
+//! let csr = x509::certificate_signing_request(
+//!     &SigningKey::EcdsaP256(&device_secret_key),
+//!     &device_name,
+//!     &["device-0042.example.com".into()],
+//! )?;
+//!
+//! // The CA checks the request and issues the certificate.
+//! let device_public_key = x509::verify_certificate_signing_request(&csr)?;
+//! let mut serial_number = vec![0u8; 16];
+//! orion::util::secure_rand_bytes(&mut serial_number)?;
+//! let device_certificate = x509::issue_certificate(
+//!     &ca_key,
+//!     &ca_name,
+//!     &device_public_key,
+//!     &CertificateParams {
+//!         serial_number,
+//!         subject: device_name,
+//!         not_before: 1_700_000_000,
+//!         not_after: 1_800_000_000,
+//!         dns_names: vec!["device-0042.example.com".into()],
+//!         is_ca: false,
+//!     },
+//! )?;
+//!
+//! let ca_public_key = x509::subject_public_key(&ca_certificate)?;
+//! assert!(x509::verify_certificate(&ca_certificate, &ca_public_key).is_ok());
+//! assert!(x509::verify_certificate(&device_certificate, &ca_public_key).is_ok());
+//! assert_eq!(x509::subject_public_key(&device_certificate)?, device_public_key);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 5280]: https://www.rfc-editor.org/rfc/rfc5280
+//! [RFC 2986]: https://www.rfc-editor.org/rfc/rfc2986
+//! [RFC 8410]: https://www.rfc-editor.org/rfc/rfc8410
+//! [RFC 5758]: https://www.rfc-editor.org/rfc/rfc5758
+//! [RFC 7093]: https://www.rfc-editor.org/rfc/rfc7093#section-2
+//! [`certificate_signing_request()`]: fn.certificate_signing_request.html
+//! [`self_signed_certificate()`]: fn.self_signed_certificate.html
+//! [`issue_certificate()`]: fn.issue_certificate.html
+//! [`verify_certificate()`]: fn.verify_certificate.html
+//! [`verify_certificate_signing_request()`]: fn.verify_certificate_signing_request.html
+//! [`secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        ecc::{ecdsa_p256, ed25519},
+        hash::sha256::{Sha256, SHA256_OUTSIZE},
+    },
+    util::der::{
+        self, DerReader, TAG_BIT_STRING, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE,
+    },
+};
+use core::convert::TryFrom;
+
+/// The maximum number of characters in a common name or organization.
+pub const MAX_NAME_LEN: usize = 64;
+/// The maximum length of a DNS name.
+pub const MAX_DNS_NAME_LEN: usize = 253;
+/// The maximum size of an encoded serial number.
+pub const MAX_SERIAL_NUMBER_SIZE: usize = 20;
+/// The largest supported time, 9999-12-31T23:59:59Z.
+pub const MAX_TIME: u64 = 253_402_300_799;
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_UTF8_STRING: u8 = 0x0C;
+const TAG_SET: u8 = 0x31;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+/// `[0]`, constructed.
+const TAG_CONTEXT_0: u8 = 0xA0;
+/// `[3]`, constructed.
+const TAG_CONTEXT_3: u8 = 0xA3;
+/// `[0] IMPLICIT`, primitive, the keyIdentifier of an authority key identifier.
+const TAG_KEY_IDENTIFIER: u8 = 0x80;
+/// `[2] IMPLICIT`, primitive, the dNSName of a general name.
+const TAG_DNS_NAME: u8 = 0x82;
+
+/// The AlgorithmIdentifier of Ed25519.
+const ED25519_ALGORITHM: [u8; 7] = [0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70];
+/// The AlgorithmIdentifier of ecdsa-with-SHA256.
+const ECDSA_P256_SHA256_ALGORITHM: [u8; 12] = [
+    0x30, 0x0A, 0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02,
+];
+/// The SubjectPublicKeyInfo of an Ed25519 public key, up to the key.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2A, 0x30, 0x05, 0x06, 0x03, 0x2B, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+/// The SubjectPublicKeyInfo of a P-256 public key, up to the key.
+const P256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x08, 0x2A,
+    0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+const OID_COMMON_NAME: [u8; 3] = [0x55, 0x04, 0x03];
+const OID_ORGANIZATION: [u8; 3] = [0x55, 0x04, 0x0A];
+const OID_EXTENSION_REQUEST: [u8; 9] = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x0E];
+const OID_SUBJECT_KEY_IDENTIFIER: [u8; 3] = [0x55, 0x1D, 0x0E];
+const OID_KEY_USAGE: [u8; 3] = [0x55, 0x1D, 0x0F];
+const OID_SUBJECT_ALT_NAME: [u8; 3] = [0x55, 0x1D, 0x11];
+const OID_BASIC_CONSTRAINTS: [u8; 3] = [0x55, 0x1D, 0x13];
+const OID_AUTHORITY_KEY_IDENTIFIER: [u8; 3] = [0x55, 0x1D, 0x23];
+
+/// The size of a key identifier.
+const KEY_IDENTIFIER_SIZE: usize = 20;
+
+#[derive(Debug)]
+/// A key to sign a certificate or certificate signing request with.
+pub enum SigningKey<'a> {
+    /// An Ed25519 secret key.
+    Ed25519(&'a ed25519::SecretKey),
+    /// An ECDSA P-256 secret key, used with SHA-256.
+    EcdsaP256(&'a ecdsa_p256::SecretKey),
+}
+
+impl SigningKey<'_> {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The public key of this key.
+    pub fn public_key(&self) -> Result<PublicKey, UnknownCryptoError> {
+        match self {
+            SigningKey::Ed25519(secret_key) => Ok(PublicKey::Ed25519(
+                ed25519::PublicKey::try_from(*secret_key)?,
+            )),
+            SigningKey::EcdsaP256(secret_key) => Ok(PublicKey::EcdsaP256(
+                ecdsa_p256::PublicKey::try_from(*secret_key)?,
+            )),
+        }
+    }
+
+    fn algorithm(&self) -> &'static [u8] {
+        match self {
+            SigningKey::Ed25519(_) => &ED25519_ALGORITHM,
+            SigningKey::EcdsaP256(_) => &ECDSA_P256_SHA256_ALGORITHM,
+        }
+    }
+
+    /// Sign `message`, returning the contents of the signature BIT STRING.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+        let mut signature = vec![0u8];
+        match self {
+            SigningKey::Ed25519(secret_key) => {
+                signature.extend_from_slice(ed25519::sign(secret_key, message)?.as_ref());
+            }
+            SigningKey::EcdsaP256(secret_key) => {
+                let raw = ecdsa_p256::sign(secret_key, message)?;
+                let mut encoded = [0u8; der::MAX_SIGNATURE_DER_SIZE];
+                let len = der::ecdsa_signature_to_der(raw.as_ref(), &mut encoded)?;
+                signature.extend_from_slice(&encoded[..len]);
+            }
+        }
+
+        Ok(signature)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A public key in a certificate or certificate signing request.
+pub enum PublicKey {
+    /// An Ed25519 public key.
+    Ed25519(ed25519::PublicKey),
+    /// An ECDSA P-256 public key, used with SHA-256.
+    EcdsaP256(ecdsa_p256::PublicKey),
+}
+
+impl PublicKey {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            PublicKey::Ed25519(public_key) => public_key.as_ref(),
+            PublicKey::EcdsaP256(public_key) => public_key.as_ref(),
+        }
+    }
+
+    /// Write the SubjectPublicKeyInfo of this key to `dst`.
+    fn write_spki(&self, dst: &mut Vec<u8>) {
+        match self {
+            PublicKey::Ed25519(_) => dst.extend_from_slice(&ED25519_SPKI_PREFIX),
+            PublicKey::EcdsaP256(_) => dst.extend_from_slice(&P256_SPKI_PREFIX),
+        }
+        dst.extend_from_slice(self.as_bytes());
+    }
+
+    /// Decode the complete encoding of a SubjectPublicKeyInfo.
+    fn from_spki(spki: &[u8]) -> Result<Self, UnknownCryptoError> {
+        if spki.starts_with(&ED25519_SPKI_PREFIX) {
+            let key = &spki[ED25519_SPKI_PREFIX.len()..];
+            Ok(PublicKey::Ed25519(ed25519::PublicKey::from_slice(key)?))
+        } else if spki.starts_with(&P256_SPKI_PREFIX) {
+            let key = &spki[P256_SPKI_PREFIX.len()..];
+            Ok(PublicKey::EcdsaP256(ecdsa_p256::PublicKey::from_slice(
+                key,
+            )?))
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    fn key_identifier(&self) -> Result<[u8; KEY_IDENTIFIER_SIZE], UnknownCryptoError> {
+        let mut digest = [0u8; SHA256_OUTSIZE];
+        Sha256::digest_into(self.as_bytes(), &mut digest)?;
+        let mut identifier = [0u8; KEY_IDENTIFIER_SIZE];
+        identifier.copy_from_slice(&digest[..KEY_IDENTIFIER_SIZE]);
+
+        Ok(identifier)
+    }
+
+    /// Verify the contents of a signature BIT STRING over `message`, made
+    /// with the algorithm whose complete AlgorithmIdentifier is `algorithm`.
+    fn verify(
+        &self,
+        algorithm: &[u8],
+        signature: &[u8],
+        message: &[u8],
+    ) -> Result<(), UnknownCryptoError> {
+        // No unused bits.
+        let signature = match signature {
+            [0, signature @ ..] => signature,
+            _ => return Err(UnknownCryptoError),
+        };
+
+        match self {
+            PublicKey::Ed25519(public_key) if algorithm == ED25519_ALGORITHM => ed25519::verify(
+                &ed25519::Signature::from_slice(signature)?,
+                public_key,
+                message,
+            ),
+            PublicKey::EcdsaP256(public_key) if algorithm == ECDSA_P256_SHA256_ALGORITHM => {
+                let mut raw = [0u8; ecdsa_p256::SIGNATURE_SIZE];
+                der::ecdsa_signature_from_der(signature, &mut raw)?;
+                ecdsa_p256::verify(
+                    &ecdsa_p256::Signature::from_slice(&raw)?,
+                    public_key,
+                    message,
+                )
+            }
+            _ => Err(UnknownCryptoError),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The name of a subject or issuer.
+pub struct Name {
+    /// The common name (CN).
+    pub common_name: String,
+    /// The organization (O).
+    pub organization: Option<String>,
+}
+
+impl Name {
+    /// Write the encoding of this name to `dst`.
+    fn write(&self, dst: &mut Vec<u8>) -> Result<(), UnknownCryptoError> {
+        let mut rdns = Vec::new();
+        if let Some(organization) = &self.organization {
+            write_attribute(&OID_ORGANIZATION, organization, &mut rdns)?;
+        }
+        write_attribute(&OID_COMMON_NAME, &self.common_name, &mut rdns)?;
+
+        write_tlv(TAG_SEQUENCE, &rdns, dst)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The contents of a certificate.
+pub struct CertificateParams {
+    /// The big-endian serial number. Leading zero bytes are ignored.
+    pub serial_number: Vec<u8>,
+    /// The subject of the certificate.
+    pub subject: Name,
+    /// The time from which the certificate is valid.
+    pub not_before: u64,
+    /// The time until which the certificate is valid.
+    pub not_after: u64,
+    /// The DNS names of the subject.
+    pub dns_names: Vec<String>,
+    /// Whether the subject is a CA, which may issue certificates.
+    pub is_ca: bool,
+}
+
+/// Write a TLV with the tag `tag` and the value `value` to `dst`.
+pub(super) fn write_tlv(
+    tag: u8,
+    value: &[u8],
+    dst: &mut Vec<u8>,
+) -> Result<(), UnknownCryptoError> {
+    let mut length = [0u8; 3];
+    let length_len = der::write_length(value.len(), &mut length)?;
+    dst.push(tag);
+    dst.extend_from_slice(&length[..length_len]);
+    dst.extend_from_slice(value);
+
+    Ok(())
+}
+
+/// Write an RDN with a single attribute of type `oid`, which is a UTF8String.
+fn write_attribute(oid: &[u8], value: &str, dst: &mut Vec<u8>) -> Result<(), UnknownCryptoError> {
+    if value.is_empty()
+        || value.chars().count() > MAX_NAME_LEN
+        || value.chars().any(char::is_control)
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut attribute = Vec::new();
+    write_tlv(TAG_OID, oid, &mut attribute)?;
+    write_tlv(TAG_UTF8_STRING, value.as_bytes(), &mut attribute)?;
+    let mut rdn = Vec::new();
+    write_tlv(TAG_SEQUENCE, &attribute, &mut rdn)?;
+
+    write_tlv(TAG_SET, &rdn, dst)
+}
+
+/// Write a non-zero big-endian serial number as an INTEGER.
+fn write_serial_number(serial_number: &[u8], dst: &mut Vec<u8>) -> Result<(), UnknownCryptoError> {
+    let zeroes = serial_number.iter().take_while(|b| **b == 0).count();
+    let serial_number = &serial_number[zeroes..];
+    let mut value = Vec::with_capacity(serial_number.len() + 1);
+    match serial_number.first() {
+        None => return Err(UnknownCryptoError),
+        Some(first) if first & 0x80 != 0 => value.push(0),
+        Some(_) => (),
+    }
+    value.extend_from_slice(serial_number);
+    if value.len() > MAX_SERIAL_NUMBER_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    write_tlv(TAG_INTEGER, &value, dst)
+}
+
+/// Push the last `width` decimal digits of `value` to `dst`.
+fn push_digits(value: u64, width: u32, dst: &mut Vec<u8>) {
+    for exp in (0..width).rev() {
+        dst.push(b'0' + ((value / 10u64.pow(exp)) % 10) as u8);
+    }
+}
+
+/// Write `time` as a UTCTime until 2049, and as a GeneralizedTime after.
+fn write_time(time: u64, dst: &mut Vec<u8>) -> Result<(), UnknownCryptoError> {
+    if time > MAX_TIME {
+        return Err(UnknownCryptoError);
+    }
+
+    // The proleptic Gregorian date of a number of days since 1970-01-01, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let z = time / 86400 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    let seconds = time % 86400;
+
+    let mut value = Vec::with_capacity(15);
+    let tag = if year < 2050 {
+        push_digits(year, 2, &mut value);
+        TAG_UTC_TIME
+    } else {
+        push_digits(year, 4, &mut value);
+        TAG_GENERALIZED_TIME
+    };
+    push_digits(month, 2, &mut value);
+    push_digits(day, 2, &mut value);
+    push_digits(seconds / 3600, 2, &mut value);
+    push_digits((seconds / 60) % 60, 2, &mut value);
+    push_digits(seconds % 60, 2, &mut value);
+    value.push(b'Z');
+
+    write_tlv(tag, &value, dst)
+}
+
+/// Write an Extension with the extension value `value`.
+fn write_extension(
+    oid: &[u8],
+    critical: bool,
+    value: &[u8],
+    dst: &mut Vec<u8>,
+) -> Result<(), UnknownCryptoError> {
+    let mut extension = Vec::new();
+    write_tlv(TAG_OID, oid, &mut extension)?;
+    if critical {
+        write_tlv(TAG_BOOLEAN, &[0xFF], &mut extension)?;
+    }
+    write_tlv(TAG_OCTET_STRING, value, &mut extension)?;
+
+    write_tlv(TAG_SEQUENCE, &extension, dst)
+}
+
+/// Write a subject alternative name extension with `dns_names`.
+fn write_subject_alt_name(
+    dns_names: &[String],
+    dst: &mut Vec<u8>,
+) -> Result<(), UnknownCryptoError> {
+    let mut names = Vec::new();
+    for dns_name in dns_names.iter() {
+        if dns_name.is_empty()
+            || dns_name.len() > MAX_DNS_NAME_LEN
+            || !dns_name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'*')
+        {
+            return Err(UnknownCryptoError);
+        }
+        write_tlv(TAG_DNS_NAME, dns_name.as_bytes(), &mut names)?;
+    }
+    let mut value = Vec::new();
+    write_tlv(TAG_SEQUENCE, &names, &mut value)?;
+
+    write_extension(&OID_SUBJECT_ALT_NAME, false, &value, dst)
+}
+
+/// Sign `tbs`, which is the complete encoding of the signed structure, and
+/// return the complete encoding of the certificate or request.
+fn write_signed(key: &SigningKey<'_>, tbs: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    let mut signed = tbs.to_vec();
+    signed.extend_from_slice(key.algorithm());
+    write_tlv(TAG_BIT_STRING, &key.sign(tbs)?, &mut signed)?;
+
+    let mut encoded = Vec::with_capacity(signed.len() + 4);
+    write_tlv(TAG_SEQUENCE, &signed, &mut encoded)?;
+
+    Ok(encoded)
+}
+
+/// A certificate or certificate signing request.
+struct Signed<'a> {
+    /// The complete encoding of the signed structure.
+    tbs: &'a [u8],
+    /// The complete encoding of the signature algorithm.
+    algorithm: &'a [u8],
+    /// The contents of the signature BIT STRING.
+    signature: &'a [u8],
+}
+
+impl<'a> Signed<'a> {
+    fn parse(input: &'a [u8]) -> Result<Self, UnknownCryptoError> {
+        let mut outer = DerReader::new(input);
+        let mut reader = DerReader::new(outer.read(TAG_SEQUENCE)?);
+        outer.finish()?;
+
+        let signed = Self {
+            tbs: reader.read_tlv(TAG_SEQUENCE)?,
+            algorithm: reader.read_tlv(TAG_SEQUENCE)?,
+            signature: reader.read(TAG_BIT_STRING)?,
+        };
+        reader.finish()?;
+
+        Ok(signed)
+    }
+
+    /// A reader of the contents of the signed structure.
+    fn tbs_reader(&self) -> Result<DerReader<'a>, UnknownCryptoError> {
+        Ok(DerReader::new(DerReader::new(self.tbs).read(TAG_SEQUENCE)?))
+    }
+}
+
+/// The parsed TBSCertificate of a certificate, up to the subject public key.
+struct Certificate<'a> {
+    signed: Signed<'a>,
+    subject_public_key: PublicKey,
+}
+
+impl<'a> Certificate<'a> {
+    fn parse(input: &'a [u8]) -> Result<Self, UnknownCryptoError> {
+        let signed = Signed::parse(input)?;
+        let mut tbs = signed.tbs_reader()?;
+        // Only v3.
+        if tbs.read(TAG_CONTEXT_0)? != [TAG_INTEGER, 0x01, 0x02] {
+            return Err(UnknownCryptoError);
+        }
+        tbs.read(TAG_INTEGER)?;
+        if tbs.read_tlv(TAG_SEQUENCE)? != signed.algorithm {
+            return Err(UnknownCryptoError);
+        }
+        // Issuer, validity and subject.
+        tbs.read(TAG_SEQUENCE)?;
+        tbs.read(TAG_SEQUENCE)?;
+        tbs.read(TAG_SEQUENCE)?;
+        let subject_public_key = PublicKey::from_spki(tbs.read_tlv(TAG_SEQUENCE)?)?;
+
+        Ok(Self {
+            signed,
+            subject_public_key,
+        })
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Build and sign a certificate for `subject_public_key`, issued by `issuer`
+/// with `key`, and return its DER encoding.
+pub fn issue_certificate(
+    key: &SigningKey<'_>,
+    issuer: &Name,
+    subject_public_key: &PublicKey,
+    params: &CertificateParams,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    if params.not_before > params.not_after {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut tbs = vec![TAG_CONTEXT_0, 0x03, TAG_INTEGER, 0x01, 0x02];
+    write_serial_number(&params.serial_number, &mut tbs)?;
+    tbs.extend_from_slice(key.algorithm());
+    issuer.write(&mut tbs)?;
+    let mut validity = Vec::new();
+    write_time(params.not_before, &mut validity)?;
+    write_time(params.not_after, &mut validity)?;
+    write_tlv(TAG_SEQUENCE, &validity, &mut tbs)?;
+    params.subject.write(&mut tbs)?;
+    subject_public_key.write_spki(&mut tbs);
+
+    let mut extensions = Vec::new();
+    let (basic_constraints, key_usage): (&[u8], &[u8]) = if params.is_ca {
+        // cA, and digitalSignature, keyCertSign and cRLSign.
+        (
+            &[0x30, 0x03, TAG_BOOLEAN, 0x01, 0xFF],
+            &[TAG_BIT_STRING, 0x02, 0x01, 0x86],
+        )
+    } else {
+        // digitalSignature.
+        (&[0x30, 0x00], &[TAG_BIT_STRING, 0x02, 0x07, 0x80])
+    };
+    write_extension(
+        &OID_BASIC_CONSTRAINTS,
+        true,
+        basic_constraints,
+        &mut extensions,
+    )?;
+    write_extension(&OID_KEY_USAGE, true, key_usage, &mut extensions)?;
+    if !params.dns_names.is_empty() {
+        write_subject_alt_name(&params.dns_names, &mut extensions)?;
+    }
+    let mut subject_key_identifier = Vec::new();
+    write_tlv(
+        TAG_OCTET_STRING,
+        &subject_public_key.key_identifier()?,
+        &mut subject_key_identifier,
+    )?;
+    write_extension(
+        &OID_SUBJECT_KEY_IDENTIFIER,
+        false,
+        &subject_key_identifier,
+        &mut extensions,
+    )?;
+    let mut key_identifier = Vec::new();
+    write_tlv(
+        TAG_KEY_IDENTIFIER,
+        &key.public_key()?.key_identifier()?,
+        &mut key_identifier,
+    )?;
+    let mut authority_key_identifier = Vec::new();
+    write_tlv(TAG_SEQUENCE, &key_identifier, &mut authority_key_identifier)?;
+    write_extension(
+        &OID_AUTHORITY_KEY_IDENTIFIER,
+        false,
+        &authority_key_identifier,
+        &mut extensions,
+    )?;
+    let mut extensions_seq = Vec::new();
+    write_tlv(TAG_SEQUENCE, &extensions, &mut extensions_seq)?;
+    write_tlv(TAG_CONTEXT_3, &extensions_seq, &mut tbs)?;
+
+    let mut encoded_tbs = Vec::with_capacity(tbs.len() + 4);
+    write_tlv(TAG_SEQUENCE, &tbs, &mut encoded_tbs)?;
+
+    write_signed(key, &encoded_tbs)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Build and sign a certificate for the public key of `key`, whose issuer is
+/// its subject, and return its DER encoding.
+pub fn self_signed_certificate(
+    key: &SigningKey<'_>,
+    params: &CertificateParams,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    issue_certificate(key, &params.subject, &key.public_key()?, params)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Build and sign a certificate signing request for `subject` and
+/// `dns_names` with `key`, and return its DER encoding.
+pub fn certificate_signing_request(
+    key: &SigningKey<'_>,
+    subject: &Name,
+    dns_names: &[String],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    // Version 1.
+    let mut info = vec![TAG_INTEGER, 0x01, 0x00];
+    subject.write(&mut info)?;
+    key.public_key()?.write_spki(&mut info);
+
+    let mut attributes = Vec::new();
+    if !dns_names.is_empty() {
+        let mut extensions = Vec::new();
+        write_subject_alt_name(dns_names, &mut extensions)?;
+        let mut values = Vec::new();
+        write_tlv(TAG_SEQUENCE, &extensions, &mut values)?;
+        let mut attribute = Vec::new();
+        write_tlv(TAG_OID, &OID_EXTENSION_REQUEST, &mut attribute)?;
+        write_tlv(TAG_SET, &values, &mut attribute)?;
+        write_tlv(TAG_SEQUENCE, &attribute, &mut attributes)?;
+    }
+    write_tlv(TAG_CONTEXT_0, &attributes, &mut info)?;
+
+    let mut encoded_info = Vec::with_capacity(info.len() + 4);
+    write_tlv(TAG_SEQUENCE, &info, &mut encoded_info)?;
+
+    write_signed(key, &encoded_info)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Return the subject public key of `certificate`, without verifying it.
+pub fn subject_public_key(certificate: &[u8]) -> Result<PublicKey, UnknownCryptoError> {
+    Ok(Certificate::parse(certificate)?.subject_public_key)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `certificate` is signed by the secret key of `issuer_public_key`.
+pub fn verify_certificate(
+    certificate: &[u8],
+    issuer_public_key: &PublicKey,
+) -> Result<(), UnknownCryptoError> {
+    let signed = Certificate::parse(certificate)?.signed;
+
+    issuer_public_key.verify(signed.algorithm, signed.signature, signed.tbs)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `csr` is signed by the secret key of its public key, and
+/// return the public key.
+pub fn verify_certificate_signing_request(csr: &[u8]) -> Result<PublicKey, UnknownCryptoError> {
+    let signed = Signed::parse(csr)?;
+    let mut info = signed.tbs_reader()?;
+    if info.read(TAG_INTEGER)? != [0x00] {
+        return Err(UnknownCryptoError);
+    }
+    // Subject.
+    info.read(TAG_SEQUENCE)?;
+    let public_key = PublicKey::from_spki(info.read_tlv(TAG_SEQUENCE)?)?;
+    info.read(TAG_CONTEXT_0)?;
+    info.finish()?;
+
+    public_key.verify(signed.algorithm, signed.signature, signed.tbs)?;
+
+    Ok(public_key)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn ed25519_key() -> ed25519::SecretKey {
+        ed25519::SecretKey::from_slice(&[0x42; 32]).unwrap()
+    }
+
+    fn p256_key() -> ecdsa_p256::SecretKey {
+        ecdsa_p256::SecretKey::from_slice(&[0x42; 32]).unwrap()
+    }
+
+    fn name() -> Name {
+        Name {
+            common_name: "device".into(),
+            organization: None,
+        }
+    }
+
+    fn params() -> CertificateParams {
+        CertificateParams {
+            serial_number: vec![0x01],
+            subject: name(),
+            not_before: 1_700_000_000,
+            not_after: 1_800_000_000,
+            dns_names: vec!["device.example.com".into()],
+            is_ca: false,
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let ed25519_key = ed25519_key();
+        let p256_key = p256_key();
+        let keys = [
+            SigningKey::Ed25519(&ed25519_key),
+            SigningKey::EcdsaP256(&p256_key),
+        ];
+
+        for key in keys.iter() {
+            let public_key = key.public_key().unwrap();
+            let certificate = self_signed_certificate(key, &params()).unwrap();
+            assert!(verify_certificate(&certificate, &public_key).is_ok());
+            assert_eq!(subject_public_key(&certificate).unwrap(), public_key);
+
+            let csr = certificate_signing_request(key, &name(), &[]).unwrap();
+            assert_eq!(
+                verify_certificate_signing_request(&csr).unwrap(),
+                public_key
+            );
+        }
+
+        let certificate = self_signed_certificate(&keys[0], &params()).unwrap();
+        let other_key = keys[1].public_key().unwrap();
+        assert!(verify_certificate(&certificate, &other_key).is_err());
+        let other_key = PublicKey::Ed25519(
+            ed25519::PublicKey::try_from(&ed25519::SecretKey::from_slice(&[0x43; 32]).unwrap())
+                .unwrap(),
+        );
+        assert!(verify_certificate(&certificate, &other_key).is_err());
+    }
+
+    #[test]
+    fn test_modified_err() {
+        let p256_key = p256_key();
+        let key = SigningKey::EcdsaP256(&p256_key);
+        let public_key = key.public_key().unwrap();
+        let certificate = self_signed_certificate(&key, &params()).unwrap();
+        let csr = certificate_signing_request(&key, &name(), &params().dns_names).unwrap();
+
+        // Every byte of the encodings is either parsed or signed.
+        for idx in 0..certificate.len() {
+            let mut modified = certificate.clone();
+            modified[idx] ^= 1;
+            assert!(verify_certificate(&modified, &public_key).is_err());
+        }
+        for idx in 0..csr.len() {
+            let mut modified = csr.clone();
+            modified[idx] ^= 1;
+            assert!(verify_certificate_signing_request(&modified).is_err());
+        }
+
+        assert!(verify_certificate(&certificate[..certificate.len() - 1], &public_key).is_err());
+        let mut appended = certificate.clone();
+        appended.push(0);
+        assert!(verify_certificate(&appended, &public_key).is_err());
+        assert!(verify_certificate(&csr, &public_key).is_err());
+        assert!(verify_certificate_signing_request(&certificate).is_err());
+        assert!(subject_public_key(&csr).is_err());
+    }
+
+    #[test]
+    fn test_invalid_names_err() {
+        let ed25519_key = ed25519_key();
+        let key = SigningKey::Ed25519(&ed25519_key);
+        let bad_names = ["", "tab\there", "new\nline"];
+
+        for bad in bad_names.iter() {
+            let mut params = params();
+            params.subject.common_name = (*bad).into();
+            assert!(self_signed_certificate(&key, &params).is_err());
+            params.subject.common_name = "device".into();
+            params.subject.organization = Some((*bad).into());
+            assert!(self_signed_certificate(&key, &params).is_err());
+        }
+
+        let mut long_name = params();
+        long_name.subject.common_name = "ü".repeat(MAX_NAME_LEN);
+        assert!(self_signed_certificate(&key, &long_name).is_ok());
+        long_name.subject.common_name.push('a');
+        assert!(self_signed_certificate(&key, &long_name).is_err());
+        assert!(certificate_signing_request(&key, &long_name.subject, &[]).is_err());
+
+        let bad_dns_names = ["", "under_score.example", "spa ce.example", "exämple.com"];
+        for bad in bad_dns_names.iter() {
+            let mut params = params();
+            params.dns_names.push((*bad).into());
+            assert!(self_signed_certificate(&key, &params).is_err());
+            assert!(certificate_signing_request(&key, &name(), &params.dns_names).is_err());
+        }
+        let mut params = params();
+        params.dns_names = vec!["a".repeat(MAX_DNS_NAME_LEN)];
+        assert!(self_signed_certificate(&key, &params).is_ok());
+        params.dns_names = vec!["a".repeat(MAX_DNS_NAME_LEN + 1)];
+        assert!(self_signed_certificate(&key, &params).is_err());
+    }
+
+    #[test]
+    fn test_serial_number() {
+        let ed25519_key = ed25519_key();
+        let key = SigningKey::Ed25519(&ed25519_key);
+        let mut params = params();
+
+        for bad in [vec![], vec![0], vec![0; 21], vec![0x80; 20], vec![1; 21]].iter() {
+            params.serial_number = bad.clone();
+            assert!(self_signed_certificate(&key, &params).is_err());
+        }
+        for good in [vec![0x7F; 20], vec![0x80; 19], vec![0, 0, 1]].iter() {
+            params.serial_number = good.clone();
+            assert!(self_signed_certificate(&key, &params).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_validity() {
+        let ed25519_key = ed25519_key();
+        let key = SigningKey::Ed25519(&ed25519_key);
+        let mut params = params();
+
+        params.not_before = params.not_after + 1;
+        assert!(self_signed_certificate(&key, &params).is_err());
+        params.not_before = params.not_after;
+        assert!(self_signed_certificate(&key, &params).is_ok());
+        params.not_before = 0;
+        params.not_after = MAX_TIME;
+        assert!(self_signed_certificate(&key, &params).is_ok());
+        params.not_after = MAX_TIME + 1;
+        assert!(self_signed_certificate(&key, &params).is_err());
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn time(value: u64) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        write_time(value, &mut encoded).unwrap();
+        encoded
+    }
+
+    #[test]
+    fn test_write_time() {
+        let vectors: [(u64, u8, &[u8]); 6] = [
+            (0, TAG_UTC_TIME, b"700101000000Z"),
+            (951_782_400, TAG_UTC_TIME, b"000229000000Z"),
+            (1_700_000_000, TAG_UTC_TIME, b"231114221320Z"),
+            (2_524_607_999, TAG_UTC_TIME, b"491231235959Z"),
+            (2_524_608_000, TAG_GENERALIZED_TIME, b"20500101000000Z"),
+            (MAX_TIME, TAG_GENERALIZED_TIME, b"99991231235959Z"),
+        ];
+        for (value, tag, expected) in vectors.iter() {
+            let encoded = time(*value);
+            assert_eq!(encoded[0], *tag);
+            assert_eq!(usize::from(encoded[1]), expected.len());
+            assert_eq!(&encoded[2..], *expected);
+        }
+
+        assert!(write_time(MAX_TIME + 1, &mut Vec::new()).is_err());
+        assert!(write_time(u64::MAX, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_spki_roundtrip() {
+        let secret_key = ecdsa_p256::SecretKey::from_slice(&[0x42; 32]).unwrap();
+        let public_key = SigningKey::EcdsaP256(&secret_key).public_key().unwrap();
+        let mut spki = Vec::new();
+        public_key.write_spki(&mut spki);
+        assert_eq!(
+            spki.len(),
+            P256_SPKI_PREFIX.len() + ecdsa_p256::PUBLIC_KEYSIZE
+        );
+        assert_eq!(PublicKey::from_spki(&spki).unwrap(), public_key);
+
+        assert!(PublicKey::from_spki(&spki[..spki.len() - 1]).is_err());
+        spki.push(0);
+        assert!(PublicKey::from_spki(&spki).is_err());
+        assert!(PublicKey::from_spki(&ED25519_SPKI_PREFIX).is_err());
+    }
+}
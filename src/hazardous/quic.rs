@@ -0,0 +1,539 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Key derivation and header protection for QUIC version 1.
+//!
+//! # About:
+//! This implements the parts of [RFC 9001] that sit between the TLS handshake
+//! and the packet protection AEAD:
+//! - [`initial_secrets()`] derives the client and server Initial secrets from
+//!   the Destination Connection ID of the client's first Initial packet.
+//! - [`expand_label()`] is HKDF-Expand-Label from TLS 1.3, with SHA-256.
+//!   [`packet_key()`], [`packet_iv()`], [`header_protection_key()`] and
+//!   [`next_secret()`] use it with the QUIC labels.
+//! - [`aes_header_mask()`] and [`chacha20_header_mask()`] compute the header
+//!   protection mask from a sample of the ciphertext, and
+//!   [`protect_header()`] and [`unprotect_header()`] apply it to a packet.
+//!
+//! Only the cipher suites that use SHA-256 are supported, which are
+//! TLS_AES_128_GCM_SHA256 and TLS_CHACHA20_POLY1305_SHA256. Initial packets
+//! always use TLS_AES_128_GCM_SHA256.
+//!
+//! # Parameters:
+//! - `connection_id`: The Destination Connection ID of the client's first
+//!   Initial packet.
+//! - `secret`: A traffic secret, such as the ones from [`initial_secrets()`].
+//! - `label`: The label, without the `"tls13 "` prefix.
+//! - `context`: The context, which is empty for all QUIC labels.
+//! - `hp_key`: The header protection key.
+//! - `sample`: The [`SAMPLE_SIZE`] bytes of the packet starting 4 bytes after
+//!   the start of the Packet Number field.
+//! - `mask`: A header protection mask.
+//! - `packet`: A packet, up to and including at least the Packet Number field.
+//! - `pn_offset`: The offset of the Packet Number field in `packet`.
+//! - `dst_out`: Destination buffer for the derived secret, key, IV or mask.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `connection_id` is greater than [`MAX_CONNECTION_ID_SIZE`] bytes.
+//! - `secret` is less than [`SECRET_SIZE`] bytes.
+//! - `"tls13 "` and `label` together, or `context`, are greater than 255 bytes.
+//! - `dst_out` is empty or greater than 255 * [`SECRET_SIZE`] bytes.
+//! - The length of `dst_out` is not the size that its function derives, where
+//!   that size is fixed.
+//! - `hp_key` is not 16 or 32 bytes for AES, or is not 32 bytes for ChaCha20.
+//! - `sample` is not [`SAMPLE_SIZE`] bytes.
+//! - `mask` is not [`MASK_SIZE`] bytes.
+//! - `packet` ends before the end of the Packet Number field.
+//!
+//! # Security:
+//! - Initial secrets only depend on the connection ID, which is sent in the
+//!   clear. Initial packets are therefore not confidential, and must not be
+//!   trusted more than unauthenticated data.
+//! - The Packet Number length is read from the first byte after it is
+//!   unmasked, so the whole Packet Number field must be available before
+//!   calling [`unprotect_header()`].
+//! - Header protection must be removed before the packet is decrypted, and the
+//!   packet must be discarded if decryption then fails. Do not act on the
+//!   unprotected header before that.
+//! - AES is a constant-time implementation without hardware acceleration, see
+//!   [`aes`].
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::quic;
+//!
+//! let connection_id = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+//! let mut client_secret = [0u8; quic::SECRET_SIZE];
+//! let mut server_secret = [0u8; quic::SECRET_SIZE];
+//! quic::initial_secrets(&connection_id, &mut client_secret, &mut server_secret)?;
+//!
+//! // Initial packets use AES-128-GCM.
+//! let mut key = [0u8; 16];
+//! let mut iv = [0u8; quic::IV_SIZE];
+//! let mut hp_key = [0u8; 16];
+//! quic::packet_key(&client_secret, &mut key)?;
+//! quic::packet_iv(&client_secret, &mut iv)?;
+//! quic::header_protection_key(&client_secret, &mut hp_key)?;
+//!
+//! // A long header packet with a 4-byte Packet Number at offset 18, followed
+//! // by the payload after it has been encrypted with `key` and `iv`.
+//! let mut packet = [0u8; 64];
+//! packet[0] = 0xc3;
+//! let pn_offset = 18;
+//!
+//! let mut mask = [0u8; quic::MASK_SIZE];
+//! let sample_offset = pn_offset + 4;
+//! quic::aes_header_mask(
+//!     &hp_key,
+//!     &packet[sample_offset..sample_offset + quic::SAMPLE_SIZE],
+//!     &mut mask,
+//! )?;
+//! quic::protect_header(&mask, &mut packet, pn_offset)?;
+//!
+//! // The receiver computes the same mask from the same sample.
+//! let pn_len = quic::unprotect_header(&mask, &mut packet, pn_offset)?;
+//! assert_eq!(pn_len, 4);
+//! assert_eq!(packet[0], 0xc3);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9001]: https://www.rfc-editor.org/rfc/rfc9001
+//! [`initial_secrets()`]: fn.initial_secrets.html
+//! [`expand_label()`]: fn.expand_label.html
+//! [`packet_key()`]: fn.packet_key.html
+//! [`packet_iv()`]: fn.packet_iv.html
+//! [`header_protection_key()`]: fn.header_protection_key.html
+//! [`next_secret()`]: fn.next_secret.html
+//! [`aes_header_mask()`]: fn.aes_header_mask.html
+//! [`chacha20_header_mask()`]: fn.chacha20_header_mask.html
+//! [`protect_header()`]: fn.protect_header.html
+//! [`unprotect_header()`]: fn.unprotect_header.html
+//! [`SAMPLE_SIZE`]: constant.SAMPLE_SIZE.html
+//! [`MASK_SIZE`]: constant.MASK_SIZE.html
+//! [`SECRET_SIZE`]: constant.SECRET_SIZE.html
+//! [`MAX_CONNECTION_ID_SIZE`]: constant.MAX_CONNECTION_ID_SIZE.html
+//! [`aes`]: ../block/aes/index.html
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::block::aes::{self, Aes, AES128_KEYSIZE, AES256_KEYSIZE, AES_BLOCKSIZE};
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+use crate::hazardous::kdf::hkdf::{self, Hkdf};
+use crate::hazardous::stream::chacha20::{
+    ChaCha20, CHACHA_BLOCKSIZE, CHACHA_KEYSIZE, IETF_CHACHA_NONCESIZE,
+};
+use crate::util::endianness::load_u32_le;
+use zeroize::Zeroize;
+
+/// The salt for Initial secrets in QUIC version 1.
+pub const INITIAL_SALT: [u8; 20] = [
+    0x38, 0x76, 0x2c, 0xf7, 0xf5, 0x59, 0x34, 0xb3, 0x4d, 0x17, 0x9a, 0xe6, 0xa4, 0xc8, 0x0c, 0xad,
+    0xcc, 0xbb, 0x7f, 0x0a,
+];
+/// The maximum size of a connection ID in QUIC version 1.
+pub const MAX_CONNECTION_ID_SIZE: usize = 20;
+/// The size of a traffic secret.
+pub const SECRET_SIZE: usize = SHA256_OUTSIZE;
+/// The size of a packet protection IV.
+pub const IV_SIZE: usize = 12;
+/// The size of the ciphertext sample used for header protection.
+pub const SAMPLE_SIZE: usize = 16;
+/// The size of a header protection mask.
+pub const MASK_SIZE: usize = 5;
+/// The prefix that TLS 1.3 adds to all labels.
+const LABEL_PREFIX: &[u8] = b"tls13 ";
+/// The maximum size of an encoded HkdfLabel.
+const MAX_HKDF_LABEL_SIZE: usize = 2 + 1 + 255 + 1 + 255;
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// HKDF-Expand-Label from TLS 1.3 with SHA-256, deriving `dst_out.len()`
+/// bytes from `secret`, `label` and `context`.
+pub fn expand_label(
+    secret: &[u8],
+    label: &[u8],
+    context: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if LABEL_PREFIX.len() + label.len() > 255 || context.len() > 255 {
+        return Err(UnknownCryptoError);
+    }
+    // Also checked by HKDF, but the length must fit in a u16 before that.
+    if dst_out.len() > 255 * SHA256_OUTSIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut hkdf_label = [0u8; MAX_HKDF_LABEL_SIZE];
+    hkdf_label[..2].copy_from_slice(&(dst_out.len() as u16).to_be_bytes());
+    hkdf_label[2] = (LABEL_PREFIX.len() + label.len()) as u8;
+    let mut len = 3;
+    hkdf_label[len..len + LABEL_PREFIX.len()].copy_from_slice(LABEL_PREFIX);
+    len += LABEL_PREFIX.len();
+    hkdf_label[len..len + label.len()].copy_from_slice(label);
+    len += label.len();
+    hkdf_label[len] = context.len() as u8;
+    len += 1;
+    hkdf_label[len..len + context.len()].copy_from_slice(context);
+    len += context.len();
+
+    Hkdf::<Sha256>::from_prk(secret)?.expand(Some(&hkdf_label[..len]), dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the client and server Initial secrets from `connection_id`.
+pub fn initial_secrets(
+    connection_id: &[u8],
+    client_out: &mut [u8],
+    server_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if connection_id.len() > MAX_CONNECTION_ID_SIZE
+        || client_out.len() != SECRET_SIZE
+        || server_out.len() != SECRET_SIZE
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut initial_secret = [0u8; SECRET_SIZE];
+    let res = hkdf::extract_into::<Sha256>(&INITIAL_SALT, connection_id, &mut initial_secret)
+        .and_then(|_| expand_label(&initial_secret, b"client in", &[], client_out))
+        .and_then(|_| expand_label(&initial_secret, b"server in", &[], server_out));
+    initial_secret.zeroize();
+
+    res
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the packet protection key from `secret`. The length of `dst_out`
+/// is the key size of the AEAD: 16 bytes for AES-128-GCM, 32 bytes for
+/// ChaCha20-Poly1305.
+pub fn packet_key(secret: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    expand_label(secret, b"quic key", &[], dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the packet protection IV from `secret`. `dst_out` must be
+/// [`IV_SIZE`] bytes.
+///
+/// [`IV_SIZE`]: constant.IV_SIZE.html
+pub fn packet_iv(secret: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != IV_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    expand_label(secret, b"quic iv", &[], dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the header protection key from `secret`. The length of `dst_out`
+/// is the key size of the AEAD, as for [`packet_key()`].
+///
+/// [`packet_key()`]: fn.packet_key.html
+pub fn header_protection_key(secret: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    expand_label(secret, b"quic hp", &[], dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Derive the secret after a key update from `secret`. `dst_out` must be
+/// [`SECRET_SIZE`] bytes. The header protection key is not updated.
+///
+/// [`SECRET_SIZE`]: constant.SECRET_SIZE.html
+pub fn next_secret(secret: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if dst_out.len() != SECRET_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    expand_label(secret, b"quic ku", &[], dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the header protection mask for AES-128-GCM or AES-256-GCM, which
+/// is the AES encryption of `sample` with `hp_key`.
+pub fn aes_header_mask(
+    hp_key: &[u8],
+    sample: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if (hp_key.len() != AES128_KEYSIZE && hp_key.len() != AES256_KEYSIZE)
+        || sample.len() != SAMPLE_SIZE
+        || dst_out.len() != MASK_SIZE
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    let ctx = Aes::new(&aes::SecretKey::from_slice(hp_key)?)?;
+    let mut block = [0u8; AES_BLOCKSIZE];
+    block.copy_from_slice(sample);
+    ctx.encrypt_block(&mut block)?;
+    dst_out.copy_from_slice(&block[..MASK_SIZE]);
+    block.zeroize();
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Compute the header protection mask for ChaCha20-Poly1305, which is the
+/// ChaCha20 keystream for `hp_key`, with the block counter and nonce taken
+/// from `sample`.
+pub fn chacha20_header_mask(
+    hp_key: &[u8],
+    sample: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if hp_key.len() != CHACHA_KEYSIZE || sample.len() != SAMPLE_SIZE || dst_out.len() != MASK_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut ctx = ChaCha20::new(hp_key, &sample[4..4 + IETF_CHACHA_NONCESIZE], true)?;
+    let mut keystream = [0u8; CHACHA_BLOCKSIZE];
+    ctx.keystream_block(load_u32_le(&sample[..4]), &mut keystream);
+    dst_out.copy_from_slice(&keystream[..MASK_SIZE]);
+    keystream.zeroize();
+
+    Ok(())
+}
+
+/// The bits of the first byte that header protection masks.
+fn first_byte_mask(first_byte: u8) -> u8 {
+    if first_byte & 0x80 == 0x80 {
+        // Long header.
+        0x0f
+    } else {
+        // Short header.
+        0x1f
+    }
+}
+
+/// Return the Packet Number length encoded in the unprotected `first_byte`.
+fn packet_number_len(first_byte: u8) -> usize {
+    usize::from(first_byte & 0x03) + 1
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Apply header protection with `mask` to the first byte and the Packet
+/// Number field at `pn_offset` of `packet`.
+pub fn protect_header(
+    mask: &[u8],
+    packet: &mut [u8],
+    pn_offset: usize,
+) -> Result<(), UnknownCryptoError> {
+    if mask.len() != MASK_SIZE || pn_offset == 0 || packet.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+    let pn_len = packet_number_len(packet[0]);
+    if packet.len() < pn_offset + pn_len {
+        return Err(UnknownCryptoError);
+    }
+
+    packet[0] ^= mask[0] & first_byte_mask(packet[0]);
+    xor_slices!(mask[1..=pn_len], packet[pn_offset..pn_offset + pn_len]);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Remove header protection with `mask` from the first byte and the Packet
+/// Number field at `pn_offset` of `packet`, returning the length of the
+/// Packet Number field.
+pub fn unprotect_header(
+    mask: &[u8],
+    packet: &mut [u8],
+    pn_offset: usize,
+) -> Result<usize, UnknownCryptoError> {
+    if mask.len() != MASK_SIZE || pn_offset == 0 || packet.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+    // The Header Form bit is not protected.
+    let first_byte = packet[0] ^ (mask[0] & first_byte_mask(packet[0]));
+    let pn_len = packet_number_len(first_byte);
+    if packet.len() < pn_offset + pn_len {
+        return Err(UnknownCryptoError);
+    }
+
+    packet[0] = first_byte;
+    xor_slices!(mask[1..=pn_len], packet[pn_offset..pn_offset + pn_len]);
+
+    Ok(pn_len)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    /// The connection ID from RFC 9001, Appendix A.
+    const CONNECTION_ID: [u8; 8] = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+
+    fn check_keys(secret: &[u8], key: &str, iv: &str, hp: &str) {
+        let mut dst = [0u8; 32];
+        let key_len = key.len() / 2;
+        packet_key(secret, &mut dst[..key_len]).unwrap();
+        assert_eq!(dst[..key_len], hex::decode(key).unwrap()[..]);
+        packet_iv(secret, &mut dst[..IV_SIZE]).unwrap();
+        assert_eq!(dst[..IV_SIZE], hex::decode(iv).unwrap()[..]);
+        header_protection_key(secret, &mut dst[..key_len]).unwrap();
+        assert_eq!(dst[..key_len], hex::decode(hp).unwrap()[..]);
+    }
+
+    #[test]
+    fn test_rfc9001_initial_keys() {
+        let mut client = [0u8; SECRET_SIZE];
+        let mut server = [0u8; SECRET_SIZE];
+        initial_secrets(&CONNECTION_ID, &mut client, &mut server).unwrap();
+
+        assert_eq!(
+            client[..],
+            hex::decode("c00cf151ca5be075ed0ebfb5c80323c42d6b7db67881289af4008f1f6c357aea")
+                .unwrap()[..]
+        );
+        check_keys(
+            &client,
+            "1f369613dd76d5467730efcbe3b1a22d",
+            "fa044b2f42a3fd3b46fb255c",
+            "9f50449e04a0e810283a1e9933adedd2",
+        );
+
+        assert_eq!(
+            server[..],
+            hex::decode("3c199828fd139efd216c155ad844cc81fb82fa8d7446fa7d78be803acdda951b")
+                .unwrap()[..]
+        );
+        check_keys(
+            &server,
+            "cf3a5331653c364c88f0f379b6067e37",
+            "0ac1493ca1905853b0bba03e",
+            "c206b8d9b9f0f37644430b490eeaa314",
+        );
+    }
+
+    #[test]
+    fn test_rfc9001_aes_header_protection() {
+        let hp = hex::decode("9f50449e04a0e810283a1e9933adedd2").unwrap();
+        let sample = hex::decode("d1b1c98dd7689fb8ec11d242b123dc9b").unwrap();
+        let mut mask = [0u8; MASK_SIZE];
+        aes_header_mask(&hp, &sample, &mut mask).unwrap();
+        assert_eq!(mask[..], hex::decode("437b9aec36").unwrap()[..]);
+
+        let header = hex::decode("c300000001088394c8f03e5157080000449e00000002").unwrap();
+        let mut packet = header.clone();
+        protect_header(&mask, &mut packet, 18).unwrap();
+        assert_eq!(
+            packet,
+            hex::decode("c000000001088394c8f03e5157080000449e7b9aec34").unwrap()
+        );
+        assert_eq!(unprotect_header(&mask, &mut packet, 18).unwrap(), 4);
+        assert_eq!(packet, header);
+    }
+
+    #[test]
+    fn test_rfc9001_chacha20_header_protection() {
+        let secret =
+            hex::decode("9ac312a7f877468ebe69422748ad00a15443f18203a07d6060f688f30f21632b")
+                .unwrap();
+        check_keys(
+            &secret,
+            "c6d98ff3441c3fe1b2182094f69caa2ed4b716b65488960a7a984979fb23e1c8",
+            "e0459b3474bdd0e44a41c144",
+            "25a282b9e82f06f21f488917a4fc8f1b73573685608597d0efcb076b0ab7a7a4",
+        );
+        let mut ku = [0u8; SECRET_SIZE];
+        next_secret(&secret, &mut ku).unwrap();
+        assert_eq!(
+            ku[..],
+            hex::decode("1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9")
+                .unwrap()[..]
+        );
+
+        let hp = hex::decode("25a282b9e82f06f21f488917a4fc8f1b73573685608597d0efcb076b0ab7a7a4")
+            .unwrap();
+        let sample = hex::decode("5e5cd55c41f69080575d7999c25a5bfb").unwrap();
+        let mut mask = [0u8; MASK_SIZE];
+        chacha20_header_mask(&hp, &sample, &mut mask).unwrap();
+        assert_eq!(mask[..], hex::decode("aefefe7d03").unwrap()[..]);
+
+        let header = hex::decode("4200bff4").unwrap();
+        let mut packet = header.clone();
+        protect_header(&mask, &mut packet, 1).unwrap();
+        assert_eq!(packet, hex::decode("4cfe4189").unwrap());
+        assert_eq!(unprotect_header(&mask, &mut packet, 1).unwrap(), 3);
+        assert_eq!(packet, header);
+    }
+
+    #[test]
+    fn test_expand_label_lengths() {
+        let secret = [0u8; SECRET_SIZE];
+        let mut out = [0u8; 32];
+        assert!(expand_label(&secret, &[0u8; 249], &[0u8; 255], &mut out).is_ok());
+        assert!(expand_label(&secret, &[0u8; 250], &[], &mut out).is_err());
+        assert!(expand_label(&secret, &[], &[0u8; 256], &mut out).is_err());
+        assert!(expand_label(&secret[..31], b"quic key", &[], &mut out).is_err());
+        assert!(expand_label(&secret, b"quic key", &[], &mut out[..0]).is_err());
+        assert!(expand_label(&secret, b"quic key", &[], &mut [0u8; 255 * 32]).is_ok());
+        assert!(expand_label(&secret, b"quic key", &[], &mut [0u8; 255 * 32 + 1]).is_err());
+
+        assert!(packet_iv(&secret, &mut out[..11]).is_err());
+        assert!(next_secret(&secret, &mut out[..31]).is_err());
+    }
+
+    #[test]
+    fn test_initial_secrets_lengths() {
+        let mut client = [0u8; SECRET_SIZE];
+        let mut server = [0u8; SECRET_SIZE];
+        assert!(initial_secrets(&[], &mut client, &mut server).is_ok());
+        assert!(initial_secrets(&[0u8; 20], &mut client, &mut server).is_ok());
+        assert!(initial_secrets(&[0u8; 21], &mut client, &mut server).is_err());
+        assert!(initial_secrets(&CONNECTION_ID, &mut client[..31], &mut server).is_err());
+        assert!(initial_secrets(&CONNECTION_ID, &mut client, &mut server[..31]).is_err());
+    }
+
+    #[test]
+    fn test_header_mask_lengths() {
+        let mut mask = [0u8; MASK_SIZE];
+        let sample = [0u8; SAMPLE_SIZE];
+        assert!(aes_header_mask(&[0u8; 16], &sample, &mut mask).is_ok());
+        assert!(aes_header_mask(&[0u8; 32], &sample, &mut mask).is_ok());
+        assert!(aes_header_mask(&[0u8; 24], &sample, &mut mask).is_err());
+        assert!(aes_header_mask(&[0u8; 16], &sample[..15], &mut mask).is_err());
+        assert!(aes_header_mask(&[0u8; 16], &sample, &mut mask[..4]).is_err());
+
+        assert!(chacha20_header_mask(&[0u8; 32], &sample, &mut mask).is_ok());
+        assert!(chacha20_header_mask(&[0u8; 16], &sample, &mut mask).is_err());
+        assert!(chacha20_header_mask(&[0u8; 32], &[0u8; 17], &mut mask).is_err());
+        assert!(chacha20_header_mask(&[0u8; 32], &sample, &mut [0u8; 6]).is_err());
+    }
+
+    #[test]
+    fn test_header_protection_bounds() {
+        let mask = [0xffu8; MASK_SIZE];
+        // Short header, 4-byte Packet Number at offset 1.
+        let mut packet = [0x43u8, 0, 0, 0, 0];
+        assert!(protect_header(&mask, &mut packet[..4], 1).is_err());
+        assert!(protect_header(&mask, &mut packet, 0).is_err());
+        assert!(protect_header(&mask[..4], &mut packet, 1).is_err());
+        assert!(protect_header(&mask, &mut [], 1).is_err());
+        protect_header(&mask, &mut packet, 1).unwrap();
+        // The Header Form and Fixed bits are not masked.
+        assert_eq!(packet, [0x5c, 0xff, 0xff, 0xff, 0xff]);
+
+        assert!(unprotect_header(&mask, &mut packet[..4], 1).is_err());
+        assert!(unprotect_header(&mask, &mut packet, 0).is_err());
+        assert_eq!(unprotect_header(&mask, &mut packet, 1).unwrap(), 4);
+        assert_eq!(packet, [0x43, 0, 0, 0, 0]);
+    }
+}
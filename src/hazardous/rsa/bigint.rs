@@ -0,0 +1,566 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Arithmetic on multi-precision integers, for RSA.
+//!
+//! Integers are stored as little-endian 64-bit limbs, and the number of limbs
+//! is treated as public. Apart from [`bit_len_vartime()`] and
+//! [`Modulus::invert_vartime()`], all operations run in constant time with
+//! respect to the values of the limbs.
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+/// a + b * c + carry, returning the low and high words.
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let wide = u128::from(a) + u128::from(b) * u128::from(c) + u128::from(carry);
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// a + b + carry, returning the sum and the carry.
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let wide = u128::from(a) + u128::from(b) + u128::from(carry);
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// a - b - borrow, returning the difference and the borrow.
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let wide = u128::from(a).wrapping_sub(u128::from(b) + u128::from(borrow));
+    (wide as u64, (wide >> 127) as u64)
+}
+
+/// Parse the big-endian integer `bytes` into `limbs` limbs. Returns `None` if
+/// it does not fit.
+pub(crate) fn from_be_bytes(bytes: &[u8], limbs: usize) -> Option<Vec<u64>> {
+    let mut out = vec![0u64; limbs];
+    let mut overflow = 0u8;
+    for (i, byte) in bytes.iter().rev().enumerate() {
+        if i / 8 < limbs {
+            out[i / 8] |= u64::from(*byte) << (8 * (i % 8));
+        } else {
+            overflow |= *byte;
+        }
+    }
+
+    if overflow != 0 {
+        out.iter_mut().zeroize();
+        return None;
+    }
+
+    Some(out)
+}
+
+/// Write `a` as a big-endian integer, filling all of `dst_out`. Limbs that do
+/// not fit in `dst_out` must be zero.
+pub(crate) fn to_be_bytes(a: &[u64], dst_out: &mut [u8]) {
+    let len = dst_out.len();
+    for (i, byte) in dst_out.iter_mut().enumerate() {
+        let pos = len - 1 - i;
+        *byte = match a.get(pos / 8) {
+            Some(limb) => (limb >> (8 * (pos % 8))) as u8,
+            None => 0,
+        };
+    }
+}
+
+/// The number of significant bits in `a`. Only for public values.
+pub(crate) fn bit_len_vartime(a: &[u64]) -> usize {
+    match a.iter().rposition(|limb| *limb != 0) {
+        Some(top) => top * 64 + 64 - a[top].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+/// a - b, writing the difference to `dst_out` and returning the borrow. `a`
+/// and `dst_out` must have the same length, and `b` may be shorter.
+pub(crate) fn sub(a: &[u64], b: &[u64], dst_out: &mut [u64]) -> u64 {
+    debug_assert!(a.len() == dst_out.len() && b.len() <= a.len());
+    let mut borrow = 0;
+    for (i, (a_i, out)) in a.iter().zip(dst_out.iter_mut()).enumerate() {
+        let (diff, next) = sbb(*a_i, b.get(i).copied().unwrap_or(0), borrow);
+        *out = diff;
+        borrow = next;
+    }
+
+    borrow
+}
+
+/// a += b, returning the carry. `b` may be shorter than `a`.
+pub(crate) fn add_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    debug_assert!(b.len() <= a.len());
+    let mut carry = 0;
+    for (i, a_i) in a.iter_mut().enumerate() {
+        let (sum, next) = adc(*a_i, b.get(i).copied().unwrap_or(0), carry);
+        *a_i = sum;
+        carry = next;
+    }
+
+    carry
+}
+
+/// The full product of `a` and `b`, with `a.len() + b.len()` limbs.
+pub(crate) fn mul(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut out = vec![0u64; a.len() + b.len()];
+    for (i, b_i) in b.iter().enumerate() {
+        let mut carry = 0;
+        for (j, a_j) in a.iter().enumerate() {
+            let (lo, hi) = mac(out[i + j], *a_j, *b_i, carry);
+            out[i + j] = lo;
+            carry = hi;
+        }
+        out[i + a.len()] = carry;
+    }
+
+    out
+}
+
+/// Whether `a` is less than `b`, where both have the same number of limbs.
+pub(crate) fn ct_lt(a: &[u64], b: &[u64]) -> Choice {
+    let mut diff = vec![0u64; a.len()];
+    let borrow = sub(a, b, &mut diff);
+    diff.iter_mut().zeroize();
+
+    Choice::from(borrow as u8)
+}
+
+/// Whether `a` and `b` are equal, where both have the same number of limbs.
+pub(crate) fn ct_eq(a: &[u64], b: &[u64]) -> Choice {
+    debug_assert!(a.len() == b.len());
+    a.ct_eq(b)
+}
+
+/// `x` mod `m`, for any non-zero `m`, with `m.len()` limbs.
+pub(crate) fn reduce(x: &[u64], m: &[u64]) -> Vec<u64> {
+    let n = m.len();
+    let mut r = vec![0u64; n + 1];
+    let mut diff = vec![0u64; n + 1];
+
+    // Shift in the bits of `x` one at a time, keeping r < m.
+    for i in (0..x.len() * 64).rev() {
+        let mut carry = (x[i / 64] >> (i % 64)) & 1;
+        for limb in r.iter_mut() {
+            let next = *limb >> 63;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+
+        let borrow = sub(&r, m, &mut diff);
+        let use_diff = Choice::from((borrow ^ 1) as u8);
+        for (limb, d) in r.iter_mut().zip(diff.iter()) {
+            limb.conditional_assign(d, use_diff);
+        }
+    }
+
+    diff.iter_mut().zeroize();
+    r.truncate(n);
+    r
+}
+
+/// Shift `a` right by one bit, shifting `carry` into the top bit.
+fn shift_right(a: &mut [u64], carry: u64) {
+    let mut carry = carry;
+    for limb in a.iter_mut().rev() {
+        let next = *limb & 1;
+        *limb = (*limb >> 1) | (carry << 63);
+        carry = next;
+    }
+}
+
+#[derive(Clone)]
+/// An odd modulus, with precomputed values for Montgomery arithmetic.
+pub(crate) struct Modulus {
+    m: Vec<u64>,
+    /// -m^-1 mod 2^64.
+    m0_inv: u64,
+    /// R mod m, where R = 2^(64 * limbs).
+    one: Vec<u64>,
+    /// R^2 mod m.
+    r2: Vec<u64>,
+}
+
+impl Drop for Modulus {
+    fn drop(&mut self) {
+        self.m.iter_mut().zeroize();
+        self.m0_inv.zeroize();
+        self.one.iter_mut().zeroize();
+        self.r2.iter_mut().zeroize();
+    }
+}
+
+impl Modulus {
+    /// Use `m` as a modulus. Returns `None` if `m` is even or less than 3.
+    pub(crate) fn new(m: &[u64]) -> Option<Self> {
+        if m.is_empty() || m[0] & 1 == 0 || bit_len_vartime(m) < 2 {
+            return None;
+        }
+
+        let n = m.len();
+        let mut m0_inv = 1u64;
+        // Newton's iteration doubles the number of correct bits each time.
+        for _ in 0..6 {
+            m0_inv = m0_inv.wrapping_mul(2u64.wrapping_sub(m[0].wrapping_mul(m0_inv)));
+        }
+
+        let mut power = vec![0u64; 2 * n + 1];
+        power[n] = 1;
+        let one = reduce(&power[..n + 1], m);
+        power[n] = 0;
+        power[2 * n] = 1;
+        let r2 = reduce(&power, m);
+
+        Some(Self {
+            m: m.to_vec(),
+            m0_inv: m0_inv.wrapping_neg(),
+            one,
+            r2,
+        })
+    }
+
+    /// The modulus.
+    pub(crate) fn limbs(&self) -> &[u64] {
+        &self.m
+    }
+
+    /// `x` mod m.
+    pub(crate) fn reduce(&self, x: &[u64]) -> Vec<u64> {
+        reduce(x, &self.m)
+    }
+
+    /// Montgomery multiplication, a * b / R mod m. Both `a` and `b` must be
+    /// less than m.
+    fn montgomery_mul(&self, a: &[u64], b: &[u64], dst_out: &mut [u64]) {
+        let n = self.m.len();
+        debug_assert!(a.len() == n && b.len() == n && dst_out.len() == n);
+
+        let mut t = vec![0u64; n + 2];
+        for b_i in b.iter() {
+            let mut carry = 0;
+            for j in 0..n {
+                let (lo, hi) = mac(t[j], a[j], *b_i, carry);
+                t[j] = lo;
+                carry = hi;
+            }
+            let (sum, c) = adc(t[n], carry, 0);
+            t[n] = sum;
+            t[n + 1] = c;
+
+            let u = t[0].wrapping_mul(self.m0_inv);
+            let (_, mut carry) = mac(t[0], u, self.m[0], 0);
+            for j in 1..n {
+                let (lo, hi) = mac(t[j], u, self.m[j], carry);
+                t[j - 1] = lo;
+                carry = hi;
+            }
+            let (sum, c) = adc(t[n], carry, 0);
+            t[n - 1] = sum;
+            t[n] = t[n + 1] + c;
+        }
+
+        // t < 2m, so subtracting m at most once is enough.
+        let borrow = sub(&t[..n], &self.m, dst_out);
+        let keep_t = Choice::from((borrow & (t[n] ^ 1)) as u8 & 1);
+        for (limb, t_j) in dst_out.iter_mut().zip(t.iter()) {
+            limb.conditional_assign(t_j, keep_t);
+        }
+
+        t.iter_mut().zeroize();
+    }
+
+    /// a * b mod m. Both `a` and `b` must be less than m.
+    pub(crate) fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut tmp = vec![0u64; self.m.len()];
+        let mut out = vec![0u64; self.m.len()];
+        self.montgomery_mul(a, b, &mut tmp);
+        self.montgomery_mul(&tmp, &self.r2, &mut out);
+        tmp.iter_mut().zeroize();
+
+        out
+    }
+
+    /// a - b mod m. Both `a` and `b` must be less than m.
+    pub(crate) fn sub(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut out = vec![0u64; self.m.len()];
+        let borrow = sub(a, b, &mut out);
+
+        let mut correction = self.m.clone();
+        for limb in correction.iter_mut() {
+            *limb &= borrow.wrapping_neg();
+        }
+        add_assign(&mut out, &correction);
+
+        out
+    }
+
+    /// a^-1 mod m, where `a` must be less than m, or `None` if `a` is not
+    /// coprime to m. The execution time depends on `a`, which must therefore
+    /// be public.
+    pub(crate) fn invert_vartime(&self, a: &[u64]) -> Option<Vec<u64>> {
+        let n = self.m.len();
+        let is_value = |x: &[u64], value: u64| x[0] == value && x[1..].iter().all(|l| *l == 0);
+
+        // Binary extended Euclid, keeping x1 * a = u and x2 * a = v mod m.
+        let mut u = a.to_vec();
+        let mut v = self.m.clone();
+        let mut x1 = vec![0u64; n];
+        x1[0] = 1;
+        let mut x2 = vec![0u64; n];
+        let mut tmp = vec![0u64; n];
+
+        // Halve `value` and `x`, keeping x * a = value mod m.
+        let halve = |value: &mut [u64], x: &mut [u64]| {
+            let carry = if x[0] & 1 == 1 {
+                add_assign(x, &self.m)
+            } else {
+                0
+            };
+            shift_right(value, 0);
+            shift_right(x, carry);
+        };
+
+        while !is_value(&u, 1) && !is_value(&v, 1) {
+            if is_value(&u, 0) || is_value(&v, 0) {
+                return None;
+            }
+            while u[0] & 1 == 0 {
+                halve(&mut u, &mut x1);
+            }
+            while v[0] & 1 == 0 {
+                halve(&mut v, &mut x2);
+            }
+
+            if sub(&u, &v, &mut tmp) == 0 {
+                u.copy_from_slice(&tmp);
+                x1 = self.sub(&x1, &x2);
+            } else {
+                sub(&v, &u, &mut tmp);
+                v.copy_from_slice(&tmp);
+                x2 = self.sub(&x2, &x1);
+            }
+        }
+
+        Some(if is_value(&u, 1) { x1 } else { x2 })
+    }
+
+    /// base^exp mod m, where `base` must be less than m. The time taken only
+    /// depends on the number of limbs of `exp`, not its value.
+    pub(crate) fn pow(&self, base: &[u64], exp: &[u64]) -> Vec<u64> {
+        let n = self.m.len();
+
+        // table[i] = base^i * R mod m
+        let mut table: Vec<Vec<u64>> = Vec::with_capacity(16);
+        table.push(self.one.clone());
+        let mut base_mont = vec![0u64; n];
+        self.montgomery_mul(base, &self.r2, &mut base_mont);
+        table.push(base_mont);
+        for i in 2..16 {
+            let mut entry = vec![0u64; n];
+            self.montgomery_mul(&table[i - 1], &table[1], &mut entry);
+            table.push(entry);
+        }
+
+        let mut acc = self.one.clone();
+        let mut tmp = vec![0u64; n];
+        let mut selected = vec![0u64; n];
+        for i in (0..exp.len() * 16).rev() {
+            for _ in 0..4 {
+                self.montgomery_mul(&acc, &acc, &mut tmp);
+                core::mem::swap(&mut acc, &mut tmp);
+            }
+
+            let window = (exp[i / 16] >> (4 * (i % 16))) & 0xf;
+            for (j, entry) in table.iter().enumerate() {
+                let choice = (j as u64).ct_eq(&window);
+                for (limb, e) in selected.iter_mut().zip(entry.iter()) {
+                    limb.conditional_assign(e, choice);
+                }
+            }
+            self.montgomery_mul(&acc, &selected, &mut tmp);
+            core::mem::swap(&mut acc, &mut tmp);
+        }
+
+        // Convert out of Montgomery form.
+        let mut unit = vec![0u64; n];
+        unit[0] = 1;
+        let mut out = vec![0u64; n];
+        self.montgomery_mul(&acc, &unit, &mut out);
+
+        for entry in table.iter_mut() {
+            entry.iter_mut().zeroize();
+        }
+        acc.iter_mut().zeroize();
+        tmp.iter_mut().zeroize();
+        selected.iter_mut().zeroize();
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn from_hex(hex_str: &str, limbs: usize) -> Vec<u64> {
+        from_be_bytes(&hex::decode(hex_str).unwrap(), limbs).unwrap()
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let bytes = hex::decode("0102030405060708090a0b0c0d0e0f10111213").unwrap();
+        let limbs = from_be_bytes(&bytes, 3).unwrap();
+        assert_eq!(limbs, [0x0c0d0e0f10111213, 0x0405060708090a0b, 0x010203]);
+        let mut out = [0u8; 19];
+        to_be_bytes(&limbs, &mut out);
+        assert_eq!(&out[..], &bytes[..]);
+
+        let mut padded = [0xffu8; 25];
+        to_be_bytes(&limbs, &mut padded);
+        assert_eq!(&padded[..6], &[0u8; 6]);
+        assert_eq!(&padded[6..], &bytes[..]);
+
+        assert!(from_be_bytes(&bytes, 2).is_none());
+        let mut leading_zeros = vec![0u8; 10];
+        leading_zeros.extend_from_slice(&bytes);
+        assert_eq!(from_be_bytes(&leading_zeros, 3).unwrap(), limbs);
+    }
+
+    #[test]
+    fn test_bit_len() {
+        assert_eq!(bit_len_vartime(&[0, 0]), 0);
+        assert_eq!(bit_len_vartime(&[1, 0]), 1);
+        assert_eq!(bit_len_vartime(&[0, 1]), 65);
+        assert_eq!(bit_len_vartime(&[u64::MAX, u64::MAX]), 128);
+    }
+
+    #[test]
+    fn test_add_sub_mul() {
+        let mut a = vec![u64::MAX, u64::MAX, 0];
+        assert_eq!(add_assign(&mut a, &[1]), 0);
+        assert_eq!(a, [0, 0, 1]);
+        assert_eq!(add_assign(&mut [u64::MAX], &[1]), 1);
+
+        let mut diff = vec![0u64; 3];
+        assert_eq!(sub(&a, &[1], &mut diff), 0);
+        assert_eq!(diff, [u64::MAX, u64::MAX, 0]);
+        assert_eq!(sub(&[0, 0], &[1], &mut diff[..2]), 1);
+
+        assert_eq!(
+            mul(&[u64::MAX, u64::MAX], &[u64::MAX]),
+            [1, u64::MAX, u64::MAX - 1]
+        );
+        assert!(bool::from(ct_lt(&[1, 2], &[2, 2])));
+        assert!(!bool::from(ct_lt(&[2, 2], &[2, 2])));
+        assert!(bool::from(ct_eq(&[2, 2], &[2, 2])));
+    }
+
+    #[test]
+    fn test_reduce() {
+        // 2^128 = 4 mod 7, so 2^128 + 5 = 2 mod 7.
+        assert_eq!(reduce(&[5, 0, 1], &[7]), [2]);
+        assert_eq!(reduce(&[12], &[7]), [5]);
+        // Even moduli are allowed. 2^64 = 6 mod 10, so 2^64 + 13 = 9 mod 10.
+        assert_eq!(reduce(&[13, 1], &[10]), [9]);
+    }
+
+    #[test]
+    fn test_invert_vartime() {
+        // 15 = 3 * 5, so only values coprime to 15 are invertible.
+        let modulus = Modulus::new(&[15]).unwrap();
+        for a in 1..15u64 {
+            match modulus.invert_vartime(&[a]) {
+                Some(inv) => assert_eq!(modulus.mul(&[a], &inv), [1]),
+                None => assert!(a % 3 == 0 || a % 5 == 0),
+            }
+        }
+        assert_eq!(modulus.invert_vartime(&[7]).unwrap(), [13]);
+        assert!(modulus.invert_vartime(&[6]).is_none());
+
+        // A modulus with its top bit set, where adding it to x overflows.
+        let m = [u64::MAX - 2, u64::MAX];
+        let modulus = Modulus::new(&m).unwrap();
+        let inv = modulus.invert_vartime(&[2, 0]).unwrap();
+        assert_eq!(modulus.mul(&[2, 0], &inv), [1, 0]);
+    }
+
+    #[test]
+    fn test_modulus_rejects() {
+        assert!(Modulus::new(&[]).is_none());
+        assert!(Modulus::new(&[1]).is_none());
+        assert!(Modulus::new(&[4, 1]).is_none());
+        assert!(Modulus::new(&[3]).is_some());
+    }
+
+    #[test]
+    fn test_modular_operations() {
+        // The RFC 3526 1536-bit MODP prime, checked against Python.
+        let p = from_hex(
+            "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74\
+             020bbea63b139b22514a08798e3404ddef9519b3cd3a431b302b0a6df25f1437\
+             4fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b0bff5cb6f406b7ed\
+             ee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf05\
+             98da48361c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb\
+             9ed529077096966d670c354e4abc9804f1746c08ca237327ffffffffffffffff",
+            24,
+        );
+        let modulus = Modulus::new(&p).unwrap();
+        let mut a = vec![0u64; 24];
+        a[0] = 2;
+        let mut b = p.clone();
+        b[0] -= 1;
+
+        // 2 * (p - 1) = p - 2 mod p
+        let mut expected = p.clone();
+        expected[0] -= 2;
+        assert_eq!(modulus.mul(&a, &b), expected);
+        // 2 - (p - 1) = 3 mod p
+        let mut three = vec![0u64; 24];
+        three[0] = 3;
+        assert_eq!(modulus.sub(&a, &b), three);
+        assert_eq!(modulus.sub(&b, &b), vec![0u64; 24]);
+
+        // Fermat: 2^(p - 1) = 1 mod p
+        let mut one = vec![0u64; 24];
+        one[0] = 1;
+        assert_eq!(modulus.pow(&a, &b), one);
+        assert_eq!(modulus.pow(&a, &[0]), one);
+        assert_eq!(modulus.pow(&a, &[10]), {
+            let mut v = vec![0u64; 24];
+            v[0] = 1024;
+            v
+        });
+        // 2^(p - 2) is the inverse of 2.
+        let mut p_minus_2 = p.clone();
+        p_minus_2[0] -= 2;
+        let inv = modulus.pow(&a, &p_minus_2);
+        assert_eq!(modulus.mul(&inv, &a), one);
+
+        assert_eq!(modulus.invert_vartime(&a).unwrap(), inv);
+        assert_eq!(modulus.invert_vartime(&b).unwrap(), b);
+        assert_eq!(modulus.invert_vartime(&one).unwrap(), one);
+        assert!(modulus.invert_vartime(&[0u64; 24]).is_none());
+
+        let wide = mul(&p, &b);
+        assert_eq!(modulus.reduce(&wide), vec![0u64; 24]);
+        assert_eq!(modulus.limbs(), &p[..]);
+    }
+}
@@ -0,0 +1,599 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! RSA blind signatures, as specified in [RFC 9474], with SHA-384 as both
+//! the hash function and the MGF1 hash function. All four variants of the
+//! RFC are supported, see [`Variant`].
+//!
+//! A client blinds a message, so that the signer can sign it without
+//! learning the message or being able to link the signature to the request
+//! later. The resulting signature is a standard RSASSA-PSS signature of the
+//! prepared message, which anyone can check with [`verify()`]:
+//! 1. The client calls [`prepare()`] on the message, and [`blind()`] on the
+//!    prepared message. It sends the blinded message to the signer and
+//!    keeps the inverse of the blinding factor.
+//! 2. The signer calls [`blind_sign()`], and sends the blind signature back.
+//! 3. The client calls [`finalize()`], which unblinds the signature and
+//!    checks it.
+//!
+//! See [`SecretKey`] and [`PublicKey`] for the supported keys and how to
+//! import them.
+//!
+//! # Parameters:
+//! - `variant`: The variant of the RFC, which must be the same for all
+//!   functions.
+//! - `secret_key`: The secret key of the signer.
+//! - `public_key`: The public key of the signer.
+//! - `message`: The message of the client.
+//! - `randomizer`: [`RANDOMIZERSIZE`] bytes of fresh randomness for the
+//!   randomized variants, or empty for the deterministic variants.
+//! - `prepared_message`: The output of [`prepare()`].
+//! - `salt`: [`SALTSIZE`] bytes of fresh randomness for the `Pss` variants, or
+//!   empty for the `PssZero` variants.
+//! - `blinding_seed`: [`BLINDING_SEEDSIZE`] bytes of fresh randomness, that
+//!   the blinding factor is derived from.
+//! - `blinded_message`: The blinded message, sent from the client to the
+//!   signer.
+//! - `inverse`: The inverse of the blinding factor, which the client keeps
+//!   until it finalizes the signature.
+//! - `blind_signature`: The signature of `blinded_message`, sent from the
+//!   signer to the client.
+//! - `signature`: The signature to be verified.
+//! - `dst_out`: Destination buffer for the prepared message or a signature.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `randomizer` or `salt` is not of the size that `variant` requires.
+//! - `blinding_seed` is not [`BLINDING_SEEDSIZE`] bytes.
+//! - `blinded_out`, `inverse_out`, `dst_out`, `blinded_message`,
+//!   `blind_signature`, `inverse` or `signature` is not
+//!   [`PublicKey::size()`] bytes.
+//! - The encoded message or the blinding factor is not invertible modulo n,
+//!   which happens with negligible probability for honestly generated keys.
+//! - The unblinded signature is not a valid signature of `prepared_message`.
+//!
+//! # Security:
+//! - The salt, randomizer and blinding seed should be generated using a
+//!   CSPRNG, such as [`util::secure_rand_bytes()`], for every signature.
+//! - A reused blinding seed gives the same blinded message for the same
+//!   prepared message, and lets the signer link the signature to the
+//!   request. The inverse must be kept secret for the same reason.
+//! - The deterministic variants are only safe to use when messages have high
+//!   entropy, or when the application does not rely on the signer not
+//!   learning whether two signatures are of the same message. Use the
+//!   randomized variants otherwise.
+//! - The signer learns nothing about the message. It must only use a key for
+//!   blind signatures, since [`blind_sign()`] signs any value.
+//! - [`blind_sign()`] runs the private key operation in constant time, blinds
+//!   its input and checks its result, as described in [`pss`].
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::rsa::blind::{self, Variant, BLINDING_SEEDSIZE, RANDOMIZERSIZE, SALTSIZE};
+//! use orion::hazardous::rsa::keys::SecretKey;
+//! use orion::util::secure_rand_bytes;
+//!
+//! # let secret_der = std::fs::read("tests/test_data/rsa/rsa2048_e65537.pk8.der").unwrap();
+//! let secret_key = SecretKey::from_pkcs8_der(&secret_der)?;
+//! let public_key = secret_key.public_key();
+//! let size = public_key.size();
+//! let variant = Variant::Sha384PssRandomized;
+//!
+//! // Client
+//! let mut randomizer = [0u8; RANDOMIZERSIZE];
+//! let mut salt = [0u8; SALTSIZE];
+//! let mut blinding_seed = [0u8; BLINDING_SEEDSIZE];
+//! secure_rand_bytes(&mut randomizer)?;
+//! secure_rand_bytes(&mut salt)?;
+//! secure_rand_bytes(&mut blinding_seed)?;
+//!
+//! let message = b"Message";
+//! let mut prepared_message = vec![0u8; RANDOMIZERSIZE + message.len()];
+//! blind::prepare(variant, message, &randomizer, &mut prepared_message)?;
+//! let (mut blinded_message, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+//! blind::blind(
+//!     &public_key,
+//!     variant,
+//!     &prepared_message,
+//!     &salt,
+//!     &blinding_seed,
+//!     &mut blinded_message,
+//!     &mut inverse,
+//! )?;
+//!
+//! // Signer
+//! let mut blind_signature = vec![0u8; size];
+//! blind::blind_sign(&secret_key, &blinded_message, &mut blind_signature)?;
+//!
+//! // Client
+//! let mut signature = vec![0u8; size];
+//! blind::finalize(
+//!     &public_key,
+//!     variant,
+//!     &prepared_message,
+//!     &blind_signature,
+//!     &inverse,
+//!     &mut signature,
+//! )?;
+//!
+//! // Anyone with the public key
+//! assert!(blind::verify(&public_key, variant, &prepared_message, &signature).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 9474]: https://www.rfc-editor.org/rfc/rfc9474
+//! [`Variant`]: enum.Variant.html
+//! [`prepare()`]: fn.prepare.html
+//! [`blind()`]: fn.blind.html
+//! [`blind_sign()`]: fn.blind_sign.html
+//! [`finalize()`]: fn.finalize.html
+//! [`verify()`]: fn.verify.html
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`PublicKey`]: struct.PublicKey.html
+//! [`PublicKey::size()`]: struct.PublicKey.html#method.size
+//! [`RANDOMIZERSIZE`]: constant.RANDOMIZERSIZE.html
+//! [`SALTSIZE`]: constant.SALTSIZE.html
+//! [`BLINDING_SEEDSIZE`]: constant.BLINDING_SEEDSIZE.html
+//! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+//! [`pss`]: ../pss/index.html
+
+use super::bigint;
+pub use super::keys::{PublicKey, SecretKey};
+use super::pss::{encode_with, mgf1_xor_with, verify_encoded_with};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha384::Sha384;
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+use zeroize::Zeroize;
+
+/// The size of the salt of the `Pss` variants.
+pub const SALTSIZE: usize = 48;
+/// The size of the random prefix of the randomized variants.
+pub const RANDOMIZERSIZE: usize = 32;
+/// The size of the seed that the blinding factor is derived from.
+pub const BLINDING_SEEDSIZE: usize = 32;
+/// The number of extra bytes of the blinding factor before it is reduced
+/// modulo n, making its bias negligible.
+const BLINDING_EXTRA_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A variant from RFC 9474.
+pub enum Variant {
+    /// RSABSSA-SHA384-PSS-Randomized.
+    Sha384PssRandomized,
+    /// RSABSSA-SHA384-PSSZERO-Randomized.
+    Sha384PssZeroRandomized,
+    /// RSABSSA-SHA384-PSS-Deterministic.
+    Sha384PssDeterministic,
+    /// RSABSSA-SHA384-PSSZERO-Deterministic.
+    Sha384PssZeroDeterministic,
+}
+
+impl Variant {
+    /// Return the size of the salt of this variant.
+    pub fn salt_size(&self) -> usize {
+        match self {
+            Variant::Sha384PssRandomized | Variant::Sha384PssDeterministic => SALTSIZE,
+            Variant::Sha384PssZeroRandomized | Variant::Sha384PssZeroDeterministic => 0,
+        }
+    }
+
+    /// Return the size of the random prefix that [`prepare()`] adds to the
+    /// message with this variant.
+    ///
+    /// [`prepare()`]: fn.prepare.html
+    pub fn randomizer_size(&self) -> usize {
+        match self {
+            Variant::Sha384PssRandomized | Variant::Sha384PssZeroRandomized => RANDOMIZERSIZE,
+            Variant::Sha384PssDeterministic | Variant::Sha384PssZeroDeterministic => 0,
+        }
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Prepare `message` for `variant`, writing `randomizer || message` to
+/// `dst_out`, which must be [`Variant::randomizer_size()`] bytes longer than
+/// `message`.
+///
+/// [`Variant::randomizer_size()`]: enum.Variant.html#method.randomizer_size
+pub fn prepare(
+    variant: Variant,
+    message: &[u8],
+    randomizer: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let prefix_len = variant.randomizer_size();
+    if randomizer.len() != prefix_len || dst_out.len() != prefix_len + message.len() {
+        return Err(UnknownCryptoError);
+    }
+
+    dst_out[..prefix_len].copy_from_slice(randomizer);
+    dst_out[prefix_len..].copy_from_slice(message);
+
+    Ok(())
+}
+
+/// Derive the blinding factor r from `blinding_seed`, as a uniformly
+/// distributed integer modulo n.
+fn blinding_factor(
+    public_key: &PublicKey,
+    blinding_seed: &[u8],
+) -> Result<Vec<u64>, UnknownCryptoError> {
+    let mut bytes = vec![0u8; public_key.size() + BLINDING_EXTRA_BYTES];
+    mgf1_xor_with::<Sha384>(blinding_seed, &mut bytes)?;
+    // This cannot fail, as the number of limbs fits all of `bytes`.
+    let mut wide = bigint::from_be_bytes(&bytes, bytes.len() / 8).unwrap();
+    let r = public_key.modulus().reduce(&wide);
+    bytes.iter_mut().zeroize();
+    wide.iter_mut().zeroize();
+
+    Ok(r)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Blind `prepared_message` for `public_key`, as the client, writing the
+/// blinded message to `blinded_out` and the inverse of the blinding factor
+/// to `inverse_out`.
+pub fn blind(
+    public_key: &PublicKey,
+    variant: Variant,
+    prepared_message: &[u8],
+    salt: &[u8],
+    blinding_seed: &[u8],
+    blinded_out: &mut [u8],
+    inverse_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let size = public_key.size();
+    if salt.len() != variant.salt_size()
+        || blinding_seed.len() != BLINDING_SEEDSIZE
+        || blinded_out.len() != size
+        || inverse_out.len() != size
+    {
+        return Err(UnknownCryptoError);
+    }
+
+    let n = public_key.modulus();
+    let mut em = vec![0u8; size];
+    encode_with::<Sha384>(prepared_message, salt, &mut em)?;
+    // This cannot fail, as `em` has the same length as the modulus. The top
+    // bit of `em` is clear, so m < n.
+    let mut m = bigint::from_be_bytes(&em, public_key.limbs()).unwrap();
+    let mut r = blinding_factor(public_key, blinding_seed)?;
+
+    // z = m * r^e mod n is sent to the signer, so it may be inverted in
+    // variable time. It is invertible exactly when both m and r are.
+    let mut r_e_minus_1 = n.pow(&r, &[public_key.exponent() - 1]);
+    let mut m_r_e_minus_1 = n.mul(&m, &r_e_minus_1);
+    let z = n.mul(&m_r_e_minus_1, &r);
+    let result = match n.invert_vartime(&z) {
+        // z^-1 * m * r^(e - 1) = r^-1.
+        Some(z_inv) => {
+            let mut inverse = n.mul(&z_inv, &m_r_e_minus_1);
+            bigint::to_be_bytes(&z, blinded_out);
+            bigint::to_be_bytes(&inverse, inverse_out);
+            inverse.iter_mut().zeroize();
+            Ok(())
+        }
+        None => Err(UnknownCryptoError),
+    };
+
+    em.iter_mut().zeroize();
+    for value in &mut [&mut m, &mut r, &mut r_e_minus_1, &mut m_r_e_minus_1] {
+        value.iter_mut().zeroize();
+    }
+
+    result
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `blinded_message` with `secret_key`, as the signer, writing the
+/// blind signature to `dst_out`.
+pub fn blind_sign(
+    secret_key: &SecretKey,
+    blinded_message: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    secret_key.raw_private(blinded_message, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Unblind `blind_signature` with `inverse`, as the client, and check that
+/// the result is a valid signature of `prepared_message` before writing it
+/// to `dst_out`.
+pub fn finalize(
+    public_key: &PublicKey,
+    variant: Variant,
+    prepared_message: &[u8],
+    blind_signature: &[u8],
+    inverse: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let size = public_key.size();
+    if blind_signature.len() != size || inverse.len() != size || dst_out.len() != size {
+        return Err(UnknownCryptoError);
+    }
+
+    let n = public_key.modulus();
+    // These cannot fail, as the inputs have the same length as the modulus.
+    let z = bigint::from_be_bytes(blind_signature, public_key.limbs()).unwrap();
+    let mut inverse = bigint::from_be_bytes(inverse, public_key.limbs()).unwrap();
+    if !bool::from(bigint::ct_lt(&z, n.limbs()) & bigint::ct_lt(&inverse, n.limbs())) {
+        inverse.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    let s = n.mul(&z, &inverse);
+    inverse.iter_mut().zeroize();
+    let mut signature = vec![0u8; size];
+    bigint::to_be_bytes(&s, &mut signature);
+    verify(public_key, variant, prepared_message, &signature)?;
+    dst_out.copy_from_slice(&signature);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `prepared_message` under
+/// `public_key`.
+pub fn verify(
+    public_key: &PublicKey,
+    variant: Variant,
+    prepared_message: &[u8],
+    signature: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut em = vec![0u8; public_key.size()];
+    bigint::to_be_bytes(&public_key.raw_public(signature)?, &mut em);
+
+    verify_encoded_with::<Sha384>(prepared_message, &mut em, variant.salt_size())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const SECRET_KEY: &[u8] = include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.pk8.der");
+    const VARIANT: Variant = Variant::Sha384PssDeterministic;
+
+    fn sign(
+        secret_key: &SecretKey,
+        message: &[u8],
+        blinding_seed: &[u8],
+    ) -> Result<Vec<u8>, UnknownCryptoError> {
+        let public_key = secret_key.public_key();
+        let size = public_key.size();
+        let (mut blinded, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+        blind(
+            &public_key,
+            VARIANT,
+            message,
+            &[1u8; SALTSIZE],
+            blinding_seed,
+            &mut blinded,
+            &mut inverse,
+        )?;
+        let mut blind_signature = vec![0u8; size];
+        blind_sign(secret_key, &blinded, &mut blind_signature)?;
+        let mut signature = vec![0u8; size];
+        finalize(
+            &public_key,
+            VARIANT,
+            message,
+            &blind_signature,
+            &inverse,
+            &mut signature,
+        )?;
+
+        Ok(signature)
+    }
+
+    #[test]
+    fn test_prepare() {
+        let mut prepared = [0u8; RANDOMIZERSIZE + 3];
+        prepare(
+            Variant::Sha384PssRandomized,
+            b"abc",
+            &[7u8; RANDOMIZERSIZE],
+            &mut prepared,
+        )
+        .unwrap();
+        assert_eq!(&prepared[..RANDOMIZERSIZE], &[7u8; RANDOMIZERSIZE]);
+        assert_eq!(&prepared[RANDOMIZERSIZE..], b"abc");
+        assert!(prepare(Variant::Sha384PssZeroRandomized, b"abc", &[], &mut prepared).is_err());
+        assert!(prepare(
+            Variant::Sha384PssRandomized,
+            b"abc",
+            &[7u8; 16],
+            &mut prepared
+        )
+        .is_err());
+
+        let mut prepared = [0u8; 3];
+        prepare(Variant::Sha384PssDeterministic, b"abc", &[], &mut prepared).unwrap();
+        assert_eq!(&prepared, b"abc");
+        assert!(prepare(
+            Variant::Sha384PssZeroDeterministic,
+            b"ab",
+            &[],
+            &mut prepared
+        )
+        .is_err());
+        assert!(prepare(
+            Variant::Sha384PssDeterministic,
+            b"abc",
+            &[0u8; 32],
+            &mut prepared
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_salt_size() {
+        let public_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap().public_key();
+        let size = public_key.size();
+        let (mut blinded, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+        let seed = [0u8; BLINDING_SEEDSIZE];
+        for variant in [
+            Variant::Sha384PssZeroRandomized,
+            Variant::Sha384PssZeroDeterministic,
+        ]
+        .iter()
+        {
+            assert_eq!(variant.salt_size(), 0);
+            assert!(blind(
+                &public_key,
+                *variant,
+                b"",
+                &[],
+                &seed,
+                &mut blinded,
+                &mut inverse
+            )
+            .is_ok());
+            assert!(blind(
+                &public_key,
+                *variant,
+                b"",
+                &[0u8; SALTSIZE],
+                &seed,
+                &mut blinded,
+                &mut inverse
+            )
+            .is_err());
+        }
+        assert!(blind(
+            &public_key,
+            VARIANT,
+            b"",
+            &[],
+            &seed,
+            &mut blinded,
+            &mut inverse
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let secret_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap();
+        let public_key = secret_key.public_key();
+        let signature = sign(&secret_key, b"message", &[2u8; BLINDING_SEEDSIZE]).unwrap();
+        assert!(verify(&public_key, VARIANT, b"message", &signature).is_ok());
+        assert!(verify(&public_key, VARIANT, b"Message", &signature).is_err());
+
+        // The signature does not depend on the blinding factor.
+        let other = sign(&secret_key, b"message", &[3u8; BLINDING_SEEDSIZE]).unwrap();
+        assert_eq!(signature, other);
+    }
+
+    #[test]
+    fn test_blinded_message_depends_on_seed() {
+        let public_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap().public_key();
+        let size = public_key.size();
+        let mut blinded = [vec![0u8; size], vec![0u8; size]];
+        let mut inverse = vec![0u8; size];
+        for (seed, blinded) in [1u8, 2u8].iter().zip(blinded.iter_mut()) {
+            blind(
+                &public_key,
+                VARIANT,
+                b"message",
+                &[1u8; SALTSIZE],
+                &[*seed; BLINDING_SEEDSIZE],
+                blinded,
+                &mut inverse,
+            )
+            .unwrap();
+        }
+        assert_ne!(blinded[0], blinded[1]);
+    }
+
+    #[test]
+    fn test_bad_lengths() {
+        let public_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap().public_key();
+        let size = public_key.size();
+        let (mut blinded, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+        let seed = [0u8; BLINDING_SEEDSIZE];
+        let salt = [0u8; SALTSIZE];
+        assert!(blind(
+            &public_key,
+            VARIANT,
+            b"",
+            &salt[1..],
+            &seed,
+            &mut blinded,
+            &mut inverse
+        )
+        .is_err());
+        assert!(blind(
+            &public_key,
+            VARIANT,
+            b"",
+            &salt,
+            &seed[1..],
+            &mut blinded,
+            &mut inverse
+        )
+        .is_err());
+        assert!(blind(
+            &public_key,
+            VARIANT,
+            b"",
+            &salt,
+            &seed,
+            &mut blinded[1..],
+            &mut inverse
+        )
+        .is_err());
+        assert!(blind(
+            &public_key,
+            VARIANT,
+            b"",
+            &salt,
+            &seed,
+            &mut blinded,
+            &mut inverse
+        )
+        .is_ok());
+
+        let mut signature = vec![0u8; size];
+        assert!(finalize(
+            &public_key,
+            VARIANT,
+            b"",
+            &blinded[1..],
+            &inverse,
+            &mut signature
+        )
+        .is_err());
+        // An inverse that is not less than the modulus.
+        let all_ones = vec![0xFFu8; size];
+        assert!(finalize(
+            &public_key,
+            VARIANT,
+            b"",
+            &blinded,
+            &all_ones,
+            &mut signature
+        )
+        .is_err());
+    }
+}
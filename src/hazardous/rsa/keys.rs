@@ -0,0 +1,672 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! RSA public and secret keys.
+//!
+//! Only two-prime keys with a 2048, 3072 or 4096-bit modulus are supported.
+//! Public keys are imported from a DER-encoded SubjectPublicKeyInfo
+//! ([RFC 5280]), and secret keys from a DER-encoded PKCS #8 PrivateKeyInfo
+//! ([RFC 5208]), both with the rsaEncryption algorithm. Secret keys are
+//! checked for consistency when they are imported.
+//!
+//! [RFC 5280]: https://www.rfc-editor.org/rfc/rfc5280#section-4.1
+//! [RFC 5208]: https://www.rfc-editor.org/rfc/rfc5208#section-5
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::vec::Vec;
+
+use super::bigint::{self, Modulus};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha512::{Sha512, SHA512_OUTSIZE};
+use crate::util::der::{
+    DerReader, TAG_BIT_STRING, TAG_NULL, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE,
+};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroize;
+
+/// The supported sizes of the modulus, in bits.
+const MODULUS_BITS: [usize; 3] = [2048, 3072, 4096];
+
+/// The contents of the rsaEncryption OID, 1.2.840.113549.1.1.1.
+const RSA_ENCRYPTION: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// Read a SEQUENCE that makes up all of `der`, and return a reader for its
+/// contents.
+fn read_outer_sequence(der: &[u8]) -> Result<DerReader<'_>, UnknownCryptoError> {
+    let mut outer = DerReader::new(der);
+    let sequence = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+
+    Ok(sequence)
+}
+
+/// Read an rsaEncryption AlgorithmIdentifier, with NULL parameters.
+fn read_rsa_algorithm(reader: &mut DerReader<'_>) -> Result<(), UnknownCryptoError> {
+    let mut algorithm = DerReader::new(reader.read(TAG_SEQUENCE)?);
+    if algorithm.read(TAG_OID)? != RSA_ENCRYPTION || !algorithm.read(TAG_NULL)?.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    algorithm.finish()
+}
+
+/// Read an INTEGER that must be zero, as used for version numbers.
+fn read_version_zero(reader: &mut DerReader<'_>) -> Result<(), UnknownCryptoError> {
+    if reader.read_unsigned_integer()? != [0] {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+/// An RSA public key.
+pub struct PublicKey {
+    n: Modulus,
+    e: u64,
+    size: usize,
+}
+
+impl Clone for PublicKey {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n.clone(),
+            e: self.e,
+            size: self.size,
+        }
+    }
+}
+
+impl core::fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut n = vec![0u8; self.size];
+        bigint::to_be_bytes(self.n.limbs(), &mut n);
+        f.debug_struct("PublicKey")
+            .field("n", &n)
+            .field("e", &self.e)
+            .finish()
+    }
+}
+
+impl PartialEq for PublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.n.limbs() == other.n.limbs() && self.e == other.e
+    }
+}
+
+impl Eq for PublicKey {}
+
+impl PublicKey {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct from the big-endian modulus and public exponent. The public
+    /// exponent must be odd, at least 3 and less than 2^64.
+    pub fn from_components(modulus: &[u8], exponent: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let modulus = strip_leading_zeros(modulus);
+        let exponent = strip_leading_zeros(exponent);
+        if !MODULUS_BITS.contains(&(modulus.len() * 8)) || modulus[0] < 0x80 {
+            return Err(UnknownCryptoError);
+        }
+
+        let e = match bigint::from_be_bytes(exponent, 1) {
+            Some(e) if e[0] & 1 == 1 && e[0] >= 3 => e[0],
+            _ => return Err(UnknownCryptoError),
+        };
+        // This cannot fail, as the modulus has exactly as many limbs as it
+        // needs.
+        let limbs = bigint::from_be_bytes(modulus, modulus.len() / 8).unwrap();
+        let n = Modulus::new(&limbs).ok_or(UnknownCryptoError)?;
+
+        Ok(Self {
+            n,
+            e,
+            size: modulus.len(),
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Import a DER-encoded SubjectPublicKeyInfo.
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut spki = read_outer_sequence(der)?;
+        read_rsa_algorithm(&mut spki)?;
+        let bit_string = spki.read(TAG_BIT_STRING)?;
+        spki.finish()?;
+
+        // There must be no unused bits.
+        let public_key = match bit_string {
+            [0, rest @ ..] => rest,
+            _ => return Err(UnknownCryptoError),
+        };
+        let mut rsa_public_key = read_outer_sequence(public_key)?;
+        let n = rsa_public_key.read_unsigned_integer()?;
+        let e = rsa_public_key.read_unsigned_integer()?;
+        rsa_public_key.finish()?;
+
+        Self::from_components(n, e)
+    }
+
+    /// Return the size of the modulus, and of signatures and ciphertexts,
+    /// in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of 64-bit limbs in the modulus.
+    pub(crate) fn limbs(&self) -> usize {
+        self.size / 8
+    }
+
+    /// The modulus n.
+    pub(crate) fn modulus(&self) -> &Modulus {
+        &self.n
+    }
+
+    /// The public exponent e.
+    pub(crate) fn exponent(&self) -> u64 {
+        self.e
+    }
+
+    /// Compute input^e mod n, where `input` is a big-endian integer of
+    /// [`PublicKey::size()`] bytes that must be less than n.
+    pub(crate) fn raw_public(&self, input: &[u8]) -> Result<Vec<u64>, UnknownCryptoError> {
+        if input.len() != self.size {
+            return Err(UnknownCryptoError);
+        }
+
+        // This cannot fail, as `input` has the same length as the modulus.
+        let x = bigint::from_be_bytes(input, self.limbs()).unwrap();
+        if !bool::from(bigint::ct_lt(&x, self.n.limbs())) {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(self.n.pow(&x, &[self.e]))
+    }
+}
+
+/// An RSA secret key.
+pub struct SecretKey {
+    public: PublicKey,
+    p: Modulus,
+    q: Modulus,
+    dp: Vec<u64>,
+    dq: Vec<u64>,
+    qinv: Vec<u64>,
+    /// A key for deriving the blinding factors, from the secret key.
+    blinding_key: [u8; SHA512_OUTSIZE],
+}
+
+impl_omitted_debug_trait!(SecretKey);
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.dp.iter_mut().zeroize();
+        self.dq.iter_mut().zeroize();
+        self.qinv.iter_mut().zeroize();
+        self.blinding_key.zeroize();
+    }
+}
+
+impl SecretKey {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Import a DER-encoded PKCS #8 PrivateKeyInfo. The private exponent is
+    /// not used, as the private key operation is done using the CRT
+    /// parameters.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut pkcs8 = read_outer_sequence(der)?;
+        read_version_zero(&mut pkcs8)?;
+        read_rsa_algorithm(&mut pkcs8)?;
+        let private_key = pkcs8.read(TAG_OCTET_STRING)?;
+        pkcs8.finish()?;
+
+        let mut rsa_private_key = read_outer_sequence(private_key)?;
+        // Only two-prime keys, which have version 0, are supported.
+        read_version_zero(&mut rsa_private_key)?;
+        let n = rsa_private_key.read_unsigned_integer()?;
+        let e = rsa_private_key.read_unsigned_integer()?;
+        let _d = rsa_private_key.read_unsigned_integer()?;
+        let p = rsa_private_key.read_unsigned_integer()?;
+        let q = rsa_private_key.read_unsigned_integer()?;
+        let dp = rsa_private_key.read_unsigned_integer()?;
+        let dq = rsa_private_key.read_unsigned_integer()?;
+        let qinv = rsa_private_key.read_unsigned_integer()?;
+        rsa_private_key.finish()?;
+
+        Self::from_crt_components(n, e, p, q, dp, dq, qinv)
+    }
+
+    /// Construct from the big-endian modulus, public exponent and CRT
+    /// parameters, checking that they are consistent.
+    fn from_crt_components(
+        n: &[u8],
+        e: &[u8],
+        p: &[u8],
+        q: &[u8],
+        dp: &[u8],
+        dq: &[u8],
+        qinv: &[u8],
+    ) -> Result<Self, UnknownCryptoError> {
+        let public = PublicKey::from_components(n, e)?;
+        let half = public.limbs() / 2;
+        let p = parse_prime(p, half)?;
+        let q = parse_prime(q, half)?;
+        let dp = parse_crt_exponent(dp, &p, public.e)?;
+        let dq = parse_crt_exponent(dq, &q, public.e)?;
+        let qinv = parse_reduced(qinv, &p)?;
+
+        let mut is_valid = bigint::ct_eq(&bigint::mul(p.limbs(), q.limbs()), public.n.limbs());
+        is_valid &= is_one(&p.mul(&p.reduce(q.limbs()), &qinv));
+        if !bool::from(is_valid) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut blinding_key = [0u8; SHA512_OUTSIZE];
+        let mut state = Sha512::new();
+        state.update(b"orion RSA blinding key")?;
+        for limbs in &[p.limbs(), q.limbs()] {
+            let mut bytes = vec![0u8; half * 8];
+            bigint::to_be_bytes(limbs, &mut bytes);
+            state.update(&bytes)?;
+            bytes.iter_mut().zeroize();
+        }
+        state.finalize_into(&mut blinding_key)?;
+
+        Ok(Self {
+            public,
+            p,
+            q,
+            dp,
+            dq,
+            qinv,
+            blinding_key,
+        })
+    }
+
+    /// Return the public key.
+    pub fn public_key(&self) -> PublicKey {
+        self.public.clone()
+    }
+
+    /// Return the size of the modulus, and of signatures and ciphertexts,
+    /// in bytes.
+    pub fn size(&self) -> usize {
+        self.public.size
+    }
+
+    /// Derive a blinding factor from the blinding key and `input`, as a
+    /// big-endian integer of the size of the modulus.
+    fn blinding_factor(&self, input: &[u8]) -> Result<Vec<u64>, UnknownCryptoError> {
+        let mut seed = [0u8; SHA512_OUTSIZE];
+        let mut state = Sha512::new();
+        state.update(&self.blinding_key)?;
+        state.update(input)?;
+        state.finalize_into(&mut seed)?;
+
+        let mut bytes = vec![0u8; self.size()];
+        for (counter, chunk) in bytes.chunks_mut(SHA512_OUTSIZE).enumerate() {
+            let mut block = [0u8; SHA512_OUTSIZE];
+            state.reset();
+            state.update(&seed)?;
+            state.update(&(counter as u32).to_be_bytes())?;
+            state.finalize_into(&mut block)?;
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            block.zeroize();
+        }
+
+        // This cannot fail, as `bytes` has the same length as the modulus.
+        let factor = bigint::from_be_bytes(&bytes, self.public.limbs()).unwrap();
+        bytes.iter_mut().zeroize();
+        seed.zeroize();
+
+        Ok(factor)
+    }
+
+    /// Compute input^d mod `prime` with the CRT exponent `d_prime`, blinded
+    /// by `blinding`.
+    fn blinded_half(
+        prime: &Modulus,
+        e: u64,
+        d_prime: &[u64],
+        x: &[u64],
+        blinding: &[u64],
+    ) -> Vec<u64> {
+        let mut x = prime.reduce(x);
+        let mut r = prime.reduce(blinding);
+
+        // (x * r^e)^d = x^d * r, since e * d = 1 mod (prime - 1).
+        let mut r_e = prime.pow(&r, &[e]);
+        let mut blinded = prime.mul(&x, &r_e);
+        let mut result = prime.pow(&blinded, d_prime);
+
+        // Unblind with r^-1 = r^(prime - 2).
+        let mut exponent = prime.limbs().to_vec();
+        exponent[0] -= 2;
+        let mut r_inv = prime.pow(&r, &exponent);
+        let out = prime.mul(&result, &r_inv);
+
+        for value in &mut [
+            &mut x,
+            &mut r,
+            &mut r_e,
+            &mut blinded,
+            &mut result,
+            &mut r_inv,
+        ] {
+            value.iter_mut().zeroize();
+        }
+
+        out
+    }
+
+    /// Compute input^d mod n, where `input` is a big-endian integer of
+    /// [`SecretKey::size()`] bytes that must be less than n, and write it to
+    /// `dst_out`. The result is checked with the public key before it is
+    /// written.
+    pub(crate) fn raw_private(
+        &self,
+        input: &[u8],
+        dst_out: &mut [u8],
+    ) -> Result<(), UnknownCryptoError> {
+        if input.len() != self.size() || dst_out.len() != self.size() {
+            return Err(UnknownCryptoError);
+        }
+
+        // This cannot fail, as `input` has the same length as the modulus.
+        let mut x = bigint::from_be_bytes(input, self.public.limbs()).unwrap();
+        if !bool::from(bigint::ct_lt(&x, self.public.n.limbs())) {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut blinding = self.blinding_factor(input)?;
+        let mut s_p = Self::blinded_half(&self.p, self.public.e, &self.dp, &x, &blinding);
+        let mut s_q = Self::blinded_half(&self.q, self.public.e, &self.dq, &x, &blinding);
+
+        // Garner's formula: s = s_q + q * (qinv * (s_p - s_q) mod p).
+        let mut s_q_mod_p = self.p.reduce(&s_q);
+        let mut diff = self.p.sub(&s_p, &s_q_mod_p);
+        let mut h = self.p.mul(&diff, &self.qinv);
+        let mut s = bigint::mul(self.q.limbs(), &h);
+        bigint::add_assign(&mut s, &s_q);
+
+        // Check the result, to guard against faults.
+        let is_valid = bigint::ct_eq(&self.public.n.pow(&s, &[self.public.e]), &x);
+        if bool::from(is_valid) {
+            bigint::to_be_bytes(&s, dst_out);
+        }
+
+        for value in &mut [
+            &mut x,
+            &mut blinding,
+            &mut s_p,
+            &mut s_q,
+            &mut s_q_mod_p,
+            &mut diff,
+            &mut h,
+            &mut s,
+        ] {
+            value.iter_mut().zeroize();
+        }
+
+        if bool::from(is_valid) {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+/// Remove the leading zero bytes of a big-endian integer.
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Whether `a` is one.
+fn is_one(a: &[u64]) -> Choice {
+    let mut acc = a[0] ^ 1;
+    for limb in a[1..].iter() {
+        acc |= limb;
+    }
+
+    acc.ct_eq(&0)
+}
+
+/// Parse a prime factor with exactly `limbs` limbs and its top bit set.
+fn parse_prime(bytes: &[u8], limbs: usize) -> Result<Modulus, UnknownCryptoError> {
+    if bytes.len() != limbs * 8 || bytes[0] < 0x80 {
+        return Err(UnknownCryptoError);
+    }
+
+    // This cannot fail, as `bytes` has exactly as many limbs as it needs.
+    let mut prime = bigint::from_be_bytes(bytes, limbs).unwrap();
+    let modulus = Modulus::new(&prime).ok_or(UnknownCryptoError);
+    prime.iter_mut().zeroize();
+
+    modulus
+}
+
+/// Parse an integer that must be less than `modulus`.
+fn parse_reduced(bytes: &[u8], modulus: &Modulus) -> Result<Vec<u64>, UnknownCryptoError> {
+    let mut value =
+        bigint::from_be_bytes(bytes, modulus.limbs().len()).ok_or(UnknownCryptoError)?;
+    if !bool::from(bigint::ct_lt(&value, modulus.limbs())) {
+        value.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(value)
+}
+
+/// Parse a CRT exponent d mod (prime - 1), checking that e * d = 1 mod
+/// (prime - 1).
+fn parse_crt_exponent(
+    bytes: &[u8],
+    prime: &Modulus,
+    e: u64,
+) -> Result<Vec<u64>, UnknownCryptoError> {
+    let d = parse_reduced(bytes, prime)?;
+    let mut prime_minus_one = prime.limbs().to_vec();
+    prime_minus_one[0] ^= 1;
+
+    let mut product = bigint::mul(&d, &[e]);
+    let mut reduced = bigint::reduce(&product, &prime_minus_one);
+    let is_valid = is_one(&reduced);
+    product.iter_mut().zeroize();
+    reduced.iter_mut().zeroize();
+    prime_minus_one.iter_mut().zeroize();
+
+    if bool::from(is_valid) {
+        Ok(d)
+    } else {
+        let mut d = d;
+        d.iter_mut().zeroize();
+        Err(UnknownCryptoError)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const SECRET_KEY: &[u8] = include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.pk8.der");
+    const PUBLIC_KEY: &[u8] =
+        include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.spki.der");
+    const SECRET_KEY_1024: &[u8] =
+        include_bytes!("../../../tests/test_data/rsa/rsa1024_e65537.pk8.der");
+    const PUBLIC_KEY_1024: &[u8] =
+        include_bytes!("../../../tests/test_data/rsa/rsa1024_e65537.spki.der");
+
+    /// The position of the `index`th INTEGER in the RSAPrivateKey of
+    /// `SECRET_KEY`, where 0 is the version and 8 is qinv.
+    fn component_position(index: usize) -> usize {
+        let mut pkcs8 = read_outer_sequence(SECRET_KEY).unwrap();
+        read_version_zero(&mut pkcs8).unwrap();
+        read_rsa_algorithm(&mut pkcs8).unwrap();
+        let mut rsa_private_key =
+            read_outer_sequence(pkcs8.read(TAG_OCTET_STRING).unwrap()).unwrap();
+        for _ in 0..index {
+            rsa_private_key.read_unsigned_integer().unwrap();
+        }
+        let value = rsa_private_key.read_unsigned_integer().unwrap();
+
+        value.as_ptr() as usize - SECRET_KEY.as_ptr() as usize
+    }
+
+    fn modify_secret_key(position: usize, value: u8) -> Vec<u8> {
+        let mut der = SECRET_KEY.to_vec();
+        der[position] = value;
+        der
+    }
+
+    #[test]
+    fn test_import() {
+        let secret_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap();
+        let public_key = PublicKey::from_spki_der(PUBLIC_KEY).unwrap();
+        assert_eq!(secret_key.size(), 256);
+        assert_eq!(public_key.size(), 256);
+        assert_eq!(secret_key.public_key(), public_key);
+        assert_eq!(public_key.e, 65537);
+    }
+
+    #[test]
+    fn test_unsupported_size() {
+        assert!(SecretKey::from_pkcs8_der(SECRET_KEY_1024).is_err());
+        assert!(PublicKey::from_spki_der(PUBLIC_KEY_1024).is_err());
+    }
+
+    #[test]
+    fn test_from_components() {
+        let mut modulus = [0xffu8; 256];
+        assert!(PublicKey::from_components(&modulus, &[1, 0, 1]).is_ok());
+        assert!(PublicKey::from_components(&modulus, &[3]).is_ok());
+        // Leading zeroes are ignored.
+        let mut padded = vec![0u8; 2];
+        padded.extend_from_slice(&modulus);
+        assert!(PublicKey::from_components(&padded, &[0, 0, 3]).is_ok());
+
+        // Invalid exponents.
+        assert!(PublicKey::from_components(&modulus, &[]).is_err());
+        assert!(PublicKey::from_components(&modulus, &[1]).is_err());
+        assert!(PublicKey::from_components(&modulus, &[1, 0, 0]).is_err());
+        assert!(PublicKey::from_components(&modulus, &[1u8; 9]).is_err());
+        assert!(PublicKey::from_components(&modulus, &[0xffu8; 8]).is_ok());
+
+        // Invalid moduli.
+        assert!(PublicKey::from_components(&modulus[..255], &[3]).is_err());
+        assert!(PublicKey::from_components(&[0xffu8; 257], &[3]).is_err());
+        assert!(PublicKey::from_components(&[], &[3]).is_err());
+        modulus[0] = 0x7f;
+        assert!(PublicKey::from_components(&modulus, &[3]).is_err());
+        modulus[0] = 0xff;
+        modulus[255] = 0xfe;
+        assert!(PublicKey::from_components(&modulus, &[3]).is_err());
+    }
+
+    #[test]
+    fn test_spki_malformed() {
+        assert!(PublicKey::from_spki_der(&PUBLIC_KEY[..PUBLIC_KEY.len() - 1]).is_err());
+        let mut trailing = PUBLIC_KEY.to_vec();
+        trailing.push(0);
+        assert!(PublicKey::from_spki_der(&trailing).is_err());
+
+        // The header is 30 82 01 22 30 0d 06 09 <OID> 05 00 03 82 01 0f 00.
+        let mut other_oid = PUBLIC_KEY.to_vec();
+        other_oid[16] = 0x0a;
+        assert!(PublicKey::from_spki_der(&other_oid).is_err());
+        let mut unused_bits = PUBLIC_KEY.to_vec();
+        unused_bits[23] = 0x01;
+        assert!(PublicKey::from_spki_der(&unused_bits).is_err());
+
+        // A bare PKCS #1 RSAPublicKey.
+        assert!(PublicKey::from_spki_der(&PUBLIC_KEY[24..]).is_err());
+        assert!(PublicKey::from_spki_der(SECRET_KEY).is_err());
+    }
+
+    #[test]
+    fn test_pkcs8_malformed() {
+        assert!(SecretKey::from_pkcs8_der(&SECRET_KEY[..SECRET_KEY.len() - 1]).is_err());
+        let mut trailing = SECRET_KEY.to_vec();
+        trailing.push(0);
+        assert!(SecretKey::from_pkcs8_der(&trailing).is_err());
+        assert!(SecretKey::from_pkcs8_der(PUBLIC_KEY).is_err());
+
+        // PrivateKeyInfo version, then the last byte of the OID.
+        assert!(SecretKey::from_pkcs8_der(&modify_secret_key(6, 1)).is_err());
+        assert!(SecretKey::from_pkcs8_der(&modify_secret_key(19, 0x0a)).is_err());
+        // RSAPrivateKey version.
+        let version = component_position(0);
+        assert!(SecretKey::from_pkcs8_der(&modify_secret_key(version, 1)).is_err());
+    }
+
+    #[test]
+    fn test_pkcs8_inconsistent() {
+        // Changing any of n, e, p, q, dp, dq and qinv must be detected. The
+        // private exponent d is not used.
+        for (index, offset) in &[
+            (1, 100),
+            (2, 2),
+            (4, 100),
+            (5, 100),
+            (6, 100),
+            (7, 100),
+            (8, 100),
+        ] {
+            let position = component_position(*index) + offset;
+            let der = modify_secret_key(position, SECRET_KEY[position] ^ 0x10);
+            assert!(SecretKey::from_pkcs8_der(&der).is_err(), "{}", index);
+        }
+
+        let position = component_position(3) + 100;
+        let der = modify_secret_key(position, SECRET_KEY[position] ^ 0x10);
+        assert!(SecretKey::from_pkcs8_der(&der).is_ok());
+    }
+
+    #[test]
+    fn test_raw_private_public() {
+        let secret_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap();
+        let public_key = secret_key.public_key();
+
+        let mut input = vec![0u8; 256];
+        input[1..].copy_from_slice(&[0xabu8; 255]);
+        let mut output = vec![0u8; 256];
+        secret_key.raw_private(&input, &mut output).unwrap();
+        let mut recovered = vec![0u8; 256];
+        bigint::to_be_bytes(&public_key.raw_public(&output).unwrap(), &mut recovered);
+        assert_eq!(input, recovered);
+
+        // Inputs not less than the modulus.
+        assert!(secret_key.raw_private(&[0xffu8; 256], &mut output).is_err());
+        assert!(public_key.raw_public(&[0xffu8; 256]).is_err());
+        // Wrong lengths.
+        assert!(secret_key.raw_private(&input[1..], &mut output).is_err());
+        assert!(secret_key.raw_private(&input, &mut output[1..]).is_err());
+        assert!(public_key.raw_public(&input[1..]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug() {
+        let secret_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap();
+        assert_eq!(format!("{:?}", secret_key), "SecretKey {***OMITTED***}");
+        let debug = format!("{:?}", secret_key.public_key());
+        assert!(debug.starts_with("PublicKey { n: ["));
+        assert!(debug.ends_with("e: 65537 }"));
+    }
+}
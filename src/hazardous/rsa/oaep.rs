@@ -0,0 +1,434 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! RSAES-OAEP encryption, as specified in [RFC 8017], with SHA-256 as both
+//! the hash function and the MGF1 hash function. These are the parameters of
+//! `RSA-OAEP-256` in JWE, and of the `RSAES_OAEP_SHA_256` wrapping algorithms
+//! of cloud key management services.
+//!
+//! See [`SecretKey`] and [`PublicKey`] for the supported keys and how to
+//! import them.
+//!
+//! # Parameters:
+//! - `public_key`: The public key used to encrypt `plaintext`.
+//! - `secret_key`: The secret key used to decrypt `ciphertext`.
+//! - `plaintext`: The data to be encrypted.
+//! - `label`: Optional data that is bound to the ciphertext, but not
+//!   encrypted. It must be the same when decrypting.
+//! - `seed`: 32 bytes of fresh randomness.
+//! - `ciphertext`: The data to be decrypted.
+//! - `dst_out`: Destination buffer for the ciphertext or the plaintext.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `plaintext` is longer than [`PublicKey::size()`] - [`PADDINGSIZE`] bytes.
+//! - `seed` is not [`SEEDSIZE`] bytes.
+//! - `dst_out` is not [`PublicKey::size()`] bytes when encrypting.
+//! - `ciphertext` is not [`SecretKey::size()`] bytes, or does not decrypt to
+//!   a valid encoding under `label`.
+//! - `dst_out` is shorter than the decrypted plaintext when decrypting.
+//!
+//! # Security:
+//! - The seed must be generated using a CSPRNG, such as
+//!   [`util::secure_rand_bytes()`]. Reusing a seed for the same plaintext
+//!   and label gives the same ciphertext.
+//! - Decryption runs in constant time up to the point where the ciphertext
+//!   is either rejected or the length of the plaintext is known. All invalid
+//!   ciphertexts are rejected with the same error, so that the decryption
+//!   does not act as a padding oracle.
+//! - The private key operation is blinded and checked with the public key,
+//!   as described in [`pss`].
+//! - OAEP is not an AEAD. Anyone with the public key can encrypt, so a
+//!   successful decryption does not authenticate the sender.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::rsa::oaep;
+//!
+//! # let secret_der = std::fs::read("tests/test_data/rsa/rsa2048_e65537.pk8.der").unwrap();
+//! # let public_der = std::fs::read("tests/test_data/rsa/rsa2048_e65537.spki.der").unwrap();
+//! let secret_key = oaep::SecretKey::from_pkcs8_der(&secret_der)?;
+//! let public_key = oaep::PublicKey::from_spki_der(&public_der)?;
+//!
+//! let mut seed = [0u8; oaep::SEEDSIZE];
+//! orion::util::secure_rand_bytes(&mut seed)?;
+//!
+//! let mut ciphertext = vec![0u8; public_key.size()];
+//! oaep::encrypt(&public_key, b"Key to wrap", None, &seed, &mut ciphertext)?;
+//!
+//! let mut plaintext = vec![0u8; secret_key.size()];
+//! let len = oaep::decrypt(&secret_key, &ciphertext, None, &mut plaintext)?;
+//! assert_eq!(&plaintext[..len], b"Key to wrap");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 8017]: https://www.rfc-editor.org/rfc/rfc8017#section-7.1
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`PublicKey`]: struct.PublicKey.html
+//! [`PADDINGSIZE`]: constant.PADDINGSIZE.html
+//! [`SEEDSIZE`]: constant.SEEDSIZE.html
+//! [`SecretKey::size()`]: struct.SecretKey.html#method.size
+//! [`PublicKey::size()`]: struct.PublicKey.html#method.size
+//! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+//! [`pss`]: ../pss/index.html
+
+use super::bigint;
+pub use super::keys::{PublicKey, SecretKey};
+use super::pss::mgf1_xor;
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+/// The size of the seed.
+pub const SEEDSIZE: usize = 32;
+
+/// The number of bytes that the encoding adds to a plaintext.
+pub const PADDINGSIZE: usize = 2 * SHA256_OUTSIZE + 2;
+
+/// SHA256(label), with an absent label being empty.
+fn label_hash(label: Option<&[u8]>) -> Result<[u8; SHA256_OUTSIZE], UnknownCryptoError> {
+    let mut l_hash = [0u8; SHA256_OUTSIZE];
+    Sha256::digest_into(label.unwrap_or(&[]), &mut l_hash)?;
+
+    Ok(l_hash)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Encrypt `plaintext` to `public_key`.
+pub fn encrypt(
+    public_key: &PublicKey,
+    plaintext: &[u8],
+    label: Option<&[u8]>,
+    seed: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let k = public_key.size();
+    if plaintext.len() > k - PADDINGSIZE || seed.len() != SEEDSIZE || dst_out.len() != k {
+        return Err(UnknownCryptoError);
+    }
+
+    // EM = 0x00 || maskedSeed || maskedDB
+    let mut em = vec![0u8; k];
+    let (masked_seed, db) = em[1..].split_at_mut(SEEDSIZE);
+    db[..SHA256_OUTSIZE].copy_from_slice(&label_hash(label)?);
+    let db_len = db.len();
+    db[db_len - plaintext.len() - 1] = 0x01;
+    db[db_len - plaintext.len()..].copy_from_slice(plaintext);
+
+    masked_seed.copy_from_slice(seed);
+    mgf1_xor(masked_seed, db)?;
+    mgf1_xor(db, masked_seed)?;
+
+    let ciphertext = public_key.raw_public(&em);
+    em.iter_mut().zeroize();
+    bigint::to_be_bytes(&ciphertext?, dst_out);
+
+    Ok(())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Decrypt `ciphertext` with `secret_key`, returning the length of the
+/// plaintext written to the start of `dst_out`.
+pub fn decrypt(
+    secret_key: &SecretKey,
+    ciphertext: &[u8],
+    label: Option<&[u8]>,
+    dst_out: &mut [u8],
+) -> Result<usize, UnknownCryptoError> {
+    let k = secret_key.size();
+    if ciphertext.len() != k {
+        return Err(UnknownCryptoError);
+    }
+
+    let l_hash = label_hash(label)?;
+    let mut em = vec![0u8; k];
+    secret_key.raw_private(ciphertext, &mut em)?;
+
+    let first_byte = em[0];
+    let (seed, db) = em[1..].split_at_mut(SEEDSIZE);
+    mgf1_xor(db, seed)?;
+    mgf1_xor(seed, db)?;
+
+    let mut is_valid = first_byte.ct_eq(&0) & db[..SHA256_OUTSIZE].ct_eq(&l_hash);
+
+    // Find the 0x01 byte after the zero padding, without branching on the
+    // contents of the padding.
+    let mut found = Choice::from(0);
+    let mut separator = 0u64;
+    for (i, byte) in db[SHA256_OUTSIZE..].iter().enumerate() {
+        let is_zero = byte.ct_eq(&0);
+        let is_one = byte.ct_eq(&1);
+        separator.conditional_assign(&(i as u64), !found & is_one);
+        is_valid &= found | is_zero | is_one;
+        found |= is_one;
+    }
+    is_valid &= found;
+
+    if !bool::from(is_valid) {
+        em.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    let plaintext = &db[SHA256_OUTSIZE + separator as usize + 1..];
+    if dst_out.len() < plaintext.len() {
+        em.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+    let len = plaintext.len();
+    dst_out[..len].copy_from_slice(plaintext);
+    em.iter_mut().zeroize();
+
+    Ok(len)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    #[cfg(not(feature = "safe_api"))]
+    use alloc::vec::Vec;
+
+    const SECRET_KEY: &[u8] = include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.pk8.der");
+
+    fn keys() -> (SecretKey, PublicKey) {
+        let secret_key = SecretKey::from_pkcs8_der(SECRET_KEY).unwrap();
+        let public_key = secret_key.public_key();
+        (secret_key, public_key)
+    }
+
+    fn encrypt_vec(public_key: &PublicKey, plaintext: &[u8], label: Option<&[u8]>) -> Vec<u8> {
+        let mut ciphertext = vec![0u8; public_key.size()];
+        encrypt(
+            public_key,
+            plaintext,
+            label,
+            &[7u8; SEEDSIZE],
+            &mut ciphertext,
+        )
+        .unwrap();
+        ciphertext
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let (secret_key, public_key) = keys();
+        let mut plaintext = vec![0u8; 256];
+
+        for len in &[0, 1, 32, 256 - PADDINGSIZE] {
+            let message = vec![0xa5u8; *len];
+            let ciphertext = encrypt_vec(&public_key, &message, None);
+            let out_len = decrypt(&secret_key, &ciphertext, None, &mut plaintext).unwrap();
+            assert_eq!(&plaintext[..out_len], &message[..]);
+        }
+    }
+
+    #[test]
+    fn test_label() {
+        let (secret_key, public_key) = keys();
+        let mut plaintext = vec![0u8; 256];
+
+        let ciphertext = encrypt_vec(&public_key, b"Message", Some(b"label"));
+        assert_eq!(
+            decrypt(&secret_key, &ciphertext, Some(b"label"), &mut plaintext).unwrap(),
+            7
+        );
+        assert!(decrypt(&secret_key, &ciphertext, Some(b"other"), &mut plaintext).is_err());
+        assert!(decrypt(&secret_key, &ciphertext, None, &mut plaintext).is_err());
+
+        // An empty label is the same as no label.
+        let ciphertext = encrypt_vec(&public_key, b"Message", Some(b""));
+        assert!(decrypt(&secret_key, &ciphertext, None, &mut plaintext).is_ok());
+    }
+
+    #[test]
+    fn test_encrypt_lengths() {
+        let (_, public_key) = keys();
+        let mut ciphertext = vec![0u8; 256];
+        let too_long = vec![0u8; 256 - PADDINGSIZE + 1];
+        assert!(encrypt(
+            &public_key,
+            &too_long,
+            None,
+            &[0u8; SEEDSIZE],
+            &mut ciphertext
+        )
+        .is_err());
+        assert!(encrypt(
+            &public_key,
+            b"",
+            None,
+            &[0u8; SEEDSIZE - 1],
+            &mut ciphertext
+        )
+        .is_err());
+        assert!(encrypt(
+            &public_key,
+            b"",
+            None,
+            &[0u8; SEEDSIZE + 1],
+            &mut ciphertext
+        )
+        .is_err());
+        assert!(encrypt(
+            &public_key,
+            b"",
+            None,
+            &[0u8; SEEDSIZE],
+            &mut ciphertext[1..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_encrypt_depends_on_seed() {
+        let (_, public_key) = keys();
+        let mut first = vec![0u8; 256];
+        let mut second = vec![0u8; 256];
+        encrypt(&public_key, b"Message", None, &[1u8; SEEDSIZE], &mut first).unwrap();
+        encrypt(&public_key, b"Message", None, &[1u8; SEEDSIZE], &mut second).unwrap();
+        assert_eq!(first, second);
+        encrypt(&public_key, b"Message", None, &[2u8; SEEDSIZE], &mut second).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_decrypt_errors() {
+        let (secret_key, public_key) = keys();
+        let ciphertext = encrypt_vec(&public_key, b"Message", None);
+        let mut plaintext = vec![0u8; 256];
+
+        // Output too short for the plaintext.
+        assert!(decrypt(&secret_key, &ciphertext, None, &mut plaintext[..6]).is_err());
+        assert_eq!(
+            decrypt(&secret_key, &ciphertext, None, &mut plaintext[..7]).unwrap(),
+            7
+        );
+
+        // Wrong lengths and values not less than the modulus.
+        assert!(decrypt(&secret_key, &ciphertext[1..], None, &mut plaintext).is_err());
+        assert!(decrypt(&secret_key, &[0xffu8; 256], None, &mut plaintext).is_err());
+
+        for idx in &[0, 100, 255] {
+            let mut bad = ciphertext.clone();
+            bad[*idx] ^= 1;
+            assert!(decrypt(&secret_key, &bad, None, &mut plaintext).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decrypt_invalid_encodings() {
+        // Encrypt encodings with the raw public key operation, each with one
+        // part of the OAEP encoding broken.
+        let (secret_key, public_key) = keys();
+        let mut plaintext = vec![0u8; 256];
+        let l_hash = label_hash(None).unwrap();
+
+        let raw_encrypt = |first_byte: u8, l_hash: &[u8], padding: &[u8]| -> Vec<u8> {
+            let mut em = vec![0u8; 256];
+            em[0] = first_byte;
+            let (seed, db) = em[1..].split_at_mut(SEEDSIZE);
+            db[..SHA256_OUTSIZE].copy_from_slice(l_hash);
+            db[SHA256_OUTSIZE..SHA256_OUTSIZE + padding.len()].copy_from_slice(padding);
+            mgf1_xor(seed, db).unwrap();
+            mgf1_xor(db, seed).unwrap();
+
+            let mut ciphertext = vec![0u8; 256];
+            bigint::to_be_bytes(&public_key.raw_public(&em).unwrap(), &mut ciphertext);
+            ciphertext
+        };
+
+        // A valid encoding of "Message".
+        let mut padding = vec![0u8; 256 - 1 - SEEDSIZE - SHA256_OUTSIZE];
+        let separator = padding.len() - 8;
+        padding[separator] = 0x01;
+        padding[separator + 1..].copy_from_slice(b"Message");
+        let valid = raw_encrypt(0, &l_hash, &padding);
+        assert_eq!(
+            decrypt(&secret_key, &valid, None, &mut plaintext).unwrap(),
+            7
+        );
+
+        // Non-zero first byte.
+        assert!(decrypt(
+            &secret_key,
+            &raw_encrypt(1, &l_hash, &padding),
+            None,
+            &mut plaintext
+        )
+        .is_err());
+        // Wrong label hash.
+        let mut bad_hash = l_hash;
+        bad_hash[31] ^= 1;
+        assert!(decrypt(
+            &secret_key,
+            &raw_encrypt(0, &bad_hash, &padding),
+            None,
+            &mut plaintext
+        )
+        .is_err());
+        // Non-zero byte in the padding.
+        let mut bad_padding = padding.clone();
+        bad_padding[10] = 0x02;
+        assert!(decrypt(
+            &secret_key,
+            &raw_encrypt(0, &l_hash, &bad_padding),
+            None,
+            &mut plaintext
+        )
+        .is_err());
+        // No separator.
+        let no_separator = vec![0u8; padding.len()];
+        assert!(decrypt(
+            &secret_key,
+            &raw_encrypt(0, &l_hash, &no_separator),
+            None,
+            &mut plaintext
+        )
+        .is_err());
+        // A separator directly after the label hash, and at the very end.
+        let mut first = vec![0x02u8; padding.len()];
+        first[0] = 0x01;
+        assert_eq!(
+            decrypt(
+                &secret_key,
+                &raw_encrypt(0, &l_hash, &first),
+                None,
+                &mut plaintext
+            )
+            .unwrap(),
+            padding.len() - 1
+        );
+        let mut last = vec![0u8; padding.len()];
+        last[padding.len() - 1] = 0x01;
+        assert_eq!(
+            decrypt(
+                &secret_key,
+                &raw_encrypt(0, &l_hash, &last),
+                None,
+                &mut plaintext
+            )
+            .unwrap(),
+            0
+        );
+    }
+}
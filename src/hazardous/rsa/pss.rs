@@ -0,0 +1,352 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! RSASSA-PSS signatures, as specified in [RFC 8017], with SHA-256 as both
+//! the hash function and the MGF1 hash function, and a 32-byte salt. These
+//! are the parameters of `PS256` in JWS and of `rsa_pss_rsae_sha256` in
+//! TLS 1.3.
+//!
+//! See [`SecretKey`] and [`PublicKey`] for the supported keys and how to
+//! import them.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key used to sign `message`.
+//! - `public_key`: The public key used to verify `signature`.
+//! - `message`: The message to be signed or verified.
+//! - `salt`: 32 bytes of fresh randomness.
+//! - `signature`: The signature to be verified.
+//! - `dst_out`: Destination buffer for the signature.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `salt` is not [`SALTSIZE`] bytes.
+//! - `dst_out` is not [`SecretKey::size()`] bytes.
+//! - `signature` is not [`PublicKey::size()`] bytes or is not a valid
+//!   signature of `message` under `public_key`.
+//! - The signature computed by [`sign`] does not verify, which indicates a
+//!   fault or an inconsistent key.
+//!
+//! # Security:
+//! - The salt should be generated using a CSPRNG, such as
+//!   [`util::secure_rand_bytes()`]. Signatures remain unforgeable if it is
+//!   not random, but a fixed salt makes signing deterministic.
+//! - The private key operation runs in constant time, using the CRT
+//!   parameters of the key. Its input is additionally blinded, with a
+//!   factor that is derived from the secret key and the input.
+//! - Signatures are verified after signing, to guard against faults.
+//! - Importing a secret key from DER leaks the number of leading zero bytes
+//!   of its values, see [`util::der`].
+//! - Verification only uses public values and makes no constant-time
+//!   guarantees.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::rsa::pss;
+//!
+//! # let secret_der = std::fs::read("tests/test_data/rsa/rsa2048_e65537.pk8.der").unwrap();
+//! # let public_der = std::fs::read("tests/test_data/rsa/rsa2048_e65537.spki.der").unwrap();
+//! let secret_key = pss::SecretKey::from_pkcs8_der(&secret_der)?;
+//! let public_key = pss::PublicKey::from_spki_der(&public_der)?;
+//!
+//! let mut salt = [0u8; pss::SALTSIZE];
+//! orion::util::secure_rand_bytes(&mut salt)?;
+//!
+//! let mut signature = vec![0u8; secret_key.size()];
+//! pss::sign(&secret_key, b"Message", &salt, &mut signature)?;
+//! assert!(pss::verify(&public_key, b"Message", &signature).is_ok());
+//! assert!(pss::verify(&public_key, b"Another message", &signature).is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 8017]: https://www.rfc-editor.org/rfc/rfc8017#section-8.1
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`PublicKey`]: struct.PublicKey.html
+//! [`SALTSIZE`]: constant.SALTSIZE.html
+//! [`SecretKey::size()`]: struct.SecretKey.html#method.size
+//! [`PublicKey::size()`]: struct.PublicKey.html#method.size
+//! [`sign`]: fn.sign.html
+//! [`util::secure_rand_bytes()`]: ../../../util/fn.secure_rand_bytes.html
+//! [`util::der`]: ../../../util/der/index.html
+
+use super::bigint;
+pub use super::keys::{PublicKey, SecretKey};
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::Sha256;
+use crate::hazardous::hash::{ShaHash, MAX_SHA_OUTSIZE};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+/// The size of the salt.
+pub const SALTSIZE: usize = 32;
+
+/// XOR `dst` with MGF1-SHA256(`seed`).
+pub(crate) fn mgf1_xor(seed: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    mgf1_xor_with::<Sha256>(seed, dst)
+}
+
+/// XOR `dst` with MGF1-H(`seed`).
+pub(crate) fn mgf1_xor_with<H: ShaHash>(
+    seed: &[u8],
+    dst: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut state = H::default();
+    let mut block = [0u8; MAX_SHA_OUTSIZE];
+    for (counter, chunk) in dst.chunks_mut(H::OUTSIZE).enumerate() {
+        state.reset();
+        state.update(seed)?;
+        state.update(&(counter as u32).to_be_bytes())?;
+        state.finalize_into(&mut block[..H::OUTSIZE])?;
+        xor_slices!(block, chunk);
+    }
+    block.zeroize();
+
+    Ok(())
+}
+
+/// H = Hash(0x00 * 8 || Hash(message) || salt), written to the first
+/// `H::OUTSIZE` bytes of the returned array.
+fn message_hash<H: ShaHash>(
+    message: &[u8],
+    salt: &[u8],
+) -> Result<[u8; MAX_SHA_OUTSIZE], UnknownCryptoError> {
+    let mut m_hash = [0u8; MAX_SHA_OUTSIZE];
+    H::digest_into(message, &mut m_hash[..H::OUTSIZE])?;
+
+    let mut state = H::default();
+    state.update(&[0u8; 8])?;
+    state.update(&m_hash[..H::OUTSIZE])?;
+    state.update(salt)?;
+    let mut h = [0u8; MAX_SHA_OUTSIZE];
+    state.finalize_into(&mut h[..H::OUTSIZE])?;
+
+    Ok(h)
+}
+
+/// EMSA-PSS-ENCODE with the hash function `H` for MGF1 and the message
+/// hash, for a modulus of exactly `em.len()` bytes.
+pub(crate) fn encode_with<H: ShaHash>(
+    message: &[u8],
+    salt: &[u8],
+    em: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if em.len() < H::OUTSIZE + salt.len() + 2 {
+        return Err(UnknownCryptoError);
+    }
+
+    let db_len = em.len() - H::OUTSIZE - 1;
+    let h = message_hash::<H>(message, salt)?;
+
+    let (db, rest) = em.split_at_mut(db_len);
+    for byte in db.iter_mut() {
+        *byte = 0;
+    }
+    db[db_len - salt.len() - 1] = 0x01;
+    db[db_len - salt.len()..].copy_from_slice(salt);
+    mgf1_xor_with::<H>(&h[..H::OUTSIZE], db)?;
+    // The modulus has a multiple of 8 bits, so emBits = 8 * em.len() - 1.
+    db[0] &= 0x7f;
+
+    rest[..H::OUTSIZE].copy_from_slice(&h[..H::OUTSIZE]);
+    rest[H::OUTSIZE] = 0xbc;
+
+    Ok(())
+}
+
+/// EMSA-PSS-VERIFY with the hash function `H` and a salt of `salt_len`
+/// bytes, for a modulus of exactly `em.len()` bytes. `em` is overwritten.
+pub(crate) fn verify_encoded_with<H: ShaHash>(
+    message: &[u8],
+    em: &mut [u8],
+    salt_len: usize,
+) -> Result<(), UnknownCryptoError> {
+    if em.len() < H::OUTSIZE + salt_len + 2 {
+        return Err(UnknownCryptoError);
+    }
+
+    let db_len = em.len() - H::OUTSIZE - 1;
+    if em[em.len() - 1] != 0xbc || em[0] & 0x80 != 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let (db, rest) = em.split_at_mut(db_len);
+    let h = &rest[..H::OUTSIZE];
+    mgf1_xor_with::<H>(h, db)?;
+    db[0] &= 0x7f;
+
+    let padding_len = db_len - salt_len - 1;
+    if db[..padding_len].iter().any(|byte| *byte != 0) || db[padding_len] != 0x01 {
+        return Err(UnknownCryptoError);
+    }
+
+    let expected = message_hash::<H>(message, &db[padding_len + 1..])?;
+    if expected[..H::OUTSIZE].ct_eq(h).into() {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+/// EMSA-PSS-ENCODE, for a modulus of exactly `em.len()` bytes.
+fn encode(message: &[u8], salt: &[u8], em: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    encode_with::<Sha256>(message, salt, em)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `message` with `secret_key`.
+pub fn sign(
+    secret_key: &SecretKey,
+    message: &[u8],
+    salt: &[u8],
+    dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if salt.len() != SALTSIZE || dst_out.len() != secret_key.size() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut em = vec![0u8; secret_key.size()];
+    encode(message, salt, &mut em)?;
+
+    secret_key.raw_private(&em, dst_out)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `message` under `public_key`.
+pub fn verify(
+    public_key: &PublicKey,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    let mut em = vec![0u8; public_key.size()];
+    bigint::to_be_bytes(&public_key.raw_public(signature)?, &mut em);
+
+    verify_encoded_with::<Sha256>(message, &mut em, SALTSIZE)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const SECRET_KEY: &[u8] = include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.pk8.der");
+    const PUBLIC_KEY: &[u8] =
+        include_bytes!("../../../tests/test_data/rsa/rsa2048_e65537.spki.der");
+
+    fn keys() -> (SecretKey, PublicKey) {
+        (
+            SecretKey::from_pkcs8_der(SECRET_KEY).unwrap(),
+            PublicKey::from_spki_der(PUBLIC_KEY).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let (secret_key, public_key) = keys();
+        assert_eq!(secret_key.public_key(), public_key);
+
+        let mut signature = vec![0u8; 256];
+        sign(&secret_key, b"Message", &[1u8; SALTSIZE], &mut signature).unwrap();
+        assert!(verify(&public_key, b"Message", &signature).is_ok());
+        assert!(verify(&public_key, b"Message2", &signature).is_err());
+
+        // Signing is deterministic in the salt.
+        let mut again = vec![0u8; 256];
+        sign(&secret_key, b"Message", &[1u8; SALTSIZE], &mut again).unwrap();
+        assert_eq!(signature, again);
+        sign(&secret_key, b"Message", &[2u8; SALTSIZE], &mut again).unwrap();
+        assert_ne!(signature, again);
+        assert!(verify(&public_key, b"Message", &again).is_ok());
+    }
+
+    #[test]
+    fn test_sign_lengths() {
+        let (secret_key, _) = keys();
+        let mut signature = vec![0u8; 256];
+        assert!(sign(&secret_key, b"", &[0u8; SALTSIZE - 1], &mut signature).is_err());
+        assert!(sign(&secret_key, b"", &[0u8; SALTSIZE + 1], &mut signature).is_err());
+        assert!(sign(&secret_key, b"", &[0u8; SALTSIZE], &mut signature[..255]).is_err());
+        let mut long = vec![0u8; 257];
+        assert!(sign(&secret_key, b"", &[0u8; SALTSIZE], &mut long).is_err());
+    }
+
+    #[test]
+    fn test_verify_malformed() {
+        let (secret_key, public_key) = keys();
+        let mut signature = vec![0u8; 256];
+        sign(&secret_key, b"Message", &[0u8; SALTSIZE], &mut signature).unwrap();
+
+        assert!(verify(&public_key, b"Message", &signature[..255]).is_err());
+        let mut long = signature.clone();
+        long.push(0);
+        assert!(verify(&public_key, b"Message", &long).is_err());
+
+        for idx in &[0, 100, 255] {
+            let mut bad = signature.clone();
+            bad[*idx] ^= 1;
+            assert!(verify(&public_key, b"Message", &bad).is_err());
+        }
+
+        // Signatures not less than the modulus.
+        assert!(verify(&public_key, b"Message", &[0xffu8; 256]).is_err());
+        // The modulus is the 256 bytes before the encoded exponent 65537.
+        let mut n = vec![0u8; 256];
+        bigint::to_be_bytes(
+            &bigint::from_be_bytes(
+                &PUBLIC_KEY[PUBLIC_KEY.len() - 5 - 256..PUBLIC_KEY.len() - 5],
+                32,
+            )
+            .unwrap(),
+            &mut n,
+        );
+        assert!(verify(&public_key, b"Message", &n).is_err());
+        assert!(verify(&public_key, b"Message", &[0u8; 256]).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_consistency() {
+        // Re-derive the encoded message from a signature with the public key.
+        let (secret_key, public_key) = keys();
+        let mut signature = vec![0u8; 256];
+        let salt = [0x5au8; SALTSIZE];
+        sign(&secret_key, b"abc", &salt, &mut signature).unwrap();
+
+        let mut em = vec![0u8; 256];
+        bigint::to_be_bytes(&public_key.raw_public(&signature).unwrap(), &mut em);
+        let mut expected = vec![0u8; 256];
+        encode(b"abc", &salt, &mut expected).unwrap();
+        assert_eq!(em, expected);
+    }
+
+    #[test]
+    fn test_mgf1() {
+        // MGF1-SHA256("abc", 40) is SHA256("abc" || counter) for counters 0 and 1,
+        // truncated to 40 bytes.
+        let mut out = [0u8; 40];
+        mgf1_xor(b"abc", &mut out).unwrap();
+        let mut expected = [0u8; 40];
+        let mut block = [0u8; 32];
+        Sha256::digest_into(&[b'a', b'b', b'c', 0, 0, 0, 0], &mut block).unwrap();
+        expected[..32].copy_from_slice(&block);
+        Sha256::digest_into(&[b'a', b'b', b'c', 0, 0, 0, 1], &mut block).unwrap();
+        expected[32..].copy_from_slice(&block[..8]);
+        assert_eq!(out, expected);
+    }
+}
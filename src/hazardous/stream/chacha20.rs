@@ -92,7 +92,10 @@
 //! [`XChaCha20Poly1305`]: ../../aead/xchacha20poly1305/index.html
 use crate::errors::UnknownCryptoError;
 use crate::util::endianness::load_u32_le;
-use crate::util::u32x4::U32x4;
+use crate::util::{
+    cpu::{self, Backend},
+    u32x4::U32x4,
+};
 use zeroize::{Zeroize, Zeroizing};
 
 /// The key size for ChaCha20.
@@ -105,6 +108,10 @@ pub(crate) const CHACHA_BLOCKSIZE: usize = 64;
 const HCHACHA_OUTSIZE: usize = 32;
 /// The nonce size for HChaCha20.
 pub(crate) const HCHACHA_NONCESIZE: usize = 16;
+/// The amount of blocks that are computed in parallel by the wide keystream function.
+pub(crate) const CHACHA_WIDE_LANES: usize = 8;
+/// The amount of keystream bytes the wide keystream function produces.
+pub(crate) const CHACHA_WIDE_BLOCKSIZE: usize = CHACHA_BLOCKSIZE * CHACHA_WIDE_LANES;
 
 construct_secret_key! {
     /// A type to represent the `SecretKey` that `chacha20`, `xchacha20`, `chacha20poly1305` and
@@ -166,6 +173,32 @@ macro_rules! DOUBLE_ROUND {
         $r3 = $r3.shl_1();
     };
 }
+
+/// A quarter round applied to the same state words of all [`CHACHA_WIDE_LANES`]
+/// blocks. Each state word holds one lane per block, so every operation is a
+/// lane-wise loop of fixed length, which the compiler can lower to vector
+/// instructions where the target has them.
+macro_rules! WIDE_QUARTER_ROUND {
+    ($x:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+        for lane in 0..CHACHA_WIDE_LANES {
+            $x[$a][lane] = $x[$a][lane].wrapping_add($x[$b][lane]);
+            $x[$d][lane] = ($x[$d][lane] ^ $x[$a][lane]).rotate_left(16);
+        }
+        for lane in 0..CHACHA_WIDE_LANES {
+            $x[$c][lane] = $x[$c][lane].wrapping_add($x[$d][lane]);
+            $x[$b][lane] = ($x[$b][lane] ^ $x[$c][lane]).rotate_left(12);
+        }
+        for lane in 0..CHACHA_WIDE_LANES {
+            $x[$a][lane] = $x[$a][lane].wrapping_add($x[$b][lane]);
+            $x[$d][lane] = ($x[$d][lane] ^ $x[$a][lane]).rotate_left(8);
+        }
+        for lane in 0..CHACHA_WIDE_LANES {
+            $x[$c][lane] = $x[$c][lane].wrapping_add($x[$d][lane]);
+            $x[$b][lane] = ($x[$b][lane] ^ $x[$c][lane]).rotate_left(7);
+        }
+    };
+}
+
 pub(crate) struct ChaCha20 {
     state: [U32x4; 4],
     internal_counter: u32,
@@ -283,6 +316,69 @@ impl ChaCha20 {
             wr3.store_into_le(iter.next().unwrap());
         }
     }
+
+    /// Process [`CHACHA_WIDE_LANES`] consecutive keystream blocks, starting at
+    /// `block_counter`, and copy them into the destination array.
+    ///
+    /// orion forbids `unsafe` code, which rules out calling SIMD intrinsics
+    /// directly. Instead, the state is kept word-sliced (one lane per block),
+    /// which lets the compiler vectorize all blocks at once for whichever target
+    /// features the crate is built with (e.g. `-C target-cpu=native`).
+    /// [`xor_keystream()`] only uses this if `util::cpu` selects the vector backend.
+    pub(crate) fn keystream_blocks_wide(&mut self, block_counter: u32, inplace: &mut [u8]) {
+        debug_assert!(self.is_ietf);
+        debug_assert!(inplace.len() == CHACHA_WIDE_BLOCKSIZE);
+        // The caller must ensure the last block counter does not overflow.
+        debug_assert!(block_counter
+            .checked_add((CHACHA_WIDE_LANES - 1) as u32)
+            .is_some());
+
+        // If this panics, max amount of keystream blocks
+        // have been retrieved.
+        self.internal_counter = self
+            .internal_counter
+            .checked_add(CHACHA_WIDE_LANES as u32)
+            .unwrap();
+
+        let mut initial = [[0u32; CHACHA_WIDE_LANES]; 16];
+        for (row_idx, row) in self.state.iter().enumerate() {
+            let words = [row.0, row.1, row.2, row.3];
+            for (col_idx, word) in words.iter().enumerate() {
+                initial[row_idx * 4 + col_idx] = [*word; CHACHA_WIDE_LANES];
+            }
+        }
+        for (lane, ctr) in initial[12].iter_mut().enumerate() {
+            *ctr = block_counter + lane as u32;
+        }
+
+        let mut x = initial;
+        for _ in 0..10 {
+            // Column rounds
+            WIDE_QUARTER_ROUND!(x, 0, 4, 8, 12);
+            WIDE_QUARTER_ROUND!(x, 1, 5, 9, 13);
+            WIDE_QUARTER_ROUND!(x, 2, 6, 10, 14);
+            WIDE_QUARTER_ROUND!(x, 3, 7, 11, 15);
+            // Diagonal rounds
+            WIDE_QUARTER_ROUND!(x, 0, 5, 10, 15);
+            WIDE_QUARTER_ROUND!(x, 1, 6, 11, 12);
+            WIDE_QUARTER_ROUND!(x, 2, 7, 8, 13);
+            WIDE_QUARTER_ROUND!(x, 3, 4, 9, 14);
+        }
+
+        for (lane, block) in inplace.chunks_exact_mut(CHACHA_BLOCKSIZE).enumerate() {
+            for (word_idx, out) in block.chunks_exact_mut(4).enumerate() {
+                let word = x[word_idx][lane].wrapping_add(initial[word_idx][lane]);
+                out.copy_from_slice(&word.to_le_bytes());
+            }
+        }
+
+        // Keep the state consistent with that of processing each block
+        // in turn using `keystream_block()`.
+        self.state[3].0 = block_counter + (CHACHA_WIDE_LANES - 1) as u32;
+
+        x.iter_mut().zeroize();
+        initial.iter_mut().zeroize();
+    }
 }
 
 /// XOR keystream into destination array using a temporary buffer for each keystream block.
@@ -297,8 +393,26 @@ pub(crate) fn xor_keystream(
         return Err(UnknownCryptoError);
     }
 
-    for (ctr, out_block) in bytes.chunks_mut(CHACHA_BLOCKSIZE).enumerate() {
-        match initial_counter.checked_add(ctr as u32) {
+    // Amount of blocks already processed by the wide keystream function.
+    let mut blocks_done = 0usize;
+    if bytes.len() >= CHACHA_WIDE_BLOCKSIZE && cpu::backend() == Backend::Vector {
+        let mut wide_block = Zeroizing::new([0u8; CHACHA_WIDE_BLOCKSIZE]);
+        for out_blocks in bytes.chunks_exact_mut(CHACHA_WIDE_BLOCKSIZE) {
+            // If the last counter of this batch cannot be represented, leave the
+            // remaining blocks to the single-block loop, which reports the overflow.
+            let last_ctr = (blocks_done + CHACHA_WIDE_LANES - 1) as u32;
+            if initial_counter.checked_add(last_ctr).is_none() {
+                break;
+            }
+            ctx.keystream_blocks_wide(initial_counter + blocks_done as u32, wide_block.as_mut());
+            xor_slices!(wide_block.as_ref(), out_blocks);
+            blocks_done += CHACHA_WIDE_LANES;
+        }
+    }
+
+    let remaining = &mut bytes[blocks_done * CHACHA_BLOCKSIZE..];
+    for (ctr, out_block) in remaining.chunks_mut(CHACHA_BLOCKSIZE).enumerate() {
+        match initial_counter.checked_add((blocks_done + ctr) as u32) {
             Some(counter) => {
                 ctx.keystream_block(counter, tmp_block);
                 xor_slices!(tmp_block, out_block);
@@ -1041,6 +1155,92 @@ mod private {
             }
         }
     }
+
+    mod test_keystream_blocks_wide {
+        use super::*;
+
+        #[test]
+        fn test_wide_same_as_single_blocks() {
+            for initial_counter in [0u32, 1, 7, u32::MAX - CHACHA_WIDE_LANES as u32].iter() {
+                let mut wide_ctx =
+                    ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true)
+                        .unwrap();
+                let mut single_ctx =
+                    ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true)
+                        .unwrap();
+
+                let mut wide = [0u8; CHACHA_WIDE_BLOCKSIZE];
+                let mut single = [0u8; CHACHA_WIDE_BLOCKSIZE];
+
+                wide_ctx.keystream_blocks_wide(*initial_counter, &mut wide);
+                for (ctr, block) in single.chunks_exact_mut(CHACHA_BLOCKSIZE).enumerate() {
+                    single_ctx.keystream_block(*initial_counter + ctr as u32, block);
+                }
+
+                assert_eq!(wide[..], single[..]);
+                assert!(wide_ctx.state == single_ctx.state);
+                assert_eq!(wide_ctx.internal_counter, single_ctx.internal_counter);
+            }
+        }
+
+        #[test]
+        fn test_xor_keystream_wide_and_tail() {
+            // Covers: only single blocks, exactly one wide batch, wide batches with
+            // a partial tail block.
+            for len in [1usize, 511, 512, 513, 1023, 1024, 1100].iter() {
+                let mut ctx =
+                    ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true)
+                        .unwrap();
+                let mut tmp = [0u8; CHACHA_BLOCKSIZE];
+                let mut actual = [0u8; 1100];
+                xor_keystream(&mut ctx, 1, &mut tmp, &mut actual[..*len]).unwrap();
+
+                let mut ctx =
+                    ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true)
+                        .unwrap();
+                let mut expected = [0u8; 1100];
+                for (ctr, block) in expected[..*len].chunks_mut(CHACHA_BLOCKSIZE).enumerate() {
+                    ctx.keystream_block(1 + ctr as u32, &mut tmp);
+                    xor_slices!(tmp, block);
+                }
+
+                assert_eq!(actual[..], expected[..]);
+            }
+        }
+
+        #[test]
+        fn test_xor_keystream_portable_same_as_vector() {
+            let mut tmp = [0u8; CHACHA_BLOCKSIZE];
+            let mut portable = [0u8; CHACHA_WIDE_BLOCKSIZE * 2 + 3];
+            let mut vector = [0u8; CHACHA_WIDE_BLOCKSIZE * 2 + 3];
+
+            crate::util::force_portable_backends(true);
+            let mut ctx =
+                ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true).unwrap();
+            xor_keystream(&mut ctx, 0, &mut tmp, &mut portable).unwrap();
+
+            crate::util::force_portable_backends(false);
+            let mut ctx =
+                ChaCha20::new(&[1u8; CHACHA_KEYSIZE], &[2u8; IETF_CHACHA_NONCESIZE], true).unwrap();
+            xor_keystream(&mut ctx, 0, &mut tmp, &mut vector).unwrap();
+
+            assert_eq!(portable[..], vector[..]);
+        }
+
+        #[test]
+        fn test_xor_keystream_counter_overflow_in_wide_batch() {
+            let mut ctx =
+                ChaCha20::new(&[0u8; CHACHA_KEYSIZE], &[0u8; IETF_CHACHA_NONCESIZE], true).unwrap();
+            let mut tmp = [0u8; CHACHA_BLOCKSIZE];
+            let mut out = [0u8; CHACHA_WIDE_BLOCKSIZE];
+
+            // Exactly enough counters left.
+            let first_ctr = u32::MAX - (CHACHA_WIDE_LANES - 1) as u32;
+            assert!(xor_keystream(&mut ctx, first_ctr, &mut tmp, &mut out).is_ok());
+            // One counter short.
+            assert!(xor_keystream(&mut ctx, first_ctr + 1, &mut tmp, &mut out).is_err());
+        }
+    }
 }
 
 // Testing any test vectors that aren't put into library's /tests folder.
@@ -31,7 +31,23 @@
 //!
 //! # About:
 //! - Both one-shot functions and a [`streaming API`] are provided.
+//! - [`seal_file`] and [`open_file`] encrypt anything implementing
+//!   `std::io::Read` into anything implementing `std::io::Write`, using the
+//!   [`streaming API`] with chunks of 64 KiB.
+//! - A stream sealed with [`seal_file`] is not encrypted with `secret_key`
+//!   directly, but with a random file key. The stream starts with a header of
+//!   [`FILE_HEADER_SIZE`] bytes: a version byte, a random 32-byte file ID, the
+//!   24-byte stream nonce and the file key, encrypted with XChaCha20Poly1305
+//!   under a key derived from `secret_key` and the file ID using HKDF-SHA512.
+//!   The version, file ID and nonce are the additional data of the encrypted
+//!   file key and of every chunk.
+//! - [`rewrap_file_header`] re-encrypts the file key of a header for a new
+//!   `secret_key`. The header keeps its size, so a key can be rotated by
+//!   overwriting only the first [`FILE_HEADER_SIZE`] bytes of each file.
 //! - The nonce is automatically generated.
+//! - With the `test_utils` feature, [`seal_with_nonce`] and
+//!   `StreamSealer::with_nonce()` take an explicit nonce instead, so that test
+//!   vectors can be created and compared with other implementations.
 //! - Returns a vector where the first 24 bytes are the nonce and the rest is
 //!   the authenticated ciphertext with the last 16 bytes being the corresponding Poly1305 tag.
 //! - Uses XChaCha20Poly1305 with no additional data.
@@ -51,6 +67,12 @@
 //! - `ciphertext_with_tag_and_nonce` is less than 41 bytes
 //!   ([`XCHACHA_NONCESIZE`] + [`POLY1305_OUTSIZE`] + 1).
 //! - The received tag does not match the calculated tag when calling [`open`].
+//! - Reading from `reader` or writing to `writer` fails when calling
+//!   [`seal_file`] or [`open_file`].
+//! - The stream read by [`open_file`] has an unknown version, has been
+//!   truncated or reordered, or has data after its last chunk.
+//! - The header is not [`FILE_HEADER_SIZE`] bytes, has an unknown version or
+//!   was not sealed with `secret_key` or `old_key`.
 //! - `plaintext.len()` + [`XCHACHA_NONCESIZE`] + [`POLY1305_OUTSIZE`] overflows when calling [`seal`].
 //!
 //! # Panics:
@@ -65,6 +87,12 @@
 //! compromised.
 //! - To securely generate a strong key, use [`SecretKey::default()`].
 //! - The length of the `plaintext` is not hidden, only its contents.
+//! - [`open_file`] writes each chunk to `writer` as soon as it has been
+//!   authenticated. If an error is returned, everything written to `writer`
+//!   so far must be discarded, since the stream may have been truncated.
+//! - After [`rewrap_file_header`], any copy of the old header still opens the
+//!   stream with the old key. Rotating a key only protects files whose old
+//!   headers, including backups, are gone.
 //!
 //! # Example:
 //! ```rust
@@ -75,8 +103,37 @@
 //! let decrypted_data = aead::open(&secret_key, &ciphertext)?;
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
+//!
+//! Encrypting and decrypting a file:
+//! ```rust
+//! use orion::aead;
+//! use std::io::Cursor;
+//!
+//! let secret_key = aead::SecretKey::default();
+//! // A `std::fs::File` works just the same.
+//! let file = Cursor::new(vec![1u8; 200_000]);
+//!
+//! let mut sealed = Vec::new();
+//! aead::seal_file(&secret_key, file, &mut sealed)?;
+//!
+//! let mut opened = Vec::new();
+//! aead::open_file(&secret_key, sealed.as_slice(), &mut opened)?;
+//! assert_eq!(opened, vec![1u8; 200_000]);
+//!
+//! // Rotating the key only requires a new header.
+//! let new_key = aead::SecretKey::default();
+//! aead::rewrap_file_header(&secret_key, &new_key, &mut sealed[..aead::FILE_HEADER_SIZE])?;
+//! assert!(aead::open_file(&secret_key, sealed.as_slice(), &mut Vec::new()).is_err());
+//! assert!(aead::open_file(&new_key, sealed.as_slice(), &mut Vec::new()).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
 //! [`seal`]: fn.seal.html
 //! [`open`]: fn.open.html
+//! [`seal_with_nonce`]: fn.seal_with_nonce.html
+//! [`seal_file`]: fn.seal_file.html
+//! [`open_file`]: fn.open_file.html
+//! [`rewrap_file_header`]: fn.rewrap_file_header.html
+//! [`FILE_HEADER_SIZE`]: constant.FILE_HEADER_SIZE.html
 //! [`POLY1305_OUTSIZE`]: ../hazardous/mac/poly1305/constant.POLY1305_OUTSIZE.html
 //! [`XCHACHA_NONCESIZE`]: ../hazardous/stream/xchacha20/constant.XCHACHA_NONCESIZE.html
 //! [`SecretKey::default()`]: struct.SecretKey.html
@@ -87,17 +144,89 @@ use crate::{
     errors::UnknownCryptoError,
     hazardous::{
         aead,
+        kdf::hkdf,
         mac::poly1305::POLY1305_OUTSIZE,
         stream::{
             chacha20,
             xchacha20::{Nonce, XCHACHA_NONCESIZE},
         },
     },
+    util::secure_rand_bytes,
 };
+use std::io::{ErrorKind, Read, Write};
+use zeroize::Zeroize;
+
+/// The version byte at the start of a stream sealed with `seal_file()`.
+const FILE_VERSION: u8 = 0x01;
+
+/// The length of the plaintext in each chunk sealed by `seal_file()`.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The size of the random file ID in the header of a sealed stream.
+const FILE_ID_SIZE: usize = 32;
+
+/// The size of the part of the header that never changes: the version, the
+/// file ID and the stream nonce. It is the additional data of the wrapped file
+/// key and of every chunk.
+const FILE_HEADER_AD_SIZE: usize = 1 + FILE_ID_SIZE + XCHACHA_NONCESIZE;
+
+/// The info used when deriving the key that wraps the file key.
+const FILE_WRAPPING_INFO: &[u8] = b"orion aead file wrapping key";
+
+/// The size of the header at the start of a stream sealed with [`seal_file`].
+///
+/// [`seal_file`]: fn.seal_file.html
+pub const FILE_HEADER_SIZE: usize =
+    FILE_HEADER_AD_SIZE + XCHACHA_NONCESIZE + chacha20::CHACHA_KEYSIZE + POLY1305_OUTSIZE;
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
 /// Authenticated encryption using XChaCha20Poly1305.
 pub fn seal(secret_key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    seal_with(secret_key, &Nonce::generate(), plaintext)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+#[cfg(feature = "test_utils")]
+/// Authenticated encryption using XChaCha20Poly1305, with `nonce` instead of
+/// a randomly generated nonce. The output has the same format as that of
+/// [`seal`], so it can be opened with [`open`].
+///
+/// This function is only available with the `test_utils` feature. It is meant
+/// for creating test vectors that other implementations can check, or for
+/// comparing against their outputs.
+///
+/// # Security:
+/// - Reusing a nonce with the same `secret_key` compromises the security of all
+///   data encrypted with that key. Never use this function outside of tests.
+///
+/// # Example:
+/// ```rust
+/// use orion::aead;
+/// use orion::hazardous::stream::xchacha20::Nonce;
+///
+/// let secret_key = aead::SecretKey::default();
+/// let nonce = Nonce::from([7u8; 24]);
+/// let ciphertext = aead::seal_with_nonce(&secret_key, &nonce, b"Test vector")?;
+/// assert_eq!(&ciphertext[..24], nonce.as_ref());
+/// assert_eq!(aead::open(&secret_key, &ciphertext)?, b"Test vector");
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`seal`]: fn.seal.html
+/// [`open`]: fn.open.html
+pub fn seal_with_nonce(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    seal_with(secret_key, nonce, plaintext)
+}
+
+/// Seal `plaintext` with `nonce`, prepending the nonce to the output.
+fn seal_with(
+    secret_key: &SecretKey,
+    nonce: &Nonce,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
     if plaintext.is_empty() {
         return Err(UnknownCryptoError);
     }
@@ -111,12 +240,11 @@ pub fn seal(secret_key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, Unknown
     };
 
     let mut dst_out = vec![0u8; out_len];
-    let nonce = Nonce::generate();
     dst_out[..XCHACHA_NONCESIZE].copy_from_slice(nonce.as_ref());
 
     aead::xchacha20poly1305::seal(
         &chacha20::SecretKey::from_slice(secret_key.unprotected_as_bytes())?,
-        &nonce,
+        nonce,
         plaintext,
         None,
         &mut dst_out[XCHACHA_NONCESIZE..],
@@ -150,6 +278,246 @@ pub fn open(
     Ok(dst_out)
 }
 
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticated encryption of everything read from `reader`, written to
+/// `writer`. Returns the amount of plaintext bytes sealed.
+pub fn seal_file<R: Read, W: Write>(
+    secret_key: &SecretKey,
+    mut reader: R,
+    mut writer: W,
+) -> Result<u64, UnknownCryptoError> {
+    let file_key = chacha20::SecretKey::generate();
+    let nonce = Nonce::generate();
+    let mut header = [0u8; FILE_HEADER_SIZE];
+    header[0] = FILE_VERSION;
+    secure_rand_bytes(&mut header[1..=FILE_ID_SIZE])?;
+    header[1 + FILE_ID_SIZE..FILE_HEADER_AD_SIZE].copy_from_slice(nonce.as_ref());
+    wrap_file_key(secret_key, &file_key, &mut header)?;
+    writer.write_all(&header)?;
+
+    let mut sealer = aead::streaming::StreamXChaCha20Poly1305::new(&file_key, &nonce);
+    let mut current = vec![0u8; FILE_CHUNK_SIZE];
+    let mut next = vec![0u8; FILE_CHUNK_SIZE];
+    let result = seal_file_chunks(
+        &mut sealer,
+        &header[..FILE_HEADER_AD_SIZE],
+        &mut reader,
+        &mut writer,
+        &mut current,
+        &mut next,
+    );
+    current.iter_mut().zeroize();
+    next.iter_mut().zeroize();
+
+    result
+}
+
+fn seal_file_chunks<R: Read, W: Write>(
+    sealer: &mut aead::streaming::StreamXChaCha20Poly1305,
+    ad: &[u8],
+    reader: &mut R,
+    writer: &mut W,
+    current: &mut Vec<u8>,
+    next: &mut Vec<u8>,
+) -> Result<u64, UnknownCryptoError> {
+    let mut frame = vec![0u8; FILE_CHUNK_SIZE + aead::streaming::ABYTES];
+    let mut current_len = read_full(reader, current)?;
+    let mut sealed_len = 0u64;
+
+    loop {
+        // A full chunk is only the last one if nothing can be read after it,
+        // so that the stream always ends with a chunk tagged FINISH.
+        let next_len = if current_len == FILE_CHUNK_SIZE {
+            read_full(reader, next)?
+        } else {
+            0
+        };
+        let tag = if next_len == 0 {
+            aead::streaming::StreamTag::FINISH
+        } else {
+            aead::streaming::StreamTag::MESSAGE
+        };
+
+        let frame_len = current_len + aead::streaming::ABYTES;
+        sealer.seal_chunk(&current[..current_len], Some(ad), &mut frame, tag)?;
+        writer.write_all(&frame[..frame_len])?;
+        sealed_len += current_len as u64;
+
+        if next_len == 0 {
+            break;
+        }
+        core::mem::swap(current, next);
+        current_len = next_len;
+    }
+
+    writer.flush()?;
+    Ok(sealed_len)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticated decryption of a stream sealed with [`seal_file`], read from
+/// `reader` and written to `writer`. Returns the amount of plaintext bytes
+/// opened.
+///
+/// [`seal_file`]: fn.seal_file.html
+pub fn open_file<R: Read, W: Write>(
+    secret_key: &SecretKey,
+    mut reader: R,
+    mut writer: W,
+) -> Result<u64, UnknownCryptoError> {
+    let mut header = [0u8; FILE_HEADER_SIZE];
+    if read_full(&mut reader, &mut header)? != FILE_HEADER_SIZE {
+        return Err(UnknownCryptoError);
+    }
+    let file_key = unwrap_file_key(secret_key, &header)?;
+    let nonce = Nonce::from_slice(&header[1 + FILE_ID_SIZE..FILE_HEADER_AD_SIZE])?;
+
+    let mut opener = aead::streaming::StreamXChaCha20Poly1305::new(&file_key, &nonce);
+    let mut chunk = vec![0u8; FILE_CHUNK_SIZE];
+    let result = open_file_chunks(
+        &mut opener,
+        &header[..FILE_HEADER_AD_SIZE],
+        &mut reader,
+        &mut writer,
+        &mut chunk,
+    );
+    chunk.iter_mut().zeroize();
+
+    result
+}
+
+fn open_file_chunks<R: Read, W: Write>(
+    opener: &mut aead::streaming::StreamXChaCha20Poly1305,
+    ad: &[u8],
+    reader: &mut R,
+    writer: &mut W,
+    chunk: &mut [u8],
+) -> Result<u64, UnknownCryptoError> {
+    let mut frame = vec![0u8; FILE_CHUNK_SIZE + aead::streaming::ABYTES];
+    let mut opened_len = 0u64;
+
+    loop {
+        let frame_len = read_full(reader, &mut frame)?;
+        // A missing FINISH chunk, from truncation, fails here at the latest.
+        let tag = opener.open_chunk(&frame[..frame_len], Some(ad), chunk)?;
+        let chunk_len = frame_len - aead::streaming::ABYTES;
+
+        let is_last = match tag {
+            aead::streaming::StreamTag::MESSAGE if frame_len == frame.len() => false,
+            aead::streaming::StreamTag::FINISH if read_full(reader, &mut [0u8; 1])? == 0 => true,
+            _ => return Err(UnknownCryptoError),
+        };
+        writer.write_all(&chunk[..chunk_len])?;
+        opened_len += chunk_len as u64;
+
+        if is_last {
+            break;
+        }
+    }
+
+    writer.flush()?;
+    Ok(opened_len)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Re-encrypt the file key in `header`, the first [`FILE_HEADER_SIZE`] bytes of
+/// a stream sealed with [`seal_file`], from `old_key` to `new_key`. The rest of
+/// the stream is left as it is.
+///
+/// [`FILE_HEADER_SIZE`]: constant.FILE_HEADER_SIZE.html
+/// [`seal_file`]: fn.seal_file.html
+pub fn rewrap_file_header(
+    old_key: &SecretKey,
+    new_key: &SecretKey,
+    header: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    let file_key = unwrap_file_key(old_key, header)?;
+    wrap_file_key(new_key, &file_key, header)
+}
+
+/// Derive the key that wraps the file key from `secret_key` and `file_id`.
+fn file_wrapping_key(
+    secret_key: &SecretKey,
+    file_id: &[u8],
+) -> Result<chacha20::SecretKey, UnknownCryptoError> {
+    let mut okm = [0u8; chacha20::CHACHA_KEYSIZE];
+    let result = hkdf::derive_key(
+        file_id,
+        secret_key.unprotected_as_bytes(),
+        Some(FILE_WRAPPING_INFO),
+        &mut okm,
+    )
+    .and_then(|_| chacha20::SecretKey::from_slice(&okm));
+    okm.zeroize();
+
+    result
+}
+
+/// Encrypt `file_key` into `header`, authenticating the rest of `header`.
+fn wrap_file_key(
+    secret_key: &SecretKey,
+    file_key: &chacha20::SecretKey,
+    header: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+    if header.len() != FILE_HEADER_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let wrapping_key = file_wrapping_key(secret_key, &header[1..=FILE_ID_SIZE])?;
+    let nonce = Nonce::generate();
+    let (ad, wrapped) = header.split_at_mut(FILE_HEADER_AD_SIZE);
+    wrapped[..XCHACHA_NONCESIZE].copy_from_slice(nonce.as_ref());
+
+    aead::xchacha20poly1305::seal(
+        &wrapping_key,
+        &nonce,
+        file_key.unprotected_as_bytes(),
+        Some(ad),
+        &mut wrapped[XCHACHA_NONCESIZE..],
+    )
+}
+
+/// Decrypt the file key in `header`, authenticating the rest of `header`.
+fn unwrap_file_key(
+    secret_key: &SecretKey,
+    header: &[u8],
+) -> Result<chacha20::SecretKey, UnknownCryptoError> {
+    if header.len() != FILE_HEADER_SIZE || header[0] != FILE_VERSION {
+        return Err(UnknownCryptoError);
+    }
+
+    let wrapping_key = file_wrapping_key(secret_key, &header[1..=FILE_ID_SIZE])?;
+    let (ad, wrapped) = header.split_at(FILE_HEADER_AD_SIZE);
+    let mut file_key = [0u8; chacha20::CHACHA_KEYSIZE];
+    let result = aead::xchacha20poly1305::open(
+        &wrapping_key,
+        &Nonce::from_slice(&wrapped[..XCHACHA_NONCESIZE])?,
+        &wrapped[XCHACHA_NONCESIZE..],
+        Some(ad),
+        &mut file_key,
+    )
+    .and_then(|_| chacha20::SecretKey::from_slice(&file_key));
+    file_key.zeroize();
+
+    result
+}
+
+/// Fill `buf` from `reader`, stopping early only at EOF. Returns the amount
+/// of bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, UnknownCryptoError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(filled)
+}
+
 pub mod streaming {
     //! Streaming AEAD based on XChaCha20Poly1305.
     //!
@@ -163,6 +531,15 @@ pub mod streaming {
     //! This implementation is based on and compatible with the ["secretstream" API](https://download.libsodium.org/doc/secret-key_cryptography/secretstream)
     //! of libsodium.
     //!
+    //! [`StreamSealer::seal_in_place()`] and [`StreamOpener::open_in_place()`]
+    //! encrypt and decrypt a whole stream within a single buffer, such as a
+    //! memory-mapped file, without copying it through intermediate buffers.
+    //! The plaintext is split into chunks of `chunk_size` bytes, the last of
+    //! which may be shorter and is sealed with `StreamTag::FINISH`. Each
+    //! chunk is framed as [`ABYTES`] + its length, exactly as
+    //! [`StreamSealer::seal_chunk()`] would output it. The buffer must be
+    //! [`sealed_len()`] bytes long when sealing.
+    //!
     //! # Parameters:
     //! - `secret_key`: The secret key.
     //! - `nonce`: The nonce value.
@@ -170,6 +547,9 @@ pub mod streaming {
     //! - `ciphertext`: The encrypted data with a Poly1305 tag and a [`StreamTag`] indicating its function.
     //! - `tag`: Indicates the type of message. The `tag` is a part of the output when encrypting. It
     //! is encrypted and authenticated.
+    //! - `buffer`: The stream to encrypt or decrypt in place.
+    //! - `plaintext_len`: The length of the plaintext at the start of `buffer`.
+    //! - `chunk_size`: The length of the plaintext in each chunk.
     //!
     //! # Errors:
     //! An error will be returned if:
@@ -179,6 +559,10 @@ pub mod streaming {
     //!   a dropped or reordered message within the stream.
     //! - More than 2^32-3 * 64 bytes of data are processed when encrypting/decrypting a single chunk.
     //! - [`ABYTES`] + `plaintext.len()` overflows when encrypting.
+    //! - `chunk_size` is 0, or the length of the sealed stream overflows `usize`.
+    //! - The length of `buffer` is not [`sealed_len()`] when calling [`StreamSealer::seal_in_place()`].
+    //! - The last chunk of `buffer` is not tagged with `StreamTag::FINISH`, or any other
+    //!   chunk is not tagged with `StreamTag::MESSAGE`, when calling [`StreamOpener::open_in_place()`].
     //!
     //! # Panics:
     //! A panic will occur if:
@@ -192,6 +576,8 @@ pub mod streaming {
     //! - The length of the messages is leaked.
     //! - It is recommended to use `StreamTag::FINISH` as tag for the last message. This allows the
     //!   decrypting side to detect if messages at the end of the stream are lost.
+    //! - If [`StreamOpener::open_in_place()`] returns an error, `buffer` is zeroed, since the
+    //!   stream may have been truncated or otherwise modified.
     //!
     //! # Example:
     //! ```rust
@@ -225,7 +611,32 @@ pub mod streaming {
     //!
     //! # Ok::<(), orion::errors::UnknownCryptoError>(())
     //! ```
+    //!
+    //! Sealing and opening a stream in place:
+    //! ```rust
+    //! use orion::aead::streaming::*;
+    //! use orion::aead::SecretKey;
+    //!
+    //! let key = SecretKey::default();
+    //! let plaintext = [1u8; 1000];
+    //!
+    //! // For example, a memory-mapped file that has been extended to `sealed_len()`.
+    //! let mut buffer = vec![0u8; sealed_len(plaintext.len(), 256)?];
+    //! buffer[..plaintext.len()].copy_from_slice(&plaintext);
+    //!
+    //! let (mut sealer, nonce) = StreamSealer::new(&key)?;
+    //! sealer.seal_in_place(&mut buffer, plaintext.len(), 256)?;
+    //!
+    //! let mut opener = StreamOpener::new(&key, &nonce)?;
+    //! let plaintext_len = opener.open_in_place(&mut buffer, 256)?;
+    //! assert_eq!(&buffer[..plaintext_len], &plaintext[..]);
+    //! # Ok::<(), orion::errors::UnknownCryptoError>(())
+    //! ```
     //! [`ABYTES`]: ../../hazardous/aead/streaming/constant.ABYTES.html
+    //! [`sealed_len()`]: fn.sealed_len.html
+    //! [`StreamSealer::seal_chunk()`]: struct.StreamSealer.html#method.seal_chunk
+    //! [`StreamSealer::seal_in_place()`]: struct.StreamSealer.html#method.seal_in_place
+    //! [`StreamOpener::open_in_place()`]: struct.StreamOpener.html#method.open_in_place
     //! [`StreamTag`]: ../../hazardous/aead/streaming/enum.StreamTag.html
     //! [`SecretKey::generate()`]: ../struct.SecretKey.html
 
@@ -233,6 +644,34 @@ pub mod streaming {
     pub use crate::hazardous::aead::streaming::Nonce;
     pub use crate::hazardous::aead::streaming::StreamTag;
 
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// The length of a stream sealed in place, with `plaintext_len` bytes of
+    /// plaintext in chunks of `chunk_size`.
+    pub fn sealed_len(
+        plaintext_len: usize,
+        chunk_size: usize,
+    ) -> Result<usize, UnknownCryptoError> {
+        if chunk_size == 0 || chunk_size.checked_add(aead::streaming::ABYTES).is_none() {
+            return Err(UnknownCryptoError);
+        }
+
+        chunk_count(plaintext_len, chunk_size)
+            .checked_mul(aead::streaming::ABYTES)
+            .and_then(|overhead| overhead.checked_add(plaintext_len))
+            .ok_or(UnknownCryptoError)
+    }
+
+    /// The amount of chunks `plaintext_len` bytes are split into. An empty
+    /// plaintext is sealed as a single empty chunk.
+    fn chunk_count(plaintext_len: usize, chunk_size: usize) -> usize {
+        debug_assert!(chunk_size != 0);
+        if plaintext_len == 0 {
+            1
+        } else {
+            (plaintext_len - 1) / chunk_size + 1
+        }
+    }
+
     #[derive(Debug)]
     /// Streaming authenticated encryption.
     pub struct StreamSealer {
@@ -244,14 +683,35 @@ pub mod streaming {
         /// Initialize a `StreamSealer` struct with a given key.
         pub fn new(secret_key: &SecretKey) -> Result<(Self, Nonce), UnknownCryptoError> {
             let nonce = Nonce::generate();
-            let sk = &aead::streaming::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+            let sealer = Self::new_with(secret_key, &nonce)?;
 
-            let sealer = Self {
-                internal_sealer: aead::streaming::StreamXChaCha20Poly1305::new(sk, &nonce),
-            };
             Ok((sealer, nonce))
         }
 
+        #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+        #[cfg(feature = "test_utils")]
+        /// Initialize a `StreamSealer` struct with a given key and `nonce`,
+        /// instead of a randomly generated nonce.
+        ///
+        /// This is only available with the `test_utils` feature, to create
+        /// test vectors of streams. Reusing a nonce with the same key
+        /// compromises the security of all streams sealed with that key.
+        pub fn with_nonce(
+            secret_key: &SecretKey,
+            nonce: &Nonce,
+        ) -> Result<Self, UnknownCryptoError> {
+            Self::new_with(secret_key, nonce)
+        }
+
+        /// Initialize a `StreamSealer` with `nonce`.
+        fn new_with(secret_key: &SecretKey, nonce: &Nonce) -> Result<Self, UnknownCryptoError> {
+            let sk = &aead::streaming::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+
+            Ok(Self {
+                internal_sealer: aead::streaming::StreamXChaCha20Poly1305::new(sk, nonce),
+            })
+        }
+
         #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
         /// Encrypts `plaintext`. The `StreamTag` indicates the type of message.
         pub fn seal_chunk(
@@ -270,6 +730,43 @@ pub mod streaming {
 
             Ok(sealed_chunk)
         }
+
+        #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+        /// Encrypts the `plaintext_len` bytes at the start of `buffer` in
+        /// place, as chunks of `chunk_size`. The last chunk is tagged with
+        /// `StreamTag::FINISH`.
+        pub fn seal_in_place(
+            &mut self,
+            buffer: &mut [u8],
+            plaintext_len: usize,
+            chunk_size: usize,
+        ) -> Result<(), UnknownCryptoError> {
+            if buffer.len() != sealed_len(plaintext_len, chunk_size)? {
+                return Err(UnknownCryptoError);
+            }
+
+            let frame_len = chunk_size + aead::streaming::ABYTES;
+            let chunks = chunk_count(plaintext_len, chunk_size);
+
+            // Move the chunks into their frames, starting from the last, so
+            // that no chunk is overwritten before it has been moved.
+            for idx in (0..chunks).rev() {
+                let start = idx * chunk_size;
+                let end = core::cmp::min(start + chunk_size, plaintext_len);
+                buffer.copy_within(start..end, idx * frame_len + aead::streaming::TAG_SIZE);
+            }
+
+            for (idx, frame) in buffer.chunks_mut(frame_len).enumerate() {
+                let tag = if idx == chunks - 1 {
+                    StreamTag::FINISH
+                } else {
+                    StreamTag::MESSAGE
+                };
+                self.internal_sealer.seal_chunk_in_place(frame, None, tag)?;
+            }
+
+            Ok(())
+        }
     }
 
     #[derive(Debug)]
@@ -305,6 +802,65 @@ pub mod streaming {
 
             Ok((opened_chunk, tag))
         }
+
+        #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+        /// Decrypts a stream sealed with [`StreamSealer::seal_in_place()`]
+        /// in place. The plaintext is moved to the start of `buffer`, and its
+        /// length is returned. `buffer` is zeroed if an error is returned.
+        ///
+        /// [`StreamSealer::seal_in_place()`]: struct.StreamSealer.html#method.seal_in_place
+        pub fn open_in_place(
+            &mut self,
+            buffer: &mut [u8],
+            chunk_size: usize,
+        ) -> Result<usize, UnknownCryptoError> {
+            let result = self.open_frames_in_place(buffer, chunk_size);
+            if result.is_err() {
+                buffer.iter_mut().zeroize();
+            }
+
+            result
+        }
+
+        fn open_frames_in_place(
+            &mut self,
+            buffer: &mut [u8],
+            chunk_size: usize,
+        ) -> Result<usize, UnknownCryptoError> {
+            let frame_len = match chunk_size.checked_add(aead::streaming::ABYTES) {
+                Some(frame_len) if chunk_size != 0 => frame_len,
+                _ => return Err(UnknownCryptoError),
+            };
+            if buffer.is_empty() {
+                return Err(UnknownCryptoError);
+            }
+
+            let chunks = (buffer.len() - 1) / frame_len + 1;
+            let mut plaintext_len = 0;
+
+            for idx in 0..chunks {
+                let start = idx * frame_len;
+                let end = core::cmp::min(start + frame_len, buffer.len());
+                let tag = self
+                    .internal_sealer
+                    .open_chunk_in_place(&mut buffer[start..end], None)?;
+                let expected_tag = if idx == chunks - 1 {
+                    StreamTag::FINISH
+                } else {
+                    StreamTag::MESSAGE
+                };
+                if tag != expected_tag {
+                    return Err(UnknownCryptoError);
+                }
+
+                let msg_start = start + aead::streaming::TAG_SIZE;
+                let msg_len = end - start - aead::streaming::ABYTES;
+                buffer.copy_within(msg_start..(msg_start + msg_len), plaintext_len);
+                plaintext_len += msg_len;
+            }
+
+            Ok(plaintext_len)
+        }
     }
 }
 
@@ -397,9 +953,301 @@ mod public {
         }
     }
 
+    mod test_seal_open_file {
+        use super::*;
+        use crate::hazardous::aead::streaming::ABYTES;
+        use std::io::{Cursor, Error, ErrorKind, Read};
+
+        const HEADER: usize = FILE_HEADER_SIZE;
+        const FRAME: usize = FILE_CHUNK_SIZE + ABYTES;
+
+        fn seal_to_vec(key: &SecretKey, plaintext: &[u8]) -> Vec<u8> {
+            let mut sealed = Vec::new();
+            assert_eq!(
+                seal_file(key, plaintext, &mut sealed).unwrap(),
+                plaintext.len() as u64
+            );
+            sealed
+        }
+
+        fn open_to_vec(key: &SecretKey, sealed: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+            let mut opened = Vec::new();
+            let opened_len = open_file(key, sealed, &mut opened)?;
+            assert_eq!(opened_len, opened.len() as u64);
+            Ok(opened)
+        }
+
+        /// A reader returning at most 7 bytes per call and interrupting
+        /// every other call.
+        struct SlowReader<'a> {
+            inner: Cursor<&'a [u8]>,
+            interrupt: bool,
+        }
+
+        impl Read for SlowReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.interrupt = !self.interrupt;
+                if self.interrupt {
+                    return Err(Error::new(ErrorKind::Interrupted, "interrupted"));
+                }
+                let len = core::cmp::min(buf.len(), 7);
+                self.inner.read(&mut buf[..len])
+            }
+        }
+
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(Error::new(ErrorKind::PermissionDenied, "failure"))
+            }
+        }
+
+        #[test]
+        fn test_roundtrip_various_lengths() {
+            let key = SecretKey::default();
+            for len in [
+                0,
+                1,
+                FILE_CHUNK_SIZE - 1,
+                FILE_CHUNK_SIZE,
+                FILE_CHUNK_SIZE + 1,
+                3 * FILE_CHUNK_SIZE,
+                3 * FILE_CHUNK_SIZE + 100,
+            ]
+            .iter()
+            {
+                let plaintext: Vec<u8> = (0..*len).map(|i| i as u8).collect();
+                let sealed = seal_to_vec(&key, &plaintext);
+                let chunks = if *len == 0 {
+                    1
+                } else {
+                    (len - 1) / FILE_CHUNK_SIZE + 1
+                };
+                assert_eq!(sealed.len(), HEADER + len + chunks * ABYTES);
+                assert_eq!(sealed[0], FILE_VERSION);
+                assert_eq!(open_to_vec(&key, &sealed).unwrap(), plaintext);
+            }
+        }
+
+        #[test]
+        fn test_roundtrip_short_reads() {
+            let key = SecretKey::default();
+            let plaintext = vec![7u8; FILE_CHUNK_SIZE + 10];
+            let mut sealed = Vec::new();
+            let reader = SlowReader {
+                inner: Cursor::new(&plaintext),
+                interrupt: false,
+            };
+            seal_file(&key, reader, &mut sealed).unwrap();
+
+            let mut opened = Vec::new();
+            let reader = SlowReader {
+                inner: Cursor::new(&sealed),
+                interrupt: false,
+            };
+            open_file(&key, reader, &mut opened).unwrap();
+            assert_eq!(opened, plaintext);
+        }
+
+        #[test]
+        fn test_same_plaintext_different_output() {
+            let key = SecretKey::default();
+            let plaintext = [0u8; 64];
+
+            assert_ne!(seal_to_vec(&key, &plaintext), seal_to_vec(&key, &plaintext));
+        }
+
+        #[test]
+        fn test_wrong_key_err() {
+            let key = SecretKey::default();
+            let sealed = seal_to_vec(&key, &[0u8; 64]);
+
+            assert!(open_to_vec(&SecretKey::default(), &sealed).is_err());
+        }
+
+        #[test]
+        fn test_bad_header_err() {
+            let key = SecretKey::default();
+            let sealed = seal_to_vec(&key, &[0u8; 64]);
+
+            let mut bad_version = sealed.clone();
+            bad_version[0] = FILE_VERSION + 1;
+            assert!(open_to_vec(&key, &bad_version).is_err());
+
+            let mut bad_nonce = sealed.clone();
+            bad_nonce[1] ^= 1;
+            assert!(open_to_vec(&key, &bad_nonce).is_err());
+
+            assert!(open_to_vec(&key, &[]).is_err());
+            assert!(open_to_vec(&key, &sealed[..HEADER - 1]).is_err());
+            assert!(open_to_vec(&key, &sealed[..HEADER]).is_err());
+        }
+
+        #[test]
+        fn test_header_size() {
+            assert_eq!(FILE_HEADER_SIZE, 129);
+        }
+
+        #[test]
+        fn test_modified_header_err() {
+            let key = SecretKey::default();
+            let sealed = seal_to_vec(&key, &[0u8; 64]);
+
+            // Every byte of the header is authenticated, either as additional
+            // data or as part of the encrypted file key.
+            for idx in 0..HEADER {
+                let mut modified = sealed.clone();
+                modified[idx] ^= 1;
+                assert!(open_to_vec(&key, &modified).is_err());
+            }
+        }
+
+        #[test]
+        fn test_swapped_header_err() {
+            let key = SecretKey::default();
+            let first = seal_to_vec(&key, &[0u8; 64]);
+            let second = seal_to_vec(&key, &[0u8; 64]);
+
+            let mut swapped = second[..HEADER].to_vec();
+            swapped.extend_from_slice(&first[HEADER..]);
+            assert!(open_to_vec(&key, &swapped).is_err());
+        }
+
+        #[test]
+        fn test_unique_file_key() {
+            let key = SecretKey::default();
+            let sealed = seal_to_vec(&key, &[0u8; 64]);
+            let file_key = unwrap_file_key(&key, &sealed[..HEADER]).unwrap();
+
+            assert_ne!(file_key.unprotected_as_bytes(), key.unprotected_as_bytes());
+            let other = seal_to_vec(&key, &[0u8; 64]);
+            assert_ne!(sealed[1..=FILE_ID_SIZE], other[1..=FILE_ID_SIZE]);
+            assert_ne!(
+                file_key.unprotected_as_bytes(),
+                unwrap_file_key(&key, &other[..HEADER])
+                    .unwrap()
+                    .unprotected_as_bytes()
+            );
+        }
+
+        #[test]
+        fn test_rewrap_file_header() {
+            let old_key = SecretKey::default();
+            let new_key = SecretKey::default();
+            let plaintext = vec![3u8; FILE_CHUNK_SIZE + 1];
+            let sealed = seal_to_vec(&old_key, &plaintext);
+
+            let mut rewrapped = sealed.clone();
+            rewrap_file_header(&old_key, &new_key, &mut rewrapped[..HEADER]).unwrap();
+            assert_eq!(
+                rewrapped[..FILE_HEADER_AD_SIZE],
+                sealed[..FILE_HEADER_AD_SIZE]
+            );
+            assert_ne!(rewrapped[..HEADER], sealed[..HEADER]);
+            assert_eq!(rewrapped[HEADER..], sealed[HEADER..]);
+
+            assert!(open_to_vec(&old_key, &rewrapped).is_err());
+            assert_eq!(open_to_vec(&new_key, &rewrapped).unwrap(), plaintext);
+            // The old header still opens with the old key.
+            assert_eq!(open_to_vec(&old_key, &sealed).unwrap(), plaintext);
+        }
+
+        #[test]
+        fn test_rewrap_file_header_err() {
+            let old_key = SecretKey::default();
+            let new_key = SecretKey::default();
+            let sealed = seal_to_vec(&old_key, &[0u8; 64]);
+
+            let mut header = sealed[..HEADER].to_vec();
+            assert!(rewrap_file_header(&new_key, &old_key, &mut header).is_err());
+            assert_eq!(header, sealed[..HEADER]);
+
+            assert!(rewrap_file_header(&old_key, &new_key, &mut header[..HEADER - 1]).is_err());
+            let mut long_header = sealed[..HEADER + 1].to_vec();
+            assert!(rewrap_file_header(&old_key, &new_key, &mut long_header).is_err());
+
+            header[0] = FILE_VERSION + 1;
+            assert!(rewrap_file_header(&old_key, &new_key, &mut header).is_err());
+        }
+
+        #[test]
+        fn test_modified_chunk_err() {
+            let key = SecretKey::default();
+            let sealed = seal_to_vec(&key, &[0u8; 2 * FILE_CHUNK_SIZE]);
+
+            for idx in [HEADER, HEADER + FRAME - 1, HEADER + FRAME, sealed.len() - 1].iter() {
+                let mut modified = sealed.clone();
+                modified[*idx] ^= 1;
+                assert!(open_to_vec(&key, &modified).is_err());
+            }
+        }
+
+        #[test]
+        fn test_truncated_err() {
+            let key = SecretKey::default();
+            // Sealed as a full MESSAGE chunk, a full FINISH chunk.
+            let sealed = seal_to_vec(&key, &[0u8; 2 * FILE_CHUNK_SIZE]);
+            assert_eq!(sealed.len(), HEADER + 2 * FRAME);
+
+            // Dropping the last chunk entirely.
+            assert!(open_to_vec(&key, &sealed[..HEADER + FRAME]).is_err());
+            // Cutting into the last chunk.
+            assert!(open_to_vec(&key, &sealed[..sealed.len() - 1]).is_err());
+            assert!(open_to_vec(&key, &sealed[..HEADER + FRAME + ABYTES]).is_err());
+        }
+
+        #[test]
+        fn test_reordered_chunks_err() {
+            let key = SecretKey::default();
+            let plaintext: Vec<u8> = (0..3 * FILE_CHUNK_SIZE).map(|i| i as u8).collect();
+            let sealed = seal_to_vec(&key, &plaintext);
+
+            let mut reordered = sealed[..HEADER].to_vec();
+            reordered.extend_from_slice(&sealed[HEADER + FRAME..HEADER + 2 * FRAME]);
+            reordered.extend_from_slice(&sealed[HEADER..HEADER + FRAME]);
+            reordered.extend_from_slice(&sealed[HEADER + 2 * FRAME..]);
+            assert!(open_to_vec(&key, &reordered).is_err());
+        }
+
+        #[test]
+        fn test_trailing_data_err() {
+            let key = SecretKey::default();
+            let mut sealed = seal_to_vec(&key, &[0u8; 64]);
+            sealed.push(0u8);
+
+            assert!(open_to_vec(&key, &sealed).is_err());
+        }
+
+        #[test]
+        fn test_appended_stream_err() {
+            let key = SecretKey::default();
+            let mut sealed = seal_to_vec(&key, &[0u8; 64]);
+            let second = seal_to_vec(&key, &[0u8; 64]);
+            sealed.extend_from_slice(&second);
+
+            assert!(open_to_vec(&key, &sealed).is_err());
+        }
+
+        #[test]
+        fn test_io_error_err() {
+            let key = SecretKey::default();
+            let mut out = Vec::new();
+
+            assert!(seal_file(&key, FailingReader, &mut out).is_err());
+            assert!(open_file(&key, FailingReader, &mut out).is_err());
+
+            let sealed = seal_to_vec(&key, &[0u8; 64]);
+            let mut full = [0u8; 10];
+            assert!(seal_file(&key, &[0u8; 64][..], &mut full[..]).is_err());
+            assert!(open_file(&key, sealed.as_slice(), &mut full[..]).is_err());
+        }
+    }
+
     mod test_stream_seal_open {
         use super::streaming::*;
         use super::*;
+        use crate::hazardous::aead::streaming::ABYTES;
 
         #[test]
         fn test_auth_enc_encryption_decryption() {
@@ -427,7 +1275,7 @@ mod public {
         #[test]
         fn test_open_chunk_less_than_abytes_err() {
             let key = SecretKey::default();
-            let ciphertext = [0u8; aead::streaming::ABYTES - 1];
+            let ciphertext = [0u8; ABYTES - 1];
             let (_, nonce) = StreamSealer::new(&key).unwrap();
             let mut opener = StreamOpener::new(&key, &nonce).unwrap();
 
@@ -567,6 +1415,166 @@ mod public {
             assert_eq!(tag2, StreamTag::FINISH);
             assert_eq!(tag3, StreamTag::MESSAGE);
         }
+
+        #[test]
+        fn test_sealed_len() {
+            assert_eq!(sealed_len(0, 10).unwrap(), ABYTES);
+            assert_eq!(sealed_len(1, 10).unwrap(), 1 + ABYTES);
+            assert_eq!(sealed_len(10, 10).unwrap(), 10 + ABYTES);
+            assert_eq!(sealed_len(11, 10).unwrap(), 11 + 2 * ABYTES);
+            assert!(sealed_len(10, 0).is_err());
+            assert!(sealed_len(10, usize::MAX).is_err());
+            assert!(sealed_len(usize::MAX, 1).is_err());
+        }
+
+        #[test]
+        fn test_seal_open_in_place() {
+            let key = SecretKey::default();
+            let plaintext: Vec<u8> = (0..100u8).collect();
+
+            for (len, chunk_size) in
+                [(0, 10), (1, 10), (10, 10), (11, 10), (100, 7), (100, 1)].iter()
+            {
+                let mut buffer = vec![0u8; sealed_len(*len, *chunk_size).unwrap()];
+                buffer[..*len].copy_from_slice(&plaintext[..*len]);
+
+                let (mut sealer, nonce) = StreamSealer::new(&key).unwrap();
+                sealer
+                    .seal_in_place(&mut buffer, *len, *chunk_size)
+                    .unwrap();
+
+                // The frames are the same as sealing each chunk separately.
+                let mut opener = StreamOpener::new(&key, &nonce).unwrap();
+                let mut frames = buffer.chunks(chunk_size + ABYTES).peekable();
+                let mut opened = Vec::new();
+                while let Some(frame) = frames.next() {
+                    let (chunk, tag) = opener.open_chunk(frame).unwrap();
+                    if frames.peek().is_some() {
+                        assert_eq!(tag, StreamTag::MESSAGE);
+                    } else {
+                        assert_eq!(tag, StreamTag::FINISH);
+                    }
+                    opened.extend_from_slice(&chunk);
+                }
+                assert_eq!(opened[..], plaintext[..*len]);
+
+                let mut opener = StreamOpener::new(&key, &nonce).unwrap();
+                let opened_len = opener.open_in_place(&mut buffer, *chunk_size).unwrap();
+                assert_eq!(buffer[..opened_len], plaintext[..*len]);
+            }
+        }
+
+        #[test]
+        fn test_seal_in_place_err() {
+            let key = SecretKey::default();
+            let (mut sealer, _) = StreamSealer::new(&key).unwrap();
+
+            let mut buffer = vec![0u8; sealed_len(11, 10).unwrap()];
+            assert!(sealer.seal_in_place(&mut buffer, 11, 0).is_err());
+            assert!(sealer.seal_in_place(&mut buffer, 10, 10).is_err());
+            assert!(sealer.seal_in_place(&mut buffer[1..], 11, 10).is_err());
+        }
+
+        #[test]
+        fn test_open_in_place_err() {
+            let key = SecretKey::default();
+            let mut buffer = vec![0u8; sealed_len(25, 10).unwrap()];
+            buffer[..25].copy_from_slice(&[1u8; 25]);
+            let (mut sealer, nonce) = StreamSealer::new(&key).unwrap();
+            sealer.seal_in_place(&mut buffer, 25, 10).unwrap();
+
+            let open = |buffer: &mut [u8], chunk_size: usize| {
+                let mut opener = StreamOpener::new(&key, &nonce).unwrap();
+                opener.open_in_place(buffer, chunk_size)
+            };
+
+            // Truncated after a whole chunk, so the FINISH chunk is missing.
+            let mut truncated = buffer[..(10 + ABYTES)].to_vec();
+            assert!(open(&mut truncated, 10).is_err());
+            assert_eq!(truncated, vec![0u8; 10 + ABYTES]);
+
+            // Modified.
+            let mut modified = buffer.clone();
+            modified[10 + ABYTES + 5] ^= 1;
+            assert!(open(&mut modified, 10).is_err());
+            assert_eq!(modified, vec![0u8; buffer.len()]);
+
+            // Wrong chunk size.
+            assert!(open(&mut buffer.clone(), 11).is_err());
+            assert!(open(&mut buffer.clone(), 0).is_err());
+            assert!(open(&mut [], 10).is_err());
+
+            assert_eq!(open(&mut buffer, 10).unwrap(), 25);
+            assert_eq!(buffer[..25], [1u8; 25]);
+        }
+    }
+
+    #[cfg(feature = "test_utils")]
+    mod test_with_nonce {
+        use super::streaming::*;
+        use super::*;
+        use crate::util::test_rng;
+
+        #[test]
+        fn test_seal_with_nonce_libsodium() {
+            // From libsodium's crypto_aead_xchacha20poly1305_ietf_encrypt().
+            let key = SecretKey::from_slice(&(0u8..32).collect::<Vec<u8>>()).unwrap();
+            let mut nonce = [0u8; XCHACHA_NONCESIZE];
+            for (idx, byte) in nonce.iter_mut().enumerate() {
+                *byte = 0x40 + idx as u8;
+            }
+            let expected = "404142434445464748494a4b4c4d4e4f5051525354555657\
+                            98586119b5935977e190a7f9caf211fef7d7c8aa333635ba\
+                            1e5998656a6842e365d96d0875b5bd2964f324e09ebb6b30\
+                            0c0123dee5a4b59d";
+
+            let plaintext = b"Ladies and Gentlemen of the class of '99";
+            let sealed = seal_with_nonce(&key, &Nonce::from(nonce), plaintext).unwrap();
+            assert_eq!(hex::encode(&sealed), expected);
+            assert_eq!(open(&key, &sealed).unwrap(), &plaintext[..]);
+        }
+
+        #[test]
+        fn test_seal_with_nonce_matches_seeded_seal() {
+            let key = SecretKey::default();
+            let plaintext = "Secret message".as_bytes();
+
+            let sealed = test_rng::with_seed(42, || seal(&key, plaintext).unwrap());
+            let nonce = test_rng::with_seed(42, Nonce::generate);
+            assert_eq!(seal_with_nonce(&key, &nonce, plaintext).unwrap(), sealed);
+        }
+
+        #[test]
+        fn test_seal_with_nonce_err() {
+            let nonce = Nonce::generate();
+            assert!(seal_with_nonce(&SecretKey::default(), &nonce, b"").is_err());
+            let key = SecretKey::generate(31).unwrap();
+            assert!(seal_with_nonce(&key, &nonce, b"Secret message").is_err());
+        }
+
+        #[test]
+        fn test_stream_sealer_with_nonce() {
+            let key = SecretKey::default();
+            let (mut sealer, nonce) = test_rng::with_seed(7, || StreamSealer::new(&key).unwrap());
+            let mut sealer_with_nonce = StreamSealer::with_nonce(&key, &nonce).unwrap();
+
+            for plaintext in [&b"first"[..], &b"second"[..]].iter() {
+                let sealed = sealer.seal_chunk(plaintext, StreamTag::MESSAGE).unwrap();
+                let sealed_with_nonce = sealer_with_nonce
+                    .seal_chunk(plaintext, StreamTag::MESSAGE)
+                    .unwrap();
+                assert_eq!(sealed, sealed_with_nonce);
+            }
+
+            let mut opener = StreamOpener::new(&key, &nonce).unwrap();
+            let sealed = StreamSealer::with_nonce(&key, &nonce)
+                .unwrap()
+                .seal_chunk(b"first", StreamTag::FINISH)
+                .unwrap();
+            let (opened, tag) = opener.open_chunk(&sealed).unwrap();
+            assert_eq!(opened, b"first");
+            assert_eq!(tag, StreamTag::FINISH);
+        }
     }
 
     mod proptest {
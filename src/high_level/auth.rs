@@ -83,14 +83,20 @@ const BLAKE2B_TAG_SIZE: usize = 32;
 /// The minimum `SecretKey` size (bytes) to be used by BLAKE2b in keyed mode.
 const BLAKE2B_MIN_KEY_SIZE: usize = 32;
 
-#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
-/// Authenticate a message using BLAKE2b-256 in keyed mode.
-pub fn authenticate(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+/// Initialize a BLAKE2b-256 state in keyed mode, for authenticating data
+/// incrementally.
+pub(crate) fn init(secret_key: &SecretKey) -> Result<Blake2b, UnknownCryptoError> {
     if secret_key.len() < BLAKE2B_MIN_KEY_SIZE {
         return Err(UnknownCryptoError);
     }
     let blake2b_secret_key = blake2b::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
-    let mut state = Blake2b::new(Some(&blake2b_secret_key), BLAKE2B_TAG_SIZE)?;
+    Blake2b::new(Some(&blake2b_secret_key), BLAKE2B_TAG_SIZE)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticate a message using BLAKE2b-256 in keyed mode.
+pub fn authenticate(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+    let mut state = init(secret_key)?;
     state.update(data)?;
     let blake2b_digest = state.finalize()?;
     Tag::from_slice(blake2b_digest.as_ref())
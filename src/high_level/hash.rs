@@ -31,10 +31,23 @@
 //!
 //! # About:
 //! - Uses BLAKE2b with an output size of 32 bytes (i.e BLAKE2b-256).
+//! - [`digest_with_size`] uses BLAKE2b with an output size between
+//!   [`MIN_DIGEST_SIZE`] and [`MAX_DIGEST_SIZE`] bytes. Digests of different
+//!   sizes are unrelated, and a shorter digest is not a prefix of a longer one.
+//! - [`DigestReader`] and [`DigestWriter`] hash data as it is read from or
+//!   written to a stream, such as when verifying the checksum of a download.
+//! - [`digest_from_reader`] hashes all data of a stream, reporting its
+//!   progress to a [`Progress`], which can also cancel it.
 //!
 //! # Parameters:
 //! - `data`:  The data to be hashed.
 //!
+//! - `size`: The size of the digest.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `size` is less than [`MIN_DIGEST_SIZE`] or greater than [`MAX_DIGEST_SIZE`].
+//!
 //! # Panics:
 //! A panic will occur if:
 //! - More than 2*(2^64-1) bytes of data are hashed.
@@ -54,9 +67,22 @@
 //! # Ok::<(), orion::errors::UnknownCryptoError>(())
 //! ```
 //! [`orion::pwhash`]: ../pwhash/index.html
+//! [`DigestReader`]: struct.DigestReader.html
+//! [`DigestWriter`]: struct.DigestWriter.html
+//! [`digest_from_reader`]: fn.digest_from_reader.html
+//! [`digest_with_size`]: fn.digest_with_size.html
+//! [`MIN_DIGEST_SIZE`]: constant.MIN_DIGEST_SIZE.html
+//! [`MAX_DIGEST_SIZE`]: constant.MAX_DIGEST_SIZE.html
+//! [`Progress`]: ../util/trait.Progress.html
 
 pub use crate::hazardous::hash::blake2b::Digest;
-use crate::{errors::UnknownCryptoError, hazardous::hash::blake2b};
+use crate::{
+    auth,
+    errors::UnknownCryptoError,
+    hazardous::hash::blake2b::{self, Blake2b},
+    util::Progress,
+};
+use std::io;
 
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
 /// Hashing using BLAKE2b-256.
@@ -64,15 +90,543 @@ pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
     blake2b::Hasher::Blake2b256.digest(data)
 }
 
+/// The minimum size of a digest from [`digest_with_size`].
+///
+/// [`digest_with_size`]: fn.digest_with_size.html
+pub const MIN_DIGEST_SIZE: usize = 16;
+
+/// The maximum size of a digest from [`digest_with_size`].
+///
+/// [`digest_with_size`]: fn.digest_with_size.html
+pub const MAX_DIGEST_SIZE: usize = blake2b::BLAKE2B_OUTSIZE;
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hashing using BLAKE2b, with an output size of `size` bytes.
+pub fn digest_with_size(data: &[u8], size: usize) -> Result<Digest, UnknownCryptoError> {
+    if !(MIN_DIGEST_SIZE..=MAX_DIGEST_SIZE).contains(&size) {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut state = Blake2b::new(None, size)?;
+    state.update(data)?;
+    state.finalize()
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hashing using BLAKE2b-256, of all data read from `reader`.
+///
+/// The number of bytes hashed so far is reported to `progress` after each
+/// read. The total is not known in advance, and is reported as `None`.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - Reading from `reader` fails.
+/// - `progress` cancels the operation.
+pub fn digest_from_reader<R: io::Read, P: Progress>(
+    mut reader: R,
+    progress: &mut P,
+) -> Result<Digest, UnknownCryptoError> {
+    let mut hasher = blake2b::Hasher::Blake2b256.init()?;
+    let mut buf = [0u8; 16384];
+    let mut completed = 0u64;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err(UnknownCryptoError),
+        };
+
+        hasher.update(&buf[..n])?;
+        completed += n as u64;
+        if !progress.report(completed, None) {
+            return Err(UnknownCryptoError);
+        }
+    }
+
+    hasher.finalize()
+}
+
+/// The hash, and optionally MAC, of the data passed through a
+/// [`DigestReader`] or [`DigestWriter`].
+///
+/// [`DigestReader`]: struct.DigestReader.html
+/// [`DigestWriter`]: struct.DigestWriter.html
+#[derive(Debug)]
+struct TeeState {
+    hasher: Blake2b,
+    authenticator: Option<Blake2b>,
+}
+
+impl TeeState {
+    fn new(secret_key: Option<&auth::SecretKey>) -> Result<Self, UnknownCryptoError> {
+        Ok(Self {
+            hasher: blake2b::Hasher::Blake2b256.init()?,
+            authenticator: match secret_key {
+                Some(sk) => Some(auth::init(sk)?),
+                None => None,
+            },
+        })
+    }
+
+    // `io::Error::other()` requires a newer MSRV.
+    #[allow(clippy::io_other_error)]
+    fn update(&mut self, data: &[u8]) -> io::Result<()> {
+        let to_io_err = |err| io::Error::new(io::ErrorKind::Other, err);
+
+        self.hasher.update(data).map_err(to_io_err)?;
+        if let Some(authenticator) = self.authenticator.as_mut() {
+            authenticator.update(data).map_err(to_io_err)?;
+        }
+
+        Ok(())
+    }
+
+    fn digest(&self) -> Result<Digest, UnknownCryptoError> {
+        self.hasher.clone().finalize()
+    }
+
+    fn tag(&self) -> Result<auth::Tag, UnknownCryptoError> {
+        match self.authenticator.as_ref() {
+            Some(authenticator) => {
+                auth::Tag::from_slice(authenticator.clone().finalize()?.as_ref())
+            }
+            None => Err(UnknownCryptoError),
+        }
+    }
+
+    fn verify_digest(&self, expected: &Digest) -> Result<(), UnknownCryptoError> {
+        // `Digest` implements constant-time equality.
+        if &self.digest()? == expected {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    fn verify_tag(&self, expected: &auth::Tag) -> Result<(), UnknownCryptoError> {
+        // `Tag` implements constant-time equality.
+        if &self.tag()? == expected {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+}
+
+macro_rules! impl_tee_methods {
+    ($name:ident, $inner:ident) => {
+        impl<$inner> $name<$inner> {
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Wrap `inner`, hashing all data passed through it using BLAKE2b-256.
+            pub fn new(inner: $inner) -> Result<Self, UnknownCryptoError> {
+                Ok(Self {
+                    inner,
+                    state: TeeState::new(None)?,
+                })
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Wrap `inner`, hashing all data passed through it using BLAKE2b-256
+            /// and also authenticating it as [`orion::auth`] would with `secret_key`.
+            ///
+            /// [`orion::auth`]: ../auth/index.html
+            pub fn new_with_key(
+                inner: $inner,
+                secret_key: &auth::SecretKey,
+            ) -> Result<Self, UnknownCryptoError> {
+                Ok(Self {
+                    inner,
+                    state: TeeState::new(Some(secret_key))?,
+                })
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Return the BLAKE2b-256 digest of the data passed through so far.
+            pub fn digest(&self) -> Result<Digest, UnknownCryptoError> {
+                self.state.digest()
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Return the [`orion::auth`] tag of the data passed through so far.
+            ///
+            /// [`orion::auth`]: ../auth/index.html
+            pub fn tag(&self) -> Result<auth::Tag, UnknownCryptoError> {
+                self.state.tag()
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Verify in constant time that the data passed through so far has
+            /// the digest `expected`.
+            pub fn verify_digest(&self, expected: &Digest) -> Result<(), UnknownCryptoError> {
+                self.state.verify_digest(expected)
+            }
+
+            #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+            /// Verify in constant time that the data passed through so far has
+            /// the [`orion::auth`] tag `expected`.
+            ///
+            /// [`orion::auth`]: ../auth/index.html
+            pub fn verify_tag(&self, expected: &auth::Tag) -> Result<(), UnknownCryptoError> {
+                self.state.verify_tag(expected)
+            }
+
+            /// Get a reference to the wrapped value.
+            pub fn get_ref(&self) -> &$inner {
+                &self.inner
+            }
+
+            /// Get a mutable reference to the wrapped value.
+            ///
+            /// Data read from or written to it directly is not hashed.
+            pub fn get_mut(&mut self) -> &mut $inner {
+                &mut self.inner
+            }
+
+            /// Unwrap this, returning the wrapped value.
+            pub fn into_inner(self) -> $inner {
+                self.inner
+            }
+        }
+    };
+}
+
+/// A reader that hashes all data read through it, and optionally
+/// authenticates it as [`orion::auth`] would.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The [`auth::SecretKey`] given to `new_with_key()` is less than 32 bytes or
+///   greater than 64 bytes.
+/// - `tag()` or `verify_tag()` is called on a `DigestReader` created with
+///   `new()`.
+/// - The calculated digest or tag does not match the expected.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - More than 2*(2^64-1) bytes of data are hashed.
+///
+/// # Security:
+/// - The data read must not be acted upon before the digest or tag has been
+///   verified, at the end of the stream.
+///
+/// # Example:
+/// ```rust
+/// use orion::hash::{digest, DigestReader};
+/// use std::io::Read;
+///
+/// let download: &[u8] = b"Some data";
+/// let expected = digest(b"Some data")?;
+///
+/// let mut reader = DigestReader::new(download)?;
+/// let mut data = Vec::new();
+/// reader.read_to_end(&mut data).unwrap();
+///
+/// assert!(reader.verify_digest(&expected).is_ok());
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`orion::auth`]: ../auth/index.html
+/// [`auth::SecretKey`]: ../auth/struct.SecretKey.html
+#[derive(Debug)]
+pub struct DigestReader<R> {
+    inner: R,
+    state: TeeState,
+}
+
+impl_tee_methods!(DigestReader, R);
+
+impl<R: io::Read> io::Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.state.update(&buf[..read])?;
+
+        Ok(read)
+    }
+}
+
+/// A writer that hashes all data written through it, and optionally
+/// authenticates it as [`orion::auth`] would.
+///
+/// Only the bytes accepted by the wrapped writer are hashed.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The [`auth::SecretKey`] given to `new_with_key()` is less than 32 bytes or
+///   greater than 64 bytes.
+/// - `tag()` or `verify_tag()` is called on a `DigestWriter` created with
+///   `new()`.
+/// - The calculated digest or tag does not match the expected.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - More than 2*(2^64-1) bytes of data are hashed.
+///
+/// # Example:
+/// ```rust
+/// use orion::{auth, hash::DigestWriter};
+/// use std::io::{self, Write};
+///
+/// let secret_key = auth::SecretKey::default();
+/// let mut upload = DigestWriter::new_with_key(Vec::new(), &secret_key)?;
+/// io::copy(&mut &b"Some data"[..], &mut upload).unwrap();
+/// upload.flush().unwrap();
+///
+/// let tag = upload.tag()?;
+/// assert!(auth::authenticate_verify(&tag, &secret_key, upload.get_ref()).is_ok());
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`orion::auth`]: ../auth/index.html
+/// [`auth::SecretKey`]: ../auth/struct.SecretKey.html
+#[derive(Debug)]
+pub struct DigestWriter<W> {
+    inner: W,
+    state: TeeState,
+}
+
+impl_tee_methods!(DigestWriter, W);
+
+impl<W: io::Write> io::Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.state.update(&buf[..written])?;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 // Testing public functions in the module.
 #[cfg(feature = "safe_api")]
 #[cfg(test)]
 mod public {
     use super::*;
 
+    mod test_tee {
+        use super::*;
+        use std::io::{Read, Write};
+
+        const DATA: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+        /// A writer that accepts at most `limit` bytes per write.
+        struct ShortWriter {
+            written: Vec<u8>,
+            limit: usize,
+        }
+
+        impl Write for ShortWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let n = core::cmp::min(buf.len(), self.limit);
+                self.written.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn test_reader_same_as_digest() {
+            let mut reader = DigestReader::new(DATA).unwrap();
+            let mut buf = [0u8; 5];
+            let mut read = Vec::new();
+            loop {
+                let n = reader.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                read.extend_from_slice(&buf[..n]);
+                // The digest can be retrieved at any point.
+                assert_eq!(reader.digest().unwrap(), digest(&read).unwrap());
+            }
+
+            assert_eq!(read, DATA);
+            assert!(reader.verify_digest(&digest(DATA).unwrap()).is_ok());
+            assert!(reader.verify_digest(&digest(b"Bad data").unwrap()).is_err());
+            assert!(reader.into_inner().is_empty());
+        }
+
+        #[test]
+        fn test_writer_same_as_digest() {
+            let mut writer = DigestWriter::new(Vec::new()).unwrap();
+            io::copy(&mut &DATA[..], &mut writer).unwrap();
+            writer.flush().unwrap();
+
+            assert_eq!(writer.get_ref(), &DATA);
+            assert!(writer.verify_digest(&digest(DATA).unwrap()).is_ok());
+            assert!(writer.verify_digest(&digest(b"Bad data").unwrap()).is_err());
+        }
+
+        #[test]
+        fn test_writer_short_writes() {
+            let mut writer = DigestWriter::new(ShortWriter {
+                written: Vec::new(),
+                limit: 3,
+            })
+            .unwrap();
+
+            assert_eq!(writer.write(DATA).unwrap(), 3);
+            assert_eq!(writer.digest().unwrap(), digest(&DATA[..3]).unwrap());
+            writer.write_all(&DATA[3..]).unwrap();
+            assert_eq!(writer.digest().unwrap(), digest(DATA).unwrap());
+            assert_eq!(writer.into_inner().written, DATA);
+        }
+
+        #[test]
+        fn test_get_mut_not_hashed() {
+            let mut writer = DigestWriter::new(Vec::new()).unwrap();
+            writer.get_mut().extend_from_slice(DATA);
+            assert_eq!(writer.digest().unwrap(), digest(b"").unwrap());
+        }
+
+        #[test]
+        fn test_tag_same_as_auth() {
+            let secret_key = auth::SecretKey::default();
+            let expected = auth::authenticate(&secret_key, DATA).unwrap();
+
+            let mut reader = DigestReader::new_with_key(DATA, &secret_key).unwrap();
+            reader.read_to_end(&mut Vec::new()).unwrap();
+            let mut writer = DigestWriter::new_with_key(Vec::new(), &secret_key).unwrap();
+            writer.write_all(DATA).unwrap();
+
+            assert_eq!(reader.tag().unwrap(), expected);
+            assert_eq!(writer.tag().unwrap(), expected);
+            assert!(reader.verify_tag(&expected).is_ok());
+            assert!(writer.verify_tag(&expected).is_ok());
+            assert!(reader.verify_digest(&digest(DATA).unwrap()).is_ok());
+
+            let bad_tag = auth::authenticate(&auth::SecretKey::default(), DATA).unwrap();
+            assert!(reader.verify_tag(&bad_tag).is_err());
+            assert!(writer.verify_tag(&bad_tag).is_err());
+        }
+
+        #[test]
+        fn test_tag_without_key_err() {
+            let reader = DigestReader::new(DATA).unwrap();
+            let writer = DigestWriter::new(Vec::<u8>::new()).unwrap();
+            let tag = auth::authenticate(&auth::SecretKey::default(), DATA).unwrap();
+
+            assert!(reader.tag().is_err());
+            assert!(writer.tag().is_err());
+            assert!(reader.verify_tag(&tag).is_err());
+            assert!(writer.verify_tag(&tag).is_err());
+        }
+
+        #[test]
+        fn test_bad_key_len_err() {
+            let short = auth::SecretKey::generate(31).unwrap();
+            let long = auth::SecretKey::generate(65).unwrap();
+
+            assert!(DigestReader::new_with_key(DATA, &short).is_err());
+            assert!(DigestWriter::new_with_key(Vec::<u8>::new(), &long).is_err());
+        }
+
+        #[test]
+        fn test_debug_omits_key() {
+            let secret_key = auth::SecretKey::default();
+            let writer = DigestWriter::new_with_key(Vec::<u8>::new(), &secret_key).unwrap();
+            let debug = format!("{:?}", writer);
+            assert!(!debug.contains(&format!("{:?}", secret_key.unprotected_as_bytes())));
+        }
+    }
+
     #[cfg(feature = "safe_api")]
     mod test_digest {
         use super::*;
+        use crate::util::NoProgress;
+
+        #[test]
+        fn test_digest_with_size() {
+            assert_eq!(
+                digest_with_size(b"Some data", 32).unwrap(),
+                digest(b"Some data").unwrap()
+            );
+
+            let mut previous: Option<Digest> = None;
+            for size in MIN_DIGEST_SIZE..=MAX_DIGEST_SIZE {
+                let actual = digest_with_size(b"Some data", size).unwrap();
+                let mut state = Blake2b::new(None, size).unwrap();
+                state.update(b"Some data").unwrap();
+                assert_eq!(actual, state.finalize().unwrap());
+                assert_eq!(actual.len(), size);
+
+                // Not a prefix of the shorter digest.
+                if let Some(previous) = previous {
+                    assert_ne!(&actual.as_ref()[..previous.len()], previous.as_ref());
+                }
+                previous = Some(actual);
+            }
+        }
+
+        #[test]
+        fn test_digest_with_size_err() {
+            assert!(digest_with_size(b"Some data", 0).is_err());
+            assert!(digest_with_size(b"Some data", MIN_DIGEST_SIZE - 1).is_err());
+            assert!(digest_with_size(b"Some data", MAX_DIGEST_SIZE + 1).is_err());
+        }
+
+        /// A reader that fails after returning `ok_reads` chunks.
+        struct FailingReader {
+            ok_reads: usize,
+        }
+
+        impl io::Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.ok_reads == 0 {
+                    return Err(io::ErrorKind::Other.into());
+                }
+                self.ok_reads -= 1;
+                Ok(buf.len())
+            }
+        }
+
+        #[test]
+        fn test_digest_from_reader() {
+            let data = vec![0xABu8; 40000];
+            let mut reports = Vec::new();
+            let actual =
+                digest_from_reader(&data[..], &mut |completed: u64, total: Option<u64>| {
+                    reports.push((completed, total));
+                    true
+                })
+                .unwrap();
+
+            assert_eq!(actual, digest(&data).unwrap());
+            assert_eq!(
+                reports,
+                [(16384, None), (32768, None), (40000, None)].to_vec()
+            );
+        }
+
+        #[test]
+        fn test_digest_from_reader_empty() {
+            let mut reported = false;
+            let actual = digest_from_reader(&b""[..], &mut |_: u64, _: Option<u64>| {
+                reported = true;
+                true
+            })
+            .unwrap();
+
+            assert_eq!(actual, digest(b"").unwrap());
+            assert!(!reported);
+        }
+
+        #[test]
+        fn test_digest_from_reader_cancel() {
+            let data = vec![0u8; 40000];
+            let mut cancel = |completed: u64, _: Option<u64>| completed < 32768;
+            assert!(digest_from_reader(&data[..], &mut cancel).is_err());
+        }
+
+        #[test]
+        fn test_digest_from_reader_err() {
+            let reader = FailingReader { ok_reads: 2 };
+            assert!(digest_from_reader(reader, &mut NoProgress).is_err());
+        }
 
         // Proptests. Only executed when NOT testing no_std.
         #[cfg(feature = "safe_api")]
@@ -0,0 +1,660 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! JSON Web Keys.
+//!
+//! # Use case:
+//! `orion::jwk` can be used to import and export keys as JSON Web Keys (JWK),
+//! which is the format used by, for example, OpenID Connect providers and key
+//! management services.
+//!
+//! # About:
+//! - Symmetric keys are represented as JWKs with key type `oct`, as defined in
+//!   [RFC 7517] and [RFC 7518].
+//! - Ed25519 and X25519 keys are represented as JWKs with key type `OKP`, as
+//!   defined in [RFC 8037]. The public key is always present, and the secret
+//!   key (`d`) is optional.
+//! - Thumbprints are computed as defined in [RFC 7638], using SHA-256 or
+//!   SHA-512.
+//! - Other key types, such as `EC` and `RSA`, are not supported.
+//!
+//! This module is only available with the `jwk` feature.
+//!
+//! # Parameters:
+//! - `json`: The JWK to parse.
+//! - `secret_key`, `private_key`: The key to represent as a JWK.
+//! - `public_key`: The public key to represent as a JWK.
+//! - `kid`: The key ID.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `json` is not a JSON object.
+//! - The `kty` member of `json` is missing, or neither `oct` nor `OKP`.
+//! - The `crv` member of an `OKP` key is missing, or neither `Ed25519` nor
+//!   `X25519`.
+//! - The `k` member of an `oct` key or the `x` member of an `OKP` key is
+//!   missing, or not unpadded base64url of a valid key.
+//! - The `d` member of an `OKP` key is present, but not unpadded base64url of
+//!   a valid key, or not the secret key of `x`.
+//! - The `kid` member of `json` is present, but not a string.
+//!
+//! # Security:
+//! - The JSON returned by [`Jwk::unprotected_to_json()`] contains the secret
+//!   key, if any, and must be protected accordingly. Neither it nor the JSON
+//!   passed to [`Jwk::from_json()`] is zeroed by orion. Use [`Jwk::to_public()`]
+//!   to export only the public key of an `OKP` key.
+//! - The base64url decoding and encoding operations do NOT run in constant-time.
+//! - Members other than `kty`, `crv`, `k`, `x`, `d` and `kid`, such as `alg`,
+//!   `use` and `key_ops`, are ignored. They are not taken into account when
+//!   using the key.
+//!
+//! # Example:
+//! ```rust
+//! use orion::{aead, jwk::{Jwk, Key}};
+//!
+//! let jwk = Jwk::from_json(r#"{"kty":"oct","kid":"1","k":"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8"}"#)?;
+//! assert_eq!(jwk.kid(), Some("1"));
+//!
+//! let ciphertext = aead::seal(jwk.secret_key().unwrap(), b"Secret message")?;
+//!
+//! let jwk = Jwk::from_json(r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#)?;
+//! assert!(matches!(jwk.key(), Key::Ed25519(_, None)));
+//! assert!(jwk.secret_key().is_none());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [RFC 7517]: https://tools.ietf.org/html/rfc7517
+//! [RFC 7518]: https://tools.ietf.org/html/rfc7518#section-6.4
+//! [RFC 8037]: https://tools.ietf.org/html/rfc8037
+//! [RFC 7638]: https://tools.ietf.org/html/rfc7638
+//! [`Jwk::unprotected_to_json()`]: struct.Jwk.html#method.unprotected_to_json
+//! [`Jwk::from_json()`]: struct.Jwk.html#method.from_json
+//! [`Jwk::to_public()`]: struct.Jwk.html#method.to_public
+
+use super::hltypes::SecretKey;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        ecc::{ed25519, x25519},
+        hash::{sha256, sha512},
+    },
+};
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use core::convert::TryFrom;
+use serde_json::{Map, Value};
+use zeroize::Zeroize;
+
+/// The key held by a [`Jwk`].
+///
+/// [`Jwk`]: struct.Jwk.html
+#[derive(Debug, PartialEq)]
+pub enum Key {
+    /// A [`SecretKey`], of key type `oct`.
+    ///
+    /// [`SecretKey`]: ../aead/struct.SecretKey.html
+    Oct(SecretKey),
+    /// An Ed25519 public key and optionally its secret key, of key type `OKP`.
+    Ed25519(ed25519::PublicKey, Option<ed25519::SecretKey>),
+    /// An X25519 public key and optionally its private key, of key type `OKP`.
+    X25519(x25519::PublicKey, Option<x25519::PrivateKey>),
+}
+
+/// A JSON Web Key of type `oct` or `OKP`, holding a [`Key`].
+///
+/// [`Key`]: enum.Key.html
+#[derive(Debug, PartialEq)]
+pub struct Jwk {
+    key: Key,
+    kid: Option<String>,
+}
+
+impl Jwk {
+    /// Decode unpadded base64url. Returns an error if `value` is padded.
+    fn decode_b64(value: &str) -> Result<Vec<u8>, UnknownCryptoError> {
+        // decode_config() accepts padding, even when the config has none.
+        if value.contains('=') {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(decode_config(value, URL_SAFE_NO_PAD)?)
+    }
+
+    /// Decode the public member `name` of `object` with `from_slice`.
+    fn decode_public<T>(
+        object: &Map<String, Value>,
+        name: &str,
+        from_slice: fn(&[u8]) -> Result<T, UnknownCryptoError>,
+    ) -> Result<T, UnknownCryptoError> {
+        match object.get(name) {
+            Some(Value::String(value)) => from_slice(&Self::decode_b64(value)?),
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    /// Remove the secret member `name` from `object` and decode it with
+    /// `from_slice`, zeroing the intermediate copies. Returns `None` if the
+    /// member is not present.
+    fn decode_secret<T>(
+        object: &mut Map<String, Value>,
+        name: &str,
+        from_slice: fn(&[u8]) -> Result<T, UnknownCryptoError>,
+    ) -> Result<Option<T>, UnknownCryptoError> {
+        match object.remove(name) {
+            Some(Value::String(value)) => {
+                let mut value = value.into_bytes();
+                let decoded = core::str::from_utf8(&value)
+                    .map_err(|_| UnknownCryptoError)
+                    .and_then(Self::decode_b64);
+                value.iter_mut().zeroize();
+
+                let mut bytes = decoded?;
+                let secret = from_slice(&bytes);
+                bytes.iter_mut().zeroize();
+                secret.map(Some)
+            }
+            Some(_) => Err(UnknownCryptoError),
+            None => Ok(None),
+        }
+    }
+
+    /// Serialize an `OKP` key with members in lexicographic order. `kid` is
+    /// the already serialized key ID member, or empty.
+    fn encode_okp(crv: &str, x: &[u8], d: Option<&[u8]>, kid: &str) -> String {
+        let x = encode_config(x, URL_SAFE_NO_PAD);
+        match d {
+            Some(d) => {
+                let mut d = encode_config(d, URL_SAFE_NO_PAD);
+                let json = format!(
+                    r#"{{"crv":"{}","d":"{}",{}"kty":"OKP","x":"{}"}}"#,
+                    crv, d, kid, x
+                );
+                zeroize_string(&mut d);
+
+                json
+            }
+            None => format!(r#"{{"crv":"{}",{}"kty":"OKP","x":"{}"}}"#, crv, kid, x),
+        }
+    }
+
+    /// Serialize this with members in lexicographic order and no whitespace,
+    /// as required for thumbprints. If `thumbprint` is true, only the members
+    /// required for the thumbprint are included, which leaves out the key ID
+    /// and the secret key of an `OKP` key.
+    fn encode(&self, thumbprint: bool) -> String {
+        let kid = match self.kid.as_ref() {
+            Some(kid) if !thumbprint => format!(r#""kid":{},"#, Value::from(kid.as_str())),
+            _ => String::new(),
+        };
+
+        match &self.key {
+            Key::Oct(secret_key) => {
+                let mut k = encode_config(secret_key.unprotected_as_bytes(), URL_SAFE_NO_PAD);
+                let json = format!(r#"{{"k":"{}",{}"kty":"oct"}}"#, k, kid);
+                zeroize_string(&mut k);
+
+                json
+            }
+            Key::Ed25519(public_key, secret_key) => Self::encode_okp(
+                "Ed25519",
+                public_key.as_ref(),
+                secret_key
+                    .as_ref()
+                    .filter(|_| !thumbprint)
+                    .map(|sk| sk.unprotected_as_bytes()),
+                &kid,
+            ),
+            Key::X25519(public_key, private_key) => Self::encode_okp(
+                "X25519",
+                public_key.as_ref(),
+                private_key
+                    .as_ref()
+                    .filter(|_| !thumbprint)
+                    .map(|sk| sk.unprotected_as_bytes()),
+                &kid,
+            ),
+        }
+    }
+
+    /// Represent `secret_key` as a JWK of type `oct`, without a key ID.
+    pub fn from_secret_key(secret_key: &SecretKey) -> Self {
+        Self {
+            // Cannot panic, since `secret_key` is valid.
+            key: Key::Oct(SecretKey::from_slice(secret_key.unprotected_as_bytes()).unwrap()),
+            kid: None,
+        }
+    }
+
+    /// Represent the Ed25519 `public_key` as a JWK of type `OKP`, without a key
+    /// ID.
+    pub fn from_ed25519_public_key(public_key: &ed25519::PublicKey) -> Self {
+        Self {
+            key: Key::Ed25519(*public_key, None),
+            kid: None,
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Represent the Ed25519 `secret_key` and its public key as a JWK of type
+    /// `OKP`, without a key ID.
+    pub fn from_ed25519_secret_key(
+        secret_key: &ed25519::SecretKey,
+    ) -> Result<Self, UnknownCryptoError> {
+        let public_key = ed25519::PublicKey::try_from(secret_key)?;
+        // Cannot panic, since `secret_key` is valid.
+        let secret_key = ed25519::SecretKey::from_slice(secret_key.unprotected_as_bytes()).unwrap();
+
+        Ok(Self {
+            key: Key::Ed25519(public_key, Some(secret_key)),
+            kid: None,
+        })
+    }
+
+    /// Represent the X25519 `public_key` as a JWK of type `OKP`, without a key
+    /// ID.
+    pub fn from_x25519_public_key(public_key: &x25519::PublicKey) -> Self {
+        Self {
+            key: Key::X25519(*public_key, None),
+            kid: None,
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Represent the X25519 `private_key` and its public key as a JWK of type
+    /// `OKP`, without a key ID.
+    pub fn from_x25519_private_key(
+        private_key: &x25519::PrivateKey,
+    ) -> Result<Self, UnknownCryptoError> {
+        let public_key = x25519::PublicKey::try_from(private_key)?;
+        // Cannot panic, since `private_key` is valid.
+        let private_key =
+            x25519::PrivateKey::from_slice(private_key.unprotected_as_bytes()).unwrap();
+
+        Ok(Self {
+            key: Key::X25519(public_key, Some(private_key)),
+            kid: None,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Parse a JWK of type `oct` or `OKP`.
+    pub fn from_json(json: &str) -> Result<Self, UnknownCryptoError> {
+        let mut object = match serde_json::from_str(json) {
+            Ok(Value::Object(object)) => object,
+            _ => return Err(UnknownCryptoError),
+        };
+
+        let kid = match object.get("kid") {
+            Some(Value::String(kid)) => Some(kid.clone()),
+            Some(_) => return Err(UnknownCryptoError),
+            None => None,
+        };
+
+        let kty = object.get("kty").and_then(Value::as_str).map(String::from);
+        let crv = object.get("crv").and_then(Value::as_str).map(String::from);
+        let key = match (kty.as_deref(), crv.as_deref()) {
+            (Some("oct"), _) => {
+                match Self::decode_secret(&mut object, "k", SecretKey::from_slice)? {
+                    Some(secret_key) => Key::Oct(secret_key),
+                    None => return Err(UnknownCryptoError),
+                }
+            }
+            (Some("OKP"), Some("Ed25519")) => {
+                let public_key = Self::decode_public(&object, "x", ed25519::PublicKey::from_slice)?;
+                let secret_key =
+                    Self::decode_secret(&mut object, "d", ed25519::SecretKey::from_slice)?;
+                if let Some(secret_key) = secret_key.as_ref() {
+                    if ed25519::PublicKey::try_from(secret_key)? != public_key {
+                        return Err(UnknownCryptoError);
+                    }
+                }
+
+                Key::Ed25519(public_key, secret_key)
+            }
+            (Some("OKP"), Some("X25519")) => {
+                let public_key = Self::decode_public(&object, "x", x25519::PublicKey::from_slice)?;
+                let private_key =
+                    Self::decode_secret(&mut object, "d", x25519::PrivateKey::from_slice)?;
+                if let Some(private_key) = private_key.as_ref() {
+                    if x25519::PublicKey::try_from(private_key)? != public_key {
+                        return Err(UnknownCryptoError);
+                    }
+                }
+
+                Key::X25519(public_key, private_key)
+            }
+            _ => return Err(UnknownCryptoError),
+        };
+
+        Ok(Self { key, kid })
+    }
+
+    /// Serialize this as a JWK, including the secret key, if any, and key ID.
+    pub fn unprotected_to_json(&self) -> String {
+        self.encode(false)
+    }
+
+    /// Get the public key of an `OKP` JWK as a new JWK, with the same key ID.
+    /// Returns `None` for a JWK of type `oct`, which has no public key.
+    pub fn to_public(&self) -> Option<Self> {
+        let key = match &self.key {
+            Key::Oct(_) => return None,
+            Key::Ed25519(public_key, _) => Key::Ed25519(*public_key, None),
+            Key::X25519(public_key, _) => Key::X25519(*public_key, None),
+        };
+
+        Some(Self {
+            key,
+            kid: self.kid.clone(),
+        })
+    }
+
+    /// Get the key.
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// Get the secret key of a JWK of type `oct`. Returns `None` for a JWK of
+    /// type `OKP`.
+    pub fn secret_key(&self) -> Option<&SecretKey> {
+        match &self.key {
+            Key::Oct(secret_key) => Some(secret_key),
+            _ => None,
+        }
+    }
+
+    /// Get the key ID.
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Set the key ID. `None` removes it.
+    pub fn set_kid(&mut self, kid: Option<&str>) {
+        self.kid = kid.map(String::from);
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Compute the [RFC 7638] thumbprint of this JWK using SHA-256.
+    ///
+    /// The thumbprint depends only on the key, not the key ID. For an `OKP`
+    /// JWK, it depends only on the public key, as specified in [RFC 8037]. For
+    /// an `oct` JWK, it is a hash of the secret key, and is therefore only
+    /// suitable for identifying keys of 32 bytes or more.
+    ///
+    /// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+    /// [RFC 8037]: https://tools.ietf.org/html/rfc8037#section-2
+    pub fn thumbprint_sha256(&self) -> Result<sha256::Digest, UnknownCryptoError> {
+        let mut json = self.encode(true);
+        let thumbprint = sha256::Sha256::digest(json.as_bytes());
+        zeroize_string(&mut json);
+
+        thumbprint
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Compute the [RFC 7638] thumbprint of this JWK using SHA-512.
+    ///
+    /// See [`thumbprint_sha256()`] for which members it depends on.
+    ///
+    /// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+    /// [`thumbprint_sha256()`]: struct.Jwk.html#method.thumbprint_sha256
+    pub fn thumbprint_sha512(&self) -> Result<sha512::Digest, UnknownCryptoError> {
+        let mut json = self.encode(true);
+        let thumbprint = sha512::Sha512::digest(json.as_bytes());
+        zeroize_string(&mut json);
+
+        thumbprint
+    }
+}
+
+/// Zero a `String` holding secret data.
+fn zeroize_string(value: &mut String) {
+    let mut bytes = core::mem::take(value).into_bytes();
+    bytes.iter_mut().zeroize();
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    // RFC 7517, Appendix A.3.
+    const RFC_7517_OCT: &str = r#"{"kty":"oct","alg":"A128KW","k":"GawgguFyGrWKav7AX4VKUg"}"#;
+
+    // RFC 8037, Appendix A.1.
+    const RFC_8037_ED25519: &str = r#"{"kty":"OKP","crv":"Ed25519","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+
+    // RFC 8037, Appendix A.6, with the private key of Bob from RFC 7748,
+    // Section 6.1.
+    const RFC_8037_X25519: &str = r#"{"kty":"OKP","crv":"X25519","kid":"Bob","d":"XasIfmJKikt54X-Lg4AO5m87sSkmGLb9HC-LJ_-I4Os","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"}"#;
+
+    #[test]
+    fn test_from_json_rfc_7517() {
+        let jwk = Jwk::from_json(RFC_7517_OCT).unwrap();
+        let expected = [
+            0x19, 0xac, 0x20, 0x82, 0xe1, 0x72, 0x1a, 0xb5, 0x8a, 0x6a, 0xfe, 0xc0, 0x5f, 0x85,
+            0x4a, 0x52,
+        ];
+        assert_eq!(
+            jwk.secret_key().unwrap().unprotected_as_bytes(),
+            &expected[..]
+        );
+        assert_eq!(jwk.kid(), None);
+        // Other members are dropped.
+        assert_eq!(
+            jwk.unprotected_to_json(),
+            r#"{"k":"GawgguFyGrWKav7AX4VKUg","kty":"oct"}"#
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let secret_key = SecretKey::default();
+        let mut jwk = Jwk::from_secret_key(&secret_key);
+        jwk.set_kid(Some("key \"1\""));
+
+        let parsed = Jwk::from_json(&jwk.unprotected_to_json()).unwrap();
+        assert_eq!(parsed, jwk);
+        assert_eq!(parsed.secret_key(), Some(&secret_key));
+        assert_eq!(parsed.kid(), Some("key \"1\""));
+
+        jwk.set_kid(None);
+        assert_eq!(Jwk::from_json(&jwk.unprotected_to_json()).unwrap(), jwk);
+    }
+
+    #[test]
+    fn test_thumbprint() {
+        let mut jwk = Jwk::from_json(RFC_7517_OCT).unwrap();
+        let expected =
+            sha512::Sha512::digest(br#"{"k":"GawgguFyGrWKav7AX4VKUg","kty":"oct"}"#).unwrap();
+        assert_eq!(jwk.thumbprint_sha512().unwrap(), expected);
+
+        // Independent of the key ID.
+        jwk.set_kid(Some("1"));
+        assert_eq!(jwk.thumbprint_sha512().unwrap(), expected);
+
+        let other = Jwk::from_secret_key(&SecretKey::default());
+        assert_ne!(other.thumbprint_sha512().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_from_json_err() {
+        let bad = [
+            "",
+            "[]",
+            r#""oct""#,
+            r#"{"kty":"oct"}"#,
+            r#"{"k":"GawgguFyGrWKav7AX4VKUg"}"#,
+            r#"{"kty":"OKP","k":"GawgguFyGrWKav7AX4VKUg"}"#,
+            r#"{"kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+            r#"{"kty":"OKP","crv":"Ed448","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+            r#"{"kty":"OKP","crv":"Ed25519"}"#,
+            r#"{"kty":"OKP","crv":"Ed25519","x":1}"#,
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHUR"}"#,
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","d":1}"#,
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","d":""}"#,
+            // The secret key does not match the public key.
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo","d":"XasIfmJKikt54X-Lg4AO5m87sSkmGLb9HC-LJ_-I4Os"}"#,
+            r#"{"kty":"OKP","crv":"X25519","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A"}"#,
+            r#"{"kty":"oct","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+            r#"{"kty":"OCT","k":"GawgguFyGrWKav7AX4VKUg"}"#,
+            r#"{"kty":"oct","k":""}"#,
+            r#"{"kty":"oct","k":1}"#,
+            r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg","kid":1}"#,
+            // Padded.
+            r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg=="}"#,
+            // Standard base64 alphabet.
+            r#"{"kty":"oct","k":"+/+/"}"#,
+            // Non-canonical trailing bits.
+            r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUh"}"#,
+            // Trailing data.
+            r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"} {}"#,
+        ];
+
+        for json in bad.iter() {
+            assert!(Jwk::from_json(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_from_json_rfc_8037_ed25519() {
+        let jwk = Jwk::from_json(RFC_8037_ED25519).unwrap();
+        let (public_key, secret_key) = match jwk.key() {
+            Key::Ed25519(public_key, Some(secret_key)) => (public_key, secret_key),
+            _ => panic!("Expected an Ed25519 secret key"),
+        };
+        assert_eq!(
+            &ed25519::PublicKey::try_from(secret_key).unwrap(),
+            public_key
+        );
+        assert_eq!(
+            public_key.as_ref(),
+            &decode_config(
+                "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+                URL_SAFE_NO_PAD
+            )
+            .unwrap()[..]
+        );
+        assert!(jwk.secret_key().is_none());
+        assert_eq!(
+            jwk.unprotected_to_json(),
+            r#"{"crv":"Ed25519","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+
+        let public = jwk.to_public().unwrap();
+        assert_eq!(public.key(), &Key::Ed25519(*public_key, None));
+        assert_eq!(
+            public.unprotected_to_json(),
+            r#"{"crv":"Ed25519","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+        assert_eq!(
+            Jwk::from_json(&public.unprotected_to_json()).unwrap(),
+            public
+        );
+    }
+
+    #[test]
+    fn test_from_json_rfc_8037_x25519() {
+        let jwk = Jwk::from_json(RFC_8037_X25519).unwrap();
+        let private_key = match jwk.key() {
+            Key::X25519(_, Some(private_key)) => private_key,
+            _ => panic!("Expected an X25519 private key"),
+        };
+        assert_eq!(
+            Jwk::from_x25519_private_key(private_key).unwrap().key(),
+            jwk.key()
+        );
+        assert_eq!(jwk.kid(), Some("Bob"));
+        assert_eq!(
+            jwk.unprotected_to_json(),
+            r#"{"crv":"X25519","d":"XasIfmJKikt54X-Lg4AO5m87sSkmGLb9HC-LJ_-I4Os","kid":"Bob","kty":"OKP","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"}"#
+        );
+        assert_eq!(
+            jwk.to_public().unwrap().unprotected_to_json(),
+            r#"{"crv":"X25519","kid":"Bob","kty":"OKP","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"}"#
+        );
+        assert!(Jwk::from_json(RFC_7517_OCT).unwrap().to_public().is_none());
+    }
+
+    #[test]
+    fn test_okp_roundtrip() {
+        let secret_key = ed25519::SecretKey::generate();
+        let mut jwk = Jwk::from_ed25519_secret_key(&secret_key).unwrap();
+        jwk.set_kid(Some("1"));
+        assert_eq!(Jwk::from_json(&jwk.unprotected_to_json()).unwrap(), jwk);
+
+        let public_key = ed25519::PublicKey::try_from(&secret_key).unwrap();
+        let jwk = Jwk::from_ed25519_public_key(&public_key);
+        assert_eq!(jwk.key(), &Key::Ed25519(public_key, None));
+        assert_eq!(Jwk::from_json(&jwk.unprotected_to_json()).unwrap(), jwk);
+
+        let private_key = x25519::PrivateKey::generate();
+        let jwk = Jwk::from_x25519_private_key(&private_key).unwrap();
+        assert_eq!(Jwk::from_json(&jwk.unprotected_to_json()).unwrap(), jwk);
+
+        let public_key = x25519::PublicKey::try_from(&private_key).unwrap();
+        let jwk = Jwk::from_x25519_public_key(&public_key);
+        assert_eq!(Jwk::from_json(&jwk.unprotected_to_json()).unwrap(), jwk);
+    }
+
+    #[test]
+    fn test_thumbprint_sha256_rfc_8037() {
+        // RFC 8037, Appendix A.3.
+        let expected = decode_config(
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k",
+            URL_SAFE_NO_PAD,
+        )
+        .unwrap();
+
+        let mut jwk = Jwk::from_json(RFC_8037_ED25519).unwrap();
+        assert_eq!(jwk.thumbprint_sha256().unwrap(), &expected[..]);
+        // Independent of the key ID and the secret key.
+        jwk.set_kid(Some("1"));
+        assert_eq!(jwk.thumbprint_sha256().unwrap(), &expected[..]);
+        assert_eq!(
+            jwk.to_public().unwrap().thumbprint_sha256().unwrap(),
+            &expected[..]
+        );
+        assert_eq!(
+            jwk.thumbprint_sha512().unwrap(),
+            sha512::Sha512::digest(
+                br#"{"crv":"Ed25519","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+            )
+            .unwrap()
+        );
+
+        let oct = Jwk::from_json(RFC_7517_OCT).unwrap();
+        assert_eq!(
+            oct.thumbprint_sha256().unwrap(),
+            sha256::Sha256::digest(br#"{"k":"GawgguFyGrWKav7AX4VKUg","kty":"oct"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_debug_impl() {
+        let jwk = Jwk::from_json(RFC_7517_OCT).unwrap();
+        assert!(!format!("{:?}", jwk).contains("GawgguFyGrWKav7AX4VKUg"));
+        assert!(!format!("{:?}", jwk).contains("25, 172"));
+
+        let jwk = Jwk::from_json(RFC_8037_ED25519).unwrap();
+        // The first bytes of the secret key.
+        assert!(!format!("{:?}", jwk).contains("157, 97, 177"));
+    }
+}
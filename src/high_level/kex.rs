@@ -0,0 +1,579 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Key exchange and anonymous public-key encryption.
+//!
+//! # Use case:
+//! `orion::kex` can be used by a client and a server to agree on session keys
+//! over an insecure channel, and to encrypt a message to the holder of a
+//! private key, knowing only the matching public key.
+//!
+//! An example of this could be a client and a server that exchange public keys
+//! at the start of a connection and then encrypt their messages with
+//! [`orion::aead`], or a client submitting a report to a server, anonymously,
+//! with a sealed box.
+//!
+//! # About:
+//! - Key exchange is compatible with libsodium's `crypto_kx`. The client and
+//!   the server each hold an X25519 key pair, in a [`ClientSession`] and a
+//!   [`ServerSession`]. After exchanging public keys, both compute the
+//!   BLAKE2b-512 digest of the X25519 shared secret, the client's public key
+//!   and the server's public key. The first 32 bytes are the key the client
+//!   receives with and the server transmits with, the last 32 bytes the other
+//!   way around.
+//! - The [`SessionKeys`] of the client and the server match:
+//!   [`SessionKeys::receiving()`] of one is [`SessionKeys::transport()`] of
+//!   the other. Both are [`SecretKey`]s that can be used with [`orion::aead`].
+//! - [`seal()`] generates an ephemeral X25519 key pair for every message and
+//!   encrypts the message to the recipient with XChaCha20 and Poly1305, under a
+//!   key agreed between the ephemeral private key and the recipient's public
+//!   key. The nonce is the BLAKE2b-192 digest of the ephemeral public key
+//!   followed by the recipient's public key, so it does not need to be sent.
+//! - A sealed box is the ephemeral public key, followed by the Poly1305 tag and
+//!   the ciphertext. It is [`SEAL_OVERHEAD`] bytes longer than the message.
+//! - Sealed boxes are compatible with those of libsodium's
+//!   `crypto_box_curve25519xchacha20poly1305_seal()` and
+//!   `crypto_box_curve25519xchacha20poly1305_seal_open()`. They are not
+//!   compatible with `crypto_box_seal()`, which uses XSalsa20 instead of
+//!   XChaCha20.
+//!
+//! # Parameters:
+//! - `private_key`: The private key of this party, or of the recipient.
+//! - `public_key`: The public key of the recipient.
+//! - `server_public_key`: The public key of the server.
+//! - `client_public_key`: The public key of the client.
+//! - `plaintext`: The data to be encrypted.
+//! - `sealed`: The sealed box to be opened.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `public_key`, `server_public_key` or `client_public_key` is a point of
+//!   small order.
+//! - `sealed` is less than [`SEAL_OVERHEAD`] bytes.
+//! - `sealed` was not sealed to the public key of `private_key`, or has been
+//!   modified.
+//! - `plaintext` is longer than `(2^32 * 64) - 32` bytes.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - Key exchange does not authenticate the other party. The public keys must
+//!   be exchanged over a channel that is authenticated some other way, or be
+//!   known in advance, such as a server key that is distributed with the
+//!   client.
+//! - The [`SessionKeys`] only depend on the two key pairs. A client should use
+//!   a new [`ClientSession`] for every session, so that keys are not re-used
+//!   across sessions.
+//! - A sealed box is not authenticated to a sender. Anyone who knows the
+//!   recipient's public key can seal a message to it, and the recipient cannot
+//!   tell who did. If the recipient needs to know who sent the message, sign
+//!   it with [`orion::sign`] before sealing.
+//! - The sender cannot decrypt the sealed box after sealing it, because the
+//!   ephemeral private key is discarded.
+//!
+//! # Example:
+//! ```rust
+//! use orion::{aead, kex::{self, ClientSession, PrivateKey, PublicKey, ServerSession}};
+//! use core::convert::TryFrom;
+//!
+//! // Key exchange, where the client and server send each other their public keys.
+//! let client = ClientSession::new()?;
+//! let server = ServerSession::new()?;
+//!
+//! let client_keys = client.establish_with_server(server.public_key())?;
+//! let server_keys = server.establish_with_client(client.public_key())?;
+//!
+//! let ciphertext = aead::seal(client_keys.transport(), b"Hello server")?;
+//! assert_eq!(aead::open(server_keys.receiving(), &ciphertext)?, b"Hello server");
+//!
+//! // A sealed box, to a recipient that has published its public key.
+//! let private_key = PrivateKey::generate();
+//! let public_key = PublicKey::try_from(&private_key)?;
+//!
+//! let sealed = kex::seal(&public_key, b"Secret message")?;
+//! assert_eq!(kex::open_sealed(&private_key, &sealed)?, b"Secret message");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`orion::aead`]: ../aead/index.html
+//! [`ClientSession`]: struct.ClientSession.html
+//! [`ServerSession`]: struct.ServerSession.html
+//! [`SessionKeys`]: struct.SessionKeys.html
+//! [`SessionKeys::receiving()`]: struct.SessionKeys.html#method.receiving
+//! [`SessionKeys::transport()`]: struct.SessionKeys.html#method.transport
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`seal()`]: fn.seal.html
+//! [`SEAL_OVERHEAD`]: constant.SEAL_OVERHEAD.html
+//! [`orion::sign`]: ../sign/index.html
+
+pub use super::hltypes::SecretKey;
+pub use crate::hazardous::ecc::x25519::{PrivateKey, PublicKey};
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        ecc::x25519::{self, PUBLIC_KEY_SIZE},
+        hash::blake2b::Blake2b,
+        mac::poly1305::{OneTimeKey, Poly1305, Tag, POLY1305_KEYSIZE, POLY1305_OUTSIZE},
+        stream::{
+            chacha20,
+            xchacha20::{self, Nonce, XCHACHA_NONCESIZE},
+        },
+    },
+};
+use core::convert::TryFrom;
+use zeroize::Zeroize;
+
+/// The size of each of the [`SessionKeys`].
+///
+/// [`SessionKeys`]: struct.SessionKeys.html
+const SESSION_KEY_SIZE: usize = 32;
+
+#[derive(Debug)]
+/// The pair of keys that a [`ClientSession`] or [`ServerSession`] establishes.
+///
+/// [`ClientSession`]: struct.ClientSession.html
+/// [`ServerSession`]: struct.ServerSession.html
+pub struct SessionKeys {
+    receiving: SecretKey,
+    transport: SecretKey,
+}
+
+impl SessionKeys {
+    /// The key to decrypt the messages received from the other party with.
+    pub fn receiving(&self) -> &SecretKey {
+        &self.receiving
+    }
+
+    /// The key to encrypt the messages sent to the other party with.
+    pub fn transport(&self) -> &SecretKey {
+        &self.transport
+    }
+}
+
+/// Compute BLAKE2b-512(X25519(`private_key`, `peer_public_key`) ||
+/// `client_public_key` || `server_public_key`), as in libsodium's `crypto_kx`.
+/// The first half is the client's receiving key, and the second half the
+/// client's transport key.
+fn session_keys(
+    private_key: &PrivateKey,
+    peer_public_key: &PublicKey,
+    client_public_key: &PublicKey,
+    server_public_key: &PublicKey,
+    is_client: bool,
+) -> Result<SessionKeys, UnknownCryptoError> {
+    let shared = x25519::key_agreement(private_key, peer_public_key)?;
+    let mut state = Blake2b::new(None, 2 * SESSION_KEY_SIZE)?;
+    state.update(shared.unprotected_as_bytes())?;
+    state.update(client_public_key.as_ref())?;
+    state.update(server_public_key.as_ref())?;
+
+    let mut keys = [0u8; 2 * SESSION_KEY_SIZE];
+    state.finalize_into(&mut keys)?;
+    let (first, second) = keys.split_at(SESSION_KEY_SIZE);
+    let (receiving, transport) = if is_client {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let session_keys = SecretKey::from_slice(receiving).and_then(|receiving| {
+        Ok(SessionKeys {
+            receiving,
+            transport: SecretKey::from_slice(transport)?,
+        })
+    });
+    keys.zeroize();
+
+    session_keys
+}
+
+#[derive(Debug)]
+/// The key pair of the client in a key exchange.
+pub struct ClientSession {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+}
+
+impl ClientSession {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a `ClientSession` with a new, random key pair.
+    pub fn new() -> Result<Self, UnknownCryptoError> {
+        Self::from_private_key(PrivateKey::generate())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a `ClientSession` with the key pair of `private_key`.
+    pub fn from_private_key(private_key: PrivateKey) -> Result<Self, UnknownCryptoError> {
+        let public_key = PublicKey::try_from(&private_key)?;
+
+        Ok(Self {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Return the public key of the client, to be sent to the server.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Establish the session keys of the client with the server of
+    /// `server_public_key`.
+    pub fn establish_with_server(
+        &self,
+        server_public_key: &PublicKey,
+    ) -> Result<SessionKeys, UnknownCryptoError> {
+        session_keys(
+            &self.private_key,
+            server_public_key,
+            &self.public_key,
+            server_public_key,
+            true,
+        )
+    }
+}
+
+#[derive(Debug)]
+/// The key pair of the server in a key exchange.
+pub struct ServerSession {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+}
+
+impl ServerSession {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a `ServerSession` with a new, random key pair.
+    pub fn new() -> Result<Self, UnknownCryptoError> {
+        Self::from_private_key(PrivateKey::generate())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a `ServerSession` with the key pair of `private_key`.
+    pub fn from_private_key(private_key: PrivateKey) -> Result<Self, UnknownCryptoError> {
+        let public_key = PublicKey::try_from(&private_key)?;
+
+        Ok(Self {
+            private_key,
+            public_key,
+        })
+    }
+
+    /// Return the public key of the server, to be sent to the client.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Establish the session keys of the server with the client of
+    /// `client_public_key`.
+    pub fn establish_with_client(
+        &self,
+        client_public_key: &PublicKey,
+    ) -> Result<SessionKeys, UnknownCryptoError> {
+        session_keys(
+            &self.private_key,
+            client_public_key,
+            client_public_key,
+            &self.public_key,
+            false,
+        )
+    }
+}
+
+/// The number of bytes a sealed box adds to the plaintext: the ephemeral
+/// public key and the Poly1305 tag.
+pub const SEAL_OVERHEAD: usize = PUBLIC_KEY_SIZE + POLY1305_OUTSIZE;
+
+/// The key shared between `private_key` and `public_key`, which is the
+/// HChaCha20 of the X25519 shared secret with an all-zero nonce, as in
+/// libsodium's `crypto_box_curve25519xchacha20poly1305_beforenm()`.
+fn box_key(
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+) -> Result<chacha20::SecretKey, UnknownCryptoError> {
+    let shared = x25519::key_agreement(private_key, public_key)?;
+    let shared_key = chacha20::SecretKey::from_slice(shared.unprotected_as_bytes())?;
+    let (key, _) = xchacha20::subkey_and_nonce(&shared_key, &Nonce::from([0u8; XCHACHA_NONCESIZE]));
+
+    Ok(key)
+}
+
+/// The nonce of a sealed box, BLAKE2b-192(`ephemeral_public` || `recipient_public`).
+fn seal_nonce(
+    ephemeral_public: &PublicKey,
+    recipient_public: &PublicKey,
+) -> Result<Nonce, UnknownCryptoError> {
+    let mut state = Blake2b::new(None, XCHACHA_NONCESIZE)?;
+    state.update(ephemeral_public.as_ref())?;
+    state.update(recipient_public.as_ref())?;
+
+    Nonce::from_slice(state.finalize()?.as_ref())
+}
+
+/// XOR `bytes` with the XChaCha20 keystream of `key` and `nonce`, where
+/// `bytes` begins with `POLY1305_KEYSIZE` zero bytes that become the one-time
+/// key, as in libsodium's `crypto_secretbox_xchacha20poly1305`. libsodium
+/// uses a 64-bit block counter, which is the same as the IETF variant with a
+/// zero prefix on the nonce until the 32-bit counter overflows, which is an
+/// error here.
+fn secretbox_xor(
+    key: &chacha20::SecretKey,
+    nonce: &Nonce,
+    bytes: &mut [u8],
+) -> Result<OneTimeKey, UnknownCryptoError> {
+    debug_assert!(bytes[..POLY1305_KEYSIZE].iter().all(|byte| *byte == 0));
+
+    let (subkey, ietf_nonce) = xchacha20::subkey_and_nonce(key, nonce);
+    chacha20::encrypt_in_place(&subkey, &ietf_nonce, 0, bytes)?;
+    let one_time_key = OneTimeKey::from_slice(&bytes[..POLY1305_KEYSIZE]);
+    bytes[..POLY1305_KEYSIZE].zeroize();
+
+    one_time_key
+}
+
+/// Seal `plaintext` to `public_key` with the ephemeral key pair of
+/// `ephemeral_private`.
+fn seal_with_ephemeral(
+    ephemeral_private: &PrivateKey,
+    public_key: &PublicKey,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    let ephemeral_public = PublicKey::try_from(ephemeral_private)?;
+    let key = box_key(ephemeral_private, public_key)?;
+    let nonce = seal_nonce(&ephemeral_public, public_key)?;
+
+    // The one-time key is taken from the bytes before the ciphertext, that
+    // are then overwritten by the ephemeral public key and the tag.
+    let mut sealed = vec![0u8; SEAL_OVERHEAD + plaintext.len()];
+    sealed[SEAL_OVERHEAD..].copy_from_slice(plaintext);
+    let one_time_key = secretbox_xor(
+        &key,
+        &nonce,
+        &mut sealed[SEAL_OVERHEAD - POLY1305_KEYSIZE..],
+    )?;
+    let tag = Poly1305::poly1305(&one_time_key, &sealed[SEAL_OVERHEAD..])?;
+
+    sealed[..PUBLIC_KEY_SIZE].copy_from_slice(ephemeral_public.as_ref());
+    sealed[PUBLIC_KEY_SIZE..SEAL_OVERHEAD].copy_from_slice(tag.unprotected_as_bytes());
+
+    Ok(sealed)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Seal `plaintext` to the holder of the private key of `public_key`.
+pub fn seal(public_key: &PublicKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    seal_with_ephemeral(&PrivateKey::generate(), public_key, plaintext)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Open a box that was sealed to the public key of `private_key`.
+pub fn open_sealed(private_key: &PrivateKey, sealed: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+    if sealed.len() < SEAL_OVERHEAD {
+        return Err(UnknownCryptoError);
+    }
+
+    let ephemeral_public = PublicKey::from_slice(&sealed[..PUBLIC_KEY_SIZE])?;
+    let expected_tag = Tag::from_slice(&sealed[PUBLIC_KEY_SIZE..SEAL_OVERHEAD])?;
+    let ciphertext = &sealed[SEAL_OVERHEAD..];
+
+    let public_key = PublicKey::try_from(private_key)?;
+    let key = box_key(private_key, &ephemeral_public)?;
+    let nonce = seal_nonce(&ephemeral_public, &public_key)?;
+
+    let mut plaintext = vec![0u8; POLY1305_KEYSIZE + ciphertext.len()];
+    plaintext[POLY1305_KEYSIZE..].copy_from_slice(ciphertext);
+    let one_time_key = secretbox_xor(&key, &nonce, &mut plaintext)?;
+    if Poly1305::verify(&expected_tag, &one_time_key, ciphertext).is_err() {
+        plaintext.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+    plaintext.drain(..POLY1305_KEYSIZE);
+
+    Ok(plaintext)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    fn key_pair() -> (PrivateKey, PublicKey) {
+        let private_key = PrivateKey::generate();
+        let public_key = PublicKey::try_from(&private_key).unwrap();
+        (private_key, public_key)
+    }
+
+    #[test]
+    fn test_session_keys_match() {
+        let client = ClientSession::new().unwrap();
+        let server = ServerSession::new().unwrap();
+
+        let client_keys = client.establish_with_server(server.public_key()).unwrap();
+        let server_keys = server.establish_with_client(client.public_key()).unwrap();
+        assert_eq!(client_keys.receiving(), server_keys.transport());
+        assert_eq!(client_keys.transport(), server_keys.receiving());
+        assert_ne!(client_keys.receiving(), client_keys.transport());
+        assert_eq!(client_keys.receiving().len(), SESSION_KEY_SIZE);
+    }
+
+    #[test]
+    fn test_session_keys_differ_per_session() {
+        let server = ServerSession::new().unwrap();
+        let first = ClientSession::new()
+            .unwrap()
+            .establish_with_server(server.public_key())
+            .unwrap();
+        let second = ClientSession::new()
+            .unwrap()
+            .establish_with_server(server.public_key())
+            .unwrap();
+        assert_ne!(first.receiving(), second.receiving());
+        assert_ne!(first.transport(), second.transport());
+    }
+
+    #[test]
+    fn test_session_keys_libsodium_compatible() {
+        // The first vector of tests/test_data/kx_generated.json, from
+        // libsodium's crypto_kx.
+        let client = ClientSession::from_private_key(
+            PrivateKey::from_slice(
+                &hex::decode("d0f36d5582cfc24173a9c006878eb92e4628603073f73a7691bbf4e14e0d1989")
+                    .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let server = ServerSession::from_private_key(
+            PrivateKey::from_slice(
+                &hex::decode("e8faf22fcfa7aff12cdba04ce1b662b72e6f32cdbc8662e4a4432ae94470bd96")
+                    .unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let client_keys = client.establish_with_server(server.public_key()).unwrap();
+        assert_eq!(
+            client_keys.receiving().unprotected_as_bytes(),
+            &hex::decode("2cb3d7ae695009df035974826ad921826ffa258a1d33ce0b0c69308e5c3cdbe9")
+                .unwrap()[..]
+        );
+        assert_eq!(
+            client_keys.transport().unprotected_as_bytes(),
+            &hex::decode("4465e9049cbaa000cd3b1a5fad525d826eabbf7cda948112663cfad4fd0affad")
+                .unwrap()[..]
+        );
+    }
+
+    #[test]
+    fn test_session_small_order_err() {
+        let small_order = PublicKey::from_slice(&[0u8; PUBLIC_KEY_SIZE]).unwrap();
+        let client = ClientSession::new().unwrap();
+        let server = ServerSession::new().unwrap();
+        assert!(client.establish_with_server(&small_order).is_err());
+        assert!(server.establish_with_client(&small_order).is_err());
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let (private_key, public_key) = key_pair();
+
+        for len in [0usize, 1, 31, 32, 33, 64, 65, 1000].iter() {
+            let plaintext = vec![0x2a; *len];
+            let sealed = seal(&public_key, &plaintext).unwrap();
+            assert_eq!(sealed.len(), SEAL_OVERHEAD + len);
+            assert_eq!(open_sealed(&private_key, &sealed).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_seal_is_randomized() {
+        let (_, public_key) = key_pair();
+        let first = seal(&public_key, b"message").unwrap();
+        let second = seal(&public_key, b"message").unwrap();
+        assert_ne!(first[..PUBLIC_KEY_SIZE], second[..PUBLIC_KEY_SIZE]);
+        assert_ne!(first[SEAL_OVERHEAD..], second[SEAL_OVERHEAD..]);
+    }
+
+    #[test]
+    fn test_libsodium_compatible() {
+        // The first vectors of tests/test_data/sealed_box_generated.json,
+        // sealed with libsodium.
+        let private_key = PrivateKey::from_slice(
+            &hex::decode("5046adc1dba838867b2bbbfdd0c3423e58b57970b5267a90f57960924a87f196")
+                .unwrap(),
+        )
+        .unwrap();
+        let public_key = PublicKey::try_from(&private_key).unwrap();
+        let ephemeral = PrivateKey::from_slice(
+            &hex::decode("5ce86efb75fa4e2c410f46e16de9f6acae1a1703528651b69bc176c088bef3ee")
+                .unwrap(),
+        )
+        .unwrap();
+        let expected = hex::decode(
+            "1b1b58dd50ea14b60da17b790cd02754d970c9bab864ebb3c0f3016fe51d3f57\
+             ef923f10d9e0d1c2373b3fe9b1eb9b43",
+        )
+        .unwrap();
+
+        assert_eq!(
+            seal_with_ephemeral(&ephemeral, &public_key, b"").unwrap(),
+            expected
+        );
+        assert!(open_sealed(&private_key, &expected).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_open_modified_err() {
+        let (private_key, public_key) = key_pair();
+        let sealed = seal(&public_key, b"Secret message").unwrap();
+
+        for idx in 0..sealed.len() {
+            let mut modified = sealed.clone();
+            modified[idx] ^= 1;
+            assert!(open_sealed(&private_key, &modified).is_err());
+        }
+        assert!(open_sealed(&private_key, &sealed[..sealed.len() - 1]).is_err());
+        assert!(open_sealed(&private_key, &sealed[..SEAL_OVERHEAD - 1]).is_err());
+        assert!(open_sealed(&private_key, &[]).is_err());
+    }
+
+    #[test]
+    fn test_open_wrong_key_err() {
+        let (_, public_key) = key_pair();
+        let (other_private, _) = key_pair();
+        let sealed = seal(&public_key, b"Secret message").unwrap();
+        assert!(open_sealed(&other_private, &sealed).is_err());
+    }
+
+    #[test]
+    fn test_small_order_public_key_err() {
+        let (private_key, _) = key_pair();
+        let small_order = PublicKey::from_slice(&[0u8; PUBLIC_KEY_SIZE]).unwrap();
+        assert!(seal(&small_order, b"message").is_err());
+
+        let mut sealed = vec![0u8; SEAL_OVERHEAD + 7];
+        sealed[..PUBLIC_KEY_SIZE].copy_from_slice(small_order.as_ref());
+        assert!(open_sealed(&private_key, &sealed).is_err());
+    }
+}
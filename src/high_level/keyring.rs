@@ -0,0 +1,647 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Authenticated secret-key encryption with a set of versioned keys.
+//!
+//! # Use case:
+//! `orion::keyring` can be used to encrypt data with keys that are rotated
+//! over time, while still being able to decrypt data that was encrypted with
+//! an older key.
+//!
+//! An example of this could be encrypting records in a database, where the
+//! key should be rotated regularly without having to re-encrypt all existing
+//! records at once.
+//!
+//! # About:
+//! - A [`KeyRing`] holds a number of keys, each identified by a `u32` key ID.
+//!   One of these keys is the primary key.
+//! - [`KeyRing::seal()`] always encrypts with the primary key.
+//!   [`KeyRing::open()`] decrypts with whichever key the ciphertext indicates.
+//! - Returns a vector where the first 5 bytes are a header, consisting of a
+//!   version byte followed by the big-endian key ID. The next 24 bytes are the
+//!   nonce and the rest is the authenticated ciphertext with the last 16 bytes
+//!   being the corresponding Poly1305 tag.
+//! - Uses XChaCha20Poly1305 with the header as additional data, so the key ID
+//!   cannot be changed without the tag verification failing.
+//! - The number of messages and bytes encrypted with each key is counted, and
+//!   can be inspected with [`KeyRing::usage()`]. Limits on these can be set
+//!   with [`KeyRing::set_usage_limits()`]. Once sealing another message would
+//!   exceed a limit for the primary key, [`KeyRing::seal()`] returns an error
+//!   and [`KeyRing::needs_rotation()`] returns `true` until a new primary key
+//!   is chosen.
+//!
+//! # Parameters:
+//! - `key_id`: The ID of a key in the key ring.
+//! - `secret_key`: A key to add to the key ring.
+//! - `plaintext`: The data to be encrypted.
+//! - `ciphertext`: The data to be decrypted, as returned by [`KeyRing::seal()`].
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes.
+//! - `key_id` is already in the key ring when calling [`KeyRing::add()`].
+//! - `key_id` is not in the key ring when calling [`KeyRing::set_primary()`]
+//!   or [`KeyRing::remove()`].
+//! - `key_id` is the primary key when calling [`KeyRing::remove()`].
+//! - The key ring already holds the key ID `u32::MAX` when calling [`KeyRing::rotate()`].
+//! - The `plaintext` is empty.
+//! - Sealing `plaintext` would exceed the usage limits of the primary key.
+//! - `ciphertext` is less than 46 bytes ([`HEADER_SIZE`] + [`XCHACHA_NONCESIZE`]
+//!   \+ [`POLY1305_OUTSIZE`] + 1).
+//! - The header of `ciphertext` has an unknown version or names a key that is
+//!   not in the key ring.
+//! - The received tag does not match the calculated tag when calling [`KeyRing::open()`].
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - The key ID is not secret. It is stored in plaintext in the header of every
+//!   ciphertext.
+//! - Removing a key from the key ring means that no ciphertext encrypted with
+//!   it can be decrypted anymore. Only remove a key once all data encrypted
+//!   with it has been re-encrypted with a newer key.
+//! - To securely generate a strong key, use [`SecretKey::default()`] or
+//!   [`KeyRing::rotate()`].
+//! - The length of the `plaintext` is not hidden, only its contents.
+//! - No usage limits are set by default. XChaCha20Poly1305 uses random 192-bit
+//!   nonces, so the probability of a nonce collision stays below 2^-32 for the
+//!   first 2^80 messages encrypted with a key, which cannot be reached with a
+//!   `u64` counter. The 2^38 byte limit on a single message is enforced by
+//!   XChaCha20Poly1305 itself. Applications with stricter requirements, such
+//!   as a policy of rotating keys after a fixed amount of data, should set
+//!   their own limits.
+//! - Failed decryptions are not counted.
+//!
+//! # Example:
+//! ```rust
+//! use orion::keyring::{KeyRing, SecretKey};
+//!
+//! let mut keyring = KeyRing::new(1, SecretKey::default())?;
+//! let old_ciphertext = keyring.seal(b"Secret message")?;
+//!
+//! // Generate a new primary key with ID 2.
+//! let new_key_id = keyring.rotate()?;
+//! assert_eq!(new_key_id, 2);
+//! let new_ciphertext = keyring.seal(b"Secret message")?;
+//!
+//! // Both ciphertexts can still be decrypted.
+//! assert_eq!(keyring.open(&old_ciphertext)?, b"Secret message");
+//! assert_eq!(keyring.open(&new_ciphertext)?, b"Secret message");
+//!
+//! // Require a new key after 2^32 messages or 2^40 bytes.
+//! keyring.set_usage_limits(1 << 32, 1 << 40);
+//! assert_eq!(keyring.usage(2).unwrap().messages(), 1);
+//! assert!(!keyring.needs_rotation());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`KeyRing`]: struct.KeyRing.html
+//! [`KeyRing::seal()`]: struct.KeyRing.html#method.seal
+//! [`KeyRing::open()`]: struct.KeyRing.html#method.open
+//! [`KeyRing::add()`]: struct.KeyRing.html#method.add
+//! [`KeyRing::set_primary()`]: struct.KeyRing.html#method.set_primary
+//! [`KeyRing::remove()`]: struct.KeyRing.html#method.remove
+//! [`KeyRing::rotate()`]: struct.KeyRing.html#method.rotate
+//! [`KeyRing::usage()`]: struct.KeyRing.html#method.usage
+//! [`KeyRing::set_usage_limits()`]: struct.KeyRing.html#method.set_usage_limits
+//! [`KeyRing::needs_rotation()`]: struct.KeyRing.html#method.needs_rotation
+//! [`HEADER_SIZE`]: constant.HEADER_SIZE.html
+//! [`POLY1305_OUTSIZE`]: ../hazardous/mac/poly1305/constant.POLY1305_OUTSIZE.html
+//! [`XCHACHA_NONCESIZE`]: ../hazardous/stream/xchacha20/constant.XCHACHA_NONCESIZE.html
+//! [`SecretKey::default()`]: struct.SecretKey.html
+
+pub use super::hltypes::SecretKey;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead,
+        mac::poly1305::POLY1305_OUTSIZE,
+        stream::{
+            chacha20::{self, CHACHA_KEYSIZE},
+            xchacha20::{Nonce, XCHACHA_NONCESIZE},
+        },
+    },
+};
+use core::fmt;
+
+/// The size of the header prepended to each ciphertext.
+pub const HEADER_SIZE: usize = 5;
+
+/// The version byte of the current header format.
+const VERSION: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The amount of data that has been encrypted with a key.
+pub struct KeyUsage {
+    messages: u64,
+    bytes: u64,
+}
+
+impl KeyUsage {
+    /// Return the number of messages encrypted.
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// Return the number of plaintext bytes encrypted.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+struct KeyEntry {
+    id: u32,
+    key: SecretKey,
+    usage: KeyUsage,
+}
+
+impl KeyEntry {
+    fn new(id: u32, key: SecretKey) -> Self {
+        Self {
+            id,
+            key,
+            usage: KeyUsage::default(),
+        }
+    }
+}
+
+/// A set of versioned keys, one of which is the primary key.
+pub struct KeyRing {
+    keys: Vec<KeyEntry>,
+    primary: u32,
+    limits: KeyUsage,
+}
+
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key_ids: Vec<u32> = self.keys.iter().map(|entry| entry.id).collect();
+        f.debug_struct("KeyRing")
+            .field("key_ids", &key_ids)
+            .field("primary", &self.primary)
+            .finish()
+    }
+}
+
+impl KeyRing {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a key ring with `secret_key` as its primary key.
+    pub fn new(key_id: u32, secret_key: SecretKey) -> Result<Self, UnknownCryptoError> {
+        if secret_key.len() != CHACHA_KEYSIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            keys: vec![KeyEntry::new(key_id, secret_key)],
+            primary: key_id,
+            limits: KeyUsage {
+                messages: u64::MAX,
+                bytes: u64::MAX,
+            },
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Add `secret_key` to the key ring. The primary key is not changed.
+    pub fn add(&mut self, key_id: u32, secret_key: SecretKey) -> Result<(), UnknownCryptoError> {
+        if secret_key.len() != CHACHA_KEYSIZE || self.contains(key_id) {
+            return Err(UnknownCryptoError);
+        }
+
+        self.keys.push(KeyEntry::new(key_id, secret_key));
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Make the key with `key_id` the primary key.
+    pub fn set_primary(&mut self, key_id: u32) -> Result<(), UnknownCryptoError> {
+        if !self.contains(key_id) {
+            return Err(UnknownCryptoError);
+        }
+
+        self.primary = key_id;
+        Ok(())
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Remove the key with `key_id`, which must not be the primary key.
+    pub fn remove(&mut self, key_id: u32) -> Result<(), UnknownCryptoError> {
+        if key_id == self.primary {
+            return Err(UnknownCryptoError);
+        }
+
+        match self.keys.iter().position(|entry| entry.id == key_id) {
+            Some(idx) => {
+                self.keys.remove(idx);
+                Ok(())
+            }
+            None => Err(UnknownCryptoError),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Generate a new primary key, with a key ID one greater than the largest
+    /// key ID in the key ring, and return its key ID.
+    pub fn rotate(&mut self) -> Result<u32, UnknownCryptoError> {
+        // There is always at least the primary key.
+        let max_id = self.keys.iter().map(|entry| entry.id).max().unwrap();
+        let key_id = max_id.checked_add(1).ok_or(UnknownCryptoError)?;
+
+        self.keys.push(KeyEntry::new(key_id, SecretKey::default()));
+        self.primary = key_id;
+        Ok(key_id)
+    }
+
+    /// Return the key ID of the primary key.
+    pub fn primary_key_id(&self) -> u32 {
+        self.primary
+    }
+
+    /// Return `true` if the key ring holds a key with `key_id`.
+    pub fn contains(&self, key_id: u32) -> bool {
+        self.get(key_id).is_some()
+    }
+
+    /// Return the usage of the key with `key_id`, or `None` if the key ring
+    /// does not hold such a key.
+    pub fn usage(&self, key_id: u32) -> Option<KeyUsage> {
+        self.get(key_id).map(|entry| entry.usage)
+    }
+
+    /// Set the maximum number of messages and plaintext bytes that may be
+    /// encrypted with a single key. The limits apply to all keys in the key ring.
+    pub fn set_usage_limits(&mut self, max_messages: u64, max_bytes: u64) {
+        self.limits = KeyUsage {
+            messages: max_messages,
+            bytes: max_bytes,
+        };
+    }
+
+    /// Return `true` if the primary key has reached either of its usage limits,
+    /// and a new primary key must be chosen before sealing again.
+    pub fn needs_rotation(&self) -> bool {
+        // An empty plaintext cannot be sealed, so at least one byte is needed.
+        self.would_exceed_limits(1)
+    }
+
+    fn would_exceed_limits(&self, plaintext_len: u64) -> bool {
+        // The primary key is always in the key ring.
+        let usage = self.get(self.primary).unwrap().usage;
+        usage.messages >= self.limits.messages
+            || match usage.bytes.checked_add(plaintext_len) {
+                Some(bytes) => bytes > self.limits.bytes,
+                None => true,
+            }
+    }
+
+    fn get(&self, key_id: u32) -> Option<&KeyEntry> {
+        self.keys.iter().find(|entry| entry.id == key_id)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Authenticated encryption using XChaCha20Poly1305 and the primary key.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+        self.seal_with_context(&[], plaintext)
+    }
+
+    /// Seal `plaintext` with `context` authenticated after the header, so
+    /// that the ciphertext can only be opened with the same `context`. This
+    /// separates the ciphertexts of formats built on a `KeyRing` from those
+    /// of [`seal()`], which uses an empty `context`.
+    ///
+    /// [`seal()`]: struct.KeyRing.html#method.seal
+    pub(crate) fn seal_with_context(
+        &mut self,
+        context: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, UnknownCryptoError> {
+        if plaintext.is_empty() || self.would_exceed_limits(plaintext.len() as u64) {
+            return Err(UnknownCryptoError);
+        }
+
+        let out_len = match plaintext
+            .len()
+            .checked_add(HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE)
+        {
+            Some(min_out_len) => min_out_len,
+            None => return Err(UnknownCryptoError),
+        };
+
+        let primary = self.primary;
+        // The primary key is always in the key ring.
+        let entry = self
+            .keys
+            .iter_mut()
+            .find(|entry| entry.id == primary)
+            .unwrap();
+
+        let mut dst_out = vec![0u8; out_len];
+        dst_out[0] = VERSION;
+        dst_out[1..HEADER_SIZE].copy_from_slice(&self.primary.to_be_bytes());
+        let nonce = Nonce::generate();
+        dst_out[HEADER_SIZE..HEADER_SIZE + XCHACHA_NONCESIZE].copy_from_slice(nonce.as_ref());
+
+        let (header, rest) = dst_out.split_at_mut(HEADER_SIZE);
+        aead::xchacha20poly1305::seal(
+            &chacha20::SecretKey::from_slice(entry.key.unprotected_as_bytes())?,
+            &nonce,
+            plaintext,
+            Some(&additional_data(header, context)),
+            &mut rest[XCHACHA_NONCESIZE..],
+        )?;
+
+        entry.usage.messages += 1;
+        entry.usage.bytes += plaintext.len() as u64;
+
+        Ok(dst_out)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Authenticated decryption using XChaCha20Poly1305 and the key named in
+    /// the header of `ciphertext`.
+    pub fn open(&self, ciphertext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+        self.open_with_context(&[], ciphertext)
+    }
+
+    /// Open a `ciphertext` returned by [`seal_with_context()`] with the same
+    /// `context`.
+    ///
+    /// [`seal_with_context()`]: struct.KeyRing.html#method.seal_with_context
+    pub(crate) fn open_with_context(
+        &self,
+        context: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, UnknownCryptoError> {
+        // Avoid empty ciphertexts
+        if ciphertext.len() <= (HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE) {
+            return Err(UnknownCryptoError);
+        }
+        if ciphertext[0] != VERSION {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut key_id = [0u8; 4];
+        key_id.copy_from_slice(&ciphertext[1..HEADER_SIZE]);
+        let entry = self
+            .get(u32::from_be_bytes(key_id))
+            .ok_or(UnknownCryptoError)?;
+
+        let mut dst_out =
+            vec![0u8; ciphertext.len() - (HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE)];
+
+        aead::xchacha20poly1305::open(
+            &chacha20::SecretKey::from_slice(entry.key.unprotected_as_bytes())?,
+            &Nonce::from_slice(&ciphertext[HEADER_SIZE..HEADER_SIZE + XCHACHA_NONCESIZE])?,
+            &ciphertext[HEADER_SIZE + XCHACHA_NONCESIZE..],
+            Some(&additional_data(&ciphertext[..HEADER_SIZE], context)),
+            &mut dst_out,
+        )?;
+
+        Ok(dst_out)
+    }
+}
+
+/// The additional data of a ciphertext: its `header`, followed by `context`.
+fn additional_data(header: &[u8], context: &[u8]) -> Vec<u8> {
+    let mut ad = Vec::with_capacity(header.len() + context.len());
+    ad.extend_from_slice(header);
+    ad.extend_from_slice(context);
+
+    ad
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let mut keyring = KeyRing::new(7, SecretKey::default()).unwrap();
+        let plaintext = "Secret message".as_bytes();
+
+        let ciphertext = keyring.seal(plaintext).unwrap();
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE
+        );
+        assert_eq!(&ciphertext[..HEADER_SIZE], &[VERSION, 0, 0, 0, 7]);
+        assert_eq!(keyring.open(&ciphertext).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_interop_with_aead_primitive() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let mut keyring =
+            KeyRing::new(0x01020304, SecretKey::from_slice(&[1u8; 32]).unwrap()).unwrap();
+        let ciphertext = keyring.seal(b"Secret message").unwrap();
+        assert_eq!(&ciphertext[..HEADER_SIZE], &[VERSION, 1, 2, 3, 4]);
+
+        let mut dst_out = [0u8; 14];
+        aead::xchacha20poly1305::open(
+            &chacha20::SecretKey::from_slice(key.unprotected_as_bytes()).unwrap(),
+            &Nonce::from_slice(&ciphertext[HEADER_SIZE..HEADER_SIZE + XCHACHA_NONCESIZE]).unwrap(),
+            &ciphertext[HEADER_SIZE + XCHACHA_NONCESIZE..],
+            Some(&ciphertext[..HEADER_SIZE]),
+            &mut dst_out,
+        )
+        .unwrap();
+        assert_eq!(&dst_out, b"Secret message");
+
+        // The header must be authenticated.
+        assert!(aead::xchacha20poly1305::open(
+            &chacha20::SecretKey::from_slice(key.unprotected_as_bytes()).unwrap(),
+            &Nonce::from_slice(&ciphertext[HEADER_SIZE..HEADER_SIZE + XCHACHA_NONCESIZE]).unwrap(),
+            &ciphertext[HEADER_SIZE + XCHACHA_NONCESIZE..],
+            None,
+            &mut dst_out,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rotate_opens_old_ciphertexts() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let old = keyring.seal(b"old").unwrap();
+        assert_eq!(keyring.rotate().unwrap(), 2);
+        assert_eq!(keyring.primary_key_id(), 2);
+        let new = keyring.seal(b"new").unwrap();
+        assert_eq!(&new[1..HEADER_SIZE], &2u32.to_be_bytes());
+
+        assert_eq!(keyring.open(&old).unwrap(), b"old");
+        assert_eq!(keyring.open(&new).unwrap(), b"new");
+
+        keyring.remove(1).unwrap();
+        assert!(keyring.open(&old).is_err());
+        assert_eq!(keyring.open(&new).unwrap(), b"new");
+    }
+
+    #[test]
+    fn test_rotate_max_key_id_err() {
+        let mut keyring = KeyRing::new(u32::MAX, SecretKey::default()).unwrap();
+        assert!(keyring.rotate().is_err());
+        assert_eq!(keyring.primary_key_id(), u32::MAX);
+    }
+
+    #[test]
+    fn test_add_set_primary_remove() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        assert!(keyring.add(1, SecretKey::default()).is_err());
+        assert!(keyring.add(2, SecretKey::generate(31).unwrap()).is_err());
+        keyring.add(2, SecretKey::default()).unwrap();
+        assert!(keyring.contains(2));
+        assert_eq!(keyring.primary_key_id(), 1);
+
+        assert!(keyring.set_primary(3).is_err());
+        keyring.set_primary(2).unwrap();
+        assert_eq!(keyring.primary_key_id(), 2);
+
+        assert!(keyring.remove(2).is_err());
+        assert!(keyring.remove(3).is_err());
+        keyring.remove(1).unwrap();
+        assert!(!keyring.contains(1));
+    }
+
+    #[test]
+    fn test_new_secret_length_err() {
+        assert!(KeyRing::new(1, SecretKey::generate(31).unwrap()).is_err());
+        assert!(KeyRing::new(1, SecretKey::generate(64).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_plaintext_empty_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        assert!(keyring.seal(b"").is_err());
+    }
+
+    #[test]
+    fn test_ciphertext_less_than_46_err() {
+        let keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let mut ciphertext = [0u8; HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE];
+        ciphertext[0] = VERSION;
+        ciphertext[4] = 1;
+        assert!(keyring.open(&ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_modified_header_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        keyring.add(2, SecretKey::default()).unwrap();
+        let ciphertext = keyring.seal(b"Secret message").unwrap();
+
+        // Unknown version
+        let mut bad = ciphertext.clone();
+        bad[0] ^= 1;
+        assert!(keyring.open(&bad).is_err());
+        // Other key in the key ring
+        let mut bad = ciphertext.clone();
+        bad[4] = 2;
+        assert!(keyring.open(&bad).is_err());
+        // Key not in the key ring
+        let mut bad = ciphertext;
+        bad[4] = 3;
+        assert!(keyring.open(&bad).is_err());
+    }
+
+    #[test]
+    fn test_modified_nonce_ciphertext_tag_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let ciphertext = keyring.seal(b"Secret message").unwrap();
+
+        for idx in &[10, 30, ciphertext.len() - 6] {
+            let mut bad = ciphertext.clone();
+            bad[*idx] ^= 1;
+            assert!(keyring.open(&bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_diff_keyring_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let other = KeyRing::new(1, SecretKey::default()).unwrap();
+        let ciphertext = keyring.seal(b"Secret message").unwrap();
+        assert!(other.open(&ciphertext).is_err());
+    }
+
+    #[test]
+    fn test_usage_is_counted_per_key() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        assert_eq!(keyring.usage(1).unwrap(), KeyUsage::default());
+        assert!(keyring.usage(2).is_none());
+
+        let ciphertext = keyring.seal(&[0u8; 10]).unwrap();
+        keyring.seal(&[0u8; 5]).unwrap();
+        keyring.open(&ciphertext).unwrap();
+        assert_eq!(keyring.usage(1).unwrap().messages(), 2);
+        assert_eq!(keyring.usage(1).unwrap().bytes(), 15);
+
+        keyring.rotate().unwrap();
+        keyring.seal(&[0u8; 7]).unwrap();
+        assert_eq!(keyring.usage(1).unwrap().messages(), 2);
+        assert_eq!(keyring.usage(2).unwrap().messages(), 1);
+        assert_eq!(keyring.usage(2).unwrap().bytes(), 7);
+    }
+
+    #[test]
+    fn test_message_limit() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        keyring.set_usage_limits(2, u64::MAX);
+        let ciphertext = keyring.seal(b"one").unwrap();
+        assert!(!keyring.needs_rotation());
+        keyring.seal(b"two").unwrap();
+        assert!(keyring.needs_rotation());
+        assert!(keyring.seal(b"three").is_err());
+        assert_eq!(keyring.usage(1).unwrap().messages(), 2);
+        // Opening is still possible.
+        assert_eq!(keyring.open(&ciphertext).unwrap(), b"one");
+
+        keyring.rotate().unwrap();
+        assert!(!keyring.needs_rotation());
+        keyring.seal(b"three").unwrap();
+        // Going back to an exhausted key is not possible.
+        keyring.set_primary(1).unwrap();
+        assert!(keyring.needs_rotation());
+        assert!(keyring.seal(b"four").is_err());
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        keyring.set_usage_limits(u64::MAX, 10);
+        keyring.seal(&[0u8; 6]).unwrap();
+        assert!(!keyring.needs_rotation());
+        assert!(keyring.seal(&[0u8; 5]).is_err());
+        keyring.seal(&[0u8; 4]).unwrap();
+        assert!(keyring.needs_rotation());
+        assert_eq!(keyring.usage(1).unwrap().bytes(), 10);
+    }
+
+    #[test]
+    fn test_debug_omits_keys() {
+        let mut keyring = KeyRing::new(1, SecretKey::from_slice(&[0xab; 32]).unwrap()).unwrap();
+        keyring.add(5, SecretKey::default()).unwrap();
+        assert_eq!(
+            format!("{:?}", keyring),
+            "KeyRing { key_ids: [1, 5], primary: 1 }"
+        );
+    }
+}
@@ -24,5 +24,13 @@ pub mod aead;
 pub mod auth;
 pub mod hash;
 mod hltypes;
+#[cfg(feature = "jwk")]
+pub mod jwk;
 pub mod kdf;
+pub mod kex;
+pub mod keyring;
 pub mod pwhash;
+pub mod sealed;
+pub mod sign;
+pub mod token;
+pub mod webhook;
@@ -0,0 +1,1746 @@
+// MIT License
+
+// Copyright (c) 2020-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Password hashing and verification.
+//!
+//! # Use case:
+//! `orion::pwhash` is suitable for securely storing passwords.
+//!
+//! An example of this would be needing to store user passwords (from a sign-up
+//! at a webstore) in a server database,
+//! where a potential disclosure of the data in this database should not result
+//! in the user's actual passwords being disclosed as well.
+//!
+//! # About:
+//! - Uses Argon2i, or Argon2id with [`hash_password_argon2id`] and
+//!   [`hash_password_argon2id_verify`].
+//! - A salt of 16 bytes is automatically generated.
+//! - The password hash length is set to 32.
+//! - [`hash_password_with_progress`] and [`hash_password_verify_with_progress`]
+//!   report their progress to a [`Progress`], which can also cancel them.
+//! - [`hash_password_verify_many`] verifies a batch of password hashes, each
+//!   with its stored parameters, on up to `max_threads` threads, returning a
+//!   result for each.
+//! - [`server_relief`] lets the client compute Argon2i, leaving the server
+//!   with a cheap keyed step.
+//! - [`compat::bcrypt_verify`] verifies existing bcrypt hashes, so that they
+//!   can be replaced by Argon2 hashes as users log in.
+//! - [`hash_password_verify_stored`] verifies a password with the variant and
+//!   parameters stored in the [`PasswordHash`], so that hashes stored in the
+//!   encoded form can still be verified after the parameters used for new
+//!   hashes have changed.
+//!
+//! [`PasswordHash`] provides two ways of retrieving the hashed password:
+//! - [`unprotected_as_encoded()`] returns the hashed password in an encoded form.
+//! The encoding specifies the settings used to hash the password.
+//! - [`unprotected_as_bytes()`] returns only the hashed password in raw bytes.
+//!
+//! The following is an example of how the encoded password hash might look:
+//! ```text
+//! $argon2i$v=19$m=8192,t=3,p=1$c21hbGxzYWx0$lmO1aPPy3x0CcvrKpFLi1TL/uSVJ/eO5hPHiWZFaWvY
+//! ```
+//!
+//! See a more detailed description of the encoding format [here](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md).
+//!
+//! # Note:
+//! Argon2i only supports a single thread/lane. Argon2id supports up to
+//! `0xFF_FFFF` lanes, which are computed in parallel with the `rayon` feature.
+//!
+//! # Parameters:
+//! - `password`: The password to be hashed.
+//! - `expected`: The expected password hash.
+//! - `iterations`: Iterations cost parameter for Argon2.
+//! - `memory`: Memory (in kibibytes (KiB)) cost parameter for Argon2.
+//! - `lanes`: Parallelism cost parameter for Argon2id.
+//! - `items`: The pairs of expected password hashes and passwords to verify.
+//! - `max_threads`: The maximum amount of threads to verify `items` on.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `memory` is less than 8, or less than `8 * lanes` for Argon2id.
+//! - `iterations` is less than 3.
+//! - `lanes` is less than 1 or greater than `0xFF_FFFF`.
+//! - The length of the `password` is greater than `u32::MAX`.
+//! - The password hash does not match `expected`.
+//! - `expected` was not hashed with the Argon2 variant it is verified with.
+//! - `max_threads` is 0.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - [`unprotected_as_encoded()`] and [`unprotected_as_bytes()`] should never
+//! be used to compare password hashes, as these will not run in constant-time.
+//! Either use [`pwhash::hash_password_verify`] or compare two [`PasswordHash`]es.
+//! - The base64 encoding and decoding operations that [`PasswordHash`] performs, do NOT run in constant-time.
+//! - Choosing the correct cost parameters is important for security. Please refer to
+//! [libsodium's docs](https://download.libsodium.org/doc/password_hashing/default_phf#guidelines-for-choosing-the-parameters)
+//! for a description of how to do this.
+//!
+//! # Example:
+//! ```rust
+//! use orion::pwhash;
+//!
+//! let password = pwhash::Password::from_slice(b"Secret password")?;
+//!
+//! let hash = pwhash::hash_password(&password, 3, 1<<16)?;
+//! assert!(pwhash::hash_password_verify(&hash, &password, 3, 1<<16).is_ok());
+//!
+//! let hash = pwhash::hash_password_argon2id(&password, 3, 1<<16, 4)?;
+//! assert!(pwhash::hash_password_argon2id_verify(&hash, &password, 3, 1<<16, 4).is_ok());
+//!
+//! // Stored with the previous parameters, and verified with them.
+//! let stored = pwhash::PasswordHash::from_encoded(hash.unprotected_as_encoded())?;
+//! assert!(pwhash::hash_password_verify_stored(&stored, &password).is_ok());
+//! // Rehash when the parameters have been raised since.
+//! if stored.iterations() < 4 {
+//!     let _rehashed = pwhash::hash_password_argon2id(&password, 4, 1<<16, 4)?;
+//! }
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`PasswordHash`]: struct.PasswordHash.html
+//! [`unprotected_as_encoded()`]: struct.PasswordHash.html#method.unprotected_as_encoded
+//! [`unprotected_as_bytes()`]: struct.PasswordHash.html#method.unprotected_as_bytes
+//! [`pwhash::hash_password`]: fn.hash_password.html
+//! [`pwhash::hash_password_verify`]: fn.hash_password_verify.html
+//! [`hash_password_with_progress`]: fn.hash_password_with_progress.html
+//! [`hash_password_verify_with_progress`]: fn.hash_password_verify_with_progress.html
+//! [`hash_password_verify_many`]: fn.hash_password_verify_many.html
+//! [`Progress`]: ../util/trait.Progress.html
+//! [`server_relief`]: server_relief/index.html
+//! [`compat::bcrypt_verify`]: compat/fn.bcrypt_verify.html
+//! [`hash_password_argon2id`]: fn.hash_password_argon2id.html
+//! [`hash_password_argon2id_verify`]: fn.hash_password_argon2id_verify.html
+//! [`hash_password_verify_stored`]: fn.hash_password_verify_stored.html
+
+pub use super::hltypes::Password;
+use super::hltypes::Salt;
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::kdf::argon2i::{self, LANES, MIN_MEMORY},
+    hazardous::kdf::argon2id::{self, MAX_LANES, MIN_LANES},
+    util::{self, NoProgress, Progress},
+};
+use base64::{decode_config, encode_config, STANDARD_NO_PAD};
+use zeroize::Zeroizing;
+
+/// The length of the salt used for password hashing.
+pub const SALT_LENGTH: usize = 16;
+
+/// The length of the hashed password.
+pub const PWHASH_LENGTH: usize = 32;
+
+/// Minimum amount of iterations.
+pub(crate) const MIN_ITERATIONS: u32 = 3;
+
+/// The Argon2 variant a `PasswordHash` was computed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Argon2i,
+    Argon2id,
+}
+
+impl Variant {
+    /// The algorithm name in the encoded password hash.
+    fn name(self) -> &'static str {
+        match self {
+            Variant::Argon2i => "argon2i",
+            Variant::Argon2id => "argon2id",
+        }
+    }
+}
+
+/// A type to represent the `PasswordHash` that Argon2i or Argon2id returns when used for password hashing.
+///
+///  
+/// # Errors:
+/// An error will be returned if:
+/// - The encoded password hash contains whitespace.
+/// - The encoded Argon2i password hash has a parallelism count other than 1.
+/// - The encoded Argon2id password hash has a parallelism count less than 1 or greater than `0xFF_FFFF`.
+/// - The encoded Argon2id password hash has less than 8 KiB of memory per lane.
+/// - The encoded password contains any other fields than: The algorithm name,
+///   version, m, t, p and the salt and password hash.
+/// - The encoded password hash contains invalid Base64 encoding.
+/// - Any decimal parameter value, such as m, contains leading zeroes and is longer
+///   than a single character.
+/// - `iterations` is less than 3.
+/// - `memory` is less than 8.
+/// - `password` is not 32 bytes.
+/// - `salt` is not 16 bytes.
+/// - The encoded password hash contains numerical values that cannot
+///   be represented as a `u32`.
+/// - The encoded password hash length is less than [`MIN_ENCODED_LEN`] or greater than [`MAX_ENCODED_LEN`].
+/// - The parameters in the encoded password hash are not correctly ordered. The ordering must be:
+///   `$argon2i$v=19$m=<value>,t=<value>,p=<value>$<salt>$<hash>`
+/// - The algorithm name is not `argon2i` or `argon2id`.
+/// # Panics:
+/// A panic will occur if:
+/// - Overflowing calculations happen on `usize` when decoding the password and salt from Base64.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as it breaks all protections
+/// that the type implements.
+/// - Never use `unprotected_as_bytes()` or `unprotected_as_encoded()` to compare password hashes,
+/// as that will not run in constant-time. Compare `PasswordHash`es directly using `==` instead.
+/// - The base64 encoding and decoding operations that `PasswordHash` performs, do NOT run in constant-time.
+/// - The trait `PartialEq<&'_ [u8]>` is implemented for this type so that users are not tempted
+/// to call `unprotected_as_bytes` to compare this sensitive value to a byte slice. The trait
+/// is implemented in such a way that the comparison happens in constant time. Thus, users should
+/// prefer `SecretType == &[u8]` over `SecretType.unprotected_as_bytes() == &[u8]`.
+/// Examples are shown below. The examples apply to any type that implements `PartialEq<&'_ [u8]>`.
+/// ```rust
+/// use orion::hazardous::mac::hmac::Tag;
+/// # use orion::errors::UnknownCryptoError;
+///
+/// # fn main() -> Result<(), Box<UnknownCryptoError>> {
+/// // Initialize an arbitrary, 64-byte tag.
+/// let tag = Tag::from_slice(&[1; 64])?;
+///
+/// // Secure, constant-time comparison with a byte slice
+/// assert!(tag == &[1; 64][..]);
+///
+/// // Secure, constant-time comparison with another Tag
+/// assert!(tag == Tag::from_slice(&[1; 64])?);
+/// # Ok(())
+/// # }
+/// ```
+/// [`MIN_ENCODED_LEN`]: struct.PasswordHash.html#associatedconstant.MIN_ENCODED_LEN
+/// [`MAX_ENCODED_LEN`]: struct.PasswordHash.html#associatedconstant.MAX_ENCODED_LEN
+pub struct PasswordHash {
+    encoded_password_hash: String,
+    password_hash: Vec<u8>,
+    salt: Salt,
+    iterations: u32,
+    memory: u32,
+    variant: Variant,
+    lanes: u32,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl PasswordHash {
+    /// Given a 16-byte salt (22 characters encoded) and 32-byte password hash (43 characters encoded),
+    /// and parameters (m, t) in decimal representation of 1..10 in length, 92 is the minimum length for an encoded password hash.
+    pub const MIN_ENCODED_LEN: usize = 92;
+
+    /// Given a 16-byte salt (22 characters encoded) and 32-byte password hash (43 characters encoded),
+    /// parameters (m, t) in decimal representation of 1..10 in length and the Argon2id parameter p
+    /// of 1..8 in length, 118 is the maximum length for an encoded password hash.
+    pub const MAX_ENCODED_LEN: usize = 118;
+
+    /// Parse a decimal parameter value to a u32. Returns an error on overflow,
+    /// if the value has leading zeroes and if it contains anything but digits.
+    fn parse_decimal_value(value: &str) -> Result<u32, UnknownCryptoError> {
+        // See: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md#decimal-encoding
+        if value.len() > 1 && value.starts_with('0') {
+            return Err(UnknownCryptoError);
+        }
+        // .parse::<T>() accepts a leading '+', which is not a valid decimal encoding.
+        if !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(UnknownCryptoError);
+        }
+        // .parse::<T>() detects overflows (in debug and release builds)
+        // and rejects empty strings. If the value contains spaces, parsing
+        // also fails.
+        Ok(value.parse::<u32>()?)
+    }
+
+    /// Parse a parameter of the form `name=value`, where value is decimal.
+    fn parse_parameter(parameter: &str, name: &str) -> Result<u32, UnknownCryptoError> {
+        let mut name_and_value = parameter.splitn(2, '=');
+        if name_and_value.next() != Some(name) {
+            return Err(UnknownCryptoError);
+        }
+
+        match name_and_value.next() {
+            Some(value) => Self::parse_decimal_value(value),
+            None => Err(UnknownCryptoError),
+        }
+    }
+
+    /// Decode unpadded base64. Returns an error if `value` is padded.
+    fn decode_b64(value: &str) -> Result<Vec<u8>, UnknownCryptoError> {
+        // See: https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md#b64
+        // decode_config() accepts padding, even when the config has none.
+        if value.contains('=') {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(decode_config(value, STANDARD_NO_PAD)?)
+    }
+
+    /// Encode password hash, salt and parameters for storage.
+    fn encode(
+        variant: Variant,
+        password_hash: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        memory: u32,
+        lanes: u32,
+    ) -> String {
+        format!(
+            "${}$v=19$m={},t={},p={}${}${}",
+            variant.name(),
+            memory,
+            iterations,
+            lanes,
+            encode_config(salt, STANDARD_NO_PAD),
+            encode_config(password_hash, STANDARD_NO_PAD)
+        )
+    }
+
+    /// Check the lanes and memory for `variant`.
+    fn check_lanes(variant: Variant, memory: u32, lanes: u32) -> Result<(), UnknownCryptoError> {
+        match variant {
+            Variant::Argon2i if lanes == LANES => Ok(()),
+            Variant::Argon2id
+                if (MIN_LANES..=MAX_LANES).contains(&lanes) && memory >= 8 * lanes =>
+            {
+                Ok(())
+            }
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct from given byte slice and parameters.
+    pub fn from_slice(
+        password_hash: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        memory: u32,
+    ) -> Result<Self, UnknownCryptoError> {
+        Self::from_parts(
+            Variant::Argon2i,
+            password_hash,
+            salt,
+            iterations,
+            memory,
+            LANES,
+        )
+    }
+
+    /// Construct from given byte slice and parameters of `variant`.
+    fn from_parts(
+        variant: Variant,
+        password_hash: &[u8],
+        salt: &[u8],
+        iterations: u32,
+        memory: u32,
+        lanes: u32,
+    ) -> Result<Self, UnknownCryptoError> {
+        if password_hash.len() != PWHASH_LENGTH {
+            return Err(UnknownCryptoError);
+        }
+        if salt.len() != SALT_LENGTH {
+            return Err(UnknownCryptoError);
+        }
+        if iterations < MIN_ITERATIONS {
+            return Err(UnknownCryptoError);
+        }
+        if memory < MIN_MEMORY {
+            return Err(UnknownCryptoError);
+        }
+        Self::check_lanes(variant, memory, lanes)?;
+
+        let encoded_password_hash =
+            Self::encode(variant, password_hash, salt, iterations, memory, lanes);
+
+        Ok(Self {
+            encoded_password_hash,
+            password_hash: password_hash.into(),
+            salt: Salt::from_slice(salt)?,
+            iterations,
+            memory,
+            variant,
+            lanes,
+        })
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Construct from encoded password hash.
+    pub fn from_encoded(password_hash: &str) -> Result<Self, UnknownCryptoError> {
+        if password_hash.len() > Self::MAX_ENCODED_LEN
+            || password_hash.len() < Self::MIN_ENCODED_LEN
+        {
+            return Err(UnknownCryptoError);
+        }
+
+        if password_hash.contains(' ') {
+            return Err(UnknownCryptoError);
+        }
+
+        let parts_split = password_hash.split('$').collect::<Vec<&str>>();
+        if parts_split.len() != 6 {
+            return Err(UnknownCryptoError);
+        }
+        let mut parts = parts_split.into_iter();
+        if parts.next() != Some("") {
+            return Err(UnknownCryptoError);
+        }
+        let variant = match parts.next() {
+            Some("argon2i") => Variant::Argon2i,
+            Some("argon2id") => Variant::Argon2id,
+            _ => return Err(UnknownCryptoError),
+        };
+        if parts.next() != Some("v=19") {
+            return Err(UnknownCryptoError);
+        }
+
+        // Splits as ["m=X", "t=Y", "p=Z"].
+        let param_parts_split = parts.next().unwrap().split(',').collect::<Vec<&str>>();
+        if param_parts_split.len() != 3 {
+            return Err(UnknownCryptoError);
+        }
+        let mut param_parts = param_parts_split.into_iter();
+
+        let memory = Self::parse_parameter(param_parts.next().unwrap(), "m")?;
+        if memory < MIN_MEMORY {
+            return Err(UnknownCryptoError);
+        }
+
+        let iterations = Self::parse_parameter(param_parts.next().unwrap(), "t")?;
+        if iterations < MIN_ITERATIONS {
+            return Err(UnknownCryptoError);
+        }
+
+        let lanes = Self::parse_parameter(param_parts.next().unwrap(), "p")?;
+        Self::check_lanes(variant, memory, lanes)?;
+
+        let salt = Self::decode_b64(parts.next().unwrap())?;
+        if salt.len() != SALT_LENGTH {
+            return Err(UnknownCryptoError);
+        }
+        let password_hash_raw = Self::decode_b64(parts.next().unwrap())?;
+        if password_hash_raw.len() != PWHASH_LENGTH {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self {
+            encoded_password_hash: password_hash.into(),
+            password_hash: password_hash_raw,
+            salt: Salt::from_slice(&salt)?,
+            iterations,
+            memory,
+            variant,
+            lanes,
+        })
+    }
+
+    #[inline]
+    /// Return encoded password hash. __**Warning**__: Should not be used to verify
+    /// password hashes. This __**breaks protections**__ that the type implements.
+    pub fn unprotected_as_encoded(&self) -> &str {
+        self.encoded_password_hash.as_ref()
+    }
+
+    #[inline]
+    /// Return the password hash as byte slice. __**Warning**__: Should not be used unless strictly
+    /// needed. This __**breaks protections**__ that the type implements.
+    pub fn unprotected_as_bytes(&self) -> &[u8] {
+        self.password_hash.as_ref()
+    }
+
+    #[inline]
+    /// Return the length of the password hash.
+    pub fn len(&self) -> usize {
+        self.password_hash.len()
+    }
+
+    #[inline]
+    /// Return the iterations (t) the password was hashed with.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    #[inline]
+    /// Return the memory (m), in kibibytes, the password was hashed with.
+    pub fn memory(&self) -> u32 {
+        self.memory
+    }
+
+    #[inline]
+    /// Return the lanes (p) the password was hashed with, which is always 1
+    /// for Argon2i.
+    pub fn lanes(&self) -> u32 {
+        self.lanes
+    }
+}
+
+impl core::fmt::Debug for PasswordHash {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PasswordHash {{ encoded_password_hash: [***OMITTED***], password_hash: [***OMITTED***], iterations: \
+             {:?}, memory: {:?} }}",
+            self.iterations, self.memory
+        )
+    }
+}
+
+impl_ct_partialeq_trait!(PasswordHash, unprotected_as_bytes);
+
+#[cfg(test)]
+impl Clone for PasswordHash {
+    fn clone(&self) -> Self {
+        Self {
+            encoded_password_hash: self.encoded_password_hash.clone(),
+            password_hash: self.password_hash.clone(),
+            salt: self.salt.clone(),
+            iterations: self.iterations,
+            memory: self.memory,
+            variant: self.variant,
+            lanes: self.lanes,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "test_utils"))]
+impl quickcheck::Arbitrary for PasswordHash {
+    /// Generate a valid `PasswordHash`, with a random hash and salt, and
+    /// parameters that are at least the minimum allowed.
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let password_hash: Vec<u8> = (0..PWHASH_LENGTH).map(|_| u8::arbitrary(g)).collect();
+        let salt: Vec<u8> = (0..SALT_LENGTH).map(|_| u8::arbitrary(g)).collect();
+        let iterations = core::cmp::max(u32::arbitrary(g), MIN_ITERATIONS);
+        let memory = core::cmp::max(u32::arbitrary(g), MIN_MEMORY);
+
+        Self::from_slice(&password_hash, &salt, iterations, memory).unwrap()
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash a password using Argon2i.
+pub fn hash_password(
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+) -> Result<PasswordHash, UnknownCryptoError> {
+    hash_password_with_progress(password, iterations, memory, &mut NoProgress)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash a password using Argon2i, reporting progress to `progress`.
+///
+/// Each of the `iterations` passes over memory consists of 4 segments. The
+/// number of segments completed is reported to `progress`, out of a total of
+/// `4 * iterations`. An error is returned if `progress` cancels the operation.
+pub fn hash_password_with_progress<P: Progress>(
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    progress: &mut P,
+) -> Result<PasswordHash, UnknownCryptoError> {
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+
+    // Cannot panic as this is a valid size.
+    let salt = Salt::generate(SALT_LENGTH).unwrap();
+    let mut buffer = Zeroizing::new([0u8; PWHASH_LENGTH]);
+
+    argon2i::derive_key_with_progress(
+        password.unprotected_as_bytes(),
+        salt.as_ref(),
+        iterations,
+        memory,
+        None,
+        None,
+        buffer.as_mut(),
+        progress,
+    )?;
+
+    PasswordHash::from_slice(buffer.as_ref(), salt.as_ref(), iterations, memory)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a password using Argon2i.
+pub fn hash_password_verify(
+    expected: &PasswordHash,
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+) -> Result<(), UnknownCryptoError> {
+    hash_password_verify_with_progress(expected, password, iterations, memory, &mut NoProgress)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a password using Argon2i, reporting progress to `progress`
+/// as [`hash_password_with_progress`] does.
+///
+/// [`hash_password_with_progress`]: fn.hash_password_with_progress.html
+pub fn hash_password_verify_with_progress<P: Progress>(
+    expected: &PasswordHash,
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    progress: &mut P,
+) -> Result<(), UnknownCryptoError> {
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+    if expected.variant != Variant::Argon2i {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut buffer = Zeroizing::new([0u8; PWHASH_LENGTH]);
+
+    argon2i::derive_key_with_progress(
+        password.unprotected_as_bytes(),
+        expected.salt.as_ref(),
+        iterations,
+        memory,
+        None,
+        None,
+        buffer.as_mut(),
+        progress,
+    )?;
+
+    util::secure_cmp(buffer.as_ref(), expected.unprotected_as_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash a password using Argon2id.
+pub fn hash_password_argon2id(
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+) -> Result<PasswordHash, UnknownCryptoError> {
+    hash_password_argon2id_with_progress(password, iterations, memory, lanes, &mut NoProgress)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash a password using Argon2id, reporting progress to `progress` as
+/// [`hash_password_with_progress`] does. A segment is completed once it has
+/// been computed in all `lanes`.
+///
+/// [`hash_password_with_progress`]: fn.hash_password_with_progress.html
+pub fn hash_password_argon2id_with_progress<P: Progress>(
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    progress: &mut P,
+) -> Result<PasswordHash, UnknownCryptoError> {
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+
+    // Cannot panic as this is a valid size.
+    let salt = Salt::generate(SALT_LENGTH).unwrap();
+    let mut buffer = Zeroizing::new([0u8; PWHASH_LENGTH]);
+
+    argon2id::derive_key_with_progress(
+        password.unprotected_as_bytes(),
+        salt.as_ref(),
+        iterations,
+        memory,
+        lanes,
+        None,
+        None,
+        buffer.as_mut(),
+        progress,
+    )?;
+
+    PasswordHash::from_parts(
+        Variant::Argon2id,
+        buffer.as_ref(),
+        salt.as_ref(),
+        iterations,
+        memory,
+        lanes,
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a password using Argon2id.
+pub fn hash_password_argon2id_verify(
+    expected: &PasswordHash,
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+) -> Result<(), UnknownCryptoError> {
+    hash_password_argon2id_verify_with_progress(
+        expected,
+        password,
+        iterations,
+        memory,
+        lanes,
+        &mut NoProgress,
+    )
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a password using Argon2id, reporting progress to `progress`
+/// as [`hash_password_argon2id_with_progress`] does.
+///
+/// [`hash_password_argon2id_with_progress`]: fn.hash_password_argon2id_with_progress.html
+pub fn hash_password_argon2id_verify_with_progress<P: Progress>(
+    expected: &PasswordHash,
+    password: &Password,
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    progress: &mut P,
+) -> Result<(), UnknownCryptoError> {
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+    if expected.variant != Variant::Argon2id {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut buffer = Zeroizing::new([0u8; PWHASH_LENGTH]);
+
+    argon2id::derive_key_with_progress(
+        password.unprotected_as_bytes(),
+        expected.salt.as_ref(),
+        iterations,
+        memory,
+        lanes,
+        None,
+        None,
+        buffer.as_mut(),
+        progress,
+    )?;
+
+    util::secure_cmp(buffer.as_ref(), expected.unprotected_as_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a password using the Argon2 variant, iterations, memory
+/// and lanes stored in `expected`.
+///
+/// The parameters determine the cost of verification, so `expected` must
+/// come from a trusted source, such as the database the password hash was
+/// stored in, and not from the party whose password is verified.
+pub fn hash_password_verify_stored(
+    expected: &PasswordHash,
+    password: &Password,
+) -> Result<(), UnknownCryptoError> {
+    match expected.variant {
+        Variant::Argon2i => {
+            hash_password_verify(expected, password, expected.iterations, expected.memory)
+        }
+        Variant::Argon2id => hash_password_argon2id_verify(
+            expected,
+            password,
+            expected.iterations,
+            expected.memory,
+            expected.lanes,
+        ),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Verify `items` on up to `max_threads` threads. The items are split into
+/// segments, one per thread, the first of which is verified on the calling
+/// thread. Segments for which a thread cannot be spawned are also verified on
+/// the calling thread.
+fn hash_password_verify_parallel(
+    items: &[(&PasswordHash, &Password)],
+    max_threads: usize,
+) -> Vec<Result<(), UnknownCryptoError>> {
+    let threads = core::cmp::min(items.len(), max_threads);
+    let items_per_segment = (items.len() - 1) / threads + 1;
+
+    let mut handles = Vec::with_capacity(threads - 1);
+    for segment in items.chunks(items_per_segment).skip(1) {
+        // The items are copied, as the threads cannot borrow them. The
+        // copied passwords are zeroized when the thread drops them.
+        let owned: Vec<(PasswordHash, Password)> = segment
+            .iter()
+            .map(|(expected, password)| {
+                // Cannot panic as both are copied from valid values.
+                let expected = PasswordHash::from_parts(
+                    expected.variant,
+                    expected.unprotected_as_bytes(),
+                    expected.salt.as_ref(),
+                    expected.iterations,
+                    expected.memory,
+                    expected.lanes,
+                )
+                .unwrap();
+                let password = Password::from_slice(password.unprotected_as_bytes()).unwrap();
+                (expected, password)
+            })
+            .collect();
+
+        let handle = std::thread::Builder::new().spawn(move || {
+            owned
+                .iter()
+                .map(|(expected, password)| hash_password_verify_stored(expected, password))
+                .collect::<Vec<_>>()
+        });
+        // If the thread cannot be spawned, the segment is verified on the
+        // calling thread instead.
+        handles.push((segment, handle.ok()));
+    }
+
+    let mut results: Vec<_> = items[..core::cmp::min(items_per_segment, items.len())]
+        .iter()
+        .map(|(expected, password)| hash_password_verify_stored(expected, password))
+        .collect();
+    for (segment, handle) in handles {
+        match handle {
+            Some(handle) => results.extend(
+                handle
+                    .join()
+                    .expect("Password verification thread panicked"),
+            ),
+            None => results.extend(
+                segment
+                    .iter()
+                    .map(|(expected, password)| hash_password_verify_stored(expected, password)),
+            ),
+        }
+    }
+
+    results
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Verify `items` on the calling thread, as threads are not available.
+fn hash_password_verify_parallel(
+    items: &[(&PasswordHash, &Password)],
+    _max_threads: usize,
+) -> Vec<Result<(), UnknownCryptoError>> {
+    items
+        .iter()
+        .map(|(expected, password)| hash_password_verify_stored(expected, password))
+        .collect()
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash and verify a batch of passwords on up to `max_threads` threads, each
+/// using the Argon2 variant, iterations, memory and lanes stored in its
+/// `PasswordHash`. The results are in the same order as `items`.
+///
+/// As with [`hash_password_verify_stored`], the parameters determine the cost
+/// of verification, so the password hashes must come from a trusted source.
+///
+/// Each thread verifies its passwords one at a time, so up to `max_threads`
+/// times the largest memory in `items` is used at once. The passwords verified
+/// on other threads than the calling one are copied to those threads, and
+/// zeroized after use. If a thread cannot be spawned, its passwords are
+/// verified on the calling thread instead. On `wasm32`, all passwords are
+/// verified on the calling thread.
+///
+/// [`hash_password_verify_stored`]: fn.hash_password_verify_stored.html
+pub fn hash_password_verify_many(
+    items: &[(&PasswordHash, &Password)],
+    max_threads: usize,
+) -> Result<Vec<Result<(), UnknownCryptoError>>, UnknownCryptoError> {
+    if max_threads == 0 {
+        return Err(UnknownCryptoError);
+    }
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(hash_password_verify_parallel(items, max_threads))
+}
+
+pub mod compat;
+
+pub mod server_relief;
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_hash_password_verify_many() {
+        let iterations = 3;
+        let memory = 8;
+        let passwords: Vec<Password> = (1..=5u8)
+            .map(|i| Password::from_slice(&[i; 16]).unwrap())
+            .collect();
+        let hashes: Vec<PasswordHash> = passwords
+            .iter()
+            .map(|password| hash_password(password, iterations, memory).unwrap())
+            .collect();
+
+        // Items 1 and 3 have the wrong password.
+        let items: Vec<(&PasswordHash, &Password)> = vec![
+            (&hashes[0], &passwords[0]),
+            (&hashes[1], &passwords[0]),
+            (&hashes[2], &passwords[2]),
+            (&hashes[3], &passwords[4]),
+            (&hashes[4], &passwords[4]),
+        ];
+
+        for max_threads in [1, 2, 3, 5, 16].iter() {
+            let results = hash_password_verify_many(&items, *max_threads).unwrap();
+            let ok: Vec<bool> = results.iter().map(|res| res.is_ok()).collect();
+            assert_eq!(ok, [true, false, true, false, true]);
+        }
+
+        assert!(hash_password_verify_many(&items, 0).is_err());
+        assert!(hash_password_verify_many(&[], 0).is_err());
+        assert!(hash_password_verify_many(&[], 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hash_password_verify_many_mixed() {
+        let password = Password::from_slice(b"Secret password").unwrap();
+        let other = Password::from_slice(b"Secret passwore").unwrap();
+        let hashes = [
+            hash_password(&password, 3, 8).unwrap(),
+            hash_password(&password, 4, 16).unwrap(),
+            hash_password_argon2id(&password, 3, 16, 2).unwrap(),
+            hash_password_argon2id(&password, 5, 8, 1).unwrap(),
+        ];
+
+        // Each password hash is verified with its own variant and parameters.
+        let items: Vec<(&PasswordHash, &Password)> =
+            hashes.iter().map(|hash| (hash, &password)).collect();
+        for max_threads in [1, 2, 4].iter() {
+            let results = hash_password_verify_many(&items, *max_threads).unwrap();
+            assert!(results.iter().all(|res| res.is_ok()));
+        }
+
+        let items: Vec<(&PasswordHash, &Password)> = vec![
+            (&hashes[0], &password),
+            (&hashes[1], &other),
+            (&hashes[2], &password),
+            (&hashes[3], &other),
+        ];
+        let results = hash_password_verify_many(&items, 2).unwrap();
+        let ok: Vec<bool> = results.iter().map(|res| res.is_ok()).collect();
+        assert_eq!(ok, [true, false, true, false]);
+    }
+
+    #[test]
+    #[cfg(feature = "safe_api")]
+    fn test_debug_impl() {
+        let valid = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+        let password_hash = PasswordHash::from_encoded(valid).unwrap();
+        let debug = format!("{:?}", password_hash);
+        let expected = "PasswordHash { encoded_password_hash: [***OMITTED***], password_hash: [***OMITTED***], iterations: 3, memory: 65536 }";
+        assert_eq!(debug, expected);
+    }
+
+    /// The tests herein were generated with the CLI tool from the reference implementation at:
+    /// https://github.com/P-H-C/phc-winner-argon2/commit/62358ba2123abd17fccf2a108a301d4b52c01a7c
+    mod test_encoding_from_ref {
+        use super::*;
+        use hex;
+
+        #[test]
+        fn test_encoding_and_verify_1() {
+            let iterations: u32 = 3;
+            let memory: u32 = 65536;
+            let password = Password::from_slice(b"password").unwrap();
+            let raw_hash =
+                hex::decode("7d1b1163d3c0b791fea802ae5d1ccbd3fe896c54a1b0277ad96e5a1f311293f7")
+                    .unwrap();
+            let encoded_hash = "$argon2i$v=19$m=65536,t=3,p=1$c29tZXNhbHRzb21lc2FsdA$fRsRY9PAt5H+qAKuXRzL0/6JbFShsCd62W5aHzESk/c";
+
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(expected.unprotected_as_bytes() == &raw_hash[..]);
+            assert!(hash_password_verify(&expected, &password, iterations, memory).is_ok());
+        }
+
+        #[test]
+        fn test_encoding_and_verify_2() {
+            let iterations: u32 = 3;
+            let memory: u32 = 65536;
+            let password = Password::from_slice(b"passwordPASSWORDPassword").unwrap();
+            let raw_hash =
+                hex::decode("ed4b0fd657e165f9ffe90f66ff315fbec878e629f03b2d6468d4b17a50c796aa")
+                    .unwrap();
+            let encoded_hash = "$argon2i$v=19$m=65536,t=3,p=1$c29tZXNhbHRzb21lc2FsdA$7UsP1lfhZfn/6Q9m/zFfvsh45inwOy1kaNSxelDHlqo";
+
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(expected.unprotected_as_bytes() == &raw_hash[..]);
+            assert!(hash_password_verify(&expected, &password, iterations, memory).is_ok());
+        }
+
+        #[test]
+        fn test_encoding_and_verify_3() {
+            let iterations: u32 = 3;
+            let memory: u32 = 65536;
+            // Different salt from test 2
+            let password = Password::from_slice(b"passwordPASSWORDPassword").unwrap();
+            let raw_hash =
+                hex::decode("fa9ea96fecd0998251d698c1303edda4df3889a39bfa87cd5e7b8656ef61b510")
+                    .unwrap();
+            let encoded_hash = "$argon2i$v=19$m=65536,t=3,p=1$U29tZVNhbHRTb21lU2FsdA$+p6pb+zQmYJR1pjBMD7dpN84iaOb+ofNXnuGVu9htRA";
+
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(expected.unprotected_as_bytes() == &raw_hash[..]);
+            assert!(hash_password_verify(&expected, &password, iterations, memory).is_ok());
+        }
+
+        #[test]
+        fn test_encoding_and_verify_4() {
+            let iterations: u32 = 3;
+            let memory: u32 = 256;
+            let password = Password::from_slice(b"passwordPASSWORDPassword").unwrap();
+            let raw_hash =
+                hex::decode("fb3e0cdf7b10970bf6711c151861851566006f8986c9109ba2cdd5d98f9ca9d7")
+                    .unwrap();
+            let encoded_hash = "$argon2i$v=19$m=256,t=3,p=1$c29tZXNhbHRzb21lc2FsdA$+z4M33sQlwv2cRwVGGGFFWYAb4mGyRCbos3V2Y+cqdc";
+
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(expected.unprotected_as_bytes() == &raw_hash[..]);
+            assert!(hash_password_verify(&expected, &password, iterations, memory).is_ok());
+        }
+
+        #[test]
+        fn test_encoding_and_verify_5() {
+            let iterations: u32 = 4;
+            let memory: u32 = 256;
+            let password = Password::from_slice(b"passwordPASSWORDPassword").unwrap();
+            let raw_hash =
+                hex::decode("9b134c4c1c34e66170d1088c18be3a8e0f4a1837d4c069703ce62f85248b1e8f")
+                    .unwrap();
+            let encoded_hash = "$argon2i$v=19$m=256,t=4,p=1$c29tZXNhbHRzb21lc2FsdA$mxNMTBw05mFw0QiMGL46jg9KGDfUwGlwPOYvhSSLHo8";
+
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(expected.unprotected_as_bytes() == &raw_hash[..]);
+            assert!(hash_password_verify(&expected, &password, iterations, memory).is_ok());
+        }
+    }
+
+    mod test_password_hash {
+        use super::*;
+
+        #[test]
+        fn test_password_hash_eq() {
+            let password_hash =
+                PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 1 << 16).unwrap();
+            assert_eq!(password_hash.len(), 32);
+            assert_eq!(password_hash.unprotected_as_bytes(), &[0u8; 32]);
+
+            let password_hash_again =
+                PasswordHash::from_encoded(password_hash.unprotected_as_encoded()).unwrap();
+            assert_eq!(password_hash, password_hash_again);
+        }
+
+        #[test]
+        fn test_password_hash_ne() {
+            let password_hash =
+                PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 1 << 16).unwrap();
+            assert_eq!(password_hash.len(), 32);
+            assert_eq!(password_hash.unprotected_as_bytes(), &[0u8; 32]);
+
+            let password_hash_again =
+                PasswordHash::from_slice(&[1u8; 32], &[0u8; 16], 3, 1 << 16).unwrap();
+
+            assert_ne!(password_hash, password_hash_again);
+        }
+
+        #[test]
+        #[cfg(feature = "test_utils")]
+        fn test_arbitrary() {
+            use quickcheck::{Arbitrary, Gen};
+
+            let mut g = Gen::new(128);
+            for _ in 0..128 {
+                let password_hash = PasswordHash::arbitrary(&mut g);
+                let password_hash_again =
+                    PasswordHash::from_encoded(password_hash.unprotected_as_encoded()).unwrap();
+                assert_eq!(password_hash, password_hash_again);
+                assert_eq!(password_hash.clone(), password_hash);
+            }
+        }
+
+        #[test]
+        fn test_valid_encoded_password() {
+            let valid = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            assert!(PasswordHash::from_encoded(valid).is_ok());
+        }
+
+        #[test]
+        fn test_bad_encoding_missing_dollar() {
+            let first_missing = "argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let second_missing = "$argon2iv=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let third_missing = "$argon2i$v=19m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let fourth_missing = "$argon2i$v=19$m=65536,t=3,p=1cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let fifth_missing = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcAMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(first_missing).is_err());
+            assert!(PasswordHash::from_encoded(second_missing).is_err());
+            assert!(PasswordHash::from_encoded(third_missing).is_err());
+            assert!(PasswordHash::from_encoded(fourth_missing).is_err());
+            assert!(PasswordHash::from_encoded(fifth_missing).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_missing_comma() {
+            let first_missing = "$argon2i$v=19$m=65536t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let second_missing = "$argon2i$v=19$m=65536,t=3p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(first_missing).is_err());
+            assert!(PasswordHash::from_encoded(second_missing).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_missing_equals() {
+            let first_missing = "$argon2i$v19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let second_missing = "$argon2$iv=19$m65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let third_missing = "$argon2i$v=19$m=65536,t3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let fourth_missing = "$argon2i$v=19$m=65536,t=3,p1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(first_missing).is_err());
+            assert!(PasswordHash::from_encoded(second_missing).is_err());
+            assert!(PasswordHash::from_encoded(third_missing).is_err());
+            assert!(PasswordHash::from_encoded(fourth_missing).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_swapped_param_separators() {
+            let comma_for_equals = "$argon2i$v=19$m=65536,t,3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let equals_for_comma = "$argon2i$v=19$m=65536=t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let double_equals = "$argon2i$v=19$m==65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(comma_for_equals).is_err());
+            assert!(PasswordHash::from_encoded(equals_for_comma).is_err());
+            assert!(PasswordHash::from_encoded(double_equals).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_whitespace() {
+            let first = "$argon2i$v=19$m=65536,t=3, p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let second = " $argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let third = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA ";
+
+            assert!(PasswordHash::from_encoded(first).is_err());
+            assert!(PasswordHash::from_encoded(second).is_err());
+            assert!(PasswordHash::from_encoded(third).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_threads() {
+            let one = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let zero = "$argon2i$v=19$m=65536,t=3,p=0$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let two = "$argon2i$v=19$m=65536,t=3,p=2$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(one).is_ok());
+            assert!(PasswordHash::from_encoded(zero).is_err());
+            assert!(PasswordHash::from_encoded(two).is_err());
+        }
+
+        #[test]
+        fn test_argon2id_encoding_lanes() {
+            let one = "$argon2id$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let zero = "$argon2id$v=19$m=65536,t=3,p=0$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let max = "$argon2id$v=19$m=134217720,t=3,p=16777215$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let above_max = "$argon2id$v=19$m=134217728,t=3,p=16777216$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let min_memory = "$argon2id$v=19$m=32,t=3,p=4$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let less_memory = "$argon2id$v=19$m=31,t=3,p=4$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(one).is_ok());
+            assert!(PasswordHash::from_encoded(zero).is_err());
+            assert!(PasswordHash::from_encoded(max).is_ok());
+            assert!(PasswordHash::from_encoded(above_max).is_err());
+            assert!(PasswordHash::from_encoded(min_memory).is_ok());
+            assert!(PasswordHash::from_encoded(less_memory).is_err());
+        }
+
+        #[test]
+        fn test_argon2id_encoding_roundtrip() {
+            let encoded = "$argon2id$v=19$m=65536,t=3,p=4$c29tZXNhbHRTT01FU0FMVA$RCM5C9LTumV4x20v7u6jDMHHbTpoYOFjshZO84A7yDk";
+            let password_hash = PasswordHash::from_encoded(encoded).unwrap();
+            assert_eq!(password_hash.variant, Variant::Argon2id);
+            assert_eq!(password_hash.lanes, 4);
+
+            let from_parts = PasswordHash::from_parts(
+                Variant::Argon2id,
+                password_hash.unprotected_as_bytes(),
+                password_hash.salt.as_ref(),
+                3,
+                65536,
+                4,
+            )
+            .unwrap();
+            assert_eq!(from_parts.unprotected_as_encoded(), encoded);
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_memory() {
+            let exact_min = "$argon2i$v=19$m=8,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let less = "$argon2i$v=19$m=7,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            // Throws error during parsing as u32
+            let u32_overflow = format!("$argon2i$v=19$m={},t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA", u64::MAX);
+
+            assert!(PasswordHash::from_encoded(exact_min).is_ok());
+            assert!(PasswordHash::from_encoded(less).is_err());
+            assert!(PasswordHash::from_encoded(&u32_overflow).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_iterations() {
+            let exact_min = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let less = "$argon2i$v=19$m=65536,t=2,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            // Throws error during parsing as u32
+            let u32_overflow = format!("$argon2i$v=19$m=65536,t={},p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA", u64::MAX);
+
+            assert!(PasswordHash::from_encoded(exact_min).is_ok());
+            assert!(PasswordHash::from_encoded(less).is_err());
+            assert!(PasswordHash::from_encoded(&u32_overflow).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_algo() {
+            let argon2 = "$argon2$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let argon2di = "$argon2di$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let argon2d = "$argon2d$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let nothing = "$$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(argon2d).is_err());
+            assert!(PasswordHash::from_encoded(argon2).is_err());
+            assert!(PasswordHash::from_encoded(argon2di).is_err());
+            assert!(PasswordHash::from_encoded(nothing).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_version() {
+            let v13 = "$argon2i$v=13$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let v0 = "$argon2i$v=0$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let nothing = "$argon2i$v=$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(v13).is_err());
+            assert!(PasswordHash::from_encoded(v0).is_err());
+            assert!(PasswordHash::from_encoded(nothing).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_order() {
+            let version_first = "$v=19$argon2i$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let t_before_m = "$argon2i$v=19$t=3,m=65536,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let p_before_t = "$argon2i$v=19$m=65536,p=1,t=3$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let p_before_m = "$argon2i$v=19$p=1,m=65536,t=3$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let pass_before_salt = "$argon2i$v=19$m=65536,t=3,p=1$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA$cHBwcHBwcHBwcHBwcHBwcA";
+            let salt_first = "$cHBwcHBwcHBwcHBwcHBwcA$argon2i$v=19$m=65536,t=3,p=1$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let pass_first = "$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA";
+
+            assert!(PasswordHash::from_encoded(version_first).is_err());
+            assert!(PasswordHash::from_encoded(t_before_m).is_err());
+            assert!(PasswordHash::from_encoded(p_before_t).is_err());
+            assert!(PasswordHash::from_encoded(p_before_m).is_err());
+            assert!(PasswordHash::from_encoded(pass_before_salt).is_err());
+            assert!(PasswordHash::from_encoded(salt_first).is_err());
+            assert!(PasswordHash::from_encoded(pass_first).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_salt() {
+            let exact = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let nothing =
+                "$argon2i$v=19$m=65536,t=3,p=1$$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let above = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcAA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(exact).is_ok());
+            assert!(PasswordHash::from_encoded(nothing).is_err());
+            assert!(PasswordHash::from_encoded(above).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_invalid_password() {
+            let exact = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let nothing = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$";
+            let above = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAA";
+
+            assert!(PasswordHash::from_encoded(exact).is_ok());
+            assert!(PasswordHash::from_encoded(nothing).is_err());
+            assert!(PasswordHash::from_encoded(above).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_bad_parsing_integers() {
+            let j_instead_of_mem = "$argon2i$v=19$m=j,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(j_instead_of_mem).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_first_not_empty() {
+            // Nothing should precede "$argon2i"
+            let non_empty_first = "apples$argon2i$v=19$m=4096,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(non_empty_first).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_bad_p() {
+            let p_is_j = "$argon2i$v=19$m=4096,t=3,j=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let p_gone = "$argon2i$v=19$m=4096,t=3,=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(p_is_j).is_err());
+            assert!(PasswordHash::from_encoded(p_gone).is_err());
+        }
+
+        #[test]
+        fn test_decimal_value_reject_leading_zeroes() {
+            // https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md#decimal-encoding
+            // According to the specification, the decimal parameters may not start with 0, if there is more than
+            // one character in the string. .parse::<u32>() will ignore leading 0's, so it will parse "0032" -> 32u32.
+            // Test here that these cases are detected and rejected by returning an error.
+            let valid = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid0 = "$argon2i$v=019$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid1 = "$argon2i$v=19$m=065536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid2 = "$argon2i$v=19$m=65536,t=03,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid3 = "$argon2i$v=19$m=65536,t=3,p=01$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(valid).is_ok());
+            assert!(PasswordHash::from_encoded(invalid0).is_err());
+            assert!(PasswordHash::from_encoded(invalid1).is_err());
+            assert!(PasswordHash::from_encoded(invalid2).is_err());
+            assert!(PasswordHash::from_encoded(invalid3).is_err());
+        }
+
+        #[test]
+        fn test_decimal_value_reject_sign() {
+            // .parse::<u32>() accepts a leading '+', so "+3" -> 3u32.
+            let invalid0 = "$argon2i$v=19$m=+65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid1 = "$argon2i$v=19$m=65536,t=+3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let invalid2 = "$argon2i$v=19$m=65536,t=3,p=+1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(invalid0).is_err());
+            assert!(PasswordHash::from_encoded(invalid1).is_err());
+            assert!(PasswordHash::from_encoded(invalid2).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_padded_b64() {
+            let padded_salt = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA==$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            let padded_password = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA=";
+
+            assert!(PasswordHash::from_encoded(padded_salt).is_err());
+            assert!(PasswordHash::from_encoded(padded_password).is_err());
+        }
+
+        #[test]
+        fn test_bad_encoding_non_canonical_b64() {
+            // The last character of the salt has non-zero trailing bits.
+            let non_canonical = "$argon2i$v=19$m=65536,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcB$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+
+            assert!(PasswordHash::from_encoded(non_canonical).is_err());
+        }
+
+        #[test]
+        fn test_bounds_max_min_encoded_len() {
+            let minimum = "$argon2i$v=19$m=8,t=3,p=1$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            assert_eq!(minimum.len(), PasswordHash::MIN_ENCODED_LEN);
+            let maximum = "$argon2id$v=19$m=1111111111,t=1111111111,p=11111111$cHBwcHBwcHBwcHBwcHBwcA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            assert_eq!(maximum.len(), PasswordHash::MAX_ENCODED_LEN);
+
+            // salt removed one char
+            let less = "$argon2i$v=19$m=8,t=3,p=1$cHBwcHBwcHBwcHBwcHBwc$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            assert_eq!(less.len(), PasswordHash::MIN_ENCODED_LEN - 1);
+            // salt added one char
+            let more = "$argon2id$v=19$m=1111111111,t=1111111111,p=11111111$cHBwcHBwcHBwcHBwcHBwcAA$MDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDA";
+            assert_eq!(more.len(), PasswordHash::MAX_ENCODED_LEN + 1);
+
+            assert!(PasswordHash::from_encoded(minimum).is_ok());
+            assert!(PasswordHash::from_encoded(maximum).is_ok());
+            assert!(PasswordHash::from_encoded(less).is_err());
+            assert!(PasswordHash::from_encoded(more).is_err());
+        }
+
+        #[test]
+        fn test_from_slice_password() {
+            assert!(PasswordHash::from_slice(&[0u8; 31], &[0u8; 16], 3, 1 << 16).is_err());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 1 << 16).is_ok());
+            assert!(PasswordHash::from_slice(&[0u8; 33], &[0u8; 16], 3, 1 << 16).is_err());
+        }
+
+        #[test]
+        fn test_from_slice_salt() {
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 15], 3, 1 << 16).is_err());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 1 << 16).is_ok());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 17], 3, 1 << 16).is_err());
+        }
+
+        #[test]
+        fn test_from_slice_mem() {
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 7).is_err());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 8).is_ok());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 9).is_ok());
+        }
+
+        #[test]
+        fn test_from_slice_bad_iter() {
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 2, 1 << 16).is_err());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 3, 1 << 16).is_ok());
+            assert!(PasswordHash::from_slice(&[0u8; 32], &[0u8; 16], 4, 1 << 16).is_ok());
+        }
+
+        // Proptests. Only executed when NOT testing no_std.
+        #[cfg(feature = "safe_api")]
+        mod proptest {
+            use super::*;
+            use quickcheck::{Arbitrary, Gen};
+
+            /// Characters that are meaningful to the encoding, used when mutating it.
+            const MUTATION_CHARS: &[u8] = b"$,=0123456789mtpv+/aAzZ- ";
+
+            #[derive(Clone, Debug)]
+            /// An encoded password hash that is valid or has a small, structure-aware
+            /// mutation applied to it.
+            struct MaybeEncodedPasswordHash(String);
+
+            impl Arbitrary for MaybeEncodedPasswordHash {
+                fn arbitrary(g: &mut Gen) -> Self {
+                    let password_hash: Vec<u8> =
+                        (0..PWHASH_LENGTH).map(|_| u8::arbitrary(g)).collect();
+                    let salt: Vec<u8> = (0..SALT_LENGTH).map(|_| u8::arbitrary(g)).collect();
+                    let iterations = core::cmp::max(u32::arbitrary(g), MIN_ITERATIONS);
+                    let memory = core::cmp::max(u32::arbitrary(g), MIN_MEMORY);
+
+                    let (variant, lanes) = if bool::arbitrary(g) {
+                        (Variant::Argon2i, LANES)
+                    } else {
+                        let max_lanes = core::cmp::min(MAX_LANES, memory / 8);
+                        (Variant::Argon2id, u32::arbitrary(g) % max_lanes + 1)
+                    };
+
+                    let mut encoded = PasswordHash::encode(
+                        variant,
+                        &password_hash,
+                        &salt,
+                        iterations,
+                        memory,
+                        lanes,
+                    )
+                    .into_bytes();
+                    let idx = usize::arbitrary(g) % encoded.len();
+                    let mutation_char = *g.choose(MUTATION_CHARS).unwrap();
+
+                    match u8::arbitrary(g) % 6 {
+                        0 => (),
+                        1 => encoded[idx] = mutation_char,
+                        2 => encoded.insert(idx, mutation_char),
+                        3 => {
+                            encoded.remove(idx);
+                        }
+                        4 => {
+                            // Swap two fields separated by '$' or ','.
+                            let text = String::from_utf8(encoded).unwrap();
+                            let separator = if bool::arbitrary(g) { '$' } else { ',' };
+                            let mut fields: Vec<&str> = text.split(separator).collect();
+                            let a = usize::arbitrary(g) % fields.len();
+                            let b = usize::arbitrary(g) % fields.len();
+                            fields.swap(a, b);
+                            encoded = fields.join(&separator.to_string()).into_bytes();
+                        }
+                        _ => {
+                            // Add leading zeroes to one of the parameters.
+                            let text = String::from_utf8(encoded).unwrap();
+                            let param = *g.choose(&["m=", "t=", "p="]).unwrap();
+                            encoded = text.replacen(param, &format!("{}0", param), 1).into_bytes();
+                        }
+                    }
+
+                    Self(String::from_utf8(encoded).unwrap())
+                }
+            }
+
+            quickcheck! {
+                /// Parsing arbitrary strings never panics.
+                fn prop_from_encoded_arbitrary_no_panic(encoded: String) -> bool {
+                    let _ = PasswordHash::from_encoded(&encoded);
+                    true
+                }
+            }
+
+            quickcheck! {
+                /// Any string that is accepted is the canonical encoding of the parsed
+                /// password hash, so parse -> serialize -> parse round-trips.
+                fn prop_from_encoded_round_trip(encoded: MaybeEncodedPasswordHash) -> bool {
+                    let parsed = match PasswordHash::from_encoded(&encoded.0) {
+                        Ok(parsed) => parsed,
+                        Err(_) => return true,
+                    };
+
+                    let serialized = PasswordHash::from_parts(
+                        parsed.variant,
+                        parsed.unprotected_as_bytes(),
+                        parsed.salt.as_ref(),
+                        parsed.iterations,
+                        parsed.memory,
+                        parsed.lanes,
+                    )
+                    .unwrap();
+                    let reparsed = PasswordHash::from_encoded(serialized.unprotected_as_encoded()).unwrap();
+
+                    serialized.unprotected_as_encoded() == encoded.0
+                        && reparsed == parsed
+                        && reparsed.iterations == parsed.iterations
+                        && reparsed.memory == parsed.memory
+                }
+            }
+
+            quickcheck! {
+                /// If valid params then it's always valid to encode/decode.
+                fn prop_always_produce_valid_encoding(password: Vec<u8>, salt: Vec<u8>, iterations: u32, memory: u32) -> bool {
+                    let res = PasswordHash::from_slice(&password[..], &salt[..], iterations, memory);
+                    if res.is_ok() {
+                        assert!(PasswordHash::from_encoded(res.unwrap().unprotected_as_encoded()).is_ok());
+                    }
+
+                    true
+                }
+            }
+        }
+    }
+
+    mod test_pwhash_and_verify {
+        use super::*;
+
+        #[test]
+        fn test_argon2id_encoding_and_verify() {
+            // Generated with the Python `cryptography` package.
+            let password = Password::from_slice(b"password").unwrap();
+            let encoded_hash = "$argon2id$v=19$m=65536,t=3,p=4$c29tZXNhbHRTT01FU0FMVA$RCM5C9LTumV4x20v7u6jDMHHbTpoYOFjshZO84A7yDk";
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(hash_password_argon2id_verify(&expected, &password, 3, 65536, 4).is_ok());
+            assert!(hash_password_argon2id_verify(&expected, &password, 3, 65536, 1).is_err());
+
+            let password = Password::from_slice(b"passwordPASSWORDPassword").unwrap();
+            let encoded_hash = "$argon2id$v=19$m=64,t=3,p=1$c29tZXNhbHRTT01FU0FMVA$7Rk0OrYJhJahg66zguanbtqCu2ObALCTwZ2Tc4U9VOE";
+            let expected = PasswordHash::from_encoded(encoded_hash).unwrap();
+            assert!(hash_password_argon2id_verify(&expected, &password, 3, 64, 1).is_ok());
+        }
+
+        #[test]
+        fn test_verify_stored() {
+            let password = Password::from_slice(b"password").unwrap();
+            let argon2id = PasswordHash::from_encoded("$argon2id$v=19$m=65536,t=3,p=4$c29tZXNhbHRTT01FU0FMVA$RCM5C9LTumV4x20v7u6jDMHHbTpoYOFjshZO84A7yDk").unwrap();
+            assert_eq!(
+                (argon2id.iterations(), argon2id.memory(), argon2id.lanes()),
+                (3, 65536, 4)
+            );
+            assert!(hash_password_verify_stored(&argon2id, &password).is_ok());
+            let other = Password::from_slice(b"passwore").unwrap();
+            assert!(hash_password_verify_stored(&argon2id, &other).is_err());
+
+            let argon2i = hash_password(&password, 4, 64).unwrap();
+            let stored = PasswordHash::from_encoded(argon2i.unprotected_as_encoded()).unwrap();
+            assert_eq!(
+                (stored.iterations(), stored.memory(), stored.lanes()),
+                (4, 64, 1)
+            );
+            assert!(hash_password_verify_stored(&stored, &password).is_ok());
+            assert!(hash_password_verify_stored(&stored, &other).is_err());
+
+            // The stored parameters are the ones verified with.
+            let changed =
+                PasswordHash::from_encoded(&argon2i.unprotected_as_encoded().replace("t=4", "t=3"))
+                    .unwrap();
+            assert!(hash_password_verify_stored(&changed, &password).is_err());
+        }
+
+        #[test]
+        fn test_argon2id_verify() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let dk = hash_password_argon2id(&password, 3, 4096, 4).unwrap();
+            assert!(dk
+                .unprotected_as_encoded()
+                .starts_with("$argon2id$v=19$m=4096,t=3,p=4$"));
+
+            assert!(hash_password_argon2id_verify(&dk, &password, 3, 4096, 4).is_ok());
+            assert!(hash_password_argon2id_verify(&dk, &password, 3, 4096, 2).is_err());
+            assert!(hash_password_argon2id_verify(&dk, &password, 4, 4096, 4).is_err());
+            assert!(hash_password_argon2id_verify(
+                &dk,
+                &Password::from_slice(&[1u8; 64]).unwrap(),
+                3,
+                4096,
+                4
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn test_argon2id_verify_rejects_other_variant() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let argon2i_dk = hash_password(&password, 3, 4096).unwrap();
+            let argon2id_dk = hash_password_argon2id(&password, 3, 4096, 1).unwrap();
+
+            assert!(hash_password_argon2id_verify(&argon2i_dk, &password, 3, 4096, 1).is_err());
+            assert!(hash_password_verify(&argon2id_dk, &password, 3, 4096).is_err());
+        }
+
+        #[test]
+        fn test_argon2id_progress() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let mut reports = 0;
+            let dk = hash_password_argon2id_with_progress(
+                &password,
+                3,
+                4096,
+                2,
+                &mut |completed: u64, total: Option<u64>| {
+                    reports += 1;
+                    assert_eq!(completed, reports);
+                    assert_eq!(total, Some(12));
+                    true
+                },
+            )
+            .unwrap();
+            assert_eq!(reports, 12);
+
+            let mut cancel = |completed: u64, _: Option<u64>| completed < 6;
+            assert!(hash_password_argon2id_verify_with_progress(
+                &dk,
+                &password,
+                3,
+                4096,
+                2,
+                &mut cancel
+            )
+            .is_err());
+            assert!(
+                hash_password_argon2id_with_progress(&password, 3, 4096, 2, &mut cancel).is_err()
+            );
+        }
+
+        #[test]
+        fn test_argon2id_invalid_parameters() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            assert!(hash_password_argon2id(&password, MIN_ITERATIONS - 1, 4096, 1).is_err());
+            assert!(hash_password_argon2id(&password, MIN_ITERATIONS, 31, 4).is_err());
+            assert!(hash_password_argon2id(&password, MIN_ITERATIONS, 32, 4).is_ok());
+            assert!(hash_password_argon2id(&password, MIN_ITERATIONS, 4096, 0).is_err());
+            assert!(
+                hash_password_argon2id(&password, MIN_ITERATIONS, 4096, MAX_LANES + 1).is_err()
+            );
+        }
+
+        #[test]
+        fn test_argon2i_progress() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let mut reports = 0;
+            let dk = hash_password_with_progress(
+                &password,
+                3,
+                4096,
+                &mut |completed: u64, total: Option<u64>| {
+                    reports += 1;
+                    assert_eq!(completed, reports);
+                    assert_eq!(total, Some(12));
+                    true
+                },
+            )
+            .unwrap();
+            assert_eq!(reports, 12);
+
+            assert!(hash_password_verify(&dk, &password, 3, 4096).is_ok());
+            assert!(
+                hash_password_verify_with_progress(&dk, &password, 3, 4096, &mut NoProgress)
+                    .is_ok()
+            );
+        }
+
+        #[test]
+        fn test_argon2i_progress_cancel() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let mut cancel = |completed: u64, _: Option<u64>| completed < 6;
+
+            assert!(hash_password_with_progress(&password, 3, 4096, &mut cancel).is_err());
+
+            let dk = hash_password(&password, 3, 4096).unwrap();
+            assert!(
+                hash_password_verify_with_progress(&dk, &password, 3, 4096, &mut cancel).is_err()
+            );
+        }
+
+        #[test]
+        fn test_argon2i_verify() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let dk = hash_password(&password, 3, 4096).unwrap();
+
+            assert!(hash_password_verify(&dk, &password, 3, 4096).is_ok());
+        }
+
+        #[test]
+        fn test_argon2i_verify_err_modified_password() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+
+            let dk = hash_password(&password, 3, 4096).unwrap();
+            let mut pwd_mod = dk.unprotected_as_bytes().to_vec();
+            pwd_mod[0..32].copy_from_slice(&[0u8; 32]);
+            let modified = PasswordHash::from_slice(&pwd_mod, dk.salt.as_ref(), 3, 4096).unwrap();
+
+            assert!(hash_password_verify(&modified, &password, 3, 4096).is_err());
+        }
+
+        #[test]
+        fn test_argon2i_verify_err_modified_salt() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+
+            let dk = hash_password(&password, 3, 4096).unwrap();
+            let mut salt_mod = dk.salt.as_ref().to_vec();
+            salt_mod[0..16].copy_from_slice(&[0u8; 16]);
+            let modified =
+                PasswordHash::from_slice(&dk.unprotected_as_bytes(), &salt_mod, 3, 4096).unwrap();
+
+            assert!(hash_password_verify(&modified, &password, 3, 4096).is_err());
+        }
+
+        #[test]
+        fn test_argon2i_verify_err_modified_salt_and_password() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+
+            let dk = hash_password(&password, 3, 4096).unwrap();
+            let mut pwd_mod = dk.unprotected_as_bytes().to_vec();
+            let mut salt_mod = dk.salt.as_ref().to_vec();
+            pwd_mod[0..32].copy_from_slice(&[0u8; 32]);
+            salt_mod[0..16].copy_from_slice(&[0u8; 16]);
+            let modified = PasswordHash::from_slice(&pwd_mod, &salt_mod, 3, 4096).unwrap();
+
+            assert!(hash_password_verify(&modified, &password, 3, 4096).is_err());
+        }
+
+        #[test]
+        fn test_argon2i_invalid_iterations() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let password_hash = PasswordHash::from_encoded("$argon2i$v=19$m=65536,t=3,p=1$c29tZXNhbHRzb21lc2FsdA$fRsRY9PAt5H+qAKuXRzL0/6JbFShsCd62W5aHzESk/c").unwrap();
+            assert!(hash_password(&password, MIN_ITERATIONS - 1, 4096).is_err());
+            assert!(
+                hash_password_verify(&password_hash, &password, MIN_ITERATIONS - 1, 4096).is_err()
+            );
+        }
+
+        #[test]
+        fn test_argon2i_invalid_memory() {
+            let password = Password::from_slice(&[0u8; 64]).unwrap();
+            let password_hash = PasswordHash::from_encoded("$argon2i$v=19$m=65536,t=3,p=1$c29tZXNhbHRzb21lc2FsdA$fRsRY9PAt5H+qAKuXRzL0/6JbFShsCd62W5aHzESk/c").unwrap();
+            assert!(hash_password(&password, MIN_ITERATIONS, MIN_MEMORY - 1).is_err());
+            assert!(hash_password_verify(
+                &password_hash,
+                &password,
+                MIN_ITERATIONS,
+                MIN_MEMORY - 1
+            )
+            .is_err());
+        }
+    }
+}
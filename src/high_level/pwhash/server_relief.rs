@@ -0,0 +1,274 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Password hashing, where the client computes Argon2i.
+//!
+//! # Use case:
+//! `orion::pwhash::server_relief` can be used when a server cannot afford to
+//! compute Argon2i with strong cost parameters for every login, by having the
+//! client compute it instead. The server then only performs a cheap keyed step.
+//!
+//! An example of this could be a login service with many users, where the
+//! clients run on hardware that is able to spend the memory and time on
+//! Argon2i.
+//!
+//! # About:
+//! - The client hashes the password with Argon2i, using the salt and cost
+//!   parameters it receives from the server, into a 32-byte [`PreHash`]
+//!   with [`client_hash()`].
+//! - The server authenticates the [`PreHash`] with BLAKE2b-256 in keyed mode,
+//!   using a secret key only it holds. The resulting [`Tag`] is stored by
+//!   [`server_hash()`] and checked by [`server_verify()`].
+//!
+//! # Parameters:
+//! - `password`: The password to be hashed.
+//! - `salt`: The salt of the user, which the server stores and sends to the client.
+//! - `iterations`: Iterations cost parameter for Argon2i.
+//! - `memory`: Memory (in kibibytes (KiB)) cost parameter for Argon2i.
+//! - `server_key`: The secret key held by the server.
+//! - `pre_hash`: The [`PreHash`] sent by the client.
+//! - `expected`: The [`Tag`] stored by the server.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `memory` is less than 8.
+//! - `iterations` is less than 3.
+//! - The length of the `password` is greater than `u32::MAX`.
+//! - The length of the `salt` is not 16 bytes.
+//! - The `server_key` is less than 32 bytes or greater than 64 bytes.
+//! - The [`Tag`] calculated from `pre_hash` does not match `expected`.
+//!
+//! # Security:
+//! - A [`PreHash`] is all that is needed to log in as its user, so it must only be
+//!   sent over a confidential and authenticated channel, and never be stored.
+//! - If the stored [`Tag`]s are disclosed, but the `server_key` is not, they cannot be
+//!   used to guess passwords offline. If the `server_key` is disclosed as well, Argon2i
+//!   must still be computed for every guess.
+//! - The server cannot check which cost parameters the client used. It must always send
+//!   the same salt and cost parameters for a given user, and store them with its [`Tag`].
+//! - The salt should always be generated using a CSPRNG. [`Salt::default()`]
+//!   can be used for this, it will generate a [`Salt`] of 16 bytes.
+//! - The `server_key` should always be generated using a CSPRNG.
+//!   [`SecretKey::default()`] can be used for this; it will generate a
+//!   [`SecretKey`] of 32 bytes.
+//! - Choosing the correct cost parameters is important for security. Please refer to
+//!   [libsodium's docs](https://download.libsodium.org/doc/password_hashing/default_phf#guidelines-for-choosing-the-parameters)
+//!   for a description of how to do this.
+//!
+//! # Example:
+//! ```rust
+//! use orion::pwhash::{server_relief, Password};
+//!
+//! let server_key = server_relief::SecretKey::default();
+//!
+//! // Sign-up: The server generates a salt and sends it to the client,
+//! // along with the cost parameters.
+//! let salt = server_relief::Salt::default();
+//! let password = Password::from_slice(b"Secret password")?;
+//! let pre_hash = server_relief::client_hash(&password, &salt, 3, 1<<16)?;
+//! let stored = server_relief::server_hash(&server_key, &pre_hash)?;
+//!
+//! // Login: The client computes the same `PreHash` from the stored salt
+//! // and cost parameters.
+//! let pre_hash = server_relief::client_hash(&password, &salt, 3, 1<<16)?;
+//! assert!(server_relief::server_verify(&stored, &server_key, &pre_hash).is_ok());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`PreHash`]: struct.PreHash.html
+//! [`Tag`]: struct.Tag.html
+//! [`Salt`]: struct.Salt.html
+//! [`Salt::default()`]: struct.Salt.html
+//! [`SecretKey`]: struct.SecretKey.html
+//! [`SecretKey::default()`]: struct.SecretKey.html
+//! [`client_hash()`]: fn.client_hash.html
+//! [`server_hash()`]: fn.server_hash.html
+//! [`server_verify()`]: fn.server_verify.html
+
+use super::{Password, MIN_ITERATIONS, PWHASH_LENGTH, SALT_LENGTH};
+pub use crate::high_level::hltypes::{Salt, SecretKey, Tag};
+use crate::{errors::UnknownCryptoError, hazardous::kdf::argon2i};
+
+construct_secret_key! {
+    /// A type to represent the `PreHash` that a client computes with Argon2i.
+    ///
+    /// # Errors:
+    /// An error will be returned if:
+    /// - `slice` is not 32 bytes.
+    ///
+    /// # Panics:
+    /// A panic will occur if:
+    /// - Failure to generate random bytes securely.
+    (PreHash, test_pre_hash, PWHASH_LENGTH, PWHASH_LENGTH, PWHASH_LENGTH)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Hash a password into a `PreHash` using Argon2i. This is done by the client.
+pub fn client_hash(
+    password: &Password,
+    salt: &Salt,
+    iterations: u32,
+    memory: u32,
+) -> Result<PreHash, UnknownCryptoError> {
+    if salt.len() != SALT_LENGTH {
+        return Err(UnknownCryptoError);
+    }
+    if iterations < MIN_ITERATIONS {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut pre_hash = PreHash::from_slice(&[0u8; PWHASH_LENGTH])?;
+    argon2i::derive_key(
+        password.unprotected_as_bytes(),
+        salt.as_ref(),
+        iterations,
+        memory,
+        None,
+        None,
+        &mut pre_hash.value,
+    )?;
+
+    Ok(pre_hash)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticate a `PreHash` using BLAKE2b-256 in keyed mode. This is done by the server.
+pub fn server_hash(server_key: &SecretKey, pre_hash: &PreHash) -> Result<Tag, UnknownCryptoError> {
+    crate::auth::authenticate(server_key, pre_hash.unprotected_as_bytes())
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Authenticate and verify a `PreHash` using BLAKE2b-256 in keyed mode. This is done by the server.
+pub fn server_verify(
+    expected: &Tag,
+    server_key: &SecretKey,
+    pre_hash: &PreHash,
+) -> Result<(), UnknownCryptoError> {
+    crate::auth::authenticate_verify(expected, server_key, pre_hash.unprotected_as_bytes())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::kdf::argon2i::MIN_MEMORY;
+
+    #[test]
+    fn test_server_relief_hash_and_verify() {
+        let server_key = SecretKey::default();
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+
+        let pre_hash = client_hash(&password, &salt, 3, 64).unwrap();
+        let stored = server_hash(&server_key, &pre_hash).unwrap();
+
+        let pre_hash_login = client_hash(&password, &salt, 3, 64).unwrap();
+        assert!(server_verify(&stored, &server_key, &pre_hash_login).is_ok());
+    }
+
+    #[test]
+    fn test_server_relief_matches_kdf_and_auth() {
+        let server_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let salt = Salt::from_slice(&[2u8; 16]).unwrap();
+        let password = Password::from_slice(&[3u8; 32]).unwrap();
+
+        let pre_hash = client_hash(&password, &salt, 3, 64).unwrap();
+        let dk = crate::kdf::derive_key(&password, &salt, 3, 64, 32).unwrap();
+        assert_eq!(pre_hash, dk.unprotected_as_bytes());
+
+        let stored = server_hash(&server_key, &pre_hash).unwrap();
+        let expected = crate::auth::authenticate(&server_key, dk.unprotected_as_bytes()).unwrap();
+        assert_eq!(stored, expected);
+    }
+
+    #[test]
+    fn test_server_relief_err_diff_password() {
+        let server_key = SecretKey::default();
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+        let other = Password::from_slice(b"Secret passwore").unwrap();
+
+        let stored =
+            server_hash(&server_key, &client_hash(&password, &salt, 3, 64).unwrap()).unwrap();
+        let pre_hash = client_hash(&other, &salt, 3, 64).unwrap();
+        assert!(server_verify(&stored, &server_key, &pre_hash).is_err());
+    }
+
+    #[test]
+    fn test_server_relief_err_diff_params() {
+        let server_key = SecretKey::default();
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+
+        let stored =
+            server_hash(&server_key, &client_hash(&password, &salt, 3, 64).unwrap()).unwrap();
+        let pre_hash = client_hash(&password, &salt, 4, 64).unwrap();
+        assert!(server_verify(&stored, &server_key, &pre_hash).is_err());
+        let pre_hash = client_hash(&password, &salt, 3, 72).unwrap();
+        assert!(server_verify(&stored, &server_key, &pre_hash).is_err());
+        let pre_hash = client_hash(&password, &Salt::default(), 3, 64).unwrap();
+        assert!(server_verify(&stored, &server_key, &pre_hash).is_err());
+    }
+
+    #[test]
+    fn test_server_relief_err_diff_server_key() {
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+        let pre_hash = client_hash(&password, &salt, 3, 64).unwrap();
+
+        let stored = server_hash(&SecretKey::default(), &pre_hash).unwrap();
+        assert!(server_verify(&stored, &SecretKey::default(), &pre_hash).is_err());
+    }
+
+    #[test]
+    fn test_server_relief_bad_salt_length() {
+        let password = Password::from_slice(b"Secret password").unwrap();
+
+        assert!(client_hash(&password, &Salt::from_slice(&[0u8; 15]).unwrap(), 3, 64).is_err());
+        assert!(client_hash(&password, &Salt::from_slice(&[0u8; 16]).unwrap(), 3, 64).is_ok());
+        assert!(client_hash(&password, &Salt::from_slice(&[0u8; 17]).unwrap(), 3, 64).is_err());
+    }
+
+    #[test]
+    fn test_server_relief_bad_params() {
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+
+        assert!(client_hash(&password, &salt, MIN_ITERATIONS - 1, 64).is_err());
+        assert!(client_hash(&password, &salt, MIN_ITERATIONS, MIN_MEMORY - 1).is_err());
+        assert!(client_hash(&password, &salt, MIN_ITERATIONS, MIN_MEMORY).is_ok());
+    }
+
+    #[test]
+    fn test_server_relief_bad_server_key() {
+        let salt = Salt::default();
+        let password = Password::from_slice(b"Secret password").unwrap();
+        let pre_hash = client_hash(&password, &salt, 3, 64).unwrap();
+        let stored = server_hash(&SecretKey::from_slice(&[0u8; 32]).unwrap(), &pre_hash).unwrap();
+
+        let short_key = SecretKey::from_slice(&[0u8; 31]).unwrap();
+        assert!(server_hash(&short_key, &pre_hash).is_err());
+        assert!(server_verify(&stored, &short_key, &pre_hash).is_err());
+        let long_key = SecretKey::from_slice(&[0u8; 65]).unwrap();
+        assert!(server_hash(&long_key, &pre_hash).is_err());
+        assert!(server_verify(&stored, &long_key, &pre_hash).is_err());
+    }
+}
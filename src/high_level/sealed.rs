@@ -0,0 +1,355 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Secrets that are kept encrypted in memory.
+//!
+//! # Use case:
+//! `orion::sealed` can be used to hold secrets that live in memory for a long
+//! time, but are only used now and then, in encrypted form. This narrows the
+//! window in which the secret is present in memory in cleartext.
+//!
+//! An example of this could be a long-running server that holds the keys of
+//! many users, but only needs any one of them while serving a request from
+//! that user.
+//!
+//! # About:
+//! - A [`SealedSecret`] holds the secret encrypted with XChaCha20Poly1305,
+//!   under a key that is the BLAKE2b-256 digest of a 16 KiB random pre-key.
+//!   The pre-key is generated once per process, on first use, and is shared by
+//!   all sealed secrets, each of which has its own random nonce. Its size means
+//!   an attacker who can read memory only partially or with errors, such as
+//!   through a side-channel, has to recover every bit of it to decrypt any
+//!   secret. This is the same approach as the shielding of private keys in
+//!   OpenSSH.
+//! - The key is derived from the pre-key on every access and zeroized
+//!   afterwards. It is never stored.
+//! - [`SealedSecret::with_secret()`] and [`SealedSecret::with_secret_mut()`]
+//!   decrypt the secret into a temporary buffer, pass it to a closure and
+//!   zeroize the buffer afterwards, including when the closure panics.
+//! - After [`SealedSecret::with_secret_mut()`], the secret is encrypted again
+//!   under a new nonce. [`SealedSecret::with_secret()`] leaves the ciphertext
+//!   as it is, as encrypting the same secret under the same key again would
+//!   not add any protection, and can therefore be used through a shared
+//!   reference.
+//!
+//! # Parameters:
+//! - `secret`: The secret to seal. It may be empty.
+//! - `f`: The closure that is given access to the secret.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The sealed secret has been modified in memory, in which case it can no
+//!   longer be accessed.
+//! - The pre-key has been modified in memory, in which case no sealed secret
+//!   can be accessed.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - This is a mitigation, not a guarantee. Anyone who can read all of the
+//!   memory of the process can decrypt the secret.
+//! - The pre-key lives until the process exits and is not zeroized.
+//! - orion contains no unsafe code and does not use operating system APIs, so
+//!   the temporary buffer is not locked in memory with `mlock()` and may be
+//!   swapped to disk while a closure runs. Keep closures short.
+//! - The closure must not copy the secret out of the buffer it is given, or
+//!   the copy is not protected.
+//! - The `secret` passed to [`SealedSecret::new()`] is not zeroized. The
+//!   caller should zeroize it after sealing.
+//!
+//! # Example:
+//! ```rust
+//! use orion::sealed::SealedSecret;
+//!
+//! let mut user_key = SealedSecret::new(b"a key of some user")?;
+//!
+//! // The secret is only decrypted for the duration of the closure.
+//! let len = user_key.with_secret(|secret| secret.len())?;
+//! assert_eq!(len, 18);
+//!
+//! user_key.with_secret_mut(|secret| secret[0] = b'A')?;
+//! assert!(user_key.with_secret(|secret| secret.starts_with(b"A key"))?);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`SealedSecret`]: struct.SealedSecret.html
+//! [`SealedSecret::new()`]: struct.SealedSecret.html#method.new
+//! [`SealedSecret::with_secret()`]: struct.SealedSecret.html#method.with_secret
+//! [`SealedSecret::with_secret_mut()`]: struct.SealedSecret.html#method.with_secret_mut
+
+use crate::{
+    errors::UnknownCryptoError,
+    hazardous::{
+        aead::xchacha20poly1305,
+        hash::blake2b::Blake2b,
+        mac::poly1305::POLY1305_OUTSIZE,
+        stream::{chacha20::CHACHA_KEYSIZE, xchacha20::Nonce},
+    },
+    util::secure_rand_bytes,
+};
+use core::{
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use std::sync::Once;
+use zeroize::Zeroize;
+
+/// The size (in 32-bit words) of the random pre-key that the encryption key is
+/// derived from.
+const PREKEY_WORDS: usize = 16 * 1024 / 4;
+
+/// The number of pre-key words that are copied out at once.
+const PREKEY_CHUNK_WORDS: usize = 256;
+
+#[allow(clippy::declare_interior_mutable_const)]
+// Only used to initialize `PREKEY`.
+const PREKEY_WORD: AtomicU32 = AtomicU32::new(0);
+
+/// The process-wide pre-key, generated by `PREKEY_INIT` on first use.
+static PREKEY: [AtomicU32; PREKEY_WORDS] = [PREKEY_WORD; PREKEY_WORDS];
+
+static PREKEY_INIT: Once = Once::new();
+
+/// A secret that is kept encrypted in memory.
+pub struct SealedSecret {
+    nonce: Nonce,
+    ciphertext: Vec<u8>,
+}
+
+impl fmt::Debug for SealedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SealedSecret {{ nonce: [***OMITTED***], ciphertext: [***OMITTED***], len: {:?} }}",
+            self.len()
+        )
+    }
+}
+
+/// A buffer holding the decrypted secret, which is zeroized on drop.
+struct SecretBuffer(Vec<u8>);
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        self.0.iter_mut().zeroize();
+    }
+}
+
+/// Fill the pre-key with random bytes.
+fn generate_prekey() {
+    let mut bytes = [0u8; PREKEY_CHUNK_WORDS * 4];
+    for words in PREKEY.chunks(PREKEY_CHUNK_WORDS) {
+        // A failure to generate random bytes panics, as documented.
+        secure_rand_bytes(&mut bytes).unwrap();
+        for (word, word_bytes) in words.iter().zip(bytes.chunks_exact(4)) {
+            let mut value = [0u8; 4];
+            value.copy_from_slice(word_bytes);
+            word.store(u32::from_le_bytes(value), Ordering::Relaxed);
+        }
+    }
+    bytes.zeroize();
+}
+
+/// Hash the pre-key into `state`, copying out `bytes.len() / 4` words at a time.
+fn hash_prekey(state: &mut Blake2b, bytes: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    for words in PREKEY.chunks(bytes.len() / 4) {
+        for (word, word_bytes) in words.iter().zip(bytes.chunks_exact_mut(4)) {
+            word_bytes.copy_from_slice(&word.load(Ordering::Relaxed).to_le_bytes());
+        }
+        state.update(&bytes[..words.len() * 4])?;
+    }
+
+    Ok(())
+}
+
+/// Derive the encryption key from the pre-key, which is generated first if
+/// this is the first use in the process.
+fn derive_key() -> Result<xchacha20poly1305::SecretKey, UnknownCryptoError> {
+    PREKEY_INIT.call_once(generate_prekey);
+
+    let mut state = Blake2b::new(None, CHACHA_KEYSIZE)?;
+    let mut bytes = [0u8; PREKEY_CHUNK_WORDS * 4];
+    let result = hash_prekey(&mut state, &mut bytes);
+    bytes.zeroize();
+    result?;
+
+    xchacha20poly1305::SecretKey::from_slice(state.finalize()?.as_ref())
+}
+
+impl SealedSecret {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Seal `secret` under the pre-key and a new random nonce.
+    pub fn new(secret: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut sealed = Self {
+            nonce: Nonce::from([0u8; 24]),
+            ciphertext: Vec::new(),
+        };
+        sealed.seal(secret)?;
+
+        Ok(sealed)
+    }
+
+    /// Return the length of the secret.
+    pub fn len(&self) -> usize {
+        self.ciphertext.len() - POLY1305_OUTSIZE
+    }
+
+    /// Return `true` if the secret is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Encrypt `secret` under a new nonce, replacing the current state only if
+    /// this succeeds.
+    fn seal(&mut self, secret: &[u8]) -> Result<(), UnknownCryptoError> {
+        let nonce = Nonce::generate();
+        let mut ciphertext = vec![0u8; secret.len() + POLY1305_OUTSIZE];
+        xchacha20poly1305::seal(&derive_key()?, &nonce, secret, None, &mut ciphertext)?;
+
+        self.nonce = nonce;
+        self.ciphertext = ciphertext;
+
+        Ok(())
+    }
+
+    /// Decrypt the secret into a buffer that is zeroized on drop.
+    fn open(&self) -> Result<SecretBuffer, UnknownCryptoError> {
+        let mut secret = SecretBuffer(vec![0u8; self.len()]);
+        let key = derive_key()?;
+        xchacha20poly1305::open(&key, &self.nonce, &self.ciphertext, None, &mut secret.0)?;
+
+        Ok(secret)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decrypt the secret, pass it to `f` and return the result of `f`. The
+    /// sealed secret is left unchanged.
+    pub fn with_secret<R, F>(&self, f: F) -> Result<R, UnknownCryptoError>
+    where
+        F: FnOnce(&[u8]) -> R,
+    {
+        let secret = self.open()?;
+
+        Ok(f(&secret.0))
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decrypt the secret, pass it to `f`, which may modify it, and return the
+    /// result of `f`. The modified secret is sealed under a new nonce
+    /// afterwards.
+    pub fn with_secret_mut<R, F>(&mut self, f: F) -> Result<R, UnknownCryptoError>
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        let mut secret = self.open()?;
+        let result = f(&mut secret.0);
+        self.seal(&secret.0)?;
+
+        Ok(result)
+    }
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    #[test]
+    fn test_debug_impl() {
+        let sealed = SealedSecret::new(b"secret").unwrap();
+        let debug = format!("{:?}", sealed);
+        let expected =
+            "SealedSecret { nonce: [***OMITTED***], ciphertext: [***OMITTED***], len: 6 }";
+        assert_eq!(debug, expected);
+    }
+
+    #[test]
+    fn test_with_secret() {
+        let sealed = SealedSecret::new(b"secret").unwrap();
+        assert_eq!(sealed.len(), 6);
+        assert!(!sealed.is_empty());
+        assert_eq!(sealed.with_secret(|s| s.to_vec()).unwrap(), b"secret");
+        // The secret is not stored in cleartext.
+        assert!(!sealed
+            .ciphertext
+            .windows(6)
+            .any(|window| window == b"secret"));
+
+        let empty = SealedSecret::new(b"").unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.with_secret(|s| s.is_empty()).unwrap());
+    }
+
+    #[test]
+    fn test_with_secret_mut() {
+        let mut sealed = SealedSecret::new(b"secret").unwrap();
+        sealed
+            .with_secret_mut(|s| s.copy_from_slice(b"SECRET"))
+            .unwrap();
+        assert_eq!(sealed.with_secret(|s| s.to_vec()).unwrap(), b"SECRET");
+    }
+
+    #[test]
+    fn test_resealed_after_modification() {
+        let mut sealed = SealedSecret::new(b"secret").unwrap();
+        let nonce = sealed.nonce;
+        let ciphertext = sealed.ciphertext.clone();
+
+        sealed.with_secret(|_| ()).unwrap();
+        assert_eq!(sealed.nonce, nonce);
+        assert_eq!(sealed.ciphertext, ciphertext);
+
+        sealed.with_secret_mut(|_| ()).unwrap();
+        assert_ne!(sealed.nonce, nonce);
+        assert_ne!(sealed.ciphertext, ciphertext);
+    }
+
+    #[test]
+    fn test_shared_prekey() {
+        // Each secret has its own nonce under the shared pre-key.
+        let first = SealedSecret::new(b"secret").unwrap();
+        let second = SealedSecret::new(b"secret").unwrap();
+        assert_ne!(first.nonce, second.nonce);
+        assert_ne!(first.ciphertext, second.ciphertext);
+
+        let key = derive_key().unwrap();
+        assert_eq!(derive_key().unwrap(), key);
+        let mut secret = [0u8; 6];
+        xchacha20poly1305::open(&key, &second.nonce, &second.ciphertext, None, &mut secret)
+            .unwrap();
+        assert_eq!(&secret, b"secret");
+    }
+
+    #[test]
+    fn test_modified_in_memory() {
+        let mut sealed = SealedSecret::new(b"secret").unwrap();
+        sealed.nonce = Nonce::from([0u8; 24]);
+        assert!(sealed.with_secret(|_| ()).is_err());
+
+        let mut sealed = SealedSecret::new(b"secret").unwrap();
+        sealed.ciphertext[0] ^= 1;
+        assert!(sealed.with_secret_mut(|_| ()).is_err());
+        // The state is left as it was, so it keeps failing.
+        assert!(sealed.with_secret(|_| ()).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Digital signatures bound to an application context.
+//!
+//! # Use case:
+//! `orion::sign` can be used to sign messages with a secret key, so that
+//! anyone holding the matching public key can check that a message was
+//! signed by the owner of the secret key and was not modified since.
+//!
+//! An example of this could be signing software updates or API responses.
+//!
+//! # About:
+//! - Uses Ed25519.
+//! - Every message is signed together with a [`Context`], which consists of
+//!   an application label and a version. A signature made under one context
+//!   does not verify under any other context, so a signature on data from one
+//!   part of an application cannot be replayed as a signature on data from
+//!   another, even if the bytes happen to be the same.
+//! - The signed bytes are the concatenation of the fixed string
+//!   `orion-sign-context`, the label, the 4-byte big-endian version and the
+//!   message, where the fixed string, the label and the message are each
+//!   prefixed with their length as a little-endian `u64`.
+//! - [`Context::encode()`] returns these bytes, which can be signed with any
+//!   other signature scheme, such as
+//!   [`ecdsa_p256`](../hazardous/ecc/ecdsa_p256/index.html), to get the same
+//!   domain separation.
+//!
+//! # Parameters:
+//! - `label`: The name of the application and purpose of the signature.
+//! - `version`: The version of the format of the signed messages.
+//! - `secret_key`: The secret key used to sign `message`.
+//! - `public_key`: The public key used to verify `signature`.
+//! - `context`: The context that `message` is signed or verified under.
+//! - `message`: The message to be signed or verified.
+//! - `signature`: The signature to be verified.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `label` is empty or longer than 255 bytes.
+//! - `signature` is not a valid signature of `message` under `public_key` and
+//!   `context`.
+//!
+//! # Security:
+//! - Each distinct kind of signed data in an application should use its own
+//!   label. Increment the version when the format of the signed messages
+//!   changes, so that old signatures are not accepted for the new format.
+//! - Signatures made with the
+//!   [`ed25519`](../hazardous/ecc/ed25519/index.html) functions directly, over
+//!   raw messages, do not verify with this module, and the other way around.
+//! - See the [`ed25519`](../hazardous/ecc/ed25519/index.html) module for the
+//!   security properties of the signatures themselves.
+//!
+//! # Example:
+//! ```rust
+//! use orion::sign::{self, Context, PublicKey, SecretKey};
+//! use core::convert::TryFrom;
+//!
+//! let secret_key = SecretKey::generate();
+//! let public_key = PublicKey::try_from(&secret_key)?;
+//!
+//! let releases = Context::new("example.com release manifest", 1)?;
+//! let signature = sign::sign(&secret_key, &releases, b"orion 0.15.6")?;
+//! assert!(sign::verify(&signature, &public_key, &releases, b"orion 0.15.6").is_ok());
+//!
+//! // The same bytes do not verify under another context.
+//! let chat = Context::new("example.com chat message", 1)?;
+//! assert!(sign::verify(&signature, &public_key, &chat, b"orion 0.15.6").is_err());
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`Context`]: struct.Context.html
+//! [`Context::encode()`]: struct.Context.html#method.encode
+
+pub use crate::hazardous::ecc::ed25519::{PublicKey, SecretKey, Signature};
+use crate::{errors::UnknownCryptoError, hazardous::ecc::ed25519};
+
+/// The fixed string that starts every encoded message.
+const DOMAIN: &[u8] = b"orion-sign-context";
+
+/// The largest allowed size of a label.
+const MAX_LABEL_SIZE: usize = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The application label and version that a message is signed under.
+pub struct Context<'a> {
+    label: &'a str,
+    version: u32,
+}
+
+impl<'a> Context<'a> {
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Create a context from an application `label` and `version`.
+    pub fn new(label: &'a str, version: u32) -> Result<Self, UnknownCryptoError> {
+        if label.is_empty() || label.len() > MAX_LABEL_SIZE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(Self { label, version })
+    }
+
+    /// Return the label of the context.
+    pub fn label(&self) -> &str {
+        self.label
+    }
+
+    /// Return the version of the context.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Return the bytes that are signed for `message` under this context.
+    pub fn encode(&self, message: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(28 + DOMAIN.len() + self.label.len() + message.len());
+        for part in [DOMAIN, self.label.as_bytes()].iter() {
+            encoded.extend_from_slice(&(part.len() as u64).to_le_bytes());
+            encoded.extend_from_slice(part);
+        }
+        encoded.extend_from_slice(&self.version.to_be_bytes());
+        encoded.extend_from_slice(&(message.len() as u64).to_le_bytes());
+        encoded.extend_from_slice(message);
+
+        encoded
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `message` under `context` with `secret_key`.
+pub fn sign(
+    secret_key: &SecretKey,
+    context: &Context<'_>,
+    message: &[u8],
+) -> Result<Signature, UnknownCryptoError> {
+    ed25519::sign(secret_key, &context.encode(message))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `signature` is a valid signature of `message` under `context`
+/// and `public_key`.
+pub fn verify(
+    signature: &Signature,
+    public_key: &PublicKey,
+    context: &Context<'_>,
+    message: &[u8],
+) -> Result<(), UnknownCryptoError> {
+    ed25519::verify(signature, public_key, &context.encode(message))
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn keypair() -> (SecretKey, PublicKey) {
+        let secret_key = SecretKey::generate();
+        let public_key = PublicKey::try_from(&secret_key).unwrap();
+        (secret_key, public_key)
+    }
+
+    #[test]
+    fn test_context_label() {
+        assert!(Context::new("", 1).is_err());
+        assert!(Context::new(&"a".repeat(MAX_LABEL_SIZE), 1).is_ok());
+        assert!(Context::new(&"a".repeat(MAX_LABEL_SIZE + 1), 1).is_err());
+
+        let context = Context::new("label", 7).unwrap();
+        assert_eq!(context.label(), "label");
+        assert_eq!(context.version(), 7);
+    }
+
+    #[test]
+    fn test_encode_known() {
+        let context = Context::new("app", 0x0102_0304).unwrap();
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&[18, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(b"orion-sign-context");
+        expected.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(b"app");
+        expected.extend_from_slice(&[1, 2, 3, 4]);
+        expected.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.extend_from_slice(b"hi");
+        assert_eq!(context.encode(b"hi"), expected);
+    }
+
+    #[test]
+    fn test_encode_unambiguous() {
+        // Moving bytes between the label and the message changes the encoding.
+        let a = Context::new("ab", 1).unwrap().encode(b"c");
+        let b = Context::new("a", 1).unwrap().encode(b"bc");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let (secret_key, public_key) = keypair();
+        let context = Context::new("test", 1).unwrap();
+
+        let signature = sign(&secret_key, &context, b"message").unwrap();
+        assert!(verify(&signature, &public_key, &context, b"message").is_ok());
+        assert!(verify(&signature, &public_key, &context, b"massage").is_err());
+        assert_eq!(
+            signature,
+            ed25519::sign(&secret_key, &context.encode(b"message")).unwrap()
+        );
+
+        let (_, other_public_key) = keypair();
+        assert!(verify(&signature, &other_public_key, &context, b"message").is_err());
+    }
+
+    #[test]
+    fn test_context_binding() {
+        let (secret_key, public_key) = keypair();
+        let context = Context::new("test", 1).unwrap();
+        let signature = sign(&secret_key, &context, b"message").unwrap();
+
+        for other in [
+            Context::new("test", 2).unwrap(),
+            Context::new("tesT", 1).unwrap(),
+            Context::new("test ", 1).unwrap(),
+        ]
+        .iter()
+        {
+            assert!(verify(&signature, &public_key, other, b"message").is_err());
+        }
+
+        // Raw signatures are not accepted, and the other way around.
+        let raw = ed25519::sign(&secret_key, b"message").unwrap();
+        assert!(verify(&raw, &public_key, &context, b"message").is_err());
+        assert!(ed25519::verify(&signature, &public_key, b"message").is_err());
+    }
+
+    #[test]
+    fn test_encode_with_ecdsa_p256() {
+        use crate::hazardous::ecc::ecdsa_p256;
+
+        let secret_key = ecdsa_p256::SecretKey::generate();
+        let public_key = ecdsa_p256::PublicKey::try_from(&secret_key).unwrap();
+        let context = Context::new("test", 1).unwrap();
+        let other = Context::new("test", 2).unwrap();
+
+        let signature = ecdsa_p256::sign(&secret_key, &context.encode(b"message")).unwrap();
+        assert!(ecdsa_p256::verify(&signature, &public_key, &context.encode(b"message")).is_ok());
+        assert!(ecdsa_p256::verify(&signature, &public_key, &other.encode(b"message")).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Encrypted tokens with an expiry time.
+//!
+//! # Use case:
+//! `orion::token` can be used to hand out opaque tokens that carry data which
+//! the holder of the token must neither read nor modify, and which expire
+//! after a given amount of time.
+//!
+//! An example of this could be session cookies, where the server stores the
+//! session state in the cookie itself instead of a database.
+//!
+//! # About:
+//! - The `payload` and the time at which the token was sealed are encrypted
+//!   with the primary key of a [`KeyRing`], and the result is encoded as
+//!   unpadded base64url, so it can be used in cookies, headers and URLs.
+//! - When opening a token, the key named in its header is used, so tokens
+//!   sealed before a [`KeyRing::rotate()`] can still be opened, for as long
+//!   as the old key is in the key ring.
+//! - The time is stored with a precision of one second.
+//! - [`seal`] and [`open`] use the system time. [`seal_at`] and [`open_at`]
+//!   take the time from the caller instead, as seconds since the Unix epoch.
+//!   On `wasm32-unknown-unknown`, where there is no system time, only the
+//!   latter are available.
+//! - The encrypted data starts with a version byte, which is checked when
+//!   opening the token.
+//! - The context string `"orion token v1"` is authenticated along with the
+//!   header of the [`KeyRing`] ciphertext. Tokens therefore cannot be opened
+//!   with [`KeyRing::open()`], and other ciphertexts of the same [`KeyRing`]
+//!   cannot be opened as tokens.
+//!
+//! # Parameters:
+//! - `keyring`: The key ring used to seal or open the token.
+//! - `payload`: The data to be sealed in the token. It may be empty.
+//! - `token`: A token returned by [`seal`].
+//! - `ttl`: How long after being sealed the token may be opened.
+//! - `issued_at`: The time at which the token is sealed.
+//! - `now`: The time at which the token is opened.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - [`KeyRing::seal()`] or [`KeyRing::open()`] returns an error.
+//! - `token` is not unpadded base64url.
+//! - `token` has an unknown version.
+//! - `token` was sealed more than `ttl` ago.
+//! - The system time is before the Unix epoch.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - Failure to generate random bytes securely.
+//!
+//! # Security:
+//! - The length of the `payload` is not hidden, only its contents.
+//! - Tokens cannot be revoked before they expire, other than by removing the
+//!   key they were sealed with from the key ring. This revokes all tokens
+//!   sealed with that key.
+//! - A token that claims to have been sealed in the future is not rejected,
+//!   to allow for clock differences between servers. Such a token can only be
+//!   created by someone holding a key from the key ring.
+//! - The base64url decoding and encoding operations do NOT run in constant-time.
+//!
+//! # Example:
+//! ```rust
+//! use core::time::Duration;
+//! use orion::{keyring::{KeyRing, SecretKey}, token};
+//!
+//! let mut keyring = KeyRing::new(1, SecretKey::default())?;
+//! let cookie = token::seal(&mut keyring, b"user_id=42")?;
+//!
+//! let payload = token::open(&keyring, &cookie, Duration::from_secs(3600))?;
+//! assert_eq!(payload, b"user_id=42");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`KeyRing`]: ../keyring/struct.KeyRing.html
+//! [`KeyRing::rotate()`]: ../keyring/struct.KeyRing.html#method.rotate
+//! [`KeyRing::seal()`]: ../keyring/struct.KeyRing.html#method.seal
+//! [`KeyRing::open()`]: ../keyring/struct.KeyRing.html#method.open
+//! [`seal`]: fn.seal.html
+//! [`open`]: fn.open.html
+//! [`seal_at`]: fn.seal_at.html
+//! [`open_at`]: fn.open_at.html
+
+use super::keyring::KeyRing;
+use crate::errors::UnknownCryptoError;
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use core::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
+
+/// The version byte of the current token format.
+const VERSION: u8 = 0x01;
+
+/// The size of the version byte and the big-endian issue time.
+const PREFIX_SIZE: usize = 9;
+
+/// The context that separates tokens from other ciphertexts of a `KeyRing`.
+const CONTEXT: &[u8] = b"orion token v1";
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Return the current time in seconds since the Unix epoch.
+fn now() -> Result<u64, UnknownCryptoError> {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => Ok(since_epoch.as_secs()),
+        Err(_) => Err(UnknownCryptoError),
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Seal `payload` into a token, using the primary key of `keyring`.
+pub fn seal(keyring: &mut KeyRing, payload: &[u8]) -> Result<String, UnknownCryptoError> {
+    seal_at(keyring, payload, now()?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Open `token` and return its payload, if it was sealed at most `ttl` ago.
+pub fn open(keyring: &KeyRing, token: &str, ttl: Duration) -> Result<Vec<u8>, UnknownCryptoError> {
+    open_at(keyring, token, ttl, now()?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Seal `payload` into a token issued at `issued_at`, using the primary key of
+/// `keyring`.
+pub fn seal_at(
+    keyring: &mut KeyRing,
+    payload: &[u8],
+    issued_at: u64,
+) -> Result<String, UnknownCryptoError> {
+    let mut plaintext = Vec::with_capacity(PREFIX_SIZE + payload.len());
+    plaintext.push(VERSION);
+    plaintext.extend_from_slice(&issued_at.to_be_bytes());
+    plaintext.extend_from_slice(payload);
+
+    let ciphertext = keyring.seal_with_context(CONTEXT, &plaintext);
+    plaintext.iter_mut().zeroize();
+
+    Ok(encode_config(ciphertext?, URL_SAFE_NO_PAD))
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Open `token` and return its payload, if it was sealed at most `ttl` before
+/// `now`.
+pub fn open_at(
+    keyring: &KeyRing,
+    token: &str,
+    ttl: Duration,
+    now: u64,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+    // decode_config() accepts padding, even when the config has none.
+    if token.contains('=') {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut plaintext =
+        keyring.open_with_context(CONTEXT, &decode_config(token, URL_SAFE_NO_PAD)?)?;
+    // KeyRing::open() never returns an empty plaintext, but the issue time
+    // may still be missing.
+    if plaintext.len() < PREFIX_SIZE || plaintext[0] != VERSION {
+        plaintext.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    let mut issued_at = [0u8; 8];
+    issued_at.copy_from_slice(&plaintext[1..PREFIX_SIZE]);
+    if now.saturating_sub(u64::from_be_bytes(issued_at)) > ttl.as_secs() {
+        plaintext.iter_mut().zeroize();
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(plaintext.split_off(PREFIX_SIZE))
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::keyring::SecretKey;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_seal_open() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let token = seal(&mut keyring, b"user_id=42").unwrap();
+        assert!(!token.contains('='));
+        assert!(!token.contains('+'));
+        assert!(!token.contains('/'));
+        assert_eq!(open(&keyring, &token, HOUR).unwrap(), b"user_id=42");
+    }
+
+    #[test]
+    fn test_empty_payload() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let token = seal(&mut keyring, b"").unwrap();
+        assert!(open(&keyring, &token, HOUR).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let token = seal_at(&mut keyring, b"payload", 1_000_000).unwrap();
+
+        assert_eq!(
+            open_at(&keyring, &token, HOUR, 1_000_000).unwrap(),
+            b"payload"
+        );
+        assert_eq!(
+            open_at(&keyring, &token, HOUR, 1_003_600).unwrap(),
+            b"payload"
+        );
+        assert!(open_at(&keyring, &token, HOUR, 1_003_601).is_err());
+        assert!(open_at(&keyring, &token, Duration::from_secs(0), 1_000_001).is_err());
+        // Tokens from the future are accepted.
+        assert_eq!(open_at(&keyring, &token, HOUR, 0).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let old = seal(&mut keyring, b"old").unwrap();
+        keyring.rotate().unwrap();
+        let new = seal(&mut keyring, b"new").unwrap();
+
+        assert_eq!(open(&keyring, &old, HOUR).unwrap(), b"old");
+        assert_eq!(open(&keyring, &new, HOUR).unwrap(), b"new");
+
+        keyring.remove(1).unwrap();
+        assert!(open(&keyring, &old, HOUR).is_err());
+        assert_eq!(open(&keyring, &new, HOUR).unwrap(), b"new");
+    }
+
+    #[test]
+    fn test_tampered_token_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let token = seal(&mut keyring, b"user_id=42").unwrap();
+        let mut bytes = decode_config(&token, URL_SAFE_NO_PAD).unwrap();
+
+        for idx in 0..bytes.len() {
+            bytes[idx] ^= 1;
+            let bad = encode_config(&bytes, URL_SAFE_NO_PAD);
+            assert!(open(&keyring, &bad, HOUR).is_err());
+            bytes[idx] ^= 1;
+        }
+
+        assert!(open(&keyring, &token[1..], HOUR).is_err());
+        assert!(open(&keyring, &format!("{}=", token), HOUR).is_err());
+        assert!(open(&keyring, "", HOUR).is_err());
+        assert!(open(&keyring, "not base64!", HOUR).is_err());
+    }
+
+    #[test]
+    fn test_other_keyring_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let other = KeyRing::new(1, SecretKey::default()).unwrap();
+        let token = seal(&mut keyring, b"payload").unwrap();
+        assert!(open(&other, &token, HOUR).is_err());
+    }
+
+    #[test]
+    fn test_bad_plaintext_err() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+        let mut seal_token = |plaintext: &[u8]| {
+            let ciphertext = keyring.seal_with_context(CONTEXT, plaintext).unwrap();
+            encode_config(ciphertext, URL_SAFE_NO_PAD)
+        };
+
+        // Too short to hold the version and issue time.
+        let short = seal_token(&[VERSION; 8]);
+        // Unknown version.
+        let mut plaintext = [0u8; PREFIX_SIZE + 1];
+        plaintext[0] = VERSION + 1;
+        let bad_version = seal_token(&plaintext);
+        plaintext[0] = VERSION;
+        let good = seal_token(&plaintext);
+
+        assert!(open(&keyring, &short, HOUR).is_err());
+        assert!(open_at(&keyring, &bad_version, HOUR, 0).is_err());
+        assert_eq!(open_at(&keyring, &good, HOUR, 0).unwrap(), &[0u8]);
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let mut keyring = KeyRing::new(1, SecretKey::default()).unwrap();
+
+        // A well-formed token plaintext sealed with KeyRing::seal().
+        let mut plaintext = [0u8; PREFIX_SIZE + 1];
+        plaintext[0] = VERSION;
+        let plain = encode_config(keyring.seal(&plaintext).unwrap(), URL_SAFE_NO_PAD);
+        assert!(open_at(&keyring, &plain, HOUR, 0).is_err());
+
+        // A token opened with KeyRing::open().
+        let token = seal(&mut keyring, b"payload").unwrap();
+        let ciphertext = decode_config(&token, URL_SAFE_NO_PAD).unwrap();
+        assert!(keyring.open(&ciphertext).is_err());
+        assert_eq!(open(&keyring, &token, HOUR).unwrap(), b"payload");
+    }
+}
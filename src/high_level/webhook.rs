@@ -0,0 +1,459 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Verifying signed webhook requests.
+//!
+//! # Use case:
+//! `orion::webhook` can be used to check that an incoming webhook request was
+//! sent by a provider that holds a shared secret, using the HMAC-SHA256
+//! signature schemes that are common among webhook providers.
+//!
+//! # About:
+//! - [`verify`] checks a timestamped signature header of the form
+//!   `t=<timestamp>,v1=<signature>`, where `<timestamp>` is the time of
+//!   signing in seconds since the Unix epoch and `<signature>` is the
+//!   lowercase hex-encoded HMAC-SHA256 of `<timestamp>.<payload>`. The header
+//!   may contain several `v1` signatures, and entries with other names are
+//!   ignored.
+//! - [`verify_body`] checks a signature header of the form
+//!   `sha256=<signature>`, where `<signature>` is the lowercase hex-encoded
+//!   HMAC-SHA256 of the payload alone.
+//! - [`sign`] and [`sign_body`] create the respective headers.
+//! - [`sign`] and [`verify`] use the system time. [`sign_at`] and
+//!   [`verify_at`] take the time from the caller instead, as seconds since the
+//!   Unix epoch. On `wasm32-unknown-unknown`, where there is no system time,
+//!   only the latter are available.
+//! - Several secrets can be passed when verifying, so that signatures made
+//!   with either the old or the new secret are accepted while a secret is
+//!   being rotated. A request is accepted if any of its signatures was made
+//!   with any of the secrets.
+//!
+//! # Parameters:
+//! - `secret`: The secret used to sign the payload.
+//! - `secrets`: The secrets accepted when verifying the payload.
+//! - `payload`: The raw body of the webhook request.
+//! - `header`: The signature header of the webhook request.
+//! - `tolerance`: The largest allowed difference between the timestamp in
+//!   `header` and the current time.
+//! - `timestamp`: The time of signing.
+//! - `now`: The current time, when verifying.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secrets` is empty.
+//! - `header` is malformed, or it does not contain exactly one timestamp.
+//! - `header` does not contain any signatures.
+//! - None of the signatures in `header` match the payload under any of the
+//!   `secrets`.
+//! - The timestamp in `header` differs from the current time by more than
+//!   `tolerance`.
+//! - The system time is before the Unix epoch.
+//!
+//! # Security:
+//! - The payload must be verified exactly as it was received, before it is
+//!   parsed. Re-serializing a parsed payload will not, in general, produce the
+//!   same bytes.
+//! - [`verify_body`] offers no protection against replay of a captured
+//!   request. Prefer [`verify`] when the provider supports it, and keep the
+//!   `tolerance` as short as the expected clock differences allow.
+//! - The signatures are compared in constant time. Parsing the header does
+//!   NOT run in constant-time.
+//!
+//! # Example:
+//! ```rust
+//! use core::time::Duration;
+//! use orion::webhook;
+//!
+//! let old_secret = webhook::SecretKey::default();
+//! let new_secret = webhook::SecretKey::default();
+//! let payload = br#"{"event":"ping"}"#;
+//!
+//! // The provider signs with the new secret, while the receiver accepts both.
+//! let header = webhook::sign(&new_secret, payload)?;
+//! webhook::verify(
+//!     &[old_secret, new_secret],
+//!     payload,
+//!     &header,
+//!     Duration::from_secs(300),
+//! )?;
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`verify`]: fn.verify.html
+//! [`verify_body`]: fn.verify_body.html
+//! [`sign`]: fn.sign.html
+//! [`sign_body`]: fn.sign_body.html
+//! [`sign_at`]: fn.sign_at.html
+//! [`verify_at`]: fn.verify_at.html
+
+pub use super::hltypes::SecretKey;
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::hash::sha256::{Sha256, SHA256_OUTSIZE};
+use crate::hazardous::mac::hmac::HmacGeneric;
+use crate::util::secure_cmp;
+use core::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The size of a hex-encoded signature.
+const SIGNATURE_HEX_SIZE: usize = SHA256_OUTSIZE * 2;
+
+/// The prefix of a signature header for [`verify_body`].
+const BODY_PREFIX: &str = "sha256=";
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Return the current time in seconds since the Unix epoch.
+fn now() -> Result<u64, UnknownCryptoError> {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => Ok(since_epoch.as_secs()),
+        Err(_) => Err(UnknownCryptoError),
+    }
+}
+
+/// Encode a nibble as a lowercase hex character in constant time.
+fn encode_hex_nibble(n: u8) -> u8 {
+    let n = u16::from(n);
+    // For `n` above 9, the subtraction wraps and the mask adds the distance
+    // between '9' + 1 and 'a'.
+    (n + 48 + ((9u16.wrapping_sub(n) >> 8) & 39)) as u8
+}
+
+/// Compute the lowercase hex-encoded HMAC-SHA256 of the concatenation of
+/// `parts` under `secret`.
+fn signature(
+    secret: &SecretKey,
+    parts: &[&[u8]],
+) -> Result<[u8; SIGNATURE_HEX_SIZE], UnknownCryptoError> {
+    let mut mac = HmacGeneric::<Sha256>::new(secret.unprotected_as_bytes())?;
+    for part in parts {
+        mac.update(part)?;
+    }
+    let mut tag = [0u8; SHA256_OUTSIZE];
+    mac.finalize_into(&mut tag)?;
+
+    let mut encoded = [0u8; SIGNATURE_HEX_SIZE];
+    for (pair, byte) in encoded.chunks_exact_mut(2).zip(tag.iter()) {
+        pair[0] = encode_hex_nibble(byte >> 4);
+        pair[1] = encode_hex_nibble(byte & 0x0f);
+    }
+
+    Ok(encoded)
+}
+
+/// Check whether any of `candidates` is the signature of `parts` under any
+/// of `secrets`. All combinations are compared, regardless of whether a
+/// match has already been found.
+fn any_valid(
+    secrets: &[SecretKey],
+    parts: &[&[u8]],
+    candidates: &[&str],
+) -> Result<(), UnknownCryptoError> {
+    if secrets.is_empty() || candidates.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut valid = false;
+    for secret in secrets {
+        let expected = signature(secret, parts)?;
+        for candidate in candidates {
+            valid |= secure_cmp(&expected, candidate.as_bytes()).is_ok();
+        }
+    }
+
+    if valid {
+        Ok(())
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Sign `payload` with `secret`, returning a `t=<timestamp>,v1=<signature>`
+/// header for the current time.
+pub fn sign(secret: &SecretKey, payload: &[u8]) -> Result<String, UnknownCryptoError> {
+    sign_at(secret, payload, now()?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+/// Verify that `header` holds a `v1` signature of `payload` under any of
+/// `secrets`, made at most `tolerance` away from the current time.
+pub fn verify(
+    secrets: &[SecretKey],
+    payload: &[u8],
+    header: &str,
+    tolerance: Duration,
+) -> Result<(), UnknownCryptoError> {
+    verify_at(secrets, payload, header, tolerance, now()?)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `payload` with `secret`, returning a `sha256=<signature>` header.
+pub fn sign_body(secret: &SecretKey, payload: &[u8]) -> Result<String, UnknownCryptoError> {
+    let encoded = signature(secret, &[payload])?;
+    let mut header = String::with_capacity(BODY_PREFIX.len() + SIGNATURE_HEX_SIZE);
+    header.push_str(BODY_PREFIX);
+    // The encoding only produces ASCII characters.
+    header.extend(encoded.iter().map(|&c| char::from(c)));
+
+    Ok(header)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `header` holds a `sha256` signature of `payload` under any of
+/// `secrets`.
+pub fn verify_body(
+    secrets: &[SecretKey],
+    payload: &[u8],
+    header: &str,
+) -> Result<(), UnknownCryptoError> {
+    if !header.starts_with(BODY_PREFIX) {
+        return Err(UnknownCryptoError);
+    }
+
+    any_valid(secrets, &[payload], &[&header[BODY_PREFIX.len()..]])
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Sign `payload` with `secret`, returning a `t=<timestamp>,v1=<signature>`
+/// header for `timestamp`.
+pub fn sign_at(
+    secret: &SecretKey,
+    payload: &[u8],
+    timestamp: u64,
+) -> Result<String, UnknownCryptoError> {
+    let timestamp = timestamp.to_string();
+    let encoded = signature(secret, &[timestamp.as_bytes(), b".", payload])?;
+
+    let mut header = String::with_capacity(timestamp.len() + SIGNATURE_HEX_SIZE + 6);
+    header.push_str("t=");
+    header.push_str(&timestamp);
+    header.push_str(",v1=");
+    // The encoding only produces ASCII characters.
+    header.extend(encoded.iter().map(|&c| char::from(c)));
+
+    Ok(header)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Verify that `header` holds a `v1` signature of `payload` under any of
+/// `secrets`, made at most `tolerance` away from `now`.
+pub fn verify_at(
+    secrets: &[SecretKey],
+    payload: &[u8],
+    header: &str,
+    tolerance: Duration,
+    now: u64,
+) -> Result<(), UnknownCryptoError> {
+    let mut timestamp: Option<&str> = None;
+    let mut candidates: Vec<&str> = Vec::new();
+
+    for entry in header.split(',') {
+        let mut name_value = entry.trim().splitn(2, '=');
+        let name = name_value.next().ok_or(UnknownCryptoError)?;
+        let value = name_value.next().ok_or(UnknownCryptoError)?;
+        match name {
+            "t" if timestamp.is_none() => timestamp = Some(value),
+            "t" => return Err(UnknownCryptoError),
+            "v1" => candidates.push(value),
+            _ => (),
+        }
+    }
+
+    let timestamp = timestamp.ok_or(UnknownCryptoError)?;
+    // `u64::from_str()` would also accept a leading '+', which would not be
+    // part of the signed timestamp the provider intended.
+    if timestamp.is_empty() || !timestamp.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(UnknownCryptoError);
+    }
+    let signed_at: u64 = timestamp.parse().map_err(|_| UnknownCryptoError)?;
+
+    any_valid(secrets, &[timestamp.as_bytes(), b".", payload], &candidates)?;
+
+    let skew = core::cmp::max(now, signed_at) - core::cmp::min(now, signed_at);
+    if skew > tolerance.as_secs() {
+        return Err(UnknownCryptoError);
+    }
+
+    Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    const TOLERANCE: Duration = Duration::from_secs(300);
+    const TIMESTAMP: u64 = 1_614_556_800;
+    const PAYLOAD: &[u8] = br#"{"id":"evt_1"}"#;
+    // HMAC-SHA256 of "1614556800.{\"id\":\"evt_1\"}" under "whsec_test_secret".
+    const SIGNATURE: &str = "4b84b790795620969b08a42ba000110ae8bf58a5784f16c215b248752ce65404";
+    // HMAC-SHA256 of "1614556800.{\"id\":\"evt_1\"}" under "whsec_rotated_key".
+    const ROTATED_SIGNATURE: &str =
+        "b24671c711c90dd41668488dc0b25197a424037a5c3b8de8a31fde28b727bf06";
+
+    fn secret() -> SecretKey {
+        SecretKey::from_slice(b"whsec_test_secret").unwrap()
+    }
+
+    fn rotated() -> SecretKey {
+        SecretKey::from_slice(b"whsec_rotated_key").unwrap()
+    }
+
+    fn header(signatures: &[&str]) -> String {
+        let mut header = format!("t={}", TIMESTAMP);
+        for signature in signatures {
+            header.push_str(",v1=");
+            header.push_str(signature);
+        }
+        header
+    }
+
+    #[test]
+    fn test_sign_known() {
+        assert_eq!(
+            sign_at(&secret(), PAYLOAD, TIMESTAMP).unwrap(),
+            header(&[SIGNATURE])
+        );
+        assert_eq!(
+            sign_at(&rotated(), PAYLOAD, TIMESTAMP).unwrap(),
+            header(&[ROTATED_SIGNATURE])
+        );
+    }
+
+    #[test]
+    fn test_verify_known() {
+        let header = header(&[SIGNATURE]);
+        assert!(verify_at(&[secret()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+        assert!(verify_at(&[rotated()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_err());
+        assert!(verify_at(&[secret()], b"{}", &header, TOLERANCE, TIMESTAMP).is_err());
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        let secret = SecretKey::default();
+        let header = sign(&secret, PAYLOAD).unwrap();
+        assert!(verify(&[secret], PAYLOAD, &header, TOLERANCE).is_ok());
+    }
+
+    #[test]
+    fn test_verify_rotation() {
+        // The receiver accepts both secrets.
+        let secrets = [rotated(), secret()];
+        for signatures in [
+            &[SIGNATURE][..],
+            &[ROTATED_SIGNATURE][..],
+            &[SIGNATURE, ROTATED_SIGNATURE][..],
+            &[&SIGNATURE[..63], ROTATED_SIGNATURE][..],
+        ]
+        .iter()
+        {
+            let header = header(signatures);
+            assert!(verify_at(&secrets, PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+        }
+
+        // The provider sends signatures under both secrets.
+        let header = header(&[ROTATED_SIGNATURE, SIGNATURE]);
+        assert!(verify_at(&[secret()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+        assert!(verify_at(&[rotated()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+        assert!(verify_at(&[], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_err());
+    }
+
+    #[test]
+    fn test_verify_tolerance() {
+        let header = header(&[SIGNATURE]);
+        let secrets = [secret()];
+        let t = TIMESTAMP;
+        assert!(verify_at(&secrets, PAYLOAD, &header, TOLERANCE, t + 300).is_ok());
+        assert!(verify_at(&secrets, PAYLOAD, &header, TOLERANCE, t - 300).is_ok());
+        assert!(verify_at(&secrets, PAYLOAD, &header, TOLERANCE, t + 301).is_err());
+        assert!(verify_at(&secrets, PAYLOAD, &header, TOLERANCE, t - 301).is_err());
+        assert!(verify_at(&secrets, PAYLOAD, &header, Duration::from_secs(0), t).is_ok());
+        assert!(verify_at(&secrets, PAYLOAD, &header, Duration::from_secs(0), t + 1).is_err());
+    }
+
+    #[test]
+    fn test_verify_ignores_other_schemes() {
+        let header = format!("t={},v0=abc,v1={}", TIMESTAMP, SIGNATURE);
+        assert!(verify_at(&[secret()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+        let header = format!("v1={}, t={}", SIGNATURE, TIMESTAMP);
+        assert!(verify_at(&[secret()], PAYLOAD, &header, TOLERANCE, TIMESTAMP).is_ok());
+    }
+
+    #[test]
+    fn test_verify_malformed_header() {
+        let secrets = [secret()];
+        for header in [
+            "".to_string(),
+            format!("v1={}", SIGNATURE),
+            format!("t={}", TIMESTAMP),
+            format!("t={},v1={}", TIMESTAMP, SIGNATURE.to_uppercase()),
+            format!("t={},t={},v1={}", TIMESTAMP, TIMESTAMP, SIGNATURE),
+            format!("t=+{},v1={}", TIMESTAMP, SIGNATURE),
+            format!("t=,v1={}", SIGNATURE),
+            format!("t={},v1", TIMESTAMP),
+            format!("t={},v1={},", TIMESTAMP, SIGNATURE),
+            format!("t={};v1={}", TIMESTAMP, SIGNATURE),
+        ]
+        .iter()
+        {
+            assert!(verify_at(&secrets, PAYLOAD, header, TOLERANCE, TIMESTAMP).is_err());
+        }
+    }
+
+    #[test]
+    fn test_body_known() {
+        // Example from the GitHub webhook documentation.
+        let secret = SecretKey::from_slice(b"It's a Secret to Everybody").unwrap();
+        let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert_eq!(sign_body(&secret, b"Hello, World!").unwrap(), header);
+        assert!(verify_body(&[rotated(), secret], b"Hello, World!", header).is_ok());
+        assert!(verify_body(&[rotated()], b"Hello, World!", header).is_err());
+        assert!(verify_body(&[], b"Hello, World!", header).is_err());
+    }
+
+    #[test]
+    fn test_body_malformed_header() {
+        let secrets = [secret()];
+        let header = sign_body(&secret(), PAYLOAD).unwrap();
+        assert!(verify_body(&secrets, PAYLOAD, &header).is_ok());
+        assert!(verify_body(&secrets, b"", &header).is_err());
+        assert!(verify_body(&secrets, PAYLOAD, &header[7..]).is_err());
+        assert!(verify_body(&secrets, PAYLOAD, &header.replace("sha256", "sha1")).is_err());
+        assert!(verify_body(&secrets, PAYLOAD, &header[..header.len() - 1]).is_err());
+        assert!(verify_body(&secrets, PAYLOAD, &header.to_uppercase()).is_err());
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    #[test]
+    fn test_encode_hex_nibble() {
+        let expected = b"0123456789abcdef";
+        for n in 0..16u8 {
+            assert_eq!(encode_hex_nibble(n), expected[n as usize]);
+        }
+    }
+}
@@ -27,7 +27,7 @@
 //! XChaCha20Poly1305.
 //!
 //! ## Password hashing and verification
-//! [`orion::pwhash`] offers password hashing and verification using Argon2i.
+//! [`orion::pwhash`] offers password hashing and verification using Argon2i or Argon2id.
 //!
 //! ## Key derivation
 //! [`orion::kdf`] offers key derivation using Argon2i.
@@ -38,6 +38,26 @@
 //! ## Hashing
 //! [`orion::hash`] offers hashing using BLAKE2b.
 //!
+//! ## Key rotation
+//! [`orion::keyring`] offers authenticated secret-key encryption with a set of
+//! versioned keys, using XChaCha20Poly1305.
+//!
+//! ## Encrypted tokens
+//! [`orion::token`] offers encrypted tokens with an expiry time, such as
+//! session cookies, built on [`orion::keyring`].
+//!
+//! ## Secrets in memory
+//! [`orion::sealed`] offers secrets that are kept encrypted in memory, and are
+//! only decrypted while they are used.
+//!
+//! ## Digital signatures
+//! [`orion::sign`] offers Ed25519 signatures that are bound to an
+//! application label and version.
+//!
+//! ## Webhooks
+//! [`orion::webhook`] offers signing and verification of webhook payloads
+//! using HMAC-SHA256.
+//!
 //! ### A note on `no_std`:
 //! When orion is used in a `no_std` context, the high-level API is not available, since it relies on access to the systems random number generator.
 //!
@@ -48,6 +68,11 @@
 //! [`orion::kdf`]: kdf/index.html
 //! [`orion::auth`]: auth/index.html
 //! [`orion::hash`]: hash/index.html
+//! [`orion::keyring`]: keyring/index.html
+//! [`orion::token`]: token/index.html
+//! [`orion::sealed`]: sealed/index.html
+//! [`orion::sign`]: sign/index.html
+//! [`orion::webhook`]: webhook/index.html
 
 #![cfg_attr(not(feature = "safe_api"), no_std)]
 #![forbid(unsafe_code)]
@@ -101,6 +126,27 @@ pub use high_level::pwhash;
 #[cfg(feature = "safe_api")]
 pub use high_level::kdf;
 
+#[cfg(feature = "safe_api")]
+pub use high_level::kex;
+
+#[cfg(feature = "safe_api")]
+pub use high_level::keyring;
+
+#[cfg(feature = "safe_api")]
+pub use high_level::sealed;
+
+#[cfg(feature = "safe_api")]
+pub use high_level::sign;
+
+#[cfg(feature = "safe_api")]
+pub use high_level::token;
+
+#[cfg(feature = "safe_api")]
+pub use high_level::webhook;
+
+#[cfg(feature = "jwk")]
+pub use high_level::jwk;
+
 #[doc(hidden)]
 /// Testing framework.
 pub mod test_framework;
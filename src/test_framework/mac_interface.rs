@@ -0,0 +1,215 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::errors::UnknownCryptoError;
+
+/// The largest tag size of any MAC that can be tested.
+const MAX_TAG_SIZE: usize = 64;
+
+/// Trait to define MACs that can be tested.
+pub trait TestableMac: Sized {
+    /// The secret key type of the MAC.
+    type Key;
+
+    /// The tag type of the MAC.
+    type Tag: PartialEq + core::fmt::Debug;
+
+    /// MAC function to initialize a new streaming state.
+    fn new(key: &Self::Key) -> Self;
+
+    /// MAC function to reset the streaming state.
+    fn reset(&mut self);
+
+    /// MAC function to update the streaming state.
+    fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+    /// MAC function to finalize the streaming state.
+    fn finalize(&mut self) -> Result<Self::Tag, UnknownCryptoError>;
+
+    /// MAC function to combine new(), update() and finalize().
+    fn one_shot(key: &Self::Key, data: &[u8]) -> Result<Self::Tag, UnknownCryptoError>;
+
+    /// MAC function to verify a tag in constant time.
+    fn verify(expected: &Self::Tag, key: &Self::Key, data: &[u8])
+        -> Result<(), UnknownCryptoError>;
+
+    /// Testing utility-function that returns the bytes of a tag.
+    fn tag_as_bytes(tag: &Self::Tag) -> &[u8];
+
+    /// Testing utility-function that creates a tag from bytes.
+    fn tag_from_slice(bytes: &[u8]) -> Result<Self::Tag, UnknownCryptoError>;
+}
+
+/// A MAC tester.
+pub struct MacTester<M: TestableMac> {
+    key: M::Key,
+    // A key different from `key`.
+    other_key: M::Key,
+}
+
+impl<M: TestableMac> MacTester<M> {
+    /// The MAC tester is created with two different secret keys.
+    pub fn new(key: M::Key, other_key: M::Key) -> Self {
+        Self { key, other_key }
+    }
+
+    // Default input to process.
+    // The number 151 has no particular meaning.
+    const DEFAULT_INPUT: [u8; 151] = [255u8; 151];
+
+    /// Run all tests given some input data.
+    /// Usually used with quickcheck.
+    pub fn run_all_tests_property(&self, data: &[u8]) {
+        self.one_shot_and_streaming_equal(data);
+        self.one_shot_and_streaming_equal(&[0u8; 0]);
+        self.verify_ok(data);
+        self.verify_modified_tag_err(data);
+        self.verify_diff_key_err(data);
+        self.verify_diff_data_err(data);
+        self.double_finalize_err(data);
+        self.update_after_finalize_err(data);
+        self.reset_after_finalize_ok(data);
+        self.reset_discards_data(data);
+    }
+
+    /// Used when quickcheck is not available to generate input.
+    /// Default input data is used instead.
+    pub fn run_all_tests(&self) {
+        self.run_all_tests_property(&Self::DEFAULT_INPUT);
+    }
+
+    /// Processing data all at once, one byte at a time and in uneven chunks
+    /// produces the same tag.
+    fn one_shot_and_streaming_equal(&self, data: &[u8]) {
+        let expected = M::one_shot(&self.key, data).unwrap();
+
+        let mut state = M::new(&self.key);
+        state.update(data).unwrap();
+        assert_eq!(state.finalize().unwrap(), expected);
+
+        let mut state = M::new(&self.key);
+        for byte in data.chunks(1) {
+            state.update(byte).unwrap();
+        }
+        assert_eq!(state.finalize().unwrap(), expected);
+
+        // The chunk size 7 has no particular meaning, other than not being a
+        // divisor of any block size.
+        let mut state = M::new(&self.key);
+        for chunk in data.chunks(7) {
+            state.update(chunk).unwrap();
+            state.update(&[0u8; 0]).unwrap();
+        }
+        assert_eq!(state.finalize().unwrap(), expected);
+    }
+
+    /// A tag verifies with the key and data it was computed with.
+    fn verify_ok(&self, data: &[u8]) {
+        let tag = M::one_shot(&self.key, data).unwrap();
+        assert!(M::verify(&tag, &self.key, data).is_ok());
+    }
+
+    /// Flipping any bit of a tag makes verification fail.
+    fn verify_modified_tag_err(&self, data: &[u8]) {
+        let tag = M::one_shot(&self.key, data).unwrap();
+        let tag_bytes = M::tag_as_bytes(&tag);
+        assert!(tag_bytes.len() <= MAX_TAG_SIZE);
+
+        let mut modified = [0u8; MAX_TAG_SIZE];
+        for byte_idx in 0..tag_bytes.len() {
+            for bit in 0..8 {
+                modified[..tag_bytes.len()].copy_from_slice(tag_bytes);
+                modified[byte_idx] ^= 1 << bit;
+
+                let bad_tag = M::tag_from_slice(&modified[..tag_bytes.len()]).unwrap();
+                assert!(M::verify(&bad_tag, &self.key, data).is_err());
+            }
+        }
+    }
+
+    /// A tag does not verify with a different key, and a different key produces
+    /// a different tag.
+    fn verify_diff_key_err(&self, data: &[u8]) {
+        let tag = M::one_shot(&self.key, data).unwrap();
+        assert!(M::verify(&tag, &self.other_key, data).is_err());
+        assert_ne!(M::one_shot(&self.other_key, data).unwrap(), tag);
+    }
+
+    /// A tag does not verify for truncated or extended data.
+    fn verify_diff_data_err(&self, data: &[u8]) {
+        let tag = M::one_shot(&self.key, data).unwrap();
+
+        if !data.is_empty() {
+            assert!(M::verify(&tag, &self.key, &data[..data.len() - 1]).is_err());
+            assert!(M::verify(&tag, &self.key, &data[1..]).is_err());
+        }
+
+        let mut state = M::new(&self.key);
+        state.update(data).unwrap();
+        state.update(&[0u8; 1]).unwrap();
+        assert_ne!(state.finalize().unwrap(), tag);
+    }
+
+    /// Calling finalize() twice without a reset() in between is an error.
+    fn double_finalize_err(&self, data: &[u8]) {
+        let mut state = M::new(&self.key);
+        state.update(data).unwrap();
+        assert!(state.finalize().is_ok());
+        assert!(state.finalize().is_err());
+    }
+
+    /// Calling update() after finalize() without a reset() in between is an error.
+    fn update_after_finalize_err(&self, data: &[u8]) {
+        let mut state = M::new(&self.key);
+        state.update(data).unwrap();
+        assert!(state.finalize().is_ok());
+        assert!(state.update(data).is_err());
+    }
+
+    /// A reset() after finalize() allows the state to be used again, with
+    /// the same key.
+    fn reset_after_finalize_ok(&self, data: &[u8]) {
+        let mut state = M::new(&self.key);
+        state.update(data).unwrap();
+        let tag_1 = state.finalize().unwrap();
+
+        state.reset();
+        state.update(data).unwrap();
+        let tag_2 = state.finalize().unwrap();
+
+        assert_eq!(tag_1, tag_2);
+        assert!(M::verify(&tag_2, &self.key, data).is_ok());
+    }
+
+    /// A reset() discards any data processed before it.
+    fn reset_discards_data(&self, data: &[u8]) {
+        let mut state = M::new(&self.key);
+        state.update(b"Data that is discarded").unwrap();
+        state.reset();
+        state.update(data).unwrap();
+
+        assert_eq!(
+            state.finalize().unwrap(),
+            M::one_shot(&self.key, data).unwrap()
+        );
+    }
+}
@@ -26,5 +26,12 @@ pub mod incremental_interface;
 /// Tests for AEAD interfaces such as `chacha20poly1305`.
 pub mod aead_interface;
 
+/// Tests for MACs such as `hmac` and `poly1305`.
+pub mod mac_interface;
+
 /// Tests for stream ciphers such as `chacha20`.
 pub mod streamcipher_interface;
+
+#[cfg(feature = "safe_api")]
+/// Tests for streaming AEAD contexts such as `streaming`.
+pub mod streaming_aead_interface;
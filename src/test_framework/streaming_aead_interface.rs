@@ -0,0 +1,348 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::errors::UnknownCryptoError;
+use core::marker::PhantomData;
+
+/// Trait to define streaming AEAD contexts that can be tested.
+pub trait TestableStreamingAead {
+    /// The type that indicates the function of a chunk.
+    type Tag: PartialEq + core::fmt::Debug;
+
+    /// The amount of bytes a sealed chunk is longer than its plaintext.
+    const ABYTES: usize;
+
+    /// Streaming AEAD function to encrypt and authenticate a single chunk.
+    fn seal_chunk(
+        &mut self,
+        plaintext: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+        tag: Self::Tag,
+    ) -> Result<(), UnknownCryptoError>;
+
+    /// Streaming AEAD function to authenticate and decrypt a single chunk.
+    fn open_chunk(
+        &mut self,
+        ciphertext: &[u8],
+        ad: Option<&[u8]>,
+        dst_out: &mut [u8],
+    ) -> Result<Self::Tag, UnknownCryptoError>;
+
+    /// The tag of a chunk with no special meaning.
+    fn message_tag() -> Self::Tag;
+
+    /// The tag marking the last chunk of a stream.
+    fn final_tag() -> Self::Tag;
+}
+
+/// The plaintext and tag of each opened chunk in a stream.
+type OpenedStream<Tag> = Vec<(Vec<u8>, Tag)>;
+
+/// A streaming AEAD tester.
+pub struct StreamingAeadConsistencyTester<T, F> {
+    _context_type: PhantomData<T>,
+    // Creates a new context with the same key and nonce every time. This is
+    // both the sealing and opening side of a stream.
+    new_context: F,
+}
+
+impl<T, F> StreamingAeadConsistencyTester<T, F>
+where
+    T: TestableStreamingAead,
+    F: Fn() -> T,
+{
+    /// The streaming AEAD tester is created with a function that returns a
+    /// new context, for the same secret key and nonce, each time it is called.
+    pub fn new(new_context: F) -> Self {
+        Self {
+            _context_type: PhantomData,
+            new_context,
+        }
+    }
+
+    // Default input to process.
+    // The numbers 37 and 11 have no particular meaning.
+    const DEFAULT_INPUT: [u8; 37] = [255u8; 37];
+    const DEFAULT_AD: [u8; 11] = [1u8; 11];
+    const DEFAULT_CHUNKING: [u8; 5] = [0, 16, 1, 0, 64];
+
+    /// Run all consistency tests given some input data, chunk sizes and
+    /// additional data. Usually used with quickcheck.
+    pub fn run_all_tests_property(&self, data: &[u8], chunking: &[u8], ad: &[u8]) {
+        let chunks = Self::split(data, chunking);
+
+        self.seal_open_chunks(&chunks, ad);
+        self.seal_open_chunks(&Self::split(&[0u8; 0], chunking), ad);
+        self.new_context_same_stream(&chunks, ad);
+        self.open_modified_ad_err(&chunks, ad);
+        self.open_modified_chunk_err(&chunks, ad);
+        self.open_truncated_chunk_err(&chunks, ad);
+        self.open_reordered_chunks_err(&chunks, ad);
+        self.open_replayed_chunk_err(&chunks, ad);
+        self.open_dropped_chunk_err(&chunks, ad);
+        self.truncated_stream_detected(&chunks, ad);
+        self.failed_open_does_not_advance(&chunks, ad);
+    }
+
+    /// Used when quickcheck is not available to generate input.
+    /// Default input data is used instead.
+    pub fn run_all_tests(&self) {
+        self.run_all_tests_property(
+            &Self::DEFAULT_INPUT,
+            &Self::DEFAULT_CHUNKING,
+            &Self::DEFAULT_AD,
+        );
+        // A stream consisting of a single chunk.
+        self.run_all_tests_property(&Self::DEFAULT_INPUT, &[0u8; 0], &Self::DEFAULT_AD);
+        self.run_all_tests_property(&Self::DEFAULT_INPUT, &Self::DEFAULT_CHUNKING, &[0u8; 0]);
+    }
+
+    /// Split `data` into chunks with the sizes in `chunking`, which are reduced
+    /// modulo 128, repeating them until all of `data` has been split. The last chunk
+    /// holds the remaining data. Zero-sized chunks are kept, as they're valid
+    /// messages in a stream.
+    fn split<'a>(data: &'a [u8], chunking: &[u8]) -> Vec<&'a [u8]> {
+        let mut chunks: Vec<&[u8]> = Vec::new();
+        let mut rest = data;
+
+        if chunking.iter().any(|size| size % 128 != 0) {
+            for size in chunking.iter().cycle() {
+                let size = core::cmp::min((size % 128) as usize, rest.len());
+                if size == rest.len() {
+                    break;
+                }
+                let (chunk, remaining) = rest.split_at(size);
+                chunks.push(chunk);
+                rest = remaining;
+            }
+        } else {
+            chunks.extend(chunking.iter().map(|_| &[0u8; 0][..]));
+        }
+
+        chunks.push(rest);
+        chunks
+    }
+
+    /// The additional data used for the chunk at `idx`. Chunks alternate
+    /// between having `ad` and no additional data, so that contexts can't
+    /// assume it is the same for all chunks.
+    fn ad_for(ad: &[u8], idx: usize) -> Option<&[u8]> {
+        if idx & 1 == 0 {
+            Some(ad)
+        } else {
+            None
+        }
+    }
+
+    /// Seal all `chunks` with a new context, tagging the last one as final.
+    fn seal_stream(&self, chunks: &[&[u8]], ad: &[u8]) -> Vec<Vec<u8>> {
+        let mut ctx = (self.new_context)();
+        let mut sealed: Vec<Vec<u8>> = Vec::with_capacity(chunks.len());
+
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let tag = if idx == chunks.len() - 1 {
+                T::final_tag()
+            } else {
+                T::message_tag()
+            };
+
+            let mut dst_out = vec![0u8; chunk.len() + T::ABYTES];
+            ctx.seal_chunk(chunk, Self::ad_for(ad, idx), &mut dst_out, tag)
+                .unwrap();
+            sealed.push(dst_out);
+        }
+
+        sealed
+    }
+
+    /// Open the `sealed` chunks in order with a new context, where chunk
+    /// `idx` is opened with the additional data of chunk `ad_offset + idx`.
+    /// Returns the plaintext and tag of each chunk, or the first error.
+    fn open_stream(
+        &self,
+        sealed: &[Vec<u8>],
+        ad: &[u8],
+        ad_offset: usize,
+    ) -> Result<OpenedStream<T::Tag>, UnknownCryptoError> {
+        let mut ctx = (self.new_context)();
+        let mut opened: OpenedStream<T::Tag> = Vec::with_capacity(sealed.len());
+
+        for (idx, chunk) in sealed.iter().enumerate() {
+            let mut dst_out = vec![0u8; chunk.len().saturating_sub(T::ABYTES)];
+            let tag = ctx.open_chunk(chunk, Self::ad_for(ad, ad_offset + idx), &mut dst_out)?;
+            opened.push((dst_out, tag));
+        }
+
+        Ok(opened)
+    }
+
+    /// Sealing and then opening the chunks of a stream produces the original
+    /// chunks and tags.
+    fn seal_open_chunks(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+        let opened = self.open_stream(&sealed, ad, 0).unwrap();
+
+        assert_eq!(opened.len(), chunks.len());
+        for (idx, (plaintext, tag)) in opened.iter().enumerate() {
+            assert_eq!(&plaintext[..], chunks[idx]);
+            if idx == chunks.len() - 1 {
+                assert_eq!(*tag, T::final_tag());
+            } else {
+                assert_eq!(*tag, T::message_tag());
+            }
+        }
+    }
+
+    /// A new context with the same key and nonce starts the same stream again,
+    /// regardless of what the previous context processed.
+    fn new_context_same_stream(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed_1 = self.seal_stream(chunks, ad);
+        let sealed_2 = self.seal_stream(chunks, ad);
+        assert_eq!(sealed_1, sealed_2);
+
+        // The same chunk sealed at a later position in the stream differs.
+        let mut ctx = (self.new_context)();
+        let mut first = vec![0u8; chunks[0].len() + T::ABYTES];
+        let mut second = first.clone();
+        ctx.seal_chunk(chunks[0], Some(ad), &mut first, T::message_tag())
+            .unwrap();
+        ctx.seal_chunk(chunks[0], Some(ad), &mut second, T::message_tag())
+            .unwrap();
+        assert_ne!(first, second);
+    }
+
+    /// Opening any chunk with different additional data than it was sealed
+    /// with fails.
+    fn open_modified_ad_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+
+        if !ad.is_empty() {
+            // Shifting the pattern makes every chunk use the other choice of
+            // additional data, so opening fails at the first chunk.
+            assert!(self.open_stream(&sealed, ad, 1).is_err());
+        }
+
+        let mut other_ad = ad.to_vec();
+        other_ad.push(0u8);
+        assert!(self.open_stream(&sealed, &other_ad, 0).is_err());
+    }
+
+    /// Flipping a bit anywhere in any chunk makes opening fail.
+    fn open_modified_chunk_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+
+        for chunk_idx in 0..sealed.len() {
+            for byte_idx in 0..sealed[chunk_idx].len() {
+                let mut modified = sealed.clone();
+                modified[chunk_idx][byte_idx] ^= 1;
+                assert!(self.open_stream(&modified, ad, 0).is_err());
+            }
+        }
+    }
+
+    /// Opening a chunk that is shorter than when it was sealed fails.
+    fn open_truncated_chunk_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+
+        for chunk_idx in 0..sealed.len() {
+            let mut truncated = sealed.clone();
+            truncated[chunk_idx].pop();
+            assert!(self.open_stream(&truncated, ad, 0).is_err());
+        }
+    }
+
+    /// Opening the chunks of a stream in a different order fails.
+    fn open_reordered_chunks_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let mut sealed = self.seal_stream(chunks, ad);
+        if sealed.len() < 3 {
+            return;
+        }
+
+        // Swap chunks two apart, so that the pattern of additional data stays
+        // the same and only their position in the stream differs.
+        sealed.swap(0, 2);
+        assert!(self.open_stream(&sealed, ad, 0).is_err());
+    }
+
+    /// Opening the same chunk twice fails the second time.
+    fn open_replayed_chunk_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+
+        let mut ctx = (self.new_context)();
+        let mut dst_out = vec![0u8; chunks[0].len()];
+        assert!(ctx
+            .open_chunk(&sealed[0], Self::ad_for(ad, 0), &mut dst_out)
+            .is_ok());
+        assert!(ctx
+            .open_chunk(&sealed[0], Self::ad_for(ad, 0), &mut dst_out)
+            .is_err());
+    }
+
+    /// Opening a stream with a chunk missing in the middle fails.
+    fn open_dropped_chunk_err(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let mut sealed = self.seal_stream(chunks, ad);
+        if sealed.len() < 3 {
+            return;
+        }
+
+        // Drop two chunks so that the pattern of additional data stays the same.
+        sealed.drain(0..2);
+        assert!(self.open_stream(&sealed, ad, 0).is_err());
+    }
+
+    /// A stream that is missing its final chunk still opens, but its last
+    /// opened chunk isn't tagged as final, which lets the receiver detect
+    /// the truncation.
+    fn truncated_stream_detected(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let mut sealed = self.seal_stream(chunks, ad);
+        sealed.pop();
+
+        let opened = self.open_stream(&sealed, ad, 0).unwrap();
+        assert_eq!(opened.len(), chunks.len() - 1);
+        assert!(opened.iter().all(|(_, tag)| *tag != T::final_tag()));
+    }
+
+    /// A chunk that fails to open does not advance the state of the context,
+    /// so the authentic chunk can still be opened afterwards.
+    fn failed_open_does_not_advance(&self, chunks: &[&[u8]], ad: &[u8]) {
+        let sealed = self.seal_stream(chunks, ad);
+        let mut modified = sealed[0].clone();
+        modified[0] ^= 1;
+
+        let mut ctx = (self.new_context)();
+        let mut dst_out = vec![0u8; chunks[0].len()];
+        assert!(ctx
+            .open_chunk(&modified, Self::ad_for(ad, 0), &mut dst_out)
+            .is_err());
+        assert_eq!(
+            ctx.open_chunk(&sealed[0], Self::ad_for(ad, 0), &mut dst_out)
+                .unwrap(),
+            if sealed.len() == 1 {
+                T::final_tag()
+            } else {
+                T::message_tag()
+            }
+        );
+        assert_eq!(&dst_out[..], chunks[0]);
+    }
+}
@@ -125,6 +125,60 @@ macro_rules! impl_from_trait (($name:ident, $size:expr) => (
     }
 ));
 
+/// Macro that implements the `Clone` trait on a object called `$name`, only
+/// in orion's own tests. `quickcheck::Arbitrary` requires `Clone`, but types
+/// containing sensitive data should otherwise not be cloned.
+macro_rules! impl_test_clone_trait (($name:ident) => (
+    #[cfg(test)]
+    impl Clone for $name {
+        fn clone(&self) -> $name {
+            $name {
+                value: self.value.clone(),
+                original_length: self.original_length,
+            }
+        }
+    }
+));
+
+/// Macro that implements the `quickcheck::Arbitrary` trait on a object
+/// called `$name`, when the `test_utils` feature is enabled. The generated
+/// objects are always valid: they have a random length within the inclusive
+/// range $lower_bound..=$upper_bound and random contents.
+macro_rules! impl_arbitrary_trait (($name:ident, $lower_bound:expr, $upper_bound:expr) => (
+    #[cfg(feature = "test_utils")]
+    impl ::quickcheck::Arbitrary for $name {
+        // Fixed-size types have $lower_bound == $upper_bound.
+        #[allow(clippy::modulo_one)]
+        fn arbitrary(g: &mut ::quickcheck::Gen) -> $name {
+            let len = $lower_bound + (<usize as ::quickcheck::Arbitrary>::arbitrary(g) % ($upper_bound - $lower_bound + 1));
+            let mut value = [0u8; $upper_bound];
+            for byte in value[..len].iter_mut() {
+                *byte = <u8 as ::quickcheck::Arbitrary>::arbitrary(g);
+            }
+
+            $name::from_slice(&value[..len]).unwrap()
+        }
+    }
+));
+
+#[cfg(feature = "safe_api")]
+/// Macro that implements the `quickcheck::Arbitrary` trait on a object
+/// called `$name`, that is stored on the heap, when the `test_utils` feature
+/// is enabled. The generated objects always contain at least one byte.
+macro_rules! impl_arbitrary_trait_variable_size (($name:ident) => (
+    #[cfg(feature = "test_utils")]
+    impl ::quickcheck::Arbitrary for $name {
+        fn arbitrary(g: &mut ::quickcheck::Gen) -> $name {
+            let mut value = <Vec<u8> as ::quickcheck::Arbitrary>::arbitrary(g);
+            if value.is_empty() {
+                value.push(<u8 as ::quickcheck::Arbitrary>::arbitrary(g));
+            }
+
+            $name::from_slice(&value).unwrap()
+        }
+    }
+));
+
 ///
 /// Function implementation macros
 
@@ -300,6 +354,25 @@ macro_rules! test_as_bytes_and_get_length (($name:ident, $lower_bound:expr, $upp
     }
 ));
 
+/// Test that objects generated by `quickcheck::Arbitrary` are within their
+/// bounds, can be re-created from their bytes and are equal to their clone.
+#[cfg(test)]
+macro_rules! test_arbitrary (($name:ident, $lower_bound:expr, $upper_bound:expr, $bytes_function:ident) => (
+    #[test]
+    #[cfg(feature = "test_utils")]
+    fn test_arbitrary() {
+        use quickcheck::{Arbitrary, Gen};
+
+        let mut g = Gen::new(128);
+        for _ in 0..128 {
+            let generated = $name::arbitrary(&mut g);
+            assert!(($lower_bound..=$upper_bound).contains(&generated.len()));
+            assert!($name::from_slice(generated.$bytes_function()).unwrap() == generated);
+            assert!(generated.clone() == generated);
+        }
+    }
+));
+
 #[cfg(test)]
 #[cfg(feature = "safe_api")]
 macro_rules! test_generate (($name:ident, $gen_length:expr) => (
@@ -424,6 +497,9 @@ macro_rules! construct_secret_key {
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
         impl_ct_partialeq_trait!($name, unprotected_as_bytes);
+        impl_test_clone_trait!($name);
+        #[cfg(test)]
+        impl_arbitrary_trait!($name, $lower_bound, $upper_bound);
 
         impl $name {
             func_from_slice!($name, $lower_bound, $upper_bound);
@@ -439,6 +515,7 @@ macro_rules! construct_secret_key {
             test_bound_parameters!($name, $lower_bound, $upper_bound, $gen_length);
             test_from_slice!($name, $lower_bound, $upper_bound);
             test_as_bytes_and_get_length!($name, $lower_bound, $upper_bound, unprotected_as_bytes);
+            test_arbitrary!($name, $lower_bound, $upper_bound, unprotected_as_bytes);
             test_partial_eq!($name, $upper_bound);
 
             #[cfg(test)]
@@ -482,6 +559,7 @@ macro_rules! construct_public {
         impl_ct_partialeq_trait!($name, as_ref);
         impl_normal_debug_trait!($name);
         impl_asref_trait!($name);
+        impl_arbitrary_trait!($name, $lower_bound, $upper_bound);
 
         impl $name {
             func_from_slice!($name, $lower_bound, $upper_bound);
@@ -496,6 +574,7 @@ macro_rules! construct_public {
             test_bound_parameters!($name, $lower_bound, $upper_bound, $upper_bound);
             test_from_slice!($name, $lower_bound, $upper_bound);
             test_as_bytes_and_get_length!($name, $lower_bound, $upper_bound, as_ref);
+            test_arbitrary!($name, $lower_bound, $upper_bound, as_ref);
             test_partial_eq!($name, $upper_bound);
 
             #[cfg(test)]
@@ -521,6 +600,7 @@ macro_rules! construct_public {
         impl_ct_partialeq_trait!($name, as_ref);
         impl_normal_debug_trait!($name);
         impl_asref_trait!($name);
+        impl_arbitrary_trait!($name, $lower_bound, $upper_bound);
 
         impl $name {
             func_from_slice!($name, $lower_bound, $upper_bound);
@@ -534,6 +614,7 @@ macro_rules! construct_public {
             test_bound_parameters!($name, $lower_bound, $upper_bound, $upper_bound);
             test_from_slice!($name, $lower_bound, $upper_bound);
             test_as_bytes_and_get_length!($name, $lower_bound, $upper_bound, as_ref);
+            test_arbitrary!($name, $lower_bound, $upper_bound, as_ref);
             test_partial_eq!($name, $upper_bound);
 
             #[cfg(test)]
@@ -587,6 +668,7 @@ macro_rules! construct_tag {
 
         impl_omitted_debug_trait!($name);
         impl_ct_partialeq_trait!($name, unprotected_as_bytes);
+        impl_arbitrary_trait!($name, $lower_bound, $upper_bound);
 
         impl $name {
             func_from_slice!($name, $lower_bound, $upper_bound);
@@ -602,6 +684,7 @@ macro_rules! construct_tag {
             test_bound_parameters!($name, $lower_bound, $upper_bound, $upper_bound);
             test_from_slice!($name, $lower_bound, $upper_bound);
             test_as_bytes_and_get_length!($name, $lower_bound, $upper_bound, unprotected_as_bytes);
+            test_arbitrary!($name, $lower_bound, $upper_bound, unprotected_as_bytes);
             test_partial_eq!($name, $upper_bound);
 
             #[cfg(test)]
@@ -619,7 +702,7 @@ macro_rules! construct_tag {
 /// to the required length specified by the HMAC specifications.
 macro_rules! construct_hmac_key {
     ($(#[$meta:meta])*
-    ($name:ident, $test_module_name:ident, $size:expr)) => (
+    ($name:ident, $test_module_name:ident, $hash:ty, $size:expr)) => (
         $(#[$meta])*
         ///
         /// # Security:
@@ -651,19 +734,38 @@ macro_rules! construct_hmac_key {
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
         impl_ct_partialeq_trait!($name, unprotected_as_bytes);
+        impl_test_clone_trait!($name);
+
+        #[cfg(all(test, feature = "test_utils"))]
+        impl ::quickcheck::Arbitrary for $name {
+            // Any byte slice is a valid HMAC key, including ones longer than
+            // the block size, which are hashed.
+            fn arbitrary(g: &mut ::quickcheck::Gen) -> $name {
+                let len = <usize as ::quickcheck::Arbitrary>::arbitrary(g) % ($size * 2 + 1);
+                let mut slice = [0u8; $size * 2];
+                for byte in slice[..len].iter_mut() {
+                    *byte = <u8 as ::quickcheck::Arbitrary>::arbitrary(g);
+                }
+
+                $name::from_slice(&slice[..len]).unwrap()
+            }
+        }
 
         impl $name {
             #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
             /// Construct from a given byte slice.
             pub fn from_slice(slice: &[u8]) -> Result<$name, UnknownCryptoError> {
-                use crate::hazardous::hash::sha512::{Sha512, SHA512_OUTSIZE};
+                use crate::hazardous::hash::ShaHash;
 
                 let mut secret_key = [0u8; $size];
 
                 let slice_len = slice.len();
 
                 if slice_len > $size {
-                    secret_key[..SHA512_OUTSIZE].copy_from_slice(&Sha512::digest(slice)?.as_ref());
+                    <$hash as ShaHash>::digest_into(
+                        slice,
+                        &mut secret_key[..<$hash as ShaHash>::OUTSIZE],
+                    )?;
                 } else {
                     secret_key[..slice_len].copy_from_slice(slice);
                 }
@@ -680,6 +782,7 @@ macro_rules! construct_hmac_key {
         mod $test_module_name {
             use super::*;
             test_as_bytes_and_get_length!($name, $size, $size, unprotected_as_bytes);
+            test_arbitrary!($name, $size, $size, unprotected_as_bytes);
             test_partial_eq!($name, $size);
 
             #[test]
@@ -745,6 +848,9 @@ macro_rules! construct_secret_key_variable_size {
         impl_drop_trait!($name);
         impl_ct_partialeq_trait!($name, unprotected_as_bytes);
         impl_default_trait!($name, $default_size);
+        impl_test_clone_trait!($name);
+        #[cfg(test)]
+        impl_arbitrary_trait_variable_size!($name);
 
         impl $name {
             func_from_slice_variable_size!($name);
@@ -759,6 +865,7 @@ macro_rules! construct_secret_key_variable_size {
 
             test_from_slice_variable!($name);
             test_as_bytes_and_get_length!($name, 1, $default_size + 1, unprotected_as_bytes);
+            test_arbitrary!($name, 1, usize::MAX, unprotected_as_bytes);
             test_generate_variable!($name);
             test_omitted_debug!($name, $default_size);
             test_partial_eq!($name, $default_size);
@@ -775,6 +882,7 @@ macro_rules! construct_salt_variable_size {
         #[cfg(feature = "safe_api")]
         $(#[$meta])*
         ///
+        #[derive(Clone)]
         pub struct $name {
             value: Vec<u8>,
             original_length: usize,
@@ -784,6 +892,7 @@ macro_rules! construct_salt_variable_size {
         impl_default_trait!($name, $default_size);
         impl_ct_partialeq_trait!($name, as_ref);
         impl_asref_trait!($name);
+        impl_arbitrary_trait_variable_size!($name);
 
         impl $name {
             func_from_slice_variable_size!($name);
@@ -797,6 +906,7 @@ macro_rules! construct_salt_variable_size {
 
             test_from_slice_variable!($name);
             test_as_bytes_and_get_length!($name, 1, $default_size + 1, as_ref);
+            test_arbitrary!($name, 1, usize::MAX, as_ref);
             test_generate_variable!($name);
             test_partial_eq!($name, $default_size);
             test_normal_debug!($name, $default_size);
@@ -0,0 +1,439 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Minimal CBOR ([RFC 8949]) encoding and decoding, as needed by COSE and
+//! CWT.
+//!
+//! Only integers, byte and text strings, arrays, maps, tags, booleans and
+//! null are supported. Values are always encoded in the preferred
+//! serialization, and decoding rejects indefinite lengths, non-minimal
+//! heads, floating-point values, invalid UTF-8, duplicate map keys and
+//! nesting deeper than `MAX_DEPTH`.
+//!
+//! [RFC 8949]: https://www.rfc-editor.org/rfc/rfc8949
+
+#[cfg(not(feature = "safe_api"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use crate::errors::UnknownCryptoError;
+
+/// The maximum nesting depth of arrays, maps and tags when decoding.
+pub(crate) const MAX_DEPTH: usize = 16;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const SIMPLE_FALSE: u64 = 20;
+const SIMPLE_TRUE: u64 = 21;
+const SIMPLE_NULL: u64 = 22;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A CBOR data item.
+pub(crate) enum Value {
+    Unsigned(u64),
+    /// The integer `-1 - n`.
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    /// The entries of a map, in the order they are encoded.
+    Map(Vec<(Value, Value)>),
+    Tag(u64, Box<Value>),
+    Bool(bool),
+    Null,
+}
+
+/// Write the head of a data item with the major type `major` and argument
+/// `argument` to `dst`.
+fn write_head(major: u8, argument: u64, dst: &mut Vec<u8>) {
+    let major = major << 5;
+    if argument < 24 {
+        dst.push(major | argument as u8);
+    } else if argument <= u64::from(u8::MAX) {
+        dst.push(major | 24);
+        dst.push(argument as u8);
+    } else if argument <= u64::from(u16::MAX) {
+        dst.push(major | 25);
+        dst.extend_from_slice(&(argument as u16).to_be_bytes());
+    } else if argument <= u64::from(u32::MAX) {
+        dst.push(major | 26);
+        dst.extend_from_slice(&(argument as u32).to_be_bytes());
+    } else {
+        dst.push(major | 27);
+        dst.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+impl Value {
+    /// The integer `value`.
+    pub(crate) fn int(value: i64) -> Self {
+        if value < 0 {
+            Value::Negative(!(value as u64))
+        } else {
+            Value::Unsigned(value as u64)
+        }
+    }
+
+    /// The integer value, if this is an integer that fits in an `i64`.
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Unsigned(n) if *n <= i64::MAX as u64 => Some(*n as i64),
+            Value::Negative(n) if *n <= i64::MAX as u64 => Some(!(*n as i64)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_map(&self) -> Option<&[(Value, Value)]> {
+        match self {
+            Value::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The value of the integer key `key`, if this is a map that has it.
+    pub(crate) fn get(&self, key: i64) -> Option<&Value> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k.as_int() == Some(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Write the encoding of this value to `dst`.
+    pub(crate) fn encode(&self, dst: &mut Vec<u8>) {
+        match self {
+            Value::Unsigned(n) => write_head(MAJOR_UNSIGNED, *n, dst),
+            Value::Negative(n) => write_head(MAJOR_NEGATIVE, *n, dst),
+            Value::Bytes(bytes) => {
+                write_head(MAJOR_BYTES, bytes.len() as u64, dst);
+                dst.extend_from_slice(bytes);
+            }
+            Value::Text(text) => {
+                write_head(MAJOR_TEXT, text.len() as u64, dst);
+                dst.extend_from_slice(text.as_bytes());
+            }
+            Value::Array(items) => {
+                write_head(MAJOR_ARRAY, items.len() as u64, dst);
+                for item in items.iter() {
+                    item.encode(dst);
+                }
+            }
+            Value::Map(entries) => {
+                write_head(MAJOR_MAP, entries.len() as u64, dst);
+                for (key, value) in entries.iter() {
+                    key.encode(dst);
+                    value.encode(dst);
+                }
+            }
+            Value::Tag(tag, value) => {
+                write_head(MAJOR_TAG, *tag, dst);
+                value.encode(dst);
+            }
+            Value::Bool(false) => write_head(MAJOR_SIMPLE, SIMPLE_FALSE, dst),
+            Value::Bool(true) => write_head(MAJOR_SIMPLE, SIMPLE_TRUE, dst),
+            Value::Null => write_head(MAJOR_SIMPLE, SIMPLE_NULL, dst),
+        }
+    }
+
+    /// The encoding of this value.
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        self.encode(&mut encoded);
+        encoded
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Decode `input`, which must be exactly one data item.
+    pub(crate) fn decode(input: &[u8]) -> Result<Self, UnknownCryptoError> {
+        let mut decoder = Decoder { input };
+        let value = decoder.value(0)?;
+        if !decoder.input.is_empty() {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok(value)
+    }
+}
+
+struct Decoder<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: u64) -> Result<&'a [u8], UnknownCryptoError> {
+        if len > self.input.len() as u64 {
+            return Err(UnknownCryptoError);
+        }
+        let (taken, rest) = self.input.split_at(len as usize);
+        self.input = rest;
+
+        Ok(taken)
+    }
+
+    /// Read a head, returning its major type and its argument.
+    fn head(&mut self) -> Result<(u8, u64), UnknownCryptoError> {
+        let first = self.take(1)?[0];
+        let (major, info) = (first >> 5, first & 0x1F);
+        let (argument, minimum) = match info {
+            0..=23 => return Ok((major, u64::from(info))),
+            24 => (u64::from(self.take(1)?[0]), 24),
+            25 => {
+                let bytes = self.take(2)?;
+                (u64::from(u16::from_be_bytes([bytes[0], bytes[1]])), 0x100)
+            }
+            26 => {
+                let bytes = self.take(4)?;
+                let mut argument = [0u8; 4];
+                argument.copy_from_slice(bytes);
+                (u64::from(u32::from_be_bytes(argument)), 0x1_0000)
+            }
+            27 => {
+                let bytes = self.take(8)?;
+                let mut argument = [0u8; 8];
+                argument.copy_from_slice(bytes);
+                (u64::from_be_bytes(argument), 0x1_0000_0000)
+            }
+            // Reserved values and indefinite lengths.
+            _ => return Err(UnknownCryptoError),
+        };
+        // Only the preferred serialization is accepted. This also rejects
+        // floating-point values, which are encoded with 25 to 27.
+        if argument < minimum || major == MAJOR_SIMPLE {
+            return Err(UnknownCryptoError);
+        }
+
+        Ok((major, argument))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, UnknownCryptoError> {
+        if depth > MAX_DEPTH {
+            return Err(UnknownCryptoError);
+        }
+
+        let (major, argument) = self.head()?;
+        match major {
+            MAJOR_UNSIGNED => Ok(Value::Unsigned(argument)),
+            MAJOR_NEGATIVE => Ok(Value::Negative(argument)),
+            MAJOR_BYTES => Ok(Value::Bytes(self.take(argument)?.to_vec())),
+            MAJOR_TEXT => match core::str::from_utf8(self.take(argument)?) {
+                Ok(text) => Ok(Value::Text(text.into())),
+                Err(_) => Err(UnknownCryptoError),
+            },
+            MAJOR_ARRAY => {
+                // Every item is at least one byte.
+                if argument > self.input.len() as u64 {
+                    return Err(UnknownCryptoError);
+                }
+                let mut items = Vec::with_capacity(argument as usize);
+                for _ in 0..argument {
+                    items.push(self.value(depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            MAJOR_MAP => {
+                if argument > self.input.len() as u64 / 2 {
+                    return Err(UnknownCryptoError);
+                }
+                let mut entries: Vec<(Value, Value)> = Vec::with_capacity(argument as usize);
+                for _ in 0..argument {
+                    let key = self.value(depth + 1)?;
+                    if entries.iter().any(|(k, _)| *k == key) {
+                        return Err(UnknownCryptoError);
+                    }
+                    let value = self.value(depth + 1)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::Map(entries))
+            }
+            MAJOR_TAG => Ok(Value::Tag(argument, Box::new(self.value(depth + 1)?))),
+            _ => match argument {
+                SIMPLE_FALSE => Ok(Value::Bool(false)),
+                SIMPLE_TRUE => Ok(Value::Bool(true)),
+                SIMPLE_NULL => Ok(Value::Null),
+                _ => Err(UnknownCryptoError),
+            },
+        }
+    }
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+    use super::*;
+
+    fn roundtrip(value: Value, expected: &str) {
+        let encoded = value.to_vec();
+        assert_eq!(hex::encode(&encoded), expected);
+        assert_eq!(Value::decode(&encoded).unwrap(), value);
+    }
+
+    #[test]
+    fn test_rfc_8949_examples() {
+        // Appendix A.
+        roundtrip(Value::Unsigned(0), "00");
+        roundtrip(Value::Unsigned(23), "17");
+        roundtrip(Value::Unsigned(24), "1818");
+        roundtrip(Value::Unsigned(1000), "1903e8");
+        roundtrip(Value::Unsigned(1_000_000), "1a000f4240");
+        roundtrip(Value::Unsigned(1_000_000_000_000), "1b000000e8d4a51000");
+        roundtrip(Value::Unsigned(u64::MAX), "1bffffffffffffffff");
+        roundtrip(Value::Negative(u64::MAX), "3bffffffffffffffff");
+        roundtrip(Value::int(-1), "20");
+        roundtrip(Value::int(-100), "3863");
+        roundtrip(Value::int(-1000), "3903e7");
+        roundtrip(Value::Bool(false), "f4");
+        roundtrip(Value::Bool(true), "f5");
+        roundtrip(Value::Null, "f6");
+        roundtrip(
+            Value::Tag(1, Box::new(Value::Unsigned(1_363_896_240))),
+            "c11a514b67b0",
+        );
+        roundtrip(Value::Bytes(vec![]), "40");
+        roundtrip(Value::Bytes(vec![1, 2, 3, 4]), "4401020304");
+        roundtrip(Value::Text("".into()), "60");
+        roundtrip(Value::Text("IETF".into()), "6449455446");
+        roundtrip(Value::Text("\u{00fc}".into()), "62c3bc");
+        roundtrip(Value::Array(vec![]), "80");
+        roundtrip(
+            Value::Array(vec![
+                Value::Unsigned(1),
+                Value::Array(vec![Value::Unsigned(2), Value::Unsigned(3)]),
+            ]),
+            "8201820203",
+        );
+        roundtrip(
+            Value::Map(vec![
+                (Value::Text("a".into()), Value::Unsigned(1)),
+                (
+                    Value::Text("b".into()),
+                    Value::Array(vec![Value::Unsigned(2), Value::Unsigned(3)]),
+                ),
+            ]),
+            "a26161016162820203",
+        );
+    }
+
+    #[test]
+    fn test_ints() {
+        for value in [0, 1, -1, 23, -24, -25, i64::MAX, i64::MIN].iter() {
+            assert_eq!(Value::int(*value).as_int(), Some(*value));
+        }
+        assert_eq!(Value::Unsigned(u64::MAX).as_int(), None);
+        assert_eq!(Value::Negative(u64::MAX).as_int(), None);
+        assert_eq!(Value::Null.as_int(), None);
+    }
+
+    #[test]
+    fn test_decode_err() {
+        let bad = [
+            // Trailing data.
+            "0000",
+            // Truncated.
+            "",
+            "18",
+            "1903",
+            "4401",
+            "8201",
+            "a101",
+            // Non-minimal heads.
+            "1817",
+            "190017",
+            "1a0000ffff",
+            "1b00000000ffffffff",
+            "5801ff",
+            // Reserved values and indefinite lengths.
+            "1c",
+            "5fff",
+            "9fff",
+            "bfff",
+            // Floating-point and unsupported simple values.
+            "f90000",
+            "fa47c35000",
+            "fb3ff199999999999a",
+            "f7",
+            "e0",
+            "f820",
+            // Invalid UTF-8.
+            "62c328",
+            // Duplicate map keys.
+            "a2010101",
+            // Lengths that can't fit in the input.
+            "9bffffffffffffffff",
+            "5bffffffffffffffff",
+        ];
+        for input in bad.iter() {
+            assert!(
+                Value::decode(&hex::decode(input).unwrap()).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut nested = Value::Null;
+        for _ in 0..MAX_DEPTH {
+            nested = Value::Array(vec![nested]);
+        }
+        assert!(Value::decode(&nested.to_vec()).is_ok());
+        nested = Value::Array(vec![nested]);
+        assert!(Value::decode(&nested.to_vec()).is_err());
+        nested = Value::Tag(0, Box::new(Value::Null));
+        for _ in 0..MAX_DEPTH {
+            nested = Value::Tag(0, Box::new(nested));
+        }
+        assert!(Value::decode(&nested.to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_get() {
+        let map = Value::Map(vec![
+            (Value::int(1), Value::int(-7)),
+            (Value::int(-1), Value::Text("negative".into())),
+            (Value::Text("1".into()), Value::Null),
+        ]);
+        assert_eq!(map.get(1), Some(&Value::int(-7)));
+        assert_eq!(map.get(-1).unwrap().as_text(), Some("negative"));
+        assert_eq!(map.get(2), None);
+        assert_eq!(Value::Null.get(1), None);
+    }
+}
@@ -0,0 +1,183 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! CPU feature detection, used by primitives to select a backend.
+//!
+//! All accelerated code in orion is written in safe Rust, since the crate
+//! forbids `unsafe`. Accelerated backends are therefore portable code shaped
+//! so that the compiler can lower it to vector instructions (for example,
+//! processing several blocks at once in word-sliced form). Whether such a
+//! backend is worth using depends on the CPU, which is what this module
+//! decides, in one place, for all primitives.
+//!
+//! With `safe_api` (and thereby `std`), the features of the CPU orion runs on
+//! are detected at runtime, using `is_x86_feature_detected!`. Without `std`,
+//! only the features enabled at compile-time (e.g. with `-C target-cpu=native`)
+//! can be detected. The word-sliced backends are only selected when AVX2 is
+//! available, since they are slower than the portable ones with 128-bit
+//! vectors (SSE2, NEON), where the portable backends already use vector
+//! instructions row by row.
+//!
+//! Since the crate cannot compile functions for features the target does not
+//! enable, the word-sliced backends only reach 256-bit vector speeds when AVX2
+//! is also enabled at compile-time.
+//!
+//! Detection runs once and is cached. With `safe_api`, setting the environment
+//! variable [`FORCE_PORTABLE_ENV`] forces the portable backends.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+#[cfg(feature = "safe_api")]
+/// Environment variable which, if set when detection runs, forces all
+/// primitives to use their portable backend.
+pub(crate) const FORCE_PORTABLE_ENV: &str = "ORION_FORCE_PORTABLE";
+
+/// Marks the cache as populated, so that zero means detection has not run.
+const DETECTED: u8 = 1 << 0;
+/// AVX2 on x86/x86_64.
+const AVX2: u8 = 1 << 1;
+
+static FEATURES: AtomicU8 = AtomicU8::new(0);
+static FORCE_PORTABLE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The backend a primitive should use.
+pub(crate) enum Backend {
+    /// Straightforward code processing one block at a time.
+    Portable,
+    /// Code processing several blocks at once, which the compiler can lower to
+    /// 256-bit vector instructions.
+    Vector,
+}
+
+#[cfg(all(feature = "safe_api", any(target_arch = "x86", target_arch = "x86_64")))]
+/// Detect the features of the CPU at runtime.
+fn detect_cpu_features() -> u8 {
+    let mut features = 0u8;
+    if is_x86_feature_detected!("avx2") {
+        features |= AVX2;
+    }
+
+    features
+}
+
+#[cfg(not(all(feature = "safe_api", any(target_arch = "x86", target_arch = "x86_64"))))]
+/// Detect the features enabled at compile-time, where runtime detection is
+/// not available.
+fn detect_cpu_features() -> u8 {
+    let mut features = 0u8;
+    if cfg!(target_feature = "avx2") {
+        features |= AVX2;
+    }
+
+    features
+}
+
+/// Run detection, including reading the environment override if available.
+fn detect() -> u8 {
+    #[cfg(feature = "safe_api")]
+    {
+        if std::env::var_os(FORCE_PORTABLE_ENV).is_some() {
+            FORCE_PORTABLE.store(true, Ordering::Relaxed);
+        }
+    }
+
+    DETECTED | detect_cpu_features()
+}
+
+/// Return the cached feature flags, running detection first if needed.
+fn features() -> u8 {
+    let cached = FEATURES.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+
+    // Racing threads all compute the same value, so there is no need to
+    // synchronize beyond the atomic store.
+    let detected = detect();
+    FEATURES.store(detected, Ordering::Relaxed);
+
+    detected
+}
+
+#[cfg(any(test, feature = "test_utils"))]
+/// Force (or stop forcing) the portable backend for all primitives.
+pub(crate) fn set_force_portable(force: bool) {
+    // Populate the cache first, so a later first detection cannot override
+    // this call with the environment variable.
+    features();
+    FORCE_PORTABLE.store(force, Ordering::Relaxed);
+}
+
+/// Select the backend for primitives that have a vectorized implementation.
+pub(crate) fn backend() -> Backend {
+    let features = features();
+    if FORCE_PORTABLE.load(Ordering::Relaxed) {
+        return Backend::Portable;
+    }
+
+    if features & AVX2 != 0 {
+        Backend::Vector
+    } else {
+        Backend::Portable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection_is_cached() {
+        let first = features();
+        assert!(first & DETECTED != 0);
+        assert_eq!(first, features());
+    }
+
+    #[test]
+    fn test_backend_follows_detected_features() {
+        if !FORCE_PORTABLE.load(Ordering::Relaxed) {
+            assert_eq!(backend() == Backend::Vector, features() & AVX2 != 0);
+        }
+    }
+
+    #[test]
+    fn test_compile_time_features_are_detected() {
+        // Features enabled at compile-time are available on every CPU the
+        // binary runs on, so runtime detection must find them too.
+        if cfg!(target_feature = "avx2") {
+            assert!(detect_cpu_features() & AVX2 != 0);
+        }
+    }
+
+    #[test]
+    fn test_force_portable() {
+        // Other tests may run concurrently, but every backend produces the same
+        // output, so toggling the global override cannot make them fail.
+        set_force_portable(true);
+        assert_eq!(backend(), Backend::Portable);
+        set_force_portable(false);
+        if features() & AVX2 != 0 {
+            assert_eq!(backend(), Backend::Vector);
+        }
+    }
+}
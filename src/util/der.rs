@@ -0,0 +1,557 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Minimal ASN.1 DER parsing and ECDSA signature encoding.
+//!
+//! # About:
+//! - [`DerReader`] parses the DER encoding of a sequence of TLVs
+//!   (tag-length-value), as found in signatures, PKCS#8 and SPKI structures.
+//!   Only single-byte tags and definite lengths of up to 65535 bytes are
+//!   supported. Non-minimal lengths and integers are rejected.
+//! - [`ecdsa_signature_to_der()`] and [`ecdsa_signature_from_der()`] convert
+//!   between the raw (`r || s`) encoding of ECDSA signatures, used by for
+//!   example JWS and WebCrypto, and the DER encoding used by X.509 and TLS.
+//!
+//! orion implements ECDSA over P-256 in [`ecdsa_p256`], which uses the raw
+//! encoding. These are provided for interoperability with other
+//! implementations and formats.
+//!
+//! # Parameters:
+//! - `raw`: A raw ECDSA signature, which is `r` followed by `s`, both of
+//!   `raw.len() / 2` bytes in big-endian.
+//! - `der`: A DER-encoded ECDSA signature.
+//! - `dst`: Destination buffer for the converted signature.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `raw` is empty, its length is odd or greater than `2 * MAX_SCALAR_SIZE`.
+//! - `dst` is shorter than the DER encoding of `raw`. A `dst` of
+//!   `MAX_SIGNATURE_DER_SIZE` bytes always suffices.
+//! - `der` is not a valid DER encoding of an ECDSA signature.
+//! - `r` or `s` in `der` do not fit in `dst.len() / 2` bytes.
+//! - `dst` for a raw signature is empty or its length is odd.
+//!
+//! # Security:
+//! - Parsing and encoding branch on the lengths and leading bytes of values.
+//!   This is fine for signatures and public keys, but these functions leak
+//!   the number of leading zero bytes of secret values, such as private keys
+//!   in PKCS#8.
+//! - A successfully parsed signature is not necessarily valid. `r` and `s`
+//!   may be zero or greater than the group order.
+//!
+//! # Example:
+//! ```rust
+//! use orion::util::der;
+//!
+//! // A P-256 signature, as returned by WebCrypto.
+//! let raw = [0x80u8; 64];
+//!
+//! let mut der_sig = [0u8; der::MAX_SIGNATURE_DER_SIZE];
+//! let der_len = der::ecdsa_signature_to_der(&raw, &mut der_sig)?;
+//!
+//! let mut raw_again = [0u8; 64];
+//! der::ecdsa_signature_from_der(&der_sig[..der_len], &mut raw_again)?;
+//! assert_eq!(raw[..], raw_again[..]);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`DerReader`]: struct.DerReader.html
+//! [`ecdsa_signature_to_der()`]: fn.ecdsa_signature_to_der.html
+//! [`ecdsa_signature_from_der()`]: fn.ecdsa_signature_from_der.html
+//! [`ecdsa_p256`]: ../../hazardous/ecc/ecdsa_p256/index.html
+
+use crate::errors::UnknownCryptoError;
+
+/// The DER tag of an INTEGER.
+pub const TAG_INTEGER: u8 = 0x02;
+/// The DER tag of a BIT STRING.
+pub const TAG_BIT_STRING: u8 = 0x03;
+/// The DER tag of an OCTET STRING.
+pub const TAG_OCTET_STRING: u8 = 0x04;
+/// The DER tag of NULL.
+pub const TAG_NULL: u8 = 0x05;
+/// The DER tag of an OBJECT IDENTIFIER.
+pub const TAG_OID: u8 = 0x06;
+/// The DER tag of a SEQUENCE.
+pub const TAG_SEQUENCE: u8 = 0x30;
+
+/// The size (bytes) of the largest supported ECDSA scalar, which is that of
+/// P-521.
+pub const MAX_SCALAR_SIZE: usize = 66;
+/// The size (bytes) of the longest DER-encoded ECDSA signature with scalars
+/// of `MAX_SCALAR_SIZE` bytes.
+pub const MAX_SIGNATURE_DER_SIZE: usize = 3 + 2 * (2 + MAX_SCALAR_SIZE + 1);
+
+#[derive(Debug, Clone)]
+/// A reader of DER-encoded TLVs.
+pub struct DerReader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    /// Read from the start of `input`.
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    /// Return `true` if all input has been read.
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Return an error if any input remains unread.
+    pub fn finish(self) -> Result<(), UnknownCryptoError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(UnknownCryptoError)
+        }
+    }
+
+    /// Take the first `len` bytes of the remaining input.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], UnknownCryptoError> {
+        if len > self.input.len() {
+            return Err(UnknownCryptoError);
+        }
+        let (taken, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        Ok(taken)
+    }
+
+    /// Read a definite length in its minimal encoding.
+    fn read_length(&mut self) -> Result<usize, UnknownCryptoError> {
+        let first = self.take(1)?[0];
+        match first {
+            0x00..=0x7F => Ok(usize::from(first)),
+            0x81 => {
+                let len = self.take(1)?[0];
+                // Lengths below 128 must use the short form.
+                if len < 0x80 {
+                    return Err(UnknownCryptoError);
+                }
+                Ok(usize::from(len))
+            }
+            0x82 => {
+                let len = self.take(2)?;
+                let len = (usize::from(len[0]) << 8) | usize::from(len[1]);
+                if len < 0x100 {
+                    return Err(UnknownCryptoError);
+                }
+                Ok(len)
+            }
+            // Indefinite lengths and lengths above 65535 bytes.
+            _ => Err(UnknownCryptoError),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Read a TLV with the tag `tag`, and return its value.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], UnknownCryptoError> {
+        if self.take(1)?[0] != tag {
+            return Err(UnknownCryptoError);
+        }
+        let len = self.read_length()?;
+
+        self.take(len)
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Read a TLV with the tag `tag` and return its value, if the next TLV
+    /// has this tag. Otherwise, nothing is read and `None` is returned.
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, UnknownCryptoError> {
+        match self.input.first() {
+            Some(next) if *next == tag => self.read(tag).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Read a TLV with the tag `tag`, and return its complete encoding.
+    pub fn read_tlv(&mut self, tag: u8) -> Result<&'a [u8], UnknownCryptoError> {
+        let input = self.input;
+        self.read(tag)?;
+
+        Ok(&input[..input.len() - self.input.len()])
+    }
+
+    #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+    /// Read a non-negative INTEGER, and return its big-endian value without
+    /// the leading zero byte that DER requires for values with the high bit set.
+    /// The value zero is returned as a single zero byte.
+    pub fn read_unsigned_integer(&mut self) -> Result<&'a [u8], UnknownCryptoError> {
+        let value = self.read(TAG_INTEGER)?;
+        match value {
+            [] => Err(UnknownCryptoError),
+            // Negative.
+            [first, ..] if first & 0x80 != 0 => Err(UnknownCryptoError),
+            [0, second, ..] if second & 0x80 == 0 => Err(UnknownCryptoError),
+            [0, rest @ ..] if !rest.is_empty() => Ok(rest),
+            _ => Ok(value),
+        }
+    }
+}
+
+/// Write the DER encoding of `len` to the start of `dst`, returning the
+/// number of bytes written. `len` must be less than 65536.
+pub(crate) fn write_length(len: usize, dst: &mut [u8]) -> Result<usize, UnknownCryptoError> {
+    let encoded: &[u8] = match len {
+        0x00..=0x7F => &[len as u8],
+        0x80..=0xFF => &[0x81, len as u8],
+        0x100..=0xFFFF => &[0x82, (len >> 8) as u8, len as u8],
+        _ => return Err(UnknownCryptoError),
+    };
+    if dst.len() < encoded.len() {
+        return Err(UnknownCryptoError);
+    }
+    dst[..encoded.len()].copy_from_slice(encoded);
+
+    Ok(encoded.len())
+}
+
+/// Strip leading zero bytes from the big-endian `scalar`, leaving at least
+/// one byte, and return it with whether it needs a zero byte prepended.
+fn minimal_unsigned(scalar: &[u8]) -> (&[u8], bool) {
+    let zeroes = scalar.iter().take_while(|b| **b == 0).count();
+    let value = &scalar[core::cmp::min(zeroes, scalar.len() - 1)..];
+
+    (value, value[0] & 0x80 != 0)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Convert the raw ECDSA signature `raw` to DER, returning the number of
+/// bytes written to `dst`.
+pub fn ecdsa_signature_to_der(raw: &[u8], dst: &mut [u8]) -> Result<usize, UnknownCryptoError> {
+    if raw.is_empty() || raw.len() & 1 != 0 || raw.len() > 2 * MAX_SCALAR_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let (r, s) = raw.split_at(raw.len() / 2);
+    let (r, r_pad) = minimal_unsigned(r);
+    let (s, s_pad) = minimal_unsigned(s);
+    let r_len = r.len() + usize::from(r_pad);
+    let s_len = s.len() + usize::from(s_pad);
+    // Both integers are shorter than 128 bytes, so their lengths take one byte.
+    let content_len = 2 + r_len + 2 + s_len;
+
+    if dst.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+    dst[0] = TAG_SEQUENCE;
+    let mut pos = 1 + write_length(content_len, &mut dst[1..])?;
+    if dst.len() < pos + content_len {
+        return Err(UnknownCryptoError);
+    }
+
+    for (value, pad, len) in [(r, r_pad, r_len), (s, s_pad, s_len)].iter() {
+        dst[pos] = TAG_INTEGER;
+        dst[pos + 1] = *len as u8;
+        pos += 2;
+        if *pad {
+            dst[pos] = 0;
+            pos += 1;
+        }
+        dst[pos..pos + value.len()].copy_from_slice(value);
+        pos += value.len();
+    }
+
+    Ok(pos)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Convert the DER-encoded ECDSA signature `der` to its raw encoding, with
+/// `r` and `s` left-padded with zeroes to `dst.len() / 2` bytes each.
+pub fn ecdsa_signature_from_der(der: &[u8], dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+    if dst.is_empty() || dst.len() & 1 != 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut outer = DerReader::new(der);
+    let mut sequence = DerReader::new(outer.read(TAG_SEQUENCE)?);
+    outer.finish()?;
+    let r = sequence.read_unsigned_integer()?;
+    let s = sequence.read_unsigned_integer()?;
+    sequence.finish()?;
+
+    let scalar_size = dst.len() / 2;
+    if r.len() > scalar_size || s.len() > scalar_size {
+        return Err(UnknownCryptoError);
+    }
+
+    for byte in dst.iter_mut() {
+        *byte = 0;
+    }
+    let (dst_r, dst_s) = dst.split_at_mut(scalar_size);
+    dst_r[scalar_size - r.len()..].copy_from_slice(r);
+    dst_s[scalar_size - s.len()..].copy_from_slice(s);
+
+    Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+
+    // The P-256 signature from RFC 6979, A.2.5, "sample" with SHA-256.
+    const P256_RAW: [u8; 64] = [
+        0xEF, 0xD4, 0x8B, 0x2A, 0xAC, 0xB6, 0xA8, 0xFD, 0x11, 0x40, 0xDD, 0x9C, 0xD4, 0x5E, 0x81,
+        0xD6, 0x9D, 0x2C, 0x87, 0x7B, 0x56, 0xAA, 0xF9, 0x91, 0xC3, 0x4D, 0x0E, 0xA8, 0x4E, 0xAF,
+        0x37, 0x16, 0xF7, 0xCB, 0x1C, 0x94, 0x2D, 0x65, 0x7C, 0x41, 0xD4, 0x36, 0xC7, 0xA1, 0xB6,
+        0xE2, 0x9F, 0x65, 0xF3, 0xE9, 0x00, 0xDB, 0xB9, 0xAF, 0xF4, 0x06, 0x4D, 0xC4, 0xAB, 0x2F,
+        0x84, 0x3A, 0xCD, 0xA8,
+    ];
+
+    fn p256_der() -> [u8; 72] {
+        let mut expected = [0u8; 72];
+        // `r` and `s` have their high bit set, so they are prefixed with a
+        // zero byte.
+        expected[..5].copy_from_slice(&[0x30, 0x46, 0x02, 0x21, 0x00]);
+        expected[5..37].copy_from_slice(&P256_RAW[..32]);
+        expected[37..40].copy_from_slice(&[0x02, 0x21, 0x00]);
+        expected[40..].copy_from_slice(&P256_RAW[32..]);
+        expected
+    }
+
+    #[test]
+    fn test_p256_roundtrip() {
+        let mut der = [0u8; MAX_SIGNATURE_DER_SIZE];
+        let len = ecdsa_signature_to_der(&P256_RAW, &mut der).unwrap();
+        assert_eq!(der[..len], p256_der()[..]);
+
+        let mut raw = [0u8; 64];
+        ecdsa_signature_from_der(&der[..len], &mut raw).unwrap();
+        assert_eq!(raw[..], P256_RAW[..]);
+    }
+
+    #[test]
+    fn test_leading_zeroes() {
+        let mut raw = [0u8; 64];
+        raw[31] = 0x01;
+        raw[40] = 0x80;
+
+        let mut der = [0u8; MAX_SIGNATURE_DER_SIZE];
+        let len = ecdsa_signature_to_der(&raw, &mut der).unwrap();
+        let mut expected = [0u8; 32];
+        expected[..9].copy_from_slice(&[0x30, 0x1E, 0x02, 0x01, 0x01, 0x02, 0x19, 0x00, 0x80]);
+        assert_eq!(der[..len], expected[..]);
+
+        let mut raw_again = [0u8; 64];
+        ecdsa_signature_from_der(&der[..len], &mut raw_again).unwrap();
+        assert_eq!(raw[..], raw_again[..]);
+
+        // Zero is encoded as a single zero byte.
+        let len = ecdsa_signature_to_der(&[0u8; 64], &mut der).unwrap();
+        assert_eq!(der[..len], [0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_max_size() {
+        let raw = [0xFFu8; 2 * MAX_SCALAR_SIZE];
+        let mut der = [0u8; MAX_SIGNATURE_DER_SIZE];
+        assert_eq!(
+            ecdsa_signature_to_der(&raw, &mut der).unwrap(),
+            MAX_SIGNATURE_DER_SIZE
+        );
+        // The sequence uses the long form of its length.
+        assert_eq!(der[..3], [0x30, 0x81, 0x8A]);
+
+        let mut raw_again = [0u8; 2 * MAX_SCALAR_SIZE];
+        ecdsa_signature_from_der(&der, &mut raw_again).unwrap();
+        assert_eq!(raw[..], raw_again[..]);
+
+        assert!(ecdsa_signature_to_der(&[0xFFu8; 2 * MAX_SCALAR_SIZE + 2], &mut der).is_err());
+        assert!(ecdsa_signature_to_der(&raw, &mut der[..MAX_SIGNATURE_DER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_to_der_err() {
+        let mut der = [0u8; MAX_SIGNATURE_DER_SIZE];
+        assert!(ecdsa_signature_to_der(&[], &mut der).is_err());
+        assert!(ecdsa_signature_to_der(&[1u8; 63], &mut der).is_err());
+        assert!(ecdsa_signature_to_der(&P256_RAW, &mut der[..71]).is_err());
+        assert!(ecdsa_signature_to_der(&P256_RAW, &mut []).is_err());
+        assert!(ecdsa_signature_to_der(&P256_RAW, &mut der[..72]).is_ok());
+    }
+
+    #[test]
+    fn test_from_der_err() {
+        let valid = p256_der();
+        let mut raw = [0u8; 64];
+
+        // Destination too short for `r`, or of odd length.
+        assert!(ecdsa_signature_from_der(&valid, &mut [0u8; 62]).is_err());
+        assert!(ecdsa_signature_from_der(&valid, &mut [0u8; 65]).is_err());
+        assert!(ecdsa_signature_from_der(&valid, &mut []).is_err());
+        // Longer destinations are left-padded.
+        let mut longer = [0u8; 66];
+        ecdsa_signature_from_der(&valid, &mut longer).unwrap();
+        assert_eq!(longer[1..33], P256_RAW[..32]);
+        assert_eq!(longer[34..], P256_RAW[32..]);
+
+        let mutate = |pos: usize, value: u8| {
+            let mut der = valid;
+            der[pos] = value;
+            der
+        };
+        // Wrong outer tag, wrong integer tag, wrong lengths.
+        assert!(ecdsa_signature_from_der(&mutate(0, 0x31), &mut raw).is_err());
+        assert!(ecdsa_signature_from_der(&mutate(2, 0x03), &mut raw).is_err());
+        assert!(ecdsa_signature_from_der(&mutate(1, 0x45), &mut raw).is_err());
+        assert!(ecdsa_signature_from_der(&mutate(1, 0x47), &mut raw).is_err());
+        assert!(ecdsa_signature_from_der(&mutate(3, 0x22), &mut raw).is_err());
+        // Negative `r`.
+        assert!(ecdsa_signature_from_der(&mutate(4, 0x80), &mut raw).is_err());
+        // Non-minimal `r`, where the zero byte is unnecessary.
+        let mut non_minimal = valid;
+        non_minimal[5] = 0x7F;
+        assert!(ecdsa_signature_from_der(&non_minimal, &mut raw).is_err());
+        // Truncated and trailing data.
+        assert!(ecdsa_signature_from_der(&valid[..71], &mut raw).is_err());
+        let mut trailing = [0u8; 73];
+        trailing[..72].copy_from_slice(&valid);
+        assert!(ecdsa_signature_from_der(&trailing, &mut raw).is_err());
+        assert!(ecdsa_signature_from_der(&[], &mut raw).is_err());
+    }
+
+    #[test]
+    fn test_reader_lengths() {
+        // Minimal long form.
+        let mut long = [0xAAu8; 3 + 0x80];
+        long[..3].copy_from_slice(&[TAG_OCTET_STRING, 0x81, 0x80]);
+        let mut reader = DerReader::new(&long);
+        assert_eq!(reader.read(TAG_OCTET_STRING).unwrap().len(), 0x80);
+        assert!(reader.finish().is_ok());
+
+        let mut longer = [0xAAu8; 4 + 0x100];
+        longer[..4].copy_from_slice(&[TAG_OCTET_STRING, 0x82, 0x01, 0x00]);
+        assert_eq!(
+            DerReader::new(&longer)
+                .read(TAG_OCTET_STRING)
+                .unwrap()
+                .len(),
+            0x100
+        );
+
+        let bad: [&[u8]; 6] = [
+            // Long form for a short length.
+            &[TAG_NULL, 0x81, 0x00],
+            &[TAG_OCTET_STRING, 0x82, 0x00, 0x80],
+            // Indefinite length.
+            &[TAG_SEQUENCE, 0x80, 0x00, 0x00],
+            // Lengths longer than two bytes.
+            &[TAG_OCTET_STRING, 0x83, 0x00, 0x01, 0x00],
+            // Missing length and value.
+            &[TAG_NULL],
+            &[TAG_OCTET_STRING, 0x02, 0x00],
+        ];
+        for input in bad.iter() {
+            assert!(DerReader::new(input).read(input[0]).is_err());
+        }
+
+        let mut reader = DerReader::new(&[TAG_NULL, 0x00]);
+        assert!(reader.read(TAG_NULL).unwrap().is_empty());
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_reader_tlv() {
+        let input = [TAG_SEQUENCE, 0x03, TAG_INTEGER, 0x01, 0x05, TAG_NULL, 0x00];
+        let mut reader = DerReader::new(&input);
+        assert_eq!(reader.read_tlv(TAG_SEQUENCE).unwrap(), &input[..5]);
+        assert!(reader.read_tlv(TAG_SEQUENCE).is_err());
+
+        let mut reader = DerReader::new(&input[5..]);
+        assert_eq!(reader.read_tlv(TAG_NULL).unwrap(), &input[5..]);
+        assert!(reader.finish().is_ok());
+    }
+
+    #[test]
+    fn test_reader_optional() {
+        let input = [TAG_INTEGER, 0x01, 0x05, TAG_NULL, 0x00];
+        let mut reader = DerReader::new(&input);
+        assert_eq!(reader.read_optional(TAG_NULL).unwrap(), None);
+        assert_eq!(
+            reader.read_optional(TAG_INTEGER).unwrap(),
+            Some(&[0x05][..])
+        );
+        assert_eq!(reader.read_optional(TAG_NULL).unwrap(), Some(&[][..]));
+        assert_eq!(reader.read_optional(TAG_NULL).unwrap(), None);
+        assert!(reader.finish().is_ok());
+
+        // A matching tag with an invalid length is an error.
+        assert!(DerReader::new(&[TAG_NULL, 0x01])
+            .read_optional(TAG_NULL)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reader_integers() {
+        let read = |input: &'static [u8]| DerReader::new(input).read_unsigned_integer();
+
+        assert_eq!(read(&[TAG_INTEGER, 0x01, 0x00]).unwrap(), &[0x00]);
+        assert_eq!(read(&[TAG_INTEGER, 0x01, 0x7F]).unwrap(), &[0x7F]);
+        assert_eq!(read(&[TAG_INTEGER, 0x02, 0x00, 0x80]).unwrap(), &[0x80]);
+        assert_eq!(
+            read(&[TAG_INTEGER, 0x02, 0x01, 0x00]).unwrap(),
+            &[0x01, 0x00]
+        );
+
+        assert!(read(&[TAG_INTEGER, 0x00]).is_err());
+        assert!(read(&[TAG_INTEGER, 0x01, 0x80]).is_err());
+        assert!(read(&[TAG_INTEGER, 0x02, 0x00, 0x7F]).is_err());
+        assert!(read(&[TAG_INTEGER, 0x02, 0xFF, 0x80]).is_err());
+        assert!(read(&[TAG_OCTET_STRING, 0x01, 0x01]).is_err());
+    }
+
+    // Proptests. Only executed when NOT testing no_std.
+    #[cfg(feature = "safe_api")]
+    mod proptest {
+        use super::*;
+
+        quickcheck! {
+            /// Any raw signature should survive conversion to DER and back.
+            fn prop_roundtrip(input: Vec<u8>) -> bool {
+                let len = core::cmp::min(input.len(), 2 * MAX_SCALAR_SIZE) & !1;
+                if len == 0 {
+                    return true;
+                }
+
+                let mut der = [0u8; MAX_SIGNATURE_DER_SIZE];
+                let der_len = ecdsa_signature_to_der(&input[..len], &mut der).unwrap();
+                let mut raw = vec![0u8; len];
+                ecdsa_signature_from_der(&der[..der_len], &mut raw).unwrap();
+
+                raw[..] == input[..len]
+            }
+        }
+
+        quickcheck! {
+            /// Parsing arbitrary input should never panic.
+            fn prop_from_der_no_panic(input: Vec<u8>) -> bool {
+                let mut raw = [0u8; 64];
+                let _ = ecdsa_signature_from_der(&input, &mut raw);
+                true
+            }
+        }
+    }
+}
@@ -72,13 +72,14 @@ macro_rules! impl_load {
 
 impl_load!(u32, u32, from_le_bytes, load_u32_le);
 
-#[cfg(test)]
 impl_load_into!(u32, u32, from_le_bytes, load_u32_into_le);
 
 impl_load_into!(u64, u64, from_le_bytes, load_u64_into_le);
 
 impl_load_into!(u64, u64, from_be_bytes, load_u64_into_be);
 
+impl_load_into!(u32, u32, from_be_bytes, load_u32_into_be);
+
 impl_store_into!(u32, to_le_bytes, store_u32_into_le);
 
 #[cfg(any(feature = "safe_api", feature = "alloc", test))]
@@ -86,6 +87,8 @@ impl_store_into!(u64, to_le_bytes, store_u64_into_le);
 
 impl_store_into!(u64, to_be_bytes, store_u64_into_be);
 
+impl_store_into!(u32, to_be_bytes, store_u32_into_be);
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -0,0 +1,400 @@
+// MIT License
+
+// Copyright (c) 2018-2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Loading secrets from environment variables and files.
+//!
+//! # About:
+//! - [`from_env()`] and [`from_file()`] read an encoded secret, decode it and
+//!   pass the decoded bytes to a constructor, such as
+//!   `orion::aead::SecretKey::from_slice`. All intermediate copies of the
+//!   secret are zeroized.
+//! - With [`Encoding::Hex`] and [`Encoding::Base64`], any trailing newlines
+//!   (`\n` or `\r\n`) are removed before decoding. With [`Encoding::Raw`], the
+//!   bytes are passed to the constructor as they are.
+//! - Hex is decoded in constant time. Both upper- and lowercase are accepted.
+//! - Base64 uses the standard alphabet, with or without padding.
+//!
+//! # Parameters:
+//! - `name`: The name of the environment variable.
+//! - `path`: The path of the file.
+//! - `encoding`: The [`Encoding`] of the secret.
+//! - `constructor`: Constructs the returned type from the decoded secret.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The environment variable is not set or is not valid unicode.
+//! - The file cannot be opened or read.
+//! - On Unix, the file can be read by users other than its owner and group.
+//! - The secret is not valid hex or Base64, when that is its `encoding`.
+//! - The `constructor` returns an error.
+//!
+//! # Security:
+//! - Only the copies made by this module are zeroized. The environment of the
+//!   process still contains the secret, as does any other copy of the file,
+//!   such as in the page cache.
+//! - Environment variables are inherited by child processes, and may be
+//!   readable by other processes of the same user. Files with restricted
+//!   permissions should be preferred.
+//! - The base64 decoding does NOT run in constant-time.
+//!
+//! # Example:
+//! ```rust
+//! use orion::aead;
+//! use orion::util::load::{self, Encoding};
+//!
+//! # std::env::set_var("ORION_EXAMPLE_KEY", "7fedc9f8f6ab27d4b8f8c3ea77ed87ea2ab5a6a0b75b9f29b7d4ec61b7f3ee02\n");
+//! let key = load::from_env("ORION_EXAMPLE_KEY", Encoding::Hex, aead::SecretKey::from_slice)?;
+//!
+//! let ciphertext = aead::seal(&key, b"Secret message")?;
+//! assert_eq!(aead::open(&key, &ciphertext)?, b"Secret message");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+//! [`from_env()`]: fn.from_env.html
+//! [`from_file()`]: fn.from_file.html
+//! [`Encoding`]: enum.Encoding.html
+//! [`Encoding::Hex`]: enum.Encoding.html#variant.Hex
+//! [`Encoding::Base64`]: enum.Encoding.html#variant.Base64
+//! [`Encoding::Raw`]: enum.Encoding.html#variant.Raw
+
+use crate::errors::UnknownCryptoError;
+use base64::{decode_config_slice, STANDARD};
+use core::convert::TryFrom;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zeroize::Zeroize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The encoding of a loaded secret.
+pub enum Encoding {
+    /// The secret is used as it is.
+    Raw,
+    /// The secret is hex-encoded.
+    Hex,
+    /// The secret is Base64-encoded, using the standard alphabet.
+    Base64,
+}
+
+/// A buffer holding a secret, which is zeroized on drop.
+struct SecretBuffer(Vec<u8>);
+
+impl Drop for SecretBuffer {
+    fn drop(&mut self) {
+        // Zeroize the spare capacity as well, which may still hold parts of
+        // the secret after a truncation.
+        let capacity = self.0.capacity();
+        self.0.resize(capacity, 0);
+        self.0.iter_mut().zeroize();
+    }
+}
+
+/// Decode a single hex character in constant time. Returns the value of the
+/// character and `0xff` if it is valid, or `0x00` otherwise.
+fn decode_hex_char(c: u8) -> (u8, u8) {
+    let c = u16::from(c);
+    // '0'..='9' map to 0..=9 here.
+    let num = c ^ 48;
+    let num_mask = (num.wrapping_sub(10) >> 8) as u8;
+    // 'A'..='F' and 'a'..='f' map to 10..=15 here.
+    let alpha = (c & !32).wrapping_sub(55);
+    let alpha_mask = ((alpha.wrapping_sub(10) ^ alpha.wrapping_sub(16)) >> 8) as u8;
+
+    (
+        (num_mask & num as u8) | (alpha_mask & alpha as u8),
+        num_mask | alpha_mask,
+    )
+}
+
+/// Decode hex `encoded` in constant time.
+fn decode_hex(encoded: &[u8]) -> Result<SecretBuffer, UnknownCryptoError> {
+    if encoded.len() & 1 != 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let mut decoded = SecretBuffer(vec![0u8; encoded.len() / 2]);
+    let mut valid = 0xffu8;
+    for (byte, pair) in decoded.0.iter_mut().zip(encoded.chunks_exact(2)) {
+        let (high, high_valid) = decode_hex_char(pair[0]);
+        let (low, low_valid) = decode_hex_char(pair[1]);
+        *byte = (high << 4) | low;
+        valid &= high_valid & low_valid;
+    }
+
+    if valid == 0xff {
+        Ok(decoded)
+    } else {
+        Err(UnknownCryptoError)
+    }
+}
+
+/// Decode base64 `encoded` into a buffer that is zeroized on drop.
+fn decode_base64(encoded: &[u8]) -> Result<SecretBuffer, UnknownCryptoError> {
+    // Decoding into a buffer of sufficient size avoids reallocations that
+    // would leave copies of the secret behind.
+    let mut decoded = SecretBuffer(vec![0u8; (encoded.len() / 4 + 1) * 3]);
+    let len = decode_config_slice(encoded, STANDARD, &mut decoded.0)?;
+    decoded.0.truncate(len);
+
+    Ok(decoded)
+}
+
+/// Remove any trailing `\n` and `\r\n` from `encoded`.
+fn trim_newlines(mut encoded: &[u8]) -> &[u8] {
+    while encoded.ends_with(b"\n") {
+        encoded = &encoded[..encoded.len() - 1];
+        if encoded.ends_with(b"\r") {
+            encoded = &encoded[..encoded.len() - 1];
+        }
+    }
+
+    encoded
+}
+
+/// Decode `encoded` and pass the result to `constructor`.
+fn decode<T, F>(encoded: &[u8], encoding: Encoding, constructor: F) -> Result<T, UnknownCryptoError>
+where
+    F: FnOnce(&[u8]) -> Result<T, UnknownCryptoError>,
+{
+    match encoding {
+        Encoding::Raw => constructor(encoded),
+        Encoding::Hex => constructor(&decode_hex(trim_newlines(encoded))?.0),
+        Encoding::Base64 => constructor(&decode_base64(trim_newlines(encoded))?.0),
+    }
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Load a secret from the environment variable `name`.
+pub fn from_env<T, F>(
+    name: &str,
+    encoding: Encoding,
+    constructor: F,
+) -> Result<T, UnknownCryptoError>
+where
+    F: FnOnce(&[u8]) -> Result<T, UnknownCryptoError>,
+{
+    let encoded = SecretBuffer(
+        std::env::var(name)
+            .map_err(|_| UnknownCryptoError)?
+            .into_bytes(),
+    );
+    decode(&encoded.0, encoding, constructor)
+}
+
+#[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
+/// Load a secret from the file at `path`.
+pub fn from_file<T, F, P>(
+    path: P,
+    encoding: Encoding,
+    constructor: F,
+) -> Result<T, UnknownCryptoError>
+where
+    F: FnOnce(&[u8]) -> Result<T, UnknownCryptoError>,
+    P: AsRef<Path>,
+{
+    let mut file = File::open(path).map_err(|_| UnknownCryptoError)?;
+    let metadata = file.metadata().map_err(|_| UnknownCryptoError)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o004 != 0 {
+            return Err(UnknownCryptoError);
+        }
+    }
+
+    // Reserving space for the whole file up front avoids reallocations that
+    // would leave copies of the secret behind.
+    let capacity = usize::try_from(metadata.len())
+        .ok()
+        .and_then(|len| len.checked_add(1))
+        .ok_or(UnknownCryptoError)?;
+    let mut encoded = SecretBuffer(Vec::with_capacity(capacity));
+    file.read_to_end(&mut encoded.0)
+        .map_err(|_| UnknownCryptoError)?;
+
+    decode(&encoded.0, encoding, constructor)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::hazardous::stream::chacha20::SecretKey;
+    use std::io::Write;
+
+    const KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const KEY_BASE64: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+
+    fn expected_key() -> SecretKey {
+        SecretKey::from_slice(&(0u8..32).collect::<Vec<u8>>()).unwrap()
+    }
+
+    /// Write `contents` to a new file in the temporary directory, that only
+    /// its owner can read and write.
+    fn write_secret_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("orion-load-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&path).unwrap().write_all(contents).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn test_decode_hex_char() {
+        for c in 0u8..=255 {
+            let (value, valid) = decode_hex_char(c);
+            match (c as char).to_digit(16) {
+                Some(digit) => {
+                    assert_eq!(valid, 0xff);
+                    assert_eq!(u32::from(value), digit);
+                }
+                None => assert_eq!(valid, 0x00),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert!(decode_hex(b"").unwrap().0.is_empty());
+        assert_eq!(&decode_hex(b"00ff7F").unwrap().0, &[0x00, 0xff, 0x7f]);
+        assert!(decode_hex(b"0").is_err());
+        assert!(decode_hex(b"0g").is_err());
+        assert!(decode_hex(b"g0").is_err());
+        assert!(decode_hex(b"00 0").is_err());
+        assert!(decode_hex(b"0x00").is_err());
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert!(decode_base64(b"").unwrap().0.is_empty());
+        assert_eq!(&decode_base64(b"AP9/").unwrap().0, &[0x00, 0xff, 0x7f]);
+        assert_eq!(&decode_base64(b"AP8=").unwrap().0, &[0x00, 0xff]);
+        assert_eq!(&decode_base64(b"AP8").unwrap().0, &[0x00, 0xff]);
+        assert_eq!(&decode_base64(b"AA==").unwrap().0, &[0x00]);
+        assert!(decode_base64(b"A").is_err());
+        assert!(decode_base64(b"AP_/").is_err());
+    }
+
+    #[test]
+    fn test_trim_newlines() {
+        assert_eq!(trim_newlines(b""), b"");
+        assert_eq!(trim_newlines(b"\n"), b"");
+        assert_eq!(trim_newlines(b"ab\n"), b"ab");
+        assert_eq!(trim_newlines(b"ab\r\n"), b"ab");
+        assert_eq!(trim_newlines(b"ab\n\r\n\n"), b"ab");
+        assert_eq!(trim_newlines(b"ab\r"), b"ab\r");
+        assert_eq!(trim_newlines(b"a\nb"), b"a\nb");
+        assert_eq!(trim_newlines(b" ab \n"), b" ab ");
+    }
+
+    #[test]
+    fn test_from_env() {
+        let name = "ORION_TEST_LOAD_FROM_ENV_HEX";
+        std::env::set_var(name, KEY_HEX);
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).unwrap() == expected_key());
+        std::env::set_var(name, format!("{}\n", KEY_HEX.to_uppercase()));
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).unwrap() == expected_key());
+
+        let name = "ORION_TEST_LOAD_FROM_ENV_BASE64";
+        std::env::set_var(name, format!("{}\r\n", KEY_BASE64));
+        assert!(from_env(name, Encoding::Base64, SecretKey::from_slice).unwrap() == expected_key());
+
+        let name = "ORION_TEST_LOAD_FROM_ENV_RAW";
+        std::env::set_var(name, "abcdefghijklmnopqrstuvwxyz012345");
+        let key = from_env(name, Encoding::Raw, SecretKey::from_slice).unwrap();
+        assert!(key == &b"abcdefghijklmnopqrstuvwxyz012345"[..]);
+        // Raw secrets are not trimmed.
+        std::env::set_var(name, "abcdefghijklmnopqrstuvwxyz012345\n");
+        assert!(from_env(name, Encoding::Raw, SecretKey::from_slice).is_err());
+    }
+
+    #[test]
+    fn test_from_env_err() {
+        let name = "ORION_TEST_LOAD_FROM_ENV_ERR";
+        std::env::remove_var(name);
+        assert!(from_env(name, Encoding::Raw, SecretKey::from_slice).is_err());
+
+        // Invalid encoding.
+        std::env::set_var(name, KEY_BASE64);
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).is_err());
+        std::env::set_var(name, format!("{}\n", &KEY_HEX[1..]));
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).is_err());
+        std::env::set_var(name, format!(" {}", KEY_HEX));
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).is_err());
+        std::env::set_var(name, "AP_/");
+        assert!(from_env(name, Encoding::Base64, SecretKey::from_slice).is_err());
+
+        // The constructor rejects the decoded secret.
+        std::env::set_var(name, &KEY_HEX[..62]);
+        assert!(from_env(name, Encoding::Hex, SecretKey::from_slice).is_err());
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = write_secret_file("hex", format!("{}\n", KEY_HEX).as_bytes());
+        assert!(from_file(&path, Encoding::Hex, SecretKey::from_slice).unwrap() == expected_key());
+        std::fs::remove_file(&path).unwrap();
+
+        let path = write_secret_file("base64", KEY_BASE64.as_bytes());
+        assert!(
+            from_file(&path, Encoding::Base64, SecretKey::from_slice).unwrap() == expected_key()
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let raw: Vec<u8> = (0u8..32).collect();
+        let path = write_secret_file("raw", &raw);
+        assert!(from_file(&path, Encoding::Raw, SecretKey::from_slice).unwrap() == expected_key());
+        std::fs::remove_file(&path).unwrap();
+
+        let path = write_secret_file("invalid", b"0g");
+        assert!(from_file(&path, Encoding::Hex, |_| Ok(())).is_err());
+        assert!(from_file(&path, Encoding::Raw, |_| Ok(())).is_ok());
+        assert!(from_file(&path, Encoding::Raw, |_| Err::<(), _>(UnknownCryptoError)).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(from_file(&path, Encoding::Raw, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_from_file_err_world_readable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = write_secret_file("world-readable", KEY_HEX.as_bytes());
+        assert!(from_file(&path, Encoding::Hex, SecretKey::from_slice).is_ok());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(from_file(&path, Encoding::Hex, SecretKey::from_slice).is_err());
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o604)).unwrap();
+        assert!(from_file(&path, Encoding::Hex, SecretKey::from_slice).is_err());
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        assert!(from_file(&path, Encoding::Hex, SecretKey::from_slice).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -34,10 +34,23 @@ macro_rules! xor_slices {
     };
 }
 
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// Minimal CBOR encoding and decoding.
+pub(crate) mod cbor;
+pub(crate) mod cpu;
+/// Minimal ASN.1 DER parsing and ECDSA signature encoding.
+pub mod der;
 pub(crate) mod endianness;
+#[cfg(feature = "safe_api")]
+/// Loading secrets from environment variables and files.
+pub mod load;
 pub(crate) mod u32x4;
 pub(crate) mod u64x4;
 
+#[cfg(all(test, feature = "safe_api"))]
+/// Deterministic randomness for reproducible tests.
+pub(crate) mod test_rng;
+
 #[must_use = "SECURITY WARNING: Ignoring a Result can have real security implications."]
 #[cfg(feature = "safe_api")]
 /// Generate random bytes using a CSPRNG. Not available in `no_std` context.
@@ -78,6 +91,14 @@ pub fn secure_rand_bytes(dst: &mut [u8]) -> Result<(), errors::UnknownCryptoErro
         return Err(errors::UnknownCryptoError);
     }
 
+    // Only orion's own tests can seed the generator.
+    #[cfg(test)]
+    {
+        if test_rng::fill_if_seeded(dst) {
+            return Ok(());
+        }
+    }
+
     getrandom::getrandom(dst).unwrap();
 
     Ok(())
@@ -118,6 +139,97 @@ pub fn secure_cmp(a: &[u8], b: &[u8]) -> Result<(), errors::UnknownCryptoError>
     }
 }
 
+#[cfg(any(test, feature = "test_utils"))]
+/// Force all primitives to use their portable backend.
+///
+/// # About:
+/// Some primitives have several backends, such as one processing multiple
+/// blocks at once that the compiler can lower to vector instructions. The
+/// backend is selected by CPU feature detection, which runs once and is cached.
+/// With `safe_api`, the CPU is queried at runtime. Without it, only features
+/// enabled at compile-time (e.g. with `-C target-cpu=native`) are detected.
+/// All backends produce identical output, so this is only useful for testing
+/// and benchmarking the portable backends on hardware that would otherwise not
+/// use them.
+///
+/// This function is only available with the `test_utils` feature. When orion
+/// is used with the `safe_api` feature, setting the environment variable
+/// `ORION_FORCE_PORTABLE` before the first cryptographic operation has the same
+/// effect as calling this function with `true`.
+///
+/// # Parameters:
+/// - `force`: Whether the portable backends should be forced.
+///
+/// # Example:
+/// ```rust
+/// # #[cfg(feature = "test_utils")] {
+/// use orion::util;
+///
+/// util::force_portable_backends(true);
+/// // ...
+/// util::force_portable_backends(false);
+/// # }
+/// ```
+pub fn force_portable_backends(force: bool) {
+    cpu::set_force_portable(force);
+}
+
+/// Progress reporting and cancellation for long-running operations.
+///
+/// # About:
+/// Operations such as [`hash::digest_from_reader()`] and
+/// [`pwhash::hash_password_with_progress()`] report their progress to a
+/// `Progress` as they go. This can be used to show progress to users, and to
+/// cancel an operation that is no longer needed.
+///
+/// The unit of work depends on the operation, and is documented by it. This is
+/// implemented for all closures of the form `FnMut(u64, Option<u64>) -> bool`.
+///
+/// # Example:
+/// ```rust
+/// use orion::hash;
+///
+/// let data = vec![0u8; 1 << 20];
+///
+/// let mut hashed = 0;
+/// let digest = hash::digest_from_reader(&data[..], &mut |completed: u64, _: Option<u64>| {
+///     hashed = completed;
+///     true
+/// })?;
+/// assert_eq!(hashed, data.len() as u64);
+/// assert_eq!(digest, hash::digest(&data)?);
+///
+/// // Cancel once half of the data has been hashed.
+/// let mut cancel_at_half = |completed: u64, _: Option<u64>| completed < (1 << 19);
+/// assert!(hash::digest_from_reader(&data[..], &mut cancel_at_half).is_err());
+/// # Ok::<(), orion::errors::UnknownCryptoError>(())
+/// ```
+/// [`hash::digest_from_reader()`]: ../hash/fn.digest_from_reader.html
+/// [`pwhash::hash_password_with_progress()`]: ../pwhash/fn.hash_password_with_progress.html
+pub trait Progress {
+    /// Report that `completed` units of work, out of `total` if known, are
+    /// done. Return `false` to cancel the operation, which then returns an
+    /// error.
+    fn report(&mut self, completed: u64, total: Option<u64>) -> bool;
+}
+
+impl<F: FnMut(u64, Option<u64>) -> bool> Progress for F {
+    fn report(&mut self, completed: u64, total: Option<u64>) -> bool {
+        self(completed, total)
+    }
+}
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+/// A `Progress` which ignores all reports and never cancels.
+pub(crate) struct NoProgress;
+
+#[cfg(any(feature = "safe_api", feature = "alloc"))]
+impl Progress for NoProgress {
+    fn report(&mut self, _completed: u64, _total: Option<u64>) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
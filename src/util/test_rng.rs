@@ -0,0 +1,242 @@
+// MIT License
+
+// Copyright (c) 2021 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Deterministic randomness for reproducible tests.
+//!
+//! # About:
+//! When a seed is set on a thread, `secure_rand_bytes()` on that thread
+//! returns bytes from a ChaCha20 keystream keyed by the seed, instead of bytes
+//! from the operating system. This also applies to everything in orion that
+//! generates random values, such as `SecretKey::generate()`,
+//! `SecretKey::default()`, `Nonce::generate()`, the nonces created by
+//! `orion::aead::seal()` and the salts created by `orion::pwhash`.
+//!
+//! Failures in tests involving generated values can then be reproduced by
+//! running the test again with the same seed.
+//!
+//! Seeds are set per thread, so tests running in parallel do not affect each
+//! other. Other threads, including ones spawned while a seed is set, keep using
+//! the operating system's CSPRNG.
+//!
+//! This module only exists in orion's own tests, so that no build of orion
+//! that is used by other crates can have its randomness replaced.
+
+use crate::errors::UnknownCryptoError;
+use crate::hazardous::stream::chacha20::{self, Nonce, SecretKey, CHACHA_BLOCKSIZE};
+use core::cell::RefCell;
+
+/// A ChaCha20 keystream, consumed in whole blocks.
+struct SeededRng {
+    key: SecretKey,
+    counter: u32,
+}
+
+impl SeededRng {
+    fn new(seed: u64) -> Self {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&seed.to_le_bytes());
+
+        Self {
+            key: SecretKey::from(key),
+            counter: 0,
+        }
+    }
+
+    fn fill(&mut self, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+        for byte in dst.iter_mut() {
+            *byte = 0;
+        }
+        chacha20::encrypt_in_place(&self.key, &Nonce::from([0u8; 12]), self.counter, dst)?;
+
+        let blocks = (dst.len() - 1) / CHACHA_BLOCKSIZE + 1;
+        self.counter = self
+            .counter
+            .checked_add(blocks as u32)
+            .ok_or(UnknownCryptoError)?;
+
+        Ok(())
+    }
+}
+
+std::thread_local! {
+    // A const initializer requires a newer MSRV.
+    #[allow(clippy::missing_const_for_thread_local)]
+    static SEEDED_RNG: RefCell<Option<SeededRng>> = RefCell::new(None);
+}
+
+/// Use the keystream seeded with `seed` for all randomness generated on the
+/// current thread, until [`clear_seed()`] is called. Setting a seed again
+/// restarts the keystream.
+///
+/// [`clear_seed()`]: fn.clear_seed.html
+pub(crate) fn set_seed(seed: u64) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = Some(SeededRng::new(seed)));
+}
+
+/// Use the operating system's CSPRNG again on the current thread.
+pub(crate) fn clear_seed() {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = None);
+}
+
+/// Restores the seeded RNG that was in use before [`with_seed()`], even if
+/// the closure panics.
+///
+/// [`with_seed()`]: fn.with_seed.html
+struct RestoreOnDrop(Option<SeededRng>);
+
+impl Drop for RestoreOnDrop {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SEEDED_RNG.with(|rng| *rng.borrow_mut() = previous);
+    }
+}
+
+/// Run `f` with all randomness on the current thread generated from `seed`.
+/// Whatever was in use before is restored afterwards.
+pub(crate) fn with_seed<T, F: FnOnce() -> T>(seed: u64, f: F) -> T {
+    let previous = SEEDED_RNG.with(|rng| rng.borrow_mut().replace(SeededRng::new(seed)));
+    let _restore = RestoreOnDrop(previous);
+
+    f()
+}
+
+/// Fill `dst` from the seeded RNG, if a seed is set on the current thread.
+/// Returns `false` if no seed is set.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - More than 256 GiB have been generated from a single seed.
+pub(crate) fn fill_if_seeded(dst: &mut [u8]) -> bool {
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(seeded) => {
+            seeded.fill(dst).unwrap();
+            true
+        }
+        None => false,
+    })
+}
+
+#[cfg(test)]
+mod public {
+    use super::*;
+    use crate::util::secure_rand_bytes;
+
+    fn rand_64() -> [u8; 64] {
+        let mut dst = [0u8; 64];
+        secure_rand_bytes(&mut dst).unwrap();
+        dst
+    }
+
+    #[test]
+    fn test_same_seed_same_output() {
+        let first = with_seed(0, || (rand_64(), rand_64()));
+        let second = with_seed(0, || (rand_64(), rand_64()));
+        assert_eq!(first.0[..], second.0[..]);
+        assert_eq!(first.1[..], second.1[..]);
+        // The keystream advances between calls.
+        assert_ne!(first.0[..], first.1[..]);
+    }
+
+    #[test]
+    fn test_diff_seed_diff_output() {
+        let first = with_seed(0, rand_64);
+        let second = with_seed(1, rand_64);
+        assert_ne!(first[..], second[..]);
+    }
+
+    #[test]
+    fn test_partial_blocks_not_reused() {
+        let (first, second) = with_seed(2, || {
+            let mut first = [0u8; 1];
+            let mut second = [0u8; 1];
+            secure_rand_bytes(&mut first).unwrap();
+            secure_rand_bytes(&mut second).unwrap();
+            (first, second)
+        });
+        let expected = with_seed(2, rand_64);
+
+        assert_eq!(first[0], expected[0]);
+        // The remainder of the first block is discarded.
+        let next_block = with_seed(2, || {
+            let mut two_blocks = [0u8; 128];
+            secure_rand_bytes(&mut two_blocks).unwrap();
+            two_blocks
+        });
+        assert_eq!(second[0], next_block[64]);
+    }
+
+    #[test]
+    fn test_set_and_clear_seed() {
+        set_seed(3);
+        let first = rand_64();
+        set_seed(3);
+        let second = rand_64();
+        clear_seed();
+        let unseeded = rand_64();
+
+        assert_eq!(first[..], second[..]);
+        assert_ne!(first[..], unseeded[..]);
+        assert!(!fill_if_seeded(&mut [0u8; 1]));
+    }
+
+    #[test]
+    fn test_with_seed_restores_previous() {
+        set_seed(4);
+        let expected = rand_64();
+
+        set_seed(4);
+        let _ = with_seed(5, rand_64);
+        assert_eq!(rand_64()[..], expected[..]);
+        clear_seed();
+
+        let _ = with_seed(5, rand_64);
+        assert!(!fill_if_seeded(&mut [0u8; 1]));
+    }
+
+    #[test]
+    fn test_with_seed_restores_on_panic() {
+        let res = std::panic::catch_unwind(|| with_seed(6, || panic!()));
+        assert!(res.is_err());
+        assert!(!fill_if_seeded(&mut [0u8; 1]));
+    }
+
+    #[test]
+    fn test_seed_is_per_thread() {
+        with_seed(7, || {
+            let other = std::thread::spawn(|| fill_if_seeded(&mut [0u8; 1]));
+            assert!(!other.join().unwrap());
+            assert!(fill_if_seeded(&mut [0u8; 1]));
+        });
+    }
+
+    #[test]
+    fn test_generate_is_reproducible() {
+        use crate::hazardous::stream::xchacha20;
+
+        let generate = || (SecretKey::generate(), xchacha20::Nonce::generate());
+        let (key_1, nonce_1) = with_seed(8, generate);
+        let (key_2, nonce_2) = with_seed(8, generate);
+
+        assert_eq!(key_1, key_2);
+        assert_eq!(nonce_1, nonce_2);
+    }
+}
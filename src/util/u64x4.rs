@@ -20,6 +20,11 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+/// Four 64-bit words, used for a row of the BLAKE2b state.
+///
+/// All operations are lane-wise with no cross-lane dependencies other than the
+/// `shl_*` rotations. The methods are always inlined, so that the compiler can
+/// keep the whole BLAKE2b state in registers.
 #[derive(Clone, Copy)]
 pub(crate) struct U64x4(
     pub(crate) u64,
@@ -32,6 +37,7 @@ impl core::ops::BitXor for U64x4 {
     type Output = Self;
 
     #[must_use]
+    #[inline(always)]
     fn bitxor(self, _rhs: Self) -> Self::Output {
         Self(
             self.0 ^ _rhs.0,
@@ -75,6 +81,7 @@ impl PartialEq<U64x4> for U64x4 {
 
 impl U64x4 {
     #[must_use]
+    #[inline(always)]
     pub(crate) const fn wrapping_add(self, _rhs: Self) -> Self {
         Self(
             self.0.wrapping_add(_rhs.0),
@@ -85,21 +92,25 @@ impl U64x4 {
     }
 
     #[must_use]
+    #[inline(always)]
     pub(crate) const fn shl_1(self) -> Self {
         Self(self.1, self.2, self.3, self.0)
     }
 
     #[must_use]
+    #[inline(always)]
     pub(crate) const fn shl_2(self) -> Self {
         Self(self.2, self.3, self.0, self.1)
     }
 
     #[must_use]
+    #[inline(always)]
     pub(crate) const fn shl_3(self) -> Self {
         Self(self.3, self.0, self.1, self.2)
     }
 
     #[must_use]
+    #[inline(always)]
     pub(crate) const fn rotate_right(self, n: u32) -> Self {
         Self(
             self.0.rotate_right(n),
@@ -109,6 +120,7 @@ impl U64x4 {
         )
     }
 
+    #[inline(always)]
     pub(crate) fn store_into_le(self, slice_in: &mut [u8]) {
         debug_assert!(slice_in.len() == core::mem::size_of::<u64>() * 4);
         let mut iter = slice_in.chunks_exact_mut(core::mem::size_of::<u64>());
@@ -0,0 +1,66 @@
+// Testing against AES-256-GCM vectors generated with an independent
+// implementation, see tests/test_generation/generate_aes256gcm_test_vectors.py.
+
+use orion::hazardous::aead::aes256gcm::{self, Nonce, SecretKey, AES256GCM_TAGSIZE};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_aes256gcm_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct Aes256GcmVectors {
+    pub aes256gcm: Vec<Aes256GcmVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Aes256GcmVector {
+    pub key: String,
+    pub nonce: String,
+    pub ad: String,
+    pub message: String,
+    pub sealed: String,
+    pub modified: String,
+}
+
+fn load() -> Aes256GcmVectors {
+    let file = File::open("./tests/test_data/aes256gcm_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn decode(value: &str) -> Vec<u8> {
+    hex::decode(value).unwrap()
+}
+
+#[test]
+fn test_generated_vectors() {
+    for vector in load().aes256gcm.iter() {
+        let secret_key = SecretKey::from_slice(&decode(&vector.key)).unwrap();
+        let nonce = Nonce::from_slice(&decode(&vector.nonce)).unwrap();
+        let ad = decode(&vector.ad);
+        let message = decode(&vector.message);
+        let sealed = decode(&vector.sealed);
+
+        let mut dst_ct = vec![0u8; message.len() + AES256GCM_TAGSIZE];
+        aes256gcm::seal(&secret_key, &nonce, &message, Some(&ad), &mut dst_ct).unwrap();
+        assert_eq!(dst_ct, sealed);
+
+        let mut dst_pt = vec![0u8; message.len()];
+        aes256gcm::open(&secret_key, &nonce, &sealed, Some(&ad), &mut dst_pt).unwrap();
+        assert_eq!(dst_pt, message);
+
+        let (ciphertext, tag) = sealed.split_at(message.len());
+        let mut dst_pt = vec![0u8; message.len()];
+        aes256gcm::open_detached(&secret_key, &nonce, ciphertext, tag, Some(&ad), &mut dst_pt)
+            .unwrap();
+        assert_eq!(dst_pt, message);
+
+        assert!(aes256gcm::open(
+            &secret_key,
+            &nonce,
+            &decode(&vector.modified),
+            Some(&ad),
+            &mut dst_pt
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod boringssl_tests;
+pub mod generated_aes256gcm;
 pub mod other_xchacha20_poly1305;
 pub mod pynacl_streaming_aead;
 pub mod rfc_chacha20_poly1305;
@@ -0,0 +1,211 @@
+// Testing against messages created with an independent implementation of
+// COSE and CWT, see tests/test_generation/generate_cose_test_vectors.py.
+
+use orion::hazardous::aead::chacha20poly1305::{Nonce, SecretKey};
+use orion::hazardous::ecc::{ecdsa_p256, ed25519};
+use orion::hazardous::protocols::cose::{self, SigningKey, VerificationKey, ALG_EDDSA};
+use orion::hazardous::protocols::cwt::{self, Claims, Validation};
+use serde::Deserialize;
+use std::{convert::TryFrom, fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_cose_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct CoseVectors {
+    pub ed25519_key: String,
+    pub p256_key: String,
+    pub chacha20_poly1305_key: String,
+    pub sign1: Vec<Sign1Vector>,
+    pub encrypt0: Vec<Encrypt0Vector>,
+    pub cwt: Vec<CwtVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Sign1Vector {
+    pub alg: i64,
+    pub payload: String,
+    pub external_aad: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Encrypt0Vector {
+    pub nonce: String,
+    pub plaintext: String,
+    pub external_aad: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CwtVector {
+    pub claims: ClaimsVector,
+    pub signed_eddsa: String,
+    pub signed_es256: String,
+    pub nonce: String,
+    pub encrypted: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ClaimsVector {
+    pub issuer: Option<String>,
+    pub subject: Option<String>,
+    pub audience: Option<String>,
+    pub expiration: Option<u64>,
+    pub not_before: Option<u64>,
+    pub issued_at: Option<u64>,
+    pub cwt_id: Option<String>,
+}
+
+struct Keys {
+    ed25519: ed25519::SecretKey,
+    p256: ecdsa_p256::SecretKey,
+    chacha20_poly1305: SecretKey,
+}
+
+fn load() -> (CoseVectors, Keys) {
+    let file = File::open("./tests/test_data/cose_generated.json").unwrap();
+    let vectors: CoseVectors = serde_json::from_reader(BufReader::new(file)).unwrap();
+    let keys = Keys {
+        ed25519: ed25519::SecretKey::from_slice(&decode(&vectors.ed25519_key)).unwrap(),
+        p256: ecdsa_p256::SecretKey::from_slice(&decode(&vectors.p256_key)).unwrap(),
+        chacha20_poly1305: SecretKey::from_slice(&decode(&vectors.chacha20_poly1305_key)).unwrap(),
+    };
+
+    (vectors, keys)
+}
+
+fn decode(value: &str) -> Vec<u8> {
+    hex::decode(value).unwrap()
+}
+
+#[test]
+fn test_sign1_vectors() {
+    let (vectors, keys) = load();
+    let ed25519_public = ed25519::PublicKey::try_from(&keys.ed25519).unwrap();
+    let p256_public = ecdsa_p256::PublicKey::try_from(&keys.p256).unwrap();
+
+    for vector in vectors.sign1.iter() {
+        let (signing_key, verification_key, other_key) = if vector.alg == ALG_EDDSA {
+            (
+                SigningKey::Ed25519(&keys.ed25519),
+                VerificationKey::Ed25519(&ed25519_public),
+                VerificationKey::EcdsaP256(&p256_public),
+            )
+        } else {
+            (
+                SigningKey::EcdsaP256(&keys.p256),
+                VerificationKey::EcdsaP256(&p256_public),
+                VerificationKey::Ed25519(&ed25519_public),
+            )
+        };
+        let payload = decode(&vector.payload);
+        let external_aad = decode(&vector.external_aad);
+        let expected = decode(&vector.message);
+
+        assert_eq!(
+            cose::sign1(&signing_key, &payload, &external_aad).unwrap(),
+            expected
+        );
+        assert_eq!(
+            cose::verify1(&verification_key, &expected, &external_aad).unwrap(),
+            payload
+        );
+        assert!(cose::verify1(&verification_key, &expected, b"other").is_err());
+        assert!(cose::verify1(&other_key, &expected, &external_aad).is_err());
+        // Without the tag.
+        assert_eq!(
+            cose::verify1(&verification_key, &expected[1..], &external_aad).unwrap(),
+            payload
+        );
+    }
+}
+
+#[test]
+fn test_encrypt0_vectors() {
+    let (vectors, keys) = load();
+    for vector in vectors.encrypt0.iter() {
+        let nonce = Nonce::from_slice(&decode(&vector.nonce)).unwrap();
+        let plaintext = decode(&vector.plaintext);
+        let external_aad = decode(&vector.external_aad);
+        let expected = decode(&vector.message);
+
+        assert_eq!(
+            cose::encrypt0(&keys.chacha20_poly1305, &nonce, &plaintext, &external_aad).unwrap(),
+            expected
+        );
+        assert_eq!(
+            cose::decrypt0(&keys.chacha20_poly1305, &expected, &external_aad).unwrap(),
+            plaintext
+        );
+        assert!(cose::decrypt0(&keys.chacha20_poly1305, &expected, b"other").is_err());
+        assert_eq!(
+            cose::decrypt0(&keys.chacha20_poly1305, &expected[1..], &external_aad).unwrap(),
+            plaintext
+        );
+    }
+}
+
+#[test]
+fn test_cwt_vectors() {
+    let (vectors, keys) = load();
+    let ed25519_public = ed25519::PublicKey::try_from(&keys.ed25519).unwrap();
+    let p256_public = ecdsa_p256::PublicKey::try_from(&keys.p256).unwrap();
+
+    for vector in vectors.cwt.iter() {
+        let claims = Claims {
+            issuer: vector.claims.issuer.clone(),
+            subject: vector.claims.subject.clone(),
+            audience: vector.claims.audience.clone(),
+            expiration: vector.claims.expiration,
+            not_before: vector.claims.not_before,
+            issued_at: vector.claims.issued_at,
+            cwt_id: vector.claims.cwt_id.as_ref().map(|cti| decode(cti)),
+        };
+        let validation = Validation {
+            issuer: claims.issuer.clone(),
+            audience: claims.audience.clone(),
+            leeway: 0,
+        };
+        let now = claims.not_before.unwrap_or(0);
+        let nonce = Nonce::from_slice(&decode(&vector.nonce)).unwrap();
+
+        let signed_eddsa = decode(&vector.signed_eddsa);
+        assert_eq!(
+            cwt::sign(&SigningKey::Ed25519(&keys.ed25519), &claims).unwrap(),
+            signed_eddsa
+        );
+        let key = VerificationKey::Ed25519(&ed25519_public);
+        assert_eq!(
+            cwt::verify(&key, &signed_eddsa, &validation, now).unwrap(),
+            claims
+        );
+
+        let signed_es256 = decode(&vector.signed_es256);
+        assert_eq!(
+            cwt::sign(&SigningKey::EcdsaP256(&keys.p256), &claims).unwrap(),
+            signed_es256
+        );
+        let key = VerificationKey::EcdsaP256(&p256_public);
+        assert_eq!(
+            cwt::verify(&key, &signed_es256, &validation, now).unwrap(),
+            claims
+        );
+
+        let encrypted = decode(&vector.encrypted);
+        assert_eq!(
+            cwt::encrypt(&keys.chacha20_poly1305, &nonce, &claims).unwrap(),
+            encrypted
+        );
+        assert_eq!(
+            cwt::decrypt(&keys.chacha20_poly1305, &encrypted, &validation, now).unwrap(),
+            claims
+        );
+
+        if let Some(expiration) = claims.expiration {
+            assert!(cwt::verify(&key, &signed_es256, &validation, expiration).is_err());
+            assert!(
+                cwt::decrypt(&keys.chacha20_poly1305, &encrypted, &validation, expiration).is_err()
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// Differential tests of orion's Argon2i against rust-argon2, a port of the
+// reference implementation, across edge parameters: the minimum amount of
+// memory and iterations, memory that isn't a multiple of the four segments,
+// salts at and below the minimum length, long salts, secrets and associated
+// data, and the minimum output length. Invalid parameters must be rejected by
+// both. orion only supports a single lane, so p = 1 throughout.
+
+use orion::hazardous::kdf::argon2i;
+use quickcheck::quickcheck;
+
+#[allow(clippy::too_many_arguments)]
+fn argon2i_compare(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    secret: &[u8],
+    ad: &[u8],
+    out_len: usize,
+) -> bool {
+    let config = argon2::Config {
+        ad,
+        hash_length: out_len as u32,
+        lanes: 1,
+        mem_cost: memory,
+        secret,
+        thread_mode: argon2::ThreadMode::Sequential,
+        time_cost: iterations,
+        variant: argon2::Variant::Argon2i,
+        version: argon2::Version::Version13,
+    };
+    let expected = argon2::hash_raw(password, salt, &config);
+
+    let mut actual = vec![0u8; out_len];
+    // orion treats an empty secret or associated data the same as none.
+    let secret = if secret.is_empty() {
+        None
+    } else {
+        Some(secret)
+    };
+    let ad = if ad.is_empty() { None } else { Some(ad) };
+    let res = argon2i::derive_key(password, salt, iterations, memory, secret, ad, &mut actual);
+
+    match expected {
+        Ok(expected) => res.is_ok() && actual == expected,
+        Err(_) => res.is_err(),
+    }
+}
+
+#[test]
+fn test_argon2i_edge_parameters() {
+    let long = [7u8; 1024];
+
+    // Minimum memory and iterations.
+    assert!(argon2i_compare(b"password", &[1u8; 8], 1, 8, &[], &[], 4));
+    // Memory not divisible by the amount of segments.
+    assert!(argon2i_compare(b"password", &[1u8; 8], 2, 13, &[], &[], 32));
+    assert!(argon2i_compare(
+        b"", &[1u8; 16], 3, 33, &[2u8; 8], &[3u8; 8], 65
+    ));
+    // Long salts, secrets, associated data and outputs.
+    assert!(argon2i_compare(&long, &long, 1, 16, &long, &long, 1025));
+    // Invalid parameters.
+    assert!(argon2i_compare(b"password", &[1u8; 7], 1, 8, &[], &[], 32));
+    assert!(argon2i_compare(b"password", &[1u8; 8], 0, 8, &[], &[], 32));
+    assert!(argon2i_compare(b"password", &[1u8; 8], 1, 7, &[], &[], 32));
+    assert!(argon2i_compare(b"password", &[1u8; 8], 1, 8, &[], &[], 3));
+}
+
+quickcheck! {
+    fn prop_argon2i_differential(
+        password: Vec<u8>,
+        salt: Vec<u8>,
+        iterations: u8,
+        memory: u8,
+        secret: Vec<u8>,
+        ad: Vec<u8>,
+        out_len: u8
+    ) -> bool {
+        // Keep parameters small for speed, while still hitting the
+        // boundaries of what is allowed.
+        argon2i_compare(
+            &password,
+            &salt,
+            u32::from(iterations % 4),
+            u32::from(memory % 64),
+            &secret,
+            &ad,
+            usize::from(out_len),
+        )
+    }
+}
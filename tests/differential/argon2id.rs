@@ -0,0 +1,211 @@
+// Differential tests of orion's Argon2id against rust-argon2, a port of the
+// reference implementation. Besides the edge parameters tested for Argon2i,
+// these cover multiple lanes, including memory that isn't a multiple of the
+// segments in all lanes and the minimum amount of memory for a given amount
+// of lanes. Invalid parameters must be rejected by both.
+
+use orion::hazardous::kdf::argon2id;
+use quickcheck::quickcheck;
+
+#[allow(clippy::too_many_arguments)]
+fn argon2id_compare(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    memory: u32,
+    lanes: u32,
+    secret: &[u8],
+    ad: &[u8],
+    out_len: usize,
+) -> bool {
+    let config = argon2::Config {
+        ad,
+        hash_length: out_len as u32,
+        lanes,
+        mem_cost: memory,
+        secret,
+        thread_mode: argon2::ThreadMode::Sequential,
+        time_cost: iterations,
+        variant: argon2::Variant::Argon2id,
+        version: argon2::Version::Version13,
+    };
+    let expected = argon2::hash_raw(password, salt, &config);
+
+    let mut actual = vec![0u8; out_len];
+    // orion treats an empty secret or associated data the same as none.
+    let secret = if secret.is_empty() {
+        None
+    } else {
+        Some(secret)
+    };
+    let ad = if ad.is_empty() { None } else { Some(ad) };
+    let res = argon2id::derive_key(
+        password,
+        salt,
+        iterations,
+        memory,
+        lanes,
+        secret,
+        ad,
+        &mut actual,
+    );
+
+    match expected {
+        Ok(expected) => res.is_ok() && actual == expected,
+        Err(_) => res.is_err(),
+    }
+}
+
+#[test]
+fn test_argon2id_edge_parameters() {
+    let long = [7u8; 1024];
+
+    // Minimum memory and iterations.
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        8,
+        1,
+        &[],
+        &[],
+        4
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        32,
+        4,
+        &[],
+        &[],
+        4
+    ));
+    // Memory not divisible by the amount of segments in all lanes.
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        2,
+        13,
+        1,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"", &[1u8; 16], 3, 70, 3, &[2u8; 8], &[3u8; 8], 65
+    ));
+    // More than a single address block per segment.
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        2,
+        1100,
+        2,
+        &[],
+        &[],
+        32
+    ));
+    // Several passes over lanes with segments long enough to be computed in
+    // parallel with the rayon feature.
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        3,
+        2048,
+        4,
+        &[],
+        &[],
+        32
+    ));
+    // Long salts, secrets, associated data and outputs.
+    assert!(argon2id_compare(&long, &long, 1, 16, 2, &long, &long, 1025));
+    // Invalid parameters.
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 7],
+        1,
+        8,
+        1,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        0,
+        8,
+        1,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        7,
+        1,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        15,
+        2,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        8,
+        0,
+        &[],
+        &[],
+        32
+    ));
+    assert!(argon2id_compare(
+        b"password",
+        &[1u8; 8],
+        1,
+        8,
+        1,
+        &[],
+        &[],
+        3
+    ));
+}
+
+quickcheck! {
+    #[allow(clippy::too_many_arguments)]
+    fn prop_argon2id_differential(
+        password: Vec<u8>,
+        salt: Vec<u8>,
+        iterations: u8,
+        memory: u8,
+        lanes: u8,
+        secret: Vec<u8>,
+        ad: Vec<u8>,
+        out_len: u8
+    ) -> bool {
+        // Keep parameters small for speed, while still hitting the
+        // boundaries of what is allowed.
+        argon2id_compare(
+            &password,
+            &salt,
+            u32::from(iterations % 4),
+            u32::from(memory % 96),
+            u32::from(lanes % 6),
+            &secret,
+            &ad,
+            usize::from(out_len),
+        )
+    }
+}
@@ -0,0 +1,257 @@
+// Differential tests against the RustCrypto implementations of ChaCha20,
+// XChaCha20, (X)ChaCha20-Poly1305 and HKDF-SHA512.
+//
+// Each comparison takes arbitrary bytes for keys, nonces and messages, the
+// same way a fuzz target would, and checks that orion produces the same output
+// as the independent implementation. For the AEADs, ciphertexts with a single
+// modified byte must be rejected by both. The comparisons are driven by
+// quickcheck here.
+
+pub mod argon2i;
+pub mod argon2id;
+
+use chacha20::cipher::{NewStreamCipher, SyncStreamCipher, SyncStreamCipherSeek};
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use orion::hazardous::aead::{chacha20poly1305 as orion_chacha20poly1305, xchacha20poly1305};
+use orion::hazardous::stream::{chacha20 as orion_chacha20, xchacha20};
+use quickcheck::quickcheck;
+
+/// Fill `dst` with `seed`, repeating it if it is too short.
+fn fill_from(seed: &[u8], dst: &mut [u8]) {
+    if seed.is_empty() {
+        return;
+    }
+    for (dst_byte, seed_byte) in dst.iter_mut().zip(seed.iter().cycle()) {
+        *dst_byte = *seed_byte;
+    }
+}
+
+/// Keep the block counter low enough that the keystream for `len` bytes
+/// does not exceed the 32-bit block counter.
+fn limit_counter(counter: u32, len: usize) -> u32 {
+    counter % (u32::MAX - (len as u32 / 64) - 1)
+}
+
+fn chacha20_compare(key_seed: &[u8], nonce_seed: &[u8], counter: u32, data: &[u8]) -> bool {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    fill_from(key_seed, &mut key);
+    fill_from(nonce_seed, &mut nonce);
+    let counter = limit_counter(counter, data.len());
+
+    let mut expected = data.to_vec();
+    let mut other = chacha20::ChaCha20::new(&key.into(), &nonce.into());
+    other.seek(u64::from(counter) * 64);
+    other.apply_keystream(&mut expected);
+
+    let mut actual = vec![0u8; data.len()];
+    let res = orion_chacha20::encrypt(
+        &orion_chacha20::SecretKey::from_slice(&key).unwrap(),
+        &orion_chacha20::Nonce::from_slice(&nonce).unwrap(),
+        counter,
+        data,
+        &mut actual,
+    );
+
+    if data.is_empty() {
+        // orion does not allow encrypting empty input.
+        return res.is_err();
+    }
+
+    res.is_ok() && actual == expected
+}
+
+fn xchacha20_compare(key_seed: &[u8], nonce_seed: &[u8], counter: u32, data: &[u8]) -> bool {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 24];
+    fill_from(key_seed, &mut key);
+    fill_from(nonce_seed, &mut nonce);
+    let counter = limit_counter(counter, data.len());
+
+    let mut expected = data.to_vec();
+    let mut other = chacha20::XChaCha20::new(&key.into(), &nonce.into());
+    other.seek(u64::from(counter) * 64);
+    other.apply_keystream(&mut expected);
+
+    let mut actual = vec![0u8; data.len()];
+    let res = xchacha20::encrypt(
+        &xchacha20::SecretKey::from_slice(&key).unwrap(),
+        &xchacha20::Nonce::from_slice(&nonce).unwrap(),
+        counter,
+        data,
+        &mut actual,
+    );
+
+    if data.is_empty() {
+        return res.is_err();
+    }
+
+    res.is_ok() && actual == expected
+}
+
+/// Compare sealing, opening and opening a ciphertext with the byte at
+/// `mutate_at` modified, between orion and the RustCrypto AEAD `A`.
+fn aead_compare<A, S, O>(
+    other: &A,
+    other_nonce: &[u8],
+    orion_seal: S,
+    orion_open: O,
+    data: &[u8],
+    ad: &[u8],
+    mutate_at: usize,
+) -> bool
+where
+    A: Aead,
+    S: Fn(&[u8], Option<&[u8]>, &mut [u8]) -> Result<(), orion::errors::UnknownCryptoError>,
+    O: Fn(&[u8], Option<&[u8]>, &mut [u8]) -> Result<(), orion::errors::UnknownCryptoError>,
+{
+    let payload = Payload { msg: data, aad: ad };
+    let expected = other
+        .encrypt(other_nonce.into(), payload)
+        .expect("RustCrypto failed to encrypt");
+
+    let mut actual = vec![0u8; data.len() + 16];
+    orion_seal(data, Some(ad), &mut actual).unwrap();
+    if actual != expected {
+        return false;
+    }
+
+    let mut plaintext = vec![0u8; data.len()];
+    orion_open(&actual, Some(ad), &mut plaintext).unwrap();
+    if plaintext != data {
+        return false;
+    }
+
+    let mut modified = actual;
+    let idx = mutate_at % modified.len();
+    modified[idx] ^= 1;
+
+    let other_res = other.decrypt(
+        other_nonce.into(),
+        Payload {
+            msg: &modified,
+            aad: ad,
+        },
+    );
+    let orion_res = orion_open(&modified, Some(ad), &mut plaintext);
+
+    other_res.is_err() && orion_res.is_err()
+}
+
+fn chacha20poly1305_compare(
+    key_seed: &[u8],
+    nonce_seed: &[u8],
+    data: &[u8],
+    ad: &[u8],
+    mutate_at: usize,
+) -> bool {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    fill_from(key_seed, &mut key);
+    fill_from(nonce_seed, &mut nonce);
+
+    let sk = orion_chacha20poly1305::SecretKey::from_slice(&key).unwrap();
+    let orion_nonce = orion_chacha20poly1305::Nonce::from_slice(&nonce).unwrap();
+
+    aead_compare(
+        &chacha20poly1305::ChaCha20Poly1305::new(&key.into()),
+        &nonce,
+        |pt, ad, dst| orion_chacha20poly1305::seal(&sk, &orion_nonce, pt, ad, dst),
+        |ct, ad, dst| orion_chacha20poly1305::open(&sk, &orion_nonce, ct, ad, dst),
+        data,
+        ad,
+        mutate_at,
+    )
+}
+
+fn xchacha20poly1305_compare(
+    key_seed: &[u8],
+    nonce_seed: &[u8],
+    data: &[u8],
+    ad: &[u8],
+    mutate_at: usize,
+) -> bool {
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 24];
+    fill_from(key_seed, &mut key);
+    fill_from(nonce_seed, &mut nonce);
+
+    let sk = xchacha20poly1305::SecretKey::from_slice(&key).unwrap();
+    let orion_nonce = xchacha20poly1305::Nonce::from_slice(&nonce).unwrap();
+
+    aead_compare(
+        &chacha20poly1305::XChaCha20Poly1305::new(&key.into()),
+        &nonce,
+        |pt, ad, dst| xchacha20poly1305::seal(&sk, &orion_nonce, pt, ad, dst),
+        |ct, ad, dst| xchacha20poly1305::open(&sk, &orion_nonce, ct, ad, dst),
+        data,
+        ad,
+        mutate_at,
+    )
+}
+
+fn hkdf_compare(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> bool {
+    // The output length is limited to 255 * 64 bytes and must not be zero.
+    let len = (len % (255 * 64)) + 1;
+
+    let mut expected = vec![0u8; len];
+    hkdf::Hkdf::<sha2::Sha512>::new(Some(salt), ikm)
+        .expand(info, &mut expected)
+        .unwrap();
+
+    let mut actual = vec![0u8; len];
+    orion::hazardous::kdf::hkdf::derive_key(salt, ikm, Some(info), &mut actual).unwrap();
+
+    actual == expected
+}
+
+#[test]
+fn test_differential_known_edges() {
+    // Empty and block-sized inputs, and the maximum HKDF output length.
+    assert!(chacha20_compare(&[1u8], &[2u8], 0, &[0u8; 0]));
+    assert!(chacha20_compare(&[1u8], &[2u8], u32::MAX, &[0u8; 64]));
+    assert!(xchacha20_compare(&[1u8], &[2u8], 1, &[0u8; 128]));
+    assert!(chacha20poly1305_compare(
+        &[1u8],
+        &[2u8],
+        &[0u8; 0],
+        &[0u8; 0],
+        0
+    ));
+    assert!(xchacha20poly1305_compare(
+        &[1u8],
+        &[2u8],
+        &[0u8; 0],
+        &[0u8; 0],
+        15
+    ));
+    assert!(hkdf_compare(&[0u8; 0], &[0u8; 0], &[0u8; 0], 0));
+    assert!(hkdf_compare(
+        &[0u8; 200],
+        &[0u8; 1],
+        &[0u8; 1],
+        255 * 64 - 1
+    ));
+}
+
+quickcheck! {
+    fn prop_chacha20_differential(key: Vec<u8>, nonce: Vec<u8>, counter: u32, data: Vec<u8>) -> bool {
+        chacha20_compare(&key, &nonce, counter, &data)
+    }
+
+    fn prop_xchacha20_differential(key: Vec<u8>, nonce: Vec<u8>, counter: u32, data: Vec<u8>) -> bool {
+        xchacha20_compare(&key, &nonce, counter, &data)
+    }
+
+    fn prop_chacha20poly1305_differential(key: Vec<u8>, nonce: Vec<u8>, data: Vec<u8>, ad: Vec<u8>, mutate_at: usize) -> bool {
+        chacha20poly1305_compare(&key, &nonce, &data, &ad, mutate_at)
+    }
+
+    fn prop_xchacha20poly1305_differential(key: Vec<u8>, nonce: Vec<u8>, data: Vec<u8>, ad: Vec<u8>, mutate_at: usize) -> bool {
+        xchacha20poly1305_compare(&key, &nonce, &data, &ad, mutate_at)
+    }
+
+    fn prop_hkdf_differential(salt: Vec<u8>, ikm: Vec<u8>, info: Vec<u8>, len: usize) -> bool {
+        hkdf_compare(&salt, &ikm, &info, len)
+    }
+}
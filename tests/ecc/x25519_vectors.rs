@@ -0,0 +1,55 @@
+// Testing against key pairs and shared secrets from OpenSSL's X25519, see
+// tests/test_generation/generate_x25519_test_vectors.py.
+
+use core::convert::TryFrom;
+use hex::decode;
+use orion::hazardous::ecc::x25519::{self, PrivateKey, PublicKey};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct X25519Vectors {
+    pub key_pairs: Vec<KeyPairCase>,
+    pub agreements: Vec<AgreementCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct KeyPairCase {
+    pub private: String,
+    pub public: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AgreementCase {
+    pub private: String,
+    pub public: String,
+    pub shared: Option<String>,
+}
+
+fn load() -> X25519Vectors {
+    let file = File::open("./tests/test_data/x25519_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_key_pairs() {
+    for case in load().key_pairs.iter() {
+        let private_key = PrivateKey::from_slice(&decode(&case.private).unwrap()).unwrap();
+        let public_key = PublicKey::try_from(&private_key).unwrap();
+        assert_eq!(public_key.as_ref(), &decode(&case.public).unwrap()[..]);
+    }
+}
+
+#[test]
+fn test_agreements() {
+    for case in load().agreements.iter() {
+        let private_key = PrivateKey::from_slice(&decode(&case.private).unwrap()).unwrap();
+        let public_key = PublicKey::from_slice(&decode(&case.public).unwrap()).unwrap();
+        let result = x25519::key_agreement(&private_key, &public_key);
+
+        match &case.shared {
+            Some(shared) => assert_eq!(result.unwrap(), &decode(shared).unwrap()[..]),
+            None => assert!(result.is_err(), "{}", case.public),
+        }
+    }
+}
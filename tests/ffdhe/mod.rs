@@ -0,0 +1,47 @@
+// Testing against key agreements generated with OpenSSL, see
+// tests/test_generation/generate_ffdhe_test_vectors.py.
+
+use orion::hazardous::ffdhe::{self, Group, SecretKey};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_ffdhe_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct FfdheVector {
+    pub group: String,
+    pub secret_key: String,
+    pub public_key: String,
+    pub peer_public_key: String,
+    pub shared_secret: String,
+}
+
+fn group(name: &str) -> Group {
+    match name {
+        "ffdhe2048" => Group::Ffdhe2048,
+        "ffdhe3072" => Group::Ffdhe3072,
+        "ffdhe4096" => Group::Ffdhe4096,
+        _ => panic!("Unknown group: {}", name),
+    }
+}
+
+#[test]
+fn test_ffdhe_generated() {
+    let file = File::open("./tests/test_data/ffdhe_generated.json").unwrap();
+    let vectors: Vec<FfdheVector> = serde_json::from_reader(BufReader::new(file)).unwrap();
+    assert_eq!(vectors.len(), 6);
+
+    for vector in vectors.iter() {
+        let group = group(&vector.group);
+        let sk = SecretKey::from_slice(&hex::decode(&vector.secret_key).unwrap()).unwrap();
+        let peer_public_key = hex::decode(&vector.peer_public_key).unwrap();
+
+        let mut public_key = vec![0u8; group.size()];
+        ffdhe::public_key(group, &sk, &mut public_key).unwrap();
+        assert_eq!(public_key, hex::decode(&vector.public_key).unwrap());
+
+        let mut shared_secret = vec![0u8; group.size()];
+        ffdhe::shared_secret(group, &sk, &peer_public_key, &mut shared_secret).unwrap();
+        assert_eq!(shared_secret, hex::decode(&vector.shared_secret).unwrap());
+    }
+}
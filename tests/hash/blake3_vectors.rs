@@ -0,0 +1,93 @@
+// Testing against outputs of an independent implementation of BLAKE3, with the
+// inputs of the official BLAKE3 test vectors, see
+// tests/test_generation/generate_blake3_test_vectors.py.
+
+use hex::decode;
+use orion::hazardous::hash::blake3::{Blake3, SecretKey, BLAKE3_OUTSIZE};
+use orion::hazardous::hash::Xof;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct Blake3Vectors {
+    pub key: String,
+    pub context_string: String,
+    pub cases: Vec<Blake3Case>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Blake3Case {
+    pub input_len: usize,
+    pub hash: String,
+    pub keyed_hash: String,
+    pub derive_key: String,
+}
+
+/// Check `state`, after absorbing `input` in a single update and in updates of
+/// varying sizes, against `expected` of any length.
+fn check_state(initial: &Blake3, input: &[u8], expected: &[u8]) {
+    let mut state = initial.clone();
+    state.update(input).unwrap();
+    assert_eq!(
+        state.finalize().unwrap().as_ref(),
+        &expected[..BLAKE3_OUTSIZE]
+    );
+
+    let mut out = vec![0u8; expected.len()];
+    state.reset();
+    state.update(input).unwrap();
+    state.finalize_into(&mut out).unwrap();
+    assert_eq!(out, expected);
+
+    // Updates of varying sizes, and squeezing in parts.
+    let mut state = initial.clone();
+    let mut remaining = input;
+    for size in [1, 63, 1, 1023, 1025, 4096].iter().cycle() {
+        if remaining.is_empty() {
+            break;
+        }
+        let take = std::cmp::min(*size, remaining.len());
+        state.absorb(&remaining[..take]).unwrap();
+        remaining = &remaining[take..];
+    }
+    let mut out = vec![0u8; expected.len()];
+    let (first, rest) = out.split_at_mut(7);
+    let (second, third) = rest.split_at_mut(64);
+    state.squeeze(first).unwrap();
+    state.squeeze(second).unwrap();
+    state.squeeze(third).unwrap();
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_blake3_vectors() {
+    let file = File::open("./tests/test_data/blake3_generated.json").unwrap();
+    let vectors: Blake3Vectors = serde_json::from_reader(BufReader::new(file)).unwrap();
+    let secret_key = SecretKey::from_slice(vectors.key.as_bytes()).unwrap();
+
+    for case in vectors.cases.iter() {
+        let input: Vec<u8> = (0..case.input_len).map(|i| (i % 251) as u8).collect();
+        let hash = decode(&case.hash).unwrap();
+        let keyed_hash = decode(&case.keyed_hash).unwrap();
+        let derive_key = decode(&case.derive_key).unwrap();
+
+        check_state(&Blake3::new(), &input, &hash);
+        check_state(&Blake3::new_keyed(&secret_key), &input, &keyed_hash);
+        check_state(
+            &Blake3::new_derive_key(&vectors.context_string),
+            &input,
+            &derive_key,
+        );
+
+        assert_eq!(
+            Blake3::digest(&input).unwrap().as_ref(),
+            &hash[..BLAKE3_OUTSIZE]
+        );
+        let mac = Blake3::keyed_hash(&secret_key, &input).unwrap();
+        assert_eq!(mac.as_ref(), &keyed_hash[..BLAKE3_OUTSIZE]);
+        assert!(Blake3::verify(&mac, &secret_key, &input).is_ok());
+        let mut derived = vec![0u8; derive_key.len()];
+        Blake3::derive_key(&vectors.context_string, &input, &mut derived).unwrap();
+        assert_eq!(derived, derive_key);
+    }
+}
@@ -1,8 +1,11 @@
 pub mod blake2b_kat;
+pub mod blake3_vectors;
 pub mod other_blake2b;
+pub mod sha256_fips;
+pub mod sha3_vectors;
 pub mod sha512_nist_cavp;
 
-use orion::hazardous::hash::{blake2b, sha512};
+use orion::hazardous::hash::{blake2b, sha256, sha512};
 
 fn blake2b_test_runner(input: &[u8], key: &[u8], output: &[u8]) {
     // Only make SecretKey if test case key value is not empty.
@@ -29,3 +32,14 @@ fn sha512_test_runner(data: &[u8], output: &[u8]) {
     assert!(digest.as_ref() == digest_one_shot.as_ref());
     assert!(digest.as_ref() == output);
 }
+
+fn sha256_test_runner(data: &[u8], output: &[u8]) {
+    let mut state = sha256::Sha256::new();
+    state.update(data).unwrap();
+    let digest = state.finalize().unwrap();
+
+    let digest_one_shot = sha256::Sha256::digest(data).unwrap();
+
+    assert!(digest.as_ref() == digest_one_shot.as_ref());
+    assert!(digest.as_ref() == output);
+}
@@ -0,0 +1,43 @@
+use crate::hash::sha256_test_runner;
+
+// Examples from FIPS 180-2, appendix B, and the empty message.
+
+#[test]
+fn test_empty() {
+    sha256_test_runner(
+        b"",
+        &hex::decode("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").unwrap(),
+    );
+}
+
+#[test]
+fn test_one_block() {
+    sha256_test_runner(
+        b"abc",
+        &hex::decode("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad").unwrap(),
+    );
+}
+
+#[test]
+fn test_two_blocks() {
+    sha256_test_runner(
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        &hex::decode("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1").unwrap(),
+    );
+}
+
+#[test]
+fn test_896_bits() {
+    sha256_test_runner(
+        b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+        &hex::decode("cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1").unwrap(),
+    );
+}
+
+#[test]
+fn test_million_a() {
+    sha256_test_runner(
+        &[b'a'; 1_000_000],
+        &hex::decode("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0").unwrap(),
+    );
+}
@@ -0,0 +1,161 @@
+// Testing against outputs of OpenSSL's SHA3 and SHAKE, and of HKDF over SHA3
+// from the `cryptography` package, see
+// tests/test_generation/generate_sha3_test_vectors.py.
+
+use hex::decode;
+use orion::hazardous::hash::sha3::{
+    sha3_256::Sha3_256, sha3_384::Sha3_384, sha3_512::Sha3_512, shake128::Shake128,
+    shake256::Shake256,
+};
+use orion::hazardous::hash::{ShaHash, Xof};
+use orion::hazardous::kdf::hkdf::Hkdf;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct Sha3Vectors {
+    pub hkdf_ikm: String,
+    pub hkdf_info: String,
+    pub hashes: Vec<Sha3HashCase>,
+    pub hkdf: Vec<Sha3HkdfCase>,
+    pub shake: Vec<ShakeCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Sha3HashCase {
+    pub input_len: usize,
+    pub sha3_256: String,
+    pub sha3_384: String,
+    pub sha3_512: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Sha3HkdfCase {
+    pub salt_len: usize,
+    pub sha3_256: String,
+    pub sha3_384: String,
+    pub sha3_512: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ShakeCase {
+    pub input_len: usize,
+    pub shake128: String,
+    pub shake256: String,
+}
+
+fn load_vectors() -> Sha3Vectors {
+    let file = File::open("./tests/test_data/sha3_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+/// Check `H` against `expected`, absorbing `input` in a single update and in
+/// updates of varying sizes.
+fn check_hash<H: ShaHash>(input: &[u8], expected: &str) {
+    let expected = decode(expected).unwrap();
+    let mut digest = vec![0u8; H::OUTSIZE];
+
+    H::digest_into(input, &mut digest).unwrap();
+    assert_eq!(digest, expected);
+
+    let mut state = H::default();
+    let mut remaining = input;
+    for size in [1, 71, 1, 136, 200].iter().cycle() {
+        if remaining.is_empty() {
+            break;
+        }
+        let take = std::cmp::min(*size, remaining.len());
+        state.update(&remaining[..take]).unwrap();
+        remaining = &remaining[take..];
+    }
+    state.finalize_into(&mut digest).unwrap();
+    assert_eq!(digest, expected);
+}
+
+fn check_hkdf<H: ShaHash>(salt: &[u8], ikm: &[u8], info: &[u8], expected: &str) {
+    let expected = decode(expected).unwrap();
+    let mut okm = vec![0u8; expected.len()];
+    Hkdf::<H>::extract(salt, ikm)
+        .unwrap()
+        .expand(Some(info), &mut okm)
+        .unwrap();
+    assert_eq!(okm, expected);
+}
+
+#[test]
+fn test_sha3_vectors() {
+    let vectors = load_vectors();
+
+    for case in vectors.hashes.iter() {
+        let input: Vec<u8> = (0..case.input_len).map(|i| (i % 251) as u8).collect();
+        check_hash::<Sha3_256>(&input, &case.sha3_256);
+        check_hash::<Sha3_384>(&input, &case.sha3_384);
+        check_hash::<Sha3_512>(&input, &case.sha3_512);
+
+        assert_eq!(
+            Sha3_256::digest(&input).unwrap().as_ref(),
+            &decode(&case.sha3_256).unwrap()[..]
+        );
+    }
+}
+
+#[test]
+fn test_sha3_hkdf_vectors() {
+    let vectors = load_vectors();
+    let ikm = decode(&vectors.hkdf_ikm).unwrap();
+    let info = decode(&vectors.hkdf_info).unwrap();
+
+    for case in vectors.hkdf.iter() {
+        let salt: Vec<u8> = (0..case.salt_len)
+            .map(|i| ((7 * i + 3) % 256) as u8)
+            .collect();
+        check_hkdf::<Sha3_256>(&salt, &ikm, &info, &case.sha3_256);
+        check_hkdf::<Sha3_384>(&salt, &ikm, &info, &case.sha3_384);
+        check_hkdf::<Sha3_512>(&salt, &ikm, &info, &case.sha3_512);
+    }
+}
+
+/// Check `X` against `expected`, absorbing `input` in updates of varying sizes
+/// and squeezing in parts.
+fn check_xof<X: Xof + Default>(input: &[u8], expected: &str) {
+    let expected = decode(expected).unwrap();
+
+    let mut xof = X::default();
+    let mut remaining = input;
+    for size in [1, 167, 1, 168, 200].iter().cycle() {
+        if remaining.is_empty() {
+            break;
+        }
+        let take = std::cmp::min(*size, remaining.len());
+        xof.absorb(&remaining[..take]).unwrap();
+        remaining = &remaining[take..];
+    }
+
+    let mut out = vec![0u8; expected.len()];
+    let mut offset = 0;
+    for size in [1, 135, 168, 169].iter().cycle() {
+        if offset == out.len() {
+            break;
+        }
+        let take = std::cmp::min(*size, out.len() - offset);
+        xof.squeeze(&mut out[offset..offset + take]).unwrap();
+        offset += take;
+    }
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_shake_vectors() {
+    let vectors = load_vectors();
+
+    for case in vectors.shake.iter() {
+        let input: Vec<u8> = (0..case.input_len).map(|i| (i % 251) as u8).collect();
+        check_xof::<Shake128>(&input, &case.shake128);
+        check_xof::<Shake256>(&input, &case.shake256);
+
+        let expected = decode(&case.shake256).unwrap();
+        let mut out = vec![0u8; expected.len()];
+        Shake256::digest_into(&input, &mut out).unwrap();
+        assert_eq!(out, expected);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::hash::sha512;
 use crate::hash::sha512_test_runner;
-use crate::TestCaseReader;
+use crate::nist::cavp_runner;
 
 #[test]
 fn test_streaming_1() {
@@ -52,24 +52,17 @@ fn test_streaming_3() {
     assert_eq!(&expected[..], res.as_ref());
 }
 
-/// NISTs SHA512 Long/Short share the same format,
-/// so fields and separator remain the same.
+/// NISTs SHA512 Long/Short share the same format.
 fn nist_cavp_runner(path: &str) {
-    let nist_cavp_fields: Vec<String> = vec!["Len".into(), "Msg".into(), "MD".into()];
-    let mut nist_cavp_reader = TestCaseReader::new(path, nist_cavp_fields, "=");
-
-    let mut test_case = nist_cavp_reader.next();
-    while test_case.is_some() {
-        let tc = test_case.unwrap();
-
-        let input: Vec<u8> = TestCaseReader::default_parse(tc.get_data("Msg"));
-        let expected_output: Vec<u8> = TestCaseReader::default_parse(tc.get_data("MD"));
+    cavp_runner(path, |tc| {
+        // "Len" is the message length in bits. A zero-length message is
+        // still given as "Msg = 00".
+        let mut input: Vec<u8> = tc.get_bytes("Msg");
+        input.truncate(tc.get_usize("Len") / 8);
+        let expected_output: Vec<u8> = tc.get_bytes("MD");
 
         sha512_test_runner(&input[..], &expected_output[..]);
-
-        // Read the next one
-        test_case = nist_cavp_reader.next();
-    }
+    });
 }
 
 #[test]
@@ -0,0 +1,54 @@
+// Testing against single-shot HPKE encryptions of OpenSSL, through the
+// `cryptography` package, see tests/test_generation/generate_hpke_test_vectors.py.
+
+use core::convert::TryFrom;
+use hex::decode;
+use orion::hazardous::hpke::{self, PrivateKey, PublicKey, TAG_SIZE};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct HpkeVectors {
+    pub hpke: Vec<HpkeCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HpkeCase {
+    pub private_key_r: String,
+    pub public_key_r: String,
+    pub info: String,
+    pub aad: String,
+    pub plaintext_len: usize,
+    pub output: String,
+}
+
+fn load_vectors() -> HpkeVectors {
+    let file = File::open("./tests/test_data/hpke_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn plaintext_bytes(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn test_open_single_shot() {
+    for case in load_vectors().hpke.iter() {
+        let private_key_r = PrivateKey::from_slice(&decode(&case.private_key_r).unwrap()).unwrap();
+        let public_key_r = PublicKey::try_from(&private_key_r).unwrap();
+        assert_eq!(public_key_r, &decode(&case.public_key_r).unwrap()[..]);
+        let info = decode(&case.info).unwrap();
+        let aad = decode(&case.aad).unwrap();
+        let output = decode(&case.output).unwrap();
+        assert_eq!(output.len(), 32 + case.plaintext_len + TAG_SIZE);
+
+        let enc = PublicKey::from_slice(&output[..32]).unwrap();
+        let mut receiver = hpke::setup_base_receiver(&enc, &private_key_r, &info).unwrap();
+        let mut plaintext = vec![0u8; case.plaintext_len];
+        receiver.open(&aad, &output[32..], &mut plaintext).unwrap();
+        assert_eq!(plaintext, plaintext_bytes(case.plaintext_len));
+
+        // The same ciphertext is the second message of no context.
+        assert!(receiver.open(&aad, &output[32..], &mut plaintext).is_err());
+    }
+}
@@ -0,0 +1 @@
+pub mod hpke_vectors;
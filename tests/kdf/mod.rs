@@ -7,6 +7,8 @@ pub mod other_hkdf;
 pub mod pynacl_argon2i;
 #[cfg(feature = "safe_api")]
 pub mod ref_argon2i;
+#[cfg(feature = "safe_api")]
+pub mod scrypt_vectors;
 pub mod wycheproof_hkdf;
 
 use orion::hazardous::{kdf::hkdf::*, mac::hmac};
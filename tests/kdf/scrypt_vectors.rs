@@ -0,0 +1,64 @@
+// Testing against scrypt derived keys of OpenSSL, see
+// tests/test_generation/generate_scrypt_test_vectors.py.
+
+use hex::decode;
+use orion::hazardous::kdf::scrypt;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct ScryptVectors {
+    pub scrypt: Vec<ScryptCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScryptCase {
+    pub password: String,
+    pub salt: String,
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+    pub dk: String,
+}
+
+fn load_vectors() -> ScryptVectors {
+    let file = File::open("./tests/test_data/scrypt_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_scrypt_generated_vectors() {
+    for case in load_vectors().scrypt.iter() {
+        let password = decode(&case.password).unwrap();
+        let salt = decode(&case.salt).unwrap();
+        let expected = decode(&case.dk).unwrap();
+
+        let memory = scrypt::required_memory(case.n, case.r, case.p).unwrap();
+        assert!(scrypt::validate_params(case.n, case.r, case.p, memory).is_ok());
+        assert!(scrypt::validate_params(case.n, case.r, case.p, memory - 1).is_err());
+
+        let mut dst_out = vec![0u8; expected.len()];
+        scrypt::derive_key(
+            &password,
+            &salt,
+            case.n,
+            case.r,
+            case.p,
+            memory,
+            &mut dst_out,
+        )
+        .unwrap();
+        assert_eq!(dst_out, expected);
+        assert!(scrypt::verify(
+            &expected,
+            &password,
+            &salt,
+            case.n,
+            case.r,
+            case.p,
+            memory,
+            &mut dst_out
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,53 @@
+// Testing against session keys of libsodium's crypto_kx, see
+// tests/test_generation/generate_kx_test_vectors.py.
+
+use hex::decode;
+use orion::kex::{ClientSession, PrivateKey, PublicKey, ServerSession};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct KxVectors {
+    pub kx: Vec<KxCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct KxCase {
+    pub client_private_key: String,
+    pub client_public_key: String,
+    pub server_private_key: String,
+    pub server_public_key: String,
+    pub client_rx: String,
+    pub client_tx: String,
+}
+
+fn load_vectors() -> KxVectors {
+    let file = File::open("./tests/test_data/kx_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_session_keys() {
+    for case in load_vectors().kx.iter() {
+        let client_private = PrivateKey::from_slice(&decode(&case.client_private_key).unwrap());
+        let client = ClientSession::from_private_key(client_private.unwrap()).unwrap();
+        let server_private = PrivateKey::from_slice(&decode(&case.server_private_key).unwrap());
+        let server = ServerSession::from_private_key(server_private.unwrap()).unwrap();
+
+        let client_public = PublicKey::from_slice(&decode(&case.client_public_key).unwrap());
+        let server_public = PublicKey::from_slice(&decode(&case.server_public_key).unwrap());
+        assert_eq!(client.public_key(), &client_public.unwrap());
+        assert_eq!(server.public_key(), &server_public.unwrap());
+
+        let rx = decode(&case.client_rx).unwrap();
+        let tx = decode(&case.client_tx).unwrap();
+
+        let client_keys = client.establish_with_server(server.public_key()).unwrap();
+        assert_eq!(client_keys.receiving().unprotected_as_bytes(), &rx[..]);
+        assert_eq!(client_keys.transport().unprotected_as_bytes(), &tx[..]);
+
+        let server_keys = server.establish_with_client(client.public_key()).unwrap();
+        assert_eq!(server_keys.receiving().unprotected_as_bytes(), &tx[..]);
+        assert_eq!(server_keys.transport().unprotected_as_bytes(), &rx[..]);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod kx_vectors;
+pub mod sealed_box_vectors;
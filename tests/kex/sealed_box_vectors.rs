@@ -0,0 +1,69 @@
+// Testing against sealed boxes of libsodium's
+// crypto_box_curve25519xchacha20poly1305_seal(), see
+// tests/test_generation/generate_sealed_box_test_vectors.py.
+
+use core::convert::TryFrom;
+use hex::decode;
+use orion::kex::{self, PrivateKey, PublicKey, SEAL_OVERHEAD};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct SealedBoxVectors {
+    pub sealed_box: Vec<SealedBoxCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SealedBoxCase {
+    pub recipient_private_key: String,
+    pub recipient_public_key: String,
+    pub ephemeral_private_key: String,
+    pub message_len: usize,
+    pub sealed: String,
+}
+
+fn load_vectors() -> SealedBoxVectors {
+    let file = File::open("./tests/test_data/sealed_box_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn message_bytes(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn test_open_libsodium_sealed_boxes() {
+    for case in load_vectors().sealed_box.iter() {
+        let private_key =
+            PrivateKey::from_slice(&decode(&case.recipient_private_key).unwrap()).unwrap();
+        let public_key = PublicKey::try_from(&private_key).unwrap();
+        assert_eq!(public_key, &decode(&case.recipient_public_key).unwrap()[..]);
+
+        let sealed = decode(&case.sealed).unwrap();
+        assert_eq!(sealed.len(), SEAL_OVERHEAD + case.message_len);
+        let expected = message_bytes(case.message_len);
+        assert_eq!(kex::open_sealed(&private_key, &sealed).unwrap(), expected);
+
+        let ephemeral_private =
+            PrivateKey::from_slice(&decode(&case.ephemeral_private_key).unwrap()).unwrap();
+        let ephemeral_public = PublicKey::try_from(&ephemeral_private).unwrap();
+        assert_eq!(ephemeral_public, &sealed[..32]);
+
+        // Another recipient cannot open it.
+        assert!(kex::open_sealed(&ephemeral_private, &sealed).is_err());
+    }
+}
+
+#[test]
+fn test_seal_roundtrip_with_vector_keys() {
+    for case in load_vectors().sealed_box.iter() {
+        let private_key =
+            PrivateKey::from_slice(&decode(&case.recipient_private_key).unwrap()).unwrap();
+        let public_key = PublicKey::try_from(&private_key).unwrap();
+        let message = message_bytes(case.message_len);
+
+        let sealed = kex::seal(&public_key, &message).unwrap();
+        assert_ne!(sealed, decode(&case.sealed).unwrap());
+        assert_eq!(kex::open_sealed(&private_key, &sealed).unwrap(), message);
+    }
+}
@@ -0,0 +1,72 @@
+// Testing against outputs of OpenSSL's KMAC128 and KMAC256, see
+// tests/test_generation/generate_kmac_test_vectors.py.
+
+use hex::decode;
+use orion::hazardous::mac::kmac::{Kmac128, Kmac256, SecretKey};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug)]
+pub struct KmacVectors {
+    pub kmac: Vec<KmacCase>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct KmacCase {
+    pub key_len: usize,
+    pub input_len: usize,
+    pub customization: String,
+    pub kmac128: String,
+    pub kmac256: String,
+}
+
+fn load_vectors() -> KmacVectors {
+    let file = File::open("./tests/test_data/kmac_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn input_bytes(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 251) as u8).collect()
+}
+
+fn key_bytes(n: usize) -> Vec<u8> {
+    (0..n).map(|i| ((7 * i + 3) % 256) as u8).collect()
+}
+
+#[test]
+fn test_kmac128() {
+    for case in load_vectors().kmac.iter() {
+        let key = SecretKey::from_slice(&key_bytes(case.key_len)).unwrap();
+        let customization = decode(&case.customization).unwrap();
+        let data = input_bytes(case.input_len);
+        let expected = decode(&case.kmac128).unwrap();
+        let mut actual = vec![0u8; expected.len()];
+
+        let mut state = Kmac128::new(&key, &customization, expected.len()).unwrap();
+        for chunk in data.chunks(37) {
+            state.update(chunk).unwrap();
+        }
+        state.finalize_into(&mut actual).unwrap();
+        assert_eq!(actual, expected);
+        assert!(Kmac128::verify(&expected, &key, &customization, &data).is_ok());
+    }
+}
+
+#[test]
+fn test_kmac256() {
+    for case in load_vectors().kmac.iter() {
+        let key = SecretKey::from_slice(&key_bytes(case.key_len)).unwrap();
+        let customization = decode(&case.customization).unwrap();
+        let data = input_bytes(case.input_len);
+        let expected = decode(&case.kmac256).unwrap();
+        let mut actual = vec![0u8; expected.len()];
+
+        let mut state = Kmac256::new(&key, &customization, expected.len()).unwrap();
+        for chunk in data.chunks(37) {
+            state.update(chunk).unwrap();
+        }
+        state.finalize_into(&mut actual).unwrap();
+        assert_eq!(actual, expected);
+        assert!(Kmac256::verify(&expected, &key, &customization, &data).is_ok());
+    }
+}
@@ -1,4 +1,6 @@
 pub mod boringssl_poly1305;
+#[cfg(feature = "safe_api")]
+pub mod kmac_vectors;
 pub mod nist_cavp_hmac;
 pub mod other_poly1305;
 pub mod rfc_hmac;
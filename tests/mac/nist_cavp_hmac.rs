@@ -1,37 +1,19 @@
 use crate::mac::hmac_test_runner;
-use crate::TestCaseReader;
+use crate::nist::cavp_runner;
 
 #[test]
 fn test_nist_cavp() {
-    let nist_cavp_fields: Vec<String> = vec![
-        "Count".into(),
-        "Klen".into(),
-        "Tlen".into(),
-        "Key".into(),
-        "Msg".into(),
-        "Mac".into(),
-    ];
-    let mut nist_cavp_reader = TestCaseReader::new(
-        "./tests/test_data/third_party/nist/HMAC.rsp",
-        nist_cavp_fields,
-        "=",
-    );
+    let mut tests_run = 0;
+    cavp_runner("./tests/test_data/third_party/nist/HMAC.rsp", |tc| {
+        // Only the [L=64] section contains HMAC-SHA512 test cases.
+        if tc.section_param("L") != Some("64") {
+            return;
+        }
 
-    // Skip ahead in the file until [L=64] is reached so that we read
-    // only SHA512 test cases.
-    let mut line = nist_cavp_reader.lines.next().unwrap().unwrap();
-    while line != "[L=64]" {
-        line = nist_cavp_reader.lines.next().unwrap().unwrap();
-    }
-
-    let mut test_case = nist_cavp_reader.next();
-    while test_case.is_some() {
-        let tc = test_case.unwrap();
-
-        let key: Vec<u8> = TestCaseReader::default_parse(tc.get_data("Key"));
-        let input: Vec<u8> = TestCaseReader::default_parse(tc.get_data("Msg"));
-        let tag_length: usize = tc.get_data("Tlen").parse::<usize>().unwrap();
-        let expected_output: Vec<u8> = TestCaseReader::default_parse(tc.get_data("Mac"));
+        let key: Vec<u8> = tc.get_bytes("Key");
+        let input: Vec<u8> = tc.get_bytes("Msg");
+        let tag_length: usize = tc.get_usize("Tlen");
+        let expected_output: Vec<u8> = tc.get_bytes("Mac");
 
         hmac_test_runner(
             &expected_output[..],
@@ -40,8 +22,8 @@ fn test_nist_cavp() {
             Some(tag_length),
             true,
         );
+        tests_run += 1;
+    });
 
-        // Read the next one
-        test_case = nist_cavp_reader.next();
-    }
+    assert!(tests_run > 0);
 }
@@ -3,14 +3,60 @@
 pub mod aead;
 #[cfg(feature = "safe_api")]
 #[cfg(test)]
+pub mod cose;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod differential;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod ecc;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod ffdhe;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
 pub mod hash;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod hpke;
 #[cfg(test)]
 pub mod kdf;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod kex;
 #[cfg(test)]
 pub mod mac;
+#[cfg(test)]
+pub mod nist;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod pkcs12;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod pkcs8;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod privacy_pass;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod rsa;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod srtp;
 #[cfg(feature = "safe_api")]
 #[cfg(test)]
 pub mod stream;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod timing;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod wireguard;
+#[cfg(test)]
+pub mod wycheproof;
+#[cfg(feature = "safe_api")]
+#[cfg(test)]
+pub mod x509;
 
 use hex::decode;
 
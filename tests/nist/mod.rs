@@ -0,0 +1,306 @@
+// Generic harness for NIST test vectors.
+//
+// CAVP response files (.rsp) consist of comment lines starting with '#',
+// section headers such as "[L = 64]" that set parameters for all following
+// test cases, and test cases made of "Name = Value" lines separated by blank
+// lines. `CavpReader` yields each test case together with the parameters of
+// the section it appeared in, so the fields of a test case don't need to be
+// known in advance.
+//
+// ACVP vector sets are JSON, with a list of test groups that each have a
+// "tgId", group-wide parameters and a list of tests with a "tcId". Like for
+// Wycheproof, only the group parameters and test data differ between
+// algorithms, so those are the type parameters of `AcvpTests`.
+
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+};
+
+#[derive(Debug, Clone)]
+/// A test case from a CAVP response file.
+pub struct CavpTestCase {
+    /// The parameters of the section the test case appeared in.
+    pub section: HashMap<String, String>,
+    /// <Field name, Field data>, eg.: <"Msg", "d545gfdfggf42312...">
+    pub fields: HashMap<String, String>,
+    pub test_case_number: u64,
+}
+
+impl CavpTestCase {
+    /// Get the raw data of `field`. Panics if the test case has no such field.
+    pub fn get(&self, field: &str) -> &str {
+        self.fields
+            .get(field)
+            .unwrap_or_else(|| panic!("CAVP: Test case field {} does not exist", field))
+    }
+
+    /// Get `field` decoded as hex.
+    pub fn get_bytes(&self, field: &str) -> Vec<u8> {
+        hex::decode(self.get(field)).unwrap()
+    }
+
+    /// Get `field` parsed as a decimal integer.
+    pub fn get_usize(&self, field: &str) -> usize {
+        self.get(field).parse::<usize>().unwrap()
+    }
+
+    /// Get the section parameter `param`, if the section defined it.
+    pub fn section_param(&self, param: &str) -> Option<&str> {
+        self.section.get(param).map(|value| value.as_str())
+    }
+}
+
+/// Parse a "Name = Value" line, trimming whitespace around both.
+fn parse_field(line: &str) -> Option<(String, String)> {
+    let split_at_idx = line.find('=')?;
+    let (name, value) = line.split_at(split_at_idx);
+
+    Some((name.trim().to_string(), value[1..].trim().to_string()))
+}
+
+#[derive(Debug)]
+/// An iterator over the test cases of a CAVP response file.
+pub struct CavpReader {
+    lines: Lines<BufReader<File>>,
+    section: HashMap<String, String>,
+    test_case_count: u64,
+}
+
+impl CavpReader {
+    pub fn new(path: &str) -> Self {
+        let file =
+            File::open(path).unwrap_or_else(|_| panic!("CAVP: Unable to open file: {}", path));
+
+        Self {
+            lines: BufReader::new(file).lines(),
+            section: HashMap::new(),
+            test_case_count: 0,
+        }
+    }
+}
+
+impl Iterator for CavpReader {
+    type Item = CavpTestCase;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut fields: HashMap<String, String> = HashMap::new();
+
+        for line in &mut self.lines {
+            let line = line.unwrap();
+            let line = line.trim();
+
+            if line.starts_with('#') {
+                continue;
+            }
+
+            if line.is_empty() {
+                if fields.is_empty() {
+                    continue;
+                }
+                break;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                // A section header ends any test case that wasn't followed by
+                // a blank line. Headers may hold a parameter, such as "[L = 64]",
+                // or a bare name, such as "[SHA-512]".
+                let header = &line[1..line.len() - 1];
+                if !fields.is_empty() {
+                    panic!("CAVP: Section header directly after a test case");
+                }
+                match parse_field(header) {
+                    Some((name, value)) => self.section.insert(name, value),
+                    None => self
+                        .section
+                        .insert(header.trim().to_string(), String::new()),
+                };
+                continue;
+            }
+
+            let (name, value) = parse_field(line)
+                .unwrap_or_else(|| panic!("CAVP: Could not find separator in line: {}", line));
+            fields.insert(name, value);
+        }
+
+        if fields.is_empty() {
+            return None;
+        }
+
+        self.test_case_count += 1;
+
+        Some(CavpTestCase {
+            section: self.section.clone(),
+            fields,
+            test_case_number: self.test_case_count,
+        })
+    }
+}
+
+/// Run `runner` on all test cases in the CAVP response file at `path`, and
+/// return the number of test cases the file contained.
+pub fn cavp_runner<F>(path: &str, mut runner: F) -> u64
+where
+    F: FnMut(&CavpTestCase),
+{
+    let mut tests_run = 0;
+    for test_case in CavpReader::new(path) {
+        runner(&test_case);
+        tests_run += 1;
+    }
+
+    assert!(tests_run > 0, "CAVP: No test cases found in {}", path);
+
+    tests_run
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+/// A single ACVP test, with the algorithm-specific data `T`.
+pub struct AcvpTest<T> {
+    pub tcId: u64,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+/// A group of ACVP tests sharing the algorithm-specific parameters `P`.
+pub struct AcvpTestGroup<P, T> {
+    pub tgId: u64,
+    #[serde(flatten)]
+    pub params: P,
+    pub tests: Vec<AcvpTest<T>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+/// An ACVP vector set, with the expected results included.
+pub struct AcvpTests<P, T> {
+    pub algorithm: String,
+    pub revision: String,
+    pub testGroups: Vec<AcvpTestGroup<P, T>>,
+}
+
+/// Run `runner` on every test in the parsed ACVP vector set `tests`. `runner`
+/// is called with the algorithm name, the group parameters and the test. It
+/// must panic if orion does not produce the expected result.
+pub fn acvp_runner<P, T, F>(tests: &AcvpTests<P, T>, mut runner: F) -> u64
+where
+    F: FnMut(&str, &P, &AcvpTest<T>),
+{
+    let mut tests_run = 0;
+    for test_group in tests.testGroups.iter() {
+        for test in test_group.tests.iter() {
+            runner(&tests.algorithm, &test_group.params, test);
+            tests_run += 1;
+        }
+    }
+
+    assert!(tests_run > 0, "ACVP: No tests found");
+
+    tests_run
+}
+
+/// Parse the ACVP vector set at `path` and run `runner` on all of its tests.
+pub fn acvp_file_runner<P, T, F>(path: &str, runner: F) -> u64
+where
+    P: DeserializeOwned,
+    T: DeserializeOwned,
+    F: FnMut(&str, &P, &AcvpTest<T>),
+{
+    let file = File::open(path).unwrap_or_else(|_| panic!("ACVP: Unable to open file: {}", path));
+    let tests: AcvpTests<P, T> = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+    acvp_runner(&tests, runner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(
+            parse_field("Len = 8"),
+            Some(("Len".to_string(), "8".to_string()))
+        );
+        assert_eq!(
+            parse_field("L=64"),
+            Some(("L".to_string(), "64".to_string()))
+        );
+        assert_eq!(
+            parse_field("Msg ="),
+            Some(("Msg".to_string(), "".to_string()))
+        );
+        assert_eq!(parse_field("SHA-512"), None);
+    }
+
+    #[test]
+    fn test_cavp_reader_sections() {
+        let mut sections: Vec<String> = Vec::new();
+        let count = cavp_runner("./tests/test_data/third_party/nist/HMAC.rsp", |tc| {
+            let l = tc.section_param("L").unwrap().to_string();
+            if sections.last() != Some(&l) {
+                sections.push(l);
+            }
+            assert_eq!(tc.get_bytes("Key").len(), tc.get_usize("Klen"));
+            assert_eq!(tc.get_bytes("Mac").len(), tc.get_usize("Tlen"));
+        });
+
+        assert_eq!(sections, ["20", "28", "32", "48", "64"]);
+        assert_eq!(count, 1575);
+    }
+
+    #[test]
+    fn test_acvp_sha512() {
+        use orion::hazardous::hash::sha512::Sha512;
+
+        // A reduced ACVP SHA2-512 vector set with expected results.
+        let json = r#"{
+            "vsId": 0,
+            "algorithm": "SHA2-512",
+            "revision": "1.0",
+            "testGroups": [{
+                "tgId": 1,
+                "testType": "AFT",
+                "tests": [
+                    {"tcId": 1, "len": 0, "msg": "", "md": "CF83E1357EEFB8BDF1542850D66D8007D620E4050B5715DC83F4A921D36CE9CE47D0D13C5D85F2B0FF8318D2877EEC2F63B931BD47417A81A538327AF927DA3E"},
+                    {"tcId": 2, "len": 24, "msg": "616263", "md": "DDAF35A193617ABACC417349AE20413112E6FA4E89A97EA20A9EEEE64B55D39A2192992A274FC1A836BA3C23A3FEEBBD454D4423643CE80E2A9AC94FA54CA49F"}
+                ]
+            }]
+        }"#;
+
+        #[allow(non_snake_case)]
+        #[derive(Deserialize)]
+        struct Params {
+            testType: String,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            len: usize,
+            msg: String,
+            md: String,
+        }
+
+        let tests: AcvpTests<Params, Data> = serde_json::from_str(json).unwrap();
+        let count = acvp_runner(&tests, |algorithm, params, test| {
+            assert_eq!(algorithm, "SHA2-512");
+            assert_eq!(params.testType, "AFT");
+
+            let msg = hex::decode(&test.data.msg).unwrap();
+            assert_eq!(msg.len() * 8, test.data.len);
+            let digest = Sha512::digest(&msg).unwrap();
+            assert_eq!(
+                digest.as_ref(),
+                &hex::decode(&test.data.md).unwrap()[..],
+                "tcId: {}",
+                test.tcId
+            );
+        });
+
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,137 @@
+// Testing against archives exported by OpenSSL, see
+// tests/test_generation/generate_pkcs12_test_vectors.py.
+
+use core::convert::TryFrom;
+use hex::decode;
+use orion::hazardous::ecc::{ecdsa_p256, ed25519};
+use orion::hazardous::protocols::pkcs12::{self, PrivateKey};
+use orion::hazardous::rsa;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_pkcs12_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct Pkcs12Vectors {
+    pub pkcs12: Vec<Pkcs12Vector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Pkcs12Vector {
+    pub name: String,
+    pub password: String,
+    pub pfx: String,
+    pub legacy: bool,
+    pub valid: bool,
+    pub private_keys: Vec<KeyVector>,
+    pub certificates: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct KeyVector {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub pkcs8: String,
+    pub spki: String,
+}
+
+fn load_vectors() -> Pkcs12Vectors {
+    let file = File::open("./tests/test_data/pkcs12_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+/// Check that `private_key` is of the type of `expected` and matches its
+/// SubjectPublicKeyInfo.
+fn assert_key_matches(private_key: &PrivateKey, expected: &KeyVector) {
+    let spki = decode(&expected.spki).unwrap();
+    match (private_key, expected.key_type.as_str()) {
+        (PrivateKey::Ed25519(secret_key), "ed25519") => {
+            let public_key = ed25519::PublicKey::try_from(secret_key).unwrap();
+            assert_eq!(public_key.as_ref(), &spki[spki.len() - 32..]);
+        }
+        (PrivateKey::EcdsaP256(secret_key), "p256") => {
+            let public_key = ecdsa_p256::PublicKey::try_from(secret_key).unwrap();
+            assert_eq!(public_key.as_ref(), &spki[spki.len() - 65..]);
+        }
+        (PrivateKey::Rsa(secret_key), "rsa") => {
+            let public_key = rsa::keys::PublicKey::from_spki_der(&spki).unwrap();
+            assert!(secret_key.public_key() == public_key);
+        }
+        _ => panic!("unexpected key type, expected {}", expected.key_type),
+    }
+}
+
+#[test]
+fn test_parse() {
+    for vector in load_vectors().pkcs12.iter() {
+        let pfx = decode(&vector.pfx).unwrap();
+        let result = pkcs12::parse(&pfx, &vector.password);
+        if !vector.valid || (vector.legacy && cfg!(not(feature = "legacy"))) {
+            assert!(result.is_err(), "{}", vector.name);
+            continue;
+        }
+
+        let contents = result.unwrap();
+        assert_eq!(
+            contents.private_keys.len(),
+            vector.private_keys.len(),
+            "{}",
+            vector.name
+        );
+        for (private_key, expected) in contents.private_keys.iter().zip(vector.private_keys.iter())
+        {
+            assert_key_matches(private_key, expected);
+        }
+        let certificates: Vec<String> = contents.certificates.iter().map(hex::encode).collect();
+        assert_eq!(certificates, vector.certificates, "{}", vector.name);
+    }
+}
+
+#[test]
+fn test_wrong_password() {
+    for vector in load_vectors().pkcs12.iter() {
+        let pfx = decode(&vector.pfx).unwrap();
+        let mut password = vector.password.clone();
+        password.push('!');
+        assert!(pkcs12::parse(&pfx, &password).is_err(), "{}", vector.name);
+        // A missing character is not the same as an empty password.
+        if !vector.password.is_empty() {
+            assert!(pkcs12::parse(&pfx, "").is_err(), "{}", vector.name);
+        }
+    }
+}
+
+#[test]
+fn test_modified_archive() {
+    let vectors = load_vectors();
+    let vector = vectors
+        .pkcs12
+        .iter()
+        .find(|vector| vector.name == "rsa_chain")
+        .unwrap();
+    let pfx = decode(&vector.pfx).unwrap();
+    assert!(pkcs12::parse(&pfx, &vector.password).is_ok());
+
+    // Flipping any bit of the authenticated contents or the MAC is detected.
+    for i in (0..pfx.len()).step_by(97) {
+        let mut modified = pfx.clone();
+        modified[i] ^= 0x01;
+        assert!(pkcs12::parse(&modified, &vector.password).is_err());
+    }
+
+    let mut appended = pfx.clone();
+    appended.push(0x00);
+    assert!(pkcs12::parse(&appended, &vector.password).is_err());
+    assert!(pkcs12::parse(&pfx[..pfx.len() - 1], &vector.password).is_err());
+}
+
+#[test]
+fn test_pkcs8() {
+    for vector in load_vectors().pkcs12.iter() {
+        for expected in vector.private_keys.iter() {
+            let private_key =
+                PrivateKey::from_pkcs8_der(&decode(&expected.pkcs8).unwrap()).unwrap();
+            assert_key_matches(&private_key, expected);
+        }
+    }
+}
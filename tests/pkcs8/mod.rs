@@ -0,0 +1,75 @@
+// Testing against private keys encrypted by OpenSSL, and with AES-GCM by an
+// independent implementation, see
+// tests/test_generation/generate_pkcs8_test_vectors.py.
+
+use hex::decode;
+use orion::hazardous::protocols::pkcs8::{self, Cipher, PrivateKey};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_pkcs8_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct Pkcs8Vectors {
+    pub pkcs8: Vec<Pkcs8Vector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Pkcs8Vector {
+    pub name: String,
+    pub key: String,
+    pub password: String,
+    pub encrypted: String,
+    pub private_key_info: String,
+    pub valid: bool,
+}
+
+fn load_vectors() -> Pkcs8Vectors {
+    let file = File::open("./tests/test_data/pkcs8_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_decrypt() {
+    for vector in load_vectors().pkcs8.iter() {
+        let encrypted = decode(&vector.encrypted).unwrap();
+        let result = pkcs8::decrypt(&encrypted, &vector.password);
+        if !vector.valid {
+            assert!(result.is_err(), "{}", vector.name);
+            continue;
+        }
+
+        let private_key_info = result.unwrap();
+        assert_eq!(
+            private_key_info.unprotected_as_bytes(),
+            &decode(&vector.private_key_info).unwrap()[..],
+            "{}",
+            vector.name
+        );
+        match (private_key_info.to_private_key(), vector.key.as_str()) {
+            (Ok(PrivateKey::Ed25519(_)), "ed25519") => (),
+            (Ok(PrivateKey::EcdsaP256(_)), "p256") => (),
+            (Ok(PrivateKey::Rsa(_)), "rsa") => (),
+            // X25519 keys can be decrypted, but not imported.
+            (Err(_), "x25519") => (),
+            _ => panic!("unexpected key type in {}", vector.name),
+        }
+
+        let mut wrong_password = vector.password.clone();
+        wrong_password.push('!');
+        assert!(pkcs8::decrypt(&encrypted, &wrong_password).is_err());
+    }
+}
+
+#[test]
+fn test_encrypt_roundtrip() {
+    for vector in load_vectors().pkcs8.iter() {
+        let private_key_info = decode(&vector.private_key_info).unwrap();
+        for cipher in [Cipher::Aes256Cbc, Cipher::Aes256Gcm].iter() {
+            let encrypted =
+                pkcs8::encrypt(&private_key_info, &vector.password, 1000, *cipher).unwrap();
+            let decrypted = pkcs8::decrypt(&encrypted, &vector.password).unwrap();
+            assert_eq!(decrypted.unprotected_as_bytes(), &private_key_info[..]);
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Testing against tokens generated with an independent implementation of
+// RFC 9578, see tests/test_generation/generate_privacy_pass_test_vectors.py.
+
+use orion::hazardous::ecc::voprf::{
+    Blind, ProofNonce, PublicKey as VoprfPublicKey, SecretKey as VoprfSecretKey,
+};
+use orion::hazardous::protocols::privacy_pass::{
+    private_token, public_token, Token, TokenChallenge, TokenRequest, TokenResponse,
+};
+use orion::hazardous::rsa::keys::SecretKey as RsaSecretKey;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_privacy_pass_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct PrivacyPassVectors {
+    pub private_tokens: Vec<PrivateTokenVector>,
+    pub public_tokens: Vec<PublicTokenVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PrivateTokenVector {
+    pub seed: String,
+    pub info: String,
+    pub public_key: String,
+    pub token_key_id: String,
+    pub challenge: String,
+    pub nonce: String,
+    pub blind: String,
+    pub proof_nonce: String,
+    pub token_request: String,
+    pub token_response: String,
+    pub token: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PublicTokenVector {
+    pub public_key: String,
+    pub token_key_id: String,
+    pub challenge: String,
+    pub nonce: String,
+    pub salt: String,
+    pub blinding_seed: String,
+    pub token_request: String,
+    pub token_response: String,
+    pub token: String,
+}
+
+fn load() -> PrivacyPassVectors {
+    let file = File::open("./tests/test_data/privacy_pass_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn decode(value: &str) -> Vec<u8> {
+    hex::decode(value).unwrap()
+}
+
+fn rsa_secret_key() -> RsaSecretKey {
+    let der = std::fs::read("./tests/test_data/rsa/rsa2048_e65537.pk8.der").unwrap();
+    RsaSecretKey::from_pkcs8_der(&der).unwrap()
+}
+
+#[test]
+fn test_private_token_vectors() {
+    for vector in load().private_tokens.iter() {
+        let secret_key =
+            VoprfSecretKey::derive(&decode(&vector.seed), &decode(&vector.info)).unwrap();
+        let public_key = secret_key.public_key().unwrap();
+        assert_eq!(
+            public_key,
+            VoprfPublicKey::from_slice(&decode(&vector.public_key)).unwrap()
+        );
+        assert_eq!(
+            private_token::token_key_id(&public_key).to_vec(),
+            decode(&vector.token_key_id)
+        );
+
+        let challenge = TokenChallenge::from_bytes(&decode(&vector.challenge)).unwrap();
+        assert_eq!(challenge.to_bytes(), decode(&vector.challenge));
+        let blind = Blind::from_slice(&decode(&vector.blind)).unwrap();
+        let (request, state) = private_token::create_token_request(
+            &public_key,
+            &challenge,
+            &decode(&vector.nonce),
+            blind,
+        )
+        .unwrap();
+        assert_eq!(request.to_bytes(), decode(&vector.token_request));
+        assert_eq!(
+            TokenRequest::from_bytes(&request.to_bytes()).unwrap(),
+            request
+        );
+
+        let proof_nonce = ProofNonce::from_slice(&decode(&vector.proof_nonce)).unwrap();
+        let response =
+            private_token::issue_token_response(&secret_key, &request, &proof_nonce).unwrap();
+        assert_eq!(response.to_bytes(), decode(&vector.token_response));
+
+        let token = private_token::finalize_token(&state, &response).unwrap();
+        assert_eq!(token.to_bytes(), decode(&vector.token));
+        let token = Token::from_bytes(&decode(&vector.token)).unwrap();
+        assert!(private_token::verify_token(&secret_key, &challenge, &token).is_ok());
+    }
+}
+
+#[test]
+fn test_public_token_vectors() {
+    let secret_key = rsa_secret_key();
+    for vector in load().public_tokens.iter() {
+        let encoded = decode(&vector.public_key);
+        assert_eq!(
+            public_token::encode_public_key(&secret_key.public_key()).unwrap(),
+            encoded
+        );
+        let public_key = public_token::decode_public_key(&encoded).unwrap();
+        assert_eq!(public_key, secret_key.public_key());
+        assert_eq!(
+            public_token::token_key_id(&public_key).unwrap().to_vec(),
+            decode(&vector.token_key_id)
+        );
+
+        let challenge = TokenChallenge::from_bytes(&decode(&vector.challenge)).unwrap();
+        let (request, state) = public_token::create_token_request(
+            &public_key,
+            &challenge,
+            &decode(&vector.nonce),
+            &decode(&vector.salt),
+            &decode(&vector.blinding_seed),
+        )
+        .unwrap();
+        assert_eq!(request.to_bytes(), decode(&vector.token_request));
+
+        let response = public_token::issue_token_response(&secret_key, &request).unwrap();
+        assert_eq!(response.to_bytes(), decode(&vector.token_response));
+
+        let token = public_token::finalize_token(&state, &response).unwrap();
+        assert_eq!(token.to_bytes(), decode(&vector.token));
+        let token = Token::from_bytes(&decode(&vector.token)).unwrap();
+        assert!(public_token::verify_token(&public_key, &challenge, &token).is_ok());
+    }
+}
+
+#[test]
+fn test_public_token_rejects_modified_response() {
+    let secret_key = rsa_secret_key();
+    let vector = &load().public_tokens[0];
+    let public_key = secret_key.public_key();
+    let challenge = TokenChallenge::from_bytes(&decode(&vector.challenge)).unwrap();
+    let (_, state) = public_token::create_token_request(
+        &public_key,
+        &challenge,
+        &decode(&vector.nonce),
+        &decode(&vector.salt),
+        &decode(&vector.blinding_seed),
+    )
+    .unwrap();
+
+    let mut response = decode(&vector.token_response);
+    response[100] ^= 1;
+    let response = TokenResponse::from_bytes(&response).unwrap();
+    assert!(public_token::finalize_token(&state, &response).is_err());
+}
+
+#[test]
+fn test_tokens_are_bound_to_challenge() {
+    let vectors = load();
+    let secret_key = rsa_secret_key();
+    let public_key = secret_key.public_key();
+    let token = Token::from_bytes(&decode(&vectors.public_tokens[0].token)).unwrap();
+    let other = TokenChallenge::from_bytes(&decode(&vectors.public_tokens[1].challenge)).unwrap();
+    assert!(public_token::verify_token(&public_key, &other, &token).is_err());
+
+    let vector = &vectors.private_tokens[0];
+    let secret_key = VoprfSecretKey::derive(&decode(&vector.seed), &decode(&vector.info)).unwrap();
+    let token = Token::from_bytes(&decode(&vector.token)).unwrap();
+    let other = TokenChallenge::from_bytes(&decode(&vectors.private_tokens[1].challenge)).unwrap();
+    assert!(private_token::verify_token(&secret_key, &other, &token).is_err());
+}
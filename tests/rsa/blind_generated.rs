@@ -0,0 +1,162 @@
+// Testing against RSA blind signatures generated with an independent
+// implementation of RFC 9474, see
+// tests/test_generation/generate_rsa_blind_test_vectors.py.
+
+use super::load_keys;
+use hex::decode;
+use orion::hazardous::rsa::blind::{self, Variant};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_rsa_blind_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct BlindKeyVectors {
+    pub key: String,
+    pub blind: Vec<BlindVector>,
+    pub signatures: Vec<SignatureVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BlindVector {
+    pub variant: String,
+    pub message: String,
+    pub randomizer: String,
+    pub salt: String,
+    pub blinding_seed: String,
+    pub blinded_message: String,
+    pub blind_signature: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SignatureVector {
+    pub variant: String,
+    pub message: String,
+    pub signature: String,
+}
+
+fn load_vectors() -> Vec<BlindKeyVectors> {
+    let file = File::open("./tests/test_data/rsa/rsa_blind_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn variant(name: &str) -> Variant {
+    match name {
+        "Sha384PssRandomized" => Variant::Sha384PssRandomized,
+        "Sha384PssZeroRandomized" => Variant::Sha384PssZeroRandomized,
+        "Sha384PssDeterministic" => Variant::Sha384PssDeterministic,
+        "Sha384PssZeroDeterministic" => Variant::Sha384PssZeroDeterministic,
+        _ => panic!("unknown variant {}", name),
+    }
+}
+
+#[test]
+fn test_blind_signatures() {
+    for vectors in load_vectors().iter() {
+        let (secret_key, public_key) = load_keys(&vectors.key);
+        let size = public_key.size();
+
+        for test in vectors.blind.iter() {
+            let variant = variant(&test.variant);
+            let message = decode(&test.message).unwrap();
+            let randomizer = decode(&test.randomizer).unwrap();
+
+            let mut prepared = vec![0u8; randomizer.len() + message.len()];
+            blind::prepare(variant, &message, &randomizer, &mut prepared).unwrap();
+            let (mut blinded, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+            blind::blind(
+                &public_key,
+                variant,
+                &prepared,
+                &decode(&test.salt).unwrap(),
+                &decode(&test.blinding_seed).unwrap(),
+                &mut blinded,
+                &mut inverse,
+            )
+            .unwrap();
+            assert_eq!(
+                blinded,
+                decode(&test.blinded_message).unwrap(),
+                "{}",
+                vectors.key
+            );
+
+            let mut blind_signature = vec![0u8; size];
+            blind::blind_sign(&secret_key, &blinded, &mut blind_signature).unwrap();
+            assert_eq!(blind_signature, decode(&test.blind_signature).unwrap());
+
+            let mut signature = vec![0u8; size];
+            blind::finalize(
+                &public_key,
+                variant,
+                &prepared,
+                &blind_signature,
+                &inverse,
+                &mut signature,
+            )
+            .unwrap();
+            assert_eq!(signature, decode(&test.signature).unwrap());
+            assert!(blind::verify(&public_key, variant, &prepared, &signature).is_ok());
+        }
+    }
+}
+
+#[test]
+fn test_signatures_from_other_implementation() {
+    for vectors in load_vectors().iter() {
+        let (_, public_key) = load_keys(&vectors.key);
+
+        for test in vectors.signatures.iter() {
+            let message = decode(&test.message).unwrap();
+            let signature = decode(&test.signature).unwrap();
+            let variant = variant(&test.variant);
+            // These are signatures of the unprepared message, with a random salt.
+            assert!(blind::verify(&public_key, variant, &message, &signature).is_ok());
+
+            // The salt length is part of the variant.
+            let other = match variant {
+                Variant::Sha384PssRandomized => Variant::Sha384PssZeroRandomized,
+                _ => Variant::Sha384PssRandomized,
+            };
+            assert!(blind::verify(&public_key, other, &message, &signature).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_modified_blind_signature() {
+    let vectors = &load_vectors()[0];
+    let (_, public_key) = load_keys(&vectors.key);
+    let size = public_key.size();
+    let test = &vectors.blind[0];
+    let variant = variant(&test.variant);
+    let mut prepared = decode(&test.randomizer).unwrap();
+    prepared.extend_from_slice(&decode(&test.message).unwrap());
+
+    let (mut blinded, mut inverse) = (vec![0u8; size], vec![0u8; size]);
+    blind::blind(
+        &public_key,
+        variant,
+        &prepared,
+        &decode(&test.salt).unwrap(),
+        &decode(&test.blinding_seed).unwrap(),
+        &mut blinded,
+        &mut inverse,
+    )
+    .unwrap();
+
+    let mut blind_signature = decode(&test.blind_signature).unwrap();
+    blind_signature[size - 1] ^= 1;
+    let mut signature = vec![0u8; size];
+    assert!(blind::finalize(
+        &public_key,
+        variant,
+        &prepared,
+        &blind_signature,
+        &inverse,
+        &mut signature
+    )
+    .is_err());
+    assert_eq!(signature, vec![0u8; size]);
+}
@@ -0,0 +1,65 @@
+pub mod blind_generated;
+pub mod oaep_generated;
+pub mod pss_generated;
+
+use orion::hazardous::rsa::keys::{PublicKey, SecretKey};
+use serde::Deserialize;
+use std::{fs, fs::File, io::BufReader};
+
+/// The keys and test vectors generated by
+/// tests/test_generation/generate_rsa_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct KeyVectors {
+    pub key: String,
+    pub pss_deterministic: Vec<PssDeterministic>,
+    pub pss_random: Vec<PssRandom>,
+    pub oaep_deterministic: Vec<OaepDeterministic>,
+    pub oaep_random: Vec<OaepRandom>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PssDeterministic {
+    pub message: String,
+    pub salt: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PssRandom {
+    pub message: String,
+    pub signature: String,
+    #[serde(default)]
+    pub invalid: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OaepDeterministic {
+    pub message: String,
+    pub label: String,
+    pub seed: String,
+    pub ciphertext: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OaepRandom {
+    pub message: String,
+    pub label: String,
+    pub ciphertext: String,
+    #[serde(default)]
+    pub invalid: bool,
+}
+
+pub fn load_vectors() -> Vec<KeyVectors> {
+    let file = File::open("./tests/test_data/rsa/rsa_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+pub fn load_keys(name: &str) -> (SecretKey, PublicKey) {
+    let pkcs8 = fs::read(format!("./tests/test_data/rsa/{}.pk8.der", name)).unwrap();
+    let spki = fs::read(format!("./tests/test_data/rsa/{}.spki.der", name)).unwrap();
+
+    (
+        SecretKey::from_pkcs8_der(&pkcs8).unwrap(),
+        PublicKey::from_spki_der(&spki).unwrap(),
+    )
+}
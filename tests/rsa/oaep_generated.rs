@@ -0,0 +1,67 @@
+// Testing against RSAES-OAEP ciphertexts generated with the Python
+// `cryptography` package, see tests/test_generation/generate_rsa_test_vectors.py.
+
+use super::{load_keys, load_vectors};
+use hex::decode;
+use orion::hazardous::rsa::oaep;
+
+/// An empty label is passed as `None`, to test both variants.
+fn label(hex_label: &str) -> Option<Vec<u8>> {
+    if hex_label.is_empty() {
+        None
+    } else {
+        Some(decode(hex_label).unwrap())
+    }
+}
+
+#[test]
+fn test_deterministic_ciphertexts() {
+    for vectors in load_vectors().iter() {
+        let (secret_key, public_key) = load_keys(&vectors.key);
+
+        for test in vectors.oaep_deterministic.iter() {
+            let message = decode(&test.message).unwrap();
+            let label = label(&test.label);
+            let seed = decode(&test.seed).unwrap();
+            let expected = decode(&test.ciphertext).unwrap();
+
+            let mut ciphertext = vec![0u8; public_key.size()];
+            oaep::encrypt(
+                &public_key,
+                &message,
+                label.as_deref(),
+                &seed,
+                &mut ciphertext,
+            )
+            .unwrap();
+            assert_eq!(ciphertext, expected, "{}", vectors.key);
+
+            let mut plaintext = vec![0u8; secret_key.size()];
+            let len =
+                oaep::decrypt(&secret_key, &ciphertext, label.as_deref(), &mut plaintext).unwrap();
+            assert_eq!(&plaintext[..len], &message[..]);
+        }
+    }
+}
+
+#[test]
+fn test_random_ciphertexts() {
+    for vectors in load_vectors().iter() {
+        let (secret_key, _) = load_keys(&vectors.key);
+
+        for test in vectors.oaep_random.iter() {
+            let message = decode(&test.message).unwrap();
+            let label = label(&test.label);
+            let ciphertext = decode(&test.ciphertext).unwrap();
+
+            let mut plaintext = vec![0u8; secret_key.size()];
+            match oaep::decrypt(&secret_key, &ciphertext, label.as_deref(), &mut plaintext) {
+                Ok(len) => {
+                    assert!(!test.invalid, "{}", vectors.key);
+                    assert_eq!(&plaintext[..len], &message[..]);
+                }
+                Err(_) => assert!(test.invalid, "{}", vectors.key),
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// Testing against RSASSA-PSS signatures generated with the Python
+// `cryptography` package, see tests/test_generation/generate_rsa_test_vectors.py.
+
+use super::{load_keys, load_vectors};
+use hex::decode;
+use orion::hazardous::rsa::pss;
+
+#[test]
+fn test_deterministic_signatures() {
+    for vectors in load_vectors().iter() {
+        let (secret_key, public_key) = load_keys(&vectors.key);
+        assert_eq!(secret_key.public_key(), public_key);
+
+        for test in vectors.pss_deterministic.iter() {
+            let message = decode(&test.message).unwrap();
+            let salt = decode(&test.salt).unwrap();
+            let expected = decode(&test.signature).unwrap();
+
+            let mut signature = vec![0u8; secret_key.size()];
+            pss::sign(&secret_key, &message, &salt, &mut signature).unwrap();
+            assert_eq!(signature, expected, "{}", vectors.key);
+            assert!(pss::verify(&public_key, &message, &signature).is_ok());
+        }
+    }
+}
+
+#[test]
+fn test_random_signatures() {
+    for vectors in load_vectors().iter() {
+        let (_, public_key) = load_keys(&vectors.key);
+
+        for test in vectors.pss_random.iter() {
+            let message = decode(&test.message).unwrap();
+            let signature = decode(&test.signature).unwrap();
+            assert_eq!(
+                pss::verify(&public_key, &message, &signature).is_ok(),
+                !test.invalid,
+                "{}",
+                vectors.key
+            );
+        }
+    }
+}
+
+#[test]
+fn test_signature_under_other_key() {
+    let vectors = load_vectors();
+    let (_, public_key) = load_keys(&vectors[0].key);
+    let (_, other_public_key) = load_keys(&vectors[3].key);
+    assert_eq!(public_key.size(), other_public_key.size());
+
+    let test = &vectors[0].pss_random[0];
+    let message = decode(&test.message).unwrap();
+    let signature = decode(&test.signature).unwrap();
+    assert!(pss::verify(&public_key, &message, &signature).is_ok());
+    assert!(pss::verify(&other_public_key, &message, &signature).is_err());
+}
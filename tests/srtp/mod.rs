@@ -0,0 +1,122 @@
+// Testing against packets protected with an independent implementation of
+// SRTP, see tests/test_generation/generate_srtp_test_vectors.py.
+
+use orion::hazardous::protocols::srtp::{Context, Profile};
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_srtp_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct SrtpVectors {
+    pub profiles: Vec<ProfileVectors>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProfileVectors {
+    pub name: String,
+    pub id: u16,
+    pub master_key: String,
+    pub master_salt: String,
+    pub rtp: Vec<RtpVector>,
+    pub rtcp: Vec<RtcpVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RtpVector {
+    pub roc: u32,
+    pub packet: String,
+    pub protected: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RtcpVector {
+    pub index: u32,
+    pub packet: String,
+    pub protected: String,
+}
+
+fn load() -> SrtpVectors {
+    let file = File::open("./tests/test_data/srtp_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn decode(value: &str) -> Vec<u8> {
+    hex::decode(value).unwrap()
+}
+
+fn context(vectors: &ProfileVectors) -> Context {
+    let profile = Profile::from_id(vectors.id).unwrap();
+    Context::new(
+        profile,
+        &decode(&vectors.master_key),
+        &decode(&vectors.master_salt),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_rtp_vectors() {
+    for vectors in load().profiles.iter() {
+        let ctx = context(vectors);
+        for vector in vectors.rtp.iter() {
+            let packet = decode(&vector.packet);
+            let expected = decode(&vector.protected);
+
+            let mut buf = packet.clone();
+            buf.resize(packet.len() + ctx.profile().rtp_tag_size(), 0);
+            ctx.protect_rtp(&mut buf, vector.roc).unwrap();
+            assert_eq!(buf, expected, "{}", vectors.name);
+
+            assert_eq!(
+                ctx.unprotect_rtp(&mut buf, vector.roc).unwrap(),
+                packet.len()
+            );
+            assert_eq!(&buf[..packet.len()], &packet[..]);
+
+            let mut wrong_roc = expected.clone();
+            assert!(ctx
+                .unprotect_rtp(&mut wrong_roc, vector.roc.wrapping_add(1))
+                .is_err());
+            assert_eq!(wrong_roc, expected);
+
+            for idx in [0, 2, 11, expected.len() - 1].iter() {
+                let mut modified = expected.clone();
+                modified[*idx] ^= 1;
+                let flipped = modified.clone();
+                assert!(ctx.unprotect_rtp(&mut modified, vector.roc).is_err());
+                assert_eq!(modified, flipped);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rtcp_vectors() {
+    for vectors in load().profiles.iter() {
+        let ctx = context(vectors);
+        for vector in vectors.rtcp.iter() {
+            let packet = decode(&vector.packet);
+            let expected = decode(&vector.protected);
+
+            let mut buf = packet.clone();
+            buf.resize(packet.len() + ctx.profile().rtcp_trailer_size(), 0);
+            ctx.protect_rtcp(&mut buf, vector.index).unwrap();
+            assert_eq!(buf, expected, "{}", vectors.name);
+
+            assert_eq!(
+                ctx.unprotect_rtcp(&mut buf).unwrap(),
+                (packet.len(), vector.index)
+            );
+            assert_eq!(&buf[..packet.len()], &packet[..]);
+
+            for idx in [0, 4, packet.len(), expected.len() - 1].iter() {
+                let mut modified = expected.clone();
+                modified[*idx] ^= 1;
+                let flipped = modified.clone();
+                assert!(ctx.unprotect_rtcp(&mut modified).is_err());
+                assert_eq!(modified, flipped);
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+# Generates tests/test_data/aes256gcm_generated.json.
+#
+# Requires the Python `cryptography` package. Every vector is sealed with its
+# AESGCM class, which is backed by OpenSSL. The message and additional data
+# lengths are chosen around the AES block size, so that both the CTR keystream
+# and GHASH are tested with partial and full final blocks. Each vector also has
+# a copy of the ciphertext with a single flipped bit in either the ciphertext
+# or the tag, which must fail to open.
+
+import json
+import os
+
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "aes256gcm_generated.json")
+
+MESSAGE_LENS = [0, 1, 15, 16, 17, 31, 32, 33, 64, 255, 1000]
+AD_LENS = [0, 1, 16, 17, 40]
+
+
+def pattern(n, seed):
+    return bytes((seed + 7 * i) % 256 for i in range(n))
+
+
+def main():
+    cases = []
+    idx = 0
+    for msg_len in MESSAGE_LENS:
+        for ad_len in AD_LENS:
+            key = pattern(32, idx)
+            nonce = pattern(12, 100 + idx)
+            message = pattern(msg_len, 200 + idx)
+            ad = pattern(ad_len, 50 + idx)
+            sealed = AESGCM(key).encrypt(nonce, message, ad)
+            assert AESGCM(key).decrypt(nonce, sealed, ad) == message
+
+            flip = (idx * 13) % len(sealed)
+            modified = bytearray(sealed)
+            modified[flip] ^= 1 << (idx % 8)
+
+            cases.append({
+                "key": key.hex(),
+                "nonce": nonce.hex(),
+                "ad": ad.hex(),
+                "message": message.hex(),
+                "sealed": sealed.hex(),
+                "modified": bytes(modified).hex(),
+            })
+            idx += 1
+
+    with open(OUT, "w") as f:
+        json.dump({"aes256gcm": cases}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
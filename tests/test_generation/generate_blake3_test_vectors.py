@@ -0,0 +1,162 @@
+# Generates tests/test_data/blake3_generated.json.
+#
+# This is an independent implementation of BLAKE3, written from the
+# specification. Unlike orion, which hashes incrementally with a stack of
+# chaining values, it builds the tree recursively, splitting the input so that
+# the left subtree holds the largest power of two number of chunks. The
+# inputs, key and context string, as well as the output format, are those of
+# the official BLAKE3 test vectors.
+#
+# It reproduces the well-known digests of the empty input and "abc":
+#   af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262
+#   6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85
+
+import json
+import os
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "blake3_generated.json")
+
+KEY = b"whats the Elvish word for friend"
+CONTEXT = "BLAKE3 2019-12-27 16:29:52 test vectors context"
+OUTPUT_LEN = 131
+INPUT_LENS = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 63, 64, 65, 127, 128, 129, 1023, 1024, 1025,
+    2048, 2049, 3072, 3073, 4096, 4097, 5120, 5121, 6144, 6145, 7168, 7169,
+    8192, 8193, 16384, 31744, 102400,
+]
+
+IV = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A,
+    0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+]
+PERMUTATION = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8]
+BLOCK_LEN = 64
+CHUNK_LEN = 1024
+
+CHUNK_START = 1 << 0
+CHUNK_END = 1 << 1
+PARENT = 1 << 2
+ROOT = 1 << 3
+KEYED_HASH = 1 << 4
+DERIVE_KEY_CONTEXT = 1 << 5
+DERIVE_KEY_MATERIAL = 1 << 6
+
+MASK = 0xFFFFFFFF
+
+
+def rotr(x, n):
+    return ((x >> n) | (x << (32 - n))) & MASK
+
+
+def g(s, a, b, c, d, x, y):
+    s[a] = (s[a] + s[b] + x) & MASK
+    s[d] = rotr(s[d] ^ s[a], 16)
+    s[c] = (s[c] + s[d]) & MASK
+    s[b] = rotr(s[b] ^ s[c], 12)
+    s[a] = (s[a] + s[b] + y) & MASK
+    s[d] = rotr(s[d] ^ s[a], 8)
+    s[c] = (s[c] + s[d]) & MASK
+    s[b] = rotr(s[b] ^ s[c], 7)
+
+
+def words(data):
+    return [int.from_bytes(data[i : i + 4], "little") for i in range(0, len(data), 4)]
+
+
+def to_bytes(ws):
+    return b"".join(w.to_bytes(4, "little") for w in ws)
+
+
+def compress(cv, block, counter, block_len, flags):
+    m = words(block)
+    s = list(cv) + IV[:4] + [counter & MASK, counter >> 32, block_len, flags]
+    for _ in range(7):
+        g(s, 0, 4, 8, 12, m[0], m[1])
+        g(s, 1, 5, 9, 13, m[2], m[3])
+        g(s, 2, 6, 10, 14, m[4], m[5])
+        g(s, 3, 7, 11, 15, m[6], m[7])
+        g(s, 0, 5, 10, 15, m[8], m[9])
+        g(s, 1, 6, 11, 12, m[10], m[11])
+        g(s, 2, 7, 8, 13, m[12], m[13])
+        g(s, 3, 4, 9, 14, m[14], m[15])
+        m = [m[p] for p in PERMUTATION]
+    return [s[i] ^ s[i + 8] for i in range(8)] + [s[i + 8] ^ cv[i] for i in range(8)]
+
+
+# A node is the input to its final compression: (cv, block, counter,
+# block_len, flags). Its chaining value is the first 8 words of the output.
+def chunk_node(key, chunk, counter, flags):
+    blocks = [chunk[i : i + BLOCK_LEN] for i in range(0, len(chunk), BLOCK_LEN)] or [b""]
+    cv = key
+    for i, block in enumerate(blocks[:-1]):
+        start = CHUNK_START if i == 0 else 0
+        cv = compress(cv, block, counter, BLOCK_LEN, flags | start)[:8]
+    start = CHUNK_START if len(blocks) == 1 else 0
+    last = blocks[-1]
+    return (cv, last.ljust(BLOCK_LEN, b"\x00"), counter, len(last), flags | start | CHUNK_END)
+
+
+def chaining_value(node):
+    return compress(*node)[:8]
+
+
+def tree_node(key, data, chunk_counter, flags):
+    if len(data) <= CHUNK_LEN:
+        return chunk_node(key, data, chunk_counter, flags)
+    chunks = (len(data) + CHUNK_LEN - 1) // CHUNK_LEN
+    left_chunks = 1
+    while left_chunks * 2 < chunks:
+        left_chunks *= 2
+    split = left_chunks * CHUNK_LEN
+    left = chaining_value(tree_node(key, data[:split], chunk_counter, flags))
+    right = chaining_value(tree_node(key, data[split:], chunk_counter + left_chunks, flags))
+    return (key, to_bytes(left + right), 0, BLOCK_LEN, flags | PARENT)
+
+
+def root_output(node, length):
+    cv, block, _, block_len, flags = node
+    out = b""
+    counter = 0
+    while len(out) < length:
+        out += to_bytes(compress(cv, block, counter, block_len, flags | ROOT))
+        counter += 1
+    return out[:length]
+
+
+def blake3_hash(data, length=32):
+    return root_output(tree_node(IV, data, 0, 0), length)
+
+
+def keyed_hash(key, data, length=32):
+    return root_output(tree_node(words(key), data, 0, KEYED_HASH), length)
+
+
+def derive_key(context, material, length=32):
+    context_key = root_output(tree_node(IV, context.encode(), 0, DERIVE_KEY_CONTEXT), 32)
+    return root_output(tree_node(words(context_key), material, 0, DERIVE_KEY_MATERIAL), length)
+
+
+def main():
+    assert blake3_hash(b"").hex().startswith("af1349b9f5f9a1a6")
+    assert blake3_hash(b"abc").hex().startswith("6437b3ac38465133")
+
+    cases = []
+    for input_len in INPUT_LENS:
+        data = bytes(i % 251 for i in range(input_len))
+        cases.append(
+            {
+                "input_len": input_len,
+                "hash": blake3_hash(data, OUTPUT_LEN).hex(),
+                "keyed_hash": keyed_hash(KEY, data, OUTPUT_LEN).hex(),
+                "derive_key": derive_key(CONTEXT, data, OUTPUT_LEN).hex(),
+            }
+        )
+
+    vectors = {"key": KEY.decode(), "context_string": CONTEXT, "cases": cases}
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
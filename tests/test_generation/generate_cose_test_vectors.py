@@ -0,0 +1,159 @@
+# Generates tests/test_data/cose_generated.json.
+#
+# Requires the `cryptography` package. This is an independent implementation
+# of COSE_Sign1 and COSE_Encrypt0 (RFC 9052) and CWT (RFC 8392), with a
+# minimal CBOR encoder, using `cryptography` for Ed25519, deterministic
+# ECDSA (RFC 6979) and ChaCha20/Poly1305. As all of these are
+# deterministic, orion must produce the exact same messages.
+
+import json
+import os
+
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric import ec, ed25519, utils
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "cose_generated.json")
+
+ED25519_KEY = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"
+P256_KEY = "6c1382765aec5358f117733d281c1c7bdc39884d04a45a1e6c67c858bc206c19"
+CHACHA_KEY = "1c9240a5eb55d38af333888604f6b5f0473917c1402b80099dca5cbc207075c0"
+
+
+def head(major, argument):
+    if argument < 24:
+        return bytes([major << 5 | argument])
+    for info, size in ((24, 1), (25, 2), (26, 4), (27, 8)):
+        if argument < 1 << (8 * size):
+            return bytes([major << 5 | info]) + argument.to_bytes(size, "big")
+    raise ValueError
+
+
+class Tag:
+    def __init__(self, tag, value):
+        self.tag = tag
+        self.value = value
+
+
+def cbor(value):
+    if isinstance(value, Tag):
+        return head(6, value.tag) + cbor(value.value)
+    if isinstance(value, int):
+        return head(0, value) if value >= 0 else head(1, -1 - value)
+    if isinstance(value, bytes):
+        return head(2, len(value)) + value
+    if isinstance(value, str):
+        encoded = value.encode()
+        return head(3, len(encoded)) + encoded
+    if isinstance(value, list):
+        return head(4, len(value)) + b"".join(cbor(item) for item in value)
+    if isinstance(value, dict):
+        return head(5, len(value)) + b"".join(cbor(k) + cbor(v) for k, v in value.items())
+    raise TypeError
+
+
+def sign1(alg, payload, external_aad):
+    protected = cbor({1: alg})
+    to_be_signed = cbor(["Signature1", protected, external_aad, payload])
+    if alg == -8:
+        key = ed25519.Ed25519PrivateKey.from_private_bytes(bytes.fromhex(ED25519_KEY))
+        signature = key.sign(to_be_signed)
+    else:
+        key = ec.derive_private_key(int(P256_KEY, 16), ec.SECP256R1())
+        der = key.sign(to_be_signed, ec.ECDSA(hashes.SHA256(), deterministic_signing=True))
+        r, s = utils.decode_dss_signature(der)
+        signature = r.to_bytes(32, "big") + s.to_bytes(32, "big")
+    return cbor(Tag(18, [protected, {}, payload, signature]))
+
+
+def encrypt0(nonce, plaintext, external_aad):
+    protected = cbor({1: 24})
+    aad = cbor(["Encrypt0", protected, external_aad])
+    ciphertext = ChaCha20Poly1305(bytes.fromhex(CHACHA_KEY)).encrypt(nonce, plaintext, aad)
+    return cbor(Tag(16, [protected, {5: nonce}, ciphertext]))
+
+
+CLAIM_KEYS = {
+    "issuer": 1,
+    "subject": 2,
+    "audience": 3,
+    "expiration": 4,
+    "not_before": 5,
+    "issued_at": 6,
+    "cwt_id": 7,
+}
+
+
+def claims_cbor(claims):
+    encoded = {}
+    for name, key in CLAIM_KEYS.items():
+        value = claims.get(name)
+        if value is not None:
+            encoded[key] = bytes.fromhex(value) if name == "cwt_id" else value
+    return cbor(encoded)
+
+
+def main():
+    sign1_vectors = []
+    for alg in (-8, -7):
+        for payload, external_aad in ((b"", b""), (b"This is the content.", b"11aa22bb33cc4455")):
+            sign1_vectors.append(
+                {
+                    "alg": alg,
+                    "payload": payload.hex(),
+                    "external_aad": external_aad.hex(),
+                    "message": sign1(alg, payload, external_aad).hex(),
+                }
+            )
+
+    encrypt0_vectors = []
+    for i, (plaintext, external_aad) in enumerate(
+        ((b"", b""), (b"This is the content.", b"external"), (bytes(range(256)), b""))
+    ):
+        nonce = bytes([i] * 12)
+        encrypt0_vectors.append(
+            {
+                "nonce": nonce.hex(),
+                "plaintext": plaintext.hex(),
+                "external_aad": external_aad.hex(),
+                "message": encrypt0(nonce, plaintext, external_aad).hex(),
+            }
+        )
+
+    all_claims = {
+        "issuer": "coap://as.example.com",
+        "subject": "erikw",
+        "audience": "coap://light.example.com",
+        "expiration": 1444064944,
+        "not_before": 1443944944,
+        "issued_at": 1443944944,
+        "cwt_id": "0b71",
+    }
+    cwt_vectors = []
+    for claims in (all_claims, {"subject": "device-0042", "expiration": 2**32}, {}):
+        payload = claims_cbor(claims)
+        cwt_vectors.append(
+            {
+                "claims": claims,
+                "signed_eddsa": sign1(-8, payload, b"").hex(),
+                "signed_es256": sign1(-7, payload, b"").hex(),
+                "nonce": "99" * 12,
+                "encrypted": encrypt0(bytes([0x99] * 12), payload, b"").hex(),
+            }
+        )
+
+    vectors = {
+        "ed25519_key": ED25519_KEY,
+        "p256_key": P256_KEY,
+        "chacha20_poly1305_key": CHACHA_KEY,
+        "sign1": sign1_vectors,
+        "encrypt0": encrypt0_vectors,
+        "cwt": cwt_vectors,
+    }
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
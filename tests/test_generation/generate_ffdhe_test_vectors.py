@@ -0,0 +1,58 @@
+# Generates tests/test_data/ffdhe_generated.json.
+#
+# Requires the `cryptography` package. The key pairs are generated by
+# OpenSSL for the RFC 7919 named groups, and the shared secrets are
+# computed by `cryptography` and checked with the textbook formula.
+
+import json
+import os
+import subprocess
+
+from cryptography.hazmat.primitives import serialization
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "ffdhe_generated.json")
+SECRET_KEYSIZE = 64
+
+
+def generate_key(group):
+    pem = subprocess.run(
+        ["openssl", "genpkey", "-algorithm", "DH", "-pkeyopt", "group:" + group],
+        check=True,
+        capture_output=True,
+    ).stdout
+    return serialization.load_pem_private_key(pem, password=None)
+
+
+def main():
+    vectors = []
+    for bits in (2048, 3072, 4096):
+        size = bits // 8
+        for _ in range(2):
+            alice = generate_key("ffdhe%d" % bits)
+            bob = generate_key("ffdhe%d" % bits)
+            p = alice.parameters().parameter_numbers().p
+            x = alice.private_numbers().x
+            y_bob = bob.public_key().public_numbers().y
+
+            shared = alice.exchange(bob.public_key())
+            assert shared == pow(y_bob, x, p).to_bytes(size, "big")
+            vectors.append(
+                {
+                    "group": "ffdhe%d" % bits,
+                    "secret_key": x.to_bytes(SECRET_KEYSIZE, "big").hex(),
+                    "public_key": alice.public_key()
+                    .public_numbers()
+                    .y.to_bytes(size, "big")
+                    .hex(),
+                    "peer_public_key": y_bob.to_bytes(size, "big").hex(),
+                    "shared_secret": shared.hex(),
+                }
+            )
+
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
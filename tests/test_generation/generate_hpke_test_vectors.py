@@ -0,0 +1,70 @@
+# Generates tests/test_data/hpke_generated.json.
+#
+# Requires the `cryptography` package, version 47 or later, whose HPKE uses
+# OpenSSL. Every vector is a single-shot base mode encryption with
+# DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and ChaCha20Poly1305, with a random
+# ephemeral key, which is the first message of a context. The output is the
+# encapsulated key followed by the ciphertext and tag. The plaintext lengths are
+# chosen around the ChaCha20 block size.
+
+import json
+import os
+
+from cryptography.hazmat.primitives.asymmetric import x25519
+from cryptography.hazmat.primitives.serialization import (
+    Encoding,
+    NoEncryption,
+    PrivateFormat,
+    PublicFormat,
+)
+from cryptography.hazmat.primitives import hpke
+from cryptography.hazmat.bindings._rust import openssl as rust_openssl
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "hpke_generated.json")
+
+PLAINTEXT_LENS = [0, 1, 63, 64, 65, 200]
+INFOS = [b"", b"orion hpke test"]
+AADS = [b"", b"associated data"]
+
+SUITE = hpke.Suite(hpke.KEM.X25519, hpke.KDF.HKDF_SHA256, hpke.AEAD.CHACHA20_POLY1305)
+
+
+def plaintext_bytes(n):
+    return bytes(i % 251 for i in range(n))
+
+
+def main():
+    cases = []
+    idx = 0
+    for info in INFOS:
+        for aad in AADS:
+            for n in PLAINTEXT_LENS:
+                private_key = x25519.X25519PrivateKey.from_private_bytes(bytes([idx + 1]) * 32)
+                public_key = private_key.public_key()
+                plaintext = plaintext_bytes(n)
+                output = rust_openssl.hpke._encrypt_with_aad(
+                    SUITE, plaintext, public_key, info=info, aad=aad
+                )
+                assert rust_openssl.hpke._decrypt_with_aad(
+                    SUITE, output, private_key, info=info, aad=aad
+                ) == plaintext
+
+                cases.append({
+                    "private_key_r": private_key.private_bytes(
+                        Encoding.Raw, PrivateFormat.Raw, NoEncryption()
+                    ).hex(),
+                    "public_key_r": public_key.public_bytes(Encoding.Raw, PublicFormat.Raw).hex(),
+                    "info": info.hex(),
+                    "aad": aad.hex(),
+                    "plaintext_len": n,
+                    "output": output.hex(),
+                })
+                idx += 1
+
+    with open(OUT, "w") as f:
+        json.dump({"hpke": cases}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,68 @@
+# Generates tests/test_data/kmac_generated.json.
+#
+# Requires the `openssl` command line tool, version 3.0 or later, which
+# implements KMAC128 and KMAC256. The input lengths are chosen around the rates
+# of cSHAKE128 and cSHAKE256 (168 and 136 bytes), the key lengths around the
+# rates to exercise the padding of the encoded key, and the output sizes around
+# the rates to exercise squeezing more than one block.
+
+import json
+import os
+import subprocess
+import tempfile
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "kmac_generated.json")
+
+INPUT_LENS = [0, 1, 135, 136, 137, 167, 168, 169, 500]
+KEY_LENS = [16, 32, 64]
+OUTPUT_SIZES = [1, 32, 64, 137, 169, 400]
+CUSTOMIZATIONS = [b"", b"orion kmac test"]
+
+
+def input_bytes(n):
+    return bytes(i % 251 for i in range(n))
+
+
+def key_bytes(n):
+    return bytes((7 * i + 3) % 256 for i in range(n))
+
+
+def kmac(name, key, customization, size, data):
+    with tempfile.NamedTemporaryFile() as f:
+        f.write(data)
+        f.flush()
+        args = [
+            "openssl", "mac",
+            "-macopt", "hexkey:" + key.hex(),
+            "-macopt", "size:%d" % size,
+        ]
+        if customization:
+            args += ["-macopt", "hexcustom:" + customization.hex()]
+        args += ["-in", f.name, name]
+        out = subprocess.run(args, check=True, capture_output=True).stdout
+    return out.decode().strip().lower()
+
+
+def main():
+    cases = []
+    for customization in CUSTOMIZATIONS:
+        for key_len in KEY_LENS:
+            for input_len in INPUT_LENS:
+                size = OUTPUT_SIZES[(key_len + input_len) % len(OUTPUT_SIZES)]
+                key = key_bytes(key_len)
+                data = input_bytes(input_len)
+                cases.append({
+                    "key_len": key_len,
+                    "input_len": input_len,
+                    "customization": customization.hex(),
+                    "kmac128": kmac("KMAC128", key, customization, size, data),
+                    "kmac256": kmac("KMAC256", key, customization, size, data),
+                })
+
+    with open(OUT, "w") as f:
+        json.dump({"kmac": cases}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
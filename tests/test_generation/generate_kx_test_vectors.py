@@ -0,0 +1,59 @@
+# Generates tests/test_data/kx_generated.json.
+#
+# Requires libsodium 1.0.18 or later, loaded with ctypes. The key pairs are
+# derived with crypto_kx_seed_keypair() and the session keys computed with
+# crypto_kx_client_session_keys() and crypto_kx_server_session_keys(), which
+# are checked to match each other.
+
+import ctypes
+import json
+import os
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "kx_generated.json")
+
+CASES = 16
+
+sodium = ctypes.CDLL("libsodium.so.23")
+assert sodium.sodium_init() >= 0
+
+
+def buf(n):
+    return ctypes.create_string_buffer(n)
+
+
+def keypair_from_seed(seed):
+    pk, sk = buf(32), buf(32)
+    assert sodium.crypto_kx_seed_keypair(pk, sk, seed) == 0
+    return pk.raw, sk.raw
+
+
+def main():
+    cases = []
+    for idx in range(CASES):
+        client_pk, client_sk = keypair_from_seed(bytes([0x40 + idx]) * 32)
+        server_pk, server_sk = keypair_from_seed(bytes([0x80 + idx]) * 32)
+
+        client_rx, client_tx = buf(32), buf(32)
+        assert sodium.crypto_kx_client_session_keys(
+            client_rx, client_tx, client_pk, client_sk, server_pk) == 0
+        server_rx, server_tx = buf(32), buf(32)
+        assert sodium.crypto_kx_server_session_keys(
+            server_rx, server_tx, server_pk, server_sk, client_pk) == 0
+        assert client_rx.raw == server_tx.raw and client_tx.raw == server_rx.raw
+
+        cases.append({
+            "client_private_key": client_sk.hex(),
+            "client_public_key": client_pk.hex(),
+            "server_private_key": server_sk.hex(),
+            "server_public_key": server_pk.hex(),
+            "client_rx": client_rx.raw.hex(),
+            "client_tx": client_tx.raw.hex(),
+        })
+
+    with open(OUT, "w") as f:
+        json.dump({"kx": cases}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
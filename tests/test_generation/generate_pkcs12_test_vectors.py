@@ -0,0 +1,119 @@
+# Generates tests/test_data/pkcs12_generated.json.
+#
+# Requires the `openssl` command line tool of OpenSSL 3. The archives are made
+# with `openssl pkcs12 -export` from Ed25519, P-256 and RSA keys with
+# self-signed or CA-issued certificates, and each valid one is checked to
+# parse with `openssl pkcs12 -info`. Archives marked as legacy use the RC2 and
+# Triple DES schemes of PKCS #12, and are only expected to parse with the
+# `legacy` feature. Archives marked as invalid are valid PKCS #12 archives
+# that orion rejects, such as those without a MAC.
+
+import json
+import os
+import subprocess
+import tempfile
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "pkcs12_generated.json")
+RSA_KEY = os.path.join(os.path.dirname(__file__), "..", "test_data", "rsa", "rsa2048_e65537.pk8.der")
+
+PASSWORD = "correct horse battery staple"
+
+
+def openssl(*args):
+    return subprocess.run(["openssl"] + list(args), check=True, capture_output=True).stdout
+
+
+def read_file(path):
+    with open(path, "rb") as f:
+        return f.read()
+
+
+def main():
+    tmp = tempfile.mkdtemp()
+    path = lambda name: os.path.join(tmp, name)
+
+    openssl("genpkey", "-algorithm", "ed25519", "-out", path("ed25519.pem"))
+    openssl("genpkey", "-algorithm", "EC", "-pkeyopt", "ec_paramgen_curve:P-256", "-out", path("p256.pem"))
+    openssl("pkey", "-inform", "DER", "-in", RSA_KEY, "-out", path("rsa.pem"))
+
+    for key in ["ed25519", "p256"]:
+        openssl("req", "-x509", "-key", path(key + ".pem"), "-subj", "/CN=" + key, "-days", "365",
+                "-out", path(key + ".crt"))
+    # An RSA identity issued by the P-256 key as a CA.
+    openssl("req", "-new", "-key", path("rsa.pem"), "-subj", "/CN=rsa", "-out", path("rsa.csr"))
+    openssl("x509", "-req", "-in", path("rsa.csr"), "-CA", path("p256.crt"), "-CAkey", path("p256.pem"),
+            "-days", "365", "-out", path("rsa.crt"))
+
+    keys = {}
+    certificates = {}
+    for key in ["ed25519", "p256", "rsa"]:
+        keys[key] = {
+            "type": key,
+            "pkcs8": openssl("pkcs8", "-topk8", "-nocrypt", "-in", path(key + ".pem"), "-outform", "DER").hex(),
+            "spki": openssl("pkey", "-in", path(key + ".pem"), "-pubout", "-outform", "DER").hex(),
+        }
+        certificates[key] = openssl("x509", "-in", path(key + ".crt"), "-outform", "DER").hex()
+
+    # Name, key, certificates, password, extra arguments, legacy and valid.
+    cases = [
+        ("ed25519_default", "ed25519", ["ed25519"], PASSWORD, [], False, True),
+        ("p256_default", "p256", ["p256"], PASSWORD, [], False, True),
+        ("rsa_chain", "rsa", ["rsa", "p256"], PASSWORD, [], False, True),
+        ("ed25519_sha1_mac", "ed25519", ["ed25519"], PASSWORD, ["-macalg", "sha1"], False, True),
+        ("p256_sha384_mac", "p256", ["p256"], PASSWORD, ["-macalg", "sha384"], False, True),
+        ("p256_sha512_mac_aes128_aes192", "p256", ["p256"], PASSWORD,
+         ["-macalg", "sha512", "-keypbe", "AES-128-CBC", "-certpbe", "AES-192-CBC"], False, True),
+        ("ed25519_no_encryption", "ed25519", ["ed25519"], PASSWORD, ["-keypbe", "NONE", "-certpbe", "NONE"],
+         False, True),
+        ("ed25519_no_iterations", "ed25519", ["ed25519"], PASSWORD, ["-noiter", "-nomaciter"], False, True),
+        ("ed25519_empty_password", "ed25519", ["ed25519"], "", [], False, True),
+        ("p256_unicode_password", "p256", ["p256"], "pässwörd ✓", [], False, True),
+        ("ed25519_key_only", "ed25519", [], PASSWORD, ["-nocerts"], False, True),
+        ("p256_certificate_only", None, ["p256"], PASSWORD, ["-nokeys"], False, True),
+        ("rsa_legacy", "rsa", ["rsa", "p256"], PASSWORD, ["-legacy"], True, True),
+        ("ed25519_legacy_descert", "ed25519", ["ed25519"], PASSWORD, ["-legacy", "-descert"], True, True),
+        ("p256_legacy_rc2_128_2des", "p256", ["p256"], PASSWORD,
+         ["-legacy", "-keypbe", "PBE-SHA1-2DES", "-certpbe", "PBE-SHA1-RC2-128"], True, True),
+        ("ed25519_no_mac", "ed25519", ["ed25519"], PASSWORD, ["-nomac"], False, False),
+        ("ed25519_pbmac1", "ed25519", ["ed25519"], PASSWORD, ["-pbmac1_pbkdf2"], False, False),
+    ]
+
+    vectors = []
+    for name, key, certs, password, extra, legacy, valid in cases:
+        args = ["pkcs12", "-export", "-passout", "pass:" + password, "-out", path(name + ".p12")]
+        if key is not None:
+            args += ["-inkey", path(key + ".pem")]
+        if certs:
+            args += ["-in", path(certs[0] + ".crt")]
+            if len(certs) > 1:
+                with open(path(name + ".chain"), "wb") as f:
+                    for cert in certs[1:]:
+                        f.write(read_file(path(cert + ".crt")))
+                args += ["-certfile", path(name + ".chain")]
+        elif "-nocerts" not in extra:
+            raise ValueError(name)
+        openssl(*(args + extra))
+
+        if valid:
+            info_args = ["pkcs12", "-in", path(name + ".p12"), "-info", "-passin", "pass:" + password, "-nodes"]
+            if legacy:
+                info_args.append("-legacy")
+            openssl(*info_args)
+
+        vectors.append({
+            "name": name,
+            "password": password,
+            "pfx": read_file(path(name + ".p12")).hex(),
+            "legacy": legacy,
+            "valid": valid,
+            "private_keys": [keys[key]] if key is not None else [],
+            "certificates": [certificates[cert] for cert in certs],
+        })
+
+    with open(OUT, "w") as f:
+        json.dump({"pkcs12": vectors}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,134 @@
+# Generates tests/test_data/pkcs8_generated.json.
+#
+# Requires the `openssl` command line tool of OpenSSL 3 and the Python
+# `cryptography` package. The EncryptedPrivateKeyInfo structures with AES-CBC
+# are made with `openssl pkcs8 -topk8 -v2`, with each supported PRF and key
+# size. OpenSSL does not support AES-GCM in PBES2, so those are built by this
+# script as specified in RFC 5084, with PBKDF2 and AES-GCM from `cryptography`.
+# Vectors marked as invalid are valid EncryptedPrivateKeyInfo structures that
+# orion rejects, such as those that use scrypt or PBES1.
+
+import json
+import os
+import subprocess
+import tempfile
+
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+from cryptography.hazmat.primitives.kdf.pbkdf2 import PBKDF2HMAC
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "pkcs8_generated.json")
+RSA_KEY = os.path.join(os.path.dirname(__file__), "..", "test_data", "rsa", "rsa2048_e65537.pk8.der")
+
+PASSWORD = "correct horse battery staple"
+
+OID_PBES2 = "2a864886f70d01050d"
+OID_PBKDF2 = "2a864886f70d01050c"
+OID_HMAC = {"sha1": "2a864886f70d0207", "sha256": "2a864886f70d0209", "sha512": "2a864886f70d020b"}
+OID_AES_GCM = {16: "608648016503040106", 24: "60864801650304011a", 32: "60864801650304012e"}
+HASHES = {"sha1": hashes.SHA1(), "sha256": hashes.SHA256(), "sha512": hashes.SHA512()}
+
+
+def openssl(*args):
+    return subprocess.run(["openssl"] + list(args), check=True, capture_output=True).stdout
+
+
+def tlv(tag, value):
+    if len(value) < 0x80:
+        length = bytes([len(value)])
+    elif len(value) < 0x100:
+        length = bytes([0x81, len(value)])
+    else:
+        length = bytes([0x82, len(value) >> 8, len(value) & 0xFF])
+    return bytes([tag]) + length + value
+
+
+def integer(value):
+    return tlv(0x02, value.to_bytes(value.bit_length() // 8 + 1, "big"))
+
+
+def gcm_encrypt(private_key_info, password, prf, iterations, key_size, nonce, salt):
+    key = PBKDF2HMAC(HASHES[prf], key_size, salt, iterations).derive(password.encode())
+    ciphertext = AESGCM(key).encrypt(nonce, private_key_info, None)
+
+    prf_algorithm = tlv(0x30, tlv(0x06, bytes.fromhex(OID_HMAC[prf])) + tlv(0x05, b""))
+    kdf = tlv(0x30, tlv(0x06, bytes.fromhex(OID_PBKDF2))
+              + tlv(0x30, tlv(0x04, salt) + integer(iterations) + prf_algorithm))
+    gcm_params = tlv(0x30, tlv(0x04, nonce) + integer(16))
+    encryption = tlv(0x30, tlv(0x06, bytes.fromhex(OID_AES_GCM[key_size])) + gcm_params)
+    algorithm = tlv(0x30, tlv(0x06, bytes.fromhex(OID_PBES2)) + tlv(0x30, kdf + encryption))
+
+    return tlv(0x30, algorithm + tlv(0x04, ciphertext))
+
+
+def main():
+    tmp = tempfile.mkdtemp()
+    path = lambda name: os.path.join(tmp, name)
+
+    openssl("genpkey", "-algorithm", "ed25519", "-out", path("ed25519.pem"))
+    openssl("genpkey", "-algorithm", "EC", "-pkeyopt", "ec_paramgen_curve:P-256", "-out", path("p256.pem"))
+    openssl("pkey", "-inform", "DER", "-in", RSA_KEY, "-out", path("rsa.pem"))
+    openssl("genpkey", "-algorithm", "x25519", "-out", path("x25519.pem"))
+
+    keys = {}
+    for key in ["ed25519", "p256", "rsa", "x25519"]:
+        keys[key] = openssl("pkcs8", "-topk8", "-nocrypt", "-in", path(key + ".pem"), "-outform", "DER")
+
+    # Name, key, password, `openssl pkcs8` arguments and valid.
+    cases = [
+        ("ed25519_default", "ed25519", PASSWORD, [], True),
+        ("p256_aes128_sha1", "p256", PASSWORD, ["-v2", "aes-128-cbc", "-v2prf", "hmacWithSHA1"], True),
+        ("rsa_aes192_sha384", "rsa", PASSWORD, ["-v2", "aes-192-cbc", "-v2prf", "hmacWithSHA384"], True),
+        ("ed25519_aes256_sha512", "ed25519", PASSWORD, ["-v2", "aes-256-cbc", "-v2prf", "hmacWithSHA512"], True),
+        ("p256_one_iteration", "p256", PASSWORD, ["-iter", "1"], True),
+        ("x25519_default", "x25519", PASSWORD, [], True),
+        ("ed25519_empty_password", "ed25519", "", [], True),
+        ("p256_unicode_password", "p256", "pässwörd ✓", [], True),
+        ("ed25519_scrypt", "ed25519", PASSWORD, ["-scrypt"], False),
+        ("ed25519_pbes1_des", "ed25519", PASSWORD, ["-v1", "PBE-SHA1-3DES"], False),
+    ]
+
+    vectors = []
+    for name, key, password, extra, valid in cases:
+        encrypted = openssl("pkcs8", "-topk8", "-in", path(key + ".pem"), "-passout", "pass:" + password,
+                            "-outform", "DER", *extra)
+        with open(path(name + ".der"), "wb") as f:
+            f.write(encrypted)
+        if valid:
+            decrypted = openssl("pkcs8", "-topk8", "-nocrypt", "-inform", "DER", "-in", path(name + ".der"),
+                                "-passin", "pass:" + password, "-outform", "DER")
+            assert decrypted == keys[key], name
+        vectors.append({
+            "name": name,
+            "key": key,
+            "password": password,
+            "encrypted": encrypted.hex(),
+            "private_key_info": keys[key].hex(),
+            "valid": valid,
+        })
+
+    # Name, key, PRF, iterations and AES key size.
+    gcm_cases = [
+        ("ed25519_aes256_gcm", "ed25519", "sha256", 2048, 32),
+        ("p256_aes128_gcm_sha1", "p256", "sha1", 1000, 16),
+        ("rsa_aes192_gcm_sha512", "rsa", "sha512", 1, 24),
+    ]
+    for idx, (name, key, prf, iterations, key_size) in enumerate(gcm_cases):
+        encrypted = gcm_encrypt(keys[key], PASSWORD, prf, iterations, key_size,
+                                bytes([idx]) * 12, bytes([0x80 + idx]) * 16)
+        vectors.append({
+            "name": name,
+            "key": key,
+            "password": PASSWORD,
+            "encrypted": encrypted.hex(),
+            "private_key_info": keys[key].hex(),
+            "valid": True,
+        })
+
+    with open(OUT, "w") as f:
+        json.dump({"pkcs8": vectors}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,338 @@
+# Generates tests/test_data/privacy_pass_generated.json.
+#
+# Requires the `cryptography` package. This is an independent implementation
+# of the Privacy Pass issuance protocols of RFC 9578: hash-to-curve for P-384
+# (RFC 9380), the P384-SHA384 VOPRF (RFC 9497) and RSABSSA-SHA384-PSS-
+# Deterministic blind signatures (RFC 9474) are written in plain Python, and
+# the final RSA signatures are checked with `cryptography`.
+#
+# The blinding factor of the blind signatures is derived from a 32-byte seed
+# as MGF1-SHA384(seed, len(n) + 16) mod n, which is how orion derives it.
+
+import hashlib
+import json
+import os
+
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import padding
+
+DIR = os.path.dirname(__file__)
+OUT = os.path.join(DIR, "..", "test_data", "privacy_pass_generated.json")
+RSA_KEY = os.path.join(DIR, "..", "test_data", "rsa", "rsa2048_e65537.pk8.der")
+
+# P-384 and hash-to-curve.
+
+p = 2**384 - 2**128 - 2**96 + 2**32 - 1
+n = int("ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973", 16)
+b = int("b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef", 16)
+A = p - 3
+G = (
+    0xAA87CA22BE8B05378EB1C71EF320AD746E1D3B628BA79B9859F741E082542A385502F25DBF55296C3A545E3872760AB7,
+    0x3617DE4A96262C6F5D9E98BF9292DC29F8F41DBD289A147CE9DA3113B5F0B8C00A60B1CE1D7E819D7A431D7C90EA0E5F,
+)
+
+
+def xmd(msg, dst, length):
+    H = hashlib.sha384
+    ell = -(-length // 48)
+    dst_prime = dst + bytes([len(dst)])
+    b0 = H(bytes(128) + msg + length.to_bytes(2, "big") + b"\0" + dst_prime).digest()
+    blocks = [H(b0 + b"\1" + dst_prime).digest()]
+    for i in range(2, ell + 1):
+        blocks.append(H(bytes(x ^ y for x, y in zip(b0, blocks[-1])) + bytes([i]) + dst_prime).digest())
+    return b"".join(blocks)[:length]
+
+
+def sqrt(x):
+    return pow(x, (p + 1) // 4, p)
+
+
+def sswu(u):
+    Z = p - 12
+    tv1 = pow((Z * Z * pow(u, 4, p) + Z * u * u) % p, p - 2, p)
+    x1 = (-b * pow(A, p - 2, p)) * (1 + tv1) % p
+    if tv1 == 0:
+        x1 = b * pow(Z * A, p - 2, p) % p
+    gx1 = (x1**3 + A * x1 + b) % p
+    x2 = Z * u * u * x1 % p
+    gx2 = (x2**3 + A * x2 + b) % p
+    if pow(gx1, (p - 1) // 2, p) in (0, 1):
+        x, y = x1, sqrt(gx1)
+    else:
+        x, y = x2, sqrt(gx2)
+    if u % 2 != y % 2:
+        y = (-y) % p
+    return (x, y)
+
+
+def add(P, Q):
+    if P is None:
+        return Q
+    if Q is None:
+        return P
+    if P[0] == Q[0]:
+        if (P[1] + Q[1]) % p == 0:
+            return None
+        slope = (3 * P[0] * P[0] + A) * pow(2 * P[1], p - 2, p) % p
+    else:
+        slope = (Q[1] - P[1]) * pow(Q[0] - P[0], p - 2, p) % p
+    x = (slope * slope - P[0] - Q[0]) % p
+    return (x, (slope * (P[0] - x) - P[1]) % p)
+
+
+def mul(k, P):
+    R = None
+    for bit in bin(k % n)[2:] if k % n else "":
+        R = add(R, R)
+        if bit == "1":
+            R = add(R, P)
+    return R
+
+
+def hash_to_curve(msg, dst):
+    u = xmd(msg, dst, 144)
+    u0 = int.from_bytes(u[:72], "big") % p
+    u1 = int.from_bytes(u[72:], "big") % p
+    return add(sswu(u0), sswu(u1))
+
+
+def hash_to_scalar(msg, dst):
+    return int.from_bytes(xmd(msg, dst, 72), "big") % n
+
+
+def ser(P):
+    return bytes([2 | (P[1] & 1)]) + P[0].to_bytes(48, "big")
+
+
+# The VOPRF.
+
+CONTEXT = b"OPRFV1-\x01-P384-SHA384"
+
+
+def i2(x):
+    return x.to_bytes(2, "big")
+
+
+def derive_key_pair(seed, info):
+    data = seed + i2(len(info)) + info
+    for counter in range(256):
+        k = hash_to_scalar(data + bytes([counter]), b"DeriveKeyPair-" + CONTEXT)
+        if k:
+            return k
+
+
+def composites(k, B, C, D):
+    Bm = ser(B)
+    seed_dst = b"Seed-" + CONTEXT
+    seed = hashlib.sha384(i2(len(Bm)) + Bm + i2(len(seed_dst)) + seed_dst).digest()
+    Ci = ser(C)
+    Di = ser(D)
+    di = hash_to_scalar(
+        i2(len(seed)) + seed + i2(0) + i2(len(Ci)) + Ci + i2(len(Di)) + Di + b"Composite",
+        b"HashToScalar-" + CONTEXT,
+    )
+    M = mul(di, C)
+    Z = mul(k, M) if k is not None else mul(di, D)
+    return M, Z
+
+
+def challenge(B, M, Z, t2, t3):
+    transcript = b""
+    for P in (B, M, Z, t2, t3):
+        e = ser(P)
+        transcript += i2(len(e)) + e
+    return hash_to_scalar(transcript + b"Challenge", b"HashToScalar-" + CONTEXT)
+
+
+def blind_evaluate(k, blinded, r):
+    pk = mul(k, G)
+    Z = mul(k, blinded)
+    M, Zc = composites(k, pk, blinded, Z)
+    c = challenge(pk, M, Zc, mul(r, G), mul(r, M))
+    s = (r - c * k) % n
+    return Z, c.to_bytes(48, "big") + s.to_bytes(48, "big")
+
+
+def oprf_finalize(inp, blind, evaluated, blinded, pk, proof):
+    c = int.from_bytes(proof[:48], "big")
+    s = int.from_bytes(proof[48:], "big")
+    M, Z = composites(None, pk, blinded, evaluated)
+    t2 = add(mul(s, G), mul(c, pk))
+    t3 = add(mul(s, M), mul(c, Z))
+    assert challenge(pk, M, Z, t2, t3) == c
+    u = ser(mul(pow(blind, -1, n), evaluated))
+    return hashlib.sha384(i2(len(inp)) + inp + i2(len(u)) + u + b"Finalize").digest()
+
+
+def oprf_evaluate(k, inp):
+    u = ser(mul(k, hash_to_curve(inp, b"HashToGroup-" + CONTEXT)))
+    return hashlib.sha384(i2(len(inp)) + inp + i2(len(u)) + u + b"Finalize").digest()
+
+
+# RSA blind signatures.
+
+
+def mgf1(seed, length):
+    out = b""
+    counter = 0
+    while len(out) < length:
+        out += hashlib.sha384(seed + counter.to_bytes(4, "big")).digest()
+        counter += 1
+    return out[:length]
+
+
+def emsa_pss_encode(msg, salt, em_bits):
+    em_len = (em_bits + 7) // 8
+    m_hash = hashlib.sha384(msg).digest()
+    h = hashlib.sha384(bytes(8) + m_hash + salt).digest()
+    db = bytes(em_len - len(salt) - 48 - 2) + b"\x01" + salt
+    masked = bytes(x ^ y for x, y in zip(db, mgf1(h, len(db))))
+    masked = bytes([masked[0] & (0xFF >> (8 * em_len - em_bits))]) + masked[1:]
+    return masked + h + b"\xbc"
+
+
+def rsa_pss_spki(n_rsa, e):
+    def tlv(tag, value):
+        if len(value) < 0x80:
+            length = bytes([len(value)])
+        elif len(value) < 0x100:
+            length = bytes([0x81, len(value)])
+        else:
+            length = bytes([0x82]) + len(value).to_bytes(2, "big")
+        return bytes([tag]) + length + value
+
+    def integer(x):
+        value = x.to_bytes((x.bit_length() + 8) // 8, "big")
+        return tlv(0x02, value)
+
+    sha384 = tlv(0x30, tlv(0x06, bytes.fromhex("608648016503040202")))
+    params = tlv(
+        0x30,
+        tlv(0xA0, sha384)
+        + tlv(0xA1, tlv(0x30, tlv(0x06, bytes.fromhex("2a864886f70d010108")) + sha384))
+        + tlv(0xA2, tlv(0x02, b"\x30")),
+    )
+    algorithm = tlv(0x30, tlv(0x06, bytes.fromhex("2a864886f70d01010a")) + params)
+    rsa_public_key = tlv(0x30, integer(n_rsa) + integer(e))
+    return tlv(0x30, algorithm + tlv(0x03, b"\x00" + rsa_public_key))
+
+
+# Privacy Pass.
+
+
+def token_challenge(token_type, issuer_name, redemption_context, origin_info):
+    return (
+        i2(token_type)
+        + i2(len(issuer_name))
+        + issuer_name
+        + bytes([len(redemption_context)])
+        + redemption_context
+        + i2(len(origin_info))
+        + origin_info
+    )
+
+
+def private_token_vector(seed, info, challenge_fields, nonce, blind, proof_nonce):
+    k = derive_key_pair(seed, info)
+    pk = mul(k, G)
+    token_key_id = hashlib.sha256(ser(pk)).digest()
+    challenge_bytes = token_challenge(1, *challenge_fields)
+    token_input = i2(1) + nonce + hashlib.sha256(challenge_bytes).digest() + token_key_id
+
+    blinded = mul(blind, hash_to_curve(token_input, b"HashToGroup-" + CONTEXT))
+    request = i2(1) + token_key_id[-1:] + ser(blinded)
+    evaluated, proof = blind_evaluate(k, blinded, proof_nonce)
+    response = ser(evaluated) + proof
+    authenticator = oprf_finalize(token_input, blind, evaluated, blinded, pk, proof)
+    assert authenticator == oprf_evaluate(k, token_input)
+
+    return {
+        "seed": seed.hex(),
+        "info": info.hex(),
+        "public_key": ser(pk).hex(),
+        "token_key_id": token_key_id.hex(),
+        "challenge": challenge_bytes.hex(),
+        "nonce": nonce.hex(),
+        "blind": "%096x" % blind,
+        "proof_nonce": "%096x" % proof_nonce,
+        "token_request": request.hex(),
+        "token_response": response.hex(),
+        "token": (token_input + authenticator).hex(),
+    }
+
+
+def public_token_vector(key, challenge_fields, nonce, salt, blinding_seed):
+    numbers = key.private_numbers()
+    n_rsa = numbers.public_numbers.n
+    e = numbers.public_numbers.e
+    d = numbers.d
+    size = (n_rsa.bit_length() + 7) // 8
+    spki = rsa_pss_spki(n_rsa, e)
+    token_key_id = hashlib.sha256(spki).digest()
+    challenge_bytes = token_challenge(2, *challenge_fields)
+    token_input = i2(2) + nonce + hashlib.sha256(challenge_bytes).digest() + token_key_id
+
+    m = int.from_bytes(emsa_pss_encode(token_input, salt, n_rsa.bit_length() - 1), "big")
+    r = int.from_bytes(mgf1(blinding_seed, size + 16), "big") % n_rsa
+    blinded = m * pow(r, e, n_rsa) % n_rsa
+    blind_signature = pow(blinded, d, n_rsa)
+    signature = (blind_signature * pow(r, -1, n_rsa) % n_rsa).to_bytes(size, "big")
+    key.public_key().verify(
+        signature,
+        token_input,
+        padding.PSS(mgf=padding.MGF1(hashes.SHA384()), salt_length=48),
+        hashes.SHA384(),
+    )
+
+    return {
+        "public_key": spki.hex(),
+        "token_key_id": token_key_id.hex(),
+        "challenge": challenge_bytes.hex(),
+        "nonce": nonce.hex(),
+        "salt": salt.hex(),
+        "blinding_seed": blinding_seed.hex(),
+        "token_request": (i2(2) + token_key_id[-1:] + blinded.to_bytes(size, "big")).hex(),
+        "token_response": blind_signature.to_bytes(size, "big").hex(),
+        "token": (token_input + signature).hex(),
+    }
+
+
+def main():
+    with open(RSA_KEY, "rb") as f:
+        rsa_key = serialization.load_der_private_key(f.read(), password=None)
+
+    challenges = [
+        (b"issuer.example", b"", b""),
+        (b"issuer.example", bytes(range(32)), b"origin.example,other.example"),
+    ]
+    private_tokens = []
+    public_tokens = []
+    for i, fields in enumerate(challenges):
+        digest = lambda label: hashlib.sha512(label + bytes([i])).digest()
+        private_tokens.append(
+            private_token_vector(
+                digest(b"seed")[:32],
+                b"privacy pass test key",
+                fields,
+                digest(b"nonce")[:32],
+                int.from_bytes(digest(b"blind"), "big") % n,
+                int.from_bytes(digest(b"proof nonce"), "big") % n,
+            )
+        )
+        public_tokens.append(
+            public_token_vector(
+                rsa_key,
+                fields,
+                digest(b"nonce")[:32],
+                digest(b"salt")[:48],
+                digest(b"blinding seed")[:32],
+            )
+        )
+
+    with open(OUT, "w") as f:
+        json.dump({"private_tokens": private_tokens, "public_tokens": public_tokens}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,124 @@
+# Generates tests/test_data/rsa/rsa_blind_generated.json.
+#
+# Requires the `cryptography` package, and the keys written by
+# generate_rsa_test_vectors.py. This is an independent implementation of the
+# RSABSSA-SHA384 variants of RFC 9474, using the textbook formulas below. The
+# final signatures are checked with `cryptography`, which also produces the
+# signatures that are only verified.
+#
+# The blinding factor is derived from a 32-byte seed as
+# MGF1-SHA384(seed, len(n) + 16) mod n, which is how orion derives it.
+
+import hashlib
+import json
+import os
+
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import padding
+
+KEY_DIR = os.path.join(os.path.dirname(__file__), "..", "test_data", "rsa")
+OUT = os.path.join(KEY_DIR, "rsa_blind_generated.json")
+
+# (name, salt length, randomized)
+VARIANTS = [
+    ("Sha384PssRandomized", 48, True),
+    ("Sha384PssZeroRandomized", 0, True),
+    ("Sha384PssDeterministic", 48, False),
+    ("Sha384PssZeroDeterministic", 0, False),
+]
+
+
+def mgf1(seed, length):
+    out = b""
+    counter = 0
+    while len(out) < length:
+        out += hashlib.sha384(seed + counter.to_bytes(4, "big")).digest()
+        counter += 1
+    return out[:length]
+
+
+def pss_encode(message, salt, em_bits):
+    em_len = (em_bits + 7) // 8
+    m_hash = hashlib.sha384(message).digest()
+    h = hashlib.sha384(b"\x00" * 8 + m_hash + salt).digest()
+    db = b"\x00" * (em_len - len(salt) - 48 - 2) + b"\x01" + salt
+    masked_db = bytes(a ^ b for a, b in zip(db, mgf1(h, len(db))))
+    masked_db = bytes([masked_db[0] & (0xFF >> (8 * em_len - em_bits))]) + masked_db[1:]
+    return masked_db + h + b"\xbc"
+
+
+def pss_padding(salt_len):
+    return padding.PSS(mgf=padding.MGF1(hashes.SHA384()), salt_length=salt_len)
+
+
+def derived(label, length):
+    return hashlib.sha512(label).digest()[:length]
+
+
+def blind_vector(key, name, variant, message):
+    variant_name, salt_len, randomized = variant
+    numbers = key.private_numbers()
+    n = numbers.public_numbers.n
+    e = numbers.public_numbers.e
+    k = key.key_size // 8
+    label = (name + variant_name).encode() + message
+
+    randomizer = derived(b"randomizer" + label, 32) if randomized else b""
+    salt = derived(b"salt" + label, salt_len)
+    blinding_seed = derived(b"blinding seed" + label, 32)
+    prepared = randomizer + message
+
+    m = int.from_bytes(pss_encode(prepared, salt, key.key_size - 1), "big")
+    r = int.from_bytes(mgf1(blinding_seed, k + 16), "big") % n
+    blinded = m * pow(r, e, n) % n
+    blind_signature = pow(blinded, numbers.d, n)
+    signature = blind_signature * pow(r, -1, n) % n
+    assert signature == pow(m, numbers.d, n)
+    key.public_key().verify(
+        signature.to_bytes(k, "big"), prepared, pss_padding(salt_len), hashes.SHA384()
+    )
+
+    return {
+        "variant": variant_name,
+        "message": message.hex(),
+        "randomizer": randomizer.hex(),
+        "salt": salt.hex(),
+        "blinding_seed": blinding_seed.hex(),
+        "blinded_message": blinded.to_bytes(k, "big").hex(),
+        "blind_signature": blind_signature.to_bytes(k, "big").hex(),
+        "signature": signature.to_bytes(k, "big").hex(),
+    }
+
+
+def signature_vectors(key):
+    out = []
+    for variant_name, salt_len, _ in VARIANTS[:2]:
+        message = b"Message signed by cryptography"
+        out.append(
+            {
+                "variant": variant_name,
+                "message": message.hex(),
+                "signature": key.sign(message, pss_padding(salt_len), hashes.SHA384()).hex(),
+            }
+        )
+    return out
+
+
+def main():
+    vectors = []
+    for name in ["rsa2048_e65537", "rsa3072_e65537", "rsa4096_e65537", "rsa2048_e3"]:
+        with open(os.path.join(KEY_DIR, name + ".pk8.der"), "rb") as f:
+            key = serialization.load_der_private_key(f.read(), password=None)
+        blind = []
+        for variant in VARIANTS:
+            for message in [b"", b"Message to be blindly signed"]:
+                blind.append(blind_vector(key, name, variant, message))
+        vectors.append({"key": name, "blind": blind, "signatures": signature_vectors(key)})
+
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
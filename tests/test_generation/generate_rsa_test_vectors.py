@@ -0,0 +1,208 @@
+# Generates the RSA keys and test vectors in tests/test_data/rsa.
+#
+# Requires the `cryptography` package. Keys are only generated if they do not
+# exist yet. Deterministic signatures and ciphertexts are computed with the
+# textbook formulas below and checked against `cryptography` before being
+# written.
+
+import hashlib
+import json
+import os
+
+from cryptography.exceptions import InvalidSignature
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import padding, rsa
+
+OUT_DIR = os.path.join(os.path.dirname(__file__), "..", "test_data", "rsa")
+SALT_LEN = 32
+
+
+def mgf1(seed, length):
+    out = b""
+    counter = 0
+    while len(out) < length:
+        out += hashlib.sha256(seed + counter.to_bytes(4, "big")).digest()
+        counter += 1
+    return out[:length]
+
+
+def pss_encode(message, salt, k):
+    m_hash = hashlib.sha256(message).digest()
+    h = hashlib.sha256(b"\x00" * 8 + m_hash + salt).digest()
+    db = b"\x00" * (k - len(salt) - 32 - 2) + b"\x01" + salt
+    masked_db = bytes(a ^ b for a, b in zip(db, mgf1(h, k - 33)))
+    masked_db = bytes([masked_db[0] & 0x7F]) + masked_db[1:]
+    return masked_db + h + b"\xbc"
+
+
+def pss_sign(key, message, salt):
+    numbers = key.private_numbers()
+    k = key.key_size // 8
+    em = int.from_bytes(pss_encode(message, salt, k), "big")
+    return pow(em, numbers.d, numbers.public_numbers.n).to_bytes(k, "big")
+
+
+def pss_vectors(key):
+    public_key = key.public_key()
+    pss = padding.PSS(mgf=padding.MGF1(hashes.SHA256()), salt_length=SALT_LEN)
+    vectors = []
+    for i, message in enumerate([b"", b"abc", os.urandom(200)]):
+        salt = os.urandom(SALT_LEN) if i else b"\x00" * SALT_LEN
+        signature = pss_sign(key, message, salt)
+        public_key.verify(signature, message, pss, hashes.SHA256())
+        vectors.append(
+            {
+                "message": message.hex(),
+                "salt": salt.hex(),
+                "signature": signature.hex(),
+            }
+        )
+    return vectors
+
+
+def pss_random_signatures(key):
+    pss = padding.PSS(mgf=padding.MGF1(hashes.SHA256()), salt_length=SALT_LEN)
+    out = []
+    for message in [b"Message signed with a random salt", os.urandom(1000)]:
+        out.append(
+            {
+                "message": message.hex(),
+                "signature": key.sign(message, pss, hashes.SHA256()).hex(),
+            }
+        )
+    # A signature with a different salt length must be rejected.
+    other = padding.PSS(mgf=padding.MGF1(hashes.SHA256()), salt_length=20)
+    message = b"Message signed with a 20-byte salt"
+    out.append(
+        {
+            "message": message.hex(),
+            "signature": key.sign(message, other, hashes.SHA256()).hex(),
+            "invalid": True,
+        }
+    )
+    return out
+
+
+def oaep_encode(message, label, seed, k):
+    l_hash = hashlib.sha256(label).digest()
+    db = l_hash + b"\x00" * (k - len(message) - 66) + b"\x01" + message
+    masked_db = bytes(a ^ b for a, b in zip(db, mgf1(seed, k - 33)))
+    masked_seed = bytes(a ^ b for a, b in zip(seed, mgf1(masked_db, 32)))
+    return b"\x00" + masked_seed + masked_db
+
+
+def oaep_encrypt(key, message, label, seed):
+    numbers = key.public_key().public_numbers()
+    k = key.key_size // 8
+    em = int.from_bytes(oaep_encode(message, label, seed, k), "big")
+    return pow(em, numbers.e, numbers.n).to_bytes(k, "big")
+
+
+def oaep_padding(label):
+    return padding.OAEP(
+        mgf=padding.MGF1(hashes.SHA256()),
+        algorithm=hashes.SHA256(),
+        label=label or None,
+    )
+
+
+def oaep_vectors(key):
+    k = key.key_size // 8
+    vectors = []
+    for message, label in [
+        (b"", b""),
+        (b"abc", b""),
+        (os.urandom(k - 66), b""),
+        (os.urandom(32), b"label"),
+    ]:
+        seed = os.urandom(32)
+        ciphertext = oaep_encrypt(key, message, label, seed)
+        assert key.decrypt(ciphertext, oaep_padding(label)) == message
+        vectors.append(
+            {
+                "message": message.hex(),
+                "label": label.hex(),
+                "seed": seed.hex(),
+                "ciphertext": ciphertext.hex(),
+            }
+        )
+    return vectors
+
+
+def oaep_random_ciphertexts(key):
+    public_key = key.public_key()
+    out = []
+    for message, label in [(b"Wrapped key", b""), (os.urandom(32), b"label")]:
+        out.append(
+            {
+                "message": message.hex(),
+                "label": label.hex(),
+                "ciphertext": public_key.encrypt(message, oaep_padding(label)).hex(),
+            }
+        )
+    # A ciphertext using SHA-1 must be rejected.
+    sha1 = padding.OAEP(
+        mgf=padding.MGF1(hashes.SHA1()), algorithm=hashes.SHA1(), label=None
+    )
+    out.append(
+        {
+            "message": "",
+            "label": "",
+            "ciphertext": public_key.encrypt(b"SHA-1", sha1).hex(),
+            "invalid": True,
+        }
+    )
+    return out
+
+
+def load_or_generate_key(name, bits, e):
+    path = os.path.join(OUT_DIR, name + ".pk8.der")
+    if os.path.exists(path):
+        with open(path, "rb") as f:
+            return serialization.load_der_private_key(f.read(), password=None)
+    key = rsa.generate_private_key(public_exponent=e, key_size=bits)
+    write_key(key, name)
+    return key
+
+
+def write_key(key, name):
+    pkcs8 = key.private_bytes(
+        serialization.Encoding.DER,
+        serialization.PrivateFormat.PKCS8,
+        serialization.NoEncryption(),
+    )
+    spki = key.public_key().public_bytes(
+        serialization.Encoding.DER,
+        serialization.PublicFormat.SubjectPublicKeyInfo,
+    )
+    with open(os.path.join(OUT_DIR, name + ".pk8.der"), "wb") as f:
+        f.write(pkcs8)
+    with open(os.path.join(OUT_DIR, name + ".spki.der"), "wb") as f:
+        f.write(spki)
+
+
+def main():
+    # An unsupported key size, which must be rejected.
+    load_or_generate_key("rsa1024_e65537", 1024, 65537)
+
+    vectors = []
+    for bits, e in [(2048, 65537), (3072, 65537), (4096, 65537), (2048, 3)]:
+        name = "rsa%d_e%d" % (bits, e)
+        key = load_or_generate_key(name, bits, e)
+        vectors.append(
+            {
+                "key": name,
+                "pss_deterministic": pss_vectors(key),
+                "pss_random": pss_random_signatures(key),
+                "oaep_deterministic": oaep_vectors(key),
+                "oaep_random": oaep_random_ciphertexts(key),
+            }
+        )
+
+    with open(os.path.join(OUT_DIR, "rsa_generated.json"), "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
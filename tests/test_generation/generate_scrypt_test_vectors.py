@@ -0,0 +1,64 @@
+# Generates tests/test_data/scrypt_generated.json.
+#
+# Requires Python 3.6 or later built against OpenSSL 1.1 or later, for
+# hashlib.scrypt(), and the `openssl` command line tool of OpenSSL 3. Each
+# derived key is checked to be the same with `openssl kdf SCRYPT`. The
+# parameters include the defaults of Node.js' crypto.scrypt() and the
+# recommended parameters of Go's golang.org/x/crypto/scrypt, the smallest
+# values of N, r and p, and derived key lengths around the SHA-256 output size.
+
+import hashlib
+import json
+import os
+import subprocess
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "scrypt_generated.json")
+
+# Password, salt, N, r, p and derived key length.
+CASES = [
+    (b"", b"", 2, 1, 1, 32),
+    (b"password", b"salt", 2, 1, 1, 1),
+    (b"password", b"salt", 4, 1, 2, 31),
+    (b"password", b"salt", 8, 2, 3, 33),
+    (b"correct horse battery staple", b"0123456789abcdef", 1024, 1, 1, 64),
+    (b"correct horse battery staple", b"0123456789abcdef", 16, 3, 5, 65),
+    (b"\x00\xff" * 40, bytes(range(64)), 256, 4, 2, 100),
+    # Node.js: crypto.scrypt(password, salt, 64).
+    (b"node password", b"node salt", 16384, 8, 1, 64),
+    # Go: scrypt.Key(password, salt, 32768, 8, 1, 32).
+    (b"go password", b"go salt", 32768, 8, 1, 32),
+]
+
+
+def openssl_scrypt(password, salt, n, r, p, dklen):
+    out = subprocess.run(
+        ["openssl", "kdf", "-binary", "-keylen", str(dklen),
+         "-kdfopt", "hexpass:" + password.hex(), "-kdfopt", "hexsalt:" + salt.hex(),
+         "-kdfopt", "n:" + str(n), "-kdfopt", "r:" + str(r), "-kdfopt", "p:" + str(p),
+         "-kdfopt", "maxmem_bytes:" + str(2 ** 30), "SCRYPT"],
+        check=True, capture_output=True).stdout
+    return out
+
+
+def main():
+    vectors = []
+    for password, salt, n, r, p, dklen in CASES:
+        dk = hashlib.scrypt(password, salt=salt, n=n, r=r, p=p, maxmem=2 ** 30, dklen=dklen)
+        assert openssl_scrypt(password, salt, n, r, p, dklen) == dk
+
+        vectors.append({
+            "password": password.hex(),
+            "salt": salt.hex(),
+            "n": n,
+            "r": r,
+            "p": p,
+            "dk": dk.hex(),
+        })
+
+    with open(OUT, "w") as f:
+        json.dump({"scrypt": vectors}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,86 @@
+# Generates tests/test_data/sealed_box_generated.json.
+#
+# Requires libsodium 1.0.18 or later, loaded with ctypes. Every sealed box is
+# built from the ephemeral key pair given in the vector, with libsodium's
+# crypto_box_curve25519xchacha20poly1305_easy() and the nonce
+# BLAKE2b-192(ephemeral public key || recipient public key), in the same way as
+# crypto_box_curve25519xchacha20poly1305_seal() does with a random ephemeral key
+# pair. Each one is checked to open with
+# crypto_box_curve25519xchacha20poly1305_seal_open(). The message lengths are
+# chosen around the 32 bytes of the first ChaCha20 block that are used for
+# encryption, and around the ChaCha20 block size.
+
+import ctypes
+import json
+import os
+
+OUT = os.path.join(
+    os.path.dirname(__file__), "..", "test_data", "sealed_box_generated.json"
+)
+
+MESSAGE_LENS = [0, 1, 31, 32, 33, 63, 64, 95, 96, 97, 1000]
+
+SEALBYTES = 48
+
+sodium = ctypes.CDLL("libsodium.so.23")
+assert sodium.sodium_init() >= 0
+
+
+def buf(n):
+    return ctypes.create_string_buffer(n)
+
+
+def keypair_from_seed(seed):
+    pk, sk = buf(32), buf(32)
+    assert sodium.crypto_box_curve25519xchacha20poly1305_seed_keypair(pk, sk, seed) == 0
+    return pk.raw, sk.raw
+
+
+def seal(message, recipient_pk, ephemeral_pk, ephemeral_sk):
+    nonce = buf(24)
+    assert sodium.crypto_generichash(
+        nonce, ctypes.c_size_t(24), ephemeral_pk + recipient_pk,
+        ctypes.c_ulonglong(64), None, ctypes.c_size_t(0)) == 0
+    boxed = buf(len(message) + 16)
+    assert sodium.crypto_box_curve25519xchacha20poly1305_easy(
+        boxed, message, ctypes.c_ulonglong(len(message)), nonce.raw,
+        recipient_pk, ephemeral_sk) == 0
+    return ephemeral_pk + boxed.raw
+
+
+def seal_open(sealed, recipient_pk, recipient_sk):
+    message = buf(max(len(sealed) - SEALBYTES, 1))
+    assert sodium.crypto_box_curve25519xchacha20poly1305_seal_open(
+        message, sealed, ctypes.c_ulonglong(len(sealed)), recipient_pk,
+        recipient_sk) == 0
+    return message.raw[:len(sealed) - SEALBYTES]
+
+
+def message_bytes(n):
+    return bytes(i % 251 for i in range(n))
+
+
+def main():
+    cases = []
+    for idx, n in enumerate(MESSAGE_LENS):
+        recipient_pk, recipient_sk = keypair_from_seed(bytes([2 * idx]) * 32)
+        ephemeral_pk, ephemeral_sk = keypair_from_seed(bytes([2 * idx + 1]) * 32)
+        message = message_bytes(n)
+        sealed = seal(message, recipient_pk, ephemeral_pk, ephemeral_sk)
+        assert seal_open(sealed, recipient_pk, recipient_sk) == message
+
+        cases.append({
+            "recipient_private_key": recipient_sk.hex(),
+            "recipient_public_key": recipient_pk.hex(),
+            "ephemeral_private_key": ephemeral_sk.hex(),
+            "message_len": n,
+            "sealed": sealed.hex(),
+        })
+
+    with open(OUT, "w") as f:
+        json.dump({"sealed_box": cases}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
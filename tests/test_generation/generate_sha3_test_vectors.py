@@ -0,0 +1,87 @@
+# Generates tests/test_data/sha3_generated.json.
+#
+# Requires the `cryptography` package. The digests and SHAKE outputs are
+# computed with Python's hashlib, which uses the SHA3 implementation of
+# OpenSSL, and HKDF with the `cryptography` package. The input lengths are
+# chosen around the rates of the variants (72, 104, 136 and 168 bytes), and the
+# HKDF salts around the block size of HMAC-SHA3-256, to exercise the hashing of
+# long HMAC keys.
+
+import hashlib
+import json
+import os
+
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.kdf.hkdf import HKDF
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "sha3_generated.json")
+
+INPUT_LENS = [
+    0, 1, 71, 72, 73, 103, 104, 105, 135, 136, 137, 143, 144, 145, 167, 168,
+    169, 207, 208, 209, 271, 272, 273, 335, 336, 337, 1000, 4096,
+]
+SALT_LENS = [0, 32, 71, 72, 73, 135, 136, 137, 300]
+HKDF_OUTPUT_LEN = 200
+SHAKE_OUTPUT_LEN = 500
+
+HASHES = [
+    ("sha3_256", hashlib.sha3_256, hashes.SHA3_256),
+    ("sha3_384", hashlib.sha3_384, hashes.SHA3_384),
+    ("sha3_512", hashlib.sha3_512, hashes.SHA3_512),
+]
+
+
+def input_bytes(n):
+    return bytes(i % 251 for i in range(n))
+
+
+def salt_bytes(n):
+    return bytes((7 * i + 3) % 256 for i in range(n))
+
+
+def main():
+    hash_cases = []
+    for n in INPUT_LENS:
+        case = {"input_len": n}
+        for name, digest, _ in HASHES:
+            case[name] = digest(input_bytes(n)).hexdigest()
+        hash_cases.append(case)
+
+    shake_cases = []
+    for n in INPUT_LENS:
+        shake_cases.append({
+            "input_len": n,
+            "shake128": hashlib.shake_128(input_bytes(n)).hexdigest(SHAKE_OUTPUT_LEN),
+            "shake256": hashlib.shake_256(input_bytes(n)).hexdigest(SHAKE_OUTPUT_LEN),
+        })
+
+    ikm = input_bytes(42)
+    info = b"orion sha3 hkdf test"
+    hkdf_cases = []
+    for n in SALT_LENS:
+        case = {"salt_len": n}
+        for name, _, algorithm in HASHES:
+            hkdf = HKDF(
+                algorithm=algorithm(),
+                length=HKDF_OUTPUT_LEN,
+                salt=salt_bytes(n),
+                info=info,
+            )
+            case[name] = hkdf.derive(ikm).hex()
+        hkdf_cases.append(case)
+
+    vectors = {
+        "hkdf_ikm": ikm.hex(),
+        "hkdf_info": info.hex(),
+        "hashes": hash_cases,
+        "hkdf": hkdf_cases,
+        "shake": shake_cases,
+    }
+
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,190 @@
+# Generates tests/test_data/srtp_generated.json.
+#
+# Requires the `cryptography` package. This is an independent implementation
+# of the SRTP key derivation function and SRTP/SRTCP packet protection
+# (RFC 3711 and RFC 7714), using `cryptography` for AES in counter mode and
+# AES-GCM, and Python's hmac module for HMAC-SHA1. As SRTP is deterministic,
+# orion must produce the exact same packets.
+
+import hashlib
+import hmac
+import json
+import os
+
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "srtp_generated.json")
+
+# (name, DTLS-SRTP id, master key size, master salt size, RTP tag size, AEAD)
+PROFILES = [
+    ("AES128_CM_HMAC_SHA1_80", 0x0001, 16, 14, 10, False),
+    ("AES128_CM_HMAC_SHA1_32", 0x0002, 16, 14, 4, False),
+    ("AEAD_AES_128_GCM", 0x0007, 16, 12, 16, True),
+    ("AEAD_AES_256_GCM", 0x0008, 32, 12, 16, True),
+]
+
+
+def det_bytes(label, n):
+    out = b""
+    counter = 0
+    while len(out) < n:
+        out += hashlib.sha256(label.encode() + counter.to_bytes(4, "big")).digest()
+        counter += 1
+    return out[:n]
+
+
+def aes_ctr(key, iv, data):
+    enc = Cipher(algorithms.AES(key), modes.CTR(iv)).encryptor()
+    return enc.update(data) + enc.finalize()
+
+
+def kdf(master_key, master_salt, label, n):
+    # The master salt is zero-padded to 14 bytes, as done by libsrtp.
+    x = bytearray(master_salt.ljust(14, b"\x00"))
+    x[7] ^= label
+    return aes_ctr(master_key, bytes(x) + b"\x00\x00", b"\x00" * n)
+
+
+def session_keys(profile, master_key, master_salt, rtcp):
+    _, _, key_size, salt_size, _, aead = profile
+    base = 3 if rtcp else 0
+    key = kdf(master_key, master_salt, base, key_size)
+    auth = None if aead else kdf(master_key, master_salt, base + 1, 20)
+    salt = kdf(master_key, master_salt, base + 2, salt_size)
+    return key, auth, salt
+
+
+def xor(a, b):
+    return bytes(x ^ y for x, y in zip(a, b))
+
+
+def rtp_header_len(packet):
+    length = 12 + 4 * (packet[0] & 0x0F)
+    if packet[0] & 0x10:
+        length += 4 + 4 * int.from_bytes(packet[length + 2 : length + 4], "big")
+    return length
+
+
+def protect_rtp(profile, keys, packet, roc):
+    key, auth, salt = keys
+    hl = rtp_header_len(packet)
+    header, payload = packet[:hl], packet[hl:]
+    ssrc = header[8:12]
+    seq = header[2:4]
+    if profile[5]:
+        iv = xor(b"\x00\x00" + ssrc + roc.to_bytes(4, "big") + seq, salt)
+        return header + AESGCM(key).encrypt(iv, payload, header)
+    index = (roc << 16) | int.from_bytes(seq, "big")
+    iv = int.from_bytes(salt, "big") << 16
+    iv ^= int.from_bytes(ssrc, "big") << 64
+    iv ^= index << 16
+    encrypted = header + aes_ctr(key, iv.to_bytes(16, "big"), payload)
+    tag = hmac.new(auth, encrypted + roc.to_bytes(4, "big"), "sha1").digest()
+    return encrypted + tag[: profile[4]]
+
+
+def protect_rtcp(profile, keys, packet, index):
+    key, auth, salt = keys
+    header, payload = packet[:8], packet[8:]
+    ssrc = header[4:8]
+    e_index = (0x80000000 | index).to_bytes(4, "big")
+    if profile[5]:
+        iv = xor(b"\x00\x00" + ssrc + b"\x00\x00" + index.to_bytes(4, "big"), salt)
+        return header + AESGCM(key).encrypt(iv, payload, header + e_index) + e_index
+    iv = int.from_bytes(salt, "big") << 16
+    iv ^= int.from_bytes(ssrc, "big") << 64
+    iv ^= index << 16
+    encrypted = header + aes_ctr(key, iv.to_bytes(16, "big"), payload) + e_index
+    return encrypted + hmac.new(auth, encrypted, "sha1").digest()[:10]
+
+
+def rtp_packet(label, seq, csrcs, extension_words, payload_len, marker):
+    first = 0x80 | len(csrcs) | (0x10 if extension_words is not None else 0)
+    header = bytes([first, (0x80 if marker else 0) | 96])
+    header += seq.to_bytes(2, "big") + (0x12345678).to_bytes(4, "big")
+    header += bytes.fromhex("deadbeef")
+    for csrc in csrcs:
+        header += csrc.to_bytes(4, "big")
+    if extension_words is not None:
+        header += bytes.fromhex("bede") + extension_words.to_bytes(2, "big")
+        header += det_bytes(label + " extension", 4 * extension_words)
+    return header + det_bytes(label + " payload", payload_len)
+
+
+def rtcp_packet(label, payload_len):
+    # A sender report header with SSRC 0xcafebabe, followed by its payload
+    # and possibly further packets of a compound packet.
+    length = (8 + payload_len) // 4 - 1
+    header = bytes([0x80, 200]) + length.to_bytes(2, "big") + bytes.fromhex("cafebabe")
+    return header + det_bytes(label + " payload", payload_len)
+
+
+RTP_CASES = [
+    # (seq, csrcs, extension words, payload length, marker, roc)
+    (0, [], None, 0, False, 0),
+    (1, [], None, 1, True, 0),
+    (0x1234, [], None, 160, False, 0),
+    (0xFFFF, [1, 2], None, 63, False, 1),
+    (42, [], 2, 1000, True, 0x12345678),
+    (7, [3], 0, 17, False, 0xFFFFFFFF),
+]
+
+RTCP_CASES = [
+    # (payload length, index)
+    (0, 0),
+    (20, 1),
+    (52, 0x1234),
+    (500, 0x7FFFFFFF),
+]
+
+
+def main():
+    vectors = {"profiles": []}
+    for profile in PROFILES:
+        name, profile_id, key_size, salt_size, _, _ = profile
+        master_key = det_bytes(name + " master key", key_size)
+        master_salt = det_bytes(name + " master salt", salt_size)
+        rtp_keys = session_keys(profile, master_key, master_salt, False)
+        rtcp_keys = session_keys(profile, master_key, master_salt, True)
+
+        rtp = []
+        for i, (seq, csrcs, ext, payload_len, marker, roc) in enumerate(RTP_CASES):
+            packet = rtp_packet("%s rtp %d" % (name, i), seq, csrcs, ext, payload_len, marker)
+            rtp.append(
+                {
+                    "roc": roc,
+                    "packet": packet.hex(),
+                    "protected": protect_rtp(profile, rtp_keys, packet, roc).hex(),
+                }
+            )
+
+        rtcp = []
+        for i, (payload_len, index) in enumerate(RTCP_CASES):
+            packet = rtcp_packet("%s rtcp %d" % (name, i), payload_len)
+            rtcp.append(
+                {
+                    "index": index,
+                    "packet": packet.hex(),
+                    "protected": protect_rtcp(profile, rtcp_keys, packet, index).hex(),
+                }
+            )
+
+        vectors["profiles"].append(
+            {
+                "name": name,
+                "id": profile_id,
+                "master_key": master_key.hex(),
+                "master_salt": master_salt.hex(),
+                "rtp": rtp,
+                "rtcp": rtcp,
+            }
+        )
+
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,213 @@
+# Generates tests/test_data/wireguard_generated.json.
+#
+# Requires the `cryptography` package. This is an independent implementation
+# of the WireGuard handshake, as described in section 5.4 of the WireGuard
+# whitepaper, using X25519 and ChaCha20Poly1305 from `cryptography` and
+# BLAKE2s from `hashlib`. XChaCha20Poly1305 is built from a Python HChaCha20.
+
+import hashlib
+import hmac
+import json
+import os
+import struct
+
+from cryptography.hazmat.primitives import serialization
+from cryptography.hazmat.primitives.asymmetric.x25519 import X25519PrivateKey, X25519PublicKey
+from cryptography.hazmat.primitives.ciphers.aead import ChaCha20Poly1305
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "wireguard_generated.json")
+
+CONSTRUCTION = b"Noise_IKpsk2_25519_ChaChaPoly_BLAKE2s"
+IDENTIFIER = b"WireGuard v1 zx2c4 Jason@zx2c4.com"
+LABEL_MAC1 = b"mac1----"
+LABEL_COOKIE = b"cookie--"
+
+
+def HASH(data):
+    return hashlib.blake2s(data).digest()
+
+
+def MAC(key, data):
+    return hashlib.blake2s(data, key=key, digest_size=16).digest()
+
+
+def HMAC(key, data):
+    return hmac.new(key, data, hashlib.blake2s).digest()
+
+
+def KDF(n, key, data):
+    t0 = HMAC(key, data)
+    out = []
+    prev = b""
+    for i in range(1, n + 1):
+        prev = HMAC(t0, prev + bytes([i]))
+        out.append(prev)
+    return out
+
+
+def AEAD(key, counter, plaintext, ad):
+    return ChaCha20Poly1305(key).encrypt(b"\x00" * 4 + struct.pack("<Q", counter), plaintext, ad)
+
+
+def rotl(v, c):
+    return ((v << c) & 0xFFFFFFFF) | (v >> (32 - c))
+
+
+def quarter_round(x, a, b, c, d):
+    x[a] = (x[a] + x[b]) & 0xFFFFFFFF
+    x[d] = rotl(x[d] ^ x[a], 16)
+    x[c] = (x[c] + x[d]) & 0xFFFFFFFF
+    x[b] = rotl(x[b] ^ x[c], 12)
+    x[a] = (x[a] + x[b]) & 0xFFFFFFFF
+    x[d] = rotl(x[d] ^ x[a], 8)
+    x[c] = (x[c] + x[d]) & 0xFFFFFFFF
+    x[b] = rotl(x[b] ^ x[c], 7)
+
+
+def hchacha20(key, nonce):
+    x = [0x61707865, 0x3320646E, 0x79622D32, 0x6B206574]
+    x += list(struct.unpack("<8I", key)) + list(struct.unpack("<4I", nonce))
+    for _ in range(10):
+        quarter_round(x, 0, 4, 8, 12)
+        quarter_round(x, 1, 5, 9, 13)
+        quarter_round(x, 2, 6, 10, 14)
+        quarter_round(x, 3, 7, 11, 15)
+        quarter_round(x, 0, 5, 10, 15)
+        quarter_round(x, 1, 6, 11, 12)
+        quarter_round(x, 2, 7, 8, 13)
+        quarter_round(x, 3, 4, 9, 14)
+    return struct.pack("<8I", *(x[0:4] + x[12:16]))
+
+
+def XAEAD(key, nonce, plaintext, ad):
+    subkey = hchacha20(key, nonce[:16])
+    return ChaCha20Poly1305(subkey).encrypt(b"\x00" * 4 + nonce[16:], plaintext, ad)
+
+
+def private_key(seed):
+    return X25519PrivateKey.from_private_bytes(hashlib.sha256(seed).digest())
+
+
+def raw(key):
+    if isinstance(key, X25519PrivateKey):
+        return key.private_bytes(
+            serialization.Encoding.Raw,
+            serialization.PrivateFormat.Raw,
+            serialization.NoEncryption(),
+        )
+    return key.public_bytes(serialization.Encoding.Raw, serialization.PublicFormat.Raw)
+
+
+def DH(private, public):
+    return private.exchange(X25519PublicKey.from_public_bytes(public))
+
+
+def macs(msg, receiver_static_public, cookie):
+    mac1 = MAC(HASH(LABEL_MAC1 + receiver_static_public), msg)
+    mac2 = MAC(cookie, msg + mac1) if cookie is not None else b"\x00" * 16
+    return msg + mac1 + mac2
+
+
+def handshake(name, psk, cookie):
+    si = private_key(name + b" initiator static")
+    sr = private_key(name + b" responder static")
+    ei = private_key(name + b" initiator ephemeral")
+    er = private_key(name + b" responder ephemeral")
+    si_pub, sr_pub, ei_pub, er_pub = raw(si.public_key()), raw(sr.public_key()), raw(ei.public_key()), raw(er.public_key())
+    sender_index = int.from_bytes(HASH(name + b" initiator index")[:4], "little")
+    responder_index = int.from_bytes(HASH(name + b" responder index")[:4], "little")
+    timestamp = struct.pack(">QI", 0x400000000000000A + 1700000000, 123456789)
+
+    # Initiation.
+    c = HASH(CONSTRUCTION)
+    h = HASH(c + IDENTIFIER)
+    h = HASH(h + sr_pub)
+    (c,) = KDF(1, c, ei_pub)
+    h = HASH(h + ei_pub)
+    c, k = KDF(2, c, DH(ei, sr_pub))
+    encrypted_static = AEAD(k, 0, si_pub, h)
+    h = HASH(h + encrypted_static)
+    c, k = KDF(2, c, DH(si, sr_pub))
+    encrypted_timestamp = AEAD(k, 0, timestamp, h)
+    h = HASH(h + encrypted_timestamp)
+    msg = struct.pack("<I", 1) + struct.pack("<I", sender_index) + ei_pub + encrypted_static + encrypted_timestamp
+    initiation = macs(msg, sr_pub, cookie)
+
+    # Response.
+    (c,) = KDF(1, c, er_pub)
+    h = HASH(h + er_pub)
+    (c,) = KDF(1, c, DH(er, ei_pub))
+    (c,) = KDF(1, c, DH(er, si_pub))
+    c, tau, k = KDF(3, c, psk if psk is not None else b"\x00" * 32)
+    h = HASH(h + tau)
+    encrypted_nothing = AEAD(k, 0, b"", h)
+    h = HASH(h + encrypted_nothing)
+    msg = struct.pack("<I", 2) + struct.pack("<I", responder_index) + struct.pack("<I", sender_index) + er_pub + encrypted_nothing
+    response = macs(msg, si_pub, cookie)
+
+    initiator_send, initiator_receive = KDF(2, c, b"")
+
+    return {
+        "name": name.decode(),
+        "initiator_static": raw(si).hex(),
+        "responder_static": raw(sr).hex(),
+        "initiator_ephemeral": raw(ei).hex(),
+        "responder_ephemeral": raw(er).hex(),
+        "preshared_key": psk.hex() if psk is not None else None,
+        "cookie": cookie.hex() if cookie is not None else None,
+        "initiator_index": sender_index,
+        "responder_index": responder_index,
+        "timestamp": timestamp.hex(),
+        "initiation": initiation.hex(),
+        "response": response.hex(),
+        "initiator_sending_key": initiator_send.hex(),
+        "initiator_receiving_key": initiator_receive.hex(),
+    }
+
+
+def cookie_reply(vector):
+    sr = X25519PrivateKey.from_private_bytes(bytes.fromhex(vector["responder_static"]))
+    sr_pub = raw(sr.public_key())
+    initiation = bytes.fromhex(vector["initiation"])
+    cookie_secret = hashlib.sha256(b"cookie secret").digest()
+    source = bytes([192, 0, 2, 1]) + struct.pack(">H", 51820)
+    nonce = hashlib.sha256(b"cookie nonce").digest()[:24]
+
+    cookie = MAC(cookie_secret, source)
+    encrypted_cookie = XAEAD(HASH(LABEL_COOKIE + sr_pub), nonce, cookie, initiation[116:132])
+    reply = struct.pack("<I", 3) + initiation[4:8] + nonce + encrypted_cookie
+    return {
+        "cookie_secret": cookie_secret.hex(),
+        "source": source.hex(),
+        "nonce": nonce.hex(),
+        "cookie": cookie.hex(),
+        "reply": reply.hex(),
+    }
+
+
+def transport(vector):
+    messages = []
+    for counter, plaintext in ((0, b""), (1, b"Hello, WireGuard!"), (2 ** 40 + 5, bytes(range(100)))):
+        key = bytes.fromhex(vector["initiator_sending_key"])
+        packet = struct.pack("<I", 4) + struct.pack("<I", vector["responder_index"]) + struct.pack("<Q", counter)
+        packet += AEAD(key, counter, plaintext, b"")
+        messages.append({"counter": counter, "plaintext": plaintext.hex(), "message": packet.hex()})
+    return messages
+
+
+def main():
+    vectors = [
+        handshake(b"no preshared key", None, None),
+        handshake(b"preshared key", hashlib.sha256(b"preshared key").digest(), None),
+    ]
+    reply = cookie_reply(vectors[0])
+    vectors.append(handshake(b"cookie", None, bytes.fromhex(reply["cookie"])))
+    out = {"handshakes": vectors, "cookie_reply": reply, "transport": transport(vectors[0])}
+
+    with open(OUT, "w") as f:
+        json.dump(out, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
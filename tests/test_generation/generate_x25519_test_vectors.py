@@ -0,0 +1,97 @@
+# Generates tests/test_data/x25519_generated.json.
+#
+# Requires the `cryptography` package, which uses the X25519 implementation of
+# OpenSSL. The private keys are derived deterministically, and include keys
+# that need clamping. Some public keys have the unused high bit set, or are
+# non-canonical encodings, both of which must be accepted. Key agreement with
+# the points of small order must fail, as the shared secret is all-zero.
+
+import hashlib
+import json
+import os
+
+from cryptography.hazmat.primitives.asymmetric.x25519 import (
+    X25519PrivateKey,
+    X25519PublicKey,
+)
+from cryptography.hazmat.primitives.serialization import Encoding, PublicFormat
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "x25519_generated.json")
+
+P = 2**255 - 19
+
+SMALL_ORDER = [
+    0,
+    1,
+    0x00B8495F16056286FDB1329CEB8D09DA6AC49FF1FAE35616AEB8413B7C7AEBE0,
+    0x57119FD0DD4E22D8868E1C58C45C44045BEF839C55B1D0B1248C50A3BC959C5F,
+    P - 1,
+    P,
+    P + 1,
+]
+
+
+def det_bytes(label, n):
+    return hashlib.sha256(label.encode()).digest()[:n]
+
+
+def public_bytes(private_key):
+    return private_key.public_key().public_bytes(Encoding.Raw, PublicFormat.Raw)
+
+
+def exchange(private, public):
+    try:
+        shared = X25519PrivateKey.from_private_bytes(private).exchange(
+            X25519PublicKey.from_public_bytes(public)
+        )
+    except ValueError:
+        return None
+    return shared.hex()
+
+
+def main():
+    key_pairs = []
+    for idx in range(32):
+        private = det_bytes("x25519 private %d" % idx, 32)
+        public = public_bytes(X25519PrivateKey.from_private_bytes(private))
+        key_pairs.append({"private": private.hex(), "public": public.hex()})
+
+    agreements = []
+    for idx in range(32):
+        private = bytes.fromhex(key_pairs[idx]["private"])
+        peer = bytearray.fromhex(key_pairs[(idx + 1) % 32]["public"])
+        if idx % 4 == 1:
+            # The high bit is masked before decoding.
+            peer[31] |= 0x80
+        agreements.append(
+            {
+                "private": private.hex(),
+                "public": bytes(peer).hex(),
+                "shared": exchange(private, bytes(peer)),
+            }
+        )
+
+    # Non-canonical encodings of u = 2^255 - 19 + k, reduced modulo p.
+    for k in range(2, 19):
+        private = det_bytes("x25519 non-canonical %d" % k, 32)
+        peer = (P + k).to_bytes(32, "little")
+        agreements.append(
+            {"private": private.hex(), "public": peer.hex(), "shared": exchange(private, peer)}
+        )
+
+    for idx, u in enumerate(SMALL_ORDER):
+        private = det_bytes("x25519 small order %d" % idx, 32)
+        for high_bit in (0, 0x80):
+            peer = bytearray(u.to_bytes(32, "little"))
+            peer[31] |= high_bit
+            shared = exchange(private, bytes(peer))
+            assert shared is None
+            agreements.append({"private": private.hex(), "public": bytes(peer).hex(), "shared": None})
+
+    with open(OUT, "w") as f:
+        json.dump({"key_pairs": key_pairs, "agreements": agreements}, f, indent=2)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
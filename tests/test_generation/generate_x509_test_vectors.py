@@ -0,0 +1,206 @@
+# Generates tests/test_data/x509_generated.json.
+#
+# Requires the `cryptography` package. Certificates and certificate signing
+# requests are built and signed by `cryptography`, with the same contents
+# that orion encodes. Both Ed25519 and RFC 6979 ECDSA signatures are
+# deterministic, so orion must produce the exact same encodings.
+
+import hashlib
+import json
+import os
+from datetime import datetime, timezone
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, ed25519
+from cryptography.x509.oid import NameOID
+
+OUT = os.path.join(os.path.dirname(__file__), "..", "test_data", "x509_generated.json")
+
+KEYS = {
+    "ed25519-ca": "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+    "ed25519-device": "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+    "p256-ca": "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+    "p256-device": "2e5e5c4a1f0d6b3a8f3e8c1a9b7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d",
+}
+
+
+def secret_key(name):
+    value = bytes.fromhex(KEYS[name])
+    if name.startswith("ed25519"):
+        return ed25519.Ed25519PrivateKey.from_private_bytes(value)
+    return ec.derive_private_key(int.from_bytes(value, "big"), ec.SECP256R1())
+
+
+def sign(builder, key):
+    if isinstance(key, ed25519.Ed25519PrivateKey):
+        return builder.sign(key, None)
+    return builder.sign(key, hashes.SHA256(), ecdsa_deterministic=True)
+
+
+def public_bytes(public_key):
+    if isinstance(public_key, ed25519.Ed25519PublicKey):
+        return public_key.public_bytes(serialization.Encoding.Raw, serialization.PublicFormat.Raw)
+    return public_key.public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint
+    )
+
+
+def key_identifier(public_key):
+    return hashlib.sha256(public_bytes(public_key)).digest()[:20]
+
+
+def name(common_name, organization):
+    attributes = []
+    if organization is not None:
+        attributes.append(x509.NameAttribute(NameOID.ORGANIZATION_NAME, organization))
+    attributes.append(x509.NameAttribute(NameOID.COMMON_NAME, common_name))
+    return x509.Name(attributes)
+
+
+def time(value):
+    return datetime.fromtimestamp(value, timezone.utc)
+
+
+def certificate(case):
+    issuer_key = secret_key(case["issuer_key"])
+    subject_public_key = secret_key(case["subject_key"]).public_key()
+    is_ca = case["is_ca"]
+
+    builder = (
+        x509.CertificateBuilder()
+        .serial_number(int(case["serial_number"], 16))
+        .issuer_name(name(*case["issuer"]))
+        .not_valid_before(time(case["not_before"]))
+        .not_valid_after(time(case["not_after"]))
+        .subject_name(name(*case["subject"]))
+        .public_key(subject_public_key)
+        .add_extension(x509.BasicConstraints(ca=is_ca, path_length=None), critical=True)
+        .add_extension(
+            x509.KeyUsage(
+                digital_signature=True,
+                content_commitment=False,
+                key_encipherment=False,
+                data_encipherment=False,
+                key_agreement=False,
+                key_cert_sign=is_ca,
+                crl_sign=is_ca,
+                encipher_only=False,
+                decipher_only=False,
+            ),
+            critical=True,
+        )
+    )
+    if case["dns_names"]:
+        builder = builder.add_extension(
+            x509.SubjectAlternativeName([x509.DNSName(n) for n in case["dns_names"]]),
+            critical=False,
+        )
+    builder = builder.add_extension(
+        x509.SubjectKeyIdentifier(key_identifier(subject_public_key)), critical=False
+    ).add_extension(
+        x509.AuthorityKeyIdentifier(key_identifier(issuer_key.public_key()), None, None),
+        critical=False,
+    )
+    cert = sign(builder, issuer_key)
+    issuer_public_key = issuer_key.public_key()
+    if isinstance(issuer_public_key, ed25519.Ed25519PublicKey):
+        issuer_public_key.verify(cert.signature, cert.tbs_certificate_bytes)
+    else:
+        issuer_public_key.verify(
+            cert.signature, cert.tbs_certificate_bytes, ec.ECDSA(hashes.SHA256())
+        )
+
+    return dict(case, certificate=cert.public_bytes(serialization.Encoding.DER).hex())
+
+
+def csr(case):
+    key = secret_key(case["key"])
+    builder = x509.CertificateSigningRequestBuilder().subject_name(name(*case["subject"]))
+    if case["dns_names"]:
+        builder = builder.add_extension(
+            x509.SubjectAlternativeName([x509.DNSName(n) for n in case["dns_names"]]),
+            critical=False,
+        )
+    request = sign(builder, key)
+    assert request.is_signature_valid
+
+    return dict(
+        case,
+        public_key=public_bytes(key.public_key()).hex(),
+        csr=request.public_bytes(serialization.Encoding.DER).hex(),
+    )
+
+
+def main():
+    certificates = [
+        {
+            "issuer_key": "ed25519-ca",
+            "issuer": ["Device Root CA", "Example"],
+            "subject_key": "ed25519-ca",
+            "subject": ["Device Root CA", "Example"],
+            "serial_number": "01",
+            "not_before": 1700000000,
+            "not_after": 2000000000,
+            "dns_names": [],
+            "is_ca": True,
+        },
+        {
+            "issuer_key": "p256-ca",
+            "issuer": ["P-256 Root CA", None],
+            "subject_key": "p256-ca",
+            "subject": ["P-256 Root CA", None],
+            "serial_number": "7fffffffffffffffffffffffffffffffffffffff",
+            "not_before": 0,
+            "not_after": 253402300799,
+            "dns_names": [],
+            "is_ca": True,
+        },
+        {
+            "issuer_key": "ed25519-ca",
+            "issuer": ["Device Root CA", "Example"],
+            "subject_key": "p256-device",
+            "subject": ["device-0042", "Example"],
+            "serial_number": "7a3f09e2c4b15d6e8f90a1b2c3d4e5f6",
+            "not_before": 1700000000,
+            "not_after": 2524608000,
+            "dns_names": ["device-0042.example.com", "*.devices.example.com"],
+            "is_ca": False,
+        },
+        {
+            "issuer_key": "p256-ca",
+            "issuer": ["P-256 Root CA", None],
+            "subject_key": "ed25519-device",
+            "subject": ["Gerät über ümlaut", None],
+            "serial_number": "00ff",
+            "not_before": 2524607999,
+            "not_after": 2524608000,
+            "dns_names": ["localhost"],
+            "is_ca": False,
+        },
+    ]
+    csrs = [
+        {
+            "key": "p256-device",
+            "subject": ["device-0042", "Example"],
+            "dns_names": ["device-0042.example.com"],
+        },
+        {
+            "key": "ed25519-device",
+            "subject": ["device-0043", None],
+            "dns_names": [],
+        },
+    ]
+
+    vectors = {
+        "keys": KEYS,
+        "certificates": [certificate(case) for case in certificates],
+        "csrs": [csr(case) for case in csrs],
+    }
+    with open(OUT, "w") as f:
+        json.dump(vectors, f, indent=2, ensure_ascii=False)
+        f.write("\n")
+
+
+if __name__ == "__main__":
+    main()
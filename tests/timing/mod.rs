@@ -0,0 +1,272 @@
+// Statistical constant-time tests, following the dudect methodology
+// ("Dude, is my code constant time?", Reparaz, Balasch and Verbauwhede, 2017).
+//
+// The operation under test is timed on inputs from two classes: a fixed input
+// and random inputs. The classes are interleaved in random order to spread
+// environmental noise evenly across both. Welch's t-test is then applied to the
+// two sets of timings, both as measured and with outliers above a number of
+// percentiles cropped. A large t-value means the execution time depends on the
+// input class, i.e. on secret data.
+//
+// These tests are ignored by default, as they take a while and their results
+// depend on the machine they run on. Run them with:
+//
+// cargo test --release --test mod -- --ignored timing
+
+use core::convert::TryFrom;
+use orion::hazardous::aead::chacha20poly1305;
+use orion::hazardous::ecc::{ed25519, x25519};
+use orion::hazardous::mac::{hmac, poly1305};
+use orion::util::{secure_cmp, secure_rand_bytes};
+use std::time::Instant;
+
+/// The number of measurements taken per test.
+const MEASUREMENTS: usize = 500_000;
+
+/// t-values above this threshold indicate a timing leak. This is the threshold
+/// dudect uses to report that an implementation is definitely not constant time.
+const T_THRESHOLD: f64 = 10.0;
+
+/// Percentiles at which the measurements are additionally cropped, to remove
+/// outliers caused by interrupts and context switches.
+const CROP_PERCENTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Online mean and variance of each class, using Welford's method.
+struct WelchTTest {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl WelchTTest {
+    fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn t_value(&self) -> f64 {
+        let var_0 = self.m2[0] / (self.n[0] - 1.0);
+        let var_1 = self.m2[1] / (self.n[1] - 1.0);
+        let den = (var_0 / self.n[0] + var_1 / self.n[1]).sqrt();
+        if den == 0.0 {
+            return 0.0;
+        }
+
+        (self.mean[0] - self.mean[1]) / den
+    }
+}
+
+/// Run `operation` on `MEASUREMENTS` inputs created by `prepare` and return the
+/// largest absolute t-value found across all crops. `prepare` is called with
+/// the class of the input to create: 0 for the fixed input and 1 for a random
+/// input. `operation` must return its result so that it isn't optimized away.
+fn dudect<I, P, O, R>(name: &str, mut prepare: P, mut operation: O) -> f64
+where
+    P: FnMut(usize) -> I,
+    O: FnMut(&I) -> R,
+{
+    let mut class_bytes = vec![0u8; MEASUREMENTS];
+    secure_rand_bytes(&mut class_bytes).unwrap();
+    let classes: Vec<usize> = class_bytes.iter().map(|b| (b & 1) as usize).collect();
+    let inputs: Vec<I> = classes.iter().map(|class| prepare(*class)).collect();
+
+    let mut timings: Vec<f64> = Vec::with_capacity(MEASUREMENTS);
+    let mut results: Vec<R> = Vec::with_capacity(MEASUREMENTS);
+    for input in inputs.iter() {
+        let start = Instant::now();
+        let result = operation(input);
+        timings.push(start.elapsed().as_nanos() as f64);
+        results.push(result);
+    }
+    drop(results);
+
+    // The first measurements are discarded as warm-up.
+    let warm_up = MEASUREMENTS / 100;
+    let mut sorted = timings[warm_up..].to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut tests = [WelchTTest::default(); CROP_PERCENTILES.len() + 1];
+    let thresholds: Vec<f64> = CROP_PERCENTILES
+        .iter()
+        .map(|p| sorted[(p * sorted.len() as f64) as usize])
+        .collect();
+
+    for (class, timing) in classes.iter().zip(timings.iter()).skip(warm_up) {
+        tests[0].push(*class, *timing);
+        for (test, threshold) in tests[1..].iter_mut().zip(thresholds.iter()) {
+            if timing < threshold {
+                test.push(*class, *timing);
+            }
+        }
+    }
+
+    let max_t = tests
+        .iter()
+        .map(|test| test.t_value().abs())
+        .fold(0.0, f64::max);
+    println!("{}: max |t| = {:.2}", name, max_t);
+
+    max_t
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    secure_rand_bytes(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_welch_t_test() {
+    let mut same = WelchTTest::default();
+    let mut diff = WelchTTest::default();
+    for i in 0..1000 {
+        let x = (i % 10) as f64;
+        same.push(i % 2, x);
+        same.push((i + 1) % 2, x);
+        diff.push(0, x);
+        diff.push(1, x + 5.0);
+    }
+
+    assert!(same.t_value().abs() < 1.0);
+    assert!(diff.t_value().abs() > T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_secure_cmp() {
+    let a = random_bytes(64);
+    let max_t = dudect(
+        "secure_cmp",
+        |class| {
+            if class == 0 {
+                a.clone()
+            } else {
+                random_bytes(64)
+            }
+        },
+        |b| secure_cmp(&a, b).is_ok(),
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_hmac_verify() {
+    let sk = hmac::SecretKey::generate();
+    let data = random_bytes(64);
+    let tag = hmac::Hmac::hmac(&sk, &data).unwrap();
+
+    let max_t = dudect(
+        "hmac::Hmac::verify",
+        |class| {
+            if class == 0 {
+                tag
+            } else {
+                hmac::Tag::from_slice(&random_bytes(64)).unwrap()
+            }
+        },
+        |expected| hmac::Hmac::verify(expected, &sk, &data).is_ok(),
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_poly1305_verify() {
+    let sk = poly1305::OneTimeKey::generate();
+    let data = random_bytes(64);
+    let tag = poly1305::Poly1305::poly1305(&sk, &data).unwrap();
+
+    let max_t = dudect(
+        "poly1305::Poly1305::verify",
+        |class| {
+            if class == 0 {
+                tag
+            } else {
+                poly1305::Tag::from_slice(&random_bytes(16)).unwrap()
+            }
+        },
+        |expected| poly1305::Poly1305::verify(expected, &sk, &data).is_ok(),
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_chacha20poly1305_open() {
+    let sk = chacha20poly1305::SecretKey::generate();
+    let nonce = chacha20poly1305::Nonce::from_slice(&random_bytes(12)).unwrap();
+    let plaintext = random_bytes(64);
+    let mut sealed = vec![0u8; plaintext.len() + 16];
+    chacha20poly1305::seal(&sk, &nonce, &plaintext, None, &mut sealed).unwrap();
+
+    // Both classes have invalid tags, so that opening fails in both and only
+    // the tag comparison can differ. The fixed class has a tag of all zeros.
+    let max_t = dudect(
+        "chacha20poly1305::open",
+        |class| {
+            let mut ct = sealed.clone();
+            if class == 0 {
+                ct[64..].copy_from_slice(&[0u8; 16]);
+            } else {
+                ct[64..].copy_from_slice(&random_bytes(16));
+            }
+            ct
+        },
+        |ct| {
+            let mut dst_out = [0u8; 64];
+            chacha20poly1305::open(&sk, &nonce, ct, None, &mut dst_out).is_ok()
+        },
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_x25519_key_agreement() {
+    let peer_public = x25519::PublicKey::try_from(&x25519::PrivateKey::generate()).unwrap();
+
+    // The fixed class is the all-zero scalar, which becomes 2^254 once clamped.
+    let max_t = dudect(
+        "x25519::key_agreement",
+        |class| {
+            if class == 0 {
+                x25519::PrivateKey::from([0u8; x25519::PRIVATE_KEY_SIZE])
+            } else {
+                x25519::PrivateKey::generate()
+            }
+        },
+        |private_key| x25519::key_agreement(private_key, &peer_public).is_ok(),
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
+
+#[test]
+#[ignore]
+fn timing_ed25519_sign() {
+    let message = random_bytes(64);
+
+    // Signing multiplies the base point by a nonce derived from the secret
+    // key. The fixed class is the all-zero secret key.
+    let max_t = dudect(
+        "ed25519::sign",
+        |class| {
+            if class == 0 {
+                ed25519::SecretKey::from([0u8; ed25519::SECRET_KEYSIZE])
+            } else {
+                ed25519::SecretKey::generate()
+            }
+        },
+        |secret_key| ed25519::sign(secret_key, &message).is_ok(),
+    );
+
+    assert!(max_t < T_THRESHOLD);
+}
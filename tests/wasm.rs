@@ -0,0 +1,124 @@
+// Tests of the high-level API on wasm32-unknown-unknown, where randomness is
+// provided by the JavaScript environment through getrandom's "js" feature.
+//
+// Run them in Node.js with:
+//
+// wasm-pack test --node
+//
+// or in a headless browser with, for example:
+//
+// wasm-pack test --headless --firefox
+//
+// These tests are kept separate from tests/mod.rs, since the test vector tests
+// there read files from disk, which is not possible on this target.
+
+#![cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+
+use core::time::Duration;
+use orion::hazardous::{kdf::pbkdf2, protocols::wireguard};
+use orion::{aead, auth, hash, kdf, keyring, pwhash, token, webhook};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_generate_random() {
+    let mut first = [0u8; 32];
+    let mut second = [0u8; 32];
+    orion::util::secure_rand_bytes(&mut first).unwrap();
+    orion::util::secure_rand_bytes(&mut second).unwrap();
+    assert_ne!(first, second);
+
+    assert_ne!(aead::SecretKey::default(), aead::SecretKey::default());
+}
+
+#[wasm_bindgen_test]
+fn test_aead() {
+    let secret_key = aead::SecretKey::default();
+    let ciphertext = aead::seal(&secret_key, b"Secret message").unwrap();
+    assert_eq!(
+        aead::open(&secret_key, &ciphertext).unwrap(),
+        b"Secret message"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_aead_streaming() {
+    use aead::streaming::{StreamOpener, StreamSealer, StreamTag};
+
+    let secret_key = aead::SecretKey::default();
+    let (mut sealer, nonce) = StreamSealer::new(&secret_key).unwrap();
+    let chunk_1 = sealer.seal_chunk(b"first", StreamTag::MESSAGE).unwrap();
+    let chunk_2 = sealer.seal_chunk(b"last", StreamTag::FINISH).unwrap();
+
+    let mut opener = StreamOpener::new(&secret_key, &nonce).unwrap();
+    let (msg_1, tag_1) = opener.open_chunk(&chunk_1).unwrap();
+    let (msg_2, tag_2) = opener.open_chunk(&chunk_2).unwrap();
+    assert_eq!(msg_1, b"first");
+    assert_eq!(tag_1, StreamTag::MESSAGE);
+    assert_eq!(msg_2, b"last");
+    assert_eq!(tag_2, StreamTag::FINISH);
+}
+
+#[wasm_bindgen_test]
+fn test_auth() {
+    let secret_key = auth::SecretKey::default();
+    let tag = auth::authenticate(&secret_key, b"Some data").unwrap();
+    assert!(auth::authenticate_verify(&tag, &secret_key, b"Some data").is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_hash() {
+    assert_eq!(
+        hash::digest(b"Some data").unwrap(),
+        hash::digest(b"Some data").unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_kdf() {
+    let user_password = kdf::Password::from_slice(b"User password").unwrap();
+    let salt = kdf::Salt::default();
+    let derived_key = kdf::derive_key(&user_password, &salt, 3, 1 << 4, 32).unwrap();
+    assert!(kdf::derive_key_verify(&derived_key, &user_password, &salt, 3, 1 << 4).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_pwhash() {
+    let password = pwhash::Password::from_slice(b"Secret password").unwrap();
+    let hash = pwhash::hash_password(&password, 3, 1 << 4).unwrap();
+    assert!(pwhash::hash_password_verify(&hash, &password, 3, 1 << 4).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_pbkdf2_long_output() {
+    // Long outputs are computed on multiple threads on other targets.
+    let password = pbkdf2::Password::from_slice(b"password").unwrap();
+    let mut dk = [0u8; 3 * 64 + 7];
+    pbkdf2::derive_key(&password, b"salt", 1000, &mut dk).unwrap();
+    assert!(pbkdf2::verify(&dk.clone(), &password, b"salt", 1000, &mut dk).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_token() {
+    // There is no system time on this target, so the caller passes the time.
+    let mut keyring = keyring::KeyRing::new(1, keyring::SecretKey::default()).unwrap();
+    let token = token::seal_at(&mut keyring, b"user_id=42", 1_000_000).unwrap();
+    let ttl = Duration::from_secs(3600);
+    assert_eq!(
+        token::open_at(&keyring, &token, ttl, 1_003_600).unwrap(),
+        b"user_id=42"
+    );
+    assert!(token::open_at(&keyring, &token, ttl, 1_003_601).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_webhook() {
+    let secret = webhook::SecretKey::default();
+    let header = webhook::sign_at(&secret, b"payload", 1_000_000).unwrap();
+    let tolerance = Duration::from_secs(300);
+    assert!(webhook::verify_at(&[secret], b"payload", &header, tolerance, 1_000_300).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_wireguard_timestamp() {
+    assert!(wireguard::tai64n(1, 0).unwrap() > wireguard::tai64n(0, 999_999_999).unwrap());
+}
@@ -0,0 +1,202 @@
+// Testing against handshakes generated with an independent implementation of
+// the WireGuard paper, see tests/test_generation/generate_wireguard_test_vectors.py.
+
+use orion::hazardous::protocols::wireguard::{self, *};
+use orion::hazardous::stream::xchacha20;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_wireguard_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct WireGuardVectors {
+    pub handshakes: Vec<HandshakeVector>,
+    pub cookie_reply: CookieReplyVector,
+    pub transport: Vec<TransportVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HandshakeVector {
+    pub name: String,
+    pub initiator_static: String,
+    pub responder_static: String,
+    pub initiator_ephemeral: String,
+    pub responder_ephemeral: String,
+    pub preshared_key: Option<String>,
+    pub cookie: Option<String>,
+    pub initiator_index: u32,
+    pub responder_index: u32,
+    pub timestamp: String,
+    pub initiation: String,
+    pub response: String,
+    pub initiator_sending_key: String,
+    pub initiator_receiving_key: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CookieReplyVector {
+    pub cookie_secret: String,
+    pub source: String,
+    pub nonce: String,
+    pub cookie: String,
+    pub reply: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TransportVector {
+    pub counter: u64,
+    pub plaintext: String,
+    pub message: String,
+}
+
+fn load() -> WireGuardVectors {
+    let file = File::open("./tests/test_data/wireguard_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn private_key(hex_key: &str) -> PrivateKey {
+    PrivateKey::from_slice(&hex::decode(hex_key).unwrap()).unwrap()
+}
+
+/// Run both sides of the handshake in `vector`, returning the transport keys
+/// of the initiator and the responder.
+fn handshake(vector: &HandshakeVector) -> (TransportKeys, TransportKeys) {
+    let initiator_static = private_key(&vector.initiator_static);
+    let responder_static = private_key(&vector.responder_static);
+    let psk = vector
+        .preshared_key
+        .as_ref()
+        .map(|psk| PresharedKey::from_slice(&hex::decode(psk).unwrap()).unwrap());
+    let cookie = vector
+        .cookie
+        .as_ref()
+        .map(|cookie| Cookie::from_slice(&hex::decode(cookie).unwrap()).unwrap());
+    let expected_initiation = hex::decode(&vector.initiation).unwrap();
+    let expected_response = hex::decode(&vector.response).unwrap();
+
+    let mut initiation = [0u8; INITIATION_SIZE];
+    let initiator = Initiator::new(
+        &initiator_static,
+        &responder_static.public_key().unwrap(),
+        psk.as_ref(),
+        &private_key(&vector.initiator_ephemeral),
+        vector.initiator_index,
+        &hex::decode(&vector.timestamp).unwrap(),
+        &mut initiation,
+    )
+    .unwrap();
+    if let Some(cookie) = cookie.as_ref() {
+        wireguard::write_mac2(cookie, &mut initiation).unwrap();
+        wireguard::verify_mac2(cookie, &expected_initiation).unwrap();
+    }
+    assert_eq!(&initiation[..], &expected_initiation[..], "{}", vector.name);
+
+    let responder = Responder::new(&responder_static, &expected_initiation).unwrap();
+    assert_eq!(
+        responder.remote_static(),
+        &initiator_static.public_key().unwrap()
+    );
+    assert_eq!(
+        responder.timestamp(),
+        &hex::decode(&vector.timestamp).unwrap()[..]
+    );
+    assert_eq!(responder.remote_index(), vector.initiator_index);
+
+    let mut response = [0u8; RESPONSE_SIZE];
+    let responder_keys = responder
+        .respond(
+            psk.as_ref(),
+            &private_key(&vector.responder_ephemeral),
+            vector.responder_index,
+            &mut response,
+        )
+        .unwrap();
+    if let Some(cookie) = cookie.as_ref() {
+        wireguard::write_mac2(cookie, &mut response).unwrap();
+    }
+    assert_eq!(&response[..], &expected_response[..], "{}", vector.name);
+
+    let initiator_keys = initiator.process_response(&expected_response).unwrap();
+
+    (initiator_keys, responder_keys)
+}
+
+#[test]
+fn test_wireguard_handshakes() {
+    let vectors = load();
+    assert_eq!(vectors.handshakes.len(), 3);
+
+    for vector in vectors.handshakes.iter() {
+        let (initiator_keys, responder_keys) = handshake(vector);
+        let sending_key = hex::decode(&vector.initiator_sending_key).unwrap();
+        let receiving_key = hex::decode(&vector.initiator_receiving_key).unwrap();
+
+        assert_eq!(
+            initiator_keys.sending_key().unprotected_as_bytes(),
+            &sending_key[..]
+        );
+        assert_eq!(
+            initiator_keys.receiving_key().unprotected_as_bytes(),
+            &receiving_key[..]
+        );
+        assert_eq!(
+            responder_keys.sending_key().unprotected_as_bytes(),
+            &receiving_key[..]
+        );
+        assert_eq!(
+            responder_keys.receiving_key().unprotected_as_bytes(),
+            &sending_key[..]
+        );
+    }
+}
+
+#[test]
+fn test_wireguard_cookie_reply() {
+    let vectors = load();
+    let vector = &vectors.cookie_reply;
+    let handshake = &vectors.handshakes[0];
+    let responder_public = private_key(&handshake.responder_static)
+        .public_key()
+        .unwrap();
+    let initiation = hex::decode(&handshake.initiation).unwrap();
+    let expected_reply = hex::decode(&vector.reply).unwrap();
+
+    let cookie_secret =
+        CookieSecret::from_slice(&hex::decode(&vector.cookie_secret).unwrap()).unwrap();
+    let cookie =
+        wireguard::make_cookie(&cookie_secret, &hex::decode(&vector.source).unwrap()).unwrap();
+    assert_eq!(cookie, &hex::decode(&vector.cookie).unwrap()[..]);
+
+    let nonce = xchacha20::Nonce::from_slice(&hex::decode(&vector.nonce).unwrap()).unwrap();
+    let mut reply = [0u8; COOKIE_REPLY_SIZE];
+    wireguard::seal_cookie_reply(&responder_public, &initiation, &cookie, &nonce, &mut reply)
+        .unwrap();
+    assert_eq!(&reply[..], &expected_reply[..]);
+
+    assert_eq!(
+        wireguard::open_cookie_reply(&responder_public, &initiation, &expected_reply).unwrap(),
+        cookie
+    );
+}
+
+#[test]
+fn test_wireguard_transport() {
+    let vectors = load();
+    let (initiator_keys, responder_keys) = handshake(&vectors.handshakes[0]);
+    assert_eq!(vectors.transport.len(), 3);
+
+    for vector in vectors.transport.iter() {
+        let plaintext = hex::decode(&vector.plaintext).unwrap();
+        let expected = hex::decode(&vector.message).unwrap();
+
+        let mut message = vec![0u8; expected.len()];
+        wireguard::seal_transport(&initiator_keys, vector.counter, &plaintext, &mut message)
+            .unwrap();
+        assert_eq!(message, expected);
+
+        let mut dst_pt = vec![0u8; plaintext.len()];
+        let counter = wireguard::open_transport(&responder_keys, &expected, &mut dst_pt).unwrap();
+        assert_eq!(counter, vector.counter);
+        assert_eq!(dst_pt, plaintext);
+    }
+}
@@ -0,0 +1,202 @@
+// Generic harness for Google Wycheproof test vector files.
+//
+// Wycheproof files share a common layout: a top-level object with the
+// algorithm and number of tests, a list of test groups with group-wide
+// parameters, and a list of tests in each group. Every test has an id, a
+// comment, flags and a result, which is one of "valid", "acceptable" or
+// "invalid". Only the group parameters and test data differ between
+// primitives, so those are the type parameters of `WycheproofTests`.
+
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{fs::File, io::BufReader};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+/// The expected result of a Wycheproof test.
+pub enum WycheproofResult {
+    /// The input is valid and must be accepted.
+    Valid,
+    /// The input is valid, but uses a weak or unusual parameter. An
+    /// implementation may accept or reject it.
+    Acceptable,
+    /// The input is invalid and must be rejected.
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// How orion is expected to treat tests marked "acceptable".
+pub enum AcceptablePolicy {
+    /// The test file is not expected to contain any acceptable tests.
+    NotExpected,
+    /// orion accepts all acceptable tests.
+    Pass,
+    /// orion rejects all acceptable tests.
+    Fail,
+    /// orion rejects acceptable tests with any of these flags and accepts
+    /// the rest.
+    FailWithFlags(&'static [&'static str]),
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+/// The fields every Wycheproof test has in common.
+pub struct TestInfo {
+    pub tcId: u64,
+    pub comment: String,
+    pub result: WycheproofResult,
+    pub flags: Vec<String>,
+}
+
+impl TestInfo {
+    /// Whether orion should accept the input of this test.
+    pub fn should_pass(&self, policy: AcceptablePolicy) -> bool {
+        match self.result {
+            WycheproofResult::Valid => true,
+            WycheproofResult::Invalid => false,
+            WycheproofResult::Acceptable => match policy {
+                AcceptablePolicy::NotExpected => panic!(
+                    "Unexpected acceptable Wycheproof test (tcId: {})",
+                    self.tcId
+                ),
+                AcceptablePolicy::Pass => true,
+                AcceptablePolicy::Fail => false,
+                AcceptablePolicy::FailWithFlags(flags) => {
+                    !self.flags.iter().any(|flag| flags.contains(&flag.as_str()))
+                }
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+/// A single test, with the primitive-specific data `T`.
+pub struct Test<T> {
+    #[serde(flatten)]
+    pub info: TestInfo,
+    #[serde(flatten)]
+    pub data: T,
+}
+
+#[derive(Deserialize, Debug)]
+/// A group of tests sharing the primitive-specific parameters `P`.
+pub struct TestGroup<P, T> {
+    #[serde(flatten)]
+    pub params: P,
+    pub tests: Vec<Test<T>>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+/// A Wycheproof test vector file.
+pub struct WycheproofTests<P, T> {
+    pub algorithm: String,
+    pub numberOfTests: u64,
+    pub testGroups: Vec<TestGroup<P, T>>,
+}
+
+#[derive(Deserialize, Debug)]
+/// Group parameters for test files where orion needs none of them.
+pub struct NoParams {}
+
+/// Run all tests in the Wycheproof file at `path`. `runner` is called with
+/// the algorithm name, the group parameters, the test data, the common test
+/// fields and whether orion should accept the test input. It must panic if
+/// orion does not behave as expected.
+pub fn wycheproof_runner<P, T, F>(path: &str, policy: AcceptablePolicy, mut runner: F)
+where
+    P: DeserializeOwned,
+    T: DeserializeOwned,
+    F: FnMut(&str, &P, &T, &TestInfo, bool),
+{
+    let file =
+        File::open(path).unwrap_or_else(|_| panic!("Wycheproof: Unable to open file: {}", path));
+    let reader = BufReader::new(file);
+    let tests: WycheproofTests<P, T> = serde_json::from_reader(reader).unwrap();
+
+    let mut tests_run = 0;
+    for test_group in tests.testGroups.iter() {
+        for test in test_group.tests.iter() {
+            let should_test_pass = test.info.should_pass(policy);
+            runner(
+                &tests.algorithm,
+                &test_group.params,
+                &test.data,
+                &test.info,
+                should_test_pass,
+            );
+
+            tests_run += 1;
+        }
+    }
+
+    assert_eq!(tests_run, tests.numberOfTests);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(result: WycheproofResult, flags: &[&str]) -> TestInfo {
+        TestInfo {
+            tcId: 1,
+            comment: String::new(),
+            result,
+            flags: flags.iter().map(|flag| flag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_acceptable_policy() {
+        let valid = info(WycheproofResult::Valid, &[]);
+        let invalid = info(WycheproofResult::Invalid, &[]);
+        let acceptable = info(WycheproofResult::Acceptable, &["SmallTag"]);
+
+        assert!(valid.should_pass(AcceptablePolicy::Fail));
+        assert!(!invalid.should_pass(AcceptablePolicy::Pass));
+        assert!(acceptable.should_pass(AcceptablePolicy::Pass));
+        assert!(!acceptable.should_pass(AcceptablePolicy::Fail));
+        assert!(!acceptable.should_pass(AcceptablePolicy::FailWithFlags(&["SmallTag"])));
+        assert!(acceptable.should_pass(AcceptablePolicy::FailWithFlags(&["WeakKey"])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_acceptable_not_expected_panics() {
+        info(WycheproofResult::Acceptable, &[]).should_pass(AcceptablePolicy::NotExpected);
+    }
+
+    #[test]
+    fn test_parse_test_file() {
+        let json = r#"{
+            "algorithm": "TEST",
+            "numberOfTests": 2,
+            "testGroups": [{
+                "type": "Ignored",
+                "keySize": 256,
+                "tests": [
+                    {"tcId": 1, "comment": "", "msg": "00", "result": "valid", "flags": []},
+                    {"tcId": 2, "comment": "c", "msg": "01", "result": "acceptable", "flags": ["F"]}
+                ]
+            }]
+        }"#;
+
+        #[allow(non_snake_case)]
+        #[derive(Deserialize)]
+        struct Params {
+            keySize: u64,
+        }
+        #[derive(Deserialize)]
+        struct Data {
+            msg: String,
+        }
+
+        let tests: WycheproofTests<Params, Data> = serde_json::from_str(json).unwrap();
+        assert_eq!(tests.testGroups[0].params.keySize, 256);
+        assert_eq!(tests.testGroups[0].tests[1].data.msg, "01");
+        assert_eq!(tests.testGroups[0].tests[1].info.tcId, 2);
+        assert_eq!(
+            tests.testGroups[0].tests[1].info.result,
+            WycheproofResult::Acceptable
+        );
+    }
+}
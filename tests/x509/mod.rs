@@ -0,0 +1,142 @@
+// Testing against certificates and certificate signing requests built by
+// `cryptography`, see tests/test_generation/generate_x509_test_vectors.py.
+
+use orion::hazardous::ecc::{ecdsa_p256, ed25519};
+use orion::hazardous::protocols::x509::{self, CertificateParams, Name, PublicKey, SigningKey};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, io::BufReader};
+
+/// The test vectors generated by
+/// tests/test_generation/generate_x509_test_vectors.py.
+#[derive(Deserialize, Debug)]
+pub struct X509Vectors {
+    pub keys: HashMap<String, String>,
+    pub certificates: Vec<CertificateVector>,
+    pub csrs: Vec<CsrVector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CertificateVector {
+    pub issuer_key: String,
+    pub issuer: (String, Option<String>),
+    pub subject_key: String,
+    pub subject: (String, Option<String>),
+    pub serial_number: String,
+    pub not_before: u64,
+    pub not_after: u64,
+    pub dns_names: Vec<String>,
+    pub is_ca: bool,
+    pub certificate: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CsrVector {
+    pub key: String,
+    pub subject: (String, Option<String>),
+    pub dns_names: Vec<String>,
+    pub public_key: String,
+    pub csr: String,
+}
+
+enum SecretKey {
+    Ed25519(ed25519::SecretKey),
+    EcdsaP256(ecdsa_p256::SecretKey),
+}
+
+impl SecretKey {
+    fn signing_key(&self) -> SigningKey<'_> {
+        match self {
+            SecretKey::Ed25519(secret_key) => SigningKey::Ed25519(secret_key),
+            SecretKey::EcdsaP256(secret_key) => SigningKey::EcdsaP256(secret_key),
+        }
+    }
+}
+
+fn load() -> X509Vectors {
+    let file = File::open("./tests/test_data/x509_generated.json").unwrap();
+    serde_json::from_reader(BufReader::new(file)).unwrap()
+}
+
+fn secret_key(vectors: &X509Vectors, name: &str) -> SecretKey {
+    let value = hex::decode(&vectors.keys[name]).unwrap();
+    if name.starts_with("ed25519") {
+        SecretKey::Ed25519(ed25519::SecretKey::from_slice(&value).unwrap())
+    } else {
+        SecretKey::EcdsaP256(ecdsa_p256::SecretKey::from_slice(&value).unwrap())
+    }
+}
+
+fn name(value: &(String, Option<String>)) -> Name {
+    Name {
+        common_name: value.0.clone(),
+        organization: value.1.clone(),
+    }
+}
+
+#[test]
+fn test_certificate_vectors() {
+    let vectors = load();
+    for vector in vectors.certificates.iter() {
+        let issuer_key = secret_key(&vectors, &vector.issuer_key);
+        let subject_key = secret_key(&vectors, &vector.subject_key);
+        let issuer_public_key = issuer_key.signing_key().public_key().unwrap();
+        let subject_public_key = subject_key.signing_key().public_key().unwrap();
+        let params = CertificateParams {
+            serial_number: hex::decode(&vector.serial_number).unwrap(),
+            subject: name(&vector.subject),
+            not_before: vector.not_before,
+            not_after: vector.not_after,
+            dns_names: vector.dns_names.clone(),
+            is_ca: vector.is_ca,
+        };
+        let expected = hex::decode(&vector.certificate).unwrap();
+
+        let certificate = x509::issue_certificate(
+            &issuer_key.signing_key(),
+            &name(&vector.issuer),
+            &subject_public_key,
+            &params,
+        )
+        .unwrap();
+        assert_eq!(certificate, expected);
+        if vector.issuer_key == vector.subject_key {
+            assert_eq!(
+                x509::self_signed_certificate(&issuer_key.signing_key(), &params).unwrap(),
+                expected
+            );
+        }
+
+        assert!(x509::verify_certificate(&expected, &issuer_public_key).is_ok());
+        assert_eq!(
+            x509::subject_public_key(&expected).unwrap(),
+            subject_public_key
+        );
+        if vector.issuer_key != vector.subject_key {
+            assert!(x509::verify_certificate(&expected, &subject_public_key).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_csr_vectors() {
+    let vectors = load();
+    for vector in vectors.csrs.iter() {
+        let key = secret_key(&vectors, &vector.key);
+        let expected = hex::decode(&vector.csr).unwrap();
+
+        let csr = x509::certificate_signing_request(
+            &key.signing_key(),
+            &name(&vector.subject),
+            &vector.dns_names,
+        )
+        .unwrap();
+        assert_eq!(csr, expected);
+
+        let public_key = x509::verify_certificate_signing_request(&expected).unwrap();
+        let encoded = hex::decode(&vector.public_key).unwrap();
+        match public_key {
+            PublicKey::Ed25519(public_key) => assert_eq!(public_key.as_ref(), &encoded[..]),
+            PublicKey::EcdsaP256(public_key) => assert_eq!(public_key.as_ref(), &encoded[..]),
+        }
+    }
+}